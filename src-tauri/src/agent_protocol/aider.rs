@@ -0,0 +1,227 @@
+//! Adapter for aider's plain-text output (`--no-pretty`)
+//!
+//! Prose is buffered into message deltas, "Applied edit to" and "Commit"
+//! lines become tool calls, and the "Tokens:" summary becomes usage. Aider
+//! has no end-of-run marker, so `finish` reports `Done` once it has exited.
+
+use serde_json::json;
+
+use super::{Adapter, AgentEvent};
+
+/// Banner and housekeeping lines aider prints around the actual reply
+const NOISE_PREFIXES: &[&str] = &[
+    "Aider v",
+    "Main model:",
+    "Weak model:",
+    "Editor model:",
+    "Git repo:",
+    "Repo-map:",
+    "Use /help",
+    "https://aider.chat/",
+    "Restored previous conversation history",
+];
+
+#[derive(Debug, Default)]
+pub struct AiderAdapter {
+    /// Prose not yet reported
+    pending: String,
+    /// All prose, for the final result
+    text: String,
+    tool_count: u32,
+    input_tokens: u64,
+    output_tokens: u64,
+    cost_usd: Option<f64>,
+}
+
+/// Parse aider's token counts ("2.1k", "150", "1.2M")
+fn parse_count(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (number, scale) = match value.chars().last()? {
+        'k' => (&value[..value.len() - 1], 1_000.0),
+        'M' => (&value[..value.len() - 1], 1_000_000.0),
+        _ => (value, 1.0),
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .map(|n| (n * scale).round() as u64)
+}
+
+fn is_noise(line: &str) -> bool {
+    NOISE_PREFIXES.iter().any(|p| line.starts_with(p))
+        || (line.starts_with("Added ") && line.ends_with(" to the chat."))
+        || (!line.is_empty() && line.chars().all(|c| c == '─' || c == '-'))
+}
+
+impl AiderAdapter {
+    /// "2.1k sent, 150 received. Cost: $0.01 message, $0.02 session."
+    fn observe_usage(&mut self, summary: &str) {
+        let (tokens, cost) = summary.split_once(". Cost:").unwrap_or((summary, ""));
+        for part in tokens.split(',') {
+            let part = part.trim().trim_end_matches('.');
+            if let Some(n) = part.strip_suffix(" sent").and_then(parse_count) {
+                self.input_tokens += n;
+            } else if let Some(n) = part.strip_suffix(" received").and_then(parse_count) {
+                self.output_tokens += n;
+            }
+        }
+        let message_cost = cost
+            .split(',')
+            .find_map(|part| part.trim().strip_suffix(" message"))
+            .and_then(|c| c.trim_start_matches('$').parse::<f64>().ok());
+        if let Some(c) = message_cost {
+            self.cost_usd = Some(self.cost_usd.unwrap_or(0.0) + c);
+        }
+    }
+}
+
+impl Adapter for AiderAdapter {
+    fn parse_line(&mut self, line: &str) -> Vec<AgentEvent> {
+        let trimmed = line.trim();
+        if is_noise(trimmed) {
+            return vec![];
+        }
+        if let Some(rest) = trimmed.strip_prefix("Tokens: ") {
+            self.observe_usage(rest);
+            return vec![];
+        }
+
+        let tool = if let Some(path) = trimmed.strip_prefix("Applied edit to ") {
+            Some(("Edit", json!({ "file_path": path })))
+        } else if let Some((hash, message)) = trimmed
+            .strip_prefix("Commit ")
+            .and_then(|commit| commit.split_once(' '))
+            .filter(|(hash, _)| hash.len() >= 7 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            Some((
+                "Bash",
+                json!({ "command": format!("git commit # {hash}"), "description": message }),
+            ))
+        } else {
+            None
+        };
+
+        let Some((name, input)) = tool else {
+            self.pending.push_str(line);
+            self.pending.push('\n');
+            return vec![];
+        };
+
+        self.tool_count += 1;
+        let id = format!("aider-tool-{}", self.tool_count);
+        let mut events = self.flush();
+        events.push(AgentEvent::ToolCall {
+            id: id.clone(),
+            name: name.to_string(),
+            input,
+            parent_id: None,
+        });
+        events.push(AgentEvent::ToolResult {
+            id,
+            output: trimmed.to_string(),
+            is_error: false,
+        });
+        events
+    }
+
+    fn flush(&mut self) -> Vec<AgentEvent> {
+        let text = std::mem::take(&mut self.pending);
+        if text.trim().is_empty() {
+            return vec![];
+        }
+        self.text.push_str(&text);
+        vec![AgentEvent::MessageDelta { text }]
+    }
+
+    fn finish(&mut self) -> Vec<AgentEvent> {
+        let mut events = self.flush();
+        if self.input_tokens > 0 || self.output_tokens > 0 {
+            events.push(AgentEvent::Usage {
+                input_tokens: self.input_tokens,
+                output_tokens: self.output_tokens,
+                cache_read_input_tokens: 0,
+                cache_creation_input_tokens: 0,
+                cost_usd: self.cost_usd,
+            });
+        }
+        let produced_nothing = self.text.trim().is_empty() && self.tool_count == 0;
+        events.push(AgentEvent::Done {
+            result: Some(self.text.trim().to_string()),
+            is_error: produced_nothing,
+        });
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(output: &str) -> Vec<AgentEvent> {
+        let mut adapter = AiderAdapter::default();
+        let mut events: Vec<_> = output
+            .lines()
+            .flat_map(|line| adapter.parse_line(line))
+            .collect();
+        events.extend(adapter.finish());
+        events
+    }
+
+    #[test]
+    fn test_parses_edits_commits_and_usage() {
+        let events = parse(
+            "\
+Aider v0.60.0
+Main model: claude-3-5-sonnet-20241022 with diff edit format
+Git repo: .git with 42 files
+Added src/lib.rs to the chat.
+────────────────────────────────────────────────
+I'll add the missing null check.
+
+src/lib.rs
+Applied edit to src/lib.rs
+Commit 1a2b3c4 fix: handle missing config
+Tokens: 2.1k sent, 150 received. Cost: $0.01 message, $0.02 session.
+",
+        );
+        let AgentEvent::MessageDelta { text } = &events[0] else {
+            panic!("expected prose first: {events:?}");
+        };
+        assert!(text.starts_with("I'll add the missing null check."));
+        assert!(
+            matches!(&events[1], AgentEvent::ToolCall { name, input, .. } if name == "Edit" && input["file_path"] == "src/lib.rs")
+        );
+        assert!(
+            matches!(&events[3], AgentEvent::ToolCall { input, .. } if input["description"] == "fix: handle missing config")
+        );
+        assert_eq!(
+            events[5],
+            AgentEvent::Usage {
+                input_tokens: 2100,
+                output_tokens: 150,
+                cache_read_input_tokens: 0,
+                cache_creation_input_tokens: 0,
+                cost_usd: Some(0.01),
+            }
+        );
+        assert!(matches!(
+            &events[6],
+            AgentEvent::Done {
+                is_error: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_prose_and_empty_runs() {
+        let events = parse("Aider v0.60.0\nCommit the change? n\n");
+        assert!(matches!(&events[0], AgentEvent::MessageDelta { .. }));
+        assert_eq!(events.len(), 2);
+        let events = parse("Aider v0.60.0\n");
+        assert!(matches!(
+            &events[..],
+            [AgentEvent::Done { is_error: true, .. }]
+        ));
+    }
+}
@@ -0,0 +1,261 @@
+//! Adapter for Claude CLI stream-json
+
+use serde_json::Value;
+
+use super::drift::{self, Schema, SchemaDrift};
+use super::{Adapter, AgentEvent};
+
+/// Message types the CLI writes (plus `raw`, see `StreamJsonEncoder`). Their
+/// fields change with most releases, so only types are checked.
+const MESSAGE_SCHEMA: Schema = &[
+    ("system", &["*"]),
+    ("assistant", &["*"]),
+    ("user", &["*"]),
+    ("result", &["*"]),
+    ("stream_event", &["*"]),
+    ("error", &["*"]),
+    ("raw", &["*"]),
+];
+
+/// Content block types of assistant and user messages
+const BLOCK_SCHEMA: Schema = &[
+    ("block:text", &["*"]),
+    ("block:thinking", &["*"]),
+    ("block:redacted_thinking", &["*"]),
+    ("block:tool_use", &["*"]),
+    ("block:server_tool_use", &["*"]),
+    ("block:web_search_tool_result", &["*"]),
+    ("block:web_fetch_tool_result", &["*"]),
+    ("block:tool_result", &["*"]),
+    ("block:image", &["*"]),
+];
+
+#[derive(Debug, Default)]
+pub struct ClaudeAdapter {
+    session_started: bool,
+    drift: Vec<SchemaDrift>,
+}
+
+/// Message and content block types in `msg` that aren't in the schema
+pub fn schema_drift(msg: &Value, line: &str) -> Vec<SchemaDrift> {
+    let msg_type = msg.get("type").and_then(Value::as_str).unwrap_or_default();
+    let mut found = drift::check(MESSAGE_SCHEMA, msg_type, msg, line);
+    if matches!(msg_type, "assistant" | "user") {
+        for block in content_blocks(msg) {
+            let block_type = block
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            found.extend(drift::check(
+                BLOCK_SCHEMA,
+                &format!("block:{block_type}"),
+                block,
+                line,
+            ));
+        }
+    }
+    found
+}
+
+/// Tool result content is a string or an array of content blocks
+fn tool_result_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn str_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn content_blocks(msg: &Value) -> &[Value] {
+    msg.get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+impl Adapter for ClaudeAdapter {
+    fn parse_line(&mut self, line: &str) -> Vec<AgentEvent> {
+        let Ok(msg) = serde_json::from_str::<Value>(line) else {
+            return vec![];
+        };
+        let mut events = Vec::new();
+        self.drift.extend(schema_drift(&msg, line));
+
+        if !self.session_started {
+            if let Some(session_id) = msg.get("session_id").and_then(Value::as_str) {
+                if !session_id.is_empty() {
+                    self.session_started = true;
+                    events.push(AgentEvent::SessionStarted {
+                        session_id: session_id.to_string(),
+                    });
+                }
+            }
+        }
+
+        let parent_id = msg
+            .get("parent_tool_use_id")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        match msg.get("type").and_then(Value::as_str).unwrap_or_default() {
+            "assistant" => {
+                for block in content_blocks(&msg) {
+                    match block
+                        .get("type")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                    {
+                        // CLI placeholder emitted when thinking starts before any text
+                        "text"
+                            if block.get("text").and_then(Value::as_str)
+                                == Some("(no content)") => {}
+                        "text" => events.push(AgentEvent::MessageDelta {
+                            text: str_field(block, "text"),
+                        }),
+                        "thinking" => events.push(AgentEvent::Thinking {
+                            text: str_field(block, "thinking"),
+                        }),
+                        "tool_use" => events.push(AgentEvent::ToolCall {
+                            id: str_field(block, "id"),
+                            name: str_field(block, "name"),
+                            input: block.get("input").cloned().unwrap_or(Value::Null),
+                            parent_id: parent_id.clone(),
+                        }),
+                        _ => {}
+                    }
+                }
+            }
+            "user" => {
+                for block in content_blocks(&msg) {
+                    if block.get("type").and_then(Value::as_str) == Some("tool_result") {
+                        events.push(AgentEvent::ToolResult {
+                            id: str_field(block, "tool_use_id"),
+                            output: tool_result_text(block.get("content")),
+                            is_error: block.get("is_error").and_then(Value::as_bool) == Some(true),
+                        });
+                    }
+                }
+            }
+            "result" => {
+                if let Some(usage) = msg.get("usage") {
+                    let tokens = |key: &str| usage.get(key).and_then(Value::as_u64).unwrap_or(0);
+                    events.push(AgentEvent::Usage {
+                        input_tokens: tokens("input_tokens"),
+                        output_tokens: tokens("output_tokens"),
+                        cache_read_input_tokens: tokens("cache_read_input_tokens"),
+                        cache_creation_input_tokens: tokens("cache_creation_input_tokens"),
+                        cost_usd: msg.get("total_cost_usd").and_then(Value::as_f64),
+                    });
+                }
+                if let Some(denials) = msg.get("permission_denials").and_then(Value::as_array) {
+                    events.extend(denials.iter().map(|d| AgentEvent::ApprovalRequest {
+                        id: str_field(d, "tool_use_id"),
+                        tool_name: str_field(d, "tool_name"),
+                        input: d.get("tool_input").cloned().unwrap_or(Value::Null),
+                    }));
+                }
+                events.push(AgentEvent::Done {
+                    result: msg
+                        .get("result")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                    is_error: msg.get("is_error").and_then(Value::as_bool) == Some(true),
+                });
+            }
+            "error" => events.push(AgentEvent::Error {
+                message: str_field(&msg, "message"),
+            }),
+            "raw" => events.push(AgentEvent::Raw {
+                value: msg.get("raw").cloned().unwrap_or(Value::Null),
+            }),
+            "system" | "stream_event" => {}
+            _ => events.push(AgentEvent::Raw { value: msg.clone() }),
+        }
+        events
+    }
+
+    fn take_drift(&mut self) -> Vec<SchemaDrift> {
+        std::mem::take(&mut self.drift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_claude_stream() {
+        let mut adapter = ClaudeAdapter::default();
+        let lines = [
+            r#"{"type":"system","subtype":"init","session_id":"s-1"}"#,
+            r#"{"type":"assistant","session_id":"s-1","message":{"content":[{"type":"text","text":"(no content)"},{"type":"thinking","thinking":"hmm"},{"type":"tool_use","id":"t1","name":"Read","input":{"file_path":"a"}}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":[{"type":"text","text":"line"}]}]}}"#,
+            r#"{"type":"result","is_error":false,"result":"ok","usage":{"input_tokens":5,"output_tokens":2},"total_cost_usd":0.5,"permission_denials":[{"tool_name":"Bash","tool_use_id":"t2","tool_input":{"command":"rm x"}}]}"#,
+            "not json",
+        ];
+        let events: Vec<_> = lines.iter().flat_map(|l| adapter.parse_line(l)).collect();
+        assert_eq!(
+            events[0],
+            AgentEvent::SessionStarted {
+                session_id: "s-1".to_string()
+            }
+        );
+        assert!(matches!(&events[1], AgentEvent::Thinking { text } if text == "hmm"));
+        assert!(matches!(&events[2], AgentEvent::ToolCall { name, .. } if name == "Read"));
+        assert!(matches!(&events[3], AgentEvent::ToolResult { output, .. } if output == "line"));
+        assert!(matches!(
+            &events[4],
+            AgentEvent::Usage {
+                input_tokens: 5,
+                cost_usd: Some(_),
+                ..
+            }
+        ));
+        assert!(
+            matches!(&events[5], AgentEvent::ApprovalRequest { tool_name, .. } if tool_name == "Bash")
+        );
+        assert!(matches!(
+            &events[6],
+            AgentEvent::Done {
+                is_error: false,
+                ..
+            }
+        ));
+        assert_eq!(events.len(), 7);
+        assert!(adapter.take_drift().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_types_pass_through() {
+        let mut adapter = ClaudeAdapter::default();
+        let lines = [
+            r#"{"type":"assistant","message":{"content":[{"type":"citation","url":"u"},{"type":"text","text":"hi"}]}}"#,
+            r#"{"type":"rate_limit","resets_at":1}"#,
+        ];
+        let events: Vec<_> = lines.iter().flat_map(|l| adapter.parse_line(l)).collect();
+        assert!(matches!(&events[0], AgentEvent::MessageDelta { text } if text == "hi"));
+        assert!(matches!(&events[1], AgentEvent::Raw { value } if value["resets_at"] == 1));
+
+        let types: Vec<_> = adapter
+            .take_drift()
+            .into_iter()
+            .map(|d| d.event_type)
+            .collect();
+        assert_eq!(types, ["block:citation", "rate_limit"]);
+        assert!(adapter.take_drift().is_empty());
+    }
+}
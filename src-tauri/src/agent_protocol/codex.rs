@@ -0,0 +1,566 @@
+//! Adapter for `codex exec --json`
+//!
+//! Codex reports thread/turn lifecycle events and "items" (messages,
+//! reasoning, commands, file changes, MCP and web search calls) that start
+//! and complete. Each line is deserialized into [`ThreadEvent`]; commands and
+//! other tools map onto Claude's tool names so the UI renders them the same
+//! way.
+//!
+//! The schema isn't versioned, so parsing degrades instead of failing: 0.44's
+//! field names are upgraded, and event and item types, fields and lines that
+//! aren't in the tested versions' schema are reported as drift and passed
+//! through as `AgentEvent::Raw`. Golden files for each tested version live in
+//! `fixtures/codex-exec/`.
+
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::drift::{self, DriftKind, Schema, SchemaDrift};
+use super::{Adapter, AgentEvent};
+
+/// Event types of the tested versions and their fields
+const EVENT_SCHEMA: Schema = &[
+    ("thread.started", &["thread_id"]),
+    ("turn.started", &[]),
+    ("turn.completed", &["usage"]),
+    ("turn.failed", &["error"]),
+    ("item.started", &["item"]),
+    ("item.updated", &["item"]),
+    ("item.completed", &["item"]),
+    ("error", &["message"]),
+];
+
+/// Item types of the tested versions and their fields
+const ITEM_SCHEMA: Schema = &[
+    ("item:agent_message", &["id", "text"]),
+    ("item:reasoning", &["id", "text"]),
+    (
+        "item:command_execution",
+        &["id", "command", "aggregated_output", "exit_code", "status"],
+    ),
+    ("item:file_change", &["id", "changes", "status"]),
+    (
+        "item:mcp_tool_call",
+        &[
+            "id",
+            "server",
+            "tool",
+            "arguments",
+            "result",
+            "error",
+            "status",
+        ],
+    ),
+    ("item:web_search", &["id", "query"]),
+    ("item:todo_list", &["id", "items"]),
+    ("item:error", &["id", "message"]),
+];
+
+/// One line of `codex exec --json` output
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type")]
+pub enum ThreadEvent {
+    #[serde(rename = "thread.started")]
+    ThreadStarted { thread_id: String },
+    #[serde(rename = "turn.started")]
+    TurnStarted {},
+    #[serde(rename = "turn.completed")]
+    TurnCompleted {
+        #[serde(default)]
+        usage: TurnUsage,
+    },
+    #[serde(rename = "turn.failed")]
+    TurnFailed { error: ThreadError },
+    #[serde(rename = "item.started")]
+    ItemStarted { item: ThreadItem },
+    #[serde(rename = "item.updated")]
+    ItemUpdated { item: ThreadItem },
+    #[serde(rename = "item.completed")]
+    ItemCompleted { item: ThreadItem },
+    #[serde(rename = "error")]
+    Error { message: String },
+    /// Added after the tested versions
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct TurnUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub cached_input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ThreadError {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ThreadItem {
+    pub id: String,
+    #[serde(flatten)]
+    pub details: ItemDetails,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    #[default]
+    InProgress,
+    Completed,
+    Failed,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FileUpdate {
+    pub path: String,
+    /// add, delete or update
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ItemDetails {
+    AgentMessage {
+        text: String,
+    },
+    Reasoning {
+        text: String,
+    },
+    CommandExecution {
+        command: String,
+        #[serde(default)]
+        aggregated_output: String,
+        #[serde(default)]
+        exit_code: Option<i64>,
+        #[serde(default)]
+        status: ItemStatus,
+    },
+    FileChange {
+        changes: Vec<FileUpdate>,
+        #[serde(default)]
+        status: ItemStatus,
+    },
+    McpToolCall {
+        server: String,
+        tool: String,
+        #[serde(default)]
+        arguments: Value,
+        #[serde(default)]
+        result: Option<Value>,
+        #[serde(default)]
+        error: Option<Value>,
+        #[serde(default)]
+        status: ItemStatus,
+    },
+    WebSearch {
+        query: String,
+    },
+    TodoList {},
+    Error {
+        message: String,
+    },
+    /// Added after the tested versions
+    #[serde(other)]
+    Unknown,
+}
+
+/// Rename 0.44's `item_type` / `assistant_message` to the current names
+fn upgrade_legacy(event: &mut Value) {
+    let Some(item) = event.get_mut("item").and_then(Value::as_object_mut) else {
+        return;
+    };
+    if item.contains_key("type") {
+        return;
+    }
+    if let Some(mut kind) = item.remove("item_type") {
+        if kind == "assistant_message" {
+            kind = json!("agent_message");
+        }
+        item.insert("type".to_string(), kind);
+    }
+}
+
+fn type_of(value: &Value) -> &str {
+    value
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+/// Event and item types and fields of one (upgraded) event that aren't in
+/// the schema
+fn schema_drift(event: &Value, line: &str) -> Vec<SchemaDrift> {
+    let event_type = type_of(event);
+    let mut found = drift::check(EVENT_SCHEMA, event_type, event, line);
+    if let Some(item) = event
+        .get("item")
+        .filter(|_| event_type.starts_with("item."))
+    {
+        let item_type = format!("item:{}", type_of(item));
+        found.extend(drift::check(ITEM_SCHEMA, &item_type, item, line));
+    }
+    found
+}
+
+/// Tool name and input for a tool-like item
+fn tool_call(details: &ItemDetails) -> Option<(String, Value)> {
+    match details {
+        ItemDetails::CommandExecution { command, .. } => {
+            Some(("Bash".to_string(), json!({ "command": command })))
+        }
+        ItemDetails::McpToolCall {
+            server,
+            tool,
+            arguments,
+            ..
+        } => Some((format!("mcp__{server}__{tool}"), arguments.clone())),
+        ItemDetails::WebSearch { query } => {
+            Some(("WebSearch".to_string(), json!({ "query": query })))
+        }
+        _ => None,
+    }
+}
+
+/// Output and failure of a completed tool-like item
+fn tool_output(details: &ItemDetails) -> (String, bool) {
+    match details {
+        ItemDetails::CommandExecution {
+            aggregated_output,
+            exit_code,
+            status,
+            ..
+        } => (
+            aggregated_output.clone(),
+            *status == ItemStatus::Failed || exit_code.unwrap_or(0) != 0,
+        ),
+        ItemDetails::McpToolCall {
+            result,
+            error,
+            status,
+            ..
+        } => {
+            let error = error.as_ref().filter(|e| !e.is_null());
+            let output = result
+                .as_ref()
+                .or(error)
+                .map(|v| v.as_str().map_or_else(|| v.to_string(), str::to_string))
+                .unwrap_or_default();
+            (output, *status == ItemStatus::Failed || error.is_some())
+        }
+        _ => (String::new(), false),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CodexAdapter {
+    /// Items whose tool call was already reported by `item.started`
+    started: HashSet<String>,
+    last_message: Option<String>,
+    /// Drift not yet taken, and what was found this run
+    drift: Vec<SchemaDrift>,
+    seen: HashSet<(String, DriftKind)>,
+}
+
+impl CodexAdapter {
+    /// Keep the first sample of each kind of drift
+    fn record(&mut self, found: Vec<SchemaDrift>) {
+        for drift in found {
+            if self
+                .seen
+                .insert((drift.event_type.clone(), drift.kind.clone()))
+            {
+                self.drift.push(drift);
+            }
+        }
+    }
+
+    fn item_started(&mut self, item: &ThreadItem) -> Vec<AgentEvent> {
+        match tool_call(&item.details) {
+            Some((name, input)) if self.started.insert(item.id.clone()) => {
+                vec![AgentEvent::ToolCall {
+                    id: item.id.clone(),
+                    name,
+                    input,
+                    parent_id: None,
+                }]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn item_completed(&mut self, item: &ThreadItem) -> Vec<AgentEvent> {
+        match &item.details {
+            ItemDetails::AgentMessage { text } => {
+                self.last_message = Some(text.clone());
+                vec![AgentEvent::MessageDelta { text: text.clone() }]
+            }
+            ItemDetails::Reasoning { text } => vec![AgentEvent::Thinking { text: text.clone() }],
+            ItemDetails::FileChange { changes, status } => changes
+                .iter()
+                .enumerate()
+                .flat_map(|(i, change)| {
+                    let id = format!("{}:{i}", item.id);
+                    [
+                        AgentEvent::ToolCall {
+                            id: id.clone(),
+                            name: "Edit".to_string(),
+                            input: json!({ "file_path": change.path }),
+                            parent_id: None,
+                        },
+                        AgentEvent::ToolResult {
+                            id,
+                            output: format!("{} {}", change.kind, change.path),
+                            is_error: *status == ItemStatus::Failed,
+                        },
+                    ]
+                })
+                .collect(),
+            ItemDetails::Error { message } => vec![AgentEvent::Error {
+                message: message.clone(),
+            }],
+            details => {
+                let mut events = self.item_started(item);
+                if self.started.remove(&item.id) {
+                    let (output, is_error) = tool_output(details);
+                    events.push(AgentEvent::ToolResult {
+                        id: item.id.clone(),
+                        output,
+                        is_error,
+                    });
+                }
+                events
+            }
+        }
+    }
+
+    /// Normalized events for one typed event
+    pub fn handle(&mut self, event: ThreadEvent) -> Vec<AgentEvent> {
+        match event {
+            ThreadEvent::ThreadStarted { thread_id } => vec![AgentEvent::SessionStarted {
+                session_id: thread_id,
+            }],
+            ThreadEvent::ItemStarted { item } => self.item_started(&item),
+            ThreadEvent::ItemCompleted { item } => self.item_completed(&item),
+            ThreadEvent::TurnCompleted { usage } => vec![
+                AgentEvent::Usage {
+                    input_tokens: usage.input_tokens,
+                    output_tokens: usage.output_tokens,
+                    cache_read_input_tokens: usage.cached_input_tokens,
+                    cache_creation_input_tokens: 0,
+                    cost_usd: None,
+                },
+                AgentEvent::Done {
+                    result: self.last_message.take(),
+                    is_error: false,
+                },
+            ],
+            ThreadEvent::TurnFailed { error } => vec![
+                AgentEvent::Error {
+                    message: error.message,
+                },
+                AgentEvent::Done {
+                    result: None,
+                    is_error: true,
+                },
+            ],
+            ThreadEvent::Error { message } => vec![AgentEvent::Error { message }],
+            ThreadEvent::TurnStarted {}
+            | ThreadEvent::ItemUpdated { .. }
+            | ThreadEvent::Unknown => {
+                vec![]
+            }
+        }
+    }
+}
+
+impl Adapter for CodexAdapter {
+    fn parse_line(&mut self, line: &str) -> Vec<AgentEvent> {
+        let line = line.trim();
+        if !line.starts_with('{') {
+            return vec![];
+        }
+        let mut value: Value = match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(e) => {
+                let error = e.to_string();
+                self.record(vec![SchemaDrift::new(
+                    "",
+                    DriftKind::Unparsable { error },
+                    line,
+                )]);
+                return vec![AgentEvent::Raw {
+                    value: Value::String(line.to_string()),
+                }];
+            }
+        };
+        upgrade_legacy(&mut value);
+        self.record(schema_drift(&value, line));
+
+        match ThreadEvent::deserialize(&value) {
+            Ok(ThreadEvent::Unknown)
+            | Ok(ThreadEvent::ItemCompleted {
+                item:
+                    ThreadItem {
+                        details: ItemDetails::Unknown,
+                        ..
+                    },
+            }) => vec![AgentEvent::Raw { value }],
+            Ok(event) => self.handle(event),
+            Err(e) => {
+                let error = e.to_string();
+                let drift =
+                    SchemaDrift::new(type_of(&value), DriftKind::Unparsable { error }, line);
+                self.record(vec![drift]);
+                vec![AgentEvent::Raw { value }]
+            }
+        }
+    }
+
+    fn take_drift(&mut self) -> Vec<SchemaDrift> {
+        std::mem::take(&mut self.drift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture_path;
+
+    fn parse(lines: &[&str]) -> Vec<AgentEvent> {
+        let mut adapter = CodexAdapter::default();
+        lines.iter().flat_map(|l| adapter.parse_line(l)).collect()
+    }
+
+    #[test]
+    fn test_command_reported_once() {
+        let events = parse(&[
+            r#"{"type":"item.started","item":{"id":"i1","type":"command_execution","command":"false","status":"in_progress"}}"#,
+            r#"{"type":"item.completed","item":{"id":"i1","type":"command_execution","command":"false","aggregated_output":"","exit_code":1,"status":"failed"}}"#,
+        ]);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], AgentEvent::ToolCall { name, .. } if name == "Bash"));
+        assert!(matches!(
+            &events[1],
+            AgentEvent::ToolResult { is_error: true, .. }
+        ));
+    }
+
+    #[test]
+    fn test_file_changes_and_failures() {
+        let events = parse(&[
+            r#"{"type":"item.completed","item":{"id":"i2","type":"file_change","changes":[{"path":"a.rs","kind":"update"},{"path":"b.rs","kind":"add"}],"status":"completed"}}"#,
+            r#"{"type":"item.completed","item":{"id":"i3","type":"mcp_tool_call","server":"docs","tool":"search","arguments":{"q":"x"},"result":"hit","status":"completed"}}"#,
+            r#"{"type":"turn.failed","error":{"message":"rate limited"}}"#,
+        ]);
+        let names: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                AgentEvent::ToolCall { id, name, .. } => Some(format!("{id} {name}")),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, ["i2:0 Edit", "i2:1 Edit", "i3 mcp__docs__search"]);
+        assert!(events.contains(&AgentEvent::Error {
+            message: "rate limited".to_string()
+        }));
+        assert!(matches!(
+            events.last(),
+            Some(AgentEvent::Done { is_error: true, .. })
+        ));
+    }
+
+    #[test]
+    fn test_schema_drift_passes_through() {
+        let mut adapter = CodexAdapter::default();
+        let events: Vec<_> = [
+            r#"{"type":"thread.resumed","thread_id":"th-1"}"#,
+            r#"{"type":"item.completed","item":{"id":"i1","type":"image_generation","prompt":"x"}}"#,
+            r#"{"type":"item.completed","item":{"id":"i2","type":"agent_message","content":"renamed"}}"#,
+            r#"{"type":"item.completed","item":{"id":"i3","type":"agent_message","text":"Done."}}"#,
+            "Reading prompt from stdin...",
+            r#"{"type":"turn.completed","usage":{"input_tokens":10,"output_tokens":2}}"#,
+        ]
+        .iter()
+        .flat_map(|l| adapter.parse_line(l))
+        .collect();
+
+        let drift: Vec<_> = adapter
+            .take_drift()
+            .into_iter()
+            .map(|d| match d.kind {
+                DriftKind::UnknownType => format!("{} type", d.event_type),
+                DriftKind::UnknownField { field } => format!("{} {field}", d.event_type),
+                DriftKind::Unparsable { .. } => format!("{} unparsable", d.event_type),
+            })
+            .collect();
+        assert_eq!(
+            drift,
+            [
+                "thread.resumed type",
+                "item:image_generation type",
+                "item:agent_message content",
+                "item.completed unparsable",
+            ]
+        );
+        let raw = events
+            .iter()
+            .filter(|e| matches!(e, AgentEvent::Raw { .. }))
+            .count();
+        assert_eq!(raw, 3);
+        assert_eq!(
+            events.last(),
+            Some(&AgentEvent::Done {
+                result: Some("Done.".to_string()),
+                is_error: false,
+            })
+        );
+    }
+
+    /// Each `fixtures/codex-exec/{version}/exec.jsonl` is a run in that
+    /// version's schema and must normalize to `events.golden.json` (set
+    /// `UPDATE_GOLDEN` to rewrite them after an intended change)
+    #[test]
+    fn test_golden_files() {
+        let root = fixture_path("codex-exec");
+        let mut versions: Vec<_> = std::fs::read_dir(&root)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        versions.sort();
+        assert!(!versions.is_empty());
+
+        for dir in versions {
+            let output = std::fs::read_to_string(dir.join("exec.jsonl")).unwrap();
+            let mut adapter = CodexAdapter::default();
+            let mut events: Vec<AgentEvent> = output
+                .lines()
+                .flat_map(|line| adapter.parse_line(line))
+                .collect();
+            events.extend(adapter.finish());
+            let drift = adapter.take_drift();
+            assert!(drift.is_empty(), "{}: {drift:?}", dir.display());
+
+            let golden = dir.join("events.golden.json");
+            let actual = serde_json::to_value(&events).unwrap();
+            if std::env::var_os("UPDATE_GOLDEN").is_some() {
+                let pretty = serde_json::to_string_pretty(&actual).unwrap();
+                std::fs::write(&golden, pretty + "\n").unwrap();
+                continue;
+            }
+            let expected: Value =
+                serde_json::from_str(&std::fs::read_to_string(&golden).unwrap()).unwrap();
+            assert_eq!(actual, expected, "{}", dir.display());
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Tauri commands for custom agent backends and schema drift samples
+
+use tauri::AppHandle;
+
+use super::custom::{self, CustomBackend};
+use super::drift::{self, DriftReport};
+
+/// All custom backend manifests
+#[tauri::command]
+pub async fn list_custom_backends(app: AppHandle) -> Result<Vec<CustomBackend>, String> {
+    Ok(custom::list_backends(&app))
+}
+
+/// Create or replace a custom backend manifest
+#[tauri::command]
+pub async fn save_custom_backend(app: AppHandle, backend: CustomBackend) -> Result<(), String> {
+    log::trace!("Saving custom backend {}", backend.name);
+    custom::save_backend(&app, &backend)
+}
+
+/// Delete a custom backend manifest
+#[tauri::command]
+pub async fn delete_custom_backend(app: AppHandle, name: String) -> Result<(), String> {
+    log::trace!("Deleting custom backend {name}");
+    custom::delete_backend(&app, &name)
+}
+
+/// Recorded samples of CLI output the adapters didn't recognize, newest first
+#[tauri::command]
+pub async fn list_schema_drift(backend: Option<String>) -> Result<Vec<DriftReport>, String> {
+    Ok(drift::list_samples(backend.as_deref()))
+}
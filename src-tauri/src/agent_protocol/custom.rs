@@ -0,0 +1,300 @@
+//! User-defined agent backends
+//!
+//! A manifest in `{app_data}/agent-backends/{name}.json` describes how to run
+//! an agent CLI Jean has no built-in support for: a command template, the
+//! format of what it prints, and what it can do. A CLI profile selects it
+//! with `"backend": "{name}"`.
+//!
+//! ```json
+//! {
+//!   "name": "acme-agent",
+//!   "command": ["acme", "run", "--prompt-file", "{message_file}", "--model={model}"],
+//!   "stream_format": "plain-text",
+//!   "capabilities": { "model_selection": true }
+//! }
+//! ```
+//!
+//! Placeholders: `{message_file}` (path of a file holding the user's
+//! message), `{message}`, `{model}` and `{cwd}`. Arguments that use `{model}`
+//! are dropped when the session has no model or the backend doesn't declare
+//! `model_selection`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::Adapter;
+use crate::runtime::PathProvider;
+
+const BACKENDS_DIR: &str = "agent-backends";
+
+/// Names taken by built-in backends
+const RESERVED_NAMES: &[&str] = &["claude", "aider", "codex"];
+
+/// What a backend prints, i.e. which adapter reads it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StreamFormat {
+    /// Claude CLI `--output-format stream-json`
+    ClaudeStreamJson,
+    /// `codex exec --json`
+    CodexJson,
+    /// aider with `--no-pretty`
+    AiderText,
+    /// Anything else: all output is shown as the reply
+    #[default]
+    PlainText,
+}
+
+impl StreamFormat {
+    pub fn adapter(self) -> Box<dyn Adapter> {
+        match self {
+            Self::ClaudeStreamJson => Box::new(super::claude::ClaudeAdapter::default()),
+            Self::CodexJson => Box::new(super::codex::CodexAdapter::default()),
+            Self::AiderText => Box::new(super::aider::AiderAdapter::default()),
+            Self::PlainText => Box::new(super::plain::PlainTextAdapter::default()),
+        }
+    }
+}
+
+/// What a backend supports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Accepts a model name (enables `{model}` arguments)
+    #[serde(default)]
+    pub model_selection: bool,
+    /// Reads the plain message on stdin; otherwise stdin gets Jean's
+    /// stream-json input message, like the Claude CLI
+    #[serde(default)]
+    pub stdin_prompt: bool,
+    /// Reports tool calls in its output (informational, for the UI)
+    #[serde(default)]
+    pub tool_calls: bool,
+    /// Reports token usage in its output (informational, for the UI)
+    #[serde(default)]
+    pub usage: bool,
+}
+
+/// A custom backend manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomBackend {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Program followed by its arguments, with placeholders
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub stream_format: StreamFormat,
+    #[serde(default)]
+    pub capabilities: Capabilities,
+    /// Extra environment for the process
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Values substituted into a command template
+pub struct TemplateVars<'a> {
+    pub message_file: &'a Path,
+    pub message: &'a str,
+    pub model: Option<&'a str>,
+    pub cwd: &'a Path,
+}
+
+const PLACEHOLDERS: &[&str] = &["message_file", "message", "model", "cwd"];
+
+/// Names of the `{placeholders}` in an argument
+fn placeholders(arg: &str) -> impl Iterator<Item = &str> {
+    arg.split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+}
+
+impl CustomBackend {
+    pub fn validate(&self) -> Result<(), String> {
+        let name_ok = !self.name.is_empty()
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !name_ok {
+            return Err(format!(
+                "Invalid backend name '{}': use lowercase letters, digits, - and _",
+                self.name
+            ));
+        }
+        if RESERVED_NAMES.contains(&self.name.as_str()) {
+            return Err(format!("'{}' is a built-in backend", self.name));
+        }
+        let Some(program) = self.command.first() else {
+            return Err("Command cannot be empty".to_string());
+        };
+        if program.contains('{') {
+            return Err("The program can't be a placeholder".to_string());
+        }
+        for arg in &self.command {
+            if let Some(unknown) = placeholders(arg).find(|p| !PLACEHOLDERS.contains(p)) {
+                return Err(format!("Unknown placeholder {{{unknown}}} in '{arg}'"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Program and arguments for one run
+    pub fn render(&self, vars: &TemplateVars) -> Result<(PathBuf, Vec<String>), String> {
+        self.validate()?;
+        let model = vars.model.filter(|_| self.capabilities.model_selection);
+        let message_file = vars.message_file.to_string_lossy();
+        let cwd = vars.cwd.to_string_lossy();
+
+        let args = self.command[1..]
+            .iter()
+            .filter(|arg| model.is_some() || !placeholders(arg).any(|p| p == "model"))
+            .map(|arg| {
+                arg.replace("{message_file}", &message_file)
+                    .replace("{cwd}", &cwd)
+                    .replace("{model}", model.unwrap_or_default())
+                    // Last, so message text can't inject other placeholders
+                    .replace("{message}", vars.message)
+            })
+            .collect();
+
+        let program = &self.command[0];
+        let path = if Path::new(program).is_absolute() {
+            PathBuf::from(program)
+        } else {
+            which::which(program).map_err(|_| format!("{program} not found on PATH"))?
+        };
+        Ok((path, args))
+    }
+}
+
+fn backends_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.app_data_dir()?.join(BACKENDS_DIR))
+}
+
+/// All valid manifests; invalid ones are logged and skipped
+pub fn list_backends(app: &impl PathProvider) -> Vec<CustomBackend> {
+    let Ok(entries) =
+        backends_dir(app).and_then(|dir| fs::read_dir(dir).map_err(|e| e.to_string()))
+    else {
+        return vec![];
+    };
+    let mut backends: Vec<CustomBackend> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let backend = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| {
+                    serde_json::from_str::<CustomBackend>(&content).map_err(|e| e.to_string())
+                })
+                .and_then(|backend| backend.validate().map(|_| backend));
+            backend
+                .map_err(|e| log::warn!("Skipping backend manifest {path:?}: {e}"))
+                .ok()
+        })
+        .collect();
+    backends.sort_by(|a, b| a.name.cmp(&b.name));
+    backends
+}
+
+pub fn find_backend(app: &impl PathProvider, name: &str) -> Option<CustomBackend> {
+    list_backends(app).into_iter().find(|b| b.name == name)
+}
+
+pub fn save_backend(app: &impl PathProvider, backend: &CustomBackend) -> Result<(), String> {
+    backend.validate()?;
+    let dir = backends_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backends directory: {e}"))?;
+    let content = serde_json::to_string_pretty(backend)
+        .map_err(|e| format!("Failed to serialize backend: {e}"))?;
+    fs::write(dir.join(format!("{}.json", backend.name)), content)
+        .map_err(|e| format!("Failed to save backend: {e}"))
+}
+
+pub fn delete_backend(app: &impl PathProvider, name: &str) -> Result<(), String> {
+    let path = backends_dir(app)?.join(format!("{name}.json"));
+    if find_backend(app, name).is_none() {
+        return Err(format!("No custom backend named {name}"));
+    }
+    fs::remove_file(path).map_err(|e| format!("Failed to delete backend: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    fn backend(command: &[&str]) -> CustomBackend {
+        CustomBackend {
+            name: "acme".to_string(),
+            description: None,
+            command: command.iter().map(|s| s.to_string()).collect(),
+            stream_format: StreamFormat::PlainText,
+            capabilities: Capabilities::default(),
+            env: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_substitutes_placeholders() {
+        let mut acme = backend(&[
+            "/bin/acme",
+            "--file",
+            "{message_file}",
+            "--model={model}",
+            "{message}",
+        ]);
+        let vars = TemplateVars {
+            message_file: Path::new("/tmp/m.md"),
+            message: "fix {cwd}",
+            model: Some("big"),
+            cwd: Path::new("/work"),
+        };
+
+        // Without model_selection the model argument is left out
+        let (program, args) = acme.render(&vars).unwrap();
+        assert_eq!(program, PathBuf::from("/bin/acme"));
+        assert_eq!(args, ["--file", "/tmp/m.md", "fix {cwd}"]);
+
+        acme.capabilities.model_selection = true;
+        let (_, args) = acme.render(&vars).unwrap();
+        assert_eq!(args[2], "--model=big");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(backend(&["acme", "{nope}"]).validate().is_err());
+        assert!(backend(&["{message}"]).validate().is_err());
+        assert!(backend(&[]).validate().is_err());
+        let mut reserved = backend(&["acme"]);
+        reserved.name = "aider".to_string();
+        assert!(reserved.validate().is_err());
+        reserved.name = "../x".to_string();
+        assert!(reserved.validate().is_err());
+    }
+
+    #[test]
+    fn test_manifests_round_trip() {
+        let paths = TempPaths::new();
+        assert!(list_backends(&paths).is_empty());
+
+        save_backend(&paths, &backend(&["acme", "{message}"])).unwrap();
+        let dir = backends_dir(&paths).unwrap();
+        fs::write(dir.join("broken.json"), "{").unwrap();
+        let manifest = r#"{"name":"minimal","command":["mini"]}"#;
+        fs::write(dir.join("minimal.json"), manifest).unwrap();
+
+        let names: Vec<_> = list_backends(&paths).into_iter().map(|b| b.name).collect();
+        assert_eq!(names, ["acme", "minimal"]);
+        let minimal = find_backend(&paths, "minimal").unwrap();
+        assert_eq!(minimal.stream_format, StreamFormat::PlainText);
+
+        delete_backend(&paths, "acme").unwrap();
+        assert!(find_backend(&paths, "acme").is_none());
+        assert!(delete_backend(&paths, "acme").is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Schema drift in agent CLI JSON output
+//!
+//! Adapters compare each JSON line with the event types and fields they know
+//! (a [`Schema`]). New types and fields from a newer CLI are reported as
+//! [`SchemaDrift`] instead of being dropped silently: the first samples of
+//! each are kept in `{app_data}/schema-drift/{backend}.jsonl`, a
+//! `backend:schema-drift` event warns the UI, and events of unknown types
+//! pass through to the run log as `AgentEvent::Raw`.
+
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::runtime::{EventSink, PathProvider};
+
+const DRIFT_DIR: &str = "schema-drift";
+
+/// Samples kept per backend
+const MAX_SAMPLES: usize = 200;
+
+/// Longest sample kept, in characters
+const MAX_SAMPLE_CHARS: usize = 2000;
+
+/// Samples directory (set by `init`)
+static SAMPLES_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// Drift already reported by this app run
+static REPORTED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Known event types and their fields; a `"*"` field accepts any field
+pub type Schema = &'static [(&'static str, &'static [&'static str])];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DriftKind {
+    UnknownType,
+    UnknownField {
+        field: String,
+    },
+    /// A known type that no longer deserializes
+    Unparsable {
+        error: String,
+    },
+}
+
+/// Something in a CLI's output its adapter doesn't know
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaDrift {
+    /// Event type, or a nested type such as "item:todo_list"
+    pub event_type: String,
+    #[serde(flatten)]
+    pub kind: DriftKind,
+    /// The line it was found in (truncated)
+    pub sample: String,
+}
+
+impl SchemaDrift {
+    pub fn new(event_type: &str, kind: DriftKind, line: &str) -> Self {
+        Self {
+            event_type: event_type.to_string(),
+            kind,
+            sample: line.trim().chars().take(MAX_SAMPLE_CHARS).collect(),
+        }
+    }
+}
+
+/// Unknown type or fields of `value`, an event of `event_type`
+pub fn check(schema: Schema, event_type: &str, value: &Value, line: &str) -> Vec<SchemaDrift> {
+    let Some((_, fields)) = schema.iter().find(|(known, _)| *known == event_type) else {
+        return vec![SchemaDrift::new(event_type, DriftKind::UnknownType, line)];
+    };
+    if fields.contains(&"*") {
+        return vec![];
+    }
+    value
+        .as_object()
+        .into_iter()
+        .flat_map(|object| object.keys())
+        .filter(|key| *key != "type" && !fields.contains(&key.as_str()))
+        .map(|field| {
+            let kind = DriftKind::UnknownField {
+                field: field.clone(),
+            };
+            SchemaDrift::new(event_type, kind, line)
+        })
+        .collect()
+}
+
+/// Payload of `backend:schema-drift` and line in the samples file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftReport {
+    pub backend: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub detected_at: u64,
+    #[serde(flatten)]
+    pub drift: SchemaDrift,
+}
+
+/// Remember where samples are kept (call once during setup)
+pub fn init(app: &impl PathProvider) {
+    match app.app_data_dir() {
+        Ok(dir) => {
+            let _ = SAMPLES_DIR.set(dir.join(DRIFT_DIR));
+        }
+        Err(e) => log::warn!("Schema drift samples disabled: {e}"),
+    }
+}
+
+fn append_sample(dir: &Path, report: &DriftReport) -> Result<(), String> {
+    let path = dir.join(format!("{}.jsonl", report.backend));
+    let count = fs::read_to_string(&path)
+        .map(|content| content.lines().count())
+        .unwrap_or(0);
+    if count >= MAX_SAMPLES {
+        return Ok(());
+    }
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create drift directory: {e}"))?;
+    let line = serde_json::to_string(report)
+        .map_err(|e| format!("Failed to serialize drift sample: {e}"))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open drift samples: {e}"))?;
+    writeln!(file, "{line}").map_err(|e| format!("Failed to write drift sample: {e}"))
+}
+
+fn read_samples(dir: &Path, backend: Option<&str>) -> Vec<DriftReport> {
+    let mut samples: Vec<DriftReport> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let stem = path.file_stem().and_then(|s| s.to_str());
+            path.extension().is_some_and(|ext| ext == "jsonl")
+                && backend.is_none_or(|backend| stem == Some(backend))
+        })
+        .flat_map(|path| {
+            fs::read_to_string(path)
+                .unwrap_or_default()
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect::<Vec<DriftReport>>()
+        })
+        .collect();
+    samples.sort_by(|a, b| b.detected_at.cmp(&a.detected_at));
+    samples
+}
+
+/// Recorded samples, newest first
+pub fn list_samples(backend: Option<&str>) -> Vec<DriftReport> {
+    SAMPLES_DIR
+        .get()
+        .map(|dir| read_samples(dir, backend))
+        .unwrap_or_default()
+}
+
+/// Record and announce drift not yet reported by this app run
+pub fn report(
+    events: &impl EventSink,
+    backend: &str,
+    session_id: Option<&str>,
+    drift: Vec<SchemaDrift>,
+) {
+    for drift in drift {
+        let key = format!("{backend}:{}:{:?}", drift.event_type, drift.kind);
+        let first = REPORTED.lock().map(|mut r| r.insert(key)).unwrap_or(false);
+        if !first {
+            continue;
+        }
+        log::warn!(
+            "{backend} output doesn't match its schema ({}: {:?})",
+            drift.event_type,
+            drift.kind
+        );
+        let report = DriftReport {
+            backend: backend.to_string(),
+            session_id: session_id.map(str::to_string),
+            detected_at: crate::runtime::now(),
+            drift,
+        };
+        if let Some(dir) = SAMPLES_DIR.get() {
+            if let Err(e) = append_sample(dir, &report) {
+                log::warn!("Failed to record schema drift: {e}");
+            }
+        }
+        if let Err(e) = events.emit_all("backend:schema-drift", &report) {
+            log::warn!("Failed to emit schema drift: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCHEMA: Schema = &[("message", &["text", "id"]), ("system", &["*"])];
+
+    #[test]
+    fn test_check() {
+        let line = r#"{"type":"message","text":"hi","tokens":3}"#;
+        let value: Value = serde_json::from_str(line).unwrap();
+        assert_eq!(
+            check(SCHEMA, "message", &value, line),
+            [SchemaDrift::new(
+                "message",
+                DriftKind::UnknownField {
+                    field: "tokens".to_string()
+                },
+                line
+            )]
+        );
+        assert!(check(SCHEMA, "system", &json!({ "anything": 1 }), "").is_empty());
+        assert_eq!(
+            check(SCHEMA, "status", &json!({}), "")[0].kind,
+            DriftKind::UnknownType
+        );
+    }
+
+    #[test]
+    fn test_samples_are_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = |at: u64| DriftReport {
+            backend: "codex".to_string(),
+            session_id: None,
+            detected_at: at,
+            drift: SchemaDrift::new("thread.resumed", DriftKind::UnknownType, "{}"),
+        };
+        for at in 0..(MAX_SAMPLES as u64 + 5) {
+            append_sample(dir.path(), &report(at)).unwrap();
+        }
+        append_sample(
+            dir.path(),
+            &DriftReport {
+                backend: "claude".to_string(),
+                ..report(1000)
+            },
+        )
+        .unwrap();
+
+        let codex = read_samples(dir.path(), Some("codex"));
+        assert_eq!(codex.len(), MAX_SAMPLES);
+        assert_eq!(codex[0].detected_at, MAX_SAMPLES as u64 - 1);
+        assert_eq!(read_samples(dir.path(), None)[0].backend, "claude");
+    }
+}
@@ -0,0 +1,185 @@
+//! Normalized events for agent CLIs
+//!
+//! Claude, Codex and aider each stream their own output format. An `Adapter`
+//! turns one CLI's output lines into `AgentEvent`s, and `StreamJsonEncoder`
+//! writes events back out as Claude stream-json, which is what run logs,
+//! tailing and replay already read. A new backend therefore only needs an
+//! adapter; everything after the run's output file stays the same. Backends
+//! without built-in support can be described by a manifest (see `custom`)
+//! and read with one of the existing adapters. Output an adapter doesn't
+//! recognize is reported as schema drift (see `drift`) and kept as `Raw`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use drift::SchemaDrift;
+
+pub mod aider;
+pub mod claude;
+pub mod codex;
+pub mod commands;
+pub mod custom;
+pub mod drift;
+mod plain;
+mod stream_json;
+
+pub use stream_json::StreamJsonEncoder;
+
+/// One thing an agent did, independent of the CLI that reported it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentEvent {
+    /// The CLI's own conversation ID, for resuming
+    SessionStarted {
+        session_id: String,
+    },
+    /// Assistant prose
+    MessageDelta {
+        text: String,
+    },
+    /// Extended thinking / reasoning
+    Thinking {
+        text: String,
+    },
+    ToolCall {
+        id: String,
+        name: String,
+        input: Value,
+        /// Sub-agent tool calls name the tool call that spawned them
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        parent_id: Option<String>,
+    },
+    ToolResult {
+        id: String,
+        output: String,
+        #[serde(default)]
+        is_error: bool,
+    },
+    /// A tool call the CLI refused to run without the user's approval
+    ApprovalRequest {
+        id: String,
+        tool_name: String,
+        input: Value,
+    },
+    /// Token usage; reported once per turn
+    Usage {
+        input_tokens: u64,
+        output_tokens: u64,
+        #[serde(default)]
+        cache_read_input_tokens: u64,
+        #[serde(default)]
+        cache_creation_input_tokens: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cost_usd: Option<f64>,
+    },
+    Error {
+        message: String,
+    },
+    /// The run finished; `result` is the final answer if the CLI reports one
+    Done {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        result: Option<String>,
+        is_error: bool,
+    },
+    /// Output of a type the adapter doesn't know, kept as the CLI wrote it
+    Raw {
+        value: Value,
+    },
+}
+
+/// Translates one CLI's output into `AgentEvent`s.
+///
+/// The CLI's combined stdout/stderr is fed in line by line with
+/// `parse_line`. `flush` is called whenever no more output is available for
+/// now, so adapters that group lines (e.g. prose) can report what they have.
+/// `finish` is called once after the process exits and should end with
+/// `AgentEvent::Done` unless the CLI already reported it.
+///
+/// ```ignore
+/// struct EchoAdapter;
+///
+/// impl Adapter for EchoAdapter {
+///     fn parse_line(&mut self, line: &str) -> Vec<AgentEvent> {
+///         vec![AgentEvent::MessageDelta { text: format!("{line}\n") }]
+///     }
+///
+///     fn finish(&mut self) -> Vec<AgentEvent> {
+///         vec![AgentEvent::Done { result: None, is_error: false }]
+///     }
+/// }
+/// ```
+pub trait Adapter: Send {
+    /// Events completed by one output line
+    fn parse_line(&mut self, line: &str) -> Vec<AgentEvent>;
+
+    /// Events buffered so far, e.g. prose still collecting lines
+    fn flush(&mut self) -> Vec<AgentEvent> {
+        vec![]
+    }
+
+    /// Events held back until the CLI exits (buffered text, a final `Done`)
+    fn finish(&mut self) -> Vec<AgentEvent> {
+        vec![]
+    }
+
+    /// Schema drift found since the last call
+    fn take_drift(&mut self) -> Vec<SchemaDrift> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::custom::StreamFormat;
+    use super::*;
+
+    fn round_trip(format: StreamFormat, output: &str) -> (Vec<AgentEvent>, Vec<AgentEvent>) {
+        let mut adapter = format.adapter();
+        let mut events: Vec<AgentEvent> = output
+            .lines()
+            .flat_map(|line| adapter.parse_line(line))
+            .collect();
+        events.extend(adapter.finish());
+
+        let mut encoder = StreamJsonEncoder::default();
+        let lines: Vec<String> = events
+            .iter()
+            .flat_map(|e| encoder.encode(e))
+            .map(|v| v.to_string())
+            .collect();
+
+        let mut claude = StreamFormat::ClaudeStreamJson.adapter();
+        let decoded = lines.iter().flat_map(|l| claude.parse_line(l)).collect();
+        (events, decoded)
+    }
+
+    #[test]
+    fn test_codex_survives_stream_json_round_trip() {
+        let (events, decoded) = round_trip(
+            StreamFormat::CodexJson,
+            r#"{"type":"thread.started","thread_id":"th-1"}
+{"type":"item.completed","item":{"id":"i1","type":"command_execution","command":"ls","aggregated_output":"Cargo.toml\n","exit_code":0,"status":"completed"}}
+{"type":"item.completed","item":{"id":"i2","type":"agent_message","text":"One file."}}
+{"type":"thread.compacted","thread_id":"th-1"}
+{"type":"turn.completed","usage":{"input_tokens":1200,"cached_input_tokens":200,"output_tokens":30}}"#,
+        );
+        assert_eq!(decoded, events);
+        assert!(events.iter().any(|e| matches!(e, AgentEvent::Raw { .. })));
+    }
+
+    #[test]
+    fn test_aider_survives_stream_json_round_trip() {
+        let (events, decoded) = round_trip(
+            StreamFormat::AiderText,
+            "Fixing it.\nApplied edit to src/lib.rs\nTokens: 1k sent, 20 received. Cost: $0.01 message, $0.01 session.\n",
+        );
+        assert_eq!(decoded, events);
+        assert!(matches!(
+            events.last(),
+            Some(AgentEvent::Done {
+                is_error: false,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+//! Adapter for CLIs that only print prose
+
+use super::{Adapter, AgentEvent};
+
+/// Treats every output line as assistant text
+#[derive(Debug, Default)]
+pub struct PlainTextAdapter {
+    pending: String,
+    text: String,
+}
+
+impl Adapter for PlainTextAdapter {
+    fn parse_line(&mut self, line: &str) -> Vec<AgentEvent> {
+        self.pending.push_str(line);
+        self.pending.push('\n');
+        vec![]
+    }
+
+    fn flush(&mut self) -> Vec<AgentEvent> {
+        let text = std::mem::take(&mut self.pending);
+        if text.trim().is_empty() {
+            return vec![];
+        }
+        self.text.push_str(&text);
+        vec![AgentEvent::MessageDelta { text }]
+    }
+
+    fn finish(&mut self) -> Vec<AgentEvent> {
+        let mut events = self.flush();
+        events.push(AgentEvent::Done {
+            result: Some(self.text.trim().to_string()),
+            is_error: self.text.trim().is_empty(),
+        });
+        events
+    }
+}
@@ -0,0 +1,136 @@
+//! Encoding normalized events as Claude stream-json
+
+use serde_json::{json, Map, Value};
+
+use super::AgentEvent;
+
+/// Writes `AgentEvent`s as the stream-json lines the Claude CLI produces.
+/// Usage and approval requests are held until `Done`, since Claude reports
+/// them on its final `result` message.
+#[derive(Debug, Default)]
+pub struct StreamJsonEncoder {
+    session_id: Option<String>,
+    usage: Option<(Value, Option<f64>)>,
+    denials: Vec<Value>,
+    last_error: Option<String>,
+}
+
+impl StreamJsonEncoder {
+    /// Stream-json messages for one event (none while it's being held back)
+    pub fn encode(&mut self, event: &AgentEvent) -> Vec<Value> {
+        let message = match event {
+            AgentEvent::SessionStarted { session_id } => {
+                self.session_id = Some(session_id.clone());
+                json!({ "type": "system", "subtype": "init" })
+            }
+            AgentEvent::MessageDelta { text } => assistant(json!({ "type": "text", "text": text })),
+            AgentEvent::Thinking { text } => {
+                assistant(json!({ "type": "thinking", "thinking": text }))
+            }
+            AgentEvent::ToolCall {
+                id,
+                name,
+                input,
+                parent_id,
+            } => {
+                let mut message = assistant(
+                    json!({ "type": "tool_use", "id": id, "name": name, "input": input }),
+                );
+                if let Some(parent_id) = parent_id {
+                    message["parent_tool_use_id"] = json!(parent_id);
+                }
+                message
+            }
+            AgentEvent::ToolResult {
+                id,
+                output,
+                is_error,
+            } => json!({
+                "type": "user",
+                "message": { "content": [{
+                    "type": "tool_result",
+                    "tool_use_id": id,
+                    "content": output,
+                    "is_error": is_error,
+                }] },
+            }),
+            AgentEvent::ApprovalRequest {
+                id,
+                tool_name,
+                input,
+            } => {
+                self.denials.push(json!({
+                    "tool_name": tool_name,
+                    "tool_use_id": id,
+                    "tool_input": input,
+                }));
+                return vec![];
+            }
+            AgentEvent::Usage {
+                input_tokens,
+                output_tokens,
+                cache_read_input_tokens,
+                cache_creation_input_tokens,
+                cost_usd,
+            } => {
+                let usage = json!({
+                    "input_tokens": input_tokens,
+                    "output_tokens": output_tokens,
+                    "cache_read_input_tokens": cache_read_input_tokens,
+                    "cache_creation_input_tokens": cache_creation_input_tokens,
+                });
+                self.usage = Some((usage, *cost_usd));
+                return vec![];
+            }
+            AgentEvent::Error { message } => {
+                self.last_error = Some(message.clone());
+                json!({ "type": "error", "message": message })
+            }
+            AgentEvent::Done { result, is_error } => {
+                let mut message = Map::new();
+                message.insert("type".into(), json!("result"));
+                message.insert(
+                    "subtype".into(),
+                    json!(if *is_error {
+                        "error_during_execution"
+                    } else {
+                        "success"
+                    }),
+                );
+                message.insert("is_error".into(), json!(is_error));
+                // Failed runs without an answer show the error instead
+                let result = result
+                    .clone()
+                    .or_else(|| is_error.then(|| self.last_error.take()).flatten());
+                message.insert("result".into(), json!(result));
+                if let Some((usage, cost_usd)) = self.usage.take() {
+                    message.insert("usage".into(), usage);
+                    if let Some(cost_usd) = cost_usd {
+                        message.insert("total_cost_usd".into(), json!(cost_usd));
+                    }
+                }
+                if !self.denials.is_empty() {
+                    message.insert(
+                        "permission_denials".into(),
+                        Value::Array(std::mem::take(&mut self.denials)),
+                    );
+                }
+                Value::Object(message)
+            }
+            // Wrapped so its type can't be mistaken for a Claude message
+            AgentEvent::Raw { value } => json!({ "type": "raw", "raw": value }),
+        };
+        vec![self.with_session(message)]
+    }
+
+    fn with_session(&self, mut message: Value) -> Value {
+        if let Some(session_id) = &self.session_id {
+            message["session_id"] = json!(session_id);
+        }
+        message
+    }
+}
+
+fn assistant(block: Value) -> Value {
+    json!({ "type": "assistant", "message": { "content": [block] } })
+}
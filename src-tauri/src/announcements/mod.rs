@@ -0,0 +1,207 @@
+//! Screen-reader announcements for important state changes
+//!
+//! Alongside their regular events, a finished session, a session waiting for
+//! input or approval, and a failed install emit an `a11y:announcement` event
+//! with one concise sentence and a priority. The frontend routes these to
+//! ARIA live regions (`polite` or `assertive`), so every view announces the
+//! same things the same way. `announcement_verbosity` sets, per category,
+//! whether announcements are off, brief or detailed.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::chat::storage::load_metadata;
+use crate::chat::types::ToolCall;
+use crate::runtime::{now, EventSink};
+use crate::speech::tts::{completion_kind, summarize_for_speech, waiting_summary, CompletionKind};
+use crate::{AnnouncementLevel, AnnouncementVerbosity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementCategory {
+    SessionFinished,
+    /// A session asked a question or presented a plan
+    ApprovalNeeded,
+    /// A CLI or helper tool failed to install
+    InstallFailed,
+}
+
+/// ARIA live region politeness
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementPriority {
+    /// Read when the user is idle
+    Polite,
+    /// Interrupts what is being read
+    Assertive,
+}
+
+/// Payload for a11y:announcement events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Announcement {
+    pub category: AnnouncementCategory,
+    pub priority: AnnouncementPriority,
+    pub message: String,
+    pub session_id: Option<String>,
+    pub created_at: u64,
+}
+
+impl AnnouncementCategory {
+    fn priority(self) -> AnnouncementPriority {
+        match self {
+            Self::SessionFinished => AnnouncementPriority::Polite,
+            Self::ApprovalNeeded | Self::InstallFailed => AnnouncementPriority::Assertive,
+        }
+    }
+}
+
+impl AnnouncementVerbosity {
+    fn level(&self, category: AnnouncementCategory) -> AnnouncementLevel {
+        match category {
+            AnnouncementCategory::SessionFinished => self.session_finished,
+            AnnouncementCategory::ApprovalNeeded => self.approval_needed,
+            AnnouncementCategory::InstallFailed => self.install_failed,
+        }
+    }
+}
+
+/// Message for a verbosity level; None when announcements are off
+fn compose(level: AnnouncementLevel, brief: &str, detail: Option<&str>) -> Option<String> {
+    match (level, detail) {
+        (AnnouncementLevel::Off, _) => None,
+        (AnnouncementLevel::Detailed, Some(detail)) => Some(format!("{brief} {detail}")),
+        _ => Some(brief.to_string()),
+    }
+}
+
+/// Emit an announcement unless its category is turned off
+pub fn emit(
+    events: &impl EventSink,
+    verbosity: &AnnouncementVerbosity,
+    category: AnnouncementCategory,
+    session_id: Option<&str>,
+    brief: &str,
+    detail: Option<&str>,
+) {
+    let Some(message) = compose(verbosity.level(category), brief, detail) else {
+        return;
+    };
+    let announcement = Announcement {
+        category,
+        priority: category.priority(),
+        message,
+        session_id: session_id.map(str::to_string),
+        created_at: now(),
+    };
+    if let Err(e) = events.emit_all("a11y:announcement", &announcement) {
+        log::error!("Failed to emit a11y:announcement event: {e}");
+    }
+}
+
+async fn verbosity(app: &AppHandle) -> AnnouncementVerbosity {
+    crate::load_preferences(app.clone())
+        .await
+        .map(|prefs| prefs.announcement_verbosity)
+        .unwrap_or_default()
+}
+
+/// Announce that a run finished, or is waiting on the user
+pub async fn announce_completion(
+    app: &AppHandle,
+    session_id: &str,
+    content: &str,
+    tool_calls: &[ToolCall],
+) {
+    let name = load_metadata(app, session_id)
+        .ok()
+        .flatten()
+        .map(|m| m.name)
+        .unwrap_or_else(|| "A session".to_string());
+    let verbosity = verbosity(app).await;
+
+    match completion_kind(tool_calls) {
+        CompletionKind::Waiting => emit(
+            app,
+            &verbosity,
+            AnnouncementCategory::ApprovalNeeded,
+            Some(session_id),
+            &format!("{name} needs your input."),
+            Some(&waiting_summary(tool_calls)),
+        ),
+        CompletionKind::Review => emit(
+            app,
+            &verbosity,
+            AnnouncementCategory::SessionFinished,
+            Some(session_id),
+            &format!("{name} finished."),
+            summarize_for_speech(content).as_deref(),
+        ),
+    }
+}
+
+/// Announce a failed install of `tool` (e.g. "Claude CLI")
+pub async fn announce_install_failure(app: &AppHandle, tool: &str, error: &str) {
+    emit(
+        app,
+        &verbosity(app).await,
+        AnnouncementCategory::InstallFailed,
+        None,
+        &format!("{tool} install failed."),
+        Some(error),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::RecordingSink;
+
+    #[test]
+    fn test_compose() {
+        let detail = Some("Tests pass.");
+        assert_eq!(compose(AnnouncementLevel::Off, "Done.", detail), None);
+        assert_eq!(
+            compose(AnnouncementLevel::Brief, "Done.", detail).as_deref(),
+            Some("Done.")
+        );
+        assert_eq!(
+            compose(AnnouncementLevel::Detailed, "Done.", detail).as_deref(),
+            Some("Done. Tests pass.")
+        );
+        assert_eq!(
+            compose(AnnouncementLevel::Detailed, "Done.", None).as_deref(),
+            Some("Done.")
+        );
+    }
+
+    #[test]
+    fn test_emit_respects_verbosity() {
+        let sink = RecordingSink::default();
+        let verbosity = AnnouncementVerbosity {
+            session_finished: AnnouncementLevel::Off,
+            ..Default::default()
+        };
+        emit(
+            &sink,
+            &verbosity,
+            AnnouncementCategory::SessionFinished,
+            Some("s1"),
+            "Fix build finished.",
+            None,
+        );
+        emit(
+            &sink,
+            &verbosity,
+            AnnouncementCategory::InstallFailed,
+            None,
+            "GitHub CLI install failed.",
+            Some("Checksum mismatch"),
+        );
+
+        let payloads = sink.payloads("a11y:announcement");
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0]["category"], "install_failed");
+        assert_eq!(payloads[0]["priority"], "assertive");
+        assert_eq!(payloads[0]["message"], "GitHub CLI install failed.");
+    }
+}
@@ -0,0 +1,24 @@
+//! Tauri commands for the audit log
+
+use tauri::AppHandle;
+
+use super::WebRequest;
+
+/// Requests returned when no limit is given
+const DEFAULT_LIMIT: usize = 200;
+
+/// Web requests made by agents, newest first
+#[tauri::command]
+pub async fn list_web_requests(
+    app: AppHandle,
+    project_id: Option<String>,
+    session_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<WebRequest>, String> {
+    Ok(super::list(
+        &app,
+        project_id.as_deref(),
+        session_id.as_deref(),
+        limit.unwrap_or(DEFAULT_LIMIT),
+    ))
+}
@@ -0,0 +1,337 @@
+//! Audit log of agent web requests
+//!
+//! After each run, the `WebFetch` and `WebSearch` calls in its output (read
+//! back through the normalized `agent_protocol` events, so every backend is
+//! covered) are appended to `{app_data}/audit/web-requests.jsonl` with the
+//! URL or query and the size of the response.
+//!
+//! Projects can block domains. Blocked domains become `--disallowedTools
+//! WebFetch(domain:…)` rules for every run, which the CLI enforces before
+//! any auto-approval (also in yolo mode); requests that were attempted anyway
+//! are marked `blocked` in the log.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::agent_protocol::custom::StreamFormat;
+use crate::agent_protocol::AgentEvent;
+use crate::runtime::PathProvider;
+
+pub mod commands;
+
+const AUDIT_DIR: &str = "audit";
+const WEB_REQUESTS_FILE: &str = "web-requests.jsonl";
+
+/// Requests kept in the log; older ones are dropped
+const MAX_WEB_REQUESTS: usize = 5000;
+
+/// Tools that reach the network
+const WEB_TOOLS: &[&str] = &["WebFetch", "WebSearch"];
+
+/// One web request made by an agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebRequest {
+    /// Unix seconds the run finished
+    pub recorded_at: u64,
+    pub session_id: String,
+    pub run_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// WebFetch or WebSearch
+    pub tool: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Host of `url`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    /// Search query (WebSearch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Bytes of tool output the agent got back
+    pub response_bytes: usize,
+    pub is_error: bool,
+    /// The domain is on the project's blocklist
+    pub blocked: bool,
+}
+
+/// Lowercase host, without a scheme, path or leading `*.`
+pub fn normalize_domain(entry: &str) -> Option<String> {
+    let entry = entry.trim().to_lowercase();
+    let host = match reqwest::Url::parse(&entry) {
+        Ok(url) if url.host_str().is_some() => url.host_str()?.to_string(),
+        _ => entry.split(['/', ':']).next()?.to_string(),
+    };
+    let host = host.trim_start_matches("*.").trim_end_matches('.');
+    (!host.is_empty() && !host.contains(char::is_whitespace)).then(|| host.to_string())
+}
+
+/// Whether `domain` is on `blocklist` (entries are normalized)
+pub fn is_blocked(domain: &str, blocklist: &[String]) -> bool {
+    blocklist.iter().any(|blocked| blocked == domain)
+}
+
+/// CLI permission rules denying fetches from blocked domains
+pub fn deny_rules(blocklist: &[String]) -> Vec<String> {
+    blocklist
+        .iter()
+        .map(|domain| format!("WebFetch(domain:{domain})"))
+        .collect()
+}
+
+/// Domains blocked for the project owning `worktree_id`
+pub fn blocked_domains(app: &tauri::AppHandle, worktree_id: &str) -> Vec<String> {
+    let Ok(data) = crate::projects::storage::load_projects_data(app) else {
+        return Vec::new();
+    };
+    data.find_worktree(worktree_id)
+        .and_then(|w| data.find_project(&w.project_id))
+        .map(|p| p.blocked_domains.clone())
+        .unwrap_or_default()
+}
+
+/// Web tool calls in a run's normalized events, with their results
+pub fn web_requests(events: &[AgentEvent], blocklist: &[String]) -> Vec<WebRequest> {
+    let results: HashMap<&str, (&str, bool)> = events
+        .iter()
+        .filter_map(|event| match event {
+            AgentEvent::ToolResult {
+                id,
+                output,
+                is_error,
+            } => Some((id.as_str(), (output.as_str(), *is_error))),
+            _ => None,
+        })
+        .collect();
+
+    events
+        .iter()
+        .filter_map(|event| match event {
+            AgentEvent::ToolCall {
+                id, name, input, ..
+            } if WEB_TOOLS.contains(&name.as_str()) => Some((id, name, input)),
+            _ => None,
+        })
+        .map(|(id, name, input)| {
+            let field = |key: &str| input.get(key).and_then(Value::as_str).map(str::to_string);
+            let url = field("url");
+            let domain = url.as_deref().and_then(normalize_domain);
+            let (output, is_error) = results.get(id.as_str()).copied().unwrap_or(("", false));
+            WebRequest {
+                recorded_at: 0,
+                session_id: String::new(),
+                run_id: String::new(),
+                project_id: None,
+                tool: name.clone(),
+                blocked: domain.as_deref().is_some_and(|d| is_blocked(d, blocklist)),
+                url,
+                domain,
+                query: field("query"),
+                response_bytes: output.len(),
+                is_error,
+            }
+        })
+        .collect()
+}
+
+fn log_path(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.app_data_dir()?.join(AUDIT_DIR).join(WEB_REQUESTS_FILE))
+}
+
+/// Append requests to the log, dropping the oldest beyond [`MAX_WEB_REQUESTS`]
+pub fn append(app: &impl PathProvider, requests: &[WebRequest]) -> Result<(), String> {
+    if requests.is_empty() {
+        return Ok(());
+    }
+    let path = log_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create audit directory: {e}"))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open audit log: {e}"))?;
+    for request in requests {
+        let line = serde_json::to_string(request)
+            .map_err(|e| format!("Failed to serialize web request: {e}"))?;
+        writeln!(file, "{line}").map_err(|e| format!("Failed to write audit log: {e}"))?;
+    }
+    drop(file);
+
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    let count = content.lines().count();
+    if count > MAX_WEB_REQUESTS {
+        let kept: Vec<&str> = content.lines().skip(count - MAX_WEB_REQUESTS).collect();
+        fs::write(&path, format!("{}\n", kept.join("\n")))
+            .map_err(|e| format!("Failed to trim audit log: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Logged requests, newest first, optionally for one project or session
+pub fn list(
+    app: &impl PathProvider,
+    project_id: Option<&str>,
+    session_id: Option<&str>,
+    limit: usize,
+) -> Vec<WebRequest> {
+    let content = log_path(app)
+        .and_then(|path| fs::read_to_string(path).map_err(|e| e.to_string()))
+        .unwrap_or_default();
+    content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<WebRequest>(line).ok())
+        .filter(|r| project_id.is_none_or(|id| r.project_id.as_deref() == Some(id)))
+        .filter(|r| session_id.is_none_or(|id| r.session_id == id))
+        .take(limit)
+        .collect()
+}
+
+/// Record the web requests of a finished run from its output file
+pub fn record_run(
+    app: &tauri::AppHandle,
+    session_id: &str,
+    worktree_id: &str,
+    run_id: &str,
+    output_file: &Path,
+) {
+    let Ok(output) = fs::read_to_string(output_file) else {
+        return;
+    };
+    let mut adapter = StreamFormat::ClaudeStreamJson.adapter();
+    let mut events: Vec<AgentEvent> = output
+        .lines()
+        .flat_map(|line| adapter.parse_line(line))
+        .collect();
+    events.extend(adapter.finish());
+
+    let blocklist = blocked_domains(app, worktree_id);
+    let mut requests = web_requests(&events, &blocklist);
+    if requests.is_empty() {
+        return;
+    }
+    let project_id = crate::projects::storage::load_projects_data(app)
+        .ok()
+        .and_then(|data| Some(data.find_worktree(worktree_id)?.project_id.clone()));
+    let recorded_at = crate::runtime::now();
+    for request in &mut requests {
+        request.recorded_at = recorded_at;
+        request.session_id = session_id.to_string();
+        request.run_id = run_id.to_string();
+        request.project_id = project_id.clone();
+        if request.blocked {
+            log::warn!(
+                "Session {session_id} requested blocked domain {:?}",
+                request.domain
+            );
+        }
+    }
+    match append(app, &requests) {
+        Ok(()) => log::trace!("Recorded {} web request(s) of run {run_id}", requests.len()),
+        Err(e) => log::warn!("Failed to record web requests: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+    use serde_json::json;
+
+    fn call(id: &str, name: &str, input: Value) -> AgentEvent {
+        AgentEvent::ToolCall {
+            id: id.to_string(),
+            name: name.to_string(),
+            input,
+            parent_id: None,
+        }
+    }
+
+    fn result(id: &str, output: &str, is_error: bool) -> AgentEvent {
+        AgentEvent::ToolResult {
+            id: id.to_string(),
+            output: output.to_string(),
+            is_error,
+        }
+    }
+
+    #[test]
+    fn test_normalize_domain() {
+        assert_eq!(
+            normalize_domain("https://Docs.RS/serde"),
+            Some("docs.rs".to_string())
+        );
+        assert_eq!(
+            normalize_domain("*.example.com"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(
+            normalize_domain("pastebin.com/raw"),
+            Some("pastebin.com".to_string())
+        );
+        assert_eq!(normalize_domain("  "), None);
+    }
+
+    #[test]
+    fn test_web_requests() {
+        let blocklist = vec!["pastebin.com".to_string()];
+        let events = vec![
+            call(
+                "t1",
+                "WebFetch",
+                json!({ "url": "https://docs.rs/serde", "prompt": "x" }),
+            ),
+            call("t2", "Bash", json!({ "command": "curl example.com" })),
+            call("t3", "WebSearch", json!({ "query": "serde derive" })),
+            call(
+                "t4",
+                "WebFetch",
+                json!({ "url": "https://pastebin.com/raw/1" }),
+            ),
+            result("t1", "serde docs", false),
+            result("t3", "results", false),
+            result("t4", "Permission denied", true),
+        ];
+        let requests = web_requests(&events, &blocklist);
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].domain.as_deref(), Some("docs.rs"));
+        assert_eq!(requests[0].response_bytes, 10);
+        assert_eq!(requests[1].query.as_deref(), Some("serde derive"));
+        assert!(requests[2].blocked && requests[2].is_error);
+        assert_eq!(deny_rules(&blocklist), ["WebFetch(domain:pastebin.com)"]);
+    }
+
+    #[test]
+    fn test_log_is_listed_newest_first() {
+        let paths = TempPaths::new();
+        let request = |session_id: &str| WebRequest {
+            recorded_at: 1,
+            session_id: session_id.to_string(),
+            run_id: "r1".to_string(),
+            project_id: Some("p1".to_string()),
+            tool: "WebSearch".to_string(),
+            url: None,
+            domain: None,
+            query: Some("q".to_string()),
+            response_bytes: 0,
+            is_error: false,
+            blocked: false,
+        };
+        append(&paths, &[request("s1"), request("s2")]).unwrap();
+        append(&paths, &[request("s3")]).unwrap();
+
+        let sessions: Vec<_> = list(&paths, Some("p1"), None, 10)
+            .into_iter()
+            .map(|r| r.session_id)
+            .collect();
+        assert_eq!(sessions, ["s3", "s2", "s1"]);
+        assert_eq!(list(&paths, None, Some("s2"), 10).len(), 1);
+        assert!(list(&paths, Some("p2"), None, 10).is_empty());
+    }
+}
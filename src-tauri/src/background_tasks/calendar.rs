@@ -0,0 +1,416 @@
+//! Busy/free state from a local calendar file
+//!
+//! Queued runs with `avoid_meetings` wait while the `.ics` file in
+//! `calendar_ics_path` has an event in progress (e.g. a subscription synced
+//! to disk, or an export of the user's calendar). Only what matters for
+//! "is there a meeting right now" is read:
+//! - timed VEVENTs with DTSTART and DTEND or DURATION; all-day events,
+//!   cancelled events and events marked TRANSP:TRANSPARENT (free) are skipped
+//! - UTC times are converted to local time; TZID times are read as local
+//!   time (no timezone database is bundled)
+//! - RRULE with FREQ=DAILY or WEEKLY (INTERVAL, UNTIL, COUNT, BYDAY) and
+//!   EXDATE; other frequencies count only their first occurrence
+
+use std::fs;
+use std::path::Path;
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    /// Any other FREQ (only the first occurrence is used)
+    Other,
+}
+
+/// A supported subset of RRULE
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recurrence {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub until: Option<NaiveDateTime>,
+    pub count: Option<u32>,
+    /// Weekdays of a weekly rule (empty = the weekday of DTSTART)
+    pub by_day: Vec<Weekday>,
+}
+
+/// A timed calendar event, in local time
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub start: NaiveDateTime,
+    pub duration: Duration,
+    pub recurrence: Option<Recurrence>,
+    /// Starts of cancelled occurrences
+    pub exdates: Vec<NaiveDateTime>,
+}
+
+impl Recurrence {
+    /// Whether the rule (ignoring COUNT/UNTIL) has an occurrence on `day`
+    fn matches_day(&self, first: NaiveDate, day: NaiveDate) -> bool {
+        let interval = i64::from(self.interval.max(1));
+        match self.freq {
+            Frequency::Daily => (day - first).num_days() % interval == 0,
+            Frequency::Weekly => {
+                let weekday_matches = if self.by_day.is_empty() {
+                    day.weekday() == first.weekday()
+                } else {
+                    self.by_day.contains(&day.weekday())
+                };
+                let week_start = |d: NaiveDate| {
+                    d - Duration::days(i64::from(d.weekday().num_days_from_monday()))
+                };
+                let weeks = (week_start(day) - week_start(first)).num_weeks();
+                weekday_matches && weeks % interval == 0
+            }
+            Frequency::Other => day == first,
+        }
+    }
+}
+
+impl CalendarEvent {
+    /// Whether an occurrence starts on `day`
+    fn occurs_on(&self, day: NaiveDate) -> bool {
+        let first = self.start.date();
+        if day < first {
+            return false;
+        }
+        let Some(rule) = &self.recurrence else {
+            return day == first;
+        };
+        if !rule.matches_day(first, day) {
+            return false;
+        }
+        if rule
+            .until
+            .is_some_and(|until| day.and_time(self.start.time()) > until)
+        {
+            return false;
+        }
+        if let Some(count) = rule.count {
+            let index = first
+                .iter_days()
+                .take_while(|d| *d < day)
+                .filter(|d| rule.matches_day(first, *d))
+                .count();
+            if index >= count as usize {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// End of the occurrence in progress at `now`, if any
+    pub fn active_until(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        // Occurrences that started up to the event's length ago
+        let lookback = self.duration.num_days() + 1;
+        (0..=lookback)
+            .map(|days_ago| now.date() - Duration::days(days_ago))
+            .filter(|day| self.occurs_on(*day))
+            .map(|day| day.and_time(self.start.time()))
+            .filter(|start| !self.exdates.contains(start))
+            .map(|start| (start, start + self.duration))
+            .find(|(start, end)| *start <= now && now < *end)
+            .map(|(_, end)| end)
+    }
+}
+
+/// When the latest event in progress at `now` ends (None if free)
+pub fn busy_until(events: &[CalendarEvent], now: NaiveDateTime) -> Option<NaiveDateTime> {
+    events.iter().filter_map(|e| e.active_until(now)).max()
+}
+
+/// Read the calendar file and return when the current meeting ends
+pub fn calendar_busy_until(path: &Path) -> Result<Option<NaiveDateTime>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read calendar file: {e}"))?;
+    Ok(busy_until(&parse_ics(&content), Local::now().naive_local()))
+}
+
+/// Join folded lines (continuations start with a space or tab)
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Split `NAME;PARAM=X:VALUE` into (name, params, value)
+fn split_property(line: &str) -> Option<(String, &str, &str)> {
+    let (head, value) = line.split_once(':')?;
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((name.to_ascii_uppercase(), params, value))
+}
+
+/// Parse a DATE-TIME value into local time (None for all-day DATE values)
+fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        let local: DateTime<Local> = Utc.from_utc_datetime(&naive).into();
+        return Some(local.naive_local());
+    }
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()
+}
+
+/// Parse an ISO 8601 duration like `PT1H30M` or `P1D`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let rest = value.trim().strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(total)
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    // BYDAY entries may carry an ordinal (e.g. "1MO"), which is ignored
+    let code = value.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-' || c == '+');
+    match code {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_rrule(value: &str) -> Recurrence {
+    let mut rule = Recurrence {
+        freq: Frequency::Other,
+        interval: 1,
+        until: None,
+        count: None,
+        by_day: Vec::new(),
+    };
+    for part in value.split(';') {
+        let Some((key, val)) = part.split_once('=') else {
+            continue;
+        };
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                rule.freq = match val {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    _ => Frequency::Other,
+                }
+            }
+            "INTERVAL" => rule.interval = val.parse().unwrap_or(1),
+            "COUNT" => rule.count = val.parse().ok(),
+            "UNTIL" => {
+                rule.until = parse_datetime(val).or_else(|| {
+                    // A DATE-only UNTIL includes that whole day
+                    NaiveDate::parse_from_str(val, "%Y%m%d")
+                        .ok()
+                        .and_then(|d| d.and_hms_opt(23, 59, 59))
+                })
+            }
+            "BYDAY" => rule.by_day = val.split(',').filter_map(parse_weekday).collect(),
+            _ => {}
+        }
+    }
+    rule
+}
+
+/// Properties collected for one VEVENT
+#[derive(Default)]
+struct EventBuilder {
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+    duration: Option<Duration>,
+    all_day: bool,
+    free: bool,
+    cancelled: bool,
+    recurrence: Option<Recurrence>,
+    exdates: Vec<NaiveDateTime>,
+}
+
+impl EventBuilder {
+    fn add(&mut self, line: &str) {
+        let Some((name, params, value)) = split_property(line) else {
+            return;
+        };
+        match name.as_str() {
+            "DTSTART" => {
+                self.all_day = params.to_ascii_uppercase().contains("VALUE=DATE")
+                    && !params.to_ascii_uppercase().contains("VALUE=DATE-TIME");
+                self.start = parse_datetime(value);
+            }
+            "DTEND" => self.end = parse_datetime(value),
+            "DURATION" => self.duration = parse_duration(value),
+            "TRANSP" => self.free = value.trim().eq_ignore_ascii_case("TRANSPARENT"),
+            "STATUS" => self.cancelled = value.trim().eq_ignore_ascii_case("CANCELLED"),
+            "RRULE" => self.recurrence = Some(parse_rrule(value)),
+            "EXDATE" => self
+                .exdates
+                .extend(value.split(',').filter_map(parse_datetime)),
+            _ => {}
+        }
+    }
+
+    fn build(self) -> Option<CalendarEvent> {
+        if self.all_day || self.free || self.cancelled {
+            return None;
+        }
+        let start = self.start?;
+        let duration = match (self.end, self.duration) {
+            (Some(end), _) => end - start,
+            (None, Some(duration)) => duration,
+            (None, None) => return None,
+        };
+        (duration > Duration::zero()).then_some(CalendarEvent {
+            start,
+            duration,
+            recurrence: self.recurrence,
+            exdates: self.exdates,
+        })
+    }
+}
+
+/// Timed events in an iCalendar file that mark the user as busy
+pub fn parse_ics(content: &str) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    // Open components, innermost last (VALARMs nest inside VEVENTs)
+    let mut stack: Vec<String> = Vec::new();
+    let mut current: Option<EventBuilder> = None;
+
+    for line in unfold(content) {
+        let upper = line.trim().to_ascii_uppercase();
+        if let Some(component) = upper.strip_prefix("BEGIN:") {
+            if component == "VEVENT" {
+                current = Some(EventBuilder::default());
+            }
+            stack.push(component.to_string());
+        } else if let Some(component) = upper.strip_prefix("END:") {
+            stack.pop();
+            if component == "VEVENT" {
+                events.extend(current.take().and_then(EventBuilder::build));
+            }
+        } else if stack.last().map(String::as_str) == Some("VEVENT") {
+            if let Some(builder) = current.as_mut() {
+                builder.add(line.trim_end());
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn calendar(events: &[&str]) -> String {
+        let body: String = events
+            .iter()
+            .map(|e| format!("BEGIN:VEVENT\r\n{e}\r\nEND:VEVENT\r\n"))
+            .collect();
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{body}END:VCALENDAR\r\n")
+    }
+
+    #[test]
+    fn test_parse_ics_skips_free_and_all_day() {
+        let ics = calendar(&[
+            "SUMMARY:Standup\r\nDTSTART:20260112T093000\r\nDTEND:20260112T094500",
+            "SUMMARY:Focus block\r\nDTSTART:20260112T100000\r\nDURATION:PT2H\r\nTRANSP:TRANSPARENT",
+            "SUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20260112\r\nDTEND;VALUE=DATE:20260113",
+            "SUMMARY:Cancelled\r\nDTSTART:20260112T140000\r\nDTEND:20260112T150000\r\nSTATUS:CANCELLED",
+            "SUMMARY:Review\r\nDTSTART;TZID=Europe/Berlin:20260112T16\r\n 0000\r\nDURATION:PT1H30M\r\nBEGIN:VALARM\r\nTRIGGER:-PT10M\r\nEND:VALARM",
+        ]);
+        let events = parse_ics(&ics);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].start, at("2026-01-12 09:30"));
+        assert_eq!(events[0].duration, Duration::minutes(15));
+        // Folded DTSTART with a TZID parameter, read as local time
+        assert_eq!(events[1].start, at("2026-01-12 16:00"));
+        assert_eq!(events[1].duration, Duration::minutes(90));
+    }
+
+    #[test]
+    fn test_parse_utc_times() {
+        let events = parse_ics(&calendar(&[
+            "DTSTART:20260112T093000Z\r\nDTEND:20260112T100000Z",
+        ]));
+        let expected: DateTime<Local> = Utc.with_ymd_and_hms(2026, 1, 12, 9, 30, 0).unwrap().into();
+        assert_eq!(events[0].start, expected.naive_local());
+    }
+
+    #[test]
+    fn test_busy_until() {
+        let events = parse_ics(&calendar(&[
+            "DTSTART:20260112T090000\r\nDTEND:20260112T100000",
+            "DTSTART:20260112T093000\r\nDTEND:20260112T103000",
+        ]));
+        assert_eq!(busy_until(&events, at("2026-01-12 08:59")), None);
+        assert_eq!(
+            busy_until(&events, at("2026-01-12 09:45")),
+            Some(at("2026-01-12 10:30"))
+        );
+        assert_eq!(busy_until(&events, at("2026-01-12 10:30")), None);
+    }
+
+    #[test]
+    fn test_weekly_recurrence() {
+        // Mon 2026-01-12; standup on Mon/Wed, every week, not on the 14th
+        let events = parse_ics(&calendar(&[
+            "DTSTART:20260112T093000\r\nDTEND:20260112T094500\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20260131T000000Z\r\nEXDATE:20260114T093000",
+        ]));
+        let busy = |when: &str| busy_until(&events, at(when)).is_some();
+        assert!(busy("2026-01-12 09:40"));
+        assert!(!busy("2026-01-13 09:40"));
+        assert!(!busy("2026-01-14 09:40"));
+        assert!(busy("2026-01-21 09:40"));
+        assert!(busy("2026-01-26 09:40"));
+        assert!(!busy("2026-02-02 09:40"));
+    }
+
+    #[test]
+    fn test_daily_recurrence_with_count_and_interval() {
+        let events = parse_ics(&calendar(&[
+            "DTSTART:20260112T220000\r\nDURATION:PT4H\r\nRRULE:FREQ=DAILY;INTERVAL=2;COUNT=3",
+        ]));
+        let busy = |when: &str| busy_until(&events, at(when));
+        // Overnight occurrence is found from the next morning
+        assert_eq!(busy("2026-01-13 01:00"), Some(at("2026-01-13 02:00")));
+        assert_eq!(busy("2026-01-13 23:00"), None);
+        assert!(busy("2026-01-16 23:00").is_some());
+        // Fourth occurrence is past COUNT
+        assert_eq!(busy("2026-01-18 23:00"), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("1H"), None);
+    }
+}
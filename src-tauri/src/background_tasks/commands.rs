@@ -1,12 +1,20 @@
 //! Tauri commands for controlling background tasks
 
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use super::calendar::calendar_busy_until;
+use super::maintenance::{self, MaintenanceReport, MaintenanceStatus};
+use super::scheduling::{
+    current_conditions, decide, evaluate_schedule, BackgroundJob, DeferralSettings, JobDecision,
+    PowerConditions, ScheduleDecision, TaskSchedule,
+};
+use super::supervisor::{health_report, TaskHealth};
 use super::{
     BackgroundTaskManager, MAX_POLL_INTERVAL, MAX_REMOTE_POLL_INTERVAL, MIN_POLL_INTERVAL,
     MIN_REMOTE_POLL_INTERVAL,
 };
 use crate::projects::git_status::ActiveWorktreeInfo;
+use crate::runtime::PathProvider;
 
 /// Set the application focus state
 ///
@@ -118,3 +126,85 @@ pub fn trigger_immediate_remote_poll(
     state.trigger_immediate_remote_poll();
     Ok(())
 }
+
+/// Get current battery and metered-connection state
+#[tauri::command]
+pub async fn get_power_conditions() -> Result<PowerConditions, String> {
+    tauri::async_runtime::spawn_blocking(current_conditions)
+        .await
+        .map_err(|e| format!("Failed to read power conditions: {e}"))
+}
+
+/// Check whether a background job should run now or be deferred
+/// (on battery below the threshold or on a metered connection)
+#[tauri::command]
+pub async fn should_run_background_job(
+    app: AppHandle,
+    job: BackgroundJob,
+) -> Result<JobDecision, String> {
+    let prefs = crate::load_preferences(app).await?;
+    let conditions = get_power_conditions().await?;
+
+    let decision = decide(
+        job,
+        conditions,
+        &DeferralSettings {
+            battery_threshold: prefs.defer_jobs_battery_threshold,
+            defer_on_metered: prefs.defer_jobs_on_metered,
+            overrides: &prefs.background_job_overrides,
+        },
+    );
+    if !decision.allowed {
+        log::trace!("Deferring background job {job:?}: {:?}", decision.reason);
+    }
+    Ok(decision)
+}
+
+/// Check whether a queued run's schedule (time window, days, meetings)
+/// allows it to start now
+#[tauri::command]
+pub async fn check_task_schedule(
+    app: AppHandle,
+    schedule: TaskSchedule,
+) -> Result<ScheduleDecision, String> {
+    let prefs = crate::load_preferences(app).await?;
+    let calendar = prefs
+        .calendar_ics_path
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| schedule.avoid_meetings && !p.is_empty());
+    // An unreadable calendar never blocks runs
+    let busy_until = calendar.and_then(|path| {
+        calendar_busy_until(std::path::Path::new(path)).unwrap_or_else(|e| {
+            log::warn!("Ignoring calendar for scheduled runs: {e}");
+            None
+        })
+    });
+
+    let decision = evaluate_schedule(&schedule, chrono::Local::now().naive_local(), busy_until)?;
+    if !decision.allowed {
+        log::trace!("Holding scheduled run: {:?}", decision.reason);
+    }
+    Ok(decision)
+}
+
+/// Get panic counts and restart state for spawned background tasks
+#[tauri::command]
+pub fn get_background_task_health() -> Vec<TaskHealth> {
+    health_report()
+}
+
+/// Get the maintenance window, whether it's open and the last maintenance run
+#[tauri::command]
+pub async fn get_maintenance_status(app: AppHandle) -> Result<MaintenanceStatus, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    Ok(maintenance::status(&prefs, &app.app_data_dir()?))
+}
+
+/// Run storage compaction, semantic index refreshes and a due backup now,
+/// regardless of the maintenance window
+#[tauri::command]
+pub async fn run_maintenance_now(app: AppHandle) -> Result<MaintenanceReport, String> {
+    log::trace!("Running maintenance now");
+    maintenance::run_jobs(&app, true).await
+}
@@ -0,0 +1,357 @@
+//! Maintenance window for heavy background work
+//!
+//! Storage compaction, semantic index refreshes and scheduled backups are
+//! heavy on disk, CPU and (for embeddings) network. When
+//! `maintenance_window_start` and `maintenance_window_end` are set (local
+//! "HH:MM", e.g. 02:00-05:00; the window may wrap past midnight), the
+//! maintenance scheduler runs these jobs once per window, and the backup
+//! scheduler and index poller defer to it outside the window.
+//! `run_maintenance_now` runs the jobs immediately regardless of the window.
+//!
+//! Without a window, jobs keep their own schedules and compaction stays
+//! manual.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::supervisor::supervise;
+use crate::runtime::{now, PathProvider};
+use crate::semantic_search::embeddings::EmbeddingsConfig;
+use crate::AppPreferences;
+
+/// Last maintenance run (in app data)
+const STATE_FILE: &str = "maintenance.json";
+
+/// How often the scheduler checks whether the window is open
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Heavy jobs run in the maintenance window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceJob {
+    /// Move oversized run log lines into compressed blobs
+    StorageCompaction,
+    /// Re-embed changed files of every semantic index
+    SemanticIndex,
+    /// Scheduled backup (when one is due)
+    Backup,
+}
+
+/// Daily window, in minutes after local midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl MaintenanceWindow {
+    /// Whether `minute` (after local midnight) falls in the window
+    pub fn contains(&self, minute: u32) -> bool {
+        if self.start < self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            // Wraps past midnight
+            minute >= self.start || minute < self.end
+        }
+    }
+
+    /// Length of the window in seconds
+    pub fn duration_secs(&self) -> u64 {
+        u64::from((self.end + MINUTES_PER_DAY - self.start) % MINUTES_PER_DAY) * 60
+    }
+}
+
+/// Outcome of one job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobResult {
+    pub job: MaintenanceJob,
+    pub success: bool,
+    pub message: String,
+}
+
+/// One maintenance run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub started_at: u64,
+    pub finished_at: u64,
+    /// Started with `run_maintenance_now` rather than by the scheduler
+    pub manual: bool,
+    pub jobs: Vec<JobResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    pub window_start: Option<String>,
+    pub window_end: Option<String>,
+    /// Why the configured window is ignored (None if valid or unset)
+    pub window_error: Option<String>,
+    /// Heavy jobs may run now (no window, or inside it)
+    pub in_window: bool,
+    pub last_run: Option<MaintenanceReport>,
+}
+
+/// Parse "HH:MM" into minutes after midnight
+pub(crate) fn parse_time(value: &str) -> Result<u32, String> {
+    let invalid = || format!("Invalid time \"{value}\", expected HH:MM");
+    let (hours, minutes) = value.trim().split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+/// Window from start/end times; None unless both are set
+pub fn parse_window(
+    start: Option<&str>,
+    end: Option<&str>,
+) -> Result<Option<MaintenanceWindow>, String> {
+    let non_empty = |s: Option<&str>| s.map(str::trim).filter(|s| !s.is_empty());
+    let (Some(start), Some(end)) = (non_empty(start), non_empty(end)) else {
+        return Ok(None);
+    };
+    let window = MaintenanceWindow {
+        start: parse_time(start)?,
+        end: parse_time(end)?,
+    };
+    if window.start == window.end {
+        return Err("Maintenance window start and end are the same".to_string());
+    }
+    Ok(Some(window))
+}
+
+/// The configured window (an invalid one is logged and ignored)
+pub fn maintenance_window(prefs: &AppPreferences) -> Option<MaintenanceWindow> {
+    parse_window(
+        prefs.maintenance_window_start.as_deref(),
+        prefs.maintenance_window_end.as_deref(),
+    )
+    .unwrap_or_else(|e| {
+        log::warn!("Ignoring maintenance window: {e}");
+        None
+    })
+}
+
+fn local_minute() -> u32 {
+    let time = chrono::Local::now();
+    time.hour() * 60 + time.minute()
+}
+
+/// Whether heavy jobs may run now under these preferences
+pub fn in_window(prefs: &AppPreferences) -> bool {
+    maintenance_window(prefs).is_none_or(|window| window.contains(local_minute()))
+}
+
+/// Whether heavy jobs may run now (for background threads)
+pub fn heavy_work_allowed(app: &AppHandle) -> bool {
+    match tauri::async_runtime::block_on(crate::load_preferences(app.clone())) {
+        Ok(prefs) => in_window(&prefs),
+        Err(e) => {
+            log::warn!("Failed to load preferences for the maintenance window: {e}");
+            true
+        }
+    }
+}
+
+fn load_last_run(root: &Path) -> Option<MaintenanceReport> {
+    let content = fs::read_to_string(root.join(STATE_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_last_run(root: &Path, report: &MaintenanceReport) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize maintenance report: {e}"))?;
+    fs::write(root.join(STATE_FILE), content)
+        .map_err(|e| format!("Failed to save maintenance report: {e}"))
+}
+
+/// Whether the scheduler should run now: in the window and not yet run in
+/// this occurrence of it
+fn is_due(window: &MaintenanceWindow, minute: u32, last_run: Option<u64>, now: u64) -> bool {
+    window.contains(minute)
+        && last_run.is_none_or(|last| now.saturating_sub(last) >= window.duration_secs())
+}
+
+fn result(job: MaintenanceJob, outcome: Result<String, String>) -> JobResult {
+    let (success, message) = match outcome {
+        Ok(message) => (true, message),
+        Err(e) => {
+            log::warn!("Maintenance job {job:?} failed: {e}");
+            (false, e)
+        }
+    };
+    JobResult {
+        job,
+        success,
+        message,
+    }
+}
+
+/// Run every heavy job now and record the report
+pub async fn run_jobs(app: &AppHandle, manual: bool) -> Result<MaintenanceReport, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let root = app.app_data_dir()?;
+    let started_at = now();
+    let mut jobs = Vec::new();
+
+    let compaction_app = app.clone();
+    let compaction = tauri::async_runtime::spawn_blocking(move || {
+        crate::chat::blobs::compact_storage(&compaction_app)
+    })
+    .await
+    .map_err(|e| format!("Storage compaction failed: {e}"))
+    .and_then(|r| r)
+    .map(|report| {
+        format!(
+            "Compacted {} runs, reclaimed {} bytes",
+            report.runs_compacted, report.bytes_reclaimed
+        )
+    });
+    jobs.push(result(MaintenanceJob::StorageCompaction, compaction));
+
+    if let Some(config) = EmbeddingsConfig::from_preferences(&prefs) {
+        let (refreshed, errors) = crate::semantic_search::refresh_all(app, &config).await;
+        let outcome = if errors.is_empty() {
+            Ok(format!("Refreshed {refreshed} indexes"))
+        } else {
+            Err(format!(
+                "Refreshed {refreshed} indexes; failed: {}",
+                errors.join("; ")
+            ))
+        };
+        jobs.push(result(MaintenanceJob::SemanticIndex, outcome));
+    }
+
+    let backup_app = app.clone();
+    let backup_prefs = prefs.clone();
+    let backup = tauri::async_runtime::spawn_blocking(move || {
+        crate::backups::backup_if_due(&backup_app, &backup_prefs)
+    })
+    .await
+    .map_err(|e| format!("Backup failed: {e}"))
+    .and_then(|r| r)
+    .map(|info| match info {
+        Some(info) => format!("Created {}", info.path),
+        None => "No backup due".to_string(),
+    });
+    jobs.push(result(MaintenanceJob::Backup, backup));
+
+    let report = MaintenanceReport {
+        started_at,
+        finished_at: now(),
+        manual,
+        jobs,
+    };
+    save_last_run(&root, &report)?;
+    log::info!(
+        "Maintenance run finished in {}s",
+        report.finished_at - report.started_at
+    );
+    Ok(report)
+}
+
+/// Configured window, whether it's open, and the last run
+pub fn status(prefs: &AppPreferences, root: &Path) -> MaintenanceStatus {
+    let window = parse_window(
+        prefs.maintenance_window_start.as_deref(),
+        prefs.maintenance_window_end.as_deref(),
+    );
+    MaintenanceStatus {
+        window_start: prefs.maintenance_window_start.clone(),
+        window_end: prefs.maintenance_window_end.clone(),
+        window_error: window.as_ref().err().cloned(),
+        in_window: in_window(prefs),
+        last_run: load_last_run(root),
+    }
+}
+
+/// Run the heavy jobs once per maintenance window
+fn run_scheduled(app: &AppHandle) -> Result<(), String> {
+    let prefs = tauri::async_runtime::block_on(crate::load_preferences(app.clone()))?;
+    let Some(window) = maintenance_window(&prefs) else {
+        return Ok(());
+    };
+    let root = app.app_data_dir()?;
+    let last_run = load_last_run(&root).map(|r| r.started_at);
+    if !is_due(&window, local_minute(), last_run, now()) {
+        return Ok(());
+    }
+    tauri::async_runtime::block_on(run_jobs(app, false))?;
+    Ok(())
+}
+
+/// Start the maintenance scheduler thread
+pub fn start_scheduler(app: AppHandle) {
+    supervise("maintenance-scheduler", move || loop {
+        if let Err(e) = run_scheduled(&app) {
+            log::error!("Scheduled maintenance failed: {e}");
+        }
+        std::thread::sleep(SCHEDULE_CHECK_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_window() {
+        let window = parse_window(Some("02:00"), Some("05:30")).unwrap().unwrap();
+        assert_eq!(
+            window,
+            MaintenanceWindow {
+                start: 120,
+                end: 330
+            }
+        );
+        assert_eq!(window.duration_secs(), 210 * 60);
+
+        assert_eq!(parse_window(None, Some("05:00")), Ok(None));
+        assert_eq!(parse_window(Some(" "), Some("05:00")), Ok(None));
+        assert!(parse_window(Some("24:00"), Some("05:00")).is_err());
+        assert!(parse_window(Some("2am"), Some("05:00")).is_err());
+        assert!(parse_window(Some("03:00"), Some("03:00")).is_err());
+    }
+
+    #[test]
+    fn test_window_contains() {
+        let night = MaintenanceWindow {
+            start: 120,
+            end: 300,
+        };
+        assert!(night.contains(120) && night.contains(299));
+        assert!(!night.contains(300) && !night.contains(60));
+
+        // 23:00-01:00 wraps past midnight
+        let wrapping = MaintenanceWindow {
+            start: 23 * 60,
+            end: 60,
+        };
+        assert!(wrapping.contains(23 * 60 + 30) && wrapping.contains(30));
+        assert!(!wrapping.contains(120));
+        assert_eq!(wrapping.duration_secs(), 2 * 3600);
+    }
+
+    #[test]
+    fn test_is_due() {
+        let window = MaintenanceWindow {
+            start: 120,
+            end: 300,
+        };
+        let now = 1_000_000;
+        assert!(is_due(&window, 130, None, now));
+        assert!(!is_due(&window, 60, None, now));
+        // Already ran in this window
+        assert!(!is_due(&window, 200, Some(now - 3600), now));
+        // Ran yesterday
+        assert!(is_due(&window, 130, Some(now - 86_000), now));
+    }
+}
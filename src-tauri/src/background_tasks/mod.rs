@@ -20,7 +20,11 @@ use crate::http_server::EmitExt;
 use crate::projects::git_status::{get_branch_status, ActiveWorktreeInfo, GitBranchStatus};
 use crate::projects::pr_status::{get_pr_status, PrStatus};
 
+pub mod calendar;
 pub mod commands;
+pub mod maintenance;
+pub mod scheduling;
+pub mod supervisor;
 
 // ============================================================================
 // Local polling constants (git commands that run locally)
@@ -117,7 +121,7 @@ impl BackgroundTaskManager {
         let last_local_poll_times = Arc::clone(&self.last_local_poll_times);
         let last_remote_poll_times = Arc::clone(&self.last_remote_poll_times);
 
-        thread::spawn(move || {
+        supervisor::supervise("git-status-polling", move || {
             log::trace!("Background task polling loop started");
 
             loop {
@@ -190,6 +194,23 @@ impl BackgroundTaskManager {
                                 if let Err(e) = emit_git_status(&app, status) {
                                     log::error!("Failed to emit git status event: {e}");
                                 }
+
+                                // Keep the worktree's repository map and
+                                // semantic index current
+                                crate::repo_map::refresh_if_stale(
+                                    app.clone(),
+                                    std::path::PathBuf::from(&info.worktree_path),
+                                );
+                                crate::semantic_search::refresh_if_stale(
+                                    app.clone(),
+                                    std::path::PathBuf::from(&info.worktree_path),
+                                );
+
+                                // Report jean.json edits (and new problems in it)
+                                crate::projects::config::check_for_changes(
+                                    &app,
+                                    &info.worktree_path,
+                                );
                             }
                             Err(e) => {
                                 log::warn!(
@@ -0,0 +1,412 @@
+//! Battery and metered-connection aware deferral of background jobs
+//!
+//! Optional background work (update checks, session digests, low-priority
+//! queued runs) asks [`decide`] before starting. Jobs are deferred when the
+//! machine is on battery below the configured threshold or on a metered
+//! connection, unless the job has a per-job override in preferences.
+//!
+//! Queued runs can also carry a [`TaskSchedule`] ("after 18:00", "weekends
+//! only", "not during meetings"), checked with [`evaluate_schedule`].
+
+use chrono::{Datelike, NaiveDateTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+
+use super::maintenance::{parse_time, MaintenanceWindow};
+use crate::platform::{battery_status, is_metered_connection, BatteryStatus};
+use crate::BackgroundJobOverrides;
+
+/// Background jobs that can be deferred
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundJob {
+    /// App update checks
+    UpdateCheck,
+    /// Session digest / report generation
+    ReportGeneration,
+    /// Queued runs marked as background priority
+    BatchTasks,
+}
+
+/// Current power and network conditions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerConditions {
+    /// Battery status (None on machines without a battery)
+    pub battery: Option<BatteryStatus>,
+    /// Whether the connection is metered (None if unknown)
+    pub metered: Option<bool>,
+}
+
+/// Whether a background job may run now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobDecision {
+    pub job: BackgroundJob,
+    pub allowed: bool,
+    /// Why the job was deferred (None if allowed)
+    pub reason: Option<String>,
+    pub conditions: PowerConditions,
+}
+
+/// Read current battery and metered-connection state from the platform layer
+pub fn current_conditions() -> PowerConditions {
+    PowerConditions {
+        battery: battery_status(),
+        metered: is_metered_connection(),
+    }
+}
+
+/// Deferral settings from preferences
+pub struct DeferralSettings<'a> {
+    /// Defer when on battery at or below this percentage (0 = never)
+    pub battery_threshold: u8,
+    /// Defer on metered connections
+    pub defer_on_metered: bool,
+    pub overrides: &'a BackgroundJobOverrides,
+}
+
+impl BackgroundJobOverrides {
+    /// True if the job should run regardless of battery/metered state
+    fn always_runs(&self, job: BackgroundJob) -> bool {
+        match job {
+            BackgroundJob::UpdateCheck => self.update_check,
+            BackgroundJob::ReportGeneration => self.report_generation,
+            BackgroundJob::BatchTasks => self.batch_tasks,
+        }
+    }
+}
+
+/// Decide whether a job may run under the given conditions
+pub fn decide(
+    job: BackgroundJob,
+    conditions: PowerConditions,
+    settings: &DeferralSettings,
+) -> JobDecision {
+    let reason = if settings.overrides.always_runs(job) {
+        None
+    } else {
+        deferral_reason(&conditions, settings)
+    };
+
+    JobDecision {
+        job,
+        allowed: reason.is_none(),
+        reason,
+        conditions,
+    }
+}
+
+fn deferral_reason(conditions: &PowerConditions, settings: &DeferralSettings) -> Option<String> {
+    if let Some(battery) = conditions.battery.as_ref().filter(|b| b.on_battery) {
+        if settings.battery_threshold > 0 {
+            // Unknown charge on battery counts as low
+            let low = battery
+                .percent
+                .is_none_or(|p| p <= settings.battery_threshold);
+            if low {
+                return Some(match battery.percent {
+                    Some(p) => format!("On battery at {p}%"),
+                    None => "On battery".to_string(),
+                });
+            }
+        }
+    }
+
+    if settings.defer_on_metered && conditions.metered == Some(true) {
+        return Some("On a metered connection".to_string());
+    }
+
+    None
+}
+
+/// Days a scheduled run may start on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleDays {
+    #[default]
+    Any,
+    Weekdays,
+    Weekends,
+}
+
+/// When a queued run may start
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskSchedule {
+    /// Local "HH:MM" the run may start from
+    #[serde(default)]
+    pub after: Option<String>,
+    /// Local "HH:MM" the run must start before (may wrap past midnight)
+    #[serde(default)]
+    pub before: Option<String>,
+    #[serde(default)]
+    pub days: ScheduleDays,
+    /// Wait while the calendar in `calendar_ics_path` shows a meeting
+    #[serde(default)]
+    pub avoid_meetings: bool,
+}
+
+/// Whether a scheduled run may start now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleDecision {
+    pub allowed: bool,
+    /// Why the run is waiting (None if allowed)
+    pub reason: Option<String>,
+    /// Local "HH:MM" the current meeting ends (None if not in one)
+    pub busy_until: Option<String>,
+}
+
+impl TaskSchedule {
+    /// The daily window from `after`/`before` (None if neither is set)
+    fn window(&self) -> Result<Option<MaintenanceWindow>, String> {
+        let non_empty = |s: &Option<String>| {
+            s.as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(parse_time)
+                .transpose()
+        };
+        let (after, before) = (non_empty(&self.after)?, non_empty(&self.before)?);
+        if after.is_none() && before.is_none() {
+            return Ok(None);
+        }
+        // An open end runs to midnight; an open start from midnight
+        let window = MaintenanceWindow {
+            start: after.unwrap_or(0),
+            end: before.unwrap_or(0),
+        };
+        if window.start == window.end {
+            return Err("Schedule start and end times are the same".to_string());
+        }
+        Ok(Some(window))
+    }
+}
+
+fn window_reason(schedule: &TaskSchedule) -> String {
+    match (schedule.after.as_deref(), schedule.before.as_deref()) {
+        (Some(after), Some(before)) => format!("Runs between {after} and {before}"),
+        (Some(after), None) => format!("Waiting until {after}"),
+        (None, Some(before)) => format!("Runs before {before}"),
+        (None, None) => String::new(),
+    }
+}
+
+/// Decide whether a scheduled run may start at local time `now`, given
+/// when the current meeting ends (from the calendar, if one is configured)
+pub fn evaluate_schedule(
+    schedule: &TaskSchedule,
+    now: NaiveDateTime,
+    busy_until: Option<NaiveDateTime>,
+) -> Result<ScheduleDecision, String> {
+    let window = schedule.window()?;
+    let weekend = matches!(now.weekday(), Weekday::Sat | Weekday::Sun);
+    let minute = now.hour() * 60 + now.minute();
+    let busy_until = busy_until.filter(|_| schedule.avoid_meetings);
+
+    let reason = match schedule.days {
+        ScheduleDays::Weekdays if weekend => Some("Runs on weekdays only".to_string()),
+        ScheduleDays::Weekends if !weekend => Some("Runs on weekends only".to_string()),
+        _ => None,
+    }
+    .or_else(|| {
+        window
+            .filter(|window| !window.contains(minute))
+            .map(|_| window_reason(schedule))
+    })
+    .or_else(|| busy_until.map(|end| format!("In a meeting until {}", end.format("%H:%M"))));
+
+    Ok(ScheduleDecision {
+        allowed: reason.is_none(),
+        reason,
+        busy_until: busy_until.map(|end| end.format("%H:%M").to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conditions(battery: Option<(bool, Option<u8>)>, metered: Option<bool>) -> PowerConditions {
+        PowerConditions {
+            battery: battery.map(|(on_battery, percent)| BatteryStatus {
+                on_battery,
+                percent,
+            }),
+            metered,
+        }
+    }
+
+    fn settings(overrides: &BackgroundJobOverrides) -> DeferralSettings<'_> {
+        DeferralSettings {
+            battery_threshold: 20,
+            defer_on_metered: true,
+            overrides,
+        }
+    }
+
+    #[test]
+    fn test_defers_on_low_battery() {
+        let overrides = BackgroundJobOverrides::default();
+        let decision = decide(
+            BackgroundJob::UpdateCheck,
+            conditions(Some((true, Some(15))), Some(false)),
+            &settings(&overrides),
+        );
+        assert!(!decision.allowed);
+        assert_eq!(decision.reason.as_deref(), Some("On battery at 15%"));
+    }
+
+    #[test]
+    fn test_allows_on_battery_above_threshold_or_plugged_in() {
+        let overrides = BackgroundJobOverrides::default();
+        let s = settings(&overrides);
+        assert!(
+            decide(
+                BackgroundJob::UpdateCheck,
+                conditions(Some((true, Some(80))), None),
+                &s
+            )
+            .allowed
+        );
+        assert!(
+            decide(
+                BackgroundJob::UpdateCheck,
+                conditions(Some((false, Some(5))), None),
+                &s
+            )
+            .allowed
+        );
+        assert!(decide(BackgroundJob::UpdateCheck, conditions(None, None), &s).allowed);
+    }
+
+    #[test]
+    fn test_defers_on_metered() {
+        let overrides = BackgroundJobOverrides::default();
+        let decision = decide(
+            BackgroundJob::BatchTasks,
+            conditions(None, Some(true)),
+            &settings(&overrides),
+        );
+        assert!(!decision.allowed);
+        assert_eq!(decision.reason.as_deref(), Some("On a metered connection"));
+    }
+
+    #[test]
+    fn test_override_always_runs() {
+        let overrides = BackgroundJobOverrides {
+            report_generation: true,
+            ..Default::default()
+        };
+        let s = settings(&overrides);
+        let low = || conditions(Some((true, Some(5))), Some(true));
+        assert!(decide(BackgroundJob::ReportGeneration, low(), &s).allowed);
+        assert!(!decide(BackgroundJob::UpdateCheck, low(), &s).allowed);
+    }
+
+    #[test]
+    fn test_parse_platform_outputs() {
+        use crate::platform::{parse_nm_metered, parse_pmset_batt};
+
+        let pmset = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=4653155)\t85%; discharging; 4:12 remaining present: true\n";
+        assert_eq!(
+            parse_pmset_batt(pmset),
+            Some(BatteryStatus {
+                on_battery: true,
+                percent: Some(85)
+            })
+        );
+        assert_eq!(parse_pmset_batt("Now drawing from 'AC Power'\n"), None);
+
+        assert_eq!(parse_nm_metered("u 1\n"), Some(true));
+        assert_eq!(parse_nm_metered("u 4"), Some(false));
+        assert_eq!(parse_nm_metered("u 0"), None);
+    }
+
+    fn schedule(after: Option<&str>, before: Option<&str>, days: ScheduleDays) -> TaskSchedule {
+        TaskSchedule {
+            after: after.map(str::to_string),
+            before: before.map(str::to_string),
+            days,
+            avoid_meetings: false,
+        }
+    }
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_schedule_time_window() {
+        let evening = schedule(Some("18:00"), None, ScheduleDays::Any);
+        let decision = evaluate_schedule(&evening, at("2026-01-12 17:59"), None).unwrap();
+        assert!(!decision.allowed);
+        assert_eq!(decision.reason.as_deref(), Some("Waiting until 18:00"));
+        assert!(
+            evaluate_schedule(&evening, at("2026-01-12 23:30"), None)
+                .unwrap()
+                .allowed
+        );
+
+        // Wraps past midnight
+        let overnight = schedule(Some("22:00"), Some("06:00"), ScheduleDays::Any);
+        assert!(
+            evaluate_schedule(&overnight, at("2026-01-13 02:00"), None)
+                .unwrap()
+                .allowed
+        );
+        assert!(
+            !evaluate_schedule(&overnight, at("2026-01-13 12:00"), None)
+                .unwrap()
+                .allowed
+        );
+
+        let invalid = schedule(Some("18:00"), Some("18:00"), ScheduleDays::Any);
+        assert!(evaluate_schedule(&invalid, at("2026-01-12 18:00"), None).is_err());
+        assert!(evaluate_schedule(
+            &schedule(Some("25:00"), None, ScheduleDays::Any),
+            at("2026-01-12 18:00"),
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_schedule_days() {
+        // 2026-01-17 is a Saturday
+        let weekends = schedule(None, None, ScheduleDays::Weekends);
+        assert!(
+            evaluate_schedule(&weekends, at("2026-01-17 10:00"), None)
+                .unwrap()
+                .allowed
+        );
+        let decision = evaluate_schedule(&weekends, at("2026-01-16 10:00"), None).unwrap();
+        assert_eq!(decision.reason.as_deref(), Some("Runs on weekends only"));
+
+        let weekdays = schedule(None, None, ScheduleDays::Weekdays);
+        assert!(
+            !evaluate_schedule(&weekdays, at("2026-01-18 10:00"), None)
+                .unwrap()
+                .allowed
+        );
+    }
+
+    #[test]
+    fn test_schedule_avoids_meetings() {
+        let mut s = schedule(None, None, ScheduleDays::Any);
+        let meeting_end = Some(at("2026-01-12 10:30"));
+        // Meetings only matter when the run asks to avoid them
+        assert!(
+            evaluate_schedule(&s, at("2026-01-12 10:00"), meeting_end)
+                .unwrap()
+                .allowed
+        );
+
+        s.avoid_meetings = true;
+        let decision = evaluate_schedule(&s, at("2026-01-12 10:00"), meeting_end).unwrap();
+        assert!(!decision.allowed);
+        assert_eq!(decision.reason.as_deref(), Some("In a meeting until 10:30"));
+        assert_eq!(decision.busy_until.as_deref(), Some("10:30"));
+        assert!(
+            evaluate_schedule(&s, at("2026-01-12 10:00"), None)
+                .unwrap()
+                .allowed
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Panic isolation for spawned tasks and threads
+//!
+//! Spawn background work through these wrappers instead of
+//! `tauri::async_runtime::spawn` / `std::thread::spawn`. A panic is caught
+//! at the task boundary, logged with the task's name and counted in the
+//! health report (`get_background_task_health`). Long-running loops started
+//! with [`supervise`] are restarted after a panic, with exponential backoff.
+//!
+//! The crash report for the panic itself is still written by the panic hook
+//! in `crash_reports`; this module only keeps the rest of the app running.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use futures_util::FutureExt;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::now;
+
+/// First restart delay for a supervised task
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest restart delay
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+/// A run this long counts as healthy, resetting the backoff
+const HEALTHY_RUN: Duration = Duration::from_secs(10 * 60);
+
+/// How a task was started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    /// Async task on the Tauri runtime
+    Task,
+    /// One-shot OS thread
+    Thread,
+    /// Long-running thread restarted after panics
+    Supervised,
+}
+
+/// Health of all tasks started under one name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskHealth {
+    pub name: String,
+    pub kind: TaskKind,
+    /// Instances currently running
+    pub running: u32,
+    /// Instances started since launch (restarts included)
+    pub started: u32,
+    pub panics: u32,
+    /// Restarts after panics (supervised tasks only)
+    pub restarts: u32,
+    pub last_panic: Option<String>,
+    /// Unix timestamp of the last panic
+    pub last_panic_at: Option<u64>,
+    /// When a supervised task waiting to restart will run again (Unix timestamp)
+    pub next_restart_at: Option<u64>,
+}
+
+static HEALTH: Lazy<Mutex<BTreeMap<String, TaskHealth>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+fn update(name: &str, kind: TaskKind, f: impl FnOnce(&mut TaskHealth)) {
+    if let Ok(mut health) = HEALTH.lock() {
+        let entry = health
+            .entry(name.to_string())
+            .or_insert_with(|| TaskHealth {
+                name: name.to_string(),
+                kind,
+                running: 0,
+                started: 0,
+                panics: 0,
+                restarts: 0,
+                last_panic: None,
+                last_panic_at: None,
+                next_restart_at: None,
+            });
+        f(entry);
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+fn started(name: &str, kind: TaskKind) {
+    update(name, kind, |h| {
+        h.running += 1;
+        h.started += 1;
+        h.next_restart_at = None;
+    });
+}
+
+/// Record the end of one run; returns the panic message if it panicked
+fn finished(name: &str, kind: TaskKind, result: Result<(), Box<dyn Any + Send>>) -> Option<String> {
+    let message = result.err().map(|payload| panic_message(&*payload));
+    if let Some(message) = &message {
+        log::error!("Background task '{name}' panicked: {message}");
+    }
+    update(name, kind, |h| {
+        h.running = h.running.saturating_sub(1);
+        if let Some(message) = &message {
+            h.panics += 1;
+            h.last_panic = Some(message.clone());
+            h.last_panic_at = Some(now());
+        }
+    });
+    message
+}
+
+/// Snapshot of every task started since launch, by name
+pub fn health_report() -> Vec<TaskHealth> {
+    HEALTH
+        .lock()
+        .map(|h| h.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Spawn an async task on the Tauri runtime, isolating panics
+pub fn spawn_task<F>(name: &'static str, future: F) -> tauri::async_runtime::JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        started(name, TaskKind::Task);
+        let result = AssertUnwindSafe(future).catch_unwind().await;
+        finished(name, TaskKind::Task, result);
+    })
+}
+
+/// Spawn a one-shot thread, isolating panics
+pub fn spawn_thread<F>(name: &'static str, f: F) -> std::thread::JoinHandle<()>
+where
+    F: FnOnce() + Send + 'static,
+{
+    std::thread::spawn(move || {
+        started(name, TaskKind::Thread);
+        let result = catch_unwind(AssertUnwindSafe(f));
+        finished(name, TaskKind::Thread, result);
+    })
+}
+
+/// Delay before the next restart
+///
+/// Doubles after each panic, up to [`MAX_BACKOFF`]; a run that stayed up for
+/// [`HEALTHY_RUN`] starts over from `initial`.
+pub fn next_backoff(previous: Duration, initial: Duration, ran_for: Duration) -> Duration {
+    if ran_for >= HEALTHY_RUN {
+        initial
+    } else {
+        (previous * 2).min(MAX_BACKOFF)
+    }
+}
+
+/// Run `f` on a thread, restarting it with backoff whenever it panics
+///
+/// The thread exits when `f` returns normally.
+pub fn supervise<F>(name: &'static str, f: F) -> std::thread::JoinHandle<()>
+where
+    F: Fn() + Send + 'static,
+{
+    supervise_with_backoff(name, INITIAL_BACKOFF, f)
+}
+
+fn supervise_with_backoff<F>(
+    name: &'static str,
+    initial: Duration,
+    f: F,
+) -> std::thread::JoinHandle<()>
+where
+    F: Fn() + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut backoff = initial / 2;
+        loop {
+            started(name, TaskKind::Supervised);
+            let run_started = Instant::now();
+            let result = catch_unwind(AssertUnwindSafe(&f));
+            if finished(name, TaskKind::Supervised, result).is_none() {
+                return;
+            }
+
+            backoff = next_backoff(backoff, initial, run_started.elapsed()).max(initial);
+            log::warn!("Restarting background task '{name}' in {backoff:?}");
+            update(name, TaskKind::Supervised, |h| {
+                h.restarts += 1;
+                h.next_restart_at = Some(now() + backoff.as_secs());
+            });
+            std::thread::sleep(backoff);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    fn health(name: &str) -> TaskHealth {
+        health_report()
+            .into_iter()
+            .find(|h| h.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_spawn_thread_records_panic() {
+        spawn_thread("test-thread-panic", || panic!("boom"))
+            .join()
+            .unwrap();
+
+        let h = health("test-thread-panic");
+        assert_eq!((h.running, h.started, h.panics), (0, 1, 1));
+        assert_eq!(h.last_panic.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn test_supervise_restarts_until_clean_exit() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&runs);
+        supervise_with_backoff("test-supervised", Duration::from_millis(5), move || {
+            if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                panic!("run {}", counter.load(Ordering::SeqCst));
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        let h = health("test-supervised");
+        assert_eq!(h.kind, TaskKind::Supervised);
+        assert_eq!((h.running, h.started, h.panics, h.restarts), (0, 3, 2, 2));
+        assert_eq!(h.last_panic.as_deref(), Some("run 2"));
+    }
+
+    #[test]
+    fn test_next_backoff() {
+        let initial = Duration::from_secs(1);
+        let short = Duration::from_secs(1);
+        assert_eq!(
+            next_backoff(Duration::from_secs(4), initial, short),
+            Duration::from_secs(8)
+        );
+        assert_eq!(next_backoff(MAX_BACKOFF, initial, short), MAX_BACKOFF);
+        assert_eq!(
+            next_backoff(Duration::from_secs(64), initial, HEALTHY_RUN),
+            initial
+        );
+    }
+}
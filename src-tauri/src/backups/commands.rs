@@ -0,0 +1,71 @@
+//! Tauri commands for backup and restore
+
+use std::path::Path;
+
+use tauri::AppHandle;
+
+use super::{backup_folder, create_backup, restore_into, rotate_backups, scan_backups, BackupInfo};
+use crate::http_server::EmitExt;
+use crate::runtime::PathProvider;
+
+fn app_version(app: &AppHandle) -> String {
+    app.package_info().version.to_string()
+}
+
+/// Back up the session store and settings now (outside the schedule)
+#[tauri::command]
+pub async fn create_backup_now(app: AppHandle) -> Result<BackupInfo, String> {
+    log::trace!("Creating backup");
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let root = app.app_data_dir()?;
+    let folder = backup_folder(&root, prefs.backup_folder.as_deref());
+
+    let info = create_backup(&root, &folder, &app_version(&app))?;
+    rotate_backups(&folder, prefs.backup_keep_count as usize);
+    Ok(info)
+}
+
+/// List backups in the configured backup folder, newest first
+#[tauri::command]
+pub async fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let root = app.app_data_dir()?;
+    Ok(scan_backups(&backup_folder(
+        &root,
+        prefs.backup_folder.as_deref(),
+    )))
+}
+
+/// Replace the session store and settings with a backup
+///
+/// Refused while Claude sessions are running, since their run logs would be
+/// swapped out from under them. Scheduled backups are held off until the
+/// restore finishes. The current data is backed up first, so a restore can
+/// itself be undone. Emits `backup:restored` so the frontend can reload.
+#[tauri::command]
+pub async fn restore_backup(app: AppHandle, path: String) -> Result<BackupInfo, String> {
+    log::trace!("Restoring backup from {path}");
+    let backup = Path::new(&path);
+    super::read_manifest(backup)?;
+
+    let running = crate::chat::registry::get_running_sessions();
+    if !running.is_empty() {
+        return Err(format!(
+            "Stop the {} running session(s) before restoring a backup",
+            running.len()
+        ));
+    }
+
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let root = app.app_data_dir()?;
+    let folder = backup_folder(&root, prefs.backup_folder.as_deref());
+    create_backup(&root, &folder, &app_version(&app))
+        .map_err(|e| format!("Failed to back up current data before restoring: {e}"))?;
+
+    let manifest = restore_into(&root, backup)?;
+    let info = BackupInfo { path, manifest };
+    if let Err(e) = app.emit_all("backup:restored", &info) {
+        log::error!("Failed to emit backup:restored event: {e}");
+    }
+    Ok(info)
+}
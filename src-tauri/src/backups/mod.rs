@@ -0,0 +1,389 @@
+//! Backup and restore of the session store and settings
+//!
+//! A backup is a directory named `jean-backup-{timestamp}` holding copies of
+//! the store entries (see `migrations::STORE_ENTRIES`), the settings files
+//! and a `manifest.json` recording the store's schema version. Backups are
+//! written to a `.partial` directory and renamed when complete, so a backup
+//! folder never contains half-written backups.
+//!
+//! Scheduled backups run on a background thread according to the
+//! `backup_interval_hours` preference (or in the maintenance window, when one
+//! is set); older backups beyond `backup_keep_count` are removed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::background_tasks::maintenance::maintenance_window;
+use crate::background_tasks::supervisor::supervise;
+use crate::migrations::{self, copy_all, BACKUPS_DIR, STORE_ENTRIES};
+use crate::runtime::{now, PathProvider};
+use crate::AppPreferences;
+
+pub mod commands;
+
+/// Value of `manifest.format` for Jean backups
+const BACKUP_FORMAT: &str = "jean-backup";
+
+/// Directory name prefix for backups (used to find them for rotation)
+const BACKUP_PREFIX: &str = "jean-backup-";
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Settings files (in app data) included alongside the store
+const SETTINGS_FILES: &[&str] = &["preferences.json", "ui-state.json"];
+
+/// How often the scheduler checks whether a backup is due
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Held while a backup or restore runs, so scheduled backups never copy a
+/// half-restored store and two restores can't interleave
+static BACKUP_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Recorded in every backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: String,
+    /// Store schema version at backup time
+    pub storage_version: u32,
+    /// Unix seconds
+    pub created_at: u64,
+    pub app_version: String,
+}
+
+/// A backup on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub manifest: BackupManifest,
+}
+
+fn all_entries() -> impl Iterator<Item = &'static str> {
+    STORE_ENTRIES.iter().chain(SETTINGS_FILES).copied()
+}
+
+/// Backup folder from preferences, or `backups/` in app data
+pub fn backup_folder(root: &Path, configured: Option<&str>) -> PathBuf {
+    configured
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root.join(BACKUPS_DIR))
+}
+
+/// Read and validate a backup's manifest
+///
+/// Backups from a newer schema than this app supports are rejected; older
+/// ones are migrated after restore.
+pub fn read_manifest(backup: &Path) -> Result<BackupManifest, String> {
+    let content = fs::read_to_string(backup.join(MANIFEST_FILE))
+        .map_err(|e| format!("Not a Jean backup ({backup:?}): {e}"))?;
+    let manifest: BackupManifest =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid backup manifest: {e}"))?;
+    if manifest.format != BACKUP_FORMAT {
+        return Err(format!("Unknown backup format: {}", manifest.format));
+    }
+    let latest = migrations::latest_version();
+    if manifest.storage_version > latest {
+        return Err(format!(
+            "Backup uses storage version {}, newer than this app supports ({latest})",
+            manifest.storage_version
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Copy the store and settings at `root` into a new backup in `folder`
+pub fn create_backup(root: &Path, folder: &Path, app_version: &str) -> Result<BackupInfo, String> {
+    let _guard = BACKUP_LOCK.lock().map_err(|e| e.to_string())?;
+
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT.to_string(),
+        storage_version: migrations::read_version(root)?,
+        created_at: now(),
+        app_version: app_version.to_string(),
+    };
+
+    let mut target = folder.join(format!("{BACKUP_PREFIX}{}", manifest.created_at));
+    let mut suffix = 1;
+    while target.exists() {
+        target = folder.join(format!("{BACKUP_PREFIX}{}-{suffix}", manifest.created_at));
+        suffix += 1;
+    }
+    let partial = target.with_extension("partial");
+    let _ = fs::remove_dir_all(&partial);
+    fs::create_dir_all(&partial).map_err(|e| format!("Failed to create backup directory: {e}"))?;
+
+    let result = (|| {
+        for entry in all_entries() {
+            let source = root.join(entry);
+            if source.exists() {
+                copy_all(&source, &partial.join(entry))?;
+            }
+        }
+        let content = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize backup manifest: {e}"))?;
+        fs::write(partial.join(MANIFEST_FILE), content)
+            .map_err(|e| format!("Failed to write backup manifest: {e}"))?;
+        fs::rename(&partial, &target).map_err(|e| format!("Failed to finish backup: {e}"))
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&partial);
+        return Err(e);
+    }
+
+    log::info!("Created backup {target:?}");
+    Ok(BackupInfo {
+        path: target.to_string_lossy().into_owned(),
+        manifest,
+    })
+}
+
+/// Backups in `folder`, newest first (unreadable ones are skipped)
+pub fn scan_backups(folder: &Path) -> Vec<BackupInfo> {
+    let mut backups: Vec<BackupInfo> = fs::read_dir(folder)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            name.starts_with(BACKUP_PREFIX) && !name.ends_with(".partial")
+        })
+        .filter_map(|e| {
+            let path = e.path();
+            let manifest = read_manifest(&path).ok()?;
+            Some(BackupInfo {
+                path: path.to_string_lossy().into_owned(),
+                manifest,
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| {
+        b.manifest
+            .created_at
+            .cmp(&a.manifest.created_at)
+            .then_with(|| b.path.cmp(&a.path))
+    });
+    backups
+}
+
+/// Remove all but the newest `keep` backups in `folder`
+pub fn rotate_backups(folder: &Path, keep: usize) -> Vec<String> {
+    let mut removed = Vec::new();
+    for backup in scan_backups(folder).into_iter().skip(keep.max(1)) {
+        match fs::remove_dir_all(&backup.path) {
+            Ok(()) => removed.push(backup.path),
+            Err(e) => log::warn!("Failed to remove old backup {}: {e}", backup.path),
+        }
+    }
+    removed
+}
+
+/// Replace the store and settings at `root` with the contents of `backup`
+///
+/// The backup is staged next to the live data first, then each entry is
+/// swapped in with a rename. If any swap fails, entries already swapped are
+/// put back. Older backups are migrated to the current schema afterwards.
+pub fn restore_into(root: &Path, backup: &Path) -> Result<BackupManifest, String> {
+    let manifest = read_manifest(backup)?;
+    let _guard = BACKUP_LOCK.lock().map_err(|e| e.to_string())?;
+
+    let staging = root.join(".restore-staging");
+    let previous = root.join(".restore-previous");
+    for dir in [&staging, &previous] {
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to prepare restore: {e}"))?;
+    }
+
+    let staged = all_entries().try_for_each(|entry| {
+        let source = backup.join(entry);
+        if source.exists() {
+            copy_all(&source, &staging.join(entry))
+        } else {
+            Ok(())
+        }
+    });
+    if let Err(e) = staged {
+        let _ = fs::remove_dir_all(&staging);
+        let _ = fs::remove_dir_all(&previous);
+        return Err(format!("Failed to read backup: {e}"));
+    }
+
+    let mut moved_aside = Vec::new();
+    let mut swapped_in = Vec::new();
+    let swap = all_entries().try_for_each(|entry| {
+        let live = root.join(entry);
+        if live.exists() {
+            fs::rename(&live, previous.join(entry)).map_err(|e| e.to_string())?;
+            moved_aside.push(entry);
+        }
+        let staged = staging.join(entry);
+        if staged.exists() {
+            fs::rename(&staged, &live).map_err(|e| e.to_string())?;
+            swapped_in.push(entry);
+        }
+        Ok::<_, String>(())
+    });
+
+    if let Err(e) = swap {
+        for entry in swapped_in {
+            let live = root.join(entry);
+            let _ = fs::remove_dir_all(&live).or_else(|_| fs::remove_file(&live));
+        }
+        for entry in moved_aside {
+            if let Err(e) = fs::rename(previous.join(entry), root.join(entry)) {
+                log::error!("Failed to put back {entry} after failed restore: {e}");
+            }
+        }
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!("Failed to restore backup: {e}"));
+    }
+
+    let _ = fs::remove_dir_all(&staging);
+    let _ = fs::remove_dir_all(&previous);
+
+    // Bring an older backup up to the current schema
+    migrations::run_migrations(root)?;
+    log::info!("Restored backup {backup:?}");
+    Ok(manifest)
+}
+
+/// Create a backup if automatic backups are on and the newest one is older
+/// than the interval; None if none was due
+pub fn backup_if_due(
+    app: &AppHandle,
+    prefs: &AppPreferences,
+) -> Result<Option<BackupInfo>, String> {
+    if prefs.backup_interval_hours == 0 {
+        return Ok(None);
+    }
+
+    let root = app.app_data_dir()?;
+    let folder = backup_folder(&root, prefs.backup_folder.as_deref());
+    let due = scan_backups(&folder).first().is_none_or(|latest| {
+        now().saturating_sub(latest.manifest.created_at)
+            >= u64::from(prefs.backup_interval_hours) * 3600
+    });
+    if !due {
+        return Ok(None);
+    }
+
+    let info = create_backup(&root, &folder, &app.package_info().version.to_string())?;
+    rotate_backups(&folder, prefs.backup_keep_count as usize);
+    Ok(Some(info))
+}
+
+/// Create a scheduled backup if one is due
+///
+/// With a maintenance window configured, backups are left to the
+/// maintenance scheduler instead.
+fn run_scheduled_backup(app: &AppHandle) -> Result<(), String> {
+    let prefs = tauri::async_runtime::block_on(crate::load_preferences(app.clone()))?;
+    if maintenance_window(&prefs).is_some() {
+        return Ok(());
+    }
+    backup_if_due(app, &prefs)?;
+    Ok(())
+}
+
+/// Start the scheduled backup thread
+pub fn start_scheduler(app: AppHandle) {
+    supervise("backup-scheduler", move || loop {
+        if let Err(e) = run_scheduled_backup(&app) {
+            log::error!("Scheduled backup failed: {e}");
+        }
+        std::thread::sleep(SCHEDULE_CHECK_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn read(root: &Path, rel: &str) -> Option<String> {
+        fs::read_to_string(root.join(rel)).ok()
+    }
+
+    #[test]
+    fn test_backup_and_restore_round_trip() {
+        let data = tempfile::tempdir().unwrap();
+        let root = data.path();
+        let folder = root.join(BACKUPS_DIR);
+        write(root, "projects.json", "before");
+        write(root, "preferences.json", "{}");
+        write(root, "sessions/index/w1.json", "index");
+
+        let backup = create_backup(root, &folder, "1.0.0").unwrap();
+        assert_eq!(backup.manifest.storage_version, 0);
+
+        // Change the live data after the backup
+        write(root, "projects.json", "after");
+        write(root, "git-context/new.json", "added later");
+        fs::remove_file(root.join("sessions/index/w1.json")).unwrap();
+
+        restore_into(root, Path::new(&backup.path)).unwrap();
+        assert_eq!(read(root, "projects.json").as_deref(), Some("before"));
+        assert_eq!(
+            read(root, "sessions/index/w1.json").as_deref(),
+            Some("index")
+        );
+        assert!(!root.join("git-context").exists());
+        assert!(!root.join(".restore-staging").exists());
+        assert!(!root.join(".restore-previous").exists());
+        // Restored store was migrated to the current schema
+        assert_eq!(
+            migrations::read_version(root).unwrap(),
+            migrations::latest_version()
+        );
+        // The backup itself is untouched
+        assert!(Path::new(&backup.path).join("projects.json").exists());
+    }
+
+    #[test]
+    fn test_rotation_keeps_newest() {
+        let data = tempfile::tempdir().unwrap();
+        let folder = data.path().join("out");
+        let paths: Vec<String> = (0..4)
+            .map(|_| create_backup(data.path(), &folder, "1.0.0").unwrap().path)
+            .collect();
+
+        let removed = rotate_backups(&folder, 2);
+        assert_eq!(removed.len(), 2);
+        let kept: Vec<String> = scan_backups(&folder).into_iter().map(|b| b.path).collect();
+        assert_eq!(kept, [paths[3].clone(), paths[2].clone()]);
+    }
+
+    #[test]
+    fn test_rejects_newer_or_foreign_backups() {
+        let data = tempfile::tempdir().unwrap();
+        let backup = data.path().join("b");
+        write(
+            &backup,
+            MANIFEST_FILE,
+            &format!(
+                r#"{{"format":"jean-backup","storage_version":{},"created_at":1,"app_version":"9"}}"#,
+                migrations::latest_version() + 1
+            ),
+        );
+        assert!(restore_into(data.path(), &backup).is_err());
+
+        write(
+            &backup,
+            MANIFEST_FILE,
+            r#"{"format":"other","storage_version":0,"created_at":1,"app_version":"9"}"#,
+        );
+        assert!(read_manifest(&backup).is_err());
+        assert!(read_manifest(data.path()).is_err());
+    }
+}
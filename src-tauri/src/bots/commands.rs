@@ -0,0 +1,40 @@
+use tauri::AppHandle;
+
+use super::{BotConnection, BotConnectionDraft};
+
+/// All bot connections, oldest first (tokens are never returned)
+#[tauri::command]
+pub async fn list_bot_connections(app: AppHandle) -> Vec<BotConnection> {
+    super::load_connections(&app)
+}
+
+/// Save a new bot connection
+#[tauri::command]
+pub async fn create_bot_connection(
+    app: AppHandle,
+    connection: BotConnectionDraft,
+) -> Result<BotConnection, String> {
+    super::create_connection(&app, connection)
+}
+
+/// Replace a bot connection's definition (a missing token keeps the stored one)
+#[tauri::command]
+pub async fn update_bot_connection(
+    app: AppHandle,
+    connection_id: String,
+    connection: BotConnectionDraft,
+) -> Result<BotConnection, String> {
+    super::update_connection(&app, &connection_id, connection)
+}
+
+/// Delete a bot connection and its token
+#[tauri::command]
+pub async fn delete_bot_connection(app: AppHandle, connection_id: String) -> Result<(), String> {
+    super::delete_connection(&app, &connection_id)
+}
+
+/// Post a test message to each of a connection's channels
+#[tauri::command]
+pub async fn test_bot_connection(app: AppHandle, connection_id: String) -> Result<(), String> {
+    super::send_test_message(&app, &connection_id).await
+}
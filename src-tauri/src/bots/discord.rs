@@ -0,0 +1,158 @@
+//! Discord REST API calls for bot connections
+//!
+//! Needs a bot token with Send Messages and Read Message History in each
+//! channel. Commands are read from message text, so either enable the
+//! Message Content intent or mention the bot (`@Jean status`).
+
+use serde::Deserialize;
+use serde_json::json;
+
+use super::Incoming;
+
+pub const API: &str = "https://discord.com/api/v10";
+
+/// Discord's epoch (2015-01-01) in Unix milliseconds
+const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+
+/// Longest message Discord accepts
+const MAX_CONTENT_CHARS: usize = 2000;
+
+#[derive(Debug, Deserialize)]
+struct Author {
+    id: String,
+    #[serde(default)]
+    bot: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    id: String,
+    #[serde(default)]
+    content: String,
+    author: Author,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    message: String,
+}
+
+/// Cursor (a snowflake) for messages posted after `unix_secs`
+pub fn cursor_at(unix_secs: u64) -> String {
+    ((unix_secs * 1000).saturating_sub(DISCORD_EPOCH_MS) << 22).to_string()
+}
+
+async fn send(
+    request: reqwest::RequestBuilder,
+    token: &str,
+    what: &str,
+) -> Result<reqwest::Response, String> {
+    let response = request
+        .header(reqwest::header::AUTHORIZATION, format!("Bot {token}"))
+        .send()
+        .await
+        .map_err(|e| format!("Discord {what} failed: {e}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        let message = response
+            .json::<ErrorBody>()
+            .await
+            .map(|b| b.message)
+            .unwrap_or_default();
+        return Err(format!("Discord {what} failed: HTTP {status} {message}")
+            .trim_end()
+            .to_string());
+    }
+    Ok(response)
+}
+
+/// Post `text` to a channel (cut to Discord's length limit)
+pub async fn post(
+    client: &reqwest::Client,
+    base: &str,
+    token: &str,
+    channel: &str,
+    text: &str,
+) -> Result<(), String> {
+    let content: String = text.chars().take(MAX_CONTENT_CHARS).collect();
+    let request = client
+        .post(format!("{base}/channels/{channel}/messages"))
+        .json(&json!({ "content": content }));
+    send(request, token, "message").await.map(|_| ())
+}
+
+/// Messages posted to a channel after `cursor`, oldest first
+pub async fn fetch(
+    client: &reqwest::Client,
+    base: &str,
+    token: &str,
+    channel: &str,
+    cursor: &str,
+) -> Result<Vec<Incoming>, String> {
+    let request = client
+        .get(format!("{base}/channels/{channel}/messages"))
+        .query(&[("after", cursor), ("limit", "100")]);
+    let messages: Vec<Message> = send(request, token, "message history")
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Discord message history failed: {e}"))?;
+    // Newest first
+    let mut incoming: Vec<Incoming> = messages
+        .into_iter()
+        .map(|m| Incoming {
+            id: m.id,
+            user: m.author.id,
+            text: m.content,
+            from_bot: m.author.bot,
+        })
+        .collect();
+    incoming.sort_by_key(|m| m.id.parse::<u64>().unwrap_or(0));
+    Ok(incoming)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture_server::{FixtureResponse, FixtureServer};
+
+    #[test]
+    fn test_cursor_at() {
+        // 2024-01-01T00:00:00Z
+        assert_eq!(cursor_at(1_704_067_200), "1191168914227200000");
+    }
+
+    #[test]
+    fn test_fetch_and_post() {
+        let history = json!([
+            { "id": "30", "content": "Started: Fix login", "author": { "id": "9", "bot": true } },
+            { "id": "20", "content": "<@9> stop fix", "author": { "id": "5" } },
+        ]);
+        let server = FixtureServer::start(vec![(
+            "/channels/77/messages".to_string(),
+            FixtureResponse::json(history.to_string()),
+        )]);
+        let base = server.url("");
+        let client = reqwest::Client::new();
+        let (messages, posted, missing) = tauri::async_runtime::block_on(async {
+            (
+                fetch(&client, &base, "t", "77", "10").await,
+                post(&client, &base, "t", "77", "hi").await,
+                post(&client, &base, "t", "78", "hi").await,
+            )
+        });
+
+        let messages = messages.unwrap();
+        assert_eq!(messages[0].id, "20");
+        assert_eq!(messages[0].text, "<@9> stop fix");
+        assert!(!messages[0].from_bot);
+        assert!(messages[1].from_bot);
+        assert!(posted.is_ok());
+        assert_eq!(
+            missing.unwrap_err(),
+            "Discord message failed: HTTP 404 Not Found"
+        );
+        assert!(server.requests()[0].contains("after=10"));
+    }
+}
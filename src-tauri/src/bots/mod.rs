@@ -0,0 +1,1185 @@
+//! Slack and Discord bots for following and controlling sessions from chat
+//!
+//! A bot connection pairs a bot token with channels. Channels with `notify`
+//! get a message when a session starts, finishes, fails, is cancelled or
+//! waits for a tool approval. Channels with allowed commands are polled for
+//! messages addressed to Jean (`jean status`, `!jean stop <session>`, or a
+//! mention of the bot), which run the matching backend action:
+//!
+//! - `status`: running sessions and pending approvals
+//! - `stop <session>`: cancel a running session
+//! - `approve <session>`: allow the tools a session was denied and let it
+//!   carry on, the way the approval prompt in the UI does
+//!
+//! Each channel lists the commands it accepts and, optionally, the user ids
+//! allowed to send them. Channels are polled over the REST APIs, starting
+//! from when Jean launched, so older messages are never run.
+//!
+//! Connections live in `bots.json` in app data; tokens are kept apart in
+//! `bot-tokens.json` (readable only by the user), like the SMTP password.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::platform::write_secret_file;
+use crate::runtime::{now, PathProvider};
+
+pub mod commands;
+mod discord;
+mod slack;
+
+const CONNECTIONS_FILE: &str = "bots.json";
+const TOKENS_FILE: &str = "bot-tokens.json";
+
+/// How often command channels are checked for new messages
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Timeout for bot API requests
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Characters of a session id shown in messages (enough to tell them apart)
+const SHORT_ID_LEN: usize = 8;
+
+/// Enabled connections, loaded on first use and reset whenever they're saved
+static CONNECTIONS_CACHE: Lazy<Mutex<Option<Vec<BotConnection>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Last message seen in each polled channel, by (connection id, channel id)
+static CURSORS: Lazy<Mutex<HashMap<(String, String), String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Tool patterns each session is waiting to have approved
+static PENDING: Lazy<Mutex<HashMap<String, Vec<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotPlatform {
+    Slack,
+    Discord,
+}
+
+impl BotPlatform {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Slack => "Slack",
+            Self::Discord => "Discord",
+        }
+    }
+
+    fn api(self) -> &'static str {
+        match self {
+            Self::Slack => slack::API,
+            Self::Discord => discord::API,
+        }
+    }
+
+    fn cursor_at(self, unix_secs: u64) -> String {
+        match self {
+            Self::Slack => slack::cursor_at(unix_secs),
+            Self::Discord => discord::cursor_at(unix_secs),
+        }
+    }
+}
+
+/// Commands a channel can be allowed to send
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotCommand {
+    Status,
+    Stop,
+    Approve,
+}
+
+impl BotCommand {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Status => "status",
+            Self::Stop => "stop",
+            Self::Approve => "approve",
+        }
+    }
+
+    /// Commands that act on sessions: `approve` grants tools and resumes a
+    /// session, so these need a list of allowed users
+    fn needs_allowed_users(self) -> bool {
+        matches!(self, Self::Stop | Self::Approve)
+    }
+}
+
+/// A channel the bot posts to and/or takes commands from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BotChannel {
+    /// Slack channel id (`C…`) or Discord channel id
+    pub channel_id: String,
+    /// Post session start/finish messages here
+    #[serde(default)]
+    pub notify: bool,
+    /// Commands accepted here (none: the channel isn't polled)
+    #[serde(default)]
+    pub allowed_commands: Vec<BotCommand>,
+    /// Platform user ids allowed to send commands (empty: anyone in the
+    /// channel, for `status` only)
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+}
+
+/// A connection as written, before it's saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotConnectionDraft {
+    pub name: String,
+    pub platform: BotPlatform,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Bot token; None keeps the stored one
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub channels: Vec<BotChannel>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A saved connection (its token is stored separately)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BotConnection {
+    pub id: String,
+    pub name: String,
+    pub platform: BotPlatform,
+    pub enabled: bool,
+    #[serde(default)]
+    pub channels: Vec<BotChannel>,
+    /// Whether a token is stored (filled in on load)
+    #[serde(default)]
+    pub has_token: bool,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// A message read from a channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct Incoming {
+    /// Platform message id, used as the channel's cursor
+    pub id: String,
+    pub user: String,
+    pub text: String,
+    pub from_bot: bool,
+}
+
+/// A command parsed from a message
+#[derive(Debug, Clone, PartialEq)]
+enum BotRequest {
+    Help,
+    Status,
+    /// Session reference (id, id prefix or name)
+    Stop(String),
+    Approve(String),
+}
+
+impl BotRequest {
+    fn command(&self) -> Option<BotCommand> {
+        match self {
+            Self::Help => None,
+            Self::Status => Some(BotCommand::Status),
+            Self::Stop(_) => Some(BotCommand::Stop),
+            Self::Approve(_) => Some(BotCommand::Approve),
+        }
+    }
+}
+
+fn connections_path(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.app_data_dir()?.join(CONNECTIONS_FILE))
+}
+
+fn load_tokens(app: &impl PathProvider) -> HashMap<String, String> {
+    app.app_data_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(TOKENS_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_tokens(app: &impl PathProvider, tokens: &HashMap<String, String>) -> Result<(), String> {
+    let dir = app.app_data_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data directory: {e}"))?;
+    let path = dir.join(TOKENS_FILE);
+    let content = serde_json::to_string(tokens)
+        .map_err(|e| format!("Failed to serialize bot tokens: {e}"))?;
+    write_secret_file(&path, content.as_bytes())
+        .map_err(|e| format!("Failed to save bot tokens: {e}"))
+}
+
+/// The stored token of a connection
+fn load_token(app: &impl PathProvider, connection_id: &str) -> Option<String> {
+    load_tokens(app).remove(connection_id)
+}
+
+/// All connections, oldest first
+pub fn load_connections(app: &impl PathProvider) -> Vec<BotConnection> {
+    let mut connections: Vec<BotConnection> = connections_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let tokens = load_tokens(app);
+    for connection in &mut connections {
+        connection.has_token = tokens.contains_key(&connection.id);
+    }
+    connections
+}
+
+fn save_connections(app: &impl PathProvider, connections: &[BotConnection]) -> Result<(), String> {
+    let path = connections_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data directory: {e}"))?;
+    }
+    let content = serde_json::to_string_pretty(connections)
+        .map_err(|e| format!("Failed to serialize bot connections: {e}"))?;
+    fs::write(path, content).map_err(|e| format!("Failed to save bot connections: {e}"))?;
+    *CONNECTIONS_CACHE.lock().unwrap() = None;
+    Ok(())
+}
+
+/// The draft's new token, if it sets one
+fn draft_token(draft: &BotConnectionDraft) -> Option<String> {
+    draft
+        .token
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+}
+
+fn validate(draft: &BotConnectionDraft) -> Result<(), String> {
+    if draft.name.trim().is_empty() {
+        return Err("A bot connection needs a name".to_string());
+    }
+    let mut seen = Vec::new();
+    for channel in &draft.channels {
+        let id = channel.channel_id.trim();
+        if id.is_empty() {
+            return Err("Every channel needs a channel id".to_string());
+        }
+        if draft.platform == BotPlatform::Discord && !id.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("Invalid Discord channel id: {id}"));
+        }
+        if seen.contains(&id) {
+            return Err(format!("Channel {id} is listed twice"));
+        }
+        let has_users = channel.allowed_users.iter().any(|u| !u.trim().is_empty());
+        if let Some(command) = channel
+            .allowed_commands
+            .iter()
+            .find(|c| c.needs_allowed_users())
+            .filter(|_| !has_users)
+        {
+            return Err(format!(
+                "Channel {id} allows `{}`, so it needs a list of allowed users",
+                command.name()
+            ));
+        }
+        seen.push(id);
+    }
+    Ok(())
+}
+
+fn trimmed_channels(channels: Vec<BotChannel>) -> Vec<BotChannel> {
+    channels
+        .into_iter()
+        .map(|c| BotChannel {
+            channel_id: c.channel_id.trim().to_string(),
+            allowed_users: c
+                .allowed_users
+                .iter()
+                .map(|u| u.trim().to_string())
+                .filter(|u| !u.is_empty())
+                .collect(),
+            ..c
+        })
+        .collect()
+}
+
+/// Save a new connection
+pub fn create_connection(
+    app: &impl PathProvider,
+    draft: BotConnectionDraft,
+) -> Result<BotConnection, String> {
+    validate(&draft)?;
+    let token = draft_token(&draft).ok_or("A bot connection needs a token")?;
+    let now = now();
+    let mut connection = BotConnection {
+        id: Uuid::new_v4().to_string(),
+        name: draft.name.trim().to_string(),
+        platform: draft.platform,
+        enabled: draft.enabled,
+        channels: trimmed_channels(draft.channels),
+        has_token: false,
+        created_at: now,
+        updated_at: now,
+    };
+    let mut tokens = load_tokens(app);
+    tokens.insert(connection.id.clone(), token);
+    save_tokens(app, &tokens)?;
+    let mut connections = load_connections(app);
+    connections.push(connection.clone());
+    save_connections(app, &connections)?;
+    connection.has_token = true;
+    Ok(connection)
+}
+
+/// Replace a connection's definition
+pub fn update_connection(
+    app: &impl PathProvider,
+    connection_id: &str,
+    draft: BotConnectionDraft,
+) -> Result<BotConnection, String> {
+    validate(&draft)?;
+    let mut connections = load_connections(app);
+    let connection = connections
+        .iter_mut()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| format!("Bot connection {connection_id} not found"))?;
+    if let Some(token) = draft_token(&draft) {
+        let mut tokens = load_tokens(app);
+        tokens.insert(connection_id.to_string(), token);
+        save_tokens(app, &tokens)?;
+        connection.has_token = true;
+    }
+    connection.name = draft.name.trim().to_string();
+    connection.platform = draft.platform;
+    connection.enabled = draft.enabled;
+    connection.channels = trimmed_channels(draft.channels);
+    connection.updated_at = now();
+    let connection = connection.clone();
+    save_connections(app, &connections)?;
+    Ok(connection)
+}
+
+/// Delete a connection and its token
+pub fn delete_connection(app: &impl PathProvider, connection_id: &str) -> Result<(), String> {
+    let mut connections = load_connections(app);
+    let before = connections.len();
+    connections.retain(|c| c.id != connection_id);
+    if connections.len() == before {
+        return Err(format!("Bot connection {connection_id} not found"));
+    }
+    save_connections(app, &connections)?;
+    let mut tokens = load_tokens(app);
+    if tokens.remove(connection_id).is_some() {
+        save_tokens(app, &tokens)?;
+    }
+    CURSORS
+        .lock()
+        .unwrap()
+        .retain(|(id, _), _| id != connection_id);
+    Ok(())
+}
+
+/// Enabled connections with a token (cached)
+fn enabled_connections(app: &impl PathProvider) -> Vec<BotConnection> {
+    let mut cache = CONNECTIONS_CACHE.lock().unwrap();
+    cache
+        .get_or_insert_with(|| {
+            load_connections(app)
+                .into_iter()
+                .filter(|c| c.enabled && c.has_token)
+                .collect()
+        })
+        .clone()
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))
+}
+
+async fn post(
+    client: &reqwest::Client,
+    platform: BotPlatform,
+    token: &str,
+    channel: &str,
+    text: &str,
+) -> Result<(), String> {
+    match platform {
+        BotPlatform::Slack => slack::post(client, platform.api(), token, channel, text).await,
+        BotPlatform::Discord => discord::post(client, platform.api(), token, channel, text).await,
+    }
+}
+
+async fn fetch(
+    client: &reqwest::Client,
+    platform: BotPlatform,
+    token: &str,
+    channel: &str,
+    cursor: &str,
+) -> Result<Vec<Incoming>, String> {
+    match platform {
+        BotPlatform::Slack => slack::fetch(client, platform.api(), token, channel, cursor).await,
+        BotPlatform::Discord => {
+            discord::fetch(client, platform.api(), token, channel, cursor).await
+        }
+    }
+}
+
+/// Post a message to every channel of a connection, for checking its setup
+pub async fn send_test_message(app: &AppHandle, connection_id: &str) -> Result<(), String> {
+    let connection = load_connections(app)
+        .into_iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| format!("Bot connection {connection_id} not found"))?;
+    let token = load_token(app, connection_id)
+        .ok_or_else(|| format!("{} has no token", connection.name))?;
+    if connection.channels.is_empty() {
+        return Err(format!("{} has no channels", connection.name));
+    }
+    let client = http_client()?;
+    let mut failures = Vec::new();
+    for channel in &connection.channels {
+        let text = format!("Jean is connected. {}", help_text(channel));
+        if let Err(e) = post(
+            &client,
+            connection.platform,
+            &token,
+            &channel.channel_id,
+            &text,
+        )
+        .await
+        {
+            failures.push(format!("{}: {e}", channel.channel_id));
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+/// The command part of a message addressed to Jean (`jean …`, `!jean …`
+/// or starting with a mention), or None for other messages
+fn addressed_text(text: &str) -> Option<&str> {
+    let text = text.trim();
+    if text.starts_with("<@") {
+        let end = text.find('>')?;
+        return Some(text[end + 1..].trim_start_matches([':', ',']).trim());
+    }
+    let text = text.strip_prefix('!').unwrap_or(text);
+    let prefix = text.get(..4)?;
+    if !prefix.eq_ignore_ascii_case("jean") {
+        return None;
+    }
+    let rest = &text[4..];
+    match rest.chars().next() {
+        None => Some(""),
+        Some(c) if c.is_whitespace() || c == ':' || c == ',' => {
+            Some(rest.trim_start_matches([':', ',']).trim())
+        }
+        _ => None,
+    }
+}
+
+fn parse_request(text: &str) -> Result<BotRequest, String> {
+    let (word, argument) = text
+        .split_once(char::is_whitespace)
+        .map(|(w, a)| (w, a.trim()))
+        .unwrap_or((text, ""));
+    let argument = argument.trim_matches('`').to_string();
+    match word.to_lowercase().as_str() {
+        "" | "help" => Ok(BotRequest::Help),
+        "status" => Ok(BotRequest::Status),
+        "stop" if !argument.is_empty() => Ok(BotRequest::Stop(argument)),
+        "approve" if !argument.is_empty() => Ok(BotRequest::Approve(argument)),
+        "stop" | "approve" => Err(format!("Usage: `jean {word} <session>`")),
+        _ => Err(format!("Unknown command `{word}`. Try `jean help`")),
+    }
+}
+
+/// Check that `user` may send `request` in this channel
+fn authorize(channel: &BotChannel, user: &str, request: &BotRequest) -> Result<(), String> {
+    if !channel.allowed_users.is_empty() && !channel.allowed_users.iter().any(|u| u == user) {
+        return Err("You're not allowed to control Jean from this channel".to_string());
+    }
+    match request.command() {
+        Some(command) if !channel.allowed_commands.contains(&command) => Err(format!(
+            "`{}` isn't allowed in this channel",
+            command.name()
+        )),
+        // Checked on save too; connections saved before that are held to it here
+        Some(command) if command.needs_allowed_users() && channel.allowed_users.is_empty() => {
+            Err(format!(
+                "`{}` needs a list of allowed users for this channel",
+                command.name()
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn help_text(channel: &BotChannel) -> String {
+    let usage: Vec<&str> = channel
+        .allowed_commands
+        .iter()
+        .map(|c| match c {
+            BotCommand::Status => "`jean status`",
+            BotCommand::Stop => "`jean stop <session>`",
+            BotCommand::Approve => "`jean approve <session>`",
+        })
+        .collect();
+    if usage.is_empty() {
+        "This channel only gets notifications.".to_string()
+    } else {
+        format!("Commands: {}", usage.join(", "))
+    }
+}
+
+fn short_id(session_id: &str) -> &str {
+    session_id.get(..SHORT_ID_LEN).unwrap_or(session_id)
+}
+
+/// The session `reference` names among (id, name) candidates: an exact id,
+/// a unique id prefix, or a unique name (case-insensitive)
+fn match_session(candidates: &[(String, String)], reference: &str) -> Result<String, String> {
+    if let Some((id, _)) = candidates.iter().find(|(id, _)| id == reference) {
+        return Ok(id.clone());
+    }
+    let by_prefix: Vec<&String> = candidates
+        .iter()
+        .map(|(id, _)| id)
+        .filter(|id| id.starts_with(reference))
+        .collect();
+    let matches = if by_prefix.is_empty() {
+        candidates
+            .iter()
+            .filter(|(_, name)| name.eq_ignore_ascii_case(reference))
+            .map(|(id, _)| id)
+            .collect()
+    } else {
+        by_prefix
+    };
+    match matches.as_slice() {
+        [id] => Ok((*id).clone()),
+        [] => Err(format!("No session matches `{reference}`")),
+        _ => Err(format!(
+            "`{reference}` matches several sessions, use its id"
+        )),
+    }
+}
+
+/// Tool patterns to allow for a `chat:permission_denied` payload's denials,
+/// as the approval prompt builds them (`Bash(command)`, else the tool name)
+fn approval_patterns(denials: &Value) -> Vec<String> {
+    let mut patterns: Vec<String> = Vec::new();
+    for denial in denials.as_array().into_iter().flatten() {
+        let Some(tool) = denial.get("tool_name").and_then(Value::as_str) else {
+            continue;
+        };
+        let command = denial
+            .pointer("/tool_input/command")
+            .and_then(Value::as_str)
+            .filter(|c| !c.is_empty());
+        let pattern = match command {
+            Some(command) if tool == "Bash" => format!("Bash({command})"),
+            _ => tool.to_string(),
+        };
+        if !patterns.contains(&pattern) {
+            patterns.push(pattern);
+        }
+    }
+    patterns
+}
+
+/// Message that resumes a session after its tools were approved, matching
+/// the one the approval prompt sends
+fn continuation_message(patterns: &[String]) -> String {
+    let commands: Vec<&str> = patterns
+        .iter()
+        .filter_map(|p| p.strip_prefix("Bash(")?.strip_suffix(')'))
+        .filter(|c| !c.is_empty())
+        .collect();
+    match commands.as_slice() {
+        [] => format!(
+            "I approved {}. Continue with the task.",
+            patterns.join(", ")
+        ),
+        _ if commands.len() < patterns.len() => {
+            format!("I approved: {}. Execute them now.", patterns.join(", "))
+        }
+        [command] => format!("I approved the command. Run it now: `{command}`"),
+        _ => format!(
+            "I approved these commands. Run them now:\n{}",
+            commands
+                .iter()
+                .map(|c| format!("- `{c}`"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    }
+}
+
+/// Channel message for a session event, or None for events not announced
+fn notification_text(
+    event: &str,
+    label: &str,
+    session_id: &str,
+    payload: &Value,
+) -> Option<String> {
+    let text = match event {
+        "chat:sending" => format!("Started: {label}"),
+        "chat:done" => format!("Finished: {label}"),
+        "chat:error" => {
+            let error = payload.get("error").and_then(Value::as_str).unwrap_or("");
+            format!("Failed: {label}\n> {}", error.lines().next().unwrap_or(""))
+        }
+        "chat:cancelled" => format!("Cancelled: {label}"),
+        "chat:permission_denied" => {
+            let patterns = approval_patterns(payload.get("denials")?);
+            format!(
+                "Waiting for approval: {label} wants {}. Reply `jean approve {}` to allow it.",
+                patterns.join(", "),
+                short_id(session_id)
+            )
+        }
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// A session's name and id, and its worktree
+fn session_label(app: &AppHandle, session_id: &str) -> (String, String) {
+    let metadata = crate::chat::storage::load_metadata(app, session_id)
+        .ok()
+        .flatten();
+    let Some(metadata) = metadata else {
+        return (
+            session_id.to_string(),
+            format!("`{}`", short_id(session_id)),
+        );
+    };
+    let worktree = crate::projects::storage::load_projects_data(app)
+        .ok()
+        .and_then(|data| data.find_worktree(&metadata.worktree_id).cloned())
+        .map(|w| format!(" in {}", w.name))
+        .unwrap_or_default();
+    let label = format!("{}{worktree} `{}`", metadata.name, short_id(session_id));
+    (metadata.name, label)
+}
+
+/// Track pending approvals and announce session events in notify channels
+pub fn on_event<S: Serialize>(app: &AppHandle, event: &str, payload: &S) {
+    if !matches!(
+        event,
+        "chat:sending" | "chat:done" | "chat:error" | "chat:cancelled" | "chat:permission_denied"
+    ) {
+        return;
+    }
+    let Ok(payload) = serde_json::to_value(payload) else {
+        return;
+    };
+    let Some(session_id) = payload.get("session_id").and_then(Value::as_str) else {
+        return;
+    };
+    let session_id = session_id.to_string();
+
+    match event {
+        "chat:permission_denied" => {
+            let patterns = payload
+                .get("denials")
+                .map(approval_patterns)
+                .unwrap_or_default();
+            if !patterns.is_empty() {
+                PENDING.lock().unwrap().insert(session_id.clone(), patterns);
+            }
+        }
+        "chat:sending" | "chat:cancelled" => {
+            PENDING.lock().unwrap().remove(&session_id);
+        }
+        _ => {}
+    }
+
+    let targets: Vec<(BotConnection, String)> = enabled_connections(app)
+        .into_iter()
+        .flat_map(|connection| {
+            connection
+                .channels
+                .iter()
+                .filter(|c| c.notify)
+                .map(|c| (connection.clone(), c.channel_id.clone()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+
+    let app = app.clone();
+    let event = event.to_string();
+    crate::background_tasks::supervisor::spawn_task("bot-notify", async move {
+        let (_, label) = session_label(&app, &session_id);
+        let Some(text) = notification_text(&event, &label, &session_id, &payload) else {
+            return;
+        };
+        let client = match http_client() {
+            Ok(client) => client,
+            Err(e) => {
+                log::warn!("Bot notification failed: {e}");
+                return;
+            }
+        };
+        for (connection, channel) in targets {
+            let Some(token) = load_token(&app, &connection.id) else {
+                continue;
+            };
+            if let Err(e) = post(&client, connection.platform, &token, &channel, &text).await {
+                log::warn!("Bot notification to {} failed: {e}", connection.name);
+            }
+        }
+    });
+}
+
+/// (id, name) of the given sessions
+fn candidates(app: &AppHandle, session_ids: Vec<String>) -> Vec<(String, String)> {
+    session_ids
+        .into_iter()
+        .map(|id| {
+            let (name, _) = session_label(app, &id);
+            (id, name)
+        })
+        .collect()
+}
+
+/// Run a command, returning the reply for the channel
+async fn execute(
+    app: &AppHandle,
+    request: BotRequest,
+    channel: &BotChannel,
+) -> Result<String, String> {
+    match request {
+        BotRequest::Help => Ok(help_text(channel)),
+        BotRequest::Status => {
+            let running = crate::chat::registry::get_running_sessions();
+            let pending: Vec<(String, Vec<String>)> = PENDING
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(id, patterns)| (id.clone(), patterns.clone()))
+                .collect();
+            if running.is_empty() && pending.is_empty() {
+                return Ok("Nothing is running.".to_string());
+            }
+            let mut lines = Vec::new();
+            if !running.is_empty() {
+                lines.push("Running:".to_string());
+                for id in &running {
+                    lines.push(format!("- {}", session_label(app, id).1));
+                }
+            }
+            if !pending.is_empty() {
+                lines.push("Waiting for approval:".to_string());
+                for (id, patterns) in &pending {
+                    lines.push(format!(
+                        "- {}: {}",
+                        session_label(app, id).1,
+                        patterns.join(", ")
+                    ));
+                }
+            }
+            Ok(lines.join("\n"))
+        }
+        BotRequest::Stop(reference) => {
+            let running = crate::chat::registry::get_running_sessions();
+            let session_id = match_session(&candidates(app, running), &reference)?;
+            let metadata = crate::chat::storage::load_metadata(app, &session_id)?
+                .ok_or_else(|| format!("Session not found: {session_id}"))?;
+            let (_, label) = session_label(app, &session_id);
+            if crate::chat::registry::cancel_process(app, &session_id, &metadata.worktree_id)? {
+                Ok(format!("Stopped {label}"))
+            } else {
+                Ok(format!("{label} isn't running"))
+            }
+        }
+        BotRequest::Approve(reference) => {
+            if crate::emergency::is_engaged() {
+                return Err("Emergency stop is engaged".to_string());
+            }
+            let waiting: Vec<String> = PENDING.lock().unwrap().keys().cloned().collect();
+            let session_id = match_session(&candidates(app, waiting), &reference)?;
+            let patterns = PENDING
+                .lock()
+                .unwrap()
+                .remove(&session_id)
+                .ok_or_else(|| format!("No approval is pending for `{reference}`"))?;
+            let metadata = crate::chat::storage::load_metadata(app, &session_id)?
+                .ok_or_else(|| format!("Session not found: {session_id}"))?;
+            use crate::http_server::EmitExt;
+            if let Err(e) = app.emit_all(
+                "chat:permission_approved",
+                &serde_json::json!({
+                    "session_id": session_id,
+                    "worktree_id": metadata.worktree_id,
+                    "patterns": patterns,
+                }),
+            ) {
+                log::error!("Failed to emit chat:permission_approved event: {e}");
+            }
+
+            let (_, label) = session_label(app, &session_id);
+            let reply = format!("Approved {} for {label}", patterns.join(", "));
+            let task_app = app.clone();
+            let message = continuation_message(&patterns);
+            crate::background_tasks::supervisor::spawn_task("bot-approve", async move {
+                if let Err(e) =
+                    crate::rules::enqueue_task(&task_app, &session_id, message, patterns).await
+                {
+                    log::warn!("Failed to resume session {session_id} after approval: {e}");
+                }
+            });
+            Ok(reply)
+        }
+    }
+}
+
+/// Check a connection's command channels for new messages and run them
+async fn poll_connection(app: &AppHandle, client: &reqwest::Client, connection: &BotConnection) {
+    let Some(token) = load_token(app, &connection.id) else {
+        return;
+    };
+    for channel in connection
+        .channels
+        .iter()
+        .filter(|c| !c.allowed_commands.is_empty())
+    {
+        let key = (connection.id.clone(), channel.channel_id.clone());
+        let cursor = CURSORS.lock().unwrap().get(&key).cloned();
+        // Start from now, so messages sent before launch never run
+        let Some(cursor) = cursor else {
+            let cursor = connection.platform.cursor_at(now());
+            CURSORS.lock().unwrap().insert(key, cursor);
+            continue;
+        };
+        let messages = match fetch(
+            client,
+            connection.platform,
+            &token,
+            &channel.channel_id,
+            &cursor,
+        )
+        .await
+        {
+            Ok(messages) => messages,
+            Err(e) => {
+                log::debug!("Polling {} failed: {e}", connection.name);
+                continue;
+            }
+        };
+        if let Some(last) = messages.last() {
+            CURSORS.lock().unwrap().insert(key, last.id.clone());
+        }
+
+        for message in messages.into_iter().filter(|m| !m.from_bot) {
+            let Some(text) = addressed_text(&message.text) else {
+                continue;
+            };
+            let outcome = match parse_request(text) {
+                Ok(request) => match authorize(channel, &message.user, &request) {
+                    Ok(()) => {
+                        log::info!(
+                            "{} command from {} in {}: {text}",
+                            connection.platform.label(),
+                            message.user,
+                            channel.channel_id
+                        );
+                        execute(app, request, channel).await
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "Refused {} command from {} in {}: {e}",
+                            connection.platform.label(),
+                            message.user,
+                            channel.channel_id
+                        );
+                        Err(e)
+                    }
+                },
+                Err(e) => Err(e),
+            };
+            let reply = outcome.unwrap_or_else(|e| format!("Couldn't do that: {e}"));
+            if let Err(e) = post(
+                client,
+                connection.platform,
+                &token,
+                &channel.channel_id,
+                &reply,
+            )
+            .await
+            {
+                log::warn!("Bot reply to {} failed: {e}", connection.name);
+            }
+        }
+    }
+}
+
+/// Start polling command channels in the background
+pub fn start_poller(app: AppHandle) {
+    crate::background_tasks::supervisor::supervise("bot-poller", move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let connections: Vec<BotConnection> = enabled_connections(&app)
+            .into_iter()
+            .filter(|c| c.channels.iter().any(|ch| !ch.allowed_commands.is_empty()))
+            .collect();
+        if connections.is_empty() {
+            continue;
+        }
+        let client = match http_client() {
+            Ok(client) => client,
+            Err(e) => {
+                log::warn!("Bot poller: {e}");
+                continue;
+            }
+        };
+        tauri::async_runtime::block_on(async {
+            for connection in &connections {
+                poll_connection(&app, &client, connection).await;
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+    use serde_json::json;
+
+    fn channel(commands: Vec<BotCommand>, users: Vec<&str>) -> BotChannel {
+        BotChannel {
+            channel_id: "C1".to_string(),
+            notify: true,
+            allowed_commands: commands,
+            allowed_users: users.into_iter().map(str::to_string).collect(),
+        }
+    }
+
+    fn draft(token: Option<&str>) -> BotConnectionDraft {
+        BotConnectionDraft {
+            name: " Team ".to_string(),
+            platform: BotPlatform::Discord,
+            enabled: true,
+            token: token.map(str::to_string),
+            channels: vec![BotChannel {
+                channel_id: " 123 ".to_string(),
+                ..channel(vec![BotCommand::Status], vec![" 42 ", ""])
+            }],
+        }
+    }
+
+    #[test]
+    fn test_connection_storage_keeps_token_apart() {
+        let paths = TempPaths::new();
+        assert_eq!(
+            create_connection(&paths, draft(None)).unwrap_err(),
+            "A bot connection needs a token"
+        );
+        let created = create_connection(&paths, draft(Some("secret"))).unwrap();
+        assert_eq!(created.name, "Team");
+        assert!(created.has_token);
+        assert_eq!(created.channels[0].channel_id, "123");
+        assert_eq!(created.channels[0].allowed_users, vec!["42"]);
+
+        let saved = fs::read_to_string(connections_path(&paths).unwrap()).unwrap();
+        assert!(!saved.contains("secret"));
+        assert_eq!(load_token(&paths, &created.id).as_deref(), Some("secret"));
+
+        // No token in the draft keeps the stored one
+        let updated = update_connection(&paths, &created.id, draft(None)).unwrap();
+        assert!(updated.has_token);
+        assert_eq!(load_token(&paths, &created.id).as_deref(), Some("secret"));
+
+        delete_connection(&paths, &created.id).unwrap();
+        assert!(load_connections(&paths).is_empty());
+        assert_eq!(load_token(&paths, &created.id), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut bad = draft(Some("t"));
+        bad.channels[0].channel_id = "general".to_string();
+        assert_eq!(
+            validate(&bad).unwrap_err(),
+            "Invalid Discord channel id: general"
+        );
+        bad.platform = BotPlatform::Slack;
+        assert!(validate(&bad).is_ok());
+        bad.channels.push(bad.channels[0].clone());
+        assert_eq!(
+            validate(&bad).unwrap_err(),
+            "Channel general is listed twice"
+        );
+    }
+
+    #[test]
+    fn test_validate_requires_users_for_session_commands() {
+        let mut open = draft(Some("t"));
+        open.channels[0].allowed_users = vec![" ".to_string()];
+        assert!(validate(&open).is_ok());
+        for command in [BotCommand::Stop, BotCommand::Approve] {
+            open.channels[0].allowed_commands = vec![BotCommand::Status, command];
+            assert_eq!(
+                validate(&open).unwrap_err(),
+                format!(
+                    "Channel 123 allows `{}`, so it needs a list of allowed users",
+                    command.name()
+                )
+            );
+        }
+        open.channels[0].allowed_users = vec!["42".to_string()];
+        assert!(validate(&open).is_ok());
+    }
+
+    #[test]
+    fn test_addressed_text() {
+        assert_eq!(addressed_text("jean status"), Some("status"));
+        assert_eq!(addressed_text("  !Jean: stop abc "), Some("stop abc"));
+        assert_eq!(addressed_text("<@U123> approve fix"), Some("approve fix"));
+        assert_eq!(addressed_text("<@!42>"), Some(""));
+        assert_eq!(addressed_text("jean"), Some(""));
+        assert_eq!(addressed_text("jeans are blue"), None);
+        assert_eq!(addressed_text("hey jean status"), None);
+    }
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(parse_request(""), Ok(BotRequest::Help));
+        assert_eq!(parse_request("STATUS"), Ok(BotRequest::Status));
+        assert_eq!(
+            parse_request("stop  Fix login"),
+            Ok(BotRequest::Stop("Fix login".to_string()))
+        );
+        assert_eq!(
+            parse_request("approve `1a2b3c4d`"),
+            Ok(BotRequest::Approve("1a2b3c4d".to_string()))
+        );
+        assert_eq!(
+            parse_request("stop").unwrap_err(),
+            "Usage: `jean stop <session>`"
+        );
+        assert!(parse_request("deploy prod").is_err());
+    }
+
+    #[test]
+    fn test_authorize() {
+        let open = channel(vec![BotCommand::Status], vec![]);
+        assert!(authorize(&open, "U1", &BotRequest::Status).is_ok());
+        assert!(authorize(&open, "U1", &BotRequest::Help).is_ok());
+        assert_eq!(
+            authorize(&open, "U1", &BotRequest::Stop("x".to_string())).unwrap_err(),
+            "`stop` isn't allowed in this channel"
+        );
+
+        let restricted = channel(vec![BotCommand::Status, BotCommand::Stop], vec!["U1"]);
+        assert!(authorize(&restricted, "U1", &BotRequest::Stop("x".to_string())).is_ok());
+        assert!(authorize(&restricted, "U2", &BotRequest::Status).is_err());
+        assert!(authorize(&restricted, "U2", &BotRequest::Help).is_err());
+
+        // Saved without allowed users: anyone may ask for status, nobody may
+        // stop or approve
+        let unrestricted = channel(
+            vec![BotCommand::Status, BotCommand::Stop, BotCommand::Approve],
+            vec![],
+        );
+        assert!(authorize(&unrestricted, "U1", &BotRequest::Status).is_ok());
+        assert_eq!(
+            authorize(&unrestricted, "U1", &BotRequest::Approve("x".to_string())).unwrap_err(),
+            "`approve` needs a list of allowed users for this channel"
+        );
+        assert!(authorize(&unrestricted, "U1", &BotRequest::Stop("x".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_match_session() {
+        let sessions = vec![
+            ("1a2b3c4d-0000".to_string(), "Fix login".to_string()),
+            ("1a2b9999-0000".to_string(), "Docs".to_string()),
+            ("77777777-0000".to_string(), "docs".to_string()),
+        ];
+        assert_eq!(match_session(&sessions, "1a2b3").unwrap(), "1a2b3c4d-0000");
+        assert_eq!(
+            match_session(&sessions, "fix LOGIN").unwrap(),
+            "1a2b3c4d-0000"
+        );
+        assert!(match_session(&sessions, "1a2b").is_err());
+        assert!(match_session(&sessions, "docs").is_err());
+        assert_eq!(
+            match_session(&sessions, "nope").unwrap_err(),
+            "No session matches `nope`"
+        );
+    }
+
+    #[test]
+    fn test_approval_patterns_and_continuation() {
+        let denials = json!([
+            { "tool_name": "Bash", "tool_use_id": "t1", "tool_input": { "command": "npm test" } },
+            { "tool_name": "Bash", "tool_use_id": "t2", "tool_input": { "command": "npm test" } },
+            { "tool_name": "WebFetch", "tool_use_id": "t3", "tool_input": {} },
+        ]);
+        let patterns = approval_patterns(&denials);
+        assert_eq!(patterns, vec!["Bash(npm test)", "WebFetch"]);
+        assert_eq!(
+            continuation_message(&patterns),
+            "I approved: Bash(npm test), WebFetch. Execute them now."
+        );
+        assert_eq!(
+            continuation_message(&patterns[..1]),
+            "I approved the command. Run it now: `npm test`"
+        );
+        assert_eq!(
+            continuation_message(&["Bash(a)".to_string(), "Bash(b)".to_string()]),
+            "I approved these commands. Run them now:\n- `a`\n- `b`"
+        );
+        assert_eq!(
+            continuation_message(&patterns[1..]),
+            "I approved WebFetch. Continue with the task."
+        );
+    }
+
+    #[test]
+    fn test_notification_text() {
+        let id = "1a2b3c4d-0000";
+        assert_eq!(
+            notification_text("chat:done", "Fix login", id, &json!({})).unwrap(),
+            "Finished: Fix login"
+        );
+        assert_eq!(
+            notification_text(
+                "chat:error",
+                "Fix login",
+                id,
+                &json!({ "error": "rate limited\ndetails" })
+            )
+            .unwrap(),
+            "Failed: Fix login\n> rate limited"
+        );
+        let denied =
+            json!({ "denials": [{ "tool_name": "Bash", "tool_input": { "command": "ls" } }] });
+        assert_eq!(
+            notification_text("chat:permission_denied", "Fix login", id, &denied).unwrap(),
+            "Waiting for approval: Fix login wants Bash(ls). Reply `jean approve 1a2b3c4d` to allow it."
+        );
+        assert_eq!(
+            notification_text("chat:chunk", "Fix login", id, &json!({})),
+            None
+        );
+    }
+}
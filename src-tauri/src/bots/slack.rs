@@ -0,0 +1,152 @@
+//! Slack Web API calls for bot connections
+//!
+//! Needs a bot token (`xoxb-…`) with `chat:write` and `channels:history`
+//! (`groups:history` for private channels), and the bot invited to each
+//! channel.
+
+use serde::Deserialize;
+use serde_json::json;
+
+use super::Incoming;
+
+pub const API: &str = "https://slack.com/api";
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    #[serde(default)]
+    ts: String,
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    bot_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    messages: Vec<Message>,
+}
+
+/// Cursor for messages posted after `unix_secs`
+pub fn cursor_at(unix_secs: u64) -> String {
+    format!("{unix_secs}.000000")
+}
+
+async fn call(
+    request: reqwest::RequestBuilder,
+    token: &str,
+    what: &str,
+) -> Result<Response, String> {
+    let response = request
+        .bearer_auth(token)
+        .send()
+        .await
+        .map_err(|e| format!("Slack {what} failed: {e}"))?;
+    let status = response.status();
+    let body: Response = response
+        .json()
+        .await
+        .map_err(|e| format!("Slack {what} failed: HTTP {status}: {e}"))?;
+    if !body.ok {
+        return Err(format!(
+            "Slack {what} failed: {}",
+            body.error.as_deref().unwrap_or("unknown error")
+        ));
+    }
+    Ok(body)
+}
+
+/// Post `text` to a channel
+pub async fn post(
+    client: &reqwest::Client,
+    base: &str,
+    token: &str,
+    channel: &str,
+    text: &str,
+) -> Result<(), String> {
+    let request = client
+        .post(format!("{base}/chat.postMessage"))
+        .json(&json!({ "channel": channel, "text": text }));
+    call(request, token, "chat.postMessage").await.map(|_| ())
+}
+
+/// Messages posted to a channel after `cursor`, oldest first
+pub async fn fetch(
+    client: &reqwest::Client,
+    base: &str,
+    token: &str,
+    channel: &str,
+    cursor: &str,
+) -> Result<Vec<Incoming>, String> {
+    let request = client.get(format!("{base}/conversations.history")).query(&[
+        ("channel", channel),
+        ("oldest", cursor),
+        ("limit", "100"),
+    ]);
+    let body = call(request, token, "conversations.history").await?;
+    // Newest first, and `oldest` is exclusive
+    Ok(body
+        .messages
+        .into_iter()
+        .rev()
+        .filter(|m| !m.ts.is_empty())
+        .map(|m| Incoming {
+            from_bot: m.bot_id.is_some() || m.user.is_none(),
+            id: m.ts,
+            user: m.user.unwrap_or_default(),
+            text: m.text,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture_server::{FixtureResponse, FixtureServer};
+
+    #[test]
+    fn test_fetch_and_post() {
+        let history = json!({
+            "ok": true,
+            "messages": [
+                { "ts": "1700000002.000200", "bot_id": "B1", "text": "Started: Fix login" },
+                { "ts": "1700000001.000100", "user": "U1", "text": "jean status" },
+            ],
+        });
+        let server = FixtureServer::start(vec![
+            (
+                "/conversations.history".to_string(),
+                FixtureResponse::json(history.to_string()),
+            ),
+            (
+                "/chat.postMessage".to_string(),
+                FixtureResponse::json(r#"{"ok":false,"error":"not_in_channel"}"#),
+            ),
+        ]);
+        let base = server.url("");
+        let client = reqwest::Client::new();
+        let (messages, posted) = tauri::async_runtime::block_on(async {
+            (
+                fetch(&client, &base, "xoxb", "C1", &cursor_at(1_700_000_000)).await,
+                post(&client, &base, "xoxb", "C1", "hi").await,
+            )
+        });
+
+        let messages = messages.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].id, "1700000001.000100");
+        assert_eq!(messages[0].user, "U1");
+        assert!(!messages[0].from_bot);
+        assert!(messages[1].from_bot);
+        assert_eq!(
+            posted.unwrap_err(),
+            "Slack chat.postMessage failed: not_in_channel"
+        );
+        assert!(server.requests()[0].contains("oldest=1700000000.000000"));
+    }
+}
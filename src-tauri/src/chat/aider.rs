@@ -0,0 +1,308 @@
+//! Aider as an alternative agent backend
+//!
+//! A CLI profile whose settings JSON has `"backend": "aider"` runs aider
+//! (installed into its own venv by `python_env`) instead of the Claude CLI.
+//! The profile's model and `autoCommit` map onto aider's flags, followed by
+//! any launch defaults (see `launch`); running and normalizing its output is
+//! shared with other backends (see `external`). Build and yolo runs confirm
+//! everything (`--yes-always`); plan runs use aider's ask mode as a dry run,
+//! so they answer without editing files, committing or running commands.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::external::ExternalRun;
+use crate::agent_protocol::custom::StreamFormat;
+use crate::cli_capabilities::args::{describe_errors, ArgBuilder, ArgError};
+use crate::cli_capabilities::{
+    self, CliCapabilities, AIDER_OPTIONAL_FLAGS, AIDER_RESERVED_FLAGS, AIDER_RULES,
+};
+use crate::python_env::PythonEnv;
+
+/// Tool name of aider's Python environment
+pub const AIDER_TOOL: &str = "aider";
+
+/// pip package aider is installed from
+pub const AIDER_PACKAGE: &str = "aider-chat";
+
+/// Flags that would let a plan-mode run change the worktree
+const PLAN_MODE_RESERVED_FLAGS: &[&str] = &[
+    "--yes-always",
+    "--chat-mode",
+    "--no-dry-run",
+    "--suggest-shell-commands",
+    "--auto-commits",
+];
+
+/// The aider-specific fields of a CLI profile's settings JSON
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiderProfile {
+    #[serde(default)]
+    backend: Option<String>,
+    /// Overrides the session's model when set
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Let aider commit each change (aider's own default is on)
+    #[serde(default)]
+    pub auto_commit: Option<bool>,
+    /// Extra environment, e.g. API keys for non-Anthropic models
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl AiderProfile {
+    /// Aider settings of a profile, if the profile selects the aider backend
+    pub fn from_settings(settings: Option<&str>) -> Option<Self> {
+        let profile: Self = serde_json::from_str(settings?).ok()?;
+        (profile.backend.as_deref() == Some("aider")).then_some(profile)
+    }
+}
+
+/// Map a Jean model name onto aider's `--model` value. The short Claude names
+/// are aider aliases too; full Claude model IDs need the provider prefix.
+fn aider_model(model: &str) -> String {
+    if model.starts_with("claude-") {
+        format!("anthropic/{model}")
+    } else {
+        model.to_string()
+    }
+}
+
+/// Build aider's arguments for one non-interactive run
+fn build_aider_args(
+    capabilities: &CliCapabilities,
+    profile: &AiderProfile,
+    model: Option<&str>,
+    message_file: &Path,
+    history_dir: &Path,
+    extra_args: &[String],
+    plan_mode: bool,
+) -> Result<Vec<String>, Vec<ArgError>> {
+    let mut args = ArgBuilder::new(capabilities, AIDER_OPTIONAL_FLAGS);
+    if plan_mode {
+        // Answer without editing files, committing or running commands
+        args.option("--chat-mode", "ask");
+        args.flag("--dry-run");
+        args.flag("--no-suggest-shell-commands");
+    } else {
+        args.flag("--yes-always");
+    }
+    for flag in [
+        "--no-pretty",
+        "--no-fancy-input",
+        "--no-check-update",
+        "--no-show-release-notes",
+        "--analytics-disable",
+        "--no-gitignore",
+        "--restore-chat-history",
+    ] {
+        args.flag(flag);
+    }
+
+    // History lives with the session so follow-up messages keep their context
+    args.option(
+        "--chat-history-file",
+        history_dir.join("aider.chat.history.md").to_string_lossy(),
+    );
+    args.option(
+        "--input-history-file",
+        history_dir.join("aider.input.history").to_string_lossy(),
+    );
+
+    if let Some(model) = profile.model.as_deref().or(model) {
+        args.option("--model", aider_model(model));
+    }
+    match profile.auto_commit.filter(|_| !plan_mode) {
+        Some(true) => {
+            args.flag("--auto-commits");
+        }
+        Some(false) => {
+            args.flag("--no-auto-commits");
+        }
+        None => {}
+    }
+
+    args.option("--message-file", message_file.to_string_lossy());
+    if plan_mode {
+        let reserved = [AIDER_RESERVED_FLAGS, PLAN_MODE_RESERVED_FLAGS].concat();
+        args.user_args(extra_args, &reserved);
+    } else {
+        args.user_args(extra_args, AIDER_RESERVED_FLAGS);
+    }
+    args.build(AIDER_RULES)
+}
+
+/// Install (or repair) aider's Python environment
+pub fn install(app: &tauri::AppHandle) -> Result<PythonEnv, String> {
+    match crate::python_env::list_envs(app)
+        .into_iter()
+        .find(|env| env.tool == AIDER_TOOL)
+    {
+        Some(_) => crate::python_env::repair_env(app, AIDER_TOOL),
+        None => crate::python_env::create_env(app, AIDER_TOOL, AIDER_PACKAGE),
+    }
+}
+
+/// Aider invocation for one message
+#[allow(clippy::too_many_arguments)]
+pub(super) fn prepare_run(
+    app: &tauri::AppHandle,
+    session_id: &str,
+    input_file: &Path,
+    message_file: &Path,
+    model: Option<&str>,
+    execution_mode: Option<&str>,
+    profile: &AiderProfile,
+    extra_args: &[String],
+) -> Result<ExternalRun, String> {
+    let program = crate::python_env::resolve_entry_point(app, AIDER_TOOL)
+        .ok_or("Aider not installed. Install it in Settings > Advanced.")?;
+    let session_dir = super::storage::get_session_dir(app, session_id)?;
+    let capabilities = cli_capabilities::probe(app, AIDER_TOOL, &program, AIDER_OPTIONAL_FLAGS);
+    let args = build_aider_args(
+        &capabilities,
+        profile,
+        model,
+        message_file,
+        &session_dir,
+        extra_args,
+        super::external::is_plan_mode(execution_mode),
+    )
+    .map_err(|errors| describe_errors("aider", &errors))?;
+    Ok(ExternalRun {
+        label: "aider".to_string(),
+        program,
+        args,
+        env: profile
+            .env
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        stdin_file: input_file.to_path_buf(),
+        format: StreamFormat::AiderText,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_selects_backend() {
+        assert!(AiderProfile::from_settings(None).is_none());
+        assert!(AiderProfile::from_settings(Some(r#"{"env":{}}"#)).is_none());
+        let profile = AiderProfile::from_settings(Some(
+            r#"{"backend":"aider","model":"gpt-4o","autoCommit":false,"env":{"OPENAI_API_KEY":"k"}}"#,
+        ))
+        .unwrap();
+        assert_eq!(profile.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(profile.auto_commit, Some(false));
+        assert_eq!(profile.env["OPENAI_API_KEY"], "k");
+    }
+
+    #[test]
+    fn test_build_args_maps_profile() {
+        let profile = AiderProfile {
+            auto_commit: Some(false),
+            ..Default::default()
+        };
+        let caps = CliCapabilities::default();
+        let args = build_aider_args(
+            &caps,
+            &profile,
+            Some("claude-sonnet-4-5"),
+            Path::new("/tmp/msg.md"),
+            Path::new("/tmp/session"),
+            &[],
+            false,
+        )
+        .unwrap();
+        let after = |flag: &str| {
+            let i = args.iter().position(|a| a == flag).unwrap();
+            args[i + 1].clone()
+        };
+        assert_eq!(after("--model"), "anthropic/claude-sonnet-4-5");
+        assert_eq!(after("--message-file"), "/tmp/msg.md");
+        assert!(after("--chat-history-file").starts_with("/tmp/session/"));
+        assert!(args.contains(&"--no-auto-commits".to_string()));
+        assert!(args.contains(&"--yes-always".to_string()));
+
+        // The profile's model wins over the session's
+        let profile = AiderProfile {
+            model: Some("sonnet".to_string()),
+            ..Default::default()
+        };
+        let args = build_aider_args(
+            &caps,
+            &profile,
+            Some("opus"),
+            Path::new("m"),
+            Path::new("h"),
+            &["--no-stream".to_string()],
+            false,
+        )
+        .unwrap();
+        assert!(args.windows(2).any(|w| w == ["--model", "sonnet"]));
+        assert!(!args.iter().any(|a| a.contains("auto-commits")));
+        assert_eq!(args.last().map(String::as_str), Some("--no-stream"));
+
+        // Launch defaults go through the same rules as Jean's flags
+        let profile = AiderProfile {
+            auto_commit: Some(false),
+            ..Default::default()
+        };
+        let extra = ["--auto-commits".to_string()];
+        let errors = build_aider_args(
+            &caps,
+            &profile,
+            None,
+            Path::new("m"),
+            Path::new("h"),
+            &extra,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(errors[0].flag, "--auto-commits");
+    }
+
+    #[test]
+    fn test_plan_mode_changes_nothing() {
+        let profile = AiderProfile {
+            auto_commit: Some(true),
+            ..Default::default()
+        };
+        let caps = CliCapabilities::default();
+        let args = build_aider_args(
+            &caps,
+            &profile,
+            None,
+            Path::new("m"),
+            Path::new("h"),
+            &[],
+            true,
+        )
+        .unwrap();
+        assert!(!args.contains(&"--yes-always".to_string()));
+        assert!(args.windows(2).any(|w| w == ["--chat-mode", "ask"]));
+        assert!(args.contains(&"--dry-run".to_string()));
+        assert!(args.contains(&"--no-suggest-shell-commands".to_string()));
+        assert!(!args.iter().any(|a| a.contains("auto-commits")));
+
+        // Launch defaults can't turn confirmations back on
+        let extra = ["--yes-always".to_string()];
+        let errors = build_aider_args(
+            &caps,
+            &profile,
+            None,
+            Path::new("m"),
+            Path::new("h"),
+            &extra,
+            true,
+        )
+        .unwrap_err();
+        assert_eq!(errors[0].flag, "--yes-always");
+    }
+}
@@ -0,0 +1,279 @@
+//! Reviewer annotations on transcript messages
+//!
+//! Notes, thumbs up/down and free-form labels ("hallucination", "great fix")
+//! attached to individual messages, so teams can review agent behavior and
+//! pick out sessions for curation. Stored on the session's metadata and
+//! carried through JSONL transcript export and import.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::storage::{load_metadata, with_metadata_mut};
+use super::types::SessionMetadata;
+use crate::runtime::{now, PathProvider};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rating {
+    Up,
+    Down,
+}
+
+/// A reviewer's annotation on one message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageAnnotation {
+    pub id: String,
+    pub message_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<Rating>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl MessageAnnotation {
+    fn is_empty(&self) -> bool {
+        self.note.is_none() && self.rating.is_none() && self.labels.is_empty()
+    }
+}
+
+/// Changes to an annotation; `None` leaves a field as it is
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnnotationUpdate {
+    /// An empty note clears it
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub rating: Option<Rating>,
+    /// Remove the rating (ignored when `rating` is set)
+    #[serde(default)]
+    pub clear_rating: bool,
+    #[serde(default)]
+    pub labels: Option<Vec<String>>,
+}
+
+/// Trimmed note, or None if blank
+fn clean_note(note: Option<String>) -> Option<String> {
+    note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty())
+}
+
+/// Trimmed, lowercased labels without blanks or duplicates, in order
+fn clean_labels(labels: Vec<String>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for label in labels {
+        let label = label.trim().to_lowercase();
+        if !label.is_empty() && !cleaned.contains(&label) {
+            cleaned.push(label);
+        }
+    }
+    cleaned
+}
+
+/// Whether a message ID belongs to one of the session's runs
+pub fn has_message(metadata: &SessionMetadata, message_id: &str) -> bool {
+    metadata.runs.iter().any(|run| {
+        run.user_message_id == message_id || run.assistant_message_id.as_deref() == Some(message_id)
+    })
+}
+
+/// Update an existing session's metadata
+fn with_session<T>(
+    app: &impl PathProvider,
+    session_id: &str,
+    f: impl FnOnce(&mut SessionMetadata) -> Result<T, String>,
+) -> Result<T, String> {
+    let metadata = load_metadata(app, session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    with_metadata_mut(
+        app,
+        session_id,
+        &metadata.worktree_id,
+        &metadata.name,
+        metadata.order,
+        f,
+    )
+}
+
+/// Annotate a message
+pub fn add(
+    app: &impl PathProvider,
+    session_id: &str,
+    message_id: &str,
+    note: Option<String>,
+    rating: Option<Rating>,
+    labels: Vec<String>,
+) -> Result<MessageAnnotation, String> {
+    let created_at = now();
+    let annotation = MessageAnnotation {
+        id: Uuid::new_v4().to_string(),
+        message_id: message_id.to_string(),
+        note: clean_note(note),
+        rating,
+        labels: clean_labels(labels),
+        created_at,
+        updated_at: created_at,
+    };
+    if annotation.is_empty() {
+        return Err("An annotation needs a note, a rating or a label".to_string());
+    }
+
+    with_session(app, session_id, |metadata| {
+        if !has_message(metadata, message_id) {
+            return Err(format!("Message not found: {message_id}"));
+        }
+        metadata.annotations.push(annotation.clone());
+        Ok(annotation)
+    })
+}
+
+/// Change an annotation
+pub fn update(
+    app: &impl PathProvider,
+    session_id: &str,
+    annotation_id: &str,
+    changes: AnnotationUpdate,
+) -> Result<MessageAnnotation, String> {
+    with_session(app, session_id, |metadata| {
+        let annotation = metadata
+            .annotations
+            .iter_mut()
+            .find(|a| a.id == annotation_id)
+            .ok_or_else(|| format!("Annotation not found: {annotation_id}"))?;
+        let mut updated = annotation.clone();
+        if let Some(note) = changes.note {
+            updated.note = clean_note(Some(note));
+        }
+        if changes.rating.is_some() || changes.clear_rating {
+            updated.rating = changes.rating;
+        }
+        if let Some(labels) = changes.labels {
+            updated.labels = clean_labels(labels);
+        }
+        if updated.is_empty() {
+            return Err(
+                "An annotation needs a note, a rating or a label; delete it instead".to_string(),
+            );
+        }
+        updated.updated_at = now();
+        *annotation = updated.clone();
+        Ok(updated)
+    })
+}
+
+/// Remove an annotation; Ok(false) if it didn't exist
+pub fn delete(
+    app: &impl PathProvider,
+    session_id: &str,
+    annotation_id: &str,
+) -> Result<bool, String> {
+    with_session(app, session_id, |metadata| {
+        let before = metadata.annotations.len();
+        metadata.annotations.retain(|a| a.id != annotation_id);
+        Ok(metadata.annotations.len() != before)
+    })
+}
+
+/// A session's annotations, optionally only those on one message
+pub fn list(
+    app: &impl PathProvider,
+    session_id: &str,
+    message_id: Option<&str>,
+) -> Result<Vec<MessageAnnotation>, String> {
+    let metadata = load_metadata(app, session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    Ok(metadata
+        .annotations
+        .into_iter()
+        .filter(|a| message_id.is_none_or(|id| a.message_id == id))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::types::{RunEntry, RunStatus};
+    use crate::test_support::runtime::TempPaths;
+
+    fn session_with_run(paths: &TempPaths) {
+        with_metadata_mut(paths, "s1", "w1", "Session 1", 0, |metadata| {
+            metadata.runs.push(RunEntry {
+                run_id: "r1".to_string(),
+                user_message_id: "u1".to_string(),
+                user_message: "Fix the bug".to_string(),
+                model: None,
+                execution_mode: None,
+                thinking_level: None,
+                effort_level: None,
+                started_at: 0,
+                ended_at: Some(1),
+                status: RunStatus::Completed,
+                assistant_message_id: Some("a1".to_string()),
+                cancelled: false,
+                recovered: false,
+                claude_session_id: None,
+                pid: None,
+                usage: None,
+                attribution: None,
+                subproject: None,
+            });
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_annotation_crud() {
+        let paths = TempPaths::new();
+        session_with_run(&paths);
+
+        let labels = vec![" Hallucination ".to_string(), "hallucination".to_string()];
+        let added = add(&paths, "s1", "a1", None, Some(Rating::Down), labels).unwrap();
+        assert_eq!(added.labels, ["hallucination"]);
+        add(
+            &paths,
+            "s1",
+            "u1",
+            Some("clear ask".to_string()),
+            None,
+            vec![],
+        )
+        .unwrap();
+        assert_eq!(list(&paths, "s1", None).unwrap().len(), 2);
+        assert_eq!(list(&paths, "s1", Some("a1")).unwrap(), [added.clone()]);
+
+        let changes = AnnotationUpdate {
+            note: Some("invented an API".to_string()),
+            clear_rating: true,
+            ..Default::default()
+        };
+        let updated = update(&paths, "s1", &added.id, changes).unwrap();
+        assert_eq!(updated.note.as_deref(), Some("invented an API"));
+        assert_eq!(updated.rating, None);
+        assert_eq!(updated.labels, ["hallucination"]);
+
+        assert!(delete(&paths, "s1", &added.id).unwrap());
+        assert!(!delete(&paths, "s1", &added.id).unwrap());
+        assert!(list(&paths, "s1", Some("a1")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_annotation_validation() {
+        let paths = TempPaths::new();
+        session_with_run(&paths);
+
+        // Nothing to record
+        assert!(add(&paths, "s1", "a1", Some("  ".to_string()), None, vec![]).is_err());
+        assert!(add(&paths, "s1", "missing", None, Some(Rating::Up), vec![]).is_err());
+        assert!(add(&paths, "nope", "a1", None, Some(Rating::Up), vec![]).is_err());
+
+        let added = add(&paths, "s1", "a1", None, Some(Rating::Up), vec![]).unwrap();
+        let clear_all = AnnotationUpdate {
+            clear_rating: true,
+            ..Default::default()
+        };
+        assert!(update(&paths, "s1", &added.id, clear_all).is_err());
+    }
+}
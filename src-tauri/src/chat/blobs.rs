@@ -0,0 +1,258 @@
+//! Compressed side files for oversized run log lines.
+//!
+//! A single tool result (a big `cat`, a test log) can make one run log line
+//! several megabytes. `compact_storage` moves lines over `BLOB_THRESHOLD` into
+//! zstd-compressed files under `sessions/data/{session_id}/blobs/`, named by
+//! the SHA-256 of the line, and leaves a `{"_blob":..., "bytes":...}`
+//! placeholder in the log. `read_run_log` expands placeholders again, so the
+//! rest of the storage API never sees them.
+//!
+//! Only finished runs are compacted: running ones are still being appended
+//! to and tailed by byte offset.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::runtime::PathProvider;
+
+use super::storage::{get_session_dir, list_all_session_ids, load_metadata};
+use super::types::RunStatus;
+
+/// Lines longer than this (in bytes) are moved into blobs
+pub const BLOB_THRESHOLD: usize = 64 * 1024;
+
+/// zstd level; a little slower than the default but logs are written once
+const COMPRESSION_LEVEL: i32 = 9;
+
+const BLOB_DIR: &str = "blobs";
+const PLACEHOLDER_PREFIX: &str = r#"{"_blob":"#;
+
+/// Placeholder line left in a run log for a compressed line
+#[derive(Debug, Serialize, Deserialize)]
+struct BlobRef {
+    #[serde(rename = "_blob")]
+    hash: String,
+    bytes: usize,
+}
+
+/// Result of `compact_storage`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageCompaction {
+    pub sessions_scanned: u32,
+    pub runs_compacted: u32,
+    pub blobs_written: u32,
+    /// Size of the compacted run logs before compaction
+    pub bytes_before: u64,
+    /// Size of the rewritten run logs plus the blobs written for them
+    pub bytes_after: u64,
+    pub bytes_reclaimed: u64,
+}
+
+fn blob_path(session_dir: &Path, hash: &str) -> PathBuf {
+    session_dir.join(BLOB_DIR).join(format!("{hash}.zst"))
+}
+
+/// Replace a blob placeholder with the line it stands for; other lines are
+/// returned unchanged
+pub(crate) fn expand_line(session_dir: &Path, line: String) -> Result<String, String> {
+    if !line.starts_with(PLACEHOLDER_PREFIX) {
+        return Ok(line);
+    }
+    let Ok(blob) = serde_json::from_str::<BlobRef>(&line) else {
+        return Ok(line);
+    };
+    // Hashes are hex; anything else would let a log line point outside blobs/
+    if !blob.hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid blob reference: {}", blob.hash));
+    }
+
+    let compressed = fs::read(blob_path(session_dir, &blob.hash))
+        .map_err(|e| format!("Failed to read blob {}: {e}", blob.hash))?;
+    let bytes = zstd::decode_all(compressed.as_slice())
+        .map_err(|e| format!("Failed to decompress blob {}: {e}", blob.hash))?;
+    String::from_utf8(bytes).map_err(|e| format!("Blob {} isn't valid UTF-8: {e}", blob.hash))
+}
+
+/// Move the oversized lines of one run log into blobs.
+/// Returns (bytes before, bytes after, blobs written), or None if nothing
+/// in the log needed compacting.
+fn compact_run_log(
+    session_dir: &Path,
+    run_id: &str,
+    threshold: usize,
+) -> Result<Option<(u64, u64, u32)>, String> {
+    let path = session_dir.join(format!("{run_id}.jsonl"));
+    let Ok(file) = fs::File::open(&path) else {
+        return Ok(None);
+    };
+    let lines: Vec<String> = BufReader::new(file)
+        .lines()
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to read run log: {e}"))?;
+    if !lines
+        .iter()
+        .any(|l| l.len() > threshold && !l.starts_with(PLACEHOLDER_PREFIX))
+    {
+        return Ok(None);
+    }
+
+    let bytes_before = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    let mut blob_bytes = 0;
+    let mut blobs_written = 0;
+    let mut out = String::new();
+    for line in lines {
+        if line.len() <= threshold || line.starts_with(PLACEHOLDER_PREFIX) {
+            out.push_str(&line);
+            out.push('\n');
+            continue;
+        }
+
+        let hash = format!("{:x}", Sha256::digest(line.as_bytes()));
+        let blob_file = blob_path(session_dir, &hash);
+        if !blob_file.exists() {
+            let compressed = zstd::encode_all(line.as_bytes(), COMPRESSION_LEVEL)
+                .map_err(|e| format!("Failed to compress run log line: {e}"))?;
+            if compressed.len() >= line.len() {
+                out.push_str(&line);
+                out.push('\n');
+                continue;
+            }
+            fs::create_dir_all(session_dir.join(BLOB_DIR))
+                .map_err(|e| format!("Failed to create blob directory: {e}"))?;
+            fs::write(&blob_file, &compressed).map_err(|e| format!("Failed to write blob: {e}"))?;
+            blob_bytes += compressed.len() as u64;
+            blobs_written += 1;
+        }
+
+        let placeholder = BlobRef {
+            hash,
+            bytes: line.len(),
+        };
+        out.push_str(&serde_json::to_string(&placeholder).map_err(|e| e.to_string())?);
+        out.push('\n');
+    }
+
+    // Write to a temp file and rename, so a crash never leaves a half log
+    let tmp = path.with_extension("jsonl.tmp");
+    let mut file = fs::File::create(&tmp).map_err(|e| format!("Failed to create run log: {e}"))?;
+    file.write_all(out.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write run log: {e}"))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace run log: {e}"))?;
+
+    Ok(Some((
+        bytes_before,
+        out.len() as u64 + blob_bytes,
+        blobs_written,
+    )))
+}
+
+/// Compact the run logs of every finished run in every session
+pub fn compact_storage(app: &impl PathProvider) -> Result<StorageCompaction, String> {
+    compact_storage_with_threshold(app, BLOB_THRESHOLD)
+}
+
+fn compact_storage_with_threshold(
+    app: &impl PathProvider,
+    threshold: usize,
+) -> Result<StorageCompaction, String> {
+    let mut report = StorageCompaction::default();
+
+    for session_id in list_all_session_ids(app)? {
+        let Ok(Some(metadata)) = load_metadata(app, &session_id) else {
+            continue;
+        };
+        report.sessions_scanned += 1;
+        let session_dir = get_session_dir(app, &session_id)?;
+
+        for run in &metadata.runs {
+            if matches!(run.status, RunStatus::Running | RunStatus::Resumable) {
+                continue;
+            }
+            match compact_run_log(&session_dir, &run.run_id, threshold) {
+                Ok(Some((before, after, blobs))) => {
+                    report.runs_compacted += 1;
+                    report.blobs_written += blobs;
+                    report.bytes_before += before;
+                    report.bytes_after += after;
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to compact run {}: {e}", run.run_id),
+            }
+        }
+    }
+
+    report.bytes_reclaimed = report.bytes_before.saturating_sub(report.bytes_after);
+    log::info!(
+        "Compacted {} run logs in {} sessions, reclaimed {} bytes",
+        report.runs_compacted,
+        report.sessions_scanned,
+        report.bytes_reclaimed
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::paging::read_tool_output;
+    use crate::chat::run_log::{get_run_log_path, load_session_messages};
+    use crate::chat::transcript::import_transcript;
+    use crate::test_support::runtime::TempPaths;
+
+    fn transcript(output: &str) -> String {
+        [
+            r#"{"type":"header","format":"jean-transcript","version":1,"session_name":"Logs","created_at":1700000000,"exported_at":1700000500}"#.to_string(),
+            r#"{"type":"message","id":"u1","role":"user","content":"Run the tests","timestamp":1700000010}"#.to_string(),
+            r#"{"type":"message","id":"a1","role":"assistant","content":"Done.","timestamp":1700000020,"content_blocks":[{"type":"tool_use","tool_call_id":"t1"}]}"#.to_string(),
+            format!(r#"{{"type":"tool_call","message_id":"a1","id":"t1","name":"Bash","input":{{}},"output":"{output}"}}"#),
+        ]
+        .join("\n")
+    }
+
+    #[test]
+    fn test_compaction_is_transparent() {
+        let paths = TempPaths::new();
+        let output = "test passed\\n".repeat(2_000);
+        let import = import_transcript(&paths, "wt-1", "/tmp/wt-1", &transcript(&output)).unwrap();
+        let session_id = import.session.id;
+        let before = load_session_messages(&paths, &session_id).unwrap();
+
+        let report = compact_storage_with_threshold(&paths, 1024).unwrap();
+        assert_eq!(report.runs_compacted, 1);
+        assert_eq!(report.blobs_written, 1);
+        assert!(report.bytes_reclaimed > 0);
+
+        let metadata = load_metadata(&paths, &session_id).unwrap().unwrap();
+        let log_path = get_run_log_path(&paths, &session_id, &metadata.runs[0].run_id).unwrap();
+        assert!(fs::read_to_string(log_path)
+            .unwrap()
+            .contains(PLACEHOLDER_PREFIX));
+
+        let after = load_session_messages(&paths, &session_id).unwrap();
+        assert_eq!(
+            after[1].tool_calls[0].output,
+            before[1].tool_calls[0].output
+        );
+        let full = read_tool_output(&paths, &session_id, "a1", "t1").unwrap();
+        assert_eq!(full, before[1].tool_calls[0].output);
+
+        // Already compacted logs are left alone
+        let again = compact_storage_with_threshold(&paths, 1024).unwrap();
+        assert_eq!(again.runs_compacted, 0);
+        assert_eq!(again.bytes_reclaimed, 0);
+    }
+
+    #[test]
+    fn test_expand_rejects_paths() {
+        let dir = Path::new("/tmp");
+        let line = r#"{"_blob":"../../etc/passwd","bytes":1}"#.to_string();
+        assert!(expand_line(dir, line).is_err());
+        let plain = r#"{"type":"assistant"}"#.to_string();
+        assert_eq!(expand_line(dir, plain.clone()).unwrap(), plain);
+    }
+}
@@ -0,0 +1,169 @@
+//! Bounded-concurrency batch operations over sessions
+//!
+//! Batch commands run their per-item work here instead of the frontend
+//! issuing one IPC call per session. Progress is reported as one
+//! `bulk:progress` event per finished item, all tagged with the same
+//! operation ID.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::EventSink;
+
+/// Worker threads per batch operation
+pub const BULK_CONCURRENCY: usize = 4;
+
+/// Payload for bulk:progress events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkProgressEvent {
+    pub operation_id: String,
+    /// "delete_sessions", "export_sessions" or "cancel_tasks"
+    pub operation: String,
+    /// Item that just finished
+    pub id: String,
+    pub completed: usize,
+    pub total: usize,
+    /// Set if this item failed
+    pub error: Option<String>,
+}
+
+/// An item a batch operation couldn't process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkFailure {
+    pub id: String,
+    pub error: String,
+}
+
+/// Outcome of a batch operation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkResult {
+    pub operation_id: String,
+    pub succeeded: Vec<String>,
+    pub failed: Vec<BulkFailure>,
+}
+
+/// Run `f` for every item, at most `BULK_CONCURRENCY` at a time
+///
+/// Items within one group run sequentially on the same worker (for work
+/// that must not interleave, e.g. edits to the same worktree index); groups
+/// run in parallel. One item failing doesn't stop the others.
+pub fn run_bulk<F>(
+    events: &(impl EventSink + Sync),
+    operation: &str,
+    groups: Vec<Vec<String>>,
+    f: F,
+) -> BulkResult
+where
+    F: Fn(&str) -> Result<(), String> + Sync,
+{
+    let operation_id = uuid::Uuid::new_v4().to_string();
+    let total: usize = groups.iter().map(Vec::len).sum();
+    log::trace!("Starting bulk {operation} ({operation_id}) for {total} items");
+
+    let next_group = AtomicUsize::new(0);
+    let completed = AtomicUsize::new(0);
+    let result = Mutex::new(BulkResult {
+        operation_id: operation_id.clone(),
+        ..Default::default()
+    });
+
+    std::thread::scope(|scope| {
+        for _ in 0..BULK_CONCURRENCY.min(groups.len()) {
+            scope.spawn(|| {
+                while let Some(group) = groups.get(next_group.fetch_add(1, Ordering::SeqCst)) {
+                    for id in group {
+                        let error = f(id).err();
+                        let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+
+                        if let Ok(mut result) = result.lock() {
+                            match &error {
+                                Some(e) => result.failed.push(BulkFailure {
+                                    id: id.clone(),
+                                    error: e.clone(),
+                                }),
+                                None => result.succeeded.push(id.clone()),
+                            }
+                        }
+
+                        let event = BulkProgressEvent {
+                            operation_id: operation_id.clone(),
+                            operation: operation.to_string(),
+                            id: id.clone(),
+                            completed: done,
+                            total,
+                            error,
+                        };
+                        if let Err(e) = events.emit_all("bulk:progress", &event) {
+                            log::error!("Failed to emit bulk:progress event: {e}");
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    let result = result.into_inner().unwrap_or_default();
+    log::trace!(
+        "Bulk {operation} finished: {} succeeded, {} failed",
+        result.succeeded.len(),
+        result.failed.len()
+    );
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::RecordingSink;
+
+    #[test]
+    fn test_run_bulk_reports_every_item() {
+        let sink = RecordingSink::default();
+        let groups = (0..10).map(|i| vec![format!("id-{i}")]).collect();
+
+        let result = run_bulk(&sink, "test", groups, |id| {
+            if id == "id-3" {
+                Err("boom".to_string())
+            } else {
+                Ok(())
+            }
+        });
+
+        assert_eq!(result.succeeded.len(), 9);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].error, "boom");
+
+        let progress = sink.payloads("bulk:progress");
+        assert_eq!(progress.len(), 10);
+        let mut completed: Vec<u64> = progress
+            .iter()
+            .map(|p| p["completed"].as_u64().unwrap())
+            .collect();
+        completed.sort_unstable();
+        assert_eq!(completed, (1..=10).collect::<Vec<_>>());
+        assert!(progress
+            .iter()
+            .all(|p| p["operation_id"] == result.operation_id.as_str() && p["total"] == 10));
+    }
+
+    #[test]
+    fn test_run_bulk_keeps_groups_sequential() {
+        let sink = RecordingSink::default();
+        let order = Mutex::new(Vec::new());
+        let groups = vec![
+            vec!["a1".to_string(), "a2".to_string(), "a3".to_string()],
+            vec!["b1".to_string()],
+        ];
+
+        run_bulk(&sink, "test", groups, |id| {
+            order.lock().unwrap().push(id.to_string());
+            Ok(())
+        });
+
+        let order = order.into_inner().unwrap();
+        let a: Vec<&String> = order.iter().filter(|id| id.starts_with('a')).collect();
+        assert_eq!(a, ["a1", "a2", "a3"]);
+    }
+}
@@ -0,0 +1,321 @@
+//! Terminal recordings of agent runs (asciinema .cast)
+//!
+//! `export_session_cast` renders a session's stored CLI event stream as the
+//! terminal output a user would have watched: prompts, the agent's text,
+//! commands and tool calls with their (truncated) results. Events keep the
+//! original timing recovered by the replay module, and the recording is
+//! written in asciicast v2 so it plays in asciinema-player, `asciinema play`,
+//! or any docs site that embeds them.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::replay::{load_replay_items, ReplayItem};
+use super::storage::load_metadata;
+use crate::runtime::PathProvider;
+
+/// Terminal size written to the recording header
+const CAST_WIDTH: u32 = 120;
+const CAST_HEIGHT: u32 = 40;
+
+/// Players compress pauses longer than this (seconds)
+const IDLE_TIME_LIMIT: f64 = 5.0;
+
+/// Tool result lines shown before "… N more lines"
+const MAX_RESULT_LINES: usize = 12;
+
+/// Longest one-line tool argument summary, in characters
+const MAX_SUMMARY_CHARS: usize = 100;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+
+/// A written recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CastExport {
+    pub path: String,
+    /// Output frames in the recording
+    pub frames: usize,
+    /// Length of the recording before idle compression (seconds)
+    pub duration_secs: f64,
+}
+
+/// Terminal line endings (players don't translate bare `\n`)
+fn crlf(text: &str) -> String {
+    text.trim_end().replace("\r\n", "\n").replace('\n', "\r\n")
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > max_chars || text.lines().nth(1).is_some() {
+        let cut: String = line.chars().take(max_chars).collect();
+        format!("{cut}…")
+    } else {
+        line.to_string()
+    }
+}
+
+/// One-line summary of a tool call's input
+fn tool_summary(input: &Value) -> String {
+    [
+        "file_path",
+        "path",
+        "pattern",
+        "url",
+        "query",
+        "description",
+    ]
+    .iter()
+    .find_map(|key| input.get(*key).and_then(Value::as_str))
+    .or_else(|| input.as_object()?.values().find_map(Value::as_str))
+    .map(|s| truncate(s, MAX_SUMMARY_CHARS))
+    .unwrap_or_default()
+}
+
+/// Text of a tool_result block (string or list of text blocks)
+fn result_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn render_result(block: &Value) -> String {
+    let text = result_text(block.get("content").unwrap_or(&Value::Null));
+    let color = if block.get("is_error").and_then(Value::as_bool) == Some(true) {
+        RED
+    } else {
+        DIM
+    };
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut out = String::new();
+    for line in lines.iter().take(MAX_RESULT_LINES) {
+        out.push_str(&format!("{color}  {line}{RESET}\r\n"));
+    }
+    if lines.len() > MAX_RESULT_LINES {
+        out.push_str(&format!(
+            "{DIM}  … {} more lines{RESET}\r\n",
+            lines.len() - MAX_RESULT_LINES
+        ));
+    }
+    out
+}
+
+/// Terminal output for one stream-json event (empty for events with none)
+pub fn render_event(event: &Value) -> String {
+    let blocks = || {
+        event
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+    };
+    match event.get("type").and_then(Value::as_str) {
+        Some("assistant") => {
+            let mut out = String::new();
+            for block in blocks() {
+                match block.get("type").and_then(Value::as_str) {
+                    Some("text") => {
+                        let text = block.get("text").and_then(Value::as_str).unwrap_or("");
+                        if !text.trim().is_empty() {
+                            out.push_str(&format!("\r\n{}\r\n", crlf(text)));
+                        }
+                    }
+                    Some("tool_use") => {
+                        let name = block.get("name").and_then(Value::as_str).unwrap_or("Tool");
+                        let input = block.get("input").unwrap_or(&Value::Null);
+                        match input.get("command").and_then(Value::as_str) {
+                            Some(command) if name == "Bash" => out.push_str(&format!(
+                                "\r\n{BOLD}{GREEN}${RESET} {}\r\n",
+                                crlf(command)
+                            )),
+                            _ => out.push_str(&format!(
+                                "\r\n{BOLD}{CYAN}● {name}{RESET}({})\r\n",
+                                tool_summary(input)
+                            )),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            out
+        }
+        Some("user") => blocks()
+            .filter(|b| b.get("type").and_then(Value::as_str) == Some("tool_result"))
+            .map(render_result)
+            .collect(),
+        Some("result") => {
+            let failed = event.get("is_error").and_then(Value::as_bool) == Some(true);
+            let seconds = event
+                .get("duration_ms")
+                .and_then(Value::as_u64)
+                .map(|ms| format!(" in {:.1}s", ms as f64 / 1000.0))
+                .unwrap_or_default();
+            if failed {
+                format!("\r\n{RED}✗ Run failed{seconds}{RESET}\r\n")
+            } else {
+                format!("\r\n{DIM}✓ Done{seconds}{RESET}\r\n")
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+/// The prompt line shown at the start of a run
+fn render_prompt(message: &str) -> String {
+    format!("\r\n{BOLD}> {RESET}{}\r\n", crlf(message))
+}
+
+/// asciicast v2: a header line, then `[seconds, "o", text]` per frame
+pub fn build_cast(title: &str, timestamp: u64, frames: &[(u64, String)]) -> String {
+    let header = json!({
+        "version": 2,
+        "width": CAST_WIDTH,
+        "height": CAST_HEIGHT,
+        "timestamp": timestamp,
+        "idle_time_limit": IDLE_TIME_LIMIT,
+        "title": title,
+        "env": { "TERM": "xterm-256color" },
+    });
+    let mut out = header.to_string();
+    out.push('\n');
+    for (offset_ms, text) in frames {
+        let frame = json!([*offset_ms as f64 / 1000.0, "o", text]);
+        out.push_str(&frame.to_string());
+        out.push('\n');
+    }
+    out
+}
+
+/// Terminal output frames for a session's runs, with offsets in milliseconds
+fn session_frames(prompts: &[(String, String)], items: &[ReplayItem]) -> Vec<(u64, String)> {
+    let mut started: HashSet<&str> = HashSet::new();
+    let mut frames = Vec::new();
+    for item in items {
+        if started.insert(item.run_id.as_str()) {
+            if let Some((_, prompt)) = prompts.iter().find(|(id, _)| *id == item.run_id) {
+                frames.push((item.offset_ms, render_prompt(prompt)));
+            }
+        }
+        let text = render_event(&item.event);
+        if !text.is_empty() {
+            frames.push((item.offset_ms, text));
+        }
+    }
+    frames
+}
+
+/// Write a session's runs to `path` as an asciicast v2 recording
+pub fn export_cast(
+    app: &impl PathProvider,
+    session_id: &str,
+    path: &Path,
+) -> Result<CastExport, String> {
+    let metadata = load_metadata(app, session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    let prompts: Vec<(String, String)> = metadata
+        .runs
+        .iter()
+        .map(|r| (r.run_id.clone(), r.user_message.clone()))
+        .collect();
+    let items = load_replay_items(app, session_id)?;
+    if items.is_empty() {
+        return Err("This session has no recorded output".to_string());
+    }
+    let frames = session_frames(&prompts, &items);
+    let timestamp = metadata.runs.first().map_or(0, |r| r.started_at);
+    fs::write(path, build_cast(&metadata.name, timestamp, &frames))
+        .map_err(|e| format!("Failed to write recording: {e}"))?;
+
+    Ok(CastExport {
+        path: path.to_string_lossy().into_owned(),
+        frames: frames.len(),
+        duration_secs: frames.last().map_or(0.0, |(ms, _)| *ms as f64 / 1000.0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_event() {
+        let assistant = json!({
+            "type": "assistant",
+            "message": { "content": [
+                { "type": "text", "text": "Running the tests.\nOne moment." },
+                { "type": "tool_use", "id": "t1", "name": "Bash", "input": { "command": "cargo test" } },
+                { "type": "tool_use", "id": "t2", "name": "Read", "input": { "file_path": "src/lib.rs" } },
+            ]}
+        });
+        let out = render_event(&assistant);
+        assert!(out.contains("Running the tests.\r\nOne moment.\r\n"));
+        assert!(out.contains(&format!("{BOLD}{GREEN}${RESET} cargo test\r\n")));
+        assert!(out.contains(&format!("{BOLD}{CYAN}● Read{RESET}(src/lib.rs)")));
+
+        let output: String = (1..=15).map(|i| format!("line {i}\n")).collect();
+        let user = json!({
+            "type": "user",
+            "message": { "content": [
+                { "type": "tool_result", "tool_use_id": "t1", "content": output },
+            ]}
+        });
+        let out = render_event(&user);
+        assert_eq!(out.lines().count(), MAX_RESULT_LINES + 1);
+        assert!(out.contains("… 3 more lines"));
+
+        assert_eq!(render_event(&json!({ "type": "system" })), "");
+        assert!(
+            render_event(&json!({ "type": "result", "duration_ms": 2500 }))
+                .contains("Done in 2.5s")
+        );
+    }
+
+    #[test]
+    fn test_build_cast() {
+        let items = vec![
+            ReplayItem {
+                run_id: "r1".to_string(),
+                offset_ms: 0,
+                event: json!({ "type": "system", "subtype": "init" }),
+            },
+            ReplayItem {
+                run_id: "r1".to_string(),
+                offset_ms: 1500,
+                event: json!({ "type": "result", "duration_ms": 1500 }),
+            },
+        ];
+        let prompts = vec![("r1".to_string(), "Fix the build".to_string())];
+        let frames = session_frames(&prompts, &items);
+        assert_eq!(frames.len(), 2);
+
+        let cast = build_cast("Fix build", 1_700_000_000, &frames);
+        let lines: Vec<Value> = cast
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["title"], "Fix build");
+        assert_eq!(lines[1][0], 0.0);
+        assert_eq!(lines[1][1], "o");
+        assert!(lines[1][2].as_str().unwrap().contains("Fix the build"));
+        assert_eq!(lines[2][0], 1.5);
+    }
+}
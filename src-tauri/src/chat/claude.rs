@@ -1,13 +1,13 @@
-use tauri::Manager;
-
 use super::types::{
     CompactMetadata, ContentBlock, EffortLevel, ThinkingLevel, ToolCall, UsageData,
 };
-use crate::http_server::EmitExt;
-use crate::projects::github_issues::{
-    get_github_contexts_dir, get_session_issue_refs, get_session_pr_refs,
+use crate::cli_capabilities::args::{ArgBuilder, ArgError};
+use crate::cli_capabilities::{
+    self, CLAUDE_OPTIONAL_FLAGS, CLAUDE_PLAN_MODE_RESERVED_FLAGS, CLAUDE_RESERVED_FLAGS,
+    CLAUDE_RULES,
 };
 use crate::projects::storage::load_projects_data;
+use crate::runtime::{EmitTarget, EventSink, PathProvider};
 
 // =============================================================================
 // Claude CLI execution
@@ -142,7 +142,7 @@ struct CompactedEvent {
 ///
 /// Returns a tuple of (args, env_vars) where env_vars are (key, value) pairs.
 #[allow(clippy::too_many_arguments)]
-fn build_claude_args(
+pub(super) fn build_claude_args(
     app: &tauri::AppHandle,
     session_id: &str,
     worktree_id: &str,
@@ -158,23 +158,27 @@ fn build_claude_args(
     mcp_config: Option<&str>,
     chrome_enabled: bool,
     custom_profile_settings: Option<&str>,
-) -> (Vec<String>, Vec<(String, String)>) {
-    let mut args = Vec::new();
-    let mut env_vars = Vec::new();
+) -> Result<(Vec<String>, Vec<(String, String)>), Vec<ArgError>> {
+    // Flags are checked against what the installed CLI version supports
+    let capabilities = crate::claude_cli::get_cli_binary_path(app)
+        .map(|cli_path| cli_capabilities::probe(app, "claude", &cli_path, CLAUDE_OPTIONAL_FLAGS))
+        .unwrap_or_default();
+    let mut args = ArgBuilder::new(&capabilities, CLAUDE_OPTIONAL_FLAGS);
+    // Launch defaults (global, project, profile) come first so Jean's own
+    // variables win; their arguments are added last
+    let defaults = super::launch::resolve(app, worktree_id, custom_profile_settings);
+    let mut env_vars: Vec<(String, String)> = defaults.env.into_iter().collect();
 
     // Core args
-    args.push("--print".to_string());
-    args.push("--output-format".to_string());
-    args.push("stream-json".to_string());
-    args.push("--input-format".to_string());
-    args.push("stream-json".to_string());
-    args.push("--verbose".to_string());
+    args.flag("--print");
+    args.option("--output-format", "stream-json");
+    args.option("--input-format", "stream-json");
+    args.flag("--verbose");
 
     // Add app data directories
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
+    if let Ok(app_data_dir) = app.app_data_dir() {
         if cfg!(debug_assertions) {
-            args.push("--add-dir".to_string());
-            args.push(app_data_dir.to_string_lossy().to_string());
+            args.option("--add-dir", app_data_dir.to_string_lossy().to_string());
         } else {
             for subdir in [
                 "pasted-images",
@@ -183,13 +187,14 @@ fn build_claude_args(
                 "git-context",
                 "combined-contexts",
             ] {
-                args.push("--add-dir".to_string());
-                args.push(app_data_dir.join(subdir).to_string_lossy().to_string());
+                args.option(
+                    "--add-dir",
+                    app_data_dir.join(subdir).to_string_lossy().to_string(),
+                );
             }
             // Add session-specific runs directory
             let session_runs_dir = app_data_dir.join("runs").join(session_id);
-            args.push("--add-dir".to_string());
-            args.push(session_runs_dir.to_string_lossy().to_string());
+            args.option("--add-dir", session_runs_dir.to_string_lossy().to_string());
         }
     }
 
@@ -199,16 +204,14 @@ fn build_claude_args(
         for subdir in ["skills", "commands"] {
             let dir_path = claude_dir.join(subdir);
             if dir_path.exists() {
-                args.push("--add-dir".to_string());
-                args.push(dir_path.to_string_lossy().to_string());
+                args.option("--add-dir", dir_path.to_string_lossy().to_string());
             }
         }
     }
 
     // Model
     if let Some(m) = model {
-        args.push("--model".to_string());
-        args.push(m.to_string());
+        args.option("--model", m);
     }
 
     // Permission mode
@@ -217,8 +220,7 @@ fn build_claude_args(
         "yolo" => "bypassPermissions",
         _ => "plan",
     };
-    args.push("--permission-mode".to_string());
-    args.push(perm_mode.to_string());
+    args.option("--permission-mode", perm_mode);
 
     // Thinking/Effort configuration
     // If disable_thinking_in_non_plan_modes is true and mode is build/yolo, force off
@@ -230,6 +232,10 @@ fn build_claude_args(
     // Build settings JSON: start with custom profile settings (if any), then merge thinking/effort
     let mut settings_json: Option<serde_json::Value> =
         custom_profile_settings.and_then(|s| serde_json::from_str(s).ok());
+    // The profile's launch defaults are Jean's, not Claude settings
+    if let Some(map) = settings_json.as_mut().and_then(|v| v.as_object_mut()) {
+        map.remove(super::launch::PROFILE_KEY);
+    }
 
     if let Some(effort) = effort_level {
         // Opus 4.6 adaptive thinking: use effort parameter via --settings JSON
@@ -274,15 +280,13 @@ fn build_claude_args(
 
     // Emit --settings if we have any settings to pass
     if let Some(settings) = &settings_json {
-        args.push("--settings".to_string());
-        args.push(settings.to_string());
+        args.option("--settings", settings.to_string());
     }
 
     // Allowed tools
     if let Some(tools) = allowed_tools {
         for tool in tools {
-            args.push("--allowedTools".to_string());
-            args.push(tool.clone());
+            args.option("--allowedTools", tool.clone());
         }
     }
 
@@ -290,24 +294,26 @@ fn build_claude_args(
     // Claude wraps paths with spaces in quotes, so the actual command is:
     // "/Users/.../Application Support/.../gh-cli/gh" --version
     // Use *gh-cli/gh* to match regardless of quoting
-    args.push("--allowedTools".to_string());
-    args.push("Bash(*gh-cli/gh*)".to_string());
-    args.push("--allowedTools".to_string());
-    args.push("Bash(*claude-cli/claude*)".to_string());
+    args.option("--allowedTools", "Bash(*gh-cli/gh*)");
+    args.option("--allowedTools", "Bash(*claude-cli/claude*)");
+
+    // Project domain blocklist. Deny rules win over allow rules and yolo mode,
+    // and the flag isn't optional so an old CLI fails instead of fetching.
+    for rule in crate::audit::deny_rules(&crate::audit::blocked_domains(app, worktree_id)) {
+        args.option("--disallowedTools", rule);
+    }
 
     // MCP server configuration
     if let Some(config) = mcp_config {
         if !config.is_empty() {
-            args.push("--mcp-config".to_string());
-            args.push(config.to_string());
+            args.option("--mcp-config", config);
 
             // Auto-allow all tools from configured MCP servers
             // Pattern "mcp__<name>" matches all tools from that server
             if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(config) {
                 if let Some(servers) = parsed.get("mcpServers").and_then(|v| v.as_object()) {
                     for server_name in servers.keys() {
-                        args.push("--allowedTools".to_string());
-                        args.push(format!("mcp__{server_name}"));
+                        args.option("--allowedTools", format!("mcp__{server_name}"));
                     }
                 }
             }
@@ -316,7 +322,7 @@ fn build_claude_args(
 
     // Chrome browser integration (beta)
     if chrome_enabled {
-        args.push("--chrome".to_string());
+        args.flag("--chrome");
     }
 
     // Build combined system prompt parts
@@ -374,83 +380,17 @@ fn build_claude_args(
         }
     }
 
-    // Collect all context files (issues and PRs) and concatenate into a single file
-    let mut all_context_paths: Vec<std::path::PathBuf> = Vec::new();
-
-    // Check for issue context files (shared storage)
-    if let Ok(issue_keys) = get_session_issue_refs(app, session_id) {
-        if let Ok(contexts_dir) = get_github_contexts_dir(app) {
-            log::debug!(
-                "Checking for issue context files in {:?} for session {}",
-                contexts_dir,
-                session_id
-            );
-            for key in issue_keys {
-                // key format: "{owner}-{repo}-{number}"
-                let parts: Vec<&str> = key.rsplitn(2, '-').collect();
-                if parts.len() == 2 {
-                    let number = parts[0];
-                    let repo_key = parts[1];
-                    let file_path = contexts_dir.join(format!("{repo_key}-issue-{number}.md"));
-                    if file_path.exists() {
-                        log::trace!("Adding issue context file: {:?}", file_path);
-                        all_context_paths.push(file_path);
-                    }
-                }
-            }
-        }
-    }
-
-    // Check for PR context files (shared storage)
-    if let Ok(pr_keys) = get_session_pr_refs(app, session_id) {
-        if let Ok(contexts_dir) = get_github_contexts_dir(app) {
-            for key in pr_keys {
-                let parts: Vec<&str> = key.rsplitn(2, '-').collect();
-                if parts.len() == 2 {
-                    let number = parts[0];
-                    let repo_key = parts[1];
-                    let file_path = contexts_dir.join(format!("{repo_key}-pr-{number}.md"));
-                    if file_path.exists() {
-                        log::trace!("Adding PR context file: {:?}", file_path);
-                        all_context_paths.push(file_path);
-                    }
-                }
-            }
-        }
-    }
-
-    // Check for attached saved context files
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
-        let saved_contexts_dir = app_data_dir.join("session-context");
-        if saved_contexts_dir.exists() {
-            let prefix = format!("{session_id}-context-");
-            if let Ok(entries) = std::fs::read_dir(&saved_contexts_dir) {
-                let mut context_files: Vec<_> = entries
-                    .flatten()
-                    .filter(|entry| {
-                        let name = entry.file_name().to_string_lossy().to_string();
-                        name.starts_with(&prefix) && name.ends_with(".md")
-                    })
-                    .collect();
-
-                context_files.sort_by_key(|e| e.file_name());
-                log::debug!(
-                    "Found {} saved context files for session {}",
-                    context_files.len(),
-                    session_id
-                );
-
-                for entry in context_files {
-                    all_context_paths.push(entry.path());
-                }
-            }
-        }
-    }
+    // Collect all context files (issues, PRs and saved contexts) and concatenate into a single file
+    let all_context_paths: Vec<std::path::PathBuf> =
+        super::context_budget::session_context_files(app, session_id)
+            .into_iter()
+            .map(|(_, _, path)| path)
+            .collect();
 
     // If we have context files OR system prompt parts, create a combined context file
     let has_system_prompts = !system_prompt_parts.is_empty();
     if !all_context_paths.is_empty() || has_system_prompts {
-        if let Ok(app_data_dir) = app.path().app_data_dir() {
+        if let Ok(app_data_dir) = app.app_data_dir() {
             let combined_contexts_dir = app_data_dir.join("combined-contexts");
             let _ = std::fs::create_dir_all(&combined_contexts_dir);
 
@@ -533,16 +473,17 @@ fn build_claude_args(
                     all_context_paths.len(),
                     combined_file
                 );
-                args.push("--append-system-prompt-file".to_string());
-                args.push(combined_file.to_string_lossy().to_string());
+                args.option(
+                    "--append-system-prompt-file",
+                    combined_file.to_string_lossy().to_string(),
+                );
             }
         }
     }
 
     // Resume existing session
     if let Some(claude_sid) = existing_claude_session_id {
-        args.push("--resume".to_string());
-        args.push(claude_sid.to_string());
+        args.option("--resume", claude_sid);
     }
 
     // Disable background tasks - forces all Task sub-agents to run in foreground.
@@ -568,7 +509,32 @@ fn build_claude_args(
         env_vars.push(("JEAN_CLAUDE_SESSION_ID".to_string(), claude_sid.to_string()));
     }
 
-    (args, env_vars)
+    // Managed scratch dir - keeps agent temp files out of /tmp and the repo
+    match super::scratch::get_session_scratch_dir(app, session_id) {
+        Ok(scratch_dir) => env_vars.extend(super::scratch::scratch_env_vars(&scratch_dir)),
+        Err(e) => log::warn!("Failed to prepare scratch directory: {e}"),
+    }
+
+    // Managed tools (rg, fd, ast-grep, Node.js, Python tools) come before the user's PATH
+    if let Some(path) = crate::tool_install::session_path(app) {
+        env_vars.push(("PATH".to_string(), path));
+    }
+
+    add_default_args(&mut args, &defaults.args, perm_mode == "plan");
+
+    Ok((args.build(CLAUDE_RULES)?, env_vars))
+}
+
+/// Add launch default arguments, which can't loosen plan mode
+fn add_default_args(args: &mut ArgBuilder, defaults: &[String], plan_mode: bool) {
+    if plan_mode {
+        args.user_args(
+            defaults,
+            &[CLAUDE_RESERVED_FLAGS, CLAUDE_PLAN_MODE_RESERVED_FLAGS].concat(),
+        );
+    } else {
+        args.user_args(defaults, CLAUDE_RESERVED_FLAGS);
+    }
 }
 
 /// Execute Claude CLI in detached mode.
@@ -596,17 +562,25 @@ pub fn execute_claude_detached(
     mcp_config: Option<&str>,
     chrome_enabled: bool,
     custom_profile_settings: Option<&str>,
+    background_priority: bool,
+    warm_pool: bool,
 ) -> Result<(u32, ClaudeResponse), String> {
     use super::detached::spawn_detached_claude;
-    use crate::claude_cli::get_cli_binary_path;
+    use crate::claude_cli::versions::resolve_binary;
 
     log::trace!("Executing Claude CLI (detached) for session: {session_id}");
     log::trace!("Input file: {input_file:?}");
     log::trace!("Output file: {output_file:?}");
     log::trace!("Working directory: {working_dir:?}");
 
-    // Get CLI path
-    let cli_path = get_cli_binary_path(app).map_err(|e| {
+    // Mock backend: stream a scripted transcript instead of running the CLI
+    if let Some(scenario) = crate::mock::active_scenario() {
+        let pid = crate::mock::start_mock_run(&scenario, output_file)?;
+        return register_and_tail(app, session_id, worktree_id, output_file, pid);
+    }
+
+    // Get CLI path (the project's pinned version if it has one)
+    let cli_path = resolve_binary(app, worktree_id, working_dir).map_err(|e| {
         let error_msg =
             format!("Failed to get CLI path: {e}. Please complete setup in Settings > Advanced.");
         log::error!("{error_msg}");
@@ -649,7 +623,39 @@ pub fn execute_claude_detached(
         mcp_config,
         chrome_enabled,
         custom_profile_settings,
-    );
+    )
+    .map_err(|errors| {
+        // Reject invalid or unsupported options before spawning anything
+        let error_msg = cli_capabilities::args::describe_errors("Claude CLI", &errors);
+        log::error!("{error_msg}");
+        let error_event = ErrorEvent {
+            session_id: session_id.to_string(),
+            worktree_id: worktree_id.to_string(),
+            error: error_msg.clone(),
+        };
+        let _ = app.emit_all("chat:error", &error_event);
+        error_msg
+    })?;
+
+    // Refuse prompts that can't fit the model's context (or the sub-project's
+    // budget), reporting what to cut
+    let message = std::fs::read_to_string(input_file).unwrap_or_default();
+    let attachments = super::context_budget::session_attachments(app, session_id, &message);
+    let limit = crate::projects::subprojects::for_session(app, session_id, worktree_id)
+        .and_then(|(subproject, _)| subproject.context_budget);
+    if let Err(report) =
+        super::context_budget::check_within(model.unwrap_or("default"), attachments, limit)
+    {
+        super::context_budget::emit_over_budget(app, Some(session_id), &report);
+        let error_msg = report.describe();
+        let error_event = ErrorEvent {
+            session_id: session_id.to_string(),
+            worktree_id: worktree_id.to_string(),
+            error: error_msg.clone(),
+        };
+        let _ = app.emit_all("chat:error", &error_event);
+        return Err(error_msg);
+    }
 
     // Log the full Claude CLI command for debugging
     log::debug!(
@@ -658,37 +664,112 @@ pub fn execute_claude_detached(
         args.join(" ")
     );
 
-    // Convert env_vars to &str references for spawn_detached_claude
-    let env_refs: Vec<(&str, &str)> = env_vars
-        .iter()
-        .map(|(k, v)| (k.as_str(), v.as_str()))
-        .collect();
-
-    // Spawn detached process
-    let pid = spawn_detached_claude(
-        &cli_path,
-        &args,
-        input_file,
-        output_file,
-        working_dir,
-        &env_refs,
-    )
-    .map_err(|e| {
-        let error_msg = format!("Failed to start Claude CLI: {e}");
-        log::error!("{error_msg}");
-        let _ = app.emit_all(
-            "chat:error",
-            &ErrorEvent {
-                session_id: session_id.to_string(),
-                worktree_id: worktree_id.to_string(),
-                error: error_msg.clone(),
-            },
-        );
-        error_msg
-    })?;
+    // New conversations can adopt (and then replenish) a pre-warmed CLI
+    let warm_settings = (warm_pool && existing_claude_session_id.is_none()).then(|| {
+        super::warm_pool::WarmSettings {
+            worktree_id: worktree_id.to_string(),
+            working_dir: working_dir.to_path_buf(),
+            model: model.map(str::to_string),
+            execution_mode: execution_mode.map(str::to_string),
+            thinking_level: thinking_level.cloned(),
+            effort_level: effort_level.cloned(),
+            allowed_tools: allowed_tools.map(<[String]>::to_vec),
+            disable_thinking_in_non_plan_modes,
+            parallel_execution_prompt: parallel_execution_prompt.map(str::to_string),
+            ai_language: ai_language.map(str::to_string),
+            mcp_config: mcp_config.map(str::to_string),
+            chrome_enabled,
+            custom_profile_settings: custom_profile_settings.map(str::to_string),
+        }
+    });
+    let adopted = warm_settings.as_ref().and_then(|settings| {
+        super::warm_pool::adopt(
+            app,
+            settings,
+            &cli_path,
+            &args,
+            &env_vars,
+            session_id,
+            input_file,
+            output_file,
+        )
+    });
+
+    let pid = match adopted {
+        Some(pid) => pid,
+        None => {
+            // Convert env_vars to &str references for spawn_detached_claude
+            let env_refs: Vec<(&str, &str)> = env_vars
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect();
+
+            // Spawn detached process
+            spawn_detached_claude(
+                &cli_path,
+                &args,
+                input_file,
+                output_file,
+                working_dir,
+                &env_refs,
+            )
+            .map_err(|e| {
+                let error_msg = format!("Failed to start Claude CLI: {e}");
+                log::error!("{error_msg}");
+                let _ = app.emit_all(
+                    "chat:error",
+                    &ErrorEvent {
+                        session_id: session_id.to_string(),
+                        worktree_id: worktree_id.to_string(),
+                        error: error_msg.clone(),
+                    },
+                );
+                error_msg
+            })?
+        }
+    };
+
+    if let Some(settings) = warm_settings {
+        super::warm_pool::refill(app.clone(), settings);
+    }
+
+    // Record what a new conversation started with, off the spawn path
+    if existing_claude_session_id.is_none() {
+        let app = app.clone();
+        let session_id = session_id.to_string();
+        let working_dir = working_dir.to_path_buf();
+        let model = model.map(str::to_string);
+        let args = args.clone();
+        let env_vars = env_vars.clone();
+        crate::background_tasks::supervisor::spawn_thread("session-environment", move || {
+            let snapshot =
+                super::environment::capture(&app, &working_dir, model.as_deref(), &args, &env_vars);
+            if let Err(e) = super::environment::record(&app, &session_id, snapshot) {
+                log::warn!("Failed to record environment for session {session_id}: {e}");
+            }
+        });
+    }
 
     log::trace!("Detached Claude CLI spawned with PID: {pid}");
 
+    // Lower scheduling priority for batch runs so they don't compete with interactive work
+    if background_priority {
+        if let Err(e) = crate::platform::set_background_priority(pid) {
+            log::warn!("Failed to apply background priority to PID {pid}: {e}");
+        }
+    }
+
+    register_and_tail(app, session_id, worktree_id, output_file, pid)
+}
+
+/// Register a running process for cancellation and tail its output file
+fn register_and_tail(
+    app: &tauri::AppHandle,
+    session_id: &str,
+    worktree_id: &str,
+    output_file: &std::path::Path,
+    pid: u32,
+) -> Result<(u32, ClaudeResponse), String> {
     // Register the process for cancellation
     super::registry::register_process(session_id.to_string(), pid);
 
@@ -722,7 +803,7 @@ pub fn execute_claude_detached(
 /// - The process is no longer running and no new output (timeout)
 /// - An error occurs
 pub fn tail_claude_output(
-    app: &tauri::AppHandle,
+    app: &impl EventSink,
     session_id: &str,
     worktree_id: &str,
     output_file: &std::path::Path,
@@ -737,6 +818,9 @@ pub fn tail_claude_output(
 
     // Create tailer starting from beginning (we want all content)
     let mut tailer = NdjsonTailer::new_from_start(output_file)?;
+    let mut timing = super::timeline::TimingRecorder::new(output_file);
+    // Streaming output only goes to windows showing this session
+    let target = EmitTarget::Session(session_id.to_string());
 
     let mut full_content = String::new();
     let mut claude_session_id = String::new();
@@ -754,6 +838,8 @@ pub fn tail_claude_output(
     let dead_process_timeout = Duration::from_secs(2);
     let started_at = Instant::now();
     let mut last_output_time = Instant::now();
+    // Non-JSON lines (CLI stderr), kept for crash reports if the process dies
+    let mut stderr_tail: std::collections::VecDeque<String> = std::collections::VecDeque::new();
     let mut received_claude_output = false; // Track if we've received any Claude output (not our metadata)
 
     loop {
@@ -786,6 +872,11 @@ pub fn tail_claude_output(
                 Ok(m) => m,
                 Err(e) => {
                     log::trace!("Failed to parse line: {e}");
+                    crate::quota::observe(app, "claude", Some(session_id), &line);
+                    if stderr_tail.len() == 20 {
+                        stderr_tail.pop_front();
+                    }
+                    stderr_tail.push_back(line);
                     continue;
                 }
             };
@@ -797,6 +888,18 @@ pub fn tail_claude_output(
                 }
             }
 
+            // Report message and block types this version of Jean doesn't know;
+            // the line itself stays in the output file
+            crate::agent_protocol::drift::report(
+                app,
+                "claude",
+                Some(session_id),
+                crate::agent_protocol::claude::schema_drift(&msg, &line),
+            );
+
+            // Record tool start/end times for the session timeline
+            timing.observe(&msg);
+
             // Track parent_tool_use_id for sub-agent tool calls
             // Must reset to None for root-level messages, otherwise parallel Tasks get wrong parent
             let current_parent_tool_use_id = msg
@@ -835,7 +938,9 @@ pub fn tail_claude_output(
                                                 worktree_id: worktree_id.to_string(),
                                                 content: text.to_string(),
                                             };
-                                            if let Err(e) = app.emit_all("chat:chunk", &event) {
+                                            if let Err(e) =
+                                                app.emit_to(&target, "chat:chunk", &event)
+                                            {
                                                 log::error!("Failed to emit chunk: {e}");
                                             }
                                         }
@@ -877,7 +982,9 @@ pub fn tail_claude_output(
                                             input: input.clone(),
                                             parent_tool_use_id: current_parent_tool_use_id.clone(),
                                         };
-                                        if let Err(e) = app.emit_all("chat:tool_use", &event) {
+                                        if let Err(e) =
+                                            app.emit_to(&target, "chat:tool_use", &event)
+                                        {
                                             log::error!("Failed to emit tool_use: {e}");
                                         }
 
@@ -888,7 +995,7 @@ pub fn tail_claude_output(
                                             tool_call_id: id.clone(),
                                         };
                                         if let Err(e) =
-                                            app.emit_all("chat:tool_block", &block_event)
+                                            app.emit_to(&target, "chat:tool_block", &block_event)
                                         {
                                             log::error!("Failed to emit tool_block: {e}");
                                         }
@@ -942,7 +1049,9 @@ pub fn tail_claude_output(
                                                 worktree_id: worktree_id.to_string(),
                                                 content: thinking.to_string(),
                                             };
-                                            if let Err(e) = app.emit_all("chat:thinking", &event) {
+                                            if let Err(e) =
+                                                app.emit_to(&target, "chat:thinking", &event)
+                                            {
                                                 log::error!("Failed to emit thinking: {e}");
                                             }
                                         }
@@ -1007,7 +1116,8 @@ pub fn tail_claude_output(
                                         tool_use_id: tool_id.to_string(),
                                         output,
                                     };
-                                    if let Err(e) = app.emit_all("chat:tool_result", &event) {
+                                    if let Err(e) = app.emit_to(&target, "chat:tool_result", &event)
+                                    {
                                         log::error!("Failed to emit tool_result: {e}");
                                     }
                                 }
@@ -1023,6 +1133,14 @@ pub fn tail_claude_output(
                         }
                     }
 
+                    // Remember login/refresh failures for the auth status
+                    if msg.get("is_error").and_then(|v| v.as_bool()) == Some(true) {
+                        if let Some(result) = msg.get("result").and_then(|v| v.as_str()) {
+                            crate::claude_cli::record_auth_failure(result);
+                            crate::quota::observe(app, "claude", Some(session_id), result);
+                        }
+                    }
+
                     // Extract token usage data
                     if let Some(usage_obj) = msg.get("usage") {
                         usage = Some(UsageData {
@@ -1042,6 +1160,7 @@ pub fn tail_claude_output(
                                 .get("cache_creation_input_tokens")
                                 .and_then(|v| v.as_u64())
                                 .unwrap_or(0),
+                            cost_usd: msg.get("total_cost_usd").and_then(|v| v.as_f64()),
                         });
                         log::trace!(
                             "Token usage: input={}, output={}, cache_read={}, cache_create={}",
@@ -1171,6 +1290,13 @@ pub fn tail_claude_output(
                 log::trace!(
                     "Process {pid} is no longer running and no new output after receiving content"
                 );
+                crate::crash_reports::record_cli_crash(
+                    "claude",
+                    "Claude CLI exited without completing the run",
+                    None,
+                    None,
+                    stderr_tail.drain(..).collect(),
+                );
                 cancelled = true;
                 break;
             }
@@ -1183,6 +1309,15 @@ pub fn tail_claude_output(
                     "Startup timeout ({:?}) exceeded waiting for Claude output, process_alive: {process_alive}",
                     startup_timeout
                 );
+                if !process_alive {
+                    crate::crash_reports::record_cli_crash(
+                        "claude",
+                        "Claude CLI exited before producing output",
+                        None,
+                        None,
+                        stderr_tail.drain(..).collect(),
+                    );
+                }
                 cancelled = true;
                 break;
             }
@@ -1228,3 +1363,78 @@ pub fn tail_claude_output(
         usage,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::RecordingSink;
+
+    #[test]
+    fn test_plan_mode_cannot_be_overridden_by_defaults() {
+        let capabilities = cli_capabilities::CliCapabilities::default();
+        let build = |defaults: &[&str], plan_mode: bool| {
+            let mut args = ArgBuilder::new(&capabilities, CLAUDE_OPTIONAL_FLAGS);
+            args.option(
+                "--permission-mode",
+                if plan_mode { "plan" } else { "acceptEdits" },
+            );
+            let defaults: Vec<String> = defaults.iter().map(|s| s.to_string()).collect();
+            add_default_args(&mut args, &defaults, plan_mode);
+            args.build(CLAUDE_RULES)
+        };
+
+        for defaults in [
+            &["--permission-mode", "bypassPermissions"][..],
+            &["--permission-mode=bypassPermissions"],
+            &["--dangerously-skip-permissions"],
+            &["--allowedTools", "Bash(*)"],
+            &["--disallowedTools", "Read"],
+        ] {
+            let errors = build(defaults, true).unwrap_err();
+            assert_eq!(errors[0].message, "set by Jean, can't be a default");
+        }
+        assert_eq!(
+            build(&["--allowedTools", "Bash(npm test)"], false).unwrap(),
+            [
+                "--permission-mode",
+                "acceptEdits",
+                "--allowedTools",
+                "Bash(npm test)"
+            ]
+        );
+        assert!(build(&["--add-dir", "/tmp/shared"], true).is_ok());
+    }
+
+    #[test]
+    fn test_tail_emits_events_for_scripted_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_file = dir.path().join("run.jsonl");
+        let scenario = crate::mock::builtin_scenario("tools").unwrap();
+        let lines: Vec<String> = crate::mock::script_lines(&scenario, "claude-sess")
+            .into_iter()
+            .map(|(_, line)| line)
+            .collect();
+        std::fs::write(&output_file, lines.join("\n") + "\n").unwrap();
+
+        // Tailing stops early if the session isn't registered as running
+        let session_id = "tail-test-session";
+        super::super::registry::register_process(session_id.to_string(), std::process::id());
+        let sink = RecordingSink::default();
+        let response =
+            tail_claude_output(&sink, session_id, "wt-1", &output_file, std::process::id());
+        super::super::registry::unregister_process(session_id);
+        let response = response.unwrap();
+
+        assert_eq!(response.session_id, "claude-sess");
+        assert!(!response.cancelled);
+        assert_eq!(response.tool_calls.len(), 2);
+        assert!(response.content.contains("`package.json`"));
+
+        assert_eq!(sink.payloads("chat:tool_use").len(), 2);
+        assert_eq!(sink.payloads("chat:tool_result").len(), 2);
+        assert_eq!(sink.payloads("chat:thinking").len(), 1);
+        let done = sink.payloads("chat:done");
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0]["session_id"], session_id);
+    }
+}
@@ -0,0 +1,407 @@
+//! The Codex CLI as an agent backend
+//!
+//! `codex exec --json` runs one non-interactive turn and prints typed events
+//! (see `agent_protocol::codex`). A CLI profile whose settings JSON has
+//! `"backend": "codex"` runs it for session messages, queued ones included,
+//! resuming the thread Codex reported for the previous message. Plan-mode
+//! messages run in the read-only sandbox whatever the profile sets. One-shot
+//! runs call [`run_exec`] directly for the final message and usage. Output
+//! from untested versions that doesn't match the schema is reported as drift.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use serde::Deserialize;
+
+use super::external::ExternalRun;
+use super::types::UsageData;
+use crate::agent_protocol::codex::CodexAdapter;
+use crate::agent_protocol::custom::StreamFormat;
+use crate::agent_protocol::drift::{self, SchemaDrift};
+use crate::agent_protocol::{Adapter, AgentEvent};
+use crate::cli_capabilities::args::{describe_errors, ArgBuilder};
+use crate::cli_capabilities::CliCapabilities;
+use crate::platform::silent_command;
+use crate::runtime::{EventSink, PathProvider};
+
+/// Binary and backend name
+pub const CODEX_TOOL: &str = "codex";
+
+/// Versions covered by the golden files in `fixtures/codex-exec/`
+pub const TESTED_VERSIONS: &[&str] = &["0.44.0", "0.50.0"];
+
+const SANDBOX_MODES: &[&str] = &["read-only", "workspace-write", "danger-full-access"];
+
+/// Flags Jean's run protocol depends on; launch defaults can't set them
+const RESERVED_FLAGS: &[&str] = &["--json", "--cd", "--skip-git-repo-check"];
+
+/// Flags that would let a plan-mode run out of the read-only sandbox
+const PLAN_MODE_RESERVED_FLAGS: &[&str] = &[
+    "--sandbox",
+    "--full-auto",
+    "--dangerously-bypass-approvals-and-sandbox",
+];
+
+/// Model names that only mean something to the Claude CLI
+const CLAUDE_ALIASES: &[&str] = &["opus", "sonnet", "haiku", "opusplan"];
+
+/// The Codex-specific fields of a CLI profile's settings JSON
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexProfile {
+    #[serde(default)]
+    backend: Option<String>,
+    /// Overrides the session's model when set
+    #[serde(default)]
+    pub model: Option<String>,
+    /// read-only, workspace-write or danger-full-access
+    #[serde(default)]
+    pub sandbox: Option<String>,
+    /// Extra environment, e.g. `OPENAI_API_KEY`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl CodexProfile {
+    /// Codex settings of a profile, if the profile selects the codex backend
+    pub fn from_settings(settings: Option<&str>) -> Option<Self> {
+        let profile: Self = serde_json::from_str(settings?).ok()?;
+        (profile.backend.as_deref() == Some(CODEX_TOOL)).then_some(profile)
+    }
+}
+
+/// The model to pass to Codex: the profile's, else the session's unless it's
+/// a Claude model (Codex then uses its configured default)
+fn codex_model<'a>(profile: &'a CodexProfile, session_model: Option<&'a str>) -> Option<&'a str> {
+    profile
+        .model
+        .as_deref()
+        .or(session_model.filter(|m| !m.starts_with("claude-") && !CLAUDE_ALIASES.contains(m)))
+}
+
+/// `codex` on the session PATH (Jean's managed tool directories first)
+pub fn resolve_binary(app: &impl PathProvider) -> Option<PathBuf> {
+    let path = crate::tool_install::session_path(app).or_else(|| std::env::var("PATH").ok())?;
+    which::which_in(CODEX_TOOL, Some(path), std::env::temp_dir()).ok()
+}
+
+/// Log when the installed version has no golden files; its output is still
+/// parsed, reporting whatever doesn't match the schema
+fn check_version(app: &impl PathProvider, binary: &Path) {
+    let capabilities = crate::cli_capabilities::probe(app, CODEX_TOOL, binary, &[]);
+    match capabilities.version.as_deref() {
+        Some(version) if TESTED_VERSIONS.contains(&version) => {}
+        version => log::info!(
+            "Codex CLI {} isn't one of the tested versions ({}); unknown output will be passed through",
+            version.unwrap_or("(unknown version)"),
+            TESTED_VERSIONS.join(", ")
+        ),
+    }
+}
+
+/// Arguments of one `codex exec --json` run that reads its prompt from stdin,
+/// with `extra_args` (launch defaults) before the prompt. `plan_mode` forces
+/// the read-only sandbox.
+///
+/// Built without probed capabilities: the probe reads `codex --help`, which
+/// doesn't list `exec`'s flags, so `extra_args` are only checked for form and
+/// reserved flags.
+pub fn build_exec_args(
+    model: Option<&str>,
+    sandbox: Option<&str>,
+    working_dir: &Path,
+    resume_thread: Option<&str>,
+    extra_args: &[String],
+    plan_mode: bool,
+) -> Result<Vec<String>, String> {
+    let sandbox = if plan_mode {
+        Some("read-only")
+    } else {
+        sandbox
+    };
+    let mut args: Vec<String> = ["exec", "--json", "--skip-git-repo-check", "--cd"]
+        .map(str::to_string)
+        .to_vec();
+    args.push(working_dir.to_string_lossy().to_string());
+    if let Some(model) = model {
+        args.push("--model".to_string());
+        args.push(model.to_string());
+    }
+    if let Some(sandbox) = sandbox {
+        if !SANDBOX_MODES.contains(&sandbox) {
+            return Err(format!(
+                "Invalid Codex sandbox {sandbox:?} (expected one of {})",
+                SANDBOX_MODES.join(", ")
+            ));
+        }
+        args.push("--sandbox".to_string());
+        args.push(sandbox.to_string());
+    }
+    let capabilities = CliCapabilities::default();
+    let mut extra = ArgBuilder::new(&capabilities, &[]);
+    if plan_mode {
+        extra.user_args(
+            extra_args,
+            &[RESERVED_FLAGS, PLAN_MODE_RESERVED_FLAGS].concat(),
+        );
+    } else {
+        extra.user_args(extra_args, RESERVED_FLAGS);
+    }
+    args.extend(
+        extra
+            .build(&[])
+            .map_err(|errors| describe_errors("Codex CLI", &errors))?,
+    );
+    if let Some(thread) = resume_thread {
+        args.push("resume".to_string());
+        args.push(thread.to_string());
+    }
+    args.push("-".to_string());
+    Ok(args)
+}
+
+/// Codex invocation for one session message
+#[allow(clippy::too_many_arguments)]
+pub(super) fn prepare_run(
+    app: &tauri::AppHandle,
+    message_file: &Path,
+    working_dir: &Path,
+    model: Option<&str>,
+    execution_mode: Option<&str>,
+    resume_thread: Option<&str>,
+    profile: &CodexProfile,
+    extra_args: &[String],
+) -> Result<ExternalRun, String> {
+    let program = resolve_binary(app).ok_or("Codex CLI not found on PATH")?;
+    check_version(app, &program);
+    let args = build_exec_args(
+        codex_model(profile, model),
+        profile.sandbox.as_deref(),
+        working_dir,
+        resume_thread,
+        extra_args,
+        super::external::is_plan_mode(execution_mode),
+    )?;
+    Ok(ExternalRun {
+        label: CODEX_TOOL.to_string(),
+        program,
+        args,
+        env: profile
+            .env
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        stdin_file: message_file.to_path_buf(),
+        format: StreamFormat::CodexJson,
+    })
+}
+
+/// What a `codex exec` run produced
+#[derive(Debug, Default)]
+pub struct ExecOutcome {
+    pub thread_id: Option<String>,
+    /// Final agent message
+    pub message: String,
+    pub usage: Option<UsageData>,
+    /// Why the turn failed
+    pub error: Option<String>,
+    /// Output that didn't match the schema
+    pub drift: Vec<SchemaDrift>,
+}
+
+/// Read a run's output, calling `on_message` with each agent message
+pub fn read_exec_output(reader: impl BufRead, mut on_message: impl FnMut(&str)) -> ExecOutcome {
+    let mut adapter = CodexAdapter::default();
+    let mut outcome = ExecOutcome::default();
+    let mut last_error = None;
+
+    let lines = reader.lines().map_while(Result::ok);
+    for event in lines.flat_map(|line| adapter.parse_line(&line)) {
+        match event {
+            AgentEvent::SessionStarted { session_id } => outcome.thread_id = Some(session_id),
+            AgentEvent::MessageDelta { text } => on_message(&text),
+            AgentEvent::Usage {
+                input_tokens,
+                output_tokens,
+                cache_read_input_tokens,
+                cache_creation_input_tokens,
+                cost_usd,
+            } => {
+                outcome.usage = Some(UsageData {
+                    input_tokens,
+                    output_tokens,
+                    cache_read_input_tokens,
+                    cache_creation_input_tokens,
+                    cost_usd,
+                })
+            }
+            AgentEvent::Error { message } => last_error = Some(message),
+            AgentEvent::Done { result, is_error } => {
+                if is_error {
+                    outcome.error = Some(
+                        last_error
+                            .take()
+                            .unwrap_or_else(|| "Codex run failed".to_string()),
+                    );
+                } else {
+                    outcome.message = result.unwrap_or_default();
+                }
+            }
+            _ => {}
+        }
+    }
+    outcome.drift = adapter.take_drift();
+    outcome
+}
+
+/// Run `codex exec` to completion, calling `on_message` with each agent message
+pub fn run_exec(
+    app: &(impl EventSink + PathProvider),
+    prompt: &str,
+    model: Option<&str>,
+    sandbox: Option<&str>,
+    working_dir: &Path,
+    on_message: impl FnMut(&str),
+) -> Result<ExecOutcome, String> {
+    let program = resolve_binary(app).ok_or("Codex CLI not found on PATH")?;
+    check_version(app, &program);
+    let args = build_exec_args(model, sandbox, working_dir, None, &[], false)?;
+
+    let mut cmd = silent_command(&program);
+    cmd.args(&args)
+        .current_dir(working_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(path) = crate::tool_install::session_path(app) {
+        cmd.env("PATH", path);
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn Codex CLI: {e}"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // Dropped at the end of this block, closing stdin so Codex sees EOF
+        stdin
+            .write_all(prompt.as_bytes())
+            .map_err(|e| format!("Failed to write to Codex CLI stdin: {e}"))?;
+    }
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or("Failed to capture Codex CLI stdout")?;
+    let outcome = read_exec_output(BufReader::new(stdout), on_message);
+    drift::report(app, CODEX_TOOL, None, outcome.drift.clone());
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to wait for Codex CLI: {e}"))?;
+    crate::crash_reports::record_cli_exit(CODEX_TOOL, &output.status, &output.stderr);
+
+    if outcome.error.is_none() && !output.status.success() && outcome.message.is_empty() {
+        return Err(format!(
+            "Codex CLI failed (exit code {:?}): {}",
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_profile_and_args() {
+        assert!(CodexProfile::from_settings(Some(r#"{"backend":"aider"}"#)).is_none());
+        let profile = CodexProfile::from_settings(Some(
+            r#"{"backend":"codex","sandbox":"workspace-write","env":{"OPENAI_API_KEY":"k"}}"#,
+        ))
+        .unwrap();
+        assert_eq!(profile.env["OPENAI_API_KEY"], "k");
+
+        // Claude model names aren't passed on
+        assert_eq!(codex_model(&profile, Some("opus")), None);
+        assert_eq!(
+            codex_model(&profile, Some("gpt-5-codex")),
+            Some("gpt-5-codex")
+        );
+
+        let args = build_exec_args(
+            Some("gpt-5-codex"),
+            profile.sandbox.as_deref(),
+            Path::new("/tmp/wt"),
+            Some("th-1"),
+            &["--oss".to_string()],
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            [
+                "exec",
+                "--json",
+                "--skip-git-repo-check",
+                "--cd",
+                "/tmp/wt",
+                "--model",
+                "gpt-5-codex",
+                "--sandbox",
+                "workspace-write",
+                "--oss",
+                "resume",
+                "th-1",
+                "-",
+            ]
+        );
+        assert!(build_exec_args(None, Some("yolo"), Path::new("/tmp"), None, &[], false).is_err());
+        let reserved = ["--cd".to_string(), "/elsewhere".to_string()];
+        assert!(build_exec_args(None, None, Path::new("/tmp"), None, &reserved, false).is_err());
+
+        // Plan mode is read-only whatever the profile or launch defaults say
+        let args = build_exec_args(
+            None,
+            Some("danger-full-access"),
+            Path::new("/tmp"),
+            None,
+            &[],
+            true,
+        )
+        .unwrap();
+        assert!(args.windows(2).any(|w| w == ["--sandbox", "read-only"]));
+        assert!(!args.contains(&"danger-full-access".to_string()));
+        let bypass = ["--dangerously-bypass-approvals-and-sandbox".to_string()];
+        assert!(build_exec_args(None, None, Path::new("/tmp"), None, &bypass, true).is_err());
+    }
+
+    #[test]
+    fn test_read_exec_output() {
+        let output = std::fs::read_to_string(crate::test_support::fixture_path(
+            "codex-exec/0.50.0/exec.jsonl",
+        ))
+        .unwrap();
+        let mut messages = Vec::new();
+
+        let outcome = read_exec_output(Cursor::new(output), |m| messages.push(m.to_string()));
+        assert_eq!(
+            outcome.thread_id.as_deref(),
+            Some("019a1b0c-5d7e-7c31-9f20-3c4e1a6b2d90")
+        );
+        assert_eq!(outcome.message, "Fixed the window size in `src/lib.rs`.");
+        assert_eq!(messages, [outcome.message.clone()]);
+        assert_eq!(outcome.usage.map(|u| u.output_tokens), Some(604));
+        assert!(outcome.error.is_none() && outcome.drift.is_empty());
+
+        let failed = [
+            r#"{"type":"thread.started","thread_id":"th-2"}"#,
+            r#"{"type":"error","message":"stream disconnected"}"#,
+            r#"{"type":"turn.failed","error":{"message":"Quota exceeded"}}"#,
+        ]
+        .join("\n");
+        let outcome = read_exec_output(Cursor::new(failed), |_| {});
+        assert_eq!(outcome.error.as_deref(), Some("Quota exceeded"));
+    }
+}
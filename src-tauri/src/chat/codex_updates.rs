@@ -0,0 +1,375 @@
+//! Update checks for the Codex CLI
+//!
+//! Jean doesn't install Codex (it comes from npm or Homebrew), but it does
+//! tell you when a newer release is out. Every `codex_update_check_hours`
+//! the installed version is compared with the newest GitHub release on the
+//! `codex_update_channel` (stable, or prereleases too), and
+//! `codex-cli:update-available` is emitted once per new version.
+//! `check_codex_update` runs the same check on demand.
+//!
+//! Where GitHub is blocked or throttled, `codex_release_mirrors` lists base
+//! URLs that stand in for `https://api.github.com` (a GitHub API mirror, or
+//! a proxy prefix like `https://ghproxy.example/https://api.github.com`).
+//! They're tried in order, then GitHub itself. Jean installs Codex through
+//! a package manager, so there are no release assets to mirror.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::codex::{resolve_binary, CODEX_TOOL};
+use crate::background_tasks::supervisor::supervise;
+use crate::http_client::github;
+use crate::http_client::retry::{emit_retry, send_with_retry, RetryAttempt, RetryPolicy};
+use crate::http_server::EmitExt;
+use crate::providers::is_newer_version;
+use crate::runtime::{now, PathProvider};
+
+/// GitHub API base URL that mirrors replace
+const GITHUB_API: &str = "https://api.github.com";
+
+/// Path of the Codex releases under the API base URL
+const CODEX_RELEASES_PATH: &str = "/repos/openai/codex/releases";
+
+/// GitHub API URL for Codex releases
+pub const CODEX_RELEASES_API: &str = "https://api.github.com/repos/openai/codex/releases";
+
+/// Per-source timeout, so a blocked source doesn't hold up the next one
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Last check and notification (in app data)
+const STATE_FILE: &str = "codex-updates.json";
+
+/// How often the scheduler checks whether a check is due
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Which releases count as updates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    /// Full releases only
+    #[default]
+    Stable,
+    /// Full releases and prereleases (alphas, betas)
+    Prerelease,
+}
+
+/// A newer Codex CLI release than the installed one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodexUpdate {
+    pub installed_version: String,
+    pub latest_version: String,
+    /// Git tag name (e.g., "rust-v0.50.0")
+    pub tag_name: String,
+    /// Publication date in ISO format
+    pub published_at: String,
+    pub prerelease: bool,
+    pub channel: UpdateChannel,
+}
+
+/// GitHub API release response structure
+#[derive(Debug, Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    #[serde(default)]
+    published_at: Option<String>,
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CheckState {
+    /// Unix seconds of the last successful check
+    checked_at: Option<u64>,
+    /// Latest version the frontend was told about
+    notified_version: Option<String>,
+}
+
+fn load_state(root: &Path) -> CheckState {
+    fs::read_to_string(root.join(STATE_FILE))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(root: &Path, state: &CheckState) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize update check state: {e}"))?;
+    fs::write(root.join(STATE_FILE), content)
+        .map_err(|e| format!("Failed to write update check state: {e}"))
+}
+
+/// Version of a release tag: Codex tags look like "rust-v0.50.0"
+fn release_version(tag: &str) -> Option<&str> {
+    let start = tag.find(|c: char| c.is_ascii_digit())?;
+    Some(&tag[start..])
+}
+
+/// Newest release on `channel`, with its version
+fn newest_release(
+    releases: &[GitHubRelease],
+    channel: UpdateChannel,
+) -> Option<(&GitHubRelease, &str)> {
+    releases
+        .iter()
+        .filter(|r| !r.draft && (channel == UpdateChannel::Prerelease || !r.prerelease))
+        .filter_map(|r| Some((r, release_version(&r.tag_name)?)))
+        .reduce(|best, next| {
+            if is_newer_version(best.1, next.1) {
+                next
+            } else {
+                best
+            }
+        })
+}
+
+/// Releases API URLs to try: each mirror, then GitHub
+fn release_api_urls(mirrors: &[String]) -> Vec<String> {
+    mirrors
+        .iter()
+        .map(|m| m.trim().trim_end_matches('/'))
+        .filter(|m| !m.is_empty() && *m != GITHUB_API)
+        .map(|m| format!("{m}{CODEX_RELEASES_PATH}"))
+        .chain(std::iter::once(CODEX_RELEASES_API.to_string()))
+        .collect()
+}
+
+async fn fetch_releases(
+    client: &reqwest::Client,
+    url: &str,
+    on_retry: &mut impl FnMut(&RetryAttempt),
+) -> Result<Vec<GitHubRelease>, String> {
+    let response = send_with_retry(
+        &RetryPolicy::default(),
+        "Codex CLI releases",
+        || github::authorize(client.get(url), url),
+        &mut *on_retry,
+    )
+    .await
+    .map_err(|e| format!("Failed to fetch Codex releases from {url}: {e}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!("{url} returned status: {}", response.status()));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse releases from {url}: {e}"))
+}
+
+/// Releases from the first of `urls` that answers (each retried on
+/// transient failures)
+async fn fetch_first(
+    urls: &[String],
+    mut on_retry: impl FnMut(&RetryAttempt),
+) -> Result<Vec<GitHubRelease>, String> {
+    let client = crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+    let mut errors = Vec::new();
+    for url in urls {
+        match fetch_releases(&client, url, &mut on_retry).await {
+            Ok(releases) => return Ok(releases),
+            Err(e) => {
+                log::debug!("{e}");
+                errors.push(e);
+            }
+        }
+    }
+    Err(errors.join("; "))
+}
+
+/// Compare `installed` with the newest release on `channel`
+async fn find_update(
+    urls: &[String],
+    installed: &str,
+    channel: UpdateChannel,
+    on_retry: impl FnMut(&RetryAttempt),
+) -> Result<Option<CodexUpdate>, String> {
+    let releases = fetch_first(urls, on_retry).await?;
+    let Some((release, latest)) = newest_release(&releases, channel) else {
+        log::trace!("No Codex CLI releases on the {channel:?} channel");
+        return Ok(None);
+    };
+    if !is_newer_version(installed, latest) {
+        return Ok(None);
+    }
+    Ok(Some(CodexUpdate {
+        installed_version: installed.to_string(),
+        latest_version: latest.to_string(),
+        tag_name: release.tag_name.clone(),
+        published_at: release.published_at.clone().unwrap_or_default(),
+        prerelease: release.prerelease,
+        channel,
+    }))
+}
+
+/// Installed Codex CLI version, if Codex is on the session PATH
+fn installed_version(app: &impl PathProvider) -> Option<String> {
+    let binary = resolve_binary(app)?;
+    crate::cli_capabilities::probe(app, CODEX_TOOL, &binary, &[]).version
+}
+
+/// Check for a newer Codex CLI on the configured channel (None when up to
+/// date or not installed)
+#[tauri::command]
+pub async fn check_codex_update(app: AppHandle) -> Result<Option<CodexUpdate>, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let Some(installed) = installed_version(&app) else {
+        return Ok(None);
+    };
+    find_update(
+        &release_api_urls(&prefs.codex_release_mirrors),
+        &installed,
+        prefs.codex_update_channel,
+        |retry| emit_retry(&app, retry),
+    )
+    .await
+}
+
+/// Check if the configured interval has passed, emitting new updates once
+fn run_scheduled_check(app: &AppHandle) -> Result<(), String> {
+    let prefs = tauri::async_runtime::block_on(crate::load_preferences(app.clone()))?;
+    if prefs.codex_update_check_hours == 0 {
+        return Ok(());
+    }
+    let root = app.app_data_dir()?;
+    let mut state = load_state(&root);
+    let interval = u64::from(prefs.codex_update_check_hours) * 3600;
+    if state
+        .checked_at
+        .is_some_and(|at| now().saturating_sub(at) < interval)
+    {
+        return Ok(());
+    }
+    let Some(installed) = installed_version(app) else {
+        return Ok(());
+    };
+
+    let update = tauri::async_runtime::block_on(find_update(
+        &release_api_urls(&prefs.codex_release_mirrors),
+        &installed,
+        prefs.codex_update_channel,
+        |_| {},
+    ))?;
+    state.checked_at = Some(now());
+    let Some(update) =
+        update.filter(|u| state.notified_version.as_ref() != Some(&u.latest_version))
+    else {
+        return save_state(&root, &state);
+    };
+    state.notified_version = Some(update.latest_version.clone());
+    save_state(&root, &state)?;
+
+    log::info!(
+        "Codex CLI {} is available (installed: {})",
+        update.latest_version,
+        update.installed_version
+    );
+    if let Err(e) = app.emit_all("codex-cli:update-available", &update) {
+        log::error!("Failed to emit codex-cli:update-available event: {e}");
+    }
+    Ok(())
+}
+
+/// Start the periodic Codex CLI update check
+pub fn start_scheduler(app: AppHandle) {
+    supervise("codex-update-check", move || loop {
+        if let Err(e) = run_scheduled_check(&app) {
+            log::warn!("Codex CLI update check failed: {e}");
+        }
+        std::thread::sleep(SCHEDULE_CHECK_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture_server::{FixtureResponse, FixtureServer};
+
+    const RELEASES: &str = r#"[
+        {"tag_name": "rust-v0.52.0-alpha.2", "published_at": "2025-06-03T00:00:00Z", "prerelease": true},
+        {"tag_name": "rust-v0.51.0", "published_at": "2025-06-01T00:00:00Z", "prerelease": false},
+        {"tag_name": "rust-v0.53.0", "published_at": null, "prerelease": false, "draft": true},
+        {"tag_name": "rust-v0.50.0", "published_at": "2025-05-20T00:00:00Z", "prerelease": false}
+    ]"#;
+
+    #[test]
+    fn test_release_version() {
+        assert_eq!(release_version("rust-v0.50.0"), Some("0.50.0"));
+        assert_eq!(release_version("v1.2.3"), Some("1.2.3"));
+        assert_eq!(release_version("0.1.0-alpha.1"), Some("0.1.0-alpha.1"));
+        assert_eq!(release_version("nightly"), None);
+    }
+
+    #[test]
+    fn test_release_api_urls() {
+        let mirrors = vec![
+            "https://gh-mirror.example/".to_string(),
+            " ".to_string(),
+            "https://proxy.example/https://api.github.com".to_string(),
+            "https://api.github.com".to_string(),
+        ];
+        assert_eq!(
+            release_api_urls(&mirrors),
+            vec![
+                "https://gh-mirror.example/repos/openai/codex/releases",
+                "https://proxy.example/https://api.github.com/repos/openai/codex/releases",
+                CODEX_RELEASES_API,
+            ]
+        );
+        assert_eq!(release_api_urls(&[]), vec![CODEX_RELEASES_API]);
+    }
+
+    #[test]
+    fn test_newest_release_by_channel() {
+        let releases: Vec<GitHubRelease> = serde_json::from_str(RELEASES).unwrap();
+        let (_, stable) = newest_release(&releases, UpdateChannel::Stable).unwrap();
+        assert_eq!(stable, "0.51.0");
+        let (release, pre) = newest_release(&releases, UpdateChannel::Prerelease).unwrap();
+        assert_eq!(pre, "0.52.0-alpha.2");
+        assert!(release.prerelease);
+        assert!(newest_release(&[], UpdateChannel::Stable).is_none());
+    }
+
+    #[test]
+    fn test_find_update() {
+        let server = FixtureServer::start(vec![(
+            "/releases".to_string(),
+            FixtureResponse::json(RELEASES),
+        )]);
+        let urls = vec![server.url("/releases")];
+        let check = |installed: &str, channel| {
+            tauri::async_runtime::block_on(find_update(&urls, installed, channel, |_| {})).unwrap()
+        };
+
+        let update = check("0.50.0", UpdateChannel::Stable).unwrap();
+        assert_eq!(update.latest_version, "0.51.0");
+        assert_eq!(update.tag_name, "rust-v0.51.0");
+        assert_eq!(update.published_at, "2025-06-01T00:00:00Z");
+        assert!(check("0.51.0", UpdateChannel::Stable).is_none());
+        assert_eq!(
+            check("0.51.0", UpdateChannel::Prerelease).map(|u| u.latest_version),
+            Some("0.52.0-alpha.2".to_string())
+        );
+
+        // A failing mirror falls through to the next source
+        let urls = vec![server.url("/missing"), server.url("/releases")];
+        let update = tauri::async_runtime::block_on(find_update(
+            &urls,
+            "0.50.0",
+            UpdateChannel::Stable,
+            |_| {},
+        ))
+        .unwrap();
+        assert_eq!(update.map(|u| u.latest_version), Some("0.51.0".to_string()));
+    }
+}
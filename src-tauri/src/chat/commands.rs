@@ -3,32 +3,136 @@ use std::path::PathBuf;
 use std::process::Stdio;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use uuid::Uuid;
 
+use super::blobs::StorageCompaction;
+use super::bulk::{run_bulk, BulkResult};
+use super::history::{
+    count_messages, load_history_entries, query_history, HistoryPage, HistoryQuery,
+    SessionHistoryEntry,
+};
+use super::input_requests::{InputRequestedEvent, ResumeSettings};
 use super::naming::{spawn_naming_task, NamingRequest};
+use super::one_shot::OneShotAnswer;
+use super::paging::{MessagePage, DEFAULT_MAX_OUTPUT_BYTES, DEFAULT_PAGE_SIZE};
 use super::registry::cancel_process;
+use super::replay::{ReplayEvent, ReplayInfo};
 use super::run_log;
+use super::share::{ShareExport, ShareFormat, DEFAULT_MAX_BYTES};
 use super::storage::{
-    delete_session_data, get_data_dir, get_index_path, get_session_dir, load_metadata,
-    load_sessions, with_sessions_mut,
+    delete_session_data, find_duplicate_groups, get_data_dir, get_index_path, get_session_dir,
+    load_metadata, load_sessions, sanitize_filename, with_metadata_mut, with_sessions_mut,
 };
+use super::time_tracking::SessionTime;
+use super::transcript::TranscriptImport;
 use super::types::{
-    AllSessionsEntry, AllSessionsResponse, ChatMessage, ClaudeContext, EffortLevel, MessageRole,
-    RunStatus, Session, SessionDigest, ThinkingLevel, WorktreeSessions,
+    AllSessionsEntry, AllSessionsResponse, ChatMessage, ClaudeContext, DuplicateReport,
+    EffortLevel, MessageRole, RunStatus, ScratchUsage, Session, SessionDigest,
+    SessionInterruptedEvent, SessionTimeline, ThinkingLevel, WorktreeSessions,
 };
 use crate::claude_cli::get_cli_binary_path;
 use crate::http_server::EmitExt;
 use crate::platform::silent_command;
 use crate::projects::storage::load_projects_data;
 use crate::projects::types::SessionType;
+use crate::runtime::{now, PathProvider};
 
-/// Get current Unix timestamp in seconds
-fn now() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+/// Attach recent project command output as a saved context of a new
+/// session, if enabled (`auto_attach_command_runs`)
+async fn attach_recent_command_runs(app: &AppHandle, session_id: &str, worktree_id: &str) {
+    let Ok(prefs) = crate::load_preferences(app.clone()).await else {
+        return;
+    };
+    if prefs.auto_attach_command_runs == 0 {
+        return;
+    }
+    let Ok(app_data_dir) = app.app_data_dir() else {
+        return;
+    };
+    let context_file = app_data_dir
+        .join("session-context")
+        .join(format!("{session_id}-context-command-runs.md"));
+    if context_file.exists() {
+        return;
+    }
+    let Some(project_id) = load_projects_data(app).ok().and_then(|data| {
+        data.find_worktree(worktree_id)
+            .map(|worktree| worktree.project_id.clone())
+    }) else {
+        return;
+    };
+    let Some(content) = crate::terminal::runner::recent_runs_context(
+        app,
+        &project_id,
+        prefs.auto_attach_command_runs as usize,
+        prefs.command_runs_token_budget as usize,
+    ) else {
+        return;
+    };
+    let written = context_file
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&context_file, content));
+    match written {
+        Ok(()) => log::trace!("Attached recent command output to session {session_id}"),
+        Err(e) => log::warn!("Failed to attach recent command output: {e}"),
+    }
+}
+
+/// Attach the repository map as a saved context of a new session in a large
+/// repository, if enabled (`repo_map_token_budget`). Worktrees without a map
+/// get one built in the background for later sessions.
+async fn attach_repo_map(app: &AppHandle, session_id: &str, worktree_path: &str) {
+    let Ok(prefs) = crate::load_preferences(app.clone()).await else {
+        return;
+    };
+    if prefs.repo_map_token_budget == 0 {
+        return;
+    }
+    let Ok(app_data_dir) = app.app_data_dir() else {
+        return;
+    };
+    let context_file = app_data_dir
+        .join("session-context")
+        .join(format!("{session_id}-context-repo-map.md"));
+    if context_file.exists() {
+        return;
+    }
+    let root = std::path::PathBuf::from(worktree_path);
+    let Some(map) = crate::repo_map::load(app, &root) else {
+        crate::repo_map::refresh_in_background(app.clone(), root);
+        return;
+    };
+    if map.file_count() < crate::repo_map::LARGE_REPO_FILES {
+        return;
+    }
+    let content = crate::repo_map::render(&map, prefs.repo_map_token_budget as usize);
+    let written = context_file
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&context_file, content));
+    match written {
+        Ok(()) => log::trace!("Attached repository map to session {session_id}"),
+        Err(e) => log::warn!("Failed to attach repository map: {e}"),
+    }
+}
+
+/// Scan the worktree's dependencies for known vulnerabilities and attach the
+/// findings to a new session, if enabled (`vulnerability_scan_before_sessions`)
+async fn attach_vulnerability_scan(app: &AppHandle, session_id: &str, worktree_path: &str) {
+    let Ok(prefs) = crate::load_preferences(app.clone()).await else {
+        return;
+    };
+    if !prefs.vulnerability_scan_before_sessions {
+        return;
+    }
+    let report = super::vulnerabilities::scan(std::path::Path::new(worktree_path), now()).await;
+    let found = report.findings.len();
+    match super::vulnerabilities::record(app, session_id, report) {
+        Ok(()) => log::trace!("Attached {found} vulnerable dependencies to session {session_id}"),
+        Err(e) => log::warn!("Failed to attach vulnerability scan: {e}"),
+    }
 }
 
 // ============================================================================
@@ -57,23 +161,7 @@ pub async fn get_sessions(
     if include_message_counts.unwrap_or(false) {
         for session in &mut sessions.sessions {
             if let Ok(Some(metadata)) = load_metadata(&app, &session.id) {
-                // Count messages: each run has 1 user message, plus 1 assistant message if not undo_send
-                let count: u32 = metadata
-                    .runs
-                    .iter()
-                    .map(|run| {
-                        let is_undo_send = run.status == RunStatus::Cancelled
-                            && run.assistant_message_id.is_none();
-                        if is_undo_send {
-                            0
-                        } else if run.assistant_message_id.is_some() {
-                            2 // user + assistant
-                        } else {
-                            1 // just user (still running or cancelled without response)
-                        }
-                    })
-                    .sum();
-                session.message_count = Some(count);
+                session.message_count = Some(count_messages(&metadata));
             }
         }
     }
@@ -137,6 +225,24 @@ pub async fn list_all_sessions(app: AppHandle) -> Result<AllSessionsResponse, St
     Ok(AllSessionsResponse { entries })
 }
 
+/// List sessions across all projects one page at a time
+///
+/// Supports sorting by recent activity, cost or duration, and filtering by
+/// project, worktree, archived state and search text. Pass `next_cursor`
+/// from a page as `cursor` to fetch the next one.
+#[tauri::command]
+pub async fn list_session_history(
+    app: AppHandle,
+    query: Option<HistoryQuery>,
+) -> Result<HistoryPage<SessionHistoryEntry>, String> {
+    let query = query.unwrap_or_default();
+    log::trace!("Listing session history: {query:?}");
+
+    let projects_data = load_projects_data(&app)?;
+    let entries = load_history_entries(&app, &projects_data, &query);
+    query_history(entries, &query)
+}
+
 /// Get a single session with full message history
 #[tauri::command]
 pub async fn get_session(
@@ -166,6 +272,39 @@ pub async fn get_session(
     Ok(session)
 }
 
+/// Load a range of a session's messages (the last page when `start` is
+/// omitted). Tool outputs over `max_output_bytes` are left out of the page
+/// and can be fetched with `get_tool_output`.
+#[tauri::command]
+pub async fn get_session_messages_page(
+    app: AppHandle,
+    session_id: String,
+    start: Option<usize>,
+    limit: Option<usize>,
+    max_output_bytes: Option<usize>,
+) -> Result<MessagePage, String> {
+    log::trace!("Loading message page for session {session_id} (start: {start:?})");
+    super::paging::load_message_page(
+        &app,
+        &session_id,
+        start,
+        limit.unwrap_or(DEFAULT_PAGE_SIZE),
+        max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES),
+    )
+}
+
+/// Read the full output of a tool call that was elided from a message page
+#[tauri::command]
+pub async fn get_tool_output(
+    app: AppHandle,
+    session_id: String,
+    message_id: String,
+    tool_call_id: String,
+) -> Result<Option<String>, String> {
+    log::trace!("Loading output of tool call {tool_call_id} in session {session_id}");
+    super::paging::read_tool_output(&app, &session_id, &message_id, &tool_call_id)
+}
+
 /// Create a new session tab
 #[tauri::command]
 pub async fn create_session(
@@ -233,6 +372,7 @@ pub async fn update_session_state(
     plan_file_path: Option<Option<String>>,
     pending_plan_message_id: Option<Option<String>>,
     label: Option<String>,
+    attribution: Option<String>,
 ) -> Result<(), String> {
     log::trace!("Updating session state for: {session_id}");
 
@@ -271,6 +411,9 @@ pub async fn update_session_state(
             if let Some(v) = label {
                 session.label = if v.is_empty() { None } else { Some(v) };
             }
+            if let Some(v) = attribution {
+                session.attribution = crate::reports::attribution::normalize(&v);
+            }
             Ok(())
         } else {
             Err(format!("Session not found: {session_id}"))
@@ -323,15 +466,26 @@ pub async fn close_session(
     worktree_id: String,
     worktree_path: String,
     session_id: String,
+) -> Result<Option<String>, String> {
+    remove_session(&app, &worktree_id, &worktree_path, &session_id)
+}
+
+/// Cancel, clean up and delete a session (shared by close_session and bulk_delete_sessions)
+fn remove_session(
+    app: &AppHandle,
+    worktree_id: &str,
+    worktree_path: &str,
+    session_id: &str,
 ) -> Result<Option<String>, String> {
     log::trace!("Closing session: {session_id}");
 
     // Cancel any running process first (outside lock - doesn't touch sessions file)
-    let _ = cancel_process(&app, &session_id, &worktree_id);
+    let _ = cancel_process(app, session_id, worktree_id);
+    super::input_requests::take_pending_input(session_id);
 
     // Collect pasted file paths for cleanup (outside lock - read-only NDJSON access)
     let mut files_to_delete: Vec<String> = Vec::new();
-    let messages = run_log::load_session_messages(&app, &session_id).unwrap_or_default();
+    let messages = run_log::load_session_messages(app, session_id).unwrap_or_default();
     for message in &messages {
         files_to_delete.extend(extract_image_paths(&message.content));
         files_to_delete.extend(extract_text_file_paths(&message.content));
@@ -349,24 +503,24 @@ pub async fn close_session(
     }
 
     // Delete session data (outside lock - separate directory)
-    if let Err(e) = delete_session_data(&app, &session_id) {
+    if let Err(e) = delete_session_data(app, session_id) {
         log::warn!("Failed to delete session data: {e}");
     }
 
     // Clean up context references for this session
     if let Err(e) =
-        crate::projects::github_issues::cleanup_issue_contexts_for_session(&app, &session_id)
+        crate::projects::github_issues::cleanup_issue_contexts_for_session(app, session_id)
     {
         log::warn!("Failed to cleanup issue/PR contexts for session: {e}");
     }
     if let Err(e) =
-        crate::projects::saved_contexts::cleanup_saved_contexts_for_session(&app, &session_id)
+        crate::projects::saved_contexts::cleanup_saved_contexts_for_session(app, session_id)
     {
         log::warn!("Failed to cleanup saved contexts for session: {e}");
     }
 
     // Now atomically modify the sessions file
-    with_sessions_mut(&app, &worktree_path, &worktree_id, |sessions| {
+    with_sessions_mut(app, worktree_path, worktree_id, |sessions| {
         // Find the index of the session being closed before removing it
         let closed_index = sessions.sessions.iter().position(|s| s.id == session_id);
 
@@ -374,7 +528,7 @@ pub async fn close_session(
         sessions.sessions.retain(|s| s.id != session_id);
 
         // Determine new active session
-        let new_active = if sessions.active_session_id.as_deref() == Some(&session_id) {
+        let new_active = if sessions.active_session_id.as_deref() == Some(session_id) {
             // The closed session was active, pick the previous one (or next if first)
             if let Some(idx) = closed_index {
                 if idx > 0 {
@@ -419,6 +573,12 @@ pub async fn archive_session(
 
     // Cancel any running process first (outside lock)
     let _ = cancel_process(&app, &session_id, &worktree_id);
+    super::input_requests::take_pending_input(&session_id);
+
+    // Scratch files are only useful while the session is active
+    if let Err(e) = super::scratch::clear_session_scratch(&app, &session_id) {
+        log::warn!("Failed to clear scratch directory: {e}");
+    }
 
     // Load messages from NDJSON to check if session has content (outside lock - read-only)
     let messages = run_log::load_session_messages(&app, &session_id).unwrap_or_default();
@@ -863,8 +1023,9 @@ pub async fn send_chat_message(
     mcp_config: Option<String>,
     chrome_enabled: Option<bool>,
     custom_profile_settings: Option<String>,
+    background_priority: Option<bool>,
 ) -> Result<ChatMessage, String> {
-    log::trace!("Sending chat message for session: {session_id}, worktree: {worktree_id}, model: {model:?}, execution_mode: {execution_mode:?}, thinking: {thinking_level:?}, effort: {effort_level:?}, disable_thinking_for_mode: {disable_thinking_for_mode:?}, allowed_tools: {allowed_tools:?}");
+    log::trace!("Sending chat message for session: {session_id}, worktree: {worktree_id}, model: {model:?}, execution_mode: {execution_mode:?}, thinking: {thinking_level:?}, effort: {effort_level:?}, disable_thinking_for_mode: {disable_thinking_for_mode:?}, allowed_tools: {allowed_tools:?}, background_priority: {background_priority:?}");
 
     // Validate inputs
     if message.trim().is_empty() {
@@ -875,6 +1036,35 @@ pub async fn send_chat_message(
         return Err("Worktree path cannot be empty".to_string());
     }
 
+    // A new message supersedes any unanswered question from the previous run
+    super::input_requests::take_pending_input(&session_id);
+
+    // Nothing is auto-approved while an emergency stop is engaged
+    let execution_mode = crate::emergency::restrict_execution_mode(execution_mode);
+    let allowed_tools = allowed_tools.filter(|_| !crate::emergency::is_engaged());
+
+    // Keep the run settings so an agent question can be answered and resumed later
+    let resume_settings = ResumeSettings {
+        worktree_id: worktree_id.clone(),
+        worktree_path: worktree_path.clone(),
+        model: model.clone(),
+        execution_mode: execution_mode.clone(),
+        thinking_level: thinking_level.clone(),
+        effort_level: effort_level.clone(),
+        disable_thinking_for_mode,
+        parallel_execution_prompt: parallel_execution_prompt.clone(),
+        ai_language: ai_language.clone(),
+        allowed_tools: allowed_tools.clone(),
+        mcp_config: mcp_config.clone(),
+        chrome_enabled,
+        custom_profile_settings: custom_profile_settings.clone(),
+        background_priority,
+    };
+
+    // Keep the machine awake until the run finishes
+    let _power_guard =
+        crate::power::PowerGuard::acquire(&app, format!("Chat session {session_id}")).await;
+
     // Load sessions
     let mut sessions = load_sessions(&app, &worktree_path, &worktree_id)?;
 
@@ -1021,8 +1211,11 @@ pub async fn send_chat_message(
     // Note: User message is stored in NDJSON run entry (run.user_message),
     // not in sessions JSON. Messages are loaded from NDJSON on demand.
 
-    // Build context for Claude
-    let context = ClaudeContext::new(worktree_path.clone());
+    // Build context for Claude; sessions scoped to a sub-project run in its directory
+    let working_dir = crate::projects::subprojects::for_session(&app, &session_id, &worktree_id)
+        .map(|(_, dir)| dir.to_string_lossy().to_string())
+        .unwrap_or_else(|| worktree_path.clone());
+    let context = ClaudeContext::new(working_dir);
 
     // Get the Claude session ID for resumption
     let claude_session_id = sessions
@@ -1058,6 +1251,30 @@ pub async fn send_chat_message(
     // Write input file with the user message
     run_log::write_input_file(&app, &session_id, &run_id, &message)?;
 
+    // New conversations can start with the project's latest command output,
+    // known vulnerable dependencies and, in large repositories, a map of the code
+    if claude_session_id.is_none() {
+        attach_recent_command_runs(&app, &session_id, &worktree_id).await;
+        attach_repo_map(&app, &session_id, &worktree_path).await;
+        attach_vulnerability_scan(&app, &session_id, &worktree_path).await;
+
+        // Remember how the session's first conversation started, for re-runs
+        let launch_settings = resume_settings.clone();
+        if let Err(e) = with_metadata_mut(
+            &app,
+            &session_id,
+            &worktree_id,
+            &session_name,
+            session_order,
+            |metadata| {
+                metadata.launch_settings.get_or_insert(launch_settings);
+                Ok(())
+            },
+        ) {
+            log::warn!("Failed to record launch settings for session {session_id}: {e}");
+        }
+    }
+
     // Use passed parameter for thinking override (computed by frontend based on preference + manual override)
     let disable_thinking_in_non_plan_modes = disable_thinking_for_mode.unwrap_or(false);
 
@@ -1067,6 +1284,18 @@ pub async fn send_chat_message(
     // Use passed parameter for Chrome browser integration (default false - beta)
     let chrome = chrome_enabled.unwrap_or(false);
 
+    // Use passed parameter for background scheduling priority (default false - interactive)
+    let background_priority = background_priority.unwrap_or(false);
+
+    // Agents can search the worktree by meaning when it's indexed
+    let mcp_config =
+        crate::semantic_search::with_search_server(&app, &worktree_path, mcp_config).await;
+
+    // Pre-warmed CLIs for new conversations (off by default)
+    let warm_pool = crate::load_preferences(app.clone())
+        .await
+        .is_ok_and(|prefs| prefs.warm_process_pool);
+
     // Inject WebFetch/WebSearch in plan mode if preference is enabled
     let mut final_allowed_tools = allowed_tools.unwrap_or_default();
     if execution_mode.as_deref() == Some("plan") {
@@ -1077,16 +1306,49 @@ pub async fn send_chat_message(
             }
         }
     }
+    // Plus the tools the project's jean.json pre-approves, once the user
+    // approved them
+    if !crate::emergency::is_engaged() {
+        for rule in
+            crate::projects::config::approved_allowed_tools(&app, &worktree_id, &worktree_path)
+        {
+            if !final_allowed_tools.contains(&rule) {
+                final_allowed_tools.push(rule);
+            }
+        }
+    }
     let allowed_tools_for_cli = if final_allowed_tools.is_empty() {
         None
     } else {
         Some(final_allowed_tools)
     };
 
+    // Profiles can select aider or a custom backend instead of the Claude CLI
+    let external_backend =
+        super::external::ExternalBackend::from_settings(&app, custom_profile_settings.as_deref())?;
+    let launch_defaults =
+        super::launch::resolve(&app, &worktree_id, custom_profile_settings.as_deref());
+
     // Execute Claude CLI in detached mode
     // If resume fails with "session not found", retry without the session ID
     let mut claude_session_id_for_call = claude_session_id.clone();
     let (pid, claude_response) = loop {
+        if let Some(backend) = &external_backend {
+            break backend.execute(
+                &app,
+                &session_id,
+                &worktree_id,
+                &input_file,
+                &output_file,
+                context.worktree_path.as_ref(),
+                &message,
+                model.as_deref(),
+                execution_mode.as_deref(),
+                claude_session_id_for_call.as_deref(),
+                &launch_defaults,
+            )?;
+        }
+
         log::trace!("About to call execute_claude_detached...");
 
         match super::claude::execute_claude_detached(
@@ -1108,6 +1370,8 @@ pub async fn send_chat_message(
             mcp_config.as_deref(),
             chrome,
             custom_profile_settings.as_deref(),
+            background_priority,
+            warm_pool,
         ) {
             Ok((pid, response)) => {
                 log::trace!("execute_claude_detached succeeded (PID: {pid})");
@@ -1158,8 +1422,22 @@ pub async fn send_chat_message(
     let has_meaningful_content = claude_response.content.len() >= 10;
     let has_tool_calls = !claude_response.tool_calls.is_empty();
     let claude_session_id_for_log = claude_response.session_id.clone();
+    // Process died while the system was asleep (see power::monitor)
+    let interrupted = claude_response.cancelled && super::registry::take_interrupted(&session_id);
+
+    if interrupted {
+        let event = SessionInterruptedEvent {
+            session_id: session_id.clone(),
+            worktree_id: worktree_id.clone(),
+            run_id: run_id.clone(),
+            resumable: !claude_session_id_for_log.is_empty(),
+        };
+        if let Err(e) = app.emit_all("session:interrupted", &event) {
+            log::error!("Failed to emit session interrupted event: {e}");
+        }
+    }
 
-    if claude_response.cancelled && !has_meaningful_content && !has_tool_calls {
+    if claude_response.cancelled && !interrupted && !has_meaningful_content && !has_tool_calls {
         // Instant cancellation with no content
         // Cancel the run log (no assistant message to save)
         if let Err(e) = run_log_writer.cancel(None) {
@@ -1206,6 +1484,57 @@ pub async fn send_chat_message(
         });
     }
 
+    // Agent asked a question: remember it so the answer can resume the run
+    if !claude_response.cancelled {
+        if let Some(tc) = super::input_requests::find_input_request(&claude_response.tool_calls) {
+            let request = InputRequestedEvent {
+                session_id: session_id.clone(),
+                worktree_id: worktree_id.clone(),
+                tool_use_id: tc.id.clone(),
+                tool_name: tc.name.clone(),
+                input: tc.input.clone(),
+                requested_at: now(),
+            };
+            if let Err(e) = app.emit_all("session:input-requested", &request) {
+                log::error!("Failed to emit input-requested event: {e}");
+            }
+            super::input_requests::register_pending_input(request, resume_settings);
+        }
+    }
+
+    // Last test command of the run failed (event rules can react, e.g. queue a fix)
+    if !claude_response.cancelled {
+        let last_test = super::compare::tests_in(&claude_response.tool_calls).pop();
+        if let Some(test) = last_test.filter(|t| t.passed == Some(false)) {
+            let event = super::compare::TestsFailedEvent {
+                session_id: session_id.clone(),
+                worktree_id: worktree_id.clone(),
+                command: test.command,
+                summary: test.summary,
+            };
+            if let Err(e) = app.emit_all("session:tests-failed", &event) {
+                log::error!("Failed to emit tests-failed event: {e}");
+            }
+        }
+    }
+
+    // Optionally announce the result (per-notification-type toggles in preferences)
+    if !claude_response.cancelled {
+        crate::speech::tts::speak_completion_summary(
+            &app,
+            &claude_response.content,
+            &claude_response.tool_calls,
+        )
+        .await;
+        crate::announcements::announce_completion(
+            &app,
+            &session_id,
+            &claude_response.content,
+            &claude_response.tool_calls,
+        )
+        .await;
+    }
+
     // Create assistant message with tool calls and content blocks
     let assistant_msg_id = Uuid::new_v4().to_string();
     let assistant_msg = ChatMessage {
@@ -1216,7 +1545,7 @@ pub async fn send_chat_message(
         timestamp: now(),
         tool_calls: claude_response.tool_calls,
         content_blocks: claude_response.content_blocks,
-        cancelled: claude_response.cancelled,
+        cancelled: claude_response.cancelled && !interrupted,
         plan_approved: false,
         model: None,
         execution_mode: None,
@@ -1228,8 +1557,12 @@ pub async fn send_chat_message(
     // Note: Assistant message is stored in NDJSON, not sessions JSON.
     // Messages are loaded from NDJSON on demand via load_session_messages().
 
-    // Finalize run log (complete or cancel based on response status)
-    if claude_response.cancelled {
+    // Finalize run log (complete, interrupt or cancel based on response status)
+    if interrupted {
+        if let Err(e) = run_log_writer.interrupt(Some(&assistant_msg_id)) {
+            log::warn!("Failed to mark run log interrupted: {e}");
+        }
+    } else if claude_response.cancelled {
         if let Err(e) = run_log_writer.cancel(Some(&assistant_msg_id)) {
             log::warn!("Failed to cancel run log: {e}");
         }
@@ -1245,6 +1578,7 @@ pub async fn send_chat_message(
             log::warn!("Failed to complete run log: {e}");
         }
     }
+    crate::audit::record_run(&app, &session_id, &worktree_id, &run_id, &output_file);
 
     // Atomically save session metadata (claude_session_id for resumption)
     // Note: Messages are NOT saved here - they're in NDJSON only
@@ -1322,6 +1656,39 @@ pub async fn set_session_model(
     })
 }
 
+/// Scope a session to a monorepo sub-project (None = the whole repository).
+/// Only before the first message, as the CLI conversation is tied to its directory.
+#[tauri::command]
+pub async fn set_session_subproject(
+    app: AppHandle,
+    worktree_id: String,
+    worktree_path: String,
+    session_id: String,
+    subproject: Option<String>,
+) -> Result<(), String> {
+    log::trace!("Setting sub-project for session {session_id}: {subproject:?}");
+
+    let subproject = match subproject {
+        Some(path) => {
+            let root = std::path::Path::new(&worktree_path);
+            let found = crate::projects::subprojects::find(root, &path)
+                .ok_or_else(|| format!("Sub-project not found: {path}"))?;
+            Some(found.path)
+        }
+        None => None,
+    };
+    with_sessions_mut(&app, &worktree_path, &worktree_id, |sessions| {
+        let session = sessions
+            .find_session_mut(&session_id)
+            .ok_or_else(|| format!("Session not found: {session_id}"))?;
+        if session.claude_session_id.is_some() && session.subproject != subproject {
+            return Err("Sub-project can only be changed before the first message".to_string());
+        }
+        session.subproject = subproject;
+        Ok(())
+    })
+}
+
 /// Set the selected thinking level for a session
 #[tauri::command]
 pub async fn set_session_thinking_level(
@@ -1356,6 +1723,505 @@ pub async fn cancel_chat_message(
     cancel_process(&app, &session_id, &worktree_id)
 }
 
+/// Get disk usage of a session's scratch directory
+#[tauri::command]
+pub async fn get_session_scratch_usage(
+    app: AppHandle,
+    session_id: String,
+) -> Result<ScratchUsage, String> {
+    log::trace!("Getting scratch usage for session: {session_id}");
+    super::scratch::get_scratch_usage(&app, &session_id)
+}
+
+/// Get the structured tool-call timeline for a session (for Gantt-style rendering)
+#[tauri::command]
+pub async fn get_session_timeline(
+    app: AppHandle,
+    session_id: String,
+) -> Result<SessionTimeline, String> {
+    log::trace!("Getting timeline for session: {session_id}");
+    super::timeline::load_session_timeline(&app, &session_id)
+}
+
+/// Get a session's wall-clock time split into agent-active, waiting and idle time
+#[tauri::command]
+pub async fn get_session_time(app: AppHandle, session_id: String) -> Result<SessionTime, String> {
+    log::trace!("Getting time tracking for session: {session_id}");
+    super::time_tracking::load_session_time(&app, &session_id)
+}
+
+/// Replay a session's stored event stream over the event bus (replay:event / replay:done)
+/// With a positive speed, events follow the original relative timing scaled by speed.
+/// Without a speed, playback is stepped manually via replay_step.
+#[tauri::command]
+pub async fn replay_session(
+    app: AppHandle,
+    session_id: String,
+    speed: Option<f64>,
+) -> Result<ReplayInfo, String> {
+    log::trace!("Starting replay for session {session_id} at speed {speed:?}");
+    super::replay::start_replay(&app, &session_id, speed)
+}
+
+/// Emit the next event of a stepped session replay
+#[tauri::command]
+pub async fn replay_step(app: AppHandle, session_id: String) -> Result<ReplayEvent, String> {
+    super::replay::step_replay(&app, &session_id)
+}
+
+/// Stop a session replay in progress
+/// Returns true if a replay was running
+#[tauri::command]
+pub async fn stop_replay(app: AppHandle, session_id: String) -> Result<bool, String> {
+    log::trace!("Stopping replay for session: {session_id}");
+    Ok(super::replay::stop_replay(&app, &session_id))
+}
+
+/// Export a session as a JSONL transcript (see docs/developer/transcript-format.md)
+/// Returns the transcript; when a path is given it is also written there.
+#[tauri::command]
+pub async fn export_session_jsonl(
+    app: AppHandle,
+    session_id: String,
+    path: Option<String>,
+) -> Result<String, String> {
+    log::trace!("Exporting transcript for session: {session_id}");
+    let transcript = super::transcript::export_transcript(&app, &session_id)?;
+    if let Some(path) = path {
+        std::fs::write(&path, &transcript)
+            .map_err(|e| format!("Failed to write transcript: {e}"))?;
+    }
+    Ok(transcript)
+}
+
+/// Export a session's runs as a replayable terminal recording (asciinema .cast)
+#[tauri::command]
+pub async fn export_session_cast(
+    app: AppHandle,
+    session_id: String,
+    path: String,
+) -> Result<super::cast::CastExport, String> {
+    log::trace!("Exporting terminal recording for session {session_id} to {path}");
+    super::cast::export_cast(&app, &session_id, std::path::Path::new(&path))
+}
+
+/// Export a session as ChatGPT or Claude conversation JSON into a directory
+/// Pasted images are copied alongside; the oldest messages are left out if
+/// the export exceeds `max_bytes` (default 4 MiB).
+#[tauri::command]
+pub async fn export_session_for_web(
+    app: AppHandle,
+    session_id: String,
+    format: ShareFormat,
+    dir: String,
+    max_bytes: Option<usize>,
+) -> Result<ShareExport, String> {
+    log::trace!("Exporting session {session_id} as {format:?} to {dir}");
+    super::share::export_for_web(
+        &app,
+        &session_id,
+        format,
+        std::path::Path::new(&dir),
+        max_bytes.unwrap_or(DEFAULT_MAX_BYTES),
+    )
+}
+
+/// Import a JSONL transcript file as a new session in a worktree
+/// Skipped (returning the existing session) if an identical session already exists.
+#[tauri::command]
+pub async fn import_session_jsonl(
+    app: AppHandle,
+    worktree_id: String,
+    worktree_path: String,
+    path: String,
+) -> Result<TranscriptImport, String> {
+    log::trace!("Importing transcript {path} into worktree: {worktree_id}");
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read transcript: {e}"))?;
+    super::transcript::import_transcript(&app, &worktree_id, &worktree_path, &content)
+}
+
+/// Ask a quick question (optionally about selected text) without a session,
+/// with the Claude CLI or Codex (`backend: "codex"`). The answer streams as `one-shot:*` events and is only saved if pinned.
+#[tauri::command]
+pub async fn run_one_shot(
+    app: AppHandle,
+    prompt: String,
+    context: Option<String>,
+    model: Option<String>,
+    backend: Option<String>,
+) -> Result<OneShotAnswer, String> {
+    log::trace!("Running one-shot prompt (model: {model:?}, backend: {backend:?})");
+    tauri::async_runtime::spawn_blocking(move || {
+        super::one_shot::run_one_shot(
+            &app,
+            &prompt,
+            context.as_deref(),
+            model.as_deref(),
+            backend.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| format!("One-shot run failed: {e}"))?
+}
+
+/// One-shot answers from this app run that can still be pinned
+#[tauri::command]
+pub async fn get_recent_one_shots() -> Vec<OneShotAnswer> {
+    super::one_shot::recent_answers()
+}
+
+/// Save a one-shot answer as a session in a worktree
+#[tauri::command]
+pub async fn pin_one_shot(
+    app: AppHandle,
+    id: String,
+    worktree_id: String,
+    worktree_path: String,
+) -> Result<TranscriptImport, String> {
+    log::trace!("Pinning one-shot {id} to worktree: {worktree_id}");
+    super::one_shot::pin_recent(&app, &id, &worktree_id, &worktree_path)
+}
+
+/// Move oversized run log lines (mostly big tool outputs) of finished runs
+/// into compressed blobs and report the space reclaimed
+#[tauri::command]
+pub async fn compact_storage(app: AppHandle) -> Result<StorageCompaction, String> {
+    log::trace!("Compacting session storage");
+    tauri::async_runtime::spawn_blocking(move || super::blobs::compact_storage(&app))
+        .await
+        .map_err(|e| format!("Compaction task failed: {e}"))?
+}
+
+/// Install aider into its own Python environment (or repair a broken one)
+#[tauri::command]
+pub async fn install_aider(app: AppHandle) -> Result<crate::python_env::PythonEnv, String> {
+    log::trace!("Installing aider");
+    let _power_guard = crate::power::PowerGuard::acquire(&app, "Aider install").await;
+    tauri::async_runtime::spawn_blocking(move || super::aider::install(&app))
+        .await
+        .map_err(|e| format!("Aider install task failed: {e}"))?
+}
+
+/// Find sessions with identical content (e.g. from repeated imports or sync)
+/// With `remove`, deletes all but the oldest session of each group.
+#[tauri::command]
+pub async fn find_duplicate_sessions(
+    app: AppHandle,
+    remove: Option<bool>,
+) -> Result<DuplicateReport, String> {
+    log::trace!("Finding duplicate sessions (remove: {remove:?})");
+    let groups = find_duplicate_groups(&app)?;
+
+    let mut removed = 0;
+    if remove.unwrap_or(false) {
+        for duplicate in groups.iter().flat_map(|g| g.sessions.iter().skip(1)) {
+            match remove_session(&app, &duplicate.worktree_id, "", &duplicate.session_id) {
+                Ok(_) => removed += 1,
+                Err(e) => log::warn!(
+                    "Failed to remove duplicate session {}: {e}",
+                    duplicate.session_id
+                ),
+            }
+        }
+        log::trace!("Removed {removed} duplicate sessions");
+    }
+
+    Ok(DuplicateReport { groups, removed })
+}
+
+/// Worktree of each session that has metadata
+fn session_worktrees(
+    app: &AppHandle,
+    session_ids: &[String],
+) -> std::collections::HashMap<String, String> {
+    session_ids
+        .iter()
+        .filter_map(|id| {
+            let metadata = load_metadata(app, id).ok()??;
+            Some((id.clone(), metadata.worktree_id))
+        })
+        .collect()
+}
+
+/// Delete many sessions at once (like close_session for each)
+/// Progress is reported via bulk:progress events.
+#[tauri::command]
+pub async fn bulk_delete_sessions(
+    app: AppHandle,
+    session_ids: Vec<String>,
+) -> Result<BulkResult, String> {
+    log::trace!("Bulk deleting {} sessions", session_ids.len());
+    let worktrees = session_worktrees(&app, &session_ids);
+
+    // Sessions of the same worktree share an index file, so delete them in sequence
+    let mut groups: std::collections::HashMap<Option<&String>, Vec<String>> =
+        std::collections::HashMap::new();
+    for id in &session_ids {
+        groups
+            .entry(worktrees.get(id))
+            .or_default()
+            .push(id.clone());
+    }
+
+    Ok(run_bulk(
+        &app,
+        "delete_sessions",
+        groups.into_values().collect(),
+        |id| {
+            let worktree_id = worktrees
+                .get(id)
+                .ok_or_else(|| format!("Session not found: {id}"))?;
+            remove_session(&app, worktree_id, "", id).map(|_| ())
+        },
+    ))
+}
+
+/// Export many sessions as JSONL transcripts into a directory
+/// Files are named `<session name>-<session id>.jsonl`.
+#[tauri::command]
+pub async fn bulk_export_sessions(
+    app: AppHandle,
+    session_ids: Vec<String>,
+    dir: String,
+) -> Result<BulkResult, String> {
+    log::trace!("Bulk exporting {} sessions to {dir}", session_ids.len());
+    let dir = PathBuf::from(dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create export directory: {e}"))?;
+
+    Ok(run_bulk(
+        &app,
+        "export_sessions",
+        session_ids.into_iter().map(|id| vec![id]).collect(),
+        |id| {
+            let metadata =
+                load_metadata(&app, id)?.ok_or_else(|| format!("Session not found: {id}"))?;
+            let transcript = super::transcript::export_transcript(&app, id)?;
+            let file_name = format!("{}-{id}.jsonl", sanitize_filename(&metadata.name));
+            std::fs::write(dir.join(file_name), transcript)
+                .map_err(|e| format!("Failed to write transcript: {e}"))
+        },
+    ))
+}
+
+/// Cancel the running Claude tasks of many sessions at once
+/// Sessions with nothing running are reported as failed.
+#[tauri::command]
+pub async fn bulk_cancel_tasks(
+    app: AppHandle,
+    session_ids: Vec<String>,
+) -> Result<BulkResult, String> {
+    log::trace!("Bulk cancelling tasks for {} sessions", session_ids.len());
+    let worktrees = session_worktrees(&app, &session_ids);
+
+    Ok(run_bulk(
+        &app,
+        "cancel_tasks",
+        session_ids.into_iter().map(|id| vec![id]).collect(),
+        |id| {
+            let worktree_id = worktrees.get(id).map(String::as_str).unwrap_or_default();
+            if cancel_process(&app, id, worktree_id)? {
+                Ok(())
+            } else {
+                Err("No running task".to_string())
+            }
+        },
+    ))
+}
+
+/// Answer a question the agent asked in a non-interactive run.
+/// Resumes the conversation with the same settings the run was started with.
+/// The resumed run streams through the usual chat:* events.
+#[tauri::command]
+pub async fn provide_session_input(
+    app: AppHandle,
+    session_id: String,
+    text: String,
+) -> Result<(), String> {
+    log::trace!("Providing input for session: {session_id}");
+
+    if text.trim().is_empty() {
+        return Err("Input cannot be empty".to_string());
+    }
+
+    let (request, settings) = super::input_requests::take_pending_input(&session_id)
+        .ok_or_else(|| format!("No pending input request for session: {session_id}"))?;
+    log::trace!(
+        "Resuming session {session_id} after {} ({})",
+        request.tool_name,
+        request.tool_use_id
+    );
+
+    crate::background_tasks::supervisor::spawn_task("resume-after-input", async move {
+        if let Err(e) = send_chat_message(
+            app,
+            session_id.clone(),
+            settings.worktree_id,
+            settings.worktree_path,
+            text,
+            settings.model,
+            settings.execution_mode,
+            settings.thinking_level,
+            settings.effort_level,
+            settings.disable_thinking_for_mode,
+            settings.parallel_execution_prompt,
+            settings.ai_language,
+            settings.allowed_tools,
+            settings.mcp_config,
+            settings.chrome_enabled,
+            settings.custom_profile_settings,
+            settings.background_priority,
+        )
+        .await
+        {
+            log::error!("Failed to resume session {session_id} with input: {e}");
+        }
+    });
+
+    Ok(())
+}
+
+/// Re-run a session: start a new session with the original's first prompt,
+/// attachments, model and flags, tagged with `rerun_of` for comparison.
+/// With `checkout_commit`, it runs in a fresh worktree at the git commit
+/// recorded when the original started. The run streams through the usual
+/// chat:* events once the worktree is ready.
+#[tauri::command]
+pub async fn rerun_session(
+    app: AppHandle,
+    session_id: String,
+    checkout_commit: Option<bool>,
+) -> Result<super::rerun::RerunInfo, String> {
+    log::trace!("Re-running session: {session_id}");
+
+    let original = load_metadata(&app, &session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    let worktree = load_projects_data(&app)?
+        .find_worktree(&original.worktree_id)
+        .cloned()
+        .ok_or_else(|| format!("Worktree not found: {}", original.worktree_id))?;
+    let (prompt, mut settings) = super::rerun::launch_of(&original, &worktree.path)?;
+
+    let git_commit = if checkout_commit.unwrap_or(false) {
+        let commit = original
+            .environment
+            .as_ref()
+            .and_then(|env| env.git_commit.clone())
+            .ok_or("No git commit was recorded when this session started")?;
+        Some(commit)
+    } else {
+        None
+    };
+    let target = match &git_commit {
+        Some(commit) => {
+            crate::projects::create_worktree(
+                app.clone(),
+                worktree.project_id.clone(),
+                Some(commit.clone()),
+                None,
+                None,
+                None,
+            )
+            .await?
+        }
+        None => worktree,
+    };
+
+    let mut session = create_session(
+        app.clone(),
+        target.id.clone(),
+        target.path.clone(),
+        Some(format!("Rerun of {}", original.name)),
+    )
+    .await?;
+    with_metadata_mut(
+        &app,
+        &session.id,
+        &target.id,
+        &session.name,
+        session.order,
+        |metadata| {
+            metadata.rerun_of = Some(session_id.clone());
+            Ok(())
+        },
+    )?;
+    session.rerun_of = Some(session_id.clone());
+    super::rerun::copy_attachments(&app, &session_id, &session.id)?;
+
+    settings.worktree_id = target.id.clone();
+    settings.worktree_path = target.path.clone();
+    let fresh_worktree = git_commit.is_some();
+    let rerun_id = session.id.clone();
+    let task_app = app.clone();
+    crate::background_tasks::supervisor::spawn_task("rerun-session", async move {
+        let app = task_app;
+        if fresh_worktree {
+            if let Err(e) = super::rerun::wait_for_worktree(&app, &settings.worktree_id).await {
+                log::error!("Failed to re-run session {rerun_id}: {e}");
+                let _ = app.emit_all(
+                    "chat:error",
+                    &super::claude::ErrorEvent {
+                        session_id: rerun_id,
+                        worktree_id: settings.worktree_id,
+                        error: e,
+                    },
+                );
+                return;
+            }
+        }
+        if let Err(e) = send_chat_message(
+            app,
+            rerun_id.clone(),
+            settings.worktree_id,
+            settings.worktree_path,
+            prompt,
+            settings.model,
+            settings.execution_mode,
+            settings.thinking_level,
+            settings.effort_level,
+            settings.disable_thinking_for_mode,
+            settings.parallel_execution_prompt,
+            settings.ai_language,
+            settings.allowed_tools,
+            settings.mcp_config,
+            settings.chrome_enabled,
+            settings.custom_profile_settings,
+            settings.background_priority,
+        )
+        .await
+        {
+            log::error!("Failed to re-run session {rerun_id}: {e}");
+        }
+    });
+
+    Ok(super::rerun::RerunInfo {
+        session,
+        worktree: target,
+        rerun_of: session_id,
+        git_commit,
+    })
+}
+
+/// Compare two sessions' outcomes: the file changes each produced, token,
+/// cost and duration deltas (B minus A), and the test runs of each
+#[tauri::command]
+pub async fn compare_sessions(
+    app: AppHandle,
+    a: String,
+    b: String,
+) -> Result<super::compare::SessionComparison, String> {
+    log::trace!("Comparing sessions {a} and {b}");
+    super::compare::compare(&app, &a, &b)
+}
+
+/// Get the unanswered agent question for a session, if any
+#[tauri::command]
+pub async fn get_pending_session_input(
+    session_id: String,
+) -> Result<Option<InputRequestedEvent>, String> {
+    Ok(super::input_requests::get_pending_input(&session_id))
+}
+
 /// Check if any sessions have running Claude processes
 /// Used for quit confirmation dialog to prevent accidental closure during active sessions
 #[tauri::command]
@@ -1610,10 +2476,7 @@ pub async fn delete_pasted_image(app: AppHandle, path: String) -> Result<(), Str
 
     // Validate that the path is within allowed directories
     let path_str = file_path.to_string_lossy();
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
     let app_data_str = app_data_dir.to_string_lossy();
 
     // Check if path is in old .jean/images/ or new app data pasted-images/
@@ -1708,10 +2571,7 @@ pub async fn delete_pasted_text(app: AppHandle, path: String) -> Result<(), Stri
 
     // Validate that the path is within allowed directories
     let path_str = file_path.to_string_lossy();
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
     let app_data_str = app_data_dir.to_string_lossy();
 
     // Check if path is in old .jean/pastes/ or new app data pasted-texts/
@@ -1748,10 +2608,7 @@ pub async fn read_pasted_text(app: AppHandle, path: String) -> Result<ReadTextRe
 
     // Validate that the path is within allowed directories
     let path_str = file_path.to_string_lossy();
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
     let app_data_str = app_data_dir.to_string_lossy();
 
     // Check if path is in old .jean/pastes/ or new app data pasted-texts/
@@ -2435,6 +3292,7 @@ fn execute_summarization_claude(
     let output = child
         .wait_with_output()
         .map_err(|e| format!("Failed to wait for Claude CLI: {e}"))?;
+    crate::crash_reports::record_cli_exit("claude", &output.status, &output.stderr);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -2594,10 +3452,7 @@ pub async fn get_session_debug_info(
     session_id: String,
 ) -> Result<SessionDebugInfo, String> {
     // Get app data directory
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
 
     let app_data_str = app_data_dir.to_str().unwrap_or("unknown").to_string();
 
@@ -2701,6 +3556,106 @@ pub async fn get_session_debug_info(
     })
 }
 
+/// Annotate a message with a note, a thumbs up/down and/or labels
+#[tauri::command]
+pub async fn add_message_annotation(
+    app: AppHandle,
+    session_id: String,
+    message_id: String,
+    note: Option<String>,
+    rating: Option<super::annotations::Rating>,
+    labels: Option<Vec<String>>,
+) -> Result<super::annotations::MessageAnnotation, String> {
+    log::trace!("Annotating message {message_id} in session {session_id}");
+    super::annotations::add(
+        &app,
+        &session_id,
+        &message_id,
+        note,
+        rating,
+        labels.unwrap_or_default(),
+    )
+}
+
+/// Change a message annotation; omitted fields are kept, an empty note clears it
+#[tauri::command]
+pub async fn update_message_annotation(
+    app: AppHandle,
+    session_id: String,
+    annotation_id: String,
+    note: Option<String>,
+    rating: Option<super::annotations::Rating>,
+    clear_rating: Option<bool>,
+    labels: Option<Vec<String>>,
+) -> Result<super::annotations::MessageAnnotation, String> {
+    log::trace!("Updating annotation {annotation_id} in session {session_id}");
+    super::annotations::update(
+        &app,
+        &session_id,
+        &annotation_id,
+        super::annotations::AnnotationUpdate {
+            note,
+            rating,
+            clear_rating: clear_rating.unwrap_or(false),
+            labels,
+        },
+    )
+}
+
+/// Delete a message annotation; false if it didn't exist
+#[tauri::command]
+pub async fn delete_message_annotation(
+    app: AppHandle,
+    session_id: String,
+    annotation_id: String,
+) -> Result<bool, String> {
+    log::trace!("Deleting annotation {annotation_id} in session {session_id}");
+    super::annotations::delete(&app, &session_id, &annotation_id)
+}
+
+/// A session's message annotations, optionally only those on one message
+#[tauri::command]
+pub async fn list_message_annotations(
+    app: AppHandle,
+    session_id: String,
+    message_id: Option<String>,
+) -> Result<Vec<super::annotations::MessageAnnotation>, String> {
+    super::annotations::list(&app, &session_id, message_id.as_deref())
+}
+
+/// Environment snapshot recorded when a session's conversation started
+///
+/// None for sessions started before snapshots were recorded.
+#[tauri::command]
+pub async fn get_session_environment(
+    app: AppHandle,
+    session_id: String,
+) -> Result<Option<super::environment::EnvironmentSnapshot>, String> {
+    Ok(load_metadata(&app, &session_id)?.and_then(|metadata| metadata.environment))
+}
+
+/// Dependency vulnerabilities found for a session (None if never scanned)
+#[tauri::command]
+pub async fn get_session_vulnerabilities(
+    app: AppHandle,
+    session_id: String,
+) -> Result<Option<super::vulnerabilities::VulnerabilityReport>, String> {
+    Ok(load_metadata(&app, &session_id)?.and_then(|metadata| metadata.vulnerabilities))
+}
+
+/// Scan a worktree's dependencies now, storing the findings on a session and
+/// attaching them to its next message
+#[tauri::command]
+pub async fn scan_session_vulnerabilities(
+    app: AppHandle,
+    session_id: String,
+    worktree_path: String,
+) -> Result<super::vulnerabilities::VulnerabilityReport, String> {
+    let report = super::vulnerabilities::scan(std::path::Path::new(&worktree_path), now()).await;
+    super::vulnerabilities::record(&app, &session_id, report.clone())?;
+    Ok(report)
+}
+
 // ============================================================================
 // Session Resume Commands
 // ============================================================================
@@ -2791,7 +3746,7 @@ pub async fn resume_session(
         let run_id_clone = run_id.clone();
 
         // Spawn a task to tail the output file
-        tauri::async_runtime::spawn(async move {
+        crate::background_tasks::supervisor::spawn_task("resumed-run-tail", async move {
             log::trace!("Starting tail task for run: {run_id_clone}, session: {session_id_clone}");
 
             // Tail the output file
@@ -2907,19 +3862,21 @@ pub struct SessionDigestResponse {
     pub last_action: String,
 }
 
-/// Execute one-shot Claude CLI call for session digest with JSON schema (non-streaming)
-fn execute_digest_claude(
+/// Execute one-shot read-only Claude CLI call with a JSON schema (non-streaming),
+/// e.g. for session digests and lessons
+fn execute_structured_claude<T: serde::de::DeserializeOwned>(
     app: &AppHandle,
     prompt: &str,
     model: &str,
-) -> Result<SessionDigestResponse, String> {
+    schema: &str,
+) -> Result<T, String> {
     let cli_path = get_cli_binary_path(app)?;
 
     if !cli_path.exists() {
         return Err("Claude CLI not installed".to_string());
     }
 
-    log::trace!("Executing one-shot Claude digest with JSON schema");
+    log::trace!("Executing one-shot structured Claude call with JSON schema");
 
     let mut cmd = silent_command(&cli_path);
     cmd.args([
@@ -2935,7 +3892,7 @@ fn execute_digest_claude(
         "--max-turns",
         "2", // Need 2 turns: one for thinking, one for structured output
         "--json-schema",
-        SESSION_DIGEST_SCHEMA,
+        schema,
         "--permission-mode",
         "plan", // Read-only mode - don't allow any tool use
     ]);
@@ -2964,6 +3921,7 @@ fn execute_digest_claude(
     let output = child
         .wait_with_output()
         .map_err(|e| format!("Failed to wait for Claude CLI: {e}"))?;
+    crate::crash_reports::record_cli_exit("claude", &output.status, &output.stderr);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -3036,7 +3994,12 @@ pub async fn generate_session_digest(
     let prompt = SESSION_DIGEST_PROMPT.replace("{conversation}", &conversation_history);
 
     // Call Claude CLI with JSON schema (non-streaming)
-    let response = execute_digest_claude(&app, &prompt, &prefs.session_recap_model)?;
+    let response: SessionDigestResponse = execute_structured_claude(
+        &app,
+        &prompt,
+        &prefs.session_recap_model,
+        SESSION_DIGEST_SCHEMA,
+    )?;
 
     Ok(SessionDigest {
         chat_summary: response.chat_summary,
@@ -3051,6 +4014,194 @@ pub async fn generate_session_digest(
     })
 }
 
+/// Prompt template for distilling a reusable lesson from a session
+const LESSON_PROMPT: &str = r#"Distill a reusable lesson from the following coding session, so a future session facing a similar task can build on it.
+
+Focus on what generalizes: the problem, the approach that worked, pitfalls hit along the way, and the key files or commands involved. Leave out chit-chat and dead ends that taught nothing.
+
+CONVERSATION TRANSCRIPT:
+{conversation}
+
+END OF TRANSCRIPT.
+
+Provide:
+- title: A short title naming the problem solved (max 80 chars), e.g. "Migrating the settings table to SQLite"
+- summary: A concise markdown write-up (max ~200 words) of the problem, the solution and its pitfalls
+- tags: 2-5 lowercase topic tags, e.g. "migration", "sqlite", "tauri""#;
+
+/// JSON schema for lesson distillation output
+const LESSON_SCHEMA: &str = r#"{"type":"object","properties":{"title":{"type":"string","description":"Short title naming the problem solved"},"summary":{"type":"string","description":"Markdown write-up of the problem, solution and pitfalls"},"tags":{"type":"array","items":{"type":"string"},"description":"2-5 lowercase topic tags"}},"required":["title","summary","tags"]}"#;
+
+/// Draft a knowledge-base lesson from a session, for the user to edit and
+/// save (`save_lesson`)
+#[tauri::command]
+pub async fn distill_session_lesson(
+    app: AppHandle,
+    session_id: String,
+) -> Result<crate::knowledge::LessonDraft, String> {
+    log::trace!("Distilling lesson from session {session_id}");
+
+    let prefs = crate::load_preferences(app.clone())
+        .await
+        .map_err(|e| format!("Failed to load preferences: {e}"))?;
+
+    let messages = run_log::load_session_messages(&app, &session_id)?;
+    if messages.len() < 2 {
+        return Err("Session has too few messages to learn from".to_string());
+    }
+
+    let prompt = LESSON_PROMPT.replace("{conversation}", &format_messages_for_summary(&messages));
+    let mut draft: crate::knowledge::LessonDraft =
+        execute_structured_claude(&app, &prompt, &prefs.session_recap_model, LESSON_SCHEMA)?;
+    draft.tags = crate::knowledge::normalize_tags(&draft.tags);
+    Ok(draft)
+}
+
+/// Draft a changelog entry, ADR or runbook section from a session and write
+/// it into the session's worktree (see `docs` in jean.json)
+#[tauri::command]
+pub async fn generate_session_doc(
+    app: AppHandle,
+    session_id: String,
+    kind: super::docs::DocKind,
+) -> Result<super::docs::DocDraft, String> {
+    log::trace!("Generating {kind:?} draft from session {session_id}");
+
+    let prefs = crate::load_preferences(app.clone())
+        .await
+        .map_err(|e| format!("Failed to load preferences: {e}"))?;
+    let metadata = load_metadata(&app, &session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    let worktree = load_projects_data(&app)?
+        .find_worktree(&metadata.worktree_id)
+        .cloned()
+        .ok_or_else(|| format!("Worktree not found: {}", metadata.worktree_id))?;
+
+    let messages = run_log::load_session_messages(&app, &session_id)?;
+    if messages.len() < 2 {
+        return Err("Session has too few messages to document".to_string());
+    }
+
+    let prompt = super::docs::DOC_PROMPT
+        .replace("{doc_kind}", kind.describe())
+        .replace("{conversation}", &format_messages_for_summary(&messages));
+    let fields: super::docs::DocFields = execute_structured_claude(
+        &app,
+        &prompt,
+        &prefs.session_recap_model,
+        super::docs::DOC_SCHEMA,
+    )?;
+
+    let docs = crate::projects::git::read_jean_config(&worktree.path)
+        .map(|config| config.docs)
+        .unwrap_or_default();
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    super::docs::write_draft(
+        kind,
+        std::path::Path::new(&worktree.path),
+        &docs,
+        &fields,
+        &date,
+        &metadata.name,
+    )
+}
+
+/// Over-budget report for sending `message` in a session, or None if it fits
+#[tauri::command]
+pub async fn check_context_budget(
+    app: AppHandle,
+    session_id: String,
+    message: String,
+    model: Option<String>,
+) -> Option<super::context_budget::BudgetReport> {
+    let attachments = super::context_budget::session_attachments(&app, &session_id, &message);
+    super::context_budget::check(model.as_deref().unwrap_or("default"), attachments).err()
+}
+
+/// Prompt template for shrinking an attached context to fit the context window
+const CONDENSE_CONTEXT_PROMPT: &str = r#"The following document is attached as background context to a coding session, but it is too large to fit the model's context window. Condense it to at most a quarter of its length.
+
+Keep what the session needs to continue the work: goals, decisions and their rationale, current state, open questions, file paths, identifiers and commands. Drop repetition, long code listings and discussion that led nowhere.
+
+DOCUMENT:
+{document}
+
+END OF DOCUMENT.
+
+Provide:
+- summary: The condensed document in markdown"#;
+
+/// JSON schema for condensed context output
+const CONDENSE_CONTEXT_SCHEMA: &str = r#"{"type":"object","properties":{"summary":{"type":"string","description":"The condensed document in markdown"}},"required":["summary"]}"#;
+
+#[derive(serde::Deserialize)]
+struct CondensedContext {
+    summary: String,
+}
+
+/// Replace saved contexts attached to a session with condensed versions,
+/// returning their new sizes. The saved originals are left untouched.
+#[tauri::command]
+pub async fn summarize_context_attachments(
+    app: AppHandle,
+    session_id: String,
+    slugs: Vec<String>,
+) -> Result<Vec<super::context_budget::Attachment>, String> {
+    use super::context_budget::{session_context_files, Attachment, AttachmentKind};
+    log::trace!(
+        "Summarizing {} context(s) for session {session_id}",
+        slugs.len()
+    );
+
+    let prefs = crate::load_preferences(app.clone())
+        .await
+        .map_err(|e| format!("Failed to load preferences: {e}"))?;
+    let files = session_context_files(&app, &session_id);
+
+    let mut summarized = Vec::new();
+    for slug in slugs {
+        let path = files
+            .iter()
+            .find(|(kind, key, _)| *kind == AttachmentKind::SavedContext && *key == slug)
+            .map(|(_, _, path)| path.clone())
+            .ok_or_else(|| format!("Saved context '{slug}' is not attached to this session"))?;
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read saved context '{slug}': {e}"))?;
+        let heading = content.lines().next().filter(|l| l.starts_with("# "));
+
+        let prompt = CONDENSE_CONTEXT_PROMPT.replace("{document}", &content);
+        let condensed: CondensedContext = execute_structured_claude(
+            &app,
+            &prompt,
+            &prefs.session_recap_model,
+            CONDENSE_CONTEXT_SCHEMA,
+        )?;
+
+        // Keep the original heading, which names the context in the UI
+        let mut body = condensed.summary.trim();
+        if heading.is_some() && body.starts_with("# ") {
+            body = body
+                .split_once('\n')
+                .map_or("", |(_, rest)| rest.trim_start());
+        }
+        let new_content = format!(
+            "{}_Summarized to fit the context window._\n\n{body}\n",
+            heading.map(|h| format!("{h}\n\n")).unwrap_or_default()
+        );
+        std::fs::write(&path, &new_content)
+            .map_err(|e| format!("Failed to write saved context '{slug}': {e}"))?;
+        let mut attachment = Attachment::text(
+            AttachmentKind::SavedContext,
+            heading.and_then(|h| h.strip_prefix("# ")).unwrap_or(&slug),
+            &new_content,
+        );
+        attachment.key = slug;
+        attachment.path = Some(path.to_string_lossy().to_string());
+        summarized.push(attachment);
+    }
+    Ok(summarized)
+}
+
 /// Update a session's persisted digest
 ///
 /// Called after generating a digest to persist it to disk so it survives app reload.
@@ -3121,6 +4272,16 @@ pub enum McpHealthStatus {
     Unknown,
 }
 
+/// Show the command a session's next message would run, with launch defaults
+/// applied and secrets redacted
+#[tauri::command]
+pub async fn get_effective_launch_command(
+    app: AppHandle,
+    options: super::launch::LaunchCommandOptions,
+) -> Result<super::launch::EffectiveLaunchCommand, String> {
+    super::launch::effective_command(&app, &options)
+}
+
 /// Result of a health check across all MCP servers
 #[derive(serde::Serialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -3291,6 +4452,7 @@ pub async fn check_mcp_health(app: AppHandle) -> Result<McpHealthResult, String>
         .stderr(Stdio::piped())
         .output()
         .map_err(|e| format!("Failed to run claude mcp list: {e}"))?;
+    crate::crash_reports::record_cli_exit("claude", &output.status, &output.stderr);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -0,0 +1,473 @@
+//! Comparing the outcomes of two sessions
+//!
+//! Fan-out and re-run workflows produce several attempts at the same task.
+//! `compare_sessions` puts two of them side by side: the file changes each
+//! produced since it started (against the commit in its environment
+//! snapshot), token, cost and duration deltas, and the test commands the
+//! agent ran with their results.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::run_log::load_session_messages;
+use super::storage::load_metadata;
+use super::types::{MessageRole, SessionMetadata, ToolCall};
+use crate::platform::silent_command;
+use crate::projects::storage::load_projects_data;
+use crate::runtime::PathProvider;
+
+/// Commands that run a test suite
+static TEST_COMMAND: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"\b(cargo (test|nextest run)|(npm|pnpm|yarn|bun)( run)? test|pytest|python3? -m (pytest|unittest)|go test|vitest|jest|mix test|rspec|dotnet test|gradlew? test|mvn test|make (test|check))\b",
+    )
+    .unwrap()
+});
+
+/// Test runner output reporting failures
+static TESTS_FAILED: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?im)test result: FAILED|\b[1-9]\d* (failed|failing|errors?)\b|^FAIL\b|exit code [1-9]",
+    )
+    .unwrap()
+});
+
+/// Test runner output reporting success
+static TESTS_PASSED: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?im)test result: ok|\b[1-9]\d* (passed|passing)\b|^ok\b|^PASS\b").unwrap()
+});
+
+/// A file a session changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub additions: u32,
+    pub deletions: u32,
+    pub binary: bool,
+}
+
+/// A test command the agent ran
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestRun {
+    pub command: String,
+    /// None when the output didn't say
+    pub passed: Option<bool>,
+    /// Result line from the output (e.g. "test result: ok. 12 passed")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Payload for session:tests-failed events, emitted when the last test
+/// command of a run failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestsFailedEvent {
+    pub session_id: String,
+    pub worktree_id: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// What one session produced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionOutcome {
+    pub session_id: String,
+    pub name: String,
+    pub worktree_path: String,
+    /// Commit the changes are measured from (HEAD when none was recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_commit: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    pub duration_secs: u64,
+    pub run_count: u32,
+    pub files: Vec<FileChange>,
+    /// Unified diff of `files`
+    pub patch: String,
+    pub tests: Vec<TestRun>,
+    /// Result of the last test run; None if no tests ran or it was unclear
+    pub verified: Option<bool>,
+}
+
+/// One file across both sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileComparison {
+    pub path: String,
+    pub a: Option<FileChange>,
+    pub b: Option<FileChange>,
+    /// Both sessions left the file with the same content
+    pub identical: bool,
+    /// Diff from A's version of the file to B's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// Side-by-side outcome of two sessions; deltas are B minus A
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionComparison {
+    pub a: SessionOutcome,
+    pub b: SessionOutcome,
+    pub files: Vec<FileComparison>,
+    pub token_delta: i64,
+    pub cost_delta: f64,
+    pub duration_delta: i64,
+    /// Session whose tests passed when the other's didn't
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub better: Option<String>,
+}
+
+fn git(worktree: &Path, args: &[&str]) -> Result<String, String> {
+    let output = silent_command("git")
+        .args(args)
+        .current_dir(worktree)
+        .output()
+        .map_err(|e| format!("Failed to run git {}: {e}", args[0]))?;
+    // `diff --no-index` exits with 1 when the files differ
+    if !output.status.success() && output.status.code() != Some(1) {
+        return Err(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parse `git diff --numstat` output
+fn parse_numstat(output: &str) -> Vec<FileChange> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let (additions, deletions, path) = (parts.next()?, parts.next()?, parts.next()?);
+            let binary = additions == "-";
+            Some(FileChange {
+                path: path.to_string(),
+                additions: additions.parse().unwrap_or(0),
+                deletions: deletions.parse().unwrap_or(0),
+                binary,
+            })
+        })
+        .collect()
+}
+
+/// Files changed in `worktree` since `base` (committed, uncommitted and
+/// untracked), with their unified diff
+pub fn changed_files(worktree: &Path, base: &str) -> Result<(Vec<FileChange>, String), String> {
+    let mut files = parse_numstat(&git(worktree, &["diff", "--numstat", base])?);
+    let mut patch = git(worktree, &["diff", base])?;
+
+    let untracked = git(worktree, &["ls-files", "--others", "--exclude-standard"])?;
+    for path in untracked.lines().filter(|p| !p.is_empty()) {
+        let content = std::fs::read(worktree.join(path)).unwrap_or_default();
+        let binary = content.contains(&0);
+        files.push(FileChange {
+            path: path.to_string(),
+            additions: if binary {
+                0
+            } else {
+                String::from_utf8_lossy(&content).lines().count() as u32
+            },
+            deletions: 0,
+            binary,
+        });
+        patch.push_str(&git(
+            worktree,
+            &["diff", "--no-index", "--", "/dev/null", path],
+        )?);
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((files, patch))
+}
+
+/// Whether test output reports success, failure or neither, with the line
+/// that said so
+fn test_outcome(output: &str) -> (Option<bool>, Option<String>) {
+    let line_of = |m: regex::Match| {
+        let start = output[..m.start()].rfind('\n').map_or(0, |i| i + 1);
+        let end = output[m.end()..]
+            .find('\n')
+            .map_or(output.len(), |i| m.end() + i);
+        output[start..end].trim().to_string()
+    };
+    if let Some(m) = TESTS_FAILED.find_iter(output).last() {
+        return (Some(false), Some(line_of(m)));
+    }
+    if let Some(m) = TESTS_PASSED.find_iter(output).last() {
+        return (Some(true), Some(line_of(m)));
+    }
+    (None, None)
+}
+
+/// Test commands run through Bash in `tool_calls`, in order
+pub(crate) fn tests_in<'a>(tool_calls: impl IntoIterator<Item = &'a ToolCall>) -> Vec<TestRun> {
+    tool_calls
+        .into_iter()
+        .filter(|call| call.name == "Bash")
+        .filter_map(|call| {
+            let command = call.input.get("command")?.as_str()?;
+            TEST_COMMAND.is_match(command).then(|| {
+                let (passed, summary) = test_outcome(call.output.as_deref().unwrap_or_default());
+                TestRun {
+                    command: command.to_string(),
+                    passed,
+                    summary,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Test commands the agent ran through Bash, in order
+pub(crate) fn test_runs(app: &impl PathProvider, session_id: &str) -> Result<Vec<TestRun>, String> {
+    let messages = load_session_messages(app, session_id)?;
+    Ok(tests_in(
+        messages
+            .iter()
+            .filter(|m| m.role == MessageRole::Assistant)
+            .flat_map(|m| &m.tool_calls),
+    ))
+}
+
+fn outcome(
+    app: &tauri::AppHandle,
+    metadata: SessionMetadata,
+    worktree_path: String,
+) -> Result<SessionOutcome, String> {
+    let usage = metadata.runs.iter().filter_map(|r| r.usage.as_ref());
+    let input_tokens = usage.clone().map(|u| u.input_tokens).sum();
+    let output_tokens = usage.clone().map(|u| u.output_tokens).sum();
+    let cost_usd = usage.filter_map(|u| u.cost_usd).sum();
+    let duration_secs = metadata
+        .runs
+        .iter()
+        .filter_map(|r| r.ended_at.map(|end| end.saturating_sub(r.started_at)))
+        .sum();
+
+    let base_commit = metadata
+        .environment
+        .as_ref()
+        .and_then(|env| env.git_commit.clone());
+    let (files, patch) = changed_files(
+        Path::new(&worktree_path),
+        base_commit.as_deref().unwrap_or("HEAD"),
+    )?;
+    let tests = test_runs(app, &metadata.id)?;
+    let verified = tests.last().and_then(|t| t.passed);
+
+    Ok(SessionOutcome {
+        session_id: metadata.id,
+        name: metadata.name,
+        worktree_path,
+        base_commit,
+        input_tokens,
+        output_tokens,
+        cost_usd,
+        duration_secs,
+        run_count: metadata.runs.len() as u32,
+        files,
+        patch,
+        tests,
+        verified,
+    })
+}
+
+/// Compare the files both sessions touched, file by file
+fn compare_files(a: &SessionOutcome, b: &SessionOutcome) -> Vec<FileComparison> {
+    let mut paths: BTreeMap<&str, (Option<&FileChange>, Option<&FileChange>)> = BTreeMap::new();
+    for file in &a.files {
+        paths.entry(&file.path).or_default().0 = Some(file);
+    }
+    for file in &b.files {
+        paths.entry(&file.path).or_default().1 = Some(file);
+    }
+
+    let a_root = Path::new(&a.worktree_path);
+    let b_root = Path::new(&b.worktree_path);
+    paths
+        .into_iter()
+        .map(|(path, (a_file, b_file))| {
+            let a_path = a_root.join(path);
+            let b_path = b_root.join(path);
+            let identical = a_root == b_root
+                || match (std::fs::read(&a_path), std::fs::read(&b_path)) {
+                    (Ok(a), Ok(b)) => a == b,
+                    (Err(_), Err(_)) => true,
+                    _ => false,
+                };
+            let side = |p: &Path| {
+                if p.exists() {
+                    p.to_string_lossy().to_string()
+                } else {
+                    "/dev/null".to_string()
+                }
+            };
+            let diff = if identical {
+                None
+            } else {
+                git(
+                    a_root,
+                    &["diff", "--no-index", "--", &side(&a_path), &side(&b_path)],
+                )
+                .ok()
+            };
+            FileComparison {
+                path: path.to_string(),
+                a: a_file.cloned(),
+                b: b_file.cloned(),
+                identical,
+                diff,
+            }
+        })
+        .collect()
+}
+
+/// Session whose last test run passed when the other's didn't
+fn better_of(a: &SessionOutcome, b: &SessionOutcome) -> Option<String> {
+    match (a.verified, b.verified) {
+        (Some(true), Some(true)) => None,
+        (Some(true), _) => Some(a.session_id.clone()),
+        (_, Some(true)) => Some(b.session_id.clone()),
+        _ => None,
+    }
+}
+
+/// Compare two sessions' outcomes
+pub fn compare(app: &tauri::AppHandle, a: &str, b: &str) -> Result<SessionComparison, String> {
+    let projects = load_projects_data(app)?;
+    let load = |id: &str| -> Result<SessionOutcome, String> {
+        let metadata = load_metadata(app, id)?.ok_or_else(|| format!("Session not found: {id}"))?;
+        let worktree_path = projects
+            .find_worktree(&metadata.worktree_id)
+            .map(|w| w.path.clone())
+            .ok_or_else(|| format!("Worktree of session {id} not found"))?;
+        outcome(app, metadata, worktree_path)
+    };
+    let a = load(a)?;
+    let b = load(b)?;
+
+    let tokens = |o: &SessionOutcome| (o.input_tokens + o.output_tokens) as i64;
+    Ok(SessionComparison {
+        files: compare_files(&a, &b),
+        token_delta: tokens(&b) - tokens(&a),
+        cost_delta: b.cost_usd - a.cost_usd,
+        duration_delta: b.duration_secs as i64 - a.duration_secs as i64,
+        better: better_of(&a, &b),
+        a,
+        b,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    fn repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        run_git(dir.path(), &["init", "-q"]);
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\n").unwrap();
+        run_git(dir.path(), &["add", "."]);
+        run_git(
+            dir.path(),
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-qm",
+                "init",
+            ],
+        );
+        dir
+    }
+
+    fn outcome_at(dir: &Path, id: &str, verified: Option<bool>) -> SessionOutcome {
+        let (files, patch) = changed_files(dir, "HEAD").unwrap();
+        SessionOutcome {
+            session_id: id.to_string(),
+            name: id.to_string(),
+            worktree_path: dir.to_string_lossy().to_string(),
+            base_commit: None,
+            input_tokens: 0,
+            output_tokens: 0,
+            cost_usd: 0.0,
+            duration_secs: 0,
+            run_count: 1,
+            files,
+            patch,
+            tests: vec![],
+            verified,
+        }
+    }
+
+    #[test]
+    fn test_parse_numstat() {
+        let files = parse_numstat("3\t1\tsrc/main.rs\n-\t-\tlogo.png\n");
+        assert_eq!(files.len(), 2);
+        assert_eq!((files[0].additions, files[0].deletions), (3, 1));
+        assert!(files[1].binary);
+    }
+
+    #[test]
+    fn test_test_outcome() {
+        let cargo = "running 3 tests\ntest result: ok. 3 passed; 0 failed; 0 ignored\n";
+        assert_eq!(
+            test_outcome(cargo),
+            (
+                Some(true),
+                Some("test result: ok. 3 passed; 0 failed; 0 ignored".to_string())
+            )
+        );
+        let vitest = " Test Files  1 failed | 4 passed (5)\n";
+        assert_eq!(test_outcome(vitest).0, Some(false));
+        assert_eq!(
+            test_outcome("Exit code 2\nerror: could not compile").0,
+            Some(false)
+        );
+        assert_eq!(test_outcome("Compiling jean v0.1.0").0, None);
+        assert!(TEST_COMMAND.is_match("cd src-tauri && cargo test --lib"));
+        assert!(TEST_COMMAND.is_match("npm run test -- --run"));
+        assert!(!TEST_COMMAND.is_match("cargo build"));
+    }
+
+    #[test]
+    fn test_compare_files() {
+        let a = repo();
+        let b = repo();
+        std::fs::write(a.path().join("lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        std::fs::write(b.path().join("lib.rs"), "fn a() {}\nfn c() {}\n").unwrap();
+        std::fs::write(a.path().join("new.rs"), "one\ntwo\n").unwrap();
+        std::fs::write(b.path().join("new.rs"), "one\ntwo\n").unwrap();
+
+        let a = outcome_at(a.path(), "a", Some(false));
+        let b = outcome_at(b.path(), "b", Some(true));
+        assert_eq!(a.files.len(), 2);
+        assert_eq!(a.files[1].path, "new.rs");
+        assert_eq!(a.files[1].additions, 2);
+        assert!(a.patch.contains("+fn b() {}"));
+
+        let files = compare_files(&a, &b);
+        let lib = files.iter().find(|f| f.path == "lib.rs").unwrap();
+        assert!(!lib.identical);
+        let diff = lib.diff.as_deref().unwrap();
+        assert!(diff.contains("-fn b() {}") && diff.contains("+fn c() {}"));
+        assert!(files.iter().find(|f| f.path == "new.rs").unwrap().identical);
+
+        assert_eq!(better_of(&a, &b), Some("b".to_string()));
+    }
+}
@@ -0,0 +1,348 @@
+//! Context budget checks for assembled prompts
+//!
+//! Sessions and one-shots describe what they are about to send as a list of
+//! [`Attachment`]s. [`check`] compares the estimated total against the
+//! model's context window and, when it doesn't fit, returns a
+//! [`BudgetReport`] (per-attachment token counts, what to drop, what could be
+//! summarized) instead of letting the CLI fail on an oversized prompt.
+//! Session attachments come from [`session_context_files`], the same list
+//! `build_claude_args` concatenates into the combined context file.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::projects::github_issues::{
+    get_github_contexts_dir, get_session_issue_refs, get_session_pr_refs,
+};
+use crate::runtime::{EventSink, PathProvider};
+
+/// Rough characters per token for estimates
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Context window of current Claude models
+pub const DEFAULT_CONTEXT_WINDOW: usize = 200_000;
+
+/// Context window of `[1m]` model variants
+const LONG_CONTEXT_WINDOW: usize = 1_000_000;
+
+/// Kept free for the CLI's own system prompt, tool definitions and the reply
+const RESERVED_TOKENS: usize = 30_000;
+
+/// Saved contexts smaller than this aren't worth summarizing
+const SUMMARIZE_MIN_TOKENS: usize = 2_000;
+
+/// What an attachment is, which decides whether it can be dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentKind {
+    Issue,
+    PullRequest,
+    SavedContext,
+    /// The user's message or one-shot question
+    Message,
+    /// Text selected for a one-shot
+    Selection,
+}
+
+impl AttachmentKind {
+    /// Whether the user can detach it and still send the prompt
+    fn droppable(self) -> bool {
+        matches!(self, Self::Issue | Self::PullRequest | Self::SavedContext)
+    }
+}
+
+/// One part of an assembled prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub kind: AttachmentKind,
+    /// Identifies it for removal: the issue/PR number or the saved context slug
+    pub key: String,
+    pub name: String,
+    pub path: Option<String>,
+    pub tokens: usize,
+}
+
+impl Attachment {
+    pub fn text(kind: AttachmentKind, name: &str, text: &str) -> Self {
+        Self {
+            kind,
+            key: String::new(),
+            name: name.to_string(),
+            path: None,
+            tokens: estimate_tokens(text),
+        }
+    }
+
+    /// Read a context file; its name is the first `# ` heading, if any
+    fn file(kind: AttachmentKind, key: String, path: &std::path::Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let name = content
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("# "))
+            .map(str::to_string)
+            .unwrap_or_else(|| key.clone());
+        Some(Self {
+            kind,
+            key,
+            name,
+            path: Some(path.to_string_lossy().to_string()),
+            tokens: estimate_tokens(&content),
+        })
+    }
+}
+
+/// Why a prompt was not sent and how to make it fit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetReport {
+    pub model: String,
+    pub context_window: usize,
+    /// Tokens available to attachments after [`RESERVED_TOKENS`], or a
+    /// sub-project's budget if lower
+    pub budget: usize,
+    pub total_tokens: usize,
+    /// Attachments, largest first
+    pub attachments: Vec<Attachment>,
+    /// Droppable attachments (largest first) whose removal brings the prompt
+    /// within budget; all of them if even that isn't enough
+    pub suggested_drops: Vec<Attachment>,
+    /// Saved contexts large enough to be worth summarizing in place
+    pub summarizable: Vec<Attachment>,
+}
+
+impl BudgetReport {
+    pub fn describe(&self) -> String {
+        format!(
+            "Prompt is about {} tokens, over the {} available to {} ({} token context). \
+             Remove or summarize some attached context and try again.",
+            self.total_tokens, self.budget, self.model, self.context_window
+        )
+    }
+}
+
+/// Payload for `context:over-budget`
+#[derive(Debug, Clone, Serialize)]
+struct OverBudgetEvent<'a> {
+    /// None for one-shots
+    session_id: Option<&'a str>,
+    report: &'a BudgetReport,
+}
+
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(CHARS_PER_TOKEN)
+}
+
+pub fn context_window(model: &str) -> usize {
+    if model.ends_with("[1m]") {
+        LONG_CONTEXT_WINDOW
+    } else {
+        DEFAULT_CONTEXT_WINDOW
+    }
+}
+
+/// Ok if the attachments fit `model`'s context, otherwise what to change
+pub fn check(model: &str, attachments: Vec<Attachment>) -> Result<(), BudgetReport> {
+    check_within(model, attachments, None)
+}
+
+/// [`check`] against a smaller budget, e.g. a sub-project's
+pub fn check_within(
+    model: &str,
+    mut attachments: Vec<Attachment>,
+    limit: Option<usize>,
+) -> Result<(), BudgetReport> {
+    let context_window = context_window(model);
+    let budget = context_window.saturating_sub(RESERVED_TOKENS);
+    let budget = limit.map_or(budget, |limit| budget.min(limit));
+    let total_tokens: usize = attachments.iter().map(|a| a.tokens).sum();
+    if total_tokens <= budget {
+        return Ok(());
+    }
+
+    attachments.sort_by(|a, b| b.tokens.cmp(&a.tokens));
+    let mut remaining = total_tokens;
+    let suggested_drops = attachments
+        .iter()
+        .filter(|a| a.kind.droppable())
+        .take_while(|a| {
+            let needed = remaining > budget;
+            remaining -= a.tokens;
+            needed
+        })
+        .cloned()
+        .collect();
+    let summarizable = attachments
+        .iter()
+        .filter(|a| a.kind == AttachmentKind::SavedContext && a.tokens >= SUMMARIZE_MIN_TOKENS)
+        .cloned()
+        .collect();
+
+    Err(BudgetReport {
+        model: model.to_string(),
+        context_window,
+        budget,
+        total_tokens,
+        attachments,
+        suggested_drops,
+        summarizable,
+    })
+}
+
+/// Tell the frontend why a prompt wasn't sent
+pub fn emit_over_budget(app: &impl EventSink, session_id: Option<&str>, report: &BudgetReport) {
+    log::warn!("{}", report.describe());
+    let _ = app.emit_all(
+        "context:over-budget",
+        &OverBudgetEvent { session_id, report },
+    );
+}
+
+/// Issue, PR and saved context files attached to a session, in prompt order
+pub fn session_context_files(
+    app: &tauri::AppHandle,
+    session_id: &str,
+) -> Vec<(AttachmentKind, String, PathBuf)> {
+    let mut files = Vec::new();
+
+    // Issue and PR contexts live in shared storage, keyed "{owner}-{repo}-{number}"
+    if let Ok(contexts_dir) = get_github_contexts_dir(app) {
+        let refs = [
+            (
+                AttachmentKind::Issue,
+                "issue",
+                get_session_issue_refs(app, session_id),
+            ),
+            (
+                AttachmentKind::PullRequest,
+                "pr",
+                get_session_pr_refs(app, session_id),
+            ),
+        ];
+        for (kind, label, keys) in refs {
+            for key in keys.unwrap_or_default() {
+                let Some((repo_key, number)) = key.rsplit_once('-') else {
+                    continue;
+                };
+                let path = contexts_dir.join(format!("{repo_key}-{label}-{number}.md"));
+                if path.exists() {
+                    log::trace!("Adding {label} context file: {path:?}");
+                    files.push((kind, number.to_string(), path));
+                }
+            }
+        }
+    }
+
+    // Saved contexts are copied per session as "{session_id}-context-{slug}.md"
+    if let Ok(app_data_dir) = app.app_data_dir() {
+        let prefix = format!("{session_id}-context-");
+        if let Ok(entries) = std::fs::read_dir(app_data_dir.join("session-context")) {
+            let mut saved: Vec<_> = entries
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let slug = name.strip_prefix(&prefix)?.strip_suffix(".md")?;
+                    Some((AttachmentKind::SavedContext, slug.to_string(), entry.path()))
+                })
+                .collect();
+            saved.sort_by(|a, b| a.2.cmp(&b.2));
+            log::debug!(
+                "Found {} saved context files for session {session_id}",
+                saved.len()
+            );
+            files.extend(saved);
+        }
+    }
+
+    files
+}
+
+/// A session's attached context plus the message about to be sent
+pub fn session_attachments(
+    app: &tauri::AppHandle,
+    session_id: &str,
+    message: &str,
+) -> Vec<Attachment> {
+    session_context_files(app, session_id)
+        .into_iter()
+        .filter_map(|(kind, key, path)| Attachment::file(kind, key, &path))
+        .chain([Attachment::text(
+            AttachmentKind::Message,
+            "Message",
+            message,
+        )])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(kind: AttachmentKind, name: &str, tokens: usize) -> Attachment {
+        Attachment {
+            kind,
+            key: name.to_string(),
+            name: name.to_string(),
+            path: None,
+            tokens,
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let fits = vec![
+            attachment(AttachmentKind::Issue, "issue", 50_000),
+            attachment(AttachmentKind::Message, "message", 1_000),
+        ];
+        assert!(check("opus", fits).is_ok());
+
+        let report = check(
+            "opus",
+            vec![
+                attachment(AttachmentKind::Message, "message", 60_000),
+                attachment(AttachmentKind::Issue, "issue", 20_000),
+                attachment(AttachmentKind::SavedContext, "plan", 70_000),
+                attachment(AttachmentKind::PullRequest, "pr", 40_000),
+                attachment(AttachmentKind::SavedContext, "notes", 1_000),
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(report.total_tokens, 191_000);
+        assert_eq!(report.budget, 170_000);
+        assert_eq!(report.attachments[0].name, "plan");
+        // Dropping the largest droppable attachment is enough
+        let drops: Vec<_> = report.suggested_drops.iter().map(|a| &a.name).collect();
+        assert_eq!(drops, ["plan"]);
+        let summarizable: Vec<_> = report.summarizable.iter().map(|a| &a.name).collect();
+        assert_eq!(summarizable, ["plan"]);
+
+        // The same prompt fits a long-context model
+        let long = vec![attachment(AttachmentKind::SavedContext, "plan", 191_000)];
+        assert!(check("opus[1m]", long).is_ok());
+
+        // A message too large on its own: every droppable attachment is suggested
+        let report = check(
+            "haiku",
+            vec![
+                attachment(AttachmentKind::Selection, "selection", 180_000),
+                attachment(AttachmentKind::Issue, "a", 5_000),
+                attachment(AttachmentKind::Issue, "b", 3_000),
+            ],
+        )
+        .unwrap_err();
+        let drops: Vec<_> = report.suggested_drops.iter().map(|a| &a.name).collect();
+        assert_eq!(drops, ["a", "b"]);
+
+        // A sub-project budget only ever lowers the limit
+        let prompt = || vec![attachment(AttachmentKind::Issue, "issue", 60_000)];
+        let report = check_within("opus", prompt(), Some(50_000)).unwrap_err();
+        assert_eq!(report.budget, 50_000);
+        assert!(check_within("opus", prompt(), Some(500_000)).is_ok());
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+}
@@ -0,0 +1,397 @@
+//! Documentation drafts from finished sessions
+//!
+//! `generate_session_doc` turns a session into a changelog entry, an
+//! architecture decision record or a runbook section: a summarization pass
+//! extracts the title, changes, decision or steps from the conversation,
+//! which fill a markdown template that is then written into the session's
+//! worktree for the agent or a human to refine. Paths and templates come
+//! from the `docs` section of the repo's jean.json, e.g.
+//!
+//! ```json
+//! { "docs": { "adr": { "path": "docs/decisions/{date}-{slug}.md" } } }
+//! ```
+//!
+//! Changelog entries go at the top of the file (below its title), runbook
+//! sections are appended, and each ADR gets a file of its own.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::projects::types::{DocTarget, JeanDocs};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocKind {
+    Changelog,
+    /// Architecture decision record
+    Adr,
+    Runbook,
+}
+
+/// Fields extracted from the conversation by the summarization pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocFields {
+    pub title: String,
+    pub slug: String,
+    pub summary: String,
+    #[serde(default)]
+    pub changes: Vec<String>,
+    #[serde(default)]
+    pub context: String,
+    #[serde(default)]
+    pub decision: String,
+    #[serde(default)]
+    pub consequences: String,
+    #[serde(default)]
+    pub steps: Vec<String>,
+}
+
+/// A written draft
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocDraft {
+    pub kind: DocKind,
+    /// Absolute path of the file written
+    pub path: String,
+    /// The generated section
+    pub content: String,
+}
+
+/// Prompt for the summarization pass
+pub(super) const DOC_PROMPT: &str = r#"Draft {doc_kind} from the following coding session, for the project's documentation. Write for readers who were not part of the session: state what changed and why, in plain words, without narrating the conversation.
+
+CONVERSATION TRANSCRIPT:
+{conversation}
+
+END OF TRANSCRIPT.
+
+Provide:
+- title: A short title (max 80 chars)
+- slug: A 2-5 word lowercase hyphenated slug for file names
+- summary: One short paragraph of what was done and why
+- changes: User-facing changes, one short sentence each (for a changelog)
+- context: The problem or forces that led to the decision (for an ADR)
+- decision: What was decided (for an ADR)
+- consequences: What follows from the decision, good and bad (for an ADR)
+- steps: Operational steps someone would follow, one per item (for a runbook)
+
+Leave fields that don't apply to {doc_kind} empty."#;
+
+/// JSON schema for the summarization pass
+pub(super) const DOC_SCHEMA: &str = r#"{"type":"object","properties":{"title":{"type":"string"},"slug":{"type":"string"},"summary":{"type":"string"},"changes":{"type":"array","items":{"type":"string"}},"context":{"type":"string"},"decision":{"type":"string"},"consequences":{"type":"string"},"steps":{"type":"array","items":{"type":"string"}}},"required":["title","slug","summary"]}"#;
+
+const DEFAULT_CHANGELOG_TEMPLATE: &str = "## {date}: {title}\n\n{changes}\n";
+
+const DEFAULT_ADR_TEMPLATE: &str = "# {title}\n\nDate: {date}\nStatus: Proposed\n\n## Context\n\n{context}\n\n## Decision\n\n{decision}\n\n## Consequences\n\n{consequences}\n";
+
+const DEFAULT_RUNBOOK_TEMPLATE: &str = "## {title}\n\n{summary}\n\n{steps}\n";
+
+impl DocKind {
+    /// How the prompt names the document
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Changelog => "a changelog entry",
+            Self::Adr => "an architecture decision record (ADR)",
+            Self::Runbook => "a runbook section",
+        }
+    }
+
+    fn default_path(self) -> &'static str {
+        match self {
+            Self::Changelog => "CHANGELOG.md",
+            Self::Adr => "docs/adr/{date}-{slug}.md",
+            Self::Runbook => "docs/runbook.md",
+        }
+    }
+
+    fn default_template(self) -> &'static str {
+        match self {
+            Self::Changelog => DEFAULT_CHANGELOG_TEMPLATE,
+            Self::Adr => DEFAULT_ADR_TEMPLATE,
+            Self::Runbook => DEFAULT_RUNBOOK_TEMPLATE,
+        }
+    }
+
+    fn target(self, docs: &JeanDocs) -> Option<&DocTarget> {
+        match self {
+            Self::Changelog => docs.changelog.as_ref(),
+            Self::Adr => docs.adr.as_ref(),
+            Self::Runbook => docs.runbook.as_ref(),
+        }
+    }
+}
+
+/// Lowercase, hyphenated, ASCII-only slug
+fn slugify(text: &str) -> String {
+    let slug: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug
+    }
+}
+
+fn bullets(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("- {}", item.trim()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn numbered(items: &[String]) -> String {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| format!("{}. {}", i + 1, item.trim()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Fill a template's placeholders
+pub fn render(template: &str, fields: &DocFields, date: &str, session_name: &str) -> String {
+    let changes = if fields.changes.is_empty() {
+        format!("- {}", fields.summary.trim())
+    } else {
+        bullets(&fields.changes)
+    };
+    [
+        ("{title}", fields.title.trim().to_string()),
+        ("{slug}", slugify(&fields.slug)),
+        ("{summary}", fields.summary.trim().to_string()),
+        ("{changes}", changes),
+        ("{context}", fields.context.trim().to_string()),
+        ("{decision}", fields.decision.trim().to_string()),
+        ("{consequences}", fields.consequences.trim().to_string()),
+        ("{steps}", numbered(&fields.steps)),
+        ("{date}", date.to_string()),
+        ("{session}", session_name.to_string()),
+    ]
+    .iter()
+    .fold(template.to_string(), |text, (key, value)| {
+        text.replace(key, value)
+    })
+}
+
+/// Template and repo-relative output path for a kind
+pub fn target_for(kind: DocKind, docs: &JeanDocs) -> (String, String) {
+    let target = kind.target(docs);
+    let path = target
+        .and_then(|t| t.path.clone())
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| kind.default_path().to_string());
+    let template = target
+        .and_then(|t| t.template.clone())
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| kind.default_template().to_string());
+    (path, template)
+}
+
+/// Resolve a configured path inside the worktree (no escaping it)
+pub fn resolve_path(
+    worktree: &Path,
+    path: &str,
+    date: &str,
+    slug: &str,
+) -> Result<PathBuf, String> {
+    let relative = path
+        .replace("{date}", date)
+        .replace("{slug}", &slugify(slug));
+    let relative = Path::new(relative.trim());
+    if relative.components().any(|c| {
+        matches!(
+            c,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_)
+        )
+    }) {
+        return Err(format!(
+            "Doc path must stay inside the repository: {}",
+            relative.display()
+        ));
+    }
+    Ok(worktree.join(relative))
+}
+
+/// Add a section to an existing document: after the title for changelogs,
+/// at the end otherwise
+pub fn insert_section(kind: DocKind, existing: &str, section: &str) -> String {
+    let section = section.trim_end();
+    if existing.trim().is_empty() {
+        return match kind {
+            DocKind::Changelog => format!("# Changelog\n\n{section}\n"),
+            _ => format!("{section}\n"),
+        };
+    }
+    match kind {
+        DocKind::Changelog => {
+            let mut lines = existing.lines();
+            match lines.clone().next() {
+                Some(first) if first.starts_with("# ") => {
+                    lines.next();
+                    let rest = lines.collect::<Vec<_>>().join("\n");
+                    format!("{first}\n\n{section}\n\n{}\n", rest.trim())
+                }
+                _ => format!("{section}\n\n{}\n", existing.trim()),
+            }
+        }
+        DocKind::Adr | DocKind::Runbook => {
+            format!("{}\n\n{section}\n", existing.trim_end())
+        }
+    }
+}
+
+/// `path`, or `path` with a numeric suffix if it already exists
+fn unused_path(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|n| path.with_file_name(format!("{stem}-{n}{extension}")))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(path)
+}
+
+/// Render and write a draft into the worktree
+pub fn write_draft(
+    kind: DocKind,
+    worktree: &Path,
+    docs: &JeanDocs,
+    fields: &DocFields,
+    date: &str,
+    session_name: &str,
+) -> Result<DocDraft, String> {
+    let (path, template) = target_for(kind, docs);
+    let path = resolve_path(worktree, &path, date, &fields.slug)?;
+    let content = render(&template, fields, date, session_name);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    let (path, text) = match kind {
+        DocKind::Adr => (unused_path(path), format!("{}\n", content.trim_end())),
+        DocKind::Changelog | DocKind::Runbook => {
+            let existing = fs::read_to_string(&path).unwrap_or_default();
+            let text = insert_section(kind, &existing, &content);
+            (path, text)
+        }
+    };
+    fs::write(&path, text).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+
+    Ok(DocDraft {
+        kind,
+        path: path.to_string_lossy().into_owned(),
+        content,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> DocFields {
+        DocFields {
+            title: "Retry failed uploads".to_string(),
+            slug: "Retry Uploads".to_string(),
+            summary: "Uploads now retry with backoff.".to_string(),
+            changes: vec!["Failed uploads are retried".to_string()],
+            decision: "Retry three times".to_string(),
+            steps: vec!["Check the queue".to_string(), "Restart it".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let (_, template) = target_for(DocKind::Runbook, &JeanDocs::default());
+        assert_eq!(
+            render(&template, &fields(), "2024-03-05", "S"),
+            "## Retry failed uploads\n\nUploads now retry with backoff.\n\n1. Check the queue\n2. Restart it\n"
+        );
+        assert_eq!(
+            render("{date} {slug}: {changes}", &fields(), "2024-03-05", "S"),
+            "2024-03-05 retry-uploads: - Failed uploads are retried"
+        );
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            resolve_path(
+                root,
+                "docs/adr/{date}-{slug}.md",
+                "2024-03-05",
+                "Retry Uploads"
+            )
+            .unwrap(),
+            root.join("docs/adr/2024-03-05-retry-uploads.md")
+        );
+        assert!(resolve_path(root, "../outside.md", "", "").is_err());
+        assert!(resolve_path(root, "/etc/passwd", "", "").is_err());
+    }
+
+    #[test]
+    fn test_insert_section() {
+        let changelog = "# Changelog\n\n## 2024-01-01: Older\n";
+        assert_eq!(
+            insert_section(DocKind::Changelog, changelog, "## 2024-03-05: New\n"),
+            "# Changelog\n\n## 2024-03-05: New\n\n## 2024-01-01: Older\n"
+        );
+        assert_eq!(
+            insert_section(DocKind::Changelog, "", "## New"),
+            "# Changelog\n\n## New\n"
+        );
+        assert_eq!(
+            insert_section(DocKind::Runbook, "# Runbook\n", "## New"),
+            "# Runbook\n\n## New\n"
+        );
+    }
+
+    #[test]
+    fn test_write_draft() {
+        let dir = tempfile::tempdir().unwrap();
+        let docs = JeanDocs::default();
+        let first = write_draft(
+            DocKind::Adr,
+            dir.path(),
+            &docs,
+            &fields(),
+            "2024-03-05",
+            "S",
+        )
+        .unwrap();
+        let second = write_draft(
+            DocKind::Adr,
+            dir.path(),
+            &docs,
+            &fields(),
+            "2024-03-05",
+            "S",
+        )
+        .unwrap();
+        assert!(first.path.ends_with("docs/adr/2024-03-05-retry-uploads.md"));
+        assert!(second
+            .path
+            .ends_with("docs/adr/2024-03-05-retry-uploads-2.md"));
+        let adr = fs::read_to_string(&first.path).unwrap();
+        assert!(adr.contains("## Decision\n\nRetry three times"));
+    }
+}
@@ -0,0 +1,387 @@
+//! Agent backends other than the Claude CLI
+//!
+//! A CLI profile's settings JSON picks the backend with `"backend"`: absent
+//! or "claude" runs the Claude CLI, "aider" runs aider (see `aider`), "codex"
+//! runs `codex exec` (see `codex`), and any other name refers to a custom
+//! backend manifest (see `agent_protocol::custom`). These backends write their own output format to
+//! a side file next to the run log; a normalizer thread runs it through the
+//! matching `agent_protocol` adapter and appends the stream-json lines the
+//! Claude CLI would have written, so tailing, run logs and replay stay
+//! unaware of which backend produced a run. Plan mode (see `is_plan_mode`)
+//! makes aider answer only and puts Codex in its read-only sandbox.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::aider::AiderProfile;
+use super::claude::{ClaudeResponse, ErrorEvent};
+use super::codex::CodexProfile;
+use super::launch::LaunchDefaults;
+use crate::agent_protocol::custom::{CustomBackend, StreamFormat, TemplateVars};
+use crate::agent_protocol::drift::SchemaDrift;
+use crate::agent_protocol::{AgentEvent, StreamJsonEncoder};
+use crate::cli_capabilities::args::{describe_errors, ArgBuilder};
+use crate::cli_capabilities::CliCapabilities;
+use crate::http_server::EmitExt;
+use crate::runtime::PathProvider;
+
+/// A backend selected by a CLI profile
+pub enum ExternalBackend {
+    Aider(AiderProfile),
+    Codex(CodexProfile),
+    Custom {
+        backend: CustomBackend,
+        /// Profile overrides of the session model and the manifest's env
+        model: Option<String>,
+        env: BTreeMap<String, String>,
+    },
+}
+
+/// Fields every profile may set for an external backend
+#[derive(Default, Deserialize)]
+struct ProfileOverrides {
+    #[serde(default)]
+    backend: Option<String>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+}
+
+/// Whether a run in `execution_mode` must leave the worktree alone, like the
+/// Claude CLI's plan mode (also the default when no mode is given)
+pub(super) fn is_plan_mode(execution_mode: Option<&str>) -> bool {
+    !matches!(execution_mode, Some("build") | Some("yolo"))
+}
+
+/// How to run an external CLI for one message
+pub(super) struct ExternalRun {
+    /// Used in logs and errors
+    pub label: String,
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    /// File piped to the CLI's stdin
+    pub stdin_file: PathBuf,
+    pub format: StreamFormat,
+}
+
+impl ExternalBackend {
+    /// The backend a profile selects, or None for the Claude CLI
+    pub fn from_settings(
+        app: &impl PathProvider,
+        settings: Option<&str>,
+    ) -> Result<Option<Self>, String> {
+        let overrides: ProfileOverrides = settings
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default();
+        match overrides.backend.as_deref() {
+            None | Some("claude") => Ok(None),
+            Some("aider") => Ok(AiderProfile::from_settings(settings).map(Self::Aider)),
+            Some("codex") => Ok(CodexProfile::from_settings(settings).map(Self::Codex)),
+            Some(name) => {
+                let backend = crate::agent_protocol::custom::find_backend(app, name)
+                    .ok_or_else(|| format!("Unknown agent backend: {name}"))?;
+                Ok(Some(Self::Custom {
+                    backend,
+                    model: overrides.model,
+                    env: overrides.env,
+                }))
+            }
+        }
+    }
+
+    /// How to run the backend for one message, with launch `defaults` applied
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn prepare(
+        &self,
+        app: &tauri::AppHandle,
+        session_id: &str,
+        input_file: &Path,
+        message_file: &Path,
+        working_dir: &Path,
+        message: &str,
+        model: Option<&str>,
+        execution_mode: Option<&str>,
+        resume_id: Option<&str>,
+        defaults: &LaunchDefaults,
+    ) -> Result<ExternalRun, String> {
+        let mut run = match self {
+            Self::Aider(profile) => super::aider::prepare_run(
+                app,
+                session_id,
+                input_file,
+                message_file,
+                model,
+                execution_mode,
+                profile,
+                &defaults.args,
+            ),
+            Self::Codex(profile) => super::codex::prepare_run(
+                app,
+                message_file,
+                working_dir,
+                model,
+                execution_mode,
+                resume_id,
+                profile,
+                &defaults.args,
+            ),
+            Self::Custom {
+                backend,
+                model: profile_model,
+                env,
+            } => {
+                // Manifests don't say what their CLI auto-approves, so they
+                // can't be held to plan mode
+                if crate::emergency::is_engaged() {
+                    return Err(format!(
+                        "Emergency stop is engaged; the {} backend can't run until it's cleared",
+                        backend.name
+                    ));
+                }
+                let (program, mut args) = backend.render(&TemplateVars {
+                    message_file,
+                    message,
+                    model: profile_model.as_deref().or(model),
+                    cwd: working_dir,
+                })?;
+                // Manifests don't list their flags, so only the form is checked
+                let capabilities = CliCapabilities::default();
+                let mut extra = ArgBuilder::new(&capabilities, &[]);
+                extra.user_args(&defaults.args, &[]);
+                args.extend(
+                    extra
+                        .build(&[])
+                        .map_err(|errors| describe_errors(&backend.name, &errors))?,
+                );
+                let env = backend
+                    .env
+                    .iter()
+                    .chain(env)
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                Ok(ExternalRun {
+                    label: backend.name.clone(),
+                    program,
+                    args,
+                    env,
+                    stdin_file: if backend.capabilities.stdin_prompt {
+                        message_file.to_path_buf()
+                    } else {
+                        input_file.to_path_buf()
+                    },
+                    format: backend.stream_format,
+                })
+            }
+        }?;
+        // Profile and backend variables win over launch defaults
+        let env = std::mem::take(&mut run.env);
+        run.env = defaults
+            .env
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .chain(env)
+            .collect();
+        Ok(run)
+    }
+
+    /// Run the backend detached, like `execute_claude_detached`.
+    /// `resume_id` is the conversation ID the backend reported last time.
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute(
+        &self,
+        app: &tauri::AppHandle,
+        session_id: &str,
+        worktree_id: &str,
+        input_file: &Path,
+        output_file: &Path,
+        working_dir: &Path,
+        message: &str,
+        model: Option<&str>,
+        execution_mode: Option<&str>,
+        resume_id: Option<&str>,
+        defaults: &LaunchDefaults,
+    ) -> Result<(u32, ClaudeResponse), String> {
+        let emit_error = |error_msg: String| {
+            log::error!("{error_msg}");
+            let _ = app.emit_all(
+                "chat:error",
+                &ErrorEvent {
+                    session_id: session_id.to_string(),
+                    worktree_id: worktree_id.to_string(),
+                    error: error_msg.clone(),
+                },
+            );
+            error_msg
+        };
+
+        let message_file = output_file.with_extension("message.md");
+        fs::write(&message_file, message)
+            .map_err(|e| emit_error(format!("Failed to write message file: {e}")))?;
+        let run = self
+            .prepare(
+                app,
+                session_id,
+                input_file,
+                &message_file,
+                working_dir,
+                message,
+                model,
+                execution_mode,
+                resume_id,
+                defaults,
+            )
+            .map_err(emit_error)?;
+
+        let raw_file = output_file.with_extension("agent.log");
+        fs::write(&raw_file, "")
+            .map_err(|e| emit_error(format!("Failed to create agent output file: {e}")))?;
+
+        log::trace!(
+            "Executing {} (detached) for session: {session_id}",
+            run.label
+        );
+        log::debug!(
+            "{} command: {} {}",
+            run.label,
+            run.program.display(),
+            run.args.join(" ")
+        );
+
+        let mut env_vars = run.env.clone();
+        if let Some(path) = crate::tool_install::session_path(app) {
+            env_vars.push(("PATH".to_string(), path));
+        }
+        let env_refs: Vec<(&str, &str)> = env_vars
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+
+        let pid = super::detached::spawn_detached_claude(
+            &run.program,
+            &run.args,
+            &run.stdin_file,
+            &raw_file,
+            working_dir,
+            &env_refs,
+        )
+        .map_err(|e| emit_error(format!("Failed to start {}: {e}", run.label)))?;
+        log::trace!("Detached {} spawned with PID: {pid}", run.label);
+
+        let output_path = output_file.to_path_buf();
+        let format = run.format;
+        let (events, backend, session) = (app.clone(), run.label.clone(), session_id.to_string());
+        crate::background_tasks::supervisor::spawn_thread("agent-normalize", move || {
+            let report = |drift| {
+                crate::agent_protocol::drift::report(&events, &backend, Some(&session), drift)
+            };
+            if let Err(e) = normalize_output(format, &raw_file, &output_path, pid, report) {
+                log::warn!("Failed to normalize agent output: {e}");
+            }
+            let _ = fs::remove_file(&raw_file);
+            let _ = fs::remove_file(&message_file);
+        });
+
+        super::registry::register_process(session_id.to_string(), pid);
+        let response =
+            super::claude::tail_claude_output(app, session_id, worktree_id, output_file, pid);
+        super::registry::unregister_process(session_id);
+        Ok((pid, response?))
+    }
+}
+
+/// Follow a CLI's raw output until it exits, appending normalized lines to
+/// the run's output file and passing schema drift to `report`
+fn normalize_output(
+    format: StreamFormat,
+    raw_file: &Path,
+    output_file: &Path,
+    pid: u32,
+    report: impl Fn(Vec<SchemaDrift>),
+) -> Result<(), String> {
+    use super::detached::is_process_alive;
+    use super::tail::{NdjsonTailer, POLL_INTERVAL};
+
+    let mut tailer = NdjsonTailer::new_from_start(raw_file)?;
+    let mut out = fs::OpenOptions::new()
+        .append(true)
+        .open(output_file)
+        .map_err(|e| format!("Failed to open output file: {e}"))?;
+    let mut adapter = format.adapter();
+    let mut encoder = StreamJsonEncoder::default();
+    // Returns whether the events included the final result
+    let mut write = |events: Vec<AgentEvent>| -> Result<bool, String> {
+        for message in events.iter().flat_map(|e| encoder.encode(e)) {
+            writeln!(out, "{message}").map_err(|e| format!("Failed to write output: {e}"))?;
+        }
+        Ok(events.iter().any(|e| matches!(e, AgentEvent::Done { .. })))
+    };
+
+    let mut done = false;
+    loop {
+        // Check before polling so the last output is read after exit
+        let alive = is_process_alive(pid);
+        for line in tailer.poll()? {
+            done |= write(adapter.parse_line(&line))?;
+        }
+        done |= write(adapter.flush())?;
+        report(adapter.take_drift());
+        if !alive {
+            break;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    done |= write(adapter.finish())?;
+    report(adapter.take_drift());
+
+    // The tailer only finishes on a result, so always end with one
+    if !done {
+        write(vec![AgentEvent::Done {
+            result: None,
+            is_error: true,
+        }])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_backend_selection() {
+        let paths = TempPaths::new();
+        assert!(ExternalBackend::from_settings(&paths, None)
+            .unwrap()
+            .is_none());
+        let claude = r#"{"backend":"claude","env":{"A":"1"}}"#;
+        assert!(ExternalBackend::from_settings(&paths, Some(claude))
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            ExternalBackend::from_settings(&paths, Some(r#"{"backend":"aider"}"#)),
+            Ok(Some(ExternalBackend::Aider(_)))
+        ));
+        assert!(matches!(
+            ExternalBackend::from_settings(&paths, Some(r#"{"backend":"codex"}"#)),
+            Ok(Some(ExternalBackend::Codex(_)))
+        ));
+        assert!(ExternalBackend::from_settings(&paths, Some(r#"{"backend":"acme"}"#)).is_err());
+
+        let manifest: CustomBackend =
+            serde_json::from_str(r#"{"name":"acme","command":["/bin/acme","{message}"]}"#).unwrap();
+        crate::agent_protocol::custom::save_backend(&paths, &manifest).unwrap();
+        let selected =
+            ExternalBackend::from_settings(&paths, Some(r#"{"backend":"acme","model":"m"}"#));
+        assert!(matches!(
+            selected,
+            Ok(Some(ExternalBackend::Custom { model: Some(ref m), .. })) if m == "m"
+        ));
+    }
+}
@@ -0,0 +1,391 @@
+//! Paginated session history across all projects
+//!
+//! Built from session metadata only (no run logs are parsed), then filtered,
+//! sorted and paged server-side so the frontend never holds thousands of
+//! rows. Pages use keyset cursors (sort key + session ID), so sessions
+//! created or deleted between requests don't shift later pages.
+
+use serde::{Deserialize, Serialize};
+
+use super::storage::{load_index, load_metadata};
+use super::types::{RunStatus, SessionMetadata};
+use crate::projects::types::ProjectsData;
+use crate::runtime::PathProvider;
+
+/// Page size when the request doesn't specify one
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Largest page a request can ask for
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Sort order for history listings (always descending)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySort {
+    /// Most recent activity first
+    #[default]
+    Recent,
+    /// Highest total cost first
+    Cost,
+    /// Longest total run time first
+    Duration,
+}
+
+/// Filters and paging for list_session_history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryQuery {
+    /// `next_cursor` from the previous page (None for the first page)
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub sort: HistorySort,
+    pub project_id: Option<String>,
+    pub worktree_id: Option<String>,
+    /// Some(true) = archived only, Some(false) = active only, None = both
+    pub archived: Option<bool>,
+    /// Case-insensitive match on session name, label or any user message
+    pub search: Option<String>,
+}
+
+/// One session in the history listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHistoryEntry {
+    pub session_id: String,
+    pub name: String,
+    pub project_id: String,
+    pub project_name: String,
+    pub worktree_id: String,
+    pub worktree_name: String,
+    pub worktree_path: String,
+    pub created_at: u64,
+    /// End of the latest run (or creation time if there are no runs)
+    pub last_activity_at: u64,
+    pub archived_at: Option<u64>,
+    pub label: Option<String>,
+    pub message_count: u32,
+    pub run_count: u32,
+    /// Sum of reported run costs
+    pub cost_usd: f64,
+    /// Sum of finished run durations in seconds
+    pub duration_secs: u64,
+    /// Searchable user messages (not serialized)
+    #[serde(skip)]
+    user_messages: Vec<String>,
+}
+
+/// A page of results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage<T> {
+    pub items: Vec<T>,
+    /// Pass as `cursor` to get the next page; None on the last page
+    pub next_cursor: Option<String>,
+    /// Matching items across all pages
+    pub total: usize,
+}
+
+/// Number of messages a session shows (each run has a user message, plus an
+/// assistant message unless the send was undone)
+pub fn count_messages(metadata: &SessionMetadata) -> u32 {
+    metadata
+        .runs
+        .iter()
+        .map(|run| {
+            let is_undo_send =
+                run.status == RunStatus::Cancelled && run.assistant_message_id.is_none();
+            if is_undo_send {
+                0
+            } else if run.assistant_message_id.is_some() {
+                2 // user + assistant
+            } else {
+                1 // just user (still running or cancelled without response)
+            }
+        })
+        .sum()
+}
+
+/// Project/worktree context for a history entry
+struct WorktreeContext<'a> {
+    project_id: &'a str,
+    project_name: &'a str,
+    worktree_id: &'a str,
+    worktree_name: &'a str,
+    worktree_path: &'a str,
+}
+
+fn history_entry(metadata: SessionMetadata, ctx: &WorktreeContext) -> SessionHistoryEntry {
+    let last_activity_at = metadata
+        .runs
+        .iter()
+        .map(|r| r.ended_at.unwrap_or(r.started_at))
+        .max()
+        .unwrap_or(metadata.created_at);
+    let cost_usd = metadata
+        .runs
+        .iter()
+        .filter_map(|r| r.usage.as_ref().and_then(|u| u.cost_usd))
+        .sum();
+    let duration_secs = metadata
+        .runs
+        .iter()
+        .filter_map(|r| r.ended_at.map(|end| end.saturating_sub(r.started_at)))
+        .sum();
+
+    SessionHistoryEntry {
+        message_count: count_messages(&metadata),
+        run_count: metadata.runs.len() as u32,
+        session_id: metadata.id,
+        name: metadata.name,
+        project_id: ctx.project_id.to_string(),
+        project_name: ctx.project_name.to_string(),
+        worktree_id: ctx.worktree_id.to_string(),
+        worktree_name: ctx.worktree_name.to_string(),
+        worktree_path: ctx.worktree_path.to_string(),
+        created_at: metadata.created_at,
+        last_activity_at,
+        archived_at: metadata.archived_at,
+        label: metadata.label,
+        cost_usd,
+        duration_secs,
+        user_messages: metadata.runs.into_iter().map(|r| r.user_message).collect(),
+    }
+}
+
+/// Load history entries for every session in every worktree (including archived ones)
+///
+/// Project/worktree filters are applied here to skip loading unrelated metadata.
+pub fn load_history_entries(
+    app: &impl PathProvider,
+    projects: &ProjectsData,
+    query: &HistoryQuery,
+) -> Vec<SessionHistoryEntry> {
+    let mut entries = Vec::new();
+    for project in &projects.projects {
+        if query
+            .project_id
+            .as_ref()
+            .is_some_and(|id| *id != project.id)
+        {
+            continue;
+        }
+        for worktree in projects.worktrees_for_project(&project.id) {
+            if query
+                .worktree_id
+                .as_ref()
+                .is_some_and(|id| *id != worktree.id)
+            {
+                continue;
+            }
+            let index = match load_index(app, &worktree.id) {
+                Ok(index) => index,
+                Err(e) => {
+                    log::warn!("Failed to load sessions for worktree {}: {e}", worktree.id);
+                    continue;
+                }
+            };
+            let ctx = WorktreeContext {
+                project_id: &project.id,
+                project_name: &project.name,
+                worktree_id: &worktree.id,
+                worktree_name: &worktree.name,
+                worktree_path: &worktree.path,
+            };
+            for session in &index.sessions {
+                if let Ok(Some(metadata)) = load_metadata(app, &session.id) {
+                    entries.push(history_entry(metadata, &ctx));
+                }
+            }
+        }
+    }
+    entries
+}
+
+fn sort_key(entry: &SessionHistoryEntry, sort: HistorySort) -> f64 {
+    match sort {
+        HistorySort::Recent => entry.last_activity_at as f64,
+        HistorySort::Cost => entry.cost_usd,
+        HistorySort::Duration => entry.duration_secs as f64,
+    }
+}
+
+fn parse_cursor(cursor: &str) -> Result<(f64, &str), String> {
+    cursor
+        .split_once('|')
+        .and_then(|(key, id)| Some((key.parse().ok()?, id)))
+        .ok_or_else(|| format!("Invalid cursor: {cursor}"))
+}
+
+/// Filter, sort and page history entries
+pub fn query_history(
+    mut entries: Vec<SessionHistoryEntry>,
+    query: &HistoryQuery,
+) -> Result<HistoryPage<SessionHistoryEntry>, String> {
+    let search = query
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_lowercase);
+    entries.retain(|e| {
+        query.archived.is_none_or(|a| a == e.archived_at.is_some())
+            && search.as_ref().is_none_or(|s| {
+                e.name.to_lowercase().contains(s)
+                    || e.label
+                        .as_ref()
+                        .is_some_and(|l| l.to_lowercase().contains(s))
+                    || e.user_messages.iter().any(|m| m.to_lowercase().contains(s))
+            })
+    });
+
+    // Descending by key, ties broken by ascending ID so the order is total
+    let sort = query.sort;
+    entries.sort_by(|a, b| {
+        sort_key(b, sort)
+            .total_cmp(&sort_key(a, sort))
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    let total = entries.len();
+
+    let start = match query.cursor.as_deref() {
+        Some(cursor) => {
+            let (key, id) = parse_cursor(cursor)?;
+            entries
+                .iter()
+                .position(|e| {
+                    let k = sort_key(e, sort);
+                    k < key || (k == key && e.session_id.as_str() > id)
+                })
+                .unwrap_or(total)
+        }
+        None => 0,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let items: Vec<SessionHistoryEntry> = entries.into_iter().skip(start).take(limit).collect();
+    let next_cursor = if start + items.len() < total {
+        items
+            .last()
+            .map(|last| format!("{}|{}", sort_key(last, sort), last.session_id))
+    } else {
+        None
+    };
+
+    Ok(HistoryPage {
+        items,
+        next_cursor,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        id: &str,
+        last_activity_at: u64,
+        cost_usd: f64,
+        archived: bool,
+    ) -> SessionHistoryEntry {
+        SessionHistoryEntry {
+            session_id: id.to_string(),
+            name: format!("Session {id}"),
+            project_id: "p".to_string(),
+            project_name: "Project".to_string(),
+            worktree_id: "w".to_string(),
+            worktree_name: "wt".to_string(),
+            worktree_path: "/tmp/wt".to_string(),
+            created_at: 0,
+            last_activity_at,
+            archived_at: archived.then_some(1),
+            label: None,
+            message_count: 0,
+            run_count: 0,
+            cost_usd,
+            duration_secs: 0,
+            user_messages: vec![format!("message for {id}")],
+        }
+    }
+
+    fn ids(page: &HistoryPage<SessionHistoryEntry>) -> Vec<&str> {
+        page.items.iter().map(|e| e.session_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_pages_follow_cursor() {
+        let entries: Vec<_> = (0..5)
+            .map(|i| entry(&format!("s{i}"), 100 + i, 0.0, false))
+            .collect();
+        let mut query = HistoryQuery {
+            limit: Some(2),
+            ..Default::default()
+        };
+
+        let first = query_history(entries.clone(), &query).unwrap();
+        assert_eq!(ids(&first), ["s4", "s3"]);
+        assert_eq!(first.total, 5);
+
+        query.cursor = first.next_cursor;
+        let second = query_history(entries.clone(), &query).unwrap();
+        assert_eq!(ids(&second), ["s2", "s1"]);
+
+        // A session deleted between requests doesn't shift the next page
+        let without_s2: Vec<_> = entries
+            .into_iter()
+            .filter(|e| e.session_id != "s2")
+            .collect();
+        query.cursor = second.next_cursor;
+        let third = query_history(without_s2, &query).unwrap();
+        assert_eq!(ids(&third), ["s0"]);
+        assert!(third.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_sort_by_cost_breaks_ties_by_id() {
+        let entries = vec![
+            entry("b", 1, 2.0, false),
+            entry("a", 2, 2.0, false),
+            entry("c", 3, 5.0, false),
+        ];
+        let query = HistoryQuery {
+            sort: HistorySort::Cost,
+            ..Default::default()
+        };
+        let page = query_history(entries, &query).unwrap();
+        assert_eq!(ids(&page), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_filters() {
+        let entries = vec![
+            entry("a", 1, 0.0, true),
+            entry("b", 2, 0.0, false),
+            entry("c", 3, 0.0, false),
+        ];
+        let archived = HistoryQuery {
+            archived: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&query_history(entries.clone(), &archived).unwrap()),
+            ["a"]
+        );
+
+        let search = HistoryQuery {
+            search: Some("MESSAGE FOR b".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&query_history(entries.clone(), &search).unwrap()),
+            ["b"]
+        );
+
+        let bad_cursor = HistoryQuery {
+            cursor: Some("nope".to_string()),
+            ..Default::default()
+        };
+        assert!(query_history(entries, &bad_cursor).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+//! Pending agent questions for non-interactive runs
+//!
+//! When Claude calls `AskUserQuestion`, the detached CLI process is stopped and
+//! the run ends. For unattended (queued) sessions this used to be a dead end.
+//! We now remember the question together with the settings the run was started
+//! with, emit `session:input-requested`, and let `provide_session_input` resume
+//! the conversation (via `--resume`) with the user's answer.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use super::types::{EffortLevel, ThinkingLevel, ToolCall};
+
+/// Tools that block on user input and end the run until answered
+const INPUT_REQUEST_TOOLS: &[&str] = &["AskUserQuestion"];
+
+/// Settings of the run that asked the question, reused when resuming with the answer.
+/// Also recorded on the session when a conversation starts, for re-runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeSettings {
+    pub worktree_id: String,
+    pub worktree_path: String,
+    pub model: Option<String>,
+    pub execution_mode: Option<String>,
+    pub thinking_level: Option<ThinkingLevel>,
+    pub effort_level: Option<EffortLevel>,
+    pub disable_thinking_for_mode: Option<bool>,
+    pub parallel_execution_prompt: Option<String>,
+    pub ai_language: Option<String>,
+    pub allowed_tools: Option<Vec<String>>,
+    pub mcp_config: Option<String>,
+    pub chrome_enabled: Option<bool>,
+    pub custom_profile_settings: Option<String>,
+    pub background_priority: Option<bool>,
+}
+
+/// Payload for session:input-requested events sent to frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputRequestedEvent {
+    pub session_id: String,
+    pub worktree_id: String,
+    /// ID of the tool call that asked for input
+    pub tool_use_id: String,
+    /// Name of the tool (e.g. "AskUserQuestion")
+    pub tool_name: String,
+    /// Raw tool input (contains the questions and options)
+    pub input: serde_json::Value,
+    /// Unix timestamp when the request was raised
+    pub requested_at: u64,
+}
+
+struct PendingInput {
+    request: InputRequestedEvent,
+    settings: ResumeSettings,
+}
+
+/// Global registry of unanswered input requests by session_id
+static PENDING_INPUTS: Lazy<Mutex<HashMap<String, PendingInput>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Find the unanswered input-request tool call in a response, if any
+pub fn find_input_request(tool_calls: &[ToolCall]) -> Option<&ToolCall> {
+    tool_calls
+        .iter()
+        .rev()
+        .find(|tc| tc.output.is_none() && INPUT_REQUEST_TOOLS.contains(&tc.name.as_str()))
+}
+
+/// Remember an input request so it can be answered later
+pub fn register_pending_input(request: InputRequestedEvent, settings: ResumeSettings) {
+    log::trace!(
+        "Registering pending input request {} for session: {}",
+        request.tool_use_id,
+        request.session_id
+    );
+    PENDING_INPUTS.lock().unwrap().insert(
+        request.session_id.clone(),
+        PendingInput { request, settings },
+    );
+}
+
+/// Remove and return the pending input request for a session
+pub fn take_pending_input(session_id: &str) -> Option<(InputRequestedEvent, ResumeSettings)> {
+    PENDING_INPUTS
+        .lock()
+        .unwrap()
+        .remove(session_id)
+        .map(|p| (p.request, p.settings))
+}
+
+/// Get the pending input request for a session without consuming it
+pub fn get_pending_input(session_id: &str) -> Option<InputRequestedEvent> {
+    PENDING_INPUTS
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .map(|p| p.request.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_call(id: &str, name: &str, output: Option<&str>) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            name: name.to_string(),
+            input: serde_json::json!({}),
+            output: output.map(|s| s.to_string()),
+            parent_tool_use_id: None,
+        }
+    }
+
+    #[test]
+    fn test_find_input_request() {
+        let calls = vec![
+            tool_call("t1", "Read", Some("file contents")),
+            tool_call("t2", "AskUserQuestion", None),
+        ];
+        assert_eq!(
+            find_input_request(&calls).map(|tc| tc.id.as_str()),
+            Some("t2")
+        );
+    }
+
+    #[test]
+    fn test_find_input_request_ignores_answered_and_other_tools() {
+        let calls = vec![
+            tool_call("t1", "AskUserQuestion", Some("answered")),
+            tool_call("t2", "ExitPlanMode", None),
+        ];
+        assert!(find_input_request(&calls).is_none());
+    }
+}
@@ -0,0 +1,440 @@
+//! Default arguments and environment for agent backends
+//!
+//! Extra flags and environment variables passed to a backend on every run can
+//! be set at three levels: globally per backend (`launch_defaults` in
+//! preferences), per project and backend (`Project::launch_defaults`), and per
+//! CLI profile (`launchDefaults` in its settings JSON, for the backend the
+//! profile selects). Arguments are appended after Jean's own in that order;
+//! for environment variables the most specific level wins. The arguments go
+//! through the backend's `ArgBuilder`, so a flag the installed CLI doesn't
+//! support, or one Jean sets itself, stops the run with a clear error.
+//! `get_effective_launch_command` shows the full command a session's next
+//! message would run: binary, arguments, environment, working directory and
+//! sandbox policy.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::environment::{redact_args, redact_env_value};
+use super::external::ExternalBackend;
+use crate::cli_capabilities::args::describe_errors;
+use crate::projects::storage::load_projects_data;
+use crate::runtime::PathProvider;
+
+/// Key of the profile level in a CLI profile's settings JSON
+pub const PROFILE_KEY: &str = "launchDefaults";
+
+/// Backend of profiles that don't select one
+const CLAUDE_BACKEND: &str = "claude";
+
+/// Extra arguments and environment for one backend
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LaunchDefaults {
+    /// `--flag`, `--flag value` or `--flag=value` tokens
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl LaunchDefaults {
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty() && self.env.is_empty()
+    }
+
+    /// Reject blank arguments and environment names a process can't have
+    pub fn validate(&self) -> Result<(), String> {
+        if self.args.iter().any(|arg| arg.trim().is_empty()) {
+            return Err("Default arguments can't be empty".to_string());
+        }
+        match self
+            .env
+            .keys()
+            .find(|name| name.is_empty() || name.contains(['=', '\0']))
+        {
+            Some(name) => Err(format!("Invalid environment variable name: {name:?}")),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchLevel {
+    Global,
+    Project,
+    Profile,
+}
+
+/// The defaults one level sets
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LevelDefaults {
+    pub level: LaunchLevel,
+    #[serde(flatten)]
+    pub defaults: LaunchDefaults,
+}
+
+/// Backend a CLI profile's settings JSON selects
+pub fn backend_name(profile_settings: Option<&str>) -> String {
+    profile_settings
+        .and_then(|s| serde_json::from_str::<Value>(s).ok())
+        .and_then(|settings| settings.get("backend")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| CLAUDE_BACKEND.to_string())
+}
+
+/// The levels that set defaults for `backend`, least specific first
+pub fn levels(
+    global: &BTreeMap<String, LaunchDefaults>,
+    project: &BTreeMap<String, LaunchDefaults>,
+    backend: &str,
+    profile_settings: Option<&str>,
+) -> Vec<LevelDefaults> {
+    let profile = profile_settings
+        .and_then(|s| serde_json::from_str::<Value>(s).ok())
+        .and_then(|mut settings| {
+            serde_json::from_value(settings.get_mut(PROFILE_KEY)?.take()).ok()
+        });
+    [
+        (LaunchLevel::Global, global.get(backend).cloned()),
+        (LaunchLevel::Project, project.get(backend).cloned()),
+        (LaunchLevel::Profile, profile),
+    ]
+    .into_iter()
+    .filter_map(|(level, defaults)| {
+        Some(LevelDefaults {
+            level,
+            defaults: defaults?,
+        })
+    })
+    .filter(|level| !level.defaults.is_empty())
+    .collect()
+}
+
+/// Arguments of every level in order; each variable from the most specific
+pub fn merge(levels: &[LevelDefaults]) -> LaunchDefaults {
+    let mut merged = LaunchDefaults::default();
+    for level in levels {
+        merged.args.extend(level.defaults.args.iter().cloned());
+        merged.env.extend(level.defaults.env.clone());
+    }
+    merged
+}
+
+/// Global defaults by backend, read straight from preferences.json
+fn global_defaults(app: &impl PathProvider) -> BTreeMap<String, LaunchDefaults> {
+    #[derive(Default, Deserialize)]
+    struct Preferences {
+        #[serde(default)]
+        launch_defaults: BTreeMap<String, LaunchDefaults>,
+    }
+    app.app_data_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join("preferences.json")).ok())
+        .and_then(|content| serde_json::from_str::<Preferences>(&content).ok())
+        .unwrap_or_default()
+        .launch_defaults
+}
+
+/// The levels that apply to a run in `worktree_id` with a profile's settings
+pub fn resolve_levels(
+    app: &AppHandle,
+    worktree_id: &str,
+    profile_settings: Option<&str>,
+) -> Vec<LevelDefaults> {
+    let project = load_projects_data(app)
+        .ok()
+        .and_then(|data| {
+            let worktree = data.find_worktree(worktree_id)?;
+            Some(
+                data.find_project(&worktree.project_id)?
+                    .launch_defaults
+                    .clone(),
+            )
+        })
+        .unwrap_or_default();
+    levels(
+        &global_defaults(app),
+        &project,
+        &backend_name(profile_settings),
+        profile_settings,
+    )
+}
+
+/// Merged defaults for a run in `worktree_id` with a profile's settings
+pub fn resolve(
+    app: &AppHandle,
+    worktree_id: &str,
+    profile_settings: Option<&str>,
+) -> LaunchDefaults {
+    merge(&resolve_levels(app, worktree_id, profile_settings))
+}
+
+/// What `get_effective_launch_command` describes
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LaunchCommandOptions {
+    pub worktree_id: String,
+    pub session_id: String,
+    /// Defaults to the session's selected model
+    pub model: Option<String>,
+    pub execution_mode: Option<String>,
+    /// Settings JSON of the CLI profile, as passed to send_chat_message
+    pub custom_profile_settings: Option<String>,
+}
+
+/// What the agent may do without asking, as the arguments set it
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SandboxPolicy {
+    /// Claude permission mode or Codex sandbox; None leaves the CLI's default
+    pub mode: Option<String>,
+    pub allowed_tools: Vec<String>,
+    pub disallowed_tools: Vec<String>,
+    /// Directories the agent can access besides `cwd`
+    pub additional_dirs: Vec<String>,
+}
+
+/// The sandbox policy `args` give the backend (the last mode flag wins)
+pub fn sandbox_policy(args: &[String]) -> SandboxPolicy {
+    let mut policy = SandboxPolicy::default();
+    for pair in args.windows(2) {
+        let value = pair[1].clone();
+        match pair[0].as_str() {
+            "--permission-mode" | "--sandbox" => policy.mode = Some(value),
+            "--allowedTools" => policy.allowed_tools.push(value),
+            "--disallowedTools" => policy.disallowed_tools.push(value),
+            "--add-dir" => policy.additional_dirs.push(value),
+            _ => {}
+        }
+    }
+    if args
+        .iter()
+        .any(|arg| arg == "--dangerously-skip-permissions")
+    {
+        policy.mode = Some("bypassPermissions".to_string());
+    }
+    policy
+}
+
+/// The command a session's next message would start (secrets redacted)
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveLaunchCommand {
+    pub backend: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub cwd: String,
+    pub sandbox: SandboxPolicy,
+    /// Levels that set defaults, least specific first
+    pub defaults: Vec<LevelDefaults>,
+}
+
+/// Build (without running) the command for a session's next message
+pub fn effective_command(
+    app: &AppHandle,
+    options: &LaunchCommandOptions,
+) -> Result<EffectiveLaunchCommand, String> {
+    let settings = options.custom_profile_settings.as_deref();
+    let data = load_projects_data(app)?;
+    let worktree = data
+        .find_worktree(&options.worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {}", options.worktree_id))?;
+    // Same directory and resume ID as send_chat_message
+    let working_dir =
+        crate::projects::subprojects::for_session(app, &options.session_id, &options.worktree_id)
+            .map(|(_, dir)| dir)
+            .unwrap_or_else(|| PathBuf::from(&worktree.path));
+    let metadata = super::storage::load_metadata(app, &options.session_id)?;
+    let resume_id = metadata
+        .as_ref()
+        .and_then(|m| m.claude_session_id.as_deref());
+    let model = options
+        .model
+        .as_deref()
+        .or_else(|| metadata.as_ref()?.selected_model.as_deref());
+
+    let (program, args, env) = match ExternalBackend::from_settings(app, settings)? {
+        Some(backend) => {
+            let defaults = resolve(app, &options.worktree_id, settings);
+            let run = backend.prepare(
+                app,
+                &options.session_id,
+                Path::new("<input-file>"),
+                Path::new("<message-file>"),
+                &working_dir,
+                "<message>",
+                model,
+                options.execution_mode.as_deref(),
+                resume_id,
+                &defaults,
+            )?;
+            (run.program, run.args, run.env)
+        }
+        None => {
+            let program = crate::claude_cli::versions::resolve_binary(
+                app,
+                &options.worktree_id,
+                &working_dir,
+            )?;
+            let (args, env) = super::claude::build_claude_args(
+                app,
+                &options.session_id,
+                &options.worktree_id,
+                resume_id,
+                model,
+                options.execution_mode.as_deref(),
+                None,
+                None,
+                None,
+                false,
+                None,
+                None,
+                None,
+                false,
+                settings,
+            )
+            .map_err(|errors| describe_errors("Claude CLI", &errors))?;
+            (program, args, env)
+        }
+    };
+
+    let mut defaults = resolve_levels(app, &options.worktree_id, settings);
+    for level in &mut defaults {
+        level.defaults.args = redact_args(&level.defaults.args);
+        for (name, value) in level.defaults.env.iter_mut() {
+            *value = redact_env_value(name, value);
+        }
+    }
+    Ok(EffectiveLaunchCommand {
+        backend: backend_name(settings),
+        program: program.to_string_lossy().to_string(),
+        sandbox: sandbox_policy(&args),
+        args: redact_args(&args),
+        env: env
+            .into_iter()
+            .map(|(name, value)| {
+                let value = redact_env_value(&name, &value);
+                (name, value)
+            })
+            .collect(),
+        cwd: working_dir.to_string_lossy().to_string(),
+        defaults,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    fn defaults(args: &[&str], env: &[(&str, &str)]) -> LaunchDefaults {
+        LaunchDefaults {
+            args: args.iter().map(|a| a.to_string()).collect(),
+            env: env
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_precedence() {
+        let global = BTreeMap::from([
+            (
+                "claude".to_string(),
+                defaults(&["--max-turns", "20"], &[("A", "global")]),
+            ),
+            ("codex".to_string(), defaults(&["--oss"], &[])),
+        ]);
+        let project = BTreeMap::from([(
+            "claude".to_string(),
+            defaults(&[], &[("A", "project"), ("B", "project")]),
+        )]);
+        let profile =
+            r#"{"env":{"X":"1"},"launchDefaults":{"args":["--verbose"],"env":{"B":"profile"}}}"#;
+
+        let found = levels(
+            &global,
+            &project,
+            &backend_name(Some(profile)),
+            Some(profile),
+        );
+        let order: Vec<_> = found.iter().map(|l| l.level).collect();
+        assert_eq!(
+            order,
+            [
+                LaunchLevel::Global,
+                LaunchLevel::Project,
+                LaunchLevel::Profile
+            ]
+        );
+        assert_eq!(
+            merge(&found),
+            defaults(
+                &["--max-turns", "20", "--verbose"],
+                &[("A", "project"), ("B", "profile")]
+            )
+        );
+
+        // A codex profile only gets codex defaults
+        let codex = r#"{"backend":"codex"}"#;
+        let found = levels(&global, &project, &backend_name(Some(codex)), Some(codex));
+        assert_eq!(merge(&found).args, ["--oss"]);
+    }
+
+    #[test]
+    fn test_global_defaults_and_validation() {
+        let paths = TempPaths::new();
+        assert!(global_defaults(&paths).is_empty());
+        fs::write(
+            paths.app_data_dir().unwrap().join("preferences.json"),
+            r#"{"theme":"dark","launch_defaults":{"aider":{"args":["--no-stream"]}}}"#,
+        )
+        .unwrap();
+        assert_eq!(global_defaults(&paths)["aider"].args, ["--no-stream"]);
+
+        assert!(defaults(&["--x"], &[("OK_NAME", "")]).validate().is_ok());
+        assert!(defaults(&[" "], &[]).validate().is_err());
+        assert!(defaults(&[], &[("A=B", "")]).validate().is_err());
+    }
+
+    #[test]
+    fn test_sandbox_policy() {
+        let args: Vec<String> = [
+            "--print",
+            "--permission-mode",
+            "plan",
+            "--add-dir",
+            "/data/runs",
+            "--allowedTools",
+            "Bash(git status)",
+            "--disallowedTools",
+            "WebFetch(domain:example.com)",
+            "--permission-mode",
+            "acceptEdits",
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        assert_eq!(
+            sandbox_policy(&args),
+            SandboxPolicy {
+                mode: Some("acceptEdits".to_string()),
+                allowed_tools: vec!["Bash(git status)".to_string()],
+                disallowed_tools: vec!["WebFetch(domain:example.com)".to_string()],
+                additional_dirs: vec!["/data/runs".to_string()],
+            }
+        );
+
+        let codex = ["exec", "--json", "--sandbox", "read-only", "-"].map(String::from);
+        assert_eq!(sandbox_policy(&codex).mode.as_deref(), Some("read-only"));
+        assert_eq!(
+            sandbox_policy(&["--message-file".to_string()]),
+            SandboxPolicy::default()
+        );
+    }
+}
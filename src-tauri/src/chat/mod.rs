@@ -1,12 +1,38 @@
+mod aider;
+pub mod annotations;
+pub mod blobs;
+pub mod bulk;
+pub mod cast;
 mod claude;
+mod codex;
+pub mod codex_updates;
 mod commands;
+pub mod compare;
+pub mod context_budget;
 pub mod detached;
+pub mod docs;
+pub mod environment;
+mod external;
+pub mod history;
+pub mod input_requests;
+pub mod launch;
 mod naming;
+pub mod one_shot;
+pub mod paging;
 pub mod registry;
+pub mod replay;
+pub mod rerun;
 pub mod run_log;
+pub mod scratch;
+pub mod share;
 pub mod storage;
 pub mod tail;
+pub mod time_tracking;
+pub mod timeline;
+pub mod transcript;
 pub mod types;
+pub mod vulnerabilities;
+pub mod warm_pool;
 
 pub use commands::*;
 pub use storage::{preserve_base_sessions, restore_base_sessions, with_sessions_mut};
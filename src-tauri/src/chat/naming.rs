@@ -10,11 +10,10 @@ use crate::projects::storage::{load_projects_data, save_projects_data};
 
 use super::storage::with_sessions_mut;
 use crate::http_server::EmitExt;
+use crate::runtime::{PathProvider, ProcessRunner, SystemProcessRunner};
 use serde::{Deserialize, Serialize};
-use std::io::Write;
 use std::path::PathBuf;
-use std::process::Stdio;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 /// Request for combined naming (session + branch)
 #[derive(Debug, Clone)]
@@ -289,7 +288,11 @@ fn extract_text_from_stream_json(output: &str) -> Result<String, String> {
 }
 
 /// Generate names using Claude CLI
-fn generate_names(app: &AppHandle, request: &NamingRequest) -> Result<NamingOutput, String> {
+fn generate_names(
+    app: &impl PathProvider,
+    runner: &impl ProcessRunner,
+    request: &NamingRequest,
+) -> Result<NamingOutput, String> {
     let cli_path = get_cli_binary_path(app)?;
 
     if !cli_path.exists() {
@@ -363,7 +366,7 @@ fn generate_names(app: &AppHandle, request: &NamingRequest) -> Result<NamingOutp
         // Add directories for Claude to read attachments
         // In dev mode: full directory access (useful for debugging)
         // In prod mode: only specific directories (security)
-        if let Ok(app_data_dir) = app.path().app_data_dir() {
+        if let Ok(app_data_dir) = app.app_data_dir() {
             if cfg!(debug_assertions) {
                 cmd.arg("--add-dir").arg(&app_data_dir);
                 log::trace!("Added full app data directory to naming scope: {app_data_dir:?}");
@@ -408,30 +411,16 @@ fn generate_names(app: &AppHandle, request: &NamingRequest) -> Result<NamingOutp
         cmd.arg("--max-turns").arg("1");
     }
 
-    cmd.stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
-
-    let mut child = cmd
-        .spawn()
-        .map_err(|e| format!("Failed to spawn Claude CLI: {e}"))?;
-
-    // Write prompt to stdin as stream-json format
-    {
-        let stdin = child.stdin.as_mut().ok_or("Failed to open stdin")?;
-        let input_message = serde_json::json!({
-            "type": "user",
-            "message": {
-                "role": "user",
-                "content": prompt
-            }
-        });
-        writeln!(stdin, "{input_message}").map_err(|e| format!("Failed to write to stdin: {e}"))?;
-    }
-
-    let output = child
-        .wait_with_output()
-        .map_err(|e| format!("Failed to wait for Claude CLI: {e}"))?;
+    // Prompt is sent on stdin as stream-json format
+    let input_message = serde_json::json!({
+        "type": "user",
+        "message": {
+            "role": "user",
+            "content": prompt
+        }
+    });
+    let output = runner.run(cmd, Some(format!("{input_message}\n").as_bytes()))?;
+    crate::crash_reports::record_cli_exit("claude", &output.status, &output.stderr);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -648,7 +637,7 @@ fn execute_naming(app: &AppHandle, request: &NamingRequest) {
     }
 
     // Generate names
-    let naming_result = match generate_names(app, request) {
+    let naming_result = match generate_names(app, &SystemProcessRunner, request) {
         Ok(result) => result,
         Err(e) => {
             log::warn!("Naming generation failed: {e}");
@@ -753,7 +742,77 @@ pub fn spawn_naming_task(app: AppHandle, request: NamingRequest) {
         request.generate_branch_name
     );
 
-    std::thread::spawn(move || {
+    crate::background_tasks::supervisor::spawn_thread("naming", move || {
         execute_naming(&app, &request);
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::{ScriptedRunner, TempPaths};
+
+    fn request(first_message: &str) -> NamingRequest {
+        NamingRequest {
+            session_id: "sess-1".to_string(),
+            worktree_id: "wt-1".to_string(),
+            worktree_path: PathBuf::from("/tmp/worktree"),
+            first_message: first_message.to_string(),
+            model: "haiku".to_string(),
+            existing_branch_names: Vec::new(),
+            generate_session_name: true,
+            generate_branch_name: false,
+        }
+    }
+
+    fn paths_with_cli() -> TempPaths {
+        let paths = TempPaths::new();
+        let cli = get_cli_binary_path(&paths).unwrap();
+        std::fs::create_dir_all(cli.parent().unwrap()).unwrap();
+        std::fs::write(&cli, "").unwrap();
+        paths
+    }
+
+    #[test]
+    fn test_generate_names_parses_cli_output() {
+        let paths = paths_with_cli();
+        let stdout = serde_json::json!({
+            "type": "assistant",
+            "message": { "content": [
+                { "type": "text", "text": "```json\n{\"session_name\": \"Fix login redirect\"}\n```" }
+            ]}
+        });
+        let runner = ScriptedRunner::succeeding(&format!("{stdout}\n"));
+        let cli = get_cli_binary_path(&paths).unwrap();
+
+        let output = generate_names(&paths, &runner, &request("The login page loops")).unwrap();
+        assert_eq!(output.session_name.as_deref(), Some("Fix login redirect"));
+        assert!(output.branch_name.is_none());
+
+        let runs = runner.runs();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].program, cli.to_string_lossy());
+        assert!(runs[0].args.windows(2).any(|w| w == ["--tools", ""]));
+        assert!(runs[0]
+            .stdin
+            .as_deref()
+            .is_some_and(|s| s.contains("The login page loops")));
+    }
+
+    #[test]
+    fn test_generate_names_reports_cli_failure() {
+        let paths = paths_with_cli();
+        let runner = ScriptedRunner::failing(1, "not logged in");
+
+        let err = generate_names(&paths, &runner, &request("hello")).unwrap_err();
+        assert!(err.contains("not logged in"), "{err}");
+    }
+
+    #[test]
+    fn test_generate_names_requires_installed_cli() {
+        let runner = ScriptedRunner::succeeding("");
+        let err = generate_names(&TempPaths::new(), &runner, &request("hello")).unwrap_err();
+        assert_eq!(err, "Claude CLI not installed");
+        assert!(runner.runs().is_empty());
+    }
+}
@@ -0,0 +1,573 @@
+//! One-shot "ask about selection" runs
+//!
+//! Backs the global-hotkey quick-ask flow: a single non-interactive Claude
+//! CLI call with no tools, MCP servers or session persistence, so it starts
+//! fast and leaves nothing behind (or a read-only `codex exec` run). The answer streams to the frontend as
+//! `one-shot:*` events. Finished answers are kept in memory only (the last
+//! [`MAX_RECENT`]) and become a regular session when pinned. Token usage is
+//! appended to `analytics/one-shot-usage.jsonl`.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::codex::CODEX_TOOL;
+use super::context_budget::{self, Attachment, AttachmentKind};
+use super::transcript::{
+    import_transcript, TranscriptHeader, TranscriptImport, TranscriptMessage, TranscriptRecord,
+    TRANSCRIPT_FORMAT, TRANSCRIPT_VERSION,
+};
+use super::types::{MessageRole, UsageData};
+use crate::claude_cli::get_cli_binary_path;
+use crate::cli_capabilities::args::{describe_errors, ArgBuilder, ArgError};
+use crate::cli_capabilities::{self, CliCapabilities, CLAUDE_OPTIONAL_FLAGS, CLAUDE_RULES};
+use crate::platform::silent_command;
+use crate::runtime::{now, EventSink, PathProvider};
+
+/// Model used when none is given (fastest to first token)
+pub const DEFAULT_MODEL: &str = "haiku";
+
+/// Finished answers kept for pinning
+const MAX_RECENT: usize = 20;
+
+/// Longest prompt excerpt used as the pinned session's name
+const SESSION_NAME_CHARS: usize = 40;
+
+/// Usage log file name (within `analytics/`)
+const USAGE_FILE: &str = "one-shot-usage.jsonl";
+
+/// Recent answers, newest last
+static RECENT: Lazy<Mutex<VecDeque<OneShotAnswer>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// A finished one-shot run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OneShotAnswer {
+    pub id: String,
+    pub prompt: String,
+    /// Selected text the question is about
+    pub context: Option<String>,
+    pub model: String,
+    pub answer: String,
+    pub usage: Option<UsageData>,
+    /// Unix seconds
+    pub started_at: u64,
+    pub duration_ms: u64,
+}
+
+/// Payload for `one-shot:started` and `one-shot:done`
+#[derive(Debug, Clone, Serialize)]
+struct OneShotEvent<'a> {
+    id: &'a str,
+}
+
+/// Payload for `one-shot:chunk`
+#[derive(Debug, Clone, Serialize)]
+struct OneShotChunk<'a> {
+    id: &'a str,
+    content: &'a str,
+}
+
+/// Line in the usage log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UsageRecord {
+    id: String,
+    model: String,
+    started_at: u64,
+    duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    usage: Option<UsageData>,
+}
+
+/// Text and usage collected from a stream-json run
+#[derive(Debug, Default)]
+struct StreamOutcome {
+    answer: String,
+    usage: Option<UsageData>,
+    /// `result` text of an errored run
+    error: Option<String>,
+}
+
+/// Prompt sent to the CLI: the question, then the selection it refers to
+fn build_prompt(prompt: &str, context: Option<&str>) -> String {
+    match context.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(context) => format!("{prompt}\n\n<selection>\n{context}\n</selection>"),
+        None => prompt.to_string(),
+    }
+}
+
+fn usage_from_result(msg: &serde_json::Value) -> Option<UsageData> {
+    let usage = msg.get("usage")?;
+    let tokens = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    Some(UsageData {
+        input_tokens: tokens("input_tokens"),
+        output_tokens: tokens("output_tokens"),
+        cache_read_input_tokens: tokens("cache_read_input_tokens"),
+        cache_creation_input_tokens: tokens("cache_creation_input_tokens"),
+        cost_usd: msg.get("total_cost_usd").and_then(|v| v.as_f64()),
+    })
+}
+
+/// Read stream-json output, emitting text deltas as `one-shot:chunk` events
+///
+/// Deltas come from `--include-partial-messages` stream events; complete
+/// assistant messages are only used if no deltas were seen.
+fn stream_answer(events: &impl EventSink, id: &str, reader: impl BufRead) -> StreamOutcome {
+    let mut outcome = StreamOutcome::default();
+    let mut streamed = false;
+
+    for line in reader.lines().map_while(Result::ok) {
+        let Ok(msg) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+            continue;
+        };
+        let text = match msg.get("type").and_then(|t| t.as_str()) {
+            Some("stream_event") => {
+                let delta = msg.get("event").and_then(|e| e.get("delta"));
+                let text = delta
+                    .filter(|d| d.get("type").and_then(|t| t.as_str()) == Some("text_delta"))
+                    .and_then(|d| d.get("text"))
+                    .and_then(|t| t.as_str());
+                streamed |= text.is_some();
+                text.map(str::to_string)
+            }
+            Some("assistant") if !streamed => msg
+                .get("message")
+                .and_then(|m| m.get("content"))
+                .and_then(|c| c.as_array())
+                .map(|blocks| {
+                    blocks
+                        .iter()
+                        .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                        .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                        .collect::<String>()
+                }),
+            Some("result") => {
+                let result = msg.get("result").and_then(|r| r.as_str());
+                if msg.get("is_error").and_then(|v| v.as_bool()) == Some(true) {
+                    outcome.error = Some(result.unwrap_or("Claude CLI reported an error").into());
+                } else if outcome.answer.is_empty() {
+                    outcome.answer = result.unwrap_or_default().to_string();
+                }
+                outcome.usage = usage_from_result(&msg);
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(text) = text.filter(|t| !t.is_empty()) {
+            outcome.answer.push_str(&text);
+            let _ = events.emit_all("one-shot:chunk", &OneShotChunk { id, content: &text });
+        }
+    }
+
+    outcome
+}
+
+fn record_usage(app: &impl PathProvider, answer: &OneShotAnswer) -> Result<(), String> {
+    let dir = app.app_data_dir()?.join("analytics");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create analytics directory: {e}"))?;
+    let line = serde_json::to_string(&UsageRecord {
+        id: answer.id.clone(),
+        model: answer.model.clone(),
+        started_at: answer.started_at,
+        duration_ms: answer.duration_ms,
+        usage: answer.usage.clone(),
+    })
+    .map_err(|e| format!("Failed to serialize one-shot usage: {e}"))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(USAGE_FILE))
+        .map_err(|e| format!("Failed to open one-shot usage log: {e}"))?;
+    writeln!(file, "{line}").map_err(|e| format!("Failed to write one-shot usage log: {e}"))
+}
+
+fn remember(answer: OneShotAnswer) {
+    if let Ok(mut recent) = RECENT.lock() {
+        if recent.len() >= MAX_RECENT {
+            recent.pop_front();
+        }
+        recent.push_back(answer);
+    }
+}
+
+/// Answers from this app run that can still be pinned (oldest first)
+pub fn recent_answers() -> Vec<OneShotAnswer> {
+    RECENT
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Arguments for a one-shot Claude run, checked like a chat run's
+fn claude_args(capabilities: &CliCapabilities, model: &str) -> Result<Vec<String>, Vec<ArgError>> {
+    let mut args = ArgBuilder::new(capabilities, CLAUDE_OPTIONAL_FLAGS);
+    args.flag("--print");
+    args.option("--input-format", "stream-json");
+    args.option("--output-format", "stream-json");
+    args.flag("--include-partial-messages");
+    args.flag("--verbose");
+    args.option("--model", model);
+    args.flag("--no-session-persistence");
+    args.flag("--strict-mcp-config");
+    // An empty list turns every tool off
+    args.option("--tools", "");
+    args.option("--max-turns", "1");
+    args.build(CLAUDE_RULES)
+}
+
+/// Run the Claude CLI for one answer: one turn, no tools, MCP servers or
+/// session persistence, straight to text
+fn ask_claude<A: EventSink + PathProvider>(
+    app: &A,
+    id: &str,
+    prompt: &str,
+    context: Option<&str>,
+    model: &str,
+) -> Result<StreamOutcome, String> {
+    let cli_path = get_cli_binary_path(app)?;
+    if !cli_path.exists() {
+        return Err("Claude CLI not installed".to_string());
+    }
+
+    // Flags are checked against what the installed CLI version supports
+    let capabilities = cli_capabilities::probe(app, "claude", &cli_path, CLAUDE_OPTIONAL_FLAGS);
+    let args = claude_args(&capabilities, model)
+        .map_err(|errors| describe_errors("Claude CLI", &errors))?;
+
+    let start = Instant::now();
+    let mut cmd = silent_command(&cli_path);
+    cmd.args(&args)
+        .current_dir(std::env::temp_dir())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn Claude CLI: {e}"))?;
+    log::trace!(
+        "One-shot {id} spawned with model {model} in {}ms",
+        start.elapsed().as_millis()
+    );
+    let _ = app.emit_all("one-shot:started", &OneShotEvent { id });
+
+    let input_message = serde_json::json!({
+        "type": "user",
+        "message": {
+            "role": "user",
+            "content": build_prompt(prompt, context)
+        }
+    });
+    if let Some(mut stdin) = child.stdin.take() {
+        // Dropped at the end of this block, closing stdin so the CLI sees EOF
+        stdin
+            .write_all(format!("{input_message}\n").as_bytes())
+            .map_err(|e| format!("Failed to write to Claude CLI stdin: {e}"))?;
+    }
+
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or("Failed to capture Claude CLI stdout")?;
+    let outcome = stream_answer(app, id, BufReader::new(stdout));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to wait for Claude CLI: {e}"))?;
+    crate::crash_reports::record_cli_exit("claude", &output.status, &output.stderr);
+    let _ = app.emit_all("one-shot:done", &OneShotEvent { id });
+
+    if let Some(error) = outcome.error {
+        crate::claude_cli::record_auth_failure(&error);
+        crate::quota::observe(app, "claude", None, &error);
+        return Err(error);
+    }
+    if !output.status.success() && outcome.answer.is_empty() {
+        return Err(format!(
+            "Claude CLI failed (exit code {:?}): {}",
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(outcome)
+}
+
+/// Run `codex exec` for one answer, read-only in a temporary directory
+fn ask_codex<A: EventSink + PathProvider>(
+    app: &A,
+    id: &str,
+    prompt: &str,
+    context: Option<&str>,
+    model: Option<&str>,
+) -> Result<StreamOutcome, String> {
+    let _ = app.emit_all("one-shot:started", &OneShotEvent { id });
+    let result = super::codex::run_exec(
+        app,
+        &build_prompt(prompt, context),
+        model,
+        Some("read-only"),
+        &std::env::temp_dir(),
+        |text| {
+            let _ = app.emit_all("one-shot:chunk", &OneShotChunk { id, content: text });
+        },
+    );
+    let _ = app.emit_all("one-shot:done", &OneShotEvent { id });
+
+    let outcome = result?;
+    if let Some(error) = outcome.error {
+        crate::quota::observe(app, CODEX_TOOL, None, &error);
+        return Err(error);
+    }
+    Ok(StreamOutcome {
+        answer: outcome.message,
+        usage: outcome.usage,
+        error: None,
+    })
+}
+
+/// Ask a single question, streaming the answer
+///
+/// Runs the Claude CLI, or Codex when `backend` is "codex". Emits
+/// `one-shot:started`, `one-shot:chunk` (text deltas) and `one-shot:done`,
+/// all carrying the answer's `id`. Blocks until the CLI exits.
+pub fn run_one_shot<A: EventSink + PathProvider>(
+    app: &A,
+    prompt: &str,
+    context: Option<&str>,
+    model: Option<&str>,
+    backend: Option<&str>,
+) -> Result<OneShotAnswer, String> {
+    let codex = match backend {
+        None | Some("claude") => false,
+        Some(CODEX_TOOL) => true,
+        Some(other) => return Err(format!("One-shot runs don't support the {other} backend")),
+    };
+    // Codex falls back to its configured model
+    let model_name = match (codex, model) {
+        (_, Some(model)) => model.to_string(),
+        (true, None) => CODEX_TOOL.to_string(),
+        (false, None) => DEFAULT_MODEL.to_string(),
+    };
+    let mut attachments = vec![Attachment::text(AttachmentKind::Message, "Prompt", prompt)];
+    if let Some(context) = context {
+        attachments.push(Attachment::text(
+            AttachmentKind::Selection,
+            "Selection",
+            context,
+        ));
+    }
+    if let Err(report) = context_budget::check(&model_name, attachments) {
+        context_budget::emit_over_budget(app, None, &report);
+        return Err(report.describe());
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let started_at = now();
+    let start = Instant::now();
+    let outcome = if codex {
+        ask_codex(app, &id, prompt, context, model)?
+    } else {
+        ask_claude(app, &id, prompt, context, &model_name)?
+    };
+
+    let answer = OneShotAnswer {
+        id,
+        prompt: prompt.to_string(),
+        context: context.map(str::to_string),
+        model: model_name,
+        answer: outcome.answer.trim().to_string(),
+        usage: outcome.usage,
+        started_at,
+        duration_ms: start.elapsed().as_millis() as u64,
+    };
+    if let Err(e) = record_usage(app, &answer) {
+        log::warn!("Failed to record one-shot usage: {e}");
+    }
+    remember(answer.clone());
+    Ok(answer)
+}
+
+/// Save a one-shot answer as a session in a worktree
+pub fn pin_answer(
+    app: &impl PathProvider,
+    answer: &OneShotAnswer,
+    worktree_id: &str,
+    worktree_path: &str,
+) -> Result<TranscriptImport, String> {
+    let mut name: String = answer.prompt.chars().take(SESSION_NAME_CHARS).collect();
+    if answer.prompt.chars().count() > SESSION_NAME_CHARS {
+        name.push('…');
+    }
+    let message = |role, content, timestamp, usage| {
+        TranscriptRecord::Message(TranscriptMessage {
+            id: Uuid::new_v4().to_string(),
+            role,
+            content,
+            timestamp,
+            content_blocks: Vec::new(),
+            model: Some(answer.model.clone()),
+            execution_mode: None,
+            thinking_level: None,
+            effort_level: None,
+            cancelled: false,
+            usage,
+        })
+    };
+    let records = [
+        TranscriptRecord::Header(TranscriptHeader {
+            format: TRANSCRIPT_FORMAT.to_string(),
+            version: TRANSCRIPT_VERSION,
+            session_name: name,
+            created_at: answer.started_at,
+            exported_at: now(),
+            model: Some(answer.model.clone()),
+        }),
+        message(
+            MessageRole::User,
+            build_prompt(&answer.prompt, answer.context.as_deref()),
+            answer.started_at,
+            None,
+        ),
+        message(
+            MessageRole::Assistant,
+            answer.answer.clone(),
+            answer.started_at + answer.duration_ms / 1000,
+            answer.usage.clone(),
+        ),
+    ];
+
+    let mut transcript = String::new();
+    for record in &records {
+        let line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to serialize one-shot answer: {e}"))?;
+        transcript.push_str(&line);
+        transcript.push('\n');
+    }
+    import_transcript(app, worktree_id, worktree_path, &transcript)
+}
+
+/// Pin a recent answer by id
+pub fn pin_recent(
+    app: &impl PathProvider,
+    id: &str,
+    worktree_id: &str,
+    worktree_path: &str,
+) -> Result<TranscriptImport, String> {
+    let answer = recent_answers()
+        .into_iter()
+        .find(|a| a.id == id)
+        .ok_or_else(|| format!("One-shot answer not found: {id}"))?;
+    pin_answer(app, &answer, worktree_id, worktree_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::run_log::load_session_messages;
+    use crate::test_support::runtime::{RecordingSink, TempPaths};
+    use std::io::Cursor;
+
+    fn answer() -> OneShotAnswer {
+        OneShotAnswer {
+            id: "os-1".to_string(),
+            prompt: "What does this regex match?".to_string(),
+            context: Some(r"^\d{3}-\d{4}$".to_string()),
+            model: "haiku".to_string(),
+            answer: "A phone number like 555-1234.".to_string(),
+            usage: None,
+            started_at: 1_790_000_000,
+            duration_ms: 1200,
+        }
+    }
+
+    #[test]
+    fn test_build_prompt_appends_selection() {
+        assert_eq!(build_prompt("Explain", None), "Explain");
+        assert_eq!(build_prompt("Explain", Some("  ")), "Explain");
+        assert_eq!(
+            build_prompt("Explain", Some("let x = 1;\n")),
+            "Explain\n\n<selection>\nlet x = 1;\n</selection>"
+        );
+    }
+
+    #[test]
+    fn test_claude_args() {
+        let args = claude_args(&CliCapabilities::default(), "haiku").unwrap();
+        assert_eq!(args[0], "--print");
+        let tools = args.iter().position(|a| a == "--tools").unwrap();
+        assert_eq!(args[tools + 1], "");
+        let model = args.iter().position(|a| a == "--model").unwrap();
+        assert_eq!(args[model + 1], "haiku");
+    }
+
+    #[test]
+    fn test_stream_answer_emits_deltas_and_usage() {
+        let delta = |text: &str| {
+            serde_json::json!({
+                "type": "stream_event",
+                "event": { "type": "content_block_delta", "delta": { "type": "text_delta", "text": text } }
+            })
+        };
+        let output = [
+            serde_json::json!({ "type": "system", "subtype": "init" }),
+            delta("A phone "),
+            delta("number."),
+            serde_json::json!({
+                "type": "assistant",
+                "message": { "content": [{ "type": "text", "text": "A phone number." }] }
+            }),
+            serde_json::json!({
+                "type": "result",
+                "result": "A phone number.",
+                "usage": { "input_tokens": 12, "output_tokens": 4 },
+                "total_cost_usd": 0.0001
+            }),
+        ]
+        .map(|v| v.to_string())
+        .join("\n");
+        let sink = RecordingSink::default();
+
+        let outcome = stream_answer(&sink, "os-1", Cursor::new(output));
+        assert_eq!(outcome.answer, "A phone number.");
+        assert!(outcome.error.is_none());
+        assert_eq!(outcome.usage.as_ref().map(|u| u.output_tokens), Some(4));
+
+        let chunks = sink.payloads("one-shot:chunk");
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0]["id"], "os-1");
+        assert_eq!(chunks[1]["content"], "number.");
+    }
+
+    #[test]
+    fn test_stream_answer_reports_errors() {
+        let output = serde_json::json!({
+            "type": "result",
+            "is_error": true,
+            "result": "Invalid API key"
+        })
+        .to_string();
+
+        let outcome = stream_answer(&RecordingSink::default(), "os-1", Cursor::new(output));
+        assert_eq!(outcome.error.as_deref(), Some("Invalid API key"));
+    }
+
+    #[test]
+    fn test_pin_answer_creates_session() {
+        let paths = TempPaths::new();
+
+        let import = pin_answer(&paths, &answer(), "wt-1", "/tmp/wt").unwrap();
+        assert!(!import.duplicate);
+        assert_eq!(import.session.name, "What does this regex match?");
+
+        let messages = load_session_messages(&paths, &import.session.id).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].content.contains("<selection>"));
+        assert_eq!(messages[1].content, "A phone number like 555-1234.");
+    }
+}
@@ -0,0 +1,250 @@
+//! Ranged message loading for very long sessions.
+//!
+//! `load_session_messages` parses every run log of a session, which is fine
+//! for normal sessions but stalls the UI on 100k-line ones. Pages only read
+//! the run logs that overlap the requested message range, and large tool
+//! outputs are left out of the page and fetched on demand with
+//! `read_tool_output` once the UI actually renders them.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::PathProvider;
+
+use super::blobs::expand_line;
+use super::run_log::{get_run_log_path, run_message_count, run_messages};
+use super::storage::{get_session_dir, load_metadata};
+use super::types::ChatMessage;
+
+/// Messages per page when the caller doesn't ask for a size
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Tool outputs larger than this are elided from pages by default
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 16 * 1024;
+
+/// A tool output left out of a page because of its size
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ElidedOutput {
+    pub message_id: String,
+    pub tool_call_id: String,
+    /// Size of the full output in bytes
+    pub bytes: usize,
+}
+
+/// A contiguous slice of a session's messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagePage {
+    pub session_id: String,
+    /// Index of the first message in this page
+    pub start: usize,
+    /// Total number of messages in the session
+    pub total: usize,
+    pub messages: Vec<ChatMessage>,
+    /// Tool outputs removed from `messages` (their `output` is None)
+    pub elided_outputs: Vec<ElidedOutput>,
+}
+
+/// Load `limit` messages starting at message index `start`.
+/// `start: None` loads the last page, which is what a session opens on.
+pub fn load_message_page(
+    app: &impl PathProvider,
+    session_id: &str,
+    start: Option<usize>,
+    limit: usize,
+    max_output_bytes: usize,
+) -> Result<MessagePage, String> {
+    let limit = limit.max(1);
+    let Some(metadata) = load_metadata(app, session_id)? else {
+        return Ok(MessagePage {
+            session_id: session_id.to_string(),
+            start: 0,
+            total: 0,
+            messages: vec![],
+            elided_outputs: vec![],
+        });
+    };
+
+    let total: usize = metadata.runs.iter().map(run_message_count).sum();
+    let start = start
+        .unwrap_or_else(|| total.saturating_sub(limit))
+        .min(total);
+    let end = (start + limit).min(total);
+
+    let mut messages = Vec::new();
+    let mut run_start = 0;
+    for run in &metadata.runs {
+        let count = run_message_count(run);
+        let run_end = run_start + count;
+        if run_end > start && run_start < end {
+            let run_msgs = run_messages(app, session_id, run)?;
+            let from = start.saturating_sub(run_start);
+            let to = (end - run_start).min(run_msgs.len());
+            messages.extend(run_msgs.into_iter().take(to).skip(from));
+        }
+        if run_end >= end {
+            break;
+        }
+        run_start = run_end;
+    }
+
+    let mut elided_outputs = Vec::new();
+    for msg in &mut messages {
+        if metadata.approved_plan_message_ids.contains(&msg.id) {
+            msg.plan_approved = true;
+        }
+        for tc in &mut msg.tool_calls {
+            let bytes = tc.output.as_ref().map_or(0, |o| o.len());
+            if bytes > max_output_bytes {
+                tc.output = None;
+                elided_outputs.push(ElidedOutput {
+                    message_id: msg.id.clone(),
+                    tool_call_id: tc.id.clone(),
+                    bytes,
+                });
+            }
+        }
+    }
+
+    Ok(MessagePage {
+        session_id: session_id.to_string(),
+        start,
+        total,
+        messages,
+        elided_outputs,
+    })
+}
+
+/// Read the full output of one tool call, streaming the run log that holds
+/// it instead of parsing the whole session
+pub fn read_tool_output(
+    app: &impl PathProvider,
+    session_id: &str,
+    message_id: &str,
+    tool_call_id: &str,
+) -> Result<Option<String>, String> {
+    let metadata = load_metadata(app, session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    let run = metadata
+        .runs
+        .iter()
+        .find(|r| r.assistant_message_id.as_deref() == Some(message_id))
+        .ok_or_else(|| format!("Message not found: {message_id}"))?;
+
+    let path = get_run_log_path(app, session_id, &run.run_id)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let file = File::open(&path).map_err(|e| format!("Failed to open run log: {e}"))?;
+    let session_dir = get_session_dir(app, session_id)?;
+
+    // Cheap substring check first so only the matching line gets parsed
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("Failed to read run log: {e}"))?;
+        let line = expand_line(&session_dir, line)?;
+        if !line.contains(tool_call_id) || !line.contains("tool_result") {
+            continue;
+        }
+        let Ok(msg) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let Some(blocks) = msg.pointer("/message/content").and_then(|c| c.as_array()) else {
+            continue;
+        };
+        for block in blocks {
+            if block.get("type").and_then(|v| v.as_str()) == Some("tool_result")
+                && block.get("tool_use_id").and_then(|v| v.as_str()) == Some(tool_call_id)
+            {
+                let output = block.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                return Ok(Some(output.to_string()));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::run_log::load_session_messages;
+    use crate::chat::transcript::import_transcript;
+    use crate::test_support::runtime::TempPaths;
+
+    fn transcript(turns: usize, big_output: &str) -> String {
+        let mut out = String::from(
+            r#"{"type":"header","format":"jean-transcript","version":1,"session_name":"Long","created_at":1700000000,"exported_at":1700000500}"#,
+        );
+        out.push('\n');
+        for i in 0..turns {
+            let output = if i == 1 { big_output } else { "ok" };
+            out.push_str(&format!(
+                concat!(
+                    r#"{{"type":"message","id":"u{i}","role":"user","content":"turn {i}","timestamp":{ts}}}"#,
+                    "\n",
+                    r#"{{"type":"message","id":"a{i}","role":"assistant","content":"done {i}","timestamp":{ts},"content_blocks":[{{"type":"tool_use","tool_call_id":"t{i}"}}]}}"#,
+                    "\n",
+                    r#"{{"type":"tool_call","message_id":"a{i}","id":"t{i}","name":"Bash","input":{{}},"output":"{output}"}}"#,
+                    "\n"
+                ),
+                i = i,
+                ts = 1700000010 + i,
+                output = output,
+            ));
+        }
+        out
+    }
+
+    #[test]
+    fn test_pages_match_full_load() {
+        let paths = TempPaths::new();
+        let import = import_transcript(&paths, "wt-1", "/tmp/wt-1", &transcript(5, "ok")).unwrap();
+        let session_id = import.session.id;
+        let all = load_session_messages(&paths, &session_id).unwrap();
+
+        let last = load_message_page(&paths, &session_id, None, 4, usize::MAX).unwrap();
+        assert_eq!(last.total, 10);
+        assert_eq!(last.start, 6);
+        let ids: Vec<_> = last.messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["u3", "a3", "u4", "a4"]);
+
+        // Ranges may start and end mid-run
+        let middle = load_message_page(&paths, &session_id, Some(3), 4, usize::MAX).unwrap();
+        let expected: Vec<_> = all[3..7].iter().map(|m| m.id.as_str()).collect();
+        let ids: Vec<_> = middle.messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, expected);
+
+        let past_end = load_message_page(&paths, &session_id, Some(50), 4, usize::MAX).unwrap();
+        assert_eq!(past_end.start, 10);
+        assert!(past_end.messages.is_empty());
+    }
+
+    #[test]
+    fn test_large_outputs_are_elided_and_fetched_on_demand() {
+        let paths = TempPaths::new();
+        let big = "x".repeat(100);
+        let import = import_transcript(&paths, "wt-1", "/tmp/wt-1", &transcript(3, &big)).unwrap();
+        let session_id = import.session.id;
+
+        let page = load_message_page(&paths, &session_id, Some(0), 10, 10).unwrap();
+        assert_eq!(
+            page.elided_outputs,
+            [ElidedOutput {
+                message_id: "a1".to_string(),
+                tool_call_id: "t1".to_string(),
+                bytes: 100,
+            }]
+        );
+        assert_eq!(page.messages[3].tool_calls[0].output, None);
+        assert_eq!(page.messages[1].tool_calls[0].output.as_deref(), Some("ok"));
+
+        let output = read_tool_output(&paths, &session_id, "a1", "t1").unwrap();
+        assert_eq!(output, Some(big));
+        assert_eq!(
+            read_tool_output(&paths, &session_id, "a1", "t0").unwrap(),
+            None
+        );
+        assert!(read_tool_output(&paths, &session_id, "nope", "t1").is_err());
+    }
+}
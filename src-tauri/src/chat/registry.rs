@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 
 use once_cell::sync::Lazy;
@@ -15,6 +15,11 @@ use crate::http_server::EmitExt;
 static PROCESS_REGISTRY: Lazy<Mutex<HashMap<String, u32>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// Sessions whose process died while the system was asleep.
+/// Checked by send_chat_message so the run is recorded as interrupted, not cancelled.
+static INTERRUPTED_SESSIONS: Lazy<Mutex<HashSet<String>>> =
+    Lazy::new(|| Mutex::new(HashSet::new()));
+
 /// Register a running Claude process PID for a session
 pub fn register_process(session_id: String, pid: u32) {
     let mut registry = PROCESS_REGISTRY.lock().unwrap();
@@ -45,6 +50,33 @@ pub fn get_running_sessions() -> Vec<String> {
     PROCESS_REGISTRY.lock().unwrap().keys().cloned().collect()
 }
 
+/// Unregister processes that are no longer alive and mark their sessions interrupted.
+/// Called after system wake; unregistering makes the tailer stop promptly.
+pub fn interrupt_dead_processes() -> Vec<String> {
+    use crate::platform::is_process_alive;
+
+    let mut registry = PROCESS_REGISTRY.lock().unwrap();
+    let dead: Vec<String> = registry
+        .iter()
+        .filter(|(_, pid)| !is_process_alive(**pid))
+        .map(|(session_id, _)| session_id.clone())
+        .collect();
+
+    let mut interrupted = INTERRUPTED_SESSIONS.lock().unwrap();
+    for session_id in &dead {
+        if let Some(pid) = registry.remove(session_id) {
+            log::trace!("Claude process {pid} died during sleep for session: {session_id}");
+        }
+        interrupted.insert(session_id.clone());
+    }
+    dead
+}
+
+/// Check and clear the interrupted flag for a session
+pub fn take_interrupted(session_id: &str) -> bool {
+    INTERRUPTED_SESSIONS.lock().unwrap().remove(session_id)
+}
+
 /// Cancel a running Claude process for a session by sending SIGKILL to the process group
 /// Returns true if a process was found and signal sent, false otherwise
 ///
@@ -0,0 +1,445 @@
+//! Session replay
+//!
+//! Re-emits a session's stored CLI event stream over the event bus so a past
+//! agent run can be demoed or debugged without a frontend playback engine.
+//! Playback either follows the original relative timing (scaled by a speed
+//! factor) or is stepped manually one event at a time.
+//!
+//! Original timing comes from the run's `started_at`/`ended_at` and the tool
+//! timing marks recorded by the timeline module; events without a recorded time
+//! are spread evenly between their nearest known neighbours.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::run_log::read_run_log;
+use super::storage::load_metadata;
+use super::timeline::load_timing_marks;
+use crate::http_server::EmitExt;
+use crate::runtime::PathProvider;
+
+/// Longest pause between two events during timed playback (after speed scaling),
+/// so idle gaps between runs don't stall the replay
+const MAX_REPLAY_GAP_MS: u64 = 5_000;
+
+/// A single stored CLI event with its offset from the start of the session
+#[derive(Debug, Clone)]
+pub(super) struct ReplayItem {
+    pub run_id: String,
+    pub offset_ms: u64,
+    pub event: serde_json::Value,
+}
+
+/// Playback state for an active replay
+struct ReplayState {
+    replay_id: String,
+    /// Manual playback via replay_step (timed playback owns its items on the playback thread)
+    stepped: bool,
+    items: Vec<ReplayItem>,
+    cursor: usize,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Global registry of active replays by session_id (one replay per session)
+static REPLAY_REGISTRY: Lazy<Mutex<HashMap<String, ReplayState>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Summary returned when a replay starts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayInfo {
+    pub replay_id: String,
+    pub session_id: String,
+    /// Total number of events that will be emitted
+    pub total_events: usize,
+    /// Original session duration covered by the events (milliseconds)
+    pub duration_ms: u64,
+    /// Whether playback is stepped manually via replay_step
+    pub stepped: bool,
+}
+
+/// Payload for replay:event, emitted once per stored CLI event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    pub replay_id: String,
+    pub session_id: String,
+    pub run_id: String,
+    /// Zero-based position of this event in the replay
+    pub index: usize,
+    pub total: usize,
+    /// Original offset from the start of the session (milliseconds)
+    pub offset_ms: u64,
+    /// Raw stream-json event as written by the CLI
+    pub event: serde_json::Value,
+}
+
+/// Payload for replay:done
+#[derive(Debug, Clone, Serialize)]
+struct ReplayDoneEvent {
+    replay_id: String,
+    session_id: String,
+    cancelled: bool,
+}
+
+/// Fill in missing timestamps by spreading them evenly between known neighbours.
+/// Leading gaps take the first known value, trailing gaps the last one.
+/// The result is clamped to be non-decreasing.
+fn interpolate_times(anchors: &[Option<u64>]) -> Vec<u64> {
+    let known: Vec<(usize, u64)> = anchors
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| t.map(|t| (i, t)))
+        .collect();
+
+    if known.is_empty() {
+        return vec![0; anchors.len()];
+    }
+
+    let mut times = Vec::with_capacity(anchors.len());
+    let mut next_known = 0;
+    for i in 0..anchors.len() {
+        while next_known < known.len() && known[next_known].0 < i {
+            next_known += 1;
+        }
+        let t = match (
+            next_known.checked_sub(1).map(|p| known[p]),
+            known.get(next_known),
+        ) {
+            (_, Some(&(idx, t))) if idx == i => t,
+            (Some((prev_idx, prev_t)), Some(&(idx, t))) => {
+                let span = (idx - prev_idx) as u64;
+                let step = (i - prev_idx) as u64;
+                prev_t + t.saturating_sub(prev_t) * step / span
+            }
+            (Some((_, prev_t)), None) => prev_t,
+            (None, Some(&(_, t))) => t,
+            (None, None) => 0,
+        };
+        times.push(t);
+    }
+
+    for i in 1..times.len() {
+        if times[i] < times[i - 1] {
+            times[i] = times[i - 1];
+        }
+    }
+    times
+}
+
+/// Determine the recorded time (ms) for a stream-json event from the run's timing marks
+fn anchor_for(event: &serde_json::Value, marks: &HashMap<(String, String), u64>) -> Option<u64> {
+    let (phase, block_type, id_field) = match event.get("type").and_then(|v| v.as_str()) {
+        Some("assistant") => ("start", "tool_use", "id"),
+        Some("user") => ("end", "tool_result", "tool_use_id"),
+        _ => return None,
+    };
+
+    event
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())?
+        .iter()
+        .filter(|b| b.get("type").and_then(|v| v.as_str()) == Some(block_type))
+        .filter_map(|b| b.get(id_field).and_then(|v| v.as_str()))
+        .filter_map(|id| marks.get(&(id.to_string(), phase.to_string())).copied())
+        .min()
+}
+
+/// Load every stored CLI event for a session with its original offset
+pub(super) fn load_replay_items(
+    app: &impl PathProvider,
+    session_id: &str,
+) -> Result<Vec<ReplayItem>, String> {
+    let metadata = load_metadata(app, session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+
+    let mut run_events: Vec<(String, Vec<serde_json::Value>, Vec<Option<u64>>)> = Vec::new();
+    for run in &metadata.runs {
+        let lines = read_run_log(app, session_id, &run.run_id)?;
+        let marks: HashMap<(String, String), u64> =
+            load_timing_marks(app, session_id, &run.run_id)?
+                .into_iter()
+                .map(|m| ((m.id, m.phase), m.ts))
+                .collect();
+
+        let events: Vec<serde_json::Value> = lines
+            .iter()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter(|v| {
+                !v.get("_run_meta")
+                    .and_then(|m| m.as_bool())
+                    .unwrap_or(false)
+            })
+            .collect();
+        if events.is_empty() {
+            continue;
+        }
+
+        let mut anchors: Vec<Option<u64>> = events.iter().map(|e| anchor_for(e, &marks)).collect();
+        // Run boundaries pin the first and last events when nothing better is known
+        if anchors[0].is_none() {
+            anchors[0] = Some(run.started_at * 1000);
+        }
+        let last = anchors.len() - 1;
+        if anchors[last].is_none() {
+            anchors[last] = run.ended_at.map(|t| t * 1000);
+        }
+
+        run_events.push((run.run_id.clone(), events, anchors));
+    }
+
+    let session_start = run_events
+        .iter()
+        .filter_map(|(_, _, anchors)| anchors.first().copied().flatten())
+        .min()
+        .unwrap_or(0);
+
+    let mut items = Vec::new();
+    for (run_id, events, anchors) in run_events {
+        let times = interpolate_times(&anchors);
+        for (event, t) in events.into_iter().zip(times) {
+            items.push(ReplayItem {
+                run_id: run_id.clone(),
+                offset_ms: t.saturating_sub(session_start),
+                event,
+            });
+        }
+    }
+
+    // Runs are sequential, but keep offsets monotonic in case clocks disagree
+    for i in 1..items.len() {
+        if items[i].offset_ms < items[i - 1].offset_ms {
+            items[i].offset_ms = items[i - 1].offset_ms;
+        }
+    }
+
+    Ok(items)
+}
+
+fn emit_item(
+    app: &AppHandle,
+    replay_id: &str,
+    session_id: &str,
+    index: usize,
+    items: &[ReplayItem],
+) -> ReplayEvent {
+    let item = &items[index];
+    let payload = ReplayEvent {
+        replay_id: replay_id.to_string(),
+        session_id: session_id.to_string(),
+        run_id: item.run_id.clone(),
+        index,
+        total: items.len(),
+        offset_ms: item.offset_ms,
+        event: item.event.clone(),
+    };
+    if let Err(e) = app.emit_all("replay:event", &payload) {
+        log::error!("Failed to emit replay event: {e}");
+    }
+    payload
+}
+
+fn emit_done(app: &AppHandle, replay_id: &str, session_id: &str, cancelled: bool) {
+    let payload = ReplayDoneEvent {
+        replay_id: replay_id.to_string(),
+        session_id: session_id.to_string(),
+        cancelled,
+    };
+    if let Err(e) = app.emit_all("replay:done", &payload) {
+        log::error!("Failed to emit replay done event: {e}");
+    }
+}
+
+/// Start replaying a session's event stream.
+///
+/// With a positive `speed`, events are emitted on a background thread using the
+/// original relative timing divided by `speed`. With no speed (or 0), the replay
+/// is stepped manually via `step_replay`. Starting a new replay for a session
+/// stops any replay already in progress for it.
+pub fn start_replay(
+    app: &AppHandle,
+    session_id: &str,
+    speed: Option<f64>,
+) -> Result<ReplayInfo, String> {
+    let items = load_replay_items(app, session_id)?;
+    if items.is_empty() {
+        return Err("Session has no recorded events to replay".to_string());
+    }
+
+    stop_replay(app, session_id);
+
+    let speed = speed.filter(|s| s.is_finite() && *s > 0.0);
+    let replay_id = uuid::Uuid::new_v4().to_string();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let info = ReplayInfo {
+        replay_id: replay_id.clone(),
+        session_id: session_id.to_string(),
+        total_events: items.len(),
+        duration_ms: items.last().map(|i| i.offset_ms).unwrap_or(0),
+        stepped: speed.is_none(),
+    };
+
+    log::trace!(
+        "Starting replay {replay_id} for session {session_id} ({} events, speed {speed:?})",
+        items.len()
+    );
+
+    let Some(speed) = speed else {
+        REPLAY_REGISTRY.lock().unwrap().insert(
+            session_id.to_string(),
+            ReplayState {
+                replay_id,
+                stepped: true,
+                items,
+                cursor: 0,
+                cancel,
+            },
+        );
+        return Ok(info);
+    };
+
+    REPLAY_REGISTRY.lock().unwrap().insert(
+        session_id.to_string(),
+        ReplayState {
+            replay_id: replay_id.clone(),
+            stepped: false,
+            items: Vec::new(),
+            cursor: 0,
+            cancel: cancel.clone(),
+        },
+    );
+
+    let app = app.clone();
+    let session_id = session_id.to_string();
+    crate::background_tasks::supervisor::spawn_thread("replay", move || {
+        let mut previous_offset = 0;
+        for index in 0..items.len() {
+            let gap = items[index].offset_ms.saturating_sub(previous_offset);
+            let delay = ((gap as f64 / speed) as u64).min(MAX_REPLAY_GAP_MS);
+            previous_offset = items[index].offset_ms;
+
+            // Sleep in small slices so stop_replay takes effect promptly
+            let mut remaining = delay;
+            while remaining > 0 && !cancel.load(Ordering::Relaxed) {
+                let slice = remaining.min(50);
+                std::thread::sleep(Duration::from_millis(slice));
+                remaining -= slice;
+            }
+            if cancel.load(Ordering::Relaxed) {
+                emit_done(&app, &replay_id, &session_id, true);
+                return;
+            }
+
+            emit_item(&app, &replay_id, &session_id, index, &items);
+        }
+
+        {
+            let mut registry = REPLAY_REGISTRY.lock().unwrap();
+            if registry
+                .get(&session_id)
+                .is_some_and(|s| s.replay_id == replay_id)
+            {
+                registry.remove(&session_id);
+            }
+        }
+        emit_done(&app, &replay_id, &session_id, false);
+    });
+
+    Ok(info)
+}
+
+/// Emit the next event of a stepped replay and return it.
+/// The replay ends (and replay:done is emitted) after the last event.
+pub fn step_replay(app: &AppHandle, session_id: &str) -> Result<ReplayEvent, String> {
+    let mut registry = REPLAY_REGISTRY.lock().unwrap();
+    let state = registry
+        .get_mut(session_id)
+        .ok_or_else(|| format!("No replay in progress for session: {session_id}"))?;
+
+    if !state.stepped {
+        return Err("Replay is running in timed mode and cannot be stepped".to_string());
+    }
+
+    let event = emit_item(
+        app,
+        &state.replay_id,
+        session_id,
+        state.cursor,
+        &state.items,
+    );
+
+    state.cursor += 1;
+    if state.cursor >= state.items.len() {
+        let replay_id = state.replay_id.clone();
+        registry.remove(session_id);
+        emit_done(app, &replay_id, session_id, false);
+    }
+
+    Ok(event)
+}
+
+/// Stop a replay in progress for a session. Returns true if one was running.
+pub fn stop_replay(app: &AppHandle, session_id: &str) -> bool {
+    let Some(state) = REPLAY_REGISTRY.lock().unwrap().remove(session_id) else {
+        return false;
+    };
+
+    state.cancel.store(true, Ordering::Relaxed);
+    // Timed replays emit their own done event when the playback thread notices the flag
+    if state.stepped {
+        emit_done(app, &state.replay_id, session_id, true);
+    }
+    log::trace!(
+        "Stopped replay {} for session {session_id}",
+        state.replay_id
+    );
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate_times_fills_gaps() {
+        let times = interpolate_times(&[Some(1_000), None, None, Some(4_000), None]);
+        assert_eq!(times, vec![1_000, 2_000, 3_000, 4_000, 4_000]);
+    }
+
+    #[test]
+    fn test_interpolate_times_leading_gap_and_monotonic() {
+        let times = interpolate_times(&[None, Some(500), Some(300), None]);
+        assert_eq!(times, vec![500, 500, 500, 500]);
+    }
+
+    #[test]
+    fn test_interpolate_times_no_anchors() {
+        assert_eq!(interpolate_times(&[None, None]), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_anchor_for_tool_events() {
+        let mut marks = HashMap::new();
+        marks.insert(("t1".to_string(), "start".to_string()), 1_200);
+        marks.insert(("t1".to_string(), "end".to_string()), 2_400);
+
+        let assistant = serde_json::json!({
+            "type": "assistant",
+            "message": {"content": [{"type": "tool_use", "id": "t1", "name": "Bash"}]}
+        });
+        let user = serde_json::json!({
+            "type": "user",
+            "message": {"content": [{"type": "tool_result", "tool_use_id": "t1"}]}
+        });
+        let system = serde_json::json!({"type": "system", "subtype": "init"});
+
+        assert_eq!(anchor_for(&assistant, &marks), Some(1_200));
+        assert_eq!(anchor_for(&user, &marks), Some(2_400));
+        assert_eq!(anchor_for(&system, &marks), None);
+    }
+}
@@ -0,0 +1,203 @@
+//! Re-running a past session
+//!
+//! `rerun_session` starts a new session with the original conversation's
+//! first prompt, attachments, model and flags, optionally in a fresh worktree
+//! checked out at the git commit recorded in its environment snapshot. The
+//! new session's `rerun_of` points back at the original for comparison.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::input_requests::ResumeSettings;
+use super::types::{Session, SessionMetadata};
+use crate::projects::github_issues::{
+    add_issue_reference, add_pr_reference, get_session_issue_refs, get_session_pr_refs,
+};
+use crate::projects::storage::load_projects_data;
+use crate::projects::types::Worktree;
+use crate::runtime::PathProvider;
+
+/// How long to wait for a fresh worktree (including its setup script)
+const WORKTREE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// A started re-run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RerunInfo {
+    pub session: Session,
+    /// Worktree the re-run happens in (pending while a fresh one is created)
+    pub worktree: Worktree,
+    pub rerun_of: String,
+    /// Commit the fresh worktree was checked out at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+}
+
+/// First prompt of a session's conversation and the settings it ran with
+///
+/// Sessions started before launch settings were recorded fall back to the
+/// model, mode and levels of their first run.
+pub fn launch_of(
+    metadata: &SessionMetadata,
+    worktree_path: &str,
+) -> Result<(String, ResumeSettings), String> {
+    let run = metadata
+        .runs
+        .first()
+        .ok_or_else(|| format!("Session {} has no messages to re-run", metadata.id))?;
+    let level = |value: &Option<String>| {
+        value
+            .clone()
+            .and_then(|v| serde_json::from_value(serde_json::Value::String(v)).ok())
+    };
+    let settings = metadata
+        .launch_settings
+        .clone()
+        .unwrap_or_else(|| ResumeSettings {
+            worktree_id: metadata.worktree_id.clone(),
+            worktree_path: worktree_path.to_string(),
+            model: run.model.clone(),
+            execution_mode: run.execution_mode.clone(),
+            thinking_level: level(&run.thinking_level),
+            effort_level: level(&run.effort_level),
+            disable_thinking_for_mode: None,
+            parallel_execution_prompt: None,
+            ai_language: None,
+            allowed_tools: None,
+            mcp_config: None,
+            chrome_enabled: None,
+            custom_profile_settings: None,
+            background_priority: None,
+        });
+    Ok((run.user_message.clone(), settings))
+}
+
+/// Copy `{from}-context-*.md` saved contexts to `{to}-context-*.md`
+fn copy_saved_contexts(dir: &Path, from: &str, to: &str) -> Result<usize, String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(0);
+    };
+    let prefix = format!("{from}-context-");
+    let mut copied = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(rest) = name.strip_prefix(&prefix) else {
+            continue;
+        };
+        std::fs::copy(entry.path(), dir.join(format!("{to}-context-{rest}")))
+            .map_err(|e| format!("Failed to copy saved context {name}: {e}"))?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+/// Attach the original session's issues, PRs and saved contexts to the re-run
+pub fn copy_attachments(app: &tauri::AppHandle, from: &str, to: &str) -> Result<(), String> {
+    for key in get_session_issue_refs(app, from)? {
+        if let Some((repo_key, number)) = key.rsplit_once('-') {
+            if let Ok(number) = number.parse() {
+                add_issue_reference(app, repo_key, number, to)?;
+            }
+        }
+    }
+    for key in get_session_pr_refs(app, from)? {
+        if let Some((repo_key, number)) = key.rsplit_once('-') {
+            if let Ok(number) = number.parse() {
+                add_pr_reference(app, repo_key, number, to)?;
+            }
+        }
+    }
+
+    let app_data_dir = app.app_data_dir()?;
+    let copied = copy_saved_contexts(&app_data_dir.join("session-context"), from, to)?;
+    log::trace!("Copied {copied} saved contexts from session {from} to {to}");
+    Ok(())
+}
+
+/// Wait until a worktree being created in the background has been saved
+pub async fn wait_for_worktree(app: &tauri::AppHandle, worktree_id: &str) -> Result<(), String> {
+    let started = std::time::Instant::now();
+    while started.elapsed() < WORKTREE_TIMEOUT {
+        let ready = load_projects_data(app)
+            .map(|data| data.find_worktree(worktree_id).is_some())
+            .unwrap_or(false);
+        if ready {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    Err(format!(
+        "Worktree {worktree_id} wasn't created within {} minutes",
+        WORKTREE_TIMEOUT.as_secs() / 60
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::types::{EffortLevel, RunEntry, RunStatus, ThinkingLevel};
+
+    fn run(message: &str) -> RunEntry {
+        RunEntry {
+            run_id: "r1".to_string(),
+            user_message_id: "m1".to_string(),
+            user_message: message.to_string(),
+            model: Some("opus".to_string()),
+            execution_mode: Some("build".to_string()),
+            thinking_level: Some("megathink".to_string()),
+            effort_level: Some("max".to_string()),
+            started_at: 0,
+            ended_at: None,
+            status: RunStatus::Completed,
+            assistant_message_id: None,
+            cancelled: false,
+            recovered: false,
+            claude_session_id: None,
+            pid: None,
+            usage: None,
+            attribution: None,
+            subproject: None,
+        }
+    }
+
+    #[test]
+    fn test_launch_of() {
+        let mut metadata =
+            SessionMetadata::new("s1".to_string(), "w1".to_string(), "S".to_string(), 0);
+        assert!(launch_of(&metadata, "/wt").is_err());
+
+        metadata.runs = vec![run("fix the build"), run("and the tests")];
+        let (prompt, settings) = launch_of(&metadata, "/wt").unwrap();
+        assert_eq!(prompt, "fix the build");
+        assert_eq!(settings.worktree_path, "/wt");
+        assert_eq!(settings.model.as_deref(), Some("opus"));
+        assert_eq!(settings.thinking_level, Some(ThinkingLevel::Megathink));
+        assert_eq!(settings.effort_level, Some(EffortLevel::Max));
+
+        // Recorded launch settings carry the flags too
+        let mut recorded = settings.clone();
+        recorded.mcp_config = Some("{}".to_string());
+        recorded.chrome_enabled = Some(true);
+        metadata.launch_settings = Some(recorded);
+        let (_, settings) = launch_of(&metadata, "/wt").unwrap();
+        assert_eq!(settings.mcp_config.as_deref(), Some("{}"));
+        assert_eq!(settings.chrome_enabled, Some(true));
+    }
+
+    #[test]
+    fn test_copy_saved_contexts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("s1-context-plan.md"), "# Plan").unwrap();
+        std::fs::write(dir.path().join("s2-context-other.md"), "# Other").unwrap();
+
+        assert_eq!(copy_saved_contexts(dir.path(), "s1", "s3"), Ok(1));
+        let copied = std::fs::read_to_string(dir.path().join("s3-context-plan.md")).unwrap();
+        assert_eq!(copied, "# Plan");
+        assert!(!dir.path().join("s3-context-other.md").exists());
+        assert_eq!(
+            copy_saved_contexts(&dir.path().join("missing"), "s1", "s3"),
+            Ok(0)
+        );
+    }
+}
@@ -10,6 +10,9 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use uuid::Uuid;
 
+use crate::runtime::PathProvider;
+
+use super::blobs::expand_line;
 use super::storage::{
     get_session_dir, list_all_session_ids, load_metadata, save_metadata, with_metadata_mut,
 };
@@ -124,6 +127,32 @@ impl RunLogWriter {
         Ok(())
     }
 
+    /// Mark the run as interrupted by system sleep and update the metadata
+    pub fn interrupt(&mut self, assistant_message_id: Option<&str>) -> Result<(), String> {
+        let now = now_timestamp();
+        let run_id = self.run_id.clone();
+        let asst_id = assistant_message_id.map(|s| s.to_string());
+
+        with_metadata_mut(
+            &self.app,
+            &self.session_id,
+            &self.worktree_id,
+            &self.session_name,
+            self.order,
+            |metadata| {
+                if let Some(run) = metadata.find_run_mut(&run_id) {
+                    run.status = RunStatus::Interrupted;
+                    run.ended_at = Some(now);
+                    run.assistant_message_id = asst_id;
+                }
+                Ok(())
+            },
+        )?;
+
+        log::trace!("Run interrupted: {}", self.run_id);
+        Ok(())
+    }
+
     /// Mark the run as crashed (for recovery)
     #[allow(dead_code)]
     pub fn mark_crashed(&mut self) -> Result<(), String> {
@@ -297,7 +326,7 @@ pub fn start_run(
     log::trace!("Run log file created with metadata header");
 
     // Add run entry to metadata
-    let run_entry = RunEntry {
+    let mut run_entry = RunEntry {
         run_id: run_id.clone(),
         user_message_id: user_message_id.to_string(),
         user_message: user_message.to_string(),
@@ -314,7 +343,12 @@ pub fn start_run(
         claude_session_id: None,
         pid: None,   // Set later via set_pid() after spawning detached process
         usage: None, // Set on completion via complete()
+        attribution: None,
+        subproject: None,
     };
+    let project_attribution = crate::reports::attribution::project_attribution(app, worktree_id);
+    let subproject = crate::projects::subprojects::for_session(app, session_id, worktree_id)
+        .map(|(subproject, _)| subproject);
 
     with_metadata_mut(
         app,
@@ -323,7 +357,14 @@ pub fn start_run(
         session_name,
         order,
         |metadata| {
-            metadata.runs.push(run_entry.clone());
+            // Bill the run to whoever the session, sub-project or project names right now
+            run_entry.attribution = metadata
+                .attribution
+                .clone()
+                .or(subproject.as_ref().and_then(|s| s.attribution.clone()))
+                .or(project_attribution);
+            run_entry.subproject = subproject.map(|s| s.path);
+            metadata.runs.push(run_entry);
             Ok(())
         },
     )?;
@@ -406,7 +447,7 @@ pub fn delete_input_file(
 
 /// Get the path to a run's JSONL file
 pub fn get_run_log_path(
-    app: &tauri::AppHandle,
+    app: &impl PathProvider,
     session_id: &str,
     run_id: &str,
 ) -> Result<PathBuf, String> {
@@ -414,9 +455,9 @@ pub fn get_run_log_path(
     Ok(session_dir.join(format!("{run_id}.jsonl")))
 }
 
-/// Read all lines from a run's JSONL file
+/// Read all lines from a run's JSONL file, expanding compacted lines
 pub fn read_run_log(
-    app: &tauri::AppHandle,
+    app: &impl PathProvider,
     session_id: &str,
     run_id: &str,
 ) -> Result<Vec<String>, String> {
@@ -428,20 +469,28 @@ pub fn read_run_log(
 
     let file = File::open(&path).map_err(|e| format!("Failed to open run log: {e}"))?;
 
-    let reader = BufReader::new(file);
-    let lines: Result<Vec<_>, _> = reader.lines().collect();
-
-    lines.map_err(|e| format!("Failed to read run log: {e}"))
+    let session_dir = get_session_dir(app, session_id)?;
+    BufReader::new(file)
+        .lines()
+        .map(|line| {
+            let line = line.map_err(|e| format!("Failed to read run log: {e}"))?;
+            expand_line(&session_dir, line)
+        })
+        .collect()
 }
 
 /// Parse JSONL lines and build a ChatMessage
 /// This replicates the parsing logic from execute_claude_streaming
-pub fn parse_run_to_message(lines: &[String], run: &RunEntry) -> Result<ChatMessage, String> {
+pub fn parse_run_to_message<S: AsRef<str>>(
+    lines: impl IntoIterator<Item = S>,
+    run: &RunEntry,
+) -> Result<ChatMessage, String> {
     let mut content = String::new();
     let mut tool_calls: Vec<ToolCall> = Vec::new();
     let mut content_blocks: Vec<ContentBlock> = Vec::new();
 
     for line in lines {
+        let line = line.as_ref();
         if line.trim().is_empty() {
             continue;
         }
@@ -598,7 +647,7 @@ pub fn parse_run_to_message(lines: &[String], run: &RunEntry) -> Result<ChatMess
 /// Load all messages for a session by parsing JSONL files
 /// Returns messages in chronological order (user message, then assistant response)
 pub fn load_session_messages(
-    app: &tauri::AppHandle,
+    app: &impl PathProvider,
     session_id: &str,
 ) -> Result<Vec<ChatMessage>, String> {
     let metadata = match load_metadata(app, session_id)? {
@@ -607,52 +656,84 @@ pub fn load_session_messages(
     };
 
     let mut messages = Vec::new();
-
     for run in &metadata.runs {
-        // Skip user message for instant-cancelled runs (undo_send)
-        // These have Cancelled status but no assistant_message_id
-        let is_undo_send = run.status == RunStatus::Cancelled && run.assistant_message_id.is_none();
-
-        if !is_undo_send {
-            // Add user message
-            messages.push(ChatMessage {
-                id: run.user_message_id.clone(),
-                session_id: session_id.to_string(),
-                role: MessageRole::User,
-                content: run.user_message.clone(),
-                timestamp: run.started_at,
-                tool_calls: vec![],
-                content_blocks: vec![],
-                cancelled: false,
-                plan_approved: false,
-                model: run.model.clone(),
-                execution_mode: run.execution_mode.clone(),
-                thinking_level: run.thinking_level.clone(),
-                effort_level: run.effort_level.clone(),
-                recovered: false,
-                usage: None, // User messages don't have token usage
-            });
-        }
+        messages.extend(run_messages(app, session_id, run)?);
+    }
 
-        // Add assistant message if run has completed/cancelled/crashed
-        if run.status != RunStatus::Running && !is_undo_send {
-            let lines = read_run_log(app, session_id, &run.run_id)?;
-
-            // Parse JSONL content (may only have metadata header if crashed early)
-            let mut assistant_msg = parse_run_to_message(&lines, run)?;
-            assistant_msg.session_id = session_id.to_string();
-
-            // For crashed runs with no content (only metadata header), add placeholder
-            if run.status == RunStatus::Crashed
-                && assistant_msg.content.is_empty()
-                && assistant_msg.tool_calls.is_empty()
-            {
-                assistant_msg.content =
-                    "*Response lost - Jean was closed before receiving a response.*".to_string();
-            }
+    Ok(messages)
+}
+
+/// Instant-cancelled runs (undo_send) have Cancelled status but no
+/// assistant_message_id, and show no messages at all
+fn is_undo_send(run: &RunEntry) -> bool {
+    run.status == RunStatus::Cancelled && run.assistant_message_id.is_none()
+}
+
+/// Number of messages a run contributes, without reading its log
+pub(crate) fn run_message_count(run: &RunEntry) -> usize {
+    if is_undo_send(run) {
+        0
+    } else if run.status == RunStatus::Running {
+        1
+    } else {
+        2
+    }
+}
+
+/// The user message of a run, then its assistant message once the run has
+/// completed/cancelled/crashed
+pub(crate) fn run_messages(
+    app: &impl PathProvider,
+    session_id: &str,
+    run: &RunEntry,
+) -> Result<Vec<ChatMessage>, String> {
+    if is_undo_send(run) {
+        return Ok(Vec::new());
+    }
+
+    let mut messages = vec![ChatMessage {
+        id: run.user_message_id.clone(),
+        session_id: session_id.to_string(),
+        role: MessageRole::User,
+        content: run.user_message.clone(),
+        timestamp: run.started_at,
+        tool_calls: vec![],
+        content_blocks: vec![],
+        cancelled: false,
+        plan_approved: false,
+        model: run.model.clone(),
+        execution_mode: run.execution_mode.clone(),
+        thinking_level: run.thinking_level.clone(),
+        effort_level: run.effort_level.clone(),
+        recovered: false,
+        usage: None, // User messages don't have token usage
+    }];
+
+    if run.status != RunStatus::Running {
+        let lines = read_run_log(app, session_id, &run.run_id)?;
 
-            messages.push(assistant_msg);
+        // Parse JSONL content (may only have metadata header if crashed early)
+        let mut assistant_msg = parse_run_to_message(&lines, run)?;
+        assistant_msg.session_id = session_id.to_string();
+
+        // For crashed runs with no content (only metadata header), add placeholder
+        if run.status == RunStatus::Crashed
+            && assistant_msg.content.is_empty()
+            && assistant_msg.tool_calls.is_empty()
+        {
+            assistant_msg.content =
+                "*Response lost - Jean was closed before receiving a response.*".to_string();
         }
+        if run.status == RunStatus::Interrupted
+            && assistant_msg.content.is_empty()
+            && assistant_msg.tool_calls.is_empty()
+        {
+            assistant_msg.content =
+                "*Response interrupted - the system went to sleep. Send a message to continue.*"
+                    .to_string();
+        }
+
+        messages.push(assistant_msg);
     }
 
     Ok(messages)
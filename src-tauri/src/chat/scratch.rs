@@ -0,0 +1,204 @@
+//! Per-session scratch directories
+//!
+//! Each session gets a managed scratch directory that is exported to the
+//! Claude CLI via environment variables (`JEAN_SCRATCH_DIR`, `TMPDIR`, ...),
+//! so temporary files created by agents land in a known place instead of
+//! the repository or the system temp dir. Scratch directories live inside the
+//! session data directory and are removed when the session is closed, archived,
+//! or has been idle longer than the configured retention period.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::AppHandle;
+
+use super::storage::{get_data_dir, get_session_dir};
+use super::types::ScratchUsage;
+
+/// Name of the scratch subdirectory inside `sessions/data/{session_id}/`
+const SCRATCH_DIR_NAME: &str = "scratch";
+
+/// Get the scratch directory for a session (creates if not exists)
+/// Path: sessions/data/{session_id}/scratch/
+pub fn get_session_scratch_dir(app: &AppHandle, session_id: &str) -> Result<PathBuf, String> {
+    let scratch_dir = get_session_dir(app, session_id)?.join(SCRATCH_DIR_NAME);
+
+    fs::create_dir_all(&scratch_dir)
+        .map_err(|e| format!("Failed to create scratch directory: {e}"))?;
+
+    Ok(scratch_dir)
+}
+
+/// Environment variables that point the CLI (and anything it spawns) at the scratch dir
+pub fn scratch_env_vars(scratch_dir: &Path) -> Vec<(String, String)> {
+    let dir = scratch_dir.to_string_lossy().to_string();
+    vec![
+        ("JEAN_SCRATCH_DIR".to_string(), dir.clone()),
+        ("TMPDIR".to_string(), dir.clone()),
+        ("TMP".to_string(), dir.clone()),
+        ("TEMP".to_string(), dir),
+    ]
+}
+
+/// Recursively compute (total bytes, file count, latest modification time) for a directory.
+/// Missing directories report zero usage. Symlinks are not followed.
+pub(crate) fn dir_usage(path: &Path) -> (u64, u64, Option<u64>) {
+    let mut size_bytes = 0u64;
+    let mut file_count = 0u64;
+    let mut last_modified: Option<u64> = None;
+
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return (0, 0, None),
+    };
+
+    for entry in entries.flatten() {
+        let metadata = match entry.path().symlink_metadata() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        last_modified = last_modified.max(modified);
+
+        if metadata.is_dir() {
+            let (sub_size, sub_count, sub_modified) = dir_usage(&entry.path());
+            size_bytes += sub_size;
+            file_count += sub_count;
+            last_modified = last_modified.max(sub_modified);
+        } else {
+            size_bytes += metadata.len();
+            file_count += 1;
+        }
+    }
+
+    (size_bytes, file_count, last_modified)
+}
+
+/// Get disk usage of a session's scratch directory
+pub fn get_scratch_usage(app: &AppHandle, session_id: &str) -> Result<ScratchUsage, String> {
+    let scratch_dir = get_data_dir(app)?.join(session_id).join(SCRATCH_DIR_NAME);
+    let (size_bytes, file_count, last_modified) = dir_usage(&scratch_dir);
+
+    Ok(ScratchUsage {
+        session_id: session_id.to_string(),
+        path: scratch_dir.to_string_lossy().to_string(),
+        exists: scratch_dir.exists(),
+        size_bytes,
+        file_count,
+        last_modified,
+    })
+}
+
+/// Remove a session's scratch directory (no-op if it doesn't exist)
+pub fn clear_session_scratch(app: &AppHandle, session_id: &str) -> Result<(), String> {
+    let scratch_dir = get_data_dir(app)?.join(session_id).join(SCRATCH_DIR_NAME);
+
+    if scratch_dir.exists() {
+        fs::remove_dir_all(&scratch_dir)
+            .map_err(|e| format!("Failed to delete scratch directory: {e}"))?;
+        log::trace!("Cleared scratch directory for session: {session_id}");
+    }
+
+    Ok(())
+}
+
+/// Remove scratch directories that haven't been touched in `retention_days`.
+/// Sessions with a running process are skipped. Returns the number of directories removed.
+pub fn cleanup_stale_scratch_dirs(app: &AppHandle, retention_days: u32) -> Result<u32, String> {
+    if retention_days == 0 {
+        log::trace!("Scratch cleanup is disabled (retention_days = 0)");
+        return Ok(0);
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let cutoff = now.saturating_sub(retention_days as u64 * 86400);
+
+    let data_dir = get_data_dir(app)?;
+    let entries =
+        fs::read_dir(&data_dir).map_err(|e| format!("Failed to read data directory: {e}"))?;
+
+    let mut removed = 0u32;
+    for entry in entries.flatten() {
+        let scratch_dir = entry.path().join(SCRATCH_DIR_NAME);
+        if !scratch_dir.is_dir() {
+            continue;
+        }
+
+        let session_id = entry.file_name().to_string_lossy().to_string();
+        if super::registry::is_process_running(&session_id) {
+            continue;
+        }
+
+        let (_, _, last_modified) = dir_usage(&scratch_dir);
+        let dir_modified = fs::metadata(&scratch_dir)
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        let last_touched = last_modified.max(dir_modified).unwrap_or(0);
+
+        if last_touched < cutoff {
+            match fs::remove_dir_all(&scratch_dir) {
+                Ok(()) => {
+                    log::trace!("Removed stale scratch directory for session: {session_id}");
+                    removed += 1;
+                }
+                Err(e) => log::warn!("Failed to remove scratch directory {scratch_dir:?}: {e}"),
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("jean-scratch-test-{name}-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_dir_usage_missing_dir() {
+        let dir =
+            std::env::temp_dir().join(format!("jean-scratch-missing-{}", uuid::Uuid::new_v4()));
+        assert_eq!(dir_usage(&dir), (0, 0, None));
+    }
+
+    #[test]
+    fn test_dir_usage_counts_nested_files() {
+        let dir = temp_dir("nested");
+        fs::write(dir.join("a.txt"), b"hello").unwrap();
+        fs::create_dir_all(dir.join("sub/deeper")).unwrap();
+        fs::write(dir.join("sub/b.bin"), [0u8; 100]).unwrap();
+        fs::write(dir.join("sub/deeper/c.txt"), b"xyz").unwrap();
+
+        let (size, count, modified) = dir_usage(&dir);
+        assert_eq!(size, 108);
+        assert_eq!(count, 3);
+        assert!(modified.is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_scratch_env_vars() {
+        let vars = scratch_env_vars(Path::new("/data/sessions/abc/scratch"));
+        let keys: Vec<&str> = vars.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["JEAN_SCRATCH_DIR", "TMPDIR", "TMP", "TEMP"]);
+        assert!(vars.iter().all(|(_, v)| v == "/data/sessions/abc/scratch"));
+    }
+}
@@ -0,0 +1,511 @@
+//! Export sessions for the ChatGPT and Claude web apps
+//!
+//! Converts a session into the conversation JSON used by each web app's data
+//! export (`conversations.json`), so a terminal-agent run can be continued
+//! in the browser. Only the conversation text carries over: tool calls are
+//! summarized as one line each, pasted text files are inlined as
+//! attachments, and pasted images are copied next to the export for manual
+//! upload. Exports larger than the size budget drop their oldest messages.
+
+use std::fs;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::run_log::load_session_messages;
+use super::storage::load_metadata;
+use super::types::{ChatMessage, MessageRole};
+use crate::runtime::PathProvider;
+
+/// Default size budget for an export (web importers reject large files)
+pub const DEFAULT_MAX_BYTES: usize = 4 * 1024 * 1024;
+
+/// Longest inlined text attachment, in characters
+const MAX_ATTACHMENT_CHARS: usize = 20_000;
+
+static IMAGE_MARKER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\[Image attached: (.+?) - Use the Read tool to view this image\]")
+        .expect("Invalid regex")
+});
+
+static TEXT_FILE_MARKER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\[Text file attached: (.+?) - Use the Read tool to view this file\]")
+        .expect("Invalid regex")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareFormat {
+    /// ChatGPT data export (`mapping` tree of messages)
+    Chatgpt,
+    /// Claude data export (`chat_messages` list), also usable as Project knowledge
+    Claude,
+}
+
+/// A pasted text file, inlined into the export
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextAttachment {
+    pub file_name: String,
+    pub content: String,
+}
+
+/// A message reduced to what the web apps can show
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShareMessage {
+    pub id: String,
+    pub role: MessageRole,
+    pub text: String,
+    pub timestamp: u64,
+    pub text_attachments: Vec<TextAttachment>,
+    /// Paths of pasted images
+    pub images: Vec<String>,
+}
+
+/// Result of export_session_for_web
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareExport {
+    /// The conversation JSON file
+    pub path: String,
+    pub format: ShareFormat,
+    pub message_count: usize,
+    /// Images copied next to the export (upload these manually)
+    pub attachments: Vec<String>,
+    /// Oldest messages left out to fit the size budget
+    pub trimmed_messages: usize,
+    pub bytes: usize,
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => format!(
+            "{}\n[... trimmed {} characters]",
+            &text[..cut],
+            text.chars().count() - max
+        ),
+        None => text.to_string(),
+    }
+}
+
+/// Reduce messages to text plus attachments
+///
+/// `read_text` loads a pasted text file (None if it no longer exists).
+pub fn share_messages(
+    messages: &[ChatMessage],
+    read_text: impl Fn(&str) -> Option<String>,
+) -> Vec<ShareMessage> {
+    messages
+        .iter()
+        .filter(|m| !m.content.trim().is_empty() || !m.tool_calls.is_empty())
+        .map(|m| {
+            let images: Vec<String> = IMAGE_MARKER
+                .captures_iter(&m.content)
+                .map(|c| c[1].to_string())
+                .collect();
+            let text_attachments = TEXT_FILE_MARKER
+                .captures_iter(&m.content)
+                .filter_map(|c| {
+                    Some(TextAttachment {
+                        file_name: file_name(&c[1]),
+                        content: truncate_chars(&read_text(&c[1])?, MAX_ATTACHMENT_CHARS),
+                    })
+                })
+                .collect();
+
+            let text = IMAGE_MARKER.replace_all(&m.content, |c: &regex::Captures| {
+                format!("[Image: {}]", file_name(&c[1]))
+            });
+            let text = TEXT_FILE_MARKER.replace_all(&text, |c: &regex::Captures| {
+                format!("[Attached: {}]", file_name(&c[1]))
+            });
+            let mut text = text.trim().to_string();
+
+            // One line per tool call, so the reader can follow what happened
+            for call in &m.tool_calls {
+                let target = ["file_path", "command", "pattern", "url"]
+                    .iter()
+                    .find_map(|k| call.input.get(*k).and_then(|v| v.as_str()));
+                let line = match target {
+                    Some(target) => {
+                        format!("[Tool: {} {}]", call.name, truncate_chars(target, 200))
+                    }
+                    None => format!("[Tool: {}]", call.name),
+                };
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                text.push_str(&line);
+            }
+
+            ShareMessage {
+                id: m.id.clone(),
+                role: m.role.clone(),
+                text,
+                timestamp: m.timestamp,
+                text_attachments,
+                images,
+            }
+        })
+        .collect()
+}
+
+/// Unix seconds as an RFC 3339 UTC timestamp
+fn rfc3339(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// ChatGPT `conversations.json` (a list with one conversation)
+pub fn to_chatgpt(title: &str, created_at: u64, messages: &[ShareMessage]) -> Value {
+    const ROOT: &str = "client-created-root";
+    let mut mapping = serde_json::Map::new();
+    mapping.insert(
+        ROOT.to_string(),
+        json!({ "id": ROOT, "message": null, "parent": null, "children": [] }),
+    );
+
+    let mut parent = ROOT.to_string();
+    for m in messages {
+        let mut text = m.text.clone();
+        for attachment in &m.text_attachments {
+            text.push_str(&format!(
+                "\n\n--- {} ---\n{}",
+                attachment.file_name, attachment.content
+            ));
+        }
+        let role = match m.role {
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+        };
+        let attachments: Vec<Value> = m
+            .images
+            .iter()
+            .map(|path| json!({ "id": file_name(path), "name": file_name(path) }))
+            .collect();
+
+        mapping.insert(
+            m.id.clone(),
+            json!({
+                "id": m.id,
+                "parent": parent,
+                "children": [],
+                "message": {
+                    "id": m.id,
+                    "author": { "role": role },
+                    "create_time": m.timestamp as f64,
+                    "content": { "content_type": "text", "parts": [text] },
+                    "status": "finished_successfully",
+                    "recipient": "all",
+                    "metadata": { "attachments": attachments },
+                },
+            }),
+        );
+        if let Some(children) = mapping
+            .get_mut(&parent)
+            .and_then(|p| p["children"].as_array_mut())
+        {
+            children.push(json!(m.id));
+        }
+        parent = m.id.clone();
+    }
+
+    let updated_at = messages.last().map(|m| m.timestamp).unwrap_or(created_at);
+    json!([{
+        "title": title,
+        "create_time": created_at as f64,
+        "update_time": updated_at as f64,
+        "mapping": mapping,
+        "current_node": parent,
+    }])
+}
+
+/// Claude `conversations.json` (a list with one conversation)
+pub fn to_claude(title: &str, created_at: u64, messages: &[ShareMessage]) -> Value {
+    let chat_messages: Vec<Value> = messages
+        .iter()
+        .map(|m| {
+            let sender = match m.role {
+                MessageRole::User => "human",
+                MessageRole::Assistant => "assistant",
+            };
+            let attachments: Vec<Value> = m
+                .text_attachments
+                .iter()
+                .map(|a| {
+                    json!({
+                        "file_name": a.file_name,
+                        "file_type": "txt",
+                        "file_size": a.content.len(),
+                        "extracted_content": a.content,
+                    })
+                })
+                .collect();
+            let files: Vec<Value> = m
+                .images
+                .iter()
+                .map(|path| json!({ "file_name": file_name(path) }))
+                .collect();
+            json!({
+                "uuid": m.id,
+                "text": m.text,
+                "sender": sender,
+                "created_at": rfc3339(m.timestamp),
+                "updated_at": rfc3339(m.timestamp),
+                "attachments": attachments,
+                "files": files,
+            })
+        })
+        .collect();
+
+    let updated_at = messages.last().map(|m| m.timestamp).unwrap_or(created_at);
+    json!([{
+        "uuid": uuid::Uuid::new_v4().to_string(),
+        "name": title,
+        "created_at": rfc3339(created_at),
+        "updated_at": rfc3339(updated_at),
+        "chat_messages": chat_messages,
+    }])
+}
+
+fn render(format: ShareFormat, title: &str, created_at: u64, messages: &[ShareMessage]) -> Value {
+    match format {
+        ShareFormat::Chatgpt => to_chatgpt(title, created_at, messages),
+        ShareFormat::Claude => to_claude(title, created_at, messages),
+    }
+}
+
+/// Render the export, dropping the oldest messages until it fits `max_bytes`
+///
+/// Returns the JSON and the number of messages left out. The newest message
+/// is always kept, even if it alone exceeds the budget.
+pub fn render_within(
+    format: ShareFormat,
+    title: &str,
+    created_at: u64,
+    messages: &[ShareMessage],
+    max_bytes: usize,
+) -> Result<(String, usize), String> {
+    let mut skip = 0;
+    loop {
+        let json =
+            serde_json::to_string_pretty(&render(format, title, created_at, &messages[skip..]))
+                .map_err(|e| format!("Failed to serialize export: {e}"))?;
+        if json.len() <= max_bytes || skip + 1 >= messages.len() {
+            return Ok((json, skip));
+        }
+        // Drop a user/assistant pair at a time so the conversation starts with the user
+        skip += if messages
+            .get(skip + 1)
+            .is_some_and(|m| m.role == MessageRole::Assistant)
+        {
+            2
+        } else {
+            1
+        };
+        skip = skip.min(messages.len() - 1);
+    }
+}
+
+/// Write a session export for a web app into `dir`
+pub fn export_for_web(
+    app: &impl PathProvider,
+    session_id: &str,
+    format: ShareFormat,
+    dir: &Path,
+    max_bytes: usize,
+) -> Result<ShareExport, String> {
+    let metadata = load_metadata(app, session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    let messages = load_session_messages(app, session_id)?;
+    let messages = share_messages(&messages, |path| fs::read_to_string(path).ok());
+    if messages.is_empty() {
+        return Err("Session has no messages to export".to_string());
+    }
+
+    let (json, trimmed_messages) = render_within(
+        format,
+        &metadata.name,
+        metadata.created_at,
+        &messages,
+        max_bytes,
+    )?;
+    let kept = &messages[trimmed_messages..];
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create export directory: {e}"))?;
+    let slug: String = metadata
+        .name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug.trim_matches('-');
+    let slug = if slug.is_empty() { "session" } else { slug };
+    let suffix = match format {
+        ShareFormat::Chatgpt => "chatgpt",
+        ShareFormat::Claude => "claude",
+    };
+    let path = dir.join(format!("{slug}-{suffix}.json"));
+    fs::write(&path, &json).map_err(|e| format!("Failed to write export: {e}"))?;
+
+    // Images can't be embedded; copy them for manual upload
+    let mut attachments = Vec::new();
+    let images: Vec<&String> = kept.iter().flat_map(|m| &m.images).collect();
+    if !images.is_empty() {
+        let attachments_dir = dir.join(format!("{slug}-attachments"));
+        fs::create_dir_all(&attachments_dir)
+            .map_err(|e| format!("Failed to create attachments directory: {e}"))?;
+        for image in images {
+            let target = attachments_dir.join(file_name(image));
+            match fs::copy(image, &target) {
+                Ok(_) => attachments.push(target.to_string_lossy().into_owned()),
+                Err(e) => log::warn!("Skipping missing image {image}: {e}"),
+            }
+        }
+    }
+
+    Ok(ShareExport {
+        path: path.to_string_lossy().into_owned(),
+        format,
+        message_count: kept.len(),
+        attachments,
+        trimmed_messages,
+        bytes: json.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::types::ToolCall;
+
+    fn message(id: &str, role: MessageRole, content: &str, timestamp: u64) -> ChatMessage {
+        ChatMessage {
+            id: id.to_string(),
+            role,
+            content: content.to_string(),
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    fn sample() -> Vec<ShareMessage> {
+        let mut assistant = message("a1", MessageRole::Assistant, "Done.", 20);
+        assistant.tool_calls = vec![ToolCall {
+            id: "t1".to_string(),
+            name: "Edit".to_string(),
+            input: json!({ "file_path": "src/main.rs" }),
+            output: None,
+            parent_tool_use_id: None,
+        }];
+        let messages = vec![
+            message(
+                "u1",
+                MessageRole::User,
+                "Fix this\n[Text file attached: /tmp/pasted-texts/log.txt - Use the Read tool to view this file]\n[Image attached: /tmp/pasted-images/shot.png - Use the Read tool to view this image]",
+                10,
+            ),
+            assistant,
+        ];
+        share_messages(&messages, |path| {
+            path.ends_with("log.txt").then(|| "error: boom".to_string())
+        })
+    }
+
+    #[test]
+    fn test_share_messages_extracts_attachments() {
+        let messages = sample();
+        assert_eq!(
+            messages[0].text,
+            "Fix this\n[Attached: log.txt]\n[Image: shot.png]"
+        );
+        assert_eq!(messages[0].text_attachments[0].content, "error: boom");
+        assert_eq!(messages[0].images, ["/tmp/pasted-images/shot.png"]);
+        assert_eq!(messages[1].text, "Done.\n[Tool: Edit src/main.rs]");
+    }
+
+    #[test]
+    fn test_chatgpt_mapping_is_a_chain() {
+        let value = to_chatgpt("Fix", 1, &sample());
+        let conversation = &value[0];
+        assert_eq!(conversation["current_node"], "a1");
+        let mapping = &conversation["mapping"];
+        assert_eq!(mapping["client-created-root"]["children"], json!(["u1"]));
+        assert_eq!(mapping["a1"]["parent"], "u1");
+        assert_eq!(mapping["u1"]["message"]["author"]["role"], "user");
+        let text = mapping["u1"]["message"]["content"]["parts"][0]
+            .as_str()
+            .unwrap();
+        assert!(text.contains("--- log.txt ---\nerror: boom"));
+    }
+
+    #[test]
+    fn test_claude_export() {
+        let value = to_claude("Fix", 1_700_000_000, &sample());
+        let conversation = &value[0];
+        assert_eq!(conversation["created_at"], "2023-11-14T22:13:20Z");
+        let first = &conversation["chat_messages"][0];
+        assert_eq!(first["sender"], "human");
+        assert_eq!(first["attachments"][0]["extracted_content"], "error: boom");
+        assert_eq!(first["files"][0]["file_name"], "shot.png");
+    }
+
+    #[test]
+    fn test_render_within_drops_oldest_pairs() {
+        let mut messages = Vec::new();
+        for i in 0..10 {
+            messages.push(ShareMessage {
+                id: format!("u{i}"),
+                role: MessageRole::User,
+                text: "x".repeat(1000),
+                timestamp: i,
+                text_attachments: Vec::new(),
+                images: Vec::new(),
+            });
+            messages.push(ShareMessage {
+                id: format!("a{i}"),
+                role: MessageRole::Assistant,
+                ..messages.last().unwrap().clone()
+            });
+        }
+
+        let (json, trimmed) = render_within(ShareFormat::Claude, "t", 0, &messages, 8_000).unwrap();
+        assert!(json.len() <= 8_000);
+        assert!(trimmed > 0 && trimmed % 2 == 0);
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["chat_messages"][0]["sender"], "human");
+
+        let (_, none_trimmed) =
+            render_within(ShareFormat::Chatgpt, "t", 0, &messages, usize::MAX).unwrap();
+        assert_eq!(none_trimmed, 0);
+    }
+}
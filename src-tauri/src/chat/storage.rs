@@ -5,11 +5,13 @@ use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 use once_cell::sync::Lazy;
-use tauri::{AppHandle, Manager};
+use sha2::{Digest, Sha256};
+
+use crate::runtime::PathProvider;
 
 use super::types::{
-    SavedContextsMetadata, Session, SessionIndexEntry, SessionMetadata, WorktreeIndex,
-    WorktreeSessions,
+    DuplicateGroup, DuplicateSession, RunEntry, RunStatus, SavedContextsMetadata, Session,
+    SessionIndexEntry, SessionMetadata, WorktreeIndex, WorktreeSessions,
 };
 
 // ============================================================================
@@ -70,11 +72,8 @@ pub fn sanitize_filename(name: &str) -> String {
 
 /// Get the sessions base directory in app data (creates if not exists)
 /// Structure: sessions/
-pub fn get_sessions_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+pub fn get_sessions_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    let app_data_dir = app.app_data_dir()?;
 
     let sessions_dir = app_data_dir.join("sessions");
 
@@ -86,7 +85,7 @@ pub fn get_sessions_dir(app: &AppHandle) -> Result<PathBuf, String> {
 
 /// Get the index directory (creates if not exists)
 /// Structure: sessions/index/
-pub fn get_index_dir(app: &AppHandle) -> Result<PathBuf, String> {
+pub fn get_index_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
     let sessions_dir = get_sessions_dir(app)?;
     let index_dir = sessions_dir.join("index");
 
@@ -97,7 +96,7 @@ pub fn get_index_dir(app: &AppHandle) -> Result<PathBuf, String> {
 
 /// Get the data directory (creates if not exists)
 /// Structure: sessions/data/
-pub fn get_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+pub fn get_data_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
     let sessions_dir = get_sessions_dir(app)?;
     let data_dir = sessions_dir.join("data");
 
@@ -108,7 +107,7 @@ pub fn get_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
 
 /// Get the index file path for a worktree
 /// Path: sessions/index/{worktree_id}.json
-pub fn get_index_path(app: &AppHandle, worktree_id: &str) -> Result<PathBuf, String> {
+pub fn get_index_path(app: &impl PathProvider, worktree_id: &str) -> Result<PathBuf, String> {
     let index_dir = get_index_dir(app)?;
     let safe_id = sanitize_filename(worktree_id);
     Ok(index_dir.join(format!("{safe_id}.json")))
@@ -116,7 +115,7 @@ pub fn get_index_path(app: &AppHandle, worktree_id: &str) -> Result<PathBuf, Str
 
 /// Get the session data directory (creates if not exists)
 /// Path: sessions/data/{session_id}/
-pub fn get_session_dir(app: &AppHandle, session_id: &str) -> Result<PathBuf, String> {
+pub fn get_session_dir(app: &impl PathProvider, session_id: &str) -> Result<PathBuf, String> {
     let data_dir = get_data_dir(app)?;
     let session_dir = data_dir.join(session_id);
 
@@ -128,14 +127,14 @@ pub fn get_session_dir(app: &AppHandle, session_id: &str) -> Result<PathBuf, Str
 
 /// Get the metadata file path for a session
 /// Path: sessions/data/{session_id}/metadata.json
-pub fn get_metadata_path(app: &AppHandle, session_id: &str) -> Result<PathBuf, String> {
+pub fn get_metadata_path(app: &impl PathProvider, session_id: &str) -> Result<PathBuf, String> {
     let session_dir = get_session_dir(app, session_id)?;
     Ok(session_dir.join("metadata.json"))
 }
 
 /// Get the path for a closed base session's preserved index file
 /// Path: sessions/index/base-{project_id}.json
-pub fn get_base_index_path(app: &AppHandle, project_id: &str) -> Result<PathBuf, String> {
+pub fn get_base_index_path(app: &impl PathProvider, project_id: &str) -> Result<PathBuf, String> {
     let index_dir = get_index_dir(app)?;
     let safe_id = sanitize_filename(project_id);
     Ok(index_dir.join(format!("base-{safe_id}.json")))
@@ -146,7 +145,10 @@ pub fn get_base_index_path(app: &AppHandle, project_id: &str) -> Result<PathBuf,
 // ============================================================================
 
 /// Load a worktree index (internal, no locking)
-fn load_index_internal(app: &AppHandle, worktree_id: &str) -> Result<WorktreeIndex, String> {
+fn load_index_internal(
+    app: &impl PathProvider,
+    worktree_id: &str,
+) -> Result<WorktreeIndex, String> {
     let path = get_index_path(app, worktree_id)?;
 
     if path.exists() {
@@ -169,7 +171,7 @@ fn load_index_internal(app: &AppHandle, worktree_id: &str) -> Result<WorktreeInd
 }
 
 /// Save a worktree index (internal, no locking - atomic write)
-fn save_index_internal(app: &AppHandle, index: &WorktreeIndex) -> Result<(), String> {
+fn save_index_internal(app: &impl PathProvider, index: &WorktreeIndex) -> Result<(), String> {
     log::trace!("Saving index for worktree: {}", index.worktree_id);
     let path = get_index_path(app, &index.worktree_id)?;
     let temp_path = path.with_extension("tmp");
@@ -198,7 +200,7 @@ fn save_index_internal(app: &AppHandle, index: &WorktreeIndex) -> Result<(), Str
 }
 
 /// Load a worktree index (with locking for thread safety)
-pub fn load_index(app: &AppHandle, worktree_id: &str) -> Result<WorktreeIndex, String> {
+pub fn load_index(app: &impl PathProvider, worktree_id: &str) -> Result<WorktreeIndex, String> {
     let lock = get_index_lock(worktree_id);
     let _guard = lock.lock().unwrap();
 
@@ -215,7 +217,7 @@ pub fn load_index(app: &AppHandle, worktree_id: &str) -> Result<WorktreeIndex, S
 
 /// Atomically load, modify, and save a worktree index.
 /// This prevents race conditions by holding a lock for the entire operation.
-pub fn with_index_mut<F, T>(app: &AppHandle, worktree_id: &str, f: F) -> Result<T, String>
+pub fn with_index_mut<F, T>(app: &impl PathProvider, worktree_id: &str, f: F) -> Result<T, String>
 where
     F: FnOnce(&mut WorktreeIndex) -> Result<T, String>,
 {
@@ -235,7 +237,7 @@ where
 
 /// Load session metadata (internal, no locking)
 fn load_metadata_internal(
-    app: &AppHandle,
+    app: &impl PathProvider,
     session_id: &str,
 ) -> Result<Option<SessionMetadata>, String> {
     let path = get_metadata_path(app, session_id)?;
@@ -255,7 +257,10 @@ fn load_metadata_internal(
 }
 
 /// Save session metadata (internal, no locking - atomic write)
-fn save_metadata_internal(app: &AppHandle, metadata: &SessionMetadata) -> Result<(), String> {
+fn save_metadata_internal(
+    app: &impl PathProvider,
+    metadata: &SessionMetadata,
+) -> Result<(), String> {
     let path = get_metadata_path(app, &metadata.id)?;
     let temp_path = path.with_extension("tmp");
 
@@ -273,14 +278,17 @@ fn save_metadata_internal(app: &AppHandle, metadata: &SessionMetadata) -> Result
 }
 
 /// Load session metadata (with locking for thread safety)
-pub fn load_metadata(app: &AppHandle, session_id: &str) -> Result<Option<SessionMetadata>, String> {
+pub fn load_metadata(
+    app: &impl PathProvider,
+    session_id: &str,
+) -> Result<Option<SessionMetadata>, String> {
     let lock = get_metadata_lock(session_id);
     let _guard = lock.lock().unwrap();
     load_metadata_internal(app, session_id)
 }
 
 /// Save session metadata (with locking for thread safety)
-pub fn save_metadata(app: &AppHandle, metadata: &SessionMetadata) -> Result<(), String> {
+pub fn save_metadata(app: &impl PathProvider, metadata: &SessionMetadata) -> Result<(), String> {
     let lock = get_metadata_lock(&metadata.id);
     let _guard = lock.lock().unwrap();
     save_metadata_internal(app, metadata)
@@ -289,7 +297,7 @@ pub fn save_metadata(app: &AppHandle, metadata: &SessionMetadata) -> Result<(),
 /// Atomically load, modify, and save session metadata.
 /// Creates new metadata if it doesn't exist.
 pub fn with_metadata_mut<F, T>(
-    app: &AppHandle,
+    app: &impl PathProvider,
     session_id: &str,
     worktree_id: &str,
     session_name: &str,
@@ -318,7 +326,7 @@ where
 }
 
 /// Delete a session's metadata and all data files (with locking)
-pub fn delete_session_data(app: &AppHandle, session_id: &str) -> Result<(), String> {
+pub fn delete_session_data(app: &impl PathProvider, session_id: &str) -> Result<(), String> {
     let lock = get_metadata_lock(session_id);
     let _guard = lock.lock().unwrap();
 
@@ -335,7 +343,7 @@ pub fn delete_session_data(app: &AppHandle, session_id: &str) -> Result<(), Stri
 }
 
 /// List all session IDs in the data directory (for recovery scanning)
-pub fn list_all_session_ids(app: &AppHandle) -> Result<Vec<String>, String> {
+pub fn list_all_session_ids(app: &impl PathProvider) -> Result<Vec<String>, String> {
     let data_dir = get_data_dir(app)?;
     let mut session_ids = Vec::new();
 
@@ -356,6 +364,86 @@ pub fn list_all_session_ids(app: &AppHandle) -> Result<Vec<String>, String> {
     Ok(session_ids)
 }
 
+// ============================================================================
+// Duplicate Detection
+// ============================================================================
+
+/// Content hash of a conversation: each user message and when it was sent
+///
+/// A session and any import of it (transcript, sync) hash the same, so
+/// duplicates can be found from metadata alone. None if there are no messages.
+pub fn content_hash(runs: &[RunEntry]) -> Option<String> {
+    let mut hasher = Sha256::new();
+    let mut any = false;
+    // Undone sends never show up in the conversation
+    for run in runs
+        .iter()
+        .filter(|r| !(r.status == RunStatus::Cancelled && r.assistant_message_id.is_none()))
+    {
+        hasher.update((run.user_message.len() as u64).to_le_bytes());
+        hasher.update(run.user_message.as_bytes());
+        hasher.update(run.started_at.to_le_bytes());
+        any = true;
+    }
+    any.then(|| {
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    })
+}
+
+/// Find an existing session with the given content hash
+pub fn find_session_by_hash(
+    app: &impl PathProvider,
+    hash: &str,
+) -> Result<Option<SessionMetadata>, String> {
+    for session_id in list_all_session_ids(app)? {
+        if let Ok(Some(metadata)) = load_metadata(app, &session_id) {
+            if content_hash(&metadata.runs).as_deref() == Some(hash) {
+                return Ok(Some(metadata));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Group sessions with identical content (oldest session first in each group)
+pub fn find_duplicate_groups(app: &impl PathProvider) -> Result<Vec<DuplicateGroup>, String> {
+    let mut by_hash: HashMap<String, Vec<SessionMetadata>> = HashMap::new();
+    for session_id in list_all_session_ids(app)? {
+        if let Ok(Some(metadata)) = load_metadata(app, &session_id) {
+            if let Some(hash) = content_hash(&metadata.runs) {
+                by_hash.entry(hash).or_default().push(metadata);
+            }
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = by_hash
+        .into_iter()
+        .filter(|(_, sessions)| sessions.len() > 1)
+        .map(|(content_hash, mut sessions)| {
+            sessions.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+            DuplicateGroup {
+                content_hash,
+                run_count: sessions[0].runs.len() as u32,
+                sessions: sessions
+                    .into_iter()
+                    .map(|m| DuplicateSession {
+                        session_id: m.id,
+                        worktree_id: m.worktree_id,
+                        name: m.name,
+                        created_at: m.created_at,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| a.content_hash.cmp(&b.content_hash));
+    Ok(groups)
+}
+
 // ============================================================================
 // High-Level Session API (Backward Compatibility)
 // ============================================================================
@@ -363,7 +451,7 @@ pub fn list_all_session_ids(app: &AppHandle) -> Result<Vec<String>, String> {
 /// Load all sessions for a worktree as WorktreeSessions (backward compatible API).
 /// This is the main function used by commands.rs for session management.
 pub fn load_sessions(
-    app: &AppHandle,
+    app: &impl PathProvider,
     _worktree_path: &str,
     worktree_id: &str,
 ) -> Result<WorktreeSessions, String> {
@@ -406,6 +494,9 @@ pub fn load_sessions(
                 last_run_status: None,
                 last_run_execution_mode: None,
                 label: None,
+                attribution: None,
+                subproject: None,
+                rerun_of: None,
             }
         };
         sessions.push(session);
@@ -424,7 +515,7 @@ pub fn load_sessions(
 /// Atomically modify sessions (backward compatible with old with_sessions_mut).
 /// Updates both index and metadata files.
 pub fn with_sessions_mut<F, T>(
-    app: &AppHandle,
+    app: &impl PathProvider,
     _worktree_path: &str,
     worktree_id: &str,
     f: F,
@@ -498,18 +589,24 @@ where
 }
 
 /// Get the index file path (for backward compatibility with old get_sessions_path)
-pub fn get_sessions_path(app: &AppHandle, worktree_id: &str) -> Result<PathBuf, String> {
+pub fn get_sessions_path(app: &impl PathProvider, worktree_id: &str) -> Result<PathBuf, String> {
     get_index_path(app, worktree_id)
 }
 
 /// Load sessions by worktree_id only (for cleanup when worktree path may not exist)
-pub fn load_sessions_by_id(app: &AppHandle, worktree_id: &str) -> Result<WorktreeSessions, String> {
+pub fn load_sessions_by_id(
+    app: &impl PathProvider,
+    worktree_id: &str,
+) -> Result<WorktreeSessions, String> {
     load_sessions(app, "", worktree_id)
 }
 
 /// Get the path for a closed base session's preserved index file
 /// (Backward compatible with old get_closed_base_sessions_path)
-pub fn get_closed_base_sessions_path(app: &AppHandle, project_id: &str) -> Result<PathBuf, String> {
+pub fn get_closed_base_sessions_path(
+    app: &impl PathProvider,
+    project_id: &str,
+) -> Result<PathBuf, String> {
     get_base_index_path(app, project_id)
 }
 
@@ -520,7 +617,7 @@ pub fn get_closed_base_sessions_path(app: &AppHandle, project_id: &str) -> Resul
 /// Preserve sessions when closing a base session
 /// Moves index file to base-{project_id}.json
 pub fn preserve_base_sessions(
-    app: &AppHandle,
+    app: &impl PathProvider,
     worktree_id: &str,
     project_id: &str,
 ) -> Result<(), String> {
@@ -544,7 +641,7 @@ pub fn preserve_base_sessions(
 /// Restore preserved sessions when reopening a base session
 /// Loads from base-{project_id}.json and updates worktree_id
 pub fn restore_base_sessions(
-    app: &AppHandle,
+    app: &impl PathProvider,
     project_id: &str,
     new_worktree_id: &str,
 ) -> Result<Option<WorktreeIndex>, String> {
@@ -591,11 +688,8 @@ pub fn restore_base_sessions(
 
 /// Get the images directory path in app data directory (creates if not exists)
 /// Used for storing pasted images: ~/Library/Application Support/<app>/pasted-images/
-pub fn get_images_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+pub fn get_images_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    let app_data_dir = app.app_data_dir()?;
 
     let path = app_data_dir.join("pasted-images");
 
@@ -606,11 +700,8 @@ pub fn get_images_dir(app: &AppHandle) -> Result<PathBuf, String> {
 
 /// Get the pastes directory path in app data directory (creates if not exists)
 /// Used for storing pasted text files: ~/Library/Application Support/<app>/pasted-texts/
-pub fn get_pastes_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+pub fn get_pastes_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    let app_data_dir = app.app_data_dir()?;
 
     let path = app_data_dir.join("pasted-texts");
 
@@ -621,11 +712,8 @@ pub fn get_pastes_dir(app: &AppHandle) -> Result<PathBuf, String> {
 
 /// Get the saved contexts directory path in app data directory (creates if not exists)
 /// Used for storing conversation context summaries: ~/Library/Application Support/<app>/session-context/
-pub fn get_saved_contexts_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+pub fn get_saved_contexts_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    let app_data_dir = app.app_data_dir()?;
 
     let path = app_data_dir.join("session-context");
 
@@ -636,13 +724,13 @@ pub fn get_saved_contexts_dir(app: &AppHandle) -> Result<PathBuf, String> {
 }
 
 /// Get the saved contexts metadata file path
-pub fn get_saved_contexts_metadata_path(app: &AppHandle) -> Result<PathBuf, String> {
+pub fn get_saved_contexts_metadata_path(app: &impl PathProvider) -> Result<PathBuf, String> {
     let contexts_dir = get_saved_contexts_dir(app)?;
     Ok(contexts_dir.join("session-context-metadata.json"))
 }
 
 /// Load saved contexts metadata (returns empty if file doesn't exist or is corrupt)
-pub fn load_saved_contexts_metadata(app: &AppHandle) -> SavedContextsMetadata {
+pub fn load_saved_contexts_metadata(app: &impl PathProvider) -> SavedContextsMetadata {
     let path = match get_saved_contexts_metadata_path(app) {
         Ok(p) => p,
         Err(_) => return SavedContextsMetadata::default(),
@@ -660,7 +748,7 @@ pub fn load_saved_contexts_metadata(app: &AppHandle) -> SavedContextsMetadata {
 
 /// Save saved contexts metadata (atomic write: temp file + rename, with locking)
 pub fn save_saved_contexts_metadata(
-    app: &AppHandle,
+    app: &impl PathProvider,
     metadata: &SavedContextsMetadata,
 ) -> Result<(), String> {
     let _lock = SAVED_CONTEXTS_LOCK.lock().unwrap();
@@ -772,4 +860,107 @@ mod tests {
         assert!(metadata.runs.is_empty());
         assert_eq!(metadata.version, 1);
     }
+
+    #[test]
+    fn test_index_and_metadata_round_trip() {
+        let paths = crate::test_support::runtime::TempPaths::new();
+
+        let index = load_index(&paths, "wt-1").unwrap();
+        assert!(get_index_path(&paths, "wt-1").unwrap().exists());
+        let session_id = index.sessions[0].id.clone();
+
+        with_index_mut(&paths, "wt-1", |index| {
+            index.find_session_mut(&session_id).unwrap().name = "Renamed".to_string();
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            load_index(&paths, "wt-1").unwrap().sessions[0].name,
+            "Renamed"
+        );
+
+        let metadata = SessionMetadata::new(
+            session_id.clone(),
+            "wt-1".to_string(),
+            "Renamed".to_string(),
+            0,
+        );
+        save_metadata(&paths, &metadata).unwrap();
+        let loaded = load_metadata(&paths, &session_id).unwrap().unwrap();
+        assert_eq!(loaded.worktree_id, "wt-1");
+        assert_eq!(
+            list_all_session_ids(&paths).unwrap(),
+            vec![session_id.clone()]
+        );
+
+        delete_session_data(&paths, &session_id).unwrap();
+        assert!(load_metadata(&paths, &session_id).unwrap().is_none());
+    }
+
+    fn run(user_message: &str, started_at: u64, assistant: bool) -> RunEntry {
+        RunEntry {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            user_message_id: uuid::Uuid::new_v4().to_string(),
+            user_message: user_message.to_string(),
+            model: None,
+            execution_mode: None,
+            thinking_level: None,
+            effort_level: None,
+            started_at,
+            ended_at: None,
+            status: if assistant {
+                RunStatus::Completed
+            } else {
+                RunStatus::Cancelled
+            },
+            assistant_message_id: assistant.then(|| "a".to_string()),
+            cancelled: !assistant,
+            recovered: false,
+            claude_session_id: None,
+            pid: None,
+            usage: None,
+            attribution: None,
+            subproject: None,
+        }
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let runs = vec![run("hi", 1, true), run("more", 2, true)];
+        let hash = content_hash(&runs).unwrap();
+
+        // Undone sends don't change the conversation
+        let mut with_undo = runs.clone();
+        with_undo.push(run("oops", 3, false));
+        assert_eq!(content_hash(&with_undo).unwrap(), hash);
+
+        // Message boundaries matter
+        let merged = vec![run("himore", 1, true)];
+        assert_ne!(content_hash(&merged).unwrap(), hash);
+        assert!(content_hash(&[]).is_none());
+    }
+
+    #[test]
+    fn test_find_duplicate_groups() {
+        let paths = crate::test_support::runtime::TempPaths::new();
+        for (id, created_at, message) in [("a", 2, "same"), ("b", 1, "same"), ("c", 3, "other")] {
+            let mut metadata =
+                SessionMetadata::new(id.to_string(), "wt".to_string(), id.to_string(), 0);
+            metadata.created_at = created_at;
+            metadata.runs = vec![run(message, 10, true)];
+            save_metadata(&paths, &metadata).unwrap();
+        }
+
+        let groups = find_duplicate_groups(&paths).unwrap();
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<&str> = groups[0]
+            .sessions
+            .iter()
+            .map(|s| s.session_id.as_str())
+            .collect();
+        assert_eq!(ids, ["b", "a"]);
+
+        let found = find_session_by_hash(&paths, &groups[0].content_hash).unwrap();
+        assert!(found.is_some_and(|m| m.id == "a" || m.id == "b"));
+    }
 }
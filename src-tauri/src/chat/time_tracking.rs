@@ -0,0 +1,338 @@
+//! Wall-clock vs agent-active time
+//!
+//! A session's wall-clock time (first run start to last run end) includes
+//! stretches where the agent wasn't working. Only runs in progress count as
+//! active; a run interrupted by system sleep counts up to its last recorded
+//! tool event. Gaps between runs are waiting time when the earlier run
+//! stopped for an answer, plan approval or permission grant, and idle time
+//! otherwise (the user's turn, sleep, Jean closed).
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::run_log::read_run_log;
+use super::storage::load_metadata;
+use super::timeline::{load_timing_marks, TimingMark};
+use super::types::{RunEntry, RunStatus};
+use crate::runtime::{now, PathProvider};
+
+/// What a run stopped to wait for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WaitReason {
+    /// AskUserQuestion
+    Question,
+    /// ExitPlanMode
+    Plan,
+    /// Tools denied until approved
+    Permission,
+}
+
+/// Time of one run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunTime {
+    pub run_id: String,
+    /// Unix timestamp when run started
+    pub started_at: u64,
+    /// Unix timestamp when run ended (None if still running)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<u64>,
+    /// Seconds the agent was generating or running tools
+    pub active_secs: u64,
+    /// Part of `active_secs` spent in tool calls (from recorded timings)
+    pub tool_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_for: Option<WaitReason>,
+}
+
+/// Where a session's wall-clock time went
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionTime {
+    pub session_id: String,
+    /// First run start to last run end (or now, while running)
+    pub wall_secs: u64,
+    pub active_secs: u64,
+    pub tool_secs: u64,
+    /// Between runs, while the agent waited on the user
+    pub waiting_secs: u64,
+    /// The rest of the wall-clock time
+    pub idle_secs: u64,
+    pub runs: Vec<RunTime>,
+}
+
+/// Seconds a run was active: up to its end (or now), but for a run
+/// interrupted by sleep only up to its last recorded tool event
+fn active_secs(run: &RunEntry, marks: &[TimingMark], now: u64) -> u64 {
+    let end = match run.status {
+        RunStatus::Running | RunStatus::Resumable => now,
+        RunStatus::Interrupted => marks
+            .iter()
+            .map(|m| m.ts / 1000)
+            .max()
+            .map_or(run.started_at, |last| {
+                last.min(run.ended_at.unwrap_or(last))
+            }),
+        _ => run.ended_at.unwrap_or(run.started_at),
+    };
+    end.saturating_sub(run.started_at)
+}
+
+/// Active seconds of a run, reading its timings only when needed
+pub fn run_active_secs(app: &impl PathProvider, session_id: &str, run: &RunEntry) -> u64 {
+    let marks = if run.status == RunStatus::Interrupted {
+        load_timing_marks(app, session_id, &run.run_id).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    active_secs(run, &marks, now())
+}
+
+/// Seconds covered by tool calls, counting parallel calls once
+fn tool_secs(marks: &[TimingMark]) -> u64 {
+    let mut starts: HashMap<&str, u64> = HashMap::new();
+    let mut spans = Vec::new();
+    for mark in marks {
+        match mark.phase.as_str() {
+            "start" => {
+                starts.entry(mark.id.as_str()).or_insert(mark.ts);
+            }
+            _ => {
+                if let Some(start) = starts.get(mark.id.as_str()) {
+                    spans.push((*start, mark.ts.max(*start)));
+                }
+            }
+        }
+    }
+    spans.sort_unstable();
+
+    let mut total_ms = 0;
+    let mut current: Option<(u64, u64)> = None;
+    for (start, end) in spans {
+        current = match current {
+            Some((s, e)) if start <= e => Some((s, e.max(end))),
+            Some((s, e)) => {
+                total_ms += e - s;
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((s, e)) = current {
+        total_ms += e - s;
+    }
+    total_ms / 1000
+}
+
+/// What the run stopped to wait for: an unanswered question or plan, or
+/// denied permissions
+fn waiting_for(lines: &[String]) -> Option<WaitReason> {
+    let mut pending: HashMap<String, WaitReason> = HashMap::new();
+    let mut answered: HashSet<String> = HashSet::new();
+    let mut denied = false;
+    for line in lines {
+        let Ok(msg) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if msg
+            .get("permission_denials")
+            .and_then(|v| v.as_array())
+            .is_some_and(|d| !d.is_empty())
+        {
+            denied = true;
+        }
+        let blocks = msg
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten();
+        for block in blocks {
+            let field = |name: &str| block.get(name).and_then(|v| v.as_str());
+            match field("type") {
+                Some("tool_use") => {
+                    let reason = match field("name") {
+                        Some("AskUserQuestion") => WaitReason::Question,
+                        Some("ExitPlanMode") => WaitReason::Plan,
+                        _ => continue,
+                    };
+                    if let Some(id) = field("id") {
+                        pending.insert(id.to_string(), reason);
+                    }
+                }
+                Some("tool_result") => {
+                    if let Some(id) = field("tool_use_id") {
+                        answered.insert(id.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    pending
+        .into_iter()
+        .filter(|(id, _)| !answered.contains(id))
+        .map(|(_, reason)| reason)
+        .min_by_key(|r| *r as u8)
+        .or(denied.then_some(WaitReason::Permission))
+}
+
+/// Split a session's wall-clock time given its runs
+fn summarize(session_id: &str, mut runs: Vec<RunTime>, now: u64) -> SessionTime {
+    runs.sort_by_key(|r| r.started_at);
+    let (Some(first), Some(last_end)) = (
+        runs.first().map(|r| r.started_at),
+        runs.iter().map(|r| r.ended_at.unwrap_or(now)).max(),
+    ) else {
+        return SessionTime {
+            session_id: session_id.to_string(),
+            ..Default::default()
+        };
+    };
+
+    let active_secs: u64 = runs.iter().map(|r| r.active_secs).sum();
+    let waiting_secs = runs
+        .windows(2)
+        .filter(|pair| pair[0].waiting_for.is_some())
+        .map(|pair| {
+            let end = pair[0].ended_at.unwrap_or(pair[0].started_at);
+            pair[1].started_at.saturating_sub(end)
+        })
+        .sum();
+    let wall_secs = last_end.saturating_sub(first);
+    SessionTime {
+        session_id: session_id.to_string(),
+        wall_secs,
+        active_secs,
+        tool_secs: runs.iter().map(|r| r.tool_secs).sum(),
+        waiting_secs,
+        idle_secs: wall_secs.saturating_sub(active_secs + waiting_secs),
+        runs,
+    }
+}
+
+/// Wall-clock, active, waiting and idle time of a session
+pub fn load_session_time(app: &impl PathProvider, session_id: &str) -> Result<SessionTime, String> {
+    let Some(metadata) = load_metadata(app, session_id)? else {
+        return Ok(summarize(session_id, Vec::new(), 0));
+    };
+
+    let now = now();
+    let mut runs = Vec::with_capacity(metadata.runs.len());
+    for run in &metadata.runs {
+        let lines = read_run_log(app, session_id, &run.run_id)?;
+        let marks = load_timing_marks(app, session_id, &run.run_id)?;
+        let active = active_secs(run, &marks, now);
+        runs.push(RunTime {
+            run_id: run.run_id.clone(),
+            started_at: run.started_at,
+            ended_at: run.ended_at,
+            active_secs: active,
+            tool_secs: tool_secs(&marks).min(active),
+            waiting_for: waiting_for(&lines),
+        });
+    }
+    Ok(summarize(session_id, runs, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mark(id: &str, phase: &str, ts: u64) -> TimingMark {
+        TimingMark {
+            id: id.to_string(),
+            phase: phase.to_string(),
+            ts,
+        }
+    }
+
+    fn run(status: RunStatus, started_at: u64, ended_at: Option<u64>) -> RunEntry {
+        serde_json::from_value(serde_json::json!({
+            "run_id": "r1",
+            "user_message_id": "u1",
+            "user_message": "hi",
+            "started_at": started_at,
+            "ended_at": ended_at,
+            "status": status,
+        }))
+        .unwrap()
+    }
+
+    fn run_time(started_at: u64, ended_at: u64, waiting_for: Option<WaitReason>) -> RunTime {
+        RunTime {
+            run_id: format!("r{started_at}"),
+            started_at,
+            ended_at: Some(ended_at),
+            active_secs: ended_at - started_at,
+            tool_secs: 0,
+            waiting_for,
+        }
+    }
+
+    #[test]
+    fn test_active_secs() {
+        let completed = run(RunStatus::Completed, 100, Some(160));
+        assert_eq!(active_secs(&completed, &[], 1_000), 60);
+        let running = run(RunStatus::Running, 100, None);
+        assert_eq!(active_secs(&running, &[], 130), 30);
+
+        // Slept from ~120 until the interruption was noticed at 5000
+        let interrupted = run(RunStatus::Interrupted, 100, Some(5_000));
+        let marks = [mark("t1", "start", 110_000), mark("t1", "end", 120_500)];
+        assert_eq!(active_secs(&interrupted, &marks, 6_000), 20);
+        assert_eq!(active_secs(&interrupted, &[], 6_000), 0);
+    }
+
+    #[test]
+    fn test_tool_secs_merges_parallel_calls() {
+        let marks = [
+            mark("t1", "start", 1_000),
+            mark("t2", "start", 2_000),
+            mark("t1", "end", 5_000),
+            mark("t2", "end", 6_000),
+            mark("t3", "start", 10_000),
+            mark("t3", "end", 12_000),
+            // Never finished
+            mark("t4", "start", 20_000),
+        ];
+        assert_eq!(tool_secs(&marks), 7);
+    }
+
+    #[test]
+    fn test_waiting_for() {
+        let lines = |l: &[&str]| l.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let question = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"q1","name":"AskUserQuestion","input":{}}]}}"#;
+        assert_eq!(waiting_for(&lines(&[question])), Some(WaitReason::Question));
+        let answered = r#"{"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"q1","content":"ok"}]}}"#;
+        assert_eq!(waiting_for(&lines(&[question, answered])), None);
+        let denied = r#"{"type":"result","permission_denials":[{"tool_name":"Bash"}]}"#;
+        assert_eq!(
+            waiting_for(&lines(&[question, answered, denied])),
+            Some(WaitReason::Permission)
+        );
+        assert_eq!(
+            waiting_for(&lines(&[r#"{"type":"result","permission_denials":[]}"#])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        let runs = vec![
+            // Asked a question, answered 10 minutes later
+            run_time(0, 100, Some(WaitReason::Question)),
+            run_time(700, 1_000, None),
+            // Next message an hour later
+            run_time(4_600, 4_800, None),
+        ];
+        let time = summarize("s1", runs, 10_000);
+        assert_eq!(time.wall_secs, 4_800);
+        assert_eq!(time.active_secs, 600);
+        assert_eq!(time.waiting_secs, 600);
+        assert_eq!(time.idle_secs, 3_600);
+
+        let empty = summarize("s1", Vec::new(), 10_000);
+        assert_eq!((empty.wall_secs, empty.active_secs), (0, 0));
+    }
+}
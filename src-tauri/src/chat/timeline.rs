@@ -0,0 +1,435 @@
+//! Tool-call timeline for session transcripts
+//!
+//! The CLI stream-json output doesn't carry per-event timestamps, so while a run
+//! is being tailed we record when each tool call and tool result was observed in
+//! a sidecar file (`{run_id}.timing.jsonl`) next to the run log. The timeline is
+//! then rebuilt on demand by parsing the run log and overlaying those timings.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::run_log::{get_run_log_path, read_run_log};
+use super::storage::load_metadata;
+use super::types::{RunTimeline, SessionTimeline, TimelineEntry, TimelineKind};
+use crate::runtime::PathProvider;
+
+/// Maximum number of characters kept from a tool's output
+const OUTPUT_PREVIEW_CHARS: usize = 500;
+
+/// Maximum number of characters kept for an entry summary
+const SUMMARY_CHARS: usize = 200;
+
+/// A single timing mark in the sidecar file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct TimingMark {
+    /// Tool use ID
+    pub id: String,
+    /// "start" (tool_use observed) or "end" (tool_result observed)
+    pub phase: String,
+    /// Unix epoch milliseconds
+    pub ts: u64,
+}
+
+/// Get the timing sidecar path for a run log file
+/// Path: sessions/data/{session_id}/{run_id}.timing.jsonl
+fn timing_path_for(output_file: &Path) -> PathBuf {
+    output_file.with_extension("timing.jsonl")
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+/// Records tool call timings while a run's output is tailed.
+///
+/// Marks that already exist in the sidecar are never overwritten, so re-tailing
+/// a run from the start (e.g. after recovery) keeps the original timings.
+pub struct TimingRecorder {
+    file: Option<File>,
+    recorded: HashSet<(String, String)>,
+}
+
+impl TimingRecorder {
+    /// Open (or create) the timing sidecar for a run log file.
+    /// Failures are logged and recording is disabled rather than failing the run.
+    pub fn new(output_file: &Path) -> Self {
+        let path = timing_path_for(output_file);
+
+        let recorded = read_timing_marks(&path)
+            .into_iter()
+            .map(|m| (m.id, m.phase))
+            .collect();
+
+        let file = match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(f) => Some(f),
+            Err(e) => {
+                log::warn!("Failed to open timing file {path:?}: {e}");
+                None
+            }
+        };
+
+        Self { file, recorded }
+    }
+
+    /// Inspect a parsed stream-json event and record any tool starts/ends it contains
+    pub fn observe(&mut self, msg: &serde_json::Value) {
+        let phase = match msg.get("type").and_then(|v| v.as_str()) {
+            Some("assistant") => "start",
+            Some("user") => "end",
+            _ => return,
+        };
+        let block_type = if phase == "start" {
+            "tool_use"
+        } else {
+            "tool_result"
+        };
+        let id_field = if phase == "start" {
+            "id"
+        } else {
+            "tool_use_id"
+        };
+
+        let Some(blocks) = msg
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_array())
+        else {
+            return;
+        };
+
+        let ts = now_ms();
+        for block in blocks {
+            if block.get("type").and_then(|v| v.as_str()) != Some(block_type) {
+                continue;
+            }
+            if let Some(id) = block.get(id_field).and_then(|v| v.as_str()) {
+                self.mark(id, phase, ts);
+            }
+        }
+    }
+
+    fn mark(&mut self, id: &str, phase: &str, ts: u64) {
+        if !self.recorded.insert((id.to_string(), phase.to_string())) {
+            return;
+        }
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+
+        let mark = TimingMark {
+            id: id.to_string(),
+            phase: phase.to_string(),
+            ts,
+        };
+        if let Ok(line) = serde_json::to_string(&mark) {
+            if let Err(e) = writeln!(file, "{line}") {
+                log::warn!("Failed to write timing mark: {e}");
+            }
+        }
+    }
+}
+
+/// Load the recorded timing marks for a run (empty if none were recorded)
+pub(super) fn load_timing_marks(
+    app: &impl PathProvider,
+    session_id: &str,
+    run_id: &str,
+) -> Result<Vec<TimingMark>, String> {
+    let run_log_path = get_run_log_path(app, session_id, run_id)?;
+    Ok(read_timing_marks(&timing_path_for(&run_log_path)))
+}
+
+fn read_timing_marks(path: &Path) -> Vec<TimingMark> {
+    let Ok(file) = File::open(path) else {
+        return vec![];
+    };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+// ============================================================================
+// Timeline Building
+// ============================================================================
+
+/// Map a tool name to its timeline category
+pub fn classify_tool(name: &str) -> TimelineKind {
+    match name {
+        "Bash" | "BashOutput" | "KillShell" | "KillBash" => TimelineKind::Command,
+        "Edit" | "MultiEdit" | "Write" | "NotebookEdit" => TimelineKind::FileEdit,
+        "Read" | "NotebookRead" => TimelineKind::FileRead,
+        "Glob" | "Grep" | "LS" => TimelineKind::Search,
+        "WebSearch" | "WebFetch" => TimelineKind::Web,
+        "Task" => TimelineKind::Agent,
+        n if n.starts_with("mcp__") => TimelineKind::Mcp,
+        _ => TimelineKind::Other,
+    }
+}
+
+/// Truncate a string to at most `max` characters, appending an ellipsis if cut
+fn truncate_chars(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let mut out: String = s.chars().take(max).collect();
+        out.push('…');
+        out
+    }
+}
+
+/// Pick the most descriptive input field for a tool as a one-line summary
+fn summarize_input(name: &str, input: &serde_json::Value) -> Option<String> {
+    let keys: &[&str] = match classify_tool(name) {
+        TimelineKind::Command => &["command", "bash_id", "shell_id"],
+        TimelineKind::FileEdit | TimelineKind::FileRead => &["file_path", "notebook_path"],
+        TimelineKind::Search => &["pattern", "path"],
+        TimelineKind::Web => &["query", "url"],
+        TimelineKind::Agent => &["description"],
+        TimelineKind::Mcp | TimelineKind::Other => &[],
+    };
+
+    keys.iter()
+        .find_map(|k| input.get(*k).and_then(|v| v.as_str()))
+        .map(|s| truncate_chars(s.lines().next().unwrap_or(""), SUMMARY_CHARS))
+}
+
+/// Extract text from a tool_result content field (string or array of text blocks)
+fn tool_result_text(content: Option<&serde_json::Value>) -> String {
+    match content {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter(|item| item.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Build timeline entries from raw run log lines and timing marks
+fn build_entries(lines: &[String], marks: &[TimingMark]) -> Vec<TimelineEntry> {
+    let mut starts: HashMap<&str, u64> = HashMap::new();
+    let mut ends: HashMap<&str, u64> = HashMap::new();
+    for mark in marks {
+        let target = if mark.phase == "start" {
+            &mut starts
+        } else {
+            &mut ends
+        };
+        target.entry(mark.id.as_str()).or_insert(mark.ts);
+    }
+
+    let mut entries: Vec<TimelineEntry> = Vec::new();
+
+    for line in lines {
+        let msg: serde_json::Value = match serde_json::from_str(line) {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+
+        let parent_tool_use_id = msg
+            .get("parent_tool_use_id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        let Some(blocks) = msg
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_array())
+        else {
+            continue;
+        };
+
+        match msg.get("type").and_then(|v| v.as_str()) {
+            Some("assistant") => {
+                for block in blocks {
+                    if block.get("type").and_then(|v| v.as_str()) != Some("tool_use") {
+                        continue;
+                    }
+                    let id = block.get("id").and_then(|v| v.as_str()).unwrap_or("");
+                    let name = block.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                    let input = block.get("input").unwrap_or(&serde_json::Value::Null);
+
+                    entries.push(TimelineEntry {
+                        tool_use_id: id.to_string(),
+                        name: name.to_string(),
+                        kind: classify_tool(name),
+                        summary: summarize_input(name, input),
+                        parent_tool_use_id: parent_tool_use_id.clone(),
+                        started_at_ms: starts.get(id).copied(),
+                        ended_at_ms: None,
+                        duration_ms: None,
+                        completed: false,
+                        is_error: false,
+                        output_preview: None,
+                    });
+                }
+            }
+            Some("user") => {
+                for block in blocks {
+                    if block.get("type").and_then(|v| v.as_str()) != Some("tool_result") {
+                        continue;
+                    }
+                    let id = block
+                        .get("tool_use_id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("");
+                    let Some(entry) = entries.iter_mut().find(|e| e.tool_use_id == id) else {
+                        continue;
+                    };
+
+                    let output = tool_result_text(block.get("content"));
+                    entry.completed = true;
+                    entry.is_error = block
+                        .get("is_error")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+                    entry.ended_at_ms = ends.get(id).copied();
+                    entry.duration_ms = match (entry.started_at_ms, entry.ended_at_ms) {
+                        (Some(start), Some(end)) => Some(end.saturating_sub(start)),
+                        _ => None,
+                    };
+                    if !output.is_empty() {
+                        entry.output_preview = Some(truncate_chars(&output, OUTPUT_PREVIEW_CHARS));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    entries
+}
+
+/// Build the tool-call timeline for every run in a session
+pub fn load_session_timeline(
+    app: &tauri::AppHandle,
+    session_id: &str,
+) -> Result<SessionTimeline, String> {
+    let metadata = match load_metadata(app, session_id)? {
+        Some(m) => m,
+        None => {
+            return Ok(SessionTimeline {
+                session_id: session_id.to_string(),
+                runs: vec![],
+            })
+        }
+    };
+
+    let mut runs = Vec::with_capacity(metadata.runs.len());
+    for run in &metadata.runs {
+        let lines = read_run_log(app, session_id, &run.run_id)?;
+        let marks = load_timing_marks(app, session_id, &run.run_id)?;
+
+        runs.push(RunTimeline {
+            run_id: run.run_id.clone(),
+            user_message_id: run.user_message_id.clone(),
+            started_at: run.started_at,
+            ended_at: run.ended_at,
+            status: run.status.clone(),
+            entries: build_entries(&lines, &marks),
+        });
+    }
+
+    Ok(SessionTimeline {
+        session_id: session_id.to_string(),
+        runs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mark(id: &str, phase: &str, ts: u64) -> TimingMark {
+        TimingMark {
+            id: id.to_string(),
+            phase: phase.to_string(),
+            ts,
+        }
+    }
+
+    #[test]
+    fn test_classify_tool() {
+        assert_eq!(classify_tool("Bash"), TimelineKind::Command);
+        assert_eq!(classify_tool("MultiEdit"), TimelineKind::FileEdit);
+        assert_eq!(classify_tool("Read"), TimelineKind::FileRead);
+        assert_eq!(classify_tool("Grep"), TimelineKind::Search);
+        assert_eq!(classify_tool("WebSearch"), TimelineKind::Web);
+        assert_eq!(classify_tool("Task"), TimelineKind::Agent);
+        assert_eq!(classify_tool("mcp__github__get_issue"), TimelineKind::Mcp);
+        assert_eq!(classify_tool("TodoWrite"), TimelineKind::Other);
+    }
+
+    #[test]
+    fn test_timing_path_for() {
+        let path = timing_path_for(Path::new("/data/abc/run-1.jsonl"));
+        assert_eq!(path, PathBuf::from("/data/abc/run-1.timing.jsonl"));
+    }
+
+    #[test]
+    fn test_build_entries_with_timings() {
+        let lines = vec![
+            r#"{"_run_meta":true,"run_id":"r1"}"#.to_string(),
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test\nsecond line"}}]}}"#.to_string(),
+            r#"{"type":"assistant","parent_tool_use_id":"t0","message":{"content":[{"type":"tool_use","id":"t2","name":"Edit","input":{"file_path":"src/lib.rs"}}]}}"#.to_string(),
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":[{"type":"text","text":"ok"}]}]}}"#.to_string(),
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"t2","content":"boom","is_error":true}]}}"#.to_string(),
+        ];
+        let marks = vec![
+            mark("t1", "start", 1_000),
+            mark("t1", "end", 3_500),
+            mark("t2", "start", 1_100),
+        ];
+
+        let entries = build_entries(&lines, &marks);
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].kind, TimelineKind::Command);
+        assert_eq!(entries[0].summary.as_deref(), Some("cargo test"));
+        assert_eq!(entries[0].duration_ms, Some(2_500));
+        assert_eq!(entries[0].output_preview.as_deref(), Some("ok"));
+        assert!(entries[0].completed);
+        assert!(!entries[0].is_error);
+
+        assert_eq!(entries[1].kind, TimelineKind::FileEdit);
+        assert_eq!(entries[1].parent_tool_use_id.as_deref(), Some("t0"));
+        assert_eq!(entries[1].started_at_ms, Some(1_100));
+        assert_eq!(entries[1].duration_ms, None);
+        assert!(entries[1].is_error);
+    }
+
+    #[test]
+    fn test_build_entries_without_timings() {
+        let lines = vec![
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"t1","name":"Read","input":{"file_path":"a.rs"}}]}}"#.to_string(),
+        ];
+
+        let entries = build_entries(&lines, &[]);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].started_at_ms, None);
+        assert!(!entries[0].completed);
+    }
+
+    #[test]
+    fn test_truncate_chars_multibyte() {
+        assert_eq!(truncate_chars("héllo", 10), "héllo");
+        assert_eq!(truncate_chars("héllo", 2), "hé…");
+    }
+}
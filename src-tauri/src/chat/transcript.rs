@@ -0,0 +1,597 @@
+//! Portable JSONL transcripts
+//!
+//! Sessions are exported as one JSON record per line so they diff cleanly
+//! in git and can be imported on another machine (or read by other tools).
+//! The format is documented in `docs/developer/transcript-format.md`.
+//!
+//! Imports are stored like any other session: each user/assistant pair
+//! becomes a run whose log is rebuilt in Claude CLI stream-json form, so the
+//! regular message loading, timeline and replay code work unchanged.
+
+use std::fs;
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::annotations::{has_message, MessageAnnotation};
+use super::run_log::load_session_messages;
+use super::storage::{
+    content_hash, find_session_by_hash, get_session_dir, load_metadata, with_metadata_mut,
+    with_sessions_mut,
+};
+use super::types::{
+    ChatMessage, ContentBlock, MessageRole, RunEntry, RunStatus, Session, ToolCall, UsageData,
+};
+use crate::runtime::{now, PathProvider};
+
+/// Value of `format` in the header record
+pub const TRANSCRIPT_FORMAT: &str = "jean-transcript";
+
+/// Current transcript version (bump on incompatible changes)
+pub const TRANSCRIPT_VERSION: u32 = 1;
+
+/// One line of a transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptRecord {
+    /// First line: format, version and session details
+    Header(TranscriptHeader),
+    Message(TranscriptMessage),
+    /// A tool call made during the preceding assistant message
+    ToolCall(TranscriptToolCall),
+    /// File change derived from an Edit/Write/MultiEdit tool call
+    /// (informational; ignored on import)
+    Diff(TranscriptDiff),
+    /// Reviewer annotation on a message
+    Annotation(MessageAnnotation),
+    /// Record types added by newer versions are skipped
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptHeader {
+    pub format: String,
+    pub version: u32,
+    pub session_name: String,
+    pub created_at: u64,
+    pub exported_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptMessage {
+    pub id: String,
+    pub role: MessageRole,
+    pub content: String,
+    pub timestamp: u64,
+    /// Text / thinking / tool-use order within an assistant message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub content_blocks: Vec<ContentBlock>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking_level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effort_level: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptToolCall {
+    /// Assistant message the call belongs to
+    pub message_id: String,
+    pub id: String,
+    pub name: String,
+    pub input: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_tool_use_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptDiff {
+    pub message_id: String,
+    pub tool_call_id: String,
+    pub path: String,
+    /// Replaced text (None for whole-file writes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_text: Option<String>,
+    pub new_text: String,
+}
+
+/// File changes made by a tool call, if it edits files
+fn diffs_for(message_id: &str, call: &ToolCall) -> Vec<TranscriptDiff> {
+    let str_field =
+        |v: &serde_json::Value, key: &str| v.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let Some(path) = str_field(&call.input, "file_path") else {
+        return Vec::new();
+    };
+    let diff = |old_text: Option<String>, new_text: String| TranscriptDiff {
+        message_id: message_id.to_string(),
+        tool_call_id: call.id.clone(),
+        path: path.clone(),
+        old_text,
+        new_text,
+    };
+
+    match call.name.as_str() {
+        "Edit" => str_field(&call.input, "new_string")
+            .map(|new| vec![diff(str_field(&call.input, "old_string"), new)])
+            .unwrap_or_default(),
+        "Write" => str_field(&call.input, "content")
+            .map(|content| vec![diff(None, content)])
+            .unwrap_or_default(),
+        "MultiEdit" => call
+            .input
+            .get("edits")
+            .and_then(|e| e.as_array())
+            .map(|edits| {
+                edits
+                    .iter()
+                    .filter_map(|e| {
+                        let new = str_field(e, "new_string")?;
+                        Some(diff(str_field(e, "old_string"), new))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Serialize a session as a JSONL transcript
+pub fn export_transcript(app: &impl PathProvider, session_id: &str) -> Result<String, String> {
+    let metadata = load_metadata(app, session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    let messages = load_session_messages(app, session_id)?;
+
+    let mut records = vec![TranscriptRecord::Header(TranscriptHeader {
+        format: TRANSCRIPT_FORMAT.to_string(),
+        version: TRANSCRIPT_VERSION,
+        session_name: metadata.name.clone(),
+        created_at: metadata.created_at,
+        exported_at: now(),
+        model: metadata.selected_model.clone(),
+    })];
+
+    for message in messages {
+        let tool_calls = message.tool_calls.clone();
+        let message_id = message.id.clone();
+        let annotations = metadata
+            .annotations
+            .iter()
+            .filter(|a| a.message_id == message_id)
+            .cloned()
+            .map(TranscriptRecord::Annotation)
+            .collect::<Vec<_>>();
+        records.push(TranscriptRecord::Message(TranscriptMessage {
+            id: message.id,
+            role: message.role,
+            content: message.content,
+            timestamp: message.timestamp,
+            content_blocks: message.content_blocks,
+            model: message.model,
+            execution_mode: message.execution_mode,
+            thinking_level: message.thinking_level,
+            effort_level: message.effort_level,
+            cancelled: message.cancelled,
+            usage: message.usage,
+        }));
+        for call in tool_calls {
+            let diffs = diffs_for(&message_id, &call);
+            records.push(TranscriptRecord::ToolCall(TranscriptToolCall {
+                message_id: message_id.clone(),
+                id: call.id,
+                name: call.name,
+                input: call.input,
+                output: call.output,
+                parent_tool_use_id: call.parent_tool_use_id,
+            }));
+            records.extend(diffs.into_iter().map(TranscriptRecord::Diff));
+        }
+        records.extend(annotations);
+    }
+
+    let mut out = String::new();
+    for record in &records {
+        let line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to serialize transcript: {e}"))?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Parse a JSONL transcript into its header, messages (with tool calls
+/// attached) and annotations
+pub fn parse_transcript(
+    content: &str,
+) -> Result<(TranscriptHeader, Vec<ChatMessage>, Vec<MessageAnnotation>), String> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty());
+
+    let header = match lines.next().map(|(_, l)| serde_json::from_str(l)) {
+        Some(Ok(TranscriptRecord::Header(header))) => header,
+        _ => return Err("Not a transcript: missing header line".to_string()),
+    };
+    if header.format != TRANSCRIPT_FORMAT {
+        return Err(format!("Unsupported transcript format: {}", header.format));
+    }
+    if header.version > TRANSCRIPT_VERSION {
+        return Err(format!(
+            "Transcript version {} is newer than supported ({TRANSCRIPT_VERSION})",
+            header.version
+        ));
+    }
+
+    let mut messages: Vec<ChatMessage> = Vec::new();
+    let mut annotations = Vec::new();
+    for (index, line) in lines {
+        let record: TranscriptRecord = serde_json::from_str(line)
+            .map_err(|e| format!("Invalid transcript record on line {}: {e}", index + 1))?;
+        match record {
+            TranscriptRecord::Message(m) => messages.push(ChatMessage {
+                id: m.id,
+                role: m.role,
+                content: m.content,
+                timestamp: m.timestamp,
+                content_blocks: m.content_blocks,
+                cancelled: m.cancelled,
+                model: m.model,
+                execution_mode: m.execution_mode,
+                thinking_level: m.thinking_level,
+                effort_level: m.effort_level,
+                usage: m.usage,
+                ..Default::default()
+            }),
+            TranscriptRecord::ToolCall(call) => {
+                let message = messages
+                    .iter_mut()
+                    .rev()
+                    .find(|m| m.id == call.message_id)
+                    .ok_or_else(|| {
+                        format!(
+                            "Tool call on line {} refers to unknown message {}",
+                            index + 1,
+                            call.message_id
+                        )
+                    })?;
+                message.tool_calls.push(ToolCall {
+                    id: call.id,
+                    name: call.name,
+                    input: call.input,
+                    output: call.output,
+                    parent_tool_use_id: call.parent_tool_use_id,
+                });
+            }
+            TranscriptRecord::Annotation(annotation) => annotations.push(annotation),
+            TranscriptRecord::Header(_) => {
+                return Err(format!("Unexpected header on line {}", index + 1));
+            }
+            TranscriptRecord::Diff(_) | TranscriptRecord::Unknown => {}
+        }
+    }
+
+    Ok((header, messages, annotations))
+}
+
+/// Rebuild an assistant message as Claude CLI stream-json lines
+fn stream_json_lines(message: &ChatMessage) -> Vec<serde_json::Value> {
+    let mut lines = Vec::new();
+    let mut emitted: Vec<&str> = Vec::new();
+
+    let tool_lines = |call: &ToolCall, lines: &mut Vec<serde_json::Value>| {
+        lines.push(serde_json::json!({
+            "type": "assistant",
+            "parent_tool_use_id": call.parent_tool_use_id,
+            "message": {
+                "role": "assistant",
+                "content": [{
+                    "type": "tool_use",
+                    "id": call.id,
+                    "name": call.name,
+                    "input": call.input,
+                }],
+            },
+        }));
+        if let Some(output) = &call.output {
+            lines.push(serde_json::json!({
+                "type": "user",
+                "parent_tool_use_id": call.parent_tool_use_id,
+                "message": {
+                    "role": "user",
+                    "content": [{
+                        "type": "tool_result",
+                        "tool_use_id": call.id,
+                        "content": output,
+                    }],
+                },
+            }));
+        }
+    };
+    let text_line = |kind: &str, text: &str| {
+        serde_json::json!({
+            "type": "assistant",
+            "message": { "role": "assistant", "content": [{ "type": kind, kind: text }] },
+        })
+    };
+
+    if message.content_blocks.is_empty() && !message.content.is_empty() {
+        lines.push(text_line("text", &message.content));
+    }
+    for block in &message.content_blocks {
+        match block {
+            ContentBlock::Text { text } => lines.push(text_line("text", text)),
+            ContentBlock::Thinking { thinking } => lines.push(text_line("thinking", thinking)),
+            ContentBlock::ToolUse { tool_call_id } => {
+                if let Some(call) = message.tool_calls.iter().find(|c| &c.id == tool_call_id) {
+                    tool_lines(call, &mut lines);
+                    emitted.push(&call.id);
+                }
+            }
+        }
+    }
+    // Tool calls without a content block (older sessions)
+    for call in &message.tool_calls {
+        if !emitted.contains(&call.id.as_str()) {
+            tool_lines(call, &mut lines);
+        }
+    }
+
+    lines.push(serde_json::json!({
+        "type": "result",
+        "subtype": "success",
+        "is_error": false,
+        "result": message.content,
+    }));
+    lines
+}
+
+/// Outcome of importing a transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptImport {
+    /// The new session, or the existing one if the transcript was a duplicate
+    pub session: Session,
+    /// True if an identical session already existed and nothing was imported
+    pub duplicate: bool,
+}
+
+/// Create a new session in a worktree from a JSONL transcript
+///
+/// Transcripts whose content matches an existing session (see
+/// `storage::content_hash`) are skipped. Imported sessions have no Claude CLI
+/// session to resume; the next message starts a fresh conversation.
+pub fn import_transcript(
+    app: &impl PathProvider,
+    worktree_id: &str,
+    worktree_path: &str,
+    content: &str,
+) -> Result<TranscriptImport, String> {
+    let (header, messages, annotations) = parse_transcript(content)?;
+
+    let mut runs = Vec::new();
+    let mut run_lines = Vec::new();
+    let mut iter = messages.iter().peekable();
+    while let Some(first) = iter.next() {
+        // Pair each user message with the assistant reply that follows it
+        let (user, assistant) = match first.role {
+            MessageRole::User => {
+                let reply = iter.next_if(|m| m.role == MessageRole::Assistant);
+                (Some(first), reply)
+            }
+            MessageRole::Assistant => (None, Some(first)),
+        };
+
+        let started_at = user.or(assistant).map(|m| m.timestamp).unwrap_or_default();
+        let cancelled = assistant.is_none_or(|a| a.cancelled);
+
+        run_lines.push(assistant.map(stream_json_lines).unwrap_or_default());
+        runs.push(RunEntry {
+            run_id: Uuid::new_v4().to_string(),
+            user_message_id: user
+                .map(|u| u.id.clone())
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            user_message: user.map(|u| u.content.clone()).unwrap_or_default(),
+            model: user.and_then(|u| u.model.clone()),
+            execution_mode: user.and_then(|u| u.execution_mode.clone()),
+            thinking_level: user.and_then(|u| u.thinking_level.clone()),
+            effort_level: user.and_then(|u| u.effort_level.clone()),
+            started_at,
+            ended_at: Some(assistant.map(|a| a.timestamp).unwrap_or(started_at)),
+            status: if cancelled {
+                RunStatus::Cancelled
+            } else {
+                RunStatus::Completed
+            },
+            // Always set so a missing reply isn't mistaken for an undone send
+            assistant_message_id: Some(
+                assistant
+                    .map(|a| a.id.clone())
+                    .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            ),
+            cancelled,
+            recovered: false,
+            claude_session_id: None,
+            pid: None,
+            usage: assistant.and_then(|a| a.usage.clone()),
+        });
+    }
+
+    if let Some(hash) = content_hash(&runs) {
+        if let Some(existing) = find_session_by_hash(app, &hash)? {
+            log::trace!(
+                "Transcript duplicates session {}, skipping import",
+                existing.id
+            );
+            return Ok(TranscriptImport {
+                session: existing.to_session(),
+                duplicate: true,
+            });
+        }
+    }
+
+    let session = with_sessions_mut(app, worktree_path, worktree_id, |sessions| {
+        let mut session = Session::new(header.session_name.clone(), sessions.sessions.len() as u32);
+        session.created_at = header.created_at;
+        session.selected_model = header.model.clone();
+        // Imported sessions keep their original name
+        session.session_naming_completed = true;
+        session.message_count = Some(messages.len() as u32);
+
+        sessions.sessions.push(session.clone());
+        sessions.active_session_id = Some(session.id.clone());
+        Ok(session)
+    })?;
+
+    let session_dir = get_session_dir(app, &session.id)?;
+    for (run, lines) in runs.iter().zip(run_lines) {
+        let mut file = fs::File::create(session_dir.join(format!("{}.jsonl", run.run_id)))
+            .map_err(|e| format!("Failed to create run log file: {e}"))?;
+        let meta = serde_json::json!({
+            "_run_meta": true,
+            "run_id": run.run_id,
+            "session_id": session.id,
+            "worktree_id": worktree_id,
+            "user_message_id": run.user_message_id,
+            "model": run.model,
+            "started_at": run.started_at,
+            "imported": true,
+        });
+        writeln!(file, "{meta}").map_err(|e| format!("Failed to write run log: {e}"))?;
+        for line in lines {
+            writeln!(file, "{line}").map_err(|e| format!("Failed to write run log: {e}"))?;
+        }
+    }
+
+    with_metadata_mut(
+        app,
+        &session.id,
+        worktree_id,
+        &session.name,
+        session.order,
+        |metadata| {
+            metadata.created_at = header.created_at;
+            metadata.runs = runs;
+            // Message IDs are kept on import, so annotations still apply
+            metadata.annotations = annotations
+                .into_iter()
+                .filter(|a| has_message(metadata, &a.message_id))
+                .collect();
+            Ok(())
+        },
+    )?;
+
+    log::trace!(
+        "Imported transcript as session {} ({} messages)",
+        session.id,
+        messages.len()
+    );
+    Ok(TranscriptImport {
+        session,
+        duplicate: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    const TRANSCRIPT: &str = r#"{"type":"header","format":"jean-transcript","version":1,"session_name":"Fix login","created_at":1700000000,"exported_at":1700000500,"model":"opus"}
+{"type":"message","id":"u1","role":"user","content":"Fix the typo","timestamp":1700000010,"model":"opus","execution_mode":"build"}
+{"type":"message","id":"a1","role":"assistant","content":"Looking.Fixed.","timestamp":1700000020,"content_blocks":[{"type":"text","text":"Looking."},{"type":"tool_use","tool_call_id":"t1"},{"type":"text","text":"Fixed."}],"usage":{"input_tokens":10,"output_tokens":5,"cache_read_input_tokens":0,"cache_creation_input_tokens":0,"cost_usd":0.01}}
+{"type":"tool_call","message_id":"a1","id":"t1","name":"Edit","input":{"file_path":"src/login.rs","old_string":"pasword","new_string":"password"},"output":"ok"}
+{"type":"diff","message_id":"a1","tool_call_id":"t1","path":"src/login.rs","old_text":"pasword","new_text":"password"}
+{"type":"annotation","id":"n1","message_id":"a1","rating":"up","labels":["great fix"],"created_at":1700000100,"updated_at":1700000100}
+{"type":"bookmark","text":"from a newer version"}
+{"type":"message","id":"u2","role":"user","content":"Thanks","timestamp":1700000030}
+"#;
+
+    #[test]
+    fn test_parse_transcript() {
+        let (header, messages, annotations) = parse_transcript(TRANSCRIPT).unwrap();
+        assert_eq!(header.session_name, "Fix login");
+        assert_eq!(messages.len(), 3);
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].labels, ["great fix"]);
+        assert_eq!(messages[1].tool_calls.len(), 1);
+        assert_eq!(messages[1].tool_calls[0].output.as_deref(), Some("ok"));
+
+        assert!(parse_transcript(r#"{"type":"message"}"#).is_err());
+        let newer = TRANSCRIPT.replacen(r#""version":1"#, r#""version":99"#, 1);
+        assert!(parse_transcript(&newer).is_err());
+    }
+
+    #[test]
+    fn test_import_then_export_round_trips() {
+        let paths = TempPaths::new();
+        let import = import_transcript(&paths, "wt-1", "/tmp/wt-1", TRANSCRIPT).unwrap();
+        assert!(!import.duplicate);
+        let session = import.session;
+        assert_eq!(session.name, "Fix login");
+
+        let messages = load_session_messages(&paths, &session.id).unwrap();
+        // The trailing user message gets an empty, cancelled reply
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[1].content, "Looking.Fixed.");
+        assert_eq!(messages[1].content_blocks.len(), 3);
+        assert!(messages[3].cancelled);
+
+        let exported = export_transcript(&paths, &session.id).unwrap();
+        let (header, reimported, annotations) = parse_transcript(&exported).unwrap();
+        assert_eq!(header.session_name, "Fix login");
+        assert_eq!(reimported[0].execution_mode.as_deref(), Some("build"));
+        assert_eq!(reimported[1].tool_calls[0].name, "Edit");
+        assert_eq!(
+            reimported[1].usage.as_ref().and_then(|u| u.cost_usd),
+            Some(0.01)
+        );
+        assert!(exported.contains(r#""type":"diff""#));
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].message_id, "a1");
+
+        // Re-importing the export is recognized as a duplicate
+        let again = import_transcript(&paths, "wt-2", "/tmp/wt-2", &exported).unwrap();
+        assert!(again.duplicate);
+        assert_eq!(again.session.id, session.id);
+    }
+
+    #[test]
+    fn test_diffs_for_multi_edit() {
+        let call = ToolCall {
+            id: "t1".to_string(),
+            name: "MultiEdit".to_string(),
+            input: serde_json::json!({
+                "file_path": "a.rs",
+                "edits": [
+                    {"old_string": "a", "new_string": "b"},
+                    {"old_string": "c", "new_string": "d"}
+                ]
+            }),
+            output: None,
+            parent_tool_use_id: None,
+        };
+        let diffs = diffs_for("m1", &call);
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[1].old_text.as_deref(), Some("c"));
+        assert!(diffs_for(
+            "m1",
+            &ToolCall {
+                name: "Read".to_string(),
+                ..call
+            }
+        )
+        .is_empty());
+    }
+}
@@ -20,6 +20,107 @@ pub struct SessionDigest {
     pub message_count: Option<usize>,
 }
 
+// ============================================================================
+// Scratch Directory Types
+// ============================================================================
+
+/// Disk usage of a session's managed scratch directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScratchUsage {
+    pub session_id: String,
+    /// Absolute path of the scratch directory
+    pub path: String,
+    /// Whether the directory currently exists on disk
+    pub exists: bool,
+    /// Total size of all files in bytes
+    pub size_bytes: u64,
+    /// Number of files (directories not counted)
+    pub file_count: u64,
+    /// Most recent modification time of any entry (unix epoch seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<u64>,
+}
+
+// ============================================================================
+// Timeline Types
+// ============================================================================
+
+/// Category of a tool call for timeline rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineKind {
+    /// Shell commands (Bash, BashOutput, KillShell)
+    Command,
+    /// File modifications (Edit, MultiEdit, Write, NotebookEdit)
+    FileEdit,
+    /// File reads (Read, NotebookRead)
+    FileRead,
+    /// Codebase searches (Glob, Grep, LS)
+    Search,
+    /// Web access (WebSearch, WebFetch)
+    Web,
+    /// Sub-agent tasks (Task)
+    Agent,
+    /// MCP server tools (mcp__*)
+    Mcp,
+    /// Anything else
+    Other,
+}
+
+/// A single tool call on the session timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    /// Tool call ID from Claude
+    pub tool_use_id: String,
+    /// Name of the tool (e.g., "Bash", "Edit")
+    pub name: String,
+    pub kind: TimelineKind,
+    /// Short human-readable description (command, file path, query, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Parent tool use ID for sub-agent tool calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_tool_use_id: Option<String>,
+    /// When the tool call was observed (unix epoch milliseconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at_ms: Option<u64>,
+    /// When the tool result was observed (unix epoch milliseconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at_ms: Option<u64>,
+    /// Duration in milliseconds (only when both timestamps are known)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Whether a tool result was received
+    pub completed: bool,
+    /// Whether the tool result was flagged as an error
+    #[serde(default)]
+    pub is_error: bool,
+    /// Truncated tool output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_preview: Option<String>,
+}
+
+/// Timeline of tool calls for a single run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunTimeline {
+    pub run_id: String,
+    pub user_message_id: String,
+    /// Unix timestamp when run started
+    pub started_at: u64,
+    /// Unix timestamp when run ended (None if still running)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<u64>,
+    pub status: RunStatus,
+    pub entries: Vec<TimelineEntry>,
+}
+
+/// Timeline of tool calls across all runs of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTimeline {
+    pub session_id: String,
+    pub runs: Vec<RunTimeline>,
+}
+
 // ============================================================================
 // Compaction Types
 // ============================================================================
@@ -51,6 +152,9 @@ pub struct UsageData {
     /// Cache creation tokens (cached for future requests)
     #[serde(default)]
     pub cache_creation_input_tokens: u64,
+    /// Cost reported by Claude CLI (`total_cost_usd`); None for older runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
 }
 
 // ============================================================================
@@ -376,6 +480,15 @@ pub struct Session {
     /// User-assigned label (e.g. "Needs testing")
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
+    /// Client or cost center for usage reports (None = the project's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    /// Sub-project directory the session is scoped to (repo-relative)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subproject: Option<String>,
+    /// Session this one re-runs (see `rerun_session`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerun_of: Option<String>,
 }
 
 impl Session {
@@ -412,6 +525,9 @@ impl Session {
             last_run_status: None,
             last_run_execution_mode: None,
             label: None,
+            attribution: None,
+            subproject: None,
+            rerun_of: None,
         }
     }
 
@@ -559,6 +675,9 @@ impl SessionMetadata {
             last_run_status: last_run.map(|r| r.status.clone()),
             last_run_execution_mode: last_run.and_then(|r| r.execution_mode.clone()),
             label: self.label.clone(),
+            attribution: self.attribution.clone(),
+            subproject: self.subproject.clone(),
+            rerun_of: self.rerun_of.clone(),
         }
     }
 
@@ -583,6 +702,8 @@ impl SessionMetadata {
         self.plan_file_path = session.plan_file_path.clone();
         self.pending_plan_message_id = session.pending_plan_message_id.clone();
         self.label = session.label.clone();
+        self.attribution = session.attribution.clone();
+        self.subproject = session.subproject.clone();
     }
 }
 
@@ -718,6 +839,36 @@ pub struct AllSessionsResponse {
     pub entries: Vec<AllSessionsEntry>,
 }
 
+// ============================================================================
+// Duplicate Detection Types
+// ============================================================================
+
+/// A session in a duplicate group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateSession {
+    pub session_id: String,
+    pub worktree_id: String,
+    pub name: String,
+    pub created_at: u64,
+}
+
+/// Sessions with identical content (see storage::content_hash)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub content_hash: String,
+    /// Oldest session first (the one kept when duplicates are removed)
+    pub sessions: Vec<DuplicateSession>,
+    pub run_count: u32,
+}
+
+/// Result of find_duplicate_sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    /// Duplicates deleted (0 unless removal was requested)
+    pub removed: usize,
+}
+
 // ============================================================================
 // Run Types (for NDJSON-based persistence)
 // ============================================================================
@@ -736,6 +887,8 @@ pub enum RunStatus {
     Crashed,
     /// Process still running after app restart (can resume tailing)
     Resumable,
+    /// Process died while the system was asleep (conversation can be continued)
+    Interrupted,
 }
 
 /// Metadata for a single Claude CLI execution (stored in manifest)
@@ -784,6 +937,13 @@ pub struct RunEntry {
     /// Token usage for this run (captured from Claude CLI result)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub usage: Option<UsageData>,
+    /// Client or cost center the run is billed to (session's, else sub-project's,
+    /// else project's, at start)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    /// Sub-project the session was scoped to when the run started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subproject: Option<String>,
 }
 
 /// Session metadata - single source of truth for session data and run history
@@ -858,6 +1018,30 @@ pub struct SessionMetadata {
     /// User-assigned label (e.g. "Needs testing")
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
+    /// Client or cost center for usage reports (None = the project's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    /// Sub-project directory the session is scoped to (repo-relative)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subproject: Option<String>,
+    /// Tool versions, git state and environment the session started with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<super::environment::EnvironmentSnapshot>,
+    /// Dependency vulnerabilities found before the conversation started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vulnerabilities: Option<super::vulnerabilities::VulnerabilityReport>,
+    /// Settings the conversation was started with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_settings: Option<super::input_requests::ResumeSettings>,
+    /// Session this one re-runs (see `rerun_session`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerun_of: Option<String>,
+    /// Reviewer notes, ratings and labels on messages
+    #[serde(default)]
+    pub annotations: Vec<super::annotations::MessageAnnotation>,
+    /// Library prompts rendered for the session, by name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<String>,
 
     /// Run history - each entry corresponds to one Claude CLI execution
     #[serde(default)]
@@ -872,6 +1056,16 @@ fn default_manifest_version() -> u32 {
     1
 }
 
+/// Payload for session:interrupted events (process died while the system slept)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInterruptedEvent {
+    pub session_id: String,
+    pub worktree_id: String,
+    pub run_id: String,
+    /// True if the Claude conversation can be continued with a new message
+    pub resumable: bool,
+}
+
 // ============================================================================
 // Debug Info Types (for SessionDebugPanel)
 // ============================================================================
@@ -944,6 +1138,14 @@ impl SessionMetadata {
             pending_plan_message_id: None,
             digest: None,
             label: None,
+            attribution: None,
+            subproject: None,
+            environment: None,
+            vulnerabilities: None,
+            launch_settings: None,
+            rerun_of: None,
+            annotations: vec![],
+            templates: vec![],
             runs: vec![],
             version: 1,
         }
@@ -1132,6 +1334,7 @@ mod tests {
             output_tokens: 200,
             cache_read_input_tokens: 50,
             cache_creation_input_tokens: 25,
+            cost_usd: None,
         };
 
         let json = serde_json::to_string(&usage).unwrap();
@@ -1305,6 +1508,8 @@ mod tests {
             claude_session_id: None,
             pid: Some(12345),
             usage: None,
+            attribution: None,
+            subproject: None,
         });
 
         assert!(metadata.find_run("run-1").is_some());
@@ -1341,6 +1546,8 @@ mod tests {
             claude_session_id: None,
             pid: None,
             usage: None,
+            attribution: None,
+            subproject: None,
         });
 
         assert!(metadata.latest_claude_session_id().is_none());
@@ -1363,6 +1570,8 @@ mod tests {
             claude_session_id: Some("claude-sess-abc".to_string()),
             pid: None,
             usage: None,
+            attribution: None,
+            subproject: None,
         });
 
         assert_eq!(metadata.latest_claude_session_id(), Some("claude-sess-abc"));
@@ -0,0 +1,515 @@
+//! Dependency vulnerability scans
+//!
+//! Before a new conversation starts (with `vulnerability_scan_before_sessions`)
+//! or on request, the worktree's dependencies are checked: `cargo audit` and
+//! `npm audit` where a lockfile and the tool exist, and the OSV database for
+//! Cargo.lock (when cargo-audit isn't installed) and go.sum. The findings are
+//! stored on the session's metadata and attached to it as a saved context,
+//! so "upgrade the vulnerable deps" starts from real data.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::storage::{load_metadata, with_metadata_mut};
+use crate::platform::silent_command;
+use crate::projects::toolchain::{self, Toolchain};
+use crate::runtime::{PathProvider, ProcessRunner, SystemProcessRunner};
+
+const OSV_QUERY_BATCH_URL: &str = "https://api.osv.dev/v1/querybatch";
+
+/// Queries per OSV batch request (the API's limit)
+const OSV_BATCH_SIZE: usize = 1000;
+
+/// Findings listed in the attached context; the rest are counted
+const MAX_CONTEXT_FINDINGS: usize = 100;
+
+/// Saved context slug of the attached report
+pub const CONTEXT_SLUG: &str = "vulnerabilities";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scanner {
+    CargoAudit,
+    NpmAudit,
+    Osv,
+}
+
+/// A vulnerable dependency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vulnerability {
+    pub scanner: Scanner,
+    pub package: String,
+    /// Installed version (npm audit only reports the affected range)
+    pub version: Option<String>,
+    /// Advisory ID (RUSTSEC-…, GHSA-…, GO-…)
+    pub id: String,
+    pub severity: Option<String>,
+    pub title: Option<String>,
+    /// Version or range that fixes it, if known
+    pub fixed_in: Option<String>,
+}
+
+/// One scanner run over one directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScannerRun {
+    pub scanner: Scanner,
+    /// Repo-relative directory ("" for the root)
+    pub dir: String,
+    /// Why it couldn't scan (its findings are then missing)
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VulnerabilityReport {
+    pub scanned_at: u64,
+    pub findings: Vec<Vulnerability>,
+    pub scanners: Vec<ScannerRun>,
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key)?.as_str().map(str::to_string)
+}
+
+/// Findings of `cargo audit --json`
+pub fn parse_cargo_audit(output: &str) -> Result<Vec<Vulnerability>, String> {
+    let json: Value =
+        serde_json::from_str(output).map_err(|e| format!("Unexpected cargo audit output: {e}"))?;
+    let list = json
+        .pointer("/vulnerabilities/list")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    Ok(list
+        .iter()
+        .filter_map(|entry| {
+            let advisory = entry.get("advisory")?;
+            let package = entry.get("package")?;
+            let patched: Vec<_> = entry
+                .pointer("/versions/patched")
+                .and_then(Value::as_array)
+                .map(|v| v.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            Some(Vulnerability {
+                scanner: Scanner::CargoAudit,
+                package: str_field(package, "name")?,
+                version: str_field(package, "version"),
+                id: str_field(advisory, "id")?,
+                severity: str_field(advisory, "severity"),
+                title: str_field(advisory, "title"),
+                fixed_in: (!patched.is_empty()).then(|| patched.join(", ")),
+            })
+        })
+        .collect())
+}
+
+/// Findings of `npm audit --json` (npm 7+): one per advisory a package is
+/// directly affected by; packages only vulnerable through others are skipped
+pub fn parse_npm_audit(output: &str) -> Result<Vec<Vulnerability>, String> {
+    let json: Value =
+        serde_json::from_str(output).map_err(|e| format!("Unexpected npm audit output: {e}"))?;
+    if let Some(error) = json.pointer("/error/summary").and_then(Value::as_str) {
+        return Err(error.to_string());
+    }
+    let Some(packages) = json.get("vulnerabilities").and_then(Value::as_object) else {
+        return Ok(Vec::new());
+    };
+    let mut findings = Vec::new();
+    for (name, entry) in packages {
+        let fixed_in = entry
+            .get("fixAvailable")
+            .filter(|fix| fix.is_object())
+            .and_then(|fix| {
+                Some(format!(
+                    "{}@{}",
+                    str_field(fix, "name")?,
+                    str_field(fix, "version")?
+                ))
+            });
+        let advisories = entry.get("via").and_then(Value::as_array);
+        for advisory in advisories.into_iter().flatten().filter(|v| v.is_object()) {
+            let id = str_field(advisory, "url")
+                .and_then(|url| url.rsplit('/').next().map(str::to_string))
+                .or_else(|| advisory.get("source").map(|s| s.to_string()))
+                .unwrap_or_default();
+            findings.push(Vulnerability {
+                scanner: Scanner::NpmAudit,
+                package: name.clone(),
+                version: str_field(advisory, "range"),
+                id,
+                severity: str_field(advisory, "severity"),
+                title: str_field(advisory, "title"),
+                fixed_in: fixed_in.clone(),
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// Registry packages in a Cargo.lock
+pub fn cargo_lock_packages(content: &str) -> Vec<(String, String)> {
+    let mut packages = Vec::new();
+    for block in content.split("[[package]]").skip(1) {
+        let field = |key: &str| {
+            block.lines().find_map(|line| {
+                let value = line.trim().strip_prefix(key)?.trim().strip_prefix('=')?;
+                value
+                    .trim()
+                    .strip_prefix('"')?
+                    .split('"')
+                    .next()
+                    .map(str::to_string)
+            })
+        };
+        let from_registry = field("source").is_some_and(|s| s.starts_with("registry+"));
+        if let (true, Some(name), Some(version)) = (from_registry, field("name"), field("version"))
+        {
+            packages.push((name, version));
+        }
+    }
+    packages
+}
+
+/// Modules in a go.sum
+pub fn go_sum_modules(content: &str) -> Vec<(String, String)> {
+    let modules: BTreeSet<_> = content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let module = parts.next()?;
+            let version = parts.next()?.trim_end_matches("/go.mod");
+            Some((module.to_string(), version.to_string()))
+        })
+        .collect();
+    modules.into_iter().collect()
+}
+
+/// Look packages up in the OSV database (`ecosystem` as OSV names it)
+async fn query_osv(
+    ecosystem: &str,
+    packages: &[(String, String)],
+) -> Result<Vec<Vulnerability>, String> {
+    let client = crate::http_client::client_builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+    let mut findings = Vec::new();
+    for batch in packages.chunks(OSV_BATCH_SIZE) {
+        let queries: Vec<_> = batch
+            .iter()
+            .map(|(name, version)| {
+                json!({ "package": { "name": name, "ecosystem": ecosystem }, "version": version })
+            })
+            .collect();
+        let response: Value = client
+            .post(OSV_QUERY_BATCH_URL)
+            .json(&json!({ "queries": queries }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("OSV query failed: {e}"))?
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected OSV response: {e}"))?;
+        let results = response
+            .get("results")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for ((name, version), result) in batch.iter().zip(results) {
+            let vulns = result.get("vulns").and_then(Value::as_array);
+            for vuln in vulns.into_iter().flatten() {
+                let Some(id) = str_field(vuln, "id") else {
+                    continue;
+                };
+                findings.push(Vulnerability {
+                    scanner: Scanner::Osv,
+                    package: name.clone(),
+                    version: Some(version.clone()),
+                    id,
+                    severity: None,
+                    title: None,
+                    fixed_in: None,
+                });
+            }
+        }
+    }
+    Ok(findings)
+}
+
+/// Run an audit tool that exits non-zero when it finds something
+fn run_audit(
+    runner: &dyn ProcessRunner,
+    dir: &Path,
+    program: &str,
+    args: &[&str],
+) -> Result<String, String> {
+    let mut command = silent_command(program);
+    command.args(args).current_dir(dir);
+    let output = runner.run(command, None)?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if stdout.trim().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{program} {}: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(stdout)
+}
+
+/// Scan the worktree at `root`
+pub async fn scan(root: &Path, now: u64) -> VulnerabilityReport {
+    let toolchains = toolchain::detect(root, now).toolchains;
+    let mut findings = Vec::new();
+    let mut scanners = Vec::new();
+    let mut record = |scanner: Scanner, dir: &str, result: Result<Vec<Vulnerability>, String>| {
+        let error = match result {
+            Ok(found) => {
+                findings.extend(found);
+                None
+            }
+            Err(e) => {
+                log::warn!("Vulnerability scan ({scanner:?}) of {dir:?} failed: {e}");
+                Some(e)
+            }
+        };
+        scanners.push(ScannerRun {
+            scanner,
+            dir: dir.to_string(),
+            error,
+        });
+    };
+
+    for detected in &toolchains {
+        let dir = root.join(&detected.dir);
+        match detected.toolchain {
+            Toolchain::Cargo => {
+                let Ok(lock) = fs::read_to_string(dir.join("Cargo.lock")) else {
+                    continue;
+                };
+                let audit_dir = dir.clone();
+                let audit = tauri::async_runtime::spawn_blocking(move || {
+                    run_audit(
+                        &SystemProcessRunner,
+                        &audit_dir,
+                        "cargo",
+                        &["audit", "--json"],
+                    )
+                })
+                .await
+                .map_err(|e| format!("cargo audit failed: {e}"))
+                .and_then(|r| r);
+                match audit {
+                    Ok(output) => record(
+                        Scanner::CargoAudit,
+                        &detected.dir,
+                        parse_cargo_audit(&output),
+                    ),
+                    Err(e) => {
+                        // Without cargo-audit, the crates are looked up in OSV
+                        log::trace!("cargo audit unavailable ({e}), querying OSV");
+                        let packages = cargo_lock_packages(&lock);
+                        record(
+                            Scanner::Osv,
+                            &detected.dir,
+                            query_osv("crates.io", &packages).await,
+                        );
+                    }
+                }
+            }
+            Toolchain::Npm if dir.join("package-lock.json").is_file() => {
+                let audit_dir = dir.clone();
+                let audit = tauri::async_runtime::spawn_blocking(move || {
+                    run_audit(
+                        &SystemProcessRunner,
+                        &audit_dir,
+                        "npm",
+                        &["audit", "--json"],
+                    )
+                })
+                .await
+                .map_err(|e| format!("npm audit failed: {e}"))
+                .and_then(|r| r)
+                .and_then(|output| parse_npm_audit(&output));
+                record(Scanner::NpmAudit, &detected.dir, audit);
+            }
+            Toolchain::Go => {
+                let Ok(sum) = fs::read_to_string(dir.join("go.sum")) else {
+                    continue;
+                };
+                let modules = go_sum_modules(&sum);
+                record(Scanner::Osv, &detected.dir, query_osv("Go", &modules).await);
+            }
+            _ => {}
+        }
+    }
+
+    VulnerabilityReport {
+        scanned_at: now,
+        findings,
+        scanners,
+    }
+}
+
+/// The report as a saved context
+pub fn render(report: &VulnerabilityReport) -> String {
+    let mut out = String::from("# Dependency vulnerabilities\n\n");
+    if report.findings.is_empty() {
+        out.push_str("No known vulnerabilities found.\n");
+    }
+    for finding in report.findings.iter().take(MAX_CONTEXT_FINDINGS) {
+        out.push_str(&format!("- {}", finding.package));
+        if let Some(version) = &finding.version {
+            out.push_str(&format!(" {version}"));
+        }
+        out.push_str(&format!(": {}", finding.id));
+        if let Some(severity) = &finding.severity {
+            out.push_str(&format!(" ({severity})"));
+        }
+        if let Some(title) = &finding.title {
+            out.push_str(&format!(" {title}"));
+        }
+        if let Some(fixed_in) = &finding.fixed_in {
+            out.push_str(&format!("; fixed in {fixed_in}"));
+        }
+        out.push('\n');
+    }
+    if report.findings.len() > MAX_CONTEXT_FINDINGS {
+        out.push_str(&format!(
+            "- …and {} more\n",
+            report.findings.len() - MAX_CONTEXT_FINDINGS
+        ));
+    }
+    let failed: Vec<_> = report
+        .scanners
+        .iter()
+        .filter_map(|run| run.error.as_ref().map(|e| (run, e)))
+        .collect();
+    if !failed.is_empty() {
+        out.push_str("\nNot scanned:\n");
+        for (run, error) in failed {
+            let dir = if run.dir.is_empty() { "." } else { &run.dir };
+            out.push_str(&format!("- {dir} ({:?}): {error}\n", run.scanner));
+        }
+    }
+    out
+}
+
+fn context_file(app: &impl PathProvider, session_id: &str) -> Result<PathBuf, String> {
+    Ok(app
+        .app_data_dir()?
+        .join("session-context")
+        .join(format!("{session_id}-context-{CONTEXT_SLUG}.md")))
+}
+
+/// Store a report on a session and attach it as a saved context
+pub fn record(
+    app: &impl PathProvider,
+    session_id: &str,
+    report: VulnerabilityReport,
+) -> Result<(), String> {
+    let path = context_file(app, session_id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create session context directory: {e}"))?;
+    }
+    fs::write(&path, render(&report))
+        .map_err(|e| format!("Failed to attach vulnerability report: {e}"))?;
+
+    let metadata =
+        load_metadata(app, session_id)?.ok_or_else(|| format!("Session {session_id} not found"))?;
+    with_metadata_mut(
+        app,
+        session_id,
+        &metadata.worktree_id,
+        &metadata.name,
+        metadata.order,
+        |metadata| {
+            metadata.vulnerabilities = Some(report);
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::ScriptedRunner;
+
+    #[test]
+    fn test_parse_cargo_audit() {
+        let output = r#"{
+            "vulnerabilities": { "found": true, "count": 1, "list": [{
+                "advisory": { "id": "RUSTSEC-2020-0071", "package": "time", "title": "Potential segfault in the time crate" },
+                "versions": { "patched": [">=0.2.23"], "unaffected": ["=0.2.0"] },
+                "package": { "name": "time", "version": "0.1.45" }
+            }] }
+        }"#;
+        let findings = parse_cargo_audit(output).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "RUSTSEC-2020-0071");
+        assert_eq!(findings[0].version.as_deref(), Some("0.1.45"));
+        assert_eq!(findings[0].fixed_in.as_deref(), Some(">=0.2.23"));
+        assert!(parse_cargo_audit("error: not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_npm_audit() {
+        let output = r#"{
+            "vulnerabilities": {
+                "lodash": {
+                    "name": "lodash", "severity": "high",
+                    "via": [{ "source": 1065, "title": "Prototype Pollution", "url": "https://github.com/advisories/GHSA-p6mc-m468-83gw", "severity": "high", "range": "<4.17.19" }],
+                    "fixAvailable": { "name": "lodash", "version": "4.17.21", "isSemVerMajor": false }
+                },
+                "uses-lodash": { "name": "uses-lodash", "severity": "high", "via": ["lodash"], "fixAvailable": true }
+            }
+        }"#;
+        let findings = parse_npm_audit(output).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "GHSA-p6mc-m468-83gw");
+        assert_eq!(findings[0].fixed_in.as_deref(), Some("lodash@4.17.21"));
+    }
+
+    #[test]
+    fn test_lockfile_packages() {
+        let lock = "version = 3\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.200\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n";
+        assert_eq!(
+            cargo_lock_packages(lock),
+            vec![("serde".to_string(), "1.0.200".to_string())]
+        );
+
+        let sum = "golang.org/x/net v0.1.0 h1:abc=\ngolang.org/x/net v0.1.0/go.mod h1:def=\n";
+        assert_eq!(
+            go_sum_modules(sum),
+            vec![("golang.org/x/net".to_string(), "v0.1.0".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_run_audit_without_output() {
+        let runner = ScriptedRunner::failing(1, "");
+        assert!(run_audit(&runner, Path::new("."), "npm", &["audit", "--json"]).is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let report = VulnerabilityReport {
+            scanned_at: 0,
+            findings: parse_cargo_audit(
+                r#"{"vulnerabilities":{"list":[{"advisory":{"id":"RUSTSEC-1","title":"Bad"},"versions":{"patched":[]},"package":{"name":"time","version":"0.1.0"}}]}}"#,
+            )
+            .unwrap(),
+            scanners: vec![ScannerRun {
+                scanner: Scanner::NpmAudit,
+                dir: "web".to_string(),
+                error: Some("npm not found".to_string()),
+            }],
+        };
+        assert_eq!(
+            render(&report),
+            "# Dependency vulnerabilities\n\n- time 0.1.0: RUSTSEC-1 Bad\n\nNot scanned:\n- web (NpmAudit): npm not found\n"
+        );
+    }
+}
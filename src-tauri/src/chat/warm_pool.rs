@@ -0,0 +1,519 @@
+//! Pre-warmed Claude CLI processes
+//!
+//! A cold CLI start takes 1–3 seconds before it reads its first message.
+//! With `warm_process_pool` on, every new conversation leaves behind an idle
+//! CLI spawned with the same settings (one per provider profile), blocked on
+//! stdin. The next new conversation adopts it if its arguments and
+//! environment match exactly once the session id is masked out: the idle
+//! process's output file is renamed onto the run's output file and the
+//! message is written to its stdin. Any mismatch (model, mode, profile, MCP
+//! config, system prompt contents, CLI binary, worktree) falls back to a cold
+//! spawn. Idle processes are respawned after [`MAX_IDLE`] so they pick up
+//! changed credentials and project settings.
+//!
+//! Warm processes are built for a placeholder session; on adoption its
+//! scratch directory is linked to the real session's. Adoption is Unix only,
+//! since Windows can't rename a file another process has open.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use super::types::{EffortLevel, ThinkingLevel};
+use crate::runtime::PathProvider;
+
+/// Idle processes older than this are respawned
+pub const MAX_IDLE: Duration = Duration::from_secs(10 * 60);
+
+/// How often idle processes are checked
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Placeholder session ids start with this
+const WARM_SESSION_PREFIX: &str = "warm-";
+
+/// Directory (within app data) for idle processes' output files
+const POOL_DIR_NAME: &str = "warm-pool";
+
+/// Idle processes by provider key
+static POOL: Lazy<Mutex<HashMap<String, WarmProcess>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Everything a run's CLI arguments are built from, except the session
+#[derive(Debug, Clone)]
+pub struct WarmSettings {
+    pub worktree_id: String,
+    pub working_dir: PathBuf,
+    pub model: Option<String>,
+    pub execution_mode: Option<String>,
+    pub thinking_level: Option<ThinkingLevel>,
+    pub effort_level: Option<EffortLevel>,
+    pub allowed_tools: Option<Vec<String>>,
+    pub disable_thinking_in_non_plan_modes: bool,
+    pub parallel_execution_prompt: Option<String>,
+    pub ai_language: Option<String>,
+    pub mcp_config: Option<String>,
+    pub chrome_enabled: bool,
+    pub custom_profile_settings: Option<String>,
+}
+
+impl WarmSettings {
+    /// Pool slot: one idle process per provider profile
+    fn provider_key(&self) -> String {
+        self.custom_profile_settings.clone().unwrap_or_default()
+    }
+}
+
+/// An idle CLI waiting on stdin
+struct WarmProcess {
+    placeholder_id: String,
+    child: Child,
+    output_file: PathBuf,
+    fingerprint: String,
+    settings: WarmSettings,
+    spawned_at: Instant,
+}
+
+/// Identity of a CLI invocation with `session_id` masked out
+///
+/// System prompt files are compared by content, since their path contains
+/// the session id and their content can include session context. The CLI
+/// binary's modification time is included so an update invalidates the pool.
+fn fingerprint(
+    cli_path: &Path,
+    working_dir: &Path,
+    args: &[String],
+    env_vars: &[(String, String)],
+    session_id: &str,
+) -> String {
+    let mask = |s: &str| s.replace(session_id, "{session}");
+    let mut hasher = Sha256::new();
+    let mut field = |bytes: &[u8]| {
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(bytes);
+    };
+
+    field(cli_path.to_string_lossy().as_bytes());
+    let modified = fs::metadata(cli_path)
+        .and_then(|m| m.modified())
+        .map(|t| format!("{t:?}"))
+        .unwrap_or_default();
+    field(modified.as_bytes());
+    field(working_dir.to_string_lossy().as_bytes());
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        field(mask(arg).as_bytes());
+        if arg == "--append-system-prompt-file" {
+            if let Some(path) = args.next() {
+                field(&fs::read(path).unwrap_or_default());
+            }
+        }
+    }
+
+    let mut env: Vec<String> = env_vars
+        .iter()
+        .map(|(k, v)| format!("{k}={}", mask(v)))
+        .collect();
+    env.sort();
+    for var in &env {
+        field(var.as_bytes());
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn pool_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    let dir = app.app_data_dir()?.join(POOL_DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create warm pool directory: {e}"))?;
+    Ok(dir)
+}
+
+/// Remove a placeholder session's files (scratch dir, combined context)
+fn remove_placeholder(app: &impl PathProvider, placeholder_id: &str) {
+    if let Ok(dir) = super::storage::get_data_dir(app) {
+        let _ = fs::remove_dir_all(dir.join(placeholder_id));
+    }
+    if let Ok(app_data_dir) = app.app_data_dir() {
+        let _ = fs::remove_file(
+            app_data_dir
+                .join("combined-contexts")
+                .join(format!("{placeholder_id}-combined.md")),
+        );
+    }
+}
+
+/// Kill an idle process and remove its files
+fn discard(app: &impl PathProvider, mut warm: WarmProcess) {
+    // The whole group: an idle CLI may already have started MCP servers
+    let _ = crate::platform::kill_process_tree(warm.child.id());
+    let _ = warm.child.kill();
+    let _ = warm.child.wait();
+    let _ = fs::remove_file(&warm.output_file);
+    remove_placeholder(app, &warm.placeholder_id);
+}
+
+#[cfg(unix)]
+fn spawn_warm(app: &AppHandle, settings: &WarmSettings) -> Result<WarmProcess, String> {
+    use std::os::unix::process::CommandExt;
+    use std::process::Stdio;
+
+    let cli_path = crate::claude_cli::versions::binary_for_worktree(
+        app,
+        &settings.worktree_id,
+        &settings.working_dir,
+    )?;
+    if !cli_path.exists() {
+        return Err("Claude CLI not installed".to_string());
+    }
+
+    let placeholder_id = format!("{WARM_SESSION_PREFIX}{}", uuid::Uuid::new_v4());
+    let (args, env_vars) = super::claude::build_claude_args(
+        app,
+        &placeholder_id,
+        &settings.worktree_id,
+        None,
+        settings.model.as_deref(),
+        settings.execution_mode.as_deref(),
+        settings.thinking_level.as_ref(),
+        settings.effort_level.as_ref(),
+        settings.allowed_tools.as_deref(),
+        settings.disable_thinking_in_non_plan_modes,
+        settings.parallel_execution_prompt.as_deref(),
+        settings.ai_language.as_deref(),
+        settings.mcp_config.as_deref(),
+        settings.chrome_enabled,
+        settings.custom_profile_settings.as_deref(),
+    )
+    .map_err(|errors| crate::cli_capabilities::args::describe_errors("Claude CLI", &errors))?;
+    let fingerprint = fingerprint(
+        &cli_path,
+        &settings.working_dir,
+        &args,
+        &env_vars,
+        &placeholder_id,
+    );
+
+    let output_file = pool_dir(app)?.join(format!("{placeholder_id}.jsonl"));
+    let out = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&output_file)
+        .map_err(|e| format!("Failed to create warm output file: {e}"))?;
+    let err = out
+        .try_clone()
+        .map_err(|e| format!("Failed to clone warm output file handle: {e}"))?;
+
+    // Own process group, like detached runs, so cancel_process can kill the tree
+    let child = crate::platform::silent_command(&cli_path)
+        .args(&args)
+        .envs(env_vars)
+        .current_dir(&settings.working_dir)
+        .stdin(Stdio::piped())
+        .stdout(out)
+        .stderr(err)
+        .process_group(0)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn warm Claude CLI: {e}"))?;
+
+    log::trace!(
+        "Spawned warm Claude CLI pid={} for worktree {}",
+        child.id(),
+        settings.worktree_id
+    );
+    Ok(WarmProcess {
+        placeholder_id,
+        child,
+        output_file,
+        fingerprint,
+        settings: settings.clone(),
+        spawned_at: Instant::now(),
+    })
+}
+
+/// Replace the idle process for these settings' provider with a fresh one
+#[cfg(unix)]
+fn fill(app: &AppHandle, settings: &WarmSettings) {
+    match spawn_warm(app, settings) {
+        Ok(warm) => {
+            let previous = POOL.lock().unwrap().insert(settings.provider_key(), warm);
+            if let Some(previous) = previous {
+                discard(app, previous);
+            }
+        }
+        Err(e) => log::warn!("Failed to pre-warm Claude CLI: {e}"),
+    }
+}
+
+/// Spawn an idle process for the next new conversation with these settings
+pub fn refill(app: AppHandle, settings: WarmSettings) {
+    #[cfg(unix)]
+    crate::background_tasks::supervisor::spawn_thread("warm-pool-refill", move || {
+        fill(&app, &settings);
+    });
+    #[cfg(not(unix))]
+    let _ = (app, settings);
+}
+
+/// Point an idle process at a run: move its output onto the run log, link
+/// its scratch dir and send the message
+#[cfg(unix)]
+fn hand_off(
+    app: &AppHandle,
+    warm: &mut WarmProcess,
+    session_id: &str,
+    input_file: &Path,
+    output_file: &Path,
+) -> Result<(), String> {
+    use std::io::Write;
+
+    // Header first, then anything the CLI printed while idle. The CLI
+    // appends, so it keeps writing to the end of the renamed file.
+    let header = fs::read(output_file).map_err(|e| format!("Failed to read run log: {e}"))?;
+    let idle_output =
+        fs::read(&warm.output_file).map_err(|e| format!("Failed to read warm output: {e}"))?;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .open(&warm.output_file)
+        .map_err(|e| format!("Failed to open warm output: {e}"))?;
+    file.set_len(0)
+        .and_then(|_| file.write_all(&header))
+        .and_then(|_| file.write_all(&idle_output))
+        .map_err(|e| format!("Failed to write warm output: {e}"))?;
+    fs::rename(&warm.output_file, output_file)
+        .map_err(|e| format!("Failed to move warm output: {e}"))?;
+
+    // Temp files from the run belong in the session's scratch dir
+    let session_scratch = super::scratch::get_session_scratch_dir(app, session_id)?;
+    let warm_scratch = super::scratch::get_session_scratch_dir(app, &warm.placeholder_id)?;
+    let _ = fs::remove_dir_all(&warm_scratch);
+    std::os::unix::fs::symlink(&session_scratch, &warm_scratch)
+        .map_err(|e| format!("Failed to link scratch directory: {e}"))?;
+
+    let input = fs::read(input_file).map_err(|e| format!("Failed to read input file: {e}"))?;
+    // Dropping stdin closes it so the CLI sees EOF after the message
+    let mut stdin = warm.child.stdin.take().ok_or("Warm process has no stdin")?;
+    stdin
+        .write_all(&input)
+        .map_err(|e| format!("Failed to write to warm process: {e}"))
+}
+
+/// Hand a new conversation's run to a matching idle process
+///
+/// `output_file` must hold only the run's header. Returns the adopted PID,
+/// or None (after discarding any stale or mismatched process) so the caller
+/// spawns cold.
+#[cfg(unix)]
+#[allow(clippy::too_many_arguments)]
+pub fn adopt(
+    app: &AppHandle,
+    settings: &WarmSettings,
+    cli_path: &Path,
+    args: &[String],
+    env_vars: &[(String, String)],
+    session_id: &str,
+    input_file: &Path,
+    output_file: &Path,
+) -> Option<u32> {
+    let mut warm = POOL.lock().unwrap().remove(&settings.provider_key())?;
+
+    let wanted = fingerprint(cli_path, &settings.working_dir, args, env_vars, session_id);
+    let reason = if warm.fingerprint != wanted {
+        Some("settings changed")
+    } else if warm.spawned_at.elapsed() > MAX_IDLE {
+        Some("idle too long")
+    } else if !matches!(warm.child.try_wait(), Ok(None)) {
+        Some("process exited")
+    } else {
+        None
+    };
+    if let Some(reason) = reason {
+        log::trace!("Not adopting warm Claude CLI ({reason}), spawning cold");
+        discard(app, warm);
+        return None;
+    }
+
+    if let Err(e) = hand_off(app, &mut warm, session_id, input_file, output_file) {
+        log::warn!("Failed to adopt warm Claude CLI: {e}");
+        discard(app, warm);
+        return None;
+    }
+
+    let pid = warm.child.id();
+    log::trace!(
+        "Session {session_id} adopted warm Claude CLI pid={pid} (idle {:?})",
+        warm.spawned_at.elapsed()
+    );
+
+    // Reap the process when it exits (a zombie still looks alive to the
+    // tailer), then drop the placeholder's files
+    let app = app.clone();
+    crate::background_tasks::supervisor::spawn_thread("warm-pool-reaper", move || {
+        let _ = warm.child.wait();
+        remove_placeholder(&app, &warm.placeholder_id);
+    });
+    Some(pid)
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::too_many_arguments)]
+pub fn adopt(
+    _app: &AppHandle,
+    _settings: &WarmSettings,
+    _cli_path: &Path,
+    _args: &[String],
+    _env_vars: &[(String, String)],
+    _session_id: &str,
+    _input_file: &Path,
+    _output_file: &Path,
+) -> Option<u32> {
+    None
+}
+
+/// Kill all idle processes (when the pool is turned off or the app quits)
+pub fn clear(app: &impl PathProvider) {
+    let drained: Vec<WarmProcess> = POOL.lock().unwrap().drain().map(|(_, w)| w).collect();
+    for warm in drained {
+        discard(app, warm);
+    }
+}
+
+/// Remove files left by idle processes of a previous app run
+pub fn cleanup_leftovers(app: &impl PathProvider) {
+    if let Ok(app_data_dir) = app.app_data_dir() {
+        let _ = fs::remove_dir_all(app_data_dir.join(POOL_DIR_NAME));
+    }
+    let Ok(data_dir) = super::storage::get_data_dir(app) else {
+        return;
+    };
+    for entry in fs::read_dir(data_dir).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(WARM_SESSION_PREFIX) {
+            remove_placeholder(app, &name);
+        }
+    }
+}
+
+/// Start the thread that respawns idle processes past [`MAX_IDLE`] or dead
+pub fn start_refresher(app: AppHandle) {
+    crate::background_tasks::supervisor::supervise("warm-pool-refresh", move || loop {
+        std::thread::sleep(REFRESH_INTERVAL);
+
+        let stale: Vec<WarmProcess> = {
+            let mut pool = POOL.lock().unwrap();
+            let keys: Vec<String> = pool
+                .iter_mut()
+                .filter(|(_, w)| {
+                    w.spawned_at.elapsed() > MAX_IDLE || !matches!(w.child.try_wait(), Ok(None))
+                })
+                .map(|(k, _)| k.clone())
+                .collect();
+            keys.iter().filter_map(|k| pool.remove(k)).collect()
+        };
+        for warm in stale {
+            let settings = warm.settings.clone();
+            discard(&app, warm);
+            #[cfg(unix)]
+            fill(&app, &settings);
+            #[cfg(not(unix))]
+            let _ = settings;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(session_id: &str, model: &str) -> Vec<String> {
+        [
+            "--print",
+            "--model",
+            model,
+            "--add-dir",
+            format!("/data/runs/{session_id}").as_str(),
+        ]
+        .map(str::to_string)
+        .to_vec()
+    }
+
+    fn env(session_id: &str) -> Vec<(String, String)> {
+        vec![
+            ("JEAN_SESSION_ID".to_string(), session_id.to_string()),
+            ("JEAN_WORKTREE_ID".to_string(), "wt-1".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_fingerprint_masks_session_id() {
+        let cli = Path::new("/nonexistent/claude");
+        let wd = Path::new("/repo");
+        let warm = fingerprint(cli, wd, &args("warm-1", "opus"), &env("warm-1"), "warm-1");
+        let run = fingerprint(cli, wd, &args("sess-9", "opus"), &env("sess-9"), "sess-9");
+        assert_eq!(warm, run);
+    }
+
+    #[test]
+    fn test_fingerprint_detects_mismatches() {
+        let cli = Path::new("/nonexistent/claude");
+        let wd = Path::new("/repo");
+        let base = fingerprint(cli, wd, &args("sess-1", "opus"), &env("sess-1"), "sess-1");
+
+        assert_ne!(
+            base,
+            fingerprint(cli, wd, &args("sess-1", "sonnet"), &env("sess-1"), "sess-1")
+        );
+        assert_ne!(
+            base,
+            fingerprint(
+                cli,
+                Path::new("/other"),
+                &args("sess-1", "opus"),
+                &env("sess-1"),
+                "sess-1"
+            )
+        );
+        let mut profile_env = env("sess-1");
+        profile_env.push(("ANTHROPIC_BASE_URL".to_string(), "https://x".to_string()));
+        assert_ne!(
+            base,
+            fingerprint(cli, wd, &args("sess-1", "opus"), &profile_env, "sess-1")
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_compares_system_prompt_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let prompt_args = |session_id: &str, content: &str| {
+            let path = dir.path().join(format!("{session_id}-combined.md"));
+            fs::write(&path, content).unwrap();
+            vec![
+                "--append-system-prompt-file".to_string(),
+                path.to_string_lossy().to_string(),
+            ]
+        };
+        let cli = Path::new("/nonexistent/claude");
+        let wd = Path::new("/repo");
+
+        let warm = fingerprint(cli, wd, &prompt_args("warm-1", "Be brief"), &[], "warm-1");
+        let same = fingerprint(cli, wd, &prompt_args("sess-1", "Be brief"), &[], "sess-1");
+        let with_context = fingerprint(
+            cli,
+            wd,
+            &prompt_args("sess-2", "Be brief\n# Loaded Context"),
+            &[],
+            "sess-2",
+        );
+        assert_eq!(warm, same);
+        assert_ne!(warm, with_context);
+    }
+}
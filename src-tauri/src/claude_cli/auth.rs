@@ -0,0 +1,503 @@
+//! Claude subscription (OAuth) login state
+//!
+//! Claude Code stores its OAuth credentials in `~/.claude/.credentials.json`
+//! (in the login Keychain on macOS). Reading them tells us the plan and token
+//! expiry without spending a query, and watching them tells us when an
+//! interactive `/login` in the login terminal has finished.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::config::get_cli_binary_path;
+use crate::http_server::EmitExt;
+
+/// Keychain service Claude Code stores credentials under (macOS)
+#[cfg(target_os = "macos")]
+const KEYCHAIN_SERVICE: &str = "Claude Code-credentials";
+
+/// How often the login watcher checks for new credentials
+const LOGIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Give up watching after this long (the user abandoned the login terminal)
+const LOGIN_WATCH_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Incremented per login flow so a newer (or cancelled) flow stops older watchers
+static LOGIN_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Last auth failure reported by the CLI, kept until the next successful login
+static LAST_AUTH_FAILURE: Lazy<Mutex<Option<AuthFailure>>> = Lazy::new(|| Mutex::new(None));
+
+/// Overall authentication state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaudeAuthState {
+    /// Valid subscription credentials
+    LoggedIn,
+    /// Using ANTHROPIC_API_KEY instead of a subscription
+    ApiKey,
+    /// Access token expired; the CLI refreshes it on the next run
+    RefreshPending,
+    /// Access token expired and there is no refresh token
+    Expired,
+    /// The CLI failed to refresh the token (revoked or expired refresh token)
+    RefreshFailed,
+    /// No credentials
+    LoggedOut,
+}
+
+/// What the user should do about the current state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaudeAuthAction {
+    /// Nothing, auth is working
+    None,
+    /// Run the login flow
+    Login,
+    /// Upgrade/renew the subscription (plan lacks Claude Code access)
+    CheckSubscription,
+}
+
+/// Result of `get_claude_auth_status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeAuthDetails {
+    pub state: ClaudeAuthState,
+    pub action: ClaudeAuthAction,
+    /// Subscription plan (e.g. "pro", "max") if logged in with a subscription
+    pub plan: Option<String>,
+    /// Access token expiry (Unix seconds)
+    pub expires_at: Option<u64>,
+    pub scopes: Vec<String>,
+    /// Where the credentials were read from ("file" or "keychain")
+    pub credentials_source: Option<String>,
+    /// Human-readable explanation of a non-working state
+    pub message: Option<String>,
+}
+
+/// Returned by `start_claude_login`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeLoginFlow {
+    /// Shell command to run in the login terminal
+    pub command: String,
+}
+
+/// An auth error recognized in CLI output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthFailure {
+    pub state: ClaudeAuthState,
+    pub action: ClaudeAuthAction,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OAuthToken {
+    #[serde(default)]
+    access_token: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    /// Unix milliseconds
+    #[serde(default)]
+    expires_at: Option<u64>,
+    #[serde(default)]
+    scopes: Vec<String>,
+    #[serde(default)]
+    subscription_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CredentialsFile {
+    #[serde(default)]
+    claude_ai_oauth: Option<OAuthToken>,
+}
+
+/// Raw credentials and where they came from
+struct StoredCredentials {
+    json: String,
+    source: &'static str,
+}
+
+/// Claude Code's config directory (`CLAUDE_CONFIG_DIR` or `~/.claude`)
+fn claude_config_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("CLAUDE_CONFIG_DIR") {
+        if !dir.is_empty() {
+            return Some(PathBuf::from(dir));
+        }
+    }
+    dirs::home_dir().map(|home| home.join(".claude"))
+}
+
+fn credentials_file_path() -> Option<PathBuf> {
+    claude_config_dir().map(|dir| dir.join(".credentials.json"))
+}
+
+fn read_stored_credentials() -> Option<StoredCredentials> {
+    #[cfg(target_os = "macos")]
+    {
+        let output = crate::platform::silent_command("security")
+            .args(["find-generic-password", "-s", KEYCHAIN_SERVICE, "-w"])
+            .output();
+        if let Ok(output) = output {
+            if output.status.success() {
+                let json = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if !json.is_empty() {
+                    return Some(StoredCredentials {
+                        json,
+                        source: "keychain",
+                    });
+                }
+            }
+        }
+    }
+
+    let json = std::fs::read_to_string(credentials_file_path()?).ok()?;
+    Some(StoredCredentials {
+        json,
+        source: "file",
+    })
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Work out the auth state from the credentials JSON (None if absent)
+fn auth_details_from_credentials(
+    credentials: Option<&str>,
+    source: Option<&str>,
+    has_api_key: bool,
+    now_ms: u64,
+) -> ClaudeAuthDetails {
+    let token = credentials
+        .and_then(|json| serde_json::from_str::<CredentialsFile>(json).ok())
+        .and_then(|c| c.claude_ai_oauth)
+        .filter(|t| t.access_token.as_deref().is_some_and(|a| !a.is_empty()));
+
+    let Some(token) = token else {
+        let state = if has_api_key {
+            ClaudeAuthState::ApiKey
+        } else {
+            ClaudeAuthState::LoggedOut
+        };
+        return ClaudeAuthDetails {
+            state,
+            action: if has_api_key {
+                ClaudeAuthAction::None
+            } else {
+                ClaudeAuthAction::Login
+            },
+            plan: None,
+            expires_at: None,
+            scopes: Vec::new(),
+            credentials_source: None,
+            message: (!has_api_key).then(|| "Not logged in to Claude".to_string()),
+        };
+    };
+
+    let expired = token.expires_at.is_some_and(|exp| exp <= now_ms);
+    let can_refresh = token
+        .refresh_token
+        .as_deref()
+        .is_some_and(|r| !r.is_empty());
+    let (state, action, message) = match (expired, can_refresh) {
+        (false, _) => (ClaudeAuthState::LoggedIn, ClaudeAuthAction::None, None),
+        (true, true) => (
+            ClaudeAuthState::RefreshPending,
+            ClaudeAuthAction::None,
+            Some("Access token expired; it will be refreshed on the next request".to_string()),
+        ),
+        (true, false) => (
+            ClaudeAuthState::Expired,
+            ClaudeAuthAction::Login,
+            Some("Claude login has expired. Log in again.".to_string()),
+        ),
+    };
+
+    ClaudeAuthDetails {
+        state,
+        action,
+        plan: token.subscription_type,
+        expires_at: token.expires_at.map(|ms| ms / 1000),
+        scopes: token.scopes,
+        credentials_source: source.map(str::to_string),
+        message,
+    }
+}
+
+/// Recognize OAuth/subscription errors in CLI output
+///
+/// Returns None for errors that aren't about authentication.
+pub fn classify_auth_error(output: &str) -> Option<AuthFailure> {
+    let lower = output.to_lowercase();
+    let failure = |state, action, message: &str| {
+        Some(AuthFailure {
+            state,
+            action,
+            message: message.to_string(),
+        })
+    };
+
+    if lower.contains("refresh token")
+        || lower.contains("invalid_grant")
+        || lower.contains("failed to refresh")
+    {
+        return failure(
+            ClaudeAuthState::RefreshFailed,
+            ClaudeAuthAction::Login,
+            "Claude could not refresh your login. Log in again.",
+        );
+    }
+    if lower.contains("oauth token has expired")
+        || lower.contains("token has been revoked")
+        || lower.contains("please run /login")
+        || lower.contains("invalid api key")
+        || lower.contains("authentication_error")
+    {
+        return failure(
+            ClaudeAuthState::Expired,
+            ClaudeAuthAction::Login,
+            "Claude login has expired or was revoked. Log in again.",
+        );
+    }
+    if lower.contains("does not have access to claude code")
+        || lower.contains("subscription")
+            && (lower.contains("inactive") || lower.contains("expired"))
+    {
+        return failure(
+            ClaudeAuthState::LoggedIn,
+            ClaudeAuthAction::CheckSubscription,
+            "Your Claude plan doesn't include Claude Code. Check your subscription.",
+        );
+    }
+    None
+}
+
+/// Remember an auth failure from CLI output so `get_claude_auth_status` can
+/// report it; returns the failure if one was recognized
+pub fn record_auth_failure(output: &str) -> Option<AuthFailure> {
+    let failure = classify_auth_error(output)?;
+    log::warn!("Claude auth failure: {}", failure.message);
+    if let Ok(mut last) = LAST_AUTH_FAILURE.lock() {
+        *last = Some(failure.clone());
+    }
+    Some(failure)
+}
+
+pub(crate) fn clear_auth_failure() {
+    if let Ok(mut last) = LAST_AUTH_FAILURE.lock() {
+        *last = None;
+    }
+}
+
+fn current_auth_details() -> ClaudeAuthDetails {
+    let stored = read_stored_credentials();
+    let has_api_key = std::env::var("ANTHROPIC_API_KEY").is_ok_and(|k| !k.is_empty());
+    let mut details = auth_details_from_credentials(
+        stored.as_ref().map(|s| s.json.as_str()),
+        stored.as_ref().map(|s| s.source),
+        has_api_key,
+        now_millis(),
+    );
+
+    // A refresh failure outranks what the (stale) credentials say
+    if let Some(failure) = LAST_AUTH_FAILURE.lock().ok().and_then(|f| f.clone()) {
+        if details.state != ClaudeAuthState::LoggedOut {
+            details.state = failure.state;
+            details.action = failure.action;
+            details.message = Some(failure.message);
+        }
+    }
+    details
+}
+
+/// Report Claude subscription login state, plan and token expiry
+///
+/// Reads the stored credentials only; no query is sent.
+#[tauri::command]
+pub async fn get_claude_auth_status() -> Result<ClaudeAuthDetails, String> {
+    if let Some(scenario) = crate::mock::active_scenario() {
+        let state = if scenario.cli_authenticated {
+            ClaudeAuthState::LoggedIn
+        } else {
+            ClaudeAuthState::LoggedOut
+        };
+        return Ok(ClaudeAuthDetails {
+            state,
+            action: if scenario.cli_authenticated {
+                ClaudeAuthAction::None
+            } else {
+                ClaudeAuthAction::Login
+            },
+            plan: scenario.cli_authenticated.then(|| "max".to_string()),
+            expires_at: None,
+            scopes: Vec::new(),
+            credentials_source: None,
+            message: None,
+        });
+    }
+
+    tauri::async_runtime::spawn_blocking(current_auth_details)
+        .await
+        .map_err(|e| format!("Auth status task failed: {e}"))
+}
+
+/// Start the subscription login flow
+///
+/// Returns the command to run in the login terminal and watches the stored
+/// credentials in the background. When they change to a valid login,
+/// `claude-auth:login-complete` is emitted with the new `ClaudeAuthDetails`.
+#[tauri::command]
+pub async fn start_claude_login(app: AppHandle) -> Result<ClaudeLoginFlow, String> {
+    let binary_path = get_cli_binary_path(&app)?;
+    if !binary_path.exists() {
+        return Err("Claude CLI not installed".to_string());
+    }
+
+    let generation = LOGIN_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let before = read_stored_credentials().map(|s| s.json);
+
+    crate::background_tasks::supervisor::spawn_thread("claude-login-watch", move || {
+        watch_for_login(app, generation, before)
+    });
+
+    Ok(ClaudeLoginFlow {
+        command: login_command(&binary_path.to_string_lossy()),
+    })
+}
+
+/// Stop watching for a login started with `start_claude_login`
+#[tauri::command]
+pub async fn cancel_claude_login() -> Result<(), String> {
+    LOGIN_GENERATION.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Shell invocation of the CLI for the login terminal
+/// (PowerShell on Windows, POSIX shell elsewhere)
+fn login_command(binary_path: &str) -> String {
+    if cfg!(windows) {
+        format!("& \"{binary_path}\" /login")
+    } else {
+        format!("'{}' /login", binary_path.replace('\'', "'\\''"))
+    }
+}
+
+fn watch_for_login(app: AppHandle, generation: u64, before: Option<String>) {
+    let started = Instant::now();
+    log::trace!("Watching for Claude login (flow {generation})");
+
+    while started.elapsed() < LOGIN_WATCH_TIMEOUT {
+        std::thread::sleep(LOGIN_POLL_INTERVAL);
+        if LOGIN_GENERATION.load(Ordering::SeqCst) != generation {
+            log::trace!("Claude login flow {generation} superseded");
+            return;
+        }
+
+        let current = read_stored_credentials().map(|s| s.json);
+        if current.is_none() || current == before {
+            continue;
+        }
+
+        clear_auth_failure();
+        let details = current_auth_details();
+        if details.state != ClaudeAuthState::LoggedIn {
+            continue;
+        }
+
+        log::info!("Claude login completed (plan: {:?})", details.plan);
+        if let Err(e) = app.emit_all("claude-auth:login-complete", &details) {
+            log::warn!("Failed to emit login complete event: {e}");
+        }
+        return;
+    }
+
+    log::trace!("Gave up watching for Claude login (flow {generation})");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW_MS: u64 = 1_800_000_000_000;
+
+    fn credentials(expires_at: u64, refresh_token: Option<&str>) -> String {
+        serde_json::json!({
+            "claudeAiOauth": {
+                "accessToken": "sk-ant-oat01-test",
+                "refreshToken": refresh_token,
+                "expiresAt": expires_at,
+                "scopes": ["user:inference", "user:profile"],
+                "subscriptionType": "max"
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_valid_subscription_credentials() {
+        let json = credentials(NOW_MS + 3_600_000, Some("refresh"));
+        let details = auth_details_from_credentials(Some(&json), Some("file"), false, NOW_MS);
+        assert_eq!(details.state, ClaudeAuthState::LoggedIn);
+        assert_eq!(details.action, ClaudeAuthAction::None);
+        assert_eq!(details.plan.as_deref(), Some("max"));
+        assert_eq!(details.expires_at, Some(NOW_MS / 1000 + 3600));
+        assert_eq!(details.scopes.len(), 2);
+    }
+
+    #[test]
+    fn test_expired_credentials() {
+        let json = credentials(NOW_MS - 1, Some("refresh"));
+        let details = auth_details_from_credentials(Some(&json), Some("file"), false, NOW_MS);
+        assert_eq!(details.state, ClaudeAuthState::RefreshPending);
+
+        let json = credentials(NOW_MS - 1, None);
+        let details = auth_details_from_credentials(Some(&json), Some("file"), false, NOW_MS);
+        assert_eq!(details.state, ClaudeAuthState::Expired);
+        assert_eq!(details.action, ClaudeAuthAction::Login);
+    }
+
+    #[test]
+    fn test_missing_credentials() {
+        let details = auth_details_from_credentials(None, None, false, NOW_MS);
+        assert_eq!(details.state, ClaudeAuthState::LoggedOut);
+        assert_eq!(details.action, ClaudeAuthAction::Login);
+
+        let details = auth_details_from_credentials(Some("{}"), Some("file"), true, NOW_MS);
+        assert_eq!(details.state, ClaudeAuthState::ApiKey);
+        assert_eq!(details.action, ClaudeAuthAction::None);
+    }
+
+    #[test]
+    fn test_classify_auth_error() {
+        let failure = classify_auth_error("Error: Failed to refresh token: invalid_grant").unwrap();
+        assert_eq!(failure.state, ClaudeAuthState::RefreshFailed);
+
+        let failure = classify_auth_error(
+            "API Error: 401 {\"type\":\"error\",\"error\":{\"type\":\"authentication_error\",\"message\":\"OAuth token has expired.\"}}",
+        )
+        .unwrap();
+        assert_eq!(failure.state, ClaudeAuthState::Expired);
+        assert_eq!(failure.action, ClaudeAuthAction::Login);
+
+        assert!(classify_auth_error("API Error: 529 Overloaded").is_none());
+    }
+
+    #[test]
+    fn test_login_command_quotes_path() {
+        let command = login_command("/Users/o'neil/claude");
+        if cfg!(windows) {
+            assert_eq!(command, "& \"/Users/o'neil/claude\" /login");
+        } else {
+            assert_eq!(command, "'/Users/o'\\''neil/claude' /login");
+        }
+    }
+}
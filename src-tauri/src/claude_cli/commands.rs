@@ -5,6 +5,7 @@ use sha2::{Digest, Sha256};
 use std::io::Write;
 use tauri::AppHandle;
 
+use super::auth::{clear_auth_failure, record_auth_failure};
 use super::config::{ensure_cli_dir, get_cli_binary_path};
 use crate::http_server::EmitExt;
 use crate::platform::silent_command;
@@ -67,6 +68,12 @@ pub struct InstallProgress {
     pub message: String,
     /// Percentage complete (0-100)
     pub percent: u8,
+    /// Bytes downloaded so far (downloading stage only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downloaded_bytes: Option<u64>,
+    /// Size of the download, if the server reported it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
 }
 
 /// Check if Claude CLI is installed and get its status
@@ -74,6 +81,16 @@ pub struct InstallProgress {
 pub async fn check_claude_cli_installed(app: AppHandle) -> Result<ClaudeCliStatus, String> {
     log::trace!("Checking Claude CLI installation status");
 
+    if let Some(scenario) = crate::mock::active_scenario() {
+        return Ok(ClaudeCliStatus {
+            installed: scenario.cli_installed,
+            version: scenario
+                .cli_installed
+                .then(|| crate::mock::MOCK_CLI_VERSION.to_string()),
+            path: None,
+        });
+    }
+
     let binary_path = get_cli_binary_path(&app)?;
 
     if !binary_path.exists() {
@@ -107,6 +124,7 @@ pub async fn check_claude_cli_installed(app: AppHandle) -> Result<ClaudeCliStatu
             None
         }
     };
+    crate::crash_reports::set_cli_version(version.clone());
 
     Ok(ClaudeCliStatus {
         installed: true,
@@ -139,7 +157,9 @@ struct Manifest {
 pub async fn get_available_cli_versions() -> Result<Vec<ReleaseInfo>, String> {
     log::trace!("Fetching available Claude CLI versions from npm registry");
 
-    let client = reqwest::Client::new();
+    let client = crate::http_client::client_builder()
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
     let response = client
         .get("https://registry.npmjs.org/@anthropic-ai/claude-code")
         .send()
@@ -196,12 +216,39 @@ pub async fn get_available_cli_versions() -> Result<Vec<ReleaseInfo>, String> {
     Ok(versions)
 }
 
+/// Claude CLI versions installed for pinning
+#[tauri::command]
+pub async fn list_installed_cli_versions(app: AppHandle) -> Vec<String> {
+    super::versions::installed_versions(&app)
+}
+
+/// Install a Claude CLI version for pinning, beside the default install
+#[tauri::command]
+pub async fn install_cli_version(app: AppHandle, version: String) -> Result<(), String> {
+    log::trace!("Installing pinned Claude CLI version: {version}");
+    let _power_guard = crate::power::PowerGuard::acquire(&app, "Claude CLI install").await;
+    super::versions::install_version(&app, &version).await?;
+    Ok(())
+}
+
+/// Claude CLI version pinned for a worktree's sessions, if any
+#[tauri::command]
+pub async fn get_pinned_cli_version(
+    app: AppHandle,
+    worktree_id: String,
+    worktree_path: String,
+) -> Option<super::versions::PinnedCliVersion> {
+    super::versions::pinned_version(&app, &worktree_id, std::path::Path::new(&worktree_path))
+}
+
 /// Fetch the latest version string from the distribution bucket
-async fn fetch_latest_version() -> Result<String, String> {
+pub(crate) async fn fetch_latest_version() -> Result<String, String> {
     let url = format!("{CLAUDE_DIST_BUCKET}/latest");
     log::trace!("Fetching latest version from {url}");
 
-    let client = reqwest::Client::new();
+    let client = crate::http_client::client_builder()
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
     let response = client
         .get(&url)
         .send()
@@ -262,7 +309,9 @@ async fn fetch_manifest(version: &str) -> Result<Manifest, String> {
     let url = format!("{CLAUDE_DIST_BUCKET}/{version}/manifest.json");
     log::trace!("Fetching manifest from {url}");
 
-    let client = reqwest::Client::new();
+    let client = crate::http_client::client_builder()
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
     let response = client
         .get(&url)
         .send()
@@ -283,7 +332,7 @@ async fn fetch_manifest(version: &str) -> Result<Manifest, String> {
 }
 
 /// Verify SHA256 checksum of downloaded data
-fn verify_checksum(data: &[u8], expected: &str) -> Result<(), String> {
+pub(crate) fn verify_checksum(data: &[u8], expected: &str) -> Result<(), String> {
     let mut hasher = Sha256::new();
     hasher.update(data);
     let computed = format!("{:x}", hasher.finalize());
@@ -300,7 +349,20 @@ fn verify_checksum(data: &[u8], expected: &str) -> Result<(), String> {
 #[tauri::command]
 pub async fn install_claude_cli(app: AppHandle, version: Option<String>) -> Result<(), String> {
     log::trace!("Installing Claude CLI, version: {:?}", version);
+    let _power_guard = crate::power::PowerGuard::acquire(&app, "Claude CLI install").await;
 
+    // Restart the install if the system slept or the network changed mid-download
+    let result = crate::power::monitor::with_wake_retry(&app, "Claude CLI", || {
+        install_claude_cli_once(app.clone(), version.clone())
+    })
+    .await;
+    if let Err(e) = &result {
+        crate::announcements::announce_install_failure(&app, "Claude CLI", e).await;
+    }
+    result
+}
+
+async fn install_claude_cli_once(app: AppHandle, version: Option<String>) -> Result<(), String> {
     // Check if any Claude processes are running - cannot replace binary while in use
     let running_sessions = crate::chat::registry::get_running_sessions();
     if !running_sessions.is_empty() {
@@ -312,22 +374,9 @@ pub async fn install_claude_cli(app: AppHandle, version: Option<String>) -> Resu
         ));
     }
 
-    let _cli_dir = ensure_cli_dir(&app)?;
-    let binary_path = get_cli_binary_path(&app)?;
-
     // Emit progress: starting
     emit_progress(&app, "starting", "Preparing installation...", 0);
 
-    // Determine version (use provided or fetch stable)
-    let version = match version {
-        Some(v) => v,
-        None => fetch_latest_version().await?,
-    };
-
-    // Detect platform
-    let platform = get_platform()?;
-    log::trace!("Installing version {version} for platform {platform}");
-
     // Fetch manifest and get expected checksum
     emit_progress(
         &app,
@@ -335,29 +384,16 @@ pub async fn install_claude_cli(app: AppHandle, version: Option<String>) -> Resu
         "Fetching release manifest...",
         10,
     );
-    let manifest = fetch_manifest(&version).await?;
-    let expected_checksum = manifest
-        .platforms
-        .get(platform)
-        .ok_or_else(|| format!("No checksum found for platform {platform}"))?
-        .checksum
-        .clone();
-    log::trace!("Expected checksum for {platform}: {expected_checksum}");
-
-    // Build download URL
-    let binary_name = if cfg!(windows) {
-        "claude.exe"
-    } else {
-        "claude"
-    };
-    let download_url = format!("{CLAUDE_DIST_BUCKET}/{version}/{platform}/{binary_name}");
+    let (download_url, expected_checksum) = resolve_claude_download(version).await?;
     log::trace!("Downloading from: {download_url}");
 
     // Emit progress: downloading
     emit_progress(&app, "downloading", "Downloading Claude CLI...", 25);
 
     // Download the binary
-    let client = reqwest::Client::new();
+    let client = crate::http_client::client_builder()
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
     let response = client
         .get(&download_url)
         .send()
@@ -371,17 +407,13 @@ pub async fn install_claude_cli(app: AppHandle, version: Option<String>) -> Resu
         ));
     }
 
-    // Get the binary content
-    let binary_content = response
-        .bytes()
-        .await
-        .map_err(|e| format!("Failed to read binary content: {e}"))?;
-
-    log::trace!(
-        "Downloaded {} bytes, saving to {:?}",
-        binary_content.len(),
-        binary_path
-    );
+    // Get the binary content (an emergency stop abandons the download)
+    let binary_content = crate::http_client::downloads::read_with_progress(
+        response,
+        "Claude CLI",
+        |downloaded, total| emit_download_progress(&app, downloaded, total),
+    )
+    .await?;
 
     // Verify checksum before writing to disk
     emit_progress(&app, "verifying_checksum", "Verifying checksum...", 55);
@@ -390,14 +422,81 @@ pub async fn install_claude_cli(app: AppHandle, version: Option<String>) -> Resu
 
     // Emit progress: installing
     emit_progress(&app, "installing", "Installing Claude CLI...", 65);
+    install_claude_binary(&app, &binary_content)?;
+
+    // Emit progress: complete
+    emit_progress(&app, "complete", "Installation complete!", 100);
+    Ok(())
+}
 
+/// Resolve the download URL and expected SHA256 checksum of a release
+/// (the latest stable one when `version` is None)
+pub(crate) async fn resolve_claude_download(
+    version: Option<String>,
+) -> Result<(String, String), String> {
+    // Determine version (use provided or fetch stable)
+    let version = match version {
+        Some(v) => v,
+        None => fetch_latest_version().await?,
+    };
+
+    // Detect platform
+    let platform = get_platform()?;
+    log::trace!("Installing version {version} for platform {platform}");
+
+    let manifest = fetch_manifest(&version).await?;
+    let expected_checksum = manifest
+        .platforms
+        .get(platform)
+        .ok_or_else(|| format!("No checksum found for platform {platform}"))?
+        .checksum
+        .clone();
+    log::trace!("Expected checksum for {platform}: {expected_checksum}");
+
+    // Build download URL
+    let binary_name = if cfg!(windows) {
+        "claude.exe"
+    } else {
+        "claude"
+    };
+    Ok((
+        format!("{CLAUDE_DIST_BUCKET}/{version}/{platform}/{binary_name}"),
+        expected_checksum,
+    ))
+}
+
+/// Verify a downloaded binary against its expected checksum and install it
+pub(crate) fn verify_and_install_claude_binary(
+    app: &AppHandle,
+    binary_content: &[u8],
+    expected_checksum: &str,
+) -> Result<(), String> {
+    verify_checksum(binary_content, expected_checksum)?;
+    install_claude_binary(app, binary_content)
+}
+
+/// Write a verified binary to the CLI directory and make it executable
+fn install_claude_binary(app: &AppHandle, binary_content: &[u8]) -> Result<(), String> {
+    let _cli_dir = ensure_cli_dir(app)?;
+    let binary_path = get_cli_binary_path(app)?;
+    write_claude_binary(&binary_path, binary_content)?;
+
+    log::trace!("Claude CLI installed successfully at {:?}", binary_path);
+    Ok(())
+}
+
+/// Write a verified binary to `binary_path` and make it executable
+pub(crate) fn write_claude_binary(
+    binary_path: &std::path::Path,
+    binary_content: &[u8],
+) -> Result<(), String> {
     // Write the binary to the target path
     log::trace!("Creating binary file at {:?}", binary_path);
-    let mut file = std::fs::File::create(&binary_path)
+    let mut file = std::fs::File::create(binary_path)
         .map_err(|e| format!("Failed to create binary file: {e}"))?;
 
     log::trace!("Writing {} bytes to binary file", binary_content.len());
-    file.write_all(&binary_content)
+    file.write_all(binary_content)
         .map_err(|e| format!("Failed to write binary file: {e}"))?;
     log::trace!("Binary file written successfully");
 
@@ -409,30 +508,17 @@ pub async fn install_claude_cli(app: AppHandle, version: Option<String>) -> Resu
             "Setting executable permissions (0o755) on {:?}",
             binary_path
         );
-        let mut perms = std::fs::metadata(&binary_path)
+        let mut perms = std::fs::metadata(binary_path)
             .map_err(|e| format!("Failed to get binary metadata: {e}"))?
             .permissions();
         perms.set_mode(0o755);
-        std::fs::set_permissions(&binary_path, perms)
+        std::fs::set_permissions(binary_path, perms)
             .map_err(|e| format!("Failed to set binary permissions: {e}"))?;
         log::trace!("Executable permissions set successfully");
     }
 
-    // Remove macOS quarantine attribute to allow execution
-    #[cfg(target_os = "macos")]
-    {
-        log::trace!("Removing quarantine attribute from {:?}", binary_path);
-        let _ = silent_command("xattr")
-            .args(["-d", "com.apple.quarantine"])
-            .arg(&binary_path)
-            .output();
-        // Ignore errors - attribute might not exist
-    }
-
-    // Emit progress: complete
-    emit_progress(&app, "complete", "Installation complete!", 100);
-
-    log::trace!("Claude CLI installed successfully at {:?}", binary_path);
+    // The checksum was verified: drop the quarantine attribute / Mark of the Web
+    crate::platform::motw::release_verified_binary(binary_path);
     Ok(())
 }
 
@@ -450,6 +536,13 @@ pub struct ClaudeAuthStatus {
 pub async fn check_claude_cli_auth(app: AppHandle) -> Result<ClaudeAuthStatus, String> {
     log::trace!("Checking Claude CLI authentication status");
 
+    if let Some(scenario) = crate::mock::active_scenario() {
+        return Ok(ClaudeAuthStatus {
+            authenticated: scenario.cli_authenticated,
+            error: (!scenario.cli_authenticated).then(|| "Not authenticated (mock)".to_string()),
+        });
+    }
+
     let binary_path = get_cli_binary_path(&app)?;
 
     if !binary_path.exists() {
@@ -477,6 +570,7 @@ pub async fn check_claude_cli_auth(app: AppHandle) -> Result<ClaudeAuthStatus, S
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
         log::trace!("Claude CLI auth check successful, response: {}", stdout);
+        clear_auth_failure();
         Ok(ClaudeAuthStatus {
             authenticated: true,
             error: None,
@@ -484,6 +578,10 @@ pub async fn check_claude_cli_auth(app: AppHandle) -> Result<ClaudeAuthStatus, S
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
         log::warn!("Claude CLI auth check failed: {}", stderr);
+        record_auth_failure(&format!(
+            "{stderr}\n{}",
+            String::from_utf8_lossy(&output.stdout)
+        ));
         Ok(ClaudeAuthStatus {
             authenticated: false,
             error: Some(stderr),
@@ -497,6 +595,27 @@ fn emit_progress(app: &AppHandle, stage: &str, message: &str, percent: u8) {
         stage: stage.to_string(),
         message: message.to_string(),
         percent,
+        downloaded_bytes: None,
+        total_bytes: None,
+    };
+
+    if let Err(e) = app.emit_all("claude-cli:install-progress", &progress) {
+        log::warn!("Failed to emit install progress: {}", e);
+    }
+}
+
+/// Emit progress of the binary download (25-55%)
+fn emit_download_progress(app: &AppHandle, downloaded: u64, total: Option<u64>) {
+    use crate::http_client::downloads::{format_progress, stage_percent};
+    let progress = InstallProgress {
+        stage: "downloading".to_string(),
+        message: format!(
+            "Downloading Claude CLI ({})...",
+            format_progress(downloaded, total)
+        ),
+        percent: stage_percent(downloaded, total, 25, 55),
+        downloaded_bytes: Some(downloaded),
+        total_bytes: total,
     };
 
     if let Err(e) = app.emit_all("claude-cli:install-progress", &progress) {
@@ -1,7 +1,8 @@
 //! Configuration and path management for the embedded Claude CLI
 
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+
+use crate::runtime::PathProvider;
 
 /// Directory name for storing the Claude CLI binary
 pub const CLI_DIR_NAME: &str = "claude-cli";
@@ -15,23 +16,20 @@ pub const CLI_BINARY_NAME: &str = "claude";
 /// Get the directory where Claude CLI is installed
 ///
 /// Returns: `~/Library/Application Support/jean/claude-cli/`
-pub fn get_cli_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+pub fn get_cli_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    let app_data_dir = app.shared_data_dir()?;
     Ok(app_data_dir.join(CLI_DIR_NAME))
 }
 
 /// Get the full path to the Claude CLI binary
 ///
 /// Returns: `~/Library/Application Support/jean/claude-cli/claude`
-pub fn get_cli_binary_path(app: &AppHandle) -> Result<PathBuf, String> {
+pub fn get_cli_binary_path(app: &impl PathProvider) -> Result<PathBuf, String> {
     Ok(get_cli_dir(app)?.join(CLI_BINARY_NAME))
 }
 
 /// Ensure the CLI directory exists, creating it if necessary
-pub fn ensure_cli_dir(app: &AppHandle) -> Result<PathBuf, String> {
+pub fn ensure_cli_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
     let cli_dir = get_cli_dir(app)?;
     std::fs::create_dir_all(&cli_dir)
         .map_err(|e| format!("Failed to create CLI directory: {e}"))?;
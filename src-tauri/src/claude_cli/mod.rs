@@ -1,10 +1,13 @@
 //! Claude CLI management module
 //!
 //! Handles downloading, installing, and managing the Claude CLI binary
-//! embedded within the Jean application.
+//! embedded within the Jean application, and its subscription login state.
 
+mod auth;
 mod commands;
 mod config;
+pub mod versions;
 
+pub use auth::*;
 pub use commands::*;
 pub use config::*;
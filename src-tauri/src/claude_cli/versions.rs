@@ -0,0 +1,287 @@
+//! Per-project Claude CLI versions
+//!
+//! A project can pin the Claude CLI version its sessions run with, in its
+//! settings or in a checked-in `jean.json` (`"cli": { "claude": "1.0.30" }`);
+//! the project setting wins. Pinned versions are installed next to the
+//! default one, in `claude-cli/versions/{version}/`. When a pinned version
+//! isn't installed, the session runs with the default install, a warning is
+//! emitted and the pinned version is installed in the background for the
+//! next run.
+//!
+//! Codex is installed through the system package manager rather than by
+//! Jean, so only the Claude CLI can be pinned.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::commands::{resolve_claude_download, verify_checksum, write_claude_binary};
+use super::config::{get_cli_binary_path, get_cli_dir, CLI_BINARY_NAME};
+use crate::http_server::EmitExt;
+use crate::projects::types::JeanConfig;
+use crate::runtime::PathProvider;
+
+/// Subdirectory of the CLI directory holding pinned versions
+const VERSIONS_DIR: &str = "versions";
+
+/// Versions being installed in the background
+static INSTALLING: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Where a pin comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PinSource {
+    ProjectSettings,
+    JeanJson,
+}
+
+/// Claude CLI version pinned for a worktree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PinnedCliVersion {
+    pub version: String,
+    pub source: PinSource,
+    pub installed: bool,
+}
+
+/// Payload of `claude-cli:pinned-version-missing`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedVersionMissing {
+    pub worktree_id: String,
+    pub version: String,
+}
+
+/// Reject anything that isn't a plain version (it becomes a path segment)
+pub fn validate_version(version: &str) -> Result<(), String> {
+    let valid = !version.is_empty()
+        && version.len() <= 64
+        && version.starts_with(|c: char| c.is_ascii_digit())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'));
+    if !valid {
+        return Err(format!("Invalid Claude CLI version: {version}"));
+    }
+    Ok(())
+}
+
+/// Path of a pinned version's binary (which may not be installed)
+pub fn version_binary_path(app: &impl PathProvider, version: &str) -> Result<PathBuf, String> {
+    validate_version(version)?;
+    Ok(get_cli_dir(app)?
+        .join(VERSIONS_DIR)
+        .join(version)
+        .join(CLI_BINARY_NAME))
+}
+
+/// Installed pinned versions, sorted
+pub fn installed_versions(app: &impl PathProvider) -> Vec<String> {
+    let Ok(dir) = get_cli_dir(app).map(|d| d.join(VERSIONS_DIR)) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join(CLI_BINARY_NAME).is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    versions.sort();
+    versions
+}
+
+/// The project setting wins over jean.json; blank pins are ignored
+fn choose_pin(
+    project_pin: Option<&str>,
+    jean_config: Option<&JeanConfig>,
+) -> Option<(String, PinSource)> {
+    let from_project = project_pin
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| (v.to_string(), PinSource::ProjectSettings));
+    from_project.or_else(|| {
+        let version = jean_config?.cli.claude.as_deref()?.trim();
+        (!version.is_empty()).then(|| (version.to_string(), PinSource::JeanJson))
+    })
+}
+
+/// Version pinned for a worktree, if any
+pub fn pinned_version(
+    app: &AppHandle,
+    worktree_id: &str,
+    worktree_path: &Path,
+) -> Option<PinnedCliVersion> {
+    let data = crate::projects::storage::load_projects_data(app).ok();
+    let worktree = data.as_ref().and_then(|d| d.find_worktree(worktree_id));
+    let project_pin = worktree.and_then(|w| {
+        let data = data.as_ref()?;
+        data.find_project(&w.project_id)?.cli_version.clone()
+    });
+    // jean.json is at the worktree root, `worktree_path` may be a sub-project
+    let root = worktree.map_or_else(
+        || worktree_path.to_string_lossy(),
+        |w| w.path.as_str().into(),
+    );
+    let jean_config = crate::projects::git::read_jean_config(&root);
+    let (version, source) = choose_pin(project_pin.as_deref(), jean_config.as_ref())?;
+    let installed = version_binary_path(app, &version).is_ok_and(|path| path.is_file());
+    Some(PinnedCliVersion {
+        version,
+        source,
+        installed,
+    })
+}
+
+/// Binary for a worktree: the pinned version if installed, else the default
+pub fn binary_for_worktree(
+    app: &AppHandle,
+    worktree_id: &str,
+    worktree_path: &Path,
+) -> Result<PathBuf, String> {
+    match pinned_version(app, worktree_id, worktree_path) {
+        Some(pin) if pin.installed => version_binary_path(app, &pin.version),
+        _ => get_cli_binary_path(app),
+    }
+}
+
+/// Binary to spawn a session with; warns and starts installing a pinned
+/// version that's missing
+pub fn resolve_binary(
+    app: &AppHandle,
+    worktree_id: &str,
+    worktree_path: &Path,
+) -> Result<PathBuf, String> {
+    match pinned_version(app, worktree_id, worktree_path) {
+        Some(pin) if pin.installed => return version_binary_path(app, &pin.version),
+        Some(pin) => match validate_version(&pin.version) {
+            Ok(()) => {
+                log::warn!(
+                    "Pinned Claude CLI {} is not installed, using the default install",
+                    pin.version
+                );
+                let _ = app.emit_all(
+                    "claude-cli:pinned-version-missing",
+                    &PinnedVersionMissing {
+                        worktree_id: worktree_id.to_string(),
+                        version: pin.version.clone(),
+                    },
+                );
+                install_in_background(app, pin.version);
+            }
+            Err(e) => log::warn!("Ignoring pinned Claude CLI version: {e}"),
+        },
+        None => {}
+    }
+    get_cli_binary_path(app)
+}
+
+fn install_in_background(app: &AppHandle, version: String) {
+    if !INSTALLING.lock().unwrap().insert(version.clone()) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match install_version(&app, &version).await {
+            Ok(_) => log::info!("Installed pinned Claude CLI {version}"),
+            Err(e) => log::error!("Failed to install pinned Claude CLI {version}: {e}"),
+        }
+        INSTALLING.lock().unwrap().remove(&version);
+    });
+}
+
+/// Download, verify and install a version into the version store
+pub async fn install_version(app: &AppHandle, version: &str) -> Result<PathBuf, String> {
+    let binary_path = version_binary_path(app, version)?;
+    let (download_url, expected_checksum) =
+        resolve_claude_download(Some(version.to_string())).await?;
+    log::trace!("Downloading Claude CLI {version} from: {download_url}");
+
+    let client = crate::http_client::client_builder()
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+    let response = client
+        .get(&download_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download Claude CLI {version}: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to download Claude CLI {version}: HTTP {}",
+            response.status()
+        ));
+    }
+
+    let download = crate::emergency::DownloadGuard::start();
+    let binary_content = tokio::select! {
+        content = response.bytes() => {
+            content.map_err(|e| format!("Failed to read binary content: {e}"))?
+        }
+        _ = download.stopped() => return Err(crate::emergency::DOWNLOAD_CANCELLED.to_string()),
+    };
+    verify_checksum(&binary_content, &expected_checksum)?;
+
+    // Write beside the final path so a failed install never looks installed
+    let dir = binary_path.parent().ok_or("Invalid Claude CLI path")?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create version directory: {e}"))?;
+    let partial = binary_path.with_extension("part");
+    write_claude_binary(&partial, &binary_content)?;
+    fs::rename(&partial, &binary_path)
+        .map_err(|e| format!("Failed to install Claude CLI {version}: {e}"))?;
+    Ok(binary_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projects::types::JeanCli;
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_validate_version() {
+        assert!(validate_version("1.0.30").is_ok());
+        assert!(validate_version("2.1.0-beta.1").is_ok());
+        assert!(validate_version("").is_err());
+        assert!(validate_version("../1.0").is_err());
+        assert!(validate_version("1.0/claude").is_err());
+    }
+
+    #[test]
+    fn test_installed_versions() {
+        let paths = TempPaths::new();
+        let installed = version_binary_path(&paths, "1.0.30").unwrap();
+        fs::create_dir_all(installed.parent().unwrap()).unwrap();
+        fs::write(&installed, b"binary").unwrap();
+        // A version directory without its binary is a failed install
+        fs::create_dir_all(
+            version_binary_path(&paths, "1.0.31")
+                .unwrap()
+                .parent()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(installed_versions(&paths), vec!["1.0.30".to_string()]);
+    }
+
+    #[test]
+    fn test_project_pin_wins_over_jean_json() {
+        let config = JeanConfig {
+            cli: JeanCli {
+                claude: Some("1.0.30".to_string()),
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            choose_pin(Some("1.0.2"), Some(&config)),
+            Some(("1.0.2".to_string(), PinSource::ProjectSettings))
+        );
+        assert_eq!(
+            choose_pin(Some(" "), Some(&config)),
+            Some(("1.0.30".to_string(), PinSource::JeanJson))
+        );
+        assert_eq!(choose_pin(None, None), None);
+    }
+}
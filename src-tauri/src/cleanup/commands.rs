@@ -0,0 +1,21 @@
+use tauri::AppHandle;
+
+use super::{CleanupReport, StaleItem};
+
+/// List stale temp dirs, partial downloads and orphaned scratch dirs
+#[tauri::command]
+pub async fn find_stale_files(app: AppHandle) -> Result<Vec<StaleItem>, String> {
+    super::find_stale(&app, crate::runtime::now())
+}
+
+/// Remove stale temp dirs, partial downloads and orphaned scratch dirs
+#[tauri::command]
+pub async fn clean_stale_files(app: AppHandle) -> Result<CleanupReport, String> {
+    let report = super::clean(&app, crate::runtime::now())?;
+    log::info!(
+        "Removed {} stale item(s), reclaimed {} bytes",
+        report.removed.len(),
+        report.reclaimed_bytes
+    );
+    Ok(report)
+}
@@ -0,0 +1,239 @@
+//! Stale temp file cleanup
+//!
+//! Failed or interrupted installs leave extraction directories (`temp`,
+//! `temp-{tool}`, `*.partial`) and partial downloads (`*.part`) in the
+//! managed tool directories, and deleted sessions can leave their scratch
+//! directory behind. These are found at startup and on demand, and removed
+//! once nothing has touched them for an hour, so installs in progress are
+//! left alone. Only the known tool directories are scanned: the rest of the
+//! data dir (e.g. a profile with the id `temp`) is user data. Resumable
+//! downloads (e.g. `gh-cli/downloads/*.part`) are kept so they can pick up
+//! where they left off, however long ago that was.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat::scratch::dir_usage;
+use crate::runtime::PathProvider;
+
+pub mod commands;
+
+/// Seconds without changes before a leftover counts as stale
+const STALE_AFTER_SECS: u64 = 60 * 60;
+
+/// Directories of the shared data dir that installs unpack and download into
+const TOOL_DIRS: &[&str] = &[
+    crate::gh_cli::config::GH_CLI_DIR_NAME,
+    crate::claude_cli::CLI_DIR_NAME,
+    crate::tool_install::helpers::HELPER_TOOLS_DIR,
+    crate::tool_install::node::RUNTIME_DIR,
+    crate::python_env::RUNTIME_DIR,
+    crate::speech::config::SPEECH_MODELS_DIR_NAME,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleKind {
+    /// Extraction directory of an install (`temp`, `temp-{tool}`, `*.partial`)
+    TempDir,
+    /// Download that never finished (`*.part`)
+    PartialDownload,
+    /// Scratch directory of a session that no longer exists
+    OrphanedScratch,
+}
+
+/// A leftover that can be removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleItem {
+    pub path: String,
+    pub kind: StaleKind,
+    pub size_bytes: u64,
+    /// Unix seconds of the latest change inside
+    pub last_modified: Option<u64>,
+}
+
+/// Result of a cleanup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CleanupReport {
+    pub removed: Vec<StaleItem>,
+    pub reclaimed_bytes: u64,
+    /// Paths that couldn't be removed, with the error
+    pub failed: Vec<String>,
+}
+
+fn modified_secs(path: &Path) -> Option<u64> {
+    fs::symlink_metadata(path)
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+fn classify(path: &Path) -> Option<StaleKind> {
+    let name = path.file_name()?.to_string_lossy();
+    let is_dir = path.is_dir();
+    if is_dir && (name == "temp" || name.starts_with("temp-") || name.ends_with(".partial")) {
+        Some(StaleKind::TempDir)
+    } else if !is_dir && name.ends_with(".part") {
+        Some(StaleKind::PartialDownload)
+    } else {
+        None
+    }
+}
+
+fn item(path: PathBuf, kind: StaleKind, now: u64) -> Option<StaleItem> {
+    let (size_bytes, last_modified) = if path.is_dir() {
+        let (size, _, modified) = dir_usage(&path);
+        (size, modified.max(modified_secs(&path)))
+    } else {
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        (size, modified_secs(&path))
+    };
+    if last_modified.unwrap_or(0).saturating_add(STALE_AFTER_SECS) > now {
+        return None;
+    }
+    Some(StaleItem {
+        path: path.to_string_lossy().to_string(),
+        kind,
+        size_bytes,
+        last_modified,
+    })
+}
+
+/// Leftovers in the tool directories: each one's staging sibling (e.g.
+/// `node-runtime.partial`) and its direct children (e.g. `gh-cli/temp`,
+/// `helper-tools/temp-codex`, `speech-models/*.part`)
+fn find_install_leftovers(root: &Path, now: u64) -> Vec<StaleItem> {
+    let mut items = Vec::new();
+    for name in TOOL_DIRS {
+        let staging = root.join(format!("{name}.partial"));
+        if staging.is_dir() {
+            items.extend(item(staging, StaleKind::TempDir, now));
+        }
+        for child in fs::read_dir(root.join(name))
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let child = child.path();
+            if let Some(kind) = classify(&child) {
+                items.extend(item(child, kind, now));
+            }
+        }
+    }
+    items
+}
+
+/// Scratch directories whose session has no metadata (deleted sessions)
+fn find_orphaned_scratch(app: &impl PathProvider, now: u64) -> Vec<StaleItem> {
+    let Ok(data_dir) = crate::chat::storage::get_data_dir(app) else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(&data_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            let session_dir = entry.path();
+            session_dir.join("scratch").is_dir()
+                && !session_dir.join("metadata.json").exists()
+                && !crate::chat::registry::is_process_running(&entry.file_name().to_string_lossy())
+        })
+        .filter_map(|entry| {
+            item(
+                entry.path().join("scratch"),
+                StaleKind::OrphanedScratch,
+                now,
+            )
+        })
+        .collect()
+}
+
+/// Stale leftovers, largest first
+pub fn find_stale(app: &impl PathProvider, now: u64) -> Result<Vec<StaleItem>, String> {
+    let mut items = find_install_leftovers(&app.shared_data_dir()?, now);
+    items.extend(find_orphaned_scratch(app, now));
+    items.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    Ok(items)
+}
+
+/// Remove stale leftovers
+pub fn clean(app: &impl PathProvider, now: u64) -> Result<CleanupReport, String> {
+    let mut report = CleanupReport::default();
+    for item in find_stale(app, now)? {
+        let path = Path::new(&item.path);
+        let result = if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        };
+        match result {
+            Ok(()) => {
+                log::trace!("Removed stale {:?}: {}", item.kind, item.path);
+                report.reclaimed_bytes += item.size_bytes;
+                report.removed.push(item);
+            }
+            Err(e) => {
+                log::warn!("Failed to remove {}: {e}", item.path);
+                report.failed.push(format!("{}: {e}", item.path));
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_clean_removes_stale_leftovers() {
+        let paths = TempPaths::new();
+        let root = paths.shared_data_dir().unwrap();
+        fs::create_dir_all(root.join("gh-cli/temp")).unwrap();
+        fs::write(root.join("gh-cli/temp/gh"), [0u8; 10]).unwrap();
+        fs::write(root.join("gh-cli/gh"), b"binary").unwrap();
+        fs::create_dir_all(root.join("node-runtime.partial")).unwrap();
+        fs::create_dir_all(root.join("speech-models")).unwrap();
+        fs::write(root.join("speech-models/base.bin.part"), [0u8; 5]).unwrap();
+        let sessions = root.join("sessions/data");
+        fs::create_dir_all(sessions.join("live/scratch")).unwrap();
+        fs::write(sessions.join("live/metadata.json"), "{}").unwrap();
+        fs::create_dir_all(sessions.join("gone/scratch")).unwrap();
+        fs::write(sessions.join("gone/scratch/out.txt"), [0u8; 3]).unwrap();
+        // Profiles named "Temp" / "Temp work" and unknown dirs are user data
+        fs::create_dir_all(root.join("profiles/temp")).unwrap();
+        fs::write(root.join("profiles/temp/preferences.json"), "{}").unwrap();
+        fs::create_dir_all(root.join("profiles/temp-work")).unwrap();
+        fs::create_dir_all(root.join("temp")).unwrap();
+        fs::create_dir_all(root.join("exports/report.partial")).unwrap();
+        // Resumable downloads are kept
+        let resumable = root.join("gh-cli/downloads/gh_2.0.0_linux_amd64.tar.gz.part");
+        fs::create_dir_all(resumable.parent().unwrap()).unwrap();
+        fs::write(&resumable, [0u8; 7]).unwrap();
+
+        let now = modified_secs(&root).unwrap();
+        // Nothing is stale right after being written
+        assert!(find_stale(&paths, now).unwrap().is_empty());
+
+        let report = clean(&paths, now + STALE_AFTER_SECS + 60).unwrap();
+        assert_eq!(report.removed.len(), 4);
+        assert_eq!(report.reclaimed_bytes, 18);
+        assert!(report.failed.is_empty());
+        assert!(!root.join("gh-cli/temp").exists());
+        assert!(root.join("gh-cli/gh").exists());
+        assert!(!root.join("node-runtime.partial").exists());
+        assert!(!root.join("speech-models/base.bin.part").exists());
+        assert!(sessions.join("live/scratch").exists());
+        assert!(!sessions.join("gone/scratch").exists());
+        assert!(root.join("profiles/temp/preferences.json").exists());
+        assert!(root.join("profiles/temp-work").exists());
+        assert!(root.join("temp").exists());
+        assert!(root.join("exports/report.partial").exists());
+        assert!(resumable.exists());
+    }
+}
@@ -0,0 +1,334 @@
+//! Argument lists checked against a CLI's probed capabilities
+//!
+//! Spawners add flags through an [`ArgBuilder`] instead of pushing strings.
+//! Optional flags the CLI doesn't know are dropped; any other unsupported
+//! flag, a value outside the allowed set, or a bad combination becomes an
+//! [`ArgError`], so nothing is spawned that the CLI would reject.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use super::{CliCapabilities, OptionalFlag};
+
+/// Why an argument list can't be passed to the CLI
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArgError {
+    pub flag: String,
+    pub message: String,
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.flag, self.message)
+    }
+}
+
+/// One message for all errors, e.g. for a chat error event
+pub fn describe_errors(cli: &str, errors: &[ArgError]) -> String {
+    let details: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    format!(
+        "Can't start {cli} ({}). Please update it in Settings > Advanced.",
+        details.join("; ")
+    )
+}
+
+/// A constraint on which flags and values go together
+pub enum Rule {
+    /// `flag` (with `value`, if given) only works together with `requires`
+    Requires {
+        flag: &'static str,
+        value: Option<&'static str>,
+        requires: &'static str,
+    },
+    /// `flag` and `other` can't be combined
+    Conflicts {
+        flag: &'static str,
+        other: &'static str,
+    },
+    /// `flag`'s value must be one of `values`
+    OneOf {
+        flag: &'static str,
+        values: &'static [&'static str],
+    },
+}
+
+/// Builds one CLI invocation's arguments
+pub struct ArgBuilder<'a> {
+    capabilities: &'a CliCapabilities,
+    optional: &'a [OptionalFlag],
+    args: Vec<(String, Option<String>)>,
+    errors: Vec<ArgError>,
+}
+
+impl<'a> ArgBuilder<'a> {
+    pub fn new(capabilities: &'a CliCapabilities, optional: &'a [OptionalFlag]) -> Self {
+        Self {
+            capabilities,
+            optional,
+            args: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Add a flag without a value
+    pub fn flag(&mut self, flag: &str) -> &mut Self {
+        self.push(flag, None)
+    }
+
+    /// Add a flag followed by its value
+    pub fn option(&mut self, flag: &str, value: impl Into<String>) -> &mut Self {
+        self.push(flag, Some(value.into()))
+    }
+
+    /// Add user-configured arguments: `--flag`, `--flag value` or
+    /// `--flag=value`. `reserved` flags are Jean's own and can't be set.
+    pub fn user_args(&mut self, args: &[String], reserved: &[&str]) -> &mut Self {
+        let mut tokens = args.iter().peekable();
+        while let Some(token) = tokens.next() {
+            let (flag, value) = match token.split_once('=') {
+                Some((flag, value)) if token.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (
+                    token.clone(),
+                    tokens.next_if(|next| !next.starts_with('-')).cloned(),
+                ),
+            };
+            if !flag.starts_with("--") || flag.len() < 3 {
+                self.error(&flag, "expected a --flag".to_string());
+            } else if reserved.contains(&flag.as_str()) {
+                self.error(&flag, "set by Jean, can't be a default".to_string());
+            } else {
+                self.push(&flag, value);
+            }
+        }
+        self
+    }
+
+    fn error(&mut self, flag: &str, message: String) {
+        if !self.errors.iter().any(|e| e.flag == flag) {
+            self.errors.push(ArgError {
+                flag: flag.to_string(),
+                message,
+            });
+        }
+    }
+
+    fn push(&mut self, flag: &str, value: Option<String>) -> &mut Self {
+        if !self.capabilities.supports(flag) {
+            if self.optional.iter().any(|o| o.flag == flag) {
+                log::warn!(
+                    "{} {} doesn't support {flag}, leaving it out",
+                    self.capabilities.cli,
+                    self.version()
+                );
+            } else {
+                let message = format!("not supported by version {}", self.version());
+                self.error(flag, message);
+            }
+            return self;
+        }
+        self.args.push((flag.to_string(), value));
+        self
+    }
+
+    fn version(&self) -> &str {
+        self.capabilities.version.as_deref().unwrap_or("(unknown)")
+    }
+
+    fn values(&self, flag: &str) -> impl Iterator<Item = Option<&str>> {
+        self.args
+            .iter()
+            .filter(move |(f, _)| f == flag)
+            .map(|(_, v)| v.as_deref())
+    }
+
+    fn has(&self, flag: &str) -> bool {
+        self.values(flag).next().is_some()
+    }
+
+    /// The arguments in order, or every reason they'd be rejected
+    pub fn build(mut self, rules: &[Rule]) -> Result<Vec<String>, Vec<ArgError>> {
+        for rule in rules {
+            let error = match *rule {
+                Rule::Requires {
+                    flag,
+                    value,
+                    requires,
+                } => {
+                    let applies = self.values(flag).any(|v| value.is_none() || v == value);
+                    (applies && !self.has(requires)).then(|| ArgError {
+                        flag: flag.to_string(),
+                        message: match value {
+                            Some(value) => format!("{value} requires {requires}"),
+                            None => format!("requires {requires}"),
+                        },
+                    })
+                }
+                Rule::Conflicts { flag, other } => {
+                    (self.has(flag) && self.has(other)).then(|| ArgError {
+                        flag: flag.to_string(),
+                        message: format!("can't be combined with {other}"),
+                    })
+                }
+                Rule::OneOf { flag, values } => self
+                    .values(flag)
+                    .find(|v| !v.is_some_and(|v| values.contains(&v)))
+                    .map(|v| ArgError {
+                        flag: flag.to_string(),
+                        message: format!(
+                            "invalid value {:?}, expected one of {}",
+                            v.unwrap_or(""),
+                            values.join(", ")
+                        ),
+                    }),
+            };
+            self.errors.extend(error);
+        }
+
+        if !self.errors.is_empty() {
+            return Err(self.errors);
+        }
+        Ok(self
+            .args
+            .into_iter()
+            .flat_map(|(flag, value)| std::iter::once(flag).chain(value))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli_capabilities::{CLAUDE_OPTIONAL_FLAGS, CLAUDE_RULES};
+
+    fn capabilities(flags: &[&str]) -> CliCapabilities {
+        CliCapabilities {
+            cli: "claude".to_string(),
+            version: Some("1.0.0".to_string()),
+            flags: flags.iter().map(|f| f.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn core(args: &mut ArgBuilder) {
+        args.flag("--print")
+            .option("--output-format", "stream-json")
+            .option("--input-format", "stream-json")
+            .flag("--verbose");
+    }
+
+    #[test]
+    fn test_optional_flags_are_dropped() {
+        let caps = capabilities(&[
+            "--print",
+            "--output-format",
+            "--input-format",
+            "--verbose",
+            "--resume",
+        ]);
+        let mut args = ArgBuilder::new(&caps, CLAUDE_OPTIONAL_FLAGS);
+        core(&mut args);
+        args.flag("--chrome")
+            .option("--settings", "{}")
+            .option("--resume", "abc");
+        assert_eq!(
+            args.build(CLAUDE_RULES).unwrap(),
+            [
+                "--print",
+                "--output-format",
+                "stream-json",
+                "--input-format",
+                "stream-json",
+                "--verbose",
+                "--resume",
+                "abc"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_and_invalid_args_are_errors() {
+        let caps = capabilities(&["--print", "--output-format", "--permission-mode"]);
+        let mut args = ArgBuilder::new(&caps, CLAUDE_OPTIONAL_FLAGS);
+        core(&mut args);
+        args.option("--permission-mode", "everything");
+        let errors = args.build(CLAUDE_RULES).unwrap_err();
+        let flags: Vec<&str> = errors.iter().map(|e| e.flag.as_str()).collect();
+        // --input-format and --verbose are unknown, so stream-json output lacks --verbose
+        assert_eq!(
+            flags,
+            [
+                "--input-format",
+                "--verbose",
+                "--output-format",
+                "--permission-mode"
+            ]
+        );
+        assert!(errors[0].message.contains("1.0.0"));
+        assert!(errors[3].message.contains("plan"));
+
+        let message = describe_errors("claude", &errors);
+        assert!(
+            message.contains("--input-format: not supported"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_user_args() {
+        let caps = capabilities(&["--print", "--max-turns", "--add-dir", "--verbose"]);
+        let mut args = ArgBuilder::new(&caps, CLAUDE_OPTIONAL_FLAGS);
+        args.flag("--print");
+        let user: Vec<String> = ["--max-turns=5", "--add-dir", "/tmp/a", "--verbose"]
+            .map(str::to_string)
+            .to_vec();
+        args.user_args(&user, &["--print"]);
+        assert_eq!(
+            args.build(&[]).unwrap(),
+            [
+                "--print",
+                "--max-turns",
+                "5",
+                "--add-dir",
+                "/tmp/a",
+                "--verbose"
+            ]
+        );
+
+        let mut args = ArgBuilder::new(&caps, CLAUDE_OPTIONAL_FLAGS);
+        let user: Vec<String> = ["stray", "--print", "--no-such-flag"]
+            .map(str::to_string)
+            .to_vec();
+        args.user_args(&user, &["--print"]);
+        let errors = args.build(&[]).unwrap_err();
+        let flags: Vec<&str> = errors.iter().map(|e| e.flag.as_str()).collect();
+        assert_eq!(flags, ["stray", "--print", "--no-such-flag"]);
+        assert!(errors[1].message.contains("set by Jean"));
+    }
+
+    #[test]
+    fn test_requires_and_conflicts() {
+        // A failed probe knows no flags, so only the rules apply
+        let caps = CliCapabilities::default();
+        let mut args = ArgBuilder::new(&caps, CLAUDE_OPTIONAL_FLAGS);
+        args.option("--input-format", "stream-json")
+            .option("--resume", "abc")
+            .flag("--continue");
+        let errors = args.build(CLAUDE_RULES).unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                ArgError {
+                    flag: "--input-format".to_string(),
+                    message: "requires --print".to_string(),
+                },
+                ArgError {
+                    flag: "--resume".to_string(),
+                    message: "can't be combined with --continue".to_string(),
+                },
+            ]
+        );
+    }
+}
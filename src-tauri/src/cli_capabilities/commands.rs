@@ -0,0 +1,27 @@
+//! Tauri commands for CLI capabilities
+
+use tauri::AppHandle;
+
+use super::{CliCapabilities, AIDER_OPTIONAL_FLAGS, CLAUDE_OPTIONAL_FLAGS};
+
+/// Probed version and flags of an installed agent CLI ("claude" or "aider")
+#[tauri::command]
+pub async fn get_cli_capabilities(app: AppHandle, cli: String) -> Result<CliCapabilities, String> {
+    let (binary, optional) = match cli.as_str() {
+        "claude" => (
+            crate::claude_cli::get_cli_binary_path(&app)?,
+            CLAUDE_OPTIONAL_FLAGS,
+        ),
+        "aider" => (
+            crate::python_env::resolve_entry_point(&app, "aider").ok_or("Aider not installed")?,
+            AIDER_OPTIONAL_FLAGS,
+        ),
+        other => return Err(format!("Unknown CLI: {other}")),
+    };
+    if !binary.exists() {
+        return Err(format!("{cli} is not installed"));
+    }
+    tauri::async_runtime::spawn_blocking(move || super::probe(&app, &cli, &binary, optional))
+        .await
+        .map_err(|e| format!("Failed to probe CLI: {e}"))
+}
@@ -0,0 +1,380 @@
+//! What each installed agent CLI version supports
+//!
+//! Flags come and go between CLI versions (resume, stream-json input,
+//! permission modes, `--chrome`...). Instead of failing mid-run with an
+//! "unknown option" error, the first spawn of a binary runs `--version` and
+//! `--help` once and records the flags it lists. Results are cached in
+//! `{app_data}/cli-capabilities.json`, keyed by CLI name and invalidated when
+//! the binary's size or modification time changes (i.e. it was updated).
+//!
+//! Spawners build their arguments with [`args::ArgBuilder`], which drops
+//! optional flags the CLI doesn't know (with a warning) and refuses to start
+//! with a clear message when anything else is unsupported or invalid. A
+//! failed probe knows no flags and gates nothing.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::platform::silent_command;
+use crate::runtime::PathProvider;
+use args::Rule;
+
+pub mod args;
+pub mod commands;
+
+const CACHE_FILE: &str = "cli-capabilities.json";
+
+/// A flag Jean can do without
+pub struct OptionalFlag {
+    pub flag: &'static str,
+    /// Feature name reported to the UI
+    pub feature: &'static str,
+}
+
+pub const CLAUDE_OPTIONAL_FLAGS: &[OptionalFlag] = &[
+    OptionalFlag {
+        flag: "--resume",
+        feature: "resume",
+    },
+    OptionalFlag {
+        flag: "--permission-mode",
+        feature: "permission_modes",
+    },
+    OptionalFlag {
+        flag: "--settings",
+        feature: "settings",
+    },
+    OptionalFlag {
+        flag: "--allowedTools",
+        feature: "allowed_tools",
+    },
+    OptionalFlag {
+        flag: "--mcp-config",
+        feature: "mcp_config",
+    },
+    OptionalFlag {
+        flag: "--chrome",
+        feature: "chrome",
+    },
+    OptionalFlag {
+        flag: "--add-dir",
+        feature: "add_dir",
+    },
+    OptionalFlag {
+        flag: "--append-system-prompt-file",
+        feature: "context_files",
+    },
+];
+
+pub const AIDER_OPTIONAL_FLAGS: &[OptionalFlag] = &[
+    OptionalFlag {
+        flag: "--restore-chat-history",
+        feature: "resume",
+    },
+    OptionalFlag {
+        flag: "--auto-commits",
+        feature: "auto_commits",
+    },
+    OptionalFlag {
+        flag: "--no-auto-commits",
+        feature: "auto_commits",
+    },
+    OptionalFlag {
+        flag: "--no-fancy-input",
+        feature: "plain_input",
+    },
+    OptionalFlag {
+        flag: "--no-show-release-notes",
+        feature: "quiet_startup",
+    },
+    OptionalFlag {
+        flag: "--analytics-disable",
+        feature: "analytics_opt_out",
+    },
+    OptionalFlag {
+        flag: "--no-gitignore",
+        feature: "no_gitignore",
+    },
+    OptionalFlag {
+        flag: "--no-check-update",
+        feature: "quiet_startup",
+    },
+];
+
+pub const CLAUDE_RULES: &[Rule] = &[
+    Rule::Requires {
+        flag: "--input-format",
+        value: None,
+        requires: "--print",
+    },
+    Rule::Requires {
+        flag: "--output-format",
+        value: None,
+        requires: "--print",
+    },
+    Rule::Requires {
+        flag: "--output-format",
+        value: Some("stream-json"),
+        requires: "--verbose",
+    },
+    Rule::OneOf {
+        flag: "--output-format",
+        values: &["text", "json", "stream-json"],
+    },
+    Rule::OneOf {
+        flag: "--input-format",
+        values: &["text", "stream-json"],
+    },
+    Rule::OneOf {
+        flag: "--permission-mode",
+        values: &["default", "acceptEdits", "bypassPermissions", "plan"],
+    },
+    Rule::Conflicts {
+        flag: "--resume",
+        other: "--continue",
+    },
+];
+
+pub const AIDER_RULES: &[Rule] = &[Rule::Conflicts {
+    flag: "--auto-commits",
+    other: "--no-auto-commits",
+}];
+
+/// Flags Jean's run protocol depends on; launch defaults can't set them
+pub const CLAUDE_RESERVED_FLAGS: &[&str] = &[
+    "--print",
+    "--output-format",
+    "--input-format",
+    "--resume",
+    "--continue",
+    "--session-id",
+];
+
+/// Permission flags Jean sets for plan mode (also forced by the emergency
+/// stop); the last one given wins, so in plan mode defaults can't set them
+pub const CLAUDE_PLAN_MODE_RESERVED_FLAGS: &[&str] = &[
+    "--permission-mode",
+    "--dangerously-skip-permissions",
+    "--allowedTools",
+    "--disallowedTools",
+];
+
+pub const AIDER_RESERVED_FLAGS: &[&str] = &[
+    "--message-file",
+    "--chat-history-file",
+    "--input-history-file",
+    "--pretty",
+    "--fancy-input",
+];
+
+/// Probed capabilities of one CLI binary
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliCapabilities {
+    pub cli: String,
+    pub version: Option<String>,
+    /// Binary size and mtime the probe ran against
+    fingerprint: String,
+    pub probed_at: u64,
+    /// Every `--flag` the CLI's help lists; empty if the probe failed
+    pub flags: BTreeSet<String>,
+    /// Named features derived from `flags`, for the UI
+    pub features: BTreeMap<String, bool>,
+}
+
+impl CliCapabilities {
+    /// Whether the CLI accepts `flag`. Unprobed CLIs are assumed to.
+    pub fn supports(&self, flag: &str) -> bool {
+        self.flags.is_empty() || self.flags.contains(flag)
+    }
+}
+
+/// All `--flags` mentioned in help output
+fn parse_flags(help: &str) -> BTreeSet<String> {
+    help.match_indices("--")
+        .filter(|(i, _)| {
+            // Skip "---" rules and "foo--bar"
+            help[..*i]
+                .chars()
+                .last()
+                .is_none_or(|c| !c.is_alphanumeric() && c != '-')
+        })
+        .filter_map(|(i, _)| {
+            let name: String = help[i + 2..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+                .collect();
+            (name.len() > 1 && name.chars().next()?.is_ascii_alphabetic())
+                .then(|| format!("--{name}"))
+        })
+        .collect()
+}
+
+fn features(flags: &BTreeSet<String>, optional: &[OptionalFlag]) -> BTreeMap<String, bool> {
+    let mut features = BTreeMap::new();
+    for opt in optional {
+        let supported = flags.is_empty() || flags.contains(opt.flag);
+        // A feature made of several flags needs all of them
+        features
+            .entry(opt.feature.to_string())
+            .and_modify(|s| *s &= supported)
+            .or_insert(supported);
+    }
+    features
+}
+
+fn fingerprint(binary: &Path) -> Option<String> {
+    let metadata = fs::metadata(binary).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Some(format!("{}:{modified}", metadata.len()))
+}
+
+fn run_for_output(binary: &Path, arg: &str) -> Option<String> {
+    let output = silent_command(binary).arg(arg).output().ok()?;
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    (output.status.success() || !text.trim().is_empty()).then_some(text)
+}
+
+fn cache_path(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.shared_data_dir()?.join(CACHE_FILE))
+}
+
+fn load_cache(app: &impl PathProvider) -> BTreeMap<String, CliCapabilities> {
+    cache_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(
+    app: &impl PathProvider,
+    cache: &BTreeMap<String, CliCapabilities>,
+) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(cache)
+        .map_err(|e| format!("Failed to serialize CLI capabilities: {e}"))?;
+    fs::write(cache_path(app)?, content)
+        .map_err(|e| format!("Failed to save CLI capabilities: {e}"))
+}
+
+/// Capabilities of `binary`, probing it if it changed since the last probe
+pub fn probe(
+    app: &impl PathProvider,
+    cli: &str,
+    binary: &Path,
+    optional: &[OptionalFlag],
+) -> CliCapabilities {
+    let fingerprint = fingerprint(binary).unwrap_or_default();
+    let mut cache = load_cache(app);
+    if let Some(cached) = cache.get(cli).filter(|c| c.fingerprint == fingerprint) {
+        return cached.clone();
+    }
+
+    log::trace!("Probing capabilities of {cli} at {binary:?}");
+    let version = run_for_output(binary, "--version").and_then(|v| {
+        v.split_whitespace()
+            .map(|w| w.trim_start_matches('v'))
+            .find(|w| w.split('.').count() >= 2 && w.split('.').all(|p| p.parse::<u32>().is_ok()))
+            .map(str::to_string)
+    });
+    let flags = run_for_output(binary, "--help")
+        .map(|help| parse_flags(&help))
+        .unwrap_or_default();
+    if flags.is_empty() {
+        log::warn!("Couldn't read the flags of {cli}; not gating any features");
+    }
+
+    let capabilities = CliCapabilities {
+        cli: cli.to_string(),
+        version,
+        fingerprint,
+        probed_at: std::time::SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        features: features(&flags, optional),
+        flags,
+    };
+    cache.insert(cli.to_string(), capabilities.clone());
+    if let Err(e) = save_cache(app, &cache) {
+        log::warn!("{e}");
+    }
+    capabilities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    const HELP: &str = "\
+Usage: claude [options] [command] [prompt]
+
+Options:
+  -p, --print                     Print response and exit
+  --output-format <format>        \"text\", \"json\", or \"stream-json\"
+  -r, --resume [sessionId]        Resume a conversation
+  --permission-mode <mode>        Permission mode
+-----------------------------------
+  --add-dir <directories...>      Additional directories (e.g. foo--bar)
+";
+
+    #[test]
+    fn test_parse_flags() {
+        let flags = parse_flags(HELP);
+        let expected = [
+            "--add-dir",
+            "--output-format",
+            "--permission-mode",
+            "--print",
+            "--resume",
+        ];
+        assert_eq!(flags.iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_features() {
+        let flags = parse_flags(HELP);
+        let features = features(&flags, CLAUDE_OPTIONAL_FLAGS);
+        assert_eq!(features["resume"], true);
+        assert_eq!(features["chrome"], false);
+
+        // Failed probes gate nothing
+        assert!(CliCapabilities::default().supports("--chrome"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_probe_is_cached_per_binary() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let paths = TempPaths::new();
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("fake-cli");
+        let counter = dir.path().join("calls");
+        let script = format!(
+            "#!/bin/sh\necho x >> '{}'\nif [ \"$1\" = --version ]; then echo '2.1.0 (Claude Code)'; else echo '  --print  --resume <id>'; fi\n",
+            counter.display()
+        );
+        fs::write(&binary, script).unwrap();
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let caps = probe(&paths, "claude", &binary, CLAUDE_OPTIONAL_FLAGS);
+        assert_eq!(caps.version.as_deref(), Some("2.1.0"));
+        assert!(caps.supports("--resume"));
+        assert!(!caps.supports("--chrome"));
+
+        probe(&paths, "claude", &binary, CLAUDE_OPTIONAL_FLAGS);
+        let calls = fs::read_to_string(&counter).unwrap();
+        assert_eq!(calls.lines().count(), 2, "probe should run once");
+    }
+}
@@ -0,0 +1,211 @@
+//! Tauri commands for viewing and submitting crash reports
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{get_crash_reports_dir, now, report_path, CrashReport};
+
+/// Where new issues are opened when no upload endpoint is configured
+const ISSUE_URL: &str = "https://github.com/coollabsio/jean/issues/new";
+
+/// Backtrace lines included in a prefilled issue (URLs have length limits)
+const MAX_ISSUE_BACKTRACE_LINES: usize = 30;
+
+/// Result of submitting a crash report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReportSubmission {
+    pub id: String,
+    /// True if the report was uploaded to the configured endpoint
+    pub uploaded: bool,
+    /// Prefilled issue URL for the user to open (when not uploaded)
+    pub issue_url: Option<String>,
+}
+
+fn read_report(app: &AppHandle, id: &str) -> Result<CrashReport, String> {
+    let dir = get_crash_reports_dir(app)?;
+    let path = report_path(&dir, id)?;
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read crash report {id}: {e}"))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse crash report {id}: {e}"))
+}
+
+fn write_report(app: &AppHandle, report: &CrashReport) -> Result<(), String> {
+    let dir = get_crash_reports_dir(app)?;
+    let path = report_path(&dir, &report.id)?;
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize crash report: {e}"))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write crash report: {e}"))
+}
+
+/// List stored crash reports, newest first
+#[tauri::command]
+pub async fn list_crash_reports(app: AppHandle) -> Result<Vec<CrashReport>, String> {
+    let dir = get_crash_reports_dir(&app)?;
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut reports: Vec<CrashReport> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|p| {
+            let content = std::fs::read_to_string(&p).ok()?;
+            serde_json::from_str(&content)
+                .map_err(|e| log::warn!("Skipping unreadable crash report {p:?}: {e}"))
+                .ok()
+        })
+        .collect();
+
+    reports.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+    Ok(reports)
+}
+
+/// Get a single crash report
+#[tauri::command]
+pub async fn get_crash_report(app: AppHandle, id: String) -> Result<CrashReport, String> {
+    read_report(&app, &id)
+}
+
+/// Delete a crash report and its minidump / OS report attachment
+#[tauri::command]
+pub async fn delete_crash_report(app: AppHandle, id: String) -> Result<(), String> {
+    let report = read_report(&app, &id)?;
+    let dir = get_crash_reports_dir(&app)?;
+
+    if let Some(attachment) = &report.attachment_file {
+        let _ = std::fs::remove_file(dir.join(attachment));
+    }
+    std::fs::remove_file(report_path(&dir, &id)?)
+        .map_err(|e| format!("Failed to delete crash report: {e}"))?;
+
+    log::trace!("Deleted crash report {id}");
+    Ok(())
+}
+
+/// Submit a crash report (explicit user action only)
+///
+/// Uploads the report as JSON to `crash_report_endpoint` if configured;
+/// otherwise returns a prefilled GitHub issue URL for the user to open.
+/// Attachments are never uploaded.
+#[tauri::command]
+pub async fn submit_crash_report(
+    app: AppHandle,
+    id: String,
+) -> Result<CrashReportSubmission, String> {
+    let mut report = read_report(&app, &id)?;
+    let prefs = crate::load_preferences(app.clone()).await?;
+
+    let endpoint = prefs
+        .crash_report_endpoint
+        .as_deref()
+        .map(str::trim)
+        .filter(|e| !e.is_empty());
+
+    let submission = match endpoint {
+        Some(endpoint) => {
+            let client = crate::http_client::client_builder()
+                .user_agent("Jean-App/1.0")
+                .timeout(Duration::from_secs(30))
+                .build()
+                .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+            let response = client
+                .post(endpoint)
+                .json(&report)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to upload crash report: {e}"))?;
+            if !response.status().is_success() {
+                return Err(format!(
+                    "Crash report upload failed: HTTP {}",
+                    response.status()
+                ));
+            }
+            log::info!("Uploaded crash report {id}");
+            CrashReportSubmission {
+                id: id.clone(),
+                uploaded: true,
+                issue_url: None,
+            }
+        }
+        None => CrashReportSubmission {
+            id: id.clone(),
+            uploaded: false,
+            issue_url: Some(issue_url(&report)?),
+        },
+    };
+
+    report.submitted_at = Some(now());
+    write_report(&app, &report)?;
+    Ok(submission)
+}
+
+/// Build a prefilled GitHub issue URL for a report
+fn issue_url(report: &CrashReport) -> Result<String, String> {
+    let title = format!("Crash report: {}", first_line(&report.message));
+    let url = reqwest::Url::parse_with_params(
+        ISSUE_URL,
+        &[("title", title.as_str()), ("body", &issue_body(report))],
+    )
+    .map_err(|e| format!("Failed to build issue URL: {e}"))?;
+    Ok(url.to_string())
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+fn issue_body(report: &CrashReport) -> String {
+    let mut body = format!(
+        "**Kind:** {:?}\n**Jean:** {}\n**Claude CLI:** {}\n**OS:** {} ({})\n\n**Message:**\n```\n{}\n```\n",
+        report.kind,
+        report.app_version,
+        report.cli_version.as_deref().unwrap_or("unknown"),
+        report.os,
+        report.arch,
+        report.message,
+    );
+    if let Some(location) = &report.location {
+        body.push_str(&format!("**Location:** `{location}`\n"));
+    }
+    if let Some(cli) = &report.cli {
+        body.push_str(&format!(
+            "\n**Program:** `{}` (exit code: {:?}, signal: {:?})\n```\n{}\n```\n",
+            cli.program,
+            cli.exit_code,
+            cli.signal,
+            cli.stderr_tail.join("\n")
+        ));
+    }
+    if let Some(backtrace) = &report.backtrace {
+        let lines: Vec<&str> = backtrace.lines().take(MAX_ISSUE_BACKTRACE_LINES).collect();
+        body.push_str(&format!(
+            "\n**Backtrace:**\n```\n{}\n```\n",
+            lines.join("\n")
+        ));
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::CrashKind;
+    use super::*;
+
+    #[test]
+    fn test_issue_url_is_prefilled_and_truncated() {
+        let mut report =
+            CrashReport::new(CrashKind::Panic, "index out of bounds\nmore".to_string());
+        report.backtrace = Some((0..100).map(|i| format!("frame {i}\n")).collect());
+
+        let url = issue_url(&report).unwrap();
+        assert!(url.starts_with(ISSUE_URL));
+        assert!(url.contains("title=Crash+report%3A+index+out+of+bounds&"));
+
+        let body = issue_body(&report);
+        assert!(body.contains("frame 29"));
+        assert!(!body.contains("frame 30"));
+    }
+}
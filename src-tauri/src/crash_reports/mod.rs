@@ -0,0 +1,317 @@
+//! Local crash reporting
+//!
+//! Captures Rust panics (with backtraces), native crashes (minidumps on
+//! Windows, the OS's own crash reports on macOS) and abnormal exits of child
+//! CLI processes. Reports are stored as JSON under `crash-reports/` in the app
+//! data directory together with the app and Claude CLI versions. Nothing leaves
+//! the machine unless the user submits a report with `submit_crash_report`.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::sync::Mutex;
+
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::platform::{abnormal_exit, install_native_crash_handler, write_minidump};
+use crate::runtime::{now, PathProvider};
+
+pub mod commands;
+
+/// Directory name for crash reports within app data
+const CRASH_REPORTS_DIR_NAME: &str = "crash-reports";
+
+/// Marker file recording when OS crash reports were last imported
+const SYSTEM_IMPORT_MARKER: &str = ".last-system-import";
+
+/// Lines of CLI stderr kept in a report
+const MAX_STDERR_LINES: usize = 40;
+
+/// Where reports are written (set once by `install`, readable from the panic hook)
+static CRASH_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// App version captured at install time
+static APP_VERSION: OnceCell<String> = OnceCell::new();
+
+/// Last known Claude CLI version (updated whenever the CLI status is checked)
+static CLI_VERSION: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// What kind of failure a report describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    /// Rust panic in Jean
+    Panic,
+    /// Native crash of Jean (access violation, signal)
+    Native,
+    /// A child CLI process (claude, gh) crashed
+    Cli,
+}
+
+/// Details about a crashed child CLI process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CliCrashDetails {
+    /// Program that crashed (e.g. "claude")
+    pub program: String,
+    pub exit_code: Option<i32>,
+    /// Terminating signal (Unix only)
+    pub signal: Option<i32>,
+    /// Last lines of stderr
+    pub stderr_tail: Vec<String>,
+}
+
+/// A stored crash report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub kind: CrashKind,
+    /// Unix timestamp when the crash was recorded
+    pub created_at: u64,
+    pub app_version: String,
+    /// Claude CLI version at the time of the crash (if known)
+    pub cli_version: Option<String>,
+    pub os: String,
+    pub arch: String,
+    /// Panic message or crash summary
+    pub message: String,
+    /// Source location of a panic (file:line:column)
+    pub location: Option<String>,
+    /// Thread that panicked
+    pub thread: Option<String>,
+    pub backtrace: Option<String>,
+    /// Minidump or OS crash report stored next to the report (file name)
+    pub attachment_file: Option<String>,
+    pub cli: Option<CliCrashDetails>,
+    /// Unix timestamp when the report was submitted (None = never)
+    pub submitted_at: Option<u64>,
+}
+
+impl CrashReport {
+    fn new(kind: CrashKind, message: String) -> Self {
+        let created_at = now();
+        Self {
+            id: format!(
+                "{created_at}-{}",
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ),
+            kind,
+            created_at,
+            app_version: APP_VERSION.get().cloned().unwrap_or_default(),
+            cli_version: CLI_VERSION.lock().ok().and_then(|v| v.clone()),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            message,
+            location: None,
+            thread: None,
+            backtrace: None,
+            attachment_file: None,
+            cli: None,
+            submitted_at: None,
+        }
+    }
+}
+
+/// Get the crash reports directory
+pub fn get_crash_reports_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app.app_data_dir()?;
+    Ok(app_data_dir.join(CRASH_REPORTS_DIR_NAME))
+}
+
+/// Path of a report's JSON file (validates the ID to prevent path traversal)
+pub fn report_path(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid crash report ID: {id}"));
+    }
+    Ok(dir.join(format!("{id}.json")))
+}
+
+/// Write a report to the crash directory (best effort; never panics)
+fn save_report(report: &CrashReport) -> Option<PathBuf> {
+    let dir = CRASH_DIR.get()?;
+    let path = report_path(dir, &report.id).ok()?;
+    let json = serde_json::to_string_pretty(report).ok()?;
+    std::fs::create_dir_all(dir).ok()?;
+    std::fs::write(&path, json).ok()?;
+    Some(path)
+}
+
+/// Install the panic hook and native crash handler. Call once during setup.
+pub fn install(app: &AppHandle) {
+    let dir = match get_crash_reports_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            log::warn!("Crash reporting disabled: {e}");
+            return;
+        }
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log::warn!("Crash reporting disabled, failed to create {dir:?}: {e}");
+        return;
+    }
+    let _ = APP_VERSION.set(app.package_info().version.to_string());
+    let _ = CRASH_DIR.set(dir.clone());
+
+    // Keep the default hook (prints to stderr) and record a report first
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        record_panic(info);
+        previous_hook(info);
+    }));
+
+    install_native_crash_handler(dir, record_native_crash);
+
+    let app_name = app.package_info().name.clone();
+    crate::background_tasks::supervisor::spawn_thread("system-crash-import", move || {
+        import_system_crash_reports(&app_name)
+    });
+}
+
+/// Remember the Claude CLI version for future reports
+pub fn set_cli_version(version: Option<String>) {
+    if let Ok(mut current) = CLI_VERSION.lock() {
+        *current = version;
+    }
+}
+
+fn record_panic(info: &std::panic::PanicHookInfo<'_>) {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic".to_string());
+
+    let mut report = CrashReport::new(CrashKind::Panic, message);
+    report.location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+    report.thread = std::thread::current().name().map(|n| n.to_string());
+    report.backtrace = Some(std::backtrace::Backtrace::force_capture().to_string());
+
+    if let Some(dir) = CRASH_DIR.get() {
+        let dump_name = format!("{}.dmp", report.id);
+        if write_minidump(&dir.join(&dump_name), std::ptr::null()).is_some() {
+            report.attachment_file = Some(dump_name);
+        }
+    }
+
+    save_report(&report);
+}
+
+fn record_native_crash(dump_path: &Path) {
+    let mut report = CrashReport::new(CrashKind::Native, "Unhandled native exception".to_string());
+    report.attachment_file = dump_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string());
+    save_report(&report);
+}
+
+/// Import crash reports the OS wrote for Jean since the last import (macOS)
+fn import_system_crash_reports(app_name: &str) {
+    let Some(dir) = CRASH_DIR.get() else {
+        return;
+    };
+    let marker = dir.join(SYSTEM_IMPORT_MARKER);
+    let since = std::fs::read_to_string(&marker)
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .unwrap_or_else(now);
+
+    for source in crate::platform::system_crash_reports(app_name, since) {
+        let mut report = CrashReport::new(
+            CrashKind::Native,
+            "Native crash recorded by the operating system".to_string(),
+        );
+        let extension = source
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_else(|| "txt".to_string());
+        let attachment = format!("{}.{extension}", report.id);
+        if std::fs::copy(&source, dir.join(&attachment)).is_ok() {
+            report.attachment_file = Some(attachment);
+        }
+        if save_report(&report).is_some() {
+            log::info!("Imported system crash report {source:?}");
+        }
+    }
+
+    let _ = std::fs::write(&marker, now().to_string());
+}
+
+/// Record a crash of a child CLI process if `status` is an abnormal exit
+/// (crash signal / NTSTATUS error). Normal failures are ignored.
+pub fn record_cli_exit(program: &str, status: &ExitStatus, stderr: &[u8]) {
+    let Some(exit) = abnormal_exit(status) else {
+        return;
+    };
+    let stderr = String::from_utf8_lossy(stderr);
+    record_cli_crash(
+        program,
+        &format!("{program} exited abnormally ({status})"),
+        exit.exit_code,
+        exit.signal,
+        stderr.lines().map(|l| l.to_string()).collect(),
+    );
+}
+
+/// Record a crash of a child CLI process
+pub fn record_cli_crash(
+    program: &str,
+    message: &str,
+    exit_code: Option<i32>,
+    signal: Option<i32>,
+    stderr_lines: Vec<String>,
+) {
+    log::warn!("Recording CLI crash: {message}");
+    let mut report = CrashReport::new(CrashKind::Cli, message.to_string());
+    let skip = stderr_lines.len().saturating_sub(MAX_STDERR_LINES);
+    report.cli = Some(CliCrashDetails {
+        program: program.to_string(),
+        exit_code,
+        signal,
+        stderr_tail: stderr_lines.into_iter().skip(skip).collect(),
+    });
+    if save_report(&report).is_none() {
+        log::warn!("Failed to save CLI crash report");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_path_rejects_traversal() {
+        let dir = Path::new("/tmp/crash-reports");
+        assert!(report_path(dir, "1700000000-abcd1234").is_ok());
+        assert!(report_path(dir, "../preferences").is_err());
+        assert!(report_path(dir, "").is_err());
+    }
+
+    #[test]
+    fn test_is_crash_exit_code() {
+        use crate::platform::is_crash_exit_code;
+        // STATUS_ACCESS_VIOLATION
+        assert!(is_crash_exit_code(0xC000_0005_u32 as i32));
+        // STATUS_CONTROL_C_EXIT is a deliberate stop
+        assert!(!is_crash_exit_code(0xC000_013A_u32 as i32));
+        assert!(!is_crash_exit_code(1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_abnormal_exit_detects_crash_signals() {
+        use std::os::unix::process::ExitStatusExt;
+        // Raw wait status: low 7 bits hold the terminating signal
+        let segv = ExitStatus::from_raw(libc::SIGSEGV);
+        assert_eq!(
+            abnormal_exit(&segv).and_then(|e| e.signal),
+            Some(libc::SIGSEGV)
+        );
+        let killed = ExitStatus::from_raw(libc::SIGKILL);
+        assert!(abnormal_exit(&killed).is_none());
+        let failed = ExitStatus::from_raw(1 << 8);
+        assert!(abnormal_exit(&failed).is_none());
+    }
+}
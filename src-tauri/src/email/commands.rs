@@ -0,0 +1,36 @@
+//! Tauri commands for outgoing email
+
+use tauri::AppHandle;
+
+use super::{save_password, send, Email, SmtpSettings};
+
+/// Store the SMTP password (None or empty forgets it)
+#[tauri::command]
+pub async fn set_smtp_password(app: AppHandle, password: Option<String>) -> Result<(), String> {
+    save_password(&app, password.as_deref())
+}
+
+/// Send a test email to `to` (default: the digest recipients, else the
+/// sender) with the SMTP preferences and stored password
+#[tauri::command]
+pub async fn send_test_email(app: AppHandle, to: Option<String>) -> Result<(), String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let settings = SmtpSettings::from_preferences(&prefs)?;
+    let to = match to.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()) {
+        Some(to) => vec![to],
+        None if !prefs.email_digest_to.is_empty() => prefs.email_digest_to.clone(),
+        None => vec![settings.from.clone()],
+    };
+    log::trace!("Sending test email to {}", to.join(", "));
+    let email = Email {
+        to,
+        subject: "Jean test email".to_string(),
+        body: format!(
+            "This is a test email from Jean, sent through {}:{}.\n",
+            settings.host, settings.port
+        ),
+    };
+    tauri::async_runtime::spawn_blocking(move || send(&app, &settings, &email))
+        .await
+        .map_err(|e| format!("Failed to send test email: {e}"))?
+}
@@ -0,0 +1,221 @@
+//! Outgoing email over SMTP
+//!
+//! The server, sender and login come from the `smtp_*` preferences. The
+//! password is kept in `smtp-credentials.json` in app data (readable only by
+//! the user) rather than in preferences, so it never ends up in exports or
+//! the shared library. Used for the run digest (see `reports::digest`).
+
+use std::fs;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::platform::write_secret_file;
+use crate::runtime::PathProvider;
+use crate::AppPreferences;
+
+pub mod commands;
+mod smtp;
+
+const CREDENTIALS_FILE: &str = "smtp-credentials.json";
+
+/// Base64 line length in message bodies (RFC 2045)
+const BASE64_LINE: usize = 76;
+
+/// How the connection to the SMTP server is secured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (usually port 587)
+    #[default]
+    StartTls,
+    /// TLS from the start (usually port 465)
+    Tls,
+    /// Unencrypted, for local relays only
+    None,
+}
+
+/// Where and as whom to send mail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    /// Login (None = the server doesn't require one)
+    pub username: Option<String>,
+    pub from: String,
+}
+
+impl SmtpSettings {
+    pub fn from_preferences(prefs: &AppPreferences) -> Result<Self, String> {
+        let trimmed = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let host = trimmed(&prefs.smtp_host).ok_or("No SMTP server configured")?;
+        let from = trimmed(&prefs.smtp_from).ok_or("No sender address configured")?;
+        if !is_address(&from) {
+            return Err(format!("Invalid sender address: {from}"));
+        }
+        Ok(Self {
+            host,
+            port: prefs.smtp_port,
+            security: prefs.smtp_security,
+            username: trimmed(&prefs.smtp_username),
+            from,
+        })
+    }
+}
+
+/// A plain-text email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: Vec<String>,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Credentials {
+    password: Option<String>,
+}
+
+/// Whether `address` looks like a bare mailbox (no display name)
+pub fn is_address(address: &str) -> bool {
+    match address.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.is_empty()
+                && !address.contains(|c: char| c.is_whitespace() || "<>,;\"".contains(c))
+        }
+        None => false,
+    }
+}
+
+/// The stored SMTP password, if any
+pub fn load_password(app: &impl PathProvider) -> Option<String> {
+    let root = app.app_data_dir().ok()?;
+    let content = fs::read_to_string(root.join(CREDENTIALS_FILE)).ok()?;
+    serde_json::from_str::<Credentials>(&content)
+        .ok()?
+        .password
+        .filter(|p| !p.is_empty())
+}
+
+/// Store (or with None, forget) the SMTP password
+pub fn save_password(app: &impl PathProvider, password: Option<&str>) -> Result<(), String> {
+    let path = app.app_data_dir()?.join(CREDENTIALS_FILE);
+    let Some(password) = password.filter(|p| !p.is_empty()) else {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove SMTP password: {e}"))
+            }
+            _ => Ok(()),
+        };
+    };
+    let content = serde_json::to_string(&Credentials {
+        password: Some(password.to_string()),
+    })
+    .map_err(|e| format!("Failed to serialize SMTP password: {e}"))?;
+    write_secret_file(&path, content.as_bytes())
+        .map_err(|e| format!("Failed to save SMTP password: {e}"))
+}
+
+/// Header value, as an RFC 2047 encoded word unless plain ASCII
+fn header_text(value: &str) -> String {
+    let value: String = value.chars().filter(|c| *c != '\r' && *c != '\n').collect();
+    if value.is_ascii() {
+        value
+    } else {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(value.as_bytes());
+        format!("=?UTF-8?B?{encoded}?=")
+    }
+}
+
+/// RFC 5322 message text with a base64-encoded UTF-8 body
+fn format_message(from: &str, email: &Email, date: &str, message_id: &str) -> String {
+    let body = base64::engine::general_purpose::STANDARD.encode(email.body.as_bytes());
+    let mut message = format!(
+        "From: {from}\r\n\
+         To: {}\r\n\
+         Subject: {}\r\n\
+         Date: {date}\r\n\
+         Message-ID: <{message_id}>\r\n\
+         MIME-Version: 1.0\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\
+         Content-Transfer-Encoding: base64\r\n\
+         \r\n",
+        email.to.join(", "),
+        header_text(&email.subject),
+    );
+    for chunk in body.as_bytes().chunks(BASE64_LINE) {
+        message.push_str(&String::from_utf8_lossy(chunk));
+        message.push_str("\r\n");
+    }
+    message
+}
+
+/// Send `email` with the configured server and stored password (blocking)
+pub fn send(app: &impl PathProvider, settings: &SmtpSettings, email: &Email) -> Result<(), String> {
+    if email.to.is_empty() {
+        return Err("No recipients".to_string());
+    }
+    if let Some(to) = email.to.iter().find(|to| !is_address(to)) {
+        return Err(format!("Invalid recipient address: {to}"));
+    }
+    let domain = settings.from.rsplit('@').next().unwrap_or("localhost");
+    let message = format_message(
+        &settings.from,
+        email,
+        &chrono::Local::now().to_rfc2822(),
+        &format!("{}@{domain}", uuid::Uuid::new_v4()),
+    );
+    let password = load_password(app);
+    smtp::deliver(settings, password.as_deref(), &email.to, &message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_is_address() {
+        assert!(is_address("dev@example.com"));
+        assert!(!is_address("Dev <dev@example.com>"));
+        assert!(!is_address("dev@"));
+        assert!(!is_address("example.com"));
+    }
+
+    #[test]
+    fn test_password_storage() {
+        let paths = TempPaths::new();
+        assert_eq!(load_password(&paths), None);
+        save_password(&paths, Some("hunter2")).unwrap();
+        assert_eq!(load_password(&paths).as_deref(), Some("hunter2"));
+        save_password(&paths, None).unwrap();
+        assert_eq!(load_password(&paths), None);
+        save_password(&paths, None).unwrap();
+    }
+
+    #[test]
+    fn test_format_message() {
+        let email = Email {
+            to: vec!["a@example.com".to_string(), "b@example.com".to_string()],
+            subject: "Résumé\r\nBcc: evil@example.com".to_string(),
+            body: "3 runs, 1 failed".to_string(),
+        };
+        let message = format_message("jean@example.com", &email, "Mon, 4 Mar 2024", "id@x");
+        let (headers, body) = message.split_once("\r\n\r\n").unwrap();
+        assert!(headers.contains("To: a@example.com, b@example.com\r\n"));
+        assert!(headers.contains("Subject: =?UTF-8?B?"));
+        assert!(!headers.contains("\r\nBcc:"));
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(body.replace("\r\n", ""))
+            .unwrap();
+        assert_eq!(decoded, b"3 runs, 1 failed");
+    }
+}
@@ -0,0 +1,385 @@
+//! Just enough SMTP to hand one message to a submission server: STARTTLS
+//! or implicit TLS, AUTH PLAIN or LOGIN, then MAIL/RCPT/DATA
+
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use rustls::pki_types::ServerName;
+use rustls::{ClientConnection, RootCertStore, StreamOwned};
+
+use super::{SmtpSecurity, SmtpSettings};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Name sent with EHLO
+const CLIENT_NAME: &str = "jean.local";
+
+enum Stream {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(s) => s.read(buf),
+            Self::Tls(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(s) => s.write(buf),
+            Self::Tls(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(s) => s.flush(),
+            Self::Tls(s) => s.flush(),
+        }
+    }
+}
+
+/// A server reply: code and text lines
+struct Reply {
+    code: u16,
+    lines: Vec<String>,
+}
+
+impl Reply {
+    /// Whether an EHLO reply lists `extension` (e.g. "STARTTLS")
+    fn has_extension(&self, extension: &str) -> bool {
+        self.lines
+            .iter()
+            .skip(1)
+            .any(|l| l.split_whitespace().next() == Some(extension))
+    }
+
+    /// Mechanisms an EHLO reply offers for AUTH
+    fn auth_mechanisms(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .skip(1)
+            .filter_map(|l| l.strip_prefix("AUTH"))
+            .flat_map(|rest| rest.split([' ', '=']))
+            .filter(|m| !m.is_empty())
+            .map(str::to_uppercase)
+            .collect()
+    }
+
+    fn text(&self) -> String {
+        self.lines.join(" ")
+    }
+}
+
+struct Connection {
+    stream: Stream,
+    buf: Vec<u8>,
+}
+
+impl Connection {
+    fn read_line(&mut self) -> Result<String, String> {
+        loop {
+            if let Some(end) = self.buf.windows(2).position(|w| w == b"\r\n") {
+                let line = String::from_utf8_lossy(&self.buf[..end]).into_owned();
+                self.buf.drain(..end + 2);
+                return Ok(line);
+            }
+            let mut chunk = [0u8; 1024];
+            let read = self
+                .stream
+                .read(&mut chunk)
+                .map_err(|e| format!("Failed to read from SMTP server: {e}"))?;
+            if read == 0 {
+                return Err("SMTP server closed the connection".to_string());
+            }
+            self.buf.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    fn read_reply(&mut self) -> Result<Reply, String> {
+        let mut lines = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            let code = line
+                .get(..3)
+                .and_then(|c| c.parse::<u16>().ok())
+                .ok_or_else(|| format!("Unexpected SMTP reply: {line}"))?;
+            let more = line.as_bytes().get(3) == Some(&b'-');
+            lines.push(line.get(4..).unwrap_or_default().to_string());
+            if !more {
+                return Ok(Reply { code, lines });
+            }
+        }
+    }
+
+    fn write(&mut self, data: &str) -> Result<(), String> {
+        self.stream
+            .write_all(data.as_bytes())
+            .and_then(|_| self.stream.flush())
+            .map_err(|e| format!("Failed to write to SMTP server: {e}"))
+    }
+
+    /// Send a command and read its reply, failing unless the code is
+    /// `expected` (`what` names the step in the error)
+    fn command(&mut self, line: &str, expected: &[u16], what: &str) -> Result<Reply, String> {
+        self.write(&format!("{line}\r\n"))?;
+        self.expect(expected, what)
+    }
+
+    fn expect(&mut self, expected: &[u16], what: &str) -> Result<Reply, String> {
+        let reply = self.read_reply()?;
+        if expected.contains(&reply.code) {
+            Ok(reply)
+        } else {
+            Err(format!(
+                "SMTP server rejected {what}: {} {}",
+                reply.code,
+                reply.text()
+            ))
+        }
+    }
+
+    /// Switch to TLS after STARTTLS
+    fn start_tls(self, host: &str) -> Result<Self, String> {
+        let Stream::Plain(tcp) = self.stream else {
+            return Err("Connection is already encrypted".to_string());
+        };
+        Ok(Self {
+            stream: tls(tcp, host)?,
+            buf: Vec::new(),
+        })
+    }
+}
+
+fn tls(tcp: TcpStream, host: &str) -> Result<Stream, String> {
+    let mut roots = RootCertStore::empty();
+    roots.add_parsable_certificates(crate::http_client::trusted_roots());
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|e| format!("Failed to configure TLS: {e}"))?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    let server_name = ServerName::try_from(host.to_string())
+        .map_err(|e| format!("Invalid server name {host}: {e}"))?;
+    let conn = ClientConnection::new(Arc::new(config), server_name)
+        .map_err(|e| format!("Failed to start TLS session: {e}"))?;
+    Ok(Stream::Tls(Box::new(StreamOwned::new(conn, tcp))))
+}
+
+/// Lines starting with "." get another one, so none ends DATA early
+fn dot_stuff(message: &str) -> String {
+    let mut stuffed = String::with_capacity(message.len() + 8);
+    for line in message.split_inclusive("\r\n") {
+        if line.starts_with('.') {
+            stuffed.push('.');
+        }
+        stuffed.push_str(line);
+    }
+    if !stuffed.ends_with("\r\n") {
+        stuffed.push_str("\r\n");
+    }
+    stuffed
+}
+
+fn authenticate(
+    conn: &mut Connection,
+    ehlo: &Reply,
+    username: &str,
+    password: &str,
+) -> Result<(), String> {
+    let b64 = |s: &[u8]| base64::engine::general_purpose::STANDARD.encode(s);
+    let mechanisms = ehlo.auth_mechanisms();
+    if mechanisms.iter().any(|m| m == "LOGIN") && !mechanisms.iter().any(|m| m == "PLAIN") {
+        conn.command("AUTH LOGIN", &[334], "AUTH LOGIN")?;
+        conn.command(&b64(username.as_bytes()), &[334], "the username")?;
+        conn.command(&b64(password.as_bytes()), &[235], "the login")?;
+    } else {
+        let credentials = format!("\0{username}\0{password}");
+        conn.command(
+            &format!("AUTH PLAIN {}", b64(credentials.as_bytes())),
+            &[235],
+            "the login",
+        )?;
+    }
+    Ok(())
+}
+
+/// Deliver `message` (headers and body, CRLF line endings) to `recipients`
+pub(super) fn deliver(
+    settings: &SmtpSettings,
+    password: Option<&str>,
+    recipients: &[String],
+    message: &str,
+) -> Result<(), String> {
+    let host = settings.host.as_str();
+    let addr = (host, settings.port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {host}: {e}"))?
+        .next()
+        .ok_or_else(|| format!("No addresses found for {host}"))?;
+    let tcp = TcpStream::connect_timeout(&addr, TIMEOUT)
+        .map_err(|e| format!("Failed to connect to {host}:{}: {e}", settings.port))?;
+    tcp.set_read_timeout(Some(TIMEOUT))
+        .and_then(|_| tcp.set_write_timeout(Some(TIMEOUT)))
+        .map_err(|e| format!("Failed to configure socket: {e}"))?;
+
+    let stream = match settings.security {
+        SmtpSecurity::Tls => tls(tcp, host)?,
+        SmtpSecurity::StartTls | SmtpSecurity::None => Stream::Plain(tcp),
+    };
+    let mut conn = Connection {
+        stream,
+        buf: Vec::new(),
+    };
+    conn.expect(&[220], "the connection")?;
+    let ehlo_line = format!("EHLO {CLIENT_NAME}");
+    let mut ehlo = conn.command(&ehlo_line, &[250], "EHLO")?;
+    if settings.security == SmtpSecurity::StartTls {
+        if !ehlo.has_extension("STARTTLS") {
+            return Err(format!("{host} doesn't offer STARTTLS"));
+        }
+        conn.command("STARTTLS", &[220], "STARTTLS")?;
+        conn = conn.start_tls(host)?;
+        ehlo = conn.command(&ehlo_line, &[250], "EHLO")?;
+    }
+
+    if let Some(username) = &settings.username {
+        let password = password.ok_or("No SMTP password set")?;
+        authenticate(&mut conn, &ehlo, username, password)?;
+    }
+
+    conn.command(
+        &format!("MAIL FROM:<{}>", settings.from),
+        &[250],
+        "the sender",
+    )?;
+    for recipient in recipients {
+        conn.command(
+            &format!("RCPT TO:<{recipient}>"),
+            &[250, 251],
+            &format!("recipient {recipient}"),
+        )?;
+    }
+    conn.command("DATA", &[354], "DATA")?;
+    conn.write(&dot_stuff(message))?;
+    conn.command(".", &[250], "the message")?;
+    let _ = conn.command("QUIT", &[221], "QUIT");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    /// One-connection SMTP server that accepts everything and returns the
+    /// lines it received
+    fn fake_server(ehlo: &'static str) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut seen = Vec::new();
+            let mut in_data = false;
+            writer.write_all(b"220 test ESMTP\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end_matches("\r\n").to_string();
+                seen.push(line.clone());
+                if in_data {
+                    if line == "." {
+                        in_data = false;
+                        writer.write_all(b"250 queued\r\n").unwrap();
+                    }
+                    continue;
+                }
+                let reply = match line.split(' ').next().unwrap_or_default() {
+                    "EHLO" => ehlo,
+                    "AUTH" => "235 ok\r\n",
+                    "MAIL" | "RCPT" => "250 ok\r\n",
+                    "DATA" => {
+                        in_data = true;
+                        "354 go ahead\r\n"
+                    }
+                    "QUIT" => "221 bye\r\n",
+                    _ => "500 unknown command\r\n",
+                };
+                writer.write_all(reply.as_bytes()).unwrap();
+            }
+            seen
+        });
+        (port, handle)
+    }
+
+    fn settings(port: u16, security: SmtpSecurity) -> SmtpSettings {
+        SmtpSettings {
+            host: "127.0.0.1".to_string(),
+            port,
+            security,
+            username: Some("dev".to_string()),
+            from: "jean@example.com".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_dot_stuff() {
+        assert_eq!(dot_stuff("a\r\n.b\r\n..c"), "a\r\n..b\r\n...c\r\n");
+    }
+
+    #[test]
+    fn test_deliver() {
+        let (port, server) = fake_server("250-test\r\n250 AUTH LOGIN PLAIN\r\n");
+        deliver(
+            &settings(port, SmtpSecurity::None),
+            Some("hunter2"),
+            &["a@example.com".to_string()],
+            "Subject: hi\r\n\r\n.hidden\r\n",
+        )
+        .unwrap();
+        let seen = server.join().unwrap();
+        assert_eq!(
+            seen,
+            vec![
+                "EHLO jean.local",
+                "AUTH PLAIN AGRldgBodW50ZXIy",
+                "MAIL FROM:<jean@example.com>",
+                "RCPT TO:<a@example.com>",
+                "DATA",
+                "Subject: hi",
+                "",
+                "..hidden",
+                ".",
+                "QUIT",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deliver_requires_starttls() {
+        let (port, _server) = fake_server("250 test\r\n");
+        let err = deliver(
+            &settings(port, SmtpSecurity::StartTls),
+            Some("hunter2"),
+            &["a@example.com".to_string()],
+            "Subject: hi\r\n\r\nbody\r\n",
+        )
+        .unwrap_err();
+        assert!(err.contains("STARTTLS"), "{err}");
+    }
+}
@@ -0,0 +1,62 @@
+use tauri::AppHandle;
+
+use super::{EmergencyStatus, EmergencyStopReport};
+use crate::chat::registry::{cancel_process, get_running_sessions};
+use crate::chat::storage::load_metadata;
+use crate::http_server::EmitExt;
+
+#[tauri::command]
+pub async fn get_emergency_status(app: AppHandle) -> EmergencyStatus {
+    super::status(&app)
+}
+
+/// Kill every running session, discard warm processes, cancel downloads and
+/// keep auto-approval off until `clear_emergency_stop`
+#[tauri::command]
+pub async fn emergency_stop(app: AppHandle) -> Result<EmergencyStopReport, String> {
+    let stopped_at = crate::runtime::now();
+    log::warn!("Emergency stop");
+    let downloads_cancelled = super::engage(&app, stopped_at);
+
+    let mut sessions_stopped = Vec::new();
+    for session_id in get_running_sessions() {
+        let worktree_id = load_metadata(&app, &session_id)
+            .ok()
+            .flatten()
+            .map(|m| m.worktree_id)
+            .unwrap_or_default();
+        match cancel_process(&app, &session_id, &worktree_id) {
+            Ok(true) => sessions_stopped.push(session_id),
+            Ok(false) => {}
+            Err(e) => log::error!("Emergency stop failed to kill session {session_id}: {e}"),
+        }
+    }
+    crate::chat::warm_pool::clear(&app);
+
+    let report = EmergencyStopReport {
+        stopped_at,
+        sessions_stopped,
+        downloads_cancelled,
+    };
+    log::warn!(
+        "Emergency stop halted {} session(s) and {} download(s)",
+        report.sessions_stopped.len(),
+        report.downloads_cancelled
+    );
+    if let Err(e) = app.emit_all("emergency:stopped", &report) {
+        log::error!("Failed to emit emergency:stopped: {e}");
+    }
+    Ok(report)
+}
+
+/// Re-enable event rules, auto-approval and the message queue
+#[tauri::command]
+pub async fn clear_emergency_stop(app: AppHandle) -> Result<EmergencyStatus, String> {
+    super::release(&app)?;
+    log::info!("Emergency stop cleared");
+    let status = super::status(&app);
+    if let Err(e) = app.emit_all("emergency:cleared", &status) {
+        log::error!("Failed to emit emergency:cleared: {e}");
+    }
+    Ok(status)
+}
@@ -0,0 +1,248 @@
+//! Emergency stop
+//!
+//! `emergency_stop` halts everything agents are doing at once: running
+//! sessions are killed with their process trees, idle warm processes are
+//! discarded, and in-flight downloads (CLI, tool and model installs) abort.
+//! Until `clear_emergency_stop` is called, the stop stays engaged (also
+//! across restarts): event rules don't fire, the frontend holds its message
+//! queue, and new runs start in plan mode without pre-approved tools, so
+//! nothing runs without a prompt. Plan mode holds for aider and Codex too
+//! (see `chat::external`); custom backends don't start at all. The result
+//! is reported in a single `emergency:stopped` event.
+
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::PathProvider;
+
+pub mod commands;
+
+/// Engaged stop (in app data)
+const STATE_FILE: &str = "emergency_stop.json";
+
+/// Error for downloads aborted by a stop
+pub const DOWNLOAD_CANCELLED: &str = "Download cancelled by emergency stop";
+
+/// How often an awaited download checks for a stop
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Stop flags shared by everything that checks for a stop
+struct StopSignal {
+    engaged: AtomicBool,
+    /// Bumped by every stop; downloads started before the bump abort
+    generation: AtomicU64,
+    /// Downloads currently in flight
+    active_downloads: AtomicUsize,
+}
+
+impl StopSignal {
+    const fn new() -> Self {
+        Self {
+            engaged: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+            active_downloads: AtomicUsize::new(0),
+        }
+    }
+
+    fn is_engaged(&self) -> bool {
+        self.engaged.load(Ordering::Relaxed)
+    }
+
+    fn status(&self, app: &impl PathProvider) -> EmergencyStatus {
+        let state = load_state(app);
+        EmergencyStatus {
+            engaged: self.is_engaged(),
+            stopped_at: state.stopped_at.filter(|_| self.is_engaged()),
+        }
+    }
+
+    fn engage(&self, app: &impl PathProvider, stopped_at: u64) -> usize {
+        self.engaged.store(true, Ordering::Relaxed);
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        // Engaged in memory even if it can't be saved; the stop must not fail
+        if let Err(e) = save_state(
+            app,
+            &StopState {
+                engaged: true,
+                stopped_at: Some(stopped_at),
+            },
+        ) {
+            log::error!("Failed to persist emergency stop: {e}");
+        }
+        self.active_downloads.load(Ordering::Relaxed)
+    }
+
+    fn release(&self, app: &impl PathProvider) -> Result<(), String> {
+        save_state(app, &StopState::default())?;
+        self.engaged.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn restrict_execution_mode(&self, execution_mode: Option<String>) -> Option<String> {
+        match execution_mode.as_deref() {
+            Some("build") | Some("yolo") if self.is_engaged() => {
+                log::warn!(
+                    "Emergency stop engaged, running in plan mode instead of {execution_mode:?}"
+                );
+                Some("plan".to_string())
+            }
+            _ => execution_mode,
+        }
+    }
+}
+
+/// The app's stop (tests use their own [`StopSignal`] so they don't cancel
+/// each other's downloads)
+static STOP: StopSignal = StopSignal::new();
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct StopState {
+    engaged: bool,
+    stopped_at: Option<u64>,
+}
+
+/// Whether the stop is engaged (for the frontend)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyStatus {
+    pub engaged: bool,
+    /// Unix seconds of the stop (None when not engaged)
+    pub stopped_at: Option<u64>,
+}
+
+/// What a stop halted (payload of `emergency:stopped`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyStopReport {
+    pub stopped_at: u64,
+    /// Sessions whose runs were killed
+    pub sessions_stopped: Vec<String>,
+    pub downloads_cancelled: usize,
+}
+
+fn load_state(app: &impl PathProvider) -> StopState {
+    app.app_data_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(STATE_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(app: &impl PathProvider, state: &StopState) -> Result<(), String> {
+    let dir = app.app_data_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data directory: {e}"))?;
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize emergency stop: {e}"))?;
+    fs::write(dir.join(STATE_FILE), content)
+        .map_err(|e| format!("Failed to save emergency stop: {e}"))
+}
+
+pub fn is_engaged() -> bool {
+    STOP.is_engaged()
+}
+
+pub fn status(app: &impl PathProvider) -> EmergencyStatus {
+    STOP.status(app)
+}
+
+/// Engage the stop and abort in-flight downloads; returns how many there were
+pub(crate) fn engage(app: &impl PathProvider, stopped_at: u64) -> usize {
+    STOP.engage(app, stopped_at)
+}
+
+/// Lift the stop
+pub(crate) fn release(app: &impl PathProvider) -> Result<(), String> {
+    STOP.release(app)
+}
+
+/// Execution mode for a new run: while engaged, nothing is auto-approved
+pub fn restrict_execution_mode(execution_mode: Option<String>) -> Option<String> {
+    STOP.restrict_execution_mode(execution_mode)
+}
+
+/// Restore the stop from the previous run (call at startup)
+pub fn init(app: &impl PathProvider) {
+    let engaged = load_state(app).engaged;
+    STOP.engaged.store(engaged, Ordering::Relaxed);
+    if engaged {
+        log::warn!("Emergency stop is still engaged");
+    }
+}
+
+/// An in-flight download that a stop can cancel
+pub struct DownloadGuard<'a> {
+    signal: &'a StopSignal,
+    generation: u64,
+}
+
+impl DownloadGuard<'static> {
+    /// Track a download for the stop
+    pub fn start() -> Self {
+        Self::start_on(&STOP)
+    }
+}
+
+impl<'a> DownloadGuard<'a> {
+    fn start_on(signal: &'a StopSignal) -> Self {
+        signal.active_downloads.fetch_add(1, Ordering::Relaxed);
+        Self {
+            signal,
+            generation: signal.generation.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Err([`DOWNLOAD_CANCELLED`]) once a stop happened after the download started
+    pub fn check(&self) -> Result<(), String> {
+        if self.signal.generation.load(Ordering::Relaxed) != self.generation {
+            return Err(DOWNLOAD_CANCELLED.to_string());
+        }
+        Ok(())
+    }
+
+    /// Resolves when a stop happens (race against a long await)
+    pub async fn stopped(&self) {
+        while self.check().is_ok() {
+            tokio::time::sleep(STOP_POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl Drop for DownloadGuard<'_> {
+    fn drop(&mut self) {
+        self.signal.active_downloads.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_stop_cancels_downloads_and_persists() {
+        // A signal of its own, so the stop doesn't cancel other tests' downloads
+        let signal = StopSignal::new();
+        let paths = TempPaths::new();
+        let download = DownloadGuard::start_on(&signal);
+        assert!(download.check().is_ok());
+
+        assert_eq!(signal.engage(&paths, 100), 1);
+        assert_eq!(download.check(), Err(DOWNLOAD_CANCELLED.to_string()));
+        assert_eq!(
+            signal.restrict_execution_mode(Some("yolo".to_string())),
+            Some("plan".to_string())
+        );
+        assert_eq!(signal.status(&paths).stopped_at, Some(100));
+
+        // Downloads started after the stop aren't affected
+        assert!(DownloadGuard::start_on(&signal).check().is_ok());
+
+        signal.release(&paths).unwrap();
+        assert!(!signal.status(&paths).engaged);
+        assert_eq!(
+            signal.restrict_execution_mode(Some("yolo".to_string())),
+            Some("yolo".to_string())
+        );
+    }
+}
@@ -0,0 +1,35 @@
+//! Tauri commands for Focus / do-not-disturb status
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::DigestEntry;
+
+/// Current Focus status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusState {
+    /// Whether the OS Focus state could be read
+    pub supported: bool,
+    /// Whether the OS is in Focus / do-not-disturb
+    pub active: bool,
+    /// Whether `respect_focus_mode` is enabled
+    pub respected: bool,
+    /// Notifications held for the digest, oldest first
+    pub held: Vec<DigestEntry>,
+}
+
+/// Get the OS Focus state and the notifications held during it
+#[tauri::command]
+pub async fn get_focus_state(app: AppHandle) -> Result<FocusState, String> {
+    let prefs = crate::load_preferences(app).await?;
+    let focus_active = tauri::async_runtime::spawn_blocking(crate::platform::focus_active)
+        .await
+        .map_err(|e| format!("Failed to read Focus state: {e}"))?;
+
+    Ok(FocusState {
+        supported: focus_active.is_some(),
+        active: focus_active.unwrap_or(false),
+        respected: prefs.respect_focus_mode,
+        held: super::held(),
+    })
+}
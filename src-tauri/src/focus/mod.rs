@@ -0,0 +1,231 @@
+//! Respecting OS Focus / do-not-disturb
+//!
+//! While macOS Focus, Windows Focus Assist or GNOME's do-not-disturb is on
+//! (`crate::platform::focus_active`), native notifications and spoken
+//! summaries are held in a digest instead of interrupting. Categories
+//! enabled in `focus_overrides` break through. When Focus ends, the watcher
+//! delivers the digest as a single notification and a `focus:digest` event.
+//! `respect_focus_mode` turns all of this off.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::background_tasks::supervisor::supervise;
+use crate::http_server::EmitExt;
+use crate::runtime::now;
+use crate::AppPreferences;
+
+pub mod commands;
+
+/// How often the watcher checks whether Focus has ended
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Titles listed in the digest notification before "and N more"
+const DIGEST_TITLES: usize = 5;
+
+/// Held notifications, oldest first
+static DIGEST: Lazy<Mutex<Vec<DigestEntry>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    SessionFinished,
+    /// A session asked a question or presented a plan
+    InputNeeded,
+    /// Fired by a user-defined event rule
+    EventRule,
+    #[default]
+    General,
+}
+
+/// A notification or spoken summary held during Focus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestEntry {
+    pub category: NotificationCategory,
+    pub title: String,
+    pub body: Option<String>,
+    pub created_at: u64,
+}
+
+/// Whether a notification of `category` should wait for the digest
+pub fn should_hold(
+    prefs: &AppPreferences,
+    category: NotificationCategory,
+    focus_active: Option<bool>,
+) -> bool {
+    let overrides = &prefs.focus_overrides;
+    let breaks_through = match category {
+        NotificationCategory::SessionFinished => overrides.session_finished,
+        NotificationCategory::InputNeeded => overrides.input_needed,
+        NotificationCategory::EventRule => overrides.event_rules,
+        NotificationCategory::General => overrides.general,
+    };
+    prefs.respect_focus_mode && focus_active == Some(true) && !breaks_through
+}
+
+/// Hold a notification for the digest if Focus is on; returns whether it was held
+pub async fn hold_if_focused(
+    app: &AppHandle,
+    category: NotificationCategory,
+    title: &str,
+    body: Option<&str>,
+) -> bool {
+    let prefs = match crate::load_preferences(app.clone()).await {
+        Ok(prefs) => prefs,
+        Err(e) => {
+            log::warn!("Failed to load preferences for Focus: {e}");
+            return false;
+        }
+    };
+    if !prefs.respect_focus_mode {
+        return false;
+    }
+    let focus_active = tauri::async_runtime::spawn_blocking(crate::platform::focus_active)
+        .await
+        .ok()
+        .flatten();
+    if !should_hold(&prefs, category, focus_active) {
+        return false;
+    }
+    log::trace!("Holding {category:?} notification during Focus: {title}");
+    DIGEST.lock().unwrap().push(DigestEntry {
+        category,
+        title: title.to_string(),
+        body: body.map(str::to_string),
+        created_at: now(),
+    });
+    true
+}
+
+/// Show a native notification, or hold it for the digest during Focus
+pub async fn notify(
+    app: &AppHandle,
+    category: NotificationCategory,
+    title: String,
+    body: Option<String>,
+) -> Result<(), String> {
+    if hold_if_focused(app, category, &title, body.as_deref()).await {
+        return Ok(());
+    }
+    crate::show_native_notification(app, title, body)
+}
+
+/// Held notifications, oldest first
+pub fn held() -> Vec<DigestEntry> {
+    DIGEST.lock().unwrap().clone()
+}
+
+/// Title and body of the notification summarizing held entries
+pub fn digest_notification(entries: &[DigestEntry]) -> (String, String) {
+    let title = match entries.len() {
+        1 => "1 notification while Focus was on".to_string(),
+        n => format!("{n} notifications while Focus was on"),
+    };
+    let mut lines: Vec<String> = entries
+        .iter()
+        .take(DIGEST_TITLES)
+        .map(|e| e.title.clone())
+        .collect();
+    if entries.len() > DIGEST_TITLES {
+        lines.push(format!("and {} more", entries.len() - DIGEST_TITLES));
+    }
+    (title, lines.join("\n"))
+}
+
+/// Deliver the digest if Focus has ended (or is no longer respected)
+fn deliver_digest(app: &AppHandle) -> Result<(), String> {
+    if DIGEST.lock().unwrap().is_empty() {
+        return Ok(());
+    }
+    let prefs = tauri::async_runtime::block_on(crate::load_preferences(app.clone()))?;
+    if prefs.respect_focus_mode && crate::platform::focus_active() == Some(true) {
+        return Ok(());
+    }
+    let entries = std::mem::take(&mut *DIGEST.lock().unwrap());
+    log::trace!(
+        "Focus ended, delivering {} held notifications",
+        entries.len()
+    );
+    if let Err(e) = app.emit_all("focus:digest", &entries) {
+        log::error!("Failed to emit focus:digest event: {e}");
+    }
+    let (title, body) = digest_notification(&entries);
+    crate::show_native_notification(app, title, Some(body))
+}
+
+/// Start the thread that delivers the digest once Focus ends
+pub fn start_watcher(app: AppHandle) {
+    supervise("focus-watcher", move || loop {
+        std::thread::sleep(WATCH_INTERVAL);
+        if let Err(e) = deliver_digest(&app) {
+            log::error!("Failed to deliver Focus digest: {e}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FocusOverrides;
+
+    fn entry(title: &str) -> DigestEntry {
+        DigestEntry {
+            category: NotificationCategory::SessionFinished,
+            title: title.to_string(),
+            body: None,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_should_hold() {
+        let mut prefs = AppPreferences {
+            focus_overrides: FocusOverrides {
+                input_needed: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let finished = NotificationCategory::SessionFinished;
+        assert!(should_hold(&prefs, finished, Some(true)));
+        assert!(!should_hold(&prefs, finished, Some(false)));
+        // Unknown Focus state never holds
+        assert!(!should_hold(&prefs, finished, None));
+        assert!(!should_hold(
+            &prefs,
+            NotificationCategory::InputNeeded,
+            Some(true)
+        ));
+
+        prefs.respect_focus_mode = false;
+        assert!(!should_hold(&prefs, finished, Some(true)));
+    }
+
+    #[test]
+    fn test_digest_notification() {
+        let (title, body) = digest_notification(&[entry("Fix build finished")]);
+        assert_eq!(title, "1 notification while Focus was on");
+        assert_eq!(body, "Fix build finished");
+
+        let entries: Vec<_> = (1..=7).map(|i| entry(&format!("Session {i}"))).collect();
+        let (title, body) = digest_notification(&entries);
+        assert_eq!(title, "7 notifications while Focus was on");
+        assert_eq!(body.lines().count(), DIGEST_TITLES + 1);
+        assert!(body.ends_with("and 2 more"));
+    }
+
+    #[test]
+    fn test_parse_focus_assertions() {
+        use crate::platform::parse_focus_assertions;
+        let on = r#"{"data":[{"storeAssertionRecords":[{"assertionDetails":{}}]}]}"#;
+        let off = r#"{"data":[{"storeAssertionRecords":[]}]}"#;
+        assert_eq!(parse_focus_assertions(on), Some(true));
+        assert_eq!(parse_focus_assertions(off), Some(false));
+        assert_eq!(parse_focus_assertions(r#"{"data":[{}]}"#), Some(false));
+        assert_eq!(parse_focus_assertions("not json"), None);
+    }
+}
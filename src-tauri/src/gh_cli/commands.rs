@@ -4,12 +4,11 @@ use crate::platform::silent_command;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
-use super::config::{ensure_gh_cli_dir, get_gh_cli_binary_path};
+use super::config::{ensure_gh_cli_dir, get_gh_cli_binary_path, ReleaseSource};
+use crate::http_client::github;
+use crate::http_client::retry::{emit_retry, send_with_retry, RetryAttempt, RetryPolicy};
 use crate::http_server::EmitExt;
 
-/// GitHub API URL for releases
-const GITHUB_RELEASES_API: &str = "https://api.github.com/repos/cli/cli/releases";
-
 /// Status of the GitHub CLI installation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GhCliStatus {
@@ -43,6 +42,12 @@ pub struct GhInstallProgress {
     pub message: String,
     /// Percentage complete (0-100)
     pub percent: u8,
+    /// Bytes downloaded so far (downloading stage only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downloaded_bytes: Option<u64>,
+    /// Size of the download, if the server reported it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
 }
 
 /// GitHub API release response structure
@@ -66,6 +71,16 @@ struct GitHubAsset {
 pub async fn check_gh_cli_installed(app: AppHandle) -> Result<GhCliStatus, String> {
     log::trace!("Checking GitHub CLI installation status");
 
+    if let Some(scenario) = crate::mock::active_scenario() {
+        return Ok(GhCliStatus {
+            installed: scenario.cli_installed,
+            version: scenario
+                .cli_installed
+                .then(|| crate::mock::MOCK_CLI_VERSION.to_string()),
+            path: None,
+        });
+    }
+
     let binary_path = get_gh_cli_binary_path(&app)?;
 
     if !binary_path.exists() {
@@ -112,19 +127,23 @@ pub async fn check_gh_cli_installed(app: AppHandle) -> Result<GhCliStatus, Strin
 
 /// Get available GitHub CLI versions from GitHub releases API
 #[tauri::command]
-pub async fn get_available_gh_versions() -> Result<Vec<GhReleaseInfo>, String> {
+pub async fn get_available_gh_versions(app: AppHandle) -> Result<Vec<GhReleaseInfo>, String> {
     log::trace!("Fetching available GitHub CLI versions from GitHub API");
 
-    let client = reqwest::Client::builder()
+    let client = crate::http_client::client_builder()
         .user_agent("Jean-App/1.0")
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
 
-    let response = client
-        .get(GITHUB_RELEASES_API)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch releases: {e}"))?;
+    let url = ReleaseSource::github().releases_api;
+    let response = send_with_retry(
+        &RetryPolicy::default(),
+        "GitHub CLI releases",
+        || github::authorize(client.get(&url), &url),
+        |retry| emit_retry(&app, retry),
+    )
+    .await
+    .map_err(|e| format!("Failed to fetch releases: {e}"))?;
 
     if !response.status().is_success() {
         return Err(format!("GitHub API returned status: {}", response.status()));
@@ -201,7 +220,20 @@ fn get_gh_platform() -> Result<(&'static str, &'static str), String> {
 #[tauri::command]
 pub async fn install_gh_cli(app: AppHandle, version: Option<String>) -> Result<(), String> {
     log::trace!("Installing GitHub CLI, version: {:?}", version);
+    let _power_guard = crate::power::PowerGuard::acquire(&app, "GitHub CLI install").await;
 
+    // Restart the install if the system slept or the network changed mid-download
+    let result = crate::power::monitor::with_wake_retry(&app, "GitHub CLI", || {
+        install_gh_cli_once(app.clone(), version.clone())
+    })
+    .await;
+    if let Err(e) = &result {
+        crate::announcements::announce_install_failure(&app, "GitHub CLI", e).await;
+    }
+    result
+}
+
+async fn install_gh_cli_once(app: AppHandle, version: Option<String>) -> Result<(), String> {
     // Check if any Claude processes are running - Claude may use gh for GitHub operations
     let running_sessions = crate::chat::registry::get_running_sessions();
     if !running_sessions.is_empty() {
@@ -219,96 +251,302 @@ pub async fn install_gh_cli(app: AppHandle, version: Option<String>) -> Result<(
     // Emit progress: starting
     emit_progress(&app, "starting", "Preparing installation...", 0);
 
+    let source = ReleaseSource::github();
+
     // Determine version (use provided or fetch latest)
     let version = match version {
         Some(v) => v,
-        None => fetch_latest_gh_version().await?,
+        None => {
+            fetch_latest_gh_version(&source, |retry| {
+                emit_progress(&app, "starting", &retry.message(), 0);
+                emit_retry(&app, retry);
+            })
+            .await?
+        }
     };
 
     // Detect platform
     let (platform, archive_ext) = get_gh_platform()?;
     log::trace!("Installing version {version} for platform {platform}");
 
-    // Build download URL
-    // Format: https://github.com/cli/cli/releases/download/v{version}/gh_{version}_{platform}.{ext}
-    let archive_name = format!("gh_{version}_{platform}.{archive_ext}");
-    let download_url =
-        format!("https://github.com/cli/cli/releases/download/v{version}/{archive_name}");
-    log::trace!("Downloading from: {download_url}");
-
     // Emit progress: downloading
     emit_progress(&app, "downloading", "Downloading GitHub CLI...", 20);
 
-    // Download the archive
-    let client = reqwest::Client::builder()
+    let archive_content = download_gh_archive(
+        &source,
+        &version,
+        platform,
+        archive_ext,
+        &cli_dir,
+        |downloaded, total| emit_download_progress(&app, downloaded, total),
+    )
+    .await?;
+
+    // Emit progress: verifying the download
+    emit_progress(
+        &app,
+        "verifying_download",
+        "Verifying download checksum...",
+        30,
+    );
+
+    let expected_sha256 = fetch_gh_checksum(&source, &version, platform, archive_ext).await?;
+    let partial = gh_partial_path(&cli_dir, &version, platform, archive_ext);
+    verify_gh_archive(&archive_content, &expected_sha256, &partial)?;
+
+    // Emit progress: extracting
+    emit_progress(&app, "extracting", "Extracting archive...", 40);
+
+    let extracted_binary_path =
+        extract_gh_archive(&archive_content, &version, platform, archive_ext, &cli_dir)?;
+    // Extracted, so the download is no longer needed
+    let _ = std::fs::remove_file(&partial);
+
+    // Emit progress: installing
+    emit_progress(&app, "installing", "Installing GitHub CLI...", 60);
+
+    install_gh_binary(&extracted_binary_path, &cli_dir, &binary_path)?;
+
+    // Emit progress: verifying
+    emit_progress(&app, "verifying", "Verifying installation...", 80);
+
+    verify_gh_binary(&binary_path)?;
+    crate::platform::motw::release_verified_binary(&binary_path);
+
+    // Emit progress: complete
+    emit_progress(&app, "complete", "Installation complete!", 100);
+
+    log::trace!("GitHub CLI installed successfully at {:?}", binary_path);
+    Ok(())
+}
+
+/// File name of a release archive for a platform
+fn gh_archive_name(version: &str, platform: &str, archive_ext: &str) -> String {
+    format!("gh_{version}_{platform}.{archive_ext}")
+}
+
+/// URL of a release archive for a platform
+pub(crate) fn gh_archive_url(
+    source: &ReleaseSource,
+    version: &str,
+    platform: &str,
+    archive_ext: &str,
+) -> String {
+    // Format: {download_base}/v{version}/gh_{version}_{platform}.{ext}
+    let archive_name = gh_archive_name(version, platform, archive_ext);
+    format!("{}/v{version}/{archive_name}", source.download_base)
+}
+
+/// SHA-256 of a release archive, from the release's `gh_{version}_checksums.txt`
+pub(crate) async fn fetch_gh_checksum(
+    source: &ReleaseSource,
+    version: &str,
+    platform: &str,
+    archive_ext: &str,
+) -> Result<String, String> {
+    let url = format!(
+        "{}/v{version}/gh_{version}_checksums.txt",
+        source.download_base
+    );
+    let client = crate::http_client::client_builder()
         .user_agent("Jean-App/1.0")
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
-
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to download GitHub CLI: {e}"))?;
-
+    let response = send_with_retry(
+        &RetryPolicy::default(),
+        "GitHub CLI checksums",
+        || client.get(&url),
+        |_| {},
+    )
+    .await
+    .map_err(|e| format!("Failed to fetch GitHub CLI checksums: {e}"))?;
     if !response.status().is_success() {
         return Err(format!(
-            "Failed to download GitHub CLI: HTTP {}",
+            "Failed to fetch GitHub CLI checksums: HTTP {}",
             response.status()
         ));
     }
-
-    let archive_content = response
-        .bytes()
+    let checksums = response
+        .text()
         .await
-        .map_err(|e| format!("Failed to read archive content: {e}"))?;
+        .map_err(|e| format!("Failed to read GitHub CLI checksums: {e}"))?;
+    let archive_name = gh_archive_name(version, platform, archive_ext);
+    crate::http_client::downloads::checksum_for(&checksums, &archive_name)
+        .ok_or_else(|| format!("No checksum for {archive_name} in {url}"))
+}
 
-    log::trace!("Downloaded {} bytes", archive_content.len());
+/// Check a downloaded archive against its published SHA-256. On a mismatch
+/// the partial file is removed so the next attempt downloads it afresh.
+pub(crate) fn verify_gh_archive(
+    archive_content: &[u8],
+    expected_sha256: &str,
+    partial: &std::path::Path,
+) -> Result<(), String> {
+    crate::claude_cli::verify_checksum(archive_content, expected_sha256).map_err(|e| {
+        let _ = std::fs::remove_file(partial);
+        format!("GitHub CLI download failed verification: {e}")
+    })
+}
 
-    // Emit progress: extracting
-    emit_progress(&app, "extracting", "Extracting archive...", 40);
+/// A release archive resolved for this platform
+pub(crate) struct GhDownload {
+    pub url: String,
+    pub version: String,
+    pub platform: &'static str,
+    pub archive_ext: &'static str,
+    /// Published SHA-256 of the archive
+    pub sha256: String,
+}
 
+/// Resolve the release archive to download (the latest release when
+/// `version` is None)
+pub(crate) async fn resolve_gh_download(version: Option<String>) -> Result<GhDownload, String> {
+    let source = ReleaseSource::github();
+    let version = match version {
+        Some(v) => v,
+        None => fetch_latest_gh_version(&source, |_| {}).await?,
+    };
+    let (platform, archive_ext) = get_gh_platform()?;
+    let sha256 = fetch_gh_checksum(&source, &version, platform, archive_ext).await?;
+    Ok(GhDownload {
+        url: gh_archive_url(&source, &version, platform, archive_ext),
+        version,
+        platform,
+        archive_ext,
+        sha256,
+    })
+}
+
+/// Check, extract, install and verify a downloaded release archive
+pub(crate) fn install_gh_archive(
+    app: &AppHandle,
+    download: &GhDownload,
+    archive_content: &[u8],
+) -> Result<(), String> {
+    crate::claude_cli::verify_checksum(archive_content, &download.sha256)
+        .map_err(|e| format!("GitHub CLI download failed verification: {e}"))?;
+    let cli_dir = ensure_gh_cli_dir(app)?;
+    let binary_path = get_gh_cli_binary_path(app)?;
+    let extracted_binary_path = extract_gh_archive(
+        archive_content,
+        &download.version,
+        download.platform,
+        download.archive_ext,
+        &cli_dir,
+    )?;
+    install_gh_binary(&extracted_binary_path, &cli_dir, &binary_path)?;
+    verify_gh_binary(&binary_path)?;
+    crate::platform::motw::release_verified_binary(&binary_path);
+    log::trace!("GitHub CLI installed successfully at {:?}", binary_path);
+    Ok(())
+}
+
+/// Download a release archive for a platform, via a partial file in
+/// `{cli_dir}/downloads` so an interrupted download resumes on the next
+/// attempt. The partial file stays until the archive has been verified and
+/// extracted, or is removed by `verify_gh_archive` on a checksum mismatch.
+///
+/// Jean doesn't download Codex archives itself (they're installed from a
+/// file or a package manager), so the gh archive is the large download that
+/// resumes. The partial file is kept out of `temp`, which the stale file
+/// cleanup sweeps, so a download interrupted for more than an hour still
+/// resumes.
+pub(crate) async fn download_gh_archive(
+    source: &ReleaseSource,
+    version: &str,
+    platform: &str,
+    archive_ext: &str,
+    cli_dir: &std::path::Path,
+    on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<Vec<u8>, String> {
+    let download_url = gh_archive_url(source, version, platform, archive_ext);
+    log::trace!("Downloading from: {download_url}");
+
+    let client = crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+    let partial = gh_partial_path(cli_dir, version, platform, archive_ext);
+    crate::http_client::downloads::download_resumable(
+        &client,
+        &download_url,
+        &partial,
+        "GitHub CLI",
+        on_progress,
+    )
+    .await
+}
+
+/// Directory of `{cli_dir}` holding resumable downloads
+const DOWNLOADS_DIR: &str = "downloads";
+
+/// Where `download_gh_archive` keeps an unfinished download
+pub(crate) fn gh_partial_path(
+    cli_dir: &std::path::Path,
+    version: &str,
+    platform: &str,
+    archive_ext: &str,
+) -> std::path::PathBuf {
+    cli_dir.join(DOWNLOADS_DIR).join(format!(
+        "{}.part",
+        gh_archive_name(version, platform, archive_ext)
+    ))
+}
+
+/// Extract a release archive into `{cli_dir}/temp`, returning the binary's path
+pub(crate) fn extract_gh_archive(
+    archive_content: &[u8],
+    version: &str,
+    platform: &str,
+    archive_ext: &str,
+    cli_dir: &std::path::Path,
+) -> Result<std::path::PathBuf, String> {
     // Create temp directory for extraction
     let temp_dir = cli_dir.join("temp");
     std::fs::create_dir_all(&temp_dir)
         .map_err(|e| format!("Failed to create temp directory: {e}"))?;
 
-    // Extract the archive
-    let extracted_binary_path = if archive_ext == "zip" {
-        extract_zip(&archive_content, &temp_dir, &version, platform)?
+    if archive_ext == "zip" {
+        extract_zip(archive_content, &temp_dir, version, platform)
     } else {
-        extract_tar_gz(&archive_content, &temp_dir, &version, platform)?
-    };
-
-    // Emit progress: installing
-    emit_progress(&app, "installing", "Installing GitHub CLI...", 60);
+        extract_tar_gz(archive_content, &temp_dir, version, platform)
+    }
+}
 
+/// Move an extracted binary to `binary_path`, clean up and mark it executable
+pub(crate) fn install_gh_binary(
+    extracted_binary_path: &std::path::Path,
+    cli_dir: &std::path::Path,
+    binary_path: &std::path::Path,
+) -> Result<(), String> {
     // Move binary to final location
-    std::fs::copy(&extracted_binary_path, &binary_path)
+    std::fs::copy(extracted_binary_path, binary_path)
         .map_err(|e| format!("Failed to copy binary: {e}"))?;
 
     // Clean up temp directory
-    let _ = std::fs::remove_dir_all(&temp_dir);
-
-    // Emit progress: verifying
-    emit_progress(&app, "verifying", "Verifying installation...", 80);
+    let _ = std::fs::remove_dir_all(cli_dir.join("temp"));
 
     // Make sure the binary is executable
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        let mut perms = std::fs::metadata(&binary_path)
+        let mut perms = std::fs::metadata(binary_path)
             .map_err(|e| format!("Failed to get binary metadata: {e}"))?
             .permissions();
         perms.set_mode(0o755);
-        std::fs::set_permissions(&binary_path, perms)
+        std::fs::set_permissions(binary_path, perms)
             .map_err(|e| format!("Failed to set binary permissions: {e}"))?;
     }
 
-    // Verify the binary works
+    Ok(())
+}
+
+/// Run `gh --version` on an installed binary and return its output
+pub(crate) fn verify_gh_binary(binary_path: &std::path::Path) -> Result<String, String> {
     // Use the binary directly - shell wrapper causes PowerShell parsing issues on Windows
     log::trace!("Verifying binary at {:?}", binary_path);
-    let version_output = silent_command(&binary_path)
+    let version_output = silent_command(binary_path)
         .arg("--version")
         .output()
         .map_err(|e| format!("Failed to verify GitHub CLI: {e}"))?;
@@ -336,28 +574,31 @@ pub async fn install_gh_cli(app: AppHandle, version: Option<String>) -> Result<(
         .trim()
         .to_string();
     log::trace!("Verified GitHub CLI version: {installed_version}");
-
-    // Emit progress: complete
-    emit_progress(&app, "complete", "Installation complete!", 100);
-
-    log::trace!("GitHub CLI installed successfully at {:?}", binary_path);
-    Ok(())
+    Ok(installed_version)
 }
 
-/// Fetch the latest GitHub CLI version from GitHub API
-async fn fetch_latest_gh_version() -> Result<String, String> {
+/// Fetch the latest GitHub CLI version from the releases API
+/// (transient failures are retried, each retry reported to `on_retry`)
+pub(crate) async fn fetch_latest_gh_version(
+    source: &ReleaseSource,
+    on_retry: impl FnMut(&RetryAttempt),
+) -> Result<String, String> {
     log::trace!("Fetching latest GitHub CLI version");
 
-    let client = reqwest::Client::builder()
+    let client = crate::http_client::client_builder()
         .user_agent("Jean-App/1.0")
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
 
-    let response = client
-        .get(format!("{GITHUB_RELEASES_API}/latest"))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch latest release: {e}"))?;
+    let url = format!("{}/latest", source.releases_api);
+    let response = send_with_retry(
+        &RetryPolicy::default(),
+        "Latest GitHub CLI release",
+        || github::authorize(client.get(&url), &url),
+        on_retry,
+    )
+    .await
+    .map_err(|e| format!("Failed to fetch latest release: {e}"))?;
 
     if !response.status().is_success() {
         return Err(format!(
@@ -381,7 +622,7 @@ async fn fetch_latest_gh_version() -> Result<String, String> {
 }
 
 /// Extract gh binary from a zip archive (macOS, Windows)
-fn extract_zip(
+pub(crate) fn extract_zip(
     archive_content: &[u8],
     temp_dir: &std::path::Path,
     version: &str,
@@ -452,7 +693,7 @@ fn extract_zip(
 }
 
 /// Extract gh binary from a tar.gz archive (Linux)
-fn extract_tar_gz(
+pub(crate) fn extract_tar_gz(
     archive_content: &[u8],
     temp_dir: &std::path::Path,
     version: &str,
@@ -497,6 +738,13 @@ pub struct GhAuthStatus {
 pub async fn check_gh_cli_auth(app: AppHandle) -> Result<GhAuthStatus, String> {
     log::trace!("Checking GitHub CLI authentication status");
 
+    if let Some(scenario) = crate::mock::active_scenario() {
+        return Ok(GhAuthStatus {
+            authenticated: scenario.cli_authenticated,
+            error: (!scenario.cli_authenticated).then(|| "Not authenticated (mock)".to_string()),
+        });
+    }
+
     let binary_path = get_gh_cli_binary_path(&app)?;
 
     if !binary_path.exists() {
@@ -538,9 +786,189 @@ fn emit_progress(app: &AppHandle, stage: &str, message: &str, percent: u8) {
         stage: stage.to_string(),
         message: message.to_string(),
         percent,
+        downloaded_bytes: None,
+        total_bytes: None,
+    };
+
+    if let Err(e) = app.emit_all("gh-cli:install-progress", &progress) {
+        log::warn!("Failed to emit install progress: {}", e);
+    }
+}
+
+/// Emit progress of the archive download (20-40%)
+fn emit_download_progress(app: &AppHandle, downloaded: u64, total: Option<u64>) {
+    use crate::http_client::downloads::{format_progress, stage_percent};
+    let progress = GhInstallProgress {
+        stage: "downloading".to_string(),
+        message: format!(
+            "Downloading GitHub CLI ({})...",
+            format_progress(downloaded, total)
+        ),
+        percent: stage_percent(downloaded, total, 20, 40),
+        downloaded_bytes: Some(downloaded),
+        total_bytes: total,
     };
 
     if let Err(e) = app.emit_all("gh-cli:install-progress", &progress) {
         log::warn!("Failed to emit install progress: {}", e);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture_path;
+    use crate::test_support::fixture_server::{FixtureResponse, FixtureServer};
+
+    const FIXTURE_VERSION: &str = "2.0.0";
+
+    /// Serve the fixture release metadata, this platform's archive and the
+    /// release checksums
+    fn fixture_server() -> (FixtureServer, ReleaseSource) {
+        let (platform, ext) = get_gh_platform().unwrap();
+        let archive_name = format!("gh_{FIXTURE_VERSION}_{platform}.{ext}");
+        let archive = std::fs::read(fixture_path(&format!("gh-cli/{archive_name}"))).unwrap();
+        let release = std::fs::read(fixture_path("gh-cli/release-latest.json")).unwrap();
+        let checksums = format!(
+            "{:x}  {archive_name}\n",
+            <sha2::Sha256 as sha2::Digest>::digest(&archive)
+        );
+
+        let server = FixtureServer::start(vec![
+            (
+                "/repos/cli/cli/releases/latest".to_string(),
+                FixtureResponse::json(release),
+            ),
+            (
+                format!("/download/v{FIXTURE_VERSION}/{archive_name}"),
+                FixtureResponse::bytes(archive),
+            ),
+            (
+                format!("/download/v{FIXTURE_VERSION}/gh_{FIXTURE_VERSION}_checksums.txt"),
+                FixtureResponse::bytes(checksums.into_bytes()),
+            ),
+        ]);
+        let source = ReleaseSource {
+            releases_api: server.url("/repos/cli/cli/releases"),
+            download_base: server.url("/download"),
+        };
+        (server, source)
+    }
+
+    #[test]
+    fn test_install_pipeline_against_fixture_server() {
+        let (server, source) = fixture_server();
+        let (platform, ext) = get_gh_platform().unwrap();
+        let cli_dir = tempfile::tempdir().unwrap();
+        let binary_path = cli_dir
+            .path()
+            .join(super::super::config::GH_CLI_BINARY_NAME);
+
+        let (archive, sha256) = tauri::async_runtime::block_on(async {
+            let version = fetch_latest_gh_version(&source, |_| {}).await.unwrap();
+            assert_eq!(version, FIXTURE_VERSION);
+            let archive =
+                download_gh_archive(&source, &version, platform, ext, cli_dir.path(), |_, _| {})
+                    .await
+                    .unwrap();
+            let sha256 = fetch_gh_checksum(&source, &version, platform, ext)
+                .await
+                .unwrap();
+            (archive, sha256)
+        });
+        let partial = gh_partial_path(cli_dir.path(), FIXTURE_VERSION, platform, ext);
+        assert!(partial.exists());
+        verify_gh_archive(&archive, &sha256, &partial).unwrap();
+        // Kept until extraction succeeds
+        assert!(partial.exists());
+
+        let extracted =
+            extract_gh_archive(&archive, FIXTURE_VERSION, platform, ext, cli_dir.path()).unwrap();
+        install_gh_binary(&extracted, cli_dir.path(), &binary_path).unwrap();
+        assert!(binary_path.exists());
+        assert!(!cli_dir.path().join("temp").exists());
+
+        // The Windows fixture "gh.exe" is a script and can't be executed
+        #[cfg(unix)]
+        assert_eq!(
+            verify_gh_binary(&binary_path).unwrap(),
+            "gh version 2.0.0 (fixture)"
+        );
+
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_checksum_mismatch_discards_partial_download() {
+        let cli_dir = tempfile::tempdir().unwrap();
+        let partial = gh_partial_path(cli_dir.path(), FIXTURE_VERSION, "linux_amd64", "tar.gz");
+        std::fs::create_dir_all(partial.parent().unwrap()).unwrap();
+        std::fs::write(&partial, b"tampered").unwrap();
+
+        let err = verify_gh_archive(b"tampered", &"0".repeat(64), &partial).unwrap_err();
+        assert!(err.starts_with("GitHub CLI download failed verification: Checksum mismatch"));
+        assert!(!partial.exists());
+    }
+
+    #[test]
+    fn test_missing_checksum_is_an_error() {
+        let (_server, source) = fixture_server();
+        let result = tauri::async_runtime::block_on(fetch_gh_checksum(
+            &source,
+            FIXTURE_VERSION,
+            "plan9_amd64",
+            "tar.gz",
+        ));
+        assert!(result
+            .unwrap_err()
+            .starts_with("No checksum for gh_2.0.0_plan9_amd64.tar.gz in "));
+    }
+
+    #[test]
+    fn test_download_reports_http_errors() {
+        let (_server, source) = fixture_server();
+        let (platform, ext) = get_gh_platform().unwrap();
+        let cli_dir = tempfile::tempdir().unwrap();
+
+        let result = tauri::async_runtime::block_on(download_gh_archive(
+            &source,
+            "9.9.9",
+            platform,
+            ext,
+            cli_dir.path(),
+            |_, _| {},
+        ));
+        assert_eq!(
+            result.unwrap_err(),
+            "Failed to download GitHub CLI: HTTP 404 Not Found"
+        );
+    }
+
+    #[test]
+    fn test_all_platform_fixtures_extract() {
+        for (platform, ext) in [
+            ("linux_amd64", "tar.gz"),
+            ("linux_arm64", "tar.gz"),
+            ("macOS_amd64", "zip"),
+            ("macOS_arm64", "zip"),
+            ("windows_amd64", "zip"),
+            ("windows_arm64", "zip"),
+        ] {
+            let archive = std::fs::read(fixture_path(&format!(
+                "gh-cli/gh_{FIXTURE_VERSION}_{platform}.{ext}"
+            )))
+            .unwrap();
+            let dir = tempfile::tempdir().unwrap();
+            let result = extract_gh_archive(&archive, FIXTURE_VERSION, platform, ext, dir.path());
+            // extract_zip looks for the host's binary name (gh vs gh.exe), so
+            // other-OS zips can only be checked for unpacking
+            let host_binary_matches =
+                ext == "tar.gz" || platform.starts_with("windows") == cfg!(windows);
+            if host_binary_matches {
+                assert!(result.is_ok(), "{platform}: {result:?}");
+            } else {
+                assert!(dir.path().join("temp").exists(), "{platform} not unpacked");
+            }
+        }
+    }
+}
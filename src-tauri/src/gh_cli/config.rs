@@ -1,7 +1,8 @@
 //! Configuration and path management for the embedded GitHub CLI
 
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+
+use crate::runtime::PathProvider;
 
 /// Directory name for storing the GitHub CLI binary
 pub const GH_CLI_DIR_NAME: &str = "gh-cli";
@@ -13,16 +14,39 @@ pub const GH_CLI_BINARY_NAME: &str = "gh";
 #[cfg(target_os = "windows")]
 pub const GH_CLI_BINARY_NAME: &str = "gh.exe";
 
+/// GitHub API URL for releases
+pub const GITHUB_RELEASES_API: &str = "https://api.github.com/repos/cli/cli/releases";
+
+/// Base URL for release asset downloads
+pub const GITHUB_RELEASES_DOWNLOAD: &str = "https://github.com/cli/cli/releases/download";
+
+/// Where GitHub CLI releases are fetched from
+///
+/// Always GitHub in the app; tests point it at a local fixture server.
+#[derive(Debug, Clone)]
+pub struct ReleaseSource {
+    /// Releases API endpoint (`{releases_api}/latest` for the newest release)
+    pub releases_api: String,
+    /// Asset download base (`{download_base}/v{version}/{archive_name}`)
+    pub download_base: String,
+}
+
+impl ReleaseSource {
+    pub fn github() -> Self {
+        Self {
+            releases_api: GITHUB_RELEASES_API.to_string(),
+            download_base: GITHUB_RELEASES_DOWNLOAD.to_string(),
+        }
+    }
+}
+
 /// Get the directory where GitHub CLI is installed
 ///
 /// Returns: `~/Library/Application Support/jean/gh-cli/` (macOS)
 ///          `~/.local/share/jean/gh-cli/` (Linux)
 ///          `%APPDATA%/jean/gh-cli/` (Windows)
-pub fn get_gh_cli_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+pub fn get_gh_cli_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    let app_data_dir = app.shared_data_dir()?;
     Ok(app_data_dir.join(GH_CLI_DIR_NAME))
 }
 
@@ -30,7 +54,7 @@ pub fn get_gh_cli_dir(app: &AppHandle) -> Result<PathBuf, String> {
 ///
 /// Returns: `~/Library/Application Support/jean/gh-cli/gh` (macOS/Linux)
 ///          `%APPDATA%/jean/gh-cli/gh.exe` (Windows)
-pub fn get_gh_cli_binary_path(app: &AppHandle) -> Result<PathBuf, String> {
+pub fn get_gh_cli_binary_path(app: &impl PathProvider) -> Result<PathBuf, String> {
     Ok(get_gh_cli_dir(app)?.join(GH_CLI_BINARY_NAME))
 }
 
@@ -38,7 +62,7 @@ pub fn get_gh_cli_binary_path(app: &AppHandle) -> Result<PathBuf, String> {
 ///
 /// Returns the embedded binary path if it exists, otherwise falls back to `"gh"` from PATH.
 /// This ensures commands work whether `gh` was installed via the app or system-wide.
-pub fn resolve_gh_binary(app: &AppHandle) -> PathBuf {
+pub fn resolve_gh_binary(app: &impl PathProvider) -> PathBuf {
     if let Ok(embedded) = get_gh_cli_binary_path(app) {
         if embedded.exists() {
             return embedded;
@@ -48,7 +72,7 @@ pub fn resolve_gh_binary(app: &AppHandle) -> PathBuf {
 }
 
 /// Ensure the CLI directory exists, creating it if necessary
-pub fn ensure_gh_cli_dir(app: &AppHandle) -> Result<PathBuf, String> {
+pub fn ensure_gh_cli_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
     let cli_dir = get_gh_cli_dir(app)?;
     std::fs::create_dir_all(&cli_dir)
         .map_err(|e| format!("Failed to create GitHub CLI directory: {e}"))?;
@@ -0,0 +1,41 @@
+use tauri::AppHandle;
+
+use super::GuestModeStatus;
+use crate::http_server::EmitExt;
+use crate::AppPreferences;
+
+fn emit_changed(app: &AppHandle, status: &GuestModeStatus) {
+    if let Err(e) = app.emit_all("guest-mode:changed", status) {
+        log::error!("Failed to emit guest-mode:changed: {e}");
+    }
+}
+
+/// Preferences for the frontend, without their secrets while guest mode is on
+#[tauri::command]
+pub async fn load_preferences(app: AppHandle) -> Result<AppPreferences, String> {
+    let mut preferences = crate::load_preferences(app).await?;
+    if super::is_enabled() {
+        super::redact_preferences(&mut preferences);
+    }
+    Ok(preferences)
+}
+
+#[tauri::command]
+pub async fn get_guest_mode(app: AppHandle) -> GuestModeStatus {
+    super::status(&app)
+}
+
+/// Switch the backend to read-only until `exit_guest_mode` gets the same passcode
+#[tauri::command]
+pub async fn enter_guest_mode(app: AppHandle, passcode: String) -> Result<GuestModeStatus, String> {
+    let status = super::enter(&app, &passcode)?;
+    emit_changed(&app, &status);
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn exit_guest_mode(app: AppHandle, passcode: String) -> Result<GuestModeStatus, String> {
+    let status = super::exit(&app, &passcode)?;
+    emit_changed(&app, &status);
+    Ok(status)
+}
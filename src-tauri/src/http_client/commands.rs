@@ -0,0 +1,473 @@
+//! TLS and proxy diagnostics for troubleshooting corporate networks, and the
+//! GitHub API token used to avoid rate limiting
+
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, RootCertStore, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use super::proxy::{ProxyConfig, ProxySource, PROXY};
+use super::{client_builder, trusted_roots, trusting_builder, TRUST_ROOTS};
+
+const DIAGNOSE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Requested by `test_proxy_connection` unless another URL is given
+const PROXY_TEST_URL: &str = "https://api.github.com";
+
+/// A certificate presented by the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub not_before: String,
+    pub not_after: String,
+    /// SHA-256 fingerprint (colon-separated hex)
+    pub sha256: String,
+    /// Subject and issuer are the same (typical of a proxy's root CA)
+    pub self_signed: bool,
+}
+
+/// Which extra roots are currently trusted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustStoreStatus {
+    pub extra_ca_cert_path: Option<String>,
+    pub extra_ca_certificates: usize,
+    pub use_system_cert_store: bool,
+    pub system_certificates: usize,
+    /// Problems loading the configured roots
+    pub errors: Vec<String>,
+}
+
+/// Result of `diagnose_tls`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsDiagnosis {
+    pub host: String,
+    pub port: u16,
+    /// True if a request with the app's HTTP client succeeded
+    pub trusted: bool,
+    /// Error from the app's HTTP client (with underlying causes)
+    pub request_error: Option<String>,
+    /// Why the presented chain fails verification against the OS trust store
+    /// plus the extra CA file (None if it verifies or the handshake failed)
+    pub verification_error: Option<String>,
+    /// Chain presented by the server, leaf first
+    pub chain: Vec<CertificateInfo>,
+    /// Error connecting to the server to capture its chain
+    pub handshake_error: Option<String>,
+    pub trust_store: TrustStoreStatus,
+}
+
+/// Check whether a URL is reachable over TLS and report the server's chain
+///
+/// Accepts a full `https://` URL or a bare `host[:port]`.
+#[tauri::command]
+pub async fn diagnose_tls(url: String) -> Result<TlsDiagnosis, String> {
+    let url = parse_https_url(&url)?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("URL has no host: {url}"))?
+        .to_string();
+    let port = url.port_or_known_default().unwrap_or(443);
+    log::trace!("Diagnosing TLS for {host}:{port}");
+
+    let request_error = match client_builder()
+        .timeout(DIAGNOSE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?
+        .head(url.clone())
+        .send()
+        .await
+    {
+        Ok(_) => None,
+        Err(e) => Some(error_chain(&e)),
+    };
+
+    let (trust_store, roots) = trust_store_snapshot();
+    let capture_host = host.clone();
+    let capture =
+        tauri::async_runtime::spawn_blocking(move || capture_chain(&capture_host, port, roots))
+            .await
+            .map_err(|e| format!("TLS diagnostics task failed: {e}"))?;
+
+    let (chain, verification_error, handshake_error) = match capture {
+        Ok((chain, verification_error)) => (
+            chain.iter().map(|der| certificate_info(der)).collect(),
+            verification_error,
+            None,
+        ),
+        Err(e) => (Vec::new(), None, Some(e)),
+    };
+
+    Ok(TlsDiagnosis {
+        host,
+        port,
+        trusted: request_error.is_none(),
+        request_error,
+        verification_error,
+        chain,
+        handshake_error,
+        trust_store,
+    })
+}
+
+/// Result of `test_proxy_connection`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyTest {
+    /// Proxy used (password redacted); None for a direct connection
+    pub proxy: Option<String>,
+    pub source: Option<ProxySource>,
+    pub url: String,
+    /// True if the request got an HTTP response (of any status)
+    pub reachable: bool,
+    pub status: Option<u16>,
+    pub elapsed_ms: u64,
+    /// Error from the proxy configuration or request (with underlying causes)
+    pub error: Option<String>,
+}
+
+/// Request `url` (default: the GitHub API) through a proxy
+///
+/// Tests `proxy` when given (e.g. before saving it), else the configured one.
+#[tauri::command]
+pub async fn test_proxy_connection(
+    proxy: Option<String>,
+    url: Option<String>,
+) -> Result<ProxyTest, String> {
+    let url = url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| PROXY_TEST_URL.to_string());
+    let (config, config_error) = match proxy.map(|p| p.trim().to_string()) {
+        Some(proxy) if !proxy.is_empty() => {
+            let config = ProxyConfig {
+                url: proxy,
+                source: ProxySource::Preferences,
+                no_proxy: None,
+            };
+            (Some(config), None)
+        }
+        _ => {
+            let configured = PROXY.lock().unwrap();
+            (configured.config.clone(), configured.error.clone())
+        }
+    };
+    log::trace!(
+        "Testing proxy {:?} with {url}",
+        config.as_ref().map(ProxyConfig::display_url)
+    );
+
+    let mut result = ProxyTest {
+        proxy: config.as_ref().map(ProxyConfig::display_url),
+        source: config.as_ref().map(|c| c.source),
+        url: url.clone(),
+        reachable: false,
+        status: None,
+        elapsed_ms: 0,
+        error: config_error,
+    };
+    if result.error.is_some() {
+        return Ok(result);
+    }
+
+    let mut builder = trusting_builder().timeout(DIAGNOSE_TIMEOUT);
+    if let Some(config) = &config {
+        match config.to_proxy() {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => {
+                result.error = Some(e);
+                return Ok(result);
+            }
+        }
+    }
+    let client = builder
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+    let started = Instant::now();
+    let response = client.get(&url).send().await;
+    result.elapsed_ms = started.elapsed().as_millis() as u64;
+    match response {
+        Ok(response) => {
+            result.reachable = true;
+            result.status = Some(response.status().as_u16());
+        }
+        Err(e) => result.error = Some(error_chain(&e)),
+    }
+    Ok(result)
+}
+
+/// Store the GitHub API token sent with release lookups (None or empty forgets it)
+#[tauri::command]
+pub async fn set_github_token(app: AppHandle, token: Option<String>) -> Result<(), String> {
+    super::github::save_token(&app, token.as_deref())
+}
+
+/// Whether a GitHub API token is stored (the token itself is never returned)
+#[tauri::command]
+pub async fn has_github_token() -> bool {
+    super::github::has_token()
+}
+
+fn parse_https_url(input: &str) -> Result<reqwest::Url, String> {
+    let input = input.trim();
+    let with_scheme = if input.contains("://") {
+        input.to_string()
+    } else {
+        format!("https://{input}")
+    };
+    let url = reqwest::Url::parse(&with_scheme).map_err(|e| format!("Invalid URL {input}: {e}"))?;
+    if url.scheme() != "https" {
+        return Err(format!("Only https URLs can be diagnosed, got {input}"));
+    }
+    Ok(url)
+}
+
+/// Format an error with all of its sources (reqwest's top-level message hides
+/// the certificate problem)
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    message
+}
+
+/// Current trust settings, and the roots to verify the captured chain against
+fn trust_store_snapshot() -> (TrustStoreStatus, Vec<CertificateDer<'static>>) {
+    let roots = TRUST_ROOTS.lock().unwrap();
+    let status = TrustStoreStatus {
+        extra_ca_cert_path: roots.settings.extra_ca_cert_path.clone(),
+        extra_ca_certificates: roots.extra.len(),
+        use_system_cert_store: roots.settings.use_system_cert_store,
+        system_certificates: roots.system.len(),
+        errors: roots.errors.clone(),
+    };
+
+    drop(roots);
+
+    // The HTTP client falls back to the OS store by default, so always verify
+    // against it here even when it isn't explicitly enabled
+    (status, trusted_roots())
+}
+
+/// Records the server's chain and whether it verifies, then accepts it so the
+/// handshake can finish
+#[derive(Debug)]
+struct ChainRecorder {
+    inner: Option<Arc<WebPkiServerVerifier>>,
+    provider: Arc<CryptoProvider>,
+    captured: Mutex<Option<(Vec<CertificateDer<'static>>, Option<String>)>>,
+}
+
+impl ServerCertVerifier for ChainRecorder {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verification_error = match &self.inner {
+            Some(inner) => inner
+                .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+                .err()
+                .map(|e| e.to_string()),
+            None => Some("No trusted root certificates are configured".to_string()),
+        };
+
+        let chain = std::iter::once(end_entity)
+            .chain(intermediates)
+            .map(|c| c.clone().into_owned())
+            .collect();
+        *self.captured.lock().unwrap() = Some((chain, verification_error));
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Handshake with the server and return its chain and verification result
+fn capture_chain(
+    host: &str,
+    port: u16,
+    roots: Vec<CertificateDer<'static>>,
+) -> Result<(Vec<CertificateDer<'static>>, Option<String>), String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let mut root_store = RootCertStore::empty();
+    root_store.add_parsable_certificates(roots);
+    let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(root_store), provider.clone())
+        .build()
+        .ok();
+
+    let recorder = Arc::new(ChainRecorder {
+        inner,
+        provider: provider.clone(),
+        captured: Mutex::new(None),
+    });
+    let config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to configure TLS: {e}"))?
+        .dangerous()
+        .with_custom_certificate_verifier(recorder.clone())
+        .with_no_client_auth();
+
+    let server_name = ServerName::try_from(host.to_string())
+        .map_err(|e| format!("Invalid server name {host}: {e}"))?;
+    let mut conn = rustls::ClientConnection::new(Arc::new(config), server_name)
+        .map_err(|e| format!("Failed to start TLS session: {e}"))?;
+
+    let addr = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {host}: {e}"))?
+        .next()
+        .ok_or_else(|| format!("No addresses found for {host}"))?;
+    let mut sock = TcpStream::connect_timeout(&addr, DIAGNOSE_TIMEOUT)
+        .map_err(|e| format!("Failed to connect to {host}:{port}: {e}"))?;
+    sock.set_read_timeout(Some(DIAGNOSE_TIMEOUT))
+        .map_err(|e| format!("Failed to configure socket: {e}"))?;
+
+    while conn.is_handshaking() {
+        conn.complete_io(&mut sock)
+            .map_err(|e| format!("TLS handshake with {host}:{port} failed: {e}"))?;
+    }
+    conn.send_close_notify();
+    let _ = conn.complete_io(&mut sock);
+    let _ = sock.flush();
+
+    let captured = recorder.captured.lock().unwrap().take();
+    captured.ok_or_else(|| format!("{host}:{port} did not present a certificate"))
+}
+
+/// Summarize a DER certificate for display
+fn certificate_info(der: &CertificateDer<'_>) -> CertificateInfo {
+    let sha256 = Sha256::digest(der.as_ref())
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":");
+
+    match x509_parser::parse_x509_certificate(der.as_ref()) {
+        Ok((_, cert)) => {
+            let subject = cert.subject().to_string();
+            let issuer = cert.issuer().to_string();
+            CertificateInfo {
+                self_signed: subject == issuer,
+                subject,
+                issuer,
+                not_before: cert.validity().not_before.to_string(),
+                not_after: cert.validity().not_after.to_string(),
+                sha256,
+            }
+        }
+        Err(e) => CertificateInfo {
+            subject: format!("(unparseable certificate: {e})"),
+            issuer: String::new(),
+            not_before: String::new(),
+            not_after: String::new(),
+            sha256,
+            self_signed: false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_https_url() {
+        let url = parse_https_url("api.github.com").unwrap();
+        assert_eq!(url.host_str(), Some("api.github.com"));
+        assert_eq!(url.port_or_known_default(), Some(443));
+        assert_eq!(
+            parse_https_url("https://proxy.corp:8443/x")
+                .unwrap()
+                .port_or_known_default(),
+            Some(8443)
+        );
+        assert!(parse_https_url("http://example.com").is_err());
+    }
+
+    #[test]
+    fn test_proxy_connection_goes_through_proxy() {
+        use crate::test_support::fixture_server::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(vec![(
+            "http://jean.invalid/ping".to_string(),
+            FixtureResponse::json("{}"),
+        )]);
+        let result = tauri::async_runtime::block_on(test_proxy_connection(
+            Some(server.url("")),
+            Some("http://jean.invalid/ping".to_string()),
+        ))
+        .unwrap();
+        assert!(result.reachable, "{:?}", result.error);
+        assert_eq!(result.status, Some(200));
+        assert_eq!(result.source, Some(ProxySource::Preferences));
+        assert_eq!(server.requests(), vec!["http://jean.invalid/ping"]);
+
+        let result = tauri::async_runtime::block_on(test_proxy_connection(
+            Some("not a url".to_string()),
+            None,
+        ))
+        .unwrap();
+        assert!(!result.reachable);
+        assert!(result.error.unwrap().contains("Invalid proxy URL"));
+    }
+
+    #[test]
+    fn test_certificate_info() {
+        let path = crate::test_support::fixture_path("tls/test-ca.pem");
+        let certs = super::super::load_pem_certificates(&path).unwrap();
+
+        let info = certificate_info(&certs[0]);
+        assert!(info.subject.contains("CN=Jean Test CA"), "{}", info.subject);
+        assert!(info.self_signed);
+        assert_eq!(info.sha256.split(':').count(), 32);
+    }
+}
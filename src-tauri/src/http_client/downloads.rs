@@ -0,0 +1,535 @@
+//! Parallel downloads with a shared bandwidth cap
+//!
+//! Used when several tools are installed together (e.g. during onboarding):
+//! all assets download at once over one client, a single cap limits their
+//! combined speed, and progress is reported as one event keyed by tool name.
+//! Single installs read their download with `read_with_progress`, or stream
+//! large archives to disk with `download_resumable` so a retry picks up
+//! where a dropped connection left off.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Minimum time between progress reports (completions are always reported)
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Something to download, labelled with the tool it belongs to
+#[derive(Debug, Clone)]
+pub struct Asset {
+    pub tool: String,
+    pub url: String,
+}
+
+/// Progress of one asset
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetProgress {
+    pub downloaded: u64,
+    /// Size reported by the server, if any
+    pub total: Option<u64>,
+    pub done: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Combined progress of a batch of downloads
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    /// Keyed by tool name
+    pub assets: BTreeMap<String, AssetProgress>,
+    pub downloaded: u64,
+    /// Sum of all sizes, or None while any size is unknown
+    pub total: Option<u64>,
+}
+
+impl DownloadProgress {
+    fn update(&mut self, tool: &str, f: impl FnOnce(&mut AssetProgress)) {
+        if let Some(asset) = self.assets.get_mut(tool) {
+            f(asset);
+        }
+        self.downloaded = self.assets.values().map(|a| a.downloaded).sum();
+        self.total = self.assets.values().map(|a| a.total).sum();
+    }
+}
+
+/// Token bucket shared by all downloads of a batch
+struct BandwidthCap {
+    bytes_per_sec: u64,
+    started: Instant,
+    consumed: Mutex<u64>,
+}
+
+impl BandwidthCap {
+    fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.max(1),
+            started: Instant::now(),
+            consumed: Mutex::new(0),
+        }
+    }
+
+    /// Record `bytes` received; returns how long to pause to stay under the cap
+    fn take(&self, bytes: u64, now: Instant) -> Duration {
+        let mut consumed = self.consumed.lock().unwrap();
+        *consumed += bytes;
+        let due = Duration::from_secs_f64(*consumed as f64 / self.bytes_per_sec as f64);
+        due.saturating_sub(now.duration_since(self.started))
+    }
+}
+
+/// Shared state of a running batch
+struct Batch<'a, F> {
+    cap: Option<BandwidthCap>,
+    progress: Mutex<(DownloadProgress, Instant)>,
+    on_progress: &'a F,
+}
+
+impl<F: Fn(&DownloadProgress)> Batch<'_, F> {
+    fn report(&self, tool: &str, force: bool, f: impl FnOnce(&mut AssetProgress)) {
+        let mut guard = self.progress.lock().unwrap();
+        let (progress, last_report) = &mut *guard;
+        progress.update(tool, f);
+        if force || last_report.elapsed() >= PROGRESS_INTERVAL {
+            *last_report = Instant::now();
+            (self.on_progress)(progress);
+        }
+    }
+
+    async fn fetch(&self, client: &reqwest::Client, asset: &Asset) -> Result<Vec<u8>, String> {
+        let tool = &asset.tool;
+        let download = crate::emergency::DownloadGuard::start();
+        let mut response = client
+            .get(&asset.url)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to download {tool}: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Failed to download {tool}: HTTP {}",
+                response.status()
+            ));
+        }
+
+        let total = response.content_length();
+        self.report(tool, false, |a| a.total = total);
+
+        let mut content = Vec::with_capacity(total.unwrap_or(0) as usize);
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Failed to read {tool} download: {e}"))?
+        {
+            download.check()?;
+            content.extend_from_slice(&chunk);
+            let len = content.len() as u64;
+            self.report(tool, false, |a| a.downloaded = len);
+
+            if let Some(cap) = &self.cap {
+                let pause = cap.take(chunk.len() as u64, Instant::now());
+                if !pause.is_zero() {
+                    tokio::time::sleep(pause).await;
+                }
+            }
+        }
+
+        log::trace!("Downloaded {} bytes for {tool}", content.len());
+        Ok(content)
+    }
+}
+
+/// Download all assets concurrently, optionally capping their combined speed.
+/// Results are in the order of `assets`; one failure doesn't stop the others.
+pub async fn download_all<F: Fn(&DownloadProgress) + Sync>(
+    assets: &[Asset],
+    max_bytes_per_sec: Option<u64>,
+    on_progress: F,
+) -> Vec<Result<Vec<u8>, String>> {
+    let client = match crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            let error = format!("Failed to create HTTP client: {e}");
+            return assets.iter().map(|_| Err(error.clone())).collect();
+        }
+    };
+
+    let initial = DownloadProgress {
+        assets: assets
+            .iter()
+            .map(|a| (a.tool.clone(), AssetProgress::default()))
+            .collect(),
+        ..Default::default()
+    };
+    let batch = Batch {
+        cap: max_bytes_per_sec.map(BandwidthCap::new),
+        progress: Mutex::new((initial, Instant::now())),
+        on_progress: &on_progress,
+    };
+
+    let downloads = assets.iter().map(|asset| {
+        let batch = &batch;
+        let client = &client;
+        async move {
+            let result = batch.fetch(client, asset).await;
+            let error = result.as_ref().err().cloned();
+            batch.report(&asset.tool, true, |a| {
+                a.done = true;
+                a.error = error;
+            });
+            result
+        }
+    });
+    futures_util::future::join_all(downloads).await
+}
+
+/// Read a response body, calling `on_progress(downloaded, total)` as chunks
+/// arrive (at most every `PROGRESS_INTERVAL`, and once at the end). `total`
+/// is the Content-Length, if the server sent one. An emergency stop abandons
+/// the download.
+pub async fn read_with_progress(
+    mut response: reqwest::Response,
+    what: &str,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<Vec<u8>, String> {
+    let download = crate::emergency::DownloadGuard::start();
+    let total = response.content_length();
+    let mut content = Vec::with_capacity(total.unwrap_or(0) as usize);
+    let mut last_report = Instant::now();
+    on_progress(0, total);
+    loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => {
+                chunk.map_err(|e| format!("Failed to read {what} download: {e}"))?
+            }
+            _ = download.stopped() => return Err(crate::emergency::DOWNLOAD_CANCELLED.to_string()),
+        };
+        let Some(chunk) = chunk else { break };
+        content.extend_from_slice(&chunk);
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            last_report = Instant::now();
+            on_progress(content.len() as u64, total);
+        }
+    }
+    on_progress(content.len() as u64, total);
+    log::trace!("Downloaded {} bytes for {what}", content.len());
+    Ok(content)
+}
+
+/// Start offset of a `Content-Range: bytes start-end/total` header
+fn content_range_start(value: &str) -> Option<u64> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    range.split_once('-')?.0.parse().ok()
+}
+
+/// Download `url` into the file `partial`, resuming with a Range request
+/// from the bytes an earlier attempt left there, and return the complete
+/// content. On failure the partial file is kept for the next attempt; on
+/// success it's left for the caller to clean up. Progress and emergency
+/// stops work as in `read_with_progress`, with `downloaded` counting the
+/// resumed bytes too.
+pub async fn download_resumable(
+    client: &reqwest::Client,
+    url: &str,
+    partial: &Path,
+    what: &str,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<Vec<u8>, String> {
+    let download = crate::emergency::DownloadGuard::start();
+    if let Some(dir) = partial.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create download directory: {e}"))?;
+    }
+
+    let (mut response, mut offset) = loop {
+        let offset = fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+        let mut request = client.get(url);
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to download {what}: {e}"))?;
+        if offset > 0 && response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            // Stale or already complete (e.g. extraction failed); start over
+            log::trace!("Discarding partial {what} download of {offset} bytes");
+            fs::remove_file(partial)
+                .map_err(|e| format!("Failed to remove partial {what} download: {e}"))?;
+            continue;
+        }
+        break (response, offset);
+    };
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to download {what}: HTTP {}",
+            response.status()
+        ));
+    }
+    if offset > 0 {
+        let resumed_at = response
+            .headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(content_range_start);
+        if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            // The server ignored the range and sent everything
+            offset = 0;
+        } else if resumed_at != Some(offset) {
+            let _ = fs::remove_file(partial);
+            return Err(format!(
+                "Failed to resume {what} download: unexpected range {resumed_at:?}"
+            ));
+        } else {
+            log::trace!("Resuming {what} download at {offset} bytes");
+        }
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(offset > 0)
+        .truncate(offset == 0)
+        .open(partial)
+        .map_err(|e| format!("Failed to open partial {what} download: {e}"))?;
+    let total = response.content_length().map(|len| offset + len);
+    let mut downloaded = offset;
+    let mut last_report = Instant::now();
+    on_progress(downloaded, total);
+    loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => {
+                chunk.map_err(|e| format!("Failed to read {what} download: {e}"))?
+            }
+            _ = download.stopped() => return Err(crate::emergency::DOWNLOAD_CANCELLED.to_string()),
+        };
+        let Some(chunk) = chunk else { break };
+        file.write_all(&chunk)
+            .map_err(|e| format!("Failed to save {what} download: {e}"))?;
+        downloaded += chunk.len() as u64;
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            last_report = Instant::now();
+            on_progress(downloaded, total);
+        }
+    }
+    on_progress(downloaded, total);
+    file.flush()
+        .map_err(|e| format!("Failed to save {what} download: {e}"))?;
+    drop(file);
+
+    if let Some(total) = total.filter(|t| *t != downloaded) {
+        return Err(format!(
+            "{what} download ended early ({downloaded} of {total} bytes)"
+        ));
+    }
+    log::trace!("Downloaded {downloaded} bytes for {what}");
+    fs::read(partial).map_err(|e| format!("Failed to read {what} download: {e}"))
+}
+
+/// Percent reached in an install stage spanning `from..=to` percent, or
+/// `from` while the size is unknown
+pub fn stage_percent(downloaded: u64, total: Option<u64>, from: u8, to: u8) -> u8 {
+    match total.filter(|t| *t > 0) {
+        Some(total) => {
+            let span = u64::from(to.saturating_sub(from));
+            from + (downloaded.min(total) * span / total) as u8
+        }
+        None => from,
+    }
+}
+
+/// "3.2 / 41.0 MB", or "3.2 MB" when the size is unknown
+pub fn format_progress(downloaded: u64, total: Option<u64>) -> String {
+    let mb = |bytes: u64| bytes as f64 / 1_000_000.0;
+    match total {
+        Some(total) => format!("{:.1} / {:.1} MB", mb(downloaded), mb(total)),
+        None => format!("{:.1} MB", mb(downloaded)),
+    }
+}
+
+/// The SHA-256 listed for `file_name` in a checksums file: `<hex>  <name>`
+/// lines as written by `sha256sum` and published with GitHub releases
+pub fn checksum_for(checksums: &str, file_name: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let (digest, name) = line.trim().split_once(char::is_whitespace)?;
+        let name = name.trim_start().trim_start_matches('*');
+        let name = name.strip_prefix("./").unwrap_or(name);
+        (name == file_name && is_sha256(digest)).then(|| digest.to_lowercase())
+    })
+}
+
+/// 64 hex digits
+pub fn is_sha256(digest: &str) -> bool {
+    digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture_server::{FixtureResponse, FixtureServer};
+
+    #[test]
+    fn test_bandwidth_cap_spreads_bytes_over_time() {
+        let cap = BandwidthCap::new(1000);
+        let start = cap.started;
+        assert_eq!(cap.take(500, start), Duration::from_millis(500));
+        // Two downloads share the same budget
+        assert_eq!(cap.take(500, start), Duration::from_secs(1));
+        assert_eq!(cap.take(0, start + Duration::from_secs(2)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_download_all_reports_per_tool_results() {
+        let server = FixtureServer::start(vec![
+            (
+                "/claude".to_string(),
+                FixtureResponse::bytes(vec![1u8; 4096]),
+            ),
+            ("/gh".to_string(), FixtureResponse::bytes(vec![2u8; 1024])),
+        ]);
+        let assets = [
+            Asset {
+                tool: "claude".to_string(),
+                url: server.url("/claude"),
+            },
+            Asset {
+                tool: "gh".to_string(),
+                url: server.url("/gh"),
+            },
+            Asset {
+                tool: "missing".to_string(),
+                url: server.url("/missing"),
+            },
+        ];
+
+        let last = Mutex::new(DownloadProgress::default());
+        let results = tauri::async_runtime::block_on(download_all(&assets, None, |p| {
+            *last.lock().unwrap() = p.clone();
+        }));
+
+        assert_eq!(results[0].as_ref().unwrap().len(), 4096);
+        assert_eq!(results[1].as_ref().unwrap().len(), 1024);
+        assert_eq!(
+            results[2].as_ref().unwrap_err(),
+            "Failed to download missing: HTTP 404 Not Found"
+        );
+
+        let last = last.into_inner().unwrap();
+        assert!(last.assets.values().all(|a| a.done));
+        assert_eq!(last.assets["claude"].downloaded, 4096);
+        assert!(last.assets["missing"].error.is_some());
+        assert_eq!(last.downloaded, 5120);
+    }
+
+    #[test]
+    fn test_read_with_progress_reports_bytes() {
+        let server = FixtureServer::start(vec![(
+            "/gh".to_string(),
+            FixtureResponse::bytes(vec![3u8; 2048]),
+        )]);
+        let mut reports = Vec::new();
+        let content = tauri::async_runtime::block_on(async {
+            let client = crate::http_client::client_builder().build().unwrap();
+            let response = client.get(server.url("/gh")).send().await.unwrap();
+            read_with_progress(response, "gh", |downloaded, total| {
+                reports.push((downloaded, total))
+            })
+            .await
+        })
+        .unwrap();
+
+        assert_eq!(content.len(), 2048);
+        assert_eq!(reports.first(), Some(&(0, Some(2048))));
+        assert_eq!(reports.last(), Some(&(2048, Some(2048))));
+    }
+
+    #[test]
+    fn test_content_range_start() {
+        assert_eq!(content_range_start("bytes 1000-4095/4096"), Some(1000));
+        assert_eq!(content_range_start("bytes */4096"), None);
+        assert_eq!(content_range_start("items 0-1/2"), None);
+    }
+
+    #[test]
+    fn test_download_resumable_continues_partial_file() {
+        let archive: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        let server = FixtureServer::start(vec![
+            (
+                "/gh.tar.gz".to_string(),
+                FixtureResponse::bytes(archive.clone()),
+            ),
+            // Ignores Range, like some mirrors
+            (
+                "/whole.tar.gz".to_string(),
+                FixtureResponse::json(archive.clone()),
+            ),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+        let partial = dir.path().join("temp").join("gh.tar.gz.part");
+        let download = |path: &str, reports: &mut Vec<(u64, Option<u64>)>| {
+            tauri::async_runtime::block_on(async {
+                let client = crate::http_client::client_builder().build().unwrap();
+                download_resumable(&client, &server.url(path), &partial, "gh", |d, t| {
+                    reports.push((d, t))
+                })
+                .await
+            })
+        };
+
+        fs::create_dir_all(partial.parent().unwrap()).unwrap();
+        fs::write(&partial, &archive[..1000]).unwrap();
+        let mut reports = Vec::new();
+        assert_eq!(download("/gh.tar.gz", &mut reports).unwrap(), archive);
+        assert_eq!(reports.first(), Some(&(1000, Some(4096))));
+        assert_eq!(reports.last(), Some(&(4096, Some(4096))));
+        assert_eq!(fs::read(&partial).unwrap(), archive);
+
+        // A partial file as long as the archive gets 416 and starts over
+        let mut reports = Vec::new();
+        assert_eq!(download("/gh.tar.gz", &mut reports).unwrap(), archive);
+        assert_eq!(reports.first(), Some(&(0, Some(4096))));
+
+        fs::write(&partial, b"stale").unwrap();
+        assert_eq!(download("/whole.tar.gz", &mut Vec::new()).unwrap(), archive);
+
+        assert_eq!(
+            download("/missing", &mut Vec::new()).unwrap_err(),
+            "Failed to download gh: HTTP 404 Not Found"
+        );
+        assert!(partial.exists());
+    }
+
+    #[test]
+    fn test_stage_percent_and_format() {
+        assert_eq!(stage_percent(0, Some(100), 20, 40), 20);
+        assert_eq!(stage_percent(50, Some(100), 20, 40), 30);
+        assert_eq!(stage_percent(500, Some(100), 20, 40), 40);
+        assert_eq!(stage_percent(50, None, 20, 40), 20);
+        assert_eq!(stage_percent(50, Some(0), 20, 40), 20);
+        assert_eq!(
+            format_progress(3_200_000, Some(41_000_000)),
+            "3.2 / 41.0 MB"
+        );
+        assert_eq!(format_progress(3_200_000, None), "3.2 MB");
+    }
+
+    #[test]
+    fn test_checksum_for() {
+        let digest = "a".repeat(64);
+        let checksums = format!(
+            "{}  gh_2.0.0_linux_arm64.tar.gz\n{digest} *gh_2.0.0_linux_amd64.tar.gz\nnot a line\n",
+            "b".repeat(64)
+        );
+        assert_eq!(
+            checksum_for(&checksums, "gh_2.0.0_linux_amd64.tar.gz"),
+            Some(digest)
+        );
+        assert_eq!(checksum_for(&checksums, "gh_2.0.0_macOS_arm64.zip"), None);
+        assert_eq!(checksum_for("xyz  file.zip", "file.zip"), None);
+    }
+}
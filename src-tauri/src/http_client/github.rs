@@ -0,0 +1,147 @@
+//! Optional GitHub API token
+//!
+//! Unauthenticated GitHub API requests are limited to 60 an hour per IP, which
+//! a shared office NAT uses up quickly. With a token (no scopes are needed for
+//! public releases) the limit is 5,000 an hour. The token is kept in app data
+//! outside preferences, readable only by the user, and is only sent to
+//! `api.github.com` (never to release mirrors standing in for it).
+
+use std::fs;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::platform::write_secret_file;
+use crate::runtime::PathProvider;
+
+const TOKEN_FILE: &str = "github-token.json";
+
+const GITHUB_API_HOST: &str = "api.github.com";
+
+/// The stored token, loaded by `init` and updated by `save_token`
+static TOKEN: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredToken {
+    token: Option<String>,
+}
+
+/// The stored GitHub token, if any
+pub fn load_token(app: &impl PathProvider) -> Option<String> {
+    let root = app.app_data_dir().ok()?;
+    let content = fs::read_to_string(root.join(TOKEN_FILE)).ok()?;
+    serde_json::from_str::<StoredToken>(&content)
+        .ok()?
+        .token
+        .filter(|t| !t.is_empty())
+}
+
+/// Store (or with None, forget) the GitHub token
+pub fn save_token(app: &impl PathProvider, token: Option<&str>) -> Result<(), String> {
+    let path = app.app_data_dir()?.join(TOKEN_FILE);
+    let token = token.map(str::trim).filter(|t| !t.is_empty());
+    match token {
+        None => match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Failed to remove GitHub token: {e}"));
+            }
+            _ => {}
+        },
+        Some(token) => {
+            let content = serde_json::to_string(&StoredToken {
+                token: Some(token.to_string()),
+            })
+            .map_err(|e| format!("Failed to serialize GitHub token: {e}"))?;
+            write_secret_file(&path, content.as_bytes())
+                .map_err(|e| format!("Failed to save GitHub token: {e}"))?;
+        }
+    }
+    *TOKEN.lock().unwrap() = token.map(str::to_string);
+    Ok(())
+}
+
+/// Load the stored token for `authorize` (called at startup)
+pub fn init(app: &impl PathProvider) {
+    *TOKEN.lock().unwrap() = load_token(app);
+}
+
+/// Whether a token is configured
+pub fn has_token() -> bool {
+    TOKEN.lock().unwrap().is_some()
+}
+
+/// True for HTTPS URLs on the GitHub API host
+fn is_github_api(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .is_ok_and(|u| u.scheme() == "https" && u.host_str() == Some(GITHUB_API_HOST))
+}
+
+fn authorize_with(
+    request: reqwest::RequestBuilder,
+    url: &str,
+    token: Option<&str>,
+) -> reqwest::RequestBuilder {
+    match token {
+        Some(token) if is_github_api(url) => request.bearer_auth(token),
+        _ => request,
+    }
+}
+
+/// Attach the configured token to a request for `url` if it goes to the
+/// GitHub API
+pub fn authorize(request: reqwest::RequestBuilder, url: &str) -> reqwest::RequestBuilder {
+    let token = TOKEN.lock().unwrap().clone();
+    authorize_with(request, url, token.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_token_storage() {
+        let paths = TempPaths::new();
+        assert_eq!(load_token(&paths), None);
+        save_token(&paths, Some(" ghp_abc \n")).unwrap();
+        assert_eq!(load_token(&paths).as_deref(), Some("ghp_abc"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let path = paths.app_data_dir().unwrap().join(TOKEN_FILE);
+            let mode = fs::metadata(path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        save_token(&paths, Some("")).unwrap();
+        assert_eq!(load_token(&paths), None);
+        save_token(&paths, None).unwrap();
+    }
+
+    #[test]
+    fn test_authorize_only_github_api() {
+        let client = reqwest::Client::new();
+        let auth = |url: &str| {
+            authorize_with(client.get(url), url, Some("ghp_abc"))
+                .build()
+                .unwrap()
+                .headers()
+                .get("authorization")
+                .map(|v| v.to_str().unwrap().to_string())
+        };
+        assert_eq!(
+            auth("https://api.github.com/repos/cli/cli/releases").as_deref(),
+            Some("Bearer ghp_abc")
+        );
+        assert_eq!(auth("http://api.github.com/repos/cli/cli/releases"), None);
+        assert_eq!(
+            auth("https://mirror.example.com/repos/cli/cli/releases"),
+            None
+        );
+        assert_eq!(auth("https://api.github.com.example.com/releases"), None);
+
+        let url = "https://api.github.com/repos/cli/cli/releases";
+        let request = authorize_with(client.get(url), url, None).build().unwrap();
+        assert!(request.headers().get("authorization").is_none());
+    }
+}
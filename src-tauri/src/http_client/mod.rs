@@ -0,0 +1,193 @@
+//! Shared HTTP client construction with configurable certificate trust
+//!
+//! Corporate proxies that intercept TLS re-sign traffic with their own CA, so
+//! every request fails certificate validation unless that CA is trusted. All
+//! reqwest clients are built from `client_builder()`, which adds the roots
+//! configured in preferences (an extra PEM file and/or the OS trust store)
+//! and routes requests through the configured proxy (see `proxy`).
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::CertificateDer;
+
+pub mod commands;
+pub mod downloads;
+pub mod github;
+pub mod proxy;
+pub mod retry;
+
+/// Trust-related preferences
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustSettings {
+    /// PEM file with additional CA certificates to trust
+    pub extra_ca_cert_path: Option<String>,
+    /// Add the OS trust store's certificates to every client
+    pub use_system_cert_store: bool,
+}
+
+impl TrustSettings {
+    pub fn from_preferences(prefs: &crate::AppPreferences) -> Self {
+        Self {
+            extra_ca_cert_path: prefs
+                .extra_ca_cert_path
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(str::to_string),
+            use_system_cert_store: prefs.use_system_cert_store,
+        }
+    }
+}
+
+/// Loaded trust roots (rebuilt whenever settings change)
+#[derive(Default)]
+struct TrustRoots {
+    settings: TrustSettings,
+    extra: Vec<CertificateDer<'static>>,
+    system: Vec<CertificateDer<'static>>,
+    /// Problems loading the configured roots (reported by diagnostics)
+    errors: Vec<String>,
+}
+
+static TRUST_ROOTS: Lazy<Mutex<TrustRoots>> = Lazy::new(|| Mutex::new(TrustRoots::default()));
+
+/// Apply trust settings to all clients built afterwards
+///
+/// Certificates are loaded once here rather than per client. Loading problems
+/// are logged and kept for `diagnose_tls`; clients still get whatever did load.
+pub fn configure(settings: TrustSettings) {
+    if let Ok(current) = TRUST_ROOTS.lock() {
+        if current.settings == settings {
+            return;
+        }
+    }
+
+    let mut roots = TrustRoots {
+        settings: settings.clone(),
+        ..Default::default()
+    };
+
+    if let Some(path) = &settings.extra_ca_cert_path {
+        match load_pem_certificates(Path::new(path)) {
+            Ok(certs) => {
+                log::info!(
+                    "Trusting {} extra CA certificate(s) from {path}",
+                    certs.len()
+                );
+                roots.extra = certs;
+            }
+            Err(e) => {
+                log::warn!("{e}");
+                roots.errors.push(e);
+            }
+        }
+    }
+
+    if settings.use_system_cert_store {
+        let (certs, errors) = load_system_certificates();
+        log::info!(
+            "Trusting {} certificate(s) from the OS trust store",
+            certs.len()
+        );
+        roots.system = certs;
+        roots.errors.extend(errors);
+    }
+
+    if let Ok(mut current) = TRUST_ROOTS.lock() {
+        *current = roots;
+    }
+}
+
+/// Start building a reqwest client that trusts the configured roots and uses
+/// the configured proxy
+///
+/// Use this instead of `reqwest::Client::builder()` / `reqwest::Client::new()`.
+pub fn client_builder() -> reqwest::ClientBuilder {
+    proxy::apply(trusting_builder())
+}
+
+/// A reqwest client builder that trusts the configured roots
+fn trusting_builder() -> reqwest::ClientBuilder {
+    let mut builder = reqwest::Client::builder();
+    if let Ok(roots) = TRUST_ROOTS.lock() {
+        for der in roots.extra.iter().chain(roots.system.iter()) {
+            match reqwest::Certificate::from_der(der) {
+                Ok(cert) => builder = builder.add_root_certificate(cert),
+                Err(e) => log::trace!("Skipping unusable root certificate: {e}"),
+            }
+        }
+    }
+    builder
+}
+
+/// Roots for TLS connections made outside reqwest (e.g. SMTP): the
+/// configured extra roots and the OS trust store
+pub fn trusted_roots() -> Vec<CertificateDer<'static>> {
+    let mut certs = Vec::new();
+    let mut has_system = false;
+    if let Ok(roots) = TRUST_ROOTS.lock() {
+        certs.extend(roots.extra.iter().cloned());
+        if roots.settings.use_system_cert_store {
+            certs.extend(roots.system.iter().cloned());
+            has_system = true;
+        }
+    }
+    if !has_system {
+        certs.extend(load_system_certificates().0);
+    }
+    certs
+}
+
+/// Read every certificate from a PEM file
+pub(crate) fn load_pem_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .map_err(|e| format!("Failed to read CA certificate file {}: {e}", path.display()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid PEM in CA certificate file {}: {e}", path.display()))?;
+
+    if certs.is_empty() {
+        return Err(format!(
+            "No certificates found in CA certificate file {}",
+            path.display()
+        ));
+    }
+    Ok(certs)
+}
+
+/// Certificates from the OS trust store, plus any per-certificate load errors
+pub(crate) fn load_system_certificates() -> (Vec<CertificateDer<'static>>, Vec<String>) {
+    let result = rustls_native_certs::load_native_certs();
+    let errors = result
+        .errors
+        .iter()
+        .map(|e| format!("Failed to load OS trust store certificate: {e}"))
+        .collect();
+    (result.certs, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_pem_certificates() {
+        let path = crate::test_support::fixture_path("tls/test-ca.pem");
+        let certs = load_pem_certificates(&path).unwrap();
+        assert_eq!(certs.len(), 1);
+        assert!(reqwest::Certificate::from_der(&certs[0]).is_ok());
+    }
+
+    #[test]
+    fn test_load_pem_certificates_rejects_empty_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.pem");
+        std::fs::write(&path, "not a certificate\n").unwrap();
+
+        let err = load_pem_certificates(&path).unwrap_err();
+        assert!(err.contains("No certificates found"), "{err}");
+        assert!(load_pem_certificates(&dir.path().join("missing.pem")).is_err());
+    }
+}
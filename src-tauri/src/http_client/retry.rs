@@ -0,0 +1,287 @@
+//! Retries with exponential backoff for API calls
+//!
+//! GitHub's API answers with the odd 502/503 and, past the unauthenticated
+//! limit, 403/429 until the rate limit resets. `send_with_retry` retries
+//! connection failures and those responses, waiting `base_delay * 2^n`
+//! (capped, with jitter so clients behind one NAT don't retry in lockstep),
+//! or what the server asks for via `Retry-After` / `x-ratelimit-reset`.
+//! When the server asks for a longer wait than `max_delay`, the response is
+//! returned as is rather than stalling the caller. Each retry is reported to
+//! `on_retry`, which callers forward to the frontend as progress.
+
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// How many times to try and how long to wait in between
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A failed attempt that will be retried (the `http:retry` event payload)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryAttempt {
+    /// What was being fetched, e.g. "GitHub CLI releases"
+    pub what: String,
+    /// The attempt that failed (1-based)
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// Wait before the next attempt
+    pub delay_ms: u64,
+    /// Why it failed, e.g. "HTTP 503 Service Unavailable"
+    pub reason: String,
+}
+
+impl RetryAttempt {
+    /// One-line description for progress messages
+    pub fn message(&self) -> String {
+        format!(
+            "{} failed ({}), retrying in {}s (attempt {} of {})...",
+            self.what,
+            self.reason,
+            self.delay_ms.div_ceil(1000),
+            self.attempt + 1,
+            self.max_attempts
+        )
+    }
+}
+
+/// Exponential delay before attempt `attempt + 1`, between half and all of
+/// `base_delay * 2^(attempt - 1)` (capped at `max_delay`)
+fn backoff(policy: &RetryPolicy, attempt: u32) -> Duration {
+    let exponential = policy
+        .base_delay
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(policy.max_delay);
+    let half = exponential / 2;
+    half + half.mul_f64(rand::thread_rng().gen::<f64>())
+}
+
+/// Wait the server asked for, from `Retry-After` (seconds) or GitHub's
+/// `x-ratelimit-reset` (Unix seconds) when no requests are left
+fn server_delay(headers: &HeaderMap, now_secs: u64) -> Option<Duration> {
+    let header = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok();
+    if let Some(secs) = header("retry-after") {
+        return Some(Duration::from_secs(secs));
+    }
+    if header("x-ratelimit-remaining") == Some(0) {
+        let reset = header("x-ratelimit-reset")?;
+        return Some(Duration::from_secs(reset.saturating_sub(now_secs)));
+    }
+    None
+}
+
+/// Why a response is worth retrying, or None if it isn't
+fn retry_reason(status: StatusCode, headers: &HeaderMap) -> Option<String> {
+    let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN
+            && headers
+                .get("x-ratelimit-remaining")
+                .is_some_and(|v| v.as_bytes() == b"0"));
+    if rate_limited {
+        Some("rate limited".to_string())
+    } else if status.is_server_error() {
+        Some(format!("HTTP {status}"))
+    } else {
+        None
+    }
+}
+
+/// Send the request built by `request`, retrying transient failures. Returns
+/// the first response that isn't retried (callers still check its status),
+/// or the last connection error.
+pub async fn send_with_retry(
+    policy: &RetryPolicy,
+    what: &str,
+    request: impl Fn() -> reqwest::RequestBuilder,
+    mut on_retry: impl FnMut(&RetryAttempt),
+) -> Result<reqwest::Response, String> {
+    let mut attempt = 1;
+    loop {
+        let (reason, delay) = match request().send().await {
+            Ok(response) => {
+                let Some(reason) = retry_reason(response.status(), response.headers()) else {
+                    return Ok(response);
+                };
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let delay = match server_delay(response.headers(), now) {
+                    Some(wait) if wait > policy.max_delay => return Ok(response),
+                    Some(wait) => wait,
+                    None => backoff(policy, attempt),
+                };
+                if attempt >= policy.max_attempts {
+                    return Ok(response);
+                }
+                (reason, delay)
+            }
+            Err(e) if attempt >= policy.max_attempts || e.is_builder() => return Err(e.to_string()),
+            Err(e) => (e.to_string(), backoff(policy, attempt)),
+        };
+
+        let retry = RetryAttempt {
+            what: what.to_string(),
+            attempt,
+            max_attempts: policy.max_attempts,
+            delay_ms: delay.as_millis() as u64,
+            reason,
+        };
+        log::warn!("{}", retry.message());
+        on_retry(&retry);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Report a retry to the frontend as an `http:retry` event
+pub fn emit_retry(app: &tauri::AppHandle, retry: &RetryAttempt) {
+    use crate::http_server::EmitExt;
+    if let Err(e) = app.emit_all("http:retry", retry) {
+        log::warn!("Failed to emit http:retry event: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture_server::{FixtureResponse, FixtureServer};
+    use reqwest::header::HeaderValue;
+
+    const FAST: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(50),
+    };
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy::default();
+        for attempt in 1..=3 {
+            let full = Duration::from_secs(1 << (attempt - 1));
+            let delay = backoff(&policy, attempt);
+            assert!(delay >= full / 2 && delay <= full, "{attempt}: {delay:?}");
+        }
+        assert!(backoff(&policy, 40) <= policy.max_delay);
+    }
+
+    #[test]
+    fn test_retry_reason() {
+        let none = HeaderMap::new();
+        assert_eq!(
+            retry_reason(StatusCode::BAD_GATEWAY, &none).as_deref(),
+            Some("HTTP 502 Bad Gateway")
+        );
+        assert_eq!(
+            retry_reason(StatusCode::TOO_MANY_REQUESTS, &none).as_deref(),
+            Some("rate limited")
+        );
+        let exhausted = headers(&[("x-ratelimit-remaining", "0")]);
+        assert!(retry_reason(StatusCode::FORBIDDEN, &exhausted).is_some());
+        assert!(retry_reason(StatusCode::FORBIDDEN, &none).is_none());
+        assert!(retry_reason(StatusCode::NOT_FOUND, &none).is_none());
+    }
+
+    #[test]
+    fn test_server_delay() {
+        assert_eq!(
+            server_delay(&headers(&[("retry-after", "7")]), 100),
+            Some(Duration::from_secs(7))
+        );
+        let limited = headers(&[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset", "160")]);
+        assert_eq!(server_delay(&limited, 100), Some(Duration::from_secs(60)));
+        let remaining = headers(&[
+            ("x-ratelimit-remaining", "12"),
+            ("x-ratelimit-reset", "160"),
+        ]);
+        assert_eq!(server_delay(&remaining, 100), None);
+    }
+
+    #[test]
+    fn test_send_with_retry() {
+        let server = FixtureServer::start(vec![
+            ("/down".to_string(), FixtureResponse::status(503)),
+            ("/ok".to_string(), FixtureResponse::json("[]")),
+        ]);
+        let client = reqwest::Client::new();
+        let mut retries = Vec::new();
+        let (down, missing, ok) = tauri::async_runtime::block_on(async {
+            let down = send_with_retry(
+                &FAST,
+                "releases",
+                || client.get(server.url("/down")),
+                |r| retries.push(r.clone()),
+            )
+            .await;
+            let missing = send_with_retry(
+                &FAST,
+                "releases",
+                || client.get(server.url("/nope")),
+                |_| panic!("404 retried"),
+            )
+            .await;
+            let ok = send_with_retry(
+                &FAST,
+                "releases",
+                || client.get(server.url("/ok")),
+                |_| panic!("200 retried"),
+            )
+            .await;
+            (down, missing, ok)
+        });
+
+        // The last response is returned once attempts run out
+        assert_eq!(down.unwrap().status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(missing.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(ok.unwrap().status(), StatusCode::OK);
+        assert_eq!(
+            retries.iter().map(|r| r.attempt).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(retries[0].reason, "HTTP 503 Service Unavailable");
+        assert_eq!(
+            server.requests(),
+            vec!["/down", "/down", "/down", "/nope", "/ok"]
+        );
+    }
+
+    #[test]
+    fn test_retry_message() {
+        let retry = RetryAttempt {
+            what: "GitHub CLI releases".to_string(),
+            attempt: 1,
+            max_attempts: 4,
+            delay_ms: 1500,
+            reason: "HTTP 503 Service Unavailable".to_string(),
+        };
+        assert_eq!(
+            retry.message(),
+            "GitHub CLI releases failed (HTTP 503 Service Unavailable), retrying in 2s (attempt 2 of 4)..."
+        );
+    }
+}
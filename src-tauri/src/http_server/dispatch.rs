@@ -14,12 +14,14 @@ pub async fn dispatch_command(
     command: &str,
     args: Value,
 ) -> Result<Value, String> {
+    crate::guest::check_command(command)?;
+
     match command {
         // =====================================================================
         // Preferences & UI State
         // =====================================================================
         "load_preferences" => {
-            let result = crate::load_preferences(app.clone()).await?;
+            let result = crate::guest::commands::load_preferences(app.clone()).await?;
             to_value(result)
         }
         "save_preferences" => {
@@ -101,16 +103,69 @@ pub async fn dispatch_command(
             let project_id: String = field(&args, "projectId", "project_id")?;
             let default_branch: Option<String> =
                 field_opt(&args, "defaultBranch", "default_branch")?;
+            let attribution: Option<String> = from_field_opt(&args, "attribution")?;
+            let cli_version: Option<String> = field_opt(&args, "cliVersion", "cli_version")?;
+            let blocked_domains: Option<Vec<String>> =
+                field_opt(&args, "blockedDomains", "blocked_domains")?;
+            let launch_defaults = field_opt(&args, "launchDefaults", "launch_defaults")?;
             let result = crate::projects::update_project_settings(
                 app.clone(),
                 project_id,
                 default_branch,
                 None,
                 None,
+                attribution,
+                cli_version,
+                blocked_domains,
+                launch_defaults,
             )
             .await?;
             to_value(result)
         }
+        "get_project_config" => {
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let result =
+                crate::projects::config::get_project_config(app.clone(), worktree_path).await;
+            to_value(result)
+        }
+        "get_allowed_tools_approval" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let result = crate::projects::config::get_allowed_tools_approval(
+                app.clone(),
+                project_id,
+                worktree_path,
+            )
+            .await;
+            to_value(result)
+        }
+        "approve_allowed_tools" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let allowed_tools: Vec<String> = field(&args, "allowedTools", "allowed_tools")?;
+            crate::projects::config::approve_allowed_tools(app.clone(), project_id, allowed_tools)
+                .await?;
+            emit_cache_invalidation(app, &["project-config"]);
+            Ok(Value::Null)
+        }
+        "generate_project_config" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let result =
+                crate::projects::config::generate_project_config(app.clone(), project_id).await?;
+            emit_cache_invalidation(app, &["project-config"]);
+            to_value(result)
+        }
+        "list_subprojects" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let result =
+                crate::projects::subprojects::list_subprojects(app.clone(), project_id).await?;
+            to_value(result)
+        }
+        "get_project_toolchain" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let result =
+                crate::projects::toolchain::get_project_toolchain(app.clone(), project_id).await?;
+            to_value(result)
+        }
         "reorder_projects" => {
             let project_ids: Vec<String> = field(&args, "projectIds", "project_ids")?;
             crate::projects::reorder_projects(app.clone(), project_ids).await?;
@@ -463,6 +518,11 @@ pub async fn dispatch_command(
             let result = crate::chat::list_all_sessions(app.clone()).await?;
             to_value(result)
         }
+        "list_session_history" => {
+            let query: Option<crate::chat::history::HistoryQuery> = from_field_opt(&args, "query")?;
+            let result = crate::chat::list_session_history(app.clone(), query).await?;
+            to_value(result)
+        }
         "get_session" => {
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
             let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
@@ -472,6 +532,31 @@ pub async fn dispatch_command(
                     .await?;
             to_value(result)
         }
+        "get_session_messages_page" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let start: Option<usize> = from_field_opt(&args, "start")?;
+            let limit: Option<usize> = from_field_opt(&args, "limit")?;
+            let max_output_bytes: Option<usize> =
+                field_opt(&args, "maxOutputBytes", "max_output_bytes")?;
+            let result = crate::chat::get_session_messages_page(
+                app.clone(),
+                session_id,
+                start,
+                limit,
+                max_output_bytes,
+            )
+            .await?;
+            to_value(result)
+        }
+        "get_tool_output" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let message_id: String = field(&args, "messageId", "message_id")?;
+            let tool_call_id: String = field(&args, "toolCallId", "tool_call_id")?;
+            let result =
+                crate::chat::get_tool_output(app.clone(), session_id, message_id, tool_call_id)
+                    .await?;
+            to_value(result)
+        }
         "create_session" => {
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
             let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
@@ -551,6 +636,8 @@ pub async fn dispatch_command(
             let chrome_enabled: Option<bool> = field_opt(&args, "chromeEnabled", "chrome_enabled")?;
             let custom_profile_settings: Option<String> =
                 field_opt(&args, "customProfileSettings", "custom_profile_settings")?;
+            let background_priority: Option<bool> =
+                field_opt(&args, "backgroundPriority", "background_priority")?;
             let result = crate::chat::send_chat_message(
                 app.clone(),
                 session_id,
@@ -568,6 +655,7 @@ pub async fn dispatch_command(
                 mcp_config,
                 chrome_enabled,
                 custom_profile_settings,
+                background_priority,
             )
             .await?;
             to_value(result)
@@ -602,6 +690,21 @@ pub async fn dispatch_command(
             .await?;
             Ok(Value::Null)
         }
+        "set_session_subproject" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let subproject: Option<String> = from_field_opt(&args, "subproject")?;
+            crate::chat::set_session_subproject(
+                app.clone(),
+                worktree_id,
+                worktree_path,
+                session_id,
+                subproject,
+            )
+            .await?;
+            Ok(Value::Null)
+        }
         "set_session_thinking_level" => {
             let session_id: String = field(&args, "sessionId", "session_id")?;
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
@@ -659,6 +762,232 @@ pub async fn dispatch_command(
             let result = crate::chat::has_running_sessions();
             to_value(result)
         }
+        "get_session_scratch_usage" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let result = crate::chat::get_session_scratch_usage(app.clone(), session_id).await?;
+            to_value(result)
+        }
+        "find_stale_files" => {
+            let result = crate::cleanup::commands::find_stale_files(app.clone()).await?;
+            to_value(result)
+        }
+        "clean_stale_files" => {
+            let result = crate::cleanup::commands::clean_stale_files(app.clone()).await?;
+            emit_cache_invalidation(app, &["stale-files"]);
+            to_value(result)
+        }
+        "get_session_timeline" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let result = crate::chat::get_session_timeline(app.clone(), session_id).await?;
+            to_value(result)
+        }
+        "get_session_time" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let result = crate::chat::get_session_time(app.clone(), session_id).await?;
+            to_value(result)
+        }
+        "replay_session" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let speed: Option<f64> = from_field_opt(&args, "speed")?;
+            let result = crate::chat::replay_session(app.clone(), session_id, speed).await?;
+            to_value(result)
+        }
+        "replay_step" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let result = crate::chat::replay_step(app.clone(), session_id).await?;
+            to_value(result)
+        }
+        "stop_replay" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let result = crate::chat::stop_replay(app.clone(), session_id).await?;
+            to_value(result)
+        }
+        "export_session_jsonl" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let path: Option<String> = from_field_opt(&args, "path")?;
+            let result = crate::chat::export_session_jsonl(app.clone(), session_id, path).await?;
+            to_value(result)
+        }
+        "export_session_cast" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let path: String = from_field(&args, "path")?;
+            let result = crate::chat::export_session_cast(app.clone(), session_id, path).await?;
+            to_value(result)
+        }
+        "export_session_for_web" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let format: crate::chat::share::ShareFormat = from_field(&args, "format")?;
+            let dir: String = from_field(&args, "dir")?;
+            let max_bytes: Option<usize> = field_opt(&args, "maxBytes", "max_bytes")?;
+            let result = crate::chat::export_session_for_web(
+                app.clone(),
+                session_id,
+                format,
+                dir,
+                max_bytes,
+            )
+            .await?;
+            to_value(result)
+        }
+        "import_session_jsonl" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let path: String = from_field(&args, "path")?;
+            let result =
+                crate::chat::import_session_jsonl(app.clone(), worktree_id, worktree_path, path)
+                    .await?;
+            to_value(result)
+        }
+        "run_one_shot" => {
+            let prompt: String = from_field(&args, "prompt")?;
+            let context: Option<String> = from_field_opt(&args, "context")?;
+            let model: Option<String> = from_field_opt(&args, "model")?;
+            let backend: Option<String> = from_field_opt(&args, "backend")?;
+            let result =
+                crate::chat::run_one_shot(app.clone(), prompt, context, model, backend).await?;
+            to_value(result)
+        }
+        "get_recent_one_shots" => {
+            let result = crate::chat::get_recent_one_shots().await;
+            to_value(result)
+        }
+        "pin_one_shot" => {
+            let id: String = from_field(&args, "id")?;
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let result =
+                crate::chat::pin_one_shot(app.clone(), id, worktree_id, worktree_path).await?;
+            to_value(result)
+        }
+        "compact_storage" => {
+            let result = crate::chat::compact_storage(app.clone()).await?;
+            to_value(result)
+        }
+        "install_aider" => {
+            let result = crate::chat::install_aider(app.clone()).await?;
+            to_value(result)
+        }
+        "list_custom_backends" => {
+            let result = crate::agent_protocol::commands::list_custom_backends(app.clone()).await?;
+            to_value(result)
+        }
+        "save_custom_backend" => {
+            let backend = from_field(&args, "backend")?;
+            crate::agent_protocol::commands::save_custom_backend(app.clone(), backend).await?;
+            Ok(Value::Null)
+        }
+        "delete_custom_backend" => {
+            let name: String = from_field(&args, "name")?;
+            crate::agent_protocol::commands::delete_custom_backend(app.clone(), name).await?;
+            Ok(Value::Null)
+        }
+        "list_schema_drift" => {
+            let backend: Option<String> = from_field_opt(&args, "backend")?;
+            let result = crate::agent_protocol::commands::list_schema_drift(backend).await?;
+            to_value(result)
+        }
+        "get_effective_launch_command" => {
+            let options = from_field(&args, "options")?;
+            let result = crate::chat::get_effective_launch_command(app.clone(), options).await?;
+            to_value(result)
+        }
+        "check_codex_update" => {
+            let result = crate::chat::codex_updates::check_codex_update(app.clone()).await?;
+            to_value(result)
+        }
+        "get_cli_capabilities" => {
+            let cli: String = from_field(&args, "cli")?;
+            let result =
+                crate::cli_capabilities::commands::get_cli_capabilities(app.clone(), cli).await?;
+            to_value(result)
+        }
+        "get_repo_map" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let worktree_id: Option<String> = field_opt(&args, "worktreeId", "worktree_id")?;
+            let budget_tokens: Option<u32> = field_opt(&args, "budgetTokens", "budget_tokens")?;
+            let result = crate::repo_map::commands::get_repo_map(
+                app.clone(),
+                project_id,
+                worktree_id,
+                budget_tokens,
+            )
+            .await?;
+            to_value(result)
+        }
+        "semantic_search" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let worktree_id: Option<String> = field_opt(&args, "worktreeId", "worktree_id")?;
+            let query: String = from_field(&args, "query")?;
+            let k: Option<usize> = from_field_opt(&args, "k")?;
+            let result = crate::semantic_search::commands::semantic_search(
+                app.clone(),
+                project_id,
+                worktree_id,
+                query,
+                k,
+            )
+            .await?;
+            to_value(result)
+        }
+        "rebuild_semantic_index" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let worktree_id: Option<String> = field_opt(&args, "worktreeId", "worktree_id")?;
+            let result = crate::semantic_search::commands::rebuild_semantic_index(
+                app.clone(),
+                project_id,
+                worktree_id,
+            )
+            .await?;
+            to_value(result)
+        }
+        "find_duplicate_sessions" => {
+            let remove: Option<bool> = from_field_opt(&args, "remove")?;
+            let result = crate::chat::find_duplicate_sessions(app.clone(), remove).await?;
+            to_value(result)
+        }
+        "bulk_delete_sessions" => {
+            let session_ids: Vec<String> = field(&args, "sessionIds", "session_ids")?;
+            let result = crate::chat::bulk_delete_sessions(app.clone(), session_ids).await?;
+            to_value(result)
+        }
+        "bulk_export_sessions" => {
+            let session_ids: Vec<String> = field(&args, "sessionIds", "session_ids")?;
+            let dir: String = from_field(&args, "dir")?;
+            let result = crate::chat::bulk_export_sessions(app.clone(), session_ids, dir).await?;
+            to_value(result)
+        }
+        "bulk_cancel_tasks" => {
+            let session_ids: Vec<String> = field(&args, "sessionIds", "session_ids")?;
+            let result = crate::chat::bulk_cancel_tasks(app.clone(), session_ids).await?;
+            to_value(result)
+        }
+        "provide_session_input" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let text: String = from_field(&args, "text")?;
+            crate::chat::provide_session_input(app.clone(), session_id, text).await?;
+            emit_cache_invalidation(app, &["sessions"]);
+            Ok(Value::Null)
+        }
+        "rerun_session" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let checkout_commit: Option<bool> =
+                field_opt(&args, "checkoutCommit", "checkout_commit")?;
+            let result =
+                crate::chat::rerun_session(app.clone(), session_id, checkout_commit).await?;
+            emit_cache_invalidation(app, &["sessions"]);
+            to_value(result)
+        }
+        "compare_sessions" => {
+            let a: String = from_field(&args, "a")?;
+            let b: String = from_field(&args, "b")?;
+            let result = crate::chat::compare_sessions(app.clone(), a, b).await?;
+            to_value(result)
+        }
+        "get_pending_session_input" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let result = crate::chat::get_pending_session_input(session_id).await?;
+            to_value(result)
+        }
 
         // =====================================================================
         // Chat - Saved Contexts
@@ -729,6 +1058,39 @@ pub async fn dispatch_command(
             crate::background_tasks::commands::set_app_focus_state(state, focused)?;
             Ok(Value::Null)
         }
+        "get_power_conditions" => {
+            let result = crate::background_tasks::commands::get_power_conditions().await?;
+            to_value(result)
+        }
+        "should_run_background_job" => {
+            let job: crate::background_tasks::scheduling::BackgroundJob = from_field(&args, "job")?;
+            let result =
+                crate::background_tasks::commands::should_run_background_job(app.clone(), job)
+                    .await?;
+            to_value(result)
+        }
+        "check_task_schedule" => {
+            let schedule: crate::background_tasks::scheduling::TaskSchedule =
+                from_field(&args, "schedule")?;
+            let result =
+                crate::background_tasks::commands::check_task_schedule(app.clone(), schedule)
+                    .await?;
+            to_value(result)
+        }
+        "get_background_task_health" => {
+            let result = crate::background_tasks::commands::get_background_task_health();
+            to_value(result)
+        }
+        "get_maintenance_status" => {
+            let result =
+                crate::background_tasks::commands::get_maintenance_status(app.clone()).await?;
+            to_value(result)
+        }
+        "run_maintenance_now" => {
+            let result =
+                crate::background_tasks::commands::run_maintenance_now(app.clone()).await?;
+            to_value(result)
+        }
         "set_active_worktree_for_polling" => {
             let worktree_id: Option<String> = field_opt(&args, "worktreeId", "worktree_id")?;
             let worktree_path: Option<String> = field_opt(&args, "worktreePath", "worktree_path")?;
@@ -823,7 +1185,9 @@ pub async fn dispatch_command(
         "send_native_notification" => {
             let title: String = from_field(&args, "title")?;
             let body: Option<String> = from_field_opt(&args, "body")?;
-            crate::send_native_notification(app.clone(), title, body).await?;
+            let category: Option<crate::focus::NotificationCategory> =
+                from_field_opt(&args, "category")?;
+            crate::send_native_notification(app.clone(), title, body, category).await?;
             Ok(Value::Null)
         }
         "save_emergency_data" => {
@@ -1107,6 +1471,23 @@ pub async fn dispatch_command(
             // NATIVE ONLY: Terminals don't work in browser mode
             Ok(Value::Null)
         }
+        "run_project_command" => {
+            // NATIVE ONLY: Runs are PTYs, which don't work in browser mode
+            Err("Project commands can only be run in the desktop app".to_string())
+        }
+        "list_project_command_runs" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let result = crate::terminal::list_project_command_runs(app.clone(), project_id).await;
+            to_value(result)
+        }
+        "get_project_command_output" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let run_id: String = field(&args, "runId", "run_id")?;
+            let result =
+                crate::terminal::get_project_command_output(app.clone(), project_id, run_id)
+                    .await?;
+            to_value(result)
+        }
 
         // =====================================================================
         // Session Management (additional)
@@ -1139,6 +1520,7 @@ pub async fn dispatch_command(
             let pending_plan_message_id: Option<Option<String>> =
                 field_opt(&args, "pendingPlanMessageId", "pending_plan_message_id")?;
             let label: Option<String> = field_opt(&args, "label", "label")?;
+            let attribution: Option<String> = from_field_opt(&args, "attribution")?;
             crate::chat::update_session_state(
                 app.clone(),
                 worktree_id,
@@ -1155,6 +1537,7 @@ pub async fn dispatch_command(
                 plan_file_path,
                 pending_plan_message_id,
                 label,
+                attribution,
             )
             .await?;
             emit_cache_invalidation(app, &["sessions"]);
@@ -1292,55 +1675,364 @@ pub async fn dispatch_command(
             crate::chat::update_session_digest(app.clone(), session_id, digest).await?;
             Ok(Value::Null)
         }
-        "get_session_debug_info" => {
-            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
-            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+        "check_context_budget" => {
             let session_id: String = field(&args, "sessionId", "session_id")?;
-            let result = crate::chat::get_session_debug_info(
-                app.clone(),
-                worktree_id,
-                worktree_path,
-                session_id,
-            )
-            .await?;
+            let message: String = from_field(&args, "message")?;
+            let model: Option<String> = from_field_opt(&args, "model")?;
+            let result =
+                crate::chat::check_context_budget(app.clone(), session_id, message, model).await;
             to_value(result)
         }
-        "resume_session" => {
+        "summarize_context_attachments" => {
             let session_id: String = field(&args, "sessionId", "session_id")?;
-            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
-            let result = crate::chat::resume_session(app.clone(), session_id, worktree_id).await?;
+            let slugs: Vec<String> = from_field(&args, "slugs")?;
+            let result =
+                crate::chat::summarize_context_attachments(app.clone(), session_id, slugs).await?;
             to_value(result)
         }
-        "broadcast_session_setting" => {
+        "distill_session_lesson" => {
             let session_id: String = field(&args, "sessionId", "session_id")?;
-            let key: String = field(&args, "key", "key")?;
-            let value: String = field(&args, "value", "value")?;
-            crate::chat::broadcast_session_setting(app.clone(), session_id, key, value).await?;
-            Ok(Value::Null)
+            let result = crate::chat::distill_session_lesson(app.clone(), session_id).await?;
+            to_value(result)
         }
-
-        // =====================================================================
-        // CLI Management
-        // =====================================================================
-        "check_claude_cli_installed" => {
-            let result = crate::claude_cli::check_claude_cli_installed(app.clone()).await?;
+        "generate_session_doc" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let kind: crate::chat::docs::DocKind = from_field(&args, "kind")?;
+            let result = crate::chat::generate_session_doc(app.clone(), session_id, kind).await?;
             to_value(result)
         }
-        "check_claude_cli_auth" => {
-            let result = crate::claude_cli::check_claude_cli_auth(app.clone()).await?;
+        "list_lessons" => {
+            let tag: Option<String> = from_field_opt(&args, "tag")?;
+            let result = crate::knowledge::commands::list_lessons(app.clone(), tag).await;
             to_value(result)
         }
-        "get_available_cli_versions" => {
-            let result = crate::claude_cli::get_available_cli_versions().await?;
+        "save_lesson" => {
+            let title: String = from_field(&args, "title")?;
+            let summary: String = from_field(&args, "summary")?;
+            let tags: Vec<String> = from_field(&args, "tags")?;
+            let session_id: Option<String> = field_opt(&args, "sessionId", "session_id")?;
+            let result = crate::knowledge::commands::save_lesson(
+                app.clone(),
+                title,
+                summary,
+                tags,
+                session_id,
+            )
+            .await?;
+            emit_cache_invalidation(app, &["lessons"]);
             to_value(result)
         }
-        "install_claude_cli" => {
-            let version: Option<String> = from_field_opt(&args, "version")?;
-            crate::claude_cli::install_claude_cli(app.clone(), version).await?;
+        "delete_lesson" => {
+            let lesson_id: String = field(&args, "lessonId", "lesson_id")?;
+            crate::knowledge::commands::delete_lesson(app.clone(), lesson_id).await?;
+            emit_cache_invalidation(app, &["lessons"]);
             Ok(Value::Null)
         }
-        "check_gh_cli_installed" => {
-            let result = crate::gh_cli::check_gh_cli_installed(app.clone()).await?;
+        "search_lessons" => {
+            let query: String = from_field(&args, "query")?;
+            let tags: Option<Vec<String>> = from_field_opt(&args, "tags")?;
+            let k: Option<usize> = from_field_opt(&args, "k")?;
+            let result =
+                crate::knowledge::commands::search_lessons(app.clone(), query, tags, k).await?;
+            to_value(result)
+        }
+        "list_event_rules" => {
+            let result = crate::rules::commands::list_event_rules(app.clone()).await;
+            to_value(result)
+        }
+        "create_event_rule" => {
+            let rule: crate::rules::RuleDraft = from_field(&args, "rule")?;
+            let result = crate::rules::commands::create_event_rule(app.clone(), rule).await?;
+            emit_cache_invalidation(app, &["event-rules"]);
+            to_value(result)
+        }
+        "update_event_rule" => {
+            let rule_id: String = field(&args, "ruleId", "rule_id")?;
+            let rule: crate::rules::RuleDraft = from_field(&args, "rule")?;
+            let result =
+                crate::rules::commands::update_event_rule(app.clone(), rule_id, rule).await?;
+            emit_cache_invalidation(app, &["event-rules"]);
+            to_value(result)
+        }
+        "delete_event_rule" => {
+            let rule_id: String = field(&args, "ruleId", "rule_id")?;
+            crate::rules::commands::delete_event_rule(app.clone(), rule_id).await?;
+            emit_cache_invalidation(app, &["event-rules"]);
+            Ok(Value::Null)
+        }
+        "get_rule_executions" => {
+            let rule_id: Option<String> = field_opt(&args, "ruleId", "rule_id")?;
+            let limit: Option<usize> = from_field_opt(&args, "limit")?;
+            let result =
+                crate::rules::commands::get_rule_executions(app.clone(), rule_id, limit).await;
+            to_value(result)
+        }
+        "list_watch_rules" => {
+            let result = crate::rules::commands::list_watch_rules(app.clone()).await;
+            to_value(result)
+        }
+        "create_watch_rule" => {
+            let rule: crate::rules::watch::WatchDraft = from_field(&args, "rule")?;
+            let result = crate::rules::commands::create_watch_rule(app.clone(), rule).await?;
+            emit_cache_invalidation(app, &["watch-rules"]);
+            to_value(result)
+        }
+        "update_watch_rule" => {
+            let rule_id: String = field(&args, "ruleId", "rule_id")?;
+            let rule: crate::rules::watch::WatchDraft = from_field(&args, "rule")?;
+            let result =
+                crate::rules::commands::update_watch_rule(app.clone(), rule_id, rule).await?;
+            emit_cache_invalidation(app, &["watch-rules"]);
+            to_value(result)
+        }
+        "delete_watch_rule" => {
+            let rule_id: String = field(&args, "ruleId", "rule_id")?;
+            crate::rules::commands::delete_watch_rule(app.clone(), rule_id).await?;
+            emit_cache_invalidation(app, &["watch-rules"]);
+            Ok(Value::Null)
+        }
+        "list_bot_connections" => {
+            let result = crate::bots::commands::list_bot_connections(app.clone()).await;
+            to_value(result)
+        }
+        "create_bot_connection" => {
+            let connection: crate::bots::BotConnectionDraft = from_field(&args, "connection")?;
+            let result =
+                crate::bots::commands::create_bot_connection(app.clone(), connection).await?;
+            emit_cache_invalidation(app, &["bot-connections"]);
+            to_value(result)
+        }
+        "update_bot_connection" => {
+            let connection_id: String = field(&args, "connectionId", "connection_id")?;
+            let connection: crate::bots::BotConnectionDraft = from_field(&args, "connection")?;
+            let result = crate::bots::commands::update_bot_connection(
+                app.clone(),
+                connection_id,
+                connection,
+            )
+            .await?;
+            emit_cache_invalidation(app, &["bot-connections"]);
+            to_value(result)
+        }
+        "delete_bot_connection" => {
+            let connection_id: String = field(&args, "connectionId", "connection_id")?;
+            crate::bots::commands::delete_bot_connection(app.clone(), connection_id).await?;
+            emit_cache_invalidation(app, &["bot-connections"]);
+            Ok(Value::Null)
+        }
+        "test_bot_connection" => {
+            let connection_id: String = field(&args, "connectionId", "connection_id")?;
+            crate::bots::commands::test_bot_connection(app.clone(), connection_id).await?;
+            Ok(Value::Null)
+        }
+        "list_profiles" => {
+            let result = crate::profiles::commands::list_profiles(app.clone()).await;
+            to_value(result)
+        }
+        "create_profile" => {
+            let name: String = from_field(&args, "name")?;
+            let result = crate::profiles::commands::create_profile(app.clone(), name).await?;
+            emit_cache_invalidation(app, &["profiles"]);
+            to_value(result)
+        }
+        "rename_profile" => {
+            let profile_id: String = field(&args, "profileId", "profile_id")?;
+            let name: String = from_field(&args, "name")?;
+            let result =
+                crate::profiles::commands::rename_profile(app.clone(), profile_id, name).await?;
+            emit_cache_invalidation(app, &["profiles"]);
+            to_value(result)
+        }
+        "delete_profile" => {
+            let profile_id: String = field(&args, "profileId", "profile_id")?;
+            crate::profiles::commands::delete_profile(app.clone(), profile_id).await?;
+            emit_cache_invalidation(app, &["profiles"]);
+            Ok(Value::Null)
+        }
+        "switch_profile" => {
+            let profile_id: String = field(&args, "profileId", "profile_id")?;
+            crate::profiles::commands::switch_profile(app.clone(), profile_id).await?;
+            Ok(Value::Null)
+        }
+        "get_guest_mode" => {
+            let result = crate::guest::commands::get_guest_mode(app.clone()).await;
+            to_value(result)
+        }
+        "enter_guest_mode" => {
+            let passcode: String = from_field(&args, "passcode")?;
+            let result = crate::guest::commands::enter_guest_mode(app.clone(), passcode).await?;
+            emit_cache_invalidation(app, &["guest-mode"]);
+            to_value(result)
+        }
+        "exit_guest_mode" => {
+            let passcode: String = from_field(&args, "passcode")?;
+            let result = crate::guest::commands::exit_guest_mode(app.clone(), passcode).await?;
+            emit_cache_invalidation(app, &["guest-mode"]);
+            to_value(result)
+        }
+        "get_emergency_status" => {
+            let result = crate::emergency::commands::get_emergency_status(app.clone()).await;
+            to_value(result)
+        }
+        "emergency_stop" => {
+            let result = crate::emergency::commands::emergency_stop(app.clone()).await?;
+            emit_cache_invalidation(app, &["emergency"]);
+            to_value(result)
+        }
+        "clear_emergency_stop" => {
+            let result = crate::emergency::commands::clear_emergency_stop(app.clone()).await?;
+            emit_cache_invalidation(app, &["emergency"]);
+            to_value(result)
+        }
+        "get_session_debug_info" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let result = crate::chat::get_session_debug_info(
+                app.clone(),
+                worktree_id,
+                worktree_path,
+                session_id,
+            )
+            .await?;
+            to_value(result)
+        }
+        "add_message_annotation" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let message_id: String = field(&args, "messageId", "message_id")?;
+            let note: Option<String> = from_field_opt(&args, "note")?;
+            let rating: Option<crate::chat::annotations::Rating> = from_field_opt(&args, "rating")?;
+            let labels: Option<Vec<String>> = from_field_opt(&args, "labels")?;
+            let result = crate::chat::add_message_annotation(
+                app.clone(),
+                session_id,
+                message_id,
+                note,
+                rating,
+                labels,
+            )
+            .await?;
+            to_value(result)
+        }
+        "update_message_annotation" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let annotation_id: String = field(&args, "annotationId", "annotation_id")?;
+            let note: Option<String> = from_field_opt(&args, "note")?;
+            let rating: Option<crate::chat::annotations::Rating> = from_field_opt(&args, "rating")?;
+            let clear_rating: Option<bool> = field_opt(&args, "clearRating", "clear_rating")?;
+            let labels: Option<Vec<String>> = from_field_opt(&args, "labels")?;
+            let result = crate::chat::update_message_annotation(
+                app.clone(),
+                session_id,
+                annotation_id,
+                note,
+                rating,
+                clear_rating,
+                labels,
+            )
+            .await?;
+            to_value(result)
+        }
+        "delete_message_annotation" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let annotation_id: String = field(&args, "annotationId", "annotation_id")?;
+            let result =
+                crate::chat::delete_message_annotation(app.clone(), session_id, annotation_id)
+                    .await?;
+            to_value(result)
+        }
+        "list_message_annotations" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let message_id: Option<String> = field_opt(&args, "messageId", "message_id")?;
+            let result =
+                crate::chat::list_message_annotations(app.clone(), session_id, message_id).await?;
+            to_value(result)
+        }
+        "get_session_environment" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let result = crate::chat::get_session_environment(app.clone(), session_id).await?;
+            to_value(result)
+        }
+        "get_session_vulnerabilities" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let result = crate::chat::get_session_vulnerabilities(app.clone(), session_id).await?;
+            to_value(result)
+        }
+        "scan_session_vulnerabilities" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let result =
+                crate::chat::scan_session_vulnerabilities(app.clone(), session_id, worktree_path)
+                    .await?;
+            emit_cache_invalidation(app, &["vulnerabilities"]);
+            to_value(result)
+        }
+        "resume_session" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result = crate::chat::resume_session(app.clone(), session_id, worktree_id).await?;
+            to_value(result)
+        }
+        "broadcast_session_setting" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let key: String = field(&args, "key", "key")?;
+            let value: String = field(&args, "value", "value")?;
+            crate::chat::broadcast_session_setting(app.clone(), session_id, key, value).await?;
+            Ok(Value::Null)
+        }
+
+        // =====================================================================
+        // CLI Management
+        // =====================================================================
+        "check_claude_cli_installed" => {
+            let result = crate::claude_cli::check_claude_cli_installed(app.clone()).await?;
+            to_value(result)
+        }
+        "check_claude_cli_auth" => {
+            let result = crate::claude_cli::check_claude_cli_auth(app.clone()).await?;
+            to_value(result)
+        }
+        "get_claude_auth_status" => {
+            let result = crate::claude_cli::get_claude_auth_status().await?;
+            to_value(result)
+        }
+        "start_claude_login" => {
+            let result = crate::claude_cli::start_claude_login(app.clone()).await?;
+            to_value(result)
+        }
+        "cancel_claude_login" => {
+            crate::claude_cli::cancel_claude_login().await?;
+            Ok(Value::Null)
+        }
+        "get_available_cli_versions" => {
+            let result = crate::claude_cli::get_available_cli_versions().await?;
+            to_value(result)
+        }
+        "install_claude_cli" => {
+            let version: Option<String> = from_field_opt(&args, "version")?;
+            crate::claude_cli::install_claude_cli(app.clone(), version).await?;
+            Ok(Value::Null)
+        }
+        "list_installed_cli_versions" => {
+            let result = crate::claude_cli::list_installed_cli_versions(app.clone()).await;
+            to_value(result)
+        }
+        "install_cli_version" => {
+            let version: String = from_field(&args, "version")?;
+            crate::claude_cli::install_cli_version(app.clone(), version).await?;
+            emit_cache_invalidation(app, &["cli-versions"]);
+            Ok(Value::Null)
+        }
+        "get_pinned_cli_version" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let result =
+                crate::claude_cli::get_pinned_cli_version(app.clone(), worktree_id, worktree_path)
+                    .await;
+            to_value(result)
+        }
+        "check_gh_cli_installed" => {
+            let result = crate::gh_cli::check_gh_cli_installed(app.clone()).await?;
             to_value(result)
         }
         "check_gh_cli_auth" => {
@@ -1348,7 +2040,7 @@ pub async fn dispatch_command(
             to_value(result)
         }
         "get_available_gh_versions" => {
-            let result = crate::gh_cli::get_available_gh_versions().await?;
+            let result = crate::gh_cli::get_available_gh_versions(app.clone()).await?;
             to_value(result)
         }
         "install_gh_cli" => {
@@ -1356,6 +2048,434 @@ pub async fn dispatch_command(
             crate::gh_cli::install_gh_cli(app.clone(), version).await?;
             Ok(Value::Null)
         }
+        "install_tools" => {
+            let tools: Vec<String> = from_field(&args, "tools")?;
+            let result = crate::tool_install::commands::install_tools(app.clone(), tools).await?;
+            to_value(result)
+        }
+        "detect_node_runtime" => {
+            let result = crate::tool_install::commands::detect_node_runtime(app.clone()).await?;
+            to_value(result)
+        }
+        "install_npm_package" => {
+            let package: String = from_field(&args, "package")?;
+            crate::tool_install::commands::install_npm_package(app.clone(), package).await?;
+            Ok(Value::Null)
+        }
+        "install_codex_cli_via_package_manager" => {
+            let result =
+                crate::tool_install::commands::install_codex_cli_via_package_manager(app.clone())
+                    .await?;
+            to_value(result)
+        }
+        "install_codex_cli_from_file" => {
+            let path: String = from_field(&args, "path")?;
+            let sha256: Option<String> = from_field_opt(&args, "sha256")?;
+            let result = crate::tool_install::commands::install_codex_cli_from_file(
+                app.clone(),
+                path,
+                sha256,
+            )
+            .await?;
+            to_value(result)
+        }
+        "list_installed_codex_versions" => {
+            let result =
+                crate::tool_install::commands::list_installed_codex_versions(app.clone()).await?;
+            to_value(result)
+        }
+        "switch_codex_version" => {
+            let version: String = from_field(&args, "version")?;
+            let result =
+                crate::tool_install::commands::switch_codex_version(app.clone(), version).await?;
+            to_value(result)
+        }
+        "get_shell_integration_status" => {
+            let result =
+                crate::tool_install::commands::get_shell_integration_status(app.clone()).await;
+            to_value(result)
+        }
+        "enable_shell_integration" => {
+            let mode: crate::tool_install::shell_integration::IntegrationMode =
+                from_field(&args, "mode")?;
+            let result =
+                crate::tool_install::commands::enable_shell_integration(app.clone(), mode).await?;
+            to_value(result)
+        }
+        "disable_shell_integration" => {
+            let result =
+                crate::tool_install::commands::disable_shell_integration(app.clone()).await?;
+            to_value(result)
+        }
+        "get_shell_completions_status" => {
+            let result =
+                crate::tool_install::commands::get_shell_completions_status(app.clone()).await;
+            to_value(result)
+        }
+        "install_shell_completions" => {
+            let shells: Option<Vec<crate::tool_install::completions::Shell>> =
+                from_field_opt(&args, "shells")?;
+            let aliases: Option<bool> = from_field_opt(&args, "aliases")?;
+            let result = crate::tool_install::commands::install_shell_completions(
+                app.clone(),
+                shells,
+                aliases,
+            )
+            .await?;
+            to_value(result)
+        }
+        "uninstall_shell_completions" => {
+            let result =
+                crate::tool_install::commands::uninstall_shell_completions(app.clone()).await?;
+            to_value(result)
+        }
+        "detect_python" => {
+            let result = crate::python_env::commands::detect_python(app.clone()).await?;
+            to_value(result)
+        }
+        "create_python_env" => {
+            let tool: String = from_field(&args, "tool")?;
+            let package: String = from_field(&args, "package")?;
+            let result =
+                crate::python_env::commands::create_python_env(app.clone(), tool, package).await?;
+            to_value(result)
+        }
+        "list_python_envs" => {
+            let result = crate::python_env::commands::list_python_envs(app.clone()).await?;
+            to_value(result)
+        }
+        "repair_python_env" => {
+            let tool: String = from_field(&args, "tool")?;
+            let result = crate::python_env::commands::repair_python_env(app.clone(), tool).await?;
+            to_value(result)
+        }
+        "remove_python_env" => {
+            let tool: String = from_field(&args, "tool")?;
+            crate::python_env::commands::remove_python_env(app.clone(), tool).await?;
+            Ok(Value::Null)
+        }
+        "get_python_tool_entry_point" => {
+            let tool: String = from_field(&args, "tool")?;
+            let result =
+                crate::python_env::commands::get_python_tool_entry_point(app.clone(), tool).await?;
+            to_value(result)
+        }
+        "get_helper_tools_status" => {
+            let result =
+                crate::tool_install::commands::get_helper_tools_status(app.clone()).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Speech-to-text
+        // =====================================================================
+        "get_speech_status" => {
+            let result = crate::speech::get_speech_status(app.clone()).await?;
+            to_value(result)
+        }
+        "list_speech_models" => {
+            let result = crate::speech::list_speech_models(app.clone()).await?;
+            to_value(result)
+        }
+        "download_speech_model" => {
+            let model: String = from_field(&args, "model")?;
+            crate::speech::download_speech_model(app.clone(), model).await?;
+            Ok(Value::Null)
+        }
+        "delete_speech_model" => {
+            let model: String = from_field(&args, "model")?;
+            crate::speech::delete_speech_model(app.clone(), model).await?;
+            Ok(Value::Null)
+        }
+        "transcribe_audio" => {
+            let data: String = from_field(&args, "data")?;
+            let mime_type: String = field(&args, "mimeType", "mime_type")?;
+            let language: Option<String> = from_field_opt(&args, "language")?;
+            let result =
+                crate::speech::transcribe_audio(app.clone(), data, mime_type, language).await?;
+            to_value(result)
+        }
+        "speak_text" => {
+            let text: String = from_field(&args, "text")?;
+            let voice: Option<String> = from_field_opt(&args, "voice")?;
+            crate::speech::speak_text(text, voice).await?;
+            Ok(Value::Null)
+        }
+
+        // =====================================================================
+        // Power management
+        // =====================================================================
+        "get_focus_state" => {
+            let result = crate::focus::commands::get_focus_state(app.clone()).await?;
+            to_value(result)
+        }
+        "get_power_state" => {
+            let result = crate::power::commands::get_power_state(app.clone()).await?;
+            to_value(result)
+        }
+        "check_provider_connectivity" => {
+            let result = crate::power::commands::check_provider_connectivity(app.clone()).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Audit log
+        // =====================================================================
+        "list_web_requests" => {
+            let project_id: Option<String> = field_opt(&args, "projectId", "project_id")?;
+            let session_id: Option<String> = field_opt(&args, "sessionId", "session_id")?;
+            let limit: Option<usize> = from_field_opt(&args, "limit")?;
+            let result = crate::audit::commands::list_web_requests(
+                app.clone(),
+                project_id,
+                session_id,
+                limit,
+            )
+            .await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Crash reports
+        // =====================================================================
+        "list_crash_reports" => {
+            let result = crate::crash_reports::commands::list_crash_reports(app.clone()).await?;
+            to_value(result)
+        }
+        "get_crash_report" => {
+            let id: String = from_field(&args, "id")?;
+            let result = crate::crash_reports::commands::get_crash_report(app.clone(), id).await?;
+            to_value(result)
+        }
+        "delete_crash_report" => {
+            let id: String = from_field(&args, "id")?;
+            crate::crash_reports::commands::delete_crash_report(app.clone(), id).await?;
+            Ok(Value::Null)
+        }
+        "submit_crash_report" => {
+            let id: String = from_field(&args, "id")?;
+            let result =
+                crate::crash_reports::commands::submit_crash_report(app.clone(), id).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Self-test
+        // =====================================================================
+        "run_self_test" => {
+            // Server is already running if we're receiving this via WebSocket
+            let http_server = crate::self_test::run_check("http_server", || {
+                Ok("Serving this WebSocket connection".to_string())
+            });
+            let result = crate::self_test::commands::run_suite(app, http_server).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Mock backend
+        // =====================================================================
+        "set_mock_scenario" => {
+            let name: Option<String> = from_field_opt(&args, "name")?;
+            let result = crate::mock::commands::set_mock_scenario(app.clone(), name).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // TLS diagnostics
+        // =====================================================================
+        "diagnose_tls" => {
+            let url: String = from_field(&args, "url")?;
+            let result = crate::http_client::commands::diagnose_tls(url).await?;
+            to_value(result)
+        }
+        "test_proxy_connection" => {
+            let proxy: Option<String> = from_field_opt(&args, "proxy")?;
+            let url: Option<String> = from_field_opt(&args, "url")?;
+            let result = crate::http_client::commands::test_proxy_connection(proxy, url).await?;
+            to_value(result)
+        }
+        "set_github_token" => {
+            let token: Option<String> = from_field_opt(&args, "token")?;
+            crate::http_client::commands::set_github_token(app.clone(), token).await?;
+            Ok(Value::Null)
+        }
+        "has_github_token" => {
+            let result = crate::http_client::commands::has_github_token().await;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Provider overview
+        // =====================================================================
+        "get_providers_overview" => {
+            let result = crate::providers::commands::get_providers_overview(app.clone()).await?;
+            to_value(result)
+        }
+        "get_provider_key_status" => {
+            let result = crate::providers::commands::get_provider_key_status(app.clone()).await?;
+            to_value(result)
+        }
+        "rotate_provider_key" => {
+            let profile: String = from_field(&args, "profile")?;
+            let new_key: String = field(&args, "newKey", "new_key")?;
+            let key_expires_at: Option<u64> = field_opt(&args, "keyExpiresAt", "key_expires_at")?;
+            let result = crate::providers::commands::rotate_provider_key(
+                app.clone(),
+                profile,
+                new_key,
+                key_expires_at,
+            )
+            .await?;
+            emit_cache_invalidation(app, &["preferences", "provider-keys"]);
+            to_value(result)
+        }
+        "rollback_provider_key" => {
+            let profile: String = from_field(&args, "profile")?;
+            let result =
+                crate::providers::commands::rollback_provider_key(app.clone(), profile).await?;
+            emit_cache_invalidation(app, &["preferences", "provider-keys"]);
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Quota telemetry
+        // =====================================================================
+        "list_quota_events" => {
+            let since: Option<u64> = from_field_opt(&args, "since")?;
+            let result = crate::quota::commands::list_quota_events(since).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Storage integrity
+        // =====================================================================
+        "verify_database_integrity" => {
+            let result =
+                crate::migrations::commands::verify_database_integrity(app.clone()).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Backups
+        // =====================================================================
+        "create_backup_now" => {
+            let result = crate::backups::commands::create_backup_now(app.clone()).await?;
+            to_value(result)
+        }
+        "list_backups" => {
+            let result = crate::backups::commands::list_backups(app.clone()).await?;
+            to_value(result)
+        }
+        "restore_backup" => {
+            let path: String = from_field(&args, "path")?;
+            let result = crate::backups::commands::restore_backup(app.clone(), path).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Prompt library
+        // =====================================================================
+        "get_prompt_library" => {
+            let result = crate::prompts::commands::get_prompt_library(app.clone()).await?;
+            to_value(result)
+        }
+        "sync_shared_library" => {
+            let result = crate::prompts::commands::sync_shared_library(app.clone()).await?;
+            to_value(result)
+        }
+        "browse_community_library" => {
+            let result = crate::prompts::commands::browse_community_library(app.clone()).await?;
+            to_value(result)
+        }
+        "install_community_item" => {
+            let item_id: String = field(&args, "itemId", "item_id")?;
+            let overwrite: Option<bool> = from_field_opt(&args, "overwrite")?;
+            let result =
+                crate::prompts::commands::install_community_item(app.clone(), item_id, overwrite)
+                    .await?;
+            emit_cache_invalidation(app, &["preferences", "community-installs"]);
+            to_value(result)
+        }
+        "list_community_installs" => {
+            let result = crate::prompts::commands::list_community_installs(app.clone()).await?;
+            to_value(result)
+        }
+        "check_community_updates" => {
+            let result = crate::prompts::commands::check_community_updates(app.clone()).await?;
+            to_value(result)
+        }
+        "list_template_variables" => {
+            let result = crate::prompts::commands::list_template_variables().await;
+            to_value(result)
+        }
+        "render_prompt_template" => {
+            let template: String = from_field(&args, "template")?;
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let session_id: Option<String> = field_opt(&args, "sessionId", "session_id")?;
+            let template_name: Option<String> = field_opt(&args, "templateName", "template_name")?;
+            let result = crate::prompts::commands::render_prompt_template(
+                app.clone(),
+                template,
+                worktree_path,
+                session_id,
+                template_name,
+            )
+            .await?;
+            to_value(result)
+        }
+        "get_template_effectiveness" => {
+            let result = crate::prompts::commands::get_template_effectiveness(app.clone()).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Report exports
+        // =====================================================================
+        "export_usage_csv" => {
+            let path: String = from_field(&args, "path")?;
+            let since: Option<u64> = from_field_opt(&args, "since")?;
+            let attribution: Option<String> = from_field_opt(&args, "attribution")?;
+            let result =
+                crate::reports::commands::export_usage_csv(app.clone(), path, since, attribution)
+                    .await?;
+            to_value(result)
+        }
+        "get_attribution_subtotals" => {
+            let since: Option<u64> = from_field_opt(&args, "since")?;
+            let result =
+                crate::reports::commands::get_attribution_subtotals(app.clone(), since).await?;
+            to_value(result)
+        }
+        "get_activity_heatmap" => {
+            let year: Option<i32> = from_field_opt(&args, "year")?;
+            let result = crate::reports::commands::get_activity_heatmap(app.clone(), year).await?;
+            to_value(result)
+        }
+        "export_attribution_csv" => {
+            let path: String = from_field(&args, "path")?;
+            let since: Option<u64> = from_field_opt(&args, "since")?;
+            let result =
+                crate::reports::commands::export_attribution_csv(app.clone(), path, since).await?;
+            to_value(result)
+        }
+        "send_email_digest" => {
+            let result = crate::reports::commands::send_email_digest(app.clone()).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Email
+        // =====================================================================
+        "set_smtp_password" => {
+            let password: Option<String> = from_field_opt(&args, "password")?;
+            crate::email::commands::set_smtp_password(app.clone(), password).await?;
+            Ok(Value::Null)
+        }
+        "send_test_email" => {
+            let to: Option<String> = from_field_opt(&args, "to")?;
+            crate::email::commands::send_test_email(app.clone(), to).await?;
+            Ok(Value::Null)
+        }
 
         // =====================================================================
         // HTTP Server control (additional)
@@ -1,6 +1,8 @@
 pub mod auth;
 pub mod dispatch;
+pub mod routing;
 pub mod server;
+pub mod watchdog;
 pub mod websocket;
 
 use serde::Serialize;
@@ -8,6 +10,9 @@ use serde_json::Value;
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::broadcast;
 
+pub use crate::runtime::EmitTarget;
+use routing::Subscriber;
+
 /// Broadcast channel for sending events to all connected WebSocket clients.
 /// Managed as Tauri state so any code with an AppHandle can broadcast.
 pub struct WsBroadcaster {
@@ -18,6 +23,7 @@ pub struct WsBroadcaster {
 pub struct WsEvent {
     pub event: String,
     pub payload: Value,
+    pub target: EmitTarget,
 }
 
 impl WsBroadcaster {
@@ -28,11 +34,13 @@ impl WsBroadcaster {
         (Self { tx }, tx_clone)
     }
 
-    pub fn broadcast(&self, event: &str, payload: &Value) {
+    /// Send to the clients `target` selects (each client filters, see `routing`)
+    pub fn send(&self, event: &str, payload: &Value, target: EmitTarget) {
         // Ignore send errors (no active receivers is fine)
         let _ = self.tx.send(WsEvent {
             event: event.to_string(),
             payload: payload.clone(),
+            target,
         });
     }
 
@@ -41,25 +49,50 @@ impl WsBroadcaster {
     }
 }
 
-/// Extension trait on AppHandle that sends to both Tauri IPC and WebSocket clients.
+/// Event sink on AppHandle that sends to both Tauri IPC and WebSocket clients.
 /// Use `app.emit_all("event", &payload)` instead of `app.emit("event", &payload)`.
-pub trait EmitExt {
-    fn emit_all<S: Serialize + Clone>(&self, event: &str, payload: &S) -> Result<(), String>;
-}
+pub use crate::runtime::EventSink as EmitExt;
 
 impl EmitExt for AppHandle {
     fn emit_all<S: Serialize + Clone>(&self, event: &str, payload: &S) -> Result<(), String> {
-        // Send to Tauri frontend (native app)
-        self.emit(event, payload.clone())
-            .map_err(|e| format!("Tauri emit failed: {e}"))?;
+        self.emit_to(&EmitTarget::All, event, payload)
+    }
 
-        // Broadcast to WebSocket clients (if server is running)
-        if let Some(ws) = self.try_state::<WsBroadcaster>() {
-            let value = serde_json::to_value(payload)
-                .map_err(|e| format!("Failed to serialize for WS broadcast: {e}"))?;
-            ws.broadcast(event, &value);
+    fn emit_to<S: Serialize + Clone>(
+        &self,
+        target: &EmitTarget,
+        event: &str,
+        payload: &S,
+    ) -> Result<(), String> {
+        // Send to Tauri frontend (native app). Only listeners registered on a
+        // window can be filtered, so the frontend transport listens on the
+        // current window.
+        self.emit_filter(event, payload.clone(), |listener| match listener {
+            tauri::EventTarget::Window { label }
+            | tauri::EventTarget::Webview { label }
+            | tauri::EventTarget::WebviewWindow { label } => {
+                routing::wants(&Subscriber::Window(label.clone()), event, target)
+            }
+            _ => *target == EmitTarget::All,
+        })
+        .map_err(|e| format!("Tauri emit failed: {e}"))?;
+
+        // Send to WebSocket clients (if server is running); each filters for
+        // itself. Window targets are native only.
+        if !matches!(target, EmitTarget::Window(_)) {
+            if let Some(ws) = self.try_state::<WsBroadcaster>() {
+                let value = serde_json::to_value(payload)
+                    .map_err(|e| format!("Failed to serialize for WS broadcast: {e}"))?;
+                ws.send(event, &value, target.clone());
+            }
         }
 
+        // User-defined event rules
+        crate::rules::on_event(self, event, payload);
+
+        // Slack/Discord bot notifications
+        crate::bots::on_event(self, event, payload);
+
         Ok(())
     }
 }
@@ -0,0 +1,174 @@
+//! Which windows and WebSocket clients receive which events
+//!
+//! Every subscriber (a native window or a WebSocket client) receives every
+//! event until it narrows that with `set_event_subscriptions`: to the
+//! sessions it shows, and to topics matching patterns like `chat:*`. Session
+//! filters only apply to events sent with `EmitTarget::Session`; broadcasts
+//! still reach every subscriber whose topics match.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::EmitTarget;
+
+/// Something that receives events
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Subscriber {
+    /// A native window, by label
+    Window(String),
+    /// A WebSocket connection
+    Client(u64),
+}
+
+/// What a subscriber wants to receive
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventSubscriptions {
+    /// Sessions whose events to receive (None = all)
+    pub sessions: Option<BTreeSet<String>>,
+    /// Event name patterns, where `*` matches any text (None = all)
+    pub topics: Option<Vec<String>>,
+}
+
+impl EventSubscriptions {
+    /// Whether `event` sent to `target` should reach this subscriber
+    pub fn wants(&self, event: &str, target: &EmitTarget) -> bool {
+        let session_ok = match (target, &self.sessions) {
+            (EmitTarget::Session(id), Some(sessions)) => sessions.contains(id),
+            _ => true,
+        };
+        let topic_ok = self
+            .topics
+            .as_ref()
+            .is_none_or(|topics| topics.iter().any(|t| topic_matches(t, event)));
+        session_ok && topic_ok
+    }
+}
+
+/// Whether `event` matches `pattern`, where `*` matches any text
+pub fn topic_matches(pattern: &str, event: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = event.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No wildcard: the whole event must match
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+static SUBSCRIPTIONS: Lazy<Mutex<HashMap<Subscriber, EventSubscriptions>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// ID for a new WebSocket connection
+pub fn next_client_id() -> u64 {
+    NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Replace a subscriber's subscriptions (the default receives everything)
+pub fn subscribe(subscriber: Subscriber, subscriptions: EventSubscriptions) {
+    let mut all = SUBSCRIPTIONS.lock().unwrap();
+    if subscriptions == EventSubscriptions::default() {
+        all.remove(&subscriber);
+    } else {
+        all.insert(subscriber, subscriptions);
+    }
+}
+
+/// Forget a subscriber that went away
+pub fn unsubscribe(subscriber: &Subscriber) {
+    SUBSCRIPTIONS.lock().unwrap().remove(subscriber);
+}
+
+/// Whether `event` sent to `target` should reach `subscriber`
+pub fn wants(subscriber: &Subscriber, event: &str, target: &EmitTarget) -> bool {
+    match target {
+        EmitTarget::Window(label) => *subscriber == Subscriber::Window(label.clone()),
+        _ => SUBSCRIPTIONS
+            .lock()
+            .unwrap()
+            .get(subscriber)
+            .is_none_or(|subscriptions| subscriptions.wants(event, target)),
+    }
+}
+
+/// Narrow the events the calling window receives
+#[tauri::command]
+pub async fn set_event_subscriptions(
+    window: tauri::WebviewWindow,
+    subscriptions: EventSubscriptions,
+) -> Result<(), String> {
+    log::trace!(
+        "Event subscriptions for window {}: {subscriptions:?}",
+        window.label()
+    );
+    subscribe(
+        Subscriber::Window(window.label().to_string()),
+        subscriptions,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("chat:chunk", "chat:chunk"));
+        assert!(!topic_matches("chat:chunk", "chat:chunks"));
+        assert!(topic_matches("chat:*", "chat:tool_use"));
+        assert!(!topic_matches("chat:*", "git:status"));
+        assert!(topic_matches("*:done", "chat:done"));
+        assert!(topic_matches("*", "anything"));
+        assert!(topic_matches("backend:*-drift", "backend:schema-drift"));
+        assert!(!topic_matches("backend:*-drift", "backend:schema"));
+    }
+
+    #[test]
+    fn test_routing() {
+        let session = |id: &str| EmitTarget::Session(id.to_string());
+        let window = Subscriber::Window("routing-test".to_string());
+        let client = Subscriber::Client(next_client_id());
+
+        // Nothing subscribed: everything is delivered
+        assert!(wants(&window, "chat:chunk", &session("s-1")));
+
+        subscribe(
+            window.clone(),
+            EventSubscriptions {
+                sessions: Some(BTreeSet::from(["s-1".to_string()])),
+                topics: Some(vec!["chat:*".to_string()]),
+            },
+        );
+        assert!(wants(&window, "chat:chunk", &session("s-1")));
+        assert!(!wants(&window, "chat:chunk", &session("s-2")));
+        assert!(wants(&window, "chat:done", &EmitTarget::All));
+        assert!(!wants(&window, "git:status", &EmitTarget::All));
+        assert!(wants(&client, "chat:chunk", &session("s-2")));
+
+        // Window targets reach only that window, whatever it subscribed to
+        let target = EmitTarget::Window("routing-test".to_string());
+        assert!(wants(&window, "git:status", &target));
+        assert!(!wants(&client, "git:status", &target));
+
+        subscribe(window.clone(), EventSubscriptions::default());
+        assert!(wants(&window, "git:status", &EmitTarget::All));
+        unsubscribe(&client);
+    }
+}
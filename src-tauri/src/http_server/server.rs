@@ -1,8 +1,9 @@
 use axum::{
-    extract::{ws::WebSocketUpgrade, Query, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{ws::WebSocketUpgrade, Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
@@ -29,6 +30,8 @@ struct AppState {
 /// Server handle for shutdown coordination.
 pub struct HttpServerHandle {
     pub shutdown_tx: tokio::sync::oneshot::Sender<()>,
+    /// The serve task; finishes with the error that stopped the accept loop
+    pub task: tokio::task::JoinHandle<Result<(), String>>,
     pub port: u16,
     pub token: String,
     pub url: String,
@@ -51,6 +54,11 @@ struct WsAuth {
     token: Option<String>,
 }
 
+#[derive(Deserialize)]
+struct HookAuth {
+    secret: Option<String>,
+}
+
 /// Resolve the dist directory path at runtime.
 /// Checks multiple locations for development and production scenarios.
 fn resolve_dist_path(app: &AppHandle) -> std::path::PathBuf {
@@ -134,6 +142,9 @@ pub async fn start_server(
         .route("/ws", get(ws_handler))
         .route("/api/auth", get(auth_handler))
         .route("/api/init", get(init_handler))
+        .route("/api/health", get(health_handler))
+        .route("/api/hooks/{key}", post(hook_handler))
+        .route("/hooks/{key}", post(hook_handler))
         .fallback_service(serve_dir)
         .layer(cors)
         .with_state(state);
@@ -163,7 +174,7 @@ pub async fn start_server(
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
 
     // Spawn the server
-    tokio::spawn(async move {
+    let task = tokio::spawn(async move {
         log::info!("HTTP server listening on {local_addr} (localhost_only: {localhost_only})");
         axum::serve(listener, router)
             .with_graceful_shutdown(async {
@@ -171,11 +182,15 @@ pub async fn start_server(
                 log::info!("HTTP server shutting down");
             })
             .await
-            .unwrap_or_else(|e| log::error!("HTTP server error: {e}"));
+            .map_err(|e| {
+                log::error!("HTTP server error: {e}");
+                e.to_string()
+            })
     });
 
     Ok(HttpServerHandle {
         shutdown_tx,
+        task,
         port: local_addr.port(),
         token,
         url,
@@ -211,6 +226,73 @@ async fn ws_handler(
     ws.on_upgrade(move |socket| handle_ws_connection(socket, app, event_rx))
 }
 
+/// Liveness endpoint used by the watchdog. Needs no token and returns no data.
+async fn health_handler() -> Response {
+    Json(serde_json::json!({ "ok": true })).into_response()
+}
+
+/// Webhook for a watch rule (e.g. from CI), by rule id or hook name.
+/// Authenticated by the rule's own secret rather than the server token
+/// (see `rules::hooks`); the JSON body, if any, becomes the trigger payload.
+async fn hook_handler(
+    Path(key): Path<String>,
+    Query(params): Query<HookAuth>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    use crate::rules::hooks::HookCredential;
+
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let credentials: Vec<HookCredential> = [
+        params.secret,
+        header("x-jean-secret"),
+        header("x-gitlab-token"),
+    ]
+    .into_iter()
+    .flatten()
+    .map(HookCredential::Secret)
+    .chain(header("x-hub-signature-256").map(HookCredential::Signature))
+    .collect();
+    let event_type = header("x-github-event").or_else(|| header("x-gitlab-event"));
+
+    let payload = if body.is_empty() {
+        Value::Null
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(payload) => payload,
+            Err(e) => {
+                return (StatusCode::BAD_REQUEST, format!("Invalid JSON body: {e}"))
+                    .into_response();
+            }
+        }
+    };
+    match crate::rules::hooks::receive_webhook(
+        &state.app,
+        &key,
+        &credentials,
+        &body,
+        payload,
+        event_type,
+    ) {
+        Ok(fired) => (
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({ "ok": true, "fired": fired })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "ok": false, "error": e })),
+        )
+            .into_response(),
+    }
+}
+
 /// Token validation endpoint. Returns 200 with { ok: true } on success,
 /// or 401 with { ok: false, error: "..." } on failure.
 async fn auth_handler(Query(params): Query<WsAuth>, State(state): State<AppState>) -> Response {
@@ -245,7 +327,7 @@ async fn init_handler(Query(params): Query<WsAuth>, State(state): State<AppState
     // Fetch base data in parallel
     let (projects_result, preferences_result, ui_state_result) = tokio::join!(
         crate::projects::list_projects(state.app.clone()),
-        crate::load_preferences(state.app.clone()),
+        crate::guest::commands::load_preferences(state.app.clone()),
         crate::load_ui_state(state.app.clone()),
     );
 
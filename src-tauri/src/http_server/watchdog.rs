@@ -0,0 +1,231 @@
+//! Supervisor that restarts a dead or wedged HTTP server
+//!
+//! The native UI talks to the backend over IPC, so it keeps working when the
+//! embedded server's accept loop dies and remote clients just stop getting
+//! responses. A background thread pings `/api/health` over a plain TCP
+//! connection; when the serve task has exited, or the ping fails several
+//! times in a row, the server is restarted on the same port with the same
+//! token and an `http-server:watchdog` event records the cause.
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use super::server::{start_server, HttpServerHandle};
+use super::EmitExt;
+use crate::background_tasks::supervisor::supervise;
+
+/// How often the server is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a health ping may take
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Consecutive failed pings before the server counts as wedged
+const FAILED_PINGS_BEFORE_RESTART: u32 = 2;
+
+/// Attempts to re-bind the port (the old listener may take a moment to close)
+const RESTART_ATTEMPTS: u32 = 5;
+
+/// Payload for http-server:watchdog events
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchdogEvent {
+    /// Why the server was considered down
+    pub cause: String,
+    pub restarted: bool,
+    /// URL of the restarted server
+    pub url: Option<String>,
+    /// Why the restart failed (if it did)
+    pub error: Option<String>,
+    /// Unix timestamp
+    pub at: u64,
+}
+
+/// Send `GET /api/health` to the server on localhost and check for HTTP 200
+pub fn ping(port: u16, timeout: Duration) -> Result<(), String> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)
+        .map_err(|e| format!("Health check couldn't connect to port {port}: {e}"))?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+        .map_err(|e| format!("Health check setup failed: {e}"))?;
+    stream
+        .write_all(b"GET /api/health HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n")
+        .map_err(|e| format!("Health check request failed: {e}"))?;
+
+    // Only the status line matters
+    let mut response = Vec::new();
+    let mut buf = [0u8; 256];
+    while !response.windows(2).any(|w| w == b"\r\n") {
+        let n = stream
+            .read(&mut buf)
+            .map_err(|e| format!("Health check got no response: {e}"))?;
+        if n == 0 {
+            break;
+        }
+        response.extend_from_slice(&buf[..n]);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) == Some("200") {
+        Ok(())
+    } else if status_line.is_empty() {
+        Err("Health check connection closed without a response".to_string())
+    } else {
+        Err(format!("Health check returned: {status_line}"))
+    }
+}
+
+/// Why the finished serve task stopped
+fn exit_cause(task: &mut tokio::task::JoinHandle<Result<(), String>>) -> String {
+    match tauri::async_runtime::block_on(task) {
+        Ok(Ok(())) => "Accept loop exited".to_string(),
+        Ok(Err(e)) => format!("Accept loop failed: {e}"),
+        Err(e) if e.is_panic() => "Server task panicked".to_string(),
+        Err(e) => format!("Server task stopped: {e}"),
+    }
+}
+
+/// Stop `old` and start a replacement on the same port
+fn restart(app: &AppHandle, old: HttpServerHandle) -> Result<HttpServerHandle, String> {
+    old.task.abort();
+    let _ = old.shutdown_tx.send(());
+
+    let mut last_error = String::new();
+    for attempt in 1..=RESTART_ATTEMPTS {
+        match tauri::async_runtime::block_on(start_server(
+            app.clone(),
+            old.port,
+            old.token.clone(),
+            old.localhost_only,
+            old.token_required,
+        )) {
+            Ok(handle) => return Ok(handle),
+            Err(e) => {
+                log::warn!("HTTP server restart attempt {attempt} failed: {e}");
+                last_error = e;
+                std::thread::sleep(Duration::from_secs(1));
+            }
+        }
+    }
+    Err(last_error)
+}
+
+/// Check the running server once; returns the updated failed-ping count
+fn check(app: &AppHandle, failed_pings: u32) -> u32 {
+    let Some(state) = app.try_state::<Arc<Mutex<Option<HttpServerHandle>>>>() else {
+        return 0;
+    };
+    let mut guard = state.blocking_lock();
+    let Some(handle) = guard.as_mut() else {
+        // Not running (or stopped by the user)
+        return 0;
+    };
+
+    let cause = if handle.task.is_finished() {
+        exit_cause(&mut handle.task)
+    } else {
+        match ping(handle.port, PING_TIMEOUT) {
+            Ok(()) => return 0,
+            Err(e) if failed_pings + 1 < FAILED_PINGS_BEFORE_RESTART => {
+                log::warn!("{e}");
+                return failed_pings + 1;
+            }
+            Err(e) => format!("Server not responding: {e}"),
+        }
+    };
+
+    log::error!("HTTP server down ({cause}); restarting");
+    let Some(old) = guard.take() else {
+        return 0;
+    };
+    let mut event = WatchdogEvent {
+        cause,
+        restarted: false,
+        url: None,
+        error: None,
+        at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    match restart(app, old) {
+        Ok(handle) => {
+            log::info!("HTTP server restarted: {}", handle.url);
+            event.restarted = true;
+            event.url = Some(handle.url.clone());
+            *guard = Some(handle);
+        }
+        Err(e) => {
+            log::error!("Failed to restart HTTP server: {e}");
+            event.error = Some(e);
+        }
+    }
+    drop(guard);
+
+    if let Err(e) = app.emit_all("http-server:watchdog", &event) {
+        log::error!("Failed to emit http-server:watchdog event: {e}");
+    }
+    0
+}
+
+/// Start the watchdog thread (idle while the server isn't running)
+pub fn start(app: AppHandle) {
+    supervise("http-server-watchdog", move || {
+        let mut failed_pings = 0;
+        loop {
+            std::thread::sleep(CHECK_INTERVAL);
+            failed_pings = check(&app, failed_pings);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn serve_once(response: &'static [u8]) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 512];
+            let _ = stream.read(&mut buf);
+            let _ = stream.write_all(response);
+        });
+        port
+    }
+
+    #[test]
+    fn test_ping_accepts_200() {
+        let port = serve_once(b"HTTP/1.1 200 OK\r\ncontent-length: 11\r\n\r\n{\"ok\":true}");
+        assert!(ping(port, Duration::from_secs(5)).is_ok());
+    }
+
+    #[test]
+    fn test_ping_rejects_errors() {
+        let port = serve_once(b"HTTP/1.1 500 Internal Server Error\r\n\r\n");
+        let err = ping(port, Duration::from_secs(5)).unwrap_err();
+        assert!(err.contains("500"));
+
+        let port = serve_once(b"");
+        assert!(ping(port, Duration::from_secs(5)).is_err());
+    }
+
+    #[test]
+    fn test_ping_times_out_when_wedged() {
+        // Accepts connections (via the backlog) but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(ping(port, Duration::from_millis(200)).is_err());
+        drop(listener);
+    }
+}
@@ -6,6 +6,7 @@ use tauri::AppHandle;
 use tokio::sync::broadcast;
 
 use super::dispatch::dispatch_command;
+use super::routing::{self, EventSubscriptions, Subscriber};
 use super::WsEvent;
 
 #[derive(Deserialize)]
@@ -37,13 +38,15 @@ struct EventMessage {
 
 /// Handle a single WebSocket connection.
 /// Reads invoke requests, dispatches to command handlers, writes responses.
-/// Also forwards broadcast events to the client.
+/// Also forwards the broadcast events the client subscribed to.
 pub async fn handle_ws_connection(
     socket: WebSocket,
     app: AppHandle,
     mut event_rx: broadcast::Receiver<WsEvent>,
 ) {
     let (mut ws_tx, mut ws_rx) = socket.split();
+    let subscriber = Subscriber::Client(routing::next_client_id());
+    let forward_subscriber = subscriber.clone();
 
     // Spawn a task to forward broadcast events to this client
     let (client_tx, mut client_rx) = tokio::sync::mpsc::channel::<String>(256);
@@ -52,6 +55,9 @@ pub async fn handle_ws_connection(
         loop {
             match event_rx.recv().await {
                 Ok(ws_event) => {
+                    if !routing::wants(&forward_subscriber, &ws_event.event, &ws_event.target) {
+                        continue;
+                    }
                     let msg = EventMessage {
                         msg_type: "event".to_string(),
                         event: ws_event.event,
@@ -83,7 +89,13 @@ pub async fn handle_ws_connection(
                         match serde_json::from_str::<InvokeRequest>(&text) {
                             Ok(req) => {
                                 let id = req.id.clone();
-                                match dispatch_command(&app_clone, &req.command, req.args).await {
+                                let result = if req.command == "set_event_subscriptions" {
+                                    // Applies to this connection, so it's handled here
+                                    set_event_subscriptions(&subscriber, req.args)
+                                } else {
+                                    dispatch_command(&app_clone, &req.command, req.args).await
+                                };
+                                match result {
                                     Ok(data) => {
                                         let resp = InvokeResponse {
                                             msg_type: "response".to_string(),
@@ -146,5 +158,17 @@ pub async fn handle_ws_connection(
     }
 
     event_forwarder.abort();
+    routing::unsubscribe(&subscriber);
     log::trace!("WebSocket client disconnected");
 }
+
+/// `set_event_subscriptions` for a WebSocket client
+fn set_event_subscriptions(subscriber: &Subscriber, args: Value) -> Result<Value, String> {
+    let subscriptions: EventSubscriptions = match args.get("subscriptions") {
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid subscriptions: {e}"))?,
+        None => EventSubscriptions::default(),
+    };
+    routing::subscribe(subscriber.clone(), subscriptions);
+    Ok(Value::Null)
+}
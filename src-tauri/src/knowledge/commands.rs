@@ -0,0 +1,86 @@
+use tauri::AppHandle;
+
+use super::{Lesson, LessonDraft, LessonHit};
+use crate::chat::storage::load_metadata;
+use crate::projects::storage::load_projects_data;
+use crate::semantic_search::embeddings::EmbeddingsConfig;
+
+async fn embeddings_config(app: &AppHandle) -> Option<EmbeddingsConfig> {
+    crate::load_preferences(app.clone())
+        .await
+        .ok()
+        .and_then(|prefs| EmbeddingsConfig::from_preferences(&prefs))
+}
+
+/// Saved lessons, newest first, optionally only those with `tag`
+#[tauri::command]
+pub async fn list_lessons(app: AppHandle, tag: Option<String>) -> Vec<Lesson> {
+    let tag = tag.map(|t| super::normalize_tags(&[t])).unwrap_or_default();
+    super::load_lessons(&app)
+        .into_iter()
+        .filter(|l| tag.iter().all(|t| l.tags.contains(t)))
+        .collect()
+}
+
+/// Save a lesson; with a session, its name and project are recorded so
+/// agents can point back at it
+#[tauri::command]
+pub async fn save_lesson(
+    app: AppHandle,
+    title: String,
+    summary: String,
+    tags: Vec<String>,
+    session_id: Option<String>,
+) -> Result<Lesson, String> {
+    let session = match &session_id {
+        Some(id) => {
+            Some(load_metadata(&app, id)?.ok_or_else(|| format!("Session {id} not found"))?)
+        }
+        None => None,
+    };
+    let project_id = session.as_ref().and_then(|s| {
+        load_projects_data(&app)
+            .ok()?
+            .find_worktree(&s.worktree_id)
+            .map(|w| w.project_id.clone())
+    });
+    let config = embeddings_config(&app).await;
+    super::add_lesson(
+        &app,
+        LessonDraft {
+            title,
+            summary,
+            tags,
+        },
+        project_id,
+        session_id,
+        session.map(|s| s.name),
+        config.as_ref(),
+    )
+    .await
+}
+
+/// Delete a lesson
+#[tauri::command]
+pub async fn delete_lesson(app: AppHandle, lesson_id: String) -> Result<(), String> {
+    super::delete_lesson(&app, &lesson_id)
+}
+
+/// Lessons matching `query` (and carrying all `tags`), best first
+#[tauri::command]
+pub async fn search_lessons(
+    app: AppHandle,
+    query: String,
+    tags: Option<Vec<String>>,
+    k: Option<usize>,
+) -> Result<Vec<LessonHit>, String> {
+    let config = embeddings_config(&app).await;
+    super::search(
+        &app,
+        query.trim(),
+        &tags.unwrap_or_default(),
+        k.unwrap_or(super::DEFAULT_RESULTS),
+        config.as_ref(),
+    )
+    .await
+}
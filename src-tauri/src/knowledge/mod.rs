@@ -0,0 +1,429 @@
+//! Knowledge base of lessons from past sessions
+//!
+//! A lesson is a short, tagged write-up of how something was solved ("how we
+//! migrated the settings table"), usually distilled from a successful session
+//! (`distill_session_lesson`) and then edited and saved by the user. Lessons
+//! live in `{app_data}/knowledge/lessons.json` and are searched by embedding
+//! similarity when semantic search is enabled, by keyword otherwise. Agents
+//! query them through the `search_lessons` MCP tool, which points back at
+//! the session each lesson came from.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::runtime::PathProvider;
+use crate::semantic_search::embeddings::{self, EmbeddingsConfig};
+use crate::semantic_search::{normalize, vector_base64};
+
+pub mod commands;
+
+/// Knowledge directory (within app data) and its lessons file
+const KNOWLEDGE_DIR: &str = "knowledge";
+const LESSONS_FILE: &str = "lessons.json";
+
+/// Results returned when the caller doesn't say
+pub const DEFAULT_RESULTS: usize = 5;
+
+/// A lesson as written, before it's saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LessonDraft {
+    pub title: String,
+    /// What the problem was and how it was solved (markdown)
+    pub summary: String,
+    pub tags: Vec<String>,
+}
+
+/// A saved lesson
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lesson {
+    pub id: String,
+    pub title: String,
+    pub summary: String,
+    /// Lowercase, hyphenated, sorted
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Session the lesson was distilled from
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub session_name: Option<String>,
+    /// Unix seconds
+    pub created_at: u64,
+    /// Embedding model of `vector`, if embedded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, with = "vector_base64", skip_serializing_if = "Vec::is_empty")]
+    vector: Vec<f32>,
+}
+
+/// A lesson matching a query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LessonHit {
+    pub lesson: Lesson,
+    /// Cosine similarity, or the share of query words found (keyword search)
+    pub score: f32,
+}
+
+/// Tags as stored: trimmed, lowercase, spaces hyphenated, no duplicates
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .iter()
+        .map(|t| {
+            t.trim()
+                .trim_start_matches('#')
+                .to_lowercase()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .filter(|t| !t.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+fn lessons_path(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.app_data_dir()?.join(KNOWLEDGE_DIR).join(LESSONS_FILE))
+}
+
+/// All lessons, newest first
+pub fn load_lessons(app: &impl PathProvider) -> Vec<Lesson> {
+    let mut lessons: Vec<Lesson> = lessons_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    lessons.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    lessons
+}
+
+fn save_lessons(app: &impl PathProvider, lessons: &[Lesson]) -> Result<(), String> {
+    let path = lessons_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create knowledge directory: {e}"))?;
+    }
+    let content = serde_json::to_string_pretty(lessons)
+        .map_err(|e| format!("Failed to serialize lessons: {e}"))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to save lessons: {e}"))
+}
+
+/// Whether any lesson has been saved
+pub fn has_lessons(app: &impl PathProvider) -> bool {
+    !load_lessons(app).is_empty()
+}
+
+/// Text a lesson is embedded and keyword-matched by
+fn lesson_text(title: &str, summary: &str, tags: &[String]) -> String {
+    format!("{title}\n\n{summary}\n\nTags: {}", tags.join(", "))
+}
+
+/// Save a lesson, embedding it when `config` is given (best effort: a
+/// failed embedding leaves it to keyword search until the next search)
+pub async fn add_lesson(
+    app: &impl PathProvider,
+    draft: LessonDraft,
+    project_id: Option<String>,
+    session_id: Option<String>,
+    session_name: Option<String>,
+    config: Option<&EmbeddingsConfig>,
+) -> Result<Lesson, String> {
+    let title = draft.title.trim().to_string();
+    let summary = draft.summary.trim().to_string();
+    if title.is_empty() || summary.is_empty() {
+        return Err("A lesson needs a title and a summary".to_string());
+    }
+    let tags = normalize_tags(&draft.tags);
+
+    let mut lesson = Lesson {
+        id: Uuid::new_v4().to_string(),
+        title,
+        summary,
+        tags,
+        project_id,
+        session_id,
+        session_name,
+        created_at: crate::runtime::now(),
+        model: None,
+        vector: Vec::new(),
+    };
+    if let Some(config) = config {
+        let text = lesson_text(&lesson.title, &lesson.summary, &lesson.tags);
+        match embeddings::embed(config, &[text]).await {
+            Ok(mut vectors) => {
+                lesson.vector = vectors.pop().map(normalize).unwrap_or_default();
+                lesson.model = Some(config.model.clone());
+            }
+            Err(e) => log::warn!("Failed to embed lesson: {e}"),
+        }
+    }
+
+    let mut lessons = load_lessons(app);
+    lessons.push(lesson.clone());
+    save_lessons(app, &lessons)?;
+    Ok(lesson)
+}
+
+/// Remove a lesson
+pub fn delete_lesson(app: &impl PathProvider, id: &str) -> Result<(), String> {
+    let mut lessons = load_lessons(app);
+    let before = lessons.len();
+    lessons.retain(|l| l.id != id);
+    if lessons.len() == before {
+        return Err(format!("Lesson not found: {id}"));
+    }
+    save_lessons(app, &lessons)
+}
+
+/// Share of the query's words (3+ characters) that appear in the lesson
+fn keyword_score(lesson: &Lesson, query: &str) -> f32 {
+    let text = lesson_text(&lesson.title, &lesson.summary, &lesson.tags).to_lowercase();
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+    words.iter().filter(|w| text.contains(w.as_str())).count() as f32 / words.len() as f32
+}
+
+/// Embedding similarity of every lesson to `query`; lessons without a
+/// vector for the configured model are embedded for this search only
+async fn embedding_scores(
+    lessons: &[Lesson],
+    query: &str,
+    config: &EmbeddingsConfig,
+) -> Result<Vec<f32>, String> {
+    let stale: Vec<usize> = (0..lessons.len())
+        .filter(|&i| {
+            lessons[i].vector.is_empty() || lessons[i].model.as_deref() != Some(&config.model)
+        })
+        .collect();
+    let mut inputs = vec![query.to_string()];
+    inputs.extend(
+        stale
+            .iter()
+            .map(|&i| lesson_text(&lessons[i].title, &lessons[i].summary, &lessons[i].tags)),
+    );
+    let mut vectors = embeddings::embed(config, &inputs)
+        .await?
+        .into_iter()
+        .map(normalize);
+    let query_vector = vectors.next().ok_or("Embeddings API returned no vector")?;
+    let mut fresh: Vec<(usize, Vec<f32>)> = stale.into_iter().zip(vectors).collect();
+
+    Ok(lessons
+        .iter()
+        .enumerate()
+        .map(|(i, lesson)| {
+            let vector = match fresh.iter().position(|(f, _)| *f == i) {
+                Some(pos) => fresh.swap_remove(pos).1,
+                None => lesson.vector.clone(),
+            };
+            vector.iter().zip(&query_vector).map(|(a, b)| a * b).sum()
+        })
+        .collect())
+}
+
+/// The `k` lessons carrying every tag in `tags` that best match `query`
+pub async fn search(
+    app: &impl PathProvider,
+    query: &str,
+    tags: &[String],
+    k: usize,
+    config: Option<&EmbeddingsConfig>,
+) -> Result<Vec<LessonHit>, String> {
+    let tags = normalize_tags(tags);
+    let lessons: Vec<Lesson> = load_lessons(app)
+        .into_iter()
+        .filter(|l| tags.iter().all(|t| l.tags.contains(t)))
+        .collect();
+    if lessons.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let scores = match config {
+        Some(config) => match embedding_scores(&lessons, query, config).await {
+            Ok(scores) => Some(scores),
+            Err(e) => {
+                log::warn!("Falling back to keyword search for lessons: {e}");
+                None
+            }
+        },
+        None => None,
+    };
+    let mut hits: Vec<LessonHit> = match scores {
+        Some(scores) => lessons
+            .into_iter()
+            .zip(scores)
+            .map(|(lesson, score)| LessonHit { lesson, score })
+            .collect(),
+        None => lessons
+            .into_iter()
+            .map(|lesson| LessonHit {
+                score: keyword_score(&lesson, query),
+                lesson,
+            })
+            .filter(|hit| hit.score > 0.0)
+            .collect(),
+    };
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(k);
+    Ok(hits)
+}
+
+/// Hits as plain text, for agents
+pub fn format_hits(hits: &[LessonHit]) -> String {
+    if hits.is_empty() {
+        return "No related lessons.".to_string();
+    }
+    hits.iter()
+        .map(|hit| {
+            let lesson = &hit.lesson;
+            let source = match (&lesson.session_name, &lesson.session_id) {
+                (Some(name), Some(id)) => format!("Solved in session \"{name}\" ({id})"),
+                (None, Some(id)) => format!("Solved in session {id}"),
+                _ => "Added manually".to_string(),
+            };
+            let tags = if lesson.tags.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", lesson.tags.join(", "))
+            };
+            format!(
+                "## {}{tags}\n{source}, score {:.2}\n\n{}\n",
+                lesson.title, hit.score, lesson.summary
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture_server::{FixtureResponse, FixtureServer};
+    use crate::test_support::runtime::TempPaths;
+
+    fn draft(title: &str, summary: &str, tags: &[&str]) -> LessonDraft {
+        LessonDraft {
+            title: title.to_string(),
+            summary: summary.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags: Vec<String> = ["  Database Migration", "#sqlite", "sqlite", ""]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        assert_eq!(normalize_tags(&tags), ["database-migration", "sqlite"]);
+    }
+
+    #[test]
+    fn test_keyword_search_and_delete() {
+        let paths = TempPaths::new();
+        tauri::async_runtime::block_on(async {
+            let migration = add_lesson(
+                &paths,
+                draft(
+                    "Settings table migration",
+                    "Copy rows into the new table, then swap names.",
+                    &["sqlite", "migration"],
+                ),
+                None,
+                Some("s-4512".to_string()),
+                Some("Migrate settings".to_string()),
+                None,
+            )
+            .await
+            .unwrap();
+            add_lesson(
+                &paths,
+                draft(
+                    "Flaky websocket test",
+                    "Wait for the open event.",
+                    &["tests"],
+                ),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            assert!(
+                add_lesson(&paths, draft(" ", "x", &[]), None, None, None, None)
+                    .await
+                    .is_err()
+            );
+
+            let hits = search(&paths, "users table migration", &[], 5, None)
+                .await
+                .unwrap();
+            assert_eq!(hits.len(), 1);
+            assert_eq!(hits[0].lesson.id, migration.id);
+            assert!(format_hits(&hits).contains("Solved in session \"Migrate settings\" (s-4512)"));
+
+            // Tags narrow the search
+            let hits = search(&paths, "table", &["tests".to_string()], 5, None)
+                .await
+                .unwrap();
+            assert!(hits.is_empty());
+
+            delete_lesson(&paths, &migration.id).unwrap();
+            assert_eq!(load_lessons(&paths).len(), 1);
+            assert!(delete_lesson(&paths, &migration.id).is_err());
+        });
+    }
+
+    #[test]
+    fn test_embedding_search() {
+        let server = FixtureServer::start(vec![(
+            "/v1/embeddings".to_string(),
+            FixtureResponse::json(r#"{"data": [{"index": 0, "embedding": [1.0, 0.0]}]}"#),
+        )]);
+        let config = EmbeddingsConfig {
+            api_url: server.url("/v1/embeddings"),
+            model: "model".to_string(),
+            api_key: None,
+        };
+        let paths = TempPaths::new();
+        let lesson = |title: &str, vector: Vec<f32>| Lesson {
+            id: title.to_string(),
+            title: title.to_string(),
+            summary: "summary".to_string(),
+            tags: Vec::new(),
+            project_id: None,
+            session_id: None,
+            session_name: None,
+            created_at: 0,
+            model: Some("model".to_string()),
+            vector,
+        };
+        save_lessons(
+            &paths,
+            &[
+                lesson("near", vec![0.8, 0.6]),
+                lesson("far", vec![0.0, 1.0]),
+            ],
+        )
+        .unwrap();
+
+        let hits =
+            tauri::async_runtime::block_on(search(&paths, "query", &[], 5, Some(&config))).unwrap();
+        let titles: Vec<&str> = hits.iter().map(|h| h.lesson.title.as_str()).collect();
+        assert_eq!(titles, ["near", "far"]);
+        assert!((hits[0].score - 0.8).abs() < 1e-6);
+        assert_eq!(server.requests().len(), 1);
+    }
+}
@@ -5,17 +5,51 @@ use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter, Manager};
 
+use crate::runtime::PathProvider;
+
 #[cfg(target_os = "macos")]
 use tauri::menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder};
 
+mod agent_protocol;
+mod announcements;
+mod audit;
 mod background_tasks;
+mod backups;
+mod bots;
 mod chat;
 mod claude_cli;
+mod cleanup;
+mod cli_capabilities;
+mod crash_reports;
+mod email;
+mod emergency;
+mod focus;
 mod gh_cli;
+mod guest;
+mod http_client;
 pub mod http_server;
+mod knowledge;
+mod migrations;
+mod mock;
 mod platform;
+mod power;
+mod profiles;
 mod projects;
+mod prompts;
+mod providers;
+mod python_env;
+mod quota;
+mod repo_map;
+mod reports;
+mod rules;
+mod runtime;
+mod self_test;
+mod semantic_search;
+mod speech;
 mod terminal;
+#[cfg(test)]
+mod test_support;
+mod tool_install;
 
 // Validation functions
 fn validate_filename(filename: &str) -> Result<(), String> {
@@ -107,6 +141,8 @@ pub struct AppPreferences {
     pub keybindings: std::collections::HashMap<String, String>, // User-configurable keyboard shortcuts
     #[serde(default = "default_archive_retention_days")]
     pub archive_retention_days: u32, // Days to keep archived items before auto-cleanup (0 = disabled)
+    #[serde(default = "default_scratch_retention_days")]
+    pub scratch_retention_days: u32, // Days to keep idle session scratch directories (0 = disabled)
     #[serde(default = "default_session_grouping_enabled")]
     pub session_grouping_enabled: bool, // Group session tabs by status when >3 sessions
     #[serde(default = "default_canvas_enabled")]
@@ -169,12 +205,126 @@ pub struct AppPreferences {
     pub custom_cli_profiles: Vec<CustomCliProfile>, // Custom CLI settings profiles (e.g., OpenRouter, MiniMax)
     #[serde(default)]
     pub default_provider: Option<String>, // Default provider profile name (None = Anthropic direct)
+    #[serde(default = "default_speech_provider")]
+    pub speech_provider: String, // Speech-to-text engine: local (whisper.cpp), api
+    #[serde(default = "default_speech_local_model")]
+    pub speech_local_model: String, // whisper.cpp model ID for local transcription
+    #[serde(default = "default_speech_api_url")]
+    pub speech_api_url: String, // OpenAI-compatible transcription endpoint
+    #[serde(default = "default_speech_api_model")]
+    pub speech_api_model: String, // Model name sent to the transcription API
+    #[serde(default)]
+    pub speech_api_key: Option<String>, // API key for the transcription endpoint
+    #[serde(default)]
+    pub speak_waiting_summary: bool, // Speak a one-sentence summary when a session is waiting for input
+    #[serde(default)]
+    pub speak_review_summary: bool, // Speak a one-sentence summary when a session finishes
+    #[serde(default)]
+    pub speech_voice: Option<String>, // OS voice for spoken summaries (None = system default)
+    #[serde(default = "default_prevent_sleep_during_runs")]
+    pub prevent_sleep_during_runs: bool, // Hold a power assertion while sessions, installs or downloads run
+    #[serde(default = "default_defer_jobs_battery_threshold")]
+    pub defer_jobs_battery_threshold: u8, // Defer background jobs on battery at or below this % (0 = never)
+    #[serde(default = "default_defer_jobs_on_metered")]
+    pub defer_jobs_on_metered: bool, // Defer background jobs on metered connections
+    #[serde(default)]
+    pub background_job_overrides: BackgroundJobOverrides, // Jobs that run regardless of battery/metered state
+    #[serde(default)]
+    pub crash_report_endpoint: Option<String>, // Opt-in URL crash reports are uploaded to (None = open a GitHub issue)
+    #[serde(default)]
+    pub extra_ca_cert_path: Option<String>, // PEM file with extra CA certificates to trust (TLS-intercepting proxies)
+    #[serde(default)]
+    pub use_system_cert_store: bool, // Add the OS trust store's certificates to all HTTP clients
+    #[serde(default)]
+    pub http_proxy: Option<String>, // Proxy URL for all HTTP clients (None = HTTPS_PROXY / HTTP_PROXY from the environment)
+    #[serde(default)]
+    pub no_proxy: Option<String>, // Comma-separated hosts that bypass the proxy (None = NO_PROXY from the environment)
+    #[serde(default)]
+    pub keep_mark_of_the_web: bool, // Windows: leave the Mark of the Web on downloaded CLIs (SmartScreen checks them)
+    #[serde(default)]
+    pub claude_monthly_budget_usd: Option<f64>, // Monthly Claude spend budget shown in the provider overview (None = no budget)
+    #[serde(default)]
+    pub backup_folder: Option<String>, // Folder for automatic backups (None = backups/ in app data)
+    #[serde(default = "default_backup_interval_hours")]
+    pub backup_interval_hours: u32, // Hours between automatic backups (0 = disabled)
+    #[serde(default = "default_backup_keep_count")]
+    pub backup_keep_count: u32, // Number of backups kept before the oldest are removed
+    #[serde(default)]
+    pub shared_library_repo: Option<String>, // Git repository with the team's shared prompts and profiles (None = local only)
+    #[serde(default = "default_shared_library_sync_hours")]
+    pub shared_library_sync_hours: u32, // Hours between shared library pulls (0 = manual only)
+    #[serde(default)]
+    pub community_index_url: Option<String>, // Community index of prompts and profiles to browse (None = off)
+    #[serde(default)]
+    pub community_index_public_key: Option<String>, // Base64 Ed25519 key community items must be signed with
+    #[serde(default)]
+    pub warm_process_pool: bool, // Keep an idle Claude CLI ready for new sessions
+    #[serde(default)]
+    pub download_speed_limit_kib: Option<u64>, // Combined KiB/s cap for parallel tool downloads (None = unlimited)
+    #[serde(default)]
+    pub auto_attach_command_runs: u32, // Attach output of this many recent project command runs to new sessions (0 = off)
+    #[serde(default = "default_command_runs_token_budget")]
+    pub command_runs_token_budget: u32, // Approximate tokens of attached command output
+    #[serde(default = "default_repo_map_token_budget")]
+    pub repo_map_token_budget: u32, // Approximate tokens of the repository map attached to new sessions in large repos (0 = off)
+    #[serde(default)]
+    pub semantic_search_enabled: bool, // Index project files with embeddings for semantic search (UI and agent MCP tool)
+    #[serde(default = "default_embeddings_api_url")]
+    pub embeddings_api_url: String, // OpenAI-compatible embeddings endpoint (local Ollama by default)
+    #[serde(default = "default_embeddings_api_model")]
+    pub embeddings_api_model: String, // Model name sent to the embeddings API
+    #[serde(default)]
+    pub embeddings_api_key: Option<String>, // API key for the embeddings endpoint
+    #[serde(default)]
+    pub maintenance_window_start: Option<String>, // Local "HH:MM" heavy background jobs may start from (None = no window)
+    #[serde(default)]
+    pub maintenance_window_end: Option<String>, // Local "HH:MM" the maintenance window closes
+    #[serde(default = "default_report_locale")]
+    pub report_locale: String, // Locale for numbers and dates in exported reports, e.g. "de-DE"
+    #[serde(default)]
+    pub csv_delimiter: Option<String>, // CSV field delimiter ("," ";" or "tab"; None = locale default)
+    #[serde(default)]
+    pub announcement_verbosity: AnnouncementVerbosity, // Screen-reader announcement detail per category
+    #[serde(default = "default_respect_focus_mode")]
+    pub respect_focus_mode: bool, // Hold notifications and spoken summaries while OS Focus / do-not-disturb is on
+    #[serde(default)]
+    pub focus_overrides: FocusOverrides, // Notification categories delivered even during Focus
+    #[serde(default)]
+    pub calendar_ics_path: Option<String>, // Local .ics file read for busy/free so scheduled runs can avoid meetings
+    #[serde(default)]
+    pub vulnerability_scan_before_sessions: bool, // Scan dependencies for known vulnerabilities and attach the findings to new sessions
+    #[serde(default)]
+    pub launch_defaults: std::collections::BTreeMap<String, chat::launch::LaunchDefaults>, // Extra CLI args/env per backend for every run (see chat::launch)
+    #[serde(default)]
+    pub codex_update_channel: chat::codex_updates::UpdateChannel, // Releases the Codex CLI update check reports: stable or prerelease
+    #[serde(default = "default_codex_update_check_hours")]
+    pub codex_update_check_hours: u32, // Hours between Codex CLI update checks (0 = disabled)
+    #[serde(default)]
+    pub codex_release_mirrors: Vec<String>, // Base URLs standing in for https://api.github.com, tried in order before GitHub itself
+    #[serde(default)]
+    pub smtp_host: Option<String>, // SMTP server for outgoing email (None = email disabled)
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16, // SMTP server port
+    #[serde(default)]
+    pub smtp_security: email::SmtpSecurity, // STARTTLS, TLS from the start, or none (local relays)
+    #[serde(default)]
+    pub smtp_username: Option<String>, // SMTP login (the password is stored separately, see email)
+    #[serde(default)]
+    pub smtp_from: Option<String>, // Sender address of outgoing email
+    #[serde(default)]
+    pub email_digest: reports::digest::DigestFrequency, // Mail a digest of runs: off, daily or weekly
+    #[serde(default)]
+    pub email_digest_to: Vec<String>, // Recipients of the run digest
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CustomCliProfile {
     pub name: String,
     pub settings_json: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_expires_at: Option<u64>, // Unix seconds the provider stops accepting the key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotate_by: Option<u64>, // Unix seconds the key should be rotated by
 }
 
 fn default_auto_branch_naming() -> bool {
@@ -268,6 +418,10 @@ fn default_archive_retention_days() -> u32 {
     30 // Keep archived items for 30 days by default
 }
 
+fn default_scratch_retention_days() -> u32 {
+    7 // Remove scratch directories idle for a week
+}
+
 fn default_syntax_theme_dark() -> String {
     "vitesse-black".to_string()
 }
@@ -300,6 +454,78 @@ fn default_chrome_enabled() -> bool {
     true // Enabled by default
 }
 
+fn default_prevent_sleep_during_runs() -> bool {
+    true // Enabled by default: overnight runs shouldn't be suspended
+}
+
+fn default_respect_focus_mode() -> bool {
+    true
+}
+
+fn default_backup_interval_hours() -> u32 {
+    24 // Back up once a day
+}
+
+fn default_backup_keep_count() -> u32 {
+    7 // A week of daily backups
+}
+
+fn default_command_runs_token_budget() -> u32 {
+    4000
+}
+
+fn default_repo_map_token_budget() -> u32 {
+    2000
+}
+
+fn default_embeddings_api_url() -> String {
+    "http://localhost:11434/v1/embeddings".to_string()
+}
+
+fn default_embeddings_api_model() -> String {
+    "nomic-embed-text".to_string()
+}
+
+fn default_shared_library_sync_hours() -> u32 {
+    6 // Pick up team prompt changes a few times a day
+}
+
+fn default_codex_update_check_hours() -> u32 {
+    24 // Once a day
+}
+
+fn default_smtp_port() -> u16 {
+    587 // Submission port (STARTTLS)
+}
+
+fn default_report_locale() -> String {
+    "en-US".to_string()
+}
+
+fn default_defer_jobs_battery_threshold() -> u8 {
+    20 // Defer background jobs when battery is at or below 20%
+}
+
+fn default_defer_jobs_on_metered() -> bool {
+    true
+}
+
+fn default_speech_provider() -> String {
+    "local".to_string()
+}
+
+fn default_speech_local_model() -> String {
+    "base.en".to_string()
+}
+
+fn default_speech_api_url() -> String {
+    "https://api.openai.com/v1/audio/transcriptions".to_string()
+}
+
+fn default_speech_api_model() -> String {
+    "whisper-1".to_string()
+}
+
 fn default_zoom_level() -> u32 {
     100 // 100% = no zoom
 }
@@ -578,6 +804,52 @@ Instruct each sub-agent to briefly outline its approach before implementing, so
         .to_string()
 }
 
+/// Background jobs that run even on low battery or metered connections
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackgroundJobOverrides {
+    #[serde(default)]
+    pub update_check: bool,
+    #[serde(default)]
+    pub report_generation: bool,
+    #[serde(default)]
+    pub batch_tasks: bool,
+}
+
+/// How much a screen-reader announcement says
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementLevel {
+    Off,
+    #[default]
+    Brief,
+    /// Brief text plus a summary (e.g. the agent's question or the error)
+    Detailed,
+}
+
+/// Screen-reader announcement verbosity per category
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnnouncementVerbosity {
+    #[serde(default)]
+    pub session_finished: AnnouncementLevel,
+    #[serde(default)]
+    pub approval_needed: AnnouncementLevel,
+    #[serde(default)]
+    pub install_failed: AnnouncementLevel,
+}
+
+/// Notification categories that break through OS Focus / do-not-disturb
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FocusOverrides {
+    #[serde(default)]
+    pub session_finished: bool,
+    #[serde(default)]
+    pub input_needed: bool,
+    #[serde(default)]
+    pub event_rules: bool,
+    #[serde(default)]
+    pub general: bool,
+}
+
 /// Per-prompt model overrides for magic prompts
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MagicPromptModels {
@@ -690,6 +962,7 @@ impl Default for AppPreferences {
             remote_poll_interval: default_remote_poll_interval(),
             keybindings: default_keybindings(),
             archive_retention_days: default_archive_retention_days(),
+            scratch_retention_days: default_scratch_retention_days(),
             session_grouping_enabled: default_session_grouping_enabled(),
             canvas_enabled: default_canvas_enabled(),
             canvas_only_mode: default_canvas_only_mode(),
@@ -722,6 +995,61 @@ impl Default for AppPreferences {
             zoom_level: default_zoom_level(),
             custom_cli_profiles: Vec::new(),
             default_provider: None,
+            speech_provider: default_speech_provider(),
+            speech_local_model: default_speech_local_model(),
+            speech_api_url: default_speech_api_url(),
+            speech_api_model: default_speech_api_model(),
+            speech_api_key: None,
+            speak_waiting_summary: false,
+            speak_review_summary: false,
+            speech_voice: None,
+            prevent_sleep_during_runs: default_prevent_sleep_during_runs(),
+            defer_jobs_battery_threshold: default_defer_jobs_battery_threshold(),
+            defer_jobs_on_metered: default_defer_jobs_on_metered(),
+            background_job_overrides: BackgroundJobOverrides::default(),
+            crash_report_endpoint: None,
+            extra_ca_cert_path: None,
+            use_system_cert_store: false,
+            http_proxy: None,
+            no_proxy: None,
+            keep_mark_of_the_web: false,
+            claude_monthly_budget_usd: None,
+            backup_folder: None,
+            backup_interval_hours: default_backup_interval_hours(),
+            backup_keep_count: default_backup_keep_count(),
+            shared_library_repo: None,
+            shared_library_sync_hours: default_shared_library_sync_hours(),
+            community_index_url: None,
+            community_index_public_key: None,
+            warm_process_pool: false,
+            download_speed_limit_kib: None,
+            auto_attach_command_runs: 0,
+            command_runs_token_budget: default_command_runs_token_budget(),
+            repo_map_token_budget: default_repo_map_token_budget(),
+            semantic_search_enabled: false,
+            embeddings_api_url: default_embeddings_api_url(),
+            embeddings_api_model: default_embeddings_api_model(),
+            embeddings_api_key: None,
+            maintenance_window_start: None,
+            maintenance_window_end: None,
+            report_locale: default_report_locale(),
+            csv_delimiter: None,
+            announcement_verbosity: AnnouncementVerbosity::default(),
+            respect_focus_mode: default_respect_focus_mode(),
+            focus_overrides: FocusOverrides::default(),
+            calendar_ics_path: None,
+            vulnerability_scan_before_sessions: false,
+            launch_defaults: std::collections::BTreeMap::new(),
+            codex_update_channel: chat::codex_updates::UpdateChannel::default(),
+            codex_update_check_hours: default_codex_update_check_hours(),
+            codex_release_mirrors: Vec::new(),
+            smtp_host: None,
+            smtp_port: default_smtp_port(),
+            smtp_security: email::SmtpSecurity::default(),
+            smtp_username: None,
+            smtp_from: None,
+            email_digest: reports::digest::DigestFrequency::default(),
+            email_digest_to: Vec::new(),
         }
     }
 }
@@ -832,10 +1160,7 @@ impl Default for UIState {
 }
 
 fn get_preferences_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
 
     // Ensure the directory exists
     std::fs::create_dir_all(&app_data_dir)
@@ -844,7 +1169,8 @@ fn get_preferences_path(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(app_data_dir.join("preferences.json"))
 }
 
-#[tauri::command]
+/// Preferences from disk (the frontend gets them through
+/// `guest::commands::load_preferences`, which redacts them in guest mode)
 async fn load_preferences(app: AppHandle) -> Result<AppPreferences, String> {
     log::trace!("Loading preferences from disk");
     let prefs_path = get_preferences_path(&app)?;
@@ -876,6 +1202,9 @@ async fn load_preferences(app: AppHandle) -> Result<AppPreferences, String> {
 async fn save_preferences(app: AppHandle, preferences: AppPreferences) -> Result<(), String> {
     // Validate theme value
     validate_theme(&preferences.theme)?;
+    for defaults in preferences.launch_defaults.values() {
+        defaults.validate()?;
+    }
 
     log::trace!("Saving preferences to disk: {preferences:?}");
     let prefs_path = get_preferences_path(&app)?;
@@ -901,15 +1230,26 @@ async fn save_preferences(app: AppHandle, preferences: AppPreferences) -> Result
         format!("Failed to finalize preferences file: {e}")
     })?;
 
+    if !preferences.prevent_sleep_during_runs {
+        power::release_assertion();
+    }
+
+    if !preferences.warm_process_pool {
+        chat::warm_pool::clear(&app);
+    }
+
+    http_client::configure(http_client::TrustSettings::from_preferences(&preferences));
+    http_client::proxy::configure(http_client::proxy::ProxySettings::from_preferences(
+        &preferences,
+    ));
+    platform::motw::configure(preferences.keep_mark_of_the_web);
+
     log::trace!("Successfully saved preferences to {prefs_path:?}");
     Ok(())
 }
 
 fn get_ui_state_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
 
     // Ensure the directory exists
     std::fs::create_dir_all(&app_data_dir)
@@ -972,11 +1312,23 @@ async fn save_ui_state(app: AppHandle, ui_state: UIState) -> Result<(), String>
     Ok(())
 }
 
+/// Send a native notification, held for the Focus digest while the OS is in
+/// do-not-disturb unless `category` breaks through
 #[tauri::command]
 async fn send_native_notification(
     app: AppHandle,
     title: String,
     body: Option<String>,
+    category: Option<focus::NotificationCategory>,
+) -> Result<(), String> {
+    focus::notify(&app, category.unwrap_or_default(), title, body).await
+}
+
+/// Show a native notification now, regardless of Focus
+fn show_native_notification(
+    app: &AppHandle,
+    title: String,
+    body: Option<String>,
 ) -> Result<(), String> {
     log::trace!("Sending native notification: {title}");
 
@@ -1011,10 +1363,7 @@ async fn send_native_notification(
 
 // Recovery functions - simple pattern for saving JSON data to disk
 fn get_recovery_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
 
     let recovery_dir = app_data_dir.join("recovery");
 
@@ -1444,6 +1793,13 @@ fn fix_macos_path() {
     }
 }
 
+/// Serve the semantic search MCP server on stdio instead of starting the
+/// app, if the command line asks for it; returns the exit code
+pub fn run_mcp_server(args: &[String]) -> Option<i32> {
+    (args.first().map(String::as_str) == Some(semantic_search::mcp::SUBCOMMAND))
+        .then(|| semantic_search::mcp::run(&args[1..]))
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Parse CLI arguments for headless mode
@@ -1542,6 +1898,74 @@ pub fn run() {
                 app.package_info().name
             );
 
+            // Pick this launch's profile before anything reads app data
+            profiles::init(app.handle());
+
+            // Stay read-only if the app was closed in guest mode
+            guest::init(app.handle());
+
+            // Keep auto-approval off if an emergency stop wasn't cleared
+            emergency::init(app.handle());
+
+            // Record panics, native crashes and CLI crashes locally
+            crash_reports::install(app.handle());
+
+            // Bring the session store up to the current schema version
+            migrations::init(app.handle());
+
+            // Scheduled backups of the session store and settings
+            backups::start_scheduler(app.handle().clone());
+
+            // Compaction, index refreshes and backups in the maintenance window
+            background_tasks::maintenance::start_scheduler(app.handle().clone());
+
+            // Scheduled pulls of the shared prompt library
+            prompts::shared::start_scheduler(app.handle().clone());
+
+            // Reminders ahead of provider key expiry and rotation dates
+            providers::keys::start_reminder_scheduler(app.handle().clone());
+
+            // Periodic check for newer Codex CLI releases
+            chat::codex_updates::start_scheduler(app.handle().clone());
+
+            // Daily or weekly email digest of runs
+            reports::digest::start_scheduler(app.handle().clone());
+
+            // File and branch triggers of watch rules
+            rules::watch::start_watcher(app.handle().clone());
+
+            // Commands from Slack/Discord bot channels
+            bots::start_poller(app.handle().clone());
+
+            // Idle Claude CLIs for new sessions (warm_process_pool)
+            chat::warm_pool::cleanup_leftovers(app.handle());
+            chat::warm_pool::start_refresher(app.handle().clone());
+
+            // Mock backend for frontend development (mock-backend builds only)
+            mock::init_from_env(app.handle());
+
+            // Record rate-limit / quota warnings from CLI output
+            quota::init(app.handle());
+
+            // Keep samples of CLI output the agent adapters don't recognize
+            agent_protocol::drift::init(app.handle());
+
+            // Trust extra CA certificates and set up the proxy before any HTTP
+            // clients are built
+            let app_handle_tls = app.handle().clone();
+            tauri::async_runtime::block_on(async move {
+                if let Ok(prefs) = load_preferences(app_handle_tls).await {
+                    http_client::configure(http_client::TrustSettings::from_preferences(&prefs));
+                    http_client::proxy::configure(
+                        http_client::proxy::ProxySettings::from_preferences(&prefs),
+                    );
+                    platform::motw::configure(prefs.keep_mark_of_the_web);
+                }
+            });
+
+            // Load the GitHub API token attached to release lookups
+            http_client::github::init(app.handle());
+
             // In headless mode, close the window immediately
             if headless {
                 log::info!("Running in headless mode");
@@ -1660,6 +2084,43 @@ pub fn run() {
             app.manage(task_manager);
             log::trace!("Background task manager initialized");
 
+            // Watch for sleep/wake and network changes to recover interrupted work
+            power::monitor::start(app.handle().clone());
+
+            // Deliver notifications held during OS Focus once it ends
+            focus::start_watcher(app.handle().clone());
+
+            // Remove stale session scratch directories in the background
+            let app_handle_scratch = app.handle().clone();
+            background_tasks::supervisor::spawn_task("scratch-cleanup", async move {
+                let retention_days = match load_preferences(app_handle_scratch.clone()).await {
+                    Ok(prefs) => prefs.scratch_retention_days,
+                    Err(_) => default_scratch_retention_days(),
+                };
+                match chat::scratch::cleanup_stale_scratch_dirs(&app_handle_scratch, retention_days)
+                {
+                    Ok(removed) if removed > 0 => {
+                        log::info!("Removed {removed} stale scratch directories")
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Scratch cleanup failed: {e}"),
+                }
+            });
+
+            // Remove leftovers of failed installs and deleted sessions
+            let app_handle_cleanup = app.handle().clone();
+            background_tasks::supervisor::spawn_task("stale-file-cleanup", async move {
+                match cleanup::clean(&app_handle_cleanup, runtime::now()) {
+                    Ok(report) if !report.removed.is_empty() => log::info!(
+                        "Removed {} stale item(s), reclaimed {} bytes",
+                        report.removed.len(),
+                        report.reclaimed_bytes
+                    ),
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Stale file cleanup failed: {e}"),
+                }
+            });
+
             // Initialize HTTP server infrastructure
             let (broadcaster, _) = http_server::WsBroadcaster::new();
             app.manage(broadcaster);
@@ -1668,9 +2129,12 @@ pub fn run() {
             )));
             log::trace!("HTTP server infrastructure initialized");
 
+            // Restart the HTTP server if its accept loop dies or stops responding
+            http_server::watchdog::start(app.handle().clone());
+
             // Start HTTP server (always in headless mode, or if auto-start configured)
             let app_handle_http = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
+            background_tasks::supervisor::spawn_task("http-server-autostart", async move {
                 match load_preferences(app_handle_http.clone()).await {
                     Ok(prefs) if headless || prefs.http_server_auto_start => {
                         let port = prefs.http_server_port;
@@ -1722,9 +2186,9 @@ pub fn run() {
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(guest::guarded(tauri::generate_handler![
             greet,
-            load_preferences,
+            guest::commands::load_preferences,
             save_preferences,
             load_ui_state,
             save_ui_state,
@@ -1775,6 +2239,12 @@ pub fn run() {
             projects::list_worktree_files,
             projects::get_project_branches,
             projects::update_project_settings,
+            projects::config::get_project_config,
+            projects::config::get_allowed_tools_approval,
+            projects::config::approve_allowed_tools,
+            projects::config::generate_project_config,
+            projects::subprojects::list_subprojects,
+            projects::toolchain::get_project_toolchain,
             projects::get_pr_prompt,
             projects::get_review_prompt,
             projects::save_worktree_pr,
@@ -1835,11 +2305,17 @@ pub fn run() {
             terminal::get_active_terminals,
             terminal::has_active_terminal,
             terminal::get_run_script,
+            terminal::run_project_command,
+            terminal::list_project_command_runs,
+            terminal::get_project_command_output,
             terminal::kill_all_terminals,
             // Chat commands - Session management
             chat::get_sessions,
             chat::list_all_sessions,
+            chat::list_session_history,
             chat::get_session,
+            chat::get_session_messages_page,
+            chat::get_tool_output,
             chat::create_session,
             chat::rename_session,
             chat::update_session_state,
@@ -1856,11 +2332,46 @@ pub fn run() {
             chat::send_chat_message,
             chat::get_mcp_servers,
             chat::check_mcp_health,
+            chat::get_effective_launch_command,
+            chat::codex_updates::check_codex_update,
+            http_server::routing::set_event_subscriptions,
             chat::clear_session_history,
             chat::set_session_model,
+            chat::set_session_subproject,
             chat::set_session_thinking_level,
             chat::cancel_chat_message,
             chat::has_running_sessions,
+            chat::get_session_scratch_usage,
+            chat::get_session_timeline,
+            chat::get_session_time,
+            chat::replay_session,
+            chat::replay_step,
+            chat::stop_replay,
+            chat::export_session_jsonl,
+            chat::export_session_cast,
+            chat::export_session_for_web,
+            chat::import_session_jsonl,
+            chat::run_one_shot,
+            chat::get_recent_one_shots,
+            chat::pin_one_shot,
+            chat::compact_storage,
+            chat::install_aider,
+            agent_protocol::commands::list_custom_backends,
+            agent_protocol::commands::save_custom_backend,
+            agent_protocol::commands::delete_custom_backend,
+            agent_protocol::commands::list_schema_drift,
+            cli_capabilities::commands::get_cli_capabilities,
+            repo_map::commands::get_repo_map,
+            semantic_search::commands::semantic_search,
+            semantic_search::commands::rebuild_semantic_index,
+            chat::find_duplicate_sessions,
+            chat::bulk_delete_sessions,
+            chat::bulk_export_sessions,
+            chat::bulk_cancel_tasks,
+            chat::provide_session_input,
+            chat::rerun_session,
+            chat::compare_sessions,
+            chat::get_pending_session_input,
             chat::save_cancelled_message,
             chat::mark_plan_approved,
             // Chat commands - Image handling
@@ -1884,28 +2395,167 @@ pub fn run() {
             chat::delete_context_file,
             chat::rename_saved_context,
             chat::generate_context_from_session,
+            chat::check_context_budget,
+            chat::summarize_context_attachments,
             // Chat commands - Session digest (context recall)
             chat::generate_session_digest,
             chat::update_session_digest,
+            chat::distill_session_lesson,
+            chat::generate_session_doc,
+            knowledge::commands::list_lessons,
+            knowledge::commands::save_lesson,
+            knowledge::commands::delete_lesson,
+            knowledge::commands::search_lessons,
+            // Event rules
+            rules::commands::list_event_rules,
+            rules::commands::create_event_rule,
+            rules::commands::update_event_rule,
+            rules::commands::delete_event_rule,
+            rules::commands::get_rule_executions,
+            rules::commands::list_watch_rules,
+            rules::commands::create_watch_rule,
+            rules::commands::update_watch_rule,
+            rules::commands::delete_watch_rule,
+            // Slack/Discord bots
+            bots::commands::list_bot_connections,
+            bots::commands::create_bot_connection,
+            bots::commands::update_bot_connection,
+            bots::commands::delete_bot_connection,
+            bots::commands::test_bot_connection,
+            // Local user profiles
+            profiles::commands::list_profiles,
+            profiles::commands::create_profile,
+            profiles::commands::rename_profile,
+            profiles::commands::delete_profile,
+            profiles::commands::switch_profile,
+            // Read-only guest mode
+            guest::commands::get_guest_mode,
+            guest::commands::enter_guest_mode,
+            guest::commands::exit_guest_mode,
+            // Emergency stop
+            emergency::commands::get_emergency_status,
+            emergency::commands::emergency_stop,
+            emergency::commands::clear_emergency_stop,
+            // Stale temp file cleanup
+            cleanup::commands::find_stale_files,
+            cleanup::commands::clean_stale_files,
             // Chat commands - Real-time setting sync
             chat::broadcast_session_setting,
             // Chat commands - Debug info
             chat::get_session_debug_info,
+            chat::get_session_environment,
+            chat::get_session_vulnerabilities,
+            chat::scan_session_vulnerabilities,
+            chat::add_message_annotation,
+            chat::update_message_annotation,
+            chat::delete_message_annotation,
+            chat::list_message_annotations,
             // Chat commands - Session resume (detached process recovery)
             chat::resume_session,
             chat::check_resumable_sessions,
             // Claude CLI management commands
             claude_cli::check_claude_cli_installed,
             claude_cli::check_claude_cli_auth,
+            claude_cli::get_claude_auth_status,
+            claude_cli::start_claude_login,
+            claude_cli::cancel_claude_login,
             claude_cli::get_available_cli_versions,
             claude_cli::install_claude_cli,
+            claude_cli::list_installed_cli_versions,
+            claude_cli::install_cli_version,
+            claude_cli::get_pinned_cli_version,
             // GitHub CLI management commands
             gh_cli::check_gh_cli_installed,
             gh_cli::check_gh_cli_auth,
             gh_cli::get_available_gh_versions,
             gh_cli::install_gh_cli,
+            tool_install::commands::install_tools,
+            tool_install::commands::get_helper_tools_status,
+            tool_install::commands::detect_node_runtime,
+            tool_install::commands::install_npm_package,
+            tool_install::commands::install_codex_cli_via_package_manager,
+            tool_install::commands::install_codex_cli_from_file,
+            tool_install::commands::list_installed_codex_versions,
+            tool_install::commands::switch_codex_version,
+            tool_install::commands::get_shell_integration_status,
+            tool_install::commands::enable_shell_integration,
+            tool_install::commands::disable_shell_integration,
+            tool_install::commands::get_shell_completions_status,
+            tool_install::commands::install_shell_completions,
+            tool_install::commands::uninstall_shell_completions,
+            python_env::commands::detect_python,
+            python_env::commands::create_python_env,
+            python_env::commands::list_python_envs,
+            python_env::commands::repair_python_env,
+            python_env::commands::remove_python_env,
+            python_env::commands::get_python_tool_entry_point,
+            // Speech-to-text commands
+            speech::get_speech_status,
+            speech::list_speech_models,
+            speech::download_speech_model,
+            speech::delete_speech_model,
+            speech::transcribe_audio,
+            speech::speak_text,
+            // Power management commands
+            power::commands::get_power_state,
+            focus::commands::get_focus_state,
+            power::commands::check_provider_connectivity,
+            // Crash report commands
+            crash_reports::commands::list_crash_reports,
+            crash_reports::commands::get_crash_report,
+            crash_reports::commands::delete_crash_report,
+            crash_reports::commands::submit_crash_report,
+            // Audit log commands
+            audit::commands::list_web_requests,
+            // Self-test commands
+            self_test::commands::run_self_test,
+            // Mock backend commands
+            mock::commands::set_mock_scenario,
+            // TLS diagnostics commands
+            http_client::commands::diagnose_tls,
+            http_client::commands::test_proxy_connection,
+            http_client::commands::set_github_token,
+            http_client::commands::has_github_token,
+            // Provider overview commands
+            providers::commands::get_providers_overview,
+            providers::commands::get_provider_key_status,
+            providers::commands::rotate_provider_key,
+            providers::commands::rollback_provider_key,
+            // Quota telemetry commands
+            quota::commands::list_quota_events,
+            // Storage integrity commands
+            migrations::commands::verify_database_integrity,
+            // Backup commands
+            backups::commands::create_backup_now,
+            backups::commands::list_backups,
+            backups::commands::restore_backup,
+            // Prompt library commands
+            prompts::commands::get_prompt_library,
+            prompts::commands::sync_shared_library,
+            prompts::commands::browse_community_library,
+            prompts::commands::install_community_item,
+            prompts::commands::list_community_installs,
+            prompts::commands::check_community_updates,
+            prompts::commands::list_template_variables,
+            prompts::commands::render_prompt_template,
+            prompts::commands::get_template_effectiveness,
+            // Report exports
+            reports::commands::export_usage_csv,
+            reports::commands::get_attribution_subtotals,
+            reports::commands::export_attribution_csv,
+            reports::commands::get_activity_heatmap,
+            reports::commands::send_email_digest,
+            // Email commands
+            email::commands::set_smtp_password,
+            email::commands::send_test_email,
             // Background task commands
             background_tasks::commands::set_app_focus_state,
+            background_tasks::commands::get_power_conditions,
+            background_tasks::commands::should_run_background_job,
+            background_tasks::commands::check_task_schedule,
+            background_tasks::commands::get_background_task_health,
+            background_tasks::commands::get_maintenance_status,
+            background_tasks::commands::run_maintenance_now,
             background_tasks::commands::set_active_worktree_for_polling,
             background_tasks::commands::set_git_poll_interval,
             background_tasks::commands::get_git_poll_interval,
@@ -1918,7 +2568,7 @@ pub fn run() {
             stop_http_server,
             get_http_server_status,
             regenerate_http_token,
-        ])
+        ]))
         .build(tauri::generate_context!())
         .expect("error building tauri application")
         .run(move |_app_handle, event| match &event {
@@ -1926,6 +2576,7 @@ pub fn run() {
                 eprintln!("[TERMINAL CLEANUP] RunEvent::Exit received");
                 let killed = terminal::cleanup_all_terminals();
                 eprintln!("[TERMINAL CLEANUP] Killed {killed} terminal(s)");
+                chat::warm_pool::clear(_app_handle);
             }
             tauri::RunEvent::ExitRequested { api, .. } => {
                 // In headless mode, prevent exit when window closes
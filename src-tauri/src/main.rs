@@ -2,5 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // Agents start this binary as an MCP server (see semantic_search::mcp)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = jean_lib::run_mcp_server(&args) {
+        std::process::exit(code);
+    }
     jean_lib::run()
 }
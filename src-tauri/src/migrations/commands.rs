@@ -0,0 +1,22 @@
+//! Tauri commands for storage integrity
+
+use tauri::AppHandle;
+
+use super::{verify_store, IntegrityReport};
+use crate::runtime::PathProvider;
+
+/// Check the session store for unreadable files and inconsistencies
+/// (index/metadata/run-log mismatches, unsupported schema version)
+#[tauri::command]
+pub async fn verify_database_integrity(app: AppHandle) -> Result<IntegrityReport, String> {
+    log::trace!("Verifying storage integrity");
+    let root = app.app_data_dir()?;
+    let report = verify_store(&root);
+    if !report.ok {
+        log::warn!(
+            "Storage integrity check found {} issues",
+            report.issues.len()
+        );
+    }
+    Ok(report)
+}
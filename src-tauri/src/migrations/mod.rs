@@ -0,0 +1,504 @@
+//! Versioned migrations and integrity checks for the session store
+//!
+//! The store is the JSON/JSONL data under the app data directory (session
+//! indexes, session metadata and run logs, projects, saved contexts). Its
+//! schema version lives in `storage-version.json`. Migrations are embedded,
+//! applied in order on startup and never rolled back; the store is copied to
+//! `backups/` before the first pending migration runs. An app that finds a
+//! store newer than it understands leaves it untouched.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat::types::{RunStatus, SessionMetadata, WorktreeIndex};
+use crate::projects::types::ProjectsData;
+use crate::runtime::{now, PathProvider};
+
+pub mod commands;
+
+/// Schema version file (in app data)
+const VERSION_FILE: &str = "storage-version.json";
+
+/// Directory (in app data) for pre-migration backups
+pub(crate) const BACKUPS_DIR: &str = "backups";
+
+/// Files and directories (in app data) that make up the session store
+pub(crate) const STORE_ENTRIES: &[&str] = &[
+    "sessions",
+    "projects.json",
+    "session-context",
+    "git-context",
+    VERSION_FILE,
+];
+
+/// A forward-only schema migration over the app data directory
+struct Migration {
+    version: u32,
+    name: &'static str,
+    run: fn(&Path) -> Result<(), String>,
+}
+
+/// All migrations, in order. Append only; never edit a released migration.
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    name: "normalize_session_metadata_keys",
+    run: normalize_session_metadata_keys,
+}];
+
+/// Store version this build writes
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VersionFile {
+    version: u32,
+    migrated_at: u64,
+}
+
+/// Current store version (0 for stores that predate versioning)
+pub fn read_version(root: &Path) -> Result<u32, String> {
+    let path = root.join(VERSION_FILE);
+    if !path.exists() {
+        return Ok(0);
+    }
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read storage version: {e}"))?;
+    serde_json::from_str::<VersionFile>(&content)
+        .map(|v| v.version)
+        .map_err(|e| format!("Failed to parse storage version: {e}"))
+}
+
+fn write_version(root: &Path, version: u32) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(&VersionFile {
+        version,
+        migrated_at: now(),
+    })
+    .map_err(|e| format!("Failed to serialize storage version: {e}"))?;
+    let temp = root.join(format!("{VERSION_FILE}.tmp"));
+    fs::write(&temp, content).map_err(|e| format!("Failed to write storage version: {e}"))?;
+    fs::rename(&temp, root.join(VERSION_FILE))
+        .map_err(|e| format!("Failed to write storage version: {e}"))
+}
+
+/// Recursively copy a file or directory
+pub(crate) fn copy_all(from: &Path, to: &Path) -> Result<(), String> {
+    if from.is_dir() {
+        fs::create_dir_all(to).map_err(|e| format!("Failed to create {to:?}: {e}"))?;
+        for entry in fs::read_dir(from).map_err(|e| format!("Failed to read {from:?}: {e}"))? {
+            let entry = entry.map_err(|e| format!("Failed to read {from:?}: {e}"))?;
+            copy_all(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy {from:?}: {e}"))
+    }
+}
+
+/// Copy the store to `backups/pre-migration-v{version}-{timestamp}/`
+fn backup_store(root: &Path, version: u32) -> Result<PathBuf, String> {
+    let backup_dir = root
+        .join(BACKUPS_DIR)
+        .join(format!("pre-migration-v{version}-{}", now()));
+    fs::create_dir_all(&backup_dir)
+        .map_err(|e| format!("Failed to create backup directory: {e}"))?;
+    for entry in STORE_ENTRIES {
+        let source = root.join(entry);
+        if source.exists() {
+            copy_all(&source, &backup_dir.join(entry))?;
+        }
+    }
+    Ok(backup_dir)
+}
+
+/// Outcome of applying migrations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Names of the migrations that ran
+    pub applied: Vec<String>,
+    pub backup_path: Option<String>,
+}
+
+/// Apply pending migrations to the store at `root`
+///
+/// The version is recorded after each migration, so a failure leaves the
+/// store at the last good version (with the backup to fall back on).
+pub fn run_migrations(root: &Path) -> Result<MigrationReport, String> {
+    let from_version = read_version(root)?;
+    let latest = latest_version();
+    if from_version > latest {
+        return Err(format!(
+            "Storage version {from_version} is newer than this app supports ({latest}); not migrating"
+        ));
+    }
+
+    let pending: Vec<&Migration> = MIGRATIONS
+        .iter()
+        .filter(|m| m.version > from_version)
+        .collect();
+    let mut report = MigrationReport {
+        from_version,
+        to_version: from_version,
+        applied: Vec::new(),
+        backup_path: None,
+    };
+    if pending.is_empty() {
+        return Ok(report);
+    }
+
+    let backup = backup_store(root, from_version)?;
+    log::info!("Backed up session store to {backup:?} before migrating");
+    report.backup_path = Some(backup.to_string_lossy().into_owned());
+
+    for migration in pending {
+        log::info!(
+            "Applying storage migration {} ({})",
+            migration.version,
+            migration.name
+        );
+        (migration.run)(root).map_err(|e| {
+            format!(
+                "Storage migration {} ({}) failed: {e}. A backup is at {backup:?}",
+                migration.version, migration.name
+            )
+        })?;
+        write_version(root, migration.version)?;
+        report.to_version = migration.version;
+        report.applied.push(migration.name.to_string());
+    }
+    Ok(report)
+}
+
+/// Migrate the store on startup (errors are logged; the app keeps running)
+pub fn init(app: &impl PathProvider) {
+    let root = match app.app_data_dir() {
+        Ok(root) => root,
+        Err(e) => {
+            log::error!("Storage migrations skipped: {e}");
+            return;
+        }
+    };
+    if !root.exists() {
+        // Fresh install: nothing to migrate
+        let _ = fs::create_dir_all(&root)
+            .and_then(|_| write_version(&root, latest_version()).map_err(std::io::Error::other));
+        return;
+    }
+    match run_migrations(&root) {
+        Ok(report) if !report.applied.is_empty() => log::info!(
+            "Storage migrated from v{} to v{}",
+            report.from_version,
+            report.to_version
+        ),
+        Ok(_) => {}
+        Err(e) => log::error!("{e}"),
+    }
+}
+
+// ============================================================================
+// Migrations
+// ============================================================================
+
+/// v1: rewrite legacy `session_id` / `session_name` metadata keys as `id` / `name`
+fn normalize_session_metadata_keys(root: &Path) -> Result<(), String> {
+    let data_dir = root.join("sessions").join("data");
+    let Ok(entries) = fs::read_dir(&data_dir) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let path = entry.path().join("metadata.json");
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let mut value: serde_json::Value = match serde_json::from_str(&content) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("Skipping unreadable metadata {path:?}: {e}");
+                continue;
+            }
+        };
+        let Some(object) = value.as_object_mut() else {
+            continue;
+        };
+
+        let mut changed = false;
+        for (old, new) in [("session_id", "id"), ("session_name", "name")] {
+            if let Some(v) = object.remove(old) {
+                object.entry(new).or_insert(v);
+                changed = true;
+            }
+        }
+        if changed {
+            let content = serde_json::to_string_pretty(&value)
+                .map_err(|e| format!("Failed to serialize {path:?}: {e}"))?;
+            let temp = path.with_extension("json.tmp");
+            fs::write(&temp, content).map_err(|e| format!("Failed to write {path:?}: {e}"))?;
+            fs::rename(&temp, &path).map_err(|e| format!("Failed to write {path:?}: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Integrity Checks
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// Data that can't be read or is inconsistent
+    Error,
+    /// Leftovers that waste space but don't affect history
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub severity: IssueSeverity,
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub storage_version: u32,
+    pub latest_version: u32,
+    pub files_checked: usize,
+    pub issues: Vec<IntegrityIssue>,
+    /// True if there are no errors (warnings are allowed)
+    pub ok: bool,
+}
+
+/// Parse `path` as `T`, recording an error issue on failure
+fn check_json<T: serde::de::DeserializeOwned>(
+    path: &Path,
+    files_checked: &mut usize,
+    issues: &mut Vec<IntegrityIssue>,
+) -> Option<T> {
+    *files_checked += 1;
+    let result = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()));
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            issues.push(IntegrityIssue {
+                severity: IssueSeverity::Error,
+                path: path.to_string_lossy().into_owned(),
+                message: format!("Unreadable: {e}"),
+            });
+            None
+        }
+    }
+}
+
+/// Check that every store file parses and that sessions, indexes and run logs agree
+pub fn verify_store(root: &Path) -> IntegrityReport {
+    let mut issues = Vec::new();
+    let mut files_checked = 0;
+    let issue = |severity, path: &Path, message: String| IntegrityIssue {
+        severity,
+        path: path.to_string_lossy().into_owned(),
+        message,
+    };
+
+    let storage_version = match read_version(root) {
+        Ok(version) => version,
+        Err(e) => {
+            issues.push(issue(IssueSeverity::Error, &root.join(VERSION_FILE), e));
+            0
+        }
+    };
+    if storage_version > latest_version() {
+        issues.push(issue(
+            IssueSeverity::Error,
+            &root.join(VERSION_FILE),
+            format!(
+                "Store version {storage_version} is newer than this app ({})",
+                latest_version()
+            ),
+        ));
+    }
+
+    let projects_path = root.join("projects.json");
+    if projects_path.exists() {
+        check_json::<ProjectsData>(&projects_path, &mut files_checked, &mut issues);
+    }
+
+    // Each session should be listed by at most one worktree index
+    let mut indexed: HashMap<String, String> = HashMap::new();
+    if let Ok(entries) = fs::read_dir(root.join("sessions").join("index")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|e| e != "json") {
+                continue;
+            }
+            let Some(index) = check_json::<WorktreeIndex>(&path, &mut files_checked, &mut issues)
+            else {
+                continue;
+            };
+            for session in index.sessions {
+                if let Some(other) = indexed.insert(session.id.clone(), index.worktree_id.clone()) {
+                    issues.push(issue(
+                        IssueSeverity::Error,
+                        &path,
+                        format!("Session {} is also listed by worktree {other}", session.id),
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(root.join("sessions").join("data")) {
+        for entry in entries.flatten() {
+            let session_dir = entry.path();
+            let metadata_path = session_dir.join("metadata.json");
+            if !metadata_path.exists() {
+                continue;
+            }
+            let Some(metadata) =
+                check_json::<SessionMetadata>(&metadata_path, &mut files_checked, &mut issues)
+            else {
+                continue;
+            };
+
+            let dir_name = entry.file_name().to_string_lossy().into_owned();
+            if metadata.id != dir_name {
+                issues.push(issue(
+                    IssueSeverity::Error,
+                    &metadata_path,
+                    format!("Session ID {} doesn't match its directory", metadata.id),
+                ));
+            }
+
+            let run_ids: HashSet<&str> = metadata.runs.iter().map(|r| r.run_id.as_str()).collect();
+            for run in &metadata.runs {
+                let log = session_dir.join(format!("{}.jsonl", run.run_id));
+                if run.status != RunStatus::Running && !log.exists() {
+                    issues.push(issue(
+                        IssueSeverity::Error,
+                        &log,
+                        format!("Run log missing for run {}", run.run_id),
+                    ));
+                }
+            }
+
+            // Run logs that no run refers to
+            for file in fs::read_dir(&session_dir).into_iter().flatten().flatten() {
+                let name = file.file_name().to_string_lossy().into_owned();
+                let Some(stem) = name.strip_suffix(".jsonl") else {
+                    continue;
+                };
+                let run_id = stem
+                    .strip_suffix(".input")
+                    .or_else(|| stem.strip_suffix(".timing"))
+                    .unwrap_or(stem);
+                if !run_ids.contains(run_id) {
+                    issues.push(issue(
+                        IssueSeverity::Warning,
+                        &file.path(),
+                        "Run log isn't referenced by the session".to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    let ok = !issues.iter().any(|i| i.severity == IssueSeverity::Error);
+    IntegrityReport {
+        storage_version,
+        latest_version: latest_version(),
+        files_checked,
+        issues,
+        ok,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    const LEGACY_METADATA: &str = r#"{"session_id":"s1","worktree_id":"w1","session_name":"Old","order":0,"created_at":1,"runs":[]}"#;
+
+    #[test]
+    fn test_migrations_back_up_and_apply_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "sessions/data/s1/metadata.json", LEGACY_METADATA);
+
+        let report = run_migrations(root).unwrap();
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.to_version, latest_version());
+        assert_eq!(read_version(root).unwrap(), latest_version());
+
+        let migrated = fs::read_to_string(root.join("sessions/data/s1/metadata.json")).unwrap();
+        assert!(migrated.contains(r#""id": "s1""#) && !migrated.contains("session_id"));
+
+        // The backup holds the pre-migration data
+        let backup = PathBuf::from(report.backup_path.unwrap());
+        let backed_up = fs::read_to_string(backup.join("sessions/data/s1/metadata.json")).unwrap();
+        assert_eq!(backed_up, LEGACY_METADATA);
+
+        // Nothing left to apply
+        let again = run_migrations(root).unwrap();
+        assert!(again.applied.is_empty() && again.backup_path.is_none());
+    }
+
+    #[test]
+    fn test_newer_store_is_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        write_version(dir.path(), latest_version() + 1).unwrap();
+        assert!(run_migrations(dir.path()).is_err());
+        assert!(!verify_store(dir.path()).ok);
+    }
+
+    #[test]
+    fn test_verify_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write_version(root, latest_version()).unwrap();
+        write(
+            root,
+            "sessions/data/s1/metadata.json",
+            r#"{"id":"s1","worktree_id":"w1","name":"S","order":0,"created_at":1,"runs":[
+                {"run_id":"r1","user_message_id":"u","user_message":"hi","started_at":1,"status":"completed"},
+                {"run_id":"r2","user_message_id":"u","user_message":"hi","started_at":2,"status":"running"}
+            ]}"#,
+        );
+        write(root, "sessions/data/s1/r1.timing.jsonl", "");
+        write(root, "sessions/data/s1/stale.jsonl", "");
+        write(root, "sessions/data/s2/metadata.json", "{not json");
+
+        let report = verify_store(root);
+        assert!(!report.ok);
+        assert_eq!(report.files_checked, 2);
+        let messages: Vec<&str> = report.issues.iter().map(|i| i.message.as_str()).collect();
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("Run log missing for run r1")));
+        assert!(messages.iter().any(|m| m.starts_with("Unreadable")));
+        // Running runs may not have a log yet; the timing sidecar belongs to r1
+        assert!(!messages.iter().any(|m| m.contains("r2")));
+        assert_eq!(
+            report
+                .issues
+                .iter()
+                .filter(|i| i.severity == IssueSeverity::Warning)
+                .count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Tauri commands for the mock backend
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{list_scenarios, load_scenario, set_active};
+
+/// Mock backend state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockStatus {
+    /// Active scenario (None = mock mode off)
+    pub active: Option<String>,
+    /// Built-in and custom scenario names
+    pub available: Vec<String>,
+}
+
+/// Switch the mock backend to a scenario, or turn it off with `None`
+///
+/// Fails in builds without the `mock-backend` feature.
+#[tauri::command]
+pub async fn set_mock_scenario(app: AppHandle, name: Option<String>) -> Result<MockStatus, String> {
+    if !cfg!(feature = "mock-backend") {
+        return Err("Mock backend is not available in this build".to_string());
+    }
+
+    let active = match name.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => {
+            let scenario = load_scenario(&app, name)?;
+            log::info!("Mock backend switched to scenario '{name}'");
+            set_active(Some(scenario));
+            Some(name.to_string())
+        }
+        None => {
+            log::info!("Mock backend turned off");
+            set_active(None);
+            None
+        }
+    };
+
+    Ok(MockStatus {
+        active,
+        available: list_scenarios(&app),
+    })
+}
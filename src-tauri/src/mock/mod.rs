@@ -0,0 +1,420 @@
+//! Mock backend for frontend development
+//!
+//! Only available in builds with the `mock-backend` feature. When a scenario
+//! is active, CLI status/auth checks return canned results and chat sessions
+//! stream a synthetic Claude CLI transcript instead of spawning the CLI, so the
+//! UI can be developed without API keys or installed CLIs.
+//!
+//! Scenarios are either built in (see [`BUILTIN_SCENARIOS`]) or loaded from
+//! `mock-scenarios/{name}.json` in the app data directory. Activate one with
+//! `set_mock_scenario` or by starting the app with `JEAN_MOCK_SCENARIO=<name>`.
+
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::runtime::PathProvider;
+
+pub mod commands;
+
+/// Directory name for custom scenario scripts within app data
+const SCENARIOS_DIR_NAME: &str = "mock-scenarios";
+
+/// Names of the scenarios that ship with the app
+pub const BUILTIN_SCENARIOS: &[&str] = &["default", "tools", "error", "no-cli"];
+
+/// CLI version reported while a scenario is active
+pub const MOCK_CLI_VERSION: &str = "0.0.0-mock";
+
+/// Placeholder processes exit on their own after this long, so one orphaned by
+/// quitting mid-run doesn't linger
+const PLACEHOLDER_MAX_SECS: u64 = 600;
+
+/// The active scenario (None = mock mode off)
+static ACTIVE_SCENARIO: Lazy<Mutex<Option<MockScenario>>> = Lazy::new(|| Mutex::new(None));
+
+/// A scripted backend behaviour
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockScenario {
+    /// Scenario name (file stem for custom scenarios)
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Whether the Claude and GitHub CLIs report as installed
+    #[serde(default = "default_true")]
+    pub cli_installed: bool,
+    /// Whether the Claude and GitHub CLIs report as authenticated
+    #[serde(default = "default_true")]
+    pub cli_authenticated: bool,
+    /// Output streamed for every chat message
+    #[serde(default)]
+    pub steps: Vec<MockStep>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_step_delay_ms() -> u64 {
+    300
+}
+
+/// One step of a synthetic session transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MockStep {
+    /// Assistant text
+    Text {
+        text: String,
+        #[serde(default = "default_step_delay_ms")]
+        delay_ms: u64,
+    },
+    /// Extended thinking block
+    Thinking {
+        text: String,
+        #[serde(default = "default_step_delay_ms")]
+        delay_ms: u64,
+    },
+    /// Tool call followed by its result
+    ToolUse {
+        name: String,
+        #[serde(default)]
+        input: Value,
+        #[serde(default)]
+        output: String,
+        #[serde(default = "default_step_delay_ms")]
+        delay_ms: u64,
+    },
+    /// End the run with an error result
+    Error { message: String },
+}
+
+/// The active scenario, if mock mode is on (always None without the feature)
+pub fn active_scenario() -> Option<MockScenario> {
+    if !cfg!(feature = "mock-backend") {
+        return None;
+    }
+    ACTIVE_SCENARIO.lock().ok().and_then(|s| s.clone())
+}
+
+/// Activate the scenario named in JEAN_MOCK_SCENARIO (call once during setup)
+pub fn init_from_env(app: &impl PathProvider) {
+    if !cfg!(feature = "mock-backend") {
+        return;
+    }
+    let Ok(name) = std::env::var("JEAN_MOCK_SCENARIO") else {
+        return;
+    };
+    match load_scenario(app, &name) {
+        Ok(scenario) => {
+            log::info!("Mock backend active with scenario '{name}'");
+            set_active(Some(scenario));
+        }
+        Err(e) => log::warn!("Mock backend not started: {e}"),
+    }
+}
+
+fn set_active(scenario: Option<MockScenario>) {
+    if let Ok(mut active) = ACTIVE_SCENARIO.lock() {
+        *active = scenario;
+    }
+}
+
+/// Get the custom scenarios directory
+pub fn get_scenarios_dir(app: &impl PathProvider) -> Result<std::path::PathBuf, String> {
+    Ok(app.app_data_dir()?.join(SCENARIOS_DIR_NAME))
+}
+
+/// Names of all available scenarios (built-in first, then custom files)
+pub fn list_scenarios(app: &impl PathProvider) -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_SCENARIOS.iter().map(|s| s.to_string()).collect();
+    if let Ok(entries) = get_scenarios_dir(app)
+        .and_then(|d| std::fs::read_dir(&d).map_err(|e| format!("Failed to read {d:?}: {e}")))
+    {
+        let mut custom: Vec<String> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .filter(|name| !BUILTIN_SCENARIOS.contains(&name.as_str()))
+            .collect();
+        custom.sort();
+        names.extend(custom);
+    }
+    names
+}
+
+/// Load a scenario by name (built-ins take precedence over custom files)
+pub fn load_scenario(app: &impl PathProvider, name: &str) -> Result<MockScenario, String> {
+    if let Some(scenario) = builtin_scenario(name) {
+        return Ok(scenario);
+    }
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid mock scenario name: {name}"));
+    }
+
+    let path = get_scenarios_dir(app)?.join(format!("{name}.json"));
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Unknown mock scenario '{name}' ({path:?}): {e}"))?;
+    let mut scenario: MockScenario = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse mock scenario '{name}': {e}"))?;
+    scenario.name = name.to_string();
+    Ok(scenario)
+}
+
+/// Built-in scenarios (deterministic output and timing)
+pub fn builtin_scenario(name: &str) -> Option<MockScenario> {
+    let text = |text: &str| MockStep::Text {
+        text: text.to_string(),
+        delay_ms: default_step_delay_ms(),
+    };
+
+    let (description, cli_installed, steps) = match name {
+        "default" => (
+            "Short streamed reply",
+            true,
+            vec![
+                text("This is a mock response from Jean's development backend. "),
+                text("No API key or Claude CLI was used to produce it.\n\n"),
+                text("Edit a scenario in `mock-scenarios/` to script your own output."),
+            ],
+        ),
+        "tools" => (
+            "Thinking, tool calls and a summary",
+            true,
+            vec![
+                MockStep::Thinking {
+                    text: "The user wants an overview. I'll read the README first.".to_string(),
+                    delay_ms: default_step_delay_ms(),
+                },
+                MockStep::ToolUse {
+                    name: "Read".to_string(),
+                    input: json!({ "file_path": "README.md" }),
+                    output: "# Example project\n\nA mock README.".to_string(),
+                    delay_ms: 600,
+                },
+                MockStep::ToolUse {
+                    name: "Bash".to_string(),
+                    input: json!({ "command": "ls", "description": "List files" }),
+                    output: "README.md\nsrc\npackage.json".to_string(),
+                    delay_ms: 600,
+                },
+                text("The project contains a README, a `src` directory and a `package.json`."),
+            ],
+        ),
+        "error" => (
+            "Run that fails partway through",
+            true,
+            vec![
+                text("Starting work on this... "),
+                MockStep::Error {
+                    message: "API Error: 529 Overloaded (mock)".to_string(),
+                },
+            ],
+        ),
+        "no-cli" => ("CLIs not installed (onboarding flow)", false, Vec::new()),
+        _ => return None,
+    };
+
+    Some(MockScenario {
+        name: name.to_string(),
+        description: description.to_string(),
+        cli_installed,
+        cli_authenticated: cli_installed,
+        steps,
+    })
+}
+
+/// Render a scenario as Claude CLI stream-json lines, each with the delay to
+/// wait before writing it
+pub fn script_lines(scenario: &MockScenario, session_id: &str) -> Vec<(Duration, String)> {
+    let mut lines = vec![(
+        Duration::ZERO,
+        json!({ "type": "system", "subtype": "init", "session_id": session_id }).to_string(),
+    )];
+    let mut content = String::new();
+    let mut error = None;
+
+    for (i, step) in scenario.steps.iter().enumerate() {
+        match step {
+            MockStep::Text { text, delay_ms } => {
+                content.push_str(text);
+                lines.push((
+                    Duration::from_millis(*delay_ms),
+                    assistant_line(session_id, json!({ "type": "text", "text": text })),
+                ));
+            }
+            MockStep::Thinking { text, delay_ms } => lines.push((
+                Duration::from_millis(*delay_ms),
+                assistant_line(session_id, json!({ "type": "thinking", "thinking": text })),
+            )),
+            MockStep::ToolUse {
+                name,
+                input,
+                output,
+                delay_ms,
+            } => {
+                let id = format!("mock_tool_{i}");
+                lines.push((
+                    Duration::ZERO,
+                    assistant_line(
+                        session_id,
+                        json!({ "type": "tool_use", "id": id, "name": name, "input": input }),
+                    ),
+                ));
+                lines.push((
+                    Duration::from_millis(*delay_ms),
+                    json!({
+                        "type": "user",
+                        "session_id": session_id,
+                        "message": { "role": "user", "content": [
+                            { "type": "tool_result", "tool_use_id": id, "content": output }
+                        ]}
+                    })
+                    .to_string(),
+                ));
+            }
+            MockStep::Error { message } => {
+                error = Some(message.clone());
+                break;
+            }
+        }
+    }
+
+    let result = match error {
+        Some(message) => json!({
+            "type": "result",
+            "subtype": "error_during_execution",
+            "is_error": true,
+            "result": message,
+            "session_id": session_id,
+        }),
+        None => json!({
+            "type": "result",
+            "subtype": "success",
+            "is_error": false,
+            "result": content,
+            "session_id": session_id,
+            "usage": {
+                "input_tokens": 100,
+                "output_tokens": content.split_whitespace().count(),
+                "cache_read_input_tokens": 0,
+                "cache_creation_input_tokens": 0,
+            },
+        }),
+    };
+    lines.push((Duration::from_millis(100), result.to_string()));
+    lines
+}
+
+fn assistant_line(session_id: &str, block: Value) -> String {
+    json!({
+        "type": "assistant",
+        "session_id": session_id,
+        "message": { "role": "assistant", "content": [block] },
+    })
+    .to_string()
+}
+
+/// Start streaming a scenario into `output_file` in place of a Claude CLI run
+///
+/// Returns the PID of a placeholder process that stands in for the CLI:
+/// cancelling the session kills it, which stops the stream.
+pub fn start_mock_run(scenario: &MockScenario, output_file: &Path) -> Result<u32, String> {
+    let mut placeholder = crate::platform::spawn_placeholder_process(PLACEHOLDER_MAX_SECS)?;
+    let pid = placeholder.id();
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(output_file)
+        .map_err(|e| format!("Failed to open output file: {e}"))?;
+    let lines = script_lines(scenario, &format!("mock-{}", uuid::Uuid::new_v4()));
+    log::trace!(
+        "Mock run with scenario '{}' ({} lines), placeholder PID {pid}",
+        scenario.name,
+        lines.len()
+    );
+
+    crate::background_tasks::supervisor::spawn_thread("mock-stream", move || {
+        for (delay, line) in lines {
+            std::thread::sleep(delay);
+            // Placeholder killed = session cancelled
+            if !matches!(placeholder.try_wait(), Ok(None)) {
+                log::trace!("Mock run cancelled");
+                return;
+            }
+            if let Err(e) = writeln!(file, "{line}") {
+                log::warn!("Mock run failed to write output: {e}");
+                break;
+            }
+        }
+        let _ = placeholder.kill();
+        let _ = placeholder.wait();
+    });
+
+    Ok(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_scenarios_exist() {
+        for name in BUILTIN_SCENARIOS {
+            assert!(builtin_scenario(name).is_some(), "missing {name}");
+        }
+        assert!(builtin_scenario("nope").is_none());
+    }
+
+    #[test]
+    fn test_script_ends_with_result() {
+        let scenario = builtin_scenario("tools").unwrap();
+        let lines = script_lines(&scenario, "s1");
+        let parsed: Vec<Value> = lines
+            .iter()
+            .map(|(_, l)| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert_eq!(parsed[0]["type"], "system");
+        let last = parsed.last().unwrap();
+        assert_eq!(last["type"], "result");
+        assert_eq!(last["is_error"], false);
+        // Each tool call is followed by its result
+        let tool_results = parsed.iter().filter(|m| m["type"] == "user").count();
+        assert_eq!(tool_results, 2);
+    }
+
+    #[test]
+    fn test_error_step_stops_script() {
+        let scenario = builtin_scenario("error").unwrap();
+        let lines = script_lines(&scenario, "s1");
+        let last: Value = serde_json::from_str(&lines.last().unwrap().1).unwrap();
+        assert_eq!(last["is_error"], true);
+        assert_eq!(last["result"], "API Error: 529 Overloaded (mock)");
+    }
+
+    #[test]
+    fn test_custom_scenario_json() {
+        let scenario: MockScenario = serde_json::from_str(
+            r#"{"steps": [{"type": "text", "text": "hi"}, {"type": "tool_use", "name": "Read"}]}"#,
+        )
+        .unwrap();
+        assert!(scenario.cli_installed);
+        assert!(matches!(
+            scenario.steps[0],
+            MockStep::Text { delay_ms: 300, .. }
+        ));
+    }
+}
@@ -0,0 +1,183 @@
+// Cross-platform crash capture helpers
+
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+/// How a child process ended abnormally
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbnormalExit {
+    /// Exit code (Windows NTSTATUS or Unix exit code), if any
+    pub exit_code: Option<i32>,
+    /// Terminating signal (Unix only)
+    pub signal: Option<i32>,
+}
+
+/// Signals that indicate a crash rather than a deliberate kill
+#[cfg(unix)]
+pub fn is_crash_signal(signal: i32) -> bool {
+    matches!(
+        signal,
+        libc::SIGSEGV | libc::SIGBUS | libc::SIGABRT | libc::SIGILL | libc::SIGFPE | libc::SIGTRAP
+    )
+}
+
+/// NTSTATUS error codes (0xC0000000+) indicate a crash, except Ctrl+C termination
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn is_crash_exit_code(code: i32) -> bool {
+    const STATUS_CONTROL_C_EXIT: u32 = 0xC000_013A;
+    let code = code as u32;
+    code >= 0xC000_0000 && code != STATUS_CONTROL_C_EXIT
+}
+
+/// Classify an exit status as a crash (None for normal or deliberate exits)
+#[cfg(unix)]
+pub fn abnormal_exit(status: &ExitStatus) -> Option<AbnormalExit> {
+    use std::os::unix::process::ExitStatusExt;
+    let signal = status.signal().filter(|s| is_crash_signal(*s))?;
+    Some(AbnormalExit {
+        exit_code: None,
+        signal: Some(signal),
+    })
+}
+
+#[cfg(windows)]
+pub fn abnormal_exit(status: &ExitStatus) -> Option<AbnormalExit> {
+    let code = status.code().filter(|c| is_crash_exit_code(*c))?;
+    Some(AbnormalExit {
+        exit_code: Some(code),
+        signal: None,
+    })
+}
+
+/// Write a minidump of the current process (Windows only; None elsewhere)
+///
+/// `exception_pointers` is the `EXCEPTION_POINTERS` from an unhandled
+/// exception filter, or null when dumping from a panic.
+#[cfg(windows)]
+pub fn write_minidump(path: &Path, exception_pointers: *const std::ffi::c_void) -> Option<PathBuf> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::System::Diagnostics::Debug::{
+        MiniDumpNormal, MiniDumpWriteDump, EXCEPTION_POINTERS, MINIDUMP_EXCEPTION_INFORMATION,
+    };
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentProcess, GetCurrentProcessId, GetCurrentThreadId,
+    };
+
+    let file = std::fs::File::create(path).ok()?;
+    let exception_info = MINIDUMP_EXCEPTION_INFORMATION {
+        ThreadId: unsafe { GetCurrentThreadId() },
+        ExceptionPointers: exception_pointers as *mut EXCEPTION_POINTERS,
+        ClientPointers: 0,
+    };
+    let exception_param = if exception_pointers.is_null() {
+        std::ptr::null()
+    } else {
+        &exception_info as *const _
+    };
+
+    let ok = unsafe {
+        MiniDumpWriteDump(
+            GetCurrentProcess(),
+            GetCurrentProcessId(),
+            file.as_raw_handle() as _,
+            MiniDumpNormal,
+            exception_param,
+            std::ptr::null(),
+            std::ptr::null(),
+        )
+    };
+    if ok == 0 {
+        drop(file);
+        let _ = std::fs::remove_file(path);
+        return None;
+    }
+    Some(path.to_path_buf())
+}
+
+#[cfg(not(windows))]
+pub fn write_minidump(
+    _path: &Path,
+    _exception_pointers: *const std::ffi::c_void,
+) -> Option<PathBuf> {
+    None
+}
+
+/// Install a handler that writes a minidump when the process hits an unhandled
+/// native exception (access violation, stack overflow, ...). Windows only.
+///
+/// `on_crash` receives the path of the written dump and must not allocate much:
+/// the process is in an undefined state.
+#[cfg(windows)]
+pub fn install_native_crash_handler(dump_dir: PathBuf, on_crash: fn(&Path)) {
+    use once_cell::sync::OnceCell;
+    use windows_sys::Win32::System::Diagnostics::Debug::{
+        SetUnhandledExceptionFilter, EXCEPTION_POINTERS,
+    };
+
+    static HANDLER: OnceCell<(PathBuf, fn(&Path))> = OnceCell::new();
+    if HANDLER.set((dump_dir, on_crash)).is_err() {
+        return;
+    }
+
+    unsafe extern "system" fn filter(info: *const EXCEPTION_POINTERS) -> i32 {
+        const EXCEPTION_CONTINUE_SEARCH: i32 = 0;
+        if let Some((dir, on_crash)) = HANDLER.get() {
+            let path = dir.join(format!(
+                "native-{}.dmp",
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0)
+            ));
+            if write_minidump(&path, info as *const std::ffi::c_void).is_some() {
+                on_crash(&path);
+            }
+        }
+        EXCEPTION_CONTINUE_SEARCH
+    }
+
+    unsafe {
+        SetUnhandledExceptionFilter(Some(filter));
+    }
+}
+
+#[cfg(not(windows))]
+pub fn install_native_crash_handler(_dump_dir: PathBuf, _on_crash: fn(&Path)) {}
+
+/// Crash reports the OS wrote for this app (macOS DiagnosticReports `.ips`
+/// files), newer than `since` (unix seconds). Empty on other platforms.
+#[cfg(target_os = "macos")]
+pub fn system_crash_reports(app_name: &str, since: u64) -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let dir = home.join("Library/Logs/DiagnosticReports");
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let prefix = app_name.to_lowercase();
+
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            let name = p
+                .file_name()
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            name.starts_with(&prefix) && (name.ends_with(".ips") || name.ends_with(".crash"))
+        })
+        .filter(|p| {
+            std::fs::metadata(p)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .is_some_and(|d| d.as_secs() > since)
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn system_crash_reports(_app_name: &str, _since: u64) -> Vec<PathBuf> {
+    Vec::new()
+}
@@ -0,0 +1,57 @@
+// Cross-platform do-not-disturb / Focus detection
+
+/// Whether the OS is currently silencing notifications (None if unknown)
+/// - macOS: an active Focus assertion in the DoNotDisturb store (manually
+///   enabled Focus modes; reading it may need Full Disk Access)
+/// - Windows: SHQueryUserNotificationState (Focus Assist, presentation mode,
+///   full-screen apps)
+/// - Linux: GNOME's notification banners switched off
+#[cfg(target_os = "macos")]
+pub fn focus_active() -> Option<bool> {
+    let home = dirs::home_dir()?;
+    let content =
+        std::fs::read_to_string(home.join("Library/DoNotDisturb/DB/Assertions.json")).ok()?;
+    parse_focus_assertions(&content)
+}
+
+#[cfg(windows)]
+pub fn focus_active() -> Option<bool> {
+    use windows_sys::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_ACCEPTS_NOTIFICATIONS};
+
+    let mut state = 0;
+    let result = unsafe { SHQueryUserNotificationState(&mut state) };
+    if result != 0 {
+        return None;
+    }
+    Some(state != QUNS_ACCEPTS_NOTIFICATIONS)
+}
+
+#[cfg(target_os = "linux")]
+pub fn focus_active() -> Option<bool> {
+    let output = super::silent_command("gsettings")
+        .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "false" => Some(true),
+        "true" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse the macOS DoNotDisturb `Assertions.json`: a Focus is on when the
+/// store holds any assertion records
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub fn parse_focus_assertions(content: &str) -> Option<bool> {
+    let json: serde_json::Value = serde_json::from_str(content).ok()?;
+    let store = json.get("data")?.as_array()?;
+    Some(store.iter().any(|entry| {
+        entry
+            .get("storeAssertionRecords")
+            .and_then(|records| records.as_array())
+            .is_some_and(|records| !records.is_empty())
+    }))
+}
@@ -1,7 +1,20 @@
 // Cross-platform abstractions for shell execution and process management
 
+pub mod crash;
+pub mod focus;
+pub mod motw;
+pub mod network;
+pub mod power;
 pub mod process;
+pub mod secret_file;
 pub mod shell;
+pub mod tts;
 
+pub use crash::*;
+pub use focus::*;
+pub use network::*;
+pub use power::*;
 pub use process::*;
+pub use secret_file::*;
 pub use shell::*;
+pub use tts::*;
@@ -0,0 +1,134 @@
+// Mark of the Web handling for downloaded CLIs
+//
+// Windows tags files that came from the internet with a `Zone.Identifier`
+// alternate data stream, and SmartScreen warns about (or blocks) running
+// them. The CLIs Jean installs are verified first, so the mark is cleared
+// afterwards, like the quarantine attribute on macOS. The
+// `keep_mark_of_the_web` preference leaves it in place for environments that
+// rely on SmartScreen.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Zone of files downloaded from the internet (SmartScreen checks 3 and up)
+pub const INTERNET_ZONE: u32 = 3;
+
+/// Set from the `keep_mark_of_the_web` preference
+static KEEP_MARK: AtomicBool = AtomicBool::new(false);
+
+/// Apply the `keep_mark_of_the_web` preference to later installs
+pub fn configure(keep_mark: bool) {
+    KEEP_MARK.store(keep_mark, Ordering::Relaxed);
+}
+
+/// Path of a file's `Zone.Identifier` stream (`file.exe:Zone.Identifier`)
+pub fn zone_identifier_path(path: &Path) -> PathBuf {
+    let mut stream = OsString::from(path.as_os_str());
+    stream.push(":Zone.Identifier");
+    PathBuf::from(stream)
+}
+
+/// `ZoneId` from a `Zone.Identifier` stream's contents
+///
+/// The stream is an INI file; the zone is in its `[ZoneTransfer]` section.
+pub fn parse_zone_id(content: &str) -> Option<u32> {
+    let mut in_section = false;
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = line.eq_ignore_ascii_case("[ZoneTransfer]");
+        } else if in_section {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim().eq_ignore_ascii_case("ZoneId") {
+                    return value.trim().parse().ok();
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Delete a file's `Zone.Identifier` stream, returning the zone it named
+/// (None if the file wasn't marked)
+pub fn clear_mark(path: &Path) -> Result<Option<u32>, String> {
+    let stream = zone_identifier_path(path);
+    let content = match std::fs::read(&stream) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read Zone.Identifier of {path:?}: {e}")),
+    };
+    std::fs::remove_file(&stream)
+        .map_err(|e| format!("Failed to remove Zone.Identifier of {path:?}: {e}"))?;
+    Ok(parse_zone_id(&String::from_utf8_lossy(&content)))
+}
+
+/// Let a verified download run without OS prompts: removes the macOS
+/// quarantine attribute and, unless configured to keep it, the Windows Mark
+/// of the Web. Failures are only logged.
+pub fn release_verified_binary(path: &Path) {
+    #[cfg(target_os = "macos")]
+    {
+        log::trace!("Removing quarantine attribute from {path:?}");
+        // Ignore errors - attribute might not exist
+        let _ = super::silent_command("xattr")
+            .args(["-d", "com.apple.quarantine"])
+            .arg(path)
+            .output();
+    }
+
+    #[cfg(windows)]
+    {
+        if KEEP_MARK.load(Ordering::Relaxed) {
+            log::trace!("Keeping Mark of the Web on {path:?}");
+            return;
+        }
+        match clear_mark(path) {
+            Ok(Some(zone)) => log::info!("Cleared Mark of the Web (zone {zone}) from {path:?}"),
+            Ok(None) => {}
+            Err(e) => log::warn!("{e}"),
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let _ = path;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zone_id() {
+        let downloaded = "\u{feff}[ZoneTransfer]\r\nZoneId=3\r\nReferrerUrl=https://example.com/\r\nHostUrl=https://example.com/claude.exe\r\n";
+        assert_eq!(parse_zone_id(downloaded), Some(INTERNET_ZONE));
+        assert_eq!(parse_zone_id("[zonetransfer]\nzoneid = 4\n"), Some(4));
+        // ZoneId outside [ZoneTransfer] doesn't count
+        assert_eq!(parse_zone_id("[Other]\nZoneId=3\n"), None);
+        assert_eq!(parse_zone_id("[ZoneTransfer]\nZoneId=internet\n"), None);
+        assert_eq!(parse_zone_id(""), None);
+    }
+
+    #[test]
+    fn test_zone_identifier_path() {
+        assert_eq!(
+            zone_identifier_path(Path::new("cli/claude.exe")),
+            PathBuf::from("cli/claude.exe:Zone.Identifier")
+        );
+    }
+
+    #[test]
+    fn test_clear_mark() {
+        // Outside NTFS the stream path is an ordinary file next to the binary
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("gh.exe");
+        std::fs::write(&binary, b"binary").unwrap();
+        assert_eq!(clear_mark(&binary), Ok(None));
+
+        let stream = zone_identifier_path(&binary);
+        std::fs::write(&stream, "[ZoneTransfer]\r\nZoneId=3\r\n").unwrap();
+        assert_eq!(clear_mark(&binary), Ok(Some(3)));
+        assert!(!stream.exists());
+        assert!(binary.exists());
+    }
+}
@@ -0,0 +1,72 @@
+// Cross-platform network state detection
+
+use std::net::{IpAddr, UdpSocket};
+
+/// Local address of the interface that routes to the internet (None when offline)
+///
+/// Connecting a UDP socket sends no packets; it only asks the OS for a route,
+/// so this is cheap enough to poll. A changed address means the machine moved
+/// networks (Wi-Fi switch, VPN up/down, cable unplugged).
+pub fn primary_local_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("1.1.1.1:80").ok()?;
+    socket
+        .local_addr()
+        .ok()
+        .map(|addr| addr.ip())
+        .filter(|ip| !ip.is_unspecified())
+}
+
+/// Whether the active internet connection is metered (None if unknown)
+/// - Linux: NetworkManager's global Metered property
+/// - Windows: the internet connection profile's cost type
+/// - macOS: not exposed outside Network.framework, always unknown
+#[cfg(target_os = "linux")]
+pub fn is_metered_connection() -> Option<bool> {
+    let output = super::silent_command("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_nm_metered(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(windows)]
+pub fn is_metered_connection() -> Option<bool> {
+    const SCRIPT: &str = "$p = [Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]::GetInternetConnectionProfile(); \
+        if ($p) { $p.GetConnectionCost().NetworkCostType }";
+
+    let output = super::silent_command("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .output()
+        .ok()?;
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "Fixed" | "Variable" => Some(true),
+        "Unrestricted" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn is_metered_connection() -> Option<bool> {
+    None
+}
+
+/// Parse `busctl get-property ... Metered` output (`u <NMMetered>`).
+/// 1 = yes, 3 = guessed yes, 2 = no, 4 = guessed no, 0 = unknown.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn parse_nm_metered(output: &str) -> Option<bool> {
+    match output.trim().strip_prefix("u ")?.trim() {
+        "1" | "3" => Some(true),
+        "2" | "4" => Some(false),
+        _ => None,
+    }
+}
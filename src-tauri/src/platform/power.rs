@@ -0,0 +1,214 @@
+// Cross-platform sleep prevention (power assertions)
+
+/// Keeps the system awake while held; released on drop
+/// - macOS: `caffeinate -i` (IOKit PreventUserIdleSystemSleep assertion)
+/// - Windows: SetThreadExecutionState(ES_SYSTEM_REQUIRED) on a dedicated thread
+/// - Linux: `systemd-inhibit --what=sleep:idle` (logind inhibitor lock)
+pub struct SleepInhibitor {
+    #[cfg(unix)]
+    child: std::process::Child,
+    #[cfg(windows)]
+    stop: Option<std::sync::mpsc::Sender<()>>,
+}
+
+#[cfg(target_os = "macos")]
+impl SleepInhibitor {
+    pub fn acquire(_reason: &str) -> Result<Self, String> {
+        // -w ties the assertion to our PID so it can't outlive a crash
+        let child = spawn_group(
+            super::silent_command("caffeinate")
+                .arg("-i")
+                .arg("-w")
+                .arg(std::process::id().to_string()),
+        )
+        .map_err(|e| format!("Failed to run caffeinate: {e}"))?;
+        Ok(Self { child })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl SleepInhibitor {
+    pub fn acquire(reason: &str) -> Result<Self, String> {
+        if !super::executable_exists("systemd-inhibit") {
+            return Err("systemd-inhibit not found; sleep prevention is unavailable".to_string());
+        }
+        // `tail --pid` exits with Jean, so the lock can't outlive a crash
+        let child = spawn_group(
+            super::silent_command("systemd-inhibit")
+                .arg("--what=sleep:idle")
+                .arg("--who=Jean")
+                .arg(format!("--why={reason}"))
+                .arg("--mode=block")
+                .arg("tail")
+                .arg(format!("--pid={}", std::process::id()))
+                .arg("-f")
+                .arg("/dev/null"),
+        )
+        .map_err(|e| format!("Failed to run systemd-inhibit: {e}"))?;
+        Ok(Self { child })
+    }
+}
+
+#[cfg(unix)]
+fn spawn_group(cmd: &mut std::process::Command) -> std::io::Result<std::process::Child> {
+    use std::os::unix::process::CommandExt;
+    use std::process::Stdio;
+    // Own process group so release can kill the whole tree
+    cmd.process_group(0)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+}
+
+#[cfg(windows)]
+impl SleepInhibitor {
+    pub fn acquire(_reason: &str) -> Result<Self, String> {
+        use windows_sys::Win32::System::Power::{
+            SetThreadExecutionState, ES_CONTINUOUS, ES_SYSTEM_REQUIRED,
+        };
+
+        // The execution state belongs to the calling thread, so park a thread on it
+        let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel::<bool>();
+        std::thread::spawn(move || {
+            let previous = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) };
+            let _ = ready_tx.send(previous != 0);
+            let _ = stop_rx.recv();
+            unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+        });
+
+        match ready_rx.recv() {
+            Ok(true) => Ok(Self {
+                stop: Some(stop_tx),
+            }),
+            _ => Err("SetThreadExecutionState failed".to_string()),
+        }
+    }
+}
+
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        {
+            if let Err(e) = super::kill_process_tree(self.child.id()) {
+                log::warn!("Failed to release sleep inhibitor: {e}");
+            }
+            let _ = self.child.wait();
+        }
+        #[cfg(windows)]
+        {
+            if let Some(stop) = self.stop.take() {
+                let _ = stop.send(());
+            }
+        }
+    }
+}
+
+/// Detect that the system slept between two ticks of a polling loop.
+///
+/// Threads don't run while the machine is suspended, so a loop that sleeps for
+/// `tick` sees a wall-clock gap far larger than `tick` after waking. Returns the
+/// approximate time spent asleep when the gap exceeds `tick + threshold`.
+pub fn detect_sleep_gap(
+    wall_elapsed: std::time::Duration,
+    tick: std::time::Duration,
+    threshold: std::time::Duration,
+) -> Option<std::time::Duration> {
+    (wall_elapsed > tick + threshold).then(|| wall_elapsed - tick)
+}
+
+/// Battery state of the machine
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BatteryStatus {
+    /// True when running on battery (not plugged in)
+    pub on_battery: bool,
+    /// Remaining charge (0-100), if reported
+    pub percent: Option<u8>,
+}
+
+/// Current battery status (None on machines without a battery or if unknown)
+/// - macOS: `pmset -g batt`
+/// - Windows: GetSystemPowerStatus
+/// - Linux: /sys/class/power_supply
+#[cfg(target_os = "macos")]
+pub fn battery_status() -> Option<BatteryStatus> {
+    let output = super::silent_command("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    parse_pmset_batt(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(windows)]
+pub fn battery_status() -> Option<BatteryStatus> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    // BatteryFlag 128 = no system battery, 255 = unknown
+    if status.BatteryFlag == 128 || status.BatteryFlag == 255 {
+        return None;
+    }
+    Some(BatteryStatus {
+        on_battery: status.ACLineStatus == 0,
+        percent: (status.BatteryLifePercent <= 100).then_some(status.BatteryLifePercent),
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn battery_status() -> Option<BatteryStatus> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let read = |path: &std::path::Path, file: &str| {
+        std::fs::read_to_string(path.join(file))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut ac_online = None;
+    let mut battery = None;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match read(&path, "type").as_str() {
+            "Mains" => ac_online = Some(read(&path, "online") == "1"),
+            // Skip peripheral batteries (mice, keyboards) reported with scope=Device
+            "Battery" if read(&path, "scope") != "Device" && battery.is_none() => {
+                battery = Some((
+                    read(&path, "status"),
+                    read(&path, "capacity").parse::<u8>().ok(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    let (status, percent) = battery?;
+    let on_battery = match ac_online {
+        Some(online) => !online,
+        None => status == "Discharging",
+    };
+    Some(BatteryStatus {
+        on_battery,
+        percent,
+    })
+}
+
+/// Parse `pmset -g batt` output, e.g.:
+/// ```text
+/// Now drawing from 'Battery Power'
+///  -InternalBattery-0 (id=1234) 85%; discharging; 4:12 remaining present: true
+/// ```
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub fn parse_pmset_batt(output: &str) -> Option<BatteryStatus> {
+    let battery_line = output.lines().find(|l| l.contains("InternalBattery"))?;
+    let percent = battery_line
+        .split_whitespace()
+        .find_map(|part| part.strip_suffix("%;"))
+        .and_then(|p| p.parse::<u8>().ok());
+    Some(BatteryStatus {
+        on_battery: output.contains("'Battery Power'"),
+        percent,
+    })
+}
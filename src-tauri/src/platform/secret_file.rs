@@ -0,0 +1,59 @@
+// Writing files that hold secrets (tokens, passwords)
+//
+// The file is created readable only by the user (0600 on Unix) from the
+// start, rather than written and then restricted, so the secret is never
+// briefly readable by others. It's written to a temp file next to the
+// target and renamed over it, so a crash can't leave a truncated file.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Write `content` to `path`, creating it readable only by the user
+pub fn write_secret_file(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let temp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let result = options
+        .open(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(content)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_secret_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token.json");
+        fs::write(&path, "old").unwrap();
+
+        write_secret_file(&path, b"secret").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "secret");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        // No temp files are left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let missing = dir.path().join("missing/token.json");
+        assert!(write_secret_file(&missing, b"secret").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+// Cross-platform text-to-speech via the OS speech engines
+
+use super::silent_command;
+
+/// Speak text aloud, blocking until speech finishes
+/// - macOS: `say` (NSSpeechSynthesizer)
+/// - Windows: System.Speech via PowerShell (SAPI)
+/// - Linux: `spd-say` (speech-dispatcher)
+#[cfg(target_os = "macos")]
+pub fn speak(text: &str, voice: Option<&str>) -> Result<(), String> {
+    let mut cmd = silent_command("say");
+    if let Some(voice) = voice {
+        cmd.arg("-v").arg(voice);
+    }
+    cmd.arg("--").arg(text);
+    run_speech_command(cmd, "say")
+}
+
+#[cfg(windows)]
+pub fn speak(text: &str, voice: Option<&str>) -> Result<(), String> {
+    // Text and voice are passed via env vars so they never need quoting in the script
+    const SCRIPT: &str = "Add-Type -AssemblyName System.Speech; \
+        $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+        if ($env:JEAN_TTS_VOICE) { $s.SelectVoice($env:JEAN_TTS_VOICE) }; \
+        $s.Speak($env:JEAN_TTS_TEXT)";
+
+    let mut cmd = silent_command("powershell.exe");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("JEAN_TTS_TEXT", text)
+        .env("JEAN_TTS_VOICE", voice.unwrap_or(""));
+    run_speech_command(cmd, "System.Speech")
+}
+
+#[cfg(target_os = "linux")]
+pub fn speak(text: &str, voice: Option<&str>) -> Result<(), String> {
+    if !super::executable_exists("spd-say") {
+        return Err("spd-say not found. Install speech-dispatcher to enable speech.".to_string());
+    }
+    let mut cmd = silent_command("spd-say");
+    cmd.arg("--wait");
+    if let Some(voice) = voice {
+        cmd.arg("-y").arg(voice);
+    }
+    cmd.arg("--").arg(text);
+    run_speech_command(cmd, "spd-say")
+}
+
+fn run_speech_command(mut cmd: std::process::Command, engine: &str) -> Result<(), String> {
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run {engine}: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{engine} failed: {}", stderr.trim()));
+    }
+    Ok(())
+}
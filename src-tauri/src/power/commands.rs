@@ -0,0 +1,41 @@
+//! Tauri commands for power management status
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::monitor::ConnectivityStatus;
+use super::POWER_MANAGER;
+
+/// Current sleep-prevention status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerState {
+    /// Whether sleep prevention is enabled in preferences
+    pub enabled: bool,
+    /// Whether an OS power assertion is currently held
+    pub assertion_active: bool,
+    /// Reasons for the work currently keeping the system awake
+    pub active_work: Vec<String>,
+    /// Last error from acquiring the assertion (e.g. systemd-inhibit missing)
+    pub error: Option<String>,
+}
+
+/// Get the current power assertion status
+#[tauri::command]
+pub async fn get_power_state(app: AppHandle) -> Result<PowerState, String> {
+    let prefs = crate::load_preferences(app).await?;
+    let manager = POWER_MANAGER.lock().unwrap();
+
+    Ok(PowerState {
+        enabled: prefs.prevent_sleep_during_runs,
+        assertion_active: manager.inhibitor.is_some(),
+        active_work: manager.holders.values().cloned().collect(),
+        error: manager.last_error.clone(),
+    })
+}
+
+/// Check that the configured provider endpoint is reachable
+/// (also emits system:connectivity)
+#[tauri::command]
+pub async fn check_provider_connectivity(app: AppHandle) -> Result<ConnectivityStatus, String> {
+    Ok(super::monitor::check_provider_connectivity(&app).await)
+}
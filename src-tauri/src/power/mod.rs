@@ -0,0 +1,101 @@
+//! Keep the machine awake while work is running
+//!
+//! Long-running work (chat runs, queued messages, CLI installs, model
+//! downloads) takes a [`PowerGuard`]. While at least one guard is alive and
+//! the `prevent_sleep_during_runs` preference is enabled, Jean holds an OS
+//! power assertion so unattended runs aren't suspended mid-way. The assertion
+//! is released as soon as the last guard is dropped.
+//!
+//! The [`monitor`] submodule handles the other side: recovering after the
+//! system did sleep or the network changed.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use tauri::AppHandle;
+
+use crate::platform::SleepInhibitor;
+
+pub mod commands;
+pub mod monitor;
+
+struct PowerManager {
+    /// Active holders by guard ID, with a human-readable reason
+    holders: BTreeMap<u64, String>,
+    next_id: u64,
+    inhibitor: Option<SleepInhibitor>,
+    /// Last error from acquiring the OS assertion (cleared on success)
+    last_error: Option<String>,
+}
+
+static POWER_MANAGER: Lazy<Mutex<PowerManager>> = Lazy::new(|| {
+    Mutex::new(PowerManager {
+        holders: BTreeMap::new(),
+        next_id: 1,
+        inhibitor: None,
+        last_error: None,
+    })
+});
+
+/// Holds the system awake until dropped (no-op when the preference is disabled)
+pub struct PowerGuard {
+    id: Option<u64>,
+}
+
+impl PowerGuard {
+    /// Register work that should keep the system awake
+    pub async fn acquire(app: &AppHandle, reason: impl Into<String>) -> PowerGuard {
+        let enabled = crate::load_preferences(app.clone())
+            .await
+            .map(|p| p.prevent_sleep_during_runs)
+            .unwrap_or(true);
+        if !enabled {
+            return PowerGuard { id: None };
+        }
+
+        let reason = reason.into();
+        let mut manager = POWER_MANAGER.lock().unwrap();
+        let id = manager.next_id;
+        manager.next_id += 1;
+
+        if manager.inhibitor.is_none() {
+            match SleepInhibitor::acquire(&reason) {
+                Ok(inhibitor) => {
+                    log::trace!("Acquired power assertion: {reason}");
+                    manager.inhibitor = Some(inhibitor);
+                    manager.last_error = None;
+                }
+                Err(e) => {
+                    log::warn!("Failed to prevent system sleep: {e}");
+                    manager.last_error = Some(e);
+                }
+            }
+        }
+
+        manager.holders.insert(id, reason);
+        PowerGuard { id: Some(id) }
+    }
+}
+
+impl Drop for PowerGuard {
+    fn drop(&mut self) {
+        let Some(id) = self.id else {
+            return;
+        };
+        let mut manager = POWER_MANAGER.lock().unwrap();
+        manager.holders.remove(&id);
+        if manager.holders.is_empty() && manager.inhibitor.take().is_some() {
+            log::trace!("Released power assertion (no active work)");
+        }
+    }
+}
+
+/// Release the OS assertion immediately (e.g. when the preference is turned off).
+/// Existing guards stay registered but no longer keep the system awake.
+pub fn release_assertion() {
+    let mut manager = POWER_MANAGER.lock().unwrap();
+    if manager.inhibitor.take().is_some() {
+        log::trace!("Released power assertion");
+    }
+}
@@ -0,0 +1,311 @@
+//! Sleep/wake and network-change monitoring
+//!
+//! A background thread polls the wall clock and the primary network address.
+//! After the system wakes (or the network comes back), it:
+//! - marks sessions whose Claude process died during sleep as interrupted
+//!   (`session:interrupted` is emitted by send_chat_message when the run ends)
+//! - re-verifies connectivity to the configured provider (`system:connectivity`)
+//! - lets downloads wrapped in [`with_wake_retry`] resume instead of failing
+
+use std::future::Future;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::background_tasks::supervisor::{spawn_task, supervise};
+use crate::http_server::EmitExt;
+use crate::platform::{detect_sleep_gap, primary_local_address};
+use crate::CustomCliProfile;
+
+/// How often the monitor thread wakes up
+const TICK: Duration = Duration::from_secs(5);
+
+/// Extra wall-clock time beyond a tick that counts as a system sleep
+const SLEEP_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// How long an interrupted download waits for the network before retrying
+const NETWORK_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Provider endpoint when no custom profile overrides it
+const DEFAULT_PROVIDER_URL: &str = "https://api.anthropic.com";
+
+/// Incremented on every wake and network change, so in-flight work can tell
+/// whether it was disrupted
+static INTERRUPTION_EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// Payload for system:wake events
+#[derive(Debug, Clone, Serialize)]
+pub struct WakeEvent {
+    /// Approximate time the system was asleep
+    pub slept_secs: u64,
+    /// Sessions whose Claude process died during sleep
+    pub interrupted_sessions: Vec<String>,
+}
+
+/// Payload for system:network-changed events
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkChangedEvent {
+    pub online: bool,
+    /// Local address of the primary interface (None when offline)
+    pub address: Option<String>,
+}
+
+/// Result of checking the configured provider endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectivityStatus {
+    /// Whether the provider endpoint answered
+    pub online: bool,
+    /// Custom provider profile name (None = Anthropic direct)
+    pub provider: Option<String>,
+    /// Endpoint that was checked
+    pub url: String,
+    /// Error from the check (if offline)
+    pub error: Option<String>,
+    /// Unix timestamp of the check
+    pub checked_at: u64,
+}
+
+/// Payload for system:download-resumed events
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadResumedEvent {
+    /// What is being downloaded (e.g. "Claude CLI")
+    pub label: String,
+    /// Error that interrupted the first attempt
+    pub error: String,
+}
+
+/// Current interruption epoch (changes after every wake or network change)
+pub fn interruption_epoch() -> u64 {
+    INTERRUPTION_EPOCH.load(Ordering::SeqCst)
+}
+
+/// Start the sleep/wake and network monitor thread
+pub fn start(app: AppHandle) {
+    supervise("power-monitor", move || {
+        let mut last_tick = SystemTime::now();
+        let mut last_address = primary_local_address();
+
+        loop {
+            std::thread::sleep(TICK);
+
+            let now = SystemTime::now();
+            let wall_elapsed = now.duration_since(last_tick).unwrap_or_default();
+            last_tick = now;
+
+            if let Some(slept) = detect_sleep_gap(wall_elapsed, TICK, SLEEP_THRESHOLD) {
+                INTERRUPTION_EPOCH.fetch_add(1, Ordering::SeqCst);
+                handle_wake(&app, slept);
+            }
+
+            let address = primary_local_address();
+            if address != last_address {
+                INTERRUPTION_EPOCH.fetch_add(1, Ordering::SeqCst);
+                handle_network_change(&app, address);
+                last_address = address;
+            }
+        }
+    });
+}
+
+fn handle_wake(app: &AppHandle, slept: Duration) {
+    log::info!("System woke after ~{}s asleep", slept.as_secs());
+
+    let interrupted_sessions = crate::chat::registry::interrupt_dead_processes();
+    if !interrupted_sessions.is_empty() {
+        log::info!(
+            "{} session(s) interrupted by system sleep",
+            interrupted_sessions.len()
+        );
+    }
+
+    let event = WakeEvent {
+        slept_secs: slept.as_secs(),
+        interrupted_sessions,
+    };
+    if let Err(e) = app.emit_all("system:wake", &event) {
+        log::error!("Failed to emit wake event: {e}");
+    }
+
+    let app = app.clone();
+    spawn_task("wake-connectivity-check", async move {
+        // Give the network a moment to come back before checking the provider
+        let _ =
+            tauri::async_runtime::spawn_blocking(|| wait_for_network(NETWORK_WAIT_TIMEOUT)).await;
+        check_provider_connectivity(&app).await;
+    });
+}
+
+fn handle_network_change(app: &AppHandle, address: Option<IpAddr>) {
+    log::info!("Network changed, primary address: {address:?}");
+
+    let event = NetworkChangedEvent {
+        online: address.is_some(),
+        address: address.map(|a| a.to_string()),
+    };
+    if let Err(e) = app.emit_all("system:network-changed", &event) {
+        log::error!("Failed to emit network changed event: {e}");
+    }
+
+    if address.is_some() {
+        let app = app.clone();
+        spawn_task("network-connectivity-check", async move {
+            check_provider_connectivity(&app).await;
+        });
+    }
+}
+
+/// Wait until a network route is available (or the timeout expires)
+fn wait_for_network(timeout: Duration) -> bool {
+    let started = std::time::Instant::now();
+    loop {
+        if primary_local_address().is_some() {
+            return true;
+        }
+        if started.elapsed() > timeout {
+            return false;
+        }
+        std::thread::sleep(Duration::from_secs(2));
+    }
+}
+
+/// Read ANTHROPIC_BASE_URL from a CLI settings profile's env block
+pub(crate) fn base_url_from_settings(settings_json: &str) -> Option<String> {
+    let settings: serde_json::Value = serde_json::from_str(settings_json).ok()?;
+    settings
+        .get("env")?
+        .get("ANTHROPIC_BASE_URL")?
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Endpoint of the default provider profile (Anthropic when none is set)
+fn provider_url(default_provider: Option<&str>, profiles: &[CustomCliProfile]) -> String {
+    default_provider
+        .and_then(|name| profiles.iter().find(|p| p.name == name))
+        .and_then(|p| base_url_from_settings(&p.settings_json))
+        .unwrap_or_else(|| DEFAULT_PROVIDER_URL.to_string())
+}
+
+/// Check that the configured provider endpoint is reachable and emit the result
+pub async fn check_provider_connectivity(app: &AppHandle) -> ConnectivityStatus {
+    let (provider, url) = match crate::load_preferences(app.clone()).await {
+        Ok(prefs) => {
+            let url = provider_url(
+                prefs.default_provider.as_deref(),
+                &prefs.custom_cli_profiles,
+            );
+            (prefs.default_provider, url)
+        }
+        Err(_) => (None, DEFAULT_PROVIDER_URL.to_string()),
+    };
+
+    // Any HTTP response (even 404) means the endpoint is reachable
+    let result = match crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .timeout(Duration::from_secs(10))
+        .build()
+    {
+        Ok(client) => client
+            .head(&url)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(format!("Failed to create HTTP client: {e}")),
+    };
+
+    let status = ConnectivityStatus {
+        online: result.is_ok(),
+        provider,
+        url,
+        error: result.err(),
+        checked_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+
+    log::trace!("Provider connectivity: {status:?}");
+    if let Err(e) = app.emit_all("system:connectivity", &status) {
+        log::error!("Failed to emit connectivity event: {e}");
+    }
+    status
+}
+
+/// Run a download, retrying once if it failed because the system slept or the
+/// network changed while it was in progress
+pub async fn with_wake_retry<T, F, Fut>(
+    app: &AppHandle,
+    label: &str,
+    mut run: F,
+) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let epoch = interruption_epoch();
+    match run().await {
+        Err(e) if interruption_epoch() != epoch => {
+            log::info!("{label} was interrupted by sleep or a network change, resuming: {e}");
+            let _ = tauri::async_runtime::spawn_blocking(|| wait_for_network(NETWORK_WAIT_TIMEOUT))
+                .await;
+
+            let event = DownloadResumedEvent {
+                label: label.to_string(),
+                error: e,
+            };
+            if let Err(e) = app.emit_all("system:download-resumed", &event) {
+                log::error!("Failed to emit download resumed event: {e}");
+            }
+            run().await
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, settings_json: &str) -> CustomCliProfile {
+        CustomCliProfile {
+            name: name.to_string(),
+            settings_json: settings_json.to_string(),
+            key_expires_at: None,
+            rotate_by: None,
+        }
+    }
+
+    #[test]
+    fn test_detect_sleep_gap() {
+        assert!(detect_sleep_gap(Duration::from_secs(6), TICK, SLEEP_THRESHOLD).is_none());
+        assert_eq!(
+            detect_sleep_gap(Duration::from_secs(605), TICK, SLEEP_THRESHOLD),
+            Some(Duration::from_secs(600))
+        );
+    }
+
+    #[test]
+    fn test_provider_url() {
+        let profiles = vec![
+            profile(
+                "OpenRouter",
+                r#"{"env": {"ANTHROPIC_BASE_URL": "https://openrouter.ai/api"}}"#,
+            ),
+            profile("Broken", "not json"),
+        ];
+        assert_eq!(
+            provider_url(Some("OpenRouter"), &profiles),
+            "https://openrouter.ai/api"
+        );
+        assert_eq!(
+            provider_url(Some("Broken"), &profiles),
+            DEFAULT_PROVIDER_URL
+        );
+        assert_eq!(provider_url(None, &profiles), DEFAULT_PROVIDER_URL);
+    }
+}
@@ -0,0 +1,45 @@
+use tauri::AppHandle;
+
+use super::{Profile, ProfileList};
+
+/// All profiles (default first) and the one this instance runs as
+#[tauri::command]
+pub async fn list_profiles(app: AppHandle) -> ProfileList {
+    super::list_profiles(&app)
+}
+
+#[tauri::command]
+pub async fn create_profile(app: AppHandle, name: String) -> Result<Profile, String> {
+    super::create_profile(&app, &name)
+}
+
+#[tauri::command]
+pub async fn rename_profile(
+    app: AppHandle,
+    profile_id: String,
+    name: String,
+) -> Result<Profile, String> {
+    super::rename_profile(&app, &profile_id, &name)
+}
+
+/// Delete a profile with its settings, sessions and credentials
+#[tauri::command]
+pub async fn delete_profile(app: AppHandle, profile_id: String) -> Result<(), String> {
+    super::delete_profile(&app, &profile_id)
+}
+
+/// Make `profile_id` the profile the app starts with; the frontend then
+/// relaunches the app to load it
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, profile_id: String) -> Result<(), String> {
+    let running = crate::chat::registry::get_running_sessions();
+    if !running.is_empty() {
+        return Err(format!(
+            "Stop the {} running session(s) before switching profiles",
+            running.len()
+        ));
+    }
+    super::set_next_profile(&app, &profile_id)?;
+    log::trace!("Switching to profile {profile_id} on relaunch");
+    Ok(())
+}
@@ -0,0 +1,352 @@
+//! Local user profiles for shared machines
+//!
+//! Several people can use Jean under one OS account, each with a profile of
+//! their own. A profile is a separate app data directory, so settings,
+//! projects, session transcripts, budgets and backups don't mix. The default
+//! profile keeps using the app data root (where data lived before profiles
+//! existed); other profiles live in `{root}/profiles/{id}/`. Installed tools,
+//! runtimes and speech models stay in the root, shared by all profiles.
+//!
+//! Claude CLI and GitHub CLI logins are kept per profile by pointing
+//! `CLAUDE_CONFIG_DIR` and `GH_CONFIG_DIR` into the profile directory (on
+//! macOS the Claude CLI may still keep its token in the login Keychain,
+//! which is shared by the OS account).
+//!
+//! The profile is chosen at launch: `JEAN_PROFILE` if set, otherwise the
+//! last one switched to. Switching saves the choice and the app restarts,
+//! so nothing from the previous profile stays in memory.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::{now, PathProvider};
+
+pub mod commands;
+
+/// Profile that uses the app data root
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+/// Profile list (in the shared root) and the directory other profiles live in
+const PROFILES_FILE: &str = "profiles.json";
+const PROFILES_DIR: &str = "profiles";
+
+/// Per-profile CLI config directories (within the profile directory)
+const CLAUDE_CONFIG_DIR: &str = "claude-config";
+const GH_CONFIG_DIR: &str = "gh-config";
+
+/// Selects a profile for this launch, overriding the saved choice
+const PROFILE_ENV: &str = "JEAN_PROFILE";
+
+/// Longest profile name, in characters
+const MAX_NAME_CHARS: usize = 60;
+
+/// Profile this process runs as (set once at startup)
+static ACTIVE_PROFILE: Lazy<RwLock<String>> =
+    Lazy::new(|| RwLock::new(DEFAULT_PROFILE_ID.to_string()));
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Lowercase slug of the name, also its directory name
+    pub id: String,
+    pub name: String,
+    pub created_at: u64,
+}
+
+/// Saved profiles and the one to start with
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfilesData {
+    /// Profile the next launch starts with (None = default)
+    #[serde(default)]
+    pub active: Option<String>,
+    /// Profiles besides the default one
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+}
+
+/// Profiles with the one this process runs as
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileList {
+    pub active: String,
+    /// Default profile first
+    pub profiles: Vec<Profile>,
+}
+
+/// Profile this process runs as
+pub fn active_profile() -> String {
+    ACTIVE_PROFILE.read().unwrap().clone()
+}
+
+/// Data directory of profile `id` under the shared root
+pub fn profile_dir(root: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_PROFILE_ID {
+        root.to_path_buf()
+    } else {
+        root.join(PROFILES_DIR).join(id)
+    }
+}
+
+fn default_profile() -> Profile {
+    Profile {
+        id: DEFAULT_PROFILE_ID.to_string(),
+        name: "Default".to_string(),
+        created_at: 0,
+    }
+}
+
+pub fn load_profiles(app: &impl PathProvider) -> ProfilesData {
+    app.shared_data_dir()
+        .ok()
+        .and_then(|root| fs::read_to_string(root.join(PROFILES_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_profiles(app: &impl PathProvider, data: &ProfilesData) -> Result<(), String> {
+    let root = app.shared_data_dir()?;
+    fs::create_dir_all(&root).map_err(|e| format!("Failed to create app data directory: {e}"))?;
+    let content = serde_json::to_string_pretty(data)
+        .map_err(|e| format!("Failed to serialize profiles: {e}"))?;
+    fs::write(root.join(PROFILES_FILE), content)
+        .map_err(|e| format!("Failed to save profiles: {e}"))
+}
+
+/// All profiles, default first
+pub fn list_profiles(app: &impl PathProvider) -> ProfileList {
+    let data = load_profiles(app);
+    ProfileList {
+        active: active_profile(),
+        profiles: std::iter::once(default_profile())
+            .chain(data.profiles)
+            .collect(),
+    }
+}
+
+fn exists(data: &ProfilesData, id: &str) -> bool {
+    id == DEFAULT_PROFILE_ID || data.profiles.iter().any(|p| p.id == id)
+}
+
+/// Profile to launch with: `JEAN_PROFILE`, then the saved choice, then the
+/// default (unknown ids fall through)
+pub fn resolve_active(data: &ProfilesData, env: Option<&str>) -> String {
+    env.map(str::trim)
+        .filter(|id| {
+            let known = exists(data, id);
+            if !known && !id.is_empty() {
+                log::warn!("Unknown profile in {PROFILE_ENV}: {id}");
+            }
+            known
+        })
+        .or(data.active.as_deref().filter(|id| exists(data, id)))
+        .unwrap_or(DEFAULT_PROFILE_ID)
+        .to_string()
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(format!(
+            "Profile name is longer than {MAX_NAME_CHARS} characters"
+        ));
+    }
+    Ok(name.to_string())
+}
+
+fn name_taken(data: &ProfilesData, name: &str, except: Option<&str>) -> bool {
+    std::iter::once(&default_profile())
+        .chain(&data.profiles)
+        .any(|p| Some(p.id.as_str()) != except && p.name.eq_ignore_ascii_case(name))
+}
+
+/// Directory-safe id for a name, unique among `data`'s profiles
+fn unique_id(data: &ProfilesData, name: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = if slug.is_empty() {
+        "profile".to_string()
+    } else {
+        slug
+    };
+    let mut id = base.clone();
+    let mut n = 1;
+    while exists(data, &id) {
+        n += 1;
+        id = format!("{base}-{n}");
+    }
+    id
+}
+
+pub fn create_profile(app: &impl PathProvider, name: &str) -> Result<Profile, String> {
+    let name = validate_name(name)?;
+    let mut data = load_profiles(app);
+    if name_taken(&data, &name, None) {
+        return Err(format!("A profile named \"{name}\" already exists"));
+    }
+    let profile = Profile {
+        id: unique_id(&data, &name),
+        name,
+        created_at: now(),
+    };
+    let dir = profile_dir(&app.shared_data_dir()?, &profile.id);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create profile directory: {e}"))?;
+    data.profiles.push(profile.clone());
+    save_profiles(app, &data)?;
+    log::trace!("Created profile {}", profile.id);
+    Ok(profile)
+}
+
+pub fn rename_profile(
+    app: &impl PathProvider,
+    profile_id: &str,
+    name: &str,
+) -> Result<Profile, String> {
+    if profile_id == DEFAULT_PROFILE_ID {
+        return Err("The default profile can't be renamed".to_string());
+    }
+    let name = validate_name(name)?;
+    let mut data = load_profiles(app);
+    if name_taken(&data, &name, Some(profile_id)) {
+        return Err(format!("A profile named \"{name}\" already exists"));
+    }
+    let profile = data
+        .profiles
+        .iter_mut()
+        .find(|p| p.id == profile_id)
+        .ok_or_else(|| format!("Profile not found: {profile_id}"))?;
+    profile.name = name;
+    let profile = profile.clone();
+    save_profiles(app, &data)?;
+    Ok(profile)
+}
+
+/// Delete a profile and all of its data
+pub fn delete_profile(app: &impl PathProvider, profile_id: &str) -> Result<(), String> {
+    if profile_id == DEFAULT_PROFILE_ID {
+        return Err("The default profile can't be deleted".to_string());
+    }
+    if profile_id == active_profile() {
+        return Err("Switch to another profile before deleting this one".to_string());
+    }
+    let mut data = load_profiles(app);
+    let before = data.profiles.len();
+    data.profiles.retain(|p| p.id != profile_id);
+    if data.profiles.len() == before {
+        return Err(format!("Profile not found: {profile_id}"));
+    }
+    if data.active.as_deref() == Some(profile_id) {
+        data.active = None;
+    }
+    save_profiles(app, &data)?;
+
+    let dir = profile_dir(&app.shared_data_dir()?, profile_id);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| format!("Failed to delete profile data: {e}"))?;
+    }
+    log::trace!("Deleted profile {profile_id}");
+    Ok(())
+}
+
+/// Save the profile the next launch starts with
+pub fn set_next_profile(app: &impl PathProvider, profile_id: &str) -> Result<(), String> {
+    let mut data = load_profiles(app);
+    if !exists(&data, profile_id) {
+        return Err(format!("Profile not found: {profile_id}"));
+    }
+    data.active = (profile_id != DEFAULT_PROFILE_ID).then(|| profile_id.to_string());
+    save_profiles(app, &data)
+}
+
+/// Select this launch's profile; call before anything reads app data
+pub fn init(app: &impl PathProvider) {
+    let data = load_profiles(app);
+    let env = std::env::var(PROFILE_ENV).ok();
+    let active = resolve_active(&data, env.as_deref());
+    *ACTIVE_PROFILE.write().unwrap() = active.clone();
+    if active == DEFAULT_PROFILE_ID {
+        return;
+    }
+
+    let Ok(dir) = app.app_data_dir() else {
+        return;
+    };
+    if let Err(e) = fs::create_dir_all(&dir) {
+        log::error!("Failed to create profile directory: {e}");
+    }
+    // Inherited by every CLI Jean starts, keeping logins per profile
+    std::env::set_var("CLAUDE_CONFIG_DIR", dir.join(CLAUDE_CONFIG_DIR));
+    std::env::set_var("GH_CONFIG_DIR", dir.join(GH_CONFIG_DIR));
+    log::info!("Running as profile {active}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_profile_lifecycle() {
+        let paths = TempPaths::new();
+        let profile = create_profile(&paths, "  Ada Lovelace ").unwrap();
+        assert_eq!(profile.id, "ada-lovelace");
+        assert_eq!(profile.name, "Ada Lovelace");
+        let root = paths.app_data_dir().unwrap();
+        assert!(profile_dir(&root, &profile.id).is_dir());
+        assert_eq!(profile_dir(&root, DEFAULT_PROFILE_ID), root);
+
+        assert!(create_profile(&paths, "ada lovelace").is_err());
+        assert!(create_profile(&paths, "default").is_err());
+        assert!(create_profile(&paths, " ").is_err());
+        // Same slug, different name
+        assert_eq!(
+            create_profile(&paths, "Ada-Lovelace!").unwrap().id,
+            "ada-lovelace-2"
+        );
+
+        let list = list_profiles(&paths);
+        assert_eq!(list.profiles.len(), 3);
+        assert_eq!(list.profiles[0].id, DEFAULT_PROFILE_ID);
+
+        let renamed = rename_profile(&paths, "ada-lovelace", "Ada").unwrap();
+        assert_eq!(renamed.id, "ada-lovelace");
+        assert!(rename_profile(&paths, DEFAULT_PROFILE_ID, "Shared").is_err());
+
+        set_next_profile(&paths, "ada-lovelace").unwrap();
+        assert!(set_next_profile(&paths, "nobody").is_err());
+        delete_profile(&paths, "ada-lovelace").unwrap();
+        let data = load_profiles(&paths);
+        assert_eq!(data.active, None);
+        assert_eq!(data.profiles.len(), 1);
+        assert!(!profile_dir(&root, "ada-lovelace").exists());
+        assert!(delete_profile(&paths, DEFAULT_PROFILE_ID).is_err());
+    }
+
+    #[test]
+    fn test_resolve_active() {
+        let data = ProfilesData {
+            active: Some("grace".to_string()),
+            profiles: vec![Profile {
+                id: "grace".to_string(),
+                name: "Grace".to_string(),
+                created_at: 0,
+            }],
+        };
+        assert_eq!(resolve_active(&data, None), "grace");
+        assert_eq!(resolve_active(&data, Some("default")), "default");
+        // Unknown ids fall back to the saved choice
+        assert_eq!(resolve_active(&data, Some("alan")), "grace");
+        assert_eq!(
+            resolve_active(&ProfilesData::default(), None),
+            DEFAULT_PROFILE_ID
+        );
+    }
+}
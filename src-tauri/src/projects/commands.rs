@@ -1,11 +1,10 @@
 use ignore::WalkBuilder;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::thread;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use tauri_plugin_dialog::DialogExt;
 use uuid::Uuid;
 
@@ -27,10 +26,13 @@ use super::types::{
     WorktreeDeleteErrorEvent, WorktreeDeletedEvent, WorktreeDeletingEvent, WorktreePathExistsEvent,
     WorktreePermanentlyDeletedEvent, WorktreeUnarchivedEvent,
 };
+use crate::background_tasks::supervisor::spawn_thread;
+use crate::chat::launch::LaunchDefaults;
 use crate::claude_cli::get_cli_binary_path;
 use crate::gh_cli::config::resolve_gh_binary;
 use crate::http_server::EmitExt;
 use crate::platform::silent_command;
+use crate::runtime::{now, PathProvider};
 
 /// Generate a unique name by appending 4 random alphanumeric chars,
 /// checking against both storage and git branches.
@@ -57,14 +59,6 @@ fn generate_unique_suffix_name(
     }
 }
 
-/// Get current Unix timestamp
-fn now() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
-}
-
 /// List all projects
 /// Check if git global user identity is configured
 #[tauri::command]
@@ -159,6 +153,7 @@ pub async fn add_project(
 
     // Create project with order at the end of the specified parent level
     let max_order = data.get_next_order(parent_id.as_deref());
+    let toolchain = super::toolchain::detect(Path::new(&path), now());
     let project = Project {
         id: Uuid::new_v4().to_string(),
         name,
@@ -171,10 +166,18 @@ pub async fn add_project(
         avatar_path: None,
         enabled_mcp_servers: Vec::new(),
         custom_system_prompt: None,
+        attribution: None,
+        cli_version: None,
+        toolchain: Some(toolchain),
+        blocked_domains: Vec::new(),
+        launch_defaults: BTreeMap::new(),
     };
 
     data.add_project(project.clone());
     save_projects_data(&app, &data)?;
+    crate::repo_map::refresh_in_background(app.clone(), PathBuf::from(&project.path));
+    crate::semantic_search::refresh_in_background(app.clone(), PathBuf::from(&project.path));
+    super::config::check_on_register(&app, &project.path).await;
 
     log::trace!("Successfully added project: {}", project.name);
     Ok(project)
@@ -311,6 +314,7 @@ pub async fn init_project(
 
     // Create project with order at the end of the specified parent level
     let max_order = data.get_next_order(parent_id.as_deref());
+    let toolchain = super::toolchain::detect(Path::new(&path), now());
     let project = Project {
         id: Uuid::new_v4().to_string(),
         name,
@@ -323,6 +327,11 @@ pub async fn init_project(
         avatar_path: None,
         enabled_mcp_servers: Vec::new(),
         custom_system_prompt: None,
+        attribution: None,
+        cli_version: None,
+        toolchain: Some(toolchain),
+        blocked_domains: Vec::new(),
+        launch_defaults: BTreeMap::new(),
     };
 
     data.add_project(project.clone());
@@ -564,7 +573,7 @@ pub async fn create_worktree(
     let pr_context_clone = pr_context.clone();
 
     // Spawn background thread for git operations
-    thread::spawn(move || {
+    spawn_thread("create-worktree", move || {
         log::trace!("Background: Creating git worktree {name_clone} at {worktree_path_clone}");
 
         // Check if path already exists
@@ -1087,7 +1096,7 @@ pub async fn create_worktree_from_existing_branch(
     let pr_context_clone = pr_context.clone();
 
     // Spawn background thread for git operations
-    thread::spawn(move || {
+    spawn_thread("create-worktree-from-branch", move || {
         log::trace!("Background: Creating git worktree {name_clone} at {worktree_path_clone} using existing branch {branch_name_clone}");
 
         // Check if path already exists
@@ -1472,7 +1481,7 @@ pub async fn checkout_pr(
     let pr_reviews = pr_detail.reviews.clone();
 
     // Do the heavy lifting in a background thread
-    thread::spawn(move || {
+    spawn_thread("checkout-pr", move || {
         log::trace!("Background: Creating worktree for PR #{pr_number}");
 
         // Step 1: Create worktree with a temporary branch based on base branch
@@ -1822,7 +1831,7 @@ pub async fn delete_worktree(app: AppHandle, worktree_id: String) -> Result<(),
 
     // Spawn background thread for git operations only
     // Storage is already updated, so git failures won't corrupt other data
-    thread::spawn(move || {
+    spawn_thread("delete-worktree", move || {
         log::trace!("Background: Removing git worktree at {worktree_path}");
 
         // Remove the git worktree (this can be slow for large repos)
@@ -2299,7 +2308,7 @@ pub async fn permanently_delete_worktree(
 
     // Spawn background thread for git operations and cleanup only
     // Storage is already updated, so git failures won't corrupt other data
-    thread::spawn(move || {
+    spawn_thread("permanently-delete-worktree", move || {
         // Only remove git worktree/branch for non-base sessions
         if !is_base_session {
             log::trace!("Background: Removing git worktree at {worktree_path}");
@@ -2318,7 +2327,7 @@ pub async fn permanently_delete_worktree(
         }
 
         // Delete the sessions file for this worktree
-        if let Ok(app_data_dir) = app_clone.path().app_data_dir() {
+        if let Ok(app_data_dir) = app_clone.app_data_dir() {
             let sessions_file = app_data_dir
                 .join("sessions")
                 .join(format!("{worktree_id_clone}.json"));
@@ -3001,6 +3010,7 @@ pub async fn get_project_branches(
 }
 
 /// Update project settings
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn update_project_settings(
     app: AppHandle,
@@ -3008,6 +3018,10 @@ pub async fn update_project_settings(
     default_branch: Option<String>,
     enabled_mcp_servers: Option<Vec<String>>,
     custom_system_prompt: Option<String>,
+    attribution: Option<String>,
+    cli_version: Option<String>,
+    blocked_domains: Option<Vec<String>>,
+    launch_defaults: Option<BTreeMap<String, LaunchDefaults>>,
 ) -> Result<Project, String> {
     log::trace!("Updating settings for project: {project_id}");
 
@@ -3041,6 +3055,44 @@ pub async fn update_project_settings(
         };
     }
 
+    if let Some(attribution) = attribution {
+        log::trace!("Updating attribution: {attribution:?}");
+        project.attribution = crate::reports::attribution::normalize(&attribution);
+    }
+
+    if let Some(version) = cli_version {
+        let version = version.trim().to_string();
+        log::trace!("Updating pinned Claude CLI version: {version:?}");
+        project.cli_version = if version.is_empty() {
+            None
+        } else {
+            crate::claude_cli::versions::validate_version(&version)?;
+            Some(version)
+        };
+    }
+
+    if let Some(domains) = blocked_domains {
+        let mut normalized = Vec::new();
+        for domain in &domains {
+            let host = crate::audit::normalize_domain(domain)
+                .ok_or_else(|| format!("Invalid domain: {domain:?}"))?;
+            if !normalized.contains(&host) {
+                normalized.push(host);
+            }
+        }
+        log::trace!("Updating blocked domains: {normalized:?}");
+        project.blocked_domains = normalized;
+    }
+
+    if let Some(mut defaults) = launch_defaults {
+        for backend_defaults in defaults.values() {
+            backend_defaults.validate()?;
+        }
+        defaults.retain(|_, backend_defaults| !backend_defaults.is_empty());
+        log::trace!("Updating launch defaults for {:?}", defaults.keys());
+        project.launch_defaults = defaults;
+    }
+
     let updated_project = project.clone();
     save_projects_data(&app, &data)?;
 
@@ -3876,6 +3928,7 @@ fn generate_pr_content(
     let output = child
         .wait_with_output()
         .map_err(|e| format!("Failed to wait for Claude CLI: {e}"))?;
+    crate::crash_reports::record_cli_exit("claude", &output.status, &output.stderr);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -4280,6 +4333,7 @@ fn generate_commit_message(
     let output = child
         .wait_with_output()
         .map_err(|e| format!("Failed to wait for Claude CLI: {e}"))?;
+    crate::crash_reports::record_cli_exit("claude", &output.status, &output.stderr);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -4490,6 +4544,7 @@ fn generate_review(
     let output = child
         .wait_with_output()
         .map_err(|e| format!("Failed to wait for Claude CLI: {e}"))?;
+    crate::crash_reports::record_cli_exit("claude", &output.status, &output.stderr);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -4823,6 +4878,7 @@ fn generate_release_notes_content(
     let output = child
         .wait_with_output()
         .map_err(|e| format!("Failed to wait for Claude CLI: {e}"))?;
+    crate::crash_reports::record_cli_exit("claude", &output.status, &output.stderr);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -5294,7 +5350,7 @@ pub async fn cleanup_old_archives(
         }
 
         // Delete the sessions file
-        if let Ok(app_data_dir) = app.path().app_data_dir() {
+        if let Ok(app_data_dir) = app.app_data_dir() {
             let sessions_file = app_data_dir
                 .join("sessions")
                 .join(format!("{}.json", worktree.id));
@@ -5422,7 +5478,7 @@ pub async fn delete_all_archives(app: AppHandle) -> Result<CleanupResult, String
         }
 
         // Delete the sessions file
-        if let Ok(app_data_dir) = app.path().app_data_dir() {
+        if let Ok(app_data_dir) = app.app_data_dir() {
             let sessions_file = app_data_dir
                 .join("sessions")
                 .join(format!("{}.json", worktree.id));
@@ -5552,6 +5608,11 @@ pub async fn create_folder(
         avatar_path: None,
         enabled_mcp_servers: Vec::new(),
         custom_system_prompt: None,
+        attribution: None,
+        cli_version: None,
+        toolchain: None,
+        blocked_domains: Vec::new(),
+        launch_defaults: BTreeMap::new(),
     };
 
     data.add_project(folder.clone());
@@ -5811,7 +5872,7 @@ pub async fn fetch_worktrees_status(app: AppHandle, project_id: String) -> Resul
         let app_clone = app.clone();
         let base_branch_clone = base_branch.clone();
 
-        thread::spawn(move || {
+        spawn_thread("fetch-worktree-status", move || {
             let info = ActiveWorktreeInfo {
                 worktree_id: worktree.id.clone(),
                 worktree_path: worktree.path.clone(),
@@ -6047,10 +6108,7 @@ pub async fn list_claude_commands() -> Result<Vec<ClaudeCommand>, String> {
 
 /// Get the avatars directory, creating it if needed
 fn get_avatars_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
 
     let avatars_dir = app_data_dir.join("avatars");
     std::fs::create_dir_all(&avatars_dir)
@@ -6136,10 +6194,7 @@ pub async fn remove_project_avatar(app: AppHandle, project_id: String) -> Result
 
     // Delete avatar file if it exists
     if let Some(ref avatar_path) = project.avatar_path {
-        let app_data_dir = app
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get app data dir: {e}"))?;
+        let app_data_dir = app.app_data_dir()?;
 
         let full_path = app_data_dir.join(avatar_path);
         if full_path.exists() {
@@ -6164,10 +6219,7 @@ pub async fn remove_project_avatar(app: AppHandle, project_id: String) -> Result
 /// Used by frontend to resolve relative avatar paths to absolute file:// URLs
 #[tauri::command]
 pub async fn get_app_data_dir(app: AppHandle) -> Result<String, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
 
     Ok(app_data_dir.to_string_lossy().to_string())
 }
@@ -0,0 +1,519 @@
+//! Project config file (`jean.json`)
+//!
+//! A `jean.json` checked into a repository configures Jean for everyone who
+//! works on it: setup and run scripts, doc targets, a pinned Claude CLI
+//! version, the provider profile sessions default to, tools sessions may use
+//! without asking, and monorepo sub-projects. It's read from each worktree,
+//! so branches can differ.
+//! Settings merge in order: a session's own choice, then project settings,
+//! then this file, then user preferences.
+//!
+//! The file is validated when a project is added and whenever the git status
+//! poller notices it changed in the active worktree; problems are reported
+//! in a `project-config:changed` event rather than blocking anything.
+//!
+//! `allowed_tools` comes from the repository, so a cloned repo could
+//! pre-approve anything. The rules only apply once the user has approved
+//! them for the project; a hash of the approved list is kept, and when the
+//! file's list changes sessions run without it and a
+//! `project-config:tools-approval-needed` event asks again.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use super::types::{JeanCli, JeanConfig, JeanScripts};
+use crate::http_server::EmitExt;
+use crate::migrations::IssueSeverity;
+use crate::runtime::PathProvider;
+
+/// Config file name, at the repository root
+pub const CONFIG_FILE: &str = "jean.json";
+
+/// Top-level keys Jean understands
+const KNOWN_KEYS: &[&str] = &[
+    "scripts",
+    "docs",
+    "cli",
+    "provider",
+    "allowed_tools",
+    "subprojects",
+];
+
+/// A Claude CLI permission rule: `Tool` or `Tool(specifier)`
+static TOOL_RULE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z][A-Za-z0-9_]*(\(.+\))?$").unwrap());
+
+/// Hashes of the `allowed_tools` lists the user approved, by project id
+const TOOL_APPROVALS_FILE: &str = "allowed-tools-approvals.json";
+
+/// Last content seen per worktree path (None when there was no file)
+static SEEN: Lazy<Mutex<HashMap<String, Option<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    /// Dotted path of the setting (empty for the whole file)
+    pub path: String,
+    pub message: String,
+}
+
+/// A worktree's config file and its problems
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectConfigStatus {
+    pub exists: bool,
+    /// None when missing or unreadable
+    pub config: Option<JeanConfig>,
+    pub issues: Vec<ConfigIssue>,
+}
+
+/// A project's `allowed_tools` and whether the user approved them (also the
+/// payload of `project-config:tools-approval-needed`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowedToolsApproval {
+    pub project_id: String,
+    pub worktree_path: String,
+    pub allowed_tools: Vec<String>,
+    pub approved: bool,
+}
+
+/// Payload of `project-config:changed`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectConfigChanged {
+    pub worktree_path: String,
+    pub status: ProjectConfigStatus,
+}
+
+fn issue(
+    severity: IssueSeverity,
+    path: impl Into<String>,
+    message: impl Into<String>,
+) -> ConfigIssue {
+    ConfigIssue {
+        severity,
+        path: path.into(),
+        message: message.into(),
+    }
+}
+
+/// Whether a tool rule is one the Claude CLI accepts
+pub fn is_valid_tool_rule(rule: &str) -> bool {
+    TOOL_RULE.is_match(rule.trim())
+}
+
+/// Parse and check a config file; `providers` are the user's profile names
+pub fn validate(content: &str, providers: &[String]) -> (Option<JeanConfig>, Vec<ConfigIssue>) {
+    let value: Value = match serde_json::from_str(content) {
+        Ok(value) => value,
+        Err(e) => {
+            let message = format!("Invalid JSON: {e}");
+            return (None, vec![issue(IssueSeverity::Error, "", message)]);
+        }
+    };
+    let Some(object) = value.as_object() else {
+        let message = "Expected an object";
+        return (None, vec![issue(IssueSeverity::Error, "", message)]);
+    };
+
+    let mut issues: Vec<ConfigIssue> = object
+        .keys()
+        .filter(|key| !KNOWN_KEYS.contains(&key.as_str()))
+        .map(|key| issue(IssueSeverity::Warning, key, "Unknown setting, ignored"))
+        .collect();
+
+    let config: JeanConfig = match serde_json::from_value(value) {
+        Ok(config) => config,
+        Err(e) => {
+            issues.push(issue(IssueSeverity::Error, "", e.to_string()));
+            return (None, issues);
+        }
+    };
+
+    if let Some(version) = &config.cli.claude {
+        if let Err(e) = crate::claude_cli::versions::validate_version(version.trim()) {
+            issues.push(issue(IssueSeverity::Error, "cli.claude", e));
+        }
+    }
+    if let Some(provider) = &config.provider {
+        if !providers.contains(provider) {
+            issues.push(issue(
+                IssueSeverity::Warning,
+                "provider",
+                format!("No provider profile named \"{provider}\", sessions use the default"),
+            ));
+        }
+    }
+    for (i, rule) in config.allowed_tools.iter().enumerate() {
+        if !is_valid_tool_rule(rule) {
+            issues.push(issue(
+                IssueSeverity::Error,
+                format!("allowed_tools[{i}]"),
+                format!("Not a tool rule: \"{rule}\" (expected Tool or Tool(pattern))"),
+            ));
+        }
+    }
+    for path in config.subprojects.keys() {
+        if !super::subprojects::is_valid_path(path.trim_start_matches("./").trim_end_matches('/')) {
+            issues.push(issue(
+                IssueSeverity::Error,
+                format!("subprojects.{path}"),
+                "Expected a path relative to the repository root",
+            ));
+        }
+    }
+    (Some(config), issues)
+}
+
+fn read(worktree_path: &str) -> Option<String> {
+    fs::read_to_string(Path::new(worktree_path).join(CONFIG_FILE)).ok()
+}
+
+async fn provider_names(app: &AppHandle) -> Vec<String> {
+    crate::load_preferences(app.clone())
+        .await
+        .map(|prefs| {
+            prefs
+                .custom_cli_profiles
+                .into_iter()
+                .map(|p| p.name)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn status_of(content: Option<&str>, providers: &[String]) -> ProjectConfigStatus {
+    match content {
+        Some(content) => {
+            let (config, issues) = validate(content, providers);
+            ProjectConfigStatus {
+                exists: true,
+                config,
+                issues,
+            }
+        }
+        None => ProjectConfigStatus {
+            exists: false,
+            config: None,
+            issues: Vec::new(),
+        },
+    }
+}
+
+/// Read and validate a worktree's config file
+pub async fn load(app: &AppHandle, worktree_path: &str) -> ProjectConfigStatus {
+    status_of(read(worktree_path).as_deref(), &provider_names(app).await)
+}
+
+/// Tools the config pre-approves (invalid rules are dropped)
+pub fn allowed_tools(worktree_path: &str) -> Vec<String> {
+    super::git::read_jean_config(worktree_path)
+        .map(|config| config.allowed_tools)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|rule| rule.trim().to_string())
+        .filter(|rule| is_valid_tool_rule(rule))
+        .collect()
+}
+
+/// Hash an approval is recorded under
+fn tools_hash(rules: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for rule in rules {
+        hasher.update(rule.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn load_approvals(app: &impl PathProvider) -> HashMap<String, String> {
+    app.app_data_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(TOOL_APPROVALS_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Whether the user approved exactly `rules` for the project
+fn is_approved(app: &impl PathProvider, project_id: &str, rules: &[String]) -> bool {
+    load_approvals(app).get(project_id) == Some(&tools_hash(rules))
+}
+
+/// Record that the user approved `rules` for the project (an empty list
+/// withdraws the approval)
+fn approve(app: &impl PathProvider, project_id: &str, rules: &[String]) -> Result<(), String> {
+    let mut approvals = load_approvals(app);
+    if rules.is_empty() {
+        approvals.remove(project_id);
+    } else {
+        approvals.insert(project_id.to_string(), tools_hash(rules));
+    }
+    let dir = app.app_data_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data directory: {e}"))?;
+    let content = serde_json::to_string_pretty(&approvals)
+        .map_err(|e| format!("Failed to serialize tool approvals: {e}"))?;
+    fs::write(dir.join(TOOL_APPROVALS_FILE), content)
+        .map_err(|e| format!("Failed to save tool approvals: {e}"))
+}
+
+/// The project a worktree (or a project's own directory) belongs to
+fn project_id_for(app: &AppHandle, worktree_id: &str, worktree_path: &str) -> Option<String> {
+    let data = super::storage::load_projects_data(app).ok()?;
+    data.find_worktree(worktree_id)
+        .map(|w| w.project_id.clone())
+        .or_else(|| {
+            data.projects
+                .iter()
+                .find(|p| p.id == worktree_id || p.path == worktree_path)
+                .map(|p| p.id.clone())
+        })
+}
+
+/// The config's `allowed_tools` if the user approved them for the project;
+/// otherwise none, and the user is asked with a
+/// `project-config:tools-approval-needed` event
+pub fn approved_allowed_tools(
+    app: &AppHandle,
+    worktree_id: &str,
+    worktree_path: &str,
+) -> Vec<String> {
+    let rules = allowed_tools(worktree_path);
+    if rules.is_empty() {
+        return rules;
+    }
+    let Some(project_id) = project_id_for(app, worktree_id, worktree_path) else {
+        return Vec::new();
+    };
+    if is_approved(app, &project_id, &rules) {
+        return rules;
+    }
+    log::trace!("{CONFIG_FILE} allowed_tools in {worktree_path} aren't approved, leaving them out");
+    let payload = AllowedToolsApproval {
+        project_id,
+        worktree_path: worktree_path.to_string(),
+        allowed_tools: rules,
+        approved: false,
+    };
+    if let Err(e) = app.emit_all("project-config:tools-approval-needed", &payload) {
+        log::error!("Failed to emit project-config:tools-approval-needed: {e}");
+    }
+    Vec::new()
+}
+
+/// Validate a newly added project's config, reporting problems
+pub async fn check_on_register(app: &AppHandle, project_path: &str) {
+    let content = read(project_path);
+    let status = status_of(content.as_deref(), &provider_names(app).await);
+    SEEN.lock()
+        .unwrap()
+        .insert(project_path.to_string(), content);
+    if status.issues.is_empty() {
+        return;
+    }
+    log::warn!(
+        "{CONFIG_FILE} in {project_path} has {} issue(s)",
+        status.issues.len()
+    );
+    emit_changed(app, project_path, status);
+}
+
+/// Report the config file if it changed since last seen (called by the poller)
+pub fn check_for_changes(app: &AppHandle, worktree_path: &str) {
+    let content = read(worktree_path);
+    let previous = SEEN
+        .lock()
+        .unwrap()
+        .insert(worktree_path.to_string(), content.clone());
+    // The first look at a worktree only records it
+    if previous.is_none() || previous == Some(content.clone()) {
+        return;
+    }
+    log::trace!("{CONFIG_FILE} changed in {worktree_path}");
+    let providers = tauri::async_runtime::block_on(provider_names(app));
+    emit_changed(
+        app,
+        worktree_path,
+        status_of(content.as_deref(), &providers),
+    );
+}
+
+fn emit_changed(app: &AppHandle, worktree_path: &str, status: ProjectConfigStatus) {
+    let payload = ProjectConfigChanged {
+        worktree_path: worktree_path.to_string(),
+        status,
+    };
+    if let Err(e) = app.emit_all("project-config:changed", &payload) {
+        log::error!("Failed to emit project-config:changed: {e}");
+    }
+}
+
+/// Starter config for a project: its pinned version and the user's default
+/// provider, with empty scripts
+pub fn generate(cli_version: Option<String>, provider: Option<String>) -> JeanConfig {
+    JeanConfig {
+        scripts: JeanScripts::default(),
+        cli: JeanCli {
+            claude: cli_version,
+        },
+        provider,
+        ..Default::default()
+    }
+}
+
+/// Write a config file; refuses to replace an existing one
+pub fn write_new(worktree_path: &str, config: &JeanConfig) -> Result<(), String> {
+    let path = Path::new(worktree_path).join(CONFIG_FILE);
+    if path.exists() {
+        return Err(format!("{CONFIG_FILE} already exists"));
+    }
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize {CONFIG_FILE}: {e}"))?;
+    fs::write(&path, format!("{content}\n"))
+        .map_err(|e| format!("Failed to write {CONFIG_FILE}: {e}"))
+}
+
+/// Read and validate the config file of a worktree
+#[tauri::command]
+pub async fn get_project_config(app: AppHandle, worktree_path: String) -> ProjectConfigStatus {
+    load(&app, &worktree_path).await
+}
+
+/// A worktree's `allowed_tools` and whether they're approved for its project
+#[tauri::command]
+pub async fn get_allowed_tools_approval(
+    app: AppHandle,
+    project_id: String,
+    worktree_path: String,
+) -> AllowedToolsApproval {
+    let allowed_tools = allowed_tools(&worktree_path);
+    AllowedToolsApproval {
+        approved: !allowed_tools.is_empty() && is_approved(&app, &project_id, &allowed_tools),
+        project_id,
+        worktree_path,
+        allowed_tools,
+    }
+}
+
+/// Approve the `allowed_tools` the user was shown for a project; if the
+/// file has changed since, the new list still needs approving
+#[tauri::command]
+pub async fn approve_allowed_tools(
+    app: AppHandle,
+    project_id: String,
+    allowed_tools: Vec<String>,
+) -> Result<(), String> {
+    log::trace!(
+        "Approving {} allowed tool(s) for project {project_id}",
+        allowed_tools.len()
+    );
+    approve(&app, &project_id, &allowed_tools)
+}
+
+/// Write a starter config file to a project's repository root, from its
+/// settings and the user's default provider
+#[tauri::command]
+pub async fn generate_project_config(
+    app: AppHandle,
+    project_id: String,
+) -> Result<ProjectConfigStatus, String> {
+    let data = super::storage::load_projects_data(&app)?;
+    let project = data
+        .find_project(&project_id)
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    let prefs = crate::load_preferences(app.clone()).await?;
+    write_new(
+        &project.path,
+        &generate(project.cli_version.clone(), prefs.default_provider),
+    )?;
+    log::trace!("Generated {CONFIG_FILE} for project: {}", project.name);
+    Ok(load(&app, &project.path).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn providers() -> Vec<String> {
+        vec!["OpenRouter".to_string()]
+    }
+
+    #[test]
+    fn test_validate_reports_issues() {
+        let content = r#"{
+            "scripts": { "setup": "npm ci" },
+            "cli": { "claude": "../1.0" },
+            "provider": "Missing",
+            "allowed_tools": ["Bash(npm test:*)", "not a rule"],
+            "subprojects": { "packages/web": {}, "../other": {} },
+            "hooks": {}
+        }"#;
+        let (config, issues) = validate(content, &providers());
+        assert_eq!(config.unwrap().scripts.setup.as_deref(), Some("npm ci"));
+        let paths: Vec<_> = issues
+            .iter()
+            .map(|i| (i.severity, i.path.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (IssueSeverity::Warning, "hooks"),
+                (IssueSeverity::Error, "cli.claude"),
+                (IssueSeverity::Warning, "provider"),
+                (IssueSeverity::Error, "allowed_tools[1]"),
+                (IssueSeverity::Error, "subprojects.../other"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_wrong_types() {
+        let (config, issues) = validate(r#"{ "allowed_tools": "Bash" }"#, &providers());
+        assert!(config.is_none());
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+
+        let (config, issues) = validate("[]", &providers());
+        assert!(config.is_none());
+        assert_eq!(issues.len(), 1);
+    }
+
+    #[test]
+    fn test_tool_approvals_follow_the_list() {
+        let paths = crate::test_support::runtime::TempPaths::new();
+        let rules = vec!["Bash(npm test:*)".to_string(), "Read".to_string()];
+        assert!(!is_approved(&paths, "p1", &rules));
+
+        approve(&paths, "p1", &rules).unwrap();
+        assert!(is_approved(&paths, "p1", &rules));
+        assert!(!is_approved(&paths, "p2", &rules));
+
+        // A changed file needs approving again
+        let mut changed = rules.clone();
+        changed.push("Bash(*)".to_string());
+        assert!(!is_approved(&paths, "p1", &changed));
+        assert!(!is_approved(&paths, "p1", &rules[..1]));
+
+        approve(&paths, "p1", &[]).unwrap();
+        assert!(!is_approved(&paths, "p1", &rules));
+    }
+
+    #[test]
+    fn test_generated_config_is_valid() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+        let config = generate(Some("1.0.30".to_string()), Some("OpenRouter".to_string()));
+        write_new(&root, &config).unwrap();
+        assert!(write_new(&root, &config).is_err());
+
+        let (parsed, issues) = validate(&read(&root).unwrap(), &providers());
+        assert!(issues.is_empty(), "{issues:?}");
+        assert_eq!(parsed.unwrap().cli.claude.as_deref(), Some("1.0.30"));
+    }
+}
@@ -245,6 +245,18 @@ pub fn branch_exists(repo_path: &str, branch_name: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Check if a full commit hash names a commit in the repository
+pub fn commit_exists(repo_path: &str, commit: &str) -> bool {
+    commit.len() == 40
+        && commit.chars().all(|c| c.is_ascii_hexdigit())
+        && silent_command("git")
+            .args(["cat-file", "-e", &format!("{commit}^{{commit}}")])
+            .current_dir(repo_path)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+}
+
 /// Check if a repository has any commits
 pub fn has_commits(repo_path: &str) -> bool {
     silent_command("git")
@@ -273,6 +285,11 @@ pub fn get_valid_base_branch(repo_path: &str, preferred_branch: &str) -> Result<
         return Ok(preferred_branch.to_string());
     }
 
+    // A specific commit, e.g. when re-running a session where it started
+    if commit_exists(repo_path, preferred_branch) {
+        return Ok(preferred_branch.to_string());
+    }
+
     log::warn!("Preferred branch '{preferred_branch}' not found, trying fallbacks");
 
     // Try common defaults
@@ -70,6 +70,7 @@ pub async fn list_workflow_runs(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        crate::quota::observe(&app, "github", None, &stderr);
         if stderr.contains("gh auth login") || stderr.contains("authentication") {
             return Err("GitHub CLI not authenticated. Run 'gh auth login' first.".to_string());
         }
@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 use super::git::get_repo_identifier;
 use crate::gh_cli::config::resolve_gh_binary;
 use crate::platform::silent_command;
+use crate::runtime::PathProvider;
 
 // =============================================================================
 // GitHub Types
@@ -113,6 +114,7 @@ pub async fn list_github_issues(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        crate::quota::observe(&app, "github", None, &stderr);
         // Handle specific errors
         if stderr.contains("gh auth login") || stderr.contains("authentication") {
             return Err("GitHub CLI not authenticated. Run 'gh auth login' first.".to_string());
@@ -204,6 +206,7 @@ pub async fn search_github_issues(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        crate::quota::observe(&app, "github", None, &stderr);
         if stderr.contains("gh auth login") || stderr.contains("authentication") {
             return Err("GitHub CLI not authenticated. Run 'gh auth login' first.".to_string());
         }
@@ -251,6 +254,7 @@ pub async fn get_github_issue(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        crate::quota::observe(&app, "github", None, &stderr);
         // Handle specific errors
         if stderr.contains("gh auth login") || stderr.contains("authentication") {
             return Err("GitHub CLI not authenticated. Run 'gh auth login' first.".to_string());
@@ -376,10 +380,7 @@ pub struct ContextReferences {
 
 /// Get the directory for shared GitHub contexts
 pub fn get_github_contexts_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
     Ok(app_data_dir.join("git-context"))
 }
 
@@ -1038,6 +1039,7 @@ pub async fn list_github_prs(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        crate::quota::observe(&app, "github", None, &stderr);
         if stderr.contains("gh auth login") || stderr.contains("authentication") {
             return Err("GitHub CLI not authenticated. Run 'gh auth login' first.".to_string());
         }
@@ -1090,6 +1092,7 @@ pub async fn search_github_prs(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        crate::quota::observe(&app, "github", None, &stderr);
         if stderr.contains("gh auth login") || stderr.contains("authentication") {
             return Err("GitHub CLI not authenticated. Run 'gh auth login' first.".to_string());
         }
@@ -1137,6 +1140,7 @@ pub async fn get_github_pr(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        crate::quota::observe(&app, "github", None, &stderr);
         if stderr.contains("gh auth login") || stderr.contains("authentication") {
             return Err("GitHub CLI not authenticated. Run 'gh auth login' first.".to_string());
         }
@@ -1,4 +1,5 @@
 mod commands;
+pub mod config;
 pub mod git;
 pub mod git_status;
 pub mod github_actions;
@@ -7,6 +8,8 @@ mod names;
 pub mod pr_status;
 pub mod saved_contexts;
 pub mod storage;
+pub mod subprojects;
+pub mod toolchain;
 pub mod types;
 
 // Re-export commands for registration in lib.rs
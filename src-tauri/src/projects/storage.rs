@@ -2,9 +2,10 @@ use std::path::PathBuf;
 use std::sync::Mutex;
 
 use once_cell::sync::Lazy;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 use super::types::ProjectsData;
+use crate::runtime::PathProvider;
 
 /// Global mutex to prevent concurrent read-modify-write races on projects.json.
 /// Multiple threads (e.g., fetch_worktrees_status) can call save_projects_data simultaneously,
@@ -13,10 +14,7 @@ static PROJECTS_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
 
 /// Get the path to the projects.json data file
 pub fn get_projects_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = app.app_data_dir()?;
 
     // Ensure the directory exists
     std::fs::create_dir_all(&app_data_dir)
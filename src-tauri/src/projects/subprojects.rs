@@ -0,0 +1,492 @@
+//! Monorepo sub-projects
+//!
+//! Packages of a monorepo are detected from its workspace manifests
+//! (`package.json` workspaces, `pnpm-workspace.yaml`, Cargo `[workspace]`
+//! members and `go.work`) and can be described further, or added, in
+//! jean.json under `subprojects`, keyed by repo-relative path. A session
+//! scoped to a sub-project runs in its directory, checks prompts against its
+//! context budget, and bills runs to its attribution unless the session
+//! names one.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::types::{SubprojectCommands, SubprojectConfig};
+
+/// Manifest a sub-project was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubprojectKind {
+    Npm,
+    Pnpm,
+    Cargo,
+    Go,
+    /// Only listed in jean.json
+    Configured,
+}
+
+/// A package of a monorepo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subproject {
+    /// Repo-relative, `/`-separated
+    pub path: String,
+    pub name: String,
+    pub kind: SubprojectKind,
+    pub commands: SubprojectCommands,
+    /// Token budget for prompts of sessions scoped to it
+    pub context_budget: Option<usize>,
+    pub attribution: Option<String>,
+}
+
+/// Whether `path` is a plain relative path inside the repository
+pub fn is_valid_path(path: &str) -> bool {
+    !path.is_empty()
+        && !path.starts_with('/')
+        && !path.contains('\\')
+        && path
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..")
+}
+
+fn normalize(pattern: &str) -> String {
+    pattern
+        .trim()
+        .trim_start_matches("./")
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// `*` matches any run of characters within one path segment
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(name) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            let rest = rest.trim_start_matches('*');
+            (0..=name.len())
+                .filter(|&i| name.is_char_boundary(i))
+                .any(|i| wildcard_match(rest, &name[i..]))
+        }
+    }
+}
+
+/// Directories a workspace pattern names; only the last segment may have
+/// wildcards (`packages/*`, `apps/web`, `crates/jean-*`)
+fn expand(root: &Path, pattern: &str) -> Vec<String> {
+    let pattern = normalize(pattern);
+    if pattern.starts_with('!') {
+        return Vec::new();
+    }
+    let (parent, last) = pattern.rsplit_once('/').unwrap_or(("", &pattern));
+    if parent.contains('*') {
+        log::trace!("Skipping workspace pattern with nested wildcards: {pattern}");
+        return Vec::new();
+    }
+    if !last.contains('*') {
+        return if is_valid_path(&pattern) && root.join(&pattern).is_dir() {
+            vec![pattern]
+        } else {
+            Vec::new()
+        };
+    }
+    let mut dirs: Vec<String> = fs::read_dir(root.join(parent))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.') && wildcard_match(last, name))
+        .map(|name| {
+            if parent.is_empty() {
+                name
+            } else {
+                format!("{parent}/{name}")
+            }
+        })
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// `workspaces` of package.json: an array, or `{ "packages": [...] }`
+fn npm_patterns(root: &Path) -> Vec<String> {
+    let Some(json) = read_json(&root.join("package.json")) else {
+        return Vec::new();
+    };
+    let workspaces = json.get("workspaces");
+    workspaces
+        .and_then(|w| w.as_array().or_else(|| w.get("packages")?.as_array()))
+        .map(|patterns| {
+            patterns
+                .iter()
+                .filter_map(|p| p.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `packages:` list items of pnpm-workspace.yaml
+fn pnpm_patterns(root: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(root.join("pnpm-workspace.yaml")) else {
+        return Vec::new();
+    };
+    let mut patterns = Vec::new();
+    let mut in_packages = false;
+    for line in content.lines() {
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = line.trim_end() == "packages:";
+            continue;
+        }
+        if let Some(item) = line.trim().strip_prefix('-').filter(|_| in_packages) {
+            patterns.push(item.trim().trim_matches(['"', '\'']).to_string());
+        }
+    }
+    patterns
+}
+
+/// Quoted strings of `members = [...]` in Cargo.toml's `[workspace]`
+fn cargo_patterns(root: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(root.join("Cargo.toml")) else {
+        return Vec::new();
+    };
+    let mut in_workspace = false;
+    let mut members = None;
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_workspace = line == "[workspace]";
+        } else if in_workspace && line.starts_with("members") {
+            members = Some(i);
+            break;
+        }
+    }
+    let Some(start) = members else {
+        return Vec::new();
+    };
+    let text: String = content.lines().skip(start).collect::<Vec<_>>().join("\n");
+    let list = text
+        .split_once('[')
+        .and_then(|(_, rest)| rest.split_once(']'))
+        .map(|(list, _)| list)
+        .unwrap_or_default();
+    list.split(',')
+        .filter_map(|item| {
+            let item = item.trim();
+            let item = item.strip_prefix('"')?;
+            item.split('"').next().map(str::to_string)
+        })
+        .collect()
+}
+
+/// `use` directives of go.work (single or in a block)
+fn go_patterns(root: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(root.join("go.work")) else {
+        return Vec::new();
+    };
+    let mut patterns = Vec::new();
+    let mut in_block = false;
+    for line in content.lines().map(str::trim) {
+        if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+            } else if !line.is_empty() && !line.starts_with("//") {
+                patterns.push(line.to_string());
+            }
+        } else if let Some(rest) = line.strip_prefix("use") {
+            let rest = rest.trim();
+            if rest.starts_with('(') {
+                in_block = true;
+            } else if !rest.is_empty() {
+                patterns.push(rest.to_string());
+            }
+        }
+    }
+    patterns
+}
+
+/// Package name from its manifest, else the directory name
+fn package_name(dir: &Path, kind: SubprojectKind) -> String {
+    let from_manifest = match kind {
+        SubprojectKind::Npm | SubprojectKind::Pnpm => read_json(&dir.join("package.json"))
+            .and_then(|json| json.get("name")?.as_str().map(str::to_string)),
+        SubprojectKind::Cargo => {
+            fs::read_to_string(dir.join("Cargo.toml"))
+                .ok()
+                .and_then(|content| {
+                    content.lines().map(str::trim).find_map(|line| {
+                        let value = line.strip_prefix("name")?.trim().strip_prefix('=')?;
+                        value
+                            .trim()
+                            .strip_prefix('"')?
+                            .split('"')
+                            .next()
+                            .map(str::to_string)
+                    })
+                })
+        }
+        SubprojectKind::Go | SubprojectKind::Configured => None,
+    };
+    from_manifest.unwrap_or_else(|| {
+        dir.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    })
+}
+
+/// Commands inferred from the manifest
+fn default_commands(dir: &Path, kind: SubprojectKind, name: &str) -> SubprojectCommands {
+    match kind {
+        SubprojectKind::Npm | SubprojectKind::Pnpm => {
+            let manager = if kind == SubprojectKind::Pnpm {
+                "pnpm"
+            } else {
+                "npm"
+            };
+            let scripts = read_json(&dir.join("package.json"))
+                .and_then(|json| json.get("scripts").cloned())
+                .unwrap_or_default();
+            let script = |names: &[&str]| {
+                names
+                    .iter()
+                    .find(|name| scripts.get(**name).is_some())
+                    .map(|name| format!("{manager} run {name}"))
+            };
+            SubprojectCommands {
+                run: script(&["dev", "start"]),
+                test: script(&["test"]),
+                build: script(&["build"]),
+            }
+        }
+        SubprojectKind::Cargo => SubprojectCommands {
+            run: None,
+            test: Some(format!("cargo test -p {name}")),
+            build: Some(format!("cargo build -p {name}")),
+        },
+        SubprojectKind::Go => SubprojectCommands {
+            run: None,
+            test: Some("go test ./...".to_string()),
+            build: Some("go build ./...".to_string()),
+        },
+        SubprojectKind::Configured => SubprojectCommands::default(),
+    }
+}
+
+/// Sub-projects of the repository at `root`, by path; jean.json entries
+/// override names and commands of detected ones
+pub fn list(root: &Path) -> Vec<Subproject> {
+    let mut subprojects: Vec<Subproject> = Vec::new();
+    let sources: [(SubprojectKind, fn(&Path) -> Vec<String>); 4] = [
+        (SubprojectKind::Pnpm, pnpm_patterns),
+        (SubprojectKind::Npm, npm_patterns),
+        (SubprojectKind::Cargo, cargo_patterns),
+        (SubprojectKind::Go, go_patterns),
+    ];
+    for (kind, patterns) in sources {
+        for pattern in patterns(root) {
+            for path in expand(root, &pattern) {
+                if subprojects.iter().any(|s| s.path == path) {
+                    continue;
+                }
+                let dir = root.join(&path);
+                let name = package_name(&dir, kind);
+                subprojects.push(Subproject {
+                    commands: default_commands(&dir, kind, &name),
+                    path,
+                    name,
+                    kind,
+                    context_budget: None,
+                    attribution: None,
+                });
+            }
+        }
+    }
+
+    let configured = super::git::read_jean_config(&root.to_string_lossy())
+        .map(|config| config.subprojects)
+        .unwrap_or_default();
+    for (path, config) in configured {
+        let path = normalize(&path);
+        if !is_valid_path(&path) || !root.join(&path).is_dir() {
+            log::warn!("Ignoring jean.json sub-project {path:?}: no such directory");
+            continue;
+        }
+        match subprojects.iter_mut().find(|s| s.path == path) {
+            Some(subproject) => apply(subproject, config),
+            None => {
+                let mut subproject = Subproject {
+                    name: package_name(&root.join(&path), SubprojectKind::Configured),
+                    path,
+                    kind: SubprojectKind::Configured,
+                    commands: SubprojectCommands::default(),
+                    context_budget: None,
+                    attribution: None,
+                };
+                apply(&mut subproject, config);
+                subprojects.push(subproject);
+            }
+        }
+    }
+
+    subprojects.sort_by(|a, b| a.path.cmp(&b.path));
+    subprojects
+}
+
+fn apply(subproject: &mut Subproject, config: SubprojectConfig) {
+    if let Some(name) = config.name {
+        subproject.name = name;
+    }
+    let commands = config.commands;
+    subproject.commands.run = commands.run.or(subproject.commands.run.take());
+    subproject.commands.test = commands.test.or(subproject.commands.test.take());
+    subproject.commands.build = commands.build.or(subproject.commands.build.take());
+    subproject.context_budget = config.context_budget;
+    subproject.attribution = config
+        .attribution
+        .as_deref()
+        .and_then(crate::reports::attribution::normalize);
+}
+
+/// One sub-project of the repository at `root`
+pub fn find(root: &Path, path: &str) -> Option<Subproject> {
+    let path = normalize(path);
+    list(root).into_iter().find(|s| s.path == path)
+}
+
+/// The sub-project a session is scoped to and its directory, if it still exists
+pub fn for_session(
+    app: &AppHandle,
+    session_id: &str,
+    worktree_id: &str,
+) -> Option<(Subproject, PathBuf)> {
+    let metadata = crate::chat::storage::load_metadata(app, session_id).ok()??;
+    let path = metadata.subproject?;
+    let data = super::storage::load_projects_data(app).ok()?;
+    let root = PathBuf::from(&data.find_worktree(worktree_id)?.path);
+    let subproject = find(&root, &path)?;
+    let dir = root.join(&subproject.path);
+    Some((subproject, dir))
+}
+
+/// Sub-projects of a project's repository
+#[tauri::command]
+pub async fn list_subprojects(
+    app: AppHandle,
+    project_id: String,
+) -> Result<Vec<Subproject>, String> {
+    let data = super::storage::load_projects_data(&app)?;
+    let project = data
+        .find_project(&project_id)
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    Ok(list(Path::new(&project.path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*", "web"));
+        assert!(wildcard_match("jean-*", "jean-core"));
+        assert!(!wildcard_match("jean-*", "core"));
+        assert!(wildcard_match("*-lib", "ui-lib"));
+    }
+
+    #[test]
+    fn test_detects_npm_and_cargo_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "package.json", r#"{ "workspaces": ["packages/*"] }"#);
+        write(
+            root,
+            "packages/web/package.json",
+            r#"{ "name": "@acme/web", "scripts": { "dev": "vite", "test": "vitest" } }"#,
+        );
+        write(root, "packages/api/package.json", r#"{ "name": "api" }"#);
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\n    \"crates/core\",\n]\n",
+        );
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"acme-core\"\n",
+        );
+        write(
+            root,
+            "jean.json",
+            r#"{ "subprojects": { "packages/web": { "context_budget": 50000, "attribution": "Web" } } }"#,
+        );
+
+        let subprojects = list(root);
+        let summary: Vec<_> = subprojects
+            .iter()
+            .map(|s| (s.path.as_str(), s.name.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("crates/core", "acme-core", SubprojectKind::Cargo),
+                ("packages/api", "api", SubprojectKind::Npm),
+                ("packages/web", "@acme/web", SubprojectKind::Npm),
+            ]
+        );
+        let web = &subprojects[2];
+        assert_eq!(web.commands.run.as_deref(), Some("npm run dev"));
+        assert_eq!(web.commands.build, None);
+        assert_eq!(web.context_budget, Some(50000));
+        assert_eq!(web.attribution.as_deref(), Some("Web"));
+        assert_eq!(
+            subprojects[0].commands.test.as_deref(),
+            Some("cargo test -p acme-core")
+        );
+    }
+
+    #[test]
+    fn test_detects_pnpm_and_go_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "pnpm-workspace.yaml",
+            "packages:\n  - 'apps/*'\n  - '!apps/legacy'\n",
+        );
+        write(root, "apps/site/package.json", "{}");
+        write(root, "go.work", "go 1.22\n\nuse (\n\t./svc/auth\n)\n");
+        fs::create_dir_all(root.join("svc/auth")).unwrap();
+
+        let paths: Vec<_> = list(root).into_iter().map(|s| (s.path, s.kind)).collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("apps/site".to_string(), SubprojectKind::Pnpm),
+                ("svc/auth".to_string(), SubprojectKind::Go),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_valid_path() {
+        assert!(is_valid_path("packages/web"));
+        assert!(!is_valid_path("../other"));
+        assert!(!is_valid_path("/abs"));
+        assert!(!is_valid_path("a//b"));
+    }
+}
@@ -0,0 +1,350 @@
+//! Language and toolchain detection
+//!
+//! When a project is registered, its manifests (Cargo.toml, package.json,
+//! go.mod, pyproject.toml) are looked for at the repository root and one
+//! directory down, and the result is stored on the project. It pre-fills
+//! verification commands, suggests which `install_tools` tools are worth
+//! having, and backs the `{languages}` and `{verify_commands}` prompt
+//! template variables.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::storage::{load_projects_data, save_projects_data};
+
+/// Directories never searched for manifests
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "dist", "build"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Rust,
+    JavaScript,
+    TypeScript,
+    Go,
+    Python,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Toolchain {
+    Cargo,
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+    Go,
+    Uv,
+    Poetry,
+    Pip,
+}
+
+/// A toolchain and where its manifest is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedToolchain {
+    pub toolchain: Toolchain,
+    /// Repo-relative directory of the manifest ("" for the root)
+    pub dir: String,
+}
+
+/// What a project is built with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectToolchain {
+    pub languages: Vec<Language>,
+    pub toolchains: Vec<DetectedToolchain>,
+    /// Commands that check a change (tests, lints, type checks), run from the root
+    pub verify_commands: Vec<String>,
+    /// `install_tools` tools useful for this project
+    pub suggested_tools: Vec<String>,
+    pub detected_at: u64,
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Package manager of a package.json directory, from `packageManager` or its lockfile
+fn js_package_manager(dir: &Path, package: &Value) -> Toolchain {
+    let declared = package
+        .get("packageManager")
+        .and_then(Value::as_str)
+        .and_then(|pm| pm.split('@').next());
+    match declared {
+        Some("pnpm") => return Toolchain::Pnpm,
+        Some("yarn") => return Toolchain::Yarn,
+        Some("bun") => return Toolchain::Bun,
+        Some("npm") => return Toolchain::Npm,
+        _ => {}
+    }
+    if dir.join("pnpm-lock.yaml").is_file() {
+        Toolchain::Pnpm
+    } else if dir.join("yarn.lock").is_file() {
+        Toolchain::Yarn
+    } else if dir.join("bun.lockb").is_file() || dir.join("bun.lock").is_file() {
+        Toolchain::Bun
+    } else {
+        Toolchain::Npm
+    }
+}
+
+fn python_toolchain(dir: &Path, pyproject: &str) -> Toolchain {
+    if dir.join("uv.lock").is_file() {
+        Toolchain::Uv
+    } else if dir.join("poetry.lock").is_file() || pyproject.contains("[tool.poetry]") {
+        Toolchain::Poetry
+    } else {
+        Toolchain::Pip
+    }
+}
+
+/// Run `command` in `dir` from the repository root
+fn in_dir(dir: &str, command: &str) -> String {
+    if dir.is_empty() {
+        command.to_string()
+    } else {
+        format!("cd {dir} && {command}")
+    }
+}
+
+/// Toolchains, languages and verify commands of one directory
+fn detect_dir(
+    root: &Path,
+    dir: &str,
+    languages: &mut Vec<Language>,
+    toolchains: &mut Vec<DetectedToolchain>,
+    verify_commands: &mut Vec<String>,
+) {
+    let path = root.join(dir);
+    let mut found = |toolchain: Toolchain| {
+        toolchains.push(DetectedToolchain {
+            toolchain,
+            dir: dir.to_string(),
+        });
+    };
+
+    if path.join("Cargo.toml").is_file() {
+        found(Toolchain::Cargo);
+        languages.push(Language::Rust);
+        verify_commands.push(in_dir(dir, "cargo clippy --all-targets"));
+        verify_commands.push(in_dir(dir, "cargo test"));
+    }
+
+    if let Some(package) = read_json(&path.join("package.json")) {
+        let manager = js_package_manager(&path, &package);
+        found(manager);
+        languages.push(Language::JavaScript);
+        let has_typescript = path.join("tsconfig.json").is_file()
+            || ["dependencies", "devDependencies"].iter().any(|section| {
+                package
+                    .get(section)
+                    .and_then(|d| d.get("typescript"))
+                    .is_some()
+            });
+        if has_typescript {
+            languages.push(Language::TypeScript);
+        }
+        let pm = serde_json::to_value(manager)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let scripts = package.get("scripts");
+        for script in ["typecheck", "lint", "test"] {
+            if scripts.and_then(|s| s.get(script)).is_some() {
+                verify_commands.push(in_dir(dir, &format!("{pm} run {script}")));
+            }
+        }
+    }
+
+    if path.join("go.mod").is_file() {
+        found(Toolchain::Go);
+        languages.push(Language::Go);
+        verify_commands.push(in_dir(dir, "go vet ./..."));
+        verify_commands.push(in_dir(dir, "go test ./..."));
+    }
+
+    let pyproject = fs::read_to_string(path.join("pyproject.toml")).ok();
+    let requirements = fs::read_to_string(path.join("requirements.txt")).ok();
+    if pyproject.is_some() || requirements.is_some() {
+        let pyproject = pyproject.unwrap_or_default();
+        let toolchain = python_toolchain(&path, &pyproject);
+        found(toolchain);
+        languages.push(Language::Python);
+        let uses_pytest =
+            pyproject.contains("pytest") || requirements.is_some_and(|r| r.contains("pytest"));
+        if uses_pytest {
+            let command = match toolchain {
+                Toolchain::Uv => "uv run pytest",
+                Toolchain::Poetry => "poetry run pytest",
+                _ => "python -m pytest",
+            };
+            verify_commands.push(in_dir(dir, command));
+        }
+    }
+}
+
+/// Detect the toolchains of the repository at `root`
+pub fn detect(root: &Path, now: u64) -> ProjectToolchain {
+    let mut languages = Vec::new();
+    let mut toolchains = Vec::new();
+    let mut verify_commands = Vec::new();
+
+    detect_dir(
+        root,
+        "",
+        &mut languages,
+        &mut toolchains,
+        &mut verify_commands,
+    );
+    let mut children: Vec<String> = fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()))
+        .collect();
+    children.sort();
+    for child in &children {
+        detect_dir(
+            root,
+            child,
+            &mut languages,
+            &mut toolchains,
+            &mut verify_commands,
+        );
+    }
+
+    languages.sort();
+    languages.dedup();
+
+    let mut suggested_tools = vec!["ripgrep".to_string(), "fd".to_string()];
+    if !languages.is_empty() {
+        suggested_tools.push("ast-grep".to_string());
+    }
+    if languages.contains(&Language::JavaScript) {
+        suggested_tools.push("node".to_string());
+    }
+    if languages.contains(&Language::Python) {
+        suggested_tools.push("python".to_string());
+    }
+
+    ProjectToolchain {
+        languages,
+        toolchains,
+        verify_commands,
+        suggested_tools,
+        detected_at: now,
+    }
+}
+
+/// A project's toolchain, detected and stored if it wasn't yet
+#[tauri::command]
+pub async fn get_project_toolchain(
+    app: AppHandle,
+    project_id: String,
+) -> Result<ProjectToolchain, String> {
+    let mut data = load_projects_data(&app)?;
+    let project = data
+        .find_project_mut(&project_id)
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    if let Some(toolchain) = &project.toolchain {
+        return Ok(toolchain.clone());
+    }
+    if project.is_folder {
+        return Err("Folders have no toolchain".to_string());
+    }
+    let toolchain = detect(Path::new(&project.path), crate::runtime::now());
+    project.toolchain = Some(toolchain.clone());
+    save_projects_data(&app, &data)?;
+    Ok(toolchain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_detects_tauri_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "package.json",
+            r#"{ "scripts": { "test": "vitest", "lint": "eslint ." }, "devDependencies": { "typescript": "5" } }"#,
+        );
+        write(root, "pnpm-lock.yaml", "");
+        write(root, "src-tauri/Cargo.toml", "[package]\nname = \"app\"\n");
+        write(root, "node_modules/dep/Cargo.toml", "");
+
+        let toolchain = detect(root, 7);
+        assert_eq!(
+            toolchain.languages,
+            vec![Language::Rust, Language::JavaScript, Language::TypeScript]
+        );
+        assert_eq!(
+            toolchain.toolchains,
+            vec![
+                DetectedToolchain {
+                    toolchain: Toolchain::Pnpm,
+                    dir: String::new(),
+                },
+                DetectedToolchain {
+                    toolchain: Toolchain::Cargo,
+                    dir: "src-tauri".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            toolchain.verify_commands,
+            vec![
+                "pnpm run lint",
+                "pnpm run test",
+                "cd src-tauri && cargo clippy --all-targets",
+                "cd src-tauri && cargo test",
+            ]
+        );
+        assert_eq!(
+            toolchain.suggested_tools,
+            ["ripgrep", "fd", "ast-grep", "node"]
+        );
+    }
+
+    #[test]
+    fn test_detects_go_and_python() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "go.mod", "module example.com/app\n");
+        write(
+            root,
+            "pyproject.toml",
+            "[project]\nname = \"app\"\n\n[dependency-groups]\ndev = [\"pytest\"]\n",
+        );
+        write(root, "uv.lock", "");
+
+        let toolchain = detect(root, 0);
+        assert_eq!(toolchain.languages, vec![Language::Go, Language::Python]);
+        assert_eq!(
+            toolchain.verify_commands,
+            vec!["go vet ./...", "go test ./...", "uv run pytest"]
+        );
+        assert!(toolchain.suggested_tools.contains(&"python".to_string()));
+    }
+
+    #[test]
+    fn test_empty_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let toolchain = detect(dir.path(), 0);
+        assert!(toolchain.languages.is_empty());
+        assert_eq!(toolchain.suggested_tools, ["ripgrep", "fd"]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Type of session (base branch or worktree)
@@ -28,6 +30,47 @@ pub enum MergeType {
 pub struct JeanConfig {
     #[serde(default)]
     pub scripts: JeanScripts,
+    #[serde(default)]
+    pub docs: JeanDocs,
+    #[serde(default)]
+    pub cli: JeanCli,
+    /// Provider profile sessions default to (a session's choice wins)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Tools sessions may use without asking, as Claude CLI rules
+    /// (e.g. `Bash(npm test:*)`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_tools: Vec<String>,
+    /// Monorepo packages by repo-relative path (adds to detected ones)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub subprojects: BTreeMap<String, SubprojectConfig>,
+}
+
+/// A sub-project entry in jean.json
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SubprojectConfig {
+    pub name: Option<String>,
+    /// Token budget for prompts of sessions scoped to it
+    pub context_budget: Option<usize>,
+    /// Client or cost center its runs are billed to
+    pub attribution: Option<String>,
+    #[serde(default)]
+    pub commands: SubprojectCommands,
+}
+
+/// Default commands of a sub-project, run in its directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct SubprojectCommands {
+    pub run: Option<String>,
+    pub test: Option<String>,
+    pub build: Option<String>,
+}
+
+/// CLI section of jean.json: tool versions the project is pinned to
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct JeanCli {
+    /// Claude CLI version (a project setting overrides it)
+    pub claude: Option<String>,
 }
 
 /// Scripts section of jean.json
@@ -39,6 +82,26 @@ pub struct JeanScripts {
     pub run: Option<String>,
 }
 
+/// Docs section of jean.json: where generated documentation drafts go
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct JeanDocs {
+    #[serde(default)]
+    pub changelog: Option<DocTarget>,
+    #[serde(default)]
+    pub adr: Option<DocTarget>,
+    #[serde(default)]
+    pub runbook: Option<DocTarget>,
+}
+
+/// Output path and template for one kind of generated document
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DocTarget {
+    /// Repo-relative path; may use {date} and {slug}
+    pub path: Option<String>,
+    /// Markdown template with {title}, {summary}, ... placeholders
+    pub template: Option<String>,
+}
+
 /// A git project that has been added to Jean, or a folder for organizing projects
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
@@ -70,6 +133,21 @@ pub struct Project {
     /// Custom system prompt appended to every session execution
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub custom_system_prompt: Option<String>,
+    /// Client or cost center usage is billed to (sessions can override)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    /// Claude CLI version sessions run with (overrides jean.json)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cli_version: Option<String>,
+    /// Languages and toolchains detected when the project was added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<super::toolchain::ProjectToolchain>,
+    /// Domains agents may not fetch from (see `crate::audit`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_domains: Vec<String>,
+    /// Extra CLI arguments and environment by backend (see `crate::chat::launch`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub launch_defaults: BTreeMap<String, crate::chat::launch::LaunchDefaults>,
 }
 
 /// A git worktree created for a project
@@ -0,0 +1,133 @@
+//! Tauri commands for the prompt library
+
+use std::path::PathBuf;
+
+use tauri::AppHandle;
+
+use super::community::{CommunityListing, CommunityUpdate, InstalledItem};
+use super::effectiveness::{record_template, template_effectiveness, TemplateEffectiveness};
+use super::shared::{load_library, sync_library, PromptLibrary};
+use super::variables::{list_variables, render, RenderedPrompt, TemplateContext, TemplateVariable};
+use crate::chat::run_log::load_session_messages;
+use crate::http_server::EmitExt;
+use crate::runtime::{PathProvider, SystemProcessRunner};
+
+/// Get local and shared prompts/profiles merged (from the last sync)
+#[tauri::command]
+pub async fn get_prompt_library(app: AppHandle) -> Result<PromptLibrary, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let root = app.app_data_dir()?;
+    Ok(load_library(&root, &prefs))
+}
+
+/// Pull the shared library repository now
+/// Returns the merged library with any conflicts between local and shared entries.
+#[tauri::command]
+pub async fn sync_shared_library(app: AppHandle) -> Result<PromptLibrary, String> {
+    log::trace!("Syncing shared prompt library");
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let root = app.app_data_dir()?;
+
+    let library = tauri::async_runtime::spawn_blocking(move || {
+        sync_library(&SystemProcessRunner, &root, &prefs)
+    })
+    .await
+    .map_err(|e| format!("Shared library sync failed: {e}"))??;
+
+    if let Err(e) = app.emit_all("prompts:library-synced", &library) {
+        log::error!("Failed to emit prompts:library-synced event: {e}");
+    }
+    Ok(library)
+}
+
+/// List the project-context variables prompts can use
+#[tauri::command]
+pub async fn list_template_variables() -> Vec<TemplateVariable> {
+    list_variables()
+}
+
+/// Fill a prompt template's project-context variables for a worktree
+/// `session_id` supplies session-based values like the last failing test output.
+/// With `template_name` (the library entry's name) the session remembers it
+/// used that template, for `get_template_effectiveness`.
+#[tauri::command]
+pub async fn render_prompt_template(
+    app: AppHandle,
+    template: String,
+    worktree_path: String,
+    session_id: Option<String>,
+    template_name: Option<String>,
+) -> Result<RenderedPrompt, String> {
+    let messages = match &session_id {
+        Some(id) => load_session_messages(&app, id)?,
+        None => Vec::new(),
+    };
+    if let (Some(id), Some(name)) = (&session_id, &template_name) {
+        if let Err(e) = record_template(&app, id, name) {
+            log::warn!("Failed to record template {name} for session {id}: {e}");
+        }
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let ctx = TemplateContext {
+            worktree_path: PathBuf::from(worktree_path),
+            messages,
+            runner: &SystemProcessRunner,
+        };
+        render(&template, &ctx)
+    })
+    .await
+    .map_err(|e| format!("Failed to render prompt template: {e}"))
+}
+
+/// Per-template session outcomes, ratings and labels
+#[tauri::command]
+pub async fn get_template_effectiveness(
+    app: AppHandle,
+) -> Result<Vec<TemplateEffectiveness>, String> {
+    tauri::async_runtime::spawn_blocking(move || template_effectiveness(&app))
+        .await
+        .map_err(|e| format!("Failed to compute template effectiveness: {e}"))?
+}
+
+/// Items in the configured community index, with what's installed
+#[tauri::command]
+pub async fn browse_community_library(app: AppHandle) -> Result<Vec<CommunityListing>, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let root = app.app_data_dir()?;
+    super::community::browse(&root, &prefs).await
+}
+
+/// Download a community item, verify its signature and install it into the
+/// local library (also used to update an installed item). With `overwrite`
+/// it may replace a CLI profile that wasn't installed from the index.
+#[tauri::command]
+pub async fn install_community_item(
+    app: AppHandle,
+    item_id: String,
+    overwrite: Option<bool>,
+) -> Result<InstalledItem, String> {
+    let mut prefs = crate::load_preferences(app.clone()).await?;
+    let root = app.app_data_dir()?;
+    let installs = super::community::load_installs(&root);
+    let installed =
+        super::community::install(&mut prefs, &installs, &item_id, overwrite.unwrap_or(false))
+            .await?;
+    crate::save_preferences(app.clone(), prefs).await?;
+    super::community::record_install(&root, &installed)?;
+    Ok(installed)
+}
+
+/// Installed community items and where they came from
+#[tauri::command]
+pub async fn list_community_installs(app: AppHandle) -> Result<Vec<InstalledItem>, String> {
+    Ok(super::community::load_installs(&app.app_data_dir()?))
+}
+
+/// Installed community items with a newer version in the index
+#[tauri::command]
+pub async fn check_community_updates(app: AppHandle) -> Result<Vec<CommunityUpdate>, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let root = app.app_data_dir()?;
+    super::community::check_updates(&root, &prefs).await
+}
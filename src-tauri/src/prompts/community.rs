@@ -0,0 +1,651 @@
+//! Community library: signed prompts and profiles from a public index
+//!
+//! Opt-in: set `community_index_url` to an index and
+//! `community_index_public_key` to the Ed25519 key (base64) its publisher
+//! signs with. Items are browsed from the index, and installing one
+//! downloads its content, checks the signature and writes it into the local
+//! library (the magic prompt of the same name, or a custom CLI profile).
+//! Every install is recorded with its provenance in `community-installs.json`
+//! in app data, which is what the update check compares the index against.
+//!
+//! Index format:
+//! ```json
+//! { "items": [{
+//!     "id": "acme/strict-review", "kind": "prompt", "name": "code_review",
+//!     "title": "Strict review", "description": "...", "author": "Acme",
+//!     "version": "1.2.0", "url": "prompts/strict-review.md",
+//!     "signature": "<base64 Ed25519 signature>"
+//! }] }
+//! ```
+//! `url` may be relative to the index. The index itself isn't signed, so
+//! the signature covers everything that decides where content goes:
+//! `"{id}\n{kind}\n{name}\n{version}\n{content}"`. A signed file can't be
+//! passed off as another item, version or install target. Installing never
+//! replaces a CLI profile that didn't come from the index unless asked to.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::shared::{local_entries, LibraryKind};
+use crate::providers::is_newer_version;
+use crate::runtime::now;
+use crate::{AppPreferences, CustomCliProfile};
+
+/// Provenance of installed items (in app data)
+const INSTALLS_FILE: &str = "community-installs.json";
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Largest index and item Jean will download
+const MAX_INDEX_BYTES: usize = 4 * 1024 * 1024;
+const MAX_ITEM_BYTES: usize = 256 * 1024;
+
+/// An item listed in the index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommunityItem {
+    /// Unique within the index, e.g. "acme/strict-review"
+    pub id: String,
+    pub kind: LibraryKind,
+    /// Magic prompt key or CLI profile name it installs as
+    pub name: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+    pub version: String,
+    /// Content URL, absolute or relative to the index
+    pub url: String,
+    /// Base64 Ed25519 signature of `"{id}\n{kind}\n{name}\n{version}\n{content}"`
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommunityIndex {
+    #[serde(default)]
+    pub items: Vec<CommunityItem>,
+}
+
+/// Where an installed item came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledItem {
+    pub id: String,
+    pub kind: LibraryKind,
+    pub name: String,
+    pub version: String,
+    pub author: String,
+    pub index_url: String,
+    pub source_url: String,
+    /// SHA-256 of the signing key (hex), to tell publishers apart
+    pub key_fingerprint: String,
+    /// SHA-256 of the installed content (hex), to spot local edits
+    pub content_sha256: String,
+    pub installed_at: u64,
+}
+
+/// An index item with its install state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommunityListing {
+    #[serde(flatten)]
+    pub item: CommunityItem,
+    pub installed_version: Option<String>,
+    pub update_available: bool,
+}
+
+/// A newer version of an installed item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommunityUpdate {
+    pub id: String,
+    pub kind: LibraryKind,
+    pub name: String,
+    pub installed_version: String,
+    pub latest_version: String,
+    /// The installed copy was edited since (updating replaces the edits)
+    pub modified: bool,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn configured_index(prefs: &AppPreferences) -> Result<(String, Vec<u8>), String> {
+    let url = prefs
+        .community_index_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+        .ok_or("No community index configured")?;
+    let key = prefs
+        .community_index_public_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .ok_or("The community index needs a public key to verify items with")?;
+    Ok((url.to_string(), decode_key(key)?))
+}
+
+fn decode_key(key: &str) -> Result<Vec<u8>, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(key)
+        .map_err(|e| format!("Invalid community index public key: {e}"))?;
+    if bytes.len() != 32 {
+        return Err(format!(
+            "Invalid community index public key: expected 32 bytes, got {}",
+            bytes.len()
+        ));
+    }
+    Ok(bytes)
+}
+
+/// What the publisher signs for an item with `content`
+pub fn signed_message(item: &CommunityItem, content: &str) -> String {
+    let kind = match item.kind {
+        LibraryKind::Prompt => "prompt",
+        LibraryKind::Profile => "profile",
+    };
+    format!(
+        "{}\n{kind}\n{}\n{}\n{content}",
+        item.id, item.name, item.version
+    )
+}
+
+/// Check an item's signature over its content
+pub fn verify(public_key: &[u8], item: &CommunityItem, content: &str) -> Result<(), String> {
+    let signature = base64::engine::general_purpose::STANDARD
+        .decode(item.signature.trim())
+        .map_err(|e| format!("Invalid signature on {}: {e}", item.id))?;
+    let message = signed_message(item, content);
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(message.as_bytes(), &signature)
+        .map_err(|_| format!("Signature check failed for {} {}", item.id, item.version))
+}
+
+pub fn load_installs(root: &Path) -> Vec<InstalledItem> {
+    fs::read_to_string(root.join(INSTALLS_FILE))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Record an install, replacing any earlier one of the same item
+pub fn record_install(root: &Path, installed: &InstalledItem) -> Result<(), String> {
+    let mut installs = load_installs(root);
+    installs.retain(|i| i.id != installed.id);
+    installs.push(installed.clone());
+    let content = serde_json::to_string_pretty(&installs)
+        .map_err(|e| format!("Failed to serialize community installs: {e}"))?;
+    fs::write(root.join(INSTALLS_FILE), content)
+        .map_err(|e| format!("Failed to save community installs: {e}"))
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))
+}
+
+/// GET `url` as text, giving up as soon as it's known to exceed `limit` bytes
+async fn get_text(client: &reqwest::Client, url: &str, limit: usize) -> Result<String, String> {
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch {url}: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("{url} returned status: {}", response.status()));
+    }
+    let too_large = || format!("{url} is larger than {limit} bytes");
+    if response
+        .content_length()
+        .is_some_and(|len| len > limit as u64)
+    {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read {url}: {e}"))?
+    {
+        if bytes.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    String::from_utf8(bytes).map_err(|_| format!("{url} isn't UTF-8 text"))
+}
+
+/// Fetch and parse the index
+pub async fn fetch_index(index_url: &str) -> Result<CommunityIndex, String> {
+    let text = get_text(&client()?, index_url, MAX_INDEX_BYTES).await?;
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse community index: {e}"))
+}
+
+/// Download an item's content and check its signature, returning the
+/// content and the URL it came from
+pub async fn fetch_verified(
+    index_url: &str,
+    public_key: &[u8],
+    item: &CommunityItem,
+) -> Result<(String, String), String> {
+    let source_url = reqwest::Url::parse(index_url)
+        .and_then(|base| base.join(&item.url))
+        .map_err(|e| format!("Invalid URL for {}: {e}", item.id))?
+        .to_string();
+    let content = get_text(&client()?, &source_url, MAX_ITEM_BYTES).await?;
+    verify(public_key, item, &content)?;
+    Ok((content, source_url))
+}
+
+/// Write verified content into the local library. An existing CLI profile
+/// of the same name is only replaced with `replace_profile`.
+pub fn apply(
+    prefs: &mut AppPreferences,
+    kind: LibraryKind,
+    name: &str,
+    content: &str,
+    replace_profile: bool,
+) -> Result<(), String> {
+    match kind {
+        LibraryKind::Prompt => {
+            let mut prompts = serde_json::to_value(&prefs.magic_prompts)
+                .map_err(|e| format!("Failed to read magic prompts: {e}"))?;
+            let slot = prompts
+                .get_mut(name)
+                .ok_or_else(|| format!("Jean has no prompt named {name}"))?;
+            *slot = serde_json::Value::String(content.to_string());
+            prefs.magic_prompts = serde_json::from_value(prompts)
+                .map_err(|e| format!("Failed to update magic prompts: {e}"))?;
+        }
+        LibraryKind::Profile => {
+            serde_json::from_str::<serde_json::Value>(content)
+                .map_err(|e| format!("Profile {name} isn't valid JSON: {e}"))?;
+            match prefs
+                .custom_cli_profiles
+                .iter_mut()
+                .find(|p| p.name == name)
+            {
+                Some(profile) if replace_profile => profile.settings_json = content.to_string(),
+                Some(_) => {
+                    return Err(format!(
+                        "A CLI profile named {name} already exists and wasn't installed from the community index"
+                    ))
+                }
+                None => prefs.custom_cli_profiles.push(CustomCliProfile {
+                    name: name.to_string(),
+                    settings_json: content.to_string(),
+                    key_expires_at: None,
+                    rotate_by: None,
+                }),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Index items with what's installed
+pub fn listings(index: CommunityIndex, installs: &[InstalledItem]) -> Vec<CommunityListing> {
+    index
+        .items
+        .into_iter()
+        .map(|item| {
+            let installed_version = installs
+                .iter()
+                .find(|i| i.id == item.id)
+                .map(|i| i.version.clone());
+            let update_available = installed_version
+                .as_deref()
+                .is_some_and(|v| is_newer_version(v, &item.version));
+            CommunityListing {
+                item,
+                installed_version,
+                update_available,
+            }
+        })
+        .collect()
+}
+
+/// Installed items with a newer version in the index
+pub fn updates(
+    index: &CommunityIndex,
+    installs: &[InstalledItem],
+    prefs: &AppPreferences,
+) -> Vec<CommunityUpdate> {
+    let local = local_entries(prefs);
+    installs
+        .iter()
+        .filter_map(|installed| {
+            let item = index.items.iter().find(|i| i.id == installed.id)?;
+            if !is_newer_version(&installed.version, &item.version) {
+                return None;
+            }
+            let modified = local
+                .iter()
+                .find(|e| e.kind == installed.kind && e.name == installed.name)
+                .is_none_or(|e| sha256_hex(e.content.as_bytes()) != installed.content_sha256);
+            Some(CommunityUpdate {
+                id: installed.id.clone(),
+                kind: installed.kind,
+                name: installed.name.clone(),
+                installed_version: installed.version.clone(),
+                latest_version: item.version.clone(),
+                modified,
+            })
+        })
+        .collect()
+}
+
+/// Browse the configured index
+pub async fn browse(root: &Path, prefs: &AppPreferences) -> Result<Vec<CommunityListing>, String> {
+    let (index_url, _) = configured_index(prefs)?;
+    let index = fetch_index(&index_url).await?;
+    Ok(listings(index, &load_installs(root)))
+}
+
+/// Download, verify and apply an item to `prefs`, returning its provenance
+/// (the caller saves the preferences, then records the install). A CLI
+/// profile not installed from the index (per `installs`) is only replaced
+/// with `overwrite`.
+pub async fn install(
+    prefs: &mut AppPreferences,
+    installs: &[InstalledItem],
+    item_id: &str,
+    overwrite: bool,
+) -> Result<InstalledItem, String> {
+    let (index_url, public_key) = configured_index(prefs)?;
+    let index = fetch_index(&index_url).await?;
+    let item = index
+        .items
+        .into_iter()
+        .find(|i| i.id == item_id)
+        .ok_or_else(|| format!("{item_id} isn't in the community index"))?;
+    let (content, source_url) = fetch_verified(&index_url, &public_key, &item).await?;
+    let from_index = installs
+        .iter()
+        .any(|i| i.kind == item.kind && i.name == item.name);
+    apply(
+        prefs,
+        item.kind,
+        &item.name,
+        &content,
+        overwrite || from_index,
+    )?;
+    log::info!(
+        "Installed community {:?} {} {} from {source_url}",
+        item.kind,
+        item.id,
+        item.version
+    );
+    Ok(InstalledItem {
+        id: item.id,
+        kind: item.kind,
+        name: item.name,
+        version: item.version,
+        author: item.author,
+        index_url,
+        source_url,
+        key_fingerprint: sha256_hex(&public_key),
+        content_sha256: sha256_hex(content.as_bytes()),
+        installed_at: now(),
+    })
+}
+
+/// Check installed items against the index
+pub async fn check_updates(
+    root: &Path,
+    prefs: &AppPreferences,
+) -> Result<Vec<CommunityUpdate>, String> {
+    let installs = load_installs(root);
+    if installs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (index_url, _) = configured_index(prefs)?;
+    let index = fetch_index(&index_url).await?;
+    Ok(updates(&index, &installs, prefs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture_server::{FixtureResponse, FixtureServer};
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    fn signed_item(
+        keys: &Ed25519KeyPair,
+        kind: LibraryKind,
+        name: &str,
+        content: &str,
+    ) -> CommunityItem {
+        let id = format!("acme/{name}");
+        let version = "1.0.0".to_string();
+        let mut item = CommunityItem {
+            id,
+            kind,
+            name: name.to_string(),
+            title: String::new(),
+            description: String::new(),
+            author: "Acme".to_string(),
+            version,
+            url: format!("items/{name}"),
+            signature: String::new(),
+        };
+        let signature = keys.sign(signed_message(&item, content).as_bytes());
+        item.signature = base64::engine::general_purpose::STANDARD.encode(signature.as_ref());
+        item
+    }
+
+    #[test]
+    fn test_verify() {
+        let keys = key_pair();
+        let public_key = keys.public_key().as_ref();
+        let item = signed_item(&keys, LibraryKind::Prompt, "code_review", "Review strictly");
+        assert!(verify(public_key, &item, "Review strictly").is_ok());
+        assert!(verify(public_key, &item, "Review loosely").is_err());
+
+        // The signature is bound to the version
+        let relabeled = CommunityItem {
+            version: "2.0.0".to_string(),
+            ..item.clone()
+        };
+        assert!(verify(public_key, &relabeled, "Review strictly").is_err());
+        // ...and to where it installs, since the index isn't signed
+        let retargeted = CommunityItem {
+            name: "commit_message".to_string(),
+            ..item.clone()
+        };
+        assert!(verify(public_key, &retargeted, "Review strictly").is_err());
+        let rekinded = CommunityItem {
+            kind: LibraryKind::Profile,
+            ..item.clone()
+        };
+        assert!(verify(public_key, &rekinded, "Review strictly").is_err());
+        assert!(verify(key_pair().public_key().as_ref(), &item, "Review strictly").is_err());
+        assert!(decode_key("c2hvcnQ=").is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut prefs = AppPreferences::default();
+        apply(
+            &mut prefs,
+            LibraryKind::Prompt,
+            "code_review",
+            "Be strict",
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            prefs.magic_prompts.code_review.as_deref(),
+            Some("Be strict")
+        );
+        assert_eq!(
+            apply(&mut prefs, LibraryKind::Prompt, "haiku", "x", false).unwrap_err(),
+            "Jean has no prompt named haiku"
+        );
+
+        apply(
+            &mut prefs,
+            LibraryKind::Profile,
+            "router",
+            r#"{"env":{}}"#,
+            false,
+        )
+        .unwrap();
+        // An existing profile is only replaced when asked to
+        assert!(apply(
+            &mut prefs,
+            LibraryKind::Profile,
+            "router",
+            r#"{"env":{"B":"2"}}"#,
+            false,
+        )
+        .is_err());
+        apply(
+            &mut prefs,
+            LibraryKind::Profile,
+            "router",
+            r#"{"env":{"A":"1"}}"#,
+            true,
+        )
+        .unwrap();
+        assert_eq!(prefs.custom_cli_profiles.len(), 1);
+        assert_eq!(
+            prefs.custom_cli_profiles[0].settings_json,
+            r#"{"env":{"A":"1"}}"#
+        );
+        assert!(apply(&mut prefs, LibraryKind::Profile, "broken", "{", false).is_err());
+    }
+
+    #[test]
+    fn test_install_and_update_check() {
+        let keys = key_pair();
+        let good = signed_item(&keys, LibraryKind::Profile, "router", r#"{"env":{}}"#);
+        let tampered = signed_item(&keys, LibraryKind::Prompt, "code_review", "original");
+        let index = CommunityIndex {
+            items: vec![good.clone(), tampered.clone()],
+        };
+        let server = FixtureServer::start(vec![
+            (
+                "/index.json".to_string(),
+                FixtureResponse::json(serde_json::to_string(&index).unwrap()),
+            ),
+            (
+                "/items/router".to_string(),
+                FixtureResponse::json(r#"{"env":{}}"#),
+            ),
+            (
+                "/items/code_review".to_string(),
+                FixtureResponse::json("something else"),
+            ),
+        ]);
+        let mut prefs = AppPreferences {
+            community_index_url: Some(server.url("/index.json")),
+            community_index_public_key: Some(
+                base64::engine::general_purpose::STANDARD.encode(keys.public_key().as_ref()),
+            ),
+            ..AppPreferences::default()
+        };
+
+        let (installed, rejected, reinstalled) = tauri::async_runtime::block_on(async {
+            let installed = install(&mut prefs, &[], &good.id, false).await.unwrap();
+            let rejected = install(&mut prefs, &[], &tampered.id, false).await;
+            // A profile that came from the index is replaced without asking
+            let reinstalled = install(
+                &mut prefs,
+                std::slice::from_ref(&installed),
+                &good.id,
+                false,
+            )
+            .await;
+            (installed, rejected, reinstalled)
+        });
+        assert!(reinstalled.is_ok());
+        assert_eq!(installed.source_url, server.url("/items/router"));
+        assert_eq!(installed.author, "Acme");
+        assert_eq!(prefs.custom_cli_profiles[0].name, "router");
+        assert_eq!(
+            rejected.unwrap_err(),
+            "Signature check failed for acme/code_review 1.0.0"
+        );
+        assert_eq!(prefs.magic_prompts.code_review, None);
+
+        // A profile the user made is only replaced with `overwrite`
+        let mut own = AppPreferences {
+            custom_cli_profiles: vec![CustomCliProfile {
+                name: "router".to_string(),
+                settings_json: r#"{"model":"mine"}"#.to_string(),
+                key_expires_at: None,
+                rotate_by: None,
+            }],
+            ..prefs.clone()
+        };
+        let kept = tauri::async_runtime::block_on(install(&mut own, &[], &good.id, false));
+        assert!(kept.unwrap_err().contains("already exists"));
+        assert_eq!(
+            own.custom_cli_profiles[0].settings_json,
+            r#"{"model":"mine"}"#
+        );
+        tauri::async_runtime::block_on(install(&mut own, &[], &good.id, true)).unwrap();
+        assert_eq!(own.custom_cli_profiles[0].settings_json, r#"{"env":{}}"#);
+
+        // Downloads stop at the size limit
+        let too_large = tauri::async_runtime::block_on(get_text(
+            &client().unwrap(),
+            &server.url("/items/router"),
+            4,
+        ));
+        assert!(too_large.unwrap_err().ends_with("is larger than 4 bytes"));
+
+        // A newer version in the index is an update; local edits are flagged
+        let mut newer = index.clone();
+        newer.items[0].version = "1.1.0".to_string();
+        let found = updates(&newer, std::slice::from_ref(&installed), &prefs);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].latest_version, "1.1.0");
+        assert!(!found[0].modified);
+        prefs.custom_cli_profiles[0].settings_json = "{}".to_string();
+        assert!(updates(&newer, std::slice::from_ref(&installed), &prefs)[0].modified);
+        assert!(updates(&index, std::slice::from_ref(&installed), &prefs).is_empty());
+
+        let listed = listings(newer, &[installed]);
+        assert_eq!(listed[0].installed_version.as_deref(), Some("1.0.0"));
+        assert!(listed[0].update_available);
+        assert_eq!(listed[1].installed_version, None);
+    }
+
+    #[test]
+    fn test_record_install_replaces_earlier() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut item = InstalledItem {
+            id: "acme/router".to_string(),
+            kind: LibraryKind::Profile,
+            name: "router".to_string(),
+            version: "1.0.0".to_string(),
+            author: String::new(),
+            index_url: String::new(),
+            source_url: String::new(),
+            key_fingerprint: String::new(),
+            content_sha256: String::new(),
+            installed_at: 0,
+        };
+        record_install(dir.path(), &item).unwrap();
+        item.version = "1.1.0".to_string();
+        record_install(dir.path(), &item).unwrap();
+        assert_eq!(load_installs(dir.path()), vec![item]);
+    }
+}
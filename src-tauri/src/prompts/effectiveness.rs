@@ -0,0 +1,250 @@
+//! How well each prompt template works
+//!
+//! Sessions remember the library prompts rendered for them
+//! (`SessionMetadata::templates`). `get_template_effectiveness` groups
+//! sessions by template and reports how their runs ended, how often the
+//! agent's tests failed at the end of the session, and the reviewer ratings
+//! and labels left on their messages, e.g. "this template's sessions fail
+//! verification 40% of the time".
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat::annotations::Rating;
+use crate::chat::compare::test_runs;
+use crate::chat::storage::{list_all_session_ids, load_metadata, with_metadata_mut};
+use crate::chat::types::{RunStatus, SessionMetadata};
+use crate::runtime::PathProvider;
+
+/// Statistics for one template
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplateEffectiveness {
+    pub template: String,
+    pub sessions: u32,
+    pub runs: u32,
+    pub completed_runs: u32,
+    pub cancelled_runs: u32,
+    /// Crashed or interrupted
+    pub failed_runs: u32,
+    /// Sessions whose last test run passed
+    pub verified: u32,
+    /// Sessions whose last test run failed
+    pub failed_verification: u32,
+    /// Sessions without a conclusive test run
+    pub unverified: u32,
+    /// Share of verified-or-failed sessions that failed (None if none ran tests)
+    pub verification_failure_rate: Option<f64>,
+    pub thumbs_up: u32,
+    pub thumbs_down: u32,
+    /// Annotation label counts
+    pub labels: BTreeMap<String, u32>,
+}
+
+/// Remember that a library prompt was rendered for a session
+pub fn record_template(
+    app: &impl PathProvider,
+    session_id: &str,
+    template: &str,
+) -> Result<(), String> {
+    let template = template.trim();
+    if template.is_empty() {
+        return Ok(());
+    }
+    let Some(metadata) = load_metadata(app, session_id)? else {
+        log::trace!("Not recording template {template} for unsaved session {session_id}");
+        return Ok(());
+    };
+    with_metadata_mut(
+        app,
+        session_id,
+        &metadata.worktree_id,
+        &metadata.name,
+        metadata.order,
+        |metadata| {
+            if !metadata.templates.iter().any(|t| t == template) {
+                metadata.templates.push(template.to_string());
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Group sessions (with their last test result) by template
+pub fn summarize(sessions: &[(SessionMetadata, Option<bool>)]) -> Vec<TemplateEffectiveness> {
+    let mut stats: BTreeMap<&str, TemplateEffectiveness> = BTreeMap::new();
+    for (metadata, verified) in sessions {
+        for template in &metadata.templates {
+            let entry = stats
+                .entry(template.as_str())
+                .or_insert_with(|| TemplateEffectiveness {
+                    template: template.clone(),
+                    ..Default::default()
+                });
+            entry.sessions += 1;
+            for run in &metadata.runs {
+                entry.runs += 1;
+                match run.status {
+                    RunStatus::Completed => entry.completed_runs += 1,
+                    RunStatus::Cancelled => entry.cancelled_runs += 1,
+                    RunStatus::Crashed | RunStatus::Interrupted => entry.failed_runs += 1,
+                    RunStatus::Running | RunStatus::Resumable => {}
+                }
+            }
+            match verified {
+                Some(true) => entry.verified += 1,
+                Some(false) => entry.failed_verification += 1,
+                None => entry.unverified += 1,
+            }
+            for annotation in &metadata.annotations {
+                match annotation.rating {
+                    Some(Rating::Up) => entry.thumbs_up += 1,
+                    Some(Rating::Down) => entry.thumbs_down += 1,
+                    None => {}
+                }
+                for label in &annotation.labels {
+                    *entry.labels.entry(label.clone()).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    let mut stats: Vec<_> = stats
+        .into_values()
+        .map(|mut entry| {
+            let tested = entry.verified + entry.failed_verification;
+            entry.verification_failure_rate =
+                (tested > 0).then(|| entry.failed_verification as f64 / tested as f64);
+            entry
+        })
+        .collect();
+    stats.sort_by(|a, b| b.sessions.cmp(&a.sessions));
+    stats
+}
+
+/// Effectiveness of every template used by a saved session
+pub fn template_effectiveness(
+    app: &impl PathProvider,
+) -> Result<Vec<TemplateEffectiveness>, String> {
+    let mut sessions = Vec::new();
+    for session_id in list_all_session_ids(app)? {
+        let metadata = match load_metadata(app, &session_id) {
+            Ok(Some(metadata)) if !metadata.templates.is_empty() => metadata,
+            Ok(_) => continue,
+            Err(e) => {
+                log::warn!("Skipping session {session_id} in template stats: {e}");
+                continue;
+            }
+        };
+        let verified = test_runs(app, &session_id)
+            .ok()
+            .and_then(|tests| tests.last().and_then(|t| t.passed));
+        sessions.push((metadata, verified));
+    }
+    Ok(summarize(&sessions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::annotations::MessageAnnotation;
+    use crate::chat::types::RunEntry;
+    use crate::test_support::runtime::TempPaths;
+
+    fn run(status: RunStatus) -> RunEntry {
+        RunEntry {
+            run_id: "r1".to_string(),
+            user_message_id: "u1".to_string(),
+            user_message: "Fix the bug".to_string(),
+            model: None,
+            execution_mode: None,
+            thinking_level: None,
+            effort_level: None,
+            started_at: 0,
+            ended_at: Some(1),
+            status,
+            assistant_message_id: Some("a1".to_string()),
+            cancelled: false,
+            recovered: false,
+            claude_session_id: None,
+            pid: None,
+            usage: None,
+        }
+    }
+
+    fn session(templates: &[&str], runs: Vec<RunEntry>) -> SessionMetadata {
+        let mut metadata =
+            SessionMetadata::new("s1".to_string(), "w1".to_string(), "S".to_string(), 0);
+        metadata.templates = templates.iter().map(|t| t.to_string()).collect();
+        metadata.runs = runs;
+        metadata
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut reviewed = session(&["fix_bug"], vec![run(RunStatus::Completed)]);
+        reviewed.annotations.push(MessageAnnotation {
+            id: "n1".to_string(),
+            message_id: "a1".to_string(),
+            note: None,
+            rating: Some(Rating::Down),
+            labels: vec!["hallucination".to_string()],
+            created_at: 0,
+            updated_at: 0,
+        });
+        let sessions = vec![
+            (reviewed, Some(false)),
+            (
+                session(&["fix_bug", "review"], vec![run(RunStatus::Crashed)]),
+                Some(true),
+            ),
+            (session(&["fix_bug"], vec![run(RunStatus::Cancelled)]), None),
+            (session(&[], vec![run(RunStatus::Completed)]), Some(true)),
+        ];
+
+        let stats = summarize(&sessions);
+        assert_eq!(stats.len(), 2);
+        let fix_bug = &stats[0];
+        assert_eq!(fix_bug.template, "fix_bug");
+        assert_eq!(fix_bug.sessions, 3);
+        assert_eq!(
+            (
+                fix_bug.completed_runs,
+                fix_bug.cancelled_runs,
+                fix_bug.failed_runs
+            ),
+            (1, 1, 1)
+        );
+        assert_eq!(
+            (
+                fix_bug.verified,
+                fix_bug.failed_verification,
+                fix_bug.unverified
+            ),
+            (1, 1, 1)
+        );
+        assert_eq!(fix_bug.verification_failure_rate, Some(0.5));
+        assert_eq!(fix_bug.thumbs_down, 1);
+        assert_eq!(fix_bug.labels.get("hallucination"), Some(&1));
+
+        let review = &stats[1];
+        assert_eq!(review.sessions, 1);
+        assert_eq!(review.verification_failure_rate, Some(0.0));
+    }
+
+    #[test]
+    fn test_record_template() {
+        let paths = TempPaths::new();
+        // Sessions without saved metadata are skipped
+        record_template(&paths, "s1", "fix_bug").unwrap();
+        assert!(load_metadata(&paths, "s1").unwrap().is_none());
+
+        with_metadata_mut(&paths, "s1", "w1", "S", 0, |_| Ok(())).unwrap();
+        record_template(&paths, "s1", "fix_bug").unwrap();
+        record_template(&paths, "s1", " fix_bug ").unwrap();
+        record_template(&paths, "s1", "").unwrap();
+        let metadata = load_metadata(&paths, "s1").unwrap().unwrap();
+        assert_eq!(metadata.templates, ["fix_bug"]);
+        assert_eq!(template_effectiveness(&paths).unwrap()[0].sessions, 1);
+    }
+}
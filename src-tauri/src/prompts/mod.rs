@@ -0,0 +1,13 @@
+//! Prompt library
+//!
+//! Local prompts and CLI profiles live in preferences; `shared` merges in a
+//! team-wide set kept in a Git repository, `community` installs signed
+//! entries from a public index, `variables` fills templates with live
+//! project data, and `effectiveness` reports how sessions started from each
+//! template went.
+
+pub mod commands;
+pub mod community;
+pub mod effectiveness;
+pub mod shared;
+pub mod variables;
@@ -0,0 +1,466 @@
+//! Shared prompt library backed by a Git repository
+//!
+//! Teams can point `shared_library_repo` at a Git repository holding a
+//! blessed set of prompts and CLI profiles. Jean keeps a shallow clone under
+//! `shared-library/` in app data and refreshes it on a schedule
+//! (`shared_library_sync_hours`) or on demand with `sync_shared_library`.
+//!
+//! Repository layout:
+//! - `prompts/<name>.md`: a prompt. Names matching a magic prompt key
+//!   (e.g. `code_review`) replace that magic prompt.
+//! - `profiles/<name>.json`: a custom CLI profile (the file is the profile's
+//!   settings JSON).
+//!
+//! Shared entries are merged with the user's local ones and are read-only.
+//! Where both define the same entry the shared one wins, and the local
+//! version is reported as a conflict.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::background_tasks::supervisor::supervise;
+use crate::http_server::EmitExt;
+use crate::platform::silent_command;
+use crate::runtime::{now, PathProvider, ProcessRunner, SystemProcessRunner};
+use crate::AppPreferences;
+
+/// Clone location (in app data)
+const SHARED_LIBRARY_DIR: &str = "shared-library";
+
+/// Last sync result (in app data, outside the clone so it stays clean)
+const SYNC_STATE_FILE: &str = "shared-library-sync.json";
+
+/// How often the scheduler checks whether a sync is due
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryKind {
+    Prompt,
+    Profile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibrarySource {
+    Local,
+    Shared,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    pub kind: LibraryKind,
+    pub name: String,
+    /// Prompt text, or a profile's settings JSON
+    pub content: String,
+    pub source: LibrarySource,
+    /// Shared entries can't be edited locally
+    pub read_only: bool,
+}
+
+/// A local entry shadowed by a different shared one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryConflict {
+    pub kind: LibraryKind,
+    pub name: String,
+    pub local_content: String,
+    pub shared_content: String,
+}
+
+/// Recorded after every sync attempt
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    pub repo_url: Option<String>,
+    /// Commit of the last successful sync
+    pub commit: Option<String>,
+    /// Unix timestamp of the last successful sync
+    pub synced_at: Option<u64>,
+    /// Error from the last attempt (None if it succeeded)
+    pub error: Option<String>,
+}
+
+/// The merged library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptLibrary {
+    pub sync: SyncState,
+    pub entries: Vec<LibraryEntry>,
+    pub conflicts: Vec<LibraryConflict>,
+    /// Shared files that couldn't be read
+    pub errors: Vec<String>,
+}
+
+fn configured_repo(prefs: &AppPreferences) -> Option<&str> {
+    prefs
+        .shared_library_repo
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// The user's own magic prompts and CLI profiles
+pub fn local_entries(prefs: &AppPreferences) -> Vec<LibraryEntry> {
+    let mut entries = Vec::new();
+    if let Ok(serde_json::Value::Object(prompts)) = serde_json::to_value(&prefs.magic_prompts) {
+        for (name, value) in prompts {
+            if let Some(content) = value.as_str() {
+                entries.push(LibraryEntry {
+                    kind: LibraryKind::Prompt,
+                    name,
+                    content: content.to_string(),
+                    source: LibrarySource::Local,
+                    read_only: false,
+                });
+            }
+        }
+    }
+    entries.extend(prefs.custom_cli_profiles.iter().map(|p| LibraryEntry {
+        kind: LibraryKind::Profile,
+        name: p.name.clone(),
+        content: p.settings_json.clone(),
+        source: LibrarySource::Local,
+        read_only: false,
+    }));
+    entries
+}
+
+/// Read prompts and profiles from a checkout of the shared repository
+pub fn read_shared_entries(dir: &Path) -> (Vec<LibraryEntry>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+
+    for (subdir, kind, extensions) in [
+        ("prompts", LibraryKind::Prompt, &["md", "txt"][..]),
+        ("profiles", LibraryKind::Profile, &["json"][..]),
+    ] {
+        let Ok(files) = fs::read_dir(dir.join(subdir)) else {
+            continue;
+        };
+        for file in files.flatten() {
+            let path = file.path();
+            let (Some(name), Some(ext)) = (path.file_stem(), path.extension()) else {
+                continue;
+            };
+            if !extensions.iter().any(|e| ext == *e) {
+                continue;
+            }
+            let relative = format!("{subdir}/{}", file.file_name().to_string_lossy());
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    errors.push(format!("{relative}: {e}"));
+                    continue;
+                }
+            };
+            if kind == LibraryKind::Profile {
+                if let Err(e) = serde_json::from_str::<serde_json::Value>(&content) {
+                    errors.push(format!("{relative}: invalid JSON: {e}"));
+                    continue;
+                }
+            }
+            entries.push(LibraryEntry {
+                kind,
+                name: name.to_string_lossy().into_owned(),
+                content: content.trim_end().to_string(),
+                source: LibrarySource::Shared,
+                read_only: true,
+            });
+        }
+    }
+    (entries, errors)
+}
+
+/// Merge local and shared entries (shared wins), sorted by kind and name
+pub fn merge_entries(
+    local: Vec<LibraryEntry>,
+    shared: Vec<LibraryEntry>,
+) -> (Vec<LibraryEntry>, Vec<LibraryConflict>) {
+    let mut merged: BTreeMap<(LibraryKind, String), LibraryEntry> = local
+        .into_iter()
+        .map(|e| ((e.kind, e.name.clone()), e))
+        .collect();
+    let mut conflicts = Vec::new();
+
+    for entry in shared {
+        let key = (entry.kind, entry.name.clone());
+        if let Some(local) = merged.get(&key) {
+            if local.content.trim_end() != entry.content {
+                conflicts.push(LibraryConflict {
+                    kind: entry.kind,
+                    name: entry.name.clone(),
+                    local_content: local.content.clone(),
+                    shared_content: entry.content.clone(),
+                });
+            }
+        }
+        merged.insert(key, entry);
+    }
+    (merged.into_values().collect(), conflicts)
+}
+
+pub(super) fn git(
+    runner: &dyn ProcessRunner,
+    dir: Option<&Path>,
+    args: &[&str],
+) -> Result<String, String> {
+    let mut command = silent_command("git");
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    command.args(args);
+    let output = runner.run(command, None)?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Clone `url` into `dir`, or fast-forward an existing clone to the remote's HEAD
+///
+/// The clone is a read-only mirror, so local changes in it are discarded.
+/// Returns the checked-out commit.
+pub fn sync_repo(runner: &impl ProcessRunner, url: &str, dir: &Path) -> Result<String, String> {
+    let cloned = dir.join(".git").exists()
+        && git(runner, Some(dir), &["remote", "get-url", "origin"]).is_ok_and(|u| u == url);
+
+    if cloned {
+        git(runner, Some(dir), &["fetch", "--depth", "1", "origin"])?;
+        git(runner, Some(dir), &["reset", "--hard", "FETCH_HEAD"])?;
+    } else {
+        if dir.exists() {
+            fs::remove_dir_all(dir)
+                .map_err(|e| format!("Failed to remove old shared library clone: {e}"))?;
+        }
+        let target = dir.to_string_lossy();
+        git(runner, None, &["clone", "--depth", "1", url, &target])?;
+    }
+    git(runner, Some(dir), &["rev-parse", "HEAD"])
+}
+
+fn load_state(root: &Path) -> SyncState {
+    fs::read_to_string(root.join(SYNC_STATE_FILE))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(root: &Path, state: &SyncState) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize sync state: {e}"))?;
+    fs::write(root.join(SYNC_STATE_FILE), content)
+        .map_err(|e| format!("Failed to write sync state: {e}"))
+}
+
+/// Merge the last synced shared library with local entries (no network access)
+pub fn load_library(root: &Path, prefs: &AppPreferences) -> PromptLibrary {
+    let sync = load_state(root);
+    let (shared, errors) = if configured_repo(prefs).is_some() {
+        read_shared_entries(&root.join(SHARED_LIBRARY_DIR))
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    let (entries, conflicts) = merge_entries(local_entries(prefs), shared);
+    PromptLibrary {
+        sync,
+        entries,
+        conflicts,
+        errors,
+    }
+}
+
+/// Pull the shared repository and return the merged library
+pub fn sync_library(
+    runner: &impl ProcessRunner,
+    root: &Path,
+    prefs: &AppPreferences,
+) -> Result<PromptLibrary, String> {
+    let url = configured_repo(prefs)
+        .ok_or("No shared library repository configured")?
+        .to_string();
+    let mut state = load_state(root);
+    state.repo_url = Some(url.clone());
+
+    match sync_repo(runner, &url, &root.join(SHARED_LIBRARY_DIR)) {
+        Ok(commit) => {
+            state.commit = Some(commit);
+            state.synced_at = Some(now());
+            state.error = None;
+            save_state(root, &state)?;
+            Ok(load_library(root, prefs))
+        }
+        Err(e) => {
+            state.error = Some(e.clone());
+            save_state(root, &state)?;
+            Err(e)
+        }
+    }
+}
+
+/// Sync if the configured interval has passed since the last successful sync
+fn run_scheduled_sync(app: &AppHandle) -> Result<(), String> {
+    let prefs = tauri::async_runtime::block_on(crate::load_preferences(app.clone()))?;
+    if configured_repo(&prefs).is_none() || prefs.shared_library_sync_hours == 0 {
+        return Ok(());
+    }
+    let root = app.app_data_dir()?;
+    let state = load_state(&root);
+    let due = state.repo_url.as_deref() != configured_repo(&prefs)
+        || state.synced_at.is_none_or(|at| {
+            now().saturating_sub(at) >= u64::from(prefs.shared_library_sync_hours) * 3600
+        });
+    if !due {
+        return Ok(());
+    }
+
+    let library = sync_library(&SystemProcessRunner, &root, &prefs)?;
+    if let Err(e) = app.emit_all("prompts:library-synced", &library) {
+        log::error!("Failed to emit prompts:library-synced event: {e}");
+    }
+    Ok(())
+}
+
+/// Start the scheduled shared library sync
+pub fn start_scheduler(app: AppHandle) {
+    supervise("shared-library-sync", move || loop {
+        if let Err(e) = run_scheduled_sync(&app) {
+            log::warn!("Shared library sync failed: {e}");
+        }
+        std::thread::sleep(SCHEDULE_CHECK_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::ScriptedRunner;
+    use crate::CustomCliProfile;
+
+    fn entry(kind: LibraryKind, name: &str, content: &str, source: LibrarySource) -> LibraryEntry {
+        LibraryEntry {
+            kind,
+            name: name.to_string(),
+            content: content.to_string(),
+            source,
+            read_only: source == LibrarySource::Shared,
+        }
+    }
+
+    #[test]
+    fn test_merge_prefers_shared_and_reports_conflicts() {
+        let local = vec![
+            entry(
+                LibraryKind::Prompt,
+                "code_review",
+                "mine",
+                LibrarySource::Local,
+            ),
+            entry(LibraryKind::Prompt, "same", "text\n", LibrarySource::Local),
+            entry(
+                LibraryKind::Profile,
+                "local-only",
+                "{}",
+                LibrarySource::Local,
+            ),
+        ];
+        let shared = vec![
+            entry(
+                LibraryKind::Prompt,
+                "code_review",
+                "team",
+                LibrarySource::Shared,
+            ),
+            entry(LibraryKind::Prompt, "same", "text", LibrarySource::Shared),
+        ];
+
+        let (entries, conflicts) = merge_entries(local, shared);
+        assert_eq!(entries.len(), 3);
+        let review = entries.iter().find(|e| e.name == "code_review").unwrap();
+        assert_eq!(review.content, "team");
+        assert!(review.read_only);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].local_content, "mine");
+    }
+
+    #[test]
+    fn test_read_shared_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("prompts")).unwrap();
+        fs::create_dir_all(dir.path().join("profiles")).unwrap();
+        fs::write(dir.path().join("prompts/code_review.md"), "Review it\n").unwrap();
+        fs::write(dir.path().join("prompts/notes.png"), "ignored").unwrap();
+        fs::write(dir.path().join("profiles/router.json"), r#"{"env":{}}"#).unwrap();
+        fs::write(dir.path().join("profiles/broken.json"), "{").unwrap();
+
+        let (entries, errors) = read_shared_entries(dir.path());
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(entries.len(), 2);
+        assert!(names.contains(&"code_review") && names.contains(&"router"));
+        assert_eq!(
+            entries
+                .iter()
+                .find(|e| e.name == "code_review")
+                .unwrap()
+                .content,
+            "Review it"
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("profiles/broken.json"));
+    }
+
+    #[test]
+    fn test_local_entries() {
+        let mut prefs = AppPreferences::default();
+        prefs.magic_prompts.code_review = Some("custom".to_string());
+        prefs.custom_cli_profiles.push(CustomCliProfile {
+            name: "router".to_string(),
+            settings_json: "{}".to_string(),
+            key_expires_at: None,
+            rotate_by: None,
+        });
+
+        let entries = local_entries(&prefs);
+        assert_eq!(entries.len(), 2);
+        assert!(entries
+            .iter()
+            .any(|e| e.kind == LibraryKind::Prompt && e.name == "code_review"));
+    }
+
+    #[test]
+    fn test_sync_repo_clones_when_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("shared-library");
+        let runner = ScriptedRunner::succeeding("abc123\n");
+
+        let commit = sync_repo(&runner, "https://example.com/prompts.git", &target).unwrap();
+        assert_eq!(commit, "abc123");
+        let runs = runner.runs();
+        assert_eq!(runs[0].program, "git");
+        assert_eq!(&runs[0].args[..3], ["clone", "--depth", "1"]);
+        assert_eq!(runs[1].args, ["rev-parse", "HEAD"]);
+    }
+
+    #[test]
+    fn test_sync_library_records_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefs = AppPreferences {
+            shared_library_repo: Some("https://example.com/prompts.git".to_string()),
+            ..Default::default()
+        };
+        let runner = ScriptedRunner::failing(128, "could not resolve host");
+
+        let err = sync_library(&runner, dir.path(), &prefs).unwrap_err();
+        assert!(err.contains("could not resolve host"));
+        let state = load_state(dir.path());
+        assert_eq!(state.error.as_deref(), Some(err.as_str()));
+        assert!(state.synced_at.is_none());
+    }
+}
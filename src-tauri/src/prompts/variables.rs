@@ -0,0 +1,417 @@
+//! Template variables backed by live project data
+//!
+//! Prompts can reference `{branch}`, `{changed_files}`,
+//! `{last_failing_test_output}`, `{package_versions}`, `{languages}` and
+//! `{verify_commands}`; these are resolved from the worktree when the prompt
+//! is rendered. Each variable is a
+//! [`VariableProvider`] in [`PROVIDERS`], and only the variables a template
+//! actually uses are resolved. Placeholders that aren't registered (e.g. the
+//! `{diff}` slots of magic prompts) are left untouched for their own callers.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::shared::git;
+use crate::chat::types::ChatMessage;
+use crate::projects::toolchain;
+use crate::runtime::ProcessRunner;
+
+/// Longest test output kept, from the end (failures are reported last)
+const MAX_TEST_OUTPUT_CHARS: usize = 4000;
+
+/// Commands treated as test runs when looking for failing output
+const TEST_COMMANDS: &[&str] = &[
+    "cargo test",
+    "cargo nextest",
+    "npm test",
+    "npm run test",
+    "pnpm test",
+    "pnpm run test",
+    "yarn test",
+    "bun test",
+    "vitest",
+    "jest",
+    "pytest",
+    "go test",
+    "mix test",
+    "rspec",
+    "phpunit",
+];
+
+/// What providers can read while rendering
+pub struct TemplateContext<'a> {
+    pub worktree_path: PathBuf,
+    /// Messages of the session the prompt is for (empty if none)
+    pub messages: Vec<ChatMessage>,
+    pub runner: &'a dyn ProcessRunner,
+}
+
+/// A template variable and how to resolve it
+pub struct VariableProvider {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub resolve: fn(&TemplateContext) -> Result<String, String>,
+}
+
+/// All project-context variables
+pub const PROVIDERS: &[VariableProvider] = &[
+    VariableProvider {
+        name: "branch",
+        description: "Current branch of the worktree",
+        resolve: resolve_branch,
+    },
+    VariableProvider {
+        name: "changed_files",
+        description: "Uncommitted changes, one `git status --short` line per file",
+        resolve: resolve_changed_files,
+    },
+    VariableProvider {
+        name: "last_failing_test_output",
+        description: "Output of the session's most recent failing test command",
+        resolve: resolve_last_failing_test_output,
+    },
+    VariableProvider {
+        name: "package_versions",
+        description: "Dependencies and versions from package.json and Cargo.toml",
+        resolve: resolve_package_versions,
+    },
+    VariableProvider {
+        name: "languages",
+        description: "Languages and toolchains the project uses",
+        resolve: resolve_languages,
+    },
+    VariableProvider {
+        name: "verify_commands",
+        description: "Commands that check a change (tests, lints), one per line",
+        resolve: resolve_verify_commands,
+    },
+];
+
+/// A registered variable (for the prompt editor)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    pub description: String,
+}
+
+/// A variable that couldn't be resolved (rendered as an empty string)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableError {
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedPrompt {
+    pub text: String,
+    /// Variables found in the template and resolved
+    pub resolved: Vec<String>,
+    pub errors: Vec<VariableError>,
+}
+
+pub fn list_variables() -> Vec<TemplateVariable> {
+    PROVIDERS
+        .iter()
+        .map(|p| TemplateVariable {
+            name: p.name.to_string(),
+            description: p.description.to_string(),
+        })
+        .collect()
+}
+
+/// Replace every registered `{variable}` in `template`
+pub fn render(template: &str, ctx: &TemplateContext) -> RenderedPrompt {
+    let mut text = template.to_string();
+    let mut resolved = Vec::new();
+    let mut errors = Vec::new();
+
+    for provider in PROVIDERS {
+        let placeholder = format!("{{{}}}", provider.name);
+        if !text.contains(&placeholder) {
+            continue;
+        }
+        let value = match (provider.resolve)(ctx) {
+            Ok(value) => {
+                resolved.push(provider.name.to_string());
+                value
+            }
+            Err(error) => {
+                errors.push(VariableError {
+                    name: provider.name.to_string(),
+                    error,
+                });
+                String::new()
+            }
+        };
+        text = text.replace(&placeholder, &value);
+    }
+
+    RenderedPrompt {
+        text,
+        resolved,
+        errors,
+    }
+}
+
+fn resolve_branch(ctx: &TemplateContext) -> Result<String, String> {
+    git(
+        ctx.runner,
+        Some(&ctx.worktree_path),
+        &["rev-parse", "--abbrev-ref", "HEAD"],
+    )
+}
+
+fn resolve_changed_files(ctx: &TemplateContext) -> Result<String, String> {
+    let status = git(
+        ctx.runner,
+        Some(&ctx.worktree_path),
+        &["status", "--short", "--untracked-files=all"],
+    )?;
+    Ok(if status.is_empty() {
+        "(no changes)".to_string()
+    } else {
+        status
+    })
+}
+
+fn is_test_command(command: &str) -> bool {
+    TEST_COMMANDS.iter().any(|c| command.contains(c))
+}
+
+fn looks_failed(output: &str) -> bool {
+    let lower = output.to_lowercase();
+    lower.contains("fail") || lower.contains("panicked") || lower.contains("error")
+}
+
+fn tail(text: &str, max_chars: usize) -> String {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text.to_string();
+    }
+    let skipped: String = text.chars().skip(count - max_chars).collect();
+    format!("…{skipped}")
+}
+
+fn resolve_last_failing_test_output(ctx: &TemplateContext) -> Result<String, String> {
+    ctx.messages
+        .iter()
+        .rev()
+        .flat_map(|m| m.tool_calls.iter().rev())
+        .filter(|call| call.name == "Bash")
+        .find_map(|call| {
+            let command = call.input.get("command")?.as_str()?;
+            let output = call.output.as_deref()?;
+            (is_test_command(command) && looks_failed(output))
+                .then(|| format!("$ {command}\n{}", tail(output, MAX_TEST_OUTPUT_CHARS)))
+        })
+        .ok_or_else(|| "No failing test run found in this session".to_string())
+}
+
+fn package_json_versions(path: &Path) -> Option<Vec<String>> {
+    let content = fs::read_to_string(path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let mut lines = Vec::new();
+    for section in ["dependencies", "devDependencies"] {
+        if let Some(deps) = json.get(section).and_then(|d| d.as_object()) {
+            for (name, version) in deps {
+                lines.push(format!("{name} {}", version.as_str().unwrap_or("?")));
+            }
+        }
+    }
+    Some(lines)
+}
+
+/// `name = "1.0"` or `name = { version = "1.0", ... }` in dependency tables
+fn cargo_toml_versions(path: &Path) -> Option<Vec<String>> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut lines = Vec::new();
+    let mut in_dependencies = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_dependencies = line.trim_matches(['[', ']']).ends_with("dependencies");
+            continue;
+        }
+        if !in_dependencies {
+            continue;
+        }
+        let Some((name, spec)) = line.split_once('=') else {
+            continue;
+        };
+        let spec = spec.trim();
+        let version = if spec.starts_with('"') {
+            Some(spec)
+        } else {
+            spec.split_once("version")
+                .and_then(|(_, rest)| rest.trim_start().strip_prefix('='))
+                .map(str::trim_start)
+        }
+        .and_then(|v| v.strip_prefix('"'))
+        .and_then(|v| v.split('"').next());
+        if let Some(version) = version {
+            lines.push(format!("{} {version}", name.trim()));
+        }
+    }
+    Some(lines)
+}
+
+fn resolve_package_versions(ctx: &TemplateContext) -> Result<String, String> {
+    let mut sections = Vec::new();
+    for (file, read) in [
+        (
+            "package.json",
+            package_json_versions as fn(&Path) -> Option<Vec<String>>,
+        ),
+        ("Cargo.toml", cargo_toml_versions),
+        ("src-tauri/Cargo.toml", cargo_toml_versions),
+    ] {
+        if let Some(lines) = read(&ctx.worktree_path.join(file)) {
+            if !lines.is_empty() {
+                sections.push(format!("{file}:\n{}", lines.join("\n")));
+            }
+        }
+    }
+    if sections.is_empty() {
+        Err("No package.json or Cargo.toml dependencies found".to_string())
+    } else {
+        Ok(sections.join("\n\n"))
+    }
+}
+
+fn label<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn resolve_languages(ctx: &TemplateContext) -> Result<String, String> {
+    let detected = toolchain::detect(&ctx.worktree_path, 0);
+    if detected.languages.is_empty() {
+        return Err("No Cargo.toml, package.json, go.mod or pyproject.toml found".to_string());
+    }
+    let languages: Vec<_> = detected.languages.iter().map(label).collect();
+    let toolchains: Vec<_> = detected
+        .toolchains
+        .iter()
+        .map(|t| match t.dir.as_str() {
+            "" => label(&t.toolchain),
+            dir => format!("{} ({dir})", label(&t.toolchain)),
+        })
+        .collect();
+    Ok(format!(
+        "{} using {}",
+        languages.join(", "),
+        toolchains.join(", ")
+    ))
+}
+
+fn resolve_verify_commands(ctx: &TemplateContext) -> Result<String, String> {
+    let detected = toolchain::detect(&ctx.worktree_path, 0);
+    if detected.verify_commands.is_empty() {
+        return Err("No test or lint commands detected".to_string());
+    }
+    Ok(detected.verify_commands.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::types::{MessageRole, ToolCall};
+    use crate::test_support::runtime::ScriptedRunner;
+
+    fn bash_message(command: &str, output: &str) -> ChatMessage {
+        ChatMessage {
+            role: MessageRole::Assistant,
+            tool_calls: vec![ToolCall {
+                id: "t1".to_string(),
+                name: "Bash".to_string(),
+                input: serde_json::json!({ "command": command }),
+                output: Some(output.to_string()),
+                parent_tool_use_id: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_resolves_used_variables_only() {
+        let runner = ScriptedRunner::succeeding("feature/login\n");
+        let ctx = TemplateContext {
+            worktree_path: PathBuf::from("/tmp/repo"),
+            messages: vec![],
+            runner: &runner,
+        };
+
+        let rendered = render("On {branch}, review {diff}", &ctx);
+        assert_eq!(rendered.text, "On feature/login, review {diff}");
+        assert_eq!(rendered.resolved, ["branch"]);
+        assert!(rendered.errors.is_empty());
+        assert_eq!(runner.runs().len(), 1);
+    }
+
+    #[test]
+    fn test_render_reports_unresolvable_variables() {
+        let runner = ScriptedRunner::succeeding("");
+        let ctx = TemplateContext {
+            worktree_path: PathBuf::from("/tmp/repo"),
+            messages: vec![bash_message("cargo test", "test result: ok")],
+            runner: &runner,
+        };
+
+        let rendered = render("Fix:\n{last_failing_test_output}", &ctx);
+        assert_eq!(rendered.text, "Fix:\n");
+        assert_eq!(rendered.errors[0].name, "last_failing_test_output");
+    }
+
+    #[test]
+    fn test_last_failing_test_output_picks_latest_failure() {
+        let runner = ScriptedRunner::succeeding("");
+        let ctx = TemplateContext {
+            worktree_path: PathBuf::from("/tmp/repo"),
+            messages: vec![
+                bash_message("npm test", "1 failed: old"),
+                bash_message("ls", "error.log"),
+                bash_message("cargo test -p app", "test foo ... FAILED"),
+            ],
+            runner: &runner,
+        };
+
+        let output = resolve_last_failing_test_output(&ctx).unwrap();
+        assert_eq!(output, "$ cargo test -p app\ntest foo ... FAILED");
+    }
+
+    #[test]
+    fn test_package_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"dependencies":{"react":"^19.0.0"},"devDependencies":{"vitest":"3.1.0"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nversion = \"0.1.0\"\n\n[dependencies]\nserde = { version = \"1.0\", features = [\"derive\"] }\nlog = \"0.4\"\nlocal = { path = \"../local\" }\n\n[dev-dependencies]\ntempfile = \"3\"\n",
+        )
+        .unwrap();
+        let runner = ScriptedRunner::succeeding("");
+        let ctx = TemplateContext {
+            worktree_path: dir.path().to_path_buf(),
+            messages: vec![],
+            runner: &runner,
+        };
+
+        let versions = resolve_package_versions(&ctx).unwrap();
+        assert_eq!(
+            versions,
+            "package.json:\nreact ^19.0.0\nvitest 3.1.0\n\nCargo.toml:\nserde 1.0\nlog 0.4\ntempfile 3"
+        );
+
+        let rendered = render("{languages}\n{verify_commands}", &ctx);
+        assert_eq!(
+            rendered.text,
+            "rust, javascript using cargo, npm\ncargo clippy --all-targets\ncargo test"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Tauri command for the provider overview
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tauri::AppHandle;
+
+use super::keys::{key_statuses, ProviderKeyStatus};
+use super::{
+    claude_spend_since, is_newer_version, month_start, parse_status_page, ProviderAuth,
+    ProviderCli, ProviderHealth, ProviderOverview, ANTHROPIC_STATUS_URL, GITHUB_STATUS_URL,
+};
+use crate::claude_cli::ClaudeAuthState;
+use crate::gh_cli::config::ReleaseSource;
+use crate::CustomCliProfile;
+
+/// Timeout for status-page requests (they shouldn't hold up the home screen)
+const STATUS_PAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Auth, CLI version, spend and health for every provider in one call
+#[tauri::command]
+pub async fn get_providers_overview(app: AppHandle) -> Result<Vec<ProviderOverview>, String> {
+    log::trace!("Building providers overview");
+    let (claude, github) = tokio::join!(claude_overview(&app), github_overview(&app));
+    Ok(vec![claude, github])
+}
+
+/// Key expiry, rotation reminders and rollback availability per provider profile
+#[tauri::command]
+pub async fn get_provider_key_status(app: AppHandle) -> Result<Vec<ProviderKeyStatus>, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    Ok(key_statuses(&app, &prefs.custom_cli_profiles))
+}
+
+/// Replace the key of `profile` with `new_key` once the provider accepts it
+/// The previous key can be restored with `rollback_provider_key` for a day.
+#[tauri::command]
+pub async fn rotate_provider_key(
+    app: AppHandle,
+    profile: String,
+    new_key: String,
+    key_expires_at: Option<u64>,
+) -> Result<CustomCliProfile, String> {
+    log::trace!("Rotating key for provider profile {profile}");
+    super::keys::rotate(&app, &profile, &new_key, key_expires_at).await
+}
+
+/// Restore the key `profile` had before its last rotation
+#[tauri::command]
+pub async fn rollback_provider_key(
+    app: AppHandle,
+    profile: String,
+) -> Result<CustomCliProfile, String> {
+    log::trace!("Rolling back key for provider profile {profile}");
+    super::keys::rollback(&app, &profile).await
+}
+
+async fn claude_overview(app: &AppHandle) -> ProviderOverview {
+    let mut errors = Vec::new();
+
+    let (installed, auth, latest, health) = tokio::join!(
+        crate::claude_cli::check_claude_cli_installed(app.clone()),
+        crate::claude_cli::get_claude_auth_status(),
+        crate::claude_cli::fetch_latest_version(),
+        fetch_health(ANTHROPIC_STATUS_URL),
+    );
+
+    let cli = cli_status(
+        installed.map(|s| (s.installed, s.version)),
+        latest,
+        &mut errors,
+    );
+
+    let auth = match auth {
+        Ok(details) => ProviderAuth {
+            authenticated: matches!(
+                details.state,
+                ClaudeAuthState::LoggedIn
+                    | ClaudeAuthState::ApiKey
+                    | ClaudeAuthState::RefreshPending
+            ),
+            state: serde_json::to_value(details.state)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string)),
+            detail: details.plan,
+            error: details.message,
+        },
+        Err(e) => {
+            errors.push(format!("Auth: {e}"));
+            unknown_auth(e)
+        }
+    };
+
+    let spend = match crate::load_preferences(app.clone()).await {
+        Ok(prefs) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            claude_spend_since(app, month_start(now), prefs.claude_monthly_budget_usd)
+                .map_err(|e| errors.push(format!("Spend: {e}")))
+                .ok()
+        }
+        Err(e) => {
+            errors.push(format!("Spend: {e}"));
+            None
+        }
+    };
+
+    ProviderOverview {
+        id: "claude".to_string(),
+        name: "Claude".to_string(),
+        auth,
+        cli,
+        spend,
+        health: health.map_err(|e| errors.push(format!("Health: {e}"))).ok(),
+        errors,
+    }
+}
+
+async fn github_overview(app: &AppHandle) -> ProviderOverview {
+    let mut errors = Vec::new();
+
+    let source = ReleaseSource::github();
+    let (installed, auth, latest, health) = tokio::join!(
+        crate::gh_cli::check_gh_cli_installed(app.clone()),
+        crate::gh_cli::check_gh_cli_auth(app.clone()),
+        crate::gh_cli::fetch_latest_gh_version(&source, |retry| {
+            crate::http_client::retry::emit_retry(app, retry)
+        }),
+        fetch_health(GITHUB_STATUS_URL),
+    );
+
+    let cli = cli_status(
+        installed.map(|s| (s.installed, s.version)),
+        latest,
+        &mut errors,
+    );
+
+    let auth = match auth {
+        Ok(status) => ProviderAuth {
+            authenticated: status.authenticated,
+            state: None,
+            detail: None,
+            error: status.error,
+        },
+        Err(e) => {
+            errors.push(format!("Auth: {e}"));
+            unknown_auth(e)
+        }
+    };
+
+    ProviderOverview {
+        id: "github".to_string(),
+        name: "GitHub".to_string(),
+        auth,
+        cli,
+        spend: None,
+        health: health.map_err(|e| errors.push(format!("Health: {e}"))).ok(),
+        errors,
+    }
+}
+
+fn unknown_auth(error: String) -> ProviderAuth {
+    ProviderAuth {
+        authenticated: false,
+        state: None,
+        detail: None,
+        error: Some(error),
+    }
+}
+
+fn cli_status(
+    installed: Result<(bool, Option<String>), String>,
+    latest: Result<String, String>,
+    errors: &mut Vec<String>,
+) -> ProviderCli {
+    let (installed, version) = installed.unwrap_or_else(|e| {
+        errors.push(format!("CLI: {e}"));
+        (false, None)
+    });
+    let latest_version = latest
+        .map_err(|e| errors.push(format!("Latest version: {e}")))
+        .ok();
+    let update_available = match (&version, &latest_version) {
+        (Some(version), Some(latest)) => is_newer_version(version, latest),
+        _ => false,
+    };
+
+    ProviderCli {
+        installed,
+        version,
+        latest_version,
+        update_available,
+    }
+}
+
+async fn fetch_health(url: &str) -> Result<ProviderHealth, String> {
+    let client = crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .timeout(STATUS_PAGE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch status page: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("Status page returned HTTP {}", response.status()));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read status page: {e}"))?;
+    parse_status_page(&body)
+}
@@ -0,0 +1,536 @@
+//! Provider key expiry, rotation reminders and rotation
+//!
+//! Provider profiles (`custom_cli_profiles`) keep their key in the settings
+//! `env` block and can carry `key_expires_at` (when the provider stops
+//! accepting it) and `rotate_by` (when policy says to replace it). The
+//! reminder scheduler notifies [`REMINDER_LEAD_DAYS`] ahead of either date,
+//! at most once a day per profile, until the key is rotated or the dates
+//! are cleared.
+//!
+//! Rotation checks the new key against the provider before swapping it in
+//! and keeps the old settings for [`ROLLBACK_WINDOW_SECS`] so a key that
+//! turns out to be wrong can be rolled back. Rollback copies live in
+//! `provider-keys.json` in app data, not in preferences, so they never end
+//! up in exports or the shared library.
+
+use std::collections::HashMap;
+use std::fs;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::background_tasks::supervisor::supervise;
+use crate::focus::NotificationCategory;
+use crate::http_server::EmitExt;
+use crate::platform::write_secret_file;
+use crate::runtime::{now, PathProvider};
+use crate::CustomCliProfile;
+
+/// Rollback copies and reminder bookkeeping (in app data)
+const STATE_FILE: &str = "provider-keys.json";
+
+/// Env vars a profile's key may live in, in order of preference
+const KEY_ENV_VARS: &[&str] = &["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"];
+
+/// Endpoint used when a profile sets no `ANTHROPIC_BASE_URL`
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+
+/// Days before a date that reminders start
+pub const REMINDER_LEAD_DAYS: u64 = 7;
+
+/// How long the previous key stays available for rollback
+pub const ROLLBACK_WINDOW_SECS: u64 = 24 * 3600;
+
+const DAY_SECS: u64 = 86_400;
+
+/// How often the scheduler checks for due reminders
+const REMINDER_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Which date a reminder is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyDate {
+    Expiry,
+    Rotation,
+}
+
+/// A key that expires or is due for rotation soon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyReminder {
+    pub profile: String,
+    pub kind: KeyDate,
+    /// Unix seconds
+    pub at: u64,
+    /// Whole days until `at` (negative once passed)
+    pub days_left: i64,
+}
+
+/// Old settings kept after a rotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Rollback {
+    settings_json: String,
+    key_expires_at: Option<u64>,
+    rotate_by: Option<u64>,
+    rotated_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct KeyState {
+    /// Profile name -> settings before the last rotation
+    #[serde(default)]
+    rollbacks: HashMap<String, Rollback>,
+    /// "{profile}:{kind}" -> when the last reminder was shown
+    #[serde(default)]
+    reminded: HashMap<String, u64>,
+}
+
+/// Key status of one profile (for settings)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderKeyStatus {
+    pub profile: String,
+    /// Env var holding the key (None when the profile has no key)
+    pub key_var: Option<String>,
+    pub key_expires_at: Option<u64>,
+    pub rotate_by: Option<u64>,
+    /// Dates within the reminder lead time
+    pub reminders: Vec<KeyReminder>,
+    /// Until when `rollback_provider_key` can restore the previous key
+    pub rollback_until: Option<u64>,
+}
+
+fn load_state(app: &impl PathProvider) -> KeyState {
+    app.app_data_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(STATE_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(app: &impl PathProvider, state: &KeyState) -> Result<(), String> {
+    let dir = app.app_data_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data directory: {e}"))?;
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize provider key state: {e}"))?;
+    write_secret_file(&dir.join(STATE_FILE), content.as_bytes())
+        .map_err(|e| format!("Failed to save provider key state: {e}"))
+}
+
+/// Env var and value of the key in a profile's settings
+pub fn read_key(settings_json: &str) -> Option<(String, String)> {
+    let settings: serde_json::Value = serde_json::from_str(settings_json).ok()?;
+    let env = settings.get("env")?;
+    KEY_ENV_VARS.iter().find_map(|var| {
+        let key = env.get(*var)?.as_str()?.trim();
+        (!key.is_empty()).then(|| (var.to_string(), key.to_string()))
+    })
+}
+
+/// Settings with the key replaced by `new_key` (in the var already used,
+/// else `ANTHROPIC_AUTH_TOKEN`)
+pub fn with_key(settings_json: &str, new_key: &str) -> Result<String, String> {
+    let mut settings: serde_json::Value = serde_json::from_str(settings_json)
+        .map_err(|e| format!("Invalid profile settings: {e}"))?;
+    let var = read_key(settings_json)
+        .map(|(var, _)| var)
+        .unwrap_or_else(|| KEY_ENV_VARS[0].to_string());
+    let obj = settings
+        .as_object_mut()
+        .ok_or("Profile settings must be a JSON object")?;
+    let env = obj
+        .entry("env")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or("Profile settings `env` must be an object")?;
+    env.insert(var, serde_json::Value::String(new_key.to_string()));
+    serde_json::to_string_pretty(&settings).map_err(|e| format!("Failed to write settings: {e}"))
+}
+
+/// Settings without the key env vars (`{}` if they can't be parsed)
+pub fn without_key(settings_json: &str) -> String {
+    let Ok(mut settings) = serde_json::from_str::<serde_json::Value>(settings_json) else {
+        return "{}".to_string();
+    };
+    if let Some(env) = settings.get_mut("env").and_then(|env| env.as_object_mut()) {
+        for var in KEY_ENV_VARS {
+            env.remove(*var);
+        }
+    }
+    serde_json::to_string_pretty(&settings).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Expiry and rotation dates of `profiles` within the lead time of `now`
+pub fn due_reminders(profiles: &[CustomCliProfile], now: u64) -> Vec<KeyReminder> {
+    let lead = REMINDER_LEAD_DAYS * DAY_SECS;
+    let mut reminders = Vec::new();
+    for profile in profiles {
+        let dates = [
+            (KeyDate::Expiry, profile.key_expires_at),
+            (KeyDate::Rotation, profile.rotate_by),
+        ];
+        for (kind, at) in dates {
+            let Some(at) = at.filter(|at| at.saturating_sub(lead) <= now) else {
+                continue;
+            };
+            reminders.push(KeyReminder {
+                profile: profile.name.clone(),
+                kind,
+                at,
+                days_left: (at as i64 - now as i64).div_euclid(DAY_SECS as i64),
+            });
+        }
+    }
+    reminders
+}
+
+/// Notification title and body for a reminder
+fn reminder_text(reminder: &KeyReminder) -> (String, String) {
+    let when = match reminder.days_left {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        d => format!("in {d} days"),
+    };
+    let status = match (reminder.kind, reminder.days_left < 0) {
+        (KeyDate::Expiry, true) => "has expired".to_string(),
+        (KeyDate::Expiry, false) => format!("expires {when}"),
+        (KeyDate::Rotation, true) => "is overdue for rotation".to_string(),
+        (KeyDate::Rotation, false) => format!("is due for rotation {when}"),
+    };
+    (
+        format!("{} key {status}", reminder.profile),
+        format!(
+            "The {} provider key {status}. Rotate it in Settings > Providers.",
+            reminder.profile
+        ),
+    )
+}
+
+/// Reminders not shown in the last day; records them as shown
+fn take_unsent(state: &mut KeyState, reminders: Vec<KeyReminder>, now: u64) -> Vec<KeyReminder> {
+    reminders
+        .into_iter()
+        .filter(|r| {
+            let key = format!("{}:{:?}", r.profile, r.kind);
+            let due = state
+                .reminded
+                .get(&key)
+                .is_none_or(|last| now.saturating_sub(*last) >= DAY_SECS);
+            if due {
+                state.reminded.insert(key, now);
+            }
+            due
+        })
+        .collect()
+}
+
+/// Drop rollback copies past their window
+fn prune_rollbacks(state: &mut KeyState, now: u64) -> bool {
+    let before = state.rollbacks.len();
+    state
+        .rollbacks
+        .retain(|_, r| now < r.rotated_at + ROLLBACK_WINDOW_SECS);
+    state.rollbacks.len() != before
+}
+
+/// Key status of every profile
+pub fn key_statuses(
+    app: &impl PathProvider,
+    profiles: &[CustomCliProfile],
+) -> Vec<ProviderKeyStatus> {
+    let now = now();
+    let state = load_state(app);
+    let reminders = due_reminders(profiles, now);
+    profiles
+        .iter()
+        .map(|p| ProviderKeyStatus {
+            profile: p.name.clone(),
+            key_var: read_key(&p.settings_json).map(|(var, _)| var),
+            key_expires_at: p.key_expires_at,
+            rotate_by: p.rotate_by,
+            reminders: reminders
+                .iter()
+                .filter(|r| r.profile == p.name)
+                .cloned()
+                .collect(),
+            rollback_until: state
+                .rollbacks
+                .get(&p.name)
+                .map(|r| r.rotated_at + ROLLBACK_WINDOW_SECS)
+                .filter(|until| now < *until),
+        })
+        .collect()
+}
+
+/// Check that the provider accepts `key`
+///
+/// Lists models with the key; 401 and 403 mean it was rejected. Any other
+/// response counts as accepted, since not every Anthropic-compatible
+/// endpoint implements the models list.
+pub async fn validate_key(settings_json: &str, key: &str) -> Result<(), String> {
+    let base_url = crate::power::monitor::base_url_from_settings(settings_json)
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+    let url = format!("{}/v1/models", base_url.trim_end_matches('/'));
+
+    let client = crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .timeout(VALIDATION_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+    let response = client
+        .get(&url)
+        .header("x-api-key", key)
+        .header("Authorization", format!("Bearer {key}"))
+        .header("anthropic-version", "2023-06-01")
+        .send()
+        .await
+        .map_err(|e| format!("Could not reach {base_url} to check the key: {e}"))?;
+
+    match response.status().as_u16() {
+        401 | 403 => Err(format!(
+            "{base_url} rejected the new key ({})",
+            response.status()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Swap in `new_key` for `profile` after validating it
+/// The old settings stay available to `rollback` for [`ROLLBACK_WINDOW_SECS`].
+/// `key_expires_at` sets the new key's expiry; the rotation date is cleared.
+pub async fn rotate(
+    app: &AppHandle,
+    profile: &str,
+    new_key: &str,
+    key_expires_at: Option<u64>,
+) -> Result<CustomCliProfile, String> {
+    let new_key = new_key.trim();
+    if new_key.is_empty() {
+        return Err("New key is empty".to_string());
+    }
+
+    let mut prefs = crate::load_preferences(app.clone()).await?;
+    let current = prefs
+        .custom_cli_profiles
+        .iter()
+        .find(|p| p.name == profile)
+        .cloned()
+        .ok_or_else(|| format!("Provider profile not found: {profile}"))?;
+    if read_key(&current.settings_json).is_some_and(|(_, key)| key == new_key) {
+        return Err("The new key is the same as the current one".to_string());
+    }
+
+    validate_key(&current.settings_json, new_key).await?;
+
+    let updated = CustomCliProfile {
+        settings_json: with_key(&current.settings_json, new_key)?,
+        key_expires_at,
+        rotate_by: None,
+        ..current.clone()
+    };
+
+    // Keep the old key before replacing it, so a failed save loses nothing
+    let mut state = load_state(app);
+    state.rollbacks.insert(
+        profile.to_string(),
+        Rollback {
+            settings_json: current.settings_json,
+            key_expires_at: current.key_expires_at,
+            rotate_by: current.rotate_by,
+            rotated_at: now(),
+        },
+    );
+    state
+        .reminded
+        .retain(|key, _| !key.starts_with(&format!("{profile}:")));
+    save_state(app, &state)?;
+
+    if let Some(p) = prefs
+        .custom_cli_profiles
+        .iter_mut()
+        .find(|p| p.name == profile)
+    {
+        *p = updated.clone();
+    }
+    crate::save_preferences(app.clone(), prefs).await?;
+    log::info!("Rotated key for provider profile {profile}");
+    Ok(updated)
+}
+
+/// Restore the key `profile` had before its last rotation
+pub async fn rollback(app: &AppHandle, profile: &str) -> Result<CustomCliProfile, String> {
+    let mut state = load_state(app);
+    prune_rollbacks(&mut state, now());
+    let previous = state
+        .rollbacks
+        .remove(profile)
+        .ok_or_else(|| format!("No recent rotation of {profile} to roll back"))?;
+
+    let mut prefs = crate::load_preferences(app.clone()).await?;
+    let target = prefs
+        .custom_cli_profiles
+        .iter_mut()
+        .find(|p| p.name == profile)
+        .ok_or_else(|| format!("Provider profile not found: {profile}"))?;
+    target.settings_json = previous.settings_json;
+    target.key_expires_at = previous.key_expires_at;
+    target.rotate_by = previous.rotate_by;
+    let restored = target.clone();
+
+    crate::save_preferences(app.clone(), prefs).await?;
+    save_state(app, &state)?;
+    log::info!("Rolled back key for provider profile {profile}");
+    Ok(restored)
+}
+
+/// Notify about due keys and drop expired rollback copies
+fn check_reminders(app: &AppHandle) -> Result<(), String> {
+    let prefs = tauri::async_runtime::block_on(crate::load_preferences(app.clone()))?;
+    let now = now();
+    let mut state = load_state(app);
+    let pruned = prune_rollbacks(&mut state, now);
+    let unsent = take_unsent(
+        &mut state,
+        due_reminders(&prefs.custom_cli_profiles, now),
+        now,
+    );
+    if unsent.is_empty() && !pruned {
+        return Ok(());
+    }
+    save_state(app, &state)?;
+
+    for reminder in unsent {
+        log::info!(
+            "Provider key reminder: {} {:?} in {} day(s)",
+            reminder.profile,
+            reminder.kind,
+            reminder.days_left
+        );
+        if let Err(e) = app.emit_all("provider-key:reminder", &reminder) {
+            log::error!("Failed to emit provider-key:reminder: {e}");
+        }
+        let (title, body) = reminder_text(&reminder);
+        if let Err(e) = tauri::async_runtime::block_on(crate::focus::notify(
+            app,
+            NotificationCategory::General,
+            title,
+            Some(body),
+        )) {
+            log::warn!("Failed to show key reminder: {e}");
+        }
+    }
+    Ok(())
+}
+
+/// Start the key reminder thread
+pub fn start_reminder_scheduler(app: AppHandle) {
+    supervise("provider-key-reminders", move || loop {
+        if let Err(e) = check_reminders(&app) {
+            log::error!("Provider key reminder check failed: {e}");
+        }
+        std::thread::sleep(REMINDER_CHECK_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(
+        name: &str,
+        key_expires_at: Option<u64>,
+        rotate_by: Option<u64>,
+    ) -> CustomCliProfile {
+        CustomCliProfile {
+            name: name.to_string(),
+            settings_json: r#"{"env":{"ANTHROPIC_API_KEY":"old"}}"#.to_string(),
+            key_expires_at,
+            rotate_by,
+        }
+    }
+
+    #[test]
+    fn test_read_and_replace_key() {
+        let settings = r#"{"env":{"ANTHROPIC_BASE_URL":"https://x","ANTHROPIC_API_KEY":"old"}}"#;
+        assert_eq!(
+            read_key(settings),
+            Some(("ANTHROPIC_API_KEY".to_string(), "old".to_string()))
+        );
+        let updated = with_key(settings, "new").unwrap();
+        assert_eq!(
+            read_key(&updated),
+            Some(("ANTHROPIC_API_KEY".to_string(), "new".to_string()))
+        );
+        assert!(updated.contains("https://x"));
+
+        let updated = with_key("{}", "new").unwrap();
+        assert_eq!(
+            read_key(&updated),
+            Some(("ANTHROPIC_AUTH_TOKEN".to_string(), "new".to_string()))
+        );
+        assert!(with_key("[]", "new").is_err());
+    }
+
+    #[test]
+    fn test_due_reminders() {
+        let now = 100 * DAY_SECS;
+        let profiles = vec![
+            profile("Soon", Some(now + 3 * DAY_SECS), None),
+            profile("Later", Some(now + 30 * DAY_SECS), None),
+            profile("Overdue", None, Some(now - DAY_SECS)),
+            profile("None", None, None),
+        ];
+        let reminders = due_reminders(&profiles, now);
+        assert_eq!(reminders.len(), 2);
+        assert_eq!(reminders[0].profile, "Soon");
+        assert_eq!(reminders[0].kind, KeyDate::Expiry);
+        assert_eq!(reminders[0].days_left, 3);
+        assert_eq!(reminders[1].profile, "Overdue");
+        assert_eq!(reminders[1].kind, KeyDate::Rotation);
+        assert_eq!(reminders[1].days_left, -1);
+
+        assert_eq!(reminder_text(&reminders[0]).0, "Soon key expires in 3 days");
+        assert_eq!(
+            reminder_text(&reminders[1]).0,
+            "Overdue key is overdue for rotation"
+        );
+    }
+
+    #[test]
+    fn test_reminders_sent_once_a_day() {
+        let now = 100 * DAY_SECS;
+        let profiles = vec![profile("Soon", Some(now + DAY_SECS), None)];
+        let mut state = KeyState::default();
+
+        let sent = take_unsent(&mut state, due_reminders(&profiles, now), now);
+        assert_eq!(sent.len(), 1);
+        let later = now + 3600;
+        assert!(take_unsent(&mut state, due_reminders(&profiles, later), later).is_empty());
+        let next_day = now + DAY_SECS;
+        assert_eq!(
+            take_unsent(&mut state, due_reminders(&profiles, next_day), next_day).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_prune_rollbacks() {
+        let mut state = KeyState::default();
+        state.rollbacks.insert(
+            "OpenRouter".to_string(),
+            Rollback {
+                settings_json: "{}".to_string(),
+                key_expires_at: None,
+                rotate_by: None,
+                rotated_at: 1000,
+            },
+        );
+        assert!(!prune_rollbacks(
+            &mut state,
+            1000 + ROLLBACK_WINDOW_SECS - 1
+        ));
+        assert!(prune_rollbacks(&mut state, 1000 + ROLLBACK_WINDOW_SECS));
+        assert!(state.rollbacks.is_empty());
+    }
+}
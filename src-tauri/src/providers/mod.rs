@@ -0,0 +1,243 @@
+//! Provider overview for the home screen
+//!
+//! Aggregates, per provider (Claude, GitHub): auth state, installed vs latest
+//! CLI version, spend against the configured budget, and status-page health.
+//! Each part is fetched independently so one failing lookup only blanks its
+//! own field.
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat::storage::{list_all_session_ids, load_metadata};
+use crate::runtime::PathProvider;
+
+pub mod commands;
+pub mod keys;
+
+/// Anthropic's Statuspage summary endpoint
+pub const ANTHROPIC_STATUS_URL: &str = "https://status.anthropic.com/api/v2/status.json";
+
+/// GitHub's Statuspage summary endpoint
+pub const GITHUB_STATUS_URL: &str = "https://www.githubstatus.com/api/v2/status.json";
+
+/// Authentication state of a provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderAuth {
+    pub authenticated: bool,
+    /// Provider-specific state (e.g. "refresh_failed" for Claude)
+    pub state: Option<String>,
+    /// Plan / account detail when known
+    pub detail: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Installed and latest CLI versions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderCli {
+    pub installed: bool,
+    pub version: Option<String>,
+    pub latest_version: Option<String>,
+    pub update_available: bool,
+}
+
+/// Spend in the current calendar month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderSpend {
+    pub current_usd: f64,
+    pub budget_usd: Option<f64>,
+    /// Start of the period (Unix seconds, first of the month UTC)
+    pub period_start: u64,
+    pub over_budget: bool,
+    /// Runs in the period without a reported cost (not included in the total)
+    pub runs_without_cost: u32,
+}
+
+/// Status-page health
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealth {
+    /// Statuspage indicator: "none", "minor", "major", "critical" or "maintenance"
+    pub indicator: String,
+    pub description: String,
+}
+
+/// Everything the home screen shows for one provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderOverview {
+    /// "claude" or "github"
+    pub id: String,
+    pub name: String,
+    pub auth: ProviderAuth,
+    pub cli: ProviderCli,
+    /// None for providers that don't bill per run
+    pub spend: Option<ProviderSpend>,
+    pub health: Option<ProviderHealth>,
+    /// Lookups that failed (the corresponding fields are empty)
+    pub errors: Vec<String>,
+}
+
+/// Parse a Statuspage `status.json` response
+pub(crate) fn parse_status_page(body: &str) -> Result<ProviderHealth, String> {
+    #[derive(Deserialize)]
+    struct StatusResponse {
+        status: StatusBody,
+    }
+    #[derive(Deserialize)]
+    struct StatusBody {
+        indicator: String,
+        description: String,
+    }
+
+    let response: StatusResponse =
+        serde_json::from_str(body).map_err(|e| format!("Failed to parse status page: {e}"))?;
+    Ok(ProviderHealth {
+        indicator: response.status.indicator,
+        description: response.status.description,
+    })
+}
+
+/// Whether `latest` is a newer dotted version than `installed`
+pub(crate) fn is_newer_version(installed: &str, latest: &str) -> bool {
+    let parts = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split(['.', '-'])
+            .map_while(|p| p.parse().ok())
+            .collect()
+    };
+    parts(latest) > parts(installed)
+}
+
+/// Start of the calendar month (UTC) containing `now` (Unix seconds)
+pub(crate) fn month_start(now: u64) -> u64 {
+    const DAY: u64 = 86_400;
+    let days = now / DAY;
+
+    // Civil-from-days (Howard Hinnant), enough to find the day of month
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day_of_month = (doy - (153 * mp + 2) / 5 + 1) as u64;
+
+    (days - (day_of_month - 1)) * DAY
+}
+
+/// Sum Claude run costs since `period_start` across all sessions
+pub(crate) fn claude_spend_since(
+    app: &impl PathProvider,
+    period_start: u64,
+    budget_usd: Option<f64>,
+) -> Result<ProviderSpend, String> {
+    let mut current_usd = 0.0;
+    let mut runs_without_cost = 0;
+
+    for session_id in list_all_session_ids(app)? {
+        let Ok(Some(metadata)) = load_metadata(app, &session_id) else {
+            continue;
+        };
+        for run in metadata
+            .runs
+            .iter()
+            .filter(|r| r.started_at >= period_start)
+        {
+            match run.usage.as_ref().and_then(|u| u.cost_usd) {
+                Some(cost) => current_usd += cost,
+                None => runs_without_cost += 1,
+            }
+        }
+    }
+
+    Ok(ProviderSpend {
+        current_usd,
+        budget_usd,
+        period_start,
+        over_budget: budget_usd.is_some_and(|b| current_usd > b),
+        runs_without_cost,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::storage::save_metadata;
+    use crate::chat::types::{RunEntry, RunStatus, SessionMetadata, UsageData};
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_parse_status_page() {
+        let health = parse_status_page(
+            r#"{"page":{"id":"x"},"status":{"indicator":"minor","description":"Partially Degraded Service"}}"#,
+        )
+        .unwrap();
+        assert_eq!(health.indicator, "minor");
+        assert_eq!(health.description, "Partially Degraded Service");
+        assert!(parse_status_page("<html>").is_err());
+    }
+
+    #[test]
+    fn test_is_newer_version() {
+        assert!(is_newer_version("1.0.28", "1.0.30"));
+        assert!(is_newer_version("2.9.0", "2.10.0"));
+        assert!(!is_newer_version("v2.10.0", "2.10.0"));
+        assert!(!is_newer_version("2.10.1", "2.10.0"));
+    }
+
+    #[test]
+    fn test_month_start() {
+        // 2026-10-17T12:00:00Z -> 2026-10-01T00:00:00Z
+        assert_eq!(month_start(1_792_238_400), 1_790_812_800);
+        // Exactly the first of the month stays put
+        assert_eq!(month_start(1_790_812_800), 1_790_812_800);
+        // 2024-02-29 (leap day) -> 2024-02-01
+        assert_eq!(month_start(1_709_208_000), 1_706_745_600);
+    }
+
+    fn run(started_at: u64, cost_usd: Option<f64>) -> RunEntry {
+        RunEntry {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            user_message_id: "msg".to_string(),
+            user_message: "hi".to_string(),
+            model: None,
+            execution_mode: None,
+            thinking_level: None,
+            effort_level: None,
+            started_at,
+            ended_at: Some(started_at + 1),
+            status: RunStatus::Completed,
+            assistant_message_id: None,
+            cancelled: false,
+            recovered: false,
+            claude_session_id: None,
+            pid: None,
+            usage: Some(UsageData {
+                cost_usd,
+                ..Default::default()
+            }),
+            attribution: None,
+            subproject: None,
+        }
+    }
+
+    #[test]
+    fn test_claude_spend_since() {
+        let paths = TempPaths::new();
+        let mut metadata = SessionMetadata::new(
+            "sess-1".to_string(),
+            "wt-1".to_string(),
+            "Session 1".to_string(),
+            0,
+        );
+        metadata.runs = vec![
+            run(500, Some(9.0)), // before the period
+            run(1_000, Some(1.25)),
+            run(2_000, Some(0.5)),
+            run(3_000, None),
+        ];
+        save_metadata(&paths, &metadata).unwrap();
+
+        let spend = claude_spend_since(&paths, 1_000, Some(1.5)).unwrap();
+        assert!((spend.current_usd - 1.75).abs() < f64::EPSILON);
+        assert!(spend.over_budget);
+        assert_eq!(spend.runs_without_cost, 1);
+    }
+}
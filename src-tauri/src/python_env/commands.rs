@@ -0,0 +1,61 @@
+//! Tauri commands for Python tool environments
+
+use tauri::AppHandle;
+
+use super::{PythonEnv, PythonStatus};
+
+/// Run a blocking env operation off the async runtime
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Python environment task failed: {e}"))?
+}
+
+/// Interpreter new environments would be created with
+#[tauri::command]
+pub async fn detect_python(app: AppHandle) -> Result<PythonStatus, String> {
+    blocking(move || Ok(super::detect(&app))).await
+}
+
+/// Create an isolated venv for a tool and pip-install `package` into it
+#[tauri::command]
+pub async fn create_python_env(
+    app: AppHandle,
+    tool: String,
+    package: String,
+) -> Result<PythonEnv, String> {
+    log::trace!("Creating Python environment for {tool} ({package})");
+    let _power_guard = crate::power::PowerGuard::acquire(&app, "Python tool install").await;
+    blocking(move || super::create_env(&app, &tool, &package)).await
+}
+
+/// All tool environments with their health
+#[tauri::command]
+pub async fn list_python_envs(app: AppHandle) -> Result<Vec<PythonEnv>, String> {
+    blocking(move || Ok(super::list_envs(&app))).await
+}
+
+/// Rebuild a tool's environment if it's broken
+#[tauri::command]
+pub async fn repair_python_env(app: AppHandle, tool: String) -> Result<PythonEnv, String> {
+    log::trace!("Repairing Python environment for {tool}");
+    blocking(move || super::repair_env(&app, &tool)).await
+}
+
+/// Delete a tool's environment
+#[tauri::command]
+pub async fn remove_python_env(app: AppHandle, tool: String) -> Result<(), String> {
+    log::trace!("Removing Python environment for {tool}");
+    blocking(move || super::remove_env(&app, &tool)).await
+}
+
+/// Executable of a tool installed in its environment, if any
+#[tauri::command]
+pub async fn get_python_tool_entry_point(
+    app: AppHandle,
+    tool: String,
+) -> Result<Option<String>, String> {
+    Ok(super::resolve_entry_point(&app, &tool).map(|p| p.to_string_lossy().into_owned()))
+}
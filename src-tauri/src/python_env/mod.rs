@@ -0,0 +1,517 @@
+//! Isolated Python environments for Python-based agent tools (e.g. aider)
+//!
+//! Each tool gets its own venv in `{app_data}/python-envs/{tool}/`, created
+//! with a detected interpreter (3.9+) or the managed standalone build that
+//! `install_tools` can download into `{app_data}/python-runtime/`. The
+//! requested package is pip-installed into the venv and recorded in
+//! `jean-env.json`, so a broken env can be rebuilt by `repair_env`. On Unix,
+//! the tool's entry point is linked into `python-envs/bin/`, which sessions
+//! get on their PATH.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::platform::silent_command;
+use crate::runtime::PathProvider;
+
+pub mod commands;
+
+/// Oldest interpreter venvs are created with
+const MIN_PYTHON: (u32, u32) = (3, 9);
+
+/// Managed standalone build (python-build-standalone)
+const STANDALONE_RELEASE: &str = "20241016";
+const STANDALONE_PYTHON: &str = "3.12.7";
+const STANDALONE_DOWNLOAD: &str =
+    "https://github.com/astral-sh/python-build-standalone/releases/download";
+
+pub(crate) const RUNTIME_DIR: &str = "python-runtime";
+const ENVS_DIR: &str = "python-envs";
+const ENV_MANIFEST: &str = "jean-env.json";
+
+/// An interpreter usable for creating venvs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonStatus {
+    pub path: Option<String>,
+    /// e.g. "3.12.7"
+    pub version: Option<String>,
+    /// Whether this is Jean's managed standalone build
+    pub managed: bool,
+    /// Present and at least 3.9
+    pub suitable: bool,
+}
+
+/// A tool's environment, as recorded when it was created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonEnv {
+    pub tool: String,
+    /// pip requirement the env was built from (e.g. "aider-chat==0.60.0")
+    pub package: String,
+    /// Interpreter the venv was created with
+    pub interpreter: String,
+    pub created_at: u64,
+    /// Absolute path of the tool's executable, if it exists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_point: Option<String>,
+    /// The venv's interpreter still runs
+    #[serde(default)]
+    pub healthy: bool,
+}
+
+/// The standalone build archive, resolved for this platform
+pub struct PythonDownload {
+    pub url: String,
+    pub checksum: String,
+}
+
+fn target() -> Result<&'static str, String> {
+    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+    {
+        return Ok("aarch64-apple-darwin");
+    }
+
+    #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+    {
+        return Ok("x86_64-apple-darwin");
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    {
+        return Ok("x86_64-unknown-linux-gnu");
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    {
+        return Ok("aarch64-unknown-linux-gnu");
+    }
+
+    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+    {
+        return Ok("x86_64-pc-windows-msvc");
+    }
+
+    #[allow(unreachable_code)]
+    Err("Unsupported platform".to_string())
+}
+
+fn runtime_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.shared_data_dir()?.join(RUNTIME_DIR))
+}
+
+fn envs_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.shared_data_dir()?.join(ENVS_DIR))
+}
+
+/// Directory holding entry point links for sessions' PATH
+pub fn shim_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(envs_dir(app)?.join("bin"))
+}
+
+fn env_dir(app: &impl PathProvider, tool: &str) -> Result<PathBuf, String> {
+    validate_tool_name(tool)?;
+    Ok(envs_dir(app)?.join(tool))
+}
+
+/// Executable directory of a venv or standalone install
+fn scripts_dir(root: &Path) -> PathBuf {
+    if cfg!(windows) {
+        root.join("Scripts")
+    } else {
+        root.join("bin")
+    }
+}
+
+fn exe_name(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{name}.exe")
+    } else {
+        name.to_string()
+    }
+}
+
+fn venv_python(env_dir: &Path) -> PathBuf {
+    scripts_dir(env_dir).join(exe_name("python"))
+}
+
+fn validate_tool_name(tool: &str) -> Result<(), String> {
+    let valid = !tool.is_empty()
+        && tool != "bin"
+        && tool
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid tool name: {tool}"))
+    }
+}
+
+/// Only plain requirement specs; they end up on pip's command line
+fn validate_package(package: &str) -> Result<(), String> {
+    let valid = !package.is_empty()
+        && !package.starts_with('-')
+        && package
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-=<>!~[],".contains(c));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid package: {package}"))
+    }
+}
+
+/// Parse "Python 3.12.7" into (major, minor) and the version string
+fn parse_version(output: &str) -> Option<((u32, u32), String)> {
+    let version = output.trim().strip_prefix("Python ")?.trim().to_string();
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some(((major, minor), version))
+}
+
+fn python_version(python: &Path) -> Option<((u32, u32), String)> {
+    let output = silent_command(python).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Python 2 printed its version to stderr
+    let text = if output.stdout.is_empty() {
+        output.stderr
+    } else {
+        output.stdout
+    };
+    parse_version(&String::from_utf8_lossy(&text))
+}
+
+/// Find the interpreter new venvs are created with: the managed build if
+/// installed, otherwise python3/python from PATH
+pub fn detect(app: &impl PathProvider) -> PythonStatus {
+    let managed = runtime_dir(app)
+        .ok()
+        .map(|root| {
+            if cfg!(windows) {
+                root.join("python.exe")
+            } else {
+                root.join("bin").join("python3")
+            }
+        })
+        .filter(|p| p.exists());
+
+    let (path, is_managed) = match managed {
+        Some(path) => (Some(path), true),
+        None => (
+            which::which("python3")
+                .or_else(|_| which::which("python"))
+                .ok(),
+            false,
+        ),
+    };
+
+    let version = path.as_deref().and_then(python_version);
+    PythonStatus {
+        path: path.map(|p| p.to_string_lossy().into_owned()),
+        suitable: version.as_ref().is_some_and(|(v, _)| *v >= MIN_PYTHON),
+        version: version.map(|(_, v)| v),
+        managed: is_managed,
+    }
+}
+
+fn archive_name(target: &str) -> String {
+    format!("cpython-{STANDALONE_PYTHON}+{STANDALONE_RELEASE}-{target}-install_only.tar.gz")
+}
+
+/// Resolve the standalone build for this platform and its published checksum
+pub async fn resolve_download() -> Result<PythonDownload, String> {
+    let file_name = archive_name(target()?);
+    let base = format!("{STANDALONE_DOWNLOAD}/{STANDALONE_RELEASE}");
+
+    let client = crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+    let response = client
+        .get(format!("{base}/SHA256SUMS"))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch Python checksums: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to fetch Python checksums: HTTP {}",
+            response.status()
+        ));
+    }
+    let sums = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read Python checksums: {e}"))?;
+    let checksum = sums
+        .lines()
+        .find_map(|line| {
+            let (hash, name) = line.split_once(char::is_whitespace)?;
+            (name.trim() == file_name).then(|| hash.to_string())
+        })
+        .ok_or_else(|| format!("No checksum found for {file_name}"))?;
+
+    Ok(PythonDownload {
+        url: format!("{base}/{}", file_name.replace('+', "%2B")),
+        checksum,
+    })
+}
+
+/// Verify and unpack the standalone build into `python-runtime/`
+pub fn install_runtime(
+    app: &impl PathProvider,
+    download: &PythonDownload,
+    archive: &[u8],
+) -> Result<(), String> {
+    let computed = format!("{:x}", Sha256::digest(archive));
+    if computed != download.checksum.to_lowercase() {
+        return Err(format!(
+            "Checksum mismatch: expected {}, got {computed}",
+            download.checksum
+        ));
+    }
+
+    let runtime_dir = runtime_dir(app)?;
+    let temp_dir = runtime_dir.with_extension("partial");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).map_err(|e| format!("Failed to create temp directory: {e}"))?;
+
+    let decoder = flate2::read::GzDecoder::new(archive);
+    let result = tar::Archive::new(decoder)
+        .unpack(&temp_dir)
+        .map_err(|e| format!("Failed to extract Python archive: {e}"))
+        .and_then(|_| {
+            // install_only archives hold a single python/ directory
+            let _ = fs::remove_dir_all(&runtime_dir);
+            fs::rename(temp_dir.join("python"), &runtime_dir)
+                .map_err(|e| format!("Failed to install Python runtime: {e}"))
+        });
+    let _ = fs::remove_dir_all(&temp_dir);
+    result?;
+
+    log::info!("Installed Python {STANDALONE_PYTHON} into {runtime_dir:?}");
+    Ok(())
+}
+
+fn run(cmd: &mut std::process::Command, what: &str) -> Result<(), String> {
+    let output = cmd.output().map_err(|e| format!("Failed to {what}: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to {what}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn load_env(env_dir: &Path) -> Option<PythonEnv> {
+    let content = fs::read_to_string(env_dir.join(ENV_MANIFEST)).ok()?;
+    let mut env: PythonEnv = serde_json::from_str(&content).ok()?;
+    let entry_point = scripts_dir(env_dir).join(exe_name(&env.tool));
+    env.entry_point = entry_point
+        .exists()
+        .then(|| entry_point.to_string_lossy().into_owned());
+    env.healthy = run(
+        silent_command(venv_python(env_dir)).args(["-c", "import sys"]),
+        "run the venv interpreter",
+    )
+    .is_ok();
+    Some(env)
+}
+
+/// Link a tool's entry point into the shim directory (Unix only; Windows
+/// callers use `entry_point` directly)
+fn link_shim(app: &impl PathProvider, tool: &str, entry_point: &Path) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        let shim_dir = shim_dir(app)?;
+        fs::create_dir_all(&shim_dir)
+            .map_err(|e| format!("Failed to create shim directory: {e}"))?;
+        let shim = shim_dir.join(tool);
+        let _ = fs::remove_file(&shim);
+        std::os::unix::fs::symlink(entry_point, &shim)
+            .map_err(|e| format!("Failed to link {tool}: {e}"))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (app, tool, entry_point);
+    Ok(())
+}
+
+/// Create (or recreate) a tool's venv and pip-install `package` into it
+pub fn create_env(app: &impl PathProvider, tool: &str, package: &str) -> Result<PythonEnv, String> {
+    validate_package(package)?;
+    let env_dir = env_dir(app, tool)?;
+
+    let python = detect(app);
+    let interpreter = match (python.path, python.suitable) {
+        (Some(path), true) => path,
+        _ => {
+            return Err(match python.version {
+                Some(v) => format!(
+                    "Python {v} is too old (need {}.{} or newer)",
+                    MIN_PYTHON.0, MIN_PYTHON.1
+                ),
+                None => "Python is not installed".to_string(),
+            })
+        }
+    };
+
+    log::trace!("Creating venv for {tool} with {interpreter}");
+    let _ = fs::remove_dir_all(&env_dir);
+    fs::create_dir_all(env_dir.parent().unwrap_or(&env_dir))
+        .map_err(|e| format!("Failed to create environments directory: {e}"))?;
+    run(
+        silent_command(&interpreter)
+            .args(["-m", "venv"])
+            .arg(&env_dir),
+        "create venv",
+    )?;
+
+    let result = run(
+        silent_command(venv_python(&env_dir)).args([
+            "-m",
+            "pip",
+            "install",
+            "--disable-pip-version-check",
+            package,
+        ]),
+        &format!("install {package}"),
+    );
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&env_dir);
+        return Err(e);
+    }
+
+    let env = PythonEnv {
+        tool: tool.to_string(),
+        package: package.to_string(),
+        interpreter,
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        entry_point: None,
+        healthy: true,
+    };
+    let content = serde_json::to_string_pretty(&env)
+        .map_err(|e| format!("Failed to serialize environment: {e}"))?;
+    fs::write(env_dir.join(ENV_MANIFEST), content)
+        .map_err(|e| format!("Failed to save environment: {e}"))?;
+
+    let env = load_env(&env_dir).ok_or_else(|| format!("Failed to load environment for {tool}"))?;
+    match &env.entry_point {
+        Some(entry_point) => link_shim(app, tool, Path::new(entry_point))?,
+        None => log::warn!("{package} installed no `{tool}` executable"),
+    }
+    log::info!("Created Python environment for {tool} ({package})");
+    Ok(env)
+}
+
+/// All tool environments
+pub fn list_envs(app: &impl PathProvider) -> Vec<PythonEnv> {
+    let Ok(entries) = envs_dir(app).and_then(|dir| fs::read_dir(dir).map_err(|e| e.to_string()))
+    else {
+        return vec![];
+    };
+    let mut envs: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| load_env(&entry.path()))
+        .collect();
+    envs.sort_by(|a, b| a.tool.cmp(&b.tool));
+    envs
+}
+
+/// Rebuild a tool's env from its recorded package if it no longer works
+/// (e.g. the interpreter it was created with was upgraded or removed)
+pub fn repair_env(app: &impl PathProvider, tool: &str) -> Result<PythonEnv, String> {
+    let env_dir = env_dir(app, tool)?;
+    let env = load_env(&env_dir).ok_or_else(|| format!("No environment for {tool}"))?;
+    if env.healthy && env.entry_point.is_some() {
+        return Ok(env);
+    }
+    log::info!("Repairing Python environment for {tool}");
+    create_env(app, tool, &env.package)
+}
+
+/// Delete a tool's env and its shim
+pub fn remove_env(app: &impl PathProvider, tool: &str) -> Result<(), String> {
+    let env_dir = env_dir(app, tool)?;
+    if let Ok(shim_dir) = shim_dir(app) {
+        let _ = fs::remove_file(shim_dir.join(tool));
+    }
+    if env_dir.exists() {
+        fs::remove_dir_all(&env_dir)
+            .map_err(|e| format!("Failed to remove environment for {tool}: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Executable of a tool installed in its env, for spawning it directly
+pub fn resolve_entry_point(app: &impl PathProvider, tool: &str) -> Option<PathBuf> {
+    let env_dir = env_dir(app, tool).ok()?;
+    let entry_point = scripts_dir(&env_dir).join(exe_name(tool));
+    entry_point.exists().then_some(entry_point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            parse_version("Python 3.12.7\n"),
+            Some(((3, 12), "3.12.7".to_string()))
+        );
+        assert!(parse_version("Python 3.8.10").unwrap().0 < MIN_PYTHON);
+        assert_eq!(parse_version("not python"), None);
+    }
+
+    #[test]
+    fn test_names_are_validated() {
+        let paths = TempPaths::new();
+        for tool in ["", "bin", "../x", "Aider"] {
+            assert!(env_dir(&paths, tool).is_err(), "{tool}");
+        }
+        assert!(validate_package("aider-chat==0.60.0").is_ok());
+        assert!(validate_package("aider-chat[playwright]").is_ok());
+        assert!(validate_package("--index-url=http://evil").is_err());
+        assert!(validate_package("pkg; rm -rf /").is_err());
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let paths = TempPaths::new();
+        let dir = env_dir(&paths, "aider").unwrap();
+        let scripts = scripts_dir(&dir);
+        fs::create_dir_all(&scripts).unwrap();
+        fs::write(scripts.join(exe_name("aider")), "").unwrap();
+        let env = PythonEnv {
+            tool: "aider".to_string(),
+            package: "aider-chat".to_string(),
+            interpreter: "/usr/bin/python3".to_string(),
+            created_at: 1,
+            entry_point: None,
+            healthy: false,
+        };
+        fs::write(dir.join(ENV_MANIFEST), serde_json::to_string(&env).unwrap()).unwrap();
+
+        let envs = list_envs(&paths);
+        assert_eq!(envs.len(), 1);
+        assert_eq!(envs[0].package, "aider-chat");
+        // There's no interpreter in this fake venv
+        assert!(!envs[0].healthy);
+        assert!(resolve_entry_point(&paths, "aider").is_some());
+
+        remove_env(&paths, "aider").unwrap();
+        assert!(list_envs(&paths).is_empty());
+        assert!(resolve_entry_point(&paths, "aider").is_none());
+    }
+}
@@ -0,0 +1,13 @@
+//! Tauri commands for reading quota telemetry
+
+use super::{read_events, QuotaWarning};
+
+/// List recorded quota / rate-limit warnings, newest first
+///
+/// `since` limits results to warnings detected at or after that Unix time.
+#[tauri::command]
+pub async fn list_quota_events(since: Option<u64>) -> Result<Vec<QuotaWarning>, String> {
+    let mut events = read_events(since);
+    events.reverse();
+    Ok(events)
+}
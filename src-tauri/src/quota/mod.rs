@@ -0,0 +1,284 @@
+//! Quota and rate-limit telemetry from CLI output
+//!
+//! Recognizes rate-limit / usage-limit messages in Claude and GitHub CLI
+//! output, emits them as `provider:quota-warning` events and appends them to
+//! `analytics/quota-events.jsonl` in app data, so failed runs can be lined up
+//! with quota resets later.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use once_cell::sync::{Lazy, OnceCell};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::{now, EventSink, PathProvider};
+
+pub mod commands;
+
+/// Directory (within app data) for local analytics logs
+const ANALYTICS_DIR_NAME: &str = "analytics";
+
+/// Quota events log file name
+const QUOTA_EVENTS_FILE: &str = "quota-events.jsonl";
+
+/// Identical warnings within this window are reported once
+/// (a limit message is often repeated on every retry)
+const DEDUPE_WINDOW_SECS: u64 = 60;
+
+/// Quota events log path (set by `init`)
+static QUOTA_LOG: OnceCell<PathBuf> = OnceCell::new();
+
+/// Last recorded warning key and when it was recorded
+static LAST_WARNING: Lazy<Mutex<Option<(String, u64)>>> = Lazy::new(|| Mutex::new(None));
+
+static RELATIVE_RESET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:in|after|retry-after:?)\s+(\d+)\s*(seconds?|secs?|s|minutes?|mins?|m|hours?|hrs?|h)\b")
+        .unwrap()
+});
+
+static EPOCH_RESET: Lazy<Regex> = Lazy::new(|| Regex::new(r"limit reached\|(\d{9,})").unwrap());
+
+static RESETS_TEXT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\bresets?(?:\s+at)?\s+([^.\n]+)").unwrap());
+
+/// What kind of limit was hit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitType {
+    /// Request rate limit (HTTP 429)
+    RateLimit,
+    /// GitHub's secondary (abuse) rate limit
+    SecondaryRateLimit,
+    /// Subscription usage limit reached
+    UsageLimit,
+    /// Close to the subscription usage limit ("approaching usage limit")
+    UsageWarning,
+}
+
+/// A recognized quota / rate-limit message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaWarning {
+    /// "claude" or "github"
+    pub provider: String,
+    pub limit_type: LimitType,
+    /// When the limit resets (Unix seconds), if the message says
+    pub reset_at: Option<u64>,
+    /// Reset time as written in the message when it can't be resolved
+    /// (e.g. "3pm (Europe/Berlin)")
+    pub reset_text: Option<String>,
+    /// The line the warning was parsed from
+    pub message: String,
+    /// Unix timestamp when it was seen
+    pub detected_at: u64,
+    pub session_id: Option<String>,
+}
+
+/// Remember where quota events are logged (call once during setup)
+pub fn init(app: &impl PathProvider) {
+    match app.app_data_dir() {
+        Ok(dir) => {
+            let _ = QUOTA_LOG.set(dir.join(ANALYTICS_DIR_NAME).join(QUOTA_EVENTS_FILE));
+        }
+        Err(e) => log::warn!("Quota telemetry disabled: {e}"),
+    }
+}
+
+/// Parse a quota / rate-limit warning from one line of CLI output
+pub fn parse_quota_warning(provider: &str, line: &str, now: u64) -> Option<QuotaWarning> {
+    let lower = line.to_lowercase();
+
+    let limit_type = if lower.contains("approaching") && lower.contains("limit") {
+        LimitType::UsageWarning
+    } else if lower.contains("usage limit") || lower.contains("5-hour limit") {
+        LimitType::UsageLimit
+    } else if lower.contains("secondary rate limit") {
+        LimitType::SecondaryRateLimit
+    } else if lower.contains("rate limit")
+        || lower.contains("rate_limit")
+        || lower.contains("too many requests")
+        || lower.contains(" 429")
+    {
+        LimitType::RateLimit
+    } else {
+        return None;
+    };
+
+    let reset_at = EPOCH_RESET
+        .captures(&lower)
+        .and_then(|c| c[1].parse::<u64>().ok())
+        .or_else(|| {
+            RELATIVE_RESET.captures(line).and_then(|c| {
+                let amount: u64 = c[1].parse().ok()?;
+                let unit = c[2].to_lowercase();
+                let secs = if unit.starts_with('h') {
+                    amount * 3600
+                } else if unit.starts_with('m') {
+                    amount * 60
+                } else {
+                    amount
+                };
+                Some(now + secs)
+            })
+        });
+    let reset_text = if reset_at.is_none() {
+        RESETS_TEXT
+            .captures(line)
+            .map(|c| c[1].trim().to_string())
+            .filter(|t| !t.is_empty())
+    } else {
+        None
+    };
+
+    Some(QuotaWarning {
+        provider: provider.to_string(),
+        limit_type,
+        reset_at,
+        reset_text,
+        message: line.trim().to_string(),
+        detected_at: now,
+        session_id: None,
+    })
+}
+
+/// Check CLI output for quota warnings and record any that are found
+///
+/// Multi-line output is checked line by line; at most one warning is
+/// recorded per call.
+pub fn observe(
+    events: &impl EventSink,
+    provider: &str,
+    session_id: Option<&str>,
+    output: &str,
+) -> Option<QuotaWarning> {
+    let now = now();
+    let mut warning = output
+        .lines()
+        .find_map(|line| parse_quota_warning(provider, line, now))?;
+    warning.session_id = session_id.map(str::to_string);
+
+    let key = format!(
+        "{}:{:?}:{:?}:{:?}",
+        warning.provider, warning.limit_type, warning.reset_at, warning.reset_text
+    );
+    if let Ok(mut last) = LAST_WARNING.lock() {
+        if let Some((last_key, at)) = last.as_ref() {
+            if *last_key == key && now.saturating_sub(*at) < DEDUPE_WINDOW_SECS {
+                return None;
+            }
+        }
+        *last = Some((key, now));
+    }
+
+    log::warn!(
+        "{} quota warning ({:?}): {}",
+        warning.provider,
+        warning.limit_type,
+        warning.message
+    );
+    if let Err(e) = append_event(&warning) {
+        log::warn!("Failed to record quota warning: {e}");
+    }
+    if let Err(e) = events.emit_all("provider:quota-warning", &warning) {
+        log::warn!("Failed to emit quota warning: {e}");
+    }
+    Some(warning)
+}
+
+fn append_event(warning: &QuotaWarning) -> Result<(), String> {
+    let Some(path) = QUOTA_LOG.get() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create analytics directory: {e}"))?;
+    }
+    let line = serde_json::to_string(warning)
+        .map_err(|e| format!("Failed to serialize quota warning: {e}"))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open quota log: {e}"))?;
+    writeln!(file, "{line}").map_err(|e| format!("Failed to write quota log: {e}"))
+}
+
+/// Read recorded warnings (oldest first), skipping unreadable lines
+pub(crate) fn read_events(since: Option<u64>) -> Vec<QuotaWarning> {
+    let Some(content) = QUOTA_LOG
+        .get()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+    else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<QuotaWarning>(line).ok())
+        .filter(|w| since.is_none_or(|since| w.detected_at >= since))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_790_000_000;
+
+    #[test]
+    fn test_usage_limit_with_epoch_reset() {
+        let w =
+            parse_quota_warning("claude", "Claude AI usage limit reached|1790003600", NOW).unwrap();
+        assert_eq!(w.limit_type, LimitType::UsageLimit);
+        assert_eq!(w.reset_at, Some(1_790_003_600));
+        assert!(w.reset_text.is_none());
+    }
+
+    #[test]
+    fn test_usage_limit_with_reset_text() {
+        let w = parse_quota_warning(
+            "claude",
+            "5-hour limit reached ∙ resets 3pm (Europe/Berlin)",
+            NOW,
+        )
+        .unwrap();
+        assert_eq!(w.limit_type, LimitType::UsageLimit);
+        assert_eq!(w.reset_text.as_deref(), Some("3pm (Europe/Berlin)"));
+
+        let w =
+            parse_quota_warning("claude", "Approaching usage limit · resets at 9pm", NOW).unwrap();
+        assert_eq!(w.limit_type, LimitType::UsageWarning);
+        assert_eq!(w.reset_text.as_deref(), Some("9pm"));
+    }
+
+    #[test]
+    fn test_rate_limits() {
+        let w = parse_quota_warning(
+            "claude",
+            r#"API Error: 429 {"type":"error","error":{"type":"rate_limit_error"}} · Retrying in 30 seconds"#,
+            NOW,
+        )
+        .unwrap();
+        assert_eq!(w.limit_type, LimitType::RateLimit);
+        assert_eq!(w.reset_at, Some(NOW + 30));
+
+        let w = parse_quota_warning(
+            "github",
+            "GraphQL: You have exceeded a secondary rate limit. Please wait a few minutes.",
+            NOW,
+        )
+        .unwrap();
+        assert_eq!(w.limit_type, LimitType::SecondaryRateLimit);
+
+        let w =
+            parse_quota_warning("github", "API rate limit exceeded for user ID 1.", NOW).unwrap();
+        assert_eq!(w.limit_type, LimitType::RateLimit);
+        assert!(w.reset_at.is_none());
+    }
+
+    #[test]
+    fn test_unrelated_output_is_ignored() {
+        assert!(parse_quota_warning("claude", "API Error: 529 Overloaded", NOW).is_none());
+        assert!(parse_quota_warning("github", "Could not resolve to a Repository", NOW).is_none());
+    }
+}
@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use tauri::AppHandle;
+
+use crate::projects::storage::load_projects_data;
+
+/// Default budget for `get_repo_map`
+const DEFAULT_BUDGET_TOKENS: u32 = 2000;
+
+/// The repository map of a project (or one of its worktrees) as markdown,
+/// built first if there's none yet
+#[tauri::command]
+pub async fn get_repo_map(
+    app: AppHandle,
+    project_id: String,
+    worktree_id: Option<String>,
+    budget_tokens: Option<u32>,
+) -> Result<String, String> {
+    let data = load_projects_data(&app)?;
+    let project = data
+        .find_project(&project_id)
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    let root = match worktree_id {
+        Some(worktree_id) => data
+            .find_worktree(&worktree_id)
+            .filter(|w| w.project_id == project_id)
+            .map(|w| w.path.clone())
+            .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?,
+        None if project.is_folder => return Err("Folders have no repository to map".to_string()),
+        None => project.path.clone(),
+    };
+    let budget = budget_tokens.unwrap_or(DEFAULT_BUDGET_TOKENS) as usize;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = Path::new(&root);
+        let map = match super::load(&app, root) {
+            Some(map) => map,
+            None => super::refresh(&app, root)?,
+        };
+        Ok(super::render(&map, budget))
+    })
+    .await
+    .map_err(|e| format!("Failed to build repo map: {e}"))?
+}
@@ -0,0 +1,506 @@
+//! Repository maps: a project's file tree with symbol outlines
+//!
+//! For large codebases a compact map of where things are saves the agent a
+//! lot of exploring. A map lists every source file (respecting .gitignore)
+//! with the functions, types and classes it defines, found by per-language
+//! line patterns. Maps are cached per directory in
+//! `{app_data}/repo-maps/{uuid-v5 of path}.json` and refreshed incrementally:
+//! only files whose size or modification time changed are parsed again.
+//!
+//! A map is built when a project is added, refreshed by the git status
+//! poller while its worktree is active, and rendered to fit a token budget
+//! by [`render`] (see `get_repo_map`).
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::runtime::PathProvider;
+
+pub mod commands;
+
+/// Maps directory (within app data)
+const MAPS_DIR: &str = "repo-maps";
+
+/// Files larger than this are listed without symbols
+const MAX_PARSE_BYTES: u64 = 512 * 1024;
+
+/// Files mapped per repository
+const MAX_FILES: usize = 20_000;
+
+/// Seconds before the poller refreshes a map again
+const REFRESH_AFTER_SECS: u64 = 60;
+
+/// Rough size of a token, for budgets
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Repositories with at least this many mapped files get their map as
+/// session context; smaller ones are cheap enough to explore
+pub const LARGE_REPO_FILES: usize = 200;
+
+/// Roots being refreshed, so the poller doesn't pile up walks
+static REFRESHING: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// A definition found in a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    /// Keyword it was defined with, e.g. "fn", "class", "interface"
+    pub kind: String,
+    pub name: String,
+    /// 1-based
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileEntry {
+    /// Relative to the map's root, with `/` separators
+    path: String,
+    size: u64,
+    modified: u64,
+    symbols: Vec<Symbol>,
+}
+
+/// A directory's map
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoMap {
+    pub root: String,
+    /// Unix seconds
+    pub generated_at: u64,
+    files: Vec<FileEntry>,
+}
+
+impl RepoMap {
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+}
+
+/// A definition pattern; `kind` comes from the pattern's `kind` group
+/// unless fixed here
+struct Pattern {
+    kind: Option<&'static str>,
+    re: Regex,
+}
+
+fn pattern(kind: Option<&'static str>, re: &str) -> Pattern {
+    Pattern {
+        kind,
+        re: Regex::new(re).unwrap(),
+    }
+}
+
+/// Definition patterns by file extension. Up to one level of indentation
+/// is allowed so methods are listed, but not nested helpers.
+static LANGUAGES: Lazy<Vec<(&'static [&'static str], Vec<Pattern>)>> = Lazy::new(|| {
+    vec![
+        (
+            &["rs"][..],
+            vec![pattern(
+                None,
+                r#"^(?: {4}|\t)?(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|unsafe|const|extern(?:\s+"[^"]*")?)\s+)*(?P<kind>fn|struct|enum|trait|type|mod|macro_rules!)\s*(?P<name>[A-Za-z_]\w*)"#,
+            )],
+        ),
+        (
+            &["ts", "tsx", "js", "jsx", "mjs", "cjs"][..],
+            vec![
+                pattern(
+                    None,
+                    r"^(?: {2}| {4}|\t)?(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(?P<kind>function|class|interface|type|enum)\*?\s+(?P<name>[A-Za-z_$][\w$]*)",
+                ),
+                pattern(
+                    Some("const"),
+                    r"^export\s+(?:const|let)\s+(?P<name>[A-Za-z_$][\w$]*)",
+                ),
+            ],
+        ),
+        (
+            &["py"][..],
+            vec![pattern(
+                None,
+                r"^(?: {4}|\t)?(?:async\s+)?(?P<kind>def|class)\s+(?P<name>[A-Za-z_]\w*)",
+            )],
+        ),
+        (
+            &["go"][..],
+            vec![
+                pattern(
+                    Some("func"),
+                    r"^func\s+(?:\([^)]*\)\s*)?(?P<name>[A-Za-z_]\w*)",
+                ),
+                pattern(Some("type"), r"^type\s+(?P<name>[A-Za-z_]\w*)"),
+            ],
+        ),
+        (
+            &["rb"][..],
+            vec![pattern(
+                None,
+                r"^(?: {2}|\t)?(?P<kind>def|class|module)\s+(?P<name>[\w.:?!=]+)",
+            )],
+        ),
+    ]
+});
+
+fn patterns_for(path: &Path) -> Option<&'static [Pattern]> {
+    let ext = path.extension()?.to_str()?;
+    LANGUAGES
+        .iter()
+        .find(|(exts, _)| exts.contains(&ext))
+        .map(|(_, patterns)| patterns.as_slice())
+}
+
+/// Definitions in a file's source
+fn extract_symbols(patterns: &[Pattern], source: &str) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        for pattern in patterns {
+            if let Some(caps) = pattern.re.captures(line) {
+                let kind = pattern
+                    .kind
+                    .or_else(|| caps.name("kind").map(|k| k.as_str()))
+                    .unwrap_or_default();
+                symbols.push(Symbol {
+                    kind: kind.trim_end_matches('!').to_string(),
+                    name: caps["name"].to_string(),
+                    line: i + 1,
+                });
+                break;
+            }
+        }
+    }
+    symbols
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Map `root`, reusing entries of `previous` for unchanged files
+pub fn build(root: &Path, previous: Option<&RepoMap>) -> RepoMap {
+    let known: HashMap<&str, &FileEntry> = previous
+        .map(|map| map.files.iter().map(|f| (f.path.as_str(), f)).collect())
+        .unwrap_or_default();
+
+    let walker = WalkBuilder::new(root)
+        .hidden(true)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .require_git(false)
+        .build();
+
+    let mut files = Vec::new();
+    let mut parsed = 0;
+    for entry in walker.flatten() {
+        if files.len() >= MAX_FILES {
+            log::warn!("Repo map of {root:?} stopped at {MAX_FILES} files");
+            break;
+        }
+        let path = entry.path();
+        let Some(patterns) = patterns_for(path) else {
+            continue;
+        };
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let size = metadata.len();
+        let modified = modified_secs(&metadata);
+
+        if let Some(entry) = known
+            .get(relative.as_str())
+            .filter(|e| e.size == size && e.modified == modified)
+        {
+            files.push((*entry).clone());
+            continue;
+        }
+
+        let symbols = if size <= MAX_PARSE_BYTES {
+            parsed += 1;
+            fs::read_to_string(path)
+                .map(|source| extract_symbols(patterns, &source))
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        files.push(FileEntry {
+            path: relative,
+            size,
+            modified,
+            symbols,
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    log::trace!("Mapped {} files in {root:?} ({parsed} parsed)", files.len());
+
+    RepoMap {
+        root: root.to_string_lossy().into_owned(),
+        generated_at: crate::runtime::now(),
+        files,
+    }
+}
+
+fn map_path(app: &impl PathProvider, root: &Path) -> Result<PathBuf, String> {
+    let key = Uuid::new_v5(&Uuid::NAMESPACE_URL, root.to_string_lossy().as_bytes());
+    Ok(app
+        .app_data_dir()?
+        .join(MAPS_DIR)
+        .join(format!("{key}.json")))
+}
+
+/// The cached map of `root`, if any
+pub fn load(app: &impl PathProvider, root: &Path) -> Option<RepoMap> {
+    let content = fs::read_to_string(map_path(app, root).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save(app: &impl PathProvider, map: &RepoMap) -> Result<(), String> {
+    let path = map_path(app, Path::new(&map.root))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create repo maps directory: {e}"))?;
+    }
+    let content =
+        serde_json::to_string(map).map_err(|e| format!("Failed to serialize repo map: {e}"))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to save repo map: {e}"))
+}
+
+/// Bring the cached map of `root` up to date
+pub fn refresh(app: &impl PathProvider, root: &Path) -> Result<RepoMap, String> {
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()));
+    }
+    let map = build(root, load(app, root).as_ref());
+    save(app, &map)?;
+    Ok(map)
+}
+
+/// Refresh the map of `root` on a background thread, unless one is running
+pub fn refresh_in_background<A: PathProvider + Send + 'static>(app: A, root: PathBuf) {
+    if !REFRESHING.lock().unwrap().insert(root.clone()) {
+        return;
+    }
+    crate::background_tasks::supervisor::spawn_thread("repo-map", move || {
+        // Cleared even if the walk panics
+        struct Done(PathBuf);
+        impl Drop for Done {
+            fn drop(&mut self) {
+                REFRESHING.lock().unwrap().remove(&self.0);
+            }
+        }
+        let _done = Done(root.clone());
+        if let Err(e) = refresh(&app, &root) {
+            log::warn!("Failed to refresh repo map: {e}");
+        }
+    });
+}
+
+/// Refresh the map of `root` if one exists and is getting old (for pollers)
+pub fn refresh_if_stale<A: PathProvider + Send + 'static>(app: A, root: PathBuf) {
+    let stale = load(&app, &root).is_some_and(|map| {
+        crate::runtime::now().saturating_sub(map.generated_at) >= REFRESH_AFTER_SECS
+    });
+    if stale {
+        refresh_in_background(app, root);
+    }
+}
+
+/// Markdown outline of `map` within roughly `budget_tokens`. Shallow files
+/// come first; files whose outline doesn't fit are listed by path only.
+pub fn render(map: &RepoMap, budget_tokens: usize) -> String {
+    let mut budget = budget_tokens * CHARS_PER_TOKEN;
+    let mut ranked: Vec<&FileEntry> = map.files.iter().collect();
+    ranked.sort_by_key(|f| (f.path.matches('/').count(), f.path.as_str()));
+
+    let mut shown: Vec<(&str, Option<String>)> = Vec::new();
+    let mut omitted = 0;
+    for file in ranked {
+        let path_line = file.path.len() + 1;
+        let outline: String = file
+            .symbols
+            .iter()
+            .map(|s| format!("  {} {} :{}\n", s.kind, s.name, s.line))
+            .collect();
+        if path_line + outline.len() <= budget {
+            budget -= path_line + outline.len();
+            shown.push((&file.path, Some(outline)));
+        } else if path_line <= budget {
+            budget -= path_line;
+            shown.push((&file.path, None));
+        } else {
+            omitted += 1;
+        }
+    }
+    // Back in tree order for reading
+    shown.sort_by_key(|(path, _)| *path);
+
+    let mut out = String::from(
+        "# Repository map\n\n\
+         Source files of this repository with the symbols they define \
+         (kind, name, line).\n\n```text\n",
+    );
+    for (path, outline) in shown {
+        out.push_str(path);
+        out.push('\n');
+        if let Some(outline) = outline {
+            out.push_str(&outline);
+        }
+    }
+    out.push_str("```\n");
+    if omitted > 0 {
+        out.push_str(&format!("\n{omitted} more files not shown.\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    fn symbols(ext: &str, source: &str) -> Vec<(String, String, usize)> {
+        let patterns = patterns_for(Path::new(&format!("x.{ext}"))).unwrap();
+        extract_symbols(patterns, source)
+            .into_iter()
+            .map(|s| (s.kind, s.name, s.line))
+            .collect()
+    }
+
+    fn s(kind: &str, name: &str, line: usize) -> (String, String, usize) {
+        (kind.to_string(), name.to_string(), line)
+    }
+
+    #[test]
+    fn test_extract_symbols() {
+        let rust = "pub struct Map {\n}\nimpl Map {\n    pub async fn build(&self) {\n        fn nested() {}\n    }\n}\npub(crate) enum Kind {}\nmacro_rules! outline {\n";
+        assert_eq!(
+            symbols("rs", rust),
+            [
+                s("struct", "Map", 1),
+                s("fn", "build", 4),
+                s("enum", "Kind", 8),
+                s("macro_rules", "outline", 9)
+            ]
+        );
+
+        let ts = "export default function App() {}\nexport interface Props {}\nexport const useThing = () => {}\nconst local = 1\nclass Store {\n  async load() {}\n}\n";
+        assert_eq!(
+            symbols("tsx", ts),
+            [
+                s("function", "App", 1),
+                s("interface", "Props", 2),
+                s("const", "useThing", 3),
+                s("class", "Store", 5)
+            ]
+        );
+
+        let py = "class Runner:\n    def run(self):\n        def inner():\n            pass\nasync def main():\n";
+        assert_eq!(
+            symbols("py", py),
+            [
+                s("class", "Runner", 1),
+                s("def", "run", 2),
+                s("def", "main", 5)
+            ]
+        );
+
+        let go = "func (s *Server) Start() error {\ntype Config struct {\nfunc main() {\n";
+        assert_eq!(
+            symbols("go", go),
+            [
+                s("func", "Start", 1),
+                s("type", "Config", 2),
+                s("func", "main", 3)
+            ]
+        );
+        assert!(patterns_for(Path::new("README.md")).is_none());
+    }
+
+    #[test]
+    fn test_build_is_incremental_and_cached() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn one() {}\n").unwrap();
+        fs::write(root.join("src/nested/deep.py"), "def two():\n").unwrap();
+        fs::write(root.join("notes.md"), "# not code\n").unwrap();
+        fs::write(root.join(".gitignore"), "build/\n").unwrap();
+        fs::create_dir_all(root.join("build")).unwrap();
+        fs::write(root.join("build/gen.rs"), "fn generated() {}\n").unwrap();
+
+        let paths = TempPaths::new();
+        assert!(load(&paths, root).is_none());
+        let map = refresh(&paths, root).unwrap();
+        let files: Vec<&str> = map.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(files, ["src/lib.rs", "src/nested/deep.py"]);
+        assert_eq!(load(&paths, root).unwrap().file_count(), 2);
+
+        // Unchanged entries are reused, changed files parsed again
+        let mut previous = map.clone();
+        previous.files[1].symbols.clear();
+        fs::write(
+            root.join("src/lib.rs"),
+            "pub fn one() {}\npub fn three() {}\n",
+        )
+        .unwrap();
+        let map = build(root, Some(&previous));
+        assert_eq!(map.files[0].symbols.len(), 2);
+        assert!(map.files[1].symbols.is_empty());
+    }
+
+    #[test]
+    fn test_render_fits_budget() {
+        let file = |path: &str, n: usize| FileEntry {
+            path: path.to_string(),
+            size: 0,
+            modified: 0,
+            symbols: (0..n)
+                .map(|i| Symbol {
+                    kind: "fn".to_string(),
+                    name: format!("symbol_{i}"),
+                    line: i + 1,
+                })
+                .collect(),
+        };
+        let map = RepoMap {
+            root: "/repo".to_string(),
+            generated_at: 0,
+            files: vec![
+                file("src/a/deep.rs", 50),
+                file("src/lib.rs", 3),
+                file("src/main.rs", 2),
+            ],
+        };
+
+        let full = render(&map, 10_000);
+        assert!(full.contains("src/a/deep.rs\n  fn symbol_0 :1\n"));
+        assert!(!full.contains("more files"));
+
+        // Shallow files keep their outlines; the deep one is listed by path
+        let small = render(&map, 60);
+        assert!(small.contains("src/lib.rs\n  fn symbol_0 :1\n"), "{small}");
+        assert!(small.contains("src/a/deep.rs\nsrc/lib.rs"), "{small}");
+        assert!(!small.contains("symbol_49"));
+
+        let tiny = render(&map, 5);
+        assert!(tiny.contains("more files not shown"), "{tiny}");
+    }
+}
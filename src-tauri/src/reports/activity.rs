@@ -0,0 +1,265 @@
+//! Activity heatmap
+//!
+//! Per-day session counts and cost for a contribution graph, with streaks and
+//! the busiest project worked out here so the frontend gets one small
+//! payload instead of every run.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::UsageRow;
+use crate::projects::storage::load_projects_data;
+
+/// Activity on one day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActivityDay {
+    /// YYYY-MM-DD in local time
+    pub date: String,
+    /// Sessions with at least one run started that day
+    pub sessions: usize,
+    pub runs: usize,
+    /// Sum of known run costs
+    pub cost_usd: f64,
+}
+
+/// Activity of one project over the year
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectActivity {
+    pub project_id: String,
+    pub name: String,
+    pub sessions: usize,
+    pub runs: usize,
+    pub cost_usd: f64,
+}
+
+/// Activity over one calendar year
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActivityHeatmap {
+    pub year: i32,
+    /// Every day of the year, in order
+    pub days: Vec<ActivityDay>,
+    /// Most sessions on one day (for scaling colors)
+    pub max_sessions: usize,
+    pub total_sessions: usize,
+    pub total_runs: usize,
+    pub total_cost_usd: f64,
+    pub active_days: usize,
+    /// Active days up to today (or yesterday, while today has no runs yet)
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    /// Project with the most sessions (then runs)
+    pub busiest_project: Option<ProjectActivity>,
+}
+
+/// Worktree ID -> (project ID, project name)
+pub fn worktree_projects(app: &AppHandle) -> HashMap<String, (String, String)> {
+    let Ok(data) = load_projects_data(app) else {
+        return HashMap::new();
+    };
+    data.worktrees
+        .iter()
+        .filter_map(|w| {
+            let project = data.find_project(&w.project_id)?;
+            Some((w.id.clone(), (project.id.clone(), project.name.clone())))
+        })
+        .collect()
+}
+
+/// Heatmap of `year` from usage rows, with days in `tz`
+pub fn heatmap<Tz: TimeZone>(
+    rows: &[UsageRow],
+    projects: &HashMap<String, (String, String)>,
+    year: i32,
+    today: NaiveDate,
+    tz: &Tz,
+) -> Result<ActivityHeatmap, String> {
+    let first =
+        NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| format!("Invalid year: {year}"))?;
+    let next =
+        NaiveDate::from_ymd_opt(year + 1, 1, 1).ok_or_else(|| format!("Invalid year: {year}"))?;
+    let mut days: Vec<ActivityDay> = first
+        .iter_days()
+        .take_while(|d| *d < next)
+        .map(|d| ActivityDay {
+            date: d.format("%Y-%m-%d").to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+    let mut day_sessions: Vec<HashSet<&str>> = vec![HashSet::new(); days.len()];
+    let mut year_sessions = HashSet::new();
+    let mut by_project: HashMap<&str, (ProjectActivity, HashSet<&str>)> = HashMap::new();
+    for row in rows {
+        let Some(started) = tz.timestamp_opt(row.started_at as i64, 0).single() else {
+            continue;
+        };
+        let date = started.date_naive();
+        if date.year() != year {
+            continue;
+        }
+        let index = date.ordinal0() as usize;
+        let cost = row.cost_usd.unwrap_or(0.0);
+        let day = &mut days[index];
+        day.runs += 1;
+        day.cost_usd += cost;
+        day_sessions[index].insert(row.session_id.as_str());
+        year_sessions.insert(row.session_id.as_str());
+
+        if let Some((project_id, name)) = projects.get(&row.worktree_id) {
+            let (activity, sessions) = by_project.entry(project_id.as_str()).or_insert_with(|| {
+                let activity = ProjectActivity {
+                    project_id: project_id.clone(),
+                    name: name.clone(),
+                    ..Default::default()
+                };
+                (activity, HashSet::new())
+            });
+            activity.runs += 1;
+            activity.cost_usd += cost;
+            sessions.insert(row.session_id.as_str());
+        }
+    }
+    for (day, sessions) in days.iter_mut().zip(&day_sessions) {
+        day.sessions = sessions.len();
+    }
+
+    let active: Vec<bool> = days.iter().map(|d| d.runs > 0).collect();
+    let busiest_project = by_project
+        .into_values()
+        .map(|(mut activity, sessions)| {
+            activity.sessions = sessions.len();
+            activity
+        })
+        .max_by(|a, b| {
+            (a.sessions, a.runs)
+                .cmp(&(b.sessions, b.runs))
+                // Stable choice between ties
+                .then_with(|| b.name.cmp(&a.name))
+        });
+
+    Ok(ActivityHeatmap {
+        year,
+        max_sessions: days.iter().map(|d| d.sessions).max().unwrap_or(0),
+        total_sessions: year_sessions.len(),
+        total_runs: days.iter().map(|d| d.runs).sum(),
+        total_cost_usd: days.iter().map(|d| d.cost_usd).sum(),
+        active_days: active.iter().filter(|a| **a).count(),
+        current_streak: current_streak(&active, first, today),
+        longest_streak: longest_streak(&active),
+        busiest_project,
+        days,
+    })
+}
+
+/// Longest run of consecutive active days
+fn longest_streak(active: &[bool]) -> usize {
+    active.split(|a| !a).map(<[bool]>::len).max().unwrap_or(0)
+}
+
+/// Active days ending today, or yesterday while today has no runs yet
+fn current_streak(active: &[bool], first: NaiveDate, today: NaiveDate) -> usize {
+    let Ok(today) = usize::try_from((today - first).num_days()) else {
+        return 0;
+    };
+    if today >= active.len() {
+        return 0;
+    }
+    let end = if active[today] { today + 1 } else { today };
+    active[..end].iter().rev().take_while(|a| **a).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn row(session_id: &str, worktree_id: &str, date: &str, cost_usd: Option<f64>) -> UsageRow {
+        let started_at = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp() as u64;
+        UsageRow {
+            session_id: session_id.to_string(),
+            session_name: session_id.to_string(),
+            worktree_id: worktree_id.to_string(),
+            attribution: None,
+            subproject: None,
+            started_at,
+            ended_at: None,
+            active_secs: 0,
+            model: None,
+            status: "completed".to_string(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            cost_usd,
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let rows = [
+            row("s1", "w1", "2024-03-01", Some(0.5)),
+            row("s1", "w1", "2024-03-01", Some(0.25)),
+            row("s2", "w2", "2024-03-01", None),
+            row("s2", "w2", "2024-03-02", Some(1.0)),
+            row("s3", "w2", "2024-03-03", None),
+            row("s4", "w2", "2024-03-05", None),
+            row("s5", "gone", "2024-03-06", None),
+            row("s6", "w1", "2023-12-31", Some(9.0)),
+        ];
+        let projects = HashMap::from([
+            ("w1".to_string(), ("p1".to_string(), "Web".to_string())),
+            ("w2".to_string(), ("p2".to_string(), "Api".to_string())),
+        ]);
+
+        let map = heatmap(&rows, &projects, 2024, date("2024-03-07"), &Utc).unwrap();
+        // 2024 is a leap year
+        assert_eq!(map.days.len(), 366);
+        let march_1 = &map.days[date("2024-03-01").ordinal0() as usize];
+        assert_eq!(march_1.date, "2024-03-01");
+        assert_eq!((march_1.sessions, march_1.runs), (2, 3));
+        assert_eq!(march_1.cost_usd, 0.75);
+        assert_eq!(map.max_sessions, 2);
+        assert_eq!(map.total_sessions, 5);
+        assert_eq!(map.total_runs, 7);
+        assert_eq!(map.total_cost_usd, 1.75);
+        assert_eq!(map.active_days, 5);
+        assert_eq!(map.longest_streak, 3);
+        // Today (the 7th) has no runs yet, so the streak ends on the 6th
+        assert_eq!(map.current_streak, 2);
+
+        let busiest = map.busiest_project.unwrap();
+        assert_eq!(busiest.project_id, "p2");
+        assert_eq!((busiest.sessions, busiest.runs), (3, 4));
+
+        assert_eq!(
+            heatmap(&rows, &projects, 2023, date("2024-03-07"), &Utc)
+                .unwrap()
+                .current_streak,
+            0
+        );
+    }
+
+    #[test]
+    fn test_streaks() {
+        assert_eq!(longest_streak(&[]), 0);
+        assert_eq!(longest_streak(&[true, true, false, true]), 2);
+        let first = date("2024-01-01");
+        let active = [true, true, true, false];
+        assert_eq!(current_streak(&active, first, date("2024-01-03")), 3);
+        assert_eq!(current_streak(&active, first, date("2024-01-04")), 3);
+        assert_eq!(current_streak(&active, first, date("2024-01-02")), 2);
+        assert_eq!(current_streak(&active, first, date("2023-12-31")), 0);
+    }
+}
@@ -0,0 +1,176 @@
+//! Cost attribution
+//!
+//! Projects, sub-projects and sessions can name a client or cost center. Each
+//! run records the attribution in effect when it started (the session's, else
+//! its sub-project's, else its project's), so later changes don't move
+//! already billed runs. Runs from
+//! before an attribution was set fall back to the current one at export.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{CsvWriter, ExportFormat, UsageRow};
+use crate::projects::storage::load_projects_data;
+
+/// Label for runs without an attribution
+pub const UNATTRIBUTED: &str = "Unattributed";
+
+/// Trimmed attribution, None when blank
+pub fn normalize(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Attribution of the project owning `worktree_id`
+pub fn project_attribution(app: &AppHandle, worktree_id: &str) -> Option<String> {
+    let data = load_projects_data(app).ok()?;
+    let worktree = data.find_worktree(worktree_id)?;
+    data.find_project(&worktree.project_id)?.attribution.clone()
+}
+
+/// Worktree ID -> project attribution, for every worktree with one
+pub fn worktree_attributions(app: &AppHandle) -> HashMap<String, String> {
+    let Ok(data) = load_projects_data(app) else {
+        return HashMap::new();
+    };
+    data.worktrees
+        .iter()
+        .filter_map(|w| {
+            let attribution = data.find_project(&w.project_id)?.attribution.clone()?;
+            Some((w.id.clone(), attribution))
+        })
+        .collect()
+}
+
+/// Usage totals for one attribution
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttributionSubtotal {
+    /// None for unattributed runs
+    pub attribution: Option<String>,
+    pub runs: usize,
+    /// Seconds the agent was working, excluding waits on the user
+    pub active_secs: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    /// Sum of known run costs
+    pub cost_usd: f64,
+    /// Runs without a reported cost (not in `cost_usd`)
+    pub runs_without_cost: usize,
+}
+
+/// Subtotals per attribution, by name with unattributed runs last
+pub fn subtotals(rows: &[UsageRow]) -> Vec<AttributionSubtotal> {
+    let mut by_attribution: BTreeMap<Option<&str>, AttributionSubtotal> = BTreeMap::new();
+    for row in rows {
+        let key = row.attribution.as_deref();
+        let total = by_attribution
+            .entry(key)
+            .or_insert_with(|| AttributionSubtotal {
+                attribution: row.attribution.clone(),
+                ..Default::default()
+            });
+        total.runs += 1;
+        total.active_secs += row.active_secs;
+        total.input_tokens += row.input_tokens;
+        total.output_tokens += row.output_tokens;
+        total.cache_read_tokens += row.cache_read_tokens;
+        total.cache_write_tokens += row.cache_write_tokens;
+        match row.cost_usd {
+            Some(cost) => total.cost_usd += cost,
+            None => total.runs_without_cost += 1,
+        }
+    }
+    // None sorts first in a BTreeMap; move it to the end
+    let mut totals: Vec<_> = by_attribution.into_values().collect();
+    if totals.first().is_some_and(|t| t.attribution.is_none()) {
+        totals.rotate_left(1);
+    }
+    totals
+}
+
+/// Subtotals as CSV
+pub fn subtotals_csv(totals: &[AttributionSubtotal], format: &ExportFormat) -> String {
+    let locale = format.locale;
+    let mut csv = CsvWriter::new(format.delimiter);
+    csv.row(&[
+        "Attribution",
+        "Runs",
+        "Active (s)",
+        "Input tokens",
+        "Output tokens",
+        "Cache read tokens",
+        "Cache write tokens",
+        "Cost (USD)",
+        "Runs without cost",
+    ]);
+    for total in totals {
+        csv.row(&[
+            total
+                .attribution
+                .clone()
+                .unwrap_or_else(|| UNATTRIBUTED.to_string()),
+            total.runs.to_string(),
+            total.active_secs.to_string(),
+            total.input_tokens.to_string(),
+            total.output_tokens.to_string(),
+            total.cache_read_tokens.to_string(),
+            total.cache_write_tokens.to_string(),
+            locale.format_number(total.cost_usd, 4),
+            total.runs_without_cost.to_string(),
+        ]);
+    }
+    csv.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(attribution: Option<&str>, cost_usd: Option<f64>) -> UsageRow {
+        UsageRow {
+            session_id: "s1".to_string(),
+            session_name: "Session 1".to_string(),
+            worktree_id: "w1".to_string(),
+            attribution: attribution.map(str::to_string),
+            subproject: None,
+            started_at: 0,
+            ended_at: None,
+            active_secs: 30,
+            model: None,
+            status: "completed".to_string(),
+            input_tokens: 100,
+            output_tokens: 10,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            cost_usd,
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("  Acme "), Some("Acme".to_string()));
+        assert_eq!(normalize("  "), None);
+    }
+
+    #[test]
+    fn test_subtotals() {
+        let rows = vec![
+            row(Some("Globex"), Some(1.0)),
+            row(None, Some(0.25)),
+            row(Some("Acme"), Some(0.5)),
+            row(Some("Globex"), None),
+        ];
+        let totals = subtotals(&rows);
+        let names: Vec<_> = totals.iter().map(|t| t.attribution.as_deref()).collect();
+        assert_eq!(names, vec![Some("Acme"), Some("Globex"), None]);
+        assert_eq!(totals[1].runs, 2);
+        assert_eq!(totals[1].active_secs, 60);
+        assert_eq!(totals[1].input_tokens, 200);
+        assert_eq!(totals[1].cost_usd, 1.0);
+        assert_eq!(totals[1].runs_without_cost, 1);
+    }
+}
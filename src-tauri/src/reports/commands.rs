@@ -0,0 +1,134 @@
+//! Tauri commands for exported reports
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::activity::{heatmap, worktree_projects, ActivityHeatmap};
+use super::attribution::{subtotals, subtotals_csv, worktree_attributions, AttributionSubtotal};
+use super::digest::{send_now, EmailDigest};
+use super::{usage_csv, usage_rows, ExportFormat, UsageRow};
+
+/// Result of an export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportExport {
+    pub path: String,
+    pub rows: usize,
+}
+
+/// Usage rows since `since`, limited to one attribution if given
+/// (an empty `attribution` selects unattributed runs)
+fn attributed_rows(
+    app: &AppHandle,
+    since: Option<u64>,
+    attribution: Option<&str>,
+) -> Result<Vec<UsageRow>, String> {
+    let mut rows = usage_rows(app, since.unwrap_or(0), &worktree_attributions(app))?;
+    if let Some(attribution) = attribution {
+        let wanted = super::attribution::normalize(attribution);
+        rows.retain(|r| r.attribution == wanted);
+    }
+    Ok(rows)
+}
+
+/// Write per-run token usage and cost to a CSV file
+/// Uses the report locale and CSV delimiter from preferences; `since` (Unix
+/// seconds) limits the export to runs started from then on, `attribution`
+/// to one client or cost center.
+#[tauri::command]
+pub async fn export_usage_csv(
+    app: AppHandle,
+    path: String,
+    since: Option<u64>,
+    attribution: Option<String>,
+) -> Result<ReportExport, String> {
+    log::trace!("Exporting usage CSV to {path}");
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let format = ExportFormat::from_preferences(&prefs)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let rows = attributed_rows(&app, since, attribution.as_deref())?;
+        let csv = usage_csv(&rows, &format, &chrono::Local);
+        std::fs::write(&path, csv).map_err(|e| format!("Failed to write {path}: {e}"))?;
+        Ok(ReportExport {
+            path,
+            rows: rows.len(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Usage export failed: {e}"))?
+}
+
+/// Token usage and cost per client or cost center since `since`
+#[tauri::command]
+pub async fn get_attribution_subtotals(
+    app: AppHandle,
+    since: Option<u64>,
+) -> Result<Vec<AttributionSubtotal>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let rows = attributed_rows(&app, since, None)?;
+        Ok(subtotals(&rows))
+    })
+    .await
+    .map_err(|e| format!("Failed to total usage: {e}"))?
+}
+
+/// Write per-attribution usage subtotals to a CSV file (one row per client)
+#[tauri::command]
+pub async fn export_attribution_csv(
+    app: AppHandle,
+    path: String,
+    since: Option<u64>,
+) -> Result<ReportExport, String> {
+    log::trace!("Exporting attribution CSV to {path}");
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let format = ExportFormat::from_preferences(&prefs)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let totals = subtotals(&attributed_rows(&app, since, None)?);
+        let csv = subtotals_csv(&totals, &format);
+        std::fs::write(&path, csv).map_err(|e| format!("Failed to write {path}: {e}"))?;
+        Ok(ReportExport {
+            path,
+            rows: totals.len(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Attribution export failed: {e}"))?
+}
+
+/// Per-day sessions and cost of `year` (default: this year) for an activity
+/// heatmap, with streaks and the busiest project
+#[tauri::command]
+pub async fn get_activity_heatmap(
+    app: AppHandle,
+    year: Option<i32>,
+) -> Result<ActivityHeatmap, String> {
+    use chrono::Datelike;
+
+    let today = chrono::Local::now().date_naive();
+    let year = year.unwrap_or(today.year());
+    tauri::async_runtime::spawn_blocking(move || {
+        // A day early so every local-time day of the year is covered
+        let since = chrono::NaiveDate::from_ymd_opt(year, 1, 1)
+            .ok_or_else(|| format!("Invalid year: {year}"))?
+            .pred_opt()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|d| d.and_utc().timestamp().max(0) as u64)
+            .unwrap_or(0);
+        // Attribution isn't shown, so no fallbacks are needed
+        let rows = usage_rows(&app, since, &Default::default())?;
+        heatmap(&rows, &worktree_projects(&app), year, today, &chrono::Local)
+    })
+    .await
+    .map_err(|e| format!("Failed to build activity heatmap: {e}"))?
+}
+
+/// Mail the digest of the last period (a day unless `email_digest` is
+/// weekly) now, whether or not digests are scheduled
+#[tauri::command]
+pub async fn send_email_digest(app: AppHandle) -> Result<EmailDigest, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    tauri::async_runtime::spawn_blocking(move || send_now(&app, &prefs))
+        .await
+        .map_err(|e| format!("Failed to send digest: {e}"))?
+}
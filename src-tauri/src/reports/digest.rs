@@ -0,0 +1,395 @@
+//! Email digest of runs
+//!
+//! With `email_digest` set to daily or weekly, a summary of the runs since
+//! the last digest (completed and failed tasks, spend, agent time) is mailed
+//! to `email_digest_to` (see `email`). The first digest goes out one period
+//! after it's enabled; `send_email_digest` sends one right away.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::TimeZone;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::attribution::worktree_attributions;
+use super::locale::ReportLocale;
+use super::{usage_rows, UsageRow};
+use crate::background_tasks::supervisor::supervise;
+use crate::email::{Email, SmtpSettings};
+use crate::runtime::{now, PathProvider};
+use crate::AppPreferences;
+
+/// When the last digest went out (in app data)
+const STATE_FILE: &str = "email-digest.json";
+
+/// How often the scheduler checks whether a digest is due
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Sessions listed in a digest (the most expensive ones)
+const MAX_SESSIONS: usize = 20;
+
+/// How often digests are sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    #[default]
+    Off,
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn period_secs(self) -> Option<u64> {
+        match self {
+            Self::Off => None,
+            Self::Daily => Some(86_400),
+            Self::Weekly => Some(7 * 86_400),
+        }
+    }
+}
+
+/// Runs of one session in a digest
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DigestSession {
+    pub session_id: String,
+    pub session_name: String,
+    pub runs: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub cost_usd: f64,
+}
+
+/// A run that crashed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestFailure {
+    pub session_name: String,
+    pub started_at: u64,
+}
+
+/// Runs started in a period
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmailDigest {
+    /// Unix seconds
+    pub since: u64,
+    pub until: u64,
+    pub runs: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    /// Sum of known run costs
+    pub cost_usd: f64,
+    /// Runs without a reported cost (not in cost_usd)
+    pub runs_without_cost: usize,
+    pub active_secs: u64,
+    /// Most expensive first
+    pub sessions: Vec<DigestSession>,
+    /// Oldest first
+    pub failures: Vec<DigestFailure>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DigestState {
+    /// Unix seconds of the last digest (or when digests were enabled)
+    sent_at: Option<u64>,
+}
+
+fn load_state(root: &Path) -> DigestState {
+    fs::read_to_string(root.join(STATE_FILE))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(root: &Path, state: &DigestState) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize digest state: {e}"))?;
+    fs::write(root.join(STATE_FILE), content)
+        .map_err(|e| format!("Failed to write digest state: {e}"))
+}
+
+/// Summarize the rows of runs started from `since` until `until`
+pub fn build_digest(rows: &[UsageRow], since: u64, until: u64) -> EmailDigest {
+    let mut digest = EmailDigest {
+        since,
+        until,
+        ..Default::default()
+    };
+    let mut sessions: HashMap<&str, DigestSession> = HashMap::new();
+    for row in rows
+        .iter()
+        .filter(|r| r.started_at >= since && r.started_at < until)
+    {
+        let session = sessions
+            .entry(row.session_id.as_str())
+            .or_insert_with(|| DigestSession {
+                session_id: row.session_id.clone(),
+                session_name: row.session_name.clone(),
+                ..Default::default()
+            });
+        session.runs += 1;
+        digest.runs += 1;
+        digest.active_secs += row.active_secs;
+        match row.status.as_str() {
+            "completed" => {
+                session.completed += 1;
+                digest.completed += 1;
+            }
+            "crashed" => {
+                session.failed += 1;
+                digest.failed += 1;
+                digest.failures.push(DigestFailure {
+                    session_name: row.session_name.clone(),
+                    started_at: row.started_at,
+                });
+            }
+            "cancelled" => digest.cancelled += 1,
+            _ => {}
+        }
+        match row.cost_usd {
+            Some(cost) => {
+                session.cost_usd += cost;
+                digest.cost_usd += cost;
+            }
+            None => digest.runs_without_cost += 1,
+        }
+    }
+    digest.sessions = sessions.into_values().collect();
+    digest.sessions.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then(b.runs.cmp(&a.runs))
+            .then(a.session_name.cmp(&b.session_name))
+    });
+    digest
+}
+
+fn format_active(secs: u64) -> String {
+    match (secs / 3600, secs % 3600 / 60) {
+        (0, 0) => format!("{secs}s"),
+        (0, minutes) => format!("{minutes}m"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+/// Subject and plain-text body of a digest, with dates in `tz`
+pub fn render_digest<Tz: TimeZone>(
+    digest: &EmailDigest,
+    locale: &ReportLocale,
+    tz: &Tz,
+) -> (String, String)
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let cost = |usd: f64| format!("${}", locale.format_number(usd, 2));
+    let subject = format!(
+        "Jean digest: {} completed, {} failed, {}",
+        digest.completed,
+        digest.failed,
+        cost(digest.cost_usd)
+    );
+
+    let mut body = format!(
+        "Runs from {} to {}\n\n",
+        locale.format_datetime(digest.since, tz),
+        locale.format_datetime(digest.until, tz)
+    );
+    if digest.runs == 0 {
+        body.push_str("No runs in this period.\n");
+        return (subject, body);
+    }
+    body.push_str(&format!(
+        "{} runs: {} completed, {} failed, {} cancelled\n",
+        digest.runs, digest.completed, digest.failed, digest.cancelled
+    ));
+    body.push_str(&format!("Spend: {}", cost(digest.cost_usd)));
+    if digest.runs_without_cost > 0 {
+        body.push_str(&format!(
+            " ({} runs without a reported cost)",
+            digest.runs_without_cost
+        ));
+    }
+    body.push_str(&format!(
+        "\nAgent time: {}\n",
+        format_active(digest.active_secs)
+    ));
+
+    body.push_str("\nSessions\n");
+    for session in digest.sessions.iter().take(MAX_SESSIONS) {
+        body.push_str(&format!(
+            "- {}: {} runs, {} completed",
+            session.session_name, session.runs, session.completed
+        ));
+        if session.failed > 0 {
+            body.push_str(&format!(", {} failed", session.failed));
+        }
+        body.push_str(&format!(", {}\n", cost(session.cost_usd)));
+    }
+    if digest.sessions.len() > MAX_SESSIONS {
+        body.push_str(&format!(
+            "- and {} more\n",
+            digest.sessions.len() - MAX_SESSIONS
+        ));
+    }
+
+    if !digest.failures.is_empty() {
+        body.push_str("\nFailures\n");
+        for failure in &digest.failures {
+            body.push_str(&format!(
+                "- {} ({})\n",
+                failure.session_name,
+                locale.format_datetime(failure.started_at, tz)
+            ));
+        }
+    }
+    (subject, body)
+}
+
+/// Recipients from preferences
+fn recipients(prefs: &AppPreferences) -> Result<Vec<String>, String> {
+    let to: Vec<String> = prefs
+        .email_digest_to
+        .iter()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect();
+    if to.is_empty() {
+        return Err("No digest recipients configured".to_string());
+    }
+    Ok(to)
+}
+
+/// Build and mail the digest of runs since `since` (blocking)
+pub fn send_digest(
+    app: &AppHandle,
+    prefs: &AppPreferences,
+    since: u64,
+    until: u64,
+) -> Result<EmailDigest, String> {
+    let settings = SmtpSettings::from_preferences(prefs)?;
+    let to = recipients(prefs)?;
+    let rows = usage_rows(app, since, &worktree_attributions(app))?;
+    let digest = build_digest(&rows, since, until);
+    let locale = ReportLocale::find(&prefs.report_locale);
+    let (subject, body) = render_digest(&digest, locale, &chrono::Local);
+    crate::email::send(app, &settings, &Email { to, subject, body })?;
+    Ok(digest)
+}
+
+/// Send the digest of the last period now (a day without a schedule)
+pub fn send_now(app: &AppHandle, prefs: &AppPreferences) -> Result<EmailDigest, String> {
+    let now = now();
+    let period = prefs.email_digest.period_secs().unwrap_or(86_400);
+    send_digest(app, prefs, now.saturating_sub(period), now)
+}
+
+/// Send a digest if a period has passed since the last one
+fn run_scheduled(app: &AppHandle) -> Result<(), String> {
+    let prefs = tauri::async_runtime::block_on(crate::load_preferences(app.clone()))?;
+    let Some(period) = prefs.email_digest.period_secs() else {
+        return Ok(());
+    };
+    let root = app.app_data_dir()?;
+    let mut state = load_state(&root);
+    let now = now();
+    let Some(sent_at) = state.sent_at else {
+        state.sent_at = Some(now);
+        return save_state(&root, &state);
+    };
+    if now.saturating_sub(sent_at) < period {
+        return Ok(());
+    }
+
+    // Cover the time since the last digest, but no more than one period
+    let since = sent_at.max(now.saturating_sub(period));
+    let digest = send_digest(app, &prefs, since, now)?;
+    log::info!(
+        "Sent run digest ({} runs) to {}",
+        digest.runs,
+        prefs.email_digest_to.join(", ")
+    );
+    state.sent_at = Some(now);
+    save_state(&root, &state)
+}
+
+/// Start the periodic run digest
+pub fn start_scheduler(app: AppHandle) {
+    supervise("email-digest", move || loop {
+        if let Err(e) = run_scheduled(&app) {
+            log::warn!("Run digest failed: {e}");
+        }
+        std::thread::sleep(SCHEDULE_CHECK_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn row(session: &str, started_at: u64, status: &str, cost: Option<f64>) -> UsageRow {
+        UsageRow {
+            session_id: session.to_string(),
+            session_name: format!("Session {session}"),
+            worktree_id: "w1".to_string(),
+            attribution: None,
+            subproject: None,
+            started_at,
+            ended_at: Some(started_at + 60),
+            active_secs: 60,
+            model: None,
+            status: status.to_string(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            cost_usd: cost,
+        }
+    }
+
+    #[test]
+    fn test_build_digest() {
+        let rows = vec![
+            row("a", 50, "completed", Some(5.0)),
+            row("a", 100, "completed", Some(1.0)),
+            row("a", 200, "crashed", Some(0.5)),
+            row("b", 300, "completed", Some(2.0)),
+            row("b", 400, "cancelled", None),
+            row("c", 1_000, "completed", Some(9.0)),
+        ];
+        let digest = build_digest(&rows, 100, 1_000);
+        assert_eq!(digest.runs, 4);
+        assert_eq!(
+            (digest.completed, digest.failed, digest.cancelled),
+            (2, 1, 1)
+        );
+        assert_eq!(digest.cost_usd, 3.5);
+        assert_eq!(digest.runs_without_cost, 1);
+        assert_eq!(digest.active_secs, 240);
+        assert_eq!(digest.sessions[0].session_id, "b");
+        assert_eq!(digest.sessions[1].failed, 1);
+        assert_eq!(digest.failures.len(), 1);
+    }
+
+    #[test]
+    fn test_render_digest() {
+        let rows = vec![
+            row("a", 1_709_647_620, "completed", Some(1.25)),
+            row("a", 1_709_651_220, "crashed", None),
+        ];
+        let digest = build_digest(&rows, 1_709_600_000, 1_709_700_000);
+        let (subject, body) = render_digest(&digest, ReportLocale::find("de-DE"), &Utc);
+        assert_eq!(subject, "Jean digest: 1 completed, 1 failed, $1,25");
+        assert!(body.contains("2 runs: 1 completed, 1 failed, 0 cancelled\n"));
+        assert!(body.contains("(1 runs without a reported cost)"));
+        assert!(body.contains("Agent time: 2m\n"));
+        assert!(body.contains("- Session a: 2 runs, 1 completed, 1 failed, $1,25\n"));
+        assert!(body.contains("- Session a (05.03.2024 15:07)\n"));
+
+        let (_, empty) =
+            render_digest(&build_digest(&[], 0, 10), ReportLocale::find("en-US"), &Utc);
+        assert!(empty.ends_with("No runs in this period.\n"));
+    }
+}
@@ -0,0 +1,163 @@
+//! Locale-aware number and date formatting
+//!
+//! Only what exported reports need: the decimal separator and the date/time
+//! layout. Locales are matched on the full tag ("de-AT"), then the language
+//! ("de"), falling back to en-US.
+
+use chrono::{DateTime, TimeZone};
+
+/// Formatting conventions of one locale
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReportLocale {
+    pub tag: &'static str,
+    pub decimal: char,
+    /// chrono format string for a date and time
+    pub datetime_format: &'static str,
+}
+
+/// Supported locales; the first of each language is its default
+const LOCALES: &[ReportLocale] = &[
+    ReportLocale {
+        tag: "en-US",
+        decimal: '.',
+        datetime_format: "%m/%d/%Y %I:%M %p",
+    },
+    ReportLocale {
+        tag: "en-GB",
+        decimal: '.',
+        datetime_format: "%d/%m/%Y %H:%M",
+    },
+    ReportLocale {
+        tag: "de-DE",
+        decimal: ',',
+        datetime_format: "%d.%m.%Y %H:%M",
+    },
+    ReportLocale {
+        tag: "fr-FR",
+        decimal: ',',
+        datetime_format: "%d/%m/%Y %H:%M",
+    },
+    ReportLocale {
+        tag: "es-ES",
+        decimal: ',',
+        datetime_format: "%d/%m/%Y %H:%M",
+    },
+    ReportLocale {
+        tag: "it-IT",
+        decimal: ',',
+        datetime_format: "%d/%m/%Y %H:%M",
+    },
+    ReportLocale {
+        tag: "nl-NL",
+        decimal: ',',
+        datetime_format: "%d-%m-%Y %H:%M",
+    },
+    ReportLocale {
+        tag: "pt-BR",
+        decimal: ',',
+        datetime_format: "%d/%m/%Y %H:%M",
+    },
+    ReportLocale {
+        tag: "sv-SE",
+        decimal: ',',
+        datetime_format: "%Y-%m-%d %H:%M",
+    },
+    ReportLocale {
+        tag: "pl-PL",
+        decimal: ',',
+        datetime_format: "%d.%m.%Y %H:%M",
+    },
+    ReportLocale {
+        tag: "ja-JP",
+        decimal: '.',
+        datetime_format: "%Y/%m/%d %H:%M",
+    },
+    ReportLocale {
+        tag: "zh-CN",
+        decimal: '.',
+        datetime_format: "%Y/%m/%d %H:%M",
+    },
+];
+
+impl ReportLocale {
+    /// Locale for a BCP 47 tag like "de-DE" or "de_AT" (en-US if unknown)
+    pub fn find(tag: &str) -> &'static ReportLocale {
+        let tag = tag.trim().replace('_', "-");
+        let language = tag.split('-').next().unwrap_or_default();
+        LOCALES
+            .iter()
+            .find(|l| l.tag.eq_ignore_ascii_case(&tag))
+            .or_else(|| {
+                LOCALES.iter().find(|l| {
+                    l.tag
+                        .split('-')
+                        .next()
+                        .is_some_and(|lang| lang.eq_ignore_ascii_case(language))
+                })
+            })
+            .unwrap_or(&LOCALES[0])
+    }
+
+    /// `value` with a fixed number of decimals and no grouping
+    pub fn format_number(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{value:.decimals$}");
+        if self.decimal == '.' {
+            formatted
+        } else {
+            formatted.replace('.', &self.decimal.to_string())
+        }
+    }
+
+    /// Unix seconds as a date and time in `tz`
+    pub fn format_datetime<Tz: TimeZone>(&self, unix: u64, tz: &Tz) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        DateTime::from_timestamp(unix as i64, 0)
+            .map(|utc| {
+                utc.with_timezone(tz)
+                    .format(self.datetime_format)
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+
+    /// CSV delimiter that doesn't clash with the decimal separator
+    pub fn default_delimiter(&self) -> char {
+        if self.decimal == ',' {
+            ';'
+        } else {
+            ','
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_find() {
+        assert_eq!(ReportLocale::find("de-DE").tag, "de-DE");
+        assert_eq!(ReportLocale::find("de_at").tag, "de-DE");
+        assert_eq!(ReportLocale::find("en-GB").tag, "en-GB");
+        assert_eq!(ReportLocale::find("en-AU").tag, "en-US");
+        assert_eq!(ReportLocale::find("").tag, "en-US");
+        assert_eq!(ReportLocale::find("xx").tag, "en-US");
+    }
+
+    #[test]
+    fn test_formatting() {
+        let us = ReportLocale::find("en-US");
+        let de = ReportLocale::find("de-DE");
+        assert_eq!(us.format_number(1234.5, 2), "1234.50");
+        assert_eq!(de.format_number(1234.5, 2), "1234,50");
+        assert_eq!((us.default_delimiter(), de.default_delimiter()), (',', ';'));
+
+        // 2024-03-05 14:07:00 UTC
+        let unix = 1_709_647_620;
+        assert_eq!(us.format_datetime(unix, &Utc), "03/05/2024 02:07 PM");
+        assert_eq!(de.format_datetime(unix, &Utc), "05.03.2024 14:07");
+    }
+}
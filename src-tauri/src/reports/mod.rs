@@ -0,0 +1,260 @@
+//! Exported reports
+//!
+//! Numbers and dates in exports follow the `report_locale` preference, and
+//! CSV files use `csv_delimiter` (by default `;` for locales with comma
+//! decimals, `,` otherwise) so they open correctly in spreadsheet and
+//! finance tooling set up for that locale.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat::storage::{list_all_session_ids, load_metadata};
+use crate::chat::time_tracking::run_active_secs;
+use crate::runtime::PathProvider;
+use crate::AppPreferences;
+use locale::ReportLocale;
+
+pub mod activity;
+pub mod attribution;
+pub mod commands;
+pub mod digest;
+pub mod locale;
+
+/// Locale and delimiter for an export
+pub struct ExportFormat {
+    pub locale: &'static ReportLocale,
+    pub delimiter: char,
+}
+
+impl ExportFormat {
+    pub fn from_preferences(prefs: &AppPreferences) -> Result<Self, String> {
+        let locale = ReportLocale::find(&prefs.report_locale);
+        let delimiter = match prefs.csv_delimiter.as_deref() {
+            None | Some("") => locale.default_delimiter(),
+            Some("tab") | Some("\t") => '\t',
+            Some(d) if d.chars().count() == 1 && !d.contains(['"', '\n', '\r']) => {
+                d.chars().next().unwrap_or(',')
+            }
+            Some(d) => return Err(format!("Invalid CSV delimiter: {d:?}")),
+        };
+        Ok(Self { locale, delimiter })
+    }
+}
+
+/// Builds CSV text, quoting fields that need it (RFC 4180)
+pub struct CsvWriter {
+    delimiter: char,
+    out: String,
+}
+
+impl CsvWriter {
+    pub fn new(delimiter: char) -> Self {
+        Self {
+            delimiter,
+            out: String::new(),
+        }
+    }
+
+    pub fn row<S: AsRef<str>>(&mut self, fields: &[S]) {
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                self.out.push(self.delimiter);
+            }
+            let field = field.as_ref();
+            if field.contains([self.delimiter, '"', '\n', '\r']) {
+                self.out.push('"');
+                self.out.push_str(&field.replace('"', "\"\""));
+                self.out.push('"');
+            } else {
+                self.out.push_str(field);
+            }
+        }
+        self.out.push_str("\r\n");
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+/// One run in a usage export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRow {
+    pub session_id: String,
+    pub session_name: String,
+    pub worktree_id: String,
+    /// Client or cost center (see [`attribution`])
+    pub attribution: Option<String>,
+    /// Sub-project the run was scoped to (repo-relative path)
+    pub subproject: Option<String>,
+    pub started_at: u64,
+    pub ended_at: Option<u64>,
+    /// Seconds the agent was working (see `chat::time_tracking`)
+    pub active_secs: u64,
+    pub model: Option<String>,
+    /// RunStatus in snake_case
+    pub status: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cost_usd: Option<f64>,
+}
+
+/// Every run started at or after `since`, oldest first
+/// Runs that didn't record an attribution get their session's, else the
+/// one in `worktree_attributions` (worktree ID -> project attribution).
+pub fn usage_rows(
+    app: &impl PathProvider,
+    since: u64,
+    worktree_attributions: &HashMap<String, String>,
+) -> Result<Vec<UsageRow>, String> {
+    let mut rows = Vec::new();
+    for session_id in list_all_session_ids(app)? {
+        let Ok(Some(metadata)) = load_metadata(app, &session_id) else {
+            continue;
+        };
+        let fallback = metadata
+            .attribution
+            .clone()
+            .or_else(|| worktree_attributions.get(&metadata.worktree_id).cloned());
+        for run in metadata.runs.iter().filter(|r| r.started_at >= since) {
+            let usage = run.usage.clone().unwrap_or_default();
+            rows.push(UsageRow {
+                session_id: metadata.id.clone(),
+                session_name: metadata.name.clone(),
+                worktree_id: metadata.worktree_id.clone(),
+                attribution: run.attribution.clone().or_else(|| fallback.clone()),
+                subproject: run.subproject.clone(),
+                started_at: run.started_at,
+                ended_at: run.ended_at,
+                active_secs: run_active_secs(app, &session_id, run),
+                model: run.model.clone(),
+                status: serde_json::to_value(&run.status)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default(),
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                cache_read_tokens: usage.cache_read_input_tokens,
+                cache_write_tokens: usage.cache_creation_input_tokens,
+                cost_usd: usage.cost_usd,
+            });
+        }
+    }
+    rows.sort_by_key(|r| r.started_at);
+    Ok(rows)
+}
+
+/// Usage rows as CSV, with dates in `tz`
+pub fn usage_csv<Tz: chrono::TimeZone>(rows: &[UsageRow], format: &ExportFormat, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let locale = format.locale;
+    let mut csv = CsvWriter::new(format.delimiter);
+    csv.row(&[
+        "Session",
+        "Session ID",
+        "Attribution",
+        "Subproject",
+        "Started",
+        "Ended",
+        "Duration (s)",
+        "Active (s)",
+        "Model",
+        "Status",
+        "Input tokens",
+        "Output tokens",
+        "Cache read tokens",
+        "Cache write tokens",
+        "Cost (USD)",
+    ]);
+    for row in rows {
+        csv.row(&[
+            row.session_name.clone(),
+            row.session_id.clone(),
+            row.attribution.clone().unwrap_or_default(),
+            row.subproject.clone().unwrap_or_default(),
+            locale.format_datetime(row.started_at, tz),
+            row.ended_at
+                .map(|t| locale.format_datetime(t, tz))
+                .unwrap_or_default(),
+            row.ended_at
+                .map(|t| t.saturating_sub(row.started_at).to_string())
+                .unwrap_or_default(),
+            row.active_secs.to_string(),
+            row.model.clone().unwrap_or_default(),
+            row.status.clone(),
+            row.input_tokens.to_string(),
+            row.output_tokens.to_string(),
+            row.cache_read_tokens.to_string(),
+            row.cache_write_tokens.to_string(),
+            row.cost_usd
+                .map(|c| locale.format_number(c, 4))
+                .unwrap_or_default(),
+        ]);
+    }
+    csv.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn prefs(locale: &str, delimiter: Option<&str>) -> AppPreferences {
+        AppPreferences {
+            report_locale: locale.to_string(),
+            csv_delimiter: delimiter.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_export_format() {
+        let format = ExportFormat::from_preferences(&prefs("de-DE", None)).unwrap();
+        assert_eq!(format.delimiter, ';');
+        let format = ExportFormat::from_preferences(&prefs("en-US", None)).unwrap();
+        assert_eq!(format.delimiter, ',');
+        let format = ExportFormat::from_preferences(&prefs("de-DE", Some("tab"))).unwrap();
+        assert_eq!(format.delimiter, '\t');
+        assert!(ExportFormat::from_preferences(&prefs("en-US", Some(";;"))).is_err());
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        let mut csv = CsvWriter::new(';');
+        csv.row(&["a;b", "say \"hi\"", "plain"]);
+        assert_eq!(csv.finish(), "\"a;b\";\"say \"\"hi\"\"\";plain\r\n");
+    }
+
+    #[test]
+    fn test_usage_csv() {
+        let rows = vec![UsageRow {
+            session_id: "s1".to_string(),
+            session_name: "Fix; build".to_string(),
+            worktree_id: "w1".to_string(),
+            attribution: Some("Acme".to_string()),
+            subproject: Some("packages/web".to_string()),
+            started_at: 1_709_647_620,
+            ended_at: Some(1_709_647_680),
+            active_secs: 45,
+            model: Some("opus".to_string()),
+            status: "completed".to_string(),
+            input_tokens: 1200,
+            output_tokens: 300,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            cost_usd: Some(0.5),
+        }];
+        let format = ExportFormat::from_preferences(&prefs("de-DE", None)).unwrap();
+        let csv = usage_csv(&rows, &format, &Utc);
+        let line = csv.lines().nth(1).unwrap();
+        assert_eq!(
+            line,
+            "\"Fix; build\";s1;Acme;packages/web;05.03.2024 14:07;05.03.2024 14:08;60;45;opus;completed;1200;300;0;0;0,5000"
+        );
+    }
+}
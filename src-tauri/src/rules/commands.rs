@@ -0,0 +1,77 @@
+use tauri::AppHandle;
+
+use super::watch::{WatchDraft, WatchRule};
+use super::{EventRule, RuleDraft, RuleExecution};
+
+/// Executions returned when the caller doesn't say
+const DEFAULT_LOG_LIMIT: usize = 100;
+
+/// All event rules, oldest first
+#[tauri::command]
+pub async fn list_event_rules(app: AppHandle) -> Vec<EventRule> {
+    super::load_rules(&app)
+}
+
+/// Save a new event rule
+#[tauri::command]
+pub async fn create_event_rule(app: AppHandle, rule: RuleDraft) -> Result<EventRule, String> {
+    super::create_rule(&app, rule)
+}
+
+/// Replace an event rule's definition (e.g. to disable it)
+#[tauri::command]
+pub async fn update_event_rule(
+    app: AppHandle,
+    rule_id: String,
+    rule: RuleDraft,
+) -> Result<EventRule, String> {
+    super::update_rule(&app, &rule_id, rule)
+}
+
+/// Delete an event rule
+#[tauri::command]
+pub async fn delete_event_rule(app: AppHandle, rule_id: String) -> Result<(), String> {
+    super::delete_rule(&app, &rule_id)
+}
+
+/// Recent rule executions, newest first, optionally for one rule
+#[tauri::command]
+pub async fn get_rule_executions(
+    app: AppHandle,
+    rule_id: Option<String>,
+    limit: Option<usize>,
+) -> Vec<RuleExecution> {
+    super::load_executions(&app)
+        .into_iter()
+        .filter(|e| rule_id.as_ref().is_none_or(|id| &e.rule_id == id))
+        .take(limit.unwrap_or(DEFAULT_LOG_LIMIT))
+        .collect()
+}
+
+/// All watch rules, oldest first
+#[tauri::command]
+pub async fn list_watch_rules(app: AppHandle) -> Vec<WatchRule> {
+    super::watch::load_watches(&app)
+}
+
+/// Save a new watch rule
+#[tauri::command]
+pub async fn create_watch_rule(app: AppHandle, rule: WatchDraft) -> Result<WatchRule, String> {
+    super::watch::create_watch(&app, rule)
+}
+
+/// Replace a watch rule's definition
+#[tauri::command]
+pub async fn update_watch_rule(
+    app: AppHandle,
+    rule_id: String,
+    rule: WatchDraft,
+) -> Result<WatchRule, String> {
+    super::watch::update_watch(&app, &rule_id, rule)
+}
+
+/// Delete a watch rule
+#[tauri::command]
+pub async fn delete_watch_rule(app: AppHandle, rule_id: String) -> Result<(), String> {
+    super::watch::delete_watch(&app, &rule_id)
+}
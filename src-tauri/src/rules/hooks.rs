@@ -0,0 +1,258 @@
+//! Inbound webhooks for watch rules
+//!
+//! A webhook watch rule is served at `/api/hooks/{rule_id}` and, when it has
+//! a name, at `/hooks/{name}`. A request proves it knows the rule's secret
+//! by sending it (`?secret=`, `X-Jean-Secret`, or GitLab's `X-Gitlab-Token`)
+//! or by signing the body with it (GitHub's `X-Hub-Signature-256`). The
+//! rule's `variables` copy body fields to the top of the payload, so a hook
+//! mapping `pr` to `pull_request.number` can queue "Review PR #{pr}".
+
+use std::collections::BTreeMap;
+
+use ring::hmac;
+use serde_json::{json, Map, Value};
+use tauri::AppHandle;
+
+use super::field;
+use super::watch::{fire, load_watches, WatchRule, WatchTrigger};
+use crate::http_server::auth::validate_token;
+
+/// Payload keys variables can't replace
+const RESERVED_KEYS: &[&str] = &[
+    "body",
+    "event",
+    "event_type",
+    "session_id",
+    "watch_id",
+    "watch_name",
+];
+
+/// What a request sent to prove it knows a hook's secret
+#[derive(Debug, Clone)]
+pub enum HookCredential {
+    /// The secret itself
+    Secret(String),
+    /// `sha256=<hex HMAC-SHA256 of the body>`
+    Signature(String),
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Check a webhook trigger's route name and variable mappings
+pub(super) fn validate(
+    name: Option<&str>,
+    variables: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    if let Some(name) = name {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid {
+            return Err(format!(
+                "Invalid hook name {name:?}: use lowercase letters, digits, - and _"
+            ));
+        }
+    }
+    for (variable, path) in variables {
+        if !is_identifier(variable) || RESERVED_KEYS.contains(&variable.as_str()) {
+            return Err(format!("Invalid variable name {variable:?}"));
+        }
+        if path.trim().is_empty() {
+            return Err(format!("Variable {variable} needs a field path"));
+        }
+    }
+    Ok(())
+}
+
+/// Route name of a webhook trigger
+pub(super) fn hook_name(trigger: &WatchTrigger) -> Option<&str> {
+    match trigger {
+        WatchTrigger::Webhook { name, .. } => name.as_deref().filter(|n| !n.is_empty()),
+        _ => None,
+    }
+}
+
+/// Bytes of a hex string (None unless every pair is a hex byte)
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Whether `signature` (`sha256=<hex>`) is the HMAC-SHA256 of `body` with
+/// `secret`, compared in constant time
+fn verify_signature(secret: &[u8], body: &[u8], signature: &str) -> bool {
+    let Some(tag) = signature.strip_prefix("sha256=").and_then(decode_hex) else {
+        return false;
+    };
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+    hmac::verify(&key, body, &tag).is_ok()
+}
+
+/// Whether a credential proves knowledge of `secret`
+fn authenticates(secret: &str, credential: &HookCredential, raw_body: &[u8]) -> bool {
+    if secret.is_empty() {
+        return false;
+    }
+    match credential {
+        HookCredential::Secret(sent) => validate_token(sent, secret),
+        HookCredential::Signature(signature) => {
+            verify_signature(secret.as_bytes(), raw_body, signature)
+        }
+    }
+}
+
+/// Trigger payload: the body, its event type, and the mapped variables
+fn hook_payload(
+    variables: &BTreeMap<String, String>,
+    body: Value,
+    event_type: Option<String>,
+) -> Value {
+    let mut payload = Map::new();
+    for (variable, path) in variables {
+        let value = field(&body, path.trim()).cloned().unwrap_or(Value::Null);
+        payload.insert(variable.clone(), value);
+    }
+    payload.insert("event_type".to_string(), json!(event_type));
+    payload.insert("body".to_string(), body);
+    Value::Object(payload)
+}
+
+/// The webhook watch rule with this id or route name
+fn find_hook<'a>(watches: &'a [WatchRule], key: &str) -> Option<&'a WatchRule> {
+    watches.iter().find(|w| {
+        matches!(w.trigger, WatchTrigger::Webhook { .. })
+            && (w.id == key || hook_name(&w.trigger) == Some(key))
+    })
+}
+
+/// Handle a request to a hook: Ok(false) when it didn't fire (disabled,
+/// conditions, cooldown); Err for an unknown hook or missing secret
+pub fn receive_webhook(
+    app: &AppHandle,
+    key: &str,
+    credentials: &[HookCredential],
+    raw_body: &[u8],
+    body: Value,
+    event_type: Option<String>,
+) -> Result<bool, String> {
+    let watches = load_watches(app);
+    let watch = find_hook(&watches, key)
+        .filter(|w| match &w.trigger {
+            WatchTrigger::Webhook { secret, .. } => credentials
+                .iter()
+                .any(|c| authenticates(secret, c, raw_body)),
+            _ => false,
+        })
+        .ok_or("Unknown hook")?;
+    if !watch.enabled || crate::emergency::is_engaged() {
+        return Ok(false);
+    }
+    let WatchTrigger::Webhook { variables, .. } = &watch.trigger else {
+        return Ok(false);
+    };
+    Ok(fire(app, watch, hook_payload(variables, body, event_type)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &[u8], body: &[u8]) -> String {
+        let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, secret), body);
+        let hex: String = tag.as_ref().iter().map(|b| format!("{b:02x}")).collect();
+        format!("sha256={hex}")
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        // The example in GitHub's "Validating webhook deliveries" docs
+        let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        let secret = b"It's a Secret to Everybody";
+        assert!(verify_signature(secret, b"Hello, World!", signature));
+        assert!(verify_signature(
+            secret,
+            b"Hello, World!",
+            &signature.to_uppercase().replace("SHA256=", "sha256=")
+        ));
+        assert!(!verify_signature(secret, b"Hello, World?", signature));
+        assert!(!verify_signature(b"wrong", b"Hello, World!", signature));
+        assert!(!verify_signature(
+            secret,
+            b"Hello, World!",
+            &signature[..70]
+        ));
+        assert!(!verify_signature(secret, b"Hello, World!", "sha256=zz"));
+        assert_eq!(sign(secret, b"Hello, World!"), signature);
+    }
+
+    #[test]
+    fn test_authenticates() {
+        let body = br#"{"action":"completed"}"#;
+        let signature = sign(b"s3cret", body);
+        let check = |c: HookCredential| authenticates("s3cret", &c, body);
+        assert!(check(HookCredential::Secret("s3cret".to_string())));
+        assert!(!check(HookCredential::Secret("guess".to_string())));
+        assert!(check(HookCredential::Signature(signature.clone())));
+        assert!(!check(HookCredential::Signature(
+            signature.replace("sha256=", "")
+        )));
+        assert!(!authenticates(
+            "s3cret",
+            &HookCredential::Signature(signature),
+            br#"{"action":"tampered"}"#
+        ));
+        assert!(!authenticates(
+            "",
+            &HookCredential::Secret(String::new()),
+            body
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let vars = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        assert!(validate(Some("ci-failed"), &vars(&[("pr", "pull_request.number")])).is_ok());
+        assert!(validate(Some("CI Failed"), &vars(&[])).is_err());
+        assert!(validate(None, &vars(&[("body", "x")])).is_err());
+        assert!(validate(None, &vars(&[("pr", " ")])).is_err());
+    }
+
+    #[test]
+    fn test_hook_payload() {
+        let body = json!({
+            "workflow_run": { "name": "CI", "conclusion": "failure" },
+            "pull_requests": [{ "number": 42 }],
+        });
+        let variables: BTreeMap<String, String> = [
+            ("workflow", "workflow_run.name"),
+            ("pr", "pull_requests.0.number"),
+            ("missing", "head_commit.id"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let payload = hook_payload(&variables, body.clone(), Some("workflow_run".to_string()));
+        assert_eq!(payload["workflow"], "CI");
+        assert_eq!(payload["pr"], 42);
+        assert_eq!(payload["missing"], Value::Null);
+        assert_eq!(payload["event_type"], "workflow_run");
+        assert_eq!(payload["body"], body);
+        assert_eq!(
+            crate::rules::interpolate("Fix {workflow} on PR #{pr}", "watch:webhook", &payload),
+            "Fix CI on PR #42"
+        );
+    }
+}
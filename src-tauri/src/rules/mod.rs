@@ -0,0 +1,753 @@
+//! User-defined event rules ("when this happens, do that")
+//!
+//! A rule watches the event bus: when an event named `event` (exact, or a
+//! prefix ending in `*` such as `chat:*`) is emitted and its payload meets
+//! every condition, the rule's action runs in the background: a native
+//! notification, a shell script, a webhook POST, or a follow-up message
+//! queued for the session. `{field.path}` placeholders in action text are
+//! filled from the payload, so a rule on `session:tests-failed` can queue
+//! "The tests fail ({summary}), fix them" for the session that failed.
+//!
+//! Rules live in `{app_data}/rules/rules.json`, and every firing is recorded
+//! in a bounded execution log next to them. A per-rule cooldown keeps rules
+//! whose actions emit matching events from looping.
+//!
+//! Watch rules (`watch`) queue tasks on file changes, CI webhooks and branch
+//! updates instead of events, and share the execution log.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::runtime::{now, PathProvider};
+
+pub mod commands;
+pub mod hooks;
+pub mod watch;
+
+/// Rules directory (within app data) and its files
+const RULES_DIR: &str = "rules";
+const RULES_FILE: &str = "rules.json";
+const LOG_FILE: &str = "executions.json";
+
+/// Executions kept in the log
+const MAX_LOG_ENTRIES: usize = 500;
+
+/// Cooldown for rules that don't set one
+pub const DEFAULT_COOLDOWN_SECS: u64 = 30;
+
+/// How often a queued task checks whether its session is idle
+const ENQUEUE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long a queued task waits for its session before giving up
+const ENQUEUE_TIMEOUT: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// `{field.path}` placeholders in action text
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([A-Za-z0-9_.]+)\}").unwrap());
+
+/// Enabled rules, loaded on first use and reset whenever rules are saved
+static RULES_CACHE: Lazy<Mutex<Option<Vec<EventRule>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Last firing of each rule (Unix seconds)
+static LAST_FIRED: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Serializes execution log writes
+static LOG_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOp {
+    Equals,
+    NotEquals,
+    /// Substring of a string, or element of an array
+    Contains,
+    /// Field is present and not null
+    Exists,
+}
+
+/// A test on one payload field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleCondition {
+    /// Dotted path into the payload, e.g. "session_id" or "run.status"
+    pub path: String,
+    pub op: ConditionOp,
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// What a rule does when it fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Show a native notification
+    Notify {
+        title: String,
+        #[serde(default)]
+        body: Option<String>,
+    },
+    /// Run a command in the default shell, with the event in `JEAN_EVENT`
+    /// and its JSON payload in `JEAN_EVENT_PAYLOAD`
+    RunScript {
+        command: String,
+        #[serde(default)]
+        working_dir: Option<String>,
+    },
+    /// POST `{ event, rule_id, rule_name, payload }` as JSON
+    Webhook { url: String },
+    /// Send a message to a session once it's idle (the event's
+    /// `session_id` unless set), with the session's launch settings
+    EnqueueTask {
+        prompt: String,
+        #[serde(default)]
+        session_id: Option<String>,
+    },
+}
+
+impl RuleAction {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Notify { .. } => "notify",
+            Self::RunScript { .. } => "run_script",
+            Self::Webhook { .. } => "webhook",
+            Self::EnqueueTask { .. } => "enqueue_task",
+        }
+    }
+}
+
+/// A rule as written, before it's saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDraft {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Event name, or a prefix ending in `*`
+    pub event: String,
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
+    pub action: RuleAction,
+    #[serde(default)]
+    pub cooldown_secs: Option<u64>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A saved rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRule {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub event: String,
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
+    pub action: RuleAction,
+    /// Minimum seconds between firings
+    pub cooldown_secs: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// One firing of a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleExecution {
+    pub id: String,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub event: String,
+    /// Action type, e.g. "webhook"
+    pub action: String,
+    pub fired_at: u64,
+    pub success: bool,
+    pub message: String,
+}
+
+fn rules_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.app_data_dir()?.join(RULES_DIR))
+}
+
+/// All rules, oldest first
+pub fn load_rules(app: &impl PathProvider) -> Vec<EventRule> {
+    rules_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(RULES_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_rules(app: &impl PathProvider, rules: &[EventRule]) -> Result<(), String> {
+    let dir = rules_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create rules directory: {e}"))?;
+    let content = serde_json::to_string_pretty(rules)
+        .map_err(|e| format!("Failed to serialize rules: {e}"))?;
+    fs::write(dir.join(RULES_FILE), content).map_err(|e| format!("Failed to save rules: {e}"))?;
+    *RULES_CACHE.lock().unwrap() = None;
+    Ok(())
+}
+
+fn validate(draft: &RuleDraft) -> Result<(), String> {
+    if draft.name.trim().is_empty() {
+        return Err("A rule needs a name".to_string());
+    }
+    let event = draft.event.trim();
+    if event.is_empty() || event == "*" {
+        return Err("A rule needs an event name or prefix".to_string());
+    }
+    if event.starts_with("rules:") {
+        return Err("Rules can't react to rules:* events".to_string());
+    }
+    if let Some(c) = draft.conditions.iter().find(|c| c.path.trim().is_empty()) {
+        return Err(format!("Condition {:?} needs a field path", c.op));
+    }
+    match &draft.action {
+        RuleAction::Notify { title, .. } if title.trim().is_empty() => {
+            Err("A notification needs a title".to_string())
+        }
+        RuleAction::RunScript { command, .. } if command.trim().is_empty() => {
+            Err("A script action needs a command".to_string())
+        }
+        RuleAction::Webhook { url }
+            if !(url.starts_with("http://") || url.starts_with("https://")) =>
+        {
+            Err(format!("Invalid webhook URL: {url}"))
+        }
+        RuleAction::EnqueueTask { prompt, .. } if prompt.trim().is_empty() => {
+            Err("A queued task needs a prompt".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Save a new rule
+pub fn create_rule(app: &impl PathProvider, draft: RuleDraft) -> Result<EventRule, String> {
+    validate(&draft)?;
+    let now = now();
+    let rule = EventRule {
+        id: Uuid::new_v4().to_string(),
+        name: draft.name.trim().to_string(),
+        enabled: draft.enabled,
+        event: draft.event.trim().to_string(),
+        conditions: draft.conditions,
+        action: draft.action,
+        cooldown_secs: draft.cooldown_secs.unwrap_or(DEFAULT_COOLDOWN_SECS),
+        created_at: now,
+        updated_at: now,
+    };
+    let mut rules = load_rules(app);
+    rules.push(rule.clone());
+    save_rules(app, &rules)?;
+    Ok(rule)
+}
+
+/// Replace a rule's definition
+pub fn update_rule(
+    app: &impl PathProvider,
+    rule_id: &str,
+    draft: RuleDraft,
+) -> Result<EventRule, String> {
+    validate(&draft)?;
+    let mut rules = load_rules(app);
+    let rule = rules
+        .iter_mut()
+        .find(|r| r.id == rule_id)
+        .ok_or_else(|| format!("Rule {rule_id} not found"))?;
+    rule.name = draft.name.trim().to_string();
+    rule.enabled = draft.enabled;
+    rule.event = draft.event.trim().to_string();
+    rule.conditions = draft.conditions;
+    rule.action = draft.action;
+    rule.cooldown_secs = draft.cooldown_secs.unwrap_or(DEFAULT_COOLDOWN_SECS);
+    rule.updated_at = now();
+    let rule = rule.clone();
+    save_rules(app, &rules)?;
+    Ok(rule)
+}
+
+/// Delete a rule
+pub fn delete_rule(app: &impl PathProvider, rule_id: &str) -> Result<(), String> {
+    let mut rules = load_rules(app);
+    let before = rules.len();
+    rules.retain(|r| r.id != rule_id);
+    if rules.len() == before {
+        return Err(format!("Rule {rule_id} not found"));
+    }
+    save_rules(app, &rules)?;
+    LAST_FIRED.lock().unwrap().remove(rule_id);
+    Ok(())
+}
+
+/// Executions, newest first
+pub fn load_executions(app: &impl PathProvider) -> Vec<RuleExecution> {
+    let mut log: Vec<RuleExecution> = rules_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(LOG_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    log.reverse();
+    log
+}
+
+fn record_execution(app: &impl PathProvider, execution: RuleExecution) -> Result<(), String> {
+    let _guard = LOG_LOCK.lock().unwrap();
+    let mut log = load_executions(app);
+    log.reverse();
+    log.push(execution);
+    let excess = log.len().saturating_sub(MAX_LOG_ENTRIES);
+    log.drain(..excess);
+
+    let dir = rules_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create rules directory: {e}"))?;
+    let content = serde_json::to_string_pretty(&log)
+        .map_err(|e| format!("Failed to serialize rule executions: {e}"))?;
+    fs::write(dir.join(LOG_FILE), content)
+        .map_err(|e| format!("Failed to save rule executions: {e}"))
+}
+
+/// Whether `event` is one a rule on `pattern` watches
+pub fn event_matches(pattern: &str, event: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => event.starts_with(prefix),
+        None => pattern == event,
+    }
+}
+
+fn field<'a>(payload: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|part| !part.is_empty())
+        .try_fold(payload, |value, part| match value {
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?),
+            _ => value.get(part),
+        })
+}
+
+/// Whether `payload` meets a condition
+pub fn condition_holds(condition: &RuleCondition, payload: &Value) -> bool {
+    let field = field(payload, condition.path.trim()).filter(|v| !v.is_null());
+    match condition.op {
+        ConditionOp::Exists => field.is_some(),
+        ConditionOp::Equals => field == Some(&condition.value),
+        ConditionOp::NotEquals => field != Some(&condition.value),
+        ConditionOp::Contains => match (field, &condition.value) {
+            (Some(Value::String(s)), Value::String(needle)) => s.contains(needle.as_str()),
+            (Some(Value::Array(items)), value) => items.contains(value),
+            _ => false,
+        },
+    }
+}
+
+/// Whether an enabled rule fires for this event
+pub fn rule_matches(rule: &EventRule, event: &str, payload: &Value) -> bool {
+    rule.enabled
+        && event_matches(&rule.event, event)
+        && rule.conditions.iter().all(|c| condition_holds(c, payload))
+}
+
+/// Fill `{field.path}` placeholders from the payload (`{event}` is the
+/// event name; missing fields become empty)
+pub fn interpolate(template: &str, event: &str, payload: &Value) -> String {
+    PLACEHOLDER
+        .replace_all(template, |caps: &regex::Captures| {
+            let path = &caps[1];
+            if path == "event" {
+                return event.to_string();
+            }
+            match field(payload, path) {
+                Some(Value::String(s)) => s.clone(),
+                Some(Value::Null) | None => String::new(),
+                Some(value) => value.to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Enabled rules (cached)
+fn enabled_rules(app: &impl PathProvider) -> Vec<EventRule> {
+    let mut cache = RULES_CACHE.lock().unwrap();
+    cache
+        .get_or_insert_with(|| load_rules(app).into_iter().filter(|r| r.enabled).collect())
+        .clone()
+}
+
+/// Claim a firing unless the rule is cooling down
+fn claim_firing(rule_id: &str, cooldown_secs: u64, now: u64) -> bool {
+    let mut last_fired = LAST_FIRED.lock().unwrap();
+    if let Some(last) = last_fired.get(rule_id) {
+        if now.saturating_sub(*last) < cooldown_secs {
+            return false;
+        }
+    }
+    last_fired.insert(rule_id.to_string(), now);
+    true
+}
+
+/// Evaluate rules against an emitted event, running the actions of those
+/// that fire in the background
+pub fn on_event<S: Serialize>(app: &AppHandle, event: &str, payload: &S) {
+    if event.starts_with("rules:") || crate::emergency::is_engaged() {
+        return;
+    }
+    let candidates: Vec<EventRule> = enabled_rules(app)
+        .into_iter()
+        .filter(|r| event_matches(&r.event, event))
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+    let Ok(payload) = serde_json::to_value(payload) else {
+        return;
+    };
+    let fired_at = now();
+    for rule in candidates {
+        if !rule_matches(&rule, event, &payload)
+            || !claim_firing(&rule.id, rule.cooldown_secs, fired_at)
+        {
+            continue;
+        }
+        log::trace!("Rule {} fired on {event}", rule.name);
+        let app = app.clone();
+        let event = event.to_string();
+        let payload = payload.clone();
+        crate::background_tasks::supervisor::spawn_task("event-rule", async move {
+            let outcome = execute(&app, &rule, &event, &payload).await;
+            let (success, message) = match outcome {
+                Ok(message) => (true, message),
+                Err(e) => {
+                    log::warn!("Rule {} failed: {e}", rule.name);
+                    (false, e)
+                }
+            };
+            let execution = RuleExecution {
+                id: Uuid::new_v4().to_string(),
+                rule_id: rule.id.clone(),
+                rule_name: rule.name.clone(),
+                event,
+                action: rule.action.kind().to_string(),
+                fired_at,
+                success,
+                message,
+            };
+            if let Err(e) = record_execution(&app, execution.clone()) {
+                log::error!("Failed to record rule execution: {e}");
+            }
+            use crate::http_server::EmitExt;
+            if let Err(e) = app.emit_all("rules:executed", &execution) {
+                log::error!("Failed to emit rules:executed event: {e}");
+            }
+        });
+    }
+}
+
+async fn execute(
+    app: &AppHandle,
+    rule: &EventRule,
+    event: &str,
+    payload: &Value,
+) -> Result<String, String> {
+    let fill = |text: &str| interpolate(text, event, payload);
+    match &rule.action {
+        RuleAction::Notify { title, body } => {
+            let title = fill(title);
+            crate::focus::notify(
+                app,
+                crate::focus::NotificationCategory::EventRule,
+                title.clone(),
+                body.as_deref().map(fill),
+            )
+            .await?;
+            Ok(format!("Notified \"{title}\""))
+        }
+        RuleAction::RunScript {
+            command,
+            working_dir,
+        } => {
+            let command = fill(command);
+            let working_dir = working_dir.as_deref().map(fill);
+            let event = event.to_string();
+            let payload = payload.to_string();
+            tauri::async_runtime::spawn_blocking(move || {
+                run_script(&command, working_dir.as_deref(), &event, &payload)
+            })
+            .await
+            .map_err(|e| format!("Script failed: {e}"))?
+        }
+        RuleAction::Webhook { url } => {
+            let client = crate::http_client::client_builder()
+                .timeout(Duration::from_secs(15))
+                .build()
+                .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+            let body = serde_json::json!({
+                "event": event,
+                "rule_id": rule.id,
+                "rule_name": rule.name,
+                "payload": payload,
+            });
+            let response = client
+                .post(url)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| format!("Webhook request failed: {e}"))?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(format!("Webhook returned {status}"));
+            }
+            Ok(format!("Webhook returned {status}"))
+        }
+        RuleAction::EnqueueTask { prompt, session_id } => {
+            let session_id = session_id
+                .as_deref()
+                .map(fill)
+                .filter(|s| !s.is_empty())
+                .or_else(|| {
+                    field(payload, "session_id")
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                })
+                .ok_or("No session to queue the task for")?;
+            let prompt = fill(prompt);
+            let task_app = app.clone();
+            let task_session = session_id.clone();
+            crate::background_tasks::supervisor::spawn_task("event-rule-task", async move {
+                if let Err(e) = enqueue_task(&task_app, &task_session, prompt, Vec::new()).await {
+                    log::error!("Queued task for session {task_session} failed: {e}");
+                }
+            });
+            Ok(format!("Queued a task for session {session_id}"))
+        }
+    }
+}
+
+fn run_script(
+    command: &str,
+    working_dir: Option<&str>,
+    event: &str,
+    payload: &str,
+) -> Result<String, String> {
+    let shell = crate::platform::get_default_shell();
+    let mut cmd = crate::platform::silent_command(&shell);
+    #[cfg(windows)]
+    cmd.arg("-Command");
+    #[cfg(not(windows))]
+    cmd.arg("-c");
+    cmd.arg(command)
+        .env("JEAN_EVENT", event)
+        .env("JEAN_EVENT_PAYLOAD", payload);
+    if let Some(dir) = working_dir.filter(|d| !d.is_empty()) {
+        cmd.current_dir(dir);
+    }
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run script: {e}"))?;
+    if output.status.success() {
+        Ok(format!("Script exited with {}", output.status))
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!(
+            "Script exited with {}: {}",
+            output.status,
+            stderr.trim()
+        ))
+    }
+}
+
+/// Send `prompt` to a session once it's no longer running, with the
+/// settings of its last run plus `extra_allowed_tools`
+pub(crate) async fn enqueue_task(
+    app: &AppHandle,
+    session_id: &str,
+    prompt: String,
+    extra_allowed_tools: Vec<String>,
+) -> Result<(), String> {
+    let started = std::time::Instant::now();
+    while crate::chat::registry::get_running_sessions()
+        .iter()
+        .any(|id| id == session_id)
+    {
+        if started.elapsed() > ENQUEUE_TIMEOUT {
+            return Err("Timed out waiting for the session to finish".to_string());
+        }
+        tokio::time::sleep(ENQUEUE_POLL_INTERVAL).await;
+    }
+    if crate::emergency::is_engaged() {
+        return Err("Emergency stop is engaged".to_string());
+    }
+
+    let metadata = crate::chat::storage::load_metadata(app, session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    let worktree = crate::projects::storage::load_projects_data(app)?
+        .find_worktree(&metadata.worktree_id)
+        .cloned()
+        .ok_or_else(|| format!("Worktree not found: {}", metadata.worktree_id))?;
+    let (_, mut settings) = crate::chat::rerun::launch_of(&metadata, &worktree.path)?;
+    if !extra_allowed_tools.is_empty() {
+        let allowed_tools = settings.allowed_tools.get_or_insert_with(Vec::new);
+        for tool in extra_allowed_tools {
+            if !allowed_tools.contains(&tool) {
+                allowed_tools.push(tool);
+            }
+        }
+    }
+    crate::chat::send_chat_message(
+        app.clone(),
+        session_id.to_string(),
+        metadata.worktree_id,
+        worktree.path,
+        prompt,
+        settings.model,
+        settings.execution_mode,
+        settings.thinking_level,
+        settings.effort_level,
+        settings.disable_thinking_for_mode,
+        settings.parallel_execution_prompt,
+        settings.ai_language,
+        settings.allowed_tools,
+        settings.mcp_config,
+        settings.chrome_enabled,
+        settings.custom_profile_settings,
+        settings.background_priority,
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+    use serde_json::json;
+
+    fn condition(path: &str, op: ConditionOp, value: Value) -> RuleCondition {
+        RuleCondition {
+            path: path.to_string(),
+            op,
+            value,
+        }
+    }
+
+    fn draft(event: &str) -> RuleDraft {
+        RuleDraft {
+            name: "Fix failing tests".to_string(),
+            enabled: true,
+            event: event.to_string(),
+            conditions: vec![],
+            action: RuleAction::EnqueueTask {
+                prompt: "Tests fail: {summary}. Fix them.".to_string(),
+                session_id: None,
+            },
+            cooldown_secs: None,
+        }
+    }
+
+    #[test]
+    fn test_event_matches() {
+        assert!(event_matches(
+            "session:tests-failed",
+            "session:tests-failed"
+        ));
+        assert!(!event_matches("session:tests-failed", "session:tests"));
+        assert!(event_matches("chat:*", "chat:done"));
+        assert!(!event_matches("chat:*", "session:done"));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let payload = json!({
+            "session_id": "s1",
+            "run": { "status": "crashed", "files": ["a.rs", "b.rs"] },
+            "summary": "2 failed",
+            "error": null,
+        });
+        let holds = |path, op, value| condition_holds(&condition(path, op, value), &payload);
+        assert!(holds("session_id", ConditionOp::Equals, json!("s1")));
+        assert!(holds(
+            "run.status",
+            ConditionOp::NotEquals,
+            json!("completed")
+        ));
+        assert!(holds("summary", ConditionOp::Contains, json!("failed")));
+        assert!(holds("run.files", ConditionOp::Contains, json!("b.rs")));
+        assert!(holds("run.files.0", ConditionOp::Equals, json!("a.rs")));
+        assert!(holds("run", ConditionOp::Exists, Value::Null));
+        assert!(!holds("error", ConditionOp::Exists, Value::Null));
+        assert!(!holds("missing", ConditionOp::Equals, json!("s1")));
+        assert!(holds("missing", ConditionOp::NotEquals, json!("s1")));
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let payload = json!({ "session_id": "s1", "run": { "exit_code": 2 } });
+        assert_eq!(
+            interpolate(
+                "{event} in {session_id} exited {run.exit_code}{missing}",
+                "session:tests-failed",
+                &payload
+            ),
+            "session:tests-failed in s1 exited 2"
+        );
+    }
+
+    #[test]
+    fn test_rule_crud() {
+        let paths = TempPaths::new();
+        assert!(create_rule(&paths, draft("")).is_err());
+        assert!(create_rule(&paths, draft("rules:executed")).is_err());
+
+        let rule = create_rule(&paths, draft("session:tests-failed")).unwrap();
+        assert_eq!(rule.cooldown_secs, DEFAULT_COOLDOWN_SECS);
+        assert!(rule_matches(&rule, "session:tests-failed", &json!({})));
+
+        let mut disabled = draft("session:tests-failed");
+        disabled.enabled = false;
+        let updated = update_rule(&paths, &rule.id, disabled).unwrap();
+        assert!(!rule_matches(&updated, "session:tests-failed", &json!({})));
+        assert_eq!(load_rules(&paths), vec![updated]);
+
+        delete_rule(&paths, &rule.id).unwrap();
+        assert!(load_rules(&paths).is_empty());
+        assert!(delete_rule(&paths, &rule.id).is_err());
+    }
+
+    #[test]
+    fn test_execution_log_is_bounded() {
+        let paths = TempPaths::new();
+        for i in 0..MAX_LOG_ENTRIES + 3 {
+            record_execution(
+                &paths,
+                RuleExecution {
+                    id: i.to_string(),
+                    rule_id: "r1".to_string(),
+                    rule_name: "Rule".to_string(),
+                    event: "chat:done".to_string(),
+                    action: "notify".to_string(),
+                    fired_at: i as u64,
+                    success: true,
+                    message: String::new(),
+                },
+            )
+            .unwrap();
+        }
+        let log = load_executions(&paths);
+        assert_eq!(log.len(), MAX_LOG_ENTRIES);
+        assert_eq!(log[0].id, (MAX_LOG_ENTRIES + 2).to_string());
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let paths = TempPaths::new();
+        let rule = create_rule(&paths, draft("chat:done")).unwrap();
+        let claim = |now| claim_firing(&rule.id, rule.cooldown_secs, now);
+        assert!(claim(1000));
+        assert!(!claim(1010));
+        assert!(claim(1000 + DEFAULT_COOLDOWN_SECS));
+    }
+}
@@ -0,0 +1,808 @@
+//! Watch rules: queue an agent task when files, CI or a branch change
+//!
+//! A watch rule sends a predefined prompt (e.g. "update the snapshot tests")
+//! to a session when its trigger fires: files matching a glob change in a
+//! worktree, CI POSTs to the rule's hook on the embedded server (see
+//! `hooks`), or the worktree's branch gets a new tip on origin. Each firing
+//! emits `watch:file-changed`, `watch:webhook` or `watch:branch-updated`
+//! and is recorded in the rules execution log.
+//!
+//! Agents edit files and push branches, which would retrigger their own
+//! watch. So file changes made while a session in the worktree runs (or
+//! within `SETTLE_SECS` of it stopping) are ignored, a branch tip that
+//! already exists locally was pushed from here and is ignored, and a rule
+//! doesn't fire again while its task is queued or running. The cooldown
+//! applies on top.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use super::{
+    claim_firing, condition_holds, default_enabled, enqueue_task, interpolate, now,
+    record_execution, rules_dir, RuleCondition, RuleExecution, DEFAULT_COOLDOWN_SECS,
+};
+use crate::background_tasks::supervisor::{spawn_task, supervise};
+use crate::http_server::EmitExt;
+use crate::platform::silent_command;
+use crate::runtime::{PathProvider, ProcessRunner, SystemProcessRunner};
+
+const WATCHES_FILE: &str = "watches.json";
+
+/// How often worktrees are scanned for changed files
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often branches are compared with origin
+const BRANCH_CHECK_SECS: u64 = 60;
+
+/// Seconds after a session stops during which its file writes still count
+/// as the agent's
+const SETTLE_SECS: u64 = 15;
+
+/// Changed files listed in `file_list`
+const MAX_LISTED_FILES: usize = 20;
+
+/// Poll state per watch rule
+static WATCH_STATE: Lazy<Mutex<HashMap<String, WatchState>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Last poll at which a session was running, per worktree
+static AGENT_SEEN: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Rules whose task is queued or running
+static IN_FLIGHT: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// What makes a watch rule fire
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WatchTrigger {
+    /// Files matching any glob (relative to the worktree; gitignored and
+    /// hidden files are skipped) were added, modified or removed
+    FileChanged {
+        worktree_id: String,
+        globs: Vec<String>,
+    },
+    /// CI POSTed to `/api/hooks/{rule_id}` (or `/hooks/{name}`) with this
+    /// secret (generated when left empty); see `hooks`
+    Webhook {
+        #[serde(default)]
+        secret: String,
+        /// Route name, e.g. "ci-failed"
+        #[serde(default)]
+        name: Option<String>,
+        /// Prompt variables filled from the request body: variable → dotted
+        /// path, e.g. "pr" → "pull_request.number"
+        #[serde(default)]
+        variables: BTreeMap<String, String>,
+    },
+    /// The branch (the worktree's unless set) has a new tip on origin
+    BranchUpdated {
+        worktree_id: String,
+        #[serde(default)]
+        branch: Option<String>,
+    },
+}
+
+impl WatchTrigger {
+    /// Event emitted when the trigger fires
+    fn event(&self) -> &'static str {
+        match self {
+            Self::FileChanged { .. } => "watch:file-changed",
+            Self::Webhook { .. } => "watch:webhook",
+            Self::BranchUpdated { .. } => "watch:branch-updated",
+        }
+    }
+
+    fn worktree_id(&self) -> Option<&str> {
+        match self {
+            Self::FileChanged { worktree_id, .. } | Self::BranchUpdated { worktree_id, .. } => {
+                Some(worktree_id)
+            }
+            Self::Webhook { .. } => None,
+        }
+    }
+}
+
+/// A watch rule as written, before it's saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchDraft {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub trigger: WatchTrigger,
+    /// Tests on the trigger payload (e.g. `body.conclusion` for webhooks)
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
+    /// Task to queue; `{field.path}` placeholders are filled from the payload
+    pub prompt: String,
+    /// Session to queue the task for (default: the worktree's active one;
+    /// required for webhooks)
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub cooldown_secs: Option<u64>,
+}
+
+/// A saved watch rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchRule {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub trigger: WatchTrigger,
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
+    pub prompt: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Minimum seconds between firings
+    pub cooldown_secs: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Default)]
+struct WatchState {
+    /// Matching files at the last scan: path → (mtime, size)
+    files: Option<HashMap<PathBuf, (u64, u64)>>,
+    /// Remote tip at the last check
+    remote_head: Option<String>,
+    branch_checked_at: u64,
+}
+
+/// All watch rules, oldest first
+pub fn load_watches(app: &impl PathProvider) -> Vec<WatchRule> {
+    rules_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(WATCHES_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_watches(app: &impl PathProvider, watches: &[WatchRule]) -> Result<(), String> {
+    let dir = rules_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create rules directory: {e}"))?;
+    let content = serde_json::to_string_pretty(watches)
+        .map_err(|e| format!("Failed to serialize watch rules: {e}"))?;
+    fs::write(dir.join(WATCHES_FILE), content)
+        .map_err(|e| format!("Failed to save watch rules: {e}"))
+}
+
+/// Matcher for `globs`, relative to `root`
+fn glob_matcher(root: &Path, globs: &[String]) -> Result<Override, String> {
+    let mut builder = OverrideBuilder::new(root);
+    for glob in globs.iter().map(|g| g.trim()).filter(|g| !g.is_empty()) {
+        builder
+            .add(glob)
+            .map_err(|e| format!("Invalid glob {glob}: {e}"))?;
+    }
+    builder.build().map_err(|e| format!("Invalid globs: {e}"))
+}
+
+fn validate(draft: &WatchDraft) -> Result<(), String> {
+    if draft.name.trim().is_empty() {
+        return Err("A watch rule needs a name".to_string());
+    }
+    if draft.prompt.trim().is_empty() {
+        return Err("A watch rule needs a prompt".to_string());
+    }
+    if let Some(c) = draft.conditions.iter().find(|c| c.path.trim().is_empty()) {
+        return Err(format!("Condition {:?} needs a field path", c.op));
+    }
+    if draft
+        .trigger
+        .worktree_id()
+        .is_some_and(|id| id.trim().is_empty())
+    {
+        return Err("A watch rule needs a worktree".to_string());
+    }
+    match &draft.trigger {
+        WatchTrigger::FileChanged { globs, .. } => {
+            if globs.iter().all(|g| g.trim().is_empty()) {
+                return Err("A file watch needs at least one glob".to_string());
+            }
+            glob_matcher(Path::new("."), globs).map(|_| ())
+        }
+        WatchTrigger::Webhook { .. }
+            if draft.session_id.as_deref().is_none_or(|s| s.is_empty()) =>
+        {
+            Err("A webhook watch needs a session".to_string())
+        }
+        WatchTrigger::Webhook {
+            name, variables, ..
+        } => super::hooks::validate(name.as_deref(), variables),
+        _ => Ok(()),
+    }
+}
+
+/// Reject a hook name another watch rule already uses
+fn check_unique_name(
+    watches: &[WatchRule],
+    watch_id: &str,
+    draft: &WatchDraft,
+) -> Result<(), String> {
+    let Some(name) = super::hooks::hook_name(&draft.trigger) else {
+        return Ok(());
+    };
+    if watches
+        .iter()
+        .any(|w| w.id != watch_id && super::hooks::hook_name(&w.trigger) == Some(name))
+    {
+        return Err(format!(
+            "Another watch rule already uses the hook name {name}"
+        ));
+    }
+    Ok(())
+}
+
+/// Give a webhook trigger without a secret a new one
+fn with_secret(trigger: WatchTrigger, existing: Option<&WatchTrigger>) -> WatchTrigger {
+    match trigger {
+        WatchTrigger::Webhook {
+            secret,
+            name,
+            variables,
+        } if secret.trim().is_empty() => WatchTrigger::Webhook {
+            secret: match existing {
+                Some(WatchTrigger::Webhook { secret, .. }) => secret.clone(),
+                _ => crate::http_server::auth::generate_token(),
+            },
+            name,
+            variables,
+        },
+        trigger => trigger,
+    }
+}
+
+/// Save a new watch rule
+pub fn create_watch(app: &impl PathProvider, draft: WatchDraft) -> Result<WatchRule, String> {
+    validate(&draft)?;
+    let mut watches = load_watches(app);
+    check_unique_name(&watches, "", &draft)?;
+    let now = now();
+    let watch = WatchRule {
+        id: Uuid::new_v4().to_string(),
+        name: draft.name.trim().to_string(),
+        enabled: draft.enabled,
+        trigger: with_secret(draft.trigger, None),
+        conditions: draft.conditions,
+        prompt: draft.prompt,
+        session_id: draft.session_id.filter(|s| !s.is_empty()),
+        cooldown_secs: draft.cooldown_secs.unwrap_or(DEFAULT_COOLDOWN_SECS),
+        created_at: now,
+        updated_at: now,
+    };
+    watches.push(watch.clone());
+    save_watches(app, &watches)?;
+    Ok(watch)
+}
+
+/// Replace a watch rule's definition (an empty webhook secret keeps the
+/// current one)
+pub fn update_watch(
+    app: &impl PathProvider,
+    watch_id: &str,
+    draft: WatchDraft,
+) -> Result<WatchRule, String> {
+    validate(&draft)?;
+    let mut watches = load_watches(app);
+    check_unique_name(&watches, watch_id, &draft)?;
+    let watch = watches
+        .iter_mut()
+        .find(|w| w.id == watch_id)
+        .ok_or_else(|| format!("Watch rule {watch_id} not found"))?;
+    if watch.trigger != draft.trigger {
+        WATCH_STATE.lock().unwrap().remove(watch_id);
+    }
+    watch.name = draft.name.trim().to_string();
+    watch.enabled = draft.enabled;
+    watch.trigger = with_secret(draft.trigger, Some(&watch.trigger));
+    watch.conditions = draft.conditions;
+    watch.prompt = draft.prompt;
+    watch.session_id = draft.session_id.filter(|s| !s.is_empty());
+    watch.cooldown_secs = draft.cooldown_secs.unwrap_or(DEFAULT_COOLDOWN_SECS);
+    watch.updated_at = now();
+    let watch = watch.clone();
+    save_watches(app, &watches)?;
+    Ok(watch)
+}
+
+/// Delete a watch rule
+pub fn delete_watch(app: &impl PathProvider, watch_id: &str) -> Result<(), String> {
+    let mut watches = load_watches(app);
+    let before = watches.len();
+    watches.retain(|w| w.id != watch_id);
+    if watches.len() == before {
+        return Err(format!("Watch rule {watch_id} not found"));
+    }
+    save_watches(app, &watches)?;
+    WATCH_STATE.lock().unwrap().remove(watch_id);
+    Ok(())
+}
+
+/// Matching files under `root` with their mtime and size
+fn snapshot(root: &Path, globs: &[String]) -> Result<HashMap<PathBuf, (u64, u64)>, String> {
+    let matcher = glob_matcher(root, globs)?;
+    let mut files = HashMap::new();
+    for entry in WalkBuilder::new(root).require_git(false).build().flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() || !matcher.matched(entry.path(), false).is_whitelist() {
+            continue;
+        }
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs());
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        files.insert(relative.to_path_buf(), (mtime, metadata.len()));
+    }
+    Ok(files)
+}
+
+/// Files added, modified or removed between scans, except those changed
+/// by an agent (at or before `agent_until`; removals count as happening
+/// `now`)
+fn changed_files(
+    before: &HashMap<PathBuf, (u64, u64)>,
+    after: &HashMap<PathBuf, (u64, u64)>,
+    agent_until: u64,
+    now: u64,
+) -> Vec<String> {
+    let modified = after
+        .iter()
+        .filter(|(path, state)| before.get(*path) != Some(*state))
+        .map(|(path, (mtime, _))| (path, *mtime));
+    let removed = before
+        .keys()
+        .filter(|path| !after.contains_key(*path))
+        .map(|path| (path, now));
+    let mut files: Vec<String> = modified
+        .chain(removed)
+        .filter(|(_, changed_at)| *changed_at > agent_until)
+        .map(|(path, _)| path.to_string_lossy().replace('\\', "/"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Record which worktrees have a running session
+fn note_agent_activity(app: &AppHandle, now: u64) {
+    let running = crate::chat::registry::get_running_sessions();
+    let mut seen = AGENT_SEEN.lock().unwrap();
+    for session_id in running {
+        if let Ok(Some(metadata)) = crate::chat::storage::load_metadata(app, &session_id) {
+            seen.insert(metadata.worktree_id, now);
+        }
+    }
+}
+
+/// Until when file changes in a worktree are the agent's
+fn agent_until(worktree_id: &str) -> u64 {
+    AGENT_SEEN
+        .lock()
+        .unwrap()
+        .get(worktree_id)
+        .map_or(0, |seen| seen + SETTLE_SECS)
+}
+
+fn worktree(
+    app: &impl PathProvider,
+    worktree_id: &str,
+) -> Result<crate::projects::types::Worktree, String> {
+    crate::projects::storage::load_projects_data(app)?
+        .find_worktree(worktree_id)
+        .cloned()
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))
+}
+
+fn poll_files(
+    app: &AppHandle,
+    watch: &WatchRule,
+    worktree_id: &str,
+    globs: &[String],
+    now: u64,
+) -> Result<Option<Value>, String> {
+    let root = PathBuf::from(worktree(app, worktree_id)?.path);
+    let files = snapshot(&root, globs)?;
+    let previous = {
+        let mut state = WATCH_STATE.lock().unwrap();
+        state
+            .entry(watch.id.clone())
+            .or_default()
+            .files
+            .replace(files.clone())
+    };
+    // The first scan is the baseline
+    let Some(previous) = previous else {
+        return Ok(None);
+    };
+    let changed = changed_files(&previous, &files, agent_until(worktree_id), now);
+    if changed.is_empty() {
+        return Ok(None);
+    }
+    let mut file_list = changed[..changed.len().min(MAX_LISTED_FILES)].join(", ");
+    if changed.len() > MAX_LISTED_FILES {
+        file_list.push_str(&format!(" (+{} more)", changed.len() - MAX_LISTED_FILES));
+    }
+    Ok(Some(json!({
+        "worktree_id": worktree_id,
+        "files": changed,
+        "file_list": file_list,
+    })))
+}
+
+fn git(runner: &dyn ProcessRunner, dir: &Path, args: &[&str]) -> Result<String, String> {
+    let mut command = silent_command("git");
+    command.current_dir(dir).args(args);
+    let output = runner.run(command, None)?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Tip of `branch` on origin (None if it isn't there)
+fn remote_head(
+    runner: &dyn ProcessRunner,
+    dir: &Path,
+    branch: &str,
+) -> Result<Option<String>, String> {
+    let reference = format!("refs/heads/{branch}");
+    let output = git(runner, dir, &["ls-remote", "origin", &reference])?;
+    Ok(output.lines().find_map(|line| {
+        let (sha, name) = line.split_once('\t')?;
+        (name.trim() == reference).then(|| sha.trim().to_string())
+    }))
+}
+
+/// Whether `sha` is a commit in the local repository
+fn is_local_commit(runner: &dyn ProcessRunner, dir: &Path, sha: &str) -> bool {
+    git(
+        runner,
+        dir,
+        &["cat-file", "-e", &format!("{sha}^{{commit}}")],
+    )
+    .is_ok()
+}
+
+fn poll_branch(
+    app: &AppHandle,
+    runner: &dyn ProcessRunner,
+    watch: &WatchRule,
+    worktree_id: &str,
+    branch: Option<&str>,
+    now: u64,
+) -> Result<Option<Value>, String> {
+    {
+        let mut state = WATCH_STATE.lock().unwrap();
+        let state = state.entry(watch.id.clone()).or_default();
+        if now.saturating_sub(state.branch_checked_at) < BRANCH_CHECK_SECS {
+            return Ok(None);
+        }
+        state.branch_checked_at = now;
+    }
+    let worktree = worktree(app, worktree_id)?;
+    let branch = branch
+        .filter(|b| !b.is_empty())
+        .unwrap_or(&worktree.branch)
+        .to_string();
+    let dir = PathBuf::from(&worktree.path);
+    let Some(head) = remote_head(runner, &dir, &branch)? else {
+        return Ok(None);
+    };
+    let previous = WATCH_STATE
+        .lock()
+        .unwrap()
+        .entry(watch.id.clone())
+        .or_default()
+        .remote_head
+        .replace(head.clone());
+    match previous {
+        // The first check is the baseline
+        None => Ok(None),
+        Some(previous) if previous == head => Ok(None),
+        // Pushed from here, likely by the agent
+        Some(_) if is_local_commit(runner, &dir, &head) => Ok(None),
+        Some(previous) => Ok(Some(json!({
+            "worktree_id": worktree_id,
+            "branch": branch,
+            "sha": head,
+            "previous_sha": previous,
+        }))),
+    }
+}
+
+/// Session a watch rule's task goes to
+fn target_session(app: &impl PathProvider, watch: &WatchRule) -> Result<String, String> {
+    if let Some(session_id) = watch.session_id.as_ref().filter(|s| !s.is_empty()) {
+        return Ok(session_id.clone());
+    }
+    let worktree_id = watch
+        .trigger
+        .worktree_id()
+        .ok_or("No session to queue the task for")?;
+    let sessions = crate::chat::storage::load_sessions_by_id(app, worktree_id)?;
+    sessions
+        .active_session_id
+        .or_else(|| sessions.sessions.first().map(|s| s.id.clone()))
+        .ok_or_else(|| format!("Worktree {worktree_id} has no sessions"))
+}
+
+/// Queue the rule's task unless its conditions fail, it's cooling down or
+/// its last task hasn't finished
+pub(super) fn fire(app: &AppHandle, watch: &WatchRule, mut payload: Value) -> bool {
+    if !watch
+        .conditions
+        .iter()
+        .all(|c| condition_holds(c, &payload))
+    {
+        return false;
+    }
+    let fired_at = now();
+    if IN_FLIGHT.lock().unwrap().contains(&watch.id)
+        || !claim_firing(&watch.id, watch.cooldown_secs, fired_at)
+    {
+        log::trace!("Watch rule {} skipped a trigger", watch.name);
+        return false;
+    }
+
+    let event = watch.trigger.event();
+    payload["watch_id"] = json!(watch.id);
+    payload["watch_name"] = json!(watch.name);
+    let outcome = target_session(app, watch).map(|session_id| {
+        payload["session_id"] = json!(session_id);
+        let prompt = interpolate(&watch.prompt, event, &payload);
+        IN_FLIGHT.lock().unwrap().insert(watch.id.clone());
+        let task_app = app.clone();
+        let task_session = session_id.clone();
+        let watch_id = watch.id.clone();
+        spawn_task("watch-rule-task", async move {
+            if let Err(e) = enqueue_task(&task_app, &task_session, prompt, Vec::new()).await {
+                log::error!("Queued task for session {task_session} failed: {e}");
+            }
+            IN_FLIGHT.lock().unwrap().remove(&watch_id);
+        });
+        format!("Queued a task for session {session_id}")
+    });
+    let (success, message) = match outcome {
+        Ok(message) => (true, message),
+        Err(e) => {
+            log::warn!("Watch rule {} failed: {e}", watch.name);
+            (false, e)
+        }
+    };
+    if success {
+        if let Err(e) = app.emit_all(event, &payload) {
+            log::error!("Failed to emit {event} event: {e}");
+        }
+    }
+
+    let execution = RuleExecution {
+        id: Uuid::new_v4().to_string(),
+        rule_id: watch.id.clone(),
+        rule_name: watch.name.clone(),
+        event: event.to_string(),
+        action: "enqueue_task".to_string(),
+        fired_at,
+        success,
+        message,
+    };
+    if let Err(e) = record_execution(app, execution.clone()) {
+        log::error!("Failed to record rule execution: {e}");
+    }
+    if let Err(e) = app.emit_all("rules:executed", &execution) {
+        log::error!("Failed to emit rules:executed event: {e}");
+    }
+    success
+}
+
+fn poll(app: &AppHandle) {
+    if crate::emergency::is_engaged() {
+        return;
+    }
+    let watches: Vec<WatchRule> = load_watches(app)
+        .into_iter()
+        .filter(|w| w.enabled && w.trigger.worktree_id().is_some())
+        .collect();
+    {
+        let ids: HashSet<&str> = watches.iter().map(|w| w.id.as_str()).collect();
+        WATCH_STATE
+            .lock()
+            .unwrap()
+            .retain(|id, _| ids.contains(id.as_str()));
+    }
+    if watches.is_empty() {
+        return;
+    }
+
+    let now = now();
+    note_agent_activity(app, now);
+    for watch in watches {
+        let triggered = match &watch.trigger {
+            WatchTrigger::FileChanged { worktree_id, globs } => {
+                poll_files(app, &watch, worktree_id, globs, now)
+            }
+            WatchTrigger::BranchUpdated {
+                worktree_id,
+                branch,
+            } => poll_branch(
+                app,
+                &SystemProcessRunner,
+                &watch,
+                worktree_id,
+                branch.as_deref(),
+                now,
+            ),
+            WatchTrigger::Webhook { .. } => Ok(None),
+        };
+        match triggered {
+            Ok(Some(payload)) => {
+                fire(app, &watch, payload);
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Watch rule {} couldn't check its trigger: {e}", watch.name),
+        }
+    }
+}
+
+/// Start polling file and branch watch rules
+pub fn start_watcher(app: AppHandle) {
+    supervise("watch-rules", move || loop {
+        poll(&app);
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::{ScriptedRunner, TempPaths};
+
+    fn draft(trigger: WatchTrigger) -> WatchDraft {
+        WatchDraft {
+            name: "Update snapshots".to_string(),
+            enabled: true,
+            trigger,
+            conditions: vec![],
+            prompt: "Snapshots changed: {file_list}. Update the snapshot tests.".to_string(),
+            session_id: None,
+            cooldown_secs: None,
+        }
+    }
+
+    fn file_trigger(globs: &[&str]) -> WatchTrigger {
+        WatchTrigger::FileChanged {
+            worktree_id: "w1".to_string(),
+            globs: globs.iter().map(|g| g.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_watch_crud() {
+        let paths = TempPaths::new();
+        assert!(create_watch(&paths, draft(file_trigger(&[]))).is_err());
+        assert!(create_watch(&paths, draft(file_trigger(&["src/[a"]))).is_err());
+        let hook = WatchTrigger::Webhook {
+            secret: String::new(),
+            name: Some("ci".to_string()),
+            variables: BTreeMap::new(),
+        };
+        assert!(create_watch(&paths, draft(hook.clone())).is_err());
+
+        let mut hook_draft = draft(hook.clone());
+        hook_draft.session_id = Some("s1".to_string());
+        let watch = create_watch(&paths, hook_draft.clone()).unwrap();
+        let WatchTrigger::Webhook { secret, .. } = &watch.trigger else {
+            panic!("expected a webhook trigger");
+        };
+        assert!(!secret.is_empty());
+        assert_eq!(watch.cooldown_secs, DEFAULT_COOLDOWN_SECS);
+
+        // An empty secret keeps the current one
+        hook_draft.enabled = false;
+        let updated = update_watch(&paths, &watch.id, hook_draft).unwrap();
+        assert_eq!(updated.trigger, watch.trigger);
+        assert!(!updated.enabled);
+        assert_eq!(load_watches(&paths), vec![updated]);
+
+        // Hook names are unique
+        let mut other = draft(hook.clone());
+        other.session_id = Some("s2".to_string());
+        assert!(create_watch(&paths, other).is_err());
+
+        delete_watch(&paths, &watch.id).unwrap();
+        assert!(load_watches(&paths).is_empty());
+        assert!(delete_watch(&paths, &watch.id).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_matches_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("tests/__snapshots__")).unwrap();
+        fs::write(root.join("tests/__snapshots__/app.snap"), "a").unwrap();
+        fs::write(root.join("tests/app.rs"), "b").unwrap();
+        fs::write(root.join("README.md"), "c").unwrap();
+
+        let globs = vec!["**/*.snap".to_string(), "*.md".to_string()];
+        let mut files: Vec<_> = snapshot(root, &globs).unwrap().into_keys().collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("README.md"),
+                PathBuf::from("tests/__snapshots__/app.snap")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_files_ignores_agent_changes() {
+        let before: HashMap<PathBuf, (u64, u64)> = [
+            (PathBuf::from("a.snap"), (100, 1)),
+            (PathBuf::from("b.snap"), (100, 1)),
+            (PathBuf::from("gone.snap"), (100, 1)),
+        ]
+        .into();
+        let after: HashMap<PathBuf, (u64, u64)> = [
+            (PathBuf::from("a.snap"), (200, 2)),
+            (PathBuf::from("b.snap"), (100, 1)),
+            (PathBuf::from("new.snap"), (150, 1)),
+        ]
+        .into();
+        assert_eq!(
+            changed_files(&before, &after, 0, 300),
+            vec!["a.snap", "gone.snap", "new.snap"]
+        );
+        // An agent ran until 160 (plus settling)
+        assert_eq!(
+            changed_files(&before, &after, 175, 300),
+            vec!["a.snap", "gone.snap"]
+        );
+        // ...or is still running
+        assert!(changed_files(&before, &after, 310, 300).is_empty());
+    }
+
+    #[test]
+    fn test_remote_head() {
+        let runner = ScriptedRunner::succeeding(
+            "1111111111111111111111111111111111111111\trefs/heads/main-old\n\
+             2222222222222222222222222222222222222222\trefs/heads/main\n",
+        );
+        assert_eq!(
+            remote_head(&runner, Path::new("."), "main").unwrap(),
+            Some("2222222222222222222222222222222222222222".to_string())
+        );
+        assert_eq!(
+            runner.runs()[0].args,
+            vec!["ls-remote", "origin", "refs/heads/main"]
+        );
+        assert_eq!(remote_head(&runner, Path::new("."), "dev").unwrap(), None);
+        assert!(is_local_commit(&runner, Path::new("."), "2222"));
+        assert!(!is_local_commit(
+            &ScriptedRunner::failing(128, "fatal: Not a valid object name"),
+            Path::new("."),
+            "3333"
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+//! Runtime capabilities that logic modules depend on instead of `AppHandle`
+//!
+//! `AppHandle` implements all of these, so Tauri commands pass `&app` as
+//! before. Tests substitute the doubles in `crate::test_support` and exercise
+//! the same code with plain `cargo test`.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+/// Resolves the app's data directories
+pub trait PathProvider {
+    /// Data directory of the active Jean profile (e.g.
+    /// `~/Library/Application Support/jean/` for the default profile)
+    fn app_data_dir(&self) -> Result<PathBuf, String>;
+
+    /// Data shared by all profiles: installed tools and runtimes, downloaded
+    /// models and the profile list itself
+    fn shared_data_dir(&self) -> Result<PathBuf, String> {
+        self.app_data_dir()
+    }
+}
+
+impl PathProvider for AppHandle {
+    fn app_data_dir(&self) -> Result<PathBuf, String> {
+        let root = self.shared_data_dir()?;
+        Ok(crate::profiles::profile_dir(
+            &root,
+            &crate::profiles::active_profile(),
+        ))
+    }
+
+    fn shared_data_dir(&self) -> Result<PathBuf, String> {
+        self.path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data directory: {e}"))
+    }
+}
+
+/// Who an event is for (see `crate::http_server::routing`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmitTarget {
+    /// Every window and client
+    All,
+    /// One native window, by label
+    Window(String),
+    /// Windows and clients subscribed to a session (or to all sessions)
+    Session(String),
+}
+
+/// Delivers events to the frontend
+///
+/// Re-exported as `crate::http_server::EmitExt`; the `AppHandle` impl lives
+/// there because it also broadcasts to WebSocket clients.
+pub trait EventSink {
+    fn emit_all<S: Serialize + Clone>(&self, event: &str, payload: &S) -> Result<(), String>;
+
+    /// Deliver an event only to `target`. Sinks without windows broadcast.
+    fn emit_to<S: Serialize + Clone>(
+        &self,
+        _target: &EmitTarget,
+        event: &str,
+        payload: &S,
+    ) -> Result<(), String> {
+        self.emit_all(event, payload)
+    }
+}
+
+/// Runs a prepared command to completion
+pub trait ProcessRunner {
+    /// Run `command`, writing `stdin` to it (then closing it) if given, and
+    /// collect its output
+    fn run(&self, command: Command, stdin: Option<&[u8]>) -> Result<Output, String>;
+}
+
+/// Spawns real child processes
+pub struct SystemProcessRunner;
+
+impl ProcessRunner for SystemProcessRunner {
+    fn run(&self, mut command: Command, stdin: Option<&[u8]>) -> Result<Output, String> {
+        let program = command.get_program().to_string_lossy().into_owned();
+        command
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to spawn {program}: {e}"))?;
+
+        if let Some(input) = stdin {
+            // Dropped at the end of this block, closing stdin so the child sees EOF
+            let mut pipe = child
+                .stdin
+                .take()
+                .ok_or_else(|| format!("Failed to open stdin for {program}"))?;
+            pipe.write_all(input)
+                .map_err(|e| format!("Failed to write to {program} stdin: {e}"))?;
+        }
+
+        child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to wait for {program}: {e}"))
+    }
+}
+
+/// Current Unix time in seconds
+pub(crate) fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,111 @@
+//! Tauri command for running the self-test suite
+
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Manager};
+
+use super::{
+    check_extract_tar_gz, check_extract_zip, check_install, check_spawn, check_storage, run_check,
+    SelfTestCheck, SelfTestReport,
+};
+use crate::runtime::PathProvider;
+
+/// Scratch directory name within app data (removed after each run)
+const SELF_TEST_DIR_NAME: &str = "self-test";
+
+/// Run the self-test suite and return per-check results
+///
+/// Checks run in order; later checks still run when earlier ones fail so the
+/// report shows every broken path at once.
+#[tauri::command]
+pub async fn run_self_test(app: AppHandle) -> Result<SelfTestReport, String> {
+    let http_server = check_http_server(&app).await;
+    run_suite(&app, http_server).await
+}
+
+/// Run the filesystem/process checks and assemble the report
+///
+/// The HTTP server check is passed in: over WebSocket the server is already
+/// serving the request, and starting a second one from inside it would make
+/// the server's own future recursive.
+pub(crate) async fn run_suite(
+    app: &AppHandle,
+    http_server: SelfTestCheck,
+) -> Result<SelfTestReport, String> {
+    log::trace!("Running self-test suite");
+
+    let work_dir = app.app_data_dir()?.join(SELF_TEST_DIR_NAME);
+    let _ = std::fs::remove_dir_all(&work_dir);
+    std::fs::create_dir_all(&work_dir)
+        .map_err(|e| format!("Failed to create self-test directory: {e}"))?;
+
+    let fs_dir = work_dir.clone();
+    let mut checks = tauri::async_runtime::spawn_blocking(move || {
+        vec![
+            run_check("extract_zip", || check_extract_zip(&fs_dir)),
+            run_check("extract_tar_gz", || check_extract_tar_gz(&fs_dir)),
+            run_check("install", || check_install(&fs_dir)),
+            run_check("spawn", || check_spawn(&fs_dir)),
+            run_check("storage", || check_storage(&fs_dir)),
+        ]
+    })
+    .await
+    .map_err(|e| format!("Self-test task failed: {e}"))?;
+
+    checks.push(http_server);
+
+    let _ = std::fs::remove_dir_all(&work_dir);
+
+    let report = SelfTestReport {
+        passed: checks.iter().all(|c| c.passed),
+        checks,
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+    };
+    log::info!(
+        "Self-test finished: {}/{} checks passed",
+        report.checks.iter().filter(|c| c.passed).count(),
+        report.checks.len()
+    );
+    Ok(report)
+}
+
+/// Start the real HTTP server on an ephemeral localhost port, hit the auth
+/// endpoint with a fresh token, then shut it down
+async fn check_http_server(app: &AppHandle) -> SelfTestCheck {
+    let started = Instant::now();
+    let result = http_server_round_trip(app).await;
+    // run_check is sync; reuse it to build the result with our own timing
+    let mut check = run_check("http_server", || result);
+    check.duration_ms = started.elapsed().as_millis() as u64;
+    check
+}
+
+async fn http_server_round_trip(app: &AppHandle) -> Result<String, String> {
+    let token = crate::http_server::auth::generate_token();
+    let handle =
+        crate::http_server::server::start_server(app.clone(), 0, token.clone(), true, true).await?;
+    let port = handle.port;
+
+    let result = async {
+        let client = crate::http_client::client_builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+        let response = client
+            .get(format!("http://127.0.0.1:{port}/api/auth"))
+            .query(&[("token", token.as_str())])
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach server on port {port}: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!("Auth endpoint returned HTTP {}", response.status()));
+        }
+        Ok(format!("Served /api/auth on 127.0.0.1:{port}"))
+    }
+    .await;
+
+    let _ = handle.shutdown_tx.send(());
+    result
+}
@@ -0,0 +1,215 @@
+//! Self-test suite for triaging "it doesn't work on my machine" reports
+//!
+//! Exercises the critical install/extract/spawn/storage/server paths against
+//! fixtures bundled into the binary, without touching the network or the
+//! user's real CLI installs. Everything runs inside a scratch directory under
+//! app data that is removed afterwards.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+pub mod commands;
+
+/// Version/platform the fixture archives are laid out for
+/// (matches the gh release layout: gh_{version}_{platform}/bin/gh)
+const FIXTURE_VERSION: &str = "0.0.0";
+const FIXTURE_PLATFORM: &str = "self-test";
+
+/// Archives in the same format as the gh CLI release assets
+const FIXTURE_ZIP: &[u8] = include_bytes!("../../fixtures/self-test/gh_0.0.0_self-test.zip");
+const FIXTURE_TAR_GZ: &[u8] = include_bytes!("../../fixtures/self-test/gh_0.0.0_self-test.tar.gz");
+
+/// What the fixture binary prints when spawned
+const FIXTURE_OUTPUT: &str = "jean-self-test";
+
+/// Result of a single check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestCheck {
+    /// Check identifier (e.g. "extract_zip")
+    pub name: String,
+    pub passed: bool,
+    pub duration_ms: u64,
+    /// What was verified (on success)
+    pub detail: Option<String>,
+    /// Why the check failed
+    pub error: Option<String>,
+}
+
+/// Result of the whole suite
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    /// True if every check passed
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+}
+
+/// Run a check, timing it and converting its result into a `SelfTestCheck`
+pub(crate) fn run_check<F>(name: &str, check: F) -> SelfTestCheck
+where
+    F: FnOnce() -> Result<String, String>,
+{
+    let started = Instant::now();
+    let result = check();
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(detail) => {
+            log::trace!("Self-test {name} passed: {detail}");
+            SelfTestCheck {
+                name: name.to_string(),
+                passed: true,
+                duration_ms,
+                detail: Some(detail),
+                error: None,
+            }
+        }
+        Err(error) => {
+            log::warn!("Self-test {name} failed: {error}");
+            SelfTestCheck {
+                name: name.to_string(),
+                passed: false,
+                duration_ms,
+                detail: None,
+                error: Some(error),
+            }
+        }
+    }
+}
+
+/// Extract the bundled zip fixture with the gh installer's zip extractor
+pub(crate) fn check_extract_zip(work_dir: &Path) -> Result<String, String> {
+    let target = work_dir.join("zip");
+    std::fs::create_dir_all(&target).map_err(|e| format!("Failed to create {target:?}: {e}"))?;
+    let binary =
+        crate::gh_cli::extract_zip(FIXTURE_ZIP, &target, FIXTURE_VERSION, FIXTURE_PLATFORM)?;
+    Ok(format!("Extracted {}", binary.display()))
+}
+
+/// Extract the bundled tar.gz fixture with the gh installer's tar extractor
+pub(crate) fn check_extract_tar_gz(work_dir: &Path) -> Result<String, String> {
+    let target = work_dir.join("tar");
+    std::fs::create_dir_all(&target).map_err(|e| format!("Failed to create {target:?}: {e}"))?;
+    let binary =
+        crate::gh_cli::extract_tar_gz(FIXTURE_TAR_GZ, &target, FIXTURE_VERSION, FIXTURE_PLATFORM)?;
+    Ok(format!("Extracted {}", binary.display()))
+}
+
+/// Where the fixture binary is installed inside the scratch directory
+fn installed_fixture_path(work_dir: &Path) -> PathBuf {
+    work_dir.join("bin").join("self-test")
+}
+
+/// Install the binary extracted by `check_extract_zip` the way CLI installs do
+/// (copy into place, mark executable)
+pub(crate) fn check_install(work_dir: &Path) -> Result<String, String> {
+    let extracted = work_dir
+        .join("zip")
+        .join(format!("gh_{FIXTURE_VERSION}_{FIXTURE_PLATFORM}"))
+        .join("bin")
+        .join("gh");
+    let binary = installed_fixture_path(work_dir);
+    if let Some(bin_dir) = binary.parent() {
+        std::fs::create_dir_all(bin_dir)
+            .map_err(|e| format!("Failed to create {bin_dir:?}: {e}"))?;
+    }
+
+    std::fs::copy(&extracted, &binary).map_err(|e| format!("Failed to copy binary: {e}"))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to set binary permissions: {e}"))?;
+    }
+
+    Ok(format!("Installed {}", binary.display()))
+}
+
+/// Spawn a dummy process via `silent_command` and check its output
+///
+/// On Unix this runs the fixture script installed by `check_install`; Windows
+/// can't execute it, so it spawns `cmd /C echo` instead.
+pub(crate) fn check_spawn(work_dir: &Path) -> Result<String, String> {
+    #[cfg(unix)]
+    let mut command = crate::platform::silent_command(installed_fixture_path(work_dir));
+    #[cfg(windows)]
+    let mut command = {
+        let _ = work_dir;
+        let mut command = crate::platform::silent_command("cmd");
+        command.args(["/C", "echo", FIXTURE_OUTPUT]);
+        command
+    };
+
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to spawn process: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    if !output.status.success() {
+        return Err(format!(
+            "Process exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    if stdout != FIXTURE_OUTPUT {
+        return Err(format!("Unexpected output: {stdout:?}"));
+    }
+    Ok(format!("Spawned process printed {stdout:?}"))
+}
+
+/// Write and read back a JSON document with the same atomic temp-file + rename
+/// pattern the session storage uses
+pub(crate) fn check_storage(work_dir: &Path) -> Result<String, String> {
+    let path = work_dir.join("storage.json");
+    let temp_path = path.with_extension("tmp");
+    let value = serde_json::json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "unicode": "✓ ünïcødé",
+    });
+
+    let json =
+        serde_json::to_string_pretty(&value).map_err(|e| format!("Failed to serialize: {e}"))?;
+    std::fs::write(&temp_path, &json).map_err(|e| format!("Failed to write: {e}"))?;
+    std::fs::rename(&temp_path, &path).map_err(|e| format!("Failed to rename: {e}"))?;
+
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read: {e}"))?;
+    let read_back: serde_json::Value =
+        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse: {e}"))?;
+    if read_back != value {
+        return Err("Read back different data than was written".to_string());
+    }
+    Ok(format!("Round-tripped {} bytes", json.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_checks_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, result) in [
+            ("extract_zip", check_extract_zip(dir.path())),
+            ("extract_tar_gz", check_extract_tar_gz(dir.path())),
+            ("install", check_install(dir.path())),
+            ("spawn", check_spawn(dir.path())),
+            ("storage", check_storage(dir.path())),
+        ] {
+            assert!(result.is_ok(), "{name} failed: {result:?}");
+        }
+    }
+
+    #[test]
+    fn test_run_check_records_failure() {
+        let check = run_check("failing", || Err("boom".to_string()));
+        assert!(!check.passed);
+        assert_eq!(check.error.as_deref(), Some("boom"));
+        assert!(check.detail.is_none());
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use tauri::AppHandle;
+
+use super::embeddings::EmbeddingsConfig;
+use super::{IndexStats, SearchHit};
+use crate::projects::storage::load_projects_data;
+
+/// Directory of a project, or of one of its worktrees
+fn resolve_root(
+    app: &AppHandle,
+    project_id: &str,
+    worktree_id: Option<String>,
+) -> Result<PathBuf, String> {
+    let data = load_projects_data(app)?;
+    let project = data
+        .find_project(project_id)
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    let path = match worktree_id {
+        Some(worktree_id) => data
+            .find_worktree(&worktree_id)
+            .filter(|w| w.project_id == project_id)
+            .map(|w| w.path.clone())
+            .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?,
+        None if project.is_folder => return Err("Folders have no files to search".to_string()),
+        None => project.path.clone(),
+    };
+    Ok(PathBuf::from(path))
+}
+
+async fn embeddings_config(app: &AppHandle) -> Result<EmbeddingsConfig, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    EmbeddingsConfig::from_preferences(&prefs)
+        .ok_or_else(|| "Semantic search is disabled in Settings".to_string())
+}
+
+/// Chunks of a project's files most similar to `query`. A project without
+/// an index starts indexing and reports that instead.
+#[tauri::command]
+pub async fn semantic_search(
+    app: AppHandle,
+    project_id: String,
+    worktree_id: Option<String>,
+    query: String,
+    k: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("Query is empty".to_string());
+    }
+    let config = embeddings_config(&app).await?;
+    let root = resolve_root(&app, &project_id, worktree_id)?;
+    if !super::is_indexed(&app, &root) {
+        super::refresh_in_background(app.clone(), root);
+        return Err("This project is being indexed for semantic search, try again shortly".into());
+    }
+    super::search(
+        &app,
+        &root,
+        &config,
+        query,
+        k.unwrap_or(super::DEFAULT_RESULTS),
+    )
+    .await
+}
+
+/// Build or update a project's semantic index now
+#[tauri::command]
+pub async fn rebuild_semantic_index(
+    app: AppHandle,
+    project_id: String,
+    worktree_id: Option<String>,
+) -> Result<IndexStats, String> {
+    let config = embeddings_config(&app).await?;
+    let root = resolve_root(&app, &project_id, worktree_id)?;
+    super::refresh(&app, &root, &config).await
+}
@@ -0,0 +1,124 @@
+//! OpenAI-compatible embeddings client
+//!
+//! Works with hosted providers and with local servers exposing the same
+//! endpoint (e.g. Ollama's `/v1/embeddings`), so embeddings can stay on the
+//! machine without bundling a model runtime.
+
+use serde_json::Value;
+
+use crate::AppPreferences;
+
+/// Inputs sent per request
+const BATCH_SIZE: usize = 64;
+
+/// Where and how to embed text
+#[derive(Debug, Clone)]
+pub struct EmbeddingsConfig {
+    pub api_url: String,
+    pub model: String,
+    pub api_key: Option<String>,
+}
+
+impl EmbeddingsConfig {
+    /// The configured endpoint, or None if semantic search is disabled
+    pub fn from_preferences(prefs: &AppPreferences) -> Option<Self> {
+        prefs.semantic_search_enabled.then(|| Self {
+            api_url: prefs.embeddings_api_url.clone(),
+            model: prefs.embeddings_api_model.clone(),
+            api_key: prefs.embeddings_api_key.clone(),
+        })
+    }
+}
+
+/// Embed `inputs`, one vector per input in the same order
+pub async fn embed(config: &EmbeddingsConfig, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let client = crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+    let mut vectors = Vec::with_capacity(inputs.len());
+    for batch in inputs.chunks(BATCH_SIZE) {
+        let mut request = client.post(&config.api_url).json(&serde_json::json!({
+            "model": config.model,
+            "input": batch,
+        }));
+        if let Some(key) = config.api_key.as_deref().filter(|k| !k.is_empty()) {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach embeddings API: {e}"))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read embeddings response: {e}"))?;
+        if !status.is_success() {
+            return Err(format!("Embeddings API returned HTTP {status}: {text}"));
+        }
+        vectors.extend(parse_response(&text, batch.len())?);
+    }
+    Ok(vectors)
+}
+
+/// Vectors of an embeddings response, ordered by their `index`
+fn parse_response(json: &str, expected: usize) -> Result<Vec<Vec<f32>>, String> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| format!("Failed to parse embeddings response: {e}"))?;
+    let data = value
+        .get("data")
+        .and_then(|v| v.as_array())
+        .ok_or("Embeddings response has no data")?;
+
+    let mut entries: Vec<(u64, Vec<f32>)> = data
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let index = entry
+                .get("index")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(i as u64);
+            let vector = entry
+                .get("embedding")
+                .and_then(|v| v.as_array())
+                .ok_or("Embeddings response entry has no embedding")?
+                .iter()
+                .map(|x| x.as_f64().map(|x| x as f32))
+                .collect::<Option<Vec<f32>>>()
+                .ok_or("Embedding contains a non-number")?;
+            Ok((index, vector))
+        })
+        .collect::<Result<_, String>>()?;
+
+    if entries.len() != expected {
+        return Err(format!(
+            "Embeddings API returned {} vectors for {expected} inputs",
+            entries.len()
+        ));
+    }
+    entries.sort_by_key(|(index, _)| *index);
+    Ok(entries.into_iter().map(|(_, vector)| vector).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let json = r#"{"data": [
+            {"index": 1, "embedding": [0.5, 1]},
+            {"index": 0, "embedding": [-1.0, 0.25]}
+        ], "model": "nomic-embed-text"}"#;
+        assert_eq!(
+            parse_response(json, 2).unwrap(),
+            [vec![-1.0, 0.25], vec![0.5, 1.0]]
+        );
+        assert!(parse_response(json, 3).unwrap_err().contains("2 vectors"));
+        assert!(parse_response(r#"{"error": "no"}"#, 1).is_err());
+        assert!(parse_response(r#"{"data": [{"embedding": ["x"]}]}"#, 1).is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! MCP server exposing semantic search to agents
+//!
+//! The Jean binary doubles as a stdio MCP server when started as
+//! `jean mcp-semantic-search --data-dir <app data> --root <worktree>`; chat
+//! sessions add it to the CLI's `--mcp-config` (see `with_search_server`).
+//! It speaks newline-delimited JSON-RPC and offers two tools:
+//! `semantic_search`, answered from the worktree's index, and
+//! `search_lessons`, answered from the knowledge base.
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use super::embeddings::EmbeddingsConfig;
+use crate::runtime::PathProvider;
+use crate::AppPreferences;
+
+/// Name of the server in MCP configs (tools show up as `mcp__jean-search__*`)
+pub const SERVER_NAME: &str = "jean-search";
+
+/// First CLI argument that starts the server instead of the app
+pub const SUBCOMMAND: &str = "mcp-semantic-search";
+
+/// Protocol version answered when the client doesn't name one
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Most results one call can ask for
+const MAX_RESULTS: usize = 20;
+
+/// A tool invocation with its arguments checked
+struct ToolCall<'a> {
+    tool: &'a str,
+    query: &'a str,
+    k: usize,
+    tags: Vec<String>,
+}
+
+/// App data directory passed on the command line (there's no AppHandle)
+struct DataDir(PathBuf);
+
+impl PathProvider for DataDir {
+    fn app_data_dir(&self) -> Result<PathBuf, String> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Value following `flag` in `args`
+fn arg(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+/// Serve MCP on stdin/stdout until stdin closes; returns the exit code
+pub fn run(args: &[String]) -> i32 {
+    let (Some(data_dir), Some(root)) = (arg(args, "--data-dir"), arg(args, "--root")) else {
+        eprintln!("Usage: jean {SUBCOMMAND} --data-dir <dir> --root <dir>");
+        return 2;
+    };
+    let app = DataDir(PathBuf::from(data_dir));
+    let root = PathBuf::from(root);
+    let config = std::fs::read_to_string(app.0.join("preferences.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<AppPreferences>(&content).ok())
+        .and_then(|prefs| EmbeddingsConfig::from_preferences(&prefs));
+
+    let call = |call: &ToolCall| -> Result<String, String> {
+        if call.tool == "search_lessons" {
+            let search =
+                crate::knowledge::search(&app, call.query, &call.tags, call.k, config.as_ref());
+            return tauri::async_runtime::block_on(search)
+                .map(|hits| crate::knowledge::format_hits(&hits));
+        }
+        let config = config
+            .as_ref()
+            .ok_or("Semantic search is disabled in Jean's settings")?;
+        tauri::async_runtime::block_on(super::search(&app, &root, config, call.query, call.k))
+            .map(|hits| super::format_hits(&hits))
+    };
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => handle(&request, &call),
+            Err(e) => Some(error(Value::Null, -32700, &format!("Parse error: {e}"))),
+        };
+        if let Some(response) = response {
+            if writeln!(stdout, "{response}")
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+    0
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn result(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+/// The response to one JSON-RPC message (None for notifications)
+fn handle(request: &Value, call: &impl Fn(&ToolCall) -> Result<String, String>) -> Option<Value> {
+    let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("");
+    let id = request.get("id").cloned()?;
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    Some(match method {
+        "initialize" => result(
+            id,
+            json!({
+                "protocolVersion": params
+                    .get("protocolVersion")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(PROTOCOL_VERSION),
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+            }),
+        ),
+        "ping" => result(id, json!({})),
+        "tools/list" => result(
+            id,
+            json!({
+                "tools": [{
+                    "name": "semantic_search",
+                    "description": "Find code in this repository by meaning rather than exact text. \
+                        Returns the most similar file sections with their line ranges.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "query": {
+                                "type": "string",
+                                "description": "What to look for, e.g. \"where retries are scheduled\"",
+                            },
+                            "k": {
+                                "type": "integer",
+                                "description": "Number of results (default 8)",
+                            },
+                        },
+                        "required": ["query"],
+                    },
+                }, {
+                    "name": "search_lessons",
+                    "description": "Search lessons learned in earlier sessions (fixes, migrations, \
+                        patterns) before solving something that may have been solved before. \
+                        Each result names the session it came from.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "query": {
+                                "type": "string",
+                                "description": "The task or problem, e.g. \"migrate settings to sqlite\"",
+                            },
+                            "tags": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "description": "Only lessons with all of these tags",
+                            },
+                            "k": {
+                                "type": "integer",
+                                "description": "Number of results (default 5)",
+                            },
+                        },
+                        "required": ["query"],
+                    },
+                }],
+            }),
+        ),
+        "tools/call" => {
+            let name = params.get("name").and_then(|n| n.as_str()).unwrap_or("");
+            let default_results = match name {
+                "semantic_search" => super::DEFAULT_RESULTS,
+                "search_lessons" => crate::knowledge::DEFAULT_RESULTS,
+                _ => return Some(error(id, -32602, &format!("Unknown tool: {name}"))),
+            };
+            let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+            let Some(query) = arguments.get("query").and_then(|q| q.as_str()) else {
+                return Some(error(id, -32602, "Missing query"));
+            };
+            let k = arguments
+                .get("k")
+                .and_then(|k| k.as_u64())
+                .map_or(default_results, |k| k as usize)
+                .clamp(1, MAX_RESULTS);
+            let tags = arguments
+                .get("tags")
+                .and_then(|t| t.as_array())
+                .map(|tags| {
+                    tags.iter()
+                        .filter_map(|t| t.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let (text, is_error) = match call(&ToolCall {
+                tool: name,
+                query,
+                k,
+                tags,
+            }) {
+                Ok(text) => (text, false),
+                Err(e) => (e, true),
+            };
+            result(
+                id,
+                json!({ "content": [{ "type": "text", "text": text }], "isError": is_error }),
+            )
+        }
+        other => error(id, -32601, &format!("Method not found: {other}")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(call: &ToolCall) -> Result<String, String> {
+        if call.query == "fail" {
+            return Err("Embeddings API returned HTTP 500".to_string());
+        }
+        Ok(format!(
+            "{} {} hits for {} {:?}",
+            call.tool, call.k, call.query, call.tags
+        ))
+    }
+
+    #[test]
+    fn test_handle() {
+        let init = handle(
+            &json!({"jsonrpc": "2.0", "id": 1, "method": "initialize",
+                    "params": {"protocolVersion": "2025-03-26"}}),
+            &search,
+        )
+        .unwrap();
+        assert_eq!(init["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(init["result"]["serverInfo"]["name"], SERVER_NAME);
+
+        assert!(handle(
+            &json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            &search
+        )
+        .is_none());
+
+        let tools = handle(
+            &json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+            &search,
+        )
+        .unwrap();
+        assert_eq!(tools["result"]["tools"][0]["name"], "semantic_search");
+        assert_eq!(tools["result"]["tools"][1]["name"], "search_lessons");
+
+        let call = |name: &str, arguments: Value| {
+            handle(
+                &json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+                        "params": {"name": name, "arguments": arguments}}),
+                &search,
+            )
+            .unwrap()
+        };
+        let hits = call("semantic_search", json!({"query": "retries", "k": 100}));
+        assert_eq!(
+            hits["result"]["content"][0]["text"],
+            "semantic_search 20 hits for retries []"
+        );
+        assert_eq!(hits["result"]["isError"], false);
+        let lessons = call(
+            "search_lessons",
+            json!({"query": "migration", "tags": ["sqlite"]}),
+        );
+        assert_eq!(
+            lessons["result"]["content"][0]["text"],
+            "search_lessons 5 hits for migration [\"sqlite\"]"
+        );
+        assert_eq!(
+            call("semantic_search", json!({"query": "fail"}))["result"]["isError"],
+            true
+        );
+        assert_eq!(call("semantic_search", json!({}))["error"]["code"], -32602);
+        assert_eq!(
+            call("delete_files", json!({"query": "x"}))["error"]["code"],
+            -32602
+        );
+
+        let unknown = handle(
+            &json!({"jsonrpc": "2.0", "id": 4, "method": "resources/list"}),
+            &search,
+        )
+        .unwrap();
+        assert_eq!(unknown["error"]["code"], -32601);
+    }
+
+    #[test]
+    fn test_arg() {
+        let args: Vec<String> = ["--data-dir", "/data", "--root"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert_eq!(arg(&args, "--data-dir").as_deref(), Some("/data"));
+        assert_eq!(arg(&args, "--root"), None);
+    }
+}
@@ -0,0 +1,665 @@
+//! Semantic code search over a project's files
+//!
+//! Optional (`semantic_search_enabled`): source files are split into
+//! overlapping line windows, each embedded through an OpenAI-compatible
+//! endpoint (a local Ollama by default, see [`embeddings`]). The index is
+//! cached per directory in `{app_data}/semantic-index/{uuid-v5 of path}.json`
+//! with vectors stored normalized, so a search is one query embedding and a
+//! dot product per chunk. Snippets are read back from disk, not stored.
+//!
+//! Like repository maps, indexes are built when a project is added and kept
+//! current by the git status poller (or the maintenance scheduler, when a
+//! maintenance window is set); only files whose size or modification
+//! time changed are embedded again. Agents query the index (and the lessons
+//! in [`crate::knowledge`]) through the MCP server in [`mcp`].
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::runtime::PathProvider;
+use embeddings::EmbeddingsConfig;
+
+pub mod commands;
+pub mod embeddings;
+pub mod mcp;
+
+/// Index directory (within app data)
+const INDEX_DIR: &str = "semantic-index";
+
+/// Indexed file types
+const EXTENSIONS: &[&str] = &[
+    "rs", "ts", "tsx", "js", "jsx", "mjs", "cjs", "py", "go", "rb", "java", "kt", "swift", "c",
+    "h", "cc", "cpp", "hpp", "cs", "php", "scala", "sh", "sql", "vue", "svelte", "md",
+];
+
+/// Larger files are skipped (usually generated)
+const MAX_FILE_BYTES: u64 = 256 * 1024;
+
+/// Chunks per index, bounding embedding cost
+const MAX_CHUNKS: usize = 20_000;
+
+/// Lines per chunk, and lines shared with the previous chunk
+const CHUNK_LINES: usize = 40;
+const CHUNK_OVERLAP: usize = 8;
+
+/// Characters of a chunk sent for embedding
+const MAX_CHUNK_CHARS: usize = 4000;
+
+/// Seconds before the poller refreshes an index again (embedding costs more
+/// than mapping, so this is longer than for repository maps)
+const REFRESH_AFTER_SECS: u64 = 300;
+
+/// Results returned when the caller doesn't say
+pub const DEFAULT_RESULTS: usize = 8;
+
+/// Roots being indexed, so polls don't start duplicate runs
+static INDEXING: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Vectors as base64 little-endian f32s, about a third the size of JSON numbers
+pub(crate) mod vector_base64 {
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const ENGINE: base64::engine::GeneralPurpose = base64::engine::general_purpose::STANDARD;
+
+    pub fn serialize<S: Serializer>(vector: &[f32], serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes: Vec<u8> = vector.iter().flat_map(|x| x.to_le_bytes()).collect();
+        serializer.serialize_str(&ENGINE.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let bytes = ENGINE.decode(encoded).map_err(serde::de::Error::custom)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Chunk {
+    /// 1-based, inclusive
+    start_line: usize,
+    end_line: usize,
+    #[serde(with = "vector_base64")]
+    vector: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    /// Relative to the index's root, with `/` separators
+    path: String,
+    size: u64,
+    modified: u64,
+    chunks: Vec<Chunk>,
+}
+
+/// A directory's embeddings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticIndex {
+    pub root: String,
+    /// Embedding model; a different model means a full rebuild
+    pub model: String,
+    /// Unix seconds
+    pub generated_at: u64,
+    files: Vec<IndexedFile>,
+}
+
+/// Size of an index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexStats {
+    pub files: usize,
+    pub chunks: usize,
+    /// Chunks embedded by this refresh (the rest were reused)
+    pub embedded: usize,
+    pub generated_at: u64,
+}
+
+/// A matching chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Cosine similarity to the query
+    pub score: f32,
+    pub snippet: String,
+}
+
+/// A chunk waiting to be embedded
+struct PendingChunk {
+    file: usize,
+    start_line: usize,
+    end_line: usize,
+    text: String,
+}
+
+/// Overlapping windows of `source` as (start line, end line, text)
+fn chunk_lines(source: &str) -> Vec<(usize, usize, String)> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let end = (start + CHUNK_LINES).min(lines.len());
+        let text = lines[start..end].join("\n");
+        if !text.trim().is_empty() {
+            let text: String = text.chars().take(MAX_CHUNK_CHARS).collect();
+            chunks.push((start + 1, end, text));
+        }
+        if end == lines.len() {
+            break;
+        }
+        start = end - CHUNK_OVERLAP;
+    }
+    chunks
+}
+
+pub(crate) fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+    vector
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Files of `root` with chunks reused from `previous` where unchanged, and
+/// the chunks still to embed
+fn plan(
+    root: &Path,
+    previous: Option<&SemanticIndex>,
+    model: &str,
+) -> (Vec<IndexedFile>, Vec<PendingChunk>) {
+    let known: HashMap<&str, &IndexedFile> = previous
+        .filter(|index| index.model == model)
+        .map(|index| index.files.iter().map(|f| (f.path.as_str(), f)).collect())
+        .unwrap_or_default();
+
+    let walker = WalkBuilder::new(root)
+        .hidden(true)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .require_git(false)
+        .build();
+
+    let mut files = Vec::new();
+    let mut pending = Vec::new();
+    let mut total_chunks = 0;
+    for entry in walker.flatten() {
+        let path = entry.path();
+        let indexed = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| EXTENSIONS.contains(&e));
+        if !indexed {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() || metadata.len() > MAX_FILE_BYTES {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let size = metadata.len();
+        let modified = modified_secs(&metadata);
+
+        if let Some(file) = known
+            .get(relative.as_str())
+            .filter(|f| f.size == size && f.modified == modified)
+        {
+            if total_chunks + file.chunks.len() > MAX_CHUNKS {
+                break;
+            }
+            total_chunks += file.chunks.len();
+            files.push((*file).clone());
+            continue;
+        }
+
+        let Ok(source) = fs::read_to_string(path) else {
+            continue;
+        };
+        let chunks = chunk_lines(&source);
+        if total_chunks + chunks.len() > MAX_CHUNKS {
+            log::warn!("Semantic index of {root:?} stopped at {MAX_CHUNKS} chunks");
+            break;
+        }
+        total_chunks += chunks.len();
+        for (start_line, end_line, text) in chunks {
+            pending.push(PendingChunk {
+                file: files.len(),
+                start_line,
+                end_line,
+                text,
+            });
+        }
+        files.push(IndexedFile {
+            path: relative,
+            size,
+            modified,
+            chunks: Vec::new(),
+        });
+    }
+    (files, pending)
+}
+
+fn index_path(app: &impl PathProvider, root: &Path) -> Result<PathBuf, String> {
+    let key = Uuid::new_v5(&Uuid::NAMESPACE_URL, root.to_string_lossy().as_bytes());
+    Ok(app
+        .app_data_dir()?
+        .join(INDEX_DIR)
+        .join(format!("{key}.json")))
+}
+
+/// Whether `root` has a cached index
+pub fn is_indexed(app: &impl PathProvider, root: &Path) -> bool {
+    index_path(app, root).is_ok_and(|path| path.exists())
+}
+
+/// The cached index of `root`, if any
+pub fn load(app: &impl PathProvider, root: &Path) -> Option<SemanticIndex> {
+    let content = fs::read_to_string(index_path(app, root).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save(app: &impl PathProvider, index: &SemanticIndex) -> Result<(), String> {
+    let path = index_path(app, Path::new(&index.root))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create semantic index directory: {e}"))?;
+    }
+    let content = serde_json::to_string(index)
+        .map_err(|e| format!("Failed to serialize semantic index: {e}"))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to save semantic index: {e}"))
+}
+
+/// Bring the cached index of `root` up to date
+pub async fn refresh(
+    app: &impl PathProvider,
+    root: &Path,
+    config: &EmbeddingsConfig,
+) -> Result<IndexStats, String> {
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()));
+    }
+    let previous = load(app, root);
+    let (mut files, pending) = plan(root, previous.as_ref(), &config.model);
+
+    let texts: Vec<String> = pending.iter().map(|c| c.text.clone()).collect();
+    let vectors = if texts.is_empty() {
+        Vec::new()
+    } else {
+        embeddings::embed(config, &texts).await?
+    };
+    let embedded = vectors.len();
+    for (chunk, vector) in pending.into_iter().zip(vectors) {
+        files[chunk.file].chunks.push(Chunk {
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            vector: normalize(vector),
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let index = SemanticIndex {
+        root: root.to_string_lossy().into_owned(),
+        model: config.model.clone(),
+        generated_at: crate::runtime::now(),
+        files,
+    };
+    save(app, &index)?;
+    log::trace!("Indexed {root:?} for semantic search ({embedded} chunks embedded)");
+    Ok(IndexStats {
+        files: index.files.len(),
+        chunks: index.files.iter().map(|f| f.chunks.len()).sum(),
+        embedded,
+        generated_at: index.generated_at,
+    })
+}
+
+/// The `k` chunks of `index` closest to a normalized query vector
+fn rank(index: &SemanticIndex, query: &[f32], k: usize) -> Vec<(usize, usize, f32)> {
+    let mut scored: Vec<(usize, usize, f32)> = index
+        .files
+        .iter()
+        .enumerate()
+        .flat_map(|(f, file)| {
+            file.chunks.iter().enumerate().map(move |(c, chunk)| {
+                let score = chunk.vector.iter().zip(query).map(|(a, b)| a * b).sum();
+                (f, c, score)
+            })
+        })
+        .collect();
+    scored.sort_by(|a, b| b.2.total_cmp(&a.2));
+    scored.truncate(k);
+    scored
+}
+
+/// Chunks of `root`'s index most similar to `query`
+pub async fn search(
+    app: &impl PathProvider,
+    root: &Path,
+    config: &EmbeddingsConfig,
+    query: &str,
+    k: usize,
+) -> Result<Vec<SearchHit>, String> {
+    let index = load(app, root).ok_or("This project hasn't been indexed for semantic search")?;
+    if index.model != config.model {
+        return Err("The semantic index was built with another model, rebuild it first".into());
+    }
+    let query_vector = embeddings::embed(config, &[query.to_string()])
+        .await?
+        .pop()
+        .map(normalize)
+        .ok_or("Embeddings API returned no vector for the query")?;
+
+    Ok(rank(&index, &query_vector, k)
+        .into_iter()
+        .map(|(f, c, score)| {
+            let file = &index.files[f];
+            let chunk = &file.chunks[c];
+            let snippet = fs::read_to_string(root.join(&file.path))
+                .map(|source| {
+                    source
+                        .lines()
+                        .skip(chunk.start_line - 1)
+                        .take(chunk.end_line + 1 - chunk.start_line)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_default();
+            SearchHit {
+                path: file.path.clone(),
+                start_line: chunk.start_line,
+                end_line: chunk.end_line,
+                score,
+                snippet,
+            }
+        })
+        .collect())
+}
+
+/// Hits as plain text, for agents
+pub fn format_hits(hits: &[SearchHit]) -> String {
+    if hits.is_empty() {
+        return "No matches.".to_string();
+    }
+    hits.iter()
+        .map(|hit| {
+            format!(
+                "{}:{}-{} (score {:.2})\n{}\n",
+                hit.path, hit.start_line, hit.end_line, hit.score, hit.snippet
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Refresh the index of `root` in the background if semantic search is
+/// enabled, unless a refresh is already running
+pub fn refresh_in_background(app: AppHandle, root: PathBuf) {
+    if !INDEXING.lock().unwrap().insert(root.clone()) {
+        return;
+    }
+    crate::background_tasks::supervisor::spawn_task("semantic-index", async move {
+        let config = crate::load_preferences(app.clone())
+            .await
+            .ok()
+            .and_then(|prefs| EmbeddingsConfig::from_preferences(&prefs));
+        if let Some(config) = config {
+            if let Err(e) = refresh(&app, &root, &config).await {
+                log::warn!("Failed to refresh semantic index: {e}");
+            }
+        }
+        INDEXING.lock().unwrap().remove(&root);
+    });
+}
+
+/// Refresh the index of `root` if one exists and is getting old (for pollers)
+///
+/// Deferred outside the maintenance window, when one is set.
+pub fn refresh_if_stale(app: AppHandle, root: PathBuf) {
+    // The index file's mtime, so polls don't parse every vector
+    let stale = index_path(&app, &root)
+        .ok()
+        .and_then(|path| fs::metadata(path).ok())
+        .and_then(|metadata| metadata.modified().ok())
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age >= Duration::from_secs(REFRESH_AFTER_SECS));
+    if stale && crate::background_tasks::maintenance::heavy_work_allowed(&app) {
+        refresh_in_background(app, root);
+    }
+}
+
+/// Refresh every cached index of a project or worktree
+///
+/// Returns how many were refreshed and the errors of the rest. Roots already
+/// being indexed are skipped.
+pub async fn refresh_all(app: &AppHandle, config: &EmbeddingsConfig) -> (usize, Vec<String>) {
+    let data = match crate::projects::storage::load_projects_data(app) {
+        Ok(data) => data,
+        Err(e) => return (0, vec![e]),
+    };
+    let roots: Vec<PathBuf> = data
+        .projects
+        .iter()
+        .filter(|p| !p.is_folder)
+        .map(|p| PathBuf::from(&p.path))
+        .chain(data.worktrees.iter().map(|w| PathBuf::from(&w.path)))
+        .filter(|root| is_indexed(app, root))
+        .collect();
+
+    let mut refreshed = 0;
+    let mut errors = Vec::new();
+    for root in roots {
+        if !INDEXING.lock().unwrap().insert(root.clone()) {
+            continue;
+        }
+        match refresh(app, &root, config).await {
+            Ok(_) => refreshed += 1,
+            Err(e) => errors.push(format!("{}: {e}", root.display())),
+        }
+        INDEXING.lock().unwrap().remove(&root);
+    }
+    (refreshed, errors)
+}
+
+/// `mcp_config` with the search MCP server added, if there's something to
+/// search: an index of `worktree_path` (with semantic search enabled) or
+/// saved lessons
+pub async fn with_search_server(
+    app: &AppHandle,
+    worktree_path: &str,
+    mcp_config: Option<String>,
+) -> Option<String> {
+    let enabled = crate::load_preferences(app.clone())
+        .await
+        .is_ok_and(|prefs| prefs.semantic_search_enabled);
+    let indexed = enabled && is_indexed(app, Path::new(worktree_path));
+    if !indexed && !crate::knowledge::has_lessons(app) {
+        return mcp_config;
+    }
+    let (Ok(exe), Ok(data_dir)) = (std::env::current_exe(), app.app_data_dir()) else {
+        return mcp_config;
+    };
+
+    let mut config: serde_json::Value = match mcp_config.as_deref().filter(|c| !c.is_empty()) {
+        Some(config) => match serde_json::from_str(config) {
+            Ok(config) => config,
+            Err(_) => return mcp_config,
+        },
+        None => serde_json::json!({}),
+    };
+    let Some(servers) = config
+        .as_object_mut()
+        .map(|c| {
+            c.entry("mcpServers")
+                .or_insert_with(|| serde_json::json!({}))
+        })
+        .and_then(|s| s.as_object_mut())
+    else {
+        return mcp_config;
+    };
+    servers.insert(
+        mcp::SERVER_NAME.to_string(),
+        serde_json::json!({
+            "command": exe,
+            "args": [
+                mcp::SUBCOMMAND,
+                "--data-dir",
+                data_dir,
+                "--root",
+                worktree_path,
+            ],
+        }),
+    );
+    Some(config.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture_server::{FixtureResponse, FixtureServer};
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_chunk_lines() {
+        let source: String = (1..=90).map(|i| format!("line {i}\n")).collect();
+        let chunks = chunk_lines(&source);
+        let ranges: Vec<(usize, usize)> = chunks.iter().map(|(s, e, _)| (*s, *e)).collect();
+        assert_eq!(ranges, [(1, 40), (33, 72), (65, 90)]);
+        assert!(chunks[1].2.starts_with("line 33\n"));
+        assert!(chunk_lines("\n\n  \n").is_empty());
+    }
+
+    #[test]
+    fn test_plan_reuses_unchanged_files() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        fs::write(root.join("a.rs"), "fn a() {}\n").unwrap();
+        fs::write(root.join("b.py"), "def b():\n    pass\n").unwrap();
+        fs::write(root.join("image.png"), "not text").unwrap();
+
+        let (files, pending) = plan(root, None, "model");
+        assert_eq!(files.len(), 2);
+        assert_eq!(pending.len(), 2);
+
+        let mut previous = SemanticIndex {
+            root: root.to_string_lossy().into_owned(),
+            model: "model".to_string(),
+            generated_at: 0,
+            files,
+        };
+        for file in &mut previous.files {
+            file.chunks.push(Chunk {
+                start_line: 1,
+                end_line: 1,
+                vector: vec![1.0],
+            });
+        }
+        fs::write(root.join("b.py"), "def b():\n    return 1\n").unwrap();
+        let (files, pending) = plan(root, Some(&previous), "model");
+        let reused = files.iter().find(|f| f.path == "a.rs").unwrap();
+        assert_eq!(reused.chunks.len(), 1);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(files[pending[0].file].path, "b.py");
+
+        // Another model invalidates everything
+        let (_, pending) = plan(root, Some(&previous), "other-model");
+        assert_eq!(pending.len(), 2);
+    }
+
+    #[test]
+    fn test_rank_and_vector_encoding() {
+        let chunk = |vector: Vec<f32>| Chunk {
+            start_line: 1,
+            end_line: 2,
+            vector: normalize(vector),
+        };
+        let index = SemanticIndex {
+            root: "/repo".to_string(),
+            model: "model".to_string(),
+            generated_at: 0,
+            files: vec![IndexedFile {
+                path: "a.rs".to_string(),
+                size: 0,
+                modified: 0,
+                chunks: vec![
+                    chunk(vec![0.0, 1.0]),
+                    chunk(vec![3.0, 0.1]),
+                    chunk(vec![-1.0, 0.0]),
+                ],
+            }],
+        };
+        let ranked = rank(&index, &normalize(vec![1.0, 0.0]), 2);
+        assert_eq!(ranked.iter().map(|r| r.1).collect::<Vec<_>>(), [1, 0]);
+        assert!(ranked[0].2 > 0.99);
+
+        let json = serde_json::to_string(&index).unwrap();
+        let decoded: SemanticIndex = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            decoded.files[0].chunks[1].vector,
+            index.files[0].chunks[1].vector
+        );
+    }
+
+    #[test]
+    fn test_refresh_and_search_against_fixture_server() {
+        let server = FixtureServer::start(vec![(
+            "/v1/embeddings".to_string(),
+            FixtureResponse::json(r#"{"data": [{"index": 0, "embedding": [0.6, 0.8]}]}"#),
+        )]);
+        let config = EmbeddingsConfig {
+            api_url: server.url("/v1/embeddings"),
+            model: "model".to_string(),
+            api_key: None,
+        };
+        let repo = tempfile::tempdir().unwrap();
+        fs::write(
+            repo.path().join("lib.rs"),
+            "pub fn answer() -> u32 {\n    42\n}\n",
+        )
+        .unwrap();
+        let paths = TempPaths::new();
+
+        tauri::async_runtime::block_on(async {
+            let stats = refresh(&paths, repo.path(), &config).await.unwrap();
+            assert_eq!((stats.files, stats.chunks, stats.embedded), (1, 1, 1));
+
+            // Nothing changed, nothing embedded
+            let stats = refresh(&paths, repo.path(), &config).await.unwrap();
+            assert_eq!(stats.embedded, 0);
+
+            let hits = search(&paths, repo.path(), &config, "answer", 5)
+                .await
+                .unwrap();
+            assert_eq!(hits.len(), 1);
+            assert_eq!((hits[0].start_line, hits[0].end_line), (1, 3));
+            assert!(hits[0].snippet.contains("42"));
+            assert!(format_hits(&hits).starts_with("lib.rs:1-3 (score 1.00)\n"));
+        });
+        assert_eq!(server.requests().len(), 2);
+    }
+}
@@ -0,0 +1,338 @@
+//! Tauri commands for speech-to-text and model management
+
+use std::io::Write;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::config::{
+    ensure_speech_models_dir, get_model_path, resolve_whisper_binary, validate_model_id,
+    WHISPER_MODELS, WHISPER_MODELS_BASE_URL,
+};
+use super::transcribe::{transcribe_api, transcribe_local, TranscriptionResult};
+use crate::http_server::EmitExt;
+use crate::power::monitor::with_wake_retry;
+
+/// Maximum accepted audio size (25MB, matches common transcription API limits)
+const MAX_AUDIO_SIZE: usize = 25 * 1024 * 1024;
+
+/// ggml model files start with this magic number ("ggml" as little-endian u32)
+const GGML_MAGIC: [u8; 4] = *b"lmgg";
+
+/// Information about a whisper model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechModelInfo {
+    /// Model ID (e.g., "base.en")
+    pub id: String,
+    /// Approximate download size in MB
+    pub size_mb: u32,
+    /// Whether the model has been downloaded
+    pub installed: bool,
+    /// Path to the model file (if installed)
+    pub path: Option<String>,
+}
+
+/// Status of the speech-to-text setup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechStatus {
+    /// Configured provider: "local" or "api"
+    pub provider: String,
+    /// Path to the whisper.cpp binary found on PATH (local provider)
+    pub whisper_binary: Option<String>,
+    /// Configured local model ID
+    pub local_model: String,
+    /// Whether the configured local model is downloaded
+    pub local_model_installed: bool,
+    /// Whether transcription can run with the current configuration
+    pub ready: bool,
+}
+
+/// Progress event for model downloads
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeechModelDownloadProgress {
+    /// Model being downloaded
+    pub model: String,
+    /// Current stage of the download
+    pub stage: String,
+    /// Progress message
+    pub message: String,
+    /// Percentage complete (0-100)
+    pub percent: u8,
+}
+
+/// Get the current speech-to-text configuration status
+#[tauri::command]
+pub async fn get_speech_status(app: AppHandle) -> Result<SpeechStatus, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let whisper_binary = resolve_whisper_binary();
+    let local_model_installed = get_model_path(&app, &prefs.speech_local_model)
+        .map(|p| p.exists())
+        .unwrap_or(false);
+
+    let ready = match prefs.speech_provider.as_str() {
+        "api" => !prefs.speech_api_url.trim().is_empty(),
+        _ => whisper_binary.is_some() && local_model_installed,
+    };
+
+    Ok(SpeechStatus {
+        provider: prefs.speech_provider,
+        whisper_binary: whisper_binary.map(|p| p.to_string_lossy().to_string()),
+        local_model: prefs.speech_local_model,
+        local_model_installed,
+        ready,
+    })
+}
+
+/// List known whisper models and whether each is downloaded
+#[tauri::command]
+pub async fn list_speech_models(app: AppHandle) -> Result<Vec<SpeechModelInfo>, String> {
+    WHISPER_MODELS
+        .iter()
+        .map(|(id, size_mb)| {
+            let path = get_model_path(&app, id)?;
+            let installed = path.exists();
+            Ok(SpeechModelInfo {
+                id: id.to_string(),
+                size_mb: *size_mb,
+                installed,
+                path: installed.then(|| path.to_string_lossy().to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Download a whisper model from the whisper.cpp model repository
+#[tauri::command]
+pub async fn download_speech_model(app: AppHandle, model: String) -> Result<(), String> {
+    log::trace!("Downloading speech model: {model}");
+    let _power_guard =
+        crate::power::PowerGuard::acquire(&app, format!("Speech model download ({model})")).await;
+    validate_model_id(&model)?;
+
+    // Restart the download if the system slept or the network changed mid-way
+    with_wake_retry(&app, &format!("Speech model {model}"), || {
+        download_speech_model_once(&app, &model)
+    })
+    .await
+}
+
+async fn download_speech_model_once(app: &AppHandle, model: &str) -> Result<(), String> {
+    let app = app.clone();
+    let model = model.to_string();
+
+    ensure_speech_models_dir(&app)?;
+    let model_path = get_model_path(&app, &model)?;
+    let temp_path = model_path.with_extension("bin.part");
+
+    emit_progress(&app, &model, "starting", "Preparing download...", 0);
+
+    let download_url = format!("{WHISPER_MODELS_BASE_URL}/ggml-{model}.bin");
+    log::trace!("Downloading from: {download_url}");
+    let download = crate::emergency::DownloadGuard::start();
+
+    let client = crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+    let mut response = client
+        .get(&download_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download speech model: {e}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to download speech model: HTTP {}",
+            response.status()
+        ));
+    }
+
+    let total_bytes = response.content_length();
+    let mut file = std::fs::File::create(&temp_path)
+        .map_err(|e| format!("Failed to create model file: {e}"))?;
+    let mut downloaded: u64 = 0;
+    let mut last_percent = 0u8;
+
+    // Stream to disk - models are hundreds of MB
+    loop {
+        let chunk = match response.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                let _ = std::fs::remove_file(&temp_path);
+                return Err(format!("Failed to download speech model: {e}"));
+            }
+        };
+        if let Err(e) = download.check() {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(e);
+        }
+        if let Err(e) = file.write_all(&chunk) {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(format!("Failed to write model file: {e}"));
+        }
+        downloaded += chunk.len() as u64;
+
+        if let Some(total) = total_bytes.filter(|t| *t > 0) {
+            // Downloading covers 0-90%, verification the rest
+            let percent = ((downloaded * 90) / total).min(90) as u8;
+            if percent > last_percent {
+                last_percent = percent;
+                emit_progress(
+                    &app,
+                    &model,
+                    "downloading",
+                    &format!(
+                        "Downloading {model} ({} / {} MB)...",
+                        downloaded / 1_000_000,
+                        total / 1_000_000
+                    ),
+                    percent,
+                );
+            }
+        }
+    }
+    drop(file);
+
+    emit_progress(&app, &model, "verifying", "Verifying model...", 95);
+
+    let mut magic = [0u8; 4];
+    let valid = std::fs::File::open(&temp_path)
+        .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut magic))
+        .is_ok()
+        && magic == GGML_MAGIC;
+    if !valid {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err("Downloaded file is not a valid whisper model".to_string());
+    }
+
+    std::fs::rename(&temp_path, &model_path)
+        .map_err(|e| format!("Failed to install model file: {e}"))?;
+
+    emit_progress(&app, &model, "complete", "Download complete!", 100);
+    log::trace!("Speech model {model} installed at {model_path:?}");
+    Ok(())
+}
+
+/// Delete a downloaded whisper model
+#[tauri::command]
+pub async fn delete_speech_model(app: AppHandle, model: String) -> Result<(), String> {
+    log::trace!("Deleting speech model: {model}");
+    let model_path = get_model_path(&app, &model)?;
+    if model_path.exists() {
+        std::fs::remove_file(&model_path)
+            .map_err(|e| format!("Failed to delete model file: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Transcribe a recorded audio buffer (base64-encoded) to text
+///
+/// The local provider requires 16 kHz WAV audio; the API provider accepts any
+/// format the configured endpoint supports (wav, webm, ogg, mp3, m4a, flac).
+#[tauri::command]
+pub async fn transcribe_audio(
+    app: AppHandle,
+    data: String,
+    mime_type: String,
+    language: Option<String>,
+) -> Result<TranscriptionResult, String> {
+    log::trace!("Transcribing audio, mime_type: {mime_type}, language: {language:?}");
+
+    let audio = STANDARD
+        .decode(&data)
+        .map_err(|e| format!("Failed to decode base64 audio data: {e}"))?;
+
+    if audio.is_empty() {
+        return Err("Audio is empty".to_string());
+    }
+    if audio.len() > MAX_AUDIO_SIZE {
+        return Err(format!(
+            "Audio too large: {} bytes. Maximum size: {} bytes (25MB)",
+            audio.len(),
+            MAX_AUDIO_SIZE
+        ));
+    }
+
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let language = language.filter(|l| !l.trim().is_empty());
+
+    if prefs.speech_provider == "api" {
+        return transcribe_api(
+            &prefs.speech_api_url,
+            prefs.speech_api_key.as_deref(),
+            &prefs.speech_api_model,
+            &audio,
+            &mime_type,
+            language.as_deref(),
+        )
+        .await;
+    }
+
+    if !mime_type.starts_with("audio/wav")
+        && !mime_type.starts_with("audio/x-wav")
+        && !mime_type.starts_with("audio/wave")
+    {
+        return Err(format!(
+            "Local transcription requires WAV audio (got {mime_type})"
+        ));
+    }
+
+    let whisper_binary = resolve_whisper_binary().ok_or(
+        "whisper.cpp not found. Install it (e.g. `brew install whisper-cpp`) or switch to the API provider.",
+    )?;
+    let model_path = get_model_path(&app, &prefs.speech_local_model)?;
+    if !model_path.exists() {
+        return Err(format!(
+            "Speech model '{}' is not downloaded",
+            prefs.speech_local_model
+        ));
+    }
+
+    // whisper.cpp reads from a file, so stage the audio in the models temp dir
+    let temp_dir = ensure_speech_models_dir(&app)?.join("temp");
+    std::fs::create_dir_all(&temp_dir)
+        .map_err(|e| format!("Failed to create temp directory: {e}"))?;
+    let wav_path = temp_dir.join(format!("{}.wav", uuid::Uuid::new_v4()));
+    std::fs::write(&wav_path, &audio).map_err(|e| format!("Failed to write audio file: {e}"))?;
+
+    let result = tauri::async_runtime::spawn_blocking({
+        let wav_path = wav_path.clone();
+        move || transcribe_local(&whisper_binary, &model_path, &wav_path, language.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Transcription task failed: {e}"))?;
+
+    let _ = std::fs::remove_file(&wav_path);
+    result
+}
+
+/// Speak text aloud with the OS speech engine (optionally with a specific voice)
+#[tauri::command]
+pub async fn speak_text(text: String, voice: Option<String>) -> Result<(), String> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("Text is empty".to_string());
+    }
+    let voice = voice.filter(|v| !v.trim().is_empty());
+
+    tauri::async_runtime::spawn_blocking(move || crate::platform::speak(&text, voice.as_deref()))
+        .await
+        .map_err(|e| format!("Speech task failed: {e}"))?
+}
+
+/// Helper function to emit model download progress events
+fn emit_progress(app: &AppHandle, model: &str, stage: &str, message: &str, percent: u8) {
+    let progress = SpeechModelDownloadProgress {
+        model: model.to_string(),
+        stage: stage.to_string(),
+        message: message.to_string(),
+        percent,
+    };
+
+    if let Err(e) = app.emit_all("speech:model-download-progress", &progress) {
+        log::warn!("Failed to emit model download progress: {e}");
+    }
+}
@@ -0,0 +1,70 @@
+//! Configuration and path management for speech models
+
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::runtime::PathProvider;
+
+/// Directory name for storing downloaded whisper models
+pub const SPEECH_MODELS_DIR_NAME: &str = "speech-models";
+
+/// Base URL for ggml whisper models published by the whisper.cpp project
+pub const WHISPER_MODELS_BASE_URL: &str =
+    "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// whisper.cpp CLI binary names, in order of preference
+/// (`whisper-cli` is the current name, `whisper-cpp` is used by Homebrew)
+pub const WHISPER_BINARY_NAMES: &[&str] = &["whisper-cli", "whisper-cpp"];
+
+/// Known whisper models and their approximate download sizes in MB
+pub const WHISPER_MODELS: &[(&str, u32)] = &[
+    ("tiny", 75),
+    ("tiny.en", 75),
+    ("base", 142),
+    ("base.en", 142),
+    ("small", 466),
+    ("small.en", 466),
+    ("medium", 1500),
+    ("medium.en", 1500),
+    ("large-v3-turbo", 1620),
+];
+
+/// Get the directory where speech models are stored
+///
+/// Returns: `~/Library/Application Support/jean/speech-models/` (macOS)
+///          `~/.local/share/jean/speech-models/` (Linux)
+///          `%APPDATA%/jean/speech-models/` (Windows)
+pub fn get_speech_models_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app.shared_data_dir()?;
+    Ok(app_data_dir.join(SPEECH_MODELS_DIR_NAME))
+}
+
+/// Ensure the models directory exists, creating it if necessary
+pub fn ensure_speech_models_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = get_speech_models_dir(app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create speech models directory: {e}"))?;
+    Ok(dir)
+}
+
+/// Validate a model ID against the known model list
+pub fn validate_model_id(model: &str) -> Result<(), String> {
+    if WHISPER_MODELS.iter().any(|(id, _)| *id == model) {
+        Ok(())
+    } else {
+        Err(format!("Unknown speech model: {model}"))
+    }
+}
+
+/// Get the path to a model file: `speech-models/ggml-{model}.bin`
+pub fn get_model_path(app: &AppHandle, model: &str) -> Result<PathBuf, String> {
+    validate_model_id(model)?;
+    Ok(get_speech_models_dir(app)?.join(format!("ggml-{model}.bin")))
+}
+
+/// Resolve the whisper.cpp CLI binary from PATH
+pub fn resolve_whisper_binary() -> Option<PathBuf> {
+    WHISPER_BINARY_NAMES
+        .iter()
+        .find_map(|name| which::which(name).ok())
+}
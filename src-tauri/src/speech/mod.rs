@@ -0,0 +1,13 @@
+//! Speech-to-text for dictated prompts, and spoken session summaries
+//!
+//! Transcribes recorded audio from the frontend either locally with whisper.cpp
+//! (using a downloaded ggml model) or through an OpenAI-compatible
+//! transcription API, depending on preferences. Finished sessions can
+//! optionally be announced through the OS text-to-speech engine.
+
+mod commands;
+pub(crate) mod config;
+mod transcribe;
+pub mod tts;
+
+pub use commands::*;
@@ -0,0 +1,322 @@
+//! Transcription engines: local whisper.cpp and OpenAI-compatible APIs
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::platform::silent_command;
+
+/// Result of transcribing an audio buffer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionResult {
+    /// Transcribed text (trimmed)
+    pub text: String,
+    /// Confidence between 0.0 and 1.0 (None if the engine didn't report one)
+    pub confidence: Option<f32>,
+    /// Engine that produced the transcript: "local" or "api"
+    pub provider: String,
+    /// Detected or requested language code (if known)
+    pub language: Option<String>,
+}
+
+// ============================================================================
+// Local (whisper.cpp)
+// ============================================================================
+
+/// Run whisper.cpp on a WAV file and parse its full JSON output.
+///
+/// whisper.cpp only reads 16 kHz WAV input, so the frontend is expected to
+/// record (or resample) to that format before sending.
+pub fn transcribe_local(
+    whisper_binary: &Path,
+    model_path: &Path,
+    wav_path: &Path,
+    language: Option<&str>,
+) -> Result<TranscriptionResult, String> {
+    // whisper.cpp appends ".json" to the output prefix
+    let output_prefix = wav_path.with_extension("");
+    let json_path = output_prefix.with_extension("json");
+
+    let mut cmd = silent_command(whisper_binary);
+    cmd.arg("-m")
+        .arg(model_path)
+        .arg("-f")
+        .arg(wav_path)
+        .arg("--output-json-full")
+        .arg("--output-file")
+        .arg(&output_prefix)
+        .arg("--no-prints")
+        .arg("--language")
+        .arg(language.unwrap_or("auto"));
+
+    log::trace!("Running whisper.cpp: {cmd:?}");
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run whisper.cpp: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("whisper.cpp failed: {}", stderr.trim()));
+    }
+
+    let json = std::fs::read_to_string(&json_path)
+        .map_err(|e| format!("Failed to read whisper.cpp output: {e}"))?;
+    let _ = std::fs::remove_file(&json_path);
+
+    parse_whisper_cpp_json(&json)
+}
+
+/// Parse whisper.cpp `--output-json-full` output.
+/// Confidence is the mean probability of all non-special tokens.
+fn parse_whisper_cpp_json(json: &str) -> Result<TranscriptionResult, String> {
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| format!("Failed to parse whisper.cpp output: {e}"))?;
+
+    let segments = value
+        .get("transcription")
+        .and_then(|t| t.as_array())
+        .ok_or("whisper.cpp output has no transcription")?;
+
+    let mut text = String::new();
+    let mut prob_sum = 0.0f64;
+    let mut prob_count = 0usize;
+
+    for segment in segments {
+        if let Some(t) = segment.get("text").and_then(|v| v.as_str()) {
+            text.push_str(t);
+        }
+        for token in segment
+            .get("tokens")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let token_text = token.get("text").and_then(|v| v.as_str()).unwrap_or("");
+            // Special tokens look like [_BEG_], [_TT_123] etc.
+            if token_text.starts_with("[_") {
+                continue;
+            }
+            if let Some(p) = token.get("p").and_then(|v| v.as_f64()) {
+                prob_sum += p;
+                prob_count += 1;
+            }
+        }
+    }
+
+    let language = value
+        .get("result")
+        .and_then(|r| r.get("language"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    Ok(TranscriptionResult {
+        text: text.trim().to_string(),
+        confidence: (prob_count > 0).then(|| (prob_sum / prob_count as f64) as f32),
+        provider: "local".to_string(),
+        language,
+    })
+}
+
+// ============================================================================
+// API (OpenAI-compatible /audio/transcriptions)
+// ============================================================================
+
+/// Build a multipart/form-data body (reqwest is built without the multipart feature)
+fn build_multipart_body(
+    boundary: &str,
+    fields: &[(&str, &str)],
+    file_name: &str,
+    mime_type: &str,
+    audio: &[u8],
+) -> Vec<u8> {
+    let mut body = Vec::with_capacity(audio.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\nContent-Type: {mime_type}\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(audio);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    body
+}
+
+/// File extension the transcription API uses to detect the audio format
+fn extension_for_mime(mime_type: &str) -> &'static str {
+    match mime_type.split(';').next().unwrap_or("").trim() {
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        "audio/webm" => "webm",
+        "audio/ogg" => "ogg",
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" => "m4a",
+        "audio/flac" => "flac",
+        _ => "bin",
+    }
+}
+
+/// Send audio to an OpenAI-compatible transcription endpoint
+pub async fn transcribe_api(
+    api_url: &str,
+    api_key: Option<&str>,
+    model: &str,
+    audio: &[u8],
+    mime_type: &str,
+    language: Option<&str>,
+) -> Result<TranscriptionResult, String> {
+    let boundary = format!("jean-{}", uuid::Uuid::new_v4().simple());
+    let file_name = format!("audio.{}", extension_for_mime(mime_type));
+
+    let mut fields = vec![("model", model), ("response_format", "verbose_json")];
+    if let Some(lang) = language {
+        fields.push(("language", lang));
+    }
+    let body = build_multipart_body(&boundary, &fields, &file_name, mime_type, audio);
+
+    let client = crate::http_client::client_builder()
+        .user_agent("Jean-App/1.0")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+    let mut request = client
+        .post(api_url)
+        .header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(body);
+    if let Some(key) = api_key.filter(|k| !k.is_empty()) {
+        request = request.bearer_auth(key);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach transcription API: {e}"))?;
+
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read transcription response: {e}"))?;
+
+    if !status.is_success() {
+        return Err(format!("Transcription API returned HTTP {status}: {text}"));
+    }
+
+    parse_api_response(&text)
+}
+
+/// Parse a verbose_json transcription response.
+/// Confidence is the mean of exp(avg_logprob) across segments.
+fn parse_api_response(json: &str) -> Result<TranscriptionResult, String> {
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| format!("Failed to parse transcription response: {e}"))?;
+
+    let text = value
+        .get("text")
+        .and_then(|v| v.as_str())
+        .ok_or("Transcription response has no text")?;
+
+    let logprobs: Vec<f64> = value
+        .get("segments")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|s| s.get("avg_logprob").and_then(|v| v.as_f64()))
+        .collect();
+
+    let confidence = (!logprobs.is_empty())
+        .then(|| (logprobs.iter().map(|lp| lp.exp()).sum::<f64>() / logprobs.len() as f64) as f32);
+
+    Ok(TranscriptionResult {
+        text: text.trim().to_string(),
+        confidence,
+        provider: "api".to_string(),
+        language: value
+            .get("language")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_whisper_cpp_json() {
+        let json = r#"{
+            "result": {"language": "en"},
+            "transcription": [
+                {"text": " Fix the login", "tokens": [
+                    {"text": "[_BEG_]", "p": 0.1},
+                    {"text": " Fix", "p": 0.9},
+                    {"text": " the", "p": 0.8}
+                ]},
+                {"text": " bug.", "tokens": [{"text": " bug", "p": 0.7}]}
+            ]
+        }"#;
+
+        let result = parse_whisper_cpp_json(json).unwrap();
+        assert_eq!(result.text, "Fix the login bug.");
+        assert_eq!(result.language.as_deref(), Some("en"));
+        assert_eq!(result.provider, "local");
+        let confidence = result.confidence.unwrap();
+        assert!((confidence - 0.8).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_parse_whisper_cpp_json_without_tokens() {
+        let json = r#"{"transcription": [{"text": " hello"}]}"#;
+        let result = parse_whisper_cpp_json(json).unwrap();
+        assert_eq!(result.text, "hello");
+        assert!(result.confidence.is_none());
+    }
+
+    #[test]
+    fn test_parse_api_response() {
+        let json = r#"{
+            "text": " Add a test ",
+            "language": "english",
+            "segments": [{"avg_logprob": 0.0}, {"avg_logprob": -0.6931471805599453}]
+        }"#;
+
+        let result = parse_api_response(json).unwrap();
+        assert_eq!(result.text, "Add a test");
+        assert_eq!(result.provider, "api");
+        assert!((result.confidence.unwrap() - 0.75).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_build_multipart_body() {
+        let body = build_multipart_body(
+            "b",
+            &[("model", "whisper-1")],
+            "a.wav",
+            "audio/wav",
+            b"RIFF",
+        );
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.starts_with(
+            "--b\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n"
+        ));
+        assert!(
+            body.contains("filename=\"a.wav\"\r\nContent-Type: audio/wav\r\n\r\nRIFF\r\n--b--\r\n")
+        );
+    }
+
+    #[test]
+    fn test_extension_for_mime() {
+        assert_eq!(extension_for_mime("audio/webm;codecs=opus"), "webm");
+        assert_eq!(extension_for_mime("audio/wav"), "wav");
+        assert_eq!(extension_for_mime("application/octet-stream"), "bin");
+    }
+}
@@ -0,0 +1,241 @@
+//! Spoken summaries when a session finishes or needs input
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tauri::AppHandle;
+
+use crate::chat::types::ToolCall;
+use crate::focus::NotificationCategory;
+
+/// Tools that leave the session waiting on the user when unanswered
+const WAITING_TOOLS: &[&str] = &["AskUserQuestion", "ExitPlanMode"];
+
+/// Spoken summaries are cut to roughly one sentence
+const MAX_SPOKEN_CHARS: usize = 200;
+
+/// Markdown links: [text](url) -> text
+static LINK_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[([^\]]*)\]\([^)]*\)").unwrap());
+
+/// Leading list markers, headings and quotes
+static LINE_PREFIX_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(?:#{1,6}\s+|>\s*|[-*+]\s+|\d+[.)]\s+)").unwrap());
+
+/// Why a run ended, mapped to the per-notification-type speech toggles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// Claude asked a question or presented a plan
+    Waiting,
+    /// Claude finished and the result is ready for review
+    Review,
+}
+
+/// Determine how a run ended from its tool calls
+pub fn completion_kind(tool_calls: &[ToolCall]) -> CompletionKind {
+    if tool_calls
+        .iter()
+        .any(|tc| tc.output.is_none() && WAITING_TOOLS.contains(&tc.name.as_str()))
+    {
+        CompletionKind::Waiting
+    } else {
+        CompletionKind::Review
+    }
+}
+
+/// Build a one-sentence summary of an assistant response suitable for speech.
+///
+/// Claude usually wraps up in its final paragraph, so this takes the first
+/// sentence of the last prose paragraph with markdown and code removed.
+pub fn summarize_for_speech(content: &str) -> Option<String> {
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut in_code_block = false;
+
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || line.trim_start().starts_with('|') {
+            continue;
+        }
+        let line = clean_markdown_line(line);
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&line);
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+
+    let paragraph = paragraphs.pop()?;
+    Some(truncate_words(first_sentence(&paragraph), MAX_SPOKEN_CHARS))
+}
+
+/// Summary for a session waiting on the user (the question text when available)
+pub(crate) fn waiting_summary(tool_calls: &[ToolCall]) -> String {
+    let pending = tool_calls
+        .iter()
+        .rev()
+        .find(|tc| tc.output.is_none() && WAITING_TOOLS.contains(&tc.name.as_str()));
+
+    match pending {
+        Some(tc) if tc.name == "ExitPlanMode" => "A plan is ready for your review.".to_string(),
+        Some(tc) => tc
+            .input
+            .get("questions")
+            .and_then(|q| q.get(0))
+            .and_then(|q| q.get("question"))
+            .and_then(|q| q.as_str())
+            .map(|q| truncate_words(&format!("Claude is asking: {q}"), MAX_SPOKEN_CHARS))
+            .unwrap_or_else(|| "Claude is waiting for your input.".to_string()),
+        None => "Claude is waiting for your input.".to_string(),
+    }
+}
+
+/// Speak a summary of a finished run if the matching toggle is enabled
+pub async fn speak_completion_summary(app: &AppHandle, content: &str, tool_calls: &[ToolCall]) {
+    let prefs = match crate::load_preferences(app.clone()).await {
+        Ok(prefs) => prefs,
+        Err(e) => {
+            log::warn!("Failed to load preferences for spoken summary: {e}");
+            return;
+        }
+    };
+
+    let (text, category) = match completion_kind(tool_calls) {
+        CompletionKind::Waiting if prefs.speak_waiting_summary => (
+            waiting_summary(tool_calls),
+            NotificationCategory::InputNeeded,
+        ),
+        CompletionKind::Review if prefs.speak_review_summary => {
+            let text = match summarize_for_speech(content) {
+                Some(text) => text,
+                None => "Claude has finished.".to_string(),
+            };
+            (text, NotificationCategory::SessionFinished)
+        }
+        _ => return,
+    };
+
+    // Don't talk over OS Focus; the summary goes to the Focus digest instead
+    if crate::focus::hold_if_focused(app, category, &text, None).await {
+        return;
+    }
+
+    let voice = prefs.speech_voice.filter(|v| !v.trim().is_empty());
+    // Speaking blocks until finished, so don't hold up the caller
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = crate::platform::speak(&text, voice.as_deref()) {
+            log::warn!("Failed to speak session summary: {e}");
+        }
+    });
+}
+
+fn clean_markdown_line(line: &str) -> String {
+    let line = LINE_PREFIX_RE.replace(line, "");
+    let line = LINK_RE.replace_all(&line, "$1");
+    line.replace("**", "")
+        .replace('`', "")
+        .replace("__", "")
+        .trim()
+        .to_string()
+}
+
+fn first_sentence(text: &str) -> &str {
+    let bytes = text.as_bytes();
+    for (i, c) in text.char_indices() {
+        if matches!(c, '.' | '!' | '?') {
+            let end = i + c.len_utf8();
+            // Only a sentence end if followed by whitespace or end of text (skips "v1.2", "e.g.x")
+            if end == bytes.len() || bytes[end].is_ascii_whitespace() {
+                return &text[..end];
+            }
+        }
+    }
+    text
+}
+
+fn truncate_words(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    let cut = match cut.rfind(' ') {
+        Some(idx) => &cut[..idx],
+        None => cut.as_str(),
+    };
+    format!("{}...", cut.trim_end_matches([',', ';', ':', ' ']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_call(name: &str, input: serde_json::Value, output: Option<&str>) -> ToolCall {
+        ToolCall {
+            id: "t1".to_string(),
+            name: name.to_string(),
+            input,
+            output: output.map(|s| s.to_string()),
+            parent_tool_use_id: None,
+        }
+    }
+
+    #[test]
+    fn test_summarize_uses_last_paragraph_first_sentence() {
+        let content = "I'll look at the code.\n\n```rust\nfn main() {}\n```\n\n## Summary\n\nFixed the **login** bug in `auth.rs`. Also added tests.";
+        assert_eq!(
+            summarize_for_speech(content).as_deref(),
+            Some("Fixed the login bug in auth.rs.")
+        );
+    }
+
+    #[test]
+    fn test_summarize_strips_links_and_lists() {
+        let content = "- Updated [the docs](https://example.com) for v1.2 release";
+        assert_eq!(
+            summarize_for_speech(content).as_deref(),
+            Some("Updated the docs for v1.2 release")
+        );
+    }
+
+    #[test]
+    fn test_summarize_empty_content() {
+        assert!(summarize_for_speech("").is_none());
+        assert!(summarize_for_speech("```\ncode only\n```").is_none());
+    }
+
+    #[test]
+    fn test_truncate_words() {
+        let long = "word ".repeat(100);
+        let result = truncate_words(long.trim(), 20);
+        assert_eq!(result, "word word word word...");
+    }
+
+    #[test]
+    fn test_completion_kind_and_waiting_summary() {
+        let question = tool_call(
+            "AskUserQuestion",
+            serde_json::json!({"questions": [{"question": "Which database?"}]}),
+            None,
+        );
+        assert_eq!(
+            completion_kind(std::slice::from_ref(&question)),
+            CompletionKind::Waiting
+        );
+        assert_eq!(
+            waiting_summary(&[question]),
+            "Claude is asking: Which database?"
+        );
+
+        let answered = tool_call("AskUserQuestion", serde_json::json!({}), Some("Postgres"));
+        assert_eq!(completion_kind(&[answered]), CompletionKind::Review);
+    }
+}
@@ -5,7 +5,9 @@ use super::pty::{
     write_to_terminal,
 };
 use super::registry::{get_all_terminal_ids, has_terminal};
+use super::runner::{self, CommandRun};
 use crate::projects::git::read_jean_config;
+use crate::projects::storage::load_projects_data;
 
 /// Start a terminal
 #[tauri::command]
@@ -71,3 +73,57 @@ pub fn kill_all_terminals() -> usize {
     log::trace!("kill_all_terminals command invoked");
     pty_kill_all_terminals()
 }
+
+/// Run a command in a project's directory (or one of its worktrees),
+/// streaming output as terminal events with the returned run's id
+#[tauri::command]
+pub async fn run_project_command(
+    app: AppHandle,
+    project_id: String,
+    command: String,
+    worktree_id: Option<String>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<CommandRun, String> {
+    let command = command.trim();
+    if command.is_empty() {
+        return Err("Command is empty".to_string());
+    }
+    let data = load_projects_data(&app)?;
+    let project = data
+        .find_project(&project_id)
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    let cwd = match worktree_id {
+        Some(worktree_id) => data
+            .find_worktree(&worktree_id)
+            .filter(|w| w.project_id == project_id)
+            .map(|w| w.path.clone())
+            .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?,
+        None if project.is_folder => return Err("Folders have no directory to run in".to_string()),
+        None => project.path.clone(),
+    };
+    runner::start_run(
+        &app,
+        &project_id,
+        command,
+        &cwd,
+        cols.unwrap_or(120),
+        rows.unwrap_or(30),
+    )
+}
+
+/// A project's command runs, newest first
+#[tauri::command]
+pub async fn list_project_command_runs(app: AppHandle, project_id: String) -> Vec<CommandRun> {
+    runner::list_runs(&app, &project_id)
+}
+
+/// A command run's output as plain text
+#[tauri::command]
+pub async fn get_project_command_output(
+    app: AppHandle,
+    project_id: String,
+    run_id: String,
+) -> Result<String, String> {
+    runner::read_output(&app, &project_id, &run_id)
+}
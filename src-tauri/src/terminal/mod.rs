@@ -1,6 +1,7 @@
 mod commands;
 mod pty;
 mod registry;
+pub mod runner;
 mod types;
 
 // Re-export commands for registration in lib.rs
@@ -9,6 +9,13 @@ use super::types::{
     TerminalOutputEvent, TerminalSession, TerminalStartedEvent, TerminalStoppedEvent,
 };
 
+/// Extra handling of a PTY's output and exit, on top of the terminal events
+pub struct PtyHooks {
+    pub on_output: Box<dyn FnMut(&[u8]) + Send>,
+    /// Called once with the exit code (`None` if killed)
+    pub on_exit: Box<dyn FnOnce(Option<i32>) + Send>,
+}
+
 /// Detect user's default shell (cross-platform)
 fn get_user_shell() -> String {
     crate::platform::get_default_shell()
@@ -28,18 +35,6 @@ pub fn spawn_terminal(
         log::trace!("Running command: {cmd}");
     }
 
-    let pty_system = native_pty_system();
-
-    // Create PTY pair
-    let pair = pty_system
-        .openpty(PtySize {
-            rows,
-            cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        })
-        .map_err(|e| format!("Failed to open PTY: {e}"))?;
-
     // Get user's shell
     let shell = get_user_shell();
     log::trace!("Using shell: {shell}");
@@ -73,6 +68,31 @@ pub fn spawn_terminal(
     cmd.env("COLORTERM", "truecolor");
     cmd.env("JEAN_WORKTREE_PATH", &worktree_path);
 
+    spawn_pty(app, terminal_id, cmd, cols, rows, None)
+}
+
+/// Spawn `cmd` in a PTY registered as terminal `terminal_id`, streaming
+/// `terminal:*` events
+pub fn spawn_pty(
+    app: &AppHandle,
+    terminal_id: String,
+    cmd: CommandBuilder,
+    cols: u16,
+    rows: u16,
+    hooks: Option<PtyHooks>,
+) -> Result<(), String> {
+    let pty_system = native_pty_system();
+
+    // Create PTY pair
+    let pair = pty_system
+        .openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to open PTY: {e}"))?;
+
     // Spawn the shell
     let child = pair
         .slave
@@ -117,6 +137,10 @@ pub fn spawn_terminal(
     // Spawn reader thread
     let app_clone = app.clone();
     let terminal_id_clone = terminal_id.clone();
+    let (mut on_output, on_exit) = match hooks {
+        Some(hooks) => (Some(hooks.on_output), Some(hooks.on_exit)),
+        None => (None, None),
+    };
     thread::spawn(move || {
         let mut buf = [0u8; 4096];
         loop {
@@ -127,6 +151,9 @@ pub fn spawn_terminal(
                     break;
                 }
                 Ok(n) => {
+                    if let Some(on_output) = on_output.as_mut() {
+                        on_output(&buf[..n]);
+                    }
                     // Convert bytes to string (lossy conversion for non-UTF8)
                     let data = String::from_utf8_lossy(&buf[..n]).to_string();
                     let event = TerminalOutputEvent {
@@ -144,16 +171,15 @@ pub fn spawn_terminal(
             }
         }
 
-        // Terminal has exited, get exit code and cleanup
+        // Terminal has exited, get exit code and cleanup (a killed
+        // terminal is already unregistered and has had its stopped event)
+        let mut exit_code = None;
         if let Some(mut session) = unregister_terminal(&terminal_id_clone) {
-            let exit_code = session.child.wait().ok().and_then(|s| {
-                if s.success() {
-                    Some(0)
-                } else {
-                    // portable-pty ExitStatus doesn't expose code directly
-                    None
-                }
-            });
+            exit_code = session
+                .child
+                .wait()
+                .ok()
+                .map(|status| status.exit_code() as i32);
 
             let stopped_event = TerminalStoppedEvent {
                 terminal_id: terminal_id_clone,
@@ -163,6 +189,9 @@ pub fn spawn_terminal(
                 log::error!("Failed to emit terminal:stopped event: {e}");
             }
         }
+        if let Some(on_exit) = on_exit {
+            on_exit(exit_code);
+        }
     });
 
     Ok(())
@@ -0,0 +1,386 @@
+//! Project command runs ("run tests", "run build")
+//!
+//! A run is a plain shell command in a project's directory, separate from
+//! agent sessions. It runs in a PTY registered as a terminal whose id is the
+//! run id, so the UI renders and stops it like any terminal. Managed tools
+//! are put on its PATH the same way as for agent sessions.
+//!
+//! Every run is recorded in `command-runs/{project_id}/`: `{run_id}.json`
+//! holds what ran, where, when and how it exited, `{run_id}.log` the raw
+//! output. The records double as the log of commands run from Jean, and their
+//! output can be attached to follow-up prompts (see [`recent_runs_context`]).
+//! Only the newest [`MAX_RUNS`] runs per project are kept.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use portable_pty::CommandBuilder;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use super::pty::{spawn_pty, PtyHooks};
+use crate::runtime::{now, EventSink, PathProvider};
+
+/// Runs directory (within app data)
+const RUNS_DIR: &str = "command-runs";
+
+/// Runs kept per project
+pub const MAX_RUNS: usize = 50;
+
+/// Output logged per run; anything beyond is dropped
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Rough size of a token, for output budgets
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Escape sequences (CSI, OSC and two-byte) in terminal output
+static ANSI_ESCAPE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]").unwrap()
+});
+
+/// A project command run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandRun {
+    pub id: String,
+    pub project_id: String,
+    pub command: String,
+    /// Directory the command ran in
+    pub cwd: String,
+    /// Unix seconds
+    pub started_at: u64,
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+    /// `None` while running or when stopped
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Output exceeded the log limit and was cut
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+fn runs_dir(app: &impl PathProvider, project_id: &str) -> Result<PathBuf, String> {
+    Ok(app.app_data_dir()?.join(RUNS_DIR).join(project_id))
+}
+
+fn save_run(app: &impl PathProvider, run: &CommandRun) -> Result<(), String> {
+    let dir = runs_dir(app, &run.project_id)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create runs directory: {e}"))?;
+    let content = serde_json::to_string_pretty(run)
+        .map_err(|e| format!("Failed to serialize command run: {e}"))?;
+    fs::write(dir.join(format!("{}.json", run.id)), content)
+        .map_err(|e| format!("Failed to save command run: {e}"))
+}
+
+/// A project's runs, newest first
+pub fn list_runs(app: &impl PathProvider, project_id: &str) -> Vec<CommandRun> {
+    let Ok(entries) = runs_dir(app, project_id).and_then(|dir| {
+        fs::read_dir(dir).map_err(|e| format!("Failed to read runs directory: {e}"))
+    }) else {
+        return Vec::new();
+    };
+    let mut runs: Vec<CommandRun> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| {
+            let content = fs::read_to_string(entry.path()).ok()?;
+            serde_json::from_str(&content).ok()
+        })
+        .collect();
+    runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    runs
+}
+
+/// Remove all but the newest [`MAX_RUNS`] runs
+fn prune_runs(app: &impl PathProvider, project_id: &str) {
+    let Ok(dir) = runs_dir(app, project_id) else {
+        return;
+    };
+    for run in list_runs(app, project_id).into_iter().skip(MAX_RUNS) {
+        let _ = fs::remove_file(dir.join(format!("{}.json", run.id)));
+        let _ = fs::remove_file(dir.join(format!("{}.log", run.id)));
+    }
+}
+
+/// Terminal output as plain text: escape sequences removed and carriage
+/// returns resolved (progress bars keep their last state)
+pub fn plain_output(raw: &str) -> String {
+    let text = ANSI_ESCAPE.replace_all(raw, "");
+    text.split('\n')
+        .map(|line| {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            line.rsplit('\r').next().unwrap_or(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A run's output as plain text
+pub fn read_output(
+    app: &impl PathProvider,
+    project_id: &str,
+    run_id: &str,
+) -> Result<String, String> {
+    if Uuid::parse_str(run_id).is_err() {
+        return Err(format!("Invalid run id: {run_id}"));
+    }
+    let path = runs_dir(app, project_id)?.join(format!("{run_id}.log"));
+    let raw = fs::read(&path).map_err(|e| format!("Failed to read command output: {e}"))?;
+    Ok(plain_output(&String::from_utf8_lossy(&raw)))
+}
+
+/// Last `max_chars` bytes of `text`, starting at a line boundary when
+/// possible. The flag says whether anything was cut.
+fn tail(text: &str, max_chars: usize) -> (&str, bool) {
+    if text.len() <= max_chars {
+        return (text, false);
+    }
+    let mut start = text.len() - max_chars;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let tail = &text[start..];
+    let tail = tail.split_once('\n').map_or(tail, |(_, rest)| rest);
+    (tail, true)
+}
+
+/// Markdown with the output of a project's latest runs, for a session's
+/// context: the newest run of up to `max_runs` distinct commands, failures
+/// first, each cut from the start to share `token_budget`
+pub fn recent_runs_context(
+    app: &impl PathProvider,
+    project_id: &str,
+    max_runs: usize,
+    token_budget: usize,
+) -> Option<String> {
+    let mut commands = HashSet::new();
+    let mut runs: Vec<CommandRun> = list_runs(app, project_id)
+        .into_iter()
+        .filter(|run| run.finished_at.is_some())
+        .filter(|run| commands.insert(run.command.clone()))
+        .take(max_runs)
+        .collect();
+    if runs.is_empty() {
+        return None;
+    }
+    // Failures are what follow-up prompts are about; they get budget first
+    runs.sort_by_key(|run| run.exit_code == Some(0));
+
+    let mut content = String::from(
+        "# Recent command output\n\n\
+         Latest output of commands run in this project, failures first. \
+         Long output only keeps its end.\n",
+    );
+    let mut budget = token_budget * CHARS_PER_TOKEN;
+    for (i, run) in runs.iter().enumerate() {
+        let output = read_output(app, project_id, &run.id).unwrap_or_default();
+        let (output, cut) = tail(output.trim_end(), budget / (runs.len() - i));
+        budget = budget.saturating_sub(output.len());
+        let status = match run.exit_code {
+            Some(0) => "succeeded".to_string(),
+            Some(code) => format!("failed with exit code {code}"),
+            None => "was stopped".to_string(),
+        };
+        content.push_str(&format!(
+            "\n## `{}` {status}\n\n```text\n{}{output}\n```\n",
+            run.command,
+            if cut { "[...]\n" } else { "" }
+        ));
+    }
+    Some(content)
+}
+
+/// Start `command` in `cwd`; output streams as `terminal:*` events and
+/// `project-command:finished` carries the final record
+pub fn start_run(
+    app: &AppHandle,
+    project_id: &str,
+    command: &str,
+    cwd: &str,
+    cols: u16,
+    rows: u16,
+) -> Result<CommandRun, String> {
+    let run = CommandRun {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        command: command.to_string(),
+        cwd: cwd.to_string(),
+        started_at: now(),
+        finished_at: None,
+        exit_code: None,
+        truncated: false,
+    };
+    log::trace!("Running project command {}: {command}", run.id);
+    save_run(app, &run)?;
+    prune_runs(app, project_id);
+
+    let log_path = runs_dir(app, project_id)?.join(format!("{}.log", run.id));
+    let mut log_file =
+        File::create(&log_path).map_err(|e| format!("Failed to create output log: {e}"))?;
+
+    let shell = crate::platform::get_default_shell();
+    let mut cmd = CommandBuilder::new(&shell);
+    #[cfg(windows)]
+    cmd.arg("-Command");
+    #[cfg(not(windows))]
+    cmd.arg("-c");
+    cmd.arg(command);
+    cmd.cwd(cwd);
+    cmd.env("TERM", "xterm-256color");
+    cmd.env("COLORTERM", "truecolor");
+    cmd.env("JEAN_WORKTREE_PATH", cwd);
+    if let Some(path) = crate::tool_install::session_path(app) {
+        cmd.env("PATH", path);
+    }
+
+    let truncated = Arc::new(AtomicBool::new(false));
+    let mut logged = 0u64;
+    let on_output = {
+        let truncated = truncated.clone();
+        Box::new(move |data: &[u8]| {
+            let room = MAX_LOG_BYTES.saturating_sub(logged) as usize;
+            if data.len() > room {
+                truncated.store(true, Ordering::Relaxed);
+            }
+            let data = &data[..data.len().min(room)];
+            if !data.is_empty() && log_file.write_all(data).is_ok() {
+                logged += data.len() as u64;
+            }
+        })
+    };
+    let on_exit = {
+        let app = app.clone();
+        let mut run = run.clone();
+        Box::new(move |exit_code: Option<i32>| {
+            run.finished_at = Some(now());
+            run.exit_code = exit_code;
+            run.truncated = truncated.load(Ordering::Relaxed);
+            log::trace!("Project command {} exited with {exit_code:?}", run.id);
+            if let Err(e) = save_run(&app, &run) {
+                log::error!("{e}");
+            }
+            let _ = app.emit_all("project-command:finished", &run);
+        })
+    };
+
+    spawn_pty(
+        app,
+        run.id.clone(),
+        cmd,
+        cols,
+        rows,
+        Some(PtyHooks { on_output, on_exit }),
+    )
+    .inspect_err(|_| {
+        // Nothing ran; don't leave a run that never finishes
+        let _ = fs::remove_file(&log_path);
+        let _ = fs::remove_file(log_path.with_extension("json"));
+    })?;
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    fn run(id: &str, started_at: u64) -> CommandRun {
+        CommandRun {
+            id: id.to_string(),
+            project_id: "p1".to_string(),
+            command: "npm test".to_string(),
+            cwd: "/repo".to_string(),
+            started_at,
+            finished_at: Some(started_at + 5),
+            exit_code: Some(1),
+            truncated: false,
+        }
+    }
+
+    fn save_with_output(paths: &TempPaths, run: &CommandRun, output: &str) {
+        save_run(paths, run).unwrap();
+        let dir = runs_dir(paths, &run.project_id).unwrap();
+        fs::write(dir.join(format!("{}.log", run.id)), output).unwrap();
+    }
+
+    #[test]
+    fn test_recent_runs_context() {
+        let paths = TempPaths::new();
+        assert!(recent_runs_context(&paths, "p1", 3, 1000).is_none());
+
+        let mut build = run(&Uuid::new_v4().to_string(), 100);
+        build.command = "npm run build".to_string();
+        build.exit_code = Some(0);
+        save_with_output(&paths, &build, "built in 2s\n");
+        let old_test = run(&Uuid::new_v4().to_string(), 50);
+        save_with_output(&paths, &old_test, "stale failure\n");
+        let test = run(&Uuid::new_v4().to_string(), 90);
+        let long: String = (0..500).map(|i| format!("line {i}\n")).collect();
+        save_with_output(
+            &paths,
+            &test,
+            &format!("{long}\x1b[31mFAIL\x1b[0m a.test.ts\n"),
+        );
+        let mut running = run(&Uuid::new_v4().to_string(), 200);
+        running.finished_at = None;
+        save_run(&paths, &running).unwrap();
+
+        let context = recent_runs_context(&paths, "p1", 3, 100).unwrap();
+        // Only the latest finished run per command, failures first
+        let tests = context
+            .find("## `npm test` failed with exit code 1")
+            .unwrap();
+        let build = context.find("## `npm run build` succeeded").unwrap();
+        assert!(tests < build, "{context}");
+        assert!(!context.contains("stale failure"));
+        // Long output keeps its end within the budget
+        assert!(context.contains("[...]\nline"));
+        assert!(context.contains("FAIL a.test.ts\n```"));
+        assert!(
+            context.len() < 100 * CHARS_PER_TOKEN + 300,
+            "{}",
+            context.len()
+        );
+        assert!(context.contains("built in 2s"));
+
+        let context = recent_runs_context(&paths, "p1", 1, 100).unwrap();
+        assert!(!context.contains("npm test"));
+    }
+
+    #[test]
+    fn test_plain_output() {
+        let raw =
+            "\x1b[32mPASS\x1b[0m a.test.ts\r\n\x1b]0;title\x07building 10%\rbuilding 100%\r\ndone";
+        assert_eq!(plain_output(raw), "PASS a.test.ts\nbuilding 100%\ndone");
+    }
+
+    #[test]
+    fn test_runs_are_listed_newest_first_and_pruned() {
+        let paths = TempPaths::new();
+        for i in 0..MAX_RUNS + 2 {
+            let run = run(&Uuid::new_v4().to_string(), 1000 + i as u64);
+            save_run(&paths, &run).unwrap();
+            fs::write(
+                runs_dir(&paths, "p1")
+                    .unwrap()
+                    .join(format!("{}.log", run.id)),
+                "\x1b[31mFAIL\x1b[0m\r\n",
+            )
+            .unwrap();
+        }
+        prune_runs(&paths, "p1");
+
+        let runs = list_runs(&paths, "p1");
+        assert_eq!(runs.len(), MAX_RUNS);
+        assert_eq!(runs[0].started_at, 1000 + MAX_RUNS as u64 + 1);
+        assert_eq!(read_output(&paths, "p1", &runs[0].id).unwrap(), "FAIL\n");
+        assert!(list_runs(&paths, "other").is_empty());
+        assert!(read_output(&paths, "p1", "../secrets").is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! Minimal HTTP server serving canned responses, so download/install
+//! pipelines can be tested end to end without hitting the network
+//!
+//! Runs on a background thread bound to an ephemeral localhost port and
+//! answers each request with the route registered for its path (404 otherwise).
+//! Every response closes the connection, which keeps the parser trivial.
+//! `bytes` responses honor `Range: bytes=N-` like a release CDN would.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+/// A canned response
+#[derive(Debug, Clone)]
+pub struct FixtureResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+    /// Answer `Range: bytes=N-` requests with 206 (or 416 past the end)
+    pub ranges: bool,
+}
+
+impl FixtureResponse {
+    pub fn json(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: body.into(),
+            ranges: false,
+        }
+    }
+
+    pub fn bytes(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/octet-stream",
+            body: body.into(),
+            ranges: true,
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            content_type: "text/plain",
+            body: Vec::new(),
+            ranges: false,
+        }
+    }
+}
+
+/// Handle to a running fixture server (its thread lives until the test binary exits)
+pub struct FixtureServer {
+    port: u16,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl FixtureServer {
+    /// Start serving `routes` (path -> response)
+    pub fn start(routes: Vec<(String, FixtureResponse)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind fixture server");
+        let port = listener.local_addr().expect("fixture server addr").port();
+        let routes: HashMap<String, FixtureResponse> = routes.into_iter().collect();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let log = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Some(path) = handle(stream, &routes) {
+                    log.lock().unwrap().push(path);
+                }
+            }
+        });
+
+        Self { port, requests }
+    }
+
+    /// Absolute URL for a path on this server
+    pub fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:{}{path}", self.port)
+    }
+
+    /// Paths requested so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn handle(mut stream: TcpStream, routes: &HashMap<String, FixtureResponse>) -> Option<String> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    // Drain headers, then any body (closing with unread data resets the connection)
+    let mut line = String::new();
+    let mut content_length = 0;
+    let mut range_start: Option<usize> = None;
+    while reader.read_line(&mut line).ok()? > 2 {
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("range") {
+                range_start = value
+                    .trim()
+                    .strip_prefix("bytes=")
+                    .and_then(|r| r.strip_suffix('-'))
+                    .and_then(|start| start.parse().ok());
+            }
+        }
+        line.clear();
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    let path = request_line.split_whitespace().nth(1)?.to_string();
+    let path_only = path.split('?').next().unwrap_or(&path);
+    let response = routes
+        .get(path_only)
+        .cloned()
+        .unwrap_or_else(|| FixtureResponse::status(404));
+
+    let len = response.body.len();
+    let (status, content_range, content) = match range_start {
+        Some(start) if response.ranges && start < len => (
+            206,
+            format!("Content-Range: bytes {start}-{}/{len}\r\n", len - 1),
+            &response.body[start..],
+        ),
+        Some(_) if response.ranges => (416, format!("Content-Range: bytes */{len}\r\n"), &[][..]),
+        _ => (response.status, String::new(), &response.body[..]),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {status} Fixture\r\nContent-Type: {}\r\nContent-Length: {}\r\n{content_range}Connection: close\r\n\r\n",
+        response.content_type,
+        content.len()
+    );
+    stream.write_all(head.as_bytes()).ok()?;
+    stream.write_all(content).ok()?;
+    Some(path)
+}
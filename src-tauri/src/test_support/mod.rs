@@ -0,0 +1,13 @@
+//! Shared helpers for tests (compiled only under `cfg(test)`)
+
+pub mod fixture_server;
+pub mod runtime;
+
+use std::path::PathBuf;
+
+/// Path to a file under `src-tauri/fixtures/`
+pub fn fixture_path(relative: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(relative)
+}
@@ -0,0 +1,129 @@
+//! Test doubles for the `crate::runtime` traits
+
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Output};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::runtime::{EventSink, PathProvider, ProcessRunner};
+
+/// App data directory backed by a temp dir (removed on drop)
+pub struct TempPaths {
+    dir: tempfile::TempDir,
+}
+
+impl TempPaths {
+    pub fn new() -> Self {
+        Self {
+            dir: tempfile::tempdir().expect("failed to create temp dir"),
+        }
+    }
+}
+
+impl PathProvider for TempPaths {
+    fn app_data_dir(&self) -> Result<PathBuf, String> {
+        Ok(self.dir.path().to_path_buf())
+    }
+}
+
+/// Collects emitted events as `(event, payload)` pairs
+#[derive(Default)]
+pub struct RecordingSink {
+    events: Mutex<Vec<(String, Value)>>,
+}
+
+impl RecordingSink {
+    pub fn events(&self) -> Vec<(String, Value)> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Payloads of every event with the given name, in emission order
+    pub fn payloads(&self, event: &str) -> Vec<Value> {
+        self.events()
+            .into_iter()
+            .filter(|(name, _)| name == event)
+            .map(|(_, payload)| payload)
+            .collect()
+    }
+}
+
+impl EventSink for RecordingSink {
+    fn emit_all<S: Serialize + Clone>(&self, event: &str, payload: &S) -> Result<(), String> {
+        let value = serde_json::to_value(payload).map_err(|e| e.to_string())?;
+        self.events.lock().unwrap().push((event.to_string(), value));
+        Ok(())
+    }
+}
+
+/// An invocation seen by `ScriptedRunner`
+#[derive(Debug, Clone)]
+pub struct RecordedRun {
+    pub program: String,
+    pub args: Vec<String>,
+    pub stdin: Option<String>,
+}
+
+/// Returns canned output instead of spawning, and records what was run
+pub struct ScriptedRunner {
+    exit_code: i32,
+    stdout: String,
+    stderr: String,
+    runs: Mutex<Vec<RecordedRun>>,
+}
+
+impl ScriptedRunner {
+    pub fn succeeding(stdout: &str) -> Self {
+        Self {
+            exit_code: 0,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            runs: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn failing(exit_code: i32, stderr: &str) -> Self {
+        Self {
+            exit_code,
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+            runs: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn runs(&self) -> Vec<RecordedRun> {
+        self.runs.lock().unwrap().clone()
+    }
+}
+
+impl ProcessRunner for ScriptedRunner {
+    fn run(&self, command: Command, stdin: Option<&[u8]>) -> Result<Output, String> {
+        self.runs.lock().unwrap().push(RecordedRun {
+            program: command.get_program().to_string_lossy().into_owned(),
+            args: command
+                .get_args()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect(),
+            stdin: stdin.map(|s| String::from_utf8_lossy(s).into_owned()),
+        });
+        Ok(Output {
+            status: exit_status(self.exit_code),
+            stdout: self.stdout.clone().into_bytes(),
+            stderr: self.stderr.clone().into_bytes(),
+        })
+    }
+}
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    // Wait status encoding: exit code lives in the second byte
+    ExitStatus::from_raw(code << 8)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
@@ -0,0 +1,559 @@
+//! Installing the Codex CLI from a release archive on disk
+//!
+//! For machines that can't reach npm, Homebrew or GitHub (e.g. air-gapped
+//! networks): a Codex release archive (`codex-<target>.tar.gz` or `.zip`)
+//! copied over by hand is checked against its SHA-256 (given explicitly, in a
+//! `<archive>.sha256` sidecar, or in a `checksums.txt`/`SHA256SUMS` next to
+//! it), then unpacked, and its binary for this platform is
+//! installed as `codex` into the helper tools directory, which `session_path`
+//! puts first on sessions' PATH. As with downloaded CLIs, the binary is
+//! verified with `--version` before it replaces the installed one, and is
+//! then released from quarantine.
+//!
+//! Every installed binary is also kept as `codex-versions/<version>/codex` in
+//! the helper bin directory, so a previous release can be switched back to
+//! without the archive.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::http_client::downloads::{checksum_for, is_sha256};
+use crate::platform::silent_command;
+use crate::runtime::PathProvider;
+
+use super::helpers::{bin_dir, exe_name, find_file, helper_dir, unpack};
+
+/// Checksum files looked for next to an archive
+const CHECKSUM_FILES: &[&str] = &["checksums.txt", "SHA256SUMS"];
+
+/// Directory in the helper bin directory keeping each installed version
+const VERSIONS_DIR: &str = "codex-versions";
+
+/// File in `VERSIONS_DIR` naming the version installed as `codex`
+const CURRENT_FILE: &str = "current";
+
+/// A Codex CLI version kept in the helper bin directory
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InstalledCodexVersion {
+    pub version: String,
+    /// Whether this is the version installed as `codex`
+    pub active: bool,
+}
+
+/// Progress event for installing the Codex CLI from an archive
+#[derive(Debug, Clone, Serialize)]
+pub struct CodexInstallProgress {
+    pub stage: String,
+    pub message: String,
+    pub percent: u8,
+}
+
+/// Names the Codex binary may have in a release archive, in the order
+/// they're searched: this platform's target names first, then plain `codex`
+fn binary_names() -> Vec<String> {
+    let arch = std::env::consts::ARCH;
+    let systems: &[&str] = match std::env::consts::OS {
+        "macos" => &["apple-darwin"],
+        "windows" => &["pc-windows-msvc", "pc-windows-gnullvm"],
+        _ => &["unknown-linux-musl", "unknown-linux-gnu"],
+    };
+    systems
+        .iter()
+        .map(|system| exe_name(&format!("codex-{arch}-{system}")))
+        .chain(std::iter::once(exe_name("codex")))
+        .collect()
+}
+
+/// The Codex binary for this platform in an unpacked archive
+fn find_binary(unpacked: &Path) -> Result<PathBuf, String> {
+    binary_names()
+        .iter()
+        .find_map(|name| find_file(unpacked, name))
+        .ok_or_else(|| {
+            format!(
+                "No Codex CLI binary for {} {} in the archive (expected one of: {})",
+                std::env::consts::OS,
+                std::env::consts::ARCH,
+                binary_names().join(", ")
+            )
+        })
+}
+
+/// Run `binary --version`, returning its output
+fn verify(binary: &Path) -> Result<String, String> {
+    let output = silent_command(binary)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("Failed to verify Codex CLI: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Codex CLI binary verification failed: {}",
+            if stderr.trim().is_empty() {
+                "Unknown error"
+            } else {
+                stderr.trim()
+            }
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn versions_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(bin_dir(app)?.join(VERSIONS_DIR))
+}
+
+/// Version number in `codex --version` output ("codex-cli 0.50.0")
+fn version_number(output: &str) -> Result<String, String> {
+    output
+        .split_whitespace()
+        .last()
+        .map(|v| v.trim_start_matches('v'))
+        .filter(|v| is_version(v))
+        .map(str::to_string)
+        .ok_or_else(|| format!("Unrecognized Codex CLI version: {output}"))
+}
+
+/// Dotted version numbers, safe to use as a directory name
+fn is_version(version: &str) -> bool {
+    version.starts_with(|c: char| c.is_ascii_digit())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+}
+
+/// Version installed as `codex`, as recorded by the last install or switch
+fn current_version(app: &impl PathProvider) -> Option<String> {
+    let content = fs::read_to_string(versions_dir(app).ok()?.join(CURRENT_FILE)).ok()?;
+    Some(content.trim().to_string()).filter(|v| is_version(v))
+}
+
+/// Copy `binary` into the kept versions as `version`
+fn keep_version(app: &impl PathProvider, binary: &Path, version: &str) -> Result<(), String> {
+    let dir = versions_dir(app)?.join(version);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {dir:?}: {e}"))?;
+    fs::copy(binary, dir.join(exe_name("codex")))
+        .map_err(|e| format!("Failed to keep Codex CLI {version}: {e}"))?;
+    Ok(())
+}
+
+/// Keep the binary about to be replaced if it isn't kept yet (e.g. one
+/// installed before versions were kept)
+fn keep_replaced(app: &impl PathProvider, target: &Path) {
+    if !target.exists() {
+        return;
+    }
+    let kept = current_version(app).is_some_and(|v| {
+        versions_dir(app).is_ok_and(|dir| dir.join(v).join(exe_name("codex")).exists())
+    });
+    if kept {
+        return;
+    }
+    if let Err(e) = verify(target)
+        .and_then(|output| version_number(&output))
+        .and_then(|version| keep_version(app, target, &version))
+    {
+        log::warn!("Not keeping the replaced Codex CLI: {e}");
+    }
+}
+
+fn set_current_version(app: &impl PathProvider, version: &str) -> Result<(), String> {
+    fs::write(versions_dir(app)?.join(CURRENT_FILE), version)
+        .map_err(|e| format!("Failed to record the active Codex CLI version: {e}"))
+}
+
+/// The Codex CLI versions kept in the helper bin directory, newest first
+pub fn list_installed_versions(
+    app: &impl PathProvider,
+) -> Result<Vec<InstalledCodexVersion>, String> {
+    let dir = versions_dir(app)?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {dir:?}: {e}")),
+    };
+    let current = current_version(app);
+    let mut versions: Vec<InstalledCodexVersion> = entries
+        .flatten()
+        .filter(|entry| entry.path().join(exe_name("codex")).is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|version| is_version(version))
+        .map(|version| InstalledCodexVersion {
+            active: current.as_deref() == Some(version.as_str()),
+            version,
+        })
+        .collect();
+    versions.sort_by(|a, b| {
+        if crate::providers::is_newer_version(&a.version, &b.version) {
+            std::cmp::Ordering::Greater
+        } else if crate::providers::is_newer_version(&b.version, &a.version) {
+            std::cmp::Ordering::Less
+        } else {
+            a.version.cmp(&b.version)
+        }
+    });
+    Ok(versions)
+}
+
+/// Install a kept version as `codex` again, returning its `--version` output
+pub fn switch_version(app: &impl PathProvider, version: &str) -> Result<String, String> {
+    if !is_version(version) {
+        return Err(format!("Invalid Codex CLI version: {version}"));
+    }
+    let kept = versions_dir(app)?.join(version).join(exe_name("codex"));
+    if !kept.is_file() {
+        return Err(format!("Codex CLI {version} isn't installed"));
+    }
+    let output = verify(&kept)?;
+
+    let temp_dir = helper_dir(app)?.join("temp-codex");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).map_err(|e| format!("Failed to create temp directory: {e}"))?;
+    let staged = temp_dir.join(exe_name("codex-staged"));
+    let result = (|| -> Result<(), String> {
+        fs::copy(&kept, &staged).map_err(|e| format!("Failed to copy Codex CLI binary: {e}"))?;
+        let target = bin_dir(app)?.join(exe_name("codex"));
+        keep_replaced(app, &target);
+        fs::rename(&staged, &target)
+            .map_err(|e| format!("Failed to install Codex CLI binary: {e}"))?;
+        crate::platform::motw::release_verified_binary(&target);
+        set_current_version(app, version)
+    })();
+    let _ = fs::remove_dir_all(&temp_dir);
+    result?;
+    log::info!("Switched Codex CLI to {version}");
+    Ok(output)
+}
+
+/// The SHA-256 an archive must match: `explicit` if given, else the one in a
+/// `<archive>.sha256` sidecar, else the archive's line in a checksums file in
+/// the same directory
+fn expected_sha256(path: &Path, explicit: Option<&str>) -> Result<String, String> {
+    if let Some(digest) = explicit.map(str::trim) {
+        return if is_sha256(digest) {
+            Ok(digest.to_lowercase())
+        } else {
+            Err(format!("Not a SHA-256 digest: {digest}"))
+        };
+    }
+
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid archive path: {path:?}"))?;
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".sha256");
+    if let Ok(content) = fs::read_to_string(&sidecar) {
+        let digest = content.split_whitespace().next().unwrap_or_default();
+        return if is_sha256(digest) {
+            Ok(digest.to_lowercase())
+        } else {
+            Err(format!("No SHA-256 digest in {sidecar:?}"))
+        };
+    }
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    CHECKSUM_FILES
+        .iter()
+        .filter_map(|name| fs::read_to_string(dir.join(name)).ok())
+        .find_map(|checksums| checksum_for(&checksums, file_name))
+        .ok_or_else(|| {
+            format!(
+                "No checksum for {file_name}: copy the release's {file_name}.sha256 or checksums file next to it, or enter its SHA-256"
+            )
+        })
+}
+
+/// Install the Codex binary from a release archive, returning its
+/// `--version` output. The archive must match `expected_sha256`, and the
+/// installed binary is only replaced once the new one runs. `on_stage` is
+/// called with each stage, a message and a percentage.
+pub fn install_from_archive(
+    app: &impl PathProvider,
+    archive: &[u8],
+    expected_sha256: &str,
+    mut on_stage: impl FnMut(&str, &str, u8),
+) -> Result<String, String> {
+    on_stage("verify_codex_download", "Verifying archive checksum...", 10);
+    crate::claude_cli::verify_checksum(archive, expected_sha256)
+        .map_err(|e| format!("Codex CLI archive failed verification: {e}"))?;
+
+    let bin_dir = bin_dir(app)?;
+    let temp_dir = helper_dir(app)?.join("temp-codex");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).map_err(|e| format!("Failed to create temp directory: {e}"))?;
+    fs::create_dir_all(&bin_dir)
+        .map_err(|e| format!("Failed to create helper tools directory: {e}"))?;
+
+    let staged = temp_dir.join(exe_name("codex-staged"));
+    let result = (|| -> Result<String, String> {
+        on_stage("extracting", "Extracting archive...", 30);
+        let unpacked = temp_dir.join("unpacked");
+        unpack(archive, &unpacked)?;
+        fs::copy(find_binary(&unpacked)?, &staged)
+            .map_err(|e| format!("Failed to copy Codex CLI binary: {e}"))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))
+                .map_err(|e| format!("Failed to set Codex CLI permissions: {e}"))?;
+        }
+
+        on_stage("verifying", "Verifying binary...", 60);
+        let version = verify(&staged)?;
+        let number = version_number(&version)?;
+        on_stage("installing", "Installing binary...", 80);
+        keep_version(app, &staged, &number)?;
+        let target = bin_dir.join(exe_name("codex"));
+        keep_replaced(app, &target);
+        fs::rename(&staged, &target)
+            .map_err(|e| format!("Failed to install Codex CLI binary: {e}"))?;
+        crate::platform::motw::release_verified_binary(&target);
+        set_current_version(app, &number)?;
+        log::info!("Installed Codex CLI ({version}) into {bin_dir:?}");
+        on_stage("complete", "Installation complete", 100);
+        Ok(version)
+    })();
+    let _ = fs::remove_dir_all(&temp_dir);
+    result
+}
+
+/// Install the Codex binary from a release archive file (see
+/// `expected_sha256` for where its checksum comes from)
+pub fn install_from_file(
+    app: &impl PathProvider,
+    path: &Path,
+    sha256: Option<&str>,
+    on_stage: impl FnMut(&str, &str, u8),
+) -> Result<String, String> {
+    let archive = fs::read(path).map_err(|e| format!("Failed to read {path:?}: {e}"))?;
+    let expected = expected_sha256(path, sha256)?;
+    install_from_archive(app, &archive, &expected, on_stage)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    fn sha256(bytes: &[u8]) -> String {
+        format!("{:x}", <sha2::Sha256 as sha2::Digest>::digest(bytes))
+    }
+
+    fn tar_gz_with(name: &str, content: &str) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, name, content.as_bytes())
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_install_from_archive() {
+        let paths = TempPaths::new();
+        let name = binary_names().remove(0);
+        let archive = tar_gz_with(&name, "#!/bin/sh\necho 'codex-cli 0.50.0'\n");
+        let mut stages = Vec::new();
+        assert_eq!(
+            install_from_archive(&paths, &archive, &sha256(&archive), |stage, _, _| {
+                stages.push(stage.to_string())
+            })
+            .unwrap(),
+            "codex-cli 0.50.0"
+        );
+        assert_eq!(
+            stages,
+            [
+                "verify_codex_download",
+                "extracting",
+                "verifying",
+                "installing",
+                "complete"
+            ]
+        );
+
+        let installed = bin_dir(&paths).unwrap().join("codex");
+        assert_eq!(verify(&installed).unwrap(), "codex-cli 0.50.0");
+        assert!(!helper_dir(&paths).unwrap().join("temp-codex").exists());
+
+        // A broken binary leaves the installed one in place
+        let broken = tar_gz_with("codex", "#!/bin/sh\necho 'bad cpu type' >&2\nexit 1\n");
+        let err =
+            install_from_archive(&paths, &broken, &sha256(&broken), |_, _, _| {}).unwrap_err();
+        assert_eq!(err, "Codex CLI binary verification failed: bad cpu type");
+        assert_eq!(verify(&installed).unwrap(), "codex-cli 0.50.0");
+    }
+
+    #[test]
+    fn test_install_requires_host_binary() {
+        let paths = TempPaths::new();
+        let archive = tar_gz_with("codex-riscv64-unknown-haiku", "#!/bin/sh\n");
+        let err =
+            install_from_archive(&paths, &archive, &sha256(&archive), |_, _, _| {}).unwrap_err();
+        assert!(err.starts_with("No Codex CLI binary for"), "{err}");
+        assert!(!bin_dir(&paths).unwrap().join("codex").exists());
+
+        let err = install_from_file(
+            &paths,
+            Path::new("/nonexistent/codex.tar.gz"),
+            None,
+            |_, _, _| {},
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Failed to read"), "{err}");
+    }
+
+    #[test]
+    fn test_switch_between_kept_versions() {
+        let paths = TempPaths::new();
+        let name = binary_names().remove(0);
+        let install = |version: &str| {
+            let archive = tar_gz_with(&name, &format!("#!/bin/sh\necho 'codex-cli {version}'\n"));
+            install_from_archive(&paths, &archive, &sha256(&archive), |_, _, _| {}).unwrap()
+        };
+        install("0.9.0");
+        install("0.50.0");
+        install("0.10.0");
+
+        let listed = |versions: &[(&str, bool)]| {
+            versions
+                .iter()
+                .map(|(version, active)| InstalledCodexVersion {
+                    version: version.to_string(),
+                    active: *active,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            list_installed_versions(&paths).unwrap(),
+            listed(&[("0.50.0", false), ("0.10.0", true), ("0.9.0", false)])
+        );
+
+        assert_eq!(
+            switch_version(&paths, "0.50.0").unwrap(),
+            "codex-cli 0.50.0"
+        );
+        let installed = bin_dir(&paths).unwrap().join("codex");
+        assert_eq!(verify(&installed).unwrap(), "codex-cli 0.50.0");
+        assert_eq!(
+            list_installed_versions(&paths).unwrap(),
+            listed(&[("0.50.0", true), ("0.10.0", false), ("0.9.0", false)])
+        );
+
+        assert_eq!(
+            switch_version(&paths, "0.1.0").unwrap_err(),
+            "Codex CLI 0.1.0 isn't installed"
+        );
+        assert_eq!(
+            switch_version(&paths, "../bin").unwrap_err(),
+            "Invalid Codex CLI version: ../bin"
+        );
+        assert_eq!(verify(&installed).unwrap(), "codex-cli 0.50.0");
+    }
+
+    #[test]
+    fn test_install_keeps_unrecorded_binary() {
+        let paths = TempPaths::new();
+        let bin = bin_dir(&paths).unwrap();
+        fs::create_dir_all(&bin).unwrap();
+        let installed = bin.join("codex");
+        fs::write(&installed, "#!/bin/sh\necho 'codex-cli 0.40.0'\n").unwrap();
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&installed, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let name = binary_names().remove(0);
+        let archive = tar_gz_with(&name, "#!/bin/sh\necho 'codex-cli 0.50.0'\n");
+        install_from_archive(&paths, &archive, &sha256(&archive), |_, _, _| {}).unwrap();
+        let versions: Vec<String> = list_installed_versions(&paths)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.version)
+            .collect();
+        assert_eq!(versions, ["0.50.0", "0.40.0"]);
+    }
+
+    #[test]
+    fn test_version_number() {
+        assert_eq!(version_number("codex-cli 0.50.0").unwrap(), "0.50.0");
+        assert_eq!(
+            version_number("codex-cli 0.51.0-alpha.2\n").unwrap(),
+            "0.51.0-alpha.2"
+        );
+        assert!(version_number("codex-cli ../../x").is_err());
+        assert!(version_number("").is_err());
+    }
+
+    #[test]
+    fn test_platform_binary_wins_over_plain_codex() {
+        let dir = tempfile::tempdir().unwrap();
+        let names = binary_names();
+        assert_eq!(names.last().unwrap(), "codex");
+        fs::create_dir_all(dir.path().join("a")).unwrap();
+        fs::write(dir.path().join("a/codex"), "").unwrap();
+        fs::create_dir_all(dir.path().join("b")).unwrap();
+        fs::write(dir.path().join("b").join(&names[0]), "").unwrap();
+        assert_eq!(
+            find_binary(dir.path()).unwrap(),
+            dir.path().join("b").join(&names[0])
+        );
+
+        fs::remove_dir_all(dir.path().join("b")).unwrap();
+        assert_eq!(find_binary(dir.path()).unwrap(), dir.path().join("a/codex"));
+    }
+
+    #[test]
+    fn test_install_rejects_checksum_mismatch() {
+        let paths = TempPaths::new();
+        let name = binary_names().remove(0);
+        let archive = tar_gz_with(&name, "#!/bin/sh\necho 'codex-cli 0.50.0'\n");
+        let err =
+            install_from_archive(&paths, &archive, &"0".repeat(64), |_, _, _| {}).unwrap_err();
+        assert!(
+            err.starts_with("Codex CLI archive failed verification: Checksum mismatch"),
+            "{err}"
+        );
+        assert!(!bin_dir(&paths).unwrap().join("codex").exists());
+    }
+
+    #[test]
+    fn test_expected_sha256_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("codex-x86_64-unknown-linux-musl.tar.gz");
+        let digest = "ab".repeat(32);
+
+        let err = expected_sha256(&archive, None).unwrap_err();
+        assert!(err.starts_with("No checksum for codex-x86_64"), "{err}");
+        assert_eq!(
+            expected_sha256(&archive, Some(&digest.to_uppercase())).unwrap(),
+            digest
+        );
+        assert!(expected_sha256(&archive, Some("abc")).is_err());
+
+        fs::write(
+            dir.path().join("SHA256SUMS"),
+            format!("{digest}  codex-x86_64-unknown-linux-musl.tar.gz\n"),
+        )
+        .unwrap();
+        assert_eq!(expected_sha256(&archive, None).unwrap(), digest);
+
+        // A sidecar file takes precedence over a checksums file
+        let sidecar_digest = "cd".repeat(32);
+        fs::write(
+            dir.path()
+                .join("codex-x86_64-unknown-linux-musl.tar.gz.sha256"),
+            format!("{sidecar_digest}  codex-x86_64-unknown-linux-musl.tar.gz\n"),
+        )
+        .unwrap();
+        assert_eq!(expected_sha256(&archive, None).unwrap(), sidecar_digest);
+    }
+}
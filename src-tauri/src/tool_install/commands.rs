@@ -0,0 +1,231 @@
+//! Tauri commands for installing several CLI tools at once
+
+use tauri::AppHandle;
+
+use crate::http_server::EmitExt;
+
+use super::codex;
+use super::completions::{self, CompletionsStatus, Shell};
+use super::helpers::{self, HelperToolStatus};
+use super::node::{self, NodeStatus};
+use super::package_manager::{self, PackageInstallResult};
+use super::shell_integration::{self, IntegrationMode, ShellIntegrationStatus};
+use super::{install_tools_once, ToolInstallResult, TOOLS};
+
+/// Download and install several tools in parallel (see `TOOLS` for names)
+///
+/// Each tool's result is reported separately, so one failed download doesn't
+/// fail the others.
+#[tauri::command]
+pub async fn install_tools(
+    app: AppHandle,
+    tools: Vec<String>,
+) -> Result<Vec<ToolInstallResult>, String> {
+    log::trace!("Installing tools: {tools:?}");
+    if let Some(unknown) = tools.iter().find(|t| !TOOLS.contains(&t.as_str())) {
+        return Err(format!(
+            "Unknown tool: {unknown} (expected one of: {})",
+            TOOLS.join(", ")
+        ));
+    }
+
+    // Both binaries may be in use by Claude sessions
+    let running_sessions = crate::chat::registry::get_running_sessions();
+    if !running_sessions.is_empty() {
+        let count = running_sessions.len();
+        return Err(format!(
+            "Cannot install tools while {} Claude {} running. Please stop all active sessions first.",
+            count,
+            if count == 1 { "session is" } else { "sessions are" }
+        ));
+    }
+
+    let max_bytes_per_sec = crate::load_preferences(app.clone())
+        .await
+        .ok()
+        .and_then(|prefs| prefs.download_speed_limit_kib)
+        .filter(|kib| *kib > 0)
+        .map(|kib| kib * 1024);
+
+    let _power_guard = crate::power::PowerGuard::acquire(&app, "Tool install").await;
+    let results = install_tools_once(&app, &tools, max_bytes_per_sec).await;
+    for result in &results {
+        if let Some(error) = &result.error {
+            crate::announcements::announce_install_failure(&app, &result.tool, error).await;
+        }
+    }
+    Ok(results)
+}
+
+/// Pinned and installed versions of the managed helper tools
+#[tauri::command]
+pub async fn get_helper_tools_status(app: AppHandle) -> Result<Vec<HelperToolStatus>, String> {
+    Ok(helpers::statuses(&app))
+}
+
+/// Node.js runtime that npm-based agent CLIs would use
+#[tauri::command]
+pub async fn detect_node_runtime(app: AppHandle) -> Result<NodeStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || node::detect(&app))
+        .await
+        .map_err(|e| format!("Node.js detection failed: {e}"))
+}
+
+/// `npm install -g` a package into Jean's npm prefix (on sessions' PATH)
+#[tauri::command]
+pub async fn install_npm_package(app: AppHandle, package: String) -> Result<(), String> {
+    log::trace!("Installing npm package: {package}");
+    let _power_guard = crate::power::PowerGuard::acquire(&app, "npm install").await;
+    tauri::async_runtime::spawn_blocking(move || node::npm_install_global(&app, &package))
+        .await
+        .map_err(|e| format!("npm install failed: {e}"))?
+}
+
+/// Install the Codex CLI with Homebrew or winget instead of a direct download
+///
+/// Output streams as `tools:package-manager-output` events. Where Jean can't
+/// run the package manager itself (apt, pacman), the result carries the
+/// command to run instead.
+#[tauri::command]
+pub async fn install_codex_cli_via_package_manager(
+    app: AppHandle,
+) -> Result<PackageInstallResult, String> {
+    log::trace!("Installing Codex CLI via package manager");
+    let _power_guard = crate::power::PowerGuard::acquire(&app, "Codex install").await;
+    tauri::async_runtime::spawn_blocking(move || package_manager::install(&app, "codex"))
+        .await
+        .map_err(|e| format!("Codex install failed: {e}"))?
+}
+
+/// Install the Codex CLI from a release archive on disk (.tar.gz or .zip),
+/// e.g. one copied onto an air-gapped machine, returning its `--version`
+///
+/// The archive is checked against `sha256`, or without it against a
+/// `.sha256` sidecar or checksums file next to it. Progress is emitted as
+/// `codex-cli:install-progress` events.
+#[tauri::command]
+pub async fn install_codex_cli_from_file(
+    app: AppHandle,
+    path: String,
+    sha256: Option<String>,
+) -> Result<String, String> {
+    log::trace!("Installing Codex CLI from {path}");
+    ensure_no_running_sessions("install")?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        codex::install_from_file(
+            &app,
+            std::path::Path::new(&path),
+            sha256.as_deref(),
+            |stage, message, percent| {
+                let progress = codex::CodexInstallProgress {
+                    stage: stage.to_string(),
+                    message: message.to_string(),
+                    percent,
+                };
+                if let Err(e) = app.emit_all("codex-cli:install-progress", &progress) {
+                    log::warn!("Failed to emit install progress: {e}");
+                }
+            },
+        )
+    })
+    .await
+    .map_err(|e| format!("Codex install failed: {e}"))?
+}
+
+/// Codex CLI versions kept from earlier installs, newest first
+#[tauri::command]
+pub async fn list_installed_codex_versions(
+    app: AppHandle,
+) -> Result<Vec<codex::InstalledCodexVersion>, String> {
+    codex::list_installed_versions(&app)
+}
+
+/// Install a kept Codex CLI version as `codex` again, returning its
+/// `--version`
+#[tauri::command]
+pub async fn switch_codex_version(app: AppHandle, version: String) -> Result<String, String> {
+    log::trace!("Switching Codex CLI to {version}");
+    ensure_no_running_sessions("switch")?;
+    tauri::async_runtime::spawn_blocking(move || codex::switch_version(&app, &version))
+        .await
+        .map_err(|e| format!("Codex switch failed: {e}"))?
+}
+
+/// Refuse to replace the Codex binary while sessions may be running it
+fn ensure_no_running_sessions(action: &str) -> Result<(), String> {
+    let running_sessions = crate::chat::registry::get_running_sessions();
+    if running_sessions.is_empty() {
+        return Ok(());
+    }
+    let count = running_sessions.len();
+    Err(format!(
+        "Cannot {action} Codex CLI while {} {} running. Please stop all active sessions first.",
+        count,
+        if count == 1 {
+            "session is"
+        } else {
+            "sessions are"
+        }
+    ))
+}
+
+/// What shell integration currently changed (links and rc files)
+#[tauri::command]
+pub async fn get_shell_integration_status(app: AppHandle) -> ShellIntegrationStatus {
+    shell_integration::load_status(&app)
+}
+
+/// Make Jean-managed CLIs available in plain terminals, via symlinks in
+/// `~/.local/bin` or a PATH block in the shell rc files
+#[tauri::command]
+pub async fn enable_shell_integration(
+    app: AppHandle,
+    mode: IntegrationMode,
+) -> Result<ShellIntegrationStatus, String> {
+    log::trace!("Enabling shell integration ({mode:?})");
+    let home = dirs::home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    shell_integration::enable(&app, &home, mode)
+}
+
+/// Remove the links and PATH blocks added by `enable_shell_integration`
+#[tauri::command]
+pub async fn disable_shell_integration(app: AppHandle) -> Result<ShellIntegrationStatus, String> {
+    log::trace!("Disabling shell integration");
+    shell_integration::disable(&app)
+}
+
+/// Completion scripts and aliases currently installed for managed CLIs
+#[tauri::command]
+pub async fn get_shell_completions_status(app: AppHandle) -> CompletionsStatus {
+    completions::load_status(&app)
+}
+
+/// Install shell completions for the managed CLIs, for `shells` (default:
+/// those in use), optionally with `jean-` prefixed aliases
+#[tauri::command]
+pub async fn install_shell_completions(
+    app: AppHandle,
+    shells: Option<Vec<Shell>>,
+    aliases: Option<bool>,
+) -> Result<CompletionsStatus, String> {
+    log::trace!("Installing shell completions (shells: {shells:?}, aliases: {aliases:?})");
+    let home = dirs::home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    let shells = shells.unwrap_or_else(|| completions::detect_shells(&home));
+    if shells.is_empty() {
+        return Err("No bash, zsh or fish configuration found".to_string());
+    }
+    let aliases = aliases.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || {
+        completions::install(&app, &home, &shells, aliases)
+    })
+    .await
+    .map_err(|e| format!("Installing completions failed: {e}"))?
+}
+
+/// Remove everything `install_shell_completions` added
+#[tauri::command]
+pub async fn uninstall_shell_completions(app: AppHandle) -> Result<CompletionsStatus, String> {
+    log::trace!("Uninstalling shell completions");
+    completions::uninstall(&app)
+}
@@ -0,0 +1,359 @@
+//! Shell completions and `jean-` aliases for managed CLIs
+//!
+//! Completion scripts come from each CLI's own completion subcommand and are
+//! written where the shell loads them per user: bash-completion's
+//! `~/.local/share/bash-completion/completions`, fish's
+//! `~/.config/fish/completions`, and for zsh a directory added to `fpath` by
+//! a marked block in `~/.zshrc`. Aliases (`jean-gh`, `jean-codex`, ...) pin
+//! the managed binaries even when PATH finds another copy first. Everything
+//! installed is recorded in `shell-completions.json` for `uninstall`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::shell_integration::{apply_block, managed_executables, remove_block};
+use crate::platform::silent_command;
+use crate::runtime::PathProvider;
+
+const BEGIN_MARKER: &str = "# >>> Jean completions >>>";
+const END_MARKER: &str = "# <<< Jean completions <<<";
+const MARKERS: (&str, &str) = (BEGIN_MARKER, END_MARKER);
+
+const STATE_FILE: &str = "shell-completions.json";
+
+/// zsh completion functions (relative to home), added to `fpath`
+const ZSH_COMPLETIONS_DIR: &str = ".local/share/jean/zsh-completions";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Bash => "bash",
+            Self::Zsh => "zsh",
+            Self::Fish => "fish",
+        }
+    }
+
+    fn rc_file(self) -> &'static str {
+        match self {
+            Self::Bash => ".bashrc",
+            Self::Zsh => ".zshrc",
+            Self::Fish => ".config/fish/config.fish",
+        }
+    }
+
+    /// Where a completion script for `command` goes (relative to home)
+    fn completion_path(self, command: &str) -> PathBuf {
+        match self {
+            Self::Bash => PathBuf::from(".local/share/bash-completion/completions").join(command),
+            Self::Zsh => Path::new(ZSH_COMPLETIONS_DIR).join(format!("_{command}")),
+            Self::Fish => PathBuf::from(".config/fish/completions").join(format!("{command}.fish")),
+        }
+    }
+}
+
+/// Arguments printing a CLI's completion script for `shell`, if it has one
+fn completion_args(command: &str, shell: Shell) -> Option<Vec<String>> {
+    let shell = shell.name();
+    let args = match command {
+        "gh" => vec!["completion", "-s", shell],
+        "codex" => vec!["completion", shell],
+        "ast-grep" | "sg" => vec!["completions", shell],
+        "fd" => vec!["--gen-completions", shell],
+        "rg" => return Some(vec!["--generate".into(), format!("complete-{shell}")]),
+        _ => return None,
+    };
+    Some(args.into_iter().map(str::to_string).collect())
+}
+
+/// What was installed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompletionsStatus {
+    #[serde(default)]
+    pub shells: Vec<Shell>,
+    /// Completion scripts written
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Rc files holding Jean's completion/alias block
+    #[serde(default)]
+    pub rc_files: Vec<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// CLIs whose completion script couldn't be generated, with the reason
+    #[serde(default)]
+    pub failures: Vec<String>,
+}
+
+fn state_path(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.shared_data_dir()?.join(STATE_FILE))
+}
+
+pub fn load_status(app: &impl PathProvider) -> CompletionsStatus {
+    state_path(app)
+        .and_then(|path| fs::read_to_string(path).map_err(|e| e.to_string()))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_status(app: &impl PathProvider, status: &CompletionsStatus) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(status)
+        .map_err(|e| format!("Failed to serialize completions state: {e}"))?;
+    fs::write(state_path(app)?, content)
+        .map_err(|e| format!("Failed to save completions state: {e}"))
+}
+
+/// Shells in use under `home`: those with an rc file, plus the login shell
+pub fn detect_shells(home: &Path) -> Vec<Shell> {
+    let login = std::env::var("SHELL").unwrap_or_default();
+    [Shell::Bash, Shell::Zsh, Shell::Fish]
+        .into_iter()
+        .filter(|shell| home.join(shell.rc_file()).is_file() || login.ends_with(shell.name()))
+        .collect()
+}
+
+/// Run a CLI and return what it printed
+fn run_generator(binary: &Path, args: &[String]) -> Result<String, String> {
+    let output = silent_command(binary)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {binary:?}: {e}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Marked rc block for `shell`: the zsh `fpath` entry and the aliases
+fn rc_block(shell: Shell, home: &Path, aliases: &[(String, PathBuf)]) -> Option<String> {
+    let mut lines = Vec::new();
+    if shell == Shell::Zsh {
+        lines.push(format!(
+            "fpath=(\"{}\" $fpath)",
+            home.join(ZSH_COMPLETIONS_DIR).display()
+        ));
+        lines.push("autoload -Uz compinit && compinit -i".to_string());
+    }
+    for (alias, binary) in aliases {
+        lines.push(match shell {
+            Shell::Fish => format!("alias {alias} \"{}\"", binary.display()),
+            _ => format!("alias {alias}=\"{}\"", binary.display()),
+        });
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{BEGIN_MARKER}\n# Added by Jean; remove with Settings > Shell integration\n{}\n{END_MARKER}\n",
+        lines.join("\n")
+    ))
+}
+
+/// Write completion scripts (and optionally aliases) for `shells` under
+/// `home`, replacing an earlier install
+fn install_with(
+    app: &impl PathProvider,
+    home: &Path,
+    shells: &[Shell],
+    aliases: bool,
+    generate: impl Fn(&Path, &[String]) -> Result<String, String>,
+) -> Result<CompletionsStatus, String> {
+    uninstall(app)?;
+    let executables = managed_executables(app);
+    let commands: Vec<(String, &PathBuf)> = executables
+        .iter()
+        .filter_map(|path| Some((path.file_stem()?.to_string_lossy().to_string(), path)))
+        .collect();
+    let alias_targets: Vec<(String, PathBuf)> = if aliases {
+        commands
+            .iter()
+            .map(|(command, path)| (format!("jean-{command}"), (*path).clone()))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut status = CompletionsStatus {
+        shells: shells.to_vec(),
+        aliases: alias_targets.iter().map(|(a, _)| a.clone()).collect(),
+        ..Default::default()
+    };
+    for &shell in shells {
+        for (command, binary) in &commands {
+            let Some(args) = completion_args(command, shell) else {
+                continue;
+            };
+            let script = match generate(binary, &args) {
+                Ok(script) if !script.trim().is_empty() => script,
+                Ok(_) => {
+                    status
+                        .failures
+                        .push(format!("{command} ({}): no output", shell.name()));
+                    continue;
+                }
+                Err(e) => {
+                    status
+                        .failures
+                        .push(format!("{command} ({}): {e}", shell.name()));
+                    continue;
+                }
+            };
+            let path = home.join(shell.completion_path(command));
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|e| format!("Failed to create {dir:?}: {e}"))?;
+            }
+            fs::write(&path, script).map_err(|e| format!("Failed to write {path:?}: {e}"))?;
+            status.files.push(path.to_string_lossy().to_string());
+        }
+
+        if let Some(block) = rc_block(shell, home, &alias_targets) {
+            let rc = home.join(shell.rc_file());
+            if let Some(dir) = rc.parent() {
+                fs::create_dir_all(dir).map_err(|e| format!("Failed to create {dir:?}: {e}"))?;
+            }
+            // Only a missing rc file counts as empty; anything else would get
+            // overwritten with just our block
+            let content = match fs::read_to_string(&rc) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(format!("Failed to read {rc:?}: {e}")),
+            };
+            fs::write(&rc, apply_block(&content, &block, MARKERS))
+                .map_err(|e| format!("Failed to update {rc:?}: {e}"))?;
+            status.rc_files.push(rc.to_string_lossy().to_string());
+        }
+    }
+
+    save_status(app, &status)?;
+    log::info!(
+        "Installed {} completion script(s) and {} alias(es)",
+        status.files.len(),
+        status.aliases.len()
+    );
+    Ok(status)
+}
+
+/// Install completions (and optionally `jean-` aliases) for the managed CLIs
+pub fn install(
+    app: &impl PathProvider,
+    home: &Path,
+    shells: &[Shell],
+    aliases: bool,
+) -> Result<CompletionsStatus, String> {
+    if cfg!(windows) {
+        return Err("Shell completions aren't available on Windows yet".to_string());
+    }
+    install_with(app, home, shells, aliases, run_generator)
+}
+
+/// Remove every completion script and rc block `install` added
+pub fn uninstall(app: &impl PathProvider) -> Result<CompletionsStatus, String> {
+    let previous = load_status(app);
+    for file in &previous.files {
+        match fs::remove_file(file) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to remove {file}: {e}")),
+        }
+    }
+    for rc in &previous.rc_files {
+        let Ok(content) = fs::read_to_string(rc) else {
+            continue;
+        };
+        if let Some(cleaned) = remove_block(&content, MARKERS) {
+            fs::write(rc, cleaned).map_err(|e| format!("Failed to update {rc}: {e}"))?;
+        }
+    }
+    let status = CompletionsStatus::default();
+    save_status(app, &status)?;
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_completion_args() {
+        assert_eq!(
+            completion_args("gh", Shell::Zsh).unwrap(),
+            ["completion", "-s", "zsh"]
+        );
+        assert_eq!(
+            completion_args("rg", Shell::Fish).unwrap(),
+            ["--generate", "complete-fish"]
+        );
+        assert!(completion_args("node", Shell::Bash).is_none());
+    }
+
+    #[test]
+    fn test_install_and_uninstall() {
+        let paths = TempPaths::new();
+        let home = tempfile::tempdir().unwrap();
+        let home = home.path();
+        // The Claude CLI has no completion command; gh does
+        let gh = crate::gh_cli::config::get_gh_cli_binary_path(&paths).unwrap();
+        fs::create_dir_all(gh.parent().unwrap()).unwrap();
+        fs::write(&gh, "").unwrap();
+        fs::write(home.join(".zshrc"), "export EDITOR=vim\n").unwrap();
+
+        let generate = |binary: &Path, args: &[String]| -> Result<String, String> {
+            match args.last().map(String::as_str) {
+                Some("fish") => Err("unsupported shell".to_string()),
+                _ => Ok(format!("# {} {}\n", binary.display(), args.join(" "))),
+            }
+        };
+        let status =
+            install_with(&paths, home, &[Shell::Zsh, Shell::Fish], true, generate).unwrap();
+
+        let command = gh.file_stem().unwrap().to_string_lossy().to_string();
+        let zsh_script = home.join(Shell::Zsh.completion_path(&command));
+        assert_eq!(status.files, [zsh_script.to_string_lossy().to_string()]);
+        assert!(fs::read_to_string(&zsh_script)
+            .unwrap()
+            .ends_with("completion -s zsh\n"));
+        assert_eq!(status.failures.len(), 1);
+        assert_eq!(status.aliases, [format!("jean-{command}")]);
+
+        let zshrc = fs::read_to_string(home.join(".zshrc")).unwrap();
+        assert!(zshrc.contains("fpath=("));
+        assert!(zshrc.contains(&format!("alias jean-{command}=")));
+        let fish = fs::read_to_string(home.join(Shell::Fish.rc_file())).unwrap();
+        assert!(fish.contains(&format!("alias jean-{command} ")));
+
+        uninstall(&paths).unwrap();
+        assert!(!zsh_script.exists());
+        assert_eq!(
+            fs::read_to_string(home.join(".zshrc")).unwrap(),
+            "export EDITOR=vim\n"
+        );
+        assert!(load_status(&paths).files.is_empty());
+    }
+
+    #[test]
+    fn test_install_keeps_unreadable_rc_file() {
+        let paths = TempPaths::new();
+        let home = tempfile::tempdir().unwrap();
+        let home = home.path();
+        let gh = crate::gh_cli::config::get_gh_cli_binary_path(&paths).unwrap();
+        fs::create_dir_all(gh.parent().unwrap()).unwrap();
+        fs::write(&gh, "").unwrap();
+        // Not valid UTF-8, so it can't be read as a string
+        let original = [b'#', 0xff, 0xfe, b'\n'];
+        fs::write(home.join(".zshrc"), original).unwrap();
+
+        let generate = |_: &Path, _: &[String]| Ok("# script\n".to_string());
+        assert!(install_with(&paths, home, &[Shell::Zsh], true, generate).is_err());
+        assert_eq!(fs::read(home.join(".zshrc")).unwrap(), original);
+    }
+}
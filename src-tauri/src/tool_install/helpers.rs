@@ -0,0 +1,326 @@
+//! Managed helper tools (ripgrep, fd, ast-grep)
+//!
+//! Agents search much better with these available, so Jean can install pinned
+//! releases into `{app_data}/helper-tools/bin/`, which `session_path` puts on
+//! the PATH of every spawned Claude CLI.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::PathProvider;
+
+pub(crate) const HELPER_TOOLS_DIR: &str = "helper-tools";
+const MANIFEST_FILE: &str = "versions.json";
+
+/// A pinned helper tool release
+#[derive(Debug)]
+pub struct HelperTool {
+    /// Name used with `install_tools`
+    pub name: &'static str,
+    pub version: &'static str,
+    /// Executables copied out of the release archive
+    binaries: &'static [&'static str],
+}
+
+pub const HELPERS: &[HelperTool] = &[
+    HelperTool {
+        name: "ripgrep",
+        version: "14.1.1",
+        binaries: &["rg"],
+    },
+    HelperTool {
+        name: "fd",
+        version: "10.2.0",
+        binaries: &["fd"],
+    },
+    HelperTool {
+        name: "ast-grep",
+        version: "0.36.2",
+        binaries: &["ast-grep", "sg"],
+    },
+];
+
+/// Install state of a helper tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HelperToolStatus {
+    pub name: String,
+    /// Version Jean installs
+    pub pinned_version: String,
+    /// Version currently installed, if any
+    pub installed_version: Option<String>,
+    /// Absolute path of the main executable, if installed
+    pub path: Option<String>,
+}
+
+pub fn find_helper(name: &str) -> Option<&'static HelperTool> {
+    HELPERS.iter().find(|h| h.name == name)
+}
+
+/// Rust target triple of the release to download. ripgrep and fd publish
+/// static musl builds for x86_64 Linux; ast-grep only has glibc ones.
+fn target(tool: &HelperTool) -> Result<&'static str, String> {
+    let _ = tool;
+
+    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+    {
+        return Ok("aarch64-apple-darwin");
+    }
+
+    #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+    {
+        return Ok("x86_64-apple-darwin");
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    {
+        return Ok(if tool.name == "ast-grep" {
+            "x86_64-unknown-linux-gnu"
+        } else {
+            "x86_64-unknown-linux-musl"
+        });
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    {
+        return Ok("aarch64-unknown-linux-gnu");
+    }
+
+    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+    {
+        return Ok("x86_64-pc-windows-msvc");
+    }
+
+    #[allow(unreachable_code)]
+    Err("Unsupported platform".to_string())
+}
+
+/// Download URL of a tool's release archive for this platform
+pub fn asset_url(tool: &HelperTool) -> Result<String, String> {
+    let target = target(tool)?;
+    let ext = if cfg!(windows) { "zip" } else { "tar.gz" };
+    let version = tool.version;
+    Ok(match tool.name {
+        "ripgrep" => format!(
+            "https://github.com/BurntSushi/ripgrep/releases/download/{version}/ripgrep-{version}-{target}.{ext}"
+        ),
+        "fd" => format!(
+            "https://github.com/sharkdp/fd/releases/download/v{version}/fd-v{version}-{target}.{ext}"
+        ),
+        "ast-grep" => format!(
+            "https://github.com/ast-grep/ast-grep/releases/download/{version}/app-{target}.zip"
+        ),
+        other => return Err(format!("Unknown helper tool: {other}")),
+    })
+}
+
+pub(super) fn helper_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.shared_data_dir()?.join(HELPER_TOOLS_DIR))
+}
+
+/// Directory holding the installed executables
+pub fn bin_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(helper_dir(app)?.join("bin"))
+}
+
+pub(super) fn exe_name(binary: &str) -> String {
+    if cfg!(windows) {
+        format!("{binary}.exe")
+    } else {
+        binary.to_string()
+    }
+}
+
+/// Installed versions, keyed by tool name
+fn load_manifest(app: &impl PathProvider) -> BTreeMap<String, String> {
+    helper_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(MANIFEST_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(
+    app: &impl PathProvider,
+    manifest: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize helper tool versions: {e}"))?;
+    fs::write(helper_dir(app)?.join(MANIFEST_FILE), content)
+        .map_err(|e| format!("Failed to save helper tool versions: {e}"))
+}
+
+/// Unpack a zip or tar.gz archive into `dest`
+pub(super) fn unpack(archive: &[u8], dest: &Path) -> Result<(), String> {
+    // Zip archives start with "PK", gzip with 0x1f 0x8b
+    if archive.starts_with(b"PK") {
+        zip::ZipArchive::new(Cursor::new(archive))
+            .and_then(|mut zip| zip.extract(dest))
+            .map_err(|e| format!("Failed to extract zip archive: {e}"))
+    } else {
+        let decoder = flate2::read::GzDecoder::new(Cursor::new(archive));
+        tar::Archive::new(decoder)
+            .unpack(dest)
+            .map_err(|e| format!("Failed to extract tar.gz archive: {e}"))
+    }
+}
+
+/// Find a file by name anywhere under `dir`
+pub(super) fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if let Some(found) = find_file(&path, name) {
+                return Some(found);
+            }
+        } else if entry.file_name() == name {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Copy a tool's executables from an unpacked archive into `bin_dir`
+fn copy_binaries(tool: &HelperTool, unpacked: &Path, bin_dir: &Path) -> Result<(), String> {
+    for binary in tool.binaries {
+        let name = exe_name(binary);
+        let extracted = find_file(unpacked, &name)
+            .ok_or_else(|| format!("{name} not found in {} archive", tool.name))?;
+        let target = bin_dir.join(&name);
+        fs::copy(&extracted, &target).map_err(|e| format!("Failed to copy {name}: {e}"))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&target, fs::Permissions::from_mode(0o755))
+                .map_err(|e| format!("Failed to set permissions on {name}: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Install a downloaded release archive of a helper tool
+pub fn install_helper(
+    app: &impl PathProvider,
+    tool: &HelperTool,
+    archive: &[u8],
+) -> Result<(), String> {
+    let bin_dir = bin_dir(app)?;
+    let temp_dir = helper_dir(app)?.join(format!("temp-{}", tool.name));
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).map_err(|e| format!("Failed to create temp directory: {e}"))?;
+    fs::create_dir_all(&bin_dir)
+        .map_err(|e| format!("Failed to create helper tools directory: {e}"))?;
+
+    let result = unpack(archive, &temp_dir).and_then(|_| copy_binaries(tool, &temp_dir, &bin_dir));
+    let _ = fs::remove_dir_all(&temp_dir);
+    result?;
+
+    let mut manifest = load_manifest(app);
+    manifest.insert(tool.name.to_string(), tool.version.to_string());
+    save_manifest(app, &manifest)?;
+    log::info!(
+        "Installed {} {} into {:?}",
+        tool.name,
+        tool.version,
+        bin_dir
+    );
+    Ok(())
+}
+
+/// Install state of every helper tool
+pub fn statuses(app: &impl PathProvider) -> Vec<HelperToolStatus> {
+    let manifest = load_manifest(app);
+    let bin_dir = bin_dir(app).ok();
+    HELPERS
+        .iter()
+        .map(|tool| {
+            let path = bin_dir
+                .as_ref()
+                .map(|dir| dir.join(exe_name(tool.binaries[0])))
+                .filter(|path| path.exists());
+            HelperToolStatus {
+                name: tool.name.to_string(),
+                pinned_version: tool.version.to_string(),
+                installed_version: path.as_ref().and_then(|_| manifest.get(tool.name).cloned()),
+                path: path.map(|p| p.to_string_lossy().to_string()),
+            }
+        })
+        .collect()
+}
+
+/// The helper tools directory, if any helper tool is installed
+pub fn installed_bin_dir(app: &impl PathProvider) -> Option<PathBuf> {
+    let bin_dir = bin_dir(app).ok()?;
+    fs::read_dir(&bin_dir).ok()?.next()?;
+    Some(bin_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+    use std::io::Write;
+
+    fn zip_with(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut buf = Cursor::new(Vec::new());
+        let mut zip = zip::ZipWriter::new(&mut buf);
+        for (name, content) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        buf.into_inner()
+    }
+
+    #[test]
+    fn test_install_helper_and_session_path() {
+        let paths = TempPaths::new();
+        assert_eq!(installed_bin_dir(&paths), None);
+
+        let fd = find_helper("fd").unwrap();
+        let name = exe_name("fd");
+        let fd_path = format!("fd-v10.2.0/{name}");
+        let archive = zip_with(&[
+            (fd_path.as_str(), "#!/bin/sh\n"),
+            ("fd-v10.2.0/README.md", "readme"),
+        ]);
+        install_helper(&paths, fd, &archive).unwrap();
+
+        let status = statuses(&paths);
+        let fd_status = status.iter().find(|s| s.name == "fd").unwrap();
+        assert_eq!(fd_status.installed_version.as_deref(), Some("10.2.0"));
+        assert!(fd_status.path.as_ref().unwrap().ends_with(&name));
+        let rg_status = status.iter().find(|s| s.name == "ripgrep").unwrap();
+        assert_eq!(rg_status.installed_version, None);
+
+        let path = crate::tool_install::session_path(&paths).unwrap();
+        let first = std::env::split_paths(&path).next().unwrap();
+        assert_eq!(first, bin_dir(&paths).unwrap());
+        assert!(!bin_dir(&paths).unwrap().join("README.md").exists());
+    }
+
+    #[test]
+    fn test_install_helper_requires_all_binaries() {
+        let paths = TempPaths::new();
+        let ast_grep = find_helper("ast-grep").unwrap();
+        let ast_grep_name = exe_name("ast-grep");
+        let archive = zip_with(&[(ast_grep_name.as_str(), "bin")]);
+        let err = install_helper(&paths, ast_grep, &archive).unwrap_err();
+        assert!(err.contains("not found"), "{err}");
+        assert!(statuses(&paths)[2].installed_version.is_none());
+    }
+
+    #[test]
+    fn test_asset_urls_use_pinned_versions() {
+        for tool in HELPERS {
+            let url = asset_url(tool).unwrap();
+            assert!(url.starts_with("https://github.com/"), "{url}");
+            assert!(url.contains(tool.version), "{url}");
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Installing several CLI tools in one go
+//!
+//! Onboarding installs the Claude CLI, the GitHub CLI, the helper tools
+//! (see `helpers`), a Node.js runtime (see `node`) and a standalone Python
+//! (see `python_env`) together. Rather than running their installers back to
+//! back, the release assets of all requested tools are resolved first,
+//! downloaded in parallel by `http_client::downloads` (capped by the
+//! `download_speed_limit_kib` preference), and then installed one by one with
+//! each tool's own verification steps. Download progress is emitted as a single
+//! `tools:download-progress` event keyed by tool name.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::runtime::PathProvider;
+
+use crate::http_client::downloads::{download_all, Asset, DownloadProgress};
+use crate::http_server::EmitExt;
+
+pub mod codex;
+pub mod commands;
+pub mod completions;
+pub mod helpers;
+pub mod node;
+pub mod package_manager;
+pub mod shell_integration;
+
+/// Tools `install_tools` knows how to install
+pub const TOOLS: &[&str] = &[
+    "claude", "gh", "ripgrep", "fd", "ast-grep", "node", "python",
+];
+
+/// Outcome of installing one tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolInstallResult {
+    pub tool: String,
+    pub installed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// PATH for spawned sessions with Jean's managed tool directories first, or
+/// None when nothing is installed
+pub fn session_path(app: &impl PathProvider) -> Option<String> {
+    let managed: Vec<_> = helpers::installed_bin_dir(app)
+        .into_iter()
+        .chain(node::bin_dirs(app))
+        .chain(crate::python_env::shim_dir(app).ok().filter(|d| d.is_dir()))
+        .collect();
+    if managed.is_empty() {
+        return None;
+    }
+
+    let current = std::env::var_os("PATH").unwrap_or_default();
+    let paths = managed.into_iter().chain(std::env::split_paths(&current));
+    std::env::join_paths(paths)
+        .ok()
+        .map(|p| p.to_string_lossy().into_owned())
+}
+
+/// A resolved release, remembering what's needed to install it
+enum Release {
+    Claude { checksum: String },
+    Gh(crate::gh_cli::GhDownload),
+    Helper(&'static helpers::HelperTool),
+    Node(node::NodeDownload),
+    Python(crate::python_env::PythonDownload),
+}
+
+async fn resolve(tool: &str) -> Result<(String, Release), String> {
+    match tool {
+        "claude" => {
+            let (url, checksum) = crate::claude_cli::resolve_claude_download(None).await?;
+            Ok((url, Release::Claude { checksum }))
+        }
+        "gh" => {
+            let download = crate::gh_cli::resolve_gh_download(None).await?;
+            Ok((download.url.clone(), Release::Gh(download)))
+        }
+        "node" => {
+            let download = node::resolve_download().await?;
+            Ok((download.url.clone(), Release::Node(download)))
+        }
+        "python" => {
+            let download = crate::python_env::resolve_download().await?;
+            Ok((download.url.clone(), Release::Python(download)))
+        }
+        _ => {
+            let helper =
+                helpers::find_helper(tool).ok_or_else(|| format!("Unknown tool: {tool}"))?;
+            Ok((helpers::asset_url(helper)?, Release::Helper(helper)))
+        }
+    }
+}
+
+fn install(app: &AppHandle, release: &Release, content: &[u8]) -> Result<(), String> {
+    match release {
+        Release::Claude { checksum } => {
+            crate::claude_cli::verify_and_install_claude_binary(app, content, checksum)
+        }
+        Release::Gh(download) => crate::gh_cli::install_gh_archive(app, download, content),
+        Release::Helper(helper) => helpers::install_helper(app, helper, content),
+        Release::Node(download) => node::install_runtime(app, download, content),
+        Release::Python(download) => crate::python_env::install_runtime(app, download, content),
+    }
+}
+
+/// Install the latest release of each tool, downloading them in parallel
+pub(crate) async fn install_tools_once(
+    app: &AppHandle,
+    tools: &[String],
+    max_bytes_per_sec: Option<u64>,
+) -> Vec<ToolInstallResult> {
+    let mut results: Vec<ToolInstallResult> = tools
+        .iter()
+        .map(|tool| ToolInstallResult {
+            tool: tool.clone(),
+            installed: false,
+            error: None,
+        })
+        .collect();
+
+    let mut assets = Vec::new();
+    let mut releases = Vec::new();
+    for (i, tool) in tools.iter().enumerate() {
+        match resolve(tool).await {
+            Ok((url, release)) => {
+                assets.push(Asset {
+                    tool: tool.clone(),
+                    url,
+                });
+                releases.push((i, release));
+            }
+            Err(e) => results[i].error = Some(e),
+        }
+    }
+
+    let downloads = download_all(&assets, max_bytes_per_sec, |progress: &DownloadProgress| {
+        if let Err(e) = app.emit_all("tools:download-progress", progress) {
+            log::warn!("Failed to emit download progress: {e}");
+        }
+    })
+    .await;
+
+    for ((i, release), content) in releases.into_iter().zip(downloads) {
+        match content.and_then(|content| install(app, &release, &content)) {
+            Ok(()) => results[i].installed = true,
+            Err(e) => results[i].error = Some(e),
+        }
+    }
+
+    for result in &results {
+        match &result.error {
+            Some(e) => log::warn!("Failed to install {}: {e}", result.tool),
+            None => log::info!("Installed {}", result.tool),
+        }
+    }
+    results
+}
@@ -0,0 +1,351 @@
+//! Node.js runtime for agent CLIs that only ship through npm
+//!
+//! `detect` prefers the managed runtime in `{app_data}/node-runtime/` and
+//! falls back to `node`/`npm` from PATH, reporting whether the version is
+//! recent enough. The managed runtime is an official build from nodejs.org,
+//! installed through `install_tools` like any other tool and verified against
+//! the release's `SHASUMS256.txt`. Packages are installed with
+//! `npm install -g` into a Jean-scoped prefix (`{app_data}/npm-global/`),
+//! whose bin directory is added to sessions' PATH next to the runtime's.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::platform::silent_command;
+use crate::runtime::PathProvider;
+
+/// Version installed as the managed runtime
+pub const NODE_VERSION: &str = "22.11.0";
+
+/// Oldest Node.js major version agent CLIs are expected to run on
+pub const MIN_NODE_MAJOR: u32 = 18;
+
+const NODE_DIST: &str = "https://nodejs.org/dist";
+pub(crate) const RUNTIME_DIR: &str = "node-runtime";
+const NPM_PREFIX_DIR: &str = "npm-global";
+
+/// Which Node.js would be used, and whether it's usable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeStatus {
+    pub node_path: Option<String>,
+    pub npm_path: Option<String>,
+    /// e.g. "22.11.0"
+    pub version: Option<String>,
+    /// Whether this is Jean's managed runtime
+    pub managed: bool,
+    /// Node and npm are present and node is at least `min_major`
+    pub suitable: bool,
+    pub min_major: u32,
+}
+
+/// The managed runtime's release archive, resolved for this platform
+pub struct NodeDownload {
+    pub url: String,
+    pub file_name: String,
+    pub checksum: String,
+}
+
+fn platform() -> Result<&'static str, String> {
+    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+    {
+        return Ok("darwin-arm64");
+    }
+
+    #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+    {
+        return Ok("darwin-x64");
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    {
+        return Ok("linux-x64");
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    {
+        return Ok("linux-arm64");
+    }
+
+    #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+    {
+        return Ok("win-x64");
+    }
+
+    #[allow(unreachable_code)]
+    Err("Unsupported platform".to_string())
+}
+
+/// Archive name of the official build, e.g. node-v22.11.0-linux-x64.tar.gz
+fn archive_name(version: &str, platform: &str) -> String {
+    let ext = if platform.starts_with("win") {
+        "zip"
+    } else {
+        "tar.gz"
+    };
+    format!("node-v{version}-{platform}.{ext}")
+}
+
+pub fn runtime_dir(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.shared_data_dir()?.join(RUNTIME_DIR))
+}
+
+pub fn npm_prefix(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.shared_data_dir()?.join(NPM_PREFIX_DIR))
+}
+
+/// Directory holding node/npm inside a Node.js install (bin/ except on Windows)
+fn bin_dir_of(root: &Path) -> PathBuf {
+    if cfg!(windows) {
+        root.to_path_buf()
+    } else {
+        root.join("bin")
+    }
+}
+
+/// Managed directories sessions should find executables in, if present
+pub fn bin_dirs(app: &impl PathProvider) -> Vec<PathBuf> {
+    [runtime_dir(app), npm_prefix(app)]
+        .into_iter()
+        .flatten()
+        .map(|root| bin_dir_of(&root))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+fn exe(dir: &Path, name: &str) -> PathBuf {
+    match (cfg!(windows), name) {
+        (true, "node") => dir.join("node.exe"),
+        (true, _) => dir.join(format!("{name}.cmd")),
+        _ => dir.join(name),
+    }
+}
+
+/// Parse `node --version` output ("v22.11.0") into its major version
+fn parse_major(version: &str) -> Option<u32> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn node_version(node: &Path) -> Option<String> {
+    let output = silent_command(node).arg("--version").output().ok()?;
+    output.status.success().then(|| {
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .trim_start_matches('v')
+            .to_string()
+    })
+}
+
+/// Find the Node.js runtime agent CLIs would use
+pub fn detect(app: &impl PathProvider) -> NodeStatus {
+    let managed_bin = runtime_dir(app).ok().map(|root| bin_dir_of(&root));
+    let managed = managed_bin
+        .as_ref()
+        .map(|dir| (exe(dir, "node"), exe(dir, "npm")))
+        .filter(|(node, _)| node.exists());
+
+    let (node, npm, is_managed) = match managed {
+        Some((node, npm)) => (Some(node), Some(npm).filter(|p| p.exists()), true),
+        None => (which::which("node").ok(), which::which("npm").ok(), false),
+    };
+
+    let version = node.as_deref().and_then(node_version);
+    let suitable = npm.is_some()
+        && version
+            .as_deref()
+            .and_then(parse_major)
+            .is_some_and(|major| major >= MIN_NODE_MAJOR);
+
+    NodeStatus {
+        node_path: node.map(|p| p.to_string_lossy().into_owned()),
+        npm_path: npm.map(|p| p.to_string_lossy().into_owned()),
+        version,
+        managed: is_managed,
+        suitable,
+        min_major: MIN_NODE_MAJOR,
+    }
+}
+
+/// Find an archive's checksum in a SHASUMS256.txt listing
+fn checksum_for(shasums: &str, file_name: &str) -> Option<String> {
+    shasums.lines().find_map(|line| {
+        let (hash, name) = line.split_once(char::is_whitespace)?;
+        (name.trim() == file_name).then(|| hash.to_string())
+    })
+}
+
+/// Resolve the official build of `NODE_VERSION` for this platform
+pub async fn resolve_download() -> Result<NodeDownload, String> {
+    let file_name = archive_name(NODE_VERSION, platform()?);
+    let base = format!("{NODE_DIST}/v{NODE_VERSION}");
+
+    let client = crate::http_client::client_builder()
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+    let response = client
+        .get(format!("{base}/SHASUMS256.txt"))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch Node.js checksums: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to fetch Node.js checksums: HTTP {}",
+            response.status()
+        ));
+    }
+    let shasums = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read Node.js checksums: {e}"))?;
+    let checksum = checksum_for(&shasums, &file_name)
+        .ok_or_else(|| format!("No checksum found for {file_name}"))?;
+
+    Ok(NodeDownload {
+        url: format!("{base}/{file_name}"),
+        file_name,
+        checksum,
+    })
+}
+
+/// Verify and unpack a downloaded runtime archive into `node-runtime/`,
+/// replacing any previous managed runtime
+pub fn install_runtime(
+    app: &impl PathProvider,
+    download: &NodeDownload,
+    archive: &[u8],
+) -> Result<(), String> {
+    let computed = format!("{:x}", Sha256::digest(archive));
+    if computed != download.checksum.to_lowercase() {
+        return Err(format!(
+            "Checksum mismatch: expected {}, got {computed}",
+            download.checksum
+        ));
+    }
+
+    let runtime_dir = runtime_dir(app)?;
+    let temp_dir = runtime_dir.with_extension("partial");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).map_err(|e| format!("Failed to create temp directory: {e}"))?;
+
+    let result = super::helpers::unpack(archive, &temp_dir).and_then(|_| {
+        // Archives hold a single node-v{version}-{platform}/ directory
+        let root_name = download
+            .file_name
+            .trim_end_matches(".tar.gz")
+            .trim_end_matches(".zip");
+        let root = temp_dir.join(root_name);
+        if !exe(&bin_dir_of(&root), "node").exists() {
+            return Err(format!("node not found in {}", download.file_name));
+        }
+        let _ = fs::remove_dir_all(&runtime_dir);
+        fs::rename(&root, &runtime_dir)
+            .map_err(|e| format!("Failed to install Node.js runtime: {e}"))
+    });
+    let _ = fs::remove_dir_all(&temp_dir);
+    result?;
+
+    log::info!("Installed Node.js {NODE_VERSION} into {runtime_dir:?}");
+    Ok(())
+}
+
+/// `npm install -g` a package into the Jean-scoped prefix
+pub fn npm_install_global(app: &impl PathProvider, package: &str) -> Result<(), String> {
+    // Only plain package specs; this ends up on a command line
+    if package.is_empty()
+        || package.starts_with('-')
+        || !package
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "@/._-~^".contains(c))
+    {
+        return Err(format!("Invalid npm package name: {package}"));
+    }
+
+    let status = detect(app);
+    if !status.suitable {
+        return Err(match status.version {
+            Some(v) => format!("Node.js {v} is too old (need {MIN_NODE_MAJOR} or newer)"),
+            None => "Node.js and npm are not installed".to_string(),
+        });
+    }
+    let npm = PathBuf::from(status.npm_path.unwrap_or_default());
+    let prefix = npm_prefix(app)?;
+    fs::create_dir_all(&prefix).map_err(|e| format!("Failed to create npm prefix: {e}"))?;
+
+    // npm's scripts run `node` from PATH, so put the chosen runtime first
+    let mut cmd = silent_command(&npm);
+    cmd.args(["install", "--global", "--prefix"])
+        .arg(&prefix)
+        .arg(package);
+    if let Some(node_dir) = status
+        .node_path
+        .as_deref()
+        .map(Path::new)
+        .and_then(Path::parent)
+    {
+        let current = std::env::var_os("PATH").unwrap_or_default();
+        let paths = std::iter::once(node_dir.to_path_buf()).chain(std::env::split_paths(&current));
+        if let Ok(path) = std::env::join_paths(paths) {
+            cmd.env("PATH", path);
+        }
+    }
+
+    log::trace!("Installing npm package {package} into {prefix:?}");
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run npm: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "npm install failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    log::info!("Installed npm package {package}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_major() {
+        assert_eq!(parse_major("v22.11.0\n"), Some(22));
+        assert_eq!(parse_major("18.0.0"), Some(18));
+        assert_eq!(parse_major("garbage"), None);
+    }
+
+    #[test]
+    fn test_checksum_for() {
+        let shasums = "\
+aaaa  node-v22.11.0-darwin-arm64.tar.gz
+bbbb  node-v22.11.0-linux-x64.tar.gz
+cccc  node-v22.11.0-linux-x64.tar.xz
+";
+        assert_eq!(
+            checksum_for(shasums, "node-v22.11.0-linux-x64.tar.gz").as_deref(),
+            Some("bbbb")
+        );
+        assert_eq!(checksum_for(shasums, "node-v22.11.0-win-x64.zip"), None);
+        assert_eq!(
+            archive_name("22.11.0", "win-x64"),
+            "node-v22.11.0-win-x64.zip"
+        );
+    }
+
+    #[test]
+    fn test_npm_install_rejects_flags() {
+        let paths = crate::test_support::runtime::TempPaths::new();
+        for package in ["", "--prefix=/", "pkg; rm -rf /"] {
+            let err = npm_install_global(&paths, package).unwrap_err();
+            assert!(err.starts_with("Invalid npm package name"), "{err}");
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! Installing agent CLIs through the system package manager
+//!
+//! An alternative to Jean's own downloads for machines where IT forbids
+//! ad-hoc binaries: Homebrew on macOS (and on Linux when present), winget on
+//! Windows. Their output is streamed line by line as
+//! `tools:package-manager-output` events. apt and pacman need root, so on
+//! Linux without Homebrew Jean only suggests the command to run.
+
+use std::io::{BufRead, BufReader};
+use std::process::Stdio;
+
+use serde::{Deserialize, Serialize};
+
+use crate::platform::silent_command;
+use crate::runtime::EventSink;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    Brew,
+    Winget,
+    Apt,
+    Pacman,
+}
+
+impl PackageManager {
+    fn program(self) -> &'static str {
+        match self {
+            Self::Brew => "brew",
+            Self::Winget => "winget",
+            Self::Apt => "apt",
+            Self::Pacman => "pacman",
+        }
+    }
+
+    /// Whether Jean can run it without a password prompt
+    fn unattended(self) -> bool {
+        matches!(self, Self::Brew | Self::Winget)
+    }
+}
+
+/// Package names of a tool per package manager
+struct Package {
+    tool: &'static str,
+    /// Binary found on PATH once installed
+    binary: &'static str,
+    brew: Option<&'static str>,
+    winget: Option<&'static str>,
+    apt: Option<&'static str>,
+    pacman: Option<&'static str>,
+    /// npm package, suggested where no package manager carries the tool
+    npm: Option<&'static str>,
+}
+
+impl Package {
+    fn name_for(&self, manager: PackageManager) -> Option<&'static str> {
+        match manager {
+            PackageManager::Brew => self.brew,
+            PackageManager::Winget => self.winget,
+            PackageManager::Apt => self.apt,
+            PackageManager::Pacman => self.pacman,
+        }
+    }
+}
+
+const PACKAGES: &[Package] = &[Package {
+    tool: "codex",
+    binary: "codex",
+    brew: Some("codex"),
+    winget: Some("OpenAI.Codex"),
+    apt: None,
+    pacman: None,
+    npm: Some("@openai/codex"),
+}];
+
+/// What installing a tool through the package manager did (or would do)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageInstallResult {
+    pub tool: String,
+    pub manager: Option<PackageManager>,
+    /// Command that was run, or that the user should run
+    pub command: Vec<String>,
+    pub installed: bool,
+    /// Where the binary was found afterwards
+    pub path: Option<String>,
+    /// What to do instead when Jean didn't run anything
+    pub hint: Option<String>,
+}
+
+/// Payload for `tools:package-manager-output`
+#[derive(Debug, Clone, Serialize)]
+struct OutputLine<'a> {
+    tool: &'a str,
+    /// "stdout" or "stderr"
+    stream: &'a str,
+    line: &'a str,
+}
+
+/// Package managers on PATH, preferred first
+pub fn available_managers() -> Vec<PackageManager> {
+    let candidates: &[PackageManager] = if cfg!(target_os = "macos") {
+        &[PackageManager::Brew]
+    } else if cfg!(windows) {
+        &[PackageManager::Winget]
+    } else {
+        &[
+            PackageManager::Brew,
+            PackageManager::Apt,
+            PackageManager::Pacman,
+        ]
+    };
+    candidates
+        .iter()
+        .copied()
+        .filter(|m| which::which(m.program()).is_ok())
+        .collect()
+}
+
+/// Command installing `package` with `manager`
+fn install_command(manager: PackageManager, package: &str) -> Vec<String> {
+    let args: &[&str] = match manager {
+        PackageManager::Brew => &["brew", "install", package],
+        PackageManager::Winget => &[
+            "winget",
+            "install",
+            "--id",
+            package,
+            "--exact",
+            "--accept-package-agreements",
+            "--accept-source-agreements",
+            "--disable-interactivity",
+        ],
+        PackageManager::Apt => &["sudo", "apt", "install", "-y", package],
+        PackageManager::Pacman => &["sudo", "pacman", "-S", "--needed", package],
+    };
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+/// Decide how to install `tool` given the available package managers
+fn plan(tool: &str, managers: &[PackageManager]) -> Result<PackageInstallResult, String> {
+    let package = PACKAGES
+        .iter()
+        .find(|p| p.tool == tool)
+        .ok_or_else(|| format!("{tool} can't be installed through a package manager"))?;
+    let mut result = PackageInstallResult {
+        tool: tool.to_string(),
+        manager: None,
+        command: Vec::new(),
+        installed: false,
+        path: None,
+        hint: None,
+    };
+
+    let Some((manager, name)) = managers
+        .iter()
+        .find_map(|&m| package.name_for(m).map(|name| (m, name)))
+    else {
+        result.hint = Some(match (managers.first(), package.npm) {
+            (Some(m), Some(npm)) => format!(
+                "{} has no {tool} package. Install it with npm instead: npm install -g {npm}",
+                m.program()
+            ),
+            (None, Some(npm)) => format!(
+                "No supported package manager found. Install {tool} with npm instead: npm install -g {npm}"
+            ),
+            (_, None) => format!("No supported package manager carries {tool}"),
+        });
+        return Ok(result);
+    };
+
+    result.manager = Some(manager);
+    result.command = install_command(manager, name);
+    if !manager.unattended() {
+        result.hint = Some(format!(
+            "Run this in a terminal (it needs administrator rights): {}",
+            result.command.join(" ")
+        ));
+    }
+    Ok(result)
+}
+
+/// Run `command`, emitting each output line; Err with the last lines on failure
+fn run_streaming(
+    events: &(impl EventSink + Sync),
+    tool: &str,
+    command: &[String],
+) -> Result<(), String> {
+    let (program, args) = command.split_first().ok_or("Empty command")?;
+    let mut child = silent_command(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {program}: {e}"))?;
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+
+    let forward = |stream: &str, reader: &mut dyn BufRead| -> Vec<String> {
+        let mut tail = Vec::new();
+        for line in reader.lines().map_while(Result::ok) {
+            let _ = events.emit_all(
+                "tools:package-manager-output",
+                &OutputLine {
+                    tool,
+                    stream,
+                    line: &line,
+                },
+            );
+            if !line.trim().is_empty() {
+                tail.push(line);
+                if tail.len() > 5 {
+                    tail.remove(0);
+                }
+            }
+        }
+        tail
+    };
+    let (_, stderr_tail) = std::thread::scope(|scope| {
+        let stderr = scope.spawn(|| forward("stderr", &mut BufReader::new(stderr)));
+        let stdout = forward("stdout", &mut BufReader::new(stdout));
+        (stdout, stderr.join().unwrap_or_default())
+    });
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to wait for {program}: {e}"))?;
+    if !status.success() {
+        return Err(format!(
+            "{program} failed (exit code {:?}): {}",
+            status.code(),
+            stderr_tail.join("\n")
+        ));
+    }
+    Ok(())
+}
+
+/// Install `tool` with the system package manager when Jean can run it
+/// unattended; otherwise report what the user should run
+pub fn install(
+    events: &(impl EventSink + Sync),
+    tool: &str,
+) -> Result<PackageInstallResult, String> {
+    let mut result = plan(tool, &available_managers())?;
+    if result.hint.is_some() {
+        return Ok(result);
+    }
+
+    log::info!("Installing {tool}: {}", result.command.join(" "));
+    run_streaming(events, tool, &result.command)?;
+
+    let binary = PACKAGES
+        .iter()
+        .find(|p| p.tool == tool)
+        .map_or(tool, |p| p.binary);
+    result.path = which::which(binary)
+        .ok()
+        .map(|p| p.to_string_lossy().to_string());
+    result.installed = true;
+    if result.path.is_none() {
+        result.hint = Some(format!(
+            "{tool} was installed but isn't on PATH yet; restart Jean to pick it up"
+        ));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::RecordingSink;
+
+    #[test]
+    fn test_plan() {
+        let brew = plan("codex", &[PackageManager::Brew]).unwrap();
+        assert_eq!(brew.manager, Some(PackageManager::Brew));
+        assert_eq!(brew.command, ["brew", "install", "codex"]);
+        assert!(brew.hint.is_none());
+
+        let winget = plan("codex", &[PackageManager::Winget]).unwrap();
+        assert_eq!(
+            &winget.command[..4],
+            ["winget", "install", "--id", "OpenAI.Codex"]
+        );
+
+        // Codex isn't packaged for apt: suggest npm
+        let apt = plan("codex", &[PackageManager::Apt]).unwrap();
+        assert_eq!(apt.manager, None);
+        assert!(apt.hint.unwrap().contains("npm install -g @openai/codex"));
+
+        // Homebrew on Linux is preferred over apt
+        let linux = plan("codex", &[PackageManager::Brew, PackageManager::Apt]).unwrap();
+        assert_eq!(linux.manager, Some(PackageManager::Brew));
+
+        assert!(plan("codex", &[]).unwrap().hint.unwrap().contains("npm"));
+        assert!(plan("vim", &[PackageManager::Brew]).is_err());
+    }
+
+    #[test]
+    fn test_install_command_needs_root() {
+        let command = install_command(PackageManager::Pacman, "github-cli");
+        assert_eq!(command, ["sudo", "pacman", "-S", "--needed", "github-cli"]);
+        assert!(!PackageManager::Pacman.unattended());
+        assert!(!PackageManager::Apt.unattended());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_streaming() {
+        let sink = RecordingSink::default();
+        let command = |script: &str| ["sh", "-c", script].map(str::to_string);
+
+        run_streaming(&sink, "codex", &command("echo fetching; echo warning >&2")).unwrap();
+        let lines: Vec<_> = sink
+            .payloads("tools:package-manager-output")
+            .into_iter()
+            .map(|p| {
+                format!(
+                    "{} {}",
+                    p["stream"].as_str().unwrap(),
+                    p["line"].as_str().unwrap()
+                )
+            })
+            .collect();
+        assert!(lines.contains(&"stdout fetching".to_string()));
+        assert!(lines.contains(&"stderr warning".to_string()));
+
+        let err =
+            run_streaming(&sink, "codex", &command("echo 'No formula' >&2; exit 1")).unwrap_err();
+        assert!(err.contains("exit code Some(1)"));
+        assert!(err.contains("No formula"));
+    }
+}
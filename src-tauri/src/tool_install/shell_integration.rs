@@ -0,0 +1,398 @@
+//! Exposing Jean-managed CLIs to the user's own terminal
+//!
+//! Opt-in, in one of two ways: symlinks to the managed executables in
+//! `~/.local/bin`, or a PATH snippet added to the shell rc files between
+//! [`BEGIN_MARKER`] and [`END_MARKER`]. What was changed is recorded in
+//! `shell-integration.json`, so `disable` only removes Jean's own links and
+//! blocks. Neither is offered on Windows yet.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::PathProvider;
+
+pub const BEGIN_MARKER: &str = "# >>> Jean managed tools >>>";
+pub const END_MARKER: &str = "# <<< Jean managed tools <<<";
+const MARKERS: (&str, &str) = (BEGIN_MARKER, END_MARKER);
+
+const STATE_FILE: &str = "shell-integration.json";
+
+/// Rc files that get the PATH snippet when they exist (relative to home)
+const RC_FILES: &[&str] = &[".zshrc", ".bashrc", ".profile", ".config/fish/config.fish"];
+
+/// Written when none of `RC_FILES` exists
+const FALLBACK_RC_FILE: &str = ".profile";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrationMode {
+    /// Symlinks in `~/.local/bin`
+    Symlinks,
+    /// PATH entries in shell rc files
+    PathSnippet,
+}
+
+/// What Jean changed outside its data directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShellIntegrationStatus {
+    #[serde(default)]
+    pub mode: Option<IntegrationMode>,
+    /// Symlinks Jean created
+    #[serde(default)]
+    pub links: Vec<String>,
+    /// Rc files holding Jean's PATH block
+    #[serde(default)]
+    pub rc_files: Vec<String>,
+    /// Executables left alone because something else already has their name
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+}
+
+fn state_path(app: &impl PathProvider) -> Result<PathBuf, String> {
+    Ok(app.shared_data_dir()?.join(STATE_FILE))
+}
+
+pub fn load_status(app: &impl PathProvider) -> ShellIntegrationStatus {
+    state_path(app)
+        .and_then(|path| fs::read_to_string(path).map_err(|e| e.to_string()))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_status(app: &impl PathProvider, status: &ShellIntegrationStatus) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(status)
+        .map_err(|e| format!("Failed to serialize shell integration state: {e}"))?;
+    fs::write(state_path(app)?, content)
+        .map_err(|e| format!("Failed to save shell integration state: {e}"))
+}
+
+/// Executables Jean installed: the Claude and GitHub CLIs, helper tools, and
+/// Node.js plus anything installed with npm into Jean's prefix
+pub fn managed_executables(app: &impl PathProvider) -> Vec<PathBuf> {
+    let clis = [
+        crate::claude_cli::get_cli_binary_path(app),
+        crate::gh_cli::config::get_gh_cli_binary_path(app),
+    ];
+    let dirs = super::helpers::installed_bin_dir(app)
+        .into_iter()
+        .chain(super::node::bin_dirs(app));
+    let mut executables: Vec<PathBuf> = clis
+        .into_iter()
+        .flatten()
+        .filter(|path| path.is_file())
+        .chain(dirs.flat_map(|dir| {
+            fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| is_executable(path))
+        }))
+        .collect();
+    executables.sort();
+    executables.dedup();
+    executables
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    // Follows symlinks, like npm's bin/ entries
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Directories of `executables`, in order, without duplicates
+fn dirs_of(executables: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in executables.iter().filter_map(|p| p.parent()) {
+        if !dirs.iter().any(|d| d == dir) {
+            dirs.push(dir.to_path_buf());
+        }
+    }
+    dirs
+}
+
+/// PATH block for an rc file (fish has its own syntax)
+fn path_block(dirs: &[PathBuf], fish: bool) -> String {
+    let quoted: Vec<String> = dirs
+        .iter()
+        .map(|d| shell_quote(&d.display().to_string(), fish))
+        .collect();
+    let line = if fish {
+        format!("set -gx PATH {} $PATH", quoted.join(" "))
+    } else {
+        format!("export PATH={}:\"$PATH\"", quoted.join(":"))
+    };
+    format!("{BEGIN_MARKER}\n# Added by Jean; remove with Settings > Shell integration\n{line}\n{END_MARKER}\n")
+}
+
+/// `s` in single quotes, which both fish and POSIX shells take literally
+/// apart from the quote itself (and, in fish, backslashes)
+fn shell_quote(s: &str, fish: bool) -> String {
+    if fish {
+        format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
+
+/// `content` without the block between `markers` (None if it had none)
+pub(super) fn remove_block(content: &str, markers: (&str, &str)) -> Option<String> {
+    let (begin, end_marker) = markers;
+    let start = content.find(begin)?;
+    let end = content[start..].find(end_marker)? + start + end_marker.len();
+    let end = if content[end..].starts_with('\n') {
+        end + 1
+    } else {
+        end
+    };
+    let before = content[..start].trim_end_matches('\n');
+    let after = &content[end..];
+    Some(match (before.is_empty(), after.is_empty()) {
+        (true, _) => after.to_string(),
+        (false, true) => format!("{before}\n"),
+        (false, false) => format!("{before}\n{after}"),
+    })
+}
+
+/// `content` with the block between `markers` replaced by (or appended as)
+/// `block`
+pub(super) fn apply_block(content: &str, block: &str, markers: (&str, &str)) -> String {
+    let content = remove_block(content, markers).unwrap_or_else(|| content.to_string());
+    if content.trim().is_empty() {
+        block.to_string()
+    } else {
+        format!("{}\n\n{block}", content.trim_end_matches('\n'))
+    }
+}
+
+/// Link each executable into `bin_dir`, skipping names that are taken
+fn link_executables(
+    executables: &[PathBuf],
+    bin_dir: &Path,
+    status: &mut ShellIntegrationStatus,
+) -> Result<(), String> {
+    fs::create_dir_all(bin_dir).map_err(|e| format!("Failed to create {bin_dir:?}: {e}"))?;
+    for target in executables {
+        let Some(name) = target.file_name() else {
+            continue;
+        };
+        let link = bin_dir.join(name);
+        match fs::read_link(&link) {
+            Ok(existing) if existing == *target => {}
+            // Nothing there yet (not even a dangling link)
+            Err(_) if link.symlink_metadata().is_err() => {
+                symlink(target, &link)?;
+            }
+            _ => {
+                status.conflicts.push(link.to_string_lossy().to_string());
+                continue;
+            }
+        }
+        status.links.push(link.to_string_lossy().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> Result<(), String> {
+    std::os::unix::fs::symlink(target, link)
+        .map_err(|e| format!("Failed to link {link:?} to {target:?}: {e}"))
+}
+
+#[cfg(not(unix))]
+fn symlink(_target: &Path, _link: &Path) -> Result<(), String> {
+    Err("Symlinks aren't supported on this platform".to_string())
+}
+
+/// Add Jean's PATH block to the rc files under `home`
+fn write_rc_files(
+    dirs: &[PathBuf],
+    home: &Path,
+    status: &mut ShellIntegrationStatus,
+) -> Result<(), String> {
+    let mut targets: Vec<&str> = RC_FILES
+        .iter()
+        .copied()
+        .filter(|rc| home.join(rc).is_file())
+        .collect();
+    if targets.is_empty() {
+        targets.push(FALLBACK_RC_FILE);
+    }
+    for rc in targets {
+        let path = home.join(rc);
+        // Anything but a missing file must stop us; writing the block over an
+        // unreadable rc file would throw away the user's own config
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Failed to read {path:?}: {e}")),
+        };
+        let block = path_block(dirs, rc.ends_with(".fish"));
+        fs::write(&path, apply_block(&content, &block, MARKERS))
+            .map_err(|e| format!("Failed to update {path:?}: {e}"))?;
+        status.rc_files.push(path.to_string_lossy().to_string());
+    }
+    Ok(())
+}
+
+/// Undo whatever `status` records, keeping links that no longer point into
+/// Jean's data directory
+fn remove_changes(app: &impl PathProvider, status: &ShellIntegrationStatus) -> Result<(), String> {
+    let data_dir = app.shared_data_dir()?;
+    for link in &status.links {
+        let link = Path::new(link);
+        if fs::read_link(link).is_ok_and(|target| target.starts_with(&data_dir)) {
+            fs::remove_file(link).map_err(|e| format!("Failed to remove {link:?}: {e}"))?;
+        }
+    }
+    for rc in &status.rc_files {
+        let path = Path::new(rc);
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        if let Some(cleaned) = remove_block(&content, MARKERS) {
+            fs::write(path, cleaned).map_err(|e| format!("Failed to update {path:?}: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Expose the managed executables to shells under `home`, replacing any
+/// earlier integration
+pub fn enable(
+    app: &impl PathProvider,
+    home: &Path,
+    mode: IntegrationMode,
+) -> Result<ShellIntegrationStatus, String> {
+    if cfg!(windows) {
+        return Err("Shell integration isn't available on Windows yet".to_string());
+    }
+    let executables = managed_executables(app);
+    if executables.is_empty() {
+        return Err("Jean hasn't installed any command-line tools yet".to_string());
+    }
+
+    remove_changes(app, &load_status(app))?;
+    let mut status = ShellIntegrationStatus {
+        mode: Some(mode),
+        ..Default::default()
+    };
+    let result = match mode {
+        IntegrationMode::Symlinks => {
+            link_executables(&executables, &home.join(".local/bin"), &mut status)
+        }
+        IntegrationMode::PathSnippet => write_rc_files(&dirs_of(&executables), home, &mut status),
+    };
+    // Record partial progress too, so it can be removed
+    save_status(app, &status)?;
+    result?;
+    log::info!(
+        "Shell integration enabled: {} link(s), {} rc file(s)",
+        status.links.len(),
+        status.rc_files.len()
+    );
+    Ok(status)
+}
+
+/// Remove Jean's links and PATH blocks
+pub fn disable(app: &impl PathProvider) -> Result<ShellIntegrationStatus, String> {
+    remove_changes(app, &load_status(app))?;
+    let status = ShellIntegrationStatus::default();
+    save_status(app, &status)?;
+    log::info!("Shell integration removed");
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::runtime::TempPaths;
+
+    #[test]
+    fn test_apply_and_remove_block() {
+        let dirs = [
+            PathBuf::from("/data/gh-cli"),
+            PathBuf::from("/data/npm-global/bin"),
+        ];
+        let block = path_block(&dirs, false);
+        assert!(block.contains("export PATH='/data/gh-cli':'/data/npm-global/bin':\"$PATH\""));
+        assert!(path_block(&dirs, true).contains("set -gx PATH '/data/gh-cli'"));
+        assert_eq!(shell_quote("it's $HOME", false), "'it'\\''s $HOME'");
+        assert_eq!(shell_quote("it's a\\b", true), "'it\\'s a\\\\b'");
+
+        let rc = "alias ll='ls -l'\n";
+        let applied = apply_block(rc, &block, MARKERS);
+        assert!(applied.starts_with(rc));
+        assert!(applied.ends_with(&format!("{END_MARKER}\n")));
+        // Applying again replaces the block instead of adding another
+        let reapplied = apply_block(&applied, &path_block(&dirs[..1], false), MARKERS);
+        assert_eq!(reapplied.matches(BEGIN_MARKER).count(), 1);
+        assert!(!reapplied.contains("npm-global"));
+
+        assert_eq!(remove_block(&applied, MARKERS).as_deref(), Some(rc));
+        assert_eq!(remove_block(rc, MARKERS), None);
+        let surrounded = format!("export A=1\n{block}export B=2\n");
+        assert_eq!(
+            remove_block(&surrounded, MARKERS).as_deref(),
+            Some("export A=1\nexport B=2\n")
+        );
+        assert_eq!(apply_block("", &block, MARKERS), block);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_enable_and_disable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let paths = TempPaths::new();
+        let home = tempfile::tempdir().unwrap();
+        let npm_bin = paths.app_data_dir().unwrap().join("npm-global/bin");
+        fs::create_dir_all(&npm_bin).unwrap();
+        for name in ["codex", "README"] {
+            fs::write(npm_bin.join(name), "#!/bin/sh\n").unwrap();
+        }
+        fs::set_permissions(npm_bin.join("codex"), fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(managed_executables(&paths), [npm_bin.join("codex")]);
+
+        // An unrelated `codex` already in ~/.local/bin is left alone
+        let local_bin = home.path().join(".local/bin");
+        fs::create_dir_all(&local_bin).unwrap();
+        fs::write(local_bin.join("codex"), "other").unwrap();
+        let status = enable(&paths, home.path(), IntegrationMode::Symlinks).unwrap();
+        assert!(status.links.is_empty());
+        assert_eq!(status.conflicts.len(), 1);
+        fs::remove_file(local_bin.join("codex")).unwrap();
+
+        let status = enable(&paths, home.path(), IntegrationMode::Symlinks).unwrap();
+        assert_eq!(status.links.len(), 1);
+        assert_eq!(
+            fs::read_link(local_bin.join("codex")).unwrap(),
+            npm_bin.join("codex")
+        );
+
+        // Switching modes removes the links
+        fs::write(home.path().join(".zshrc"), "export EDITOR=vim\n").unwrap();
+        let status = enable(&paths, home.path(), IntegrationMode::PathSnippet).unwrap();
+        assert!(!local_bin.join("codex").exists());
+        assert_eq!(status.rc_files.len(), 1);
+        let zshrc = fs::read_to_string(home.path().join(".zshrc")).unwrap();
+        assert!(zshrc.contains(&npm_bin.display().to_string()));
+        assert_eq!(load_status(&paths).mode, Some(IntegrationMode::PathSnippet));
+
+        disable(&paths).unwrap();
+        assert_eq!(
+            fs::read_to_string(home.path().join(".zshrc")).unwrap(),
+            "export EDITOR=vim\n"
+        );
+        assert!(load_status(&paths).mode.is_none());
+    }
+}
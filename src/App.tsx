@@ -13,6 +13,7 @@ import { chatQueryKeys } from '@/services/chat'
 import type { WorktreeSessions } from '@/types/chat'
 import { initializeCommandSystem } from './lib/commands'
 import { logger } from './lib/logger'
+import { shouldRunBackgroundJob } from './lib/background-jobs'
 import { toast } from 'sonner'
 import { cleanupOldFiles } from './lib/recovery'
 import './App.css'
@@ -495,6 +496,8 @@ function App() {
     // Auto-updater logic - check for updates 5 seconds after app loads
     const checkForUpdates = async () => {
       if (!isNativeApp()) return
+      // Skip on low battery or metered connections (checked again next launch)
+      if (!(await shouldRunBackgroundJob('update_check'))) return
 
       try {
         const { check } = await import('@tauri-apps/plugin-updater')
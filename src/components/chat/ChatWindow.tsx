@@ -41,6 +41,7 @@ import {
   useRunScript,
   projectsQueryKeys,
 } from '@/services/projects'
+import { useProjectConfig } from '@/services/project-config'
 import {
   useLoadedIssueContexts,
   useLoadedPRContexts,
@@ -423,8 +424,15 @@ export function ChatWindow({
   const selectedModel: ClaudeModel =
     (session?.selected_model as ClaudeModel) ?? defaultModel
 
-  // Per-session provider selection, falls back to preferences default
-  const defaultProvider = preferences?.default_provider ?? null
+  // Per-session provider selection, falls back to the project's jean.json,
+  // then preferences default
+  const { data: projectConfig } = useProjectConfig(activeWorktreePath ?? null)
+  const projectProvider = projectConfig?.config?.provider
+  const defaultProvider =
+    projectProvider &&
+    preferences?.custom_cli_profiles?.some(p => p.name === projectProvider)
+      ? projectProvider
+      : (preferences?.default_provider ?? null)
   const sessionProvider = useChatStore(state =>
     deferredSessionId ? state.selectedProviders[deferredSessionId] : undefined
   )
@@ -1128,6 +1136,7 @@ export function ChatWindow({
           disableThinkingForMode: queuedMsg.disableThinkingForMode,
           effortLevel: queuedMsg.effortLevel,
           mcpConfig: queuedMsg.mcpConfig,
+          backgroundPriority: queuedMsg.backgroundPriority,
           customProfileSettings: resolved.customProfileSettings,
           parallelExecutionPrompt:
             preferences?.parallel_execution_prompt_enabled
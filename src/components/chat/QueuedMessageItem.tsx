@@ -1,6 +1,7 @@
 import { memo, useCallback } from 'react'
 import {
   Brain,
+  CalendarClock,
   ClipboardList,
   Clock,
   Hammer,
@@ -11,6 +12,7 @@ import {
 } from 'lucide-react'
 import { cn } from '@/lib/utils'
 import type { QueuedMessage } from '@/types/chat'
+import type { TaskSchedule } from '@/types/power'
 import {
   MODEL_OPTIONS,
   THINKING_LEVEL_OPTIONS,
@@ -22,6 +24,21 @@ import {
   TooltipContent,
 } from '@/components/ui/tooltip'
 
+/** Short description of a queued run's schedule, e.g. "after 18:00 · weekends" */
+function scheduleLabel(schedule: TaskSchedule): string {
+  const parts: string[] = []
+  if (schedule.after && schedule.before) {
+    parts.push(`${schedule.after}–${schedule.before}`)
+  } else if (schedule.after) {
+    parts.push(`after ${schedule.after}`)
+  } else if (schedule.before) {
+    parts.push(`before ${schedule.before}`)
+  }
+  if (schedule.days && schedule.days !== 'any') parts.push(schedule.days)
+  if (schedule.avoid_meetings) parts.push('no meetings')
+  return parts.join(' · ') || 'scheduled'
+}
+
 interface QueuedMessageItemProps {
   message: QueuedMessage
   index: number
@@ -158,6 +175,13 @@ export const QueuedMessageItem = memo(function QueuedMessageItem({
                 {THINKING_LEVEL_OPTIONS.find(o => o.value === message.thinkingLevel)?.label}
               </span>
             ) : null)}
+          {/* Schedule badge */}
+          {message.schedule && (
+            <span className="inline-flex items-center gap-1 rounded bg-muted/80 px-1.5 py-0.5 text-[10px] text-muted-foreground">
+              <CalendarClock className="h-2.5 w-2.5" />
+              {scheduleLabel(message.schedule)}
+            </span>
+          )}
         </div>
       </div>
     </div>
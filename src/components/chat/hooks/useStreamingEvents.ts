@@ -12,6 +12,7 @@ import type { AppPreferences, NotificationSound } from '@/types/preferences'
 import { triggerImmediateGitPoll } from '@/services/git-status'
 import { isAskUserQuestion, isExitPlanMode } from '@/types/chat'
 import { playNotificationSound } from '@/lib/sounds'
+import { shouldRunBackgroundJob } from '@/lib/background-jobs'
 import { findPlanFilePath } from '@/components/chat/tool-call-utils'
 import { generateId } from '@/lib/uuid'
 import type {
@@ -24,6 +25,7 @@ import type {
   CancelledEvent,
   ThinkingEvent,
   PermissionDeniedEvent,
+  PermissionApprovedEvent,
   CompactingEvent,
   CompactedEvent,
   Session,
@@ -40,8 +42,8 @@ interface UseStreamingEventsParams {
  * Events include session_id for routing to the correct session.
  *
  * Handles: chat:chunk, chat:tool_use, chat:tool_block, chat:thinking,
- * chat:tool_result, chat:permission_denied, chat:done, chat:error,
- * chat:cancelled, chat:compacted
+ * chat:tool_result, chat:permission_denied, chat:permission_approved,
+ * chat:done, chat:error, chat:cancelled, chat:compacted
  */
 export default function useStreamingEvents({
   queryClient,
@@ -203,6 +205,22 @@ export default function useStreamingEvents({
       }
     )
 
+    // Denied tools approved elsewhere (bot channels): drop the approval prompt
+    const unlistenPermissionApproved = listen<PermissionApprovedEvent>(
+      'chat:permission_approved',
+      event => {
+        const { session_id } = event.payload
+        const {
+          clearPendingDenials,
+          clearDeniedMessageContext,
+          setWaitingForInput,
+        } = useChatStore.getState()
+        clearPendingDenials(session_id)
+        clearDeniedMessageContext(session_id)
+        setWaitingForInput(session_id, false)
+      }
+    )
+
     const unlistenDone = listen<DoneEvent>('chat:done', event => {
       const sessionId = event.payload.session_id
       const worktreeId = event.payload.worktree_id
@@ -258,30 +276,35 @@ export default function useStreamingEvents({
           sessionId
         )
 
-        // Generate digest in background (fire and forget)
-        invoke<SessionDigest>('generate_session_digest', { sessionId })
-          .then(digest => {
-            useChatStore.getState().setSessionDigest(sessionId, digest)
-            console.log(
-              '[useStreamingEvents] Digest generated for session:',
-              sessionId
-            )
-            // Persist digest to disk so it survives app reload
-            invoke('update_session_digest', { sessionId, digest }).catch(
-              err => {
-                console.error(
-                  '[useStreamingEvents] Failed to persist digest:',
-                  err
-                )
-              }
-            )
-          })
-          .catch(err => {
-            console.error(
-              '[useStreamingEvents] Failed to generate digest:',
-              err
-            )
-          })
+        // Generate digest in background (fire and forget). When deferred on
+        // battery/metered, the session stays marked and the digest is
+        // generated when the user opens it.
+        shouldRunBackgroundJob('report_generation').then(allowed => {
+          if (!allowed) return
+          invoke<SessionDigest>('generate_session_digest', { sessionId })
+            .then(digest => {
+              useChatStore.getState().setSessionDigest(sessionId, digest)
+              console.log(
+                '[useStreamingEvents] Digest generated for session:',
+                sessionId
+              )
+              // Persist digest to disk so it survives app reload
+              invoke('update_session_digest', { sessionId, digest }).catch(
+                err => {
+                  console.error(
+                    '[useStreamingEvents] Failed to persist digest:',
+                    err
+                  )
+                }
+              )
+            })
+            .catch(err => {
+              console.error(
+                '[useStreamingEvents] Failed to generate digest:',
+                err
+              )
+            })
+        })
       }
 
       // Capture streaming state to local variables BEFORE clearing
@@ -901,6 +924,7 @@ export default function useStreamingEvents({
       unlistenThinking.then(f => f())
       unlistenToolResult.then(f => f())
       unlistenPermissionDenied.then(f => f())
+      unlistenPermissionApproved.then(f => f())
       unlistenDone.then(f => f())
       unlistenError.then(f => f())
       unlistenCancelled.then(f => f())
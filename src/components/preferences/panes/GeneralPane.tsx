@@ -11,6 +11,7 @@ import { Input } from '@/components/ui/input'
 import {
   useClaudeCliStatus,
   useClaudeCliAuth,
+  useCliCapabilities,
   claudeCliQueryKeys,
 } from '@/services/claude-cli'
 import { useGhCliStatus, useGhCliAuth, ghCliQueryKeys } from '@/services/gh-cli'
@@ -117,6 +118,11 @@ export const GeneralPane: React.FC = () => {
   // CLI status hooks
   const { data: cliStatus, isLoading: isCliLoading } = useClaudeCliStatus()
   const { data: ghStatus, isLoading: isGhLoading } = useGhCliStatus()
+  const { data: cliCapabilities } = useCliCapabilities('claude', {
+    enabled: !!cliStatus?.installed,
+  })
+  // Unknown until probed; only hide features the probe ruled out
+  const chromeSupported = cliCapabilities?.features.chrome !== false
 
   // Auth status queries - only enabled when CLI is installed
   const { data: claudeAuth, isLoading: isClaudeAuthLoading } = useClaudeCliAuth(
@@ -628,10 +634,15 @@ export const GeneralPane: React.FC = () => {
 
           <InlineField
             label="Chrome browser integration"
-            description="Enable browser automation via Chrome extension"
+            description={
+              chromeSupported
+                ? 'Enable browser automation via Chrome extension'
+                : 'Not supported by the installed Claude CLI version'
+            }
           >
             <Switch
-              checked={preferences?.chrome_enabled ?? true}
+              disabled={!chromeSupported}
+              checked={chromeSupported && (preferences?.chrome_enabled ?? true)}
               onCheckedChange={checked => {
                 if (preferences) {
                   savePreferences.mutate({
@@ -14,6 +14,7 @@ import { disposeTerminal } from '@/lib/terminal-instances'
 import { useCommandContext } from './use-command-context'
 import { usePreferences } from '@/services/preferences'
 import { logger } from '@/lib/logger'
+import { toast } from 'sonner'
 import {
   eventToShortcutString,
   DEFAULT_KEYBINDINGS,
@@ -21,6 +22,13 @@ import {
   type KeybindingsMap,
 } from '@/types/keybindings'
 import { isBaseSession, type Project, type Worktree } from '@/types/projects'
+import type { PinnedVersionMissing } from '@/types/claude-cli'
+import type {
+  AllowedToolsApproval,
+  ProjectConfigChanged,
+} from '@/types/project-config'
+import { approveAllowedTools } from '@/services/project-config'
+import type { DriftReport } from '@/types/schema-drift'
 
 // Throttle tracking for worktree switching
 let lastWorktreeSwitchTime = 0
@@ -603,6 +611,79 @@ export function useMainWindowEventListeners() {
           // Silent failure - don't show toast to avoid interrupting workflow
         }),
 
+        // A project's pinned Claude CLI is missing; the backend installs it
+        listen<PinnedVersionMissing>(
+          'claude-cli:pinned-version-missing',
+          event => {
+            const { version } = event.payload
+            logger.warn('Pinned Claude CLI version missing', { version })
+            toast.warning(`Claude CLI ${version} is not installed`, {
+              description:
+                'This session uses the default install. The pinned version is being installed for the next run.',
+            })
+            queryClient.invalidateQueries({
+              queryKey: ['claude-cli', 'pinned'],
+            })
+          }
+        ),
+
+        // jean.json with problems was added or edited
+        listen<ProjectConfigChanged>('project-config:changed', event => {
+          const errors = event.payload.status.issues.filter(
+            issue => issue.severity === 'error'
+          )
+          if (errors.length === 0) return
+          toast.error('jean.json has errors', {
+            description: errors
+              .map(issue =>
+                issue.path ? `${issue.path}: ${issue.message}` : issue.message
+              )
+              .join('\n'),
+          })
+        }),
+
+        // jean.json pre-approves tools the user hasn't approved yet; sessions
+        // run without them until approved
+        listen<AllowedToolsApproval>(
+          'project-config:tools-approval-needed',
+          event => {
+            const { project_id, allowed_tools } = event.payload
+            const toastId = `tools-approval-${project_id}`
+            toast.warning('jean.json wants to pre-approve tools', {
+              id: toastId,
+              description: `${allowed_tools.join(', ')}\nThis session runs without them. Approve them for later sessions in this project?`,
+              duration: Infinity,
+              action: {
+                label: 'Approve',
+                onClick: () => {
+                  approveAllowedTools(project_id, allowed_tools)
+                    .then(() => toast.success('Approved tools from jean.json'))
+                    .catch(error => {
+                      logger.error('Failed to approve jean.json tools', {
+                        error,
+                      })
+                      toast.error(`Failed to approve tools: ${error}`)
+                    })
+                  toast.dismiss(toastId)
+                },
+              },
+              cancel: {
+                label: 'Not now',
+                onClick: () => toast.dismiss(toastId),
+              },
+            })
+          }
+        ),
+
+        // A CLI wrote output its adapter doesn't recognize (reported once per kind)
+        listen<DriftReport>('backend:schema-drift', event => {
+          const { backend, event_type } = event.payload
+          logger.warn('Agent CLI schema drift', event.payload)
+          toast.warning(`Unrecognized ${backend} output`, {
+            description: `"${event_type}" isn't supported by this version of Jean yet. The session continues and the output is kept in the run log.`,
+          })
+        }),
+
         // Real-time cache sync between native + web clients
         listen<{ keys: string[] }>('cache:invalidate', event => {
           const { keys } = event.payload
@@ -637,6 +718,54 @@ export function useMainWindowEventListeners() {
                   queryKey: ['saved-contexts'],
                 })
                 break
+              case 'lessons':
+                queryClient.invalidateQueries({
+                  queryKey: ['knowledge'],
+                })
+                break
+              case 'profiles':
+                queryClient.invalidateQueries({
+                  queryKey: ['profiles'],
+                })
+                break
+              case 'guest-mode':
+                queryClient.invalidateQueries({
+                  queryKey: ['guest-mode'],
+                })
+                break
+              case 'provider-keys':
+                queryClient.invalidateQueries({
+                  queryKey: ['provider-keys'],
+                })
+                break
+              case 'emergency':
+                queryClient.invalidateQueries({
+                  queryKey: ['emergency'],
+                })
+                break
+              case 'stale-files':
+                queryClient.invalidateQueries({
+                  queryKey: ['stale-files'],
+                })
+                break
+              case 'project-config':
+                queryClient.invalidateQueries({
+                  queryKey: ['project-config'],
+                })
+                break
+              case 'cli-versions':
+                queryClient.invalidateQueries({
+                  queryKey: ['claude-cli', 'installed-versions'],
+                })
+                queryClient.invalidateQueries({
+                  queryKey: ['claude-cli', 'pinned'],
+                })
+                break
+              case 'vulnerabilities':
+                queryClient.invalidateQueries({
+                  queryKey: ['vulnerabilities'],
+                })
+                break
             }
           }
         }),
@@ -1,12 +1,14 @@
-import { useEffect, useRef } from 'react'
+import { useEffect, useRef, useState } from 'react'
 import { useChatStore } from '@/store/chat-store'
 import { useSendMessage } from '@/services/chat'
 import { usePreferences } from '@/services/preferences'
+import { useEmergencyStatus } from '@/services/emergency'
 import { DEFAULT_PARALLEL_EXECUTION_PROMPT } from '@/types/preferences'
 import { isTauri } from '@/services/projects'
 import { useWsConnectionStatus } from '@/lib/transport'
 import type { QueuedMessage } from '@/types/chat'
 import { logger } from '@/lib/logger'
+import { isScheduleOpen, shouldRunBackgroundJob } from '@/lib/background-jobs'
 
 // GIT_ALLOWED_TOOLS duplicated from ChatWindow - tools always allowed for git operations
 const GIT_ALLOWED_TOOLS = ['Bash', 'Read', 'Glob', 'Grep']
@@ -64,6 +66,12 @@ function buildMessageWithRefs(queuedMsg: QueuedMessage): string {
   return message
 }
 
+/** How often to re-check whether background-priority runs may start */
+const BATCH_TASKS_RECHECK_MS = 60_000
+
+/** How often to re-check the schedules of scheduled runs at queue heads */
+const SCHEDULE_RECHECK_MS = 60_000
+
 /**
  * Global queue processor hook - must be at App level so it stays active
  * even when ChatWindow is unmounted (e.g., when viewing session board or different worktree)
@@ -76,10 +84,25 @@ export function useQueueProcessor(): void {
   const { data: preferences } = usePreferences()
   // Re-run effect when WS connects so queue processing works in web mode
   const wsConnected = useWsConnectionStatus()
+  // Queues are held while an emergency stop is engaged
+  const { data: emergencyStatus } = useEmergencyStatus()
+  const emergencyStopped = emergencyStatus?.engaged ?? false
 
   // Track which sessions we're currently processing to prevent race conditions
   const processingRef = useRef<Set<string>>(new Set())
 
+  // Background-priority runs wait while on low battery or a metered connection
+  const [batchTasksAllowed, setBatchTasksAllowed] = useState(true)
+  useEffect(() => {
+    if (!isTauri()) return
+    const check = () => {
+      shouldRunBackgroundJob('batch_tasks').then(setBatchTasksAllowed)
+    }
+    check()
+    const interval = setInterval(check, BATCH_TASKS_RECHECK_MS)
+    return () => clearInterval(interval)
+  }, [])
+
   // Subscribe to queue-related state changes
   const messageQueues = useChatStore(state => state.messageQueues)
   const sendingSessionIds = useChatStore(state => state.sendingSessionIds)
@@ -87,8 +110,31 @@ export function useQueueProcessor(): void {
     state => state.waitingForInputSessionIds
   )
 
+  // Scheduled runs at queue heads that may start now, by message ID
+  const [openSchedules, setOpenSchedules] = useState<Record<string, boolean>>(
+    {}
+  )
   useEffect(() => {
     if (!isTauri()) return
+    const check = () => {
+      const scheduled = Object.values(messageQueues)
+        .map(queue => queue?.[0])
+        .filter((msg): msg is QueuedMessage => !!msg?.schedule)
+      Promise.all(
+        scheduled.map(
+          async msg =>
+            [msg.id, await isScheduleOpen(msg.schedule ?? {})] as const
+        )
+      ).then(results => setOpenSchedules(Object.fromEntries(results)))
+    }
+    check()
+    const interval = setInterval(check, SCHEDULE_RECHECK_MS)
+    return () => clearInterval(interval)
+  }, [messageQueues])
+
+  useEffect(() => {
+    if (!isTauri()) return
+    if (emergencyStopped) return
 
     // Process each session's queue
     for (const [sessionId, queue] of Object.entries(messageQueues)) {
@@ -104,6 +150,12 @@ export function useQueueProcessor(): void {
       // Skip if session is waiting for user input (AskUserQuestion/ExitPlanMode)
       if (waitingForInputSessionIds[sessionId]) continue
 
+      // Defer background-priority runs on low battery or metered connections
+      if (queue[0]?.backgroundPriority && !batchTasksAllowed) continue
+
+      // Hold scheduled runs outside their window, days or during meetings
+      if (queue[0]?.schedule && !openSchedules[queue[0].id]) continue
+
       // Get worktree info for this session
       const {
         sessionWorktreeMap,
@@ -184,6 +236,7 @@ export function useQueueProcessor(): void {
           disableThinkingForMode: queuedMsg.disableThinkingForMode,
           effortLevel: queuedMsg.effortLevel,
           mcpConfig: queuedMsg.mcpConfig,
+          backgroundPriority: queuedMsg.backgroundPriority,
           parallelExecutionPrompt: preferences?.parallel_execution_prompt_enabled
             ? (preferences.magic_prompts?.parallel_execution ?? DEFAULT_PARALLEL_EXECUTION_PROMPT)
             : undefined,
@@ -206,5 +259,8 @@ export function useQueueProcessor(): void {
     preferences?.parallel_execution_prompt_enabled,
     preferences?.chrome_enabled,
     wsConnected,
+    batchTasksAllowed,
+    openSchedules,
+    emergencyStopped,
   ])
 }
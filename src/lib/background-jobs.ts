@@ -0,0 +1,54 @@
+/**
+ * Battery/metered-connection aware gating for optional background work
+ */
+
+import { invoke } from '@/lib/transport'
+import type {
+  BackgroundJob,
+  JobDecision,
+  ScheduleDecision,
+  TaskSchedule,
+} from '@/types/power'
+import { logger } from './logger'
+
+/**
+ * Check whether a background job should run now.
+ * Returns false when the backend defers it (low battery or metered connection);
+ * errors fail open so jobs are never blocked by a broken check.
+ */
+export async function shouldRunBackgroundJob(
+  job: BackgroundJob
+): Promise<boolean> {
+  try {
+    const decision = await invoke<JobDecision>('should_run_background_job', {
+      job,
+    })
+    if (!decision.allowed) {
+      logger.info('Deferring background job', { job, reason: decision.reason })
+    }
+    return decision.allowed
+  } catch (error) {
+    logger.warn('Background job check failed, running anyway', { job, error })
+    return true
+  }
+}
+
+/**
+ * Check whether a queued run's schedule (time window, days, meetings)
+ * allows it to start now. An invalid schedule or failed check holds the run
+ * rather than starting it at the wrong time.
+ */
+export async function isScheduleOpen(schedule: TaskSchedule): Promise<boolean> {
+  try {
+    const decision = await invoke<ScheduleDecision>('check_task_schedule', {
+      schedule,
+    })
+    if (!decision.allowed) {
+      logger.info('Holding scheduled run', { reason: decision.reason })
+    }
+    return decision.allowed
+  } catch (error) {
+    logger.warn('Schedule check failed, holding run', { schedule, error })
+    return false
+  }
+}
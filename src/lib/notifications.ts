@@ -5,6 +5,7 @@
 import { invoke } from '@/lib/transport'
 import { toast } from 'sonner'
 import { logger } from './logger'
+import type { NotificationCategory } from '@/types/focus'
 
 type NotificationType = 'success' | 'error' | 'info' | 'warning'
 
@@ -15,6 +16,8 @@ interface NotificationOptions {
   native?: boolean
   /** Duration in milliseconds for toasts (0 = no auto-dismiss) */
   duration?: number
+  /** Category of a native notification, for Focus / do-not-disturb overrides */
+  category?: NotificationCategory
 }
 
 /**
@@ -41,7 +44,7 @@ export async function notify(
   message?: string,
   options: NotificationOptions = {}
 ): Promise<void> {
-  const { type = 'info', native = false, duration } = options
+  const { type = 'info', native = false, duration, category } = options
 
   try {
     if (native) {
@@ -50,6 +53,7 @@ export async function notify(
       await invoke('send_native_notification', {
         title,
         body: message,
+        ...(category && { category }),
       })
     } else {
       // Send in-app toast notification
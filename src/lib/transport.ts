@@ -55,14 +55,19 @@ export async function invoke<T>(
 /**
  * Listen for backend events. Drop-in replacement for Tauri's listen().
  * Returns an unlisten function.
+ *
+ * Native listeners are registered on the current window so that events sent
+ * to other windows or sessions (see set_event_subscriptions) are filtered out.
  */
 export async function listen<T>(
   event: string,
   handler: (event: { payload: T }) => void
 ): Promise<() => void> {
   if (isNativeApp()) {
-    const { listen: tauriListen } = await import('@tauri-apps/api/event')
-    return tauriListen<T>(event, handler)
+    const { getCurrentWebviewWindow } = await import(
+      '@tauri-apps/api/webviewWindow'
+    )
+    return getCurrentWebviewWindow().listen<T>(event, handler)
   }
   return wsTransport.listen<T>(event, handler)
 }
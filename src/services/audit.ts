@@ -0,0 +1,47 @@
+/**
+ * Audit log service
+ *
+ * Provides a TanStack Query hook for the web requests agents made, recorded
+ * after each run.
+ */
+
+import { useQuery } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import type { WebRequest } from '@/types/audit'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for the audit log
+export const auditQueryKeys = {
+  all: ['audit'] as const,
+  webRequests: (projectId: string | null, sessionId: string | null) =>
+    [...auditQueryKeys.all, 'web-requests', projectId, sessionId] as const,
+}
+
+/**
+ * Hook for web requests made by agents, newest first
+ */
+export function useWebRequests({
+  projectId = null,
+  sessionId = null,
+  limit,
+}: {
+  projectId?: string | null
+  sessionId?: string | null
+  limit?: number
+}) {
+  return useQuery({
+    queryKey: auditQueryKeys.webRequests(projectId, sessionId),
+    queryFn: async (): Promise<WebRequest[]> => {
+      if (!isTauri()) return []
+      return invoke<WebRequest[]>('list_web_requests', {
+        projectId,
+        sessionId,
+        limit,
+      })
+    },
+    staleTime: 1000 * 30,
+  })
+}
@@ -439,6 +439,7 @@ export function useUpdateSessionState() {
       waitingForInputType,
       planFilePath,
       pendingPlanMessageId,
+      attribution,
     }: {
       worktreeId: string
       worktreePath: string
@@ -461,6 +462,8 @@ export function useUpdateSessionState() {
       waitingForInputType?: 'question' | 'plan' | null
       planFilePath?: string | null
       pendingPlanMessageId?: string | null
+      /** Client or cost center; empty string falls back to the project's */
+      attribution?: string
     }): Promise<void> => {
       if (!isTauri()) {
         throw new Error('Not in Tauri context')
@@ -481,6 +484,7 @@ export function useUpdateSessionState() {
         waitingForInputType,
         planFilePath,
         pendingPlanMessageId,
+        attribution,
       })
       logger.debug('Session state updated')
     },
@@ -1176,6 +1180,7 @@ export function useSendMessage() {
       mcpConfig,
       chromeEnabled,
       customProfileSettings,
+      backgroundPriority,
     }: {
       sessionId: string
       worktreeId: string
@@ -1192,6 +1197,7 @@ export function useSendMessage() {
       mcpConfig?: string
       chromeEnabled?: boolean
       customProfileSettings?: string
+      backgroundPriority?: boolean
     }): Promise<ChatMessage> => {
       if (!isTauri()) {
         throw new Error('Not in Tauri context')
@@ -1210,6 +1216,7 @@ export function useSendMessage() {
         allowedTools,
         mcpConfig: mcpConfig ? '(set)' : undefined,
         chromeEnabled,
+        backgroundPriority,
       })
       const response = await invoke<ChatMessage>('send_chat_message', {
         sessionId,
@@ -1227,6 +1234,7 @@ export function useSendMessage() {
         mcpConfig,
         chromeEnabled,
         customProfileSettings,
+        backgroundPriority,
       })
       logger.info('Chat message sent', { responseId: response.id })
       return response
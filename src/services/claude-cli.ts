@@ -16,7 +16,9 @@ import type {
   ClaudeAuthStatus,
   ReleaseInfo,
   InstallProgress,
+  PinnedCliVersion,
 } from '@/types/claude-cli'
+import type { CliCapabilities } from '@/types/cli-capabilities'
 
 import { hasBackend } from '@/lib/environment'
 
@@ -28,6 +30,12 @@ export const claudeCliQueryKeys = {
   status: () => [...claudeCliQueryKeys.all, 'status'] as const,
   auth: () => [...claudeCliQueryKeys.all, 'auth'] as const,
   versions: () => [...claudeCliQueryKeys.all, 'versions'] as const,
+  installedVersions: () =>
+    [...claudeCliQueryKeys.all, 'installed-versions'] as const,
+  pinned: (worktreeId: string) =>
+    [...claudeCliQueryKeys.all, 'pinned', worktreeId] as const,
+  capabilities: (cli: string) =>
+    [...claudeCliQueryKeys.all, 'capabilities', cli] as const,
 }
 
 /**
@@ -60,6 +68,32 @@ export function useClaudeCliStatus() {
   })
 }
 
+/**
+ * Hook to get the probed flags and features of an installed agent CLI.
+ * The backend re-probes only when the binary changes.
+ */
+export function useCliCapabilities(
+  cli: 'claude' | 'aider',
+  options?: { enabled?: boolean }
+) {
+  return useQuery({
+    queryKey: claudeCliQueryKeys.capabilities(cli),
+    queryFn: async (): Promise<CliCapabilities | null> => {
+      if (!isTauri()) return null
+
+      try {
+        return await invoke<CliCapabilities>('get_cli_capabilities', { cli })
+      } catch (error) {
+        logger.warn('Failed to get CLI capabilities', { cli, error })
+        return null
+      }
+    },
+    enabled: options?.enabled ?? true,
+    staleTime: 1000 * 60 * 5, // 5 minutes
+    gcTime: 1000 * 60 * 10, // 10 minutes
+  })
+}
+
 /**
  * Hook to check if Claude CLI is authenticated
  */
@@ -152,6 +186,9 @@ export function useInstallClaudeCli() {
     onSuccess: () => {
       // Invalidate status to refetch
       queryClient.invalidateQueries({ queryKey: claudeCliQueryKeys.status() })
+      queryClient.invalidateQueries({
+        queryKey: claudeCliQueryKeys.capabilities('claude'),
+      })
       logger.info('Claude CLI installed successfully')
       toast.success('Claude CLI installed successfully')
     },
@@ -163,6 +200,75 @@ export function useInstallClaudeCli() {
   })
 }
 
+/**
+ * Hook for the Claude CLI versions installed for pinning
+ */
+export function useInstalledCliVersions() {
+  return useQuery({
+    queryKey: claudeCliQueryKeys.installedVersions(),
+    queryFn: async (): Promise<string[]> => {
+      if (!isTauri()) return []
+      return invoke<string[]>('list_installed_cli_versions')
+    },
+  })
+}
+
+/**
+ * Hook for the Claude CLI version a worktree's sessions are pinned to
+ * (project settings, else jean.json)
+ */
+export function usePinnedCliVersion(
+  worktreeId: string | null,
+  worktreePath: string | null
+) {
+  return useQuery({
+    queryKey: claudeCliQueryKeys.pinned(worktreeId ?? ''),
+    queryFn: async (): Promise<PinnedCliVersion | null> => {
+      if (!isTauri() || !worktreeId || !worktreePath) return null
+      return invoke<PinnedCliVersion | null>('get_pinned_cli_version', {
+        worktreeId,
+        worktreePath,
+      })
+    },
+    enabled: !!worktreeId && !!worktreePath,
+  })
+}
+
+/**
+ * Hook to install a Claude CLI version for pinning, beside the default install
+ */
+export function useInstallCliVersion() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (version: string) => {
+      if (!isTauri()) {
+        throw new Error('Cannot install CLI outside Tauri context')
+      }
+
+      logger.info('Installing pinned Claude CLI version', { version })
+      await invoke('install_cli_version', { version })
+    },
+    retry: false,
+    onSuccess: (_, version) => {
+      queryClient.invalidateQueries({
+        queryKey: claudeCliQueryKeys.installedVersions(),
+      })
+      queryClient.invalidateQueries({
+        queryKey: [...claudeCliQueryKeys.all, 'pinned'],
+      })
+      toast.success(`Claude CLI ${version} installed`)
+    },
+    onError: error => {
+      const message = error instanceof Error ? error.message : String(error)
+      logger.error('Failed to install Claude CLI version', { error })
+      toast.error('Failed to install Claude CLI version', {
+        description: message,
+      })
+    },
+  })
+}
+
 /**
  * Hook to listen for installation progress events
  * Returns [progress, resetProgress] tuple to allow resetting state before new install
@@ -0,0 +1,63 @@
+/**
+ * Stale temp file cleanup service
+ *
+ * Provides TanStack Query hooks to list and remove leftovers of failed
+ * installs (extraction dirs, partial downloads) and scratch dirs of deleted
+ * sessions. The backend also cleans these up at startup.
+ */
+
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type { CleanupReport, StaleItem } from '@/types/cleanup'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for stale files
+export const staleFilesQueryKeys = {
+  all: ['stale-files'] as const,
+}
+
+/**
+ * Hook for the stale leftovers that a cleanup would remove
+ */
+export function useStaleFiles() {
+  return useQuery({
+    queryKey: staleFilesQueryKeys.all,
+    queryFn: async (): Promise<StaleItem[]> => {
+      if (!isTauri()) return []
+      return invoke<StaleItem[]>('find_stale_files')
+    },
+  })
+}
+
+/**
+ * Hook to remove stale leftovers, reporting the reclaimed space
+ */
+export function useCleanStaleFiles() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (): Promise<CleanupReport> => {
+      return invoke<CleanupReport>('clean_stale_files')
+    },
+    onSuccess: report => {
+      queryClient.invalidateQueries({ queryKey: staleFilesQueryKeys.all })
+      const mb = (report.reclaimed_bytes / (1024 * 1024)).toFixed(1)
+      if (report.failed.length > 0) {
+        toast.warning(
+          `Reclaimed ${mb} MB, ${report.failed.length} item(s) couldn't be removed`
+        )
+      } else {
+        toast.success(`Reclaimed ${mb} MB`)
+      }
+    },
+    onError: error => {
+      logger.error('Failed to clean up stale files', { error })
+      toast.error(`Failed to clean up stale files: ${error}`)
+    },
+  })
+}
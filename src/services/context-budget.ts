@@ -0,0 +1,62 @@
+/**
+ * Context budget service
+ *
+ * Checks whether a message fits the model's context window together with
+ * the session's attached context, and condenses large saved contexts when
+ * it doesn't. Prompts sent anyway are refused with a context:over-budget
+ * event carrying the same report.
+ */
+
+import { useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type { Attachment, BudgetReport } from '@/types/context-budget'
+import { githubQueryKeys } from './github'
+
+/**
+ * Over-budget report for sending a message in a session, or null if it fits
+ */
+export async function checkContextBudget(
+  sessionId: string,
+  message: string,
+  model?: string | null
+): Promise<BudgetReport | null> {
+  return invoke<BudgetReport | null>('check_context_budget', {
+    sessionId,
+    message,
+    model: model ?? null,
+  })
+}
+
+/**
+ * Hook to replace a session's attached saved contexts with condensed versions
+ */
+export function useSummarizeContextAttachments() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async ({
+      sessionId,
+      slugs,
+    }: {
+      sessionId: string
+      slugs: string[]
+    }): Promise<Attachment[]> => {
+      return invoke<Attachment[]>('summarize_context_attachments', {
+        sessionId,
+        slugs,
+      })
+    },
+    onSuccess: (_, { sessionId }) => {
+      queryClient.invalidateQueries({
+        queryKey: githubQueryKeys.attachedContexts(sessionId),
+      })
+      toast.success('Context summarized')
+    },
+    onError: error => {
+      logger.error('Failed to summarize context', { error })
+      toast.error(`Failed to summarize context: ${error}`)
+    },
+  })
+}
@@ -0,0 +1,113 @@
+/**
+ * Emergency stop service
+ *
+ * Provides TanStack Query hooks for the emergency stop. A stop kills every
+ * running session and cancels downloads; until it is cleared, event rules
+ * don't fire, queued messages are held and runs get no auto-approval.
+ */
+
+import { useEffect } from 'react'
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke, listen } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type { EmergencyStatus, EmergencyStopReport } from '@/types/emergency'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for the emergency stop
+export const emergencyQueryKeys = {
+  all: ['emergency'] as const,
+}
+
+/**
+ * Hook for the emergency stop status, kept current when another window or a
+ * web client engages or clears it
+ */
+export function useEmergencyStatus() {
+  const queryClient = useQueryClient()
+
+  useEffect(() => {
+    if (!isTauri()) return
+
+    let cancelled = false
+    const unlistens: (() => void)[] = []
+    const subscribe = (event: string, handler: () => void) => {
+      listen(event, handler)
+        .then(fn => {
+          if (cancelled) fn()
+          else unlistens.push(fn)
+        })
+        .catch(error => {
+          logger.error(`Failed to listen for ${event}`, { error })
+        })
+    }
+    const refresh = () => {
+      queryClient.invalidateQueries({ queryKey: emergencyQueryKeys.all })
+    }
+    subscribe('emergency:stopped', refresh)
+    subscribe('emergency:cleared', refresh)
+
+    return () => {
+      cancelled = true
+      unlistens.forEach(fn => fn())
+    }
+  }, [queryClient])
+
+  return useQuery({
+    queryKey: emergencyQueryKeys.all,
+    queryFn: async (): Promise<EmergencyStatus> => {
+      if (!isTauri()) return { engaged: false, stopped_at: null }
+      return invoke<EmergencyStatus>('get_emergency_status')
+    },
+  })
+}
+
+/**
+ * Hook to halt all agent activity at once
+ */
+export function useEmergencyStop() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (): Promise<EmergencyStopReport> => {
+      return invoke<EmergencyStopReport>('emergency_stop')
+    },
+    onSuccess: report => {
+      queryClient.setQueryData<EmergencyStatus>(emergencyQueryKeys.all, {
+        engaged: true,
+        stopped_at: report.stopped_at,
+      })
+      toast.warning(
+        `Emergency stop: halted ${report.sessions_stopped.length} session(s) and ${report.downloads_cancelled} download(s)`
+      )
+    },
+    onError: error => {
+      logger.error('Emergency stop failed', { error })
+      toast.error(`Emergency stop failed: ${error}`)
+    },
+  })
+}
+
+/**
+ * Hook to lift the emergency stop and resume queues, rules and auto-approval
+ */
+export function useClearEmergencyStop() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (): Promise<EmergencyStatus> => {
+      return invoke<EmergencyStatus>('clear_emergency_stop')
+    },
+    onSuccess: status => {
+      queryClient.setQueryData(emergencyQueryKeys.all, status)
+      toast.success('Emergency stop cleared')
+    },
+    onError: error => {
+      logger.error('Failed to clear emergency stop', { error })
+      toast.error(`Failed to clear emergency stop: ${error}`)
+    },
+  })
+}
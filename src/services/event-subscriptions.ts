@@ -0,0 +1,19 @@
+import { invoke } from '@/lib/transport'
+import { logger } from '@/lib/logger'
+import { hasBackend } from '@/lib/environment'
+import type { EventSubscriptions } from '@/types/http-server'
+
+/**
+ * Narrow the events this window (or WebSocket client) receives.
+ * Pass `{}` to receive everything again.
+ */
+export async function setEventSubscriptions(
+  subscriptions: EventSubscriptions
+): Promise<void> {
+  if (!hasBackend()) return
+  try {
+    await invoke('set_event_subscriptions', { subscriptions })
+  } catch (error) {
+    logger.warn('Failed to set event subscriptions', { error })
+  }
+}
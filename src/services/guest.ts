@@ -0,0 +1,107 @@
+/**
+ * Read-only guest mode service
+ *
+ * Provides TanStack Query hooks for the guest mode status and for entering
+ * and leaving it with a passcode. While guest mode is on, the backend
+ * refuses every command that changes something.
+ */
+
+import { useEffect } from 'react'
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke, listen } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type { GuestModeStatus } from '@/types/guest'
+import { preferencesQueryKeys } from '@/services/preferences'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for guest mode
+export const guestModeQueryKeys = {
+  all: ['guest-mode'] as const,
+}
+
+/**
+ * Hook for the guest mode status, kept current when another window or a
+ * web client toggles it
+ */
+export function useGuestMode() {
+  const queryClient = useQueryClient()
+
+  useEffect(() => {
+    if (!isTauri()) return
+
+    let cancelled = false
+    let unlisten: (() => void) | null = null
+    listen<GuestModeStatus>('guest-mode:changed', event => {
+      queryClient.setQueryData(guestModeQueryKeys.all, event.payload)
+      // Preferences are redacted in guest mode
+      queryClient.invalidateQueries({ queryKey: preferencesQueryKeys.all })
+    })
+      .then(fn => {
+        if (cancelled) fn()
+        else unlisten = fn
+      })
+      .catch(error => {
+        logger.error('Failed to listen for guest mode changes', { error })
+      })
+
+    return () => {
+      cancelled = true
+      unlisten?.()
+    }
+  }, [queryClient])
+
+  return useQuery({
+    queryKey: guestModeQueryKeys.all,
+    queryFn: async (): Promise<GuestModeStatus> => {
+      if (!isTauri()) return { enabled: false, enabled_at: null }
+      return invoke<GuestModeStatus>('get_guest_mode')
+    },
+  })
+}
+
+/**
+ * Hook to enter guest mode; the same passcode is needed to leave it
+ */
+export function useEnterGuestMode() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (passcode: string): Promise<GuestModeStatus> => {
+      return invoke<GuestModeStatus>('enter_guest_mode', { passcode })
+    },
+    onSuccess: status => {
+      queryClient.setQueryData(guestModeQueryKeys.all, status)
+      toast.success('Guest mode on: changes are disabled')
+    },
+    onError: error => {
+      logger.error('Failed to enter guest mode', { error })
+      toast.error(`Failed to enter guest mode: ${error}`)
+    },
+  })
+}
+
+/**
+ * Hook to leave guest mode. Wrong passcodes lock it for a minute after a
+ * few attempts.
+ */
+export function useExitGuestMode() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (passcode: string): Promise<GuestModeStatus> => {
+      return invoke<GuestModeStatus>('exit_guest_mode', { passcode })
+    },
+    onSuccess: status => {
+      queryClient.setQueryData(guestModeQueryKeys.all, status)
+      toast.success('Guest mode off')
+    },
+    onError: error => {
+      logger.error('Failed to exit guest mode', { error })
+      toast.error(`${error}`)
+    },
+  })
+}
@@ -0,0 +1,118 @@
+/**
+ * Knowledge base service
+ *
+ * Provides TanStack Query hooks for lessons: distilling one from a session,
+ * saving, listing, searching and deleting them.
+ */
+
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type { Lesson, LessonDraft, LessonHit } from '@/types/knowledge'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for the knowledge base
+export const knowledgeQueryKeys = {
+  all: ['knowledge'] as const,
+  lessons: (tag: string | null) =>
+    [...knowledgeQueryKeys.all, 'lessons', tag] as const,
+  search: (query: string, tags: string[]) =>
+    [...knowledgeQueryKeys.all, 'search', query, tags] as const,
+}
+
+/**
+ * Hook to list saved lessons, newest first, optionally with a tag
+ */
+export function useLessons(tag: string | null = null) {
+  return useQuery({
+    queryKey: knowledgeQueryKeys.lessons(tag),
+    queryFn: async (): Promise<Lesson[]> => {
+      if (!isTauri()) return []
+      return invoke<Lesson[]>('list_lessons', { tag })
+    },
+  })
+}
+
+/**
+ * Hook to search lessons. Disabled until there's a query.
+ */
+export function useSearchLessons(query: string, tags: string[] = []) {
+  const trimmed = query.trim()
+  return useQuery({
+    queryKey: knowledgeQueryKeys.search(trimmed, tags),
+    queryFn: async (): Promise<LessonHit[]> => {
+      if (!isTauri()) return []
+      return invoke<LessonHit[]>('search_lessons', { query: trimmed, tags })
+    },
+    enabled: trimmed.length > 0,
+  })
+}
+
+/**
+ * Hook to draft a lesson from a session (not saved until useSaveLesson)
+ */
+export function useDistillLesson() {
+  return useMutation({
+    mutationFn: async (sessionId: string): Promise<LessonDraft> => {
+      return invoke<LessonDraft>('distill_session_lesson', { sessionId })
+    },
+    onError: error => {
+      logger.error('Failed to distill lesson', { error })
+      toast.error(`Failed to distill lesson: ${error}`)
+    },
+  })
+}
+
+/**
+ * Hook to save a lesson, optionally linked to the session it came from
+ */
+export function useSaveLesson() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async ({
+      draft,
+      sessionId,
+    }: {
+      draft: LessonDraft
+      sessionId?: string | null
+    }): Promise<Lesson> => {
+      return invoke<Lesson>('save_lesson', {
+        ...draft,
+        sessionId: sessionId ?? null,
+      })
+    },
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: knowledgeQueryKeys.all })
+      toast.success('Lesson saved')
+    },
+    onError: error => {
+      logger.error('Failed to save lesson', { error })
+      toast.error(`Failed to save lesson: ${error}`)
+    },
+  })
+}
+
+/**
+ * Hook to delete a lesson
+ */
+export function useDeleteLesson() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (lessonId: string): Promise<void> => {
+      await invoke('delete_lesson', { lessonId })
+    },
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: knowledgeQueryKeys.all })
+    },
+    onError: error => {
+      logger.error('Failed to delete lesson', { error })
+      toast.error(`Failed to delete lesson: ${error}`)
+    },
+  })
+}
@@ -0,0 +1,41 @@
+/**
+ * Launch defaults service
+ *
+ * Provides a TanStack Query hook for the command a session's next message
+ * would start, with the global, project and profile launch defaults applied.
+ */
+
+import { useQuery } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import type {
+  EffectiveLaunchCommand,
+  LaunchCommandOptions,
+} from '@/types/launch'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for launch commands
+export const launchQueryKeys = {
+  all: ['launch'] as const,
+  effectiveCommand: (options: LaunchCommandOptions | null) =>
+    [...launchQueryKeys.all, 'effective-command', options] as const,
+}
+
+/**
+ * Hook for the effective launch command of a session (null options disable it)
+ */
+export function useEffectiveLaunchCommand(options: LaunchCommandOptions | null) {
+  return useQuery({
+    queryKey: launchQueryKeys.effectiveCommand(options),
+    queryFn: async (): Promise<EffectiveLaunchCommand | null> => {
+      if (!isTauri() || !options) return null
+      return invoke<EffectiveLaunchCommand>('get_effective_launch_command', {
+        options,
+      })
+    },
+    enabled: !!options,
+    staleTime: 1000 * 10,
+  })
+}
@@ -95,6 +95,7 @@ describe('preferences service', () => {
         remote_poll_interval: 60,
         keybindings: DEFAULT_KEYBINDINGS,
         archive_retention_days: 30,
+        scratch_retention_days: 7,
         session_grouping_enabled: true,
         syntax_theme_dark: 'vitesse-black',
         syntax_theme_light: 'github-light',
@@ -128,6 +129,59 @@ describe('preferences service', () => {
         zoom_level: 100,
         custom_cli_profiles: [],
         default_provider: null,
+        speech_provider: 'local',
+        speech_local_model: 'base.en',
+        speech_api_url: 'https://api.openai.com/v1/audio/transcriptions',
+        speech_api_model: 'whisper-1',
+        speech_api_key: null,
+        speak_waiting_summary: false,
+        speak_review_summary: false,
+        speech_voice: null,
+        prevent_sleep_during_runs: true,
+        defer_jobs_battery_threshold: 20,
+        defer_jobs_on_metered: true,
+        background_job_overrides: {
+          update_check: false,
+          report_generation: false,
+          batch_tasks: false,
+        },
+        crash_report_endpoint: null,
+        extra_ca_cert_path: null,
+        use_system_cert_store: false,
+        keep_mark_of_the_web: false,
+        claude_monthly_budget_usd: null,
+        backup_folder: null,
+        backup_interval_hours: 24,
+        backup_keep_count: 7,
+        shared_library_repo: null,
+        shared_library_sync_hours: 6,
+        warm_process_pool: false,
+        download_speed_limit_kib: null,
+        auto_attach_command_runs: 0,
+        command_runs_token_budget: 4000,
+        repo_map_token_budget: 2000,
+        semantic_search_enabled: false,
+        embeddings_api_url: 'http://localhost:11434/v1/embeddings',
+        embeddings_api_model: 'nomic-embed-text',
+        embeddings_api_key: null,
+        maintenance_window_start: null,
+        maintenance_window_end: null,
+        report_locale: 'en-US',
+        csv_delimiter: null,
+        announcement_verbosity: {
+          session_finished: 'brief',
+          approval_needed: 'brief',
+          install_failed: 'brief',
+        },
+        respect_focus_mode: true,
+        focus_overrides: {
+          session_finished: false,
+          input_needed: false,
+          event_rules: false,
+          general: false,
+        },
+        calendar_ics_path: null,
+        vulnerability_scan_before_sessions: false,
       }
       vi.mocked(invoke).mockResolvedValueOnce(mockPreferences)
 
@@ -191,6 +245,7 @@ describe('preferences service', () => {
           toggle_left_sidebar: 'mod+1', // Old default
         },
         archive_retention_days: 30,
+        scratch_retention_days: 7,
         session_grouping_enabled: true,
         syntax_theme_dark: 'vitesse-black',
         syntax_theme_light: 'github-light',
@@ -224,6 +279,59 @@ describe('preferences service', () => {
         zoom_level: 100,
         custom_cli_profiles: [],
         default_provider: null,
+        speech_provider: 'local',
+        speech_local_model: 'base.en',
+        speech_api_url: 'https://api.openai.com/v1/audio/transcriptions',
+        speech_api_model: 'whisper-1',
+        speech_api_key: null,
+        speak_waiting_summary: false,
+        speak_review_summary: false,
+        speech_voice: null,
+        prevent_sleep_during_runs: true,
+        defer_jobs_battery_threshold: 20,
+        defer_jobs_on_metered: true,
+        background_job_overrides: {
+          update_check: false,
+          report_generation: false,
+          batch_tasks: false,
+        },
+        crash_report_endpoint: null,
+        extra_ca_cert_path: null,
+        use_system_cert_store: false,
+        keep_mark_of_the_web: false,
+        claude_monthly_budget_usd: null,
+        backup_folder: null,
+        backup_interval_hours: 24,
+        backup_keep_count: 7,
+        shared_library_repo: null,
+        shared_library_sync_hours: 6,
+        warm_process_pool: false,
+        download_speed_limit_kib: null,
+        auto_attach_command_runs: 0,
+        command_runs_token_budget: 4000,
+        repo_map_token_budget: 2000,
+        semantic_search_enabled: false,
+        embeddings_api_url: 'http://localhost:11434/v1/embeddings',
+        embeddings_api_model: 'nomic-embed-text',
+        embeddings_api_key: null,
+        maintenance_window_start: null,
+        maintenance_window_end: null,
+        report_locale: 'en-US',
+        csv_delimiter: null,
+        announcement_verbosity: {
+          session_finished: 'brief',
+          approval_needed: 'brief',
+          install_failed: 'brief',
+        },
+        respect_focus_mode: true,
+        focus_overrides: {
+          session_finished: false,
+          input_needed: false,
+          event_rules: false,
+          general: false,
+        },
+        calendar_ics_path: null,
+        vulnerability_scan_before_sessions: false,
       }
       vi.mocked(invoke).mockResolvedValueOnce(prefsWithOldBinding)
 
@@ -264,6 +372,7 @@ describe('preferences service', () => {
         remote_poll_interval: 120,
         keybindings: DEFAULT_KEYBINDINGS,
         archive_retention_days: 7,
+        scratch_retention_days: 7,
         session_grouping_enabled: false,
         syntax_theme_dark: 'vitesse-black',
         syntax_theme_light: 'github-light',
@@ -297,6 +406,59 @@ describe('preferences service', () => {
         zoom_level: 100,
         custom_cli_profiles: [],
         default_provider: null,
+        speech_provider: 'local',
+        speech_local_model: 'base.en',
+        speech_api_url: 'https://api.openai.com/v1/audio/transcriptions',
+        speech_api_model: 'whisper-1',
+        speech_api_key: null,
+        speak_waiting_summary: false,
+        speak_review_summary: false,
+        speech_voice: null,
+        prevent_sleep_during_runs: true,
+        defer_jobs_battery_threshold: 20,
+        defer_jobs_on_metered: true,
+        background_job_overrides: {
+          update_check: false,
+          report_generation: false,
+          batch_tasks: false,
+        },
+        crash_report_endpoint: null,
+        extra_ca_cert_path: null,
+        use_system_cert_store: false,
+        keep_mark_of_the_web: false,
+        claude_monthly_budget_usd: null,
+        backup_folder: null,
+        backup_interval_hours: 24,
+        backup_keep_count: 7,
+        shared_library_repo: null,
+        shared_library_sync_hours: 6,
+        warm_process_pool: false,
+        download_speed_limit_kib: null,
+        auto_attach_command_runs: 0,
+        command_runs_token_budget: 4000,
+        repo_map_token_budget: 2000,
+        semantic_search_enabled: false,
+        embeddings_api_url: 'http://localhost:11434/v1/embeddings',
+        embeddings_api_model: 'nomic-embed-text',
+        embeddings_api_key: null,
+        maintenance_window_start: null,
+        maintenance_window_end: null,
+        report_locale: 'en-US',
+        csv_delimiter: null,
+        announcement_verbosity: {
+          session_finished: 'brief',
+          approval_needed: 'brief',
+          install_failed: 'brief',
+        },
+        respect_focus_mode: true,
+        focus_overrides: {
+          session_finished: false,
+          input_needed: false,
+          event_rules: false,
+          general: false,
+        },
+        calendar_ics_path: null,
+        vulnerability_scan_before_sessions: false,
       }
 
       const { result } = renderHook(() => useSavePreferences(), {
@@ -335,6 +497,7 @@ describe('preferences service', () => {
         remote_poll_interval: 60,
         keybindings: DEFAULT_KEYBINDINGS,
         archive_retention_days: 30,
+        scratch_retention_days: 7,
         session_grouping_enabled: true,
         syntax_theme_dark: 'vitesse-black',
         syntax_theme_light: 'github-light',
@@ -368,6 +531,59 @@ describe('preferences service', () => {
         zoom_level: 100,
         custom_cli_profiles: [],
         default_provider: null,
+        speech_provider: 'local',
+        speech_local_model: 'base.en',
+        speech_api_url: 'https://api.openai.com/v1/audio/transcriptions',
+        speech_api_model: 'whisper-1',
+        speech_api_key: null,
+        speak_waiting_summary: false,
+        speak_review_summary: false,
+        speech_voice: null,
+        prevent_sleep_during_runs: true,
+        defer_jobs_battery_threshold: 20,
+        defer_jobs_on_metered: true,
+        background_job_overrides: {
+          update_check: false,
+          report_generation: false,
+          batch_tasks: false,
+        },
+        crash_report_endpoint: null,
+        extra_ca_cert_path: null,
+        use_system_cert_store: false,
+        keep_mark_of_the_web: false,
+        claude_monthly_budget_usd: null,
+        backup_folder: null,
+        backup_interval_hours: 24,
+        backup_keep_count: 7,
+        shared_library_repo: null,
+        shared_library_sync_hours: 6,
+        warm_process_pool: false,
+        download_speed_limit_kib: null,
+        auto_attach_command_runs: 0,
+        command_runs_token_budget: 4000,
+        repo_map_token_budget: 2000,
+        semantic_search_enabled: false,
+        embeddings_api_url: 'http://localhost:11434/v1/embeddings',
+        embeddings_api_model: 'nomic-embed-text',
+        embeddings_api_key: null,
+        maintenance_window_start: null,
+        maintenance_window_end: null,
+        report_locale: 'en-US',
+        csv_delimiter: null,
+        announcement_verbosity: {
+          session_finished: 'brief',
+          approval_needed: 'brief',
+          install_failed: 'brief',
+        },
+        respect_focus_mode: true,
+        focus_overrides: {
+          session_finished: false,
+          input_needed: false,
+          event_rules: false,
+          general: false,
+        },
+        calendar_ics_path: null,
+        vulnerability_scan_before_sessions: false,
       }
 
       const { result } = renderHook(() => useSavePreferences(), {
@@ -406,6 +622,7 @@ describe('preferences service', () => {
         remote_poll_interval: 60,
         keybindings: DEFAULT_KEYBINDINGS,
         archive_retention_days: 30,
+        scratch_retention_days: 7,
         session_grouping_enabled: true,
         syntax_theme_dark: 'vitesse-black',
         syntax_theme_light: 'github-light',
@@ -439,6 +656,59 @@ describe('preferences service', () => {
         zoom_level: 100,
         custom_cli_profiles: [],
         default_provider: null,
+        speech_provider: 'local',
+        speech_local_model: 'base.en',
+        speech_api_url: 'https://api.openai.com/v1/audio/transcriptions',
+        speech_api_model: 'whisper-1',
+        speech_api_key: null,
+        speak_waiting_summary: false,
+        speak_review_summary: false,
+        speech_voice: null,
+        prevent_sleep_during_runs: true,
+        defer_jobs_battery_threshold: 20,
+        defer_jobs_on_metered: true,
+        background_job_overrides: {
+          update_check: false,
+          report_generation: false,
+          batch_tasks: false,
+        },
+        crash_report_endpoint: null,
+        extra_ca_cert_path: null,
+        use_system_cert_store: false,
+        keep_mark_of_the_web: false,
+        claude_monthly_budget_usd: null,
+        backup_folder: null,
+        backup_interval_hours: 24,
+        backup_keep_count: 7,
+        shared_library_repo: null,
+        shared_library_sync_hours: 6,
+        warm_process_pool: false,
+        download_speed_limit_kib: null,
+        auto_attach_command_runs: 0,
+        command_runs_token_budget: 4000,
+        repo_map_token_budget: 2000,
+        semantic_search_enabled: false,
+        embeddings_api_url: 'http://localhost:11434/v1/embeddings',
+        embeddings_api_model: 'nomic-embed-text',
+        embeddings_api_key: null,
+        maintenance_window_start: null,
+        maintenance_window_end: null,
+        report_locale: 'en-US',
+        csv_delimiter: null,
+        announcement_verbosity: {
+          session_finished: 'brief',
+          approval_needed: 'brief',
+          install_failed: 'brief',
+        },
+        respect_focus_mode: true,
+        focus_overrides: {
+          session_finished: false,
+          input_needed: false,
+          event_rules: false,
+          general: false,
+        },
+        calendar_ics_path: null,
+        vulnerability_scan_before_sessions: false,
       }
 
       const { result } = renderHook(() => useSavePreferences(), {
@@ -475,6 +745,7 @@ describe('preferences service', () => {
         remote_poll_interval: 60,
         keybindings: DEFAULT_KEYBINDINGS,
         archive_retention_days: 30,
+        scratch_retention_days: 7,
         session_grouping_enabled: true,
         syntax_theme_dark: 'vitesse-black',
         syntax_theme_light: 'github-light',
@@ -508,6 +779,59 @@ describe('preferences service', () => {
         zoom_level: 100,
         custom_cli_profiles: [],
         default_provider: null,
+        speech_provider: 'local',
+        speech_local_model: 'base.en',
+        speech_api_url: 'https://api.openai.com/v1/audio/transcriptions',
+        speech_api_model: 'whisper-1',
+        speech_api_key: null,
+        speak_waiting_summary: false,
+        speak_review_summary: false,
+        speech_voice: null,
+        prevent_sleep_during_runs: true,
+        defer_jobs_battery_threshold: 20,
+        defer_jobs_on_metered: true,
+        background_job_overrides: {
+          update_check: false,
+          report_generation: false,
+          batch_tasks: false,
+        },
+        crash_report_endpoint: null,
+        extra_ca_cert_path: null,
+        use_system_cert_store: false,
+        keep_mark_of_the_web: false,
+        claude_monthly_budget_usd: null,
+        backup_folder: null,
+        backup_interval_hours: 24,
+        backup_keep_count: 7,
+        shared_library_repo: null,
+        shared_library_sync_hours: 6,
+        warm_process_pool: false,
+        download_speed_limit_kib: null,
+        auto_attach_command_runs: 0,
+        command_runs_token_budget: 4000,
+        repo_map_token_budget: 2000,
+        semantic_search_enabled: false,
+        embeddings_api_url: 'http://localhost:11434/v1/embeddings',
+        embeddings_api_model: 'nomic-embed-text',
+        embeddings_api_key: null,
+        maintenance_window_start: null,
+        maintenance_window_end: null,
+        report_locale: 'en-US',
+        csv_delimiter: null,
+        announcement_verbosity: {
+          session_finished: 'brief',
+          approval_needed: 'brief',
+          install_failed: 'brief',
+        },
+        respect_focus_mode: true,
+        focus_overrides: {
+          session_finished: false,
+          input_needed: false,
+          event_rules: false,
+          general: false,
+        },
+        calendar_ics_path: null,
+        vulnerability_scan_before_sessions: false,
       }
 
       const { result } = renderHook(() => useSavePreferences(), {
@@ -0,0 +1,119 @@
+/**
+ * Local user profiles service
+ *
+ * Provides TanStack Query hooks for listing, creating, renaming, deleting
+ * and switching profiles. Switching relaunches the app into the new profile.
+ */
+
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type { Profile, ProfileList } from '@/types/profiles'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for profiles
+export const profilesQueryKeys = {
+  all: ['profiles'] as const,
+}
+
+/**
+ * Hook to list profiles and the active one
+ */
+export function useProfiles() {
+  return useQuery({
+    queryKey: profilesQueryKeys.all,
+    queryFn: async (): Promise<ProfileList> => {
+      if (!isTauri()) return { active: 'default', profiles: [] }
+      return invoke<ProfileList>('list_profiles')
+    },
+  })
+}
+
+/**
+ * Hook to create a profile (its data directory starts empty)
+ */
+export function useCreateProfile() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (name: string): Promise<Profile> => {
+      return invoke<Profile>('create_profile', { name })
+    },
+    onSuccess: profile => {
+      queryClient.invalidateQueries({ queryKey: profilesQueryKeys.all })
+      toast.success(`Profile "${profile.name}" created`)
+    },
+    onError: error => {
+      logger.error('Failed to create profile', { error })
+      toast.error(`Failed to create profile: ${error}`)
+    },
+  })
+}
+
+/**
+ * Hook to rename a profile
+ */
+export function useRenameProfile() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async ({
+      profileId,
+      name,
+    }: {
+      profileId: string
+      name: string
+    }): Promise<Profile> => {
+      return invoke<Profile>('rename_profile', { profileId, name })
+    },
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: profilesQueryKeys.all })
+    },
+    onError: error => {
+      logger.error('Failed to rename profile', { error })
+      toast.error(`Failed to rename profile: ${error}`)
+    },
+  })
+}
+
+/**
+ * Hook to delete a profile with all of its data
+ */
+export function useDeleteProfile() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (profileId: string): Promise<void> => {
+      await invoke('delete_profile', { profileId })
+    },
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: profilesQueryKeys.all })
+    },
+    onError: error => {
+      logger.error('Failed to delete profile', { error })
+      toast.error(`Failed to delete profile: ${error}`)
+    },
+  })
+}
+
+/**
+ * Hook to switch profiles. The backend refuses while sessions are running;
+ * otherwise the app relaunches into the new profile.
+ */
+export function useSwitchProfile() {
+  return useMutation({
+    mutationFn: async (profileId: string): Promise<void> => {
+      await invoke('switch_profile', { profileId })
+      const { relaunch } = await import('@tauri-apps/plugin-process')
+      await relaunch()
+    },
+    onError: error => {
+      logger.error('Failed to switch profile', { error })
+      toast.error(`Failed to switch profile: ${error}`)
+    },
+  })
+}
@@ -0,0 +1,106 @@
+/**
+ * Project command runner service
+ *
+ * Provides TanStack Query hooks for running commands ("run tests",
+ * "run build") in a project and reading back their stored output.
+ */
+
+import { useEffect } from 'react'
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke, listen } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type { CommandRun } from '@/types/terminal'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for project command runs
+export const projectCommandQueryKeys = {
+  all: ['project-commands'] as const,
+  runs: (projectId: string) =>
+    [...projectCommandQueryKeys.all, 'runs', projectId] as const,
+  output: (projectId: string, runId: string) =>
+    [...projectCommandQueryKeys.all, 'output', projectId, runId] as const,
+}
+
+/**
+ * Hook to list a project's command runs, newest first.
+ * Refetches when a run finishes.
+ */
+export function useProjectCommandRuns(projectId: string | null) {
+  const queryClient = useQueryClient()
+
+  useEffect(() => {
+    if (!projectId || !isTauri()) return
+    const unlisten = listen<CommandRun>('project-command:finished', event => {
+      if (event.payload.project_id === projectId) {
+        queryClient.invalidateQueries({
+          queryKey: projectCommandQueryKeys.runs(projectId),
+        })
+      }
+    })
+    return () => {
+      unlisten.then(fn => fn())
+    }
+  }, [projectId, queryClient])
+
+  return useQuery({
+    queryKey: projectCommandQueryKeys.runs(projectId ?? ''),
+    queryFn: async (): Promise<CommandRun[]> => {
+      if (!isTauri() || !projectId) return []
+      return invoke<CommandRun[]>('list_project_command_runs', { projectId })
+    },
+    enabled: !!projectId,
+  })
+}
+
+/**
+ * Hook to get a finished run's output as plain text
+ */
+export function useProjectCommandOutput(
+  projectId: string | null,
+  runId: string | null
+) {
+  return useQuery({
+    queryKey: projectCommandQueryKeys.output(projectId ?? '', runId ?? ''),
+    queryFn: async (): Promise<string> => {
+      if (!isTauri() || !projectId || !runId) return ''
+      return invoke<string>('get_project_command_output', { projectId, runId })
+    },
+    enabled: !!projectId && !!runId,
+  })
+}
+
+/**
+ * Hook to run a command in a project (or one of its worktrees)
+ */
+export function useRunProjectCommand() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (params: {
+      projectId: string
+      command: string
+      worktreeId?: string
+    }): Promise<CommandRun> => {
+      logger.info('Running project command', params)
+      return invoke<CommandRun>('run_project_command', {
+        projectId: params.projectId,
+        command: params.command,
+        worktreeId: params.worktreeId ?? null,
+      })
+    },
+    onSuccess: run => {
+      queryClient.invalidateQueries({
+        queryKey: projectCommandQueryKeys.runs(run.project_id),
+      })
+    },
+    onError: error => {
+      const message = error instanceof Error ? error.message : String(error)
+      logger.error('Failed to run project command', { error })
+      toast.error('Failed to run command', { description: message })
+    },
+  })
+}
@@ -0,0 +1,107 @@
+/**
+ * Project config file service
+ *
+ * Provides TanStack Query hooks for a worktree's jean.json (validated by the
+ * backend) and for generating a starter one. The backend reports edits to
+ * the file in the active worktree with a project-config:changed event.
+ */
+
+import { useEffect } from 'react'
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke, listen } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type {
+  ProjectConfigChanged,
+  ProjectConfigStatus,
+} from '@/types/project-config'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for project config files
+export const projectConfigQueryKeys = {
+  all: ['project-config'] as const,
+  worktree: (worktreePath: string) =>
+    [...projectConfigQueryKeys.all, worktreePath] as const,
+}
+
+/**
+ * Hook for a worktree's jean.json, refreshed when the file changes
+ */
+export function useProjectConfig(worktreePath: string | null) {
+  const queryClient = useQueryClient()
+
+  useEffect(() => {
+    if (!isTauri()) return
+
+    let cancelled = false
+    let unlisten: (() => void) | null = null
+    listen<ProjectConfigChanged>('project-config:changed', event => {
+      const { worktree_path, status } = event.payload
+      queryClient.setQueryData(
+        projectConfigQueryKeys.worktree(worktree_path),
+        status
+      )
+    })
+      .then(fn => {
+        if (cancelled) fn()
+        else unlisten = fn
+      })
+      .catch(error => {
+        logger.error('Failed to listen for project config changes', { error })
+      })
+
+    return () => {
+      cancelled = true
+      unlisten?.()
+    }
+  }, [queryClient])
+
+  return useQuery({
+    queryKey: projectConfigQueryKeys.worktree(worktreePath ?? ''),
+    queryFn: async (): Promise<ProjectConfigStatus> => {
+      if (!isTauri() || !worktreePath) {
+        return { exists: false, config: null, issues: [] }
+      }
+      return invoke<ProjectConfigStatus>('get_project_config', {
+        worktreePath,
+      })
+    },
+    enabled: !!worktreePath,
+  })
+}
+
+/**
+ * Hook to write a starter jean.json to a project's repository root
+ */
+export function useGenerateProjectConfig() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (projectId: string): Promise<ProjectConfigStatus> => {
+      return invoke<ProjectConfigStatus>('generate_project_config', {
+        projectId,
+      })
+    },
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: projectConfigQueryKeys.all })
+      toast.success('Created jean.json')
+    },
+    onError: error => {
+      logger.error('Failed to generate jean.json', { error })
+      toast.error(`Failed to generate jean.json: ${error}`)
+    },
+  })
+}
+
+/**
+ * Approve the jean.json allowed_tools shown to the user for a project
+ */
+export async function approveAllowedTools(
+  projectId: string,
+  allowedTools: string[]
+): Promise<void> {
+  await invoke('approve_allowed_tools', { projectId, allowedTools })
+}
@@ -20,6 +20,7 @@ import type {
   WorktreePathExistsEvent,
   WorktreeBranchExistsEvent,
 } from '@/types/projects'
+import type { LaunchDefaultsByBackend } from '@/types/launch'
 import { useProjectsStore } from '@/store/projects-store'
 import { useChatStore } from '@/store/chat-store'
 import { useUIStore } from '@/store/ui-store'
@@ -1934,11 +1935,23 @@ export function useUpdateProjectSettings() {
       defaultBranch,
       enabledMcpServers,
       customSystemPrompt,
+      attribution,
+      cliVersion,
+      blockedDomains,
+      launchDefaults,
     }: {
       projectId: string
       defaultBranch?: string
       enabledMcpServers?: string[]
       customSystemPrompt?: string
+      /** Client or cost center; empty string clears it */
+      attribution?: string
+      /** Pinned Claude CLI version; empty string clears it */
+      cliVersion?: string
+      /** Domains agents may not fetch from; replaces the list */
+      blockedDomains?: string[]
+      /** Extra CLI args/env by backend; replaces all of them */
+      launchDefaults?: LaunchDefaultsByBackend
     }): Promise<Project> => {
       if (!isTauri()) {
         throw new Error('Not in Tauri context')
@@ -1950,6 +1963,10 @@ export function useUpdateProjectSettings() {
         defaultBranch,
         enabledMcpServers,
         customSystemPrompt,
+        attribution,
+        cliVersion,
+        blockedDomains,
+        launchDefaults,
       })
       logger.info('Project settings updated', { project })
       return project
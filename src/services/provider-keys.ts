@@ -0,0 +1,98 @@
+/**
+ * Provider key rotation service
+ *
+ * Provides TanStack Query hooks for key expiry and rotation status of the
+ * provider profiles, rotating a key (validated by the provider first) and
+ * rolling a rotation back within a day.
+ */
+
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type { CustomCliProfile } from '@/types/preferences'
+import type { ProviderKeyStatus } from '@/types/providers'
+import { preferencesQueryKeys } from '@/services/preferences'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for provider keys
+export const providerKeysQueryKeys = {
+  all: ['provider-keys'] as const,
+}
+
+/**
+ * Hook for key expiry, reminders and rollback availability per profile
+ */
+export function useProviderKeyStatus() {
+  return useQuery({
+    queryKey: providerKeysQueryKeys.all,
+    queryFn: async (): Promise<ProviderKeyStatus[]> => {
+      if (!isTauri()) return []
+      return invoke<ProviderKeyStatus[]>('get_provider_key_status')
+    },
+    staleTime: 1000 * 60 * 5,
+  })
+}
+
+/**
+ * Hook to rotate a profile's key. The provider must accept the new key
+ * before it replaces the old one.
+ */
+export function useRotateProviderKey() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async ({
+      profile,
+      newKey,
+      keyExpiresAt,
+    }: {
+      profile: string
+      newKey: string
+      /** Unix seconds the new key expires, if known */
+      keyExpiresAt?: number | null
+    }): Promise<CustomCliProfile> => {
+      return invoke<CustomCliProfile>('rotate_provider_key', {
+        profile,
+        newKey,
+        keyExpiresAt,
+      })
+    },
+    onSuccess: profile => {
+      queryClient.invalidateQueries({ queryKey: preferencesQueryKeys.all })
+      queryClient.invalidateQueries({ queryKey: providerKeysQueryKeys.all })
+      toast.success(`${profile.name} key rotated`, {
+        description: 'The previous key can be restored for 24 hours.',
+      })
+    },
+    onError: error => {
+      logger.error('Failed to rotate provider key', { error })
+      toast.error(`Failed to rotate key: ${error}`)
+    },
+  })
+}
+
+/**
+ * Hook to restore the key a profile had before its last rotation
+ */
+export function useRollbackProviderKey() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async (profile: string): Promise<CustomCliProfile> => {
+      return invoke<CustomCliProfile>('rollback_provider_key', { profile })
+    },
+    onSuccess: profile => {
+      queryClient.invalidateQueries({ queryKey: preferencesQueryKeys.all })
+      queryClient.invalidateQueries({ queryKey: providerKeysQueryKeys.all })
+      toast.success(`${profile.name} key restored`)
+    },
+    onError: error => {
+      logger.error('Failed to roll back provider key', { error })
+      toast.error(`Failed to roll back key: ${error}`)
+    },
+  })
+}
@@ -0,0 +1,45 @@
+/**
+ * Repository map service
+ *
+ * Provides a TanStack Query hook for a project's repository map: its source
+ * files with the symbols they define, rendered as markdown within a token
+ * budget.
+ */
+
+import { useQuery } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for repository maps
+export const repoMapQueryKeys = {
+  all: ['repo-map'] as const,
+  map: (projectId: string, worktreeId: string | null, budgetTokens: number) =>
+    [...repoMapQueryKeys.all, projectId, worktreeId, budgetTokens] as const,
+}
+
+/**
+ * Hook to get the repository map of a project, or of one of its worktrees.
+ * The map is built on first use, which can take a moment in large repos.
+ */
+export function useRepoMap(
+  projectId: string | null,
+  worktreeId: string | null = null,
+  budgetTokens = 2000
+) {
+  return useQuery({
+    queryKey: repoMapQueryKeys.map(projectId ?? '', worktreeId, budgetTokens),
+    queryFn: async (): Promise<string | null> => {
+      if (!isTauri() || !projectId) return null
+      return invoke<string>('get_repo_map', {
+        projectId,
+        worktreeId,
+        budgetTokens,
+      })
+    },
+    enabled: !!projectId,
+    staleTime: 1000 * 60, // Maps refresh about once a minute
+  })
+}
@@ -0,0 +1,86 @@
+/**
+ * Semantic code search service
+ *
+ * Provides TanStack Query hooks for searching a project's files by meaning
+ * and for rebuilding its embeddings index.
+ */
+
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type { IndexStats, SearchHit } from '@/types/semantic-search'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for semantic search
+export const semanticSearchQueryKeys = {
+  all: ['semantic-search'] as const,
+  search: (projectId: string, worktreeId: string | null, query: string) =>
+    [...semanticSearchQueryKeys.all, projectId, worktreeId, query] as const,
+}
+
+/**
+ * Hook to search a project (or one of its worktrees) by meaning.
+ * Disabled until there's a query.
+ */
+export function useSemanticSearch(
+  projectId: string | null,
+  worktreeId: string | null,
+  query: string,
+  k?: number
+) {
+  const trimmed = query.trim()
+  return useQuery({
+    queryKey: semanticSearchQueryKeys.search(
+      projectId ?? '',
+      worktreeId,
+      trimmed
+    ),
+    queryFn: async (): Promise<SearchHit[]> => {
+      if (!isTauri() || !projectId) return []
+      return invoke<SearchHit[]>('semantic_search', {
+        projectId,
+        worktreeId,
+        query: trimmed,
+        k,
+      })
+    },
+    enabled: !!projectId && trimmed.length > 0,
+    retry: false,
+  })
+}
+
+/**
+ * Hook to build or update a project's semantic index now
+ */
+export function useRebuildSemanticIndex() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async ({
+      projectId,
+      worktreeId,
+    }: {
+      projectId: string
+      worktreeId?: string | null
+    }): Promise<IndexStats> => {
+      return invoke<IndexStats>('rebuild_semantic_index', {
+        projectId,
+        worktreeId: worktreeId ?? null,
+      })
+    },
+    onSuccess: stats => {
+      queryClient.invalidateQueries({ queryKey: semanticSearchQueryKeys.all })
+      toast.success(
+        `Indexed ${stats.files} files (${stats.embedded} sections embedded)`
+      )
+    },
+    onError: error => {
+      logger.error('Failed to rebuild semantic index', { error })
+      toast.error(`Failed to index project: ${error}`)
+    },
+  })
+}
@@ -0,0 +1,79 @@
+/**
+ * Monorepo sub-project service
+ *
+ * Provides TanStack Query hooks for a project's sub-projects (detected from
+ * workspace manifests and jean.json) and for scoping a session to one.
+ */
+
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import { chatQueryKeys } from '@/services/chat'
+import type { Subproject } from '@/types/subprojects'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for sub-projects
+export const subprojectQueryKeys = {
+  all: ['subprojects'] as const,
+  project: (projectId: string) =>
+    [...subprojectQueryKeys.all, projectId] as const,
+}
+
+/**
+ * Hook for the sub-projects of a project's repository
+ */
+export function useSubprojects(projectId: string | null) {
+  return useQuery({
+    queryKey: subprojectQueryKeys.project(projectId ?? ''),
+    queryFn: async (): Promise<Subproject[]> => {
+      if (!isTauri() || !projectId) return []
+      return invoke<Subproject[]>('list_subprojects', { projectId })
+    },
+    enabled: !!projectId,
+  })
+}
+
+/**
+ * Hook to scope a session to a sub-project (null = the whole repository).
+ * Only possible before the session's first message.
+ */
+export function useSetSessionSubproject() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async ({
+      worktreeId,
+      worktreePath,
+      sessionId,
+      subproject,
+    }: {
+      worktreeId: string
+      worktreePath: string
+      sessionId: string
+      subproject: string | null
+    }): Promise<void> => {
+      await invoke('set_session_subproject', {
+        worktreeId,
+        worktreePath,
+        sessionId,
+        subproject,
+      })
+    },
+    onSuccess: (_, { sessionId, worktreeId }) => {
+      queryClient.invalidateQueries({
+        queryKey: chatQueryKeys.session(sessionId),
+      })
+      queryClient.invalidateQueries({
+        queryKey: chatQueryKeys.sessions(worktreeId),
+      })
+    },
+    onError: error => {
+      logger.error('Failed to set session sub-project', { error })
+      toast.error(`Failed to set sub-project: ${error}`)
+    },
+  })
+}
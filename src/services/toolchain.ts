@@ -0,0 +1,36 @@
+/**
+ * Project toolchain service
+ *
+ * Provides a TanStack Query hook for the languages and toolchains detected
+ * in a project, used to pre-fill verification commands and suggest tools.
+ */
+
+import { useQuery } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import type { ProjectToolchain } from '@/types/toolchain'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for project toolchains
+export const toolchainQueryKeys = {
+  all: ['toolchain'] as const,
+  project: (projectId: string) =>
+    [...toolchainQueryKeys.all, projectId] as const,
+}
+
+/**
+ * Hook for a project's detected toolchain
+ */
+export function useProjectToolchain(projectId: string | null) {
+  return useQuery({
+    queryKey: toolchainQueryKeys.project(projectId ?? ''),
+    queryFn: async (): Promise<ProjectToolchain | null> => {
+      if (!isTauri() || !projectId) return null
+      return invoke<ProjectToolchain>('get_project_toolchain', { projectId })
+    },
+    enabled: !!projectId,
+    staleTime: Infinity,
+  })
+}
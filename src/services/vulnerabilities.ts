@@ -0,0 +1,79 @@
+/**
+ * Dependency vulnerability scan service
+ *
+ * Provides TanStack Query hooks for the vulnerability report stored on a
+ * session and for scanning a worktree on demand. Scan results are attached
+ * to the session as a saved context.
+ */
+
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
+import { invoke } from '@/lib/transport'
+import { toast } from 'sonner'
+import { logger } from '@/lib/logger'
+import type { VulnerabilityReport } from '@/types/vulnerabilities'
+
+import { hasBackend } from '@/lib/environment'
+
+const isTauri = hasBackend
+
+// Query keys for vulnerability reports
+export const vulnerabilityQueryKeys = {
+  all: ['vulnerabilities'] as const,
+  session: (sessionId: string) =>
+    [...vulnerabilityQueryKeys.all, sessionId] as const,
+}
+
+/**
+ * Hook for a session's vulnerability report (null if never scanned)
+ */
+export function useSessionVulnerabilities(sessionId: string | null) {
+  return useQuery({
+    queryKey: vulnerabilityQueryKeys.session(sessionId ?? ''),
+    queryFn: async (): Promise<VulnerabilityReport | null> => {
+      if (!isTauri() || !sessionId) return null
+      return invoke<VulnerabilityReport | null>(
+        'get_session_vulnerabilities',
+        { sessionId }
+      )
+    },
+    enabled: !!sessionId,
+  })
+}
+
+/**
+ * Hook to scan a worktree's dependencies and attach the findings to a session
+ */
+export function useScanSessionVulnerabilities() {
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async ({
+      sessionId,
+      worktreePath,
+    }: {
+      sessionId: string
+      worktreePath: string
+    }): Promise<VulnerabilityReport> => {
+      return invoke<VulnerabilityReport>('scan_session_vulnerabilities', {
+        sessionId,
+        worktreePath,
+      })
+    },
+    onSuccess: (report, { sessionId }) => {
+      queryClient.setQueryData(
+        vulnerabilityQueryKeys.session(sessionId),
+        report
+      )
+      const count = report.findings.length
+      toast.success(
+        count === 0
+          ? 'No known vulnerabilities found'
+          : `Found ${count} vulnerable ${count === 1 ? 'dependency' : 'dependencies'}`
+      )
+    },
+    onError: error => {
+      logger.error('Failed to scan dependencies', { error })
+      toast.error(`Failed to scan dependencies: ${error}`)
+    },
+  })
+}
@@ -447,6 +447,37 @@ describe('ChatStore', () => {
       expect(messages[0]?.id).toBe('msg-2')
     })
 
+    it('sets a queued message schedule', () => {
+      const { enqueueMessage, setQueuedMessageSchedule, getQueuedMessages } =
+        useChatStore.getState()
+
+      enqueueMessage('session-1', createMockMessage('msg-1', 'First'))
+      enqueueMessage('session-1', createMockMessage('msg-2', 'Second'))
+
+      setQueuedMessageSchedule('session-1', 'msg-2', {
+        after: '18:00',
+        days: 'weekends',
+      })
+
+      const messages = getQueuedMessages('session-1')
+      expect(messages[0]?.schedule).toBeUndefined()
+      expect(messages[1]?.schedule).toEqual({ after: '18:00', days: 'weekends' })
+    })
+
+    it('force processing overrides the head schedule', () => {
+      const { enqueueMessage, forceProcessQueue, getQueuedMessages } =
+        useChatStore.getState()
+
+      enqueueMessage('session-1', {
+        ...createMockMessage('msg-1', 'First'),
+        schedule: { avoid_meetings: true },
+      })
+
+      forceProcessQueue('session-1')
+
+      expect(getQueuedMessages('session-1')[0]?.schedule).toBeUndefined()
+    })
+
     it('clears queue', () => {
       const { enqueueMessage, clearQueue, getQueueLength } =
         useChatStore.getState()
@@ -21,6 +21,7 @@ import {
   isExitPlanMode,
 } from '@/types/chat'
 import type { ReviewResponse } from '@/types/projects'
+import type { TaskSchedule } from '@/types/power'
 
 /** Available Claude models */
 export type ClaudeModel = 'opus' | 'opus-4.5' | 'sonnet' | 'haiku'
@@ -375,6 +376,11 @@ interface ChatUIState {
   enqueueMessage: (sessionId: string, message: QueuedMessage) => void
   dequeueMessage: (sessionId: string) => QueuedMessage | undefined
   removeQueuedMessage: (sessionId: string, messageId: string) => void
+  setQueuedMessageSchedule: (
+    sessionId: string,
+    messageId: string,
+    schedule: TaskSchedule | undefined
+  ) => void
   clearQueue: (sessionId: string) => void
   getQueueLength: (sessionId: string) => number
   getQueuedMessages: (sessionId: string) => QueuedMessage[]
@@ -1598,6 +1604,20 @@ export const useChatStore = create<ChatUIState>()(
           'removeQueuedMessage'
         ),
 
+      setQueuedMessageSchedule: (sessionId, messageId, schedule) =>
+        set(
+          state => ({
+            messageQueues: {
+              ...state.messageQueues,
+              [sessionId]: (state.messageQueues[sessionId] ?? []).map(msg =>
+                msg.id === messageId ? { ...msg, schedule } : msg
+              ),
+            },
+          }),
+          undefined,
+          'setQueuedMessageSchedule'
+        ),
+
       clearQueue: sessionId =>
         set(
           state => {
@@ -1620,9 +1640,18 @@ export const useChatStore = create<ChatUIState>()(
             const { [sessionId]: _s, ...restSending } = state.sendingSessionIds
             const { [sessionId]: _w, ...restWaiting } =
               state.waitingForInputSessionIds
+            // Forcing also overrides the head message's schedule
+            const [head, ...rest] = state.messageQueues[sessionId] ?? []
+            const messageQueues = head?.schedule
+              ? {
+                  ...state.messageQueues,
+                  [sessionId]: [{ ...head, schedule: undefined }, ...rest],
+                }
+              : state.messageQueues
             return {
               sendingSessionIds: restSending,
               waitingForInputSessionIds: restWaiting,
+              messageQueues,
             }
           },
           undefined,
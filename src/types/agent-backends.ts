@@ -0,0 +1,41 @@
+/**
+ * Types for custom agent backends (agent-backends/{name}.json manifests)
+ */
+
+/** What a backend prints, i.e. which adapter reads its output */
+export type StreamFormat =
+  | 'claude-stream-json'
+  | 'codex-json'
+  | 'aider-text'
+  | 'plain-text'
+
+/**
+ * What a backend supports
+ */
+export interface BackendCapabilities {
+  /** Accepts a model name (enables `{model}` arguments) */
+  model_selection: boolean
+  /** Reads the plain message on stdin instead of Jean's stream-json input */
+  stdin_prompt: boolean
+  /** Reports tool calls in its output */
+  tool_calls: boolean
+  /** Reports token usage in its output */
+  usage: boolean
+}
+
+/**
+ * A custom backend manifest. A CLI profile selects it with
+ * `"backend": "<name>"` in its settings JSON.
+ */
+export interface CustomBackend {
+  name: string
+  description?: string
+  /**
+   * Program followed by its arguments. Placeholders: {message_file},
+   * {message}, {model}, {cwd}
+   */
+  command: string[]
+  stream_format: StreamFormat
+  capabilities: BackendCapabilities
+  env: Record<string, string>
+}
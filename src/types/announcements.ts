@@ -0,0 +1,22 @@
+/**
+ * Screen-reader announcements (a11y:announcement event)
+ */
+
+export type AnnouncementCategory =
+  | 'session_finished'
+  | 'approval_needed'
+  | 'install_failed'
+
+/** ARIA live region politeness */
+export type AnnouncementPriority = 'polite' | 'assertive'
+
+/** Payload of a11y:announcement, for an ARIA live region */
+export interface Announcement {
+  category: AnnouncementCategory
+  priority: AnnouncementPriority
+  /** Concise text, already shortened to the category's verbosity */
+  message: string
+  session_id: string | null
+  /** Unix seconds */
+  created_at: number
+}
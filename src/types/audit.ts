@@ -0,0 +1,23 @@
+/**
+ * Types for the agent audit log (list_web_requests)
+ */
+
+/** A WebFetch or WebSearch call made by an agent */
+export interface WebRequest {
+  /** Unix seconds the run finished */
+  recorded_at: number
+  session_id: string
+  run_id: string
+  project_id?: string
+  tool: 'WebFetch' | 'WebSearch' | string
+  url?: string
+  /** Host of `url` */
+  domain?: string
+  /** Search query (WebSearch) */
+  query?: string
+  /** Bytes of tool output the agent got back */
+  response_bytes: number
+  is_error: boolean
+  /** The domain is on the project's blocklist */
+  blocked: boolean
+}
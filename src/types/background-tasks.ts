@@ -0,0 +1,54 @@
+/**
+ * Types for background task health (get_background_task_health) and the
+ * maintenance window (get_maintenance_status, run_maintenance_now)
+ */
+
+/** How a task was started */
+export type TaskKind = 'task' | 'thread' | 'supervised'
+
+/** Health of all tasks started under one name */
+export interface TaskHealth {
+  name: string
+  kind: TaskKind
+  /** Instances currently running */
+  running: number
+  /** Instances started since launch (restarts included) */
+  started: number
+  panics: number
+  /** Restarts after panics (supervised tasks only) */
+  restarts: number
+  last_panic: string | null
+  /** Unix timestamp of the last panic */
+  last_panic_at: number | null
+  /** When a supervised task waiting to restart will run again (Unix timestamp) */
+  next_restart_at: number | null
+}
+
+/** Heavy job run in the maintenance window */
+export type MaintenanceJob = 'storage_compaction' | 'semantic_index' | 'backup'
+
+export interface MaintenanceJobResult {
+  job: MaintenanceJob
+  success: boolean
+  message: string
+}
+
+/** One maintenance run */
+export interface MaintenanceReport {
+  /** Unix timestamps */
+  started_at: number
+  finished_at: number
+  /** Started with run_maintenance_now rather than by the scheduler */
+  manual: boolean
+  jobs: MaintenanceJobResult[]
+}
+
+export interface MaintenanceStatus {
+  window_start: string | null
+  window_end: string | null
+  /** Why the configured window is ignored */
+  window_error: string | null
+  /** Heavy jobs may run now (no window, or inside it) */
+  in_window: boolean
+  last_run: MaintenanceReport | null
+}
@@ -0,0 +1,19 @@
+/**
+ * Types for backup and restore (create_backup_now, list_backups, restore_backup)
+ */
+
+/** Recorded in every backup */
+export interface BackupManifest {
+  format: string
+  /** Store schema version at backup time */
+  storage_version: number
+  /** Unix seconds */
+  created_at: number
+  app_version: string
+}
+
+/** A backup on disk (also the backup:restored event payload) */
+export interface BackupInfo {
+  path: string
+  manifest: BackupManifest
+}
@@ -0,0 +1,41 @@
+/**
+ * Slack/Discord bots that announce sessions and take commands from channels
+ */
+
+export type BotPlatform = 'slack' | 'discord'
+
+export type BotCommand = 'status' | 'stop' | 'approve'
+
+export interface BotChannel {
+  /** Slack channel id (C…) or Discord channel id */
+  channel_id: string
+  /** Post session start/finish messages here */
+  notify?: boolean
+  /** Commands accepted here (none: the channel isn't polled) */
+  allowed_commands?: BotCommand[]
+  /** Platform user ids allowed to send commands (empty: anyone, for `status` only) */
+  allowed_users?: string[]
+}
+
+/** A connection as written, before it's saved */
+export interface BotConnectionDraft {
+  name: string
+  platform: BotPlatform
+  enabled?: boolean
+  /** Bot token; omit to keep the stored one */
+  token?: string | null
+  channels?: BotChannel[]
+}
+
+/** A saved connection (its token is never returned) */
+export interface BotConnection {
+  id: string
+  name: string
+  platform: BotPlatform
+  enabled: boolean
+  channels: BotChannel[]
+  has_token: boolean
+  /** Unix seconds */
+  created_at: number
+  updated_at: number
+}
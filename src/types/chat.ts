@@ -1,3 +1,6 @@
+import type { Worktree } from './projects'
+import type { TaskSchedule } from './power'
+
 /**
  * Role of a chat message sender
  */
@@ -170,6 +173,12 @@ export interface Session {
   last_run_execution_mode?: ExecutionMode
   /** User-assigned label (e.g. "Needs testing") */
   label?: string
+  /** Client or cost center for usage reports (unset = the project's) */
+  attribution?: string
+  /** Sub-project directory the session is scoped to (repo-relative) */
+  subproject?: string
+  /** Session this one re-runs (rerun_session) */
+  rerun_of?: string
 }
 
 /**
@@ -233,6 +242,8 @@ export interface UsageData {
   cache_read_input_tokens?: number
   /** Cache creation tokens (cached for future requests) */
   cache_creation_input_tokens?: number
+  /** Cost reported by Claude CLI (absent for older runs) */
+  cost_usd?: number
 }
 
 // ============================================================================
@@ -374,6 +385,17 @@ export interface PermissionDeniedEvent {
   denials: PermissionDenial[]
 }
 
+/**
+ * Event payload when denied tools were approved outside the approval UI
+ * (e.g. from a Slack/Discord bot); the session is resumed by the backend
+ */
+export interface PermissionApprovedEvent {
+  session_id: string
+  worktree_id: string
+  /** Approved tool patterns, e.g. "Bash(npm test)" */
+  patterns: string[]
+}
+
 // ============================================================================
 // AskUserQuestion Types
 // ============================================================================
@@ -709,6 +731,10 @@ export interface QueuedMessage {
   effortLevel?: EffortLevel
   /** MCP config JSON to pass to CLI (snapshot at queue time) */
   mcpConfig?: string
+  /** Run the CLI at lowered CPU/IO priority so batch work doesn't slow interactive use */
+  backgroundPriority?: boolean
+  /** Only start within this time window / on these days / outside meetings */
+  schedule?: TaskSchedule
   /** Timestamp when queued (for display ordering) */
   queuedAt: number
 }
@@ -827,6 +853,7 @@ export type RunStatus =
   | 'cancelled'
   | 'crashed'
   | 'resumable'
+  | 'interrupted'
 
 /**
  * Information about a single JSONL run log file
@@ -866,6 +893,116 @@ export interface SessionDebugInfo {
   total_usage: UsageData
 }
 
+/**
+ * Environment a session's conversation started with (get_session_environment).
+ * Secret-looking env vars and CLI argument fields are "[redacted]".
+ */
+export interface EnvironmentSnapshot {
+  /** Unix timestamp (seconds) */
+  captured_at: number
+  app_version: string
+  os: string
+  arch: string
+  /** Tool name -> version output (claude, git, gh, node) */
+  tools: Record<string, string>
+  working_dir: string
+  git_branch?: string
+  git_commit?: string
+  /** Uncommitted changes when the session started */
+  git_dirty: boolean
+  model?: string
+  cli_args: string[]
+  env: Record<string, string>
+}
+
+/** Thumbs up/down on a message */
+export type AnnotationRating = 'up' | 'down'
+
+/**
+ * A reviewer's annotation on a message (add_message_annotation). Included in
+ * JSONL transcript exports.
+ */
+export interface MessageAnnotation {
+  id: string
+  message_id: string
+  note?: string
+  rating?: AnnotationRating
+  /** Lowercase labels, e.g. "hallucination", "great fix" */
+  labels?: string[]
+  /** Unix timestamp (seconds) */
+  created_at: number
+  updated_at: number
+}
+
+/**
+ * A started re-run of a past session (rerun_session). The prompt is sent once
+ * the worktree is ready; a fresh worktree is pending until worktree:created.
+ */
+export interface RerunInfo {
+  session: Session
+  worktree: Worktree
+  rerun_of: string
+  /** Commit the fresh worktree was checked out at */
+  git_commit?: string
+}
+
+/** A file a session changed (compare_sessions) */
+export interface SessionFileChange {
+  path: string
+  additions: number
+  deletions: number
+  binary: boolean
+}
+
+/** A test command the agent ran, with its result when the output said */
+export interface SessionTestRun {
+  command: string
+  passed: boolean | null
+  summary?: string
+}
+
+/** What one session produced since it started */
+export interface SessionOutcome {
+  session_id: string
+  name: string
+  worktree_path: string
+  /** Commit the changes are measured from (HEAD when none was recorded) */
+  base_commit?: string
+  input_tokens: number
+  output_tokens: number
+  cost_usd: number
+  duration_secs: number
+  run_count: number
+  files: SessionFileChange[]
+  /** Unified diff of files */
+  patch: string
+  tests: SessionTestRun[]
+  /** Result of the last test run; null if no tests ran or it was unclear */
+  verified: boolean | null
+}
+
+/** One file across both compared sessions */
+export interface SessionFileComparison {
+  path: string
+  a: SessionFileChange | null
+  b: SessionFileChange | null
+  identical: boolean
+  /** Diff from A's version of the file to B's */
+  diff?: string
+}
+
+/** Side-by-side outcome of two sessions; deltas are B minus A */
+export interface SessionComparison {
+  a: SessionOutcome
+  b: SessionOutcome
+  files: SessionFileComparison[]
+  token_delta: number
+  cost_delta: number
+  duration_delta: number
+  /** Session whose tests passed when the other's didn't */
+  better?: string
+}
+
 // ============================================================================
 // Session Digest Types (for context recall after switching)
 // ============================================================================
@@ -884,3 +1021,346 @@ export interface SessionDigest {
   /** Number of messages when this digest was generated */
   message_count?: number
 }
+
+/** Disk usage of a session's managed scratch directory */
+export interface ScratchUsage {
+  session_id: string
+  /** Absolute path of the scratch directory */
+  path: string
+  /** Whether the directory currently exists on disk */
+  exists: boolean
+  /** Total size of all files in bytes */
+  size_bytes: number
+  /** Number of files (directories not counted) */
+  file_count: number
+  /** Most recent modification time of any entry (unix epoch seconds) */
+  last_modified?: number
+}
+
+/** Category of a tool call on the session timeline */
+export type TimelineKind =
+  | 'command'
+  | 'file_edit'
+  | 'file_read'
+  | 'search'
+  | 'web'
+  | 'agent'
+  | 'mcp'
+  | 'other'
+
+/** A single tool call on the session timeline */
+export interface TimelineEntry {
+  tool_use_id: string
+  name: string
+  kind: TimelineKind
+  /** Short human-readable description (command, file path, query, ...) */
+  summary?: string
+  parent_tool_use_id?: string
+  /** When the tool call was observed (unix epoch milliseconds) */
+  started_at_ms?: number
+  /** When the tool result was observed (unix epoch milliseconds) */
+  ended_at_ms?: number
+  /** Only set when both timestamps are known */
+  duration_ms?: number
+  completed: boolean
+  is_error: boolean
+  /** Truncated tool output */
+  output_preview?: string
+}
+
+/** Timeline of tool calls for a single run */
+export interface RunTimeline {
+  run_id: string
+  user_message_id: string
+  started_at: number
+  ended_at?: number
+  status: RunStatus
+  entries: TimelineEntry[]
+}
+
+/** Timeline of tool calls across all runs of a session */
+export interface SessionTimeline {
+  session_id: string
+  runs: RunTimeline[]
+}
+
+/** What a run stopped to wait for */
+export type WaitReason = 'question' | 'plan' | 'permission'
+
+/** Time of one run (get_session_time) */
+export interface RunTime {
+  run_id: string
+  started_at: number
+  ended_at?: number
+  /** Seconds the agent was generating or running tools */
+  active_secs: number
+  /** Part of active_secs spent in tool calls (from recorded timings) */
+  tool_secs: number
+  waiting_for?: WaitReason
+}
+
+/** Where a session's wall-clock time went (get_session_time) */
+export interface SessionTime {
+  session_id: string
+  /** First run start to last run end (or now, while running) */
+  wall_secs: number
+  active_secs: number
+  tool_secs: number
+  /** Between runs, while the agent waited on the user */
+  waiting_secs: number
+  /** The rest of the wall-clock time (user's turn, sleep, Jean closed) */
+  idle_secs: number
+  runs: RunTime[]
+}
+
+/** Returned by replay_session when playback starts */
+export interface ReplayInfo {
+  replay_id: string
+  session_id: string
+  total_events: number
+  /** Original session duration covered by the events (milliseconds) */
+  duration_ms: number
+  /** Whether playback is stepped manually via replay_step */
+  stepped: boolean
+}
+
+/** Payload of the replay:event event (one per stored CLI event) */
+export interface ReplayEvent {
+  replay_id: string
+  session_id: string
+  run_id: string
+  index: number
+  total: number
+  /** Original offset from the start of the session (milliseconds) */
+  offset_ms: number
+  /** Raw stream-json event as written by the CLI */
+  event: Record<string, unknown>
+}
+
+/** Payload of the replay:done event */
+export interface ReplayDoneEvent {
+  replay_id: string
+  session_id: string
+  cancelled: boolean
+}
+
+/**
+ * Payload of the session:input-requested event, emitted when the agent asks a
+ * question in a run. Answer it with provide_session_input to resume the run.
+ */
+export interface InputRequestedEvent {
+  session_id: string
+  worktree_id: string
+  /** ID of the tool call that asked for input */
+  tool_use_id: string
+  /** Name of the tool (e.g. "AskUserQuestion") */
+  tool_name: string
+  /** Raw tool input (contains the questions and options) */
+  input: Record<string, unknown>
+  /** Unix timestamp when the request was raised */
+  requested_at: number
+}
+
+/**
+ * Payload of the session:interrupted event, emitted when a run's process died
+ * while the system was asleep. Sending a new message continues the conversation.
+ */
+export interface SessionInterruptedEvent {
+  session_id: string
+  worktree_id: string
+  run_id: string
+  /** True if the Claude conversation can be continued with a new message */
+  resumable: boolean
+}
+
+/**
+ * Payload of the bulk:progress event, emitted once per finished item of a
+ * bulk_delete_sessions / bulk_export_sessions / bulk_cancel_tasks call
+ */
+export interface BulkProgressEvent {
+  operation_id: string
+  operation: 'delete_sessions' | 'export_sessions' | 'cancel_tasks'
+  /** Session that just finished */
+  id: string
+  completed: number
+  total: number
+  /** Set if this item failed */
+  error: string | null
+}
+
+/** Result of a bulk operation */
+export interface BulkResult {
+  operation_id: string
+  succeeded: string[]
+  failed: { id: string; error: string }[]
+}
+
+/** Sort order for list_session_history (always descending) */
+export type HistorySort = 'recent' | 'cost' | 'duration'
+
+/** Filters and paging for list_session_history */
+export interface HistoryQuery {
+  /** next_cursor from the previous page (omit for the first page) */
+  cursor?: string | null
+  limit?: number | null
+  sort?: HistorySort
+  project_id?: string | null
+  worktree_id?: string | null
+  /** true = archived only, false = active only, omitted = both */
+  archived?: boolean | null
+  /** Case-insensitive match on session name, label or any user message */
+  search?: string | null
+}
+
+/** One session in the history listing */
+export interface SessionHistoryEntry {
+  session_id: string
+  name: string
+  project_id: string
+  project_name: string
+  worktree_id: string
+  worktree_name: string
+  worktree_path: string
+  created_at: number
+  /** End of the latest run (or creation time if there are no runs) */
+  last_activity_at: number
+  archived_at: number | null
+  label: string | null
+  message_count: number
+  run_count: number
+  cost_usd: number
+  duration_secs: number
+}
+
+/** A page of results from a paginated listing command */
+export interface HistoryPage<T> {
+  items: T[]
+  /** Pass as cursor to get the next page; null on the last page */
+  next_cursor: string | null
+  /** Matching items across all pages */
+  total: number
+}
+
+/** Result of import_session_jsonl */
+export interface TranscriptImport {
+  /** The new session, or the existing one if the transcript was a duplicate */
+  session: Session
+  /** True if an identical session already existed and nothing was imported */
+  duplicate: boolean
+}
+
+/** A session in a duplicate group */
+export interface DuplicateSession {
+  session_id: string
+  worktree_id: string
+  name: string
+  created_at: number
+}
+
+/** Sessions with identical content */
+export interface DuplicateGroup {
+  content_hash: string
+  /** Oldest session first (the one kept when duplicates are removed) */
+  sessions: DuplicateSession[]
+  run_count: number
+}
+
+/** Result of find_duplicate_sessions */
+export interface DuplicateReport {
+  groups: DuplicateGroup[]
+  /** Duplicates deleted (0 unless removal was requested) */
+  removed: number
+}
+
+/** Web app an exported session can be continued in */
+export type ShareFormat = 'chatgpt' | 'claude'
+
+/** Result of export_session_for_web */
+export interface ShareExport {
+  /** The conversation JSON file */
+  path: string
+  format: ShareFormat
+  message_count: number
+  /** Images copied next to the export (upload these manually) */
+  attachments: string[]
+  /** Oldest messages left out to fit the size budget */
+  trimmed_messages: number
+  bytes: number
+}
+
+/** Result of export_session_cast (asciicast v2 terminal recording) */
+export interface CastExport {
+  path: string
+  /** Output frames in the recording */
+  frames: number
+  /** Length of the recording before idle compression (seconds) */
+  duration_secs: number
+}
+
+/** Kind of documentation drafted by generate_session_doc */
+export type DocKind = 'changelog' | 'adr' | 'runbook'
+
+/** Result of generate_session_doc */
+export interface DocDraft {
+  kind: DocKind
+  /** Absolute path of the file written (in the session's worktree) */
+  path: string
+  /** The generated section */
+  content: string
+}
+
+/** A quick-ask answer (run_one_shot); kept in memory until pinned */
+export interface OneShotAnswer {
+  id: string
+  prompt: string
+  /** Selected text the question is about */
+  context: string | null
+  model: string
+  answer: string
+  usage: UsageData | null
+  /** Unix seconds */
+  started_at: number
+  duration_ms: number
+}
+
+/** Payload of one-shot:started and one-shot:done */
+export interface OneShotEvent {
+  id: string
+}
+
+/** Payload of one-shot:chunk (a text delta) */
+export interface OneShotChunkEvent {
+  id: string
+  content: string
+}
+
+/** A tool output left out of a MessagePage; fetch it with get_tool_output */
+export interface ElidedOutput {
+  message_id: string
+  tool_call_id: string
+  /** Size of the full output in bytes */
+  bytes: number
+}
+
+/** A range of a session's messages (get_session_messages_page) */
+export interface MessagePage {
+  session_id: string
+  /** Index of the first message in this page */
+  start: number
+  /** Total number of messages in the session */
+  total: number
+  messages: ChatMessage[]
+  elided_outputs: ElidedOutput[]
+}
+
+/** Result of compact_storage */
+export interface StorageCompaction {
+  sessions_scanned: number
+  runs_compacted: number
+  blobs_written: number
+  /** Size of the compacted run logs before compaction */
+  bytes_before: number
+  /** Size of the rewritten run logs plus their blobs */
+  bytes_after: number
+  bytes_reclaimed: number
+}
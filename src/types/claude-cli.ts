@@ -24,6 +24,48 @@ export interface ClaudeAuthStatus {
   error: string | null
 }
 
+/**
+ * Claude subscription login state (from get_claude_auth_status)
+ */
+export type ClaudeAuthState =
+  | 'logged_in'
+  | 'api_key'
+  | 'refresh_pending'
+  | 'expired'
+  | 'refresh_failed'
+  | 'logged_out'
+
+/**
+ * What the user should do about the current auth state
+ */
+export type ClaudeAuthAction = 'none' | 'login' | 'check_subscription'
+
+/**
+ * Subscription auth details read from Claude's stored credentials
+ */
+export interface ClaudeAuthDetails {
+  state: ClaudeAuthState
+  action: ClaudeAuthAction
+  /** Subscription plan (e.g. "pro", "max") if logged in with a subscription */
+  plan: string | null
+  /** Access token expiry (Unix seconds) */
+  expires_at: number | null
+  scopes: string[]
+  /** Where the credentials were read from ("file" or "keychain") */
+  credentials_source: string | null
+  /** Human-readable explanation of a non-working state */
+  message: string | null
+}
+
+/**
+ * Returned by start_claude_login; completion is signalled by the
+ * `claude-auth:login-complete` event (payload: ClaudeAuthDetails)
+ */
+export interface ClaudeLoginFlow {
+  /** Shell command to run in the login terminal */
+  command: string
+}
+
 /**
  * Information about a Claude CLI release from GitHub
  */
@@ -48,4 +90,27 @@ export interface InstallProgress {
   message: string
   /** Percentage complete (0-100) */
   percent: number
+  /** Bytes downloaded so far (downloading stage only) */
+  downloaded_bytes?: number
+  /** Size of the download, if the server reported it */
+  total_bytes?: number
+}
+
+/** Where a pinned Claude CLI version comes from */
+export type PinSource = 'project_settings' | 'jean_json'
+
+/**
+ * Claude CLI version pinned for a worktree (from get_pinned_cli_version)
+ */
+export interface PinnedCliVersion {
+  version: string
+  source: PinSource
+  /** False until the version is installed; sessions use the default install */
+  installed: boolean
+}
+
+/** Payload of the claude-cli:pinned-version-missing event */
+export interface PinnedVersionMissing {
+  worktree_id: string
+  version: string
 }
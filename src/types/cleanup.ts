@@ -0,0 +1,23 @@
+/**
+ * Types for stale temp file cleanup (find_stale_files, clean_stale_files)
+ */
+
+/** What a leftover is */
+export type StaleKind = 'temp_dir' | 'partial_download' | 'orphaned_scratch'
+
+/** A leftover of a failed install or deleted session */
+export interface StaleItem {
+  path: string
+  kind: StaleKind
+  size_bytes: number
+  /** Unix timestamp (seconds) of the latest change inside */
+  last_modified: number | null
+}
+
+/** Result of clean_stale_files */
+export interface CleanupReport {
+  removed: StaleItem[]
+  reclaimed_bytes: number
+  /** Paths that couldn't be removed, with the error */
+  failed: string[]
+}
@@ -0,0 +1,22 @@
+/**
+ * Types for probed agent CLI capabilities (get_cli_capabilities)
+ */
+
+/**
+ * Version and flags of an installed agent CLI. Flags the CLI doesn't list
+ * are left out of its invocations.
+ */
+export interface CliCapabilities {
+  /** "claude" or "aider" */
+  cli: string
+  /** Version parsed from `--version`, if any */
+  version: string | null
+  /** Binary size and mtime the probe ran against */
+  fingerprint: string
+  /** Unix timestamp (seconds) of the probe */
+  probed_at: number
+  /** Every `--flag` the CLI's help lists; empty if the probe failed */
+  flags: string[]
+  /** Named features (e.g. "chrome", "mcp_config") and whether they're available */
+  features: Record<string, boolean>
+}
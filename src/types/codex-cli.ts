@@ -0,0 +1,48 @@
+/**
+ * Types for Codex CLI update checks
+ */
+
+/** Which releases count as updates (codex_update_channel preference) */
+export type CodexUpdateChannel = 'stable' | 'prerelease'
+
+/**
+ * A newer Codex CLI release than the installed one
+ * (check_codex_update, `codex-cli:update-available` event)
+ */
+export interface CodexUpdate {
+  installed_version: string
+  latest_version: string
+  /** Git tag name (e.g., "rust-v0.50.0") */
+  tag_name: string
+  /** Publication date in ISO format */
+  published_at: string
+  prerelease: boolean
+  channel: CodexUpdateChannel
+}
+
+/**
+ * Progress event for installing the Codex CLI from an archive
+ * (install_codex_cli_from_file, `codex-cli:install-progress` event)
+ */
+export interface CodexInstallProgress {
+  stage:
+    | 'verify_codex_download'
+    | 'extracting'
+    | 'verifying'
+    | 'installing'
+    | 'complete'
+  /** Progress message */
+  message: string
+  /** Percentage complete (0-100) */
+  percent: number
+}
+
+/**
+ * A Codex CLI version kept from an earlier install
+ * (list_installed_codex_versions, switch_codex_version)
+ */
+export interface InstalledCodexVersion {
+  version: string
+  /** Whether this is the version installed as `codex` */
+  active: boolean
+}
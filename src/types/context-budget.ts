@@ -0,0 +1,43 @@
+/**
+ * Types for prompts that don't fit the model's context window
+ */
+
+export type AttachmentKind =
+  | 'issue'
+  | 'pull_request'
+  | 'saved_context'
+  | 'message'
+  | 'selection'
+
+/** One part of an assembled prompt */
+export interface Attachment {
+  kind: AttachmentKind
+  /** Issue/PR number or saved context slug (for removing it) */
+  key: string
+  name: string
+  path: string | null
+  /** Estimated tokens */
+  tokens: number
+}
+
+/** Why a prompt was not sent and how to make it fit */
+export interface BudgetReport {
+  model: string
+  context_window: number
+  /** Tokens available to attachments */
+  budget: number
+  total_tokens: number
+  /** Largest first */
+  attachments: Attachment[]
+  /** Attachments whose removal brings the prompt within budget */
+  suggested_drops: Attachment[]
+  /** Saved contexts worth summarizing in place */
+  summarizable: Attachment[]
+}
+
+/** Payload of context:over-budget */
+export interface OverBudgetEvent {
+  /** null for one-shots */
+  session_id: string | null
+  report: BudgetReport
+}
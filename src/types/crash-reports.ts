@@ -0,0 +1,56 @@
+/**
+ * Types for locally stored crash reports
+ */
+
+export type CrashKind = 'panic' | 'native' | 'cli'
+
+/**
+ * Details about a crashed child CLI process
+ */
+export interface CliCrashDetails {
+  /** Program that crashed (e.g. "claude") */
+  program: string
+  exit_code: number | null
+  /** Terminating signal (Unix only) */
+  signal: number | null
+  /** Last lines of stderr */
+  stderr_tail: string[]
+}
+
+/**
+ * A stored crash report (from list_crash_reports / get_crash_report)
+ */
+export interface CrashReport {
+  id: string
+  kind: CrashKind
+  /** Unix timestamp when the crash was recorded */
+  created_at: number
+  app_version: string
+  /** Claude CLI version at the time of the crash (if known) */
+  cli_version: string | null
+  os: string
+  arch: string
+  /** Panic message or crash summary */
+  message: string
+  /** Source location of a panic (file:line:column) */
+  location: string | null
+  /** Thread that panicked */
+  thread: string | null
+  backtrace: string | null
+  /** Minidump or OS crash report stored next to the report (file name) */
+  attachment_file: string | null
+  cli: CliCrashDetails | null
+  /** Unix timestamp when the report was submitted (null = never) */
+  submitted_at: number | null
+}
+
+/**
+ * Result of submit_crash_report
+ */
+export interface CrashReportSubmission {
+  id: string
+  /** True if the report was uploaded to the configured endpoint */
+  uploaded: boolean
+  /** Prefilled issue URL for the user to open (when not uploaded) */
+  issue_url: string | null
+}
@@ -0,0 +1,9 @@
+/**
+ * Outgoing email (send_test_email, set_smtp_password) and the run digest
+ */
+
+/** How the connection to the SMTP server is secured */
+export type SmtpSecurity = 'start_tls' | 'tls' | 'none'
+
+/** How often the run digest is mailed */
+export type EmailDigestFrequency = 'off' | 'daily' | 'weekly'
@@ -0,0 +1,18 @@
+/**
+ * Types for the emergency stop
+ */
+
+/** Emergency stop status (from get_emergency_status) */
+export interface EmergencyStatus {
+  engaged: boolean
+  /** Unix timestamp (seconds) of the stop, null when not engaged */
+  stopped_at: number | null
+}
+
+/** What a stop halted (from emergency_stop and the emergency:stopped event) */
+export interface EmergencyStopReport {
+  stopped_at: number
+  /** Sessions whose runs were killed */
+  sessions_stopped: string[]
+  downloads_cancelled: number
+}
@@ -0,0 +1,30 @@
+/**
+ * OS Focus / do-not-disturb handling
+ */
+
+export type NotificationCategory =
+  | 'session_finished'
+  | 'input_needed'
+  | 'event_rule'
+  | 'general'
+
+/** A notification or spoken summary held during Focus */
+export interface DigestEntry {
+  category: NotificationCategory
+  title: string
+  body: string | null
+  /** Unix seconds */
+  created_at: number
+}
+
+/** Result of get_focus_state */
+export interface FocusState {
+  /** Whether the OS Focus state could be read */
+  supported: boolean
+  /** Whether the OS is in Focus / do-not-disturb */
+  active: boolean
+  /** Whether the respect_focus_mode preference is on */
+  respected: boolean
+  /** Notifications held for the digest, oldest first */
+  held: DigestEntry[]
+}
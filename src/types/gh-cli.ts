@@ -46,6 +46,7 @@ export interface GhInstallProgress {
   stage:
     | 'starting'
     | 'downloading'
+    | 'verifying_download'
     | 'extracting'
     | 'installing'
     | 'verifying'
@@ -54,4 +55,8 @@ export interface GhInstallProgress {
   message: string
   /** Percentage complete (0-100) */
   percent: number
+  /** Bytes downloaded so far (downloading stage only) */
+  downloaded_bytes?: number
+  /** Size of the download, if the server reported it */
+  total_bytes?: number
 }
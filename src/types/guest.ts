@@ -0,0 +1,13 @@
+/**
+ * Types for read-only guest mode
+ */
+
+/** Guest mode status (from get_guest_mode and the guest-mode:changed event) */
+export interface GuestModeStatus {
+  enabled: boolean
+  /** Unix timestamp (seconds) guest mode was entered, null when off */
+  enabled_at: number | null
+}
+
+/** Error the backend returns for commands refused in guest mode */
+export const GUEST_MODE_ERROR = 'Not available in guest mode'
@@ -0,0 +1,27 @@
+/**
+ * Types for the embedded HTTP server watchdog (http-server:watchdog) and
+ * event routing (set_event_subscriptions)
+ */
+
+/** Emitted when the watchdog finds the server dead or unresponsive */
+export interface WatchdogEvent {
+  /** Why the server was considered down */
+  cause: string
+  restarted: boolean
+  /** URL of the restarted server */
+  url: string | null
+  /** Why the restart failed (if it did) */
+  error: string | null
+  /** Unix timestamp */
+  at: number
+}
+
+/**
+ * Events a window or WebSocket client receives. Omitted fields receive all.
+ */
+export interface EventSubscriptions {
+  /** Sessions whose streaming output to receive */
+  sessions?: string[] | null
+  /** Event name patterns, where `*` matches any text (e.g. `chat:*`) */
+  topics?: string[] | null
+}
@@ -0,0 +1,31 @@
+/**
+ * Types for the knowledge base of lessons from past sessions
+ */
+
+/** A lesson as distilled from a session, before it's edited and saved */
+export interface LessonDraft {
+  title: string
+  /** What the problem was and how it was solved (markdown) */
+  summary: string
+  tags: string[]
+}
+
+/** A saved lesson */
+export interface Lesson extends LessonDraft {
+  id: string
+  project_id: string | null
+  /** Session the lesson was distilled from */
+  session_id: string | null
+  session_name: string | null
+  /** Unix timestamp (seconds) */
+  created_at: number
+  /** Embedding model, if the lesson has been embedded */
+  model?: string
+}
+
+/** A lesson matching a query */
+export interface LessonHit {
+  lesson: Lesson
+  /** Cosine similarity, or the share of query words found (keyword search) */
+  score: number
+}
@@ -0,0 +1,58 @@
+/**
+ * Types for per-backend launch defaults (get_effective_launch_command)
+ */
+
+/**
+ * Extra CLI arguments and environment passed to a backend on every run
+ */
+export interface LaunchDefaults {
+  /** `--flag`, `--flag value` or `--flag=value` tokens */
+  args: string[]
+  env: Record<string, string>
+}
+
+/** Backend name ("claude", "codex", "aider" or a custom backend) -> defaults */
+export type LaunchDefaultsByBackend = Record<string, LaunchDefaults>
+
+/** Later levels win: global < project < profile */
+export type LaunchLevel = 'global' | 'project' | 'profile'
+
+export interface LevelDefaults extends LaunchDefaults {
+  level: LaunchLevel
+}
+
+export interface LaunchCommandOptions {
+  worktree_id: string
+  session_id: string
+  /** Defaults to the session's selected model */
+  model?: string | null
+  execution_mode?: string | null
+  /** Settings JSON of the CLI profile, as passed to send_chat_message */
+  custom_profile_settings?: string | null
+}
+
+/**
+ * What the agent may do without asking, as the arguments set it
+ */
+export interface SandboxPolicy {
+  /** Claude permission mode or Codex sandbox; null leaves the CLI's default */
+  mode: string | null
+  allowed_tools: string[]
+  disallowed_tools: string[]
+  /** Directories the agent can access besides `cwd` */
+  additional_dirs: string[]
+}
+
+/**
+ * The command a session's next message would start (secrets redacted)
+ */
+export interface EffectiveLaunchCommand {
+  backend: string
+  program: string
+  args: string[]
+  env: Record<string, string>
+  cwd: string
+  sandbox: SandboxPolicy
+  /** Levels that set defaults, least specific first */
+  defaults: LevelDefaults[]
+}
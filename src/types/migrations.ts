@@ -0,0 +1,22 @@
+/**
+ * Types for storage integrity checks (verify_database_integrity)
+ */
+
+/** Severity of a storage integrity issue */
+export type IssueSeverity = 'error' | 'warning'
+
+export interface IntegrityIssue {
+  severity: IssueSeverity
+  path: string
+  message: string
+}
+
+/** Result of verify_database_integrity */
+export interface IntegrityReport {
+  storage_version: number
+  latest_version: number
+  files_checked: number
+  issues: IntegrityIssue[]
+  /** True if there are no errors (warnings are allowed) */
+  ok: boolean
+}
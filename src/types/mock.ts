@@ -0,0 +1,13 @@
+/**
+ * Types for the mock backend (mock-backend builds only)
+ */
+
+/**
+ * Mock backend state (from set_mock_scenario)
+ */
+export interface MockStatus {
+  /** Active scenario (null = mock mode off) */
+  active: string | null
+  /** Built-in and custom scenario names */
+  available: string[]
+}
@@ -0,0 +1,128 @@
+/**
+ * Types for OS power management (sleep prevention and wake recovery)
+ */
+
+/**
+ * Current sleep-prevention status (from get_power_state)
+ */
+export interface PowerState {
+  /** Whether sleep prevention is enabled in preferences */
+  enabled: boolean
+  /** Whether an OS power assertion is currently held */
+  assertion_active: boolean
+  /** Reasons for the work currently keeping the system awake */
+  active_work: string[]
+  /** Last error from acquiring the assertion (e.g. systemd-inhibit missing) */
+  error: string | null
+}
+
+/**
+ * Payload of the system:wake event
+ */
+export interface WakeEvent {
+  /** Approximate time the system was asleep */
+  slept_secs: number
+  /** Sessions whose Claude process died during sleep */
+  interrupted_sessions: string[]
+}
+
+/**
+ * Payload of the system:network-changed event
+ */
+export interface NetworkChangedEvent {
+  online: boolean
+  /** Local address of the primary interface (null when offline) */
+  address: string | null
+}
+
+/**
+ * Result of checking the configured provider endpoint
+ * (check_provider_connectivity command and system:connectivity event)
+ */
+export interface ConnectivityStatus {
+  /** Whether the provider endpoint answered */
+  online: boolean
+  /** Custom provider profile name (null = Anthropic direct) */
+  provider: string | null
+  /** Endpoint that was checked */
+  url: string
+  /** Error from the check (if offline) */
+  error: string | null
+  /** Unix timestamp of the check */
+  checked_at: number
+}
+
+/**
+ * Payload of the system:download-resumed event
+ */
+export interface DownloadResumedEvent {
+  /** What is being downloaded (e.g. "Claude CLI") */
+  label: string
+  /** Error that interrupted the first attempt */
+  error: string
+}
+
+/**
+ * Background jobs that can be deferred on battery or metered connections
+ */
+export type BackgroundJob = 'update_check' | 'report_generation' | 'batch_tasks'
+
+/**
+ * Battery state of the machine
+ */
+export interface BatteryStatus {
+  /** True when running on battery (not plugged in) */
+  on_battery: boolean
+  /** Remaining charge (0-100), if reported */
+  percent: number | null
+}
+
+/**
+ * Current power and network conditions (from get_power_conditions)
+ */
+export interface PowerConditions {
+  /** Battery status (null on machines without a battery) */
+  battery: BatteryStatus | null
+  /** Whether the connection is metered (null if unknown) */
+  metered: boolean | null
+}
+
+/**
+ * Whether a background job may run now (from should_run_background_job)
+ */
+export interface JobDecision {
+  job: BackgroundJob
+  allowed: boolean
+  /** Why the job was deferred (null if allowed) */
+  reason: string | null
+  conditions: PowerConditions
+}
+
+/**
+ * Days a scheduled queued run may start on
+ */
+export type ScheduleDays = 'any' | 'weekdays' | 'weekends'
+
+/**
+ * When a queued run may start (checked with check_task_schedule)
+ */
+export interface TaskSchedule {
+  /** Local "HH:MM" the run may start from */
+  after?: string | null
+  /** Local "HH:MM" the run must start before (may wrap past midnight) */
+  before?: string | null
+  days?: ScheduleDays
+  /** Wait while the calendar in calendar_ics_path shows a meeting */
+  avoid_meetings?: boolean
+}
+
+/**
+ * Whether a scheduled run may start now (from check_task_schedule)
+ */
+export interface ScheduleDecision {
+  allowed: boolean
+  /** Why the run is waiting (null if allowed) */
+  reason: string | null
+  /** Local "HH:MM" the current meeting ends (null if not in one) */
+  busy_until: string | null
+}
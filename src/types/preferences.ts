@@ -1,5 +1,8 @@
 import type { ThinkingLevel, EffortLevel } from './chat'
 import { DEFAULT_KEYBINDINGS, type KeybindingsMap } from './keybindings'
+import type { CodexUpdateChannel } from './codex-cli'
+import type { LaunchDefaultsByBackend } from './launch'
+import type { EmailDigestFrequency, SmtpSecurity } from './email'
 
 // =============================================================================
 // Notification Sounds
@@ -358,6 +361,55 @@ export const DEFAULT_MAGIC_PROMPT_MODELS: MagicPromptModels = {
   release_notes_model: 'haiku',
 }
 
+/**
+ * Background jobs that run even on low battery or metered connections.
+ * Field names use snake_case to match Rust struct exactly.
+ */
+export interface BackgroundJobOverrides {
+  update_check: boolean
+  report_generation: boolean
+  batch_tasks: boolean
+}
+
+/** By default every background job can be deferred */
+export const DEFAULT_BACKGROUND_JOB_OVERRIDES: BackgroundJobOverrides = {
+  update_check: false,
+  report_generation: false,
+  batch_tasks: false,
+}
+
+/** How much a screen-reader announcement says */
+export type AnnouncementLevel = 'off' | 'brief' | 'detailed'
+
+/** Screen-reader announcement verbosity per category */
+export interface AnnouncementVerbosity {
+  session_finished: AnnouncementLevel
+  approval_needed: AnnouncementLevel
+  install_failed: AnnouncementLevel
+}
+
+export const DEFAULT_ANNOUNCEMENT_VERBOSITY: AnnouncementVerbosity = {
+  session_finished: 'brief',
+  approval_needed: 'brief',
+  install_failed: 'brief',
+}
+
+/** Notification categories that break through OS Focus / do-not-disturb */
+export interface FocusOverrides {
+  session_finished: boolean
+  input_needed: boolean
+  event_rules: boolean
+  general: boolean
+}
+
+/** By default every category waits for the Focus digest */
+export const DEFAULT_FOCUS_OVERRIDES: FocusOverrides = {
+  session_finished: false,
+  input_needed: false,
+  event_rules: false,
+  general: false,
+}
+
 // Types that match the Rust AppPreferences struct
 // Only contains settings that should be persisted to disk
 // Note: Field names use snake_case to match Rust struct exactly
@@ -380,6 +432,7 @@ export interface AppPreferences {
   remote_poll_interval: number // Remote API polling interval in seconds (30-600)
   keybindings: KeybindingsMap // User-configurable keyboard shortcuts
   archive_retention_days: number // Days to keep archived items (0 = never delete)
+  scratch_retention_days: number // Days to keep idle session scratch directories (0 = never delete)
   session_grouping_enabled: boolean // Group session tabs by status when >3 sessions
   canvas_enabled: boolean // Show the canvas tab for session overview
   canvas_only_mode: boolean // Always show canvas view, hide session tabs
@@ -412,11 +465,68 @@ export interface AppPreferences {
   zoom_level: number // Zoom level percentage (50-200, default 100)
   custom_cli_profiles: CustomCliProfile[] // Custom CLI settings profiles (e.g., OpenRouter, MiniMax)
   default_provider: string | null // Default provider profile name (null = Anthropic direct)
+  speech_provider: SpeechProvider // Speech-to-text engine for dictated prompts
+  speech_local_model: string // whisper.cpp model ID for local transcription
+  speech_api_url: string // OpenAI-compatible transcription endpoint
+  speech_api_model: string // Model name sent to the transcription API
+  speech_api_key: string | null // API key for the transcription endpoint
+  speak_waiting_summary: boolean // Speak a one-sentence summary when a session is waiting for input
+  speak_review_summary: boolean // Speak a one-sentence summary when a session finishes
+  speech_voice: string | null // OS voice for spoken summaries (null = system default)
+  prevent_sleep_during_runs: boolean // Keep the machine awake while sessions, installs or downloads run
+  defer_jobs_battery_threshold: number // Defer background jobs on battery at or below this % (0 = never)
+  defer_jobs_on_metered: boolean // Defer background jobs on metered connections
+  background_job_overrides: BackgroundJobOverrides // Jobs that run regardless of battery/metered state
+  crash_report_endpoint: string | null // Opt-in URL crash reports are uploaded to (null = open a GitHub issue)
+  extra_ca_cert_path: string | null // PEM file with extra CA certificates to trust (TLS-intercepting proxies)
+  use_system_cert_store: boolean // Add the OS trust store's certificates to all HTTP clients
+  http_proxy: string | null // Proxy URL for all HTTP clients (null = HTTPS_PROXY / HTTP_PROXY from the environment)
+  no_proxy: string | null // Comma-separated hosts that bypass the proxy (null = NO_PROXY from the environment)
+  keep_mark_of_the_web: boolean // Windows: leave the Mark of the Web on downloaded CLIs (SmartScreen checks them)
+  claude_monthly_budget_usd: number | null // Monthly Claude spend budget shown in the provider overview (null = no budget)
+  backup_folder: string | null // Folder for automatic backups (null = backups/ in app data)
+  backup_interval_hours: number // Hours between automatic backups (0 = disabled)
+  backup_keep_count: number // Number of backups kept before the oldest are removed
+  shared_library_repo: string | null // Git repository with the team's shared prompts and profiles (null = local only)
+  shared_library_sync_hours: number // Hours between shared library pulls (0 = manual only)
+  community_index_url: string | null // Community index of prompts and profiles to browse (null = off)
+  community_index_public_key: string | null // Base64 Ed25519 key community items must be signed with
+  warm_process_pool: boolean // Keep an idle Claude CLI ready for new sessions
+  download_speed_limit_kib: number | null // Combined KiB/s cap for parallel tool downloads (null = unlimited)
+  auto_attach_command_runs: number // Attach output of this many recent project command runs to new sessions (0 = off)
+  command_runs_token_budget: number // Approximate tokens of attached command output
+  repo_map_token_budget: number // Approximate tokens of the repository map attached to new sessions in large repos (0 = off)
+  semantic_search_enabled: boolean // Index project files with embeddings for semantic search (UI and agent MCP tool)
+  embeddings_api_url: string // OpenAI-compatible embeddings endpoint (local Ollama by default)
+  embeddings_api_model: string // Model name sent to the embeddings API
+  embeddings_api_key: string | null // API key for the embeddings endpoint
+  maintenance_window_start: string | null // Local "HH:MM" heavy background jobs may start from (null = no window)
+  maintenance_window_end: string | null // Local "HH:MM" the maintenance window closes
+  report_locale: string // Locale for numbers and dates in exported reports, e.g. "de-DE"
+  csv_delimiter: string | null // CSV field delimiter ("," ";" or "tab"; null = locale default)
+  announcement_verbosity: AnnouncementVerbosity // Screen-reader announcement detail per category
+  respect_focus_mode: boolean // Hold notifications and spoken summaries while OS Focus / do-not-disturb is on
+  focus_overrides: FocusOverrides // Notification categories delivered even during Focus
+  calendar_ics_path: string | null // Local .ics file read for busy/free so scheduled runs can avoid meetings
+  vulnerability_scan_before_sessions: boolean // Scan dependencies for known vulnerabilities and attach the findings to new sessions
+  launch_defaults: LaunchDefaultsByBackend // Extra CLI args/env per backend for every run (projects and profiles can add more)
+  codex_update_channel: CodexUpdateChannel // Releases the Codex CLI update check reports
+  codex_update_check_hours: number // Hours between Codex CLI update checks (0 = disabled)
+  codex_release_mirrors: string[] // Base URLs standing in for https://api.github.com, tried in order before GitHub itself
+  smtp_host: string | null // SMTP server for outgoing email (null = email disabled)
+  smtp_port: number // SMTP server port
+  smtp_security: SmtpSecurity // STARTTLS, TLS from the start, or none (local relays)
+  smtp_username: string | null // SMTP login (the password is set with set_smtp_password)
+  smtp_from: string | null // Sender address of outgoing email
+  email_digest: EmailDigestFrequency // Mail a digest of runs: off, daily or weekly
+  email_digest_to: string[] // Recipients of the run digest
 }
 
 export interface CustomCliProfile {
   name: string // Display name, e.g. "OpenRouter"
-  settings_json: string // JSON string matching Claude CLI settings format (with env block)
+  settings_json: string // JSON string matching Claude CLI settings format (with env block); `backend: 'aider'` or `backend: 'codex'` runs that CLI instead; `launchDefaults: { args, env }` adds CLI args/env
+  key_expires_at?: number | null // Unix seconds the provider stops accepting the key
+  rotate_by?: number | null // Unix seconds the key should be rotated by
 }
 
 export const PREDEFINED_CLI_PROFILES: CustomCliProfile[] = [
@@ -488,6 +598,35 @@ export const PREDEFINED_CLI_PROFILES: CustomCliProfile[] = [
       2
     ),
   },
+  {
+    name: 'Aider',
+    settings_json: JSON.stringify(
+      {
+        backend: 'aider',
+        model: 'sonnet',
+        autoCommit: false,
+        env: {
+          ANTHROPIC_API_KEY: '<your_api_key>',
+        },
+      },
+      null,
+      2
+    ),
+  },
+  {
+    name: 'Codex',
+    settings_json: JSON.stringify(
+      {
+        backend: 'codex',
+        sandbox: 'workspace-write',
+        env: {
+          OPENAI_API_KEY: '<your_api_key>',
+        },
+      },
+      null,
+      2
+    ),
+  },
 ]
 
 export type FileEditMode = 'inline' | 'external'
@@ -660,6 +799,26 @@ export const removalBehaviorOptions: {
   },
 ]
 
+// Speech-to-text provider options - where dictated audio is transcribed
+export type SpeechProvider = 'local' | 'api'
+
+export const speechProviderOptions: {
+  value: SpeechProvider
+  label: string
+  description: string
+}[] = [
+  {
+    value: 'local',
+    label: 'Local (whisper.cpp)',
+    description: 'Transcribe on-device with a downloaded whisper model',
+  },
+  {
+    value: 'api',
+    label: 'API',
+    description: 'Send audio to an OpenAI-compatible transcription endpoint',
+  },
+]
+
 // Archive retention options (days) - how long to keep archived items
 export const archiveRetentionOptions: { value: number; label: string }[] = [
   { value: 0, label: 'Never (keep forever)' },
@@ -750,6 +909,7 @@ export const defaultPreferences: AppPreferences = {
   remote_poll_interval: 60,
   keybindings: DEFAULT_KEYBINDINGS,
   archive_retention_days: 30,
+  scratch_retention_days: 7,
   session_grouping_enabled: true,
   canvas_enabled: true,
   canvas_only_mode: true,
@@ -782,4 +942,59 @@ export const defaultPreferences: AppPreferences = {
   zoom_level: ZOOM_LEVEL_DEFAULT,
   custom_cli_profiles: [],
   default_provider: null,
+  speech_provider: 'local',
+  speech_local_model: 'base.en',
+  speech_api_url: 'https://api.openai.com/v1/audio/transcriptions',
+  speech_api_model: 'whisper-1',
+  speech_api_key: null,
+  speak_waiting_summary: false,
+  speak_review_summary: false,
+  speech_voice: null,
+  prevent_sleep_during_runs: true,
+  defer_jobs_battery_threshold: 20,
+  defer_jobs_on_metered: true,
+  background_job_overrides: DEFAULT_BACKGROUND_JOB_OVERRIDES,
+  crash_report_endpoint: null,
+  extra_ca_cert_path: null,
+  use_system_cert_store: false,
+  http_proxy: null,
+  no_proxy: null,
+  keep_mark_of_the_web: false,
+  claude_monthly_budget_usd: null,
+  backup_folder: null,
+  backup_interval_hours: 24,
+  backup_keep_count: 7,
+  shared_library_repo: null,
+  shared_library_sync_hours: 6,
+  community_index_url: null,
+  community_index_public_key: null,
+  warm_process_pool: false,
+  download_speed_limit_kib: null,
+  auto_attach_command_runs: 0,
+  command_runs_token_budget: 4000,
+  repo_map_token_budget: 2000,
+  semantic_search_enabled: false,
+  embeddings_api_url: 'http://localhost:11434/v1/embeddings',
+  embeddings_api_model: 'nomic-embed-text',
+  embeddings_api_key: null,
+  maintenance_window_start: null,
+  maintenance_window_end: null,
+  report_locale: 'en-US',
+  csv_delimiter: null,
+  announcement_verbosity: DEFAULT_ANNOUNCEMENT_VERBOSITY,
+  respect_focus_mode: true,
+  focus_overrides: DEFAULT_FOCUS_OVERRIDES,
+  calendar_ics_path: null,
+  vulnerability_scan_before_sessions: false,
+  launch_defaults: {},
+  codex_update_channel: 'stable',
+  codex_update_check_hours: 24,
+  codex_release_mirrors: [],
+  smtp_host: null,
+  smtp_port: 587,
+  smtp_security: 'start_tls',
+  smtp_username: null,
+  smtp_from: null,
+  email_digest: 'off',
+  email_digest_to: [],
 }
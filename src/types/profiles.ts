@@ -0,0 +1,19 @@
+/**
+ * Types for local user profiles (separate data per person on a shared machine)
+ */
+
+/** A local profile; "default" uses the app data root */
+export interface Profile {
+  /** Lowercase slug of the name, also its directory name */
+  id: string
+  name: string
+  /** Unix timestamp (seconds), 0 for the default profile */
+  created_at: number
+}
+
+/** Profiles with the one this instance runs as (from list_profiles) */
+export interface ProfileList {
+  active: string
+  /** Default profile first */
+  profiles: Profile[]
+}
@@ -0,0 +1,58 @@
+/**
+ * Types for the project config file (jean.json)
+ */
+
+import type { IssueSeverity } from './migrations'
+import type { SubprojectConfig } from './subprojects'
+
+/** Settings from a repository's jean.json */
+export interface ProjectConfig {
+  scripts: {
+    /** Script to run after worktree creation */
+    setup: string | null
+    /** Script to run the dev environment */
+    run: string | null
+  }
+  cli: {
+    /** Pinned Claude CLI version (project settings override it) */
+    claude: string | null
+  }
+  /** Provider profile sessions default to (a session's choice wins) */
+  provider?: string
+  /** Tools sessions may use without asking, e.g. "Bash(npm test:*)" */
+  allowed_tools?: string[]
+  /** Sub-project settings, keyed by repo-relative path */
+  subprojects?: Record<string, SubprojectConfig>
+}
+
+export interface ConfigIssue {
+  severity: IssueSeverity
+  /** Dotted path of the setting (empty for the whole file) */
+  path: string
+  message: string
+}
+
+/** A worktree's config file and its problems (from get_project_config) */
+export interface ProjectConfigStatus {
+  exists: boolean
+  /** Null when missing or unreadable */
+  config: ProjectConfig | null
+  issues: ConfigIssue[]
+}
+
+/**
+ * A project's jean.json allowed_tools and whether the user approved them
+ * (get_allowed_tools_approval, project-config:tools-approval-needed event)
+ */
+export interface AllowedToolsApproval {
+  project_id: string
+  worktree_path: string
+  allowed_tools: string[]
+  approved: boolean
+}
+
+/** Payload of the project-config:changed event */
+export interface ProjectConfigChanged {
+  worktree_path: string
+  status: ProjectConfigStatus
+}
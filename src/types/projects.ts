@@ -1,3 +1,6 @@
+import type { LaunchDefaultsByBackend } from './launch'
+import type { ProjectToolchain } from './toolchain'
+
 /**
  * Type of session (base branch or worktree)
  */
@@ -41,6 +44,16 @@ export interface Project {
   enabled_mcp_servers?: string[]
   /** Custom system prompt appended to every session execution */
   custom_system_prompt?: string
+  /** Client or cost center usage is billed to (sessions can override) */
+  attribution?: string
+  /** Claude CLI version sessions run with (overrides jean.json) */
+  cli_version?: string
+  /** Languages and toolchains detected when the project was added */
+  toolchain?: ProjectToolchain
+  /** Domains agents may not fetch from */
+  blocked_domains?: string[]
+  /** Extra CLI args/env by backend, over the global defaults */
+  launch_defaults?: LaunchDefaultsByBackend
 }
 
 /**
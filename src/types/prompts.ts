@@ -0,0 +1,135 @@
+/**
+ * Types for the prompt library (get_prompt_library, sync_shared_library,
+ * render_prompt_template, get_template_effectiveness)
+ */
+
+export type LibraryKind = 'prompt' | 'profile'
+
+export type LibrarySource = 'local' | 'shared'
+
+export interface LibraryEntry {
+  kind: LibraryKind
+  name: string
+  /** Prompt text, or a profile's settings JSON */
+  content: string
+  source: LibrarySource
+  /** Shared entries can't be edited locally */
+  read_only: boolean
+}
+
+/** A local entry shadowed by a different shared one */
+export interface LibraryConflict {
+  kind: LibraryKind
+  name: string
+  local_content: string
+  shared_content: string
+}
+
+export interface SyncState {
+  repo_url: string | null
+  /** Commit of the last successful sync */
+  commit: string | null
+  /** Unix seconds of the last successful sync */
+  synced_at: number | null
+  /** Error from the last attempt */
+  error: string | null
+}
+
+/** Merged library (also the prompts:library-synced event payload) */
+export interface PromptLibrary {
+  sync: SyncState
+  entries: LibraryEntry[]
+  conflicts: LibraryConflict[]
+  /** Shared files that couldn't be read */
+  errors: string[]
+}
+
+/** A project-context template variable (list_template_variables) */
+export interface TemplateVariable {
+  /** Used as `{name}` in prompts */
+  name: string
+  description: string
+}
+
+export interface VariableError {
+  name: string
+  error: string
+}
+
+/** Result of render_prompt_template */
+export interface RenderedPrompt {
+  text: string
+  /** Variables found in the template and resolved */
+  resolved: string[]
+  /** Variables that couldn't be resolved (rendered empty) */
+  errors: VariableError[]
+}
+
+/** How sessions that used a template went (get_template_effectiveness) */
+export interface TemplateEffectiveness {
+  /** Library entry name */
+  template: string
+  sessions: number
+  runs: number
+  completed_runs: number
+  cancelled_runs: number
+  /** Crashed or interrupted */
+  failed_runs: number
+  /** Sessions whose last test run passed */
+  verified: number
+  /** Sessions whose last test run failed */
+  failed_verification: number
+  /** Sessions without a conclusive test run */
+  unverified: number
+  /** 0-1; null if no session ran tests */
+  verification_failure_rate: number | null
+  thumbs_up: number
+  thumbs_down: number
+  /** Annotation label counts */
+  labels: Record<string, number>
+}
+
+/** An item in the community index (browse_community_library) */
+export interface CommunityListing {
+  /** Unique within the index, e.g. "acme/strict-review" */
+  id: string
+  kind: LibraryKind
+  /** Magic prompt key or CLI profile name it installs as */
+  name: string
+  title: string
+  description: string
+  author: string
+  version: string
+  url: string
+  signature: string
+  installed_version: string | null
+  update_available: boolean
+}
+
+/** Provenance of an installed community item (install_community_item) */
+export interface InstalledItem {
+  id: string
+  kind: LibraryKind
+  name: string
+  version: string
+  author: string
+  index_url: string
+  source_url: string
+  /** SHA-256 of the signing key (hex) */
+  key_fingerprint: string
+  /** SHA-256 of the installed content (hex) */
+  content_sha256: string
+  /** Unix seconds */
+  installed_at: number
+}
+
+/** A newer version of an installed community item (check_community_updates) */
+export interface CommunityUpdate {
+  id: string
+  kind: LibraryKind
+  name: string
+  installed_version: string
+  latest_version: string
+  /** Edited since install; updating replaces the edits */
+  modified: boolean
+}
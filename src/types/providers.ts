@@ -0,0 +1,81 @@
+/**
+ * Types for the provider overview (get_providers_overview)
+ */
+
+export interface ProviderAuth {
+  authenticated: boolean
+  /** Provider-specific state (e.g. "refresh_failed" for Claude) */
+  state: string | null
+  /** Plan / account detail when known */
+  detail: string | null
+  error: string | null
+}
+
+export interface ProviderCli {
+  installed: boolean
+  version: string | null
+  latest_version: string | null
+  update_available: boolean
+}
+
+/**
+ * Spend in the current calendar month
+ */
+export interface ProviderSpend {
+  current_usd: number
+  budget_usd: number | null
+  /** Start of the period (Unix seconds, first of the month UTC) */
+  period_start: number
+  over_budget: boolean
+  /** Runs in the period without a reported cost (not included in the total) */
+  runs_without_cost: number
+}
+
+/**
+ * Status-page health
+ */
+export interface ProviderHealth {
+  indicator: 'none' | 'minor' | 'major' | 'critical' | 'maintenance'
+  description: string
+}
+
+/**
+ * Everything the home screen shows for one provider
+ */
+export interface ProviderOverview {
+  id: 'claude' | 'github'
+  name: string
+  auth: ProviderAuth
+  cli: ProviderCli
+  /** null for providers that don't bill per run */
+  spend: ProviderSpend | null
+  health: ProviderHealth | null
+  /** Lookups that failed (the corresponding fields are empty) */
+  errors: string[]
+}
+
+/** Which date a key reminder is about */
+export type KeyDate = 'expiry' | 'rotation'
+
+/** A key that expires or is due for rotation soon */
+export interface KeyReminder {
+  profile: string
+  kind: KeyDate
+  /** Unix timestamp (seconds) */
+  at: number
+  /** Whole days until `at` (negative once passed) */
+  days_left: number
+}
+
+/** Key status of one provider profile (get_provider_key_status) */
+export interface ProviderKeyStatus {
+  profile: string
+  /** Env var holding the key, null when the profile has none */
+  key_var: string | null
+  key_expires_at: number | null
+  rotate_by: number | null
+  /** Dates within the reminder lead time */
+  reminders: KeyReminder[]
+  /** Until when rollback_provider_key can restore the previous key */
+  rollback_until: number | null
+}
@@ -0,0 +1,32 @@
+/**
+ * Types for isolated Python tool environments
+ */
+
+/**
+ * Interpreter new environments are created with (detect_python)
+ */
+export interface PythonStatus {
+  path: string | null
+  /** e.g. "3.12.7" */
+  version: string | null
+  /** Whether this is Jean's managed standalone build */
+  managed: boolean
+  /** Present and at least 3.9 */
+  suitable: boolean
+}
+
+/**
+ * A tool's Python environment
+ */
+export interface PythonEnv {
+  tool: string
+  /** pip requirement the env was built from (e.g. "aider-chat==0.60.0") */
+  package: string
+  /** Interpreter the venv was created with */
+  interpreter: string
+  created_at: number
+  /** Absolute path of the tool's executable, if it exists */
+  entry_point?: string
+  /** The venv's interpreter still runs */
+  healthy: boolean
+}
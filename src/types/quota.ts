@@ -0,0 +1,27 @@
+/**
+ * Types for quota / rate-limit telemetry (provider:quota-warning, list_quota_events)
+ */
+
+export type LimitType =
+  | 'rate_limit'
+  | 'secondary_rate_limit'
+  | 'usage_limit'
+  | 'usage_warning'
+
+/**
+ * A rate-limit or usage-limit message recognized in CLI output
+ */
+export interface QuotaWarning {
+  /** "claude" or "github" */
+  provider: string
+  limit_type: LimitType
+  /** When the limit resets (Unix seconds), if the message says */
+  reset_at: number | null
+  /** Reset time as written in the message when it can't be resolved */
+  reset_text: string | null
+  /** The line the warning was parsed from */
+  message: string
+  /** Unix timestamp when it was seen */
+  detected_at: number
+  session_id: string | null
+}
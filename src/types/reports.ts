@@ -0,0 +1,94 @@
+/**
+ * Types for exported reports (export_usage_csv, export_attribution_csv)
+ */
+
+/** Result of an export */
+export interface ReportExport {
+  path: string
+  rows: number
+}
+
+/** Usage totals for one client or cost center (get_attribution_subtotals) */
+export interface AttributionSubtotal {
+  /** null for unattributed runs */
+  attribution: string | null
+  runs: number
+  /** Seconds the agent was working, excluding waits on the user */
+  active_secs: number
+  input_tokens: number
+  output_tokens: number
+  cache_read_tokens: number
+  cache_write_tokens: number
+  /** Sum of known run costs */
+  cost_usd: number
+  /** Runs without a reported cost (not in cost_usd) */
+  runs_without_cost: number
+}
+
+/** Activity on one day (get_activity_heatmap) */
+export interface ActivityDay {
+  /** YYYY-MM-DD in local time */
+  date: string
+  /** Sessions with at least one run started that day */
+  sessions: number
+  runs: number
+  /** Sum of known run costs */
+  cost_usd: number
+}
+
+/** Activity of one project over the year */
+export interface ProjectActivity {
+  project_id: string
+  name: string
+  sessions: number
+  runs: number
+  cost_usd: number
+}
+
+/** Activity over one calendar year (get_activity_heatmap) */
+export interface ActivityHeatmap {
+  year: number
+  /** Every day of the year, in order */
+  days: ActivityDay[]
+  /** Most sessions on one day (for scaling colors) */
+  max_sessions: number
+  total_sessions: number
+  total_runs: number
+  total_cost_usd: number
+  active_days: number
+  /** Active days up to today (or yesterday, while today has no runs yet) */
+  current_streak: number
+  longest_streak: number
+  /** Project with the most sessions (then runs) */
+  busiest_project: ProjectActivity | null
+}
+
+/** Runs of one session in an email digest */
+export interface DigestSession {
+  session_id: string
+  session_name: string
+  runs: number
+  completed: number
+  failed: number
+  cost_usd: number
+}
+
+/** Runs started in a period, as mailed (send_email_digest) */
+export interface EmailDigest {
+  /** Unix seconds */
+  since: number
+  until: number
+  runs: number
+  completed: number
+  failed: number
+  cancelled: number
+  /** Sum of known run costs */
+  cost_usd: number
+  /** Runs without a reported cost (not in cost_usd) */
+  runs_without_cost: number
+  active_secs: number
+  /** Most expensive first */
+  sessions: DigestSession[]
+  /** Crashed runs, oldest first */
+  failures: { session_name: string; started_at: number }[]
+}
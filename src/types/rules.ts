@@ -0,0 +1,137 @@
+/**
+ * User-defined event rules ("when this happens, do that")
+ */
+
+export type ConditionOp = 'equals' | 'not_equals' | 'contains' | 'exists'
+
+/** A test on one payload field */
+export interface RuleCondition {
+  /** Dotted path into the payload, e.g. "run.status" */
+  path: string
+  op: ConditionOp
+  /** Ignored for `exists` */
+  value?: unknown
+}
+
+/** Action text may use {field.path} placeholders filled from the payload */
+export type RuleAction =
+  | { type: 'notify'; title: string; body?: string | null }
+  | { type: 'run_script'; command: string; working_dir?: string | null }
+  | { type: 'webhook'; url: string }
+  | { type: 'enqueue_task'; prompt: string; session_id?: string | null }
+
+/** A rule as written, before it's saved */
+export interface RuleDraft {
+  name: string
+  enabled?: boolean
+  /** Event name, or a prefix ending in `*` (e.g. "chat:*") */
+  event: string
+  conditions?: RuleCondition[]
+  action: RuleAction
+  /** Minimum seconds between firings (default 30) */
+  cooldown_secs?: number | null
+}
+
+export interface EventRule {
+  id: string
+  name: string
+  enabled: boolean
+  event: string
+  conditions: RuleCondition[]
+  action: RuleAction
+  cooldown_secs: number
+  /** Unix seconds */
+  created_at: number
+  updated_at: number
+}
+
+/** One firing of a rule (also the rules:executed event payload) */
+export interface RuleExecution {
+  id: string
+  rule_id: string
+  rule_name: string
+  event: string
+  action: RuleAction['type']
+  /** Unix seconds */
+  fired_at: number
+  success: boolean
+  message: string
+}
+
+/** Payload of session:tests-failed, emitted when a run's last test command failed */
+export interface TestsFailedEvent {
+  session_id: string
+  worktree_id: string
+  command: string
+  summary?: string
+}
+
+/** What makes a watch rule fire */
+export type WatchTrigger =
+  /** Files matching a glob (relative to the worktree) changed */
+  | { type: 'file_changed'; worktree_id: string; globs: string[] }
+  /**
+   * POST to /api/hooks/{rule_id} (or /hooks/{name}) with the secret as
+   * ?secret=, X-Jean-Secret or X-Gitlab-Token, or signed with it as GitHub's
+   * X-Hub-Signature-256
+   */
+  | {
+      type: 'webhook'
+      secret: string
+      name?: string | null
+      /** Prompt variable → dotted path into the request body */
+      variables?: Record<string, string>
+    }
+  /** The branch (the worktree's unless set) has a new tip on origin */
+  | { type: 'branch_updated'; worktree_id: string; branch?: string | null }
+
+/** A watch rule as written, before it's saved */
+export interface WatchDraft {
+  name: string
+  enabled?: boolean
+  /** Leave a webhook secret empty to generate (or keep) one */
+  trigger: WatchTrigger
+  /** Tests on the trigger payload (webhook bodies are under "body") */
+  conditions?: RuleCondition[]
+  /** Task to queue; may use {field.path} placeholders (e.g. {file_list}) */
+  prompt: string
+  /** Default: the worktree's active session (required for webhooks) */
+  session_id?: string | null
+  /** Minimum seconds between firings (default 30) */
+  cooldown_secs?: number | null
+}
+
+export interface WatchRule {
+  id: string
+  name: string
+  enabled: boolean
+  trigger: WatchTrigger
+  conditions: RuleCondition[]
+  prompt: string
+  session_id: string | null
+  cooldown_secs: number
+  /** Unix seconds */
+  created_at: number
+  updated_at: number
+}
+
+/** Payload of watch:file-changed, watch:webhook and watch:branch-updated */
+export interface WatchTriggeredEvent {
+  watch_id: string
+  watch_name: string
+  session_id: string
+  worktree_id?: string
+  /** watch:file-changed */
+  files?: string[]
+  file_list?: string
+  /** watch:branch-updated */
+  branch?: string
+  sha?: string
+  previous_sha?: string
+  /** watch:webhook: the request's JSON body */
+  body?: unknown
+  /** watch:webhook: X-GitHub-Event or X-Gitlab-Event */
+  event_type?: string | null
+  /** watch:webhook: the rule's mapped variables */
+  [variable: string]: unknown
+}
@@ -0,0 +1,23 @@
+/**
+ * Types for agent CLI schema drift (backend:schema-drift, list_schema_drift)
+ */
+
+export type DriftKind =
+  | { kind: 'unknown_type' }
+  | { kind: 'unknown_field'; field: string }
+  | { kind: 'unparsable'; error: string }
+
+/**
+ * CLI output an agent adapter didn't recognize (kept in the run log as-is)
+ */
+export type DriftReport = DriftKind & {
+  /** "claude", "codex", or a custom backend's name */
+  backend: string
+  session_id?: string
+  /** Unix timestamp when it was seen */
+  detected_at: number
+  /** Event type, or a nested type such as "item:todo_list" */
+  event_type: string
+  /** The output line it was found in (truncated) */
+  sample: string
+}
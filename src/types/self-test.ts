@@ -0,0 +1,29 @@
+/**
+ * Types for the self-test suite (run_self_test)
+ */
+
+/**
+ * Result of a single self-test check
+ */
+export interface SelfTestCheck {
+  /** Check identifier (e.g. "extract_zip") */
+  name: string
+  passed: boolean
+  duration_ms: number
+  /** What was verified (on success) */
+  detail: string | null
+  /** Why the check failed */
+  error: string | null
+}
+
+/**
+ * Result of the whole self-test suite
+ */
+export interface SelfTestReport {
+  /** True if every check passed */
+  passed: boolean
+  checks: SelfTestCheck[]
+  app_version: string
+  os: string
+  arch: string
+}
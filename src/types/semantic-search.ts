@@ -0,0 +1,25 @@
+/**
+ * Types for semantic code search (semantic_search, rebuild_semantic_index)
+ */
+
+/** A file section matching a query */
+export interface SearchHit {
+  /** Relative to the project or worktree root */
+  path: string
+  /** 1-based, inclusive */
+  start_line: number
+  end_line: number
+  /** Cosine similarity to the query */
+  score: number
+  snippet: string
+}
+
+/** Size of a semantic index after a rebuild */
+export interface IndexStats {
+  files: number
+  chunks: number
+  /** Chunks embedded by this rebuild (the rest were reused) */
+  embedded: number
+  /** Unix timestamp (seconds) */
+  generated_at: number
+}
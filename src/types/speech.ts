@@ -0,0 +1,63 @@
+/**
+ * Types for speech-to-text (voice input) transcription
+ */
+
+import type { SpeechProvider } from './preferences'
+
+/**
+ * Result of transcribing an audio buffer
+ */
+export interface TranscriptionResult {
+  /** Transcribed text (trimmed) */
+  text: string
+  /** Confidence between 0 and 1 (null if the engine didn't report one) */
+  confidence: number | null
+  /** Engine that produced the transcript */
+  provider: SpeechProvider
+  /** Detected or requested language code (if known) */
+  language: string | null
+}
+
+/**
+ * Status of the speech-to-text setup
+ */
+export interface SpeechStatus {
+  /** Configured provider */
+  provider: SpeechProvider
+  /** Path to the whisper.cpp binary found on PATH (local provider) */
+  whisper_binary: string | null
+  /** Configured local model ID */
+  local_model: string
+  /** Whether the configured local model is downloaded */
+  local_model_installed: boolean
+  /** Whether transcription can run with the current configuration */
+  ready: boolean
+}
+
+/**
+ * Information about a whisper model
+ */
+export interface SpeechModelInfo {
+  /** Model ID (e.g., "base.en") */
+  id: string
+  /** Approximate download size in MB */
+  size_mb: number
+  /** Whether the model has been downloaded */
+  installed: boolean
+  /** Path to the model file (if installed) */
+  path: string | null
+}
+
+/**
+ * Progress event for model downloads (speech:model-download-progress)
+ */
+export interface SpeechModelDownloadProgress {
+  /** Model being downloaded */
+  model: string
+  /** Current stage of the download */
+  stage: 'starting' | 'downloading' | 'verifying' | 'complete'
+  /** Progress message */
+  message: string
+  /** Percentage complete (0-100) */
+  percent: number
+}
@@ -0,0 +1,33 @@
+/**
+ * Types for monorepo sub-projects
+ */
+
+/** Manifest a sub-project was found in ('configured' = only in jean.json) */
+export type SubprojectKind = 'npm' | 'pnpm' | 'cargo' | 'go' | 'configured'
+
+export interface SubprojectCommands {
+  run: string | null
+  test: string | null
+  build: string | null
+}
+
+/** A sub-project entry in jean.json */
+export interface SubprojectConfig {
+  name?: string
+  /** Token budget for prompts of sessions scoped to it */
+  context_budget?: number
+  /** Client or cost center its runs are billed to */
+  attribution?: string
+  commands?: Partial<SubprojectCommands>
+}
+
+/** A package of a monorepo */
+export interface Subproject {
+  /** Repo-relative, '/'-separated */
+  path: string
+  name: string
+  kind: SubprojectKind
+  commands: SubprojectCommands
+  context_budget: number | null
+  attribution: string | null
+}
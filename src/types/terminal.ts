@@ -13,3 +13,23 @@ export interface TerminalStoppedEvent {
   terminal_id: string
   exit_code: number | null
 }
+
+/**
+ * A project command run (run_project_command). Output streams as terminal
+ * events with `id` as the terminal id; `project-command:finished` carries
+ * the final record.
+ */
+export interface CommandRun {
+  id: string
+  project_id: string
+  command: string
+  /** Directory the command ran in */
+  cwd: string
+  /** Unix seconds */
+  started_at: number
+  finished_at: number | null
+  /** null while running or when stopped */
+  exit_code: number | null
+  /** Output exceeded the log limit and was cut */
+  truncated: boolean
+}
@@ -0,0 +1,83 @@
+/**
+ * Types for TLS and proxy diagnostics (custom CA / corporate proxy support)
+ */
+
+/**
+ * A certificate presented by the server
+ */
+export interface CertificateInfo {
+  subject: string
+  issuer: string
+  not_before: string
+  not_after: string
+  /** SHA-256 fingerprint (colon-separated hex) */
+  sha256: string
+  /** Subject and issuer are the same (typical of a proxy's root CA) */
+  self_signed: boolean
+}
+
+/**
+ * Which extra roots are currently trusted
+ */
+export interface TrustStoreStatus {
+  extra_ca_cert_path: string | null
+  extra_ca_certificates: number
+  use_system_cert_store: boolean
+  system_certificates: number
+  /** Problems loading the configured roots */
+  errors: string[]
+}
+
+/**
+ * Result of diagnose_tls
+ */
+export interface TlsDiagnosis {
+  host: string
+  port: number
+  /** True if a request with the app's HTTP client succeeded */
+  trusted: boolean
+  /** Error from the app's HTTP client (with underlying causes) */
+  request_error: string | null
+  /** Why the presented chain fails verification (null if it verifies) */
+  verification_error: string | null
+  /** Chain presented by the server, leaf first */
+  chain: CertificateInfo[]
+  /** Error connecting to the server to capture its chain */
+  handshake_error: string | null
+  trust_store: TrustStoreStatus
+}
+
+/** Where the proxy in use came from */
+export type ProxySource = 'preferences' | 'environment'
+
+/**
+ * Result of test_proxy_connection
+ */
+export interface ProxyTest {
+  /** Proxy used (password redacted); null for a direct connection */
+  proxy: string | null
+  source: ProxySource | null
+  url: string
+  /** True if the request got an HTTP response (of any status) */
+  reachable: boolean
+  status: number | null
+  elapsed_ms: number
+  /** Error from the proxy configuration or request (with underlying causes) */
+  error: string | null
+}
+
+/**
+ * A failed API request that is about to be retried (the `http:retry` event,
+ * e.g. while GitHub answers 502 or is rate limiting). Setting a token with
+ * set_github_token (checked with has_github_token) raises GitHub's limit.
+ */
+export interface RetryAttempt {
+  /** What was being fetched, e.g. "GitHub CLI releases" */
+  what: string
+  /** The attempt that failed (1-based) */
+  attempt: number
+  max_attempts: number
+  /** Wait before the next attempt */
+  delay_ms: number
+  reason: string
+}
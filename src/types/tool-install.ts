@@ -0,0 +1,126 @@
+/**
+ * Types for installing several CLI tools at once (install_tools)
+ */
+
+/** Tool names accepted by install_tools */
+export type ToolName = 'claude' | 'gh' | 'ripgrep' | 'fd' | 'ast-grep' | 'node' | 'python'
+
+/**
+ * Outcome of installing one tool
+ */
+export interface ToolInstallResult {
+  tool: ToolName
+  installed: boolean
+  error?: string
+}
+
+/**
+ * Progress of one tool's download
+ */
+export interface AssetProgress {
+  downloaded: number
+  /** Size reported by the server, if any */
+  total: number | null
+  done: boolean
+  error?: string
+}
+
+/**
+ * Payload of the tools:download-progress event
+ */
+export interface DownloadProgress {
+  /** Keyed by tool name */
+  assets: Record<string, AssetProgress>
+  downloaded: number
+  /** Sum of all sizes, or null while any size is unknown */
+  total: number | null
+}
+
+/**
+ * A managed helper tool (get_helper_tools_status)
+ */
+export interface HelperToolStatus {
+  name: ToolName
+  /** Version Jean installs */
+  pinned_version: string
+  /** Version currently installed, if any */
+  installed_version: string | null
+  /** Absolute path of the main executable, if installed */
+  path: string | null
+}
+
+/**
+ * Node.js runtime npm-based CLIs would use (detect_node_runtime)
+ */
+export interface NodeStatus {
+  node_path: string | null
+  npm_path: string | null
+  /** e.g. "22.11.0" */
+  version: string | null
+  /** Whether this is Jean's managed runtime */
+  managed: boolean
+  /** Node and npm are present and node is at least min_major */
+  suitable: boolean
+  min_major: number
+}
+
+export type PackageManager = 'brew' | 'winget' | 'apt' | 'pacman'
+
+/**
+ * What install_codex_cli_via_package_manager did (or would do)
+ */
+export interface PackageInstallResult {
+  tool: string
+  manager: PackageManager | null
+  /** Command that was run, or that the user should run */
+  command: string[]
+  installed: boolean
+  /** Where the binary was found afterwards */
+  path: string | null
+  /** What to do instead when Jean didn't run anything */
+  hint: string | null
+}
+
+/**
+ * Payload of the tools:package-manager-output event (one output line)
+ */
+export interface PackageManagerOutput {
+  tool: string
+  stream: 'stdout' | 'stderr'
+  line: string
+}
+
+/** How managed CLIs are exposed to the user's shell */
+export type IntegrationMode = 'symlinks' | 'path_snippet'
+
+/**
+ * What shell integration changed outside Jean's data directory
+ * (get_shell_integration_status, enable/disable_shell_integration)
+ */
+export interface ShellIntegrationStatus {
+  mode: IntegrationMode | null
+  /** Symlinks Jean created in ~/.local/bin */
+  links: string[]
+  /** Rc files holding Jean's PATH block */
+  rc_files: string[]
+  /** Executables skipped because the name was already taken */
+  conflicts: string[]
+}
+
+export type CompletionShell = 'bash' | 'zsh' | 'fish'
+
+/**
+ * Completion scripts and aliases installed for managed CLIs
+ * (install_shell_completions, uninstall_shell_completions)
+ */
+export interface CompletionsStatus {
+  shells: CompletionShell[]
+  /** Completion scripts written */
+  files: string[]
+  /** Rc files holding Jean's completion/alias block */
+  rc_files: string[]
+  /** e.g. "jean-gh" */
+  aliases: string[]
+  /** CLIs whose completion script couldn't be generated, with the reason */
+  failures: string[]
+}
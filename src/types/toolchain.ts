@@ -0,0 +1,32 @@
+/**
+ * Types for detected project languages and toolchains (get_project_toolchain)
+ */
+
+export type Language = 'rust' | 'javascript' | 'typescript' | 'go' | 'python'
+
+export type Toolchain =
+  | 'cargo'
+  | 'npm'
+  | 'pnpm'
+  | 'yarn'
+  | 'bun'
+  | 'go'
+  | 'uv'
+  | 'poetry'
+  | 'pip'
+
+export interface DetectedToolchain {
+  toolchain: Toolchain
+  /** Repo-relative directory of the manifest ('' for the root) */
+  dir: string
+}
+
+export interface ProjectToolchain {
+  languages: Language[]
+  toolchains: DetectedToolchain[]
+  /** Commands that check a change (tests, lints, type checks), run from the root */
+  verify_commands: string[]
+  /** install_tools tools useful for this project */
+  suggested_tools: string[]
+  detected_at: number
+}
@@ -0,0 +1,34 @@
+/**
+ * Types for dependency vulnerability scans (get_session_vulnerabilities)
+ */
+
+export type VulnerabilityScanner = 'cargo_audit' | 'npm_audit' | 'osv'
+
+/** A vulnerable dependency */
+export interface Vulnerability {
+  scanner: VulnerabilityScanner
+  package: string
+  /** Installed version (npm audit only reports the affected range) */
+  version: string | null
+  /** Advisory ID (RUSTSEC-…, GHSA-…, GO-…) */
+  id: string
+  severity: string | null
+  title: string | null
+  /** Version or range that fixes it, if known */
+  fixed_in: string | null
+}
+
+/** One scanner run over one directory */
+export interface ScannerRun {
+  scanner: VulnerabilityScanner
+  /** Repo-relative directory ('' for the root) */
+  dir: string
+  /** Why it couldn't scan (its findings are then missing) */
+  error: string | null
+}
+
+export interface VulnerabilityReport {
+  scanned_at: number
+  findings: Vulnerability[]
+  scanners: ScannerRun[]
+}